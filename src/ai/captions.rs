@@ -0,0 +1,196 @@
+use base64::Engine;
+use image::imageops::FilterType;
+use std::io::Cursor;
+
+use super::{prompts, AiRequest};
+use crate::models::portfolio::PortfolioItem;
+use crate::models::search::strip_html;
+use crate::models::seo_suggestion::SeoSuggestion;
+use crate::store::Store;
+
+/// Items captioned per run when `ai_vision_captions_batch` isn't set.
+const DEFAULT_BATCH: usize = 5;
+
+/// Pause between provider calls when `ai_vision_captions_delay` isn't set, in seconds.
+const DEFAULT_DELAY_SECS: u64 = 5;
+
+/// Longest side of the image sent to the provider.
+const MAX_IMAGE_SIDE: u32 = 1024;
+
+const META_DESCRIPTION_MAX: usize = 155;
+
+/// Suggestion fields an item is missing and hasn't had suggested before.
+/// A dismissed caption isn't asked for again.
+pub fn missing_fields(store: &dyn Store, item: &PortfolioItem) -> Vec<&'static str> {
+    let mut fields = Vec::new();
+    let description = item.description_html.as_deref().unwrap_or("");
+    if strip_html(description).trim().is_empty() {
+        fields.push("description");
+    }
+    if item
+        .meta_description
+        .as_deref()
+        .unwrap_or("")
+        .trim()
+        .is_empty()
+    {
+        fields.push("meta_description");
+    }
+    fields.retain(|f| !store.seo_suggestion_exists("portfolio", item.id, f));
+    fields
+}
+
+/// Read an uploaded image, shrink it and encode it as base64 JPEG for a
+/// vision provider. None for videos and unreadable files.
+pub fn load_image_base64(store: &dyn Store, image_path: &str) -> Option<String> {
+    let name = image_path
+        .trim_start_matches('/')
+        .trim_start_matches("uploads/");
+    if name.is_empty() || name.contains("..") {
+        return None;
+    }
+    let storage = store.setting_get_or("images_storage_path", "website/site/uploads/");
+    let img = image::open(format!("{}{}", storage, name)).ok()?;
+    let img = if img.width() > MAX_IMAGE_SIDE || img.height() > MAX_IMAGE_SIDE {
+        img.resize(MAX_IMAGE_SIDE, MAX_IMAGE_SIDE, FilterType::Triangle)
+    } else {
+        img
+    };
+    let mut buf = Cursor::new(Vec::new());
+    image::DynamicImage::ImageRgb8(img.to_rgb8())
+        .write_to(&mut buf, image::ImageFormat::Jpeg)
+        .ok()?;
+    Some(base64::engine::general_purpose::STANDARD.encode(buf.into_inner()))
+}
+
+/// A meta description from a caption: whole sentences up to the length
+/// limit, or the first words with an ellipsis when one sentence is too long.
+pub fn meta_from_caption(caption: &str) -> String {
+    let caption = caption.split_whitespace().collect::<Vec<_>>().join(" ");
+    if caption.chars().count() <= META_DESCRIPTION_MAX {
+        return caption;
+    }
+    let mut out = String::new();
+    for sentence in caption.split_inclusive(['.', '!', '?']) {
+        if out.chars().count() + sentence.chars().count() > META_DESCRIPTION_MAX {
+            break;
+        }
+        out.push_str(sentence);
+    }
+    if !out.trim().is_empty() {
+        return out.trim().to_string();
+    }
+    let mut out = String::new();
+    for word in caption.split(' ') {
+        if out.chars().count() + word.chars().count() + 2 > META_DESCRIPTION_MAX {
+            break;
+        }
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    format!("{}…", out.trim_end_matches([',', ';', ':']))
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Queue caption suggestions for one item from a vision provider's
+/// description of its image. Returns how many were queued.
+pub fn caption_item(
+    store: &dyn Store,
+    item: &PortfolioItem,
+    fields: &[&str],
+) -> Result<usize, String> {
+    let image = load_image_base64(store, &item.image_path).ok_or("Image could not be read")?;
+    let req = AiRequest {
+        system: prompts::seo_system(),
+        prompt: prompts::describe_image(),
+        max_tokens: Some(512),
+        temperature: Some(0.5),
+        image_base64: Some(image),
+        feature: "vision_captions".to_string(),
+    };
+    let resp = super::complete(store, &req).map_err(|e| e.to_string())?;
+    let caption = crate::routes::ai::parse_json_from_text(&resp.text)
+        .and_then(|v| v.get("description")?.as_str().map(|s| s.to_string()))
+        .unwrap_or(resp.text)
+        .trim()
+        .to_string();
+    if caption.is_empty() {
+        return Err("The provider returned an empty description".into());
+    }
+
+    let mut queued = 0;
+    for field in fields {
+        let (current, suggested) = match *field {
+            "description" => (
+                item.description_html.clone().unwrap_or_default(),
+                format!("<p>{}</p>", html_escape(&caption)),
+            ),
+            "meta_description" => (
+                item.meta_description.clone().unwrap_or_default(),
+                meta_from_caption(&caption),
+            ),
+            _ => continue,
+        };
+        store.seo_suggestion_create(&SeoSuggestion {
+            id: 0,
+            content_type: "portfolio".to_string(),
+            content_id: item.id,
+            field: field.to_string(),
+            current,
+            suggested,
+            level: 0,
+            reason: format!("Captioned from the image by {}", resp.provider),
+            status: "pending".to_string(),
+            created_at: String::new(),
+        })?;
+        queued += 1;
+    }
+    Ok(queued)
+}
+
+/// One background run: caption up to `ai_vision_captions_batch` portfolio
+/// items missing a description or meta description, pausing between calls.
+/// Returns how many items got suggestions.
+pub fn run_batch(store: &dyn Store) -> Result<usize, String> {
+    if !super::has_vision_provider(store) {
+        return Err("No vision-capable AI provider is enabled".into());
+    }
+    let batch = match store.setting_get_i64("ai_vision_captions_batch") {
+        n if n > 0 => n as usize,
+        _ => DEFAULT_BATCH,
+    };
+    let delay = match store.setting_get_i64("ai_vision_captions_delay") {
+        n if n >= 0 => n as u64,
+        _ => DEFAULT_DELAY_SECS,
+    };
+
+    let items = store.portfolio_list(None, store.portfolio_count(None), 0);
+    let mut done = 0;
+    let mut attempted = 0;
+    for item in &items {
+        if attempted >= batch {
+            break;
+        }
+        let fields = missing_fields(store, item);
+        if fields.is_empty() {
+            continue;
+        }
+        if attempted > 0 && delay > 0 {
+            std::thread::sleep(std::time::Duration::from_secs(delay));
+        }
+        attempted += 1;
+        match caption_item(store, item, &fields) {
+            Ok(n) if n > 0 => done += 1,
+            Ok(_) => {}
+            Err(e) => log::warn!("[ai] Captioning portfolio item {} failed: {}", item.id, e),
+        }
+    }
+    Ok(done)
+}
@@ -0,0 +1,500 @@
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use super::{usage, AiError, Provider};
+use crate::models::embedding::Embedding;
+use crate::models::search::{strip_html, truncate_words, SearchResult};
+use crate::store::Store;
+
+/// Items embedded per background run, so a large backlog doesn't hammer the provider.
+const SYNC_BATCH: usize = 25;
+
+/// Words of body text sent along with the title; enough for the gist of a
+/// post while staying inside every provider's input limit.
+const MAX_WORDS: usize = 1500;
+
+/// Matches scoring below this are noise rather than related content.
+pub const MIN_SIMILARITY: f32 = 0.3;
+
+/// Semantic results are only mixed into search when full-text search finds
+/// fewer than this many matches.
+const SEARCH_FALLBACK_BELOW: usize = 3;
+
+pub fn enabled(settings: &HashMap<String, String>) -> bool {
+    settings.get("ai_embeddings_enabled").map(|v| v.as_str()) == Some("true")
+}
+
+// ── Providers ─────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Api {
+    OpenAi,
+    Ollama,
+    Gemini,
+    Cloudflare,
+}
+
+/// Where and how to ask one provider for a vector.
+struct Endpoint {
+    provider: &'static str,
+    api: Api,
+    url: String,
+    bearer: Option<String>,
+    model: String,
+}
+
+impl Endpoint {
+    fn model_id(&self) -> String {
+        format!("{}:{}", self.provider, self.model)
+    }
+}
+
+fn setting(settings: &HashMap<String, String>, key: &str) -> String {
+    settings
+        .get(key)
+        .map(|v| v.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// The embedding endpoint for `provider`, or None when it has no embedding
+/// API (Groq) or isn't configured.
+fn endpoint(provider: &Provider, settings: &HashMap<String, String>) -> Option<Endpoint> {
+    let non_empty = |v: String| if v.is_empty() { None } else { Some(v) };
+    match provider {
+        Provider::OpenAi => {
+            let key = non_empty(setting(settings, "ai_openai_api_key"))?;
+            let base = non_empty(setting(settings, "ai_openai_base_url"))
+                .unwrap_or_else(|| "https://api.openai.com/v1".to_string());
+            Some(Endpoint {
+                provider: "openai",
+                api: Api::OpenAi,
+                url: format!("{}/embeddings", base.trim_end_matches('/')),
+                bearer: Some(key),
+                model: "text-embedding-3-small".into(),
+            })
+        }
+        Provider::Mistral => {
+            let key = non_empty(setting(settings, "ai_mistral_api_key"))?;
+            Some(Endpoint {
+                provider: "mistral",
+                api: Api::OpenAi,
+                url: "https://api.mistral.ai/v1/embeddings".into(),
+                bearer: Some(key),
+                model: "mistral-embed".into(),
+            })
+        }
+        Provider::OpenRouter => {
+            let model = non_empty(setting(settings, "ai_openrouter_embedding_model"))?;
+            let base = non_empty(setting(settings, "ai_openrouter_base_url"))
+                .unwrap_or_else(|| "https://openrouter.ai/api/v1".to_string());
+            Some(Endpoint {
+                provider: "openrouter",
+                api: Api::OpenAi,
+                url: format!("{}/embeddings", base.trim_end_matches('/')),
+                bearer: non_empty(setting(settings, "ai_openrouter_api_key")),
+                model,
+            })
+        }
+        Provider::Ollama => {
+            let model = non_empty(setting(settings, "ai_ollama_embedding_model"))?;
+            let base = non_empty(setting(settings, "ai_ollama_url"))
+                .unwrap_or_else(|| "http://localhost:11434".to_string());
+            Some(Endpoint {
+                provider: "ollama",
+                api: Api::Ollama,
+                url: format!("{}/api/embed", base.trim_end_matches('/')),
+                bearer: None,
+                model,
+            })
+        }
+        Provider::Gemini => {
+            let key = non_empty(setting(settings, "ai_gemini_api_key"))?;
+            let model = "text-embedding-004";
+            Some(Endpoint {
+                provider: "gemini",
+                api: Api::Gemini,
+                url: format!(
+                    "https://generativelanguage.googleapis.com/v1beta/models/{}:embedContent?key={}",
+                    model, key
+                ),
+                bearer: None,
+                model: model.into(),
+            })
+        }
+        Provider::Cloudflare => {
+            let account = non_empty(setting(settings, "ai_cloudflare_account_id"))?;
+            let token = non_empty(setting(settings, "ai_cloudflare_api_token"))?;
+            let model = "@cf/baai/bge-base-en-v1.5";
+            Some(Endpoint {
+                provider: "cloudflare",
+                api: Api::Cloudflare,
+                url: format!(
+                    "https://api.cloudflare.com/client/v4/accounts/{}/ai/run/{}",
+                    account, model
+                ),
+                bearer: Some(token),
+                model: model.into(),
+            })
+        }
+        Provider::Groq => None,
+    }
+}
+
+/// The first enabled provider in the failover chain that can embed. Unlike
+/// completions there is no failover: vectors from different models can't be
+/// compared, so everything is embedded with the same one.
+fn active_endpoint(settings: &HashMap<String, String>) -> Option<Endpoint> {
+    super::failover_chain(settings)
+        .iter()
+        .filter_map(|p| Provider::from_str(p))
+        .filter(|p| {
+            settings
+                .get(&format!("ai_{}_enabled", p.name()))
+                .map(|v| v.as_str())
+                == Some("true")
+        })
+        .find_map(|p| endpoint(&p, settings))
+}
+
+/// Identifier of the model new vectors are computed with, e.g.
+/// "openai:text-embedding-3-small". None when no enabled provider can embed.
+pub fn model_id(settings: &HashMap<String, String>) -> Option<String> {
+    active_endpoint(settings).map(|e| e.model_id())
+}
+
+/// Compute a vector for `text`. Returns the model id alongside it.
+pub fn embed(
+    settings: &HashMap<String, String>,
+    text: &str,
+) -> Result<(String, Vec<f32>), AiError> {
+    let ep = active_endpoint(settings)
+        .ok_or_else(|| AiError("No enabled AI provider supports embeddings".into()))?;
+
+    let body = match ep.api {
+        Api::OpenAi | Api::Ollama => json!({"model": ep.model, "input": text}),
+        Api::Gemini => json!({"content": {"parts": [{"text": text}]}}),
+        Api::Cloudflare => json!({"text": [text]}),
+    };
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(60))
+        .build()
+        .map_err(|e| AiError(format!("HTTP client error: {}", e)))?;
+
+    let mut request = client
+        .post(&ep.url)
+        .header("Content-Type", "application/json");
+    if let Some(ref token) = ep.bearer {
+        request = request.header("Authorization", format!("Bearer {}", token));
+    }
+    let resp = request
+        .json(&body)
+        .send()
+        .map_err(|e| AiError(format!("{} embedding request failed: {}", ep.provider, e)))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().unwrap_or_default();
+        return Err(AiError(format!(
+            "{} embeddings returned {}: {}",
+            ep.provider, status, text
+        )));
+    }
+
+    let json: Value = resp
+        .json()
+        .map_err(|e| AiError(format!("{} embedding JSON parse error: {}", ep.provider, e)))?;
+
+    let values = match ep.api {
+        Api::OpenAi => json
+            .get("data")
+            .and_then(|d| d.get(0))
+            .and_then(|d| d.get("embedding")),
+        Api::Ollama => json.get("embeddings").and_then(|e| e.get(0)),
+        Api::Gemini => json.get("embedding").and_then(|e| e.get("values")),
+        Api::Cloudflare => json
+            .get("result")
+            .and_then(|r| r.get("data"))
+            .and_then(|d| d.get(0)),
+    };
+    let vector: Vec<f32> = values
+        .and_then(|v| v.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|x| x.as_f64())
+                .map(|x| x as f32)
+                .collect()
+        })
+        .unwrap_or_default();
+    if vector.is_empty() {
+        return Err(AiError(format!("{} returned no embedding", ep.provider)));
+    }
+    Ok((ep.model_id(), vector))
+}
+
+/// `embed`, with the call recorded against `feature` in AI usage.
+fn embed_recorded(
+    store: &dyn Store,
+    settings: &HashMap<String, String>,
+    text: &str,
+    feature: &str,
+) -> Result<(String, Vec<f32>), AiError> {
+    let (provider, model) = match active_endpoint(settings) {
+        Some(ep) => (ep.provider, ep.model),
+        None => return embed(settings, text),
+    };
+    let started = std::time::Instant::now();
+    let result = embed(settings, text);
+    usage::record(
+        store,
+        provider,
+        &model,
+        feature,
+        usage::estimate_tokens(text),
+        0,
+        started.elapsed(),
+        result.as_ref().err().map(|e| e.0.as_str()),
+    );
+    result
+}
+
+// ── Index ─────────────────────────────────────────────
+
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Brute-force cosine index over stored vectors. Sites hold hundreds or a
+/// few thousand items, which a linear scan handles in well under a
+/// millisecond per thousand.
+pub struct Index {
+    entries: Vec<(String, i64, Vec<f32>)>,
+}
+
+impl Index {
+    pub fn new(embeddings: Vec<Embedding>) -> Self {
+        Index {
+            entries: embeddings
+                .into_iter()
+                .map(|e| (e.item_type, e.item_id, e.vector))
+                .collect(),
+        }
+    }
+
+    pub fn vector_for(&self, item_type: &str, item_id: i64) -> Option<&[f32]> {
+        self.entries
+            .iter()
+            .find(|(t, id, _)| t == item_type && *id == item_id)
+            .map(|(_, _, v)| v.as_slice())
+    }
+
+    /// The `limit` closest items to `query` scoring at least
+    /// `MIN_SIMILARITY`, best first, skipping `exclude`.
+    pub fn nearest(
+        &self,
+        query: &[f32],
+        limit: usize,
+        exclude: Option<(&str, i64)>,
+    ) -> Vec<(String, i64, f32)> {
+        let mut scored: Vec<(String, i64, f32)> = self
+            .entries
+            .iter()
+            .filter(|(t, id, _)| exclude != Some((t.as_str(), *id)))
+            .map(|(t, id, v)| (t.clone(), *id, cosine(query, v)))
+            .filter(|(_, _, score)| *score >= MIN_SIMILARITY)
+            .collect();
+        scored.sort_by(|a, b| b.2.total_cmp(&a.2));
+        scored.truncate(limit);
+        scored
+    }
+}
+
+// ── Sync ──────────────────────────────────────────────
+
+/// Text sent for an item: the title, then the start of the body.
+fn item_text(title: &str, html: &str) -> String {
+    let body = truncate_words(&strip_html(html), MAX_WORDS);
+    format!("{}\n\n{}", title, body)
+}
+
+fn content_hash(text: &str) -> String {
+    hex::encode(Sha256::digest(text.as_bytes()))
+}
+
+/// Embed published posts and portfolio items that have no vector yet or
+/// whose text changed, up to `SYNC_BATCH` per call. Vectors from a model
+/// that's no longer active are dropped first. Returns how many were embedded.
+pub fn sync(store: &dyn Store) -> Result<usize, String> {
+    let settings = store.setting_all();
+    if !enabled(&settings) {
+        return Ok(0);
+    }
+    let model = model_id(&settings).ok_or("No enabled AI provider supports embeddings")?;
+    store.embedding_prune(&model)?;
+
+    let existing: HashMap<(String, i64), String> = store
+        .embedding_list(&model)
+        .into_iter()
+        .map(|e| ((e.item_type, e.item_id), e.content_hash))
+        .collect();
+
+    let mut items: Vec<(&str, i64, String)> = store
+        .post_list(Some("published"), store.post_count(Some("published")), 0)
+        .into_iter()
+        .map(|p| ("post", p.id, item_text(&p.title, &p.content_html)))
+        .collect();
+    items.extend(
+        store
+            .portfolio_list(
+                Some("published"),
+                store.portfolio_count(Some("published")),
+                0,
+            )
+            .into_iter()
+            .map(|p| {
+                let html = p.description_html.unwrap_or_default();
+                ("portfolio", p.id, item_text(&p.title, &html))
+            }),
+    );
+
+    let mut count = 0;
+    for (item_type, item_id, text) in items {
+        if count >= SYNC_BATCH {
+            break;
+        }
+        let hash = content_hash(&text);
+        if existing.get(&(item_type.to_string(), item_id)) == Some(&hash) {
+            continue;
+        }
+        let (model, vector) =
+            embed_recorded(store, &settings, &text, "embeddings").map_err(|e| e.0)?;
+        store.embedding_upsert(&Embedding {
+            item_type: item_type.to_string(),
+            item_id,
+            model,
+            content_hash: hash,
+            vector,
+        })?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+// ── Lookups ───────────────────────────────────────────
+
+/// Items closest in meaning to `item`, best first. Uses stored vectors
+/// only, so it never calls a provider.
+pub fn related(
+    store: &dyn Store,
+    item_type: &str,
+    item_id: i64,
+    limit: usize,
+) -> Vec<(String, i64, f32)> {
+    let settings = store.setting_all();
+    if !enabled(&settings) {
+        return vec![];
+    }
+    let model = match model_id(&settings) {
+        Some(m) => m,
+        None => return vec![],
+    };
+    let index = Index::new(store.embedding_list(&model));
+    match index.vector_for(item_type, item_id) {
+        Some(v) => index.nearest(v, limit, Some((item_type, item_id))),
+        None => vec![],
+    }
+}
+
+/// Search by meaning: embed `query` and return the nearest published items.
+pub fn semantic_search(store: &dyn Store, query: &str, limit: usize) -> Vec<SearchResult> {
+    let settings = store.setting_all();
+    if !enabled(&settings) || query.trim().is_empty() {
+        return vec![];
+    }
+    let (model, vector) = match embed_recorded(store, &settings, query.trim(), "semantic_search") {
+        Ok(r) => r,
+        Err(e) => {
+            log::warn!("[ai] Semantic search unavailable: {}", e.0);
+            return vec![];
+        }
+    };
+    let index = Index::new(store.embedding_list(&model));
+    index
+        .nearest(&vector, limit, None)
+        .into_iter()
+        .filter_map(|(item_type, id, score)| search_result(store, &item_type, id, score))
+        .collect()
+}
+
+fn search_result(store: &dyn Store, item_type: &str, id: i64, score: f32) -> Option<SearchResult> {
+    // FTS ranks are "lower is better"; keep semantic matches in the same order
+    let rank = -(score as f64);
+    match item_type {
+        "post" => {
+            let p = store
+                .post_find_by_id(id)
+                .filter(|p| p.status == "published")?;
+            Some(SearchResult {
+                item_type: "post".into(),
+                item_id: p.id,
+                snippet: truncate_words(&strip_html(&p.content_html), 40),
+                title: p.title,
+                slug: p.slug,
+                image: p.featured_image,
+                date: p.published_at.map(|d| d.to_string()),
+                rank,
+            })
+        }
+        "portfolio" => {
+            let p = store
+                .portfolio_find_by_id(id)
+                .filter(|p| p.status == "published")?;
+            let html = p.description_html.unwrap_or_default();
+            Some(SearchResult {
+                item_type: "portfolio".into(),
+                item_id: p.id,
+                snippet: truncate_words(&strip_html(&html), 40),
+                title: p.title,
+                slug: p.slug,
+                image: Some(p.image_path),
+                date: p.published_at.map(|d| d.to_string()),
+                rank,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Full-text search (through the external search engine when one is
+/// configured), topped up with semantic matches when it finds little
+/// (a query worded differently from the posts, or a typo).
+pub fn search_with_fallback(store: &dyn Store, query: &str, limit: i64) -> Vec<SearchResult> {
+    let mut results = crate::search_engine::query(store, query, limit);
+    if results.len() >= SEARCH_FALLBACK_BELOW || !enabled(&store.setting_all()) {
+        return results;
+    }
+    let room = (limit.max(0) as usize).saturating_sub(results.len());
+    for r in semantic_search(store, query, room + results.len()) {
+        if results.len() as i64 >= limit {
+            break;
+        }
+        if !results
+            .iter()
+            .any(|e| e.item_type == r.item_type && e.item_id == r.item_id)
+        {
+            results.push(r);
+        }
+    }
+    results
+}
@@ -83,9 +83,20 @@ pub fn call(settings: &HashMap<String, String>, req: &AiRequest) -> Result<AiRes
         .unwrap_or("")
         .to_string();
 
+    let (prompt_tokens, completion_tokens) = (
+        json.pointer("/usage/prompt_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32,
+        json.pointer("/usage/completion_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32,
+    );
+
     Ok(AiResponse {
         text,
         provider: "groq".into(),
         model,
+        prompt_tokens,
+        completion_tokens,
     })
 }
@@ -1,9 +1,14 @@
+pub mod captions;
 pub mod cloudflare;
+pub mod embeddings;
 pub mod gemini;
 pub mod groq;
+pub mod mistral;
 pub mod ollama;
 pub mod openai;
+pub mod openrouter;
 pub mod prompts;
+pub mod usage;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -20,6 +25,10 @@ pub struct AiRequest {
     pub temperature: Option<f32>,
     #[serde(default)]
     pub image_base64: Option<String>,
+    /// What asked for the completion ("suggest_meta", "generate_post", …),
+    /// recorded with the call's token usage.
+    #[serde(default)]
+    pub feature: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +36,11 @@ pub struct AiResponse {
     pub text: String,
     pub provider: String,
     pub model: String,
+    /// Token counts as reported by the provider; 0 when it didn't say.
+    #[serde(default)]
+    pub prompt_tokens: u32,
+    #[serde(default)]
+    pub completion_tokens: u32,
 }
 
 #[derive(Debug)]
@@ -47,6 +61,8 @@ enum Provider {
     Gemini,
     Cloudflare,
     Groq,
+    Mistral,
+    OpenRouter,
 }
 
 impl Provider {
@@ -57,6 +73,8 @@ impl Provider {
             "gemini" => Some(Self::Gemini),
             "cloudflare" => Some(Self::Cloudflare),
             "groq" => Some(Self::Groq),
+            "mistral" => Some(Self::Mistral),
+            "openrouter" => Some(Self::OpenRouter),
             _ => None,
         }
     }
@@ -68,30 +86,35 @@ impl Provider {
             Self::Gemini => "gemini",
             Self::Cloudflare => "cloudflare",
             Self::Groq => "groq",
+            Self::Mistral => "mistral",
+            Self::OpenRouter => "openrouter",
         }
     }
 
     fn supports_vision(&self) -> bool {
         matches!(
             self,
-            Self::OpenAi | Self::Gemini | Self::Ollama | Self::Groq
+            Self::OpenAi
+                | Self::Gemini
+                | Self::Ollama
+                | Self::Groq
+                | Self::Mistral
+                | Self::OpenRouter
         )
     }
 }
 
 // ── Public API ────────────────────────────────────────
 
+/// Failover order used when `ai_failover_chain` is unset.
+pub const DEFAULT_CHAIN: &str = "ollama,openai,gemini,groq,mistral,openrouter,cloudflare";
+
 /// Send a request through the failover chain. Returns the first successful response.
 pub fn complete(store: &dyn Store, req: &AiRequest) -> Result<AiResponse, AiError> {
     let settings: HashMap<String, String> = store.setting_all();
-    let chain_str = settings
-        .get("ai_failover_chain")
-        .cloned()
-        .unwrap_or_else(|| "ollama,openai,gemini,groq,cloudflare".to_string());
-
-    let chain: Vec<Provider> = chain_str
-        .split(',')
-        .filter_map(Provider::from_str)
+    let chain: Vec<Provider> = failover_chain(&settings)
+        .iter()
+        .filter_map(|p| Provider::from_str(p))
         .collect();
 
     if chain.is_empty() {
@@ -114,10 +137,46 @@ pub fn complete(store: &dyn Store, req: &AiRequest) -> Result<AiResponse, AiErro
             continue;
         }
 
+        let started = std::time::Instant::now();
         match call_provider(provider, &settings, req) {
-            Ok(resp) => return Ok(resp),
+            Ok(resp) => {
+                // Not every provider reports usage; estimate from the text instead
+                let prompt_tokens = match resp.prompt_tokens {
+                    0 => usage::estimate_tokens(&req.system) + usage::estimate_tokens(&req.prompt),
+                    n => n,
+                };
+                let completion_tokens = match resp.completion_tokens {
+                    0 => usage::estimate_tokens(&resp.text),
+                    n => n,
+                };
+                usage::record(
+                    store,
+                    provider.name(),
+                    &resp.model,
+                    &req.feature,
+                    prompt_tokens,
+                    completion_tokens,
+                    started.elapsed(),
+                    None,
+                );
+                return Ok(resp);
+            }
             Err(e) => {
                 log::warn!("AI provider {} failed: {}", provider.name(), e.0);
+                let model = settings
+                    .get(&format!("ai_{}_model", provider.name()))
+                    .cloned()
+                    .unwrap_or_default();
+                usage::record(
+                    store,
+                    provider.name(),
+                    &model,
+                    &req.feature,
+                    0,
+                    0,
+                    started.elapsed(),
+                    Some(&e.0),
+                );
                 last_error = e.0;
             }
         }
@@ -129,23 +188,62 @@ pub fn complete(store: &dyn Store, req: &AiRequest) -> Result<AiResponse, AiErro
     )))
 }
 
+/// The saved failover order, with providers added since it was saved
+/// appended at the end so enabling them is enough to use them.
+pub fn failover_chain(settings: &HashMap<String, String>) -> Vec<String> {
+    let chain_str = settings
+        .get("ai_failover_chain")
+        .filter(|s| !s.trim().is_empty())
+        .cloned()
+        .unwrap_or_else(|| DEFAULT_CHAIN.to_string());
+    let mut chain: Vec<String> = chain_str
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| Provider::from_str(s).is_some())
+        .collect();
+    for p in DEFAULT_CHAIN.split(',') {
+        if !chain.iter().any(|c| c == p) {
+            chain.push(p.to_string());
+        }
+    }
+    chain
+}
+
 /// Check if any AI provider is enabled
 pub fn is_enabled(store: &dyn Store) -> bool {
     let settings: HashMap<String, String> = store.setting_all();
-    ["ollama", "openai", "gemini", "cloudflare", "groq"]
-        .iter()
-        .any(|p| {
-            settings
-                .get(&format!("ai_{}_enabled", p))
-                .map(|v| v.as_str())
-                == Some("true")
-        })
+    [
+        "ollama",
+        "openai",
+        "gemini",
+        "cloudflare",
+        "groq",
+        "mistral",
+        "openrouter",
+    ]
+    .iter()
+    .any(|p| {
+        settings
+            .get(&format!("ai_{}_enabled", p))
+            .map(|v| v.as_str())
+            == Some("true")
+    })
 }
 
-/// Check if any vision-capable provider is enabled (Ollama, OpenAI, Gemini, Groq)
+/// Check if any vision-capable provider is enabled (Ollama, OpenAI, Gemini,
+/// Groq, Mistral, OpenRouter)
 pub fn has_vision_provider(store: &dyn Store) -> bool {
     let settings: HashMap<String, String> = store.setting_all();
-    ["ollama", "openai", "gemini", "groq"].iter().any(|p| {
+    [
+        "ollama",
+        "openai",
+        "gemini",
+        "groq",
+        "mistral",
+        "openrouter",
+    ]
+    .iter()
+    .any(|p| {
         settings
             .get(&format!("ai_{}_enabled", p))
             .map(|v| v.as_str())
@@ -187,5 +285,7 @@ fn call_provider(
         Provider::Gemini => gemini::call(settings, req),
         Provider::Cloudflare => cloudflare::call(settings, req),
         Provider::Groq => groq::call(settings, req),
+        Provider::Mistral => mistral::call(settings, req),
+        Provider::OpenRouter => openrouter::call(settings, req),
     }
 }
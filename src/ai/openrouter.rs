@@ -0,0 +1,118 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use super::{AiError, AiRequest, AiResponse};
+
+/// Generic OpenAI-compatible provider. Points at OpenRouter by default, but
+/// any `/chat/completions` endpoint works (vLLM, LM Studio, LiteLLM, Together…),
+/// so self-hosted and aggregated models need no code changes.
+pub fn call(settings: &HashMap<String, String>, req: &AiRequest) -> Result<AiResponse, AiError> {
+    let base_url = settings
+        .get("ai_openrouter_base_url")
+        .map(|u| u.trim().trim_end_matches('/').to_string())
+        .filter(|u| !u.is_empty())
+        .unwrap_or_else(|| "https://openrouter.ai/api/v1".to_string());
+
+    let model = settings
+        .get("ai_openrouter_model")
+        .map(|m| m.trim().to_string())
+        .unwrap_or_default();
+    if model.is_empty() {
+        return Err(AiError("OpenRouter model not configured".into()));
+    }
+
+    // Self-hosted servers often run without a key; OpenRouter itself needs one
+    let api_key = settings
+        .get("ai_openrouter_api_key")
+        .cloned()
+        .unwrap_or_default();
+    if api_key.is_empty() && base_url.contains("openrouter.ai") {
+        return Err(AiError("OpenRouter API key not configured".into()));
+    }
+
+    let url = format!("{}/chat/completions", base_url);
+
+    let user_message = if let Some(ref img) = req.image_base64 {
+        json!({
+            "role": "user",
+            "content": [
+                {"type": "text", "text": req.prompt},
+                {"type": "image_url", "image_url": {"url": format!("data:image/jpeg;base64,{}", img)}}
+            ]
+        })
+    } else {
+        json!({"role": "user", "content": req.prompt})
+    };
+
+    let body = json!({
+        "model": model,
+        "messages": [
+            {"role": "system", "content": req.system},
+            user_message
+        ],
+        "max_tokens": req.max_tokens.unwrap_or(1024),
+        "temperature": req.temperature.unwrap_or(0.7)
+    });
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(120))
+        .build()
+        .map_err(|e| AiError(format!("HTTP client error: {}", e)))?;
+
+    let mut request = client
+        .post(&url)
+        .header("Content-Type", "application/json")
+        // OpenRouter attribution headers; other servers ignore them
+        .header(
+            "HTTP-Referer",
+            settings.get("site_url").cloned().unwrap_or_default(),
+        )
+        .header(
+            "X-Title",
+            settings.get("site_name").cloned().unwrap_or_default(),
+        );
+    if !api_key.is_empty() {
+        request = request.header("Authorization", format!("Bearer {}", api_key));
+    }
+
+    let resp = request
+        .json(&body)
+        .send()
+        .map_err(|e| AiError(format!("OpenRouter request failed: {}", e)))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().unwrap_or_default();
+        return Err(AiError(format!("OpenRouter returned {}: {}", status, text)));
+    }
+
+    let json: Value = resp
+        .json()
+        .map_err(|e| AiError(format!("OpenRouter JSON parse error: {}", e)))?;
+
+    let text = json
+        .get("choices")
+        .and_then(|c| c.get(0))
+        .and_then(|c| c.get("message"))
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_str())
+        .unwrap_or("")
+        .to_string();
+
+    let (prompt_tokens, completion_tokens) = (
+        json.pointer("/usage/prompt_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32,
+        json.pointer("/usage/completion_tokens")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as u32,
+    );
+
+    Ok(AiResponse {
+        text,
+        provider: "openrouter".into(),
+        model,
+        prompt_tokens,
+        completion_tokens,
+    })
+}
@@ -108,6 +108,64 @@ pub fn generate_post(description: &str) -> String {
     )
 }
 
+/// First step of outline-based generation: plan the post without writing it
+pub fn generate_outline(description: &str) -> String {
+    format!(
+        "Plan a blog post based on this description:\n\n{}\n\n\
+         Do NOT write the post yet. Produce an outline:\n\
+         - A title (≤70 chars)\n\
+         - 3-6 sections, each with an h2 heading and 2-4 key points to cover\n\
+         - 3-6 target keywords or keyphrases for search\n\
+         - An excerpt (1-2 sentences) and 3-5 tags\n\n\
+         Respond as JSON:\n\
+         {{\"title\": \"...\", \"keywords\": [\"...\"], \"sections\": [{{\"heading\": \"...\", \"points\": [\"...\"]}}], \"excerpt\": \"...\", \"tags\": [\"...\"]}}",
+        description
+    )
+}
+
+/// Second step: write one section of an approved outline
+pub fn expand_section(
+    title: &str,
+    keywords: &[String],
+    outline: &[String],
+    heading: &str,
+    points: &[String],
+) -> String {
+    let outline = outline
+        .iter()
+        .enumerate()
+        .map(|(i, h)| format!("{}. {}", i + 1, h))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let points = if points.is_empty() {
+        "- (use your judgement)".to_string()
+    } else {
+        points
+            .iter()
+            .map(|p| format!("- {}", p))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let keywords = if keywords.is_empty() {
+        "none".to_string()
+    } else {
+        keywords.join(", ")
+    };
+    format!(
+        "You are writing the blog post \"{}\" one section at a time.\n\n\
+         Full outline:\n{}\n\n\
+         Target keywords: {}\n\n\
+         Write ONLY the section \"{}\", covering:\n{}\n\n\
+         Requirements:\n\
+         - Start with <h2>{}</h2>, use h3 for any subheadings\n\
+         - HTML formatting (p, ul/li, strong, em)\n\
+         - 150-300 words, professional but approachable tone\n\
+         - Work keywords in naturally; don't repeat other sections\n\n\
+         Respond as JSON: {{\"html\": \"...\"}}",
+        title, outline, keywords, heading, points, heading
+    )
+}
+
 /// Suggest all fields at once: title, tags, description, categories, meta
 pub fn suggest_all(
     title: &str,
@@ -185,3 +243,52 @@ pub fn inline_assist(action: &str, selected_text: &str) -> String {
         instruction, selected_text
     )
 }
+
+/// Rewrite suggestions for a page that scored poorly in the SEO audit
+pub fn seo_rewrite(
+    content_type: &str,
+    title: &str,
+    meta_title: &str,
+    meta_description: &str,
+    headings: &[(u8, String)],
+    issues: &[String],
+    content_excerpt: &str,
+) -> String {
+    let headings = if headings.is_empty() {
+        "none".to_string()
+    } else {
+        headings
+            .iter()
+            .map(|(level, text)| format!("- h{}: {}", level, text))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let issues = if issues.is_empty() {
+        "none".to_string()
+    } else {
+        issues
+            .iter()
+            .map(|i| format!("- {}", i))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    format!(
+        "Audit this {} for search engines and suggest specific rewrites.\n\n\
+         Title: {}\nMeta title: {}\nMeta description: {}\n\n\
+         Headings:\n{}\n\nIssues found by the audit:\n{}\n\nContent excerpt:\n{}\n\n\
+         Suggest 3 meta title variants (50-60 chars), one meta description (120-155 chars), \
+         and up to 4 heading changes that give the content a clearer h2/h3 structure. \
+         Only change headings listed above, quoting them exactly in \"from\". \
+         Give a short reason for each suggestion.\n\
+         Respond as JSON: {{\"titles\": [{{\"text\": \"...\", \"reason\": \"...\"}}], \
+         \"meta_description\": {{\"text\": \"...\", \"reason\": \"...\"}}, \
+         \"headings\": [{{\"from\": \"...\", \"to\": \"...\", \"level\": 2, \"reason\": \"...\"}}]}}",
+        content_type,
+        title,
+        if meta_title.is_empty() { "(none)" } else { meta_title },
+        if meta_description.is_empty() { "(none)" } else { meta_description },
+        headings,
+        issues,
+        content_excerpt
+    )
+}
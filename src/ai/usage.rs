@@ -0,0 +1,234 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::models::ai_usage::{AiUsage, AiUsageTotal};
+use crate::store::Store;
+
+/// List prices in USD per million (input, output) tokens, matched against the
+/// model name by longest prefix. Rough figures for estimates only; override
+/// them with the `ai_pricing` setting.
+const DEFAULT_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4-turbo", 10.00, 30.00),
+    ("gpt-4", 30.00, 60.00),
+    ("gpt-3.5-turbo", 0.50, 1.50),
+    ("text-embedding-3-small", 0.02, 0.0),
+    ("text-embedding-3-large", 0.13, 0.0),
+    ("gemini-2.0-flash", 0.10, 0.40),
+    ("gemini-1.5-flash", 0.075, 0.30),
+    ("gemini-1.5-pro", 1.25, 5.00),
+    ("gemini-pro", 0.50, 1.50),
+    ("text-embedding-004", 0.0, 0.0),
+    ("llama-3.3-70b-versatile", 0.59, 0.79),
+    ("llama-3.1-8b-instant", 0.05, 0.08),
+    ("mistral-large-latest", 2.00, 6.00),
+    ("mistral-small-latest", 0.20, 0.60),
+    ("pixtral-large-latest", 2.00, 6.00),
+    ("mistral-embed", 0.10, 0.0),
+];
+
+/// Rough token count for text a provider didn't report usage for.
+pub fn estimate_tokens(text: &str) -> u32 {
+    text.chars().count().div_ceil(4) as u32
+}
+
+/// Record one provider call. Failing to record never fails the call itself.
+#[allow(clippy::too_many_arguments)]
+pub fn record(
+    store: &dyn Store,
+    provider: &str,
+    model: &str,
+    feature: &str,
+    prompt_tokens: u32,
+    completion_tokens: u32,
+    latency: std::time::Duration,
+    error: Option<&str>,
+) {
+    let usage = AiUsage {
+        id: 0,
+        provider: provider.to_string(),
+        model: model.to_string(),
+        feature: if feature.is_empty() {
+            "other".to_string()
+        } else {
+            feature.to_string()
+        },
+        prompt_tokens: prompt_tokens as i64,
+        completion_tokens: completion_tokens as i64,
+        latency_ms: latency.as_millis() as i64,
+        success: error.is_none(),
+        error: error.unwrap_or("").chars().take(500).collect(),
+        created_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+    };
+    if let Err(e) = store.ai_usage_record(&usage) {
+        log::warn!("[ai] Failed to record usage: {}", e);
+    }
+}
+
+/// Parse the `ai_pricing` setting: one `model: input, output` per line, in
+/// USD per million tokens.
+pub fn parse_pricing(raw: &str) -> Vec<(String, f64, f64)> {
+    raw.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let (model, prices) = line.rsplit_once(':')?;
+            let (input, output) = prices.split_once(',')?;
+            Some((
+                model.trim().to_lowercase(),
+                input.trim().parse().ok()?,
+                output.trim().parse().ok()?,
+            ))
+        })
+        .collect()
+}
+
+/// Price per million (input, output) tokens for `provider`/`model`, or None
+/// when it isn't known. Local Ollama models cost nothing.
+pub fn price_for(
+    provider: &str,
+    model: &str,
+    overrides: &[(String, f64, f64)],
+) -> Option<(f64, f64)> {
+    if provider == "ollama" {
+        return Some((0.0, 0.0));
+    }
+    let model = model.to_lowercase();
+    // OpenRouter and Cloudflare name models "vendor/model"
+    let short = model.rsplit('/').next().unwrap_or(&model);
+    overrides
+        .iter()
+        .filter(|(m, _, _)| model.starts_with(m.as_str()) || short.starts_with(m.as_str()))
+        .max_by_key(|(m, _, _)| m.len())
+        .map(|(_, i, o)| (*i, *o))
+        .or_else(|| {
+            DEFAULT_PRICES
+                .iter()
+                .filter(|(m, _, _)| short.starts_with(m))
+                .max_by_key(|(m, _, _)| m.len())
+                .map(|(_, i, o)| (*i, *o))
+        })
+}
+
+/// Estimated cost in USD, or None when the model's price is unknown.
+pub fn estimate_cost(
+    provider: &str,
+    model: &str,
+    prompt_tokens: i64,
+    completion_tokens: i64,
+    overrides: &[(String, f64, f64)],
+) -> Option<f64> {
+    let (input, output) = price_for(provider, model, overrides)?;
+    Some((prompt_tokens as f64 * input + completion_tokens as f64 * output) / 1_000_000.0)
+}
+
+/// One line of the monthly report.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct UsageRow {
+    pub name: String,
+    pub calls: i64,
+    pub failures: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub avg_latency_ms: i64,
+    pub cost: f64,
+    /// Some calls were to models without a known price
+    pub unpriced: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct MonthReport {
+    /// "YYYY-MM"
+    pub month: String,
+    pub total: UsageRow,
+    pub by_provider: Vec<UsageRow>,
+    pub by_feature: Vec<UsageRow>,
+    pub by_model: Vec<UsageRow>,
+}
+
+/// First instant of `month` ("YYYY-MM") and of the month after, as stored
+/// timestamps. None when `month` doesn't parse.
+pub fn month_bounds(month: &str) -> Option<(String, String)> {
+    let start = chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok()?;
+    let end = start.checked_add_months(chrono::Months::new(1))?;
+    Some((
+        format!("{} 00:00:00", start.format("%Y-%m-%d")),
+        format!("{} 00:00:00", end.format("%Y-%m-%d")),
+    ))
+}
+
+fn add(row: &mut UsageRow, t: &AiUsageTotal, cost: Option<f64>) {
+    row.calls += t.calls;
+    row.failures += t.failures;
+    row.prompt_tokens += t.prompt_tokens;
+    row.completion_tokens += t.completion_tokens;
+    // Summed here, averaged in `finish`
+    row.avg_latency_ms += t.latency_ms;
+    match cost {
+        Some(c) => row.cost += c,
+        None => row.unpriced = true,
+    }
+}
+
+fn finish(rows: HashMap<String, UsageRow>) -> Vec<UsageRow> {
+    let mut rows: Vec<UsageRow> = rows
+        .into_iter()
+        .map(|(name, mut r)| {
+            r.name = name;
+            r.avg_latency_ms = r.avg_latency_ms.checked_div(r.calls).unwrap_or(0);
+            r
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.cost
+            .partial_cmp(&a.cost)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.calls.cmp(&a.calls))
+    });
+    rows
+}
+
+/// Calls, tokens and estimated cost for `month`, per provider, feature and model.
+pub fn month_report(store: &dyn Store, month: &str) -> MonthReport {
+    let (since, until) = match month_bounds(month) {
+        Some(b) => b,
+        None => return MonthReport::default(),
+    };
+    let overrides = parse_pricing(&store.setting_get_or("ai_pricing", ""));
+
+    let mut total = UsageRow::default();
+    let mut providers: HashMap<String, UsageRow> = HashMap::new();
+    let mut features: HashMap<String, UsageRow> = HashMap::new();
+    let mut models: HashMap<String, UsageRow> = HashMap::new();
+    for t in store.ai_usage_totals(&since, &until) {
+        let cost = estimate_cost(
+            &t.provider,
+            &t.model,
+            t.prompt_tokens,
+            t.completion_tokens,
+            &overrides,
+        );
+        add(&mut total, &t, cost);
+        add(providers.entry(t.provider.clone()).or_default(), &t, cost);
+        add(features.entry(t.feature.clone()).or_default(), &t, cost);
+        add(
+            models
+                .entry(format!("{} · {}", t.provider, t.model))
+                .or_default(),
+            &t,
+            cost,
+        );
+    }
+    total.avg_latency_ms = total.avg_latency_ms.checked_div(total.calls).unwrap_or(0);
+
+    MonthReport {
+        month: month.to_string(),
+        total,
+        by_provider: finish(providers),
+        by_feature: finish(features),
+        by_model: finish(models),
+    }
+}
@@ -1,7 +1,18 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
 use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::HeaderMap;
+use rocket::request::{FromRequest, Outcome};
 use rocket::{Data, Request};
+use serde::Serialize;
+use serde_json::Value;
 use sha2::{Digest, Sha256};
 
+use crate::models::analytics::{CountEntry, EngagementPing, Utm};
+use crate::models::experiment::{self, Experiment, ExperimentReport, VariantReport};
+use crate::models::goal::{self, GoalReport};
 use crate::store::Store;
 use crate::ADMIN_INTERNAL_MOUNT;
 
@@ -27,6 +38,8 @@ impl Fairing for AnalyticsFairing {
             || path.starts_with("/uploads")
             || path.starts_with("/api")
             || path == "/favicon.ico"
+            || path == "/out"
+            || path == "/beacon"
         {
             return;
         }
@@ -36,42 +49,702 @@ impl Fairing for AnalyticsFairing {
             None => return,
         };
 
+        // Visitors who send Do-Not-Track or Global Privacy Control aren't
+        // recorded at all when the site honors them
+        if store.setting_get_bool("analytics_respect_dnt") && opted_out(request.headers()) {
+            return;
+        }
+
         let ip = request
             .client_ip()
             .map(|ip| ip.to_string())
             .unwrap_or_else(|| "unknown".to_string());
 
-        let ip_hash = hash_ip(&ip);
+        let ip_hash = visitor_hash(&**store, &ip);
 
         let referrer = request.headers().get_one("Referer").map(extract_domain);
 
         let ua_string = request.headers().get_one("User-Agent").unwrap_or("");
         let (device_type, browser) = parse_user_agent(ua_string);
 
-        // GeoIP lookup would happen here with maxminddb
-        // For now, country/city are None until GeoLite2 DB is configured
-        let country: Option<&str> = None;
-        let city: Option<&str> = None;
+        // Local GeoLite2 database; empty when none is configured
+        let geo = crate::geoip::lookup(&**store, &ip);
+
+        if let Some(live) = request.rocket().state::<LiveVisitors>() {
+            live.record(&path, &ip_hash, referrer.as_deref());
+        }
+
+        let utm = request
+            .uri()
+            .query()
+            .map(|q| Utm::from_query(q.as_str()))
+            .unwrap_or_default();
 
         let _ = store.analytics_record(
             &path,
             &ip_hash,
-            country,
-            city,
+            geo.country.as_deref(),
+            geo.city.as_deref(),
             referrer.as_deref(),
             Some(ua_string),
             Some(device_type),
             Some(browser),
+            &utm,
         );
+        record_goals(&**store, &path, &ip_hash);
+
+        // A result link followed from a logged search
+        if let Some(sid) = request.uri().query().and_then(|q| search_id(q.as_str())) {
+            let _ = store.search_log_click(sid, &ip_hash, &path);
+        }
+    }
+}
+
+/// How far back the live view looks.
+pub const LIVE_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Hits kept at most; the oldest are dropped first on a busy site.
+const LIVE_CAPACITY: usize = 5000;
+
+struct LiveHit {
+    at: Instant,
+    path: String,
+    ip_hash: String,
+    referrer: Option<String>,
+}
+
+/// Ring buffer of the last few minutes of page views, fed by
+/// `AnalyticsFairing`, so the live dashboard never touches the database.
+pub struct LiveVisitors {
+    hits: Mutex<VecDeque<LiveHit>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LiveSnapshot {
+    /// Distinct visitors in the last five minutes
+    pub active_visitors: usize,
+    pub views: usize,
+    pub top_pages: Vec<CountEntry>,
+    pub top_referrers: Vec<CountEntry>,
+}
+
+impl LiveVisitors {
+    pub fn new() -> Self {
+        LiveVisitors {
+            hits: Mutex::new(VecDeque::with_capacity(LIVE_CAPACITY)),
+        }
+    }
+
+    pub fn record(&self, path: &str, ip_hash: &str, referrer: Option<&str>) {
+        self.record_at(Instant::now(), path, ip_hash, referrer);
+    }
+
+    pub(crate) fn record_at(&self, at: Instant, path: &str, ip_hash: &str, referrer: Option<&str>) {
+        let mut hits = self.hits.lock().unwrap();
+        if hits.len() >= LIVE_CAPACITY {
+            hits.pop_front();
+        }
+        hits.push_back(LiveHit {
+            at,
+            path: path.to_string(),
+            ip_hash: ip_hash.to_string(),
+            referrer: referrer.map(|r| r.to_string()),
+        });
+    }
+
+    pub fn snapshot(&self) -> LiveSnapshot {
+        self.snapshot_at(Instant::now())
+    }
+
+    pub(crate) fn snapshot_at(&self, now: Instant) -> LiveSnapshot {
+        let mut hits = self.hits.lock().unwrap();
+        while hits
+            .front()
+            .is_some_and(|h| now.saturating_duration_since(h.at) > LIVE_WINDOW)
+        {
+            hits.pop_front();
+        }
+
+        let mut visitors = HashSet::new();
+        let mut pages: HashMap<&str, i64> = HashMap::new();
+        let mut referrers: HashMap<&str, i64> = HashMap::new();
+        for h in hits.iter() {
+            visitors.insert(h.ip_hash.as_str());
+            *pages.entry(&h.path).or_default() += 1;
+            if let Some(r) = &h.referrer {
+                *referrers.entry(r).or_default() += 1;
+            }
+        }
+        LiveSnapshot {
+            active_visitors: visitors.len(),
+            views: hits.len(),
+            top_pages: top_counts(pages),
+            top_referrers: top_counts(referrers),
+        }
+    }
+}
+
+impl Default for LiveVisitors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn top_counts(counts: HashMap<&str, i64>) -> Vec<CountEntry> {
+    let mut entries: Vec<CountEntry> = counts
+        .into_iter()
+        .map(|(label, count)| CountEntry {
+            label: label.to_string(),
+            count,
+        })
+        .collect();
+    entries.sort_by(|a, b| b.count.cmp(&a.count).then(a.label.cmp(&b.label)));
+    entries.truncate(10);
+    entries
+}
+
+/// Record a completion for every configured goal `path` reaches.
+pub fn record_goals(store: &dyn Store, path: &str, ip_hash: &str) {
+    let raw = store.setting_get_or("analytics_goals", "");
+    if raw.trim().is_empty() {
+        return;
+    }
+    for g in goal::parse_goals(&raw) {
+        if g.matches(path) {
+            let _ = store.goal_record(&g.name, ip_hash, path);
+        }
+    }
+}
+
+/// Completions and conversion rate of every configured goal over a period,
+/// against the period's unique visitors.
+pub fn goal_report(store: &dyn Store, from: &str, to: &str) -> Vec<GoalReport> {
+    let goals = goal::parse_goals(&store.setting_get_or("analytics_goals", ""));
+    if goals.is_empty() {
+        return vec![];
+    }
+    let visitors = store.analytics_overview(from, to).unique_visitors;
+    let stats = store.goal_stats(from, to);
+    goals
+        .into_iter()
+        .map(|g| {
+            let stat = stats
+                .iter()
+                .find(|s| s.goal == g.name)
+                .cloned()
+                .unwrap_or_default();
+            let conversion_rate = if visitors > 0 {
+                (stat.converters as f64 * 1000.0 / visitors as f64).round() / 10.0
+            } else {
+                0.0
+            };
+            GoalReport {
+                goal: g.name,
+                pattern: g.pattern,
+                completions: stat.completions,
+                converters: stat.converters,
+                visitors,
+                conversion_rate,
+            }
+        })
+        .collect()
+}
+
+/// Longest search query kept in the search log
+const SEARCH_QUERY_MAX: usize = 200;
+
+/// A search as logged: trimmed, lower-case, with runs of whitespace
+/// collapsed, so "Night  Sky" and "night sky" count as one query.
+pub fn normalize_search_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .take(SEARCH_QUERY_MAX)
+        .collect()
+}
+
+/// Log a public site search when `analytics_search_log` is on. Returns the
+/// log id, which result links carry as `sid` so the click can be attributed.
+pub fn record_search(store: &dyn Store, query: &str, results: usize, ip: &str) -> Option<i64> {
+    if !store.setting_get_bool("analytics_search_log") {
+        return None;
+    }
+    let query = normalize_search_query(query);
+    if query.is_empty() {
+        return None;
+    }
+    store
+        .search_log_record(&query, results as i64, &visitor_hash(store, ip))
+        .ok()
+}
+
+/// The `sid` search log id in a query string, if any.
+fn search_id(query: &str) -> Option<i64> {
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(k, _)| k == "sid")
+        .and_then(|(_, v)| v.parse().ok())
+}
+
+/// Configured experiments. An empty `visitor` (one who opted out of
+/// tracking) takes part in none.
+fn experiments(store: &dyn Store, visitor: &str) -> Vec<Experiment> {
+    if visitor.is_empty() {
+        return vec![];
+    }
+    experiment::parse_experiments(&store.setting_get_or("analytics_experiments", ""))
+}
+
+/// Give each listed post under a title experiment the title this visitor
+/// is assigned, and record the impression.
+pub fn apply_title_experiments(store: &dyn Store, posts: &mut [Value], visitor: &str) {
+    for exp in experiments(store, visitor) {
+        let Some(slug) = exp.post_slug() else {
+            continue;
+        };
+        for post in posts.iter_mut().filter(|p| p["slug"] == slug) {
+            let v = exp.variant_for(visitor);
+            post["title"] = Value::String(exp.variants[v].clone());
+            let _ = store.experiment_event_record(
+                &exp.name,
+                experiment::VARIANT_LABELS[v],
+                "impression",
+                visitor,
+            );
+        }
     }
 }
 
-fn hash_ip(ip: &str) -> String {
+/// Swap the homepage hero line (the site tagline) for this visitor's variant
+/// of the first homepage experiment, and record the impression.
+pub fn apply_hero_experiment(
+    store: &dyn Store,
+    settings: &mut HashMap<String, String>,
+    visitor: &str,
+) {
+    if let Some(exp) = experiments(store, visitor)
+        .into_iter()
+        .find(|e| e.is_home())
+    {
+        let v = exp.variant_for(visitor);
+        settings.insert("site_caption".to_string(), exp.variants[v].clone());
+        let _ = store.experiment_event_record(
+            &exp.name,
+            experiment::VARIANT_LABELS[v],
+            "impression",
+            visitor,
+        );
+    }
+}
+
+/// A visitor opened a post or portfolio item. That's a click for every
+/// homepage experiment, and for the title experiment on this post, whose
+/// variant `post` (the post's JSON) is given so its heading stays the same.
+/// Clicks only count in reports when the visitor saw an impression first.
+pub fn record_experiment_click(store: &dyn Store, mut post: Option<&mut Value>, visitor: &str) {
+    let exps = experiments(store, visitor);
+    if exps.is_empty() {
+        return;
+    }
+    let slug = post
+        .as_ref()
+        .and_then(|p| p["slug"].as_str())
+        .map(str::to_string);
+    for exp in exps {
+        let is_this_post = slug.is_some() && exp.post_slug() == slug.as_deref();
+        if !exp.is_home() && !is_this_post {
+            continue;
+        }
+        let v = exp.variant_for(visitor);
+        if is_this_post {
+            if let Some(p) = post.as_deref_mut() {
+                p["title"] = Value::String(exp.variants[v].clone());
+            }
+        }
+        let _ = store.experiment_event_record(
+            &exp.name,
+            experiment::VARIANT_LABELS[v],
+            "click",
+            visitor,
+        );
+    }
+}
+
+/// Impressions, click-through rates and significance of every configured
+/// experiment over a period.
+pub fn experiment_report(store: &dyn Store, from: &str, to: &str) -> Vec<ExperimentReport> {
+    let exps = experiment::parse_experiments(&store.setting_get_or("analytics_experiments", ""));
+    if exps.is_empty() {
+        return vec![];
+    }
+    let stats = store.experiment_stats(from, to);
+    exps.into_iter()
+        .map(|exp| {
+            let variants: Vec<VariantReport> = experiment::VARIANT_LABELS
+                .iter()
+                .zip(exp.variants.iter())
+                .map(|(label, text)| {
+                    let stat = stats
+                        .iter()
+                        .find(|s| s.experiment == exp.name && s.variant == *label)
+                        .cloned()
+                        .unwrap_or_default();
+                    let click_rate = if stat.impressions > 0 {
+                        (stat.clicks as f64 * 1000.0 / stat.impressions as f64).round() / 10.0
+                    } else {
+                        0.0
+                    };
+                    VariantReport {
+                        variant: label.to_string(),
+                        text: text.clone(),
+                        impressions: stat.impressions,
+                        clicks: stat.clicks,
+                        click_rate,
+                    }
+                })
+                .collect();
+            let (a, b) = (&variants[0], &variants[1]);
+            let (z_score, p_value) =
+                experiment::two_proportion_test(a.impressions, a.clicks, b.impressions, b.clicks)
+                    .unwrap_or((0.0, 1.0));
+            let enough =
+                a.impressions >= experiment::MIN_SAMPLE && b.impressions >= experiment::MIN_SAMPLE;
+            let winner = (enough && p_value < 0.05).then(|| {
+                if z_score > 0.0 {
+                    b.variant.clone()
+                } else {
+                    a.variant.clone()
+                }
+            });
+            ExperimentReport {
+                name: exp.name,
+                target: exp.target,
+                z_score: (z_score * 100.0).round() / 100.0,
+                p_value: (p_value * 10000.0).round() / 10000.0,
+                confidence: ((1.0 - p_value) * 1000.0).round() / 10.0,
+                winner,
+                variants,
+            }
+        })
+        .collect()
+}
+
+/// Longest time on page an engagement ping may report; anything longer is a
+/// tab left open.
+const ENGAGEMENT_MAX_SECONDS: i64 = 3600;
+
+/// Record an engagement beacon ping when `analytics_engagement_tracking` is
+/// on. Scroll depth and seconds are clamped; pings for paths that aren't a
+/// plain site path are dropped. Returns whether it was recorded.
+pub fn record_engagement(store: &dyn Store, ping: &EngagementPing, ip: &str) -> bool {
+    if !store.setting_get_bool("analytics_engagement_tracking") {
+        return false;
+    }
+    let path = ping.path.trim();
+    if !path.starts_with('/') || path.starts_with("//") || path.len() > 500 {
+        return false;
+    }
+    if path.starts_with(ADMIN_INTERNAL_MOUNT) {
+        return false;
+    }
+    let scroll = ping.scroll.clamp(0, 100);
+    let seconds = ping.seconds.clamp(0, ENGAGEMENT_MAX_SECONDS);
+    store
+        .engagement_record(path, &visitor_hash(store, ip), scroll, seconds)
+        .is_ok()
+}
+
+/// Days of traffic in the post and portfolio editor's analytics panel.
+pub const CONTENT_PANEL_DAYS: i64 = 30;
+
+/// Traffic to one post or portfolio item (its public `path`) over the last
+/// `CONTENT_PANEL_DAYS`, for the analytics panel in its editor. Days
+/// without views are filled in so the chart has one bar per day.
+pub fn content_panel(store: &dyn Store, path: &str) -> Value {
+    let now = chrono::Utc::now().naive_utc();
+    let start = now - chrono::Duration::days(CONTENT_PANEL_DAYS - 1);
+    let from = format!("{} 00:00:00", start.format("%Y-%m-%d"));
+    let to = now.format("%Y-%m-%d %H:%M:%S").to_string();
+
+    let stats = store.analytics_path_stats(path, &from, &to);
+    let by_day: HashMap<&str, i64> = stats
+        .daily
+        .iter()
+        .map(|d| (d.date.as_str(), d.count))
+        .collect();
+    let daily: Vec<Value> = (0..CONTENT_PANEL_DAYS)
+        .map(|i| {
+            let date = (start + chrono::Duration::days(i))
+                .format("%Y-%m-%d")
+                .to_string();
+            let count = by_day.get(date.as_str()).copied().unwrap_or(0);
+            serde_json::json!({ "date": date, "count": count })
+        })
+        .collect();
+    let max_daily = stats.daily.iter().map(|d| d.count).max().unwrap_or(0);
+    let engagement = store.engagement_path_stats(path, &from, &to);
+
+    serde_json::json!({
+        "path": path,
+        "days": CONTENT_PANEL_DAYS,
+        "views": stats.views,
+        "unique_visitors": stats.unique_visitors,
+        "daily": daily,
+        "max_daily": max_daily,
+        "engagement": {
+            "pings": engagement.pings,
+            "avg_scroll": engagement.avg_scroll.round() as i64,
+            "avg_seconds": engagement.avg_seconds.round() as i64,
+            "read_through": engagement.read_through.round() as i64,
+        },
+        "referrers": store.analytics_path_breakdown(path, "referrer", &from, &to, 5),
+        "countries": store.analytics_path_breakdown(path, "country", &from, &to, 5),
+    })
+}
+
+/// Posts listed in each part of the author dashboard widget
+const CONTRIBUTION_LIMIT: i64 = 10;
+
+/// The dashboard's contributions widget for `user_id`: their drafts,
+/// scheduled posts, the latest comments on their posts, and views of their
+/// recent posts over the last `CONTENT_PANEL_DAYS`. Built from per-path
+/// stats so authors see their own numbers without site-wide analytics.
+pub fn author_contributions(store: &dyn Store, user_id: i64) -> Value {
+    let now = chrono::Utc::now().naive_utc();
+    let start = now - chrono::Duration::days(CONTENT_PANEL_DAYS - 1);
+    let from = format!("{} 00:00:00", start.format("%Y-%m-%d"));
+    let to = now.format("%Y-%m-%d %H:%M:%S").to_string();
+    let blog_slug = store.setting_get_or("blog_slug", "journal");
+
+    let mut posts: Vec<Value> = store
+        .post_list_by_owner(user_id, Some("published"), CONTRIBUTION_LIMIT, 0)
+        .iter()
+        .map(|p| {
+            let path = crate::render::slug_url(&blog_slug, &p.slug);
+            let stats = store.analytics_path_stats(&path, &from, &to);
+            serde_json::json!({
+                "id": p.id,
+                "title": p.title,
+                "path": path,
+                "views": stats.views,
+                "unique_visitors": stats.unique_visitors,
+            })
+        })
+        .collect();
+    posts.sort_by_key(|p| std::cmp::Reverse(p["views"].as_i64().unwrap_or(0)));
+
+    let summary = |status: &str| -> Vec<Value> {
+        store
+            .post_list_by_owner(user_id, Some(status), CONTRIBUTION_LIMIT, 0)
+            .iter()
+            .map(|p| {
+                serde_json::json!({
+                    "id": p.id,
+                    "title": p.title,
+                    "updated_at": p.updated_at,
+                    "published_at": p.published_at,
+                })
+            })
+            .collect()
+    };
+    // Listed newest first; the next one to go out reads best at the top
+    let mut scheduled = summary("scheduled");
+    scheduled.reverse();
+
+    let comments: Vec<Value> = store
+        .comment_list_on_author_posts(user_id, 5)
+        .iter()
+        .map(|c| {
+            serde_json::json!({
+                "id": c.id,
+                "post_id": c.post_id,
+                "post_title": store.post_find_by_id(c.post_id).map(|p| p.title),
+                "author_name": c.author_name,
+                "excerpt": c.body.chars().take(120).collect::<String>(),
+                "status": c.status,
+                "created_at": c.created_at,
+            })
+        })
+        .collect();
+
+    serde_json::json!({
+        "days": CONTENT_PANEL_DAYS,
+        "drafts_count": store.post_count_by_owner(user_id, Some("draft")),
+        "drafts": summary("draft"),
+        "scheduled": scheduled,
+        "comments": comments,
+        "posts": posts,
+    })
+}
+
+/// Datasets offered by the analytics export, by name.
+pub const EXPORT_DATASETS: &[&str] = &["overview", "geo", "referrers", "calendar"];
+
+/// Most referrers a single export will contain
+const EXPORT_LIMIT: i64 = 10_000;
+
+/// One analytics dataset as a header row and data rows. None for an
+/// unknown dataset.
+pub fn export_table(
+    store: &dyn Store,
+    dataset: &str,
+    from: &str,
+    to: &str,
+) -> Option<(Vec<&'static str>, Vec<Vec<String>>)> {
+    let counts = |entries: Vec<CountEntry>| -> Vec<Vec<String>> {
+        entries
+            .into_iter()
+            .map(|e| vec![e.label, e.count.to_string()])
+            .collect()
+    };
+    match dataset {
+        "overview" => {
+            let o = store.analytics_overview(from, to);
+            let rows = [
+                ("total_views", o.total_views),
+                ("unique_visitors", o.unique_visitors),
+                ("posts_count", o.posts_count),
+                ("portfolio_count", o.portfolio_count),
+                ("comments_pending", o.comments_pending),
+                ("total_likes", o.total_likes),
+            ]
+            .into_iter()
+            .map(|(k, v)| vec![k.to_string(), v.to_string()])
+            .collect();
+            Some((vec!["metric", "value"], rows))
+        }
+        "geo" => Some((
+            vec!["country", "views"],
+            counts(store.analytics_geo_data(from, to)),
+        )),
+        "referrers" => Some((
+            vec!["referrer", "views"],
+            counts(store.analytics_top_referrers(from, to, EXPORT_LIMIT)),
+        )),
+        "calendar" => {
+            let rows = store
+                .analytics_calendar_data(from, to)
+                .into_iter()
+                .map(|d| vec![d.date, d.count.to_string()])
+                .collect();
+            Some((vec!["date", "views"], rows))
+        }
+        _ => None,
+    }
+}
+
+/// An analytics dataset as CSV, with a header row.
+pub fn export_csv(store: &dyn Store, dataset: &str, from: &str, to: &str) -> Option<String> {
+    use crate::security::siem::csv_field;
+    let (header, rows) = export_table(store, dataset, from, to)?;
+    let mut out = header.join(",");
+    out.push('\n');
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|c| csv_field(c)).collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// An analytics dataset as JSON: the overview as one object, the others
+/// as arrays of row objects keyed by column.
+pub fn export_json(store: &dyn Store, dataset: &str, from: &str, to: &str) -> Option<Value> {
+    let (header, rows) = export_table(store, dataset, from, to)?;
+    if dataset == "overview" {
+        let map = rows
+            .into_iter()
+            .map(|r| {
+                let n = r[1].parse::<i64>().unwrap_or(0);
+                (r[0].clone(), Value::from(n))
+            })
+            .collect::<serde_json::Map<_, _>>();
+        return Some(Value::Object(map));
+    }
+    let items = rows
+        .into_iter()
+        .map(|r| {
+            let obj = header
+                .iter()
+                .zip(r)
+                .map(|(k, v)| {
+                    // Counts stay numeric; labels and dates stay strings
+                    let v = match (*k, v.parse::<i64>()) {
+                        ("views", Ok(n)) => Value::from(n),
+                        _ => Value::from(v),
+                    };
+                    (k.to_string(), v)
+                })
+                .collect::<serde_json::Map<_, _>>();
+            Value::Object(obj)
+        })
+        .collect();
+    Some(Value::Array(items))
+}
+
+pub(crate) fn hash_ip(ip: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(ip.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// The hash that identifies a visitor in analytics. In privacy mode the IP
+/// is mixed with a salt that changes every day, so a visitor can't be
+/// followed from one day to the next or traced back to their IP.
+pub(crate) fn visitor_hash(store: &dyn Store, ip: &str) -> String {
+    if !store.setting_get_bool("analytics_privacy_mode") {
+        return hash_ip(ip);
+    }
+    let salt = daily_salt(store, chrono::Utc::now().date_naive());
     let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(b":");
     hasher.update(ip.as_bytes());
     hex::encode(hasher.finalize())
 }
 
+/// The salt for `day`, kept in `analytics_daily_salt` as "YYYY-MM-DD:hex".
+/// The first visit of a new day replaces the old salt with a fresh one.
+fn daily_salt(store: &dyn Store, day: chrono::NaiveDate) -> String {
+    use rand::Rng;
+    let day = day.format("%Y-%m-%d").to_string();
+    let stored = store.setting_get_or("analytics_daily_salt", "");
+    if let Some((d, salt)) = stored.split_once(':') {
+        if d == day && !salt.is_empty() {
+            return salt.to_string();
+        }
+    }
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    let salt = hex::encode(bytes);
+    let _ = store.setting_set("analytics_daily_salt", &format!("{}:{}", day, salt));
+    salt
+}
+
+/// Whether a request carries a Do-Not-Track or Global Privacy Control signal.
+pub fn opted_out(headers: &HeaderMap<'_>) -> bool {
+    headers.get_one("DNT").map(str::trim) == Some("1")
+        || headers.get_one("Sec-GPC").map(str::trim) == Some("1")
+}
+
+/// Request guard: true when the visitor opted out with DNT or GPC and
+/// `analytics_respect_dnt` is on, so nothing about them should be recorded.
+pub struct DoNotTrack(pub bool);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for DoNotTrack {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let respect = request
+            .rocket()
+            .state::<std::sync::Arc<dyn Store>>()
+            .is_some_and(|s| s.setting_get_bool("analytics_respect_dnt"));
+        Outcome::Success(DoNotTrack(respect && opted_out(request.headers())))
+    }
+}
+
 fn extract_domain(url: &str) -> String {
     url::Url::parse(url)
         .ok()
@@ -79,7 +752,7 @@ fn extract_domain(url: &str) -> String {
         .unwrap_or_else(|| url.to_string())
 }
 
-fn parse_user_agent(ua: &str) -> (&str, &str) {
+pub fn parse_user_agent(ua: &str) -> (&str, &str) {
     let device = if ua.contains("Mobile") || ua.contains("Android") {
         "mobile"
     } else if ua.contains("Tablet") || ua.contains("iPad") {
@@ -0,0 +1,376 @@
+pub mod s3;
+pub mod sftp;
+
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::store::Store;
+
+/// Off-site destinations as (key, label). S3 and B2 share the S3 client,
+/// B2 through its S3-compatible endpoint.
+pub const DESTINATIONS: &[(&str, &str)] = &[
+    ("s3", "Amazon S3"),
+    ("b2", "Backblaze B2"),
+    ("sftp", "SFTP"),
+];
+
+/// Uploaded archives are named `velocty_backup_<timestamp>.zip.enc`
+const NAME_PREFIX: &str = "velocty_backup_";
+const NAME_SUFFIX: &str = ".zip.enc";
+
+/// Start of every encrypted archive, so a restore can tell it's ours
+const MAGIC: &[u8] = b"VLCTBAK1";
+const SALT_LEN: usize = 16;
+const IV_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const PBKDF2_ROUNDS: usize = 100_000;
+/// Read size while compressing and encrypting, so neither step holds the
+/// archive in memory
+const CHUNK: usize = 64 * 1024;
+
+/// Where archives are stored remotely, by file name.
+pub trait Remote {
+    /// Upload the local file at `local` as `name`
+    fn put(&self, name: &str, local: &Path) -> Result<(), String>;
+    /// File names in the destination's backup folder
+    fn list(&self) -> Result<Vec<String>, String>;
+    fn delete(&self, name: &str) -> Result<(), String>;
+}
+
+/// The last run against one destination, for the health page
+#[derive(Debug, Clone, Serialize)]
+pub struct DestinationStatus {
+    pub key: String,
+    pub label: String,
+    pub enabled: bool,
+    pub last_attempt: String,
+    pub last_success: String,
+    pub last_error: String,
+}
+
+/// Status of every destination, from the `backup_<key>_last_*` settings.
+pub fn status(store: &dyn Store) -> Vec<DestinationStatus> {
+    DESTINATIONS
+        .iter()
+        .map(|(key, label)| DestinationStatus {
+            key: key.to_string(),
+            label: label.to_string(),
+            enabled: store.setting_get_bool(&format!("backup_{}_enabled", key)),
+            last_attempt: store.setting_get_or(&format!("backup_{}_last_attempt", key), ""),
+            last_success: store.setting_get_or(&format!("backup_{}_last_success", key), ""),
+            last_error: store.setting_get_or(&format!("backup_{}_last_error", key), ""),
+        })
+        .collect()
+}
+
+/// Site export as a ZIP: `export.json` plus everything under uploads/,
+/// the same layout as the health page's full site export. Written to a
+/// scratch file so uploads are never held in memory.
+pub fn build_archive(store: &dyn Store) -> Result<TempFile, String> {
+    let json_data = store.health_export_full()?;
+    let json_bytes = serde_json::to_string_pretty(&json_data).map_err(|e| e.to_string())?;
+
+    let mut out = TempFile::new("zip")?;
+    {
+        let mut zip = ZipWriter::new(out.file());
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file("export.json", options)
+            .map_err(|e| e.to_string())?;
+        zip.write_all(json_bytes.as_bytes())
+            .map_err(|e| e.to_string())?;
+        let uploads_dir = std::path::Path::new("website/site/uploads");
+        if uploads_dir.is_dir() {
+            add_dir_to_zip(&mut zip, uploads_dir, "uploads", options).map_err(|e| e.to_string())?;
+        }
+        zip.finish().map_err(|e| e.to_string())?;
+    }
+    out.rewind()?;
+    Ok(out)
+}
+
+/// A scratch file under the system temp directory, removed on drop.
+pub struct TempFile {
+    path: PathBuf,
+    file: File,
+}
+
+impl TempFile {
+    pub fn new(ext: &str) -> Result<Self, String> {
+        use rand::Rng;
+        let path = std::env::temp_dir().join(format!(
+            "velocty_{:016x}.{}",
+            rand::thread_rng().gen::<u64>(),
+            ext
+        ));
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| format!("Could not create {}: {}", path.display(), e))?;
+        Ok(TempFile { path, file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn file(&mut self) -> &mut File {
+        &mut self.file
+    }
+
+    pub fn size(&self) -> Result<u64, String> {
+        self.file
+            .metadata()
+            .map(|m| m.len())
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn rewind(&mut self) -> Result<(), String> {
+        self.file
+            .seek(SeekFrom::Start(0))
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// The open file, rewound, for streaming out after the name is gone.
+    /// The data stays readable until the handle is closed.
+    pub fn into_file(mut self) -> Result<File, String> {
+        self.rewind()?;
+        self.file.try_clone().map_err(|e| e.to_string())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+pub fn add_dir_to_zip<W: Write + std::io::Seek>(
+    zip: &mut ZipWriter<W>,
+    dir: &std::path::Path,
+    prefix: &str,
+    options: SimpleFileOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            add_dir_to_zip(zip, &path, &name, options)?;
+        } else if path.is_file() {
+            zip.start_file(&name, options)?;
+            std::io::copy(&mut File::open(&path)?, zip)?;
+        }
+    }
+    Ok(())
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    openssl::pkcs5::pbkdf2_hmac(
+        passphrase.as_bytes(),
+        salt,
+        PBKDF2_ROUNDS,
+        openssl::hash::MessageDigest::sha256(),
+        &mut key,
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+/// AES-256-GCM with a key derived from the passphrase (PBKDF2-SHA256).
+/// Layout: magic, salt, IV, tag, ciphertext.
+pub fn encrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let mut out = Cursor::new(Vec::new());
+    encrypt_stream(&mut Cursor::new(data), &mut out, passphrase)?;
+    Ok(out.into_inner())
+}
+
+/// `encrypt` in chunks from `input` to `out`, in the same layout. The tag
+/// is only known at the end, so its slot is written blank and filled in
+/// afterwards.
+pub fn encrypt_stream<R: Read, W: Write + Seek>(
+    input: &mut R,
+    out: &mut W,
+    passphrase: &str,
+) -> Result<(), String> {
+    use openssl::symm::{Cipher, Crypter, Mode};
+    use rand::RngCore;
+    if passphrase.is_empty() {
+        return Err("No backup passphrase set".into());
+    }
+    let mut salt = [0u8; SALT_LEN];
+    let mut iv = [0u8; IV_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut iv);
+    let key = derive_key(passphrase, &salt)?;
+    let cipher = Cipher::aes_256_gcm();
+    let fail = |e: openssl::error::ErrorStack| format!("Encryption failed: {}", e);
+    let mut crypter = Crypter::new(cipher, Mode::Encrypt, &key, Some(&iv)).map_err(fail)?;
+    crypter.aad_update(MAGIC).map_err(fail)?;
+
+    let io = |e: std::io::Error| e.to_string();
+    let start = out.stream_position().map_err(io)?;
+    out.write_all(MAGIC).map_err(io)?;
+    out.write_all(&salt).map_err(io)?;
+    out.write_all(&iv).map_err(io)?;
+    out.write_all(&[0u8; TAG_LEN]).map_err(io)?;
+
+    let mut plain = vec![0u8; CHUNK];
+    let mut sealed = vec![0u8; CHUNK + cipher.block_size()];
+    loop {
+        let n = input.read(&mut plain).map_err(io)?;
+        if n == 0 {
+            break;
+        }
+        let m = crypter.update(&plain[..n], &mut sealed).map_err(fail)?;
+        out.write_all(&sealed[..m]).map_err(io)?;
+    }
+    let m = crypter.finalize(&mut sealed).map_err(fail)?;
+    out.write_all(&sealed[..m]).map_err(io)?;
+    let mut tag = [0u8; TAG_LEN];
+    crypter.get_tag(&mut tag).map_err(fail)?;
+
+    let end = out.stream_position().map_err(io)?;
+    let tag_at = start + (MAGIC.len() + SALT_LEN + IV_LEN) as u64;
+    out.seek(SeekFrom::Start(tag_at)).map_err(io)?;
+    out.write_all(&tag).map_err(io)?;
+    out.seek(SeekFrom::Start(end)).map_err(io)?;
+    out.flush().map_err(io)
+}
+
+/// Reverse of `encrypt`. Fails on a wrong passphrase or a damaged file.
+pub fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    let header = MAGIC.len() + SALT_LEN + IV_LEN + TAG_LEN;
+    if data.len() < header || &data[..MAGIC.len()] != MAGIC {
+        return Err("Not a Velocty backup archive".into());
+    }
+    let salt = &data[MAGIC.len()..MAGIC.len() + SALT_LEN];
+    let iv = &data[MAGIC.len() + SALT_LEN..MAGIC.len() + SALT_LEN + IV_LEN];
+    let tag = &data[header - TAG_LEN..header];
+    let key = derive_key(passphrase, salt)?;
+    openssl::symm::decrypt_aead(
+        openssl::symm::Cipher::aes_256_gcm(),
+        &key,
+        Some(iv),
+        MAGIC,
+        &data[header..],
+        tag,
+    )
+    .map_err(|_| "Wrong passphrase or damaged archive".to_string())
+}
+
+/// Archives to delete so only the newest `keep` remain. Other files in the
+/// folder are left alone; `keep` of 0 keeps everything.
+pub fn expired(names: &[String], keep: usize) -> Vec<String> {
+    if keep == 0 {
+        return vec![];
+    }
+    let mut ours: Vec<&String> = names
+        .iter()
+        .filter(|n| n.starts_with(NAME_PREFIX) && n.ends_with(NAME_SUFFIX))
+        .collect();
+    // Timestamps sort lexically, newest last
+    ours.sort();
+    let excess = ours.len().saturating_sub(keep);
+    ours.into_iter().take(excess).cloned().collect()
+}
+
+fn remote_for(store: &dyn Store, key: &str) -> Result<Box<dyn Remote>, String> {
+    match key {
+        "s3" | "b2" => Ok(Box::new(s3::S3Remote::from_settings(store, key)?)),
+        "sftp" => Ok(Box::new(sftp::SftpRemote::from_settings(store)?)),
+        _ => Err(format!("Unknown backup destination: {}", key)),
+    }
+}
+
+fn push(store: &dyn Store, key: &str, name: &str, local: &Path) -> Result<usize, String> {
+    let remote = remote_for(store, key)?;
+    remote.put(name, local)?;
+    let keep = store
+        .setting_get_or(&format!("backup_{}_keep", key), "7")
+        .parse::<usize>()
+        .unwrap_or(7);
+    let mut removed = 0;
+    for old in expired(&remote.list()?, keep) {
+        remote.delete(&old)?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// Build, encrypt and upload one archive to every enabled destination,
+/// then prune each down to its retention count. Returns how many
+/// destinations succeeded; each one's outcome is recorded for the
+/// health page.
+pub fn run(store: &dyn Store) -> Result<usize, String> {
+    let enabled: Vec<&str> = DESTINATIONS
+        .iter()
+        .map(|(key, _)| *key)
+        .filter(|key| store.setting_get_bool(&format!("backup_{}_enabled", key)))
+        .collect();
+    if enabled.is_empty() {
+        return Ok(0);
+    }
+    let now = chrono::Utc::now();
+    let stamp = now.format("%Y-%m-%d %H:%M:%S UTC").to_string();
+    let record_failure = |key: &str, e: &str| {
+        let _ = store.setting_set(&format!("backup_{}_last_attempt", key), &stamp);
+        let _ = store.setting_set(&format!("backup_{}_last_error", key), e);
+    };
+
+    // Checked up front so a missing passphrase doesn't cost a full export
+    let passphrase = store.setting_get_or("backup_passphrase", "");
+    let encrypted = if passphrase.is_empty() {
+        Err("No backup passphrase set".to_string())
+    } else {
+        build_archive(store).and_then(|mut zip| {
+            let mut out = TempFile::new("enc")?;
+            encrypt_stream(zip.file(), out.file(), &passphrase)?;
+            Ok(out)
+        })
+    };
+    let encrypted = match encrypted {
+        Ok(data) => data,
+        Err(e) => {
+            for key in &enabled {
+                record_failure(key, &e);
+            }
+            return Err(e);
+        }
+    };
+
+    let name = format!(
+        "{}{}{}",
+        NAME_PREFIX,
+        now.format("%Y%m%d_%H%M%S"),
+        NAME_SUFFIX
+    );
+    let mut succeeded = 0;
+    for key in enabled {
+        match push(store, key, &name, encrypted.path()) {
+            Ok(removed) => {
+                let _ = store.setting_set(&format!("backup_{}_last_attempt", key), &stamp);
+                let _ = store.setting_set(&format!("backup_{}_last_success", key), &stamp);
+                let _ = store.setting_set(&format!("backup_{}_last_error", key), "");
+                if removed > 0 {
+                    log::info!("[backup] Removed {} old archives from {}", removed, key);
+                }
+                succeeded += 1;
+            }
+            Err(e) => {
+                log::error!("[backup] Upload to {} failed: {}", key, e);
+                record_failure(key, &e);
+            }
+        }
+    }
+    Ok(succeeded)
+}
@@ -0,0 +1,223 @@
+use std::fs::File;
+use std::path::Path;
+
+use crate::email::ses::{aws_urlencode, hex_sha256, hmac_sha256};
+use crate::store::Store;
+
+use super::Remote;
+
+/// An S3-compatible bucket (Amazon S3, or Backblaze B2's S3 endpoint),
+/// addressed path-style and signed with AWS Signature Version 4.
+pub struct S3Remote {
+    endpoint: String,
+    host: String,
+    region: String,
+    bucket: String,
+    prefix: String,
+    access_key: String,
+    secret_key: String,
+}
+
+impl S3Remote {
+    /// From the `backup_<key>_*` settings, `key` being "s3" or "b2".
+    pub fn from_settings(store: &dyn Store, key: &str) -> Result<Self, String> {
+        let get = |field: &str| {
+            store
+                .setting_get_or(&format!("backup_{}_{}", key, field), "")
+                .trim()
+                .to_string()
+        };
+        let region = match get("region") {
+            r if r.is_empty() => "us-east-1".to_string(),
+            r => r,
+        };
+        let endpoint = match get("endpoint") {
+            e if e.is_empty() && key == "s3" => format!("https://s3.{}.amazonaws.com", region),
+            e if e.is_empty() => return Err("No endpoint configured".into()),
+            e if e.starts_with("http://") || e.starts_with("https://") => e,
+            e => format!("https://{}", e),
+        };
+        let endpoint = endpoint.trim_end_matches('/').to_string();
+        let host = endpoint
+            .split_once("://")
+            .map(|(_, h)| h)
+            .unwrap_or(&endpoint)
+            .to_string();
+        let remote = S3Remote {
+            endpoint,
+            host,
+            region,
+            bucket: get("bucket"),
+            prefix: get("prefix").trim_matches('/').to_string(),
+            access_key: get("access_key"),
+            secret_key: get("secret_key"),
+        };
+        if remote.bucket.is_empty() || remote.access_key.is_empty() || remote.secret_key.is_empty()
+        {
+            return Err("Bucket, access key and secret key are required".into());
+        }
+        Ok(remote)
+    }
+
+    fn object_key(&self, name: &str) -> String {
+        if self.prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", self.prefix, name)
+        }
+    }
+
+    /// Send a signed request. `key` is the object key ("" for the bucket),
+    /// `query` the already sorted (name, value) pairs and `upload` a local
+    /// file streamed as the body.
+    fn request(
+        &self,
+        method: reqwest::Method,
+        key: &str,
+        query: &[(&str, &str)],
+        upload: Option<&Path>,
+    ) -> Result<String, String> {
+        let mut path = format!("/{}", aws_urlencode(&self.bucket));
+        if !key.is_empty() {
+            for segment in key.split('/') {
+                path.push('/');
+                path.push_str(&aws_urlencode(segment));
+            }
+        }
+        let query_string = query
+            .iter()
+            .map(|(k, v)| format!("{}={}", aws_urlencode(k), aws_urlencode(v)))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let date_stamp = &timestamp[..8];
+        let (body, payload_hash) = match upload {
+            Some(local) => {
+                let hash = file_sha256(local)?;
+                let file = File::open(local).map_err(|e| e.to_string())?;
+                let len = file.metadata().map_err(|e| e.to_string())?.len();
+                (reqwest::blocking::Body::sized(file, len), hash)
+            }
+            None => (reqwest::blocking::Body::from(Vec::new()), hex_sha256(b"")),
+        };
+
+        let canonical_headers = format!(
+            "host:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n",
+            self.host, payload_hash, timestamp
+        );
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            method.as_str(),
+            path,
+            query_string,
+            canonical_headers,
+            signed_headers,
+            payload_hash
+        );
+        let credential_scope = format!("{}/{}/s3/aws4_request", date_stamp, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            timestamp,
+            credential_scope,
+            hex_sha256(canonical_request.as_bytes())
+        );
+        let k_date = hmac_sha256(
+            format!("AWS4{}", self.secret_key).as_bytes(),
+            date_stamp.as_bytes(),
+        );
+        let k_region = hmac_sha256(&k_date, self.region.as_bytes());
+        let k_service = hmac_sha256(&k_region, b"s3");
+        let k_signing = hmac_sha256(&k_service, b"aws4_request");
+        let signature = hex::encode(hmac_sha256(&k_signing, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key, credential_scope, signed_headers, signature
+        );
+
+        let url = if query_string.is_empty() {
+            format!("{}{}", self.endpoint, path)
+        } else {
+            format!("{}{}?{}", self.endpoint, path, query_string)
+        };
+        let client = reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_secs(600))
+            .build()
+            .map_err(|e| format!("HTTP client error: {}", e))?;
+        let resp = client
+            .request(method, &url)
+            .header("Authorization", &authorization)
+            .header("x-amz-date", &timestamp)
+            .header("x-amz-content-sha256", &payload_hash)
+            .body(body)
+            .send()
+            .map_err(|e| format!("Request failed: {}", e))?;
+        let status = resp.status();
+        let text = resp.text().unwrap_or_default();
+        if !status.is_success() {
+            return Err(format!("{} returned {}: {}", self.host, status, text));
+        }
+        Ok(text)
+    }
+}
+
+/// SigV4 signs the payload hash, so the file is read once to hash it and
+/// again as the body
+fn file_sha256(path: &Path) -> Result<String, String> {
+    use sha2::{Digest, Sha256};
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| e.to_string())?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+impl Remote for S3Remote {
+    fn put(&self, name: &str, local: &Path) -> Result<(), String> {
+        self.request(
+            reqwest::Method::PUT,
+            &self.object_key(name),
+            &[],
+            Some(local),
+        )
+        .map(|_| ())
+    }
+
+    fn list(&self) -> Result<Vec<String>, String> {
+        let key_re = regex::Regex::new(r"<Key>([^<]*)</Key>").unwrap();
+        let token_re =
+            regex::Regex::new(r"<NextContinuationToken>([^<]*)</NextContinuationToken>").unwrap();
+        let prefix = self.object_key("");
+        let mut names = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query: Vec<(&str, &str)> = Vec::new();
+            if let Some(t) = token.as_deref() {
+                query.push(("continuation-token", t));
+            }
+            query.push(("list-type", "2"));
+            query.push(("prefix", &prefix));
+            let xml = self.request(reqwest::Method::GET, "", &query, None)?;
+            for cap in key_re.captures_iter(&xml) {
+                let key = cap[1].replace("&amp;", "&");
+                if let Some(name) = key.strip_prefix(&prefix) {
+                    if !name.is_empty() && !name.contains('/') {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+            match token_re.captures(&xml) {
+                Some(cap) if xml.contains("<IsTruncated>true</IsTruncated>") => {
+                    token = Some(cap[1].replace("&amp;", "&"));
+                }
+                _ => break,
+            }
+        }
+        Ok(names)
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        self.request(reqwest::Method::DELETE, &self.object_key(name), &[], None)
+            .map(|_| ())
+    }
+}
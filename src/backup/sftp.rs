@@ -0,0 +1,176 @@
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::store::Store;
+
+use super::Remote;
+
+/// An SFTP folder, reached through the system `sftp` client in batch mode.
+/// Authentication is by key only; the host key is pinned on first use.
+pub struct SftpRemote {
+    host: String,
+    port: String,
+    user: String,
+    path: String,
+    key_path: String,
+}
+
+/// Seconds ssh waits for the TCP connection and handshake
+const CONNECT_TIMEOUT_SECS: u64 = 15;
+/// Longest a listing or delete may run before sftp is killed
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(120);
+/// Longest an upload may run before sftp is killed. A stalled connection
+/// is caught sooner by the keepalives.
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(4 * 60 * 60);
+
+/// Quote an argument for an sftp batch line. Quotes and newlines are
+/// refused rather than escaped.
+fn quote(arg: &str) -> Result<String, String> {
+    if arg.contains('"') || arg.contains('\n') || arg.contains('\r') {
+        return Err(format!("Unsupported character in path: {}", arg));
+    }
+    Ok(format!("\"{}\"", arg))
+}
+
+impl SftpRemote {
+    pub fn from_settings(store: &dyn Store) -> Result<Self, String> {
+        let get = |field: &str| {
+            store
+                .setting_get_or(&format!("backup_sftp_{}", field), "")
+                .trim()
+                .to_string()
+        };
+        let remote = SftpRemote {
+            host: get("host"),
+            port: match get("port") {
+                p if p.is_empty() => "22".to_string(),
+                p => p,
+            },
+            user: get("user"),
+            path: match get("path") {
+                p if p.is_empty() => ".".to_string(),
+                p => p.trim_end_matches('/').to_string(),
+            },
+            key_path: get("key_path"),
+        };
+        if remote.host.is_empty() || remote.user.is_empty() {
+            return Err("Host and user are required".into());
+        }
+        if remote.port.parse::<u16>().is_err() {
+            return Err(format!("Invalid port: {}", remote.port));
+        }
+        if remote.host.starts_with('-') || remote.user.starts_with('-') {
+            return Err("Invalid host or user".into());
+        }
+        Ok(remote)
+    }
+
+    fn remote_file(&self, name: &str) -> Result<String, String> {
+        quote(&format!("{}/{}", self.path, name))
+    }
+
+    /// Run batch commands, returning stdout. sftp is killed if it hasn't
+    /// finished within `timeout`.
+    fn batch(&self, commands: &str, timeout: Duration) -> Result<String, String> {
+        let connect_timeout = format!("ConnectTimeout={}", CONNECT_TIMEOUT_SECS);
+        let mut cmd = Command::new("sftp");
+        cmd.args([
+            "-b",
+            "-",
+            "-o",
+            "BatchMode=yes",
+            "-o",
+            "StrictHostKeyChecking=accept-new",
+            "-o",
+            &connect_timeout,
+            "-o",
+            "ServerAliveInterval=15",
+            "-o",
+            "ServerAliveCountMax=4",
+            "-P",
+            &self.port,
+        ]);
+        if !self.key_path.is_empty() {
+            cmd.args(["-i", &self.key_path]);
+        }
+        cmd.arg(format!("{}@{}", self.user, self.host))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("Could not start sftp: {}", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Err(e) = stdin.write_all(commands.as_bytes()) {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(e.to_string());
+            }
+        }
+        // Drained on their own threads so a full pipe can't stall the wait
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+
+        let deadline = Instant::now() + timeout;
+        let status = loop {
+            match child.try_wait().map_err(|e| e.to_string())? {
+                Some(status) => break status,
+                None if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!("sftp timed out after {}s", timeout.as_secs()));
+                }
+                None => std::thread::sleep(Duration::from_millis(200)),
+            }
+        };
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        if !status.success() {
+            return Err(format!("sftp failed: {}", stderr.trim()));
+        }
+        Ok(stdout)
+    }
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut out = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut out);
+        }
+        String::from_utf8_lossy(&out).to_string()
+    })
+}
+
+impl Remote for SftpRemote {
+    fn put(&self, name: &str, local: &Path) -> Result<(), String> {
+        let commands = format!(
+            "put {} {}\n",
+            quote(&local.to_string_lossy())?,
+            self.remote_file(name)?
+        );
+        self.batch(&commands, UPLOAD_TIMEOUT).map(|_| ())
+    }
+
+    fn list(&self) -> Result<Vec<String>, String> {
+        let out = self.batch(&format!("ls -1 {}\n", quote(&self.path)?), COMMAND_TIMEOUT)?;
+        Ok(out
+            .lines()
+            // The echoed batch command is prefixed with "sftp>"
+            .filter(|l| !l.starts_with("sftp>"))
+            .filter_map(|l| l.trim().rsplit('/').next())
+            .filter(|n| !n.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn delete(&self, name: &str) -> Result<(), String> {
+        self.batch(
+            &format!("rm {}\n", self.remote_file(name)?),
+            COMMAND_TIMEOUT,
+        )
+        .map(|_| ())
+    }
+}
@@ -1,7 +1,10 @@
 use log::{error, info, warn};
+use serde::Serialize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
 
 /// Required directories that will be created if missing
 const REQUIRED_DIRS: &[&str] = &[
@@ -17,6 +20,9 @@ const REQUIRED_DIRS: &[&str] = &[
     "website/templates/admin",
 ];
 
+/// Rocket's `template_dir`
+const TEMPLATE_DIR: &str = "website/templates";
+
 /// Critical template files — server cannot function without these
 const CRITICAL_TEMPLATES: &[&str] = &[
     "website/templates/admin/base.html.tera",
@@ -151,6 +157,16 @@ pub fn run() {
         warnings += 1;
     }
 
+    // ── 8. Templates compile ────────────────────────────
+    // Rocket would only report these once the first page using them fails
+    match compile_templates(TEMPLATE_DIR) {
+        Ok(count) => info!("  Compiled {} templates", count),
+        Err(e) => {
+            error!("  Template error: {}", e);
+            errors += 1;
+        }
+    }
+
     // ── Summary ─────────────────────────────────────────
     if errors > 0 {
         error!(
@@ -241,3 +257,97 @@ fn migrate_to_site_layout() {
 
     info!("Migration complete. Data is now in website/site/");
 }
+
+/// Parse every `.tera` template under `dir` under the name Rocket gives it
+/// ("admin/base.html.tera" is "admin/base") and resolve the `extends`
+/// chains between them. Returns how many there are, or the first error
+/// with the template it's in and the line and column.
+pub fn compile_templates(dir: &str) -> Result<usize, String> {
+    let root = Path::new(dir);
+    let mut files = Vec::new();
+    collect_templates(root, &mut files);
+    let mut sources = Vec::with_capacity(files.len());
+    for path in files {
+        let rel = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let source = fs::read_to_string(&path).map_err(|e| format!("{}: {}", rel, e))?;
+        sources.push((template_name(&rel), source));
+    }
+    let mut tera = rocket_dyn_templates::tera::Tera::default();
+    tera.add_raw_templates(sources.iter().map(|(n, s)| (n.as_str(), s.as_str())))
+        .map_err(|e| error_chain(&e))?;
+    Ok(sources.len())
+}
+
+fn collect_templates(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.is_dir() {
+            collect_templates(&path, out);
+        } else if path.extension().is_some_and(|ext| ext == "tera") {
+            out.push(path);
+        }
+    }
+}
+
+/// "admin/base.html.tera" → "admin/base"
+fn template_name(rel: &str) -> String {
+    let name = rel.strip_suffix(".tera").unwrap_or(rel);
+    let file_start = name.rfind('/').map(|i| i + 1).unwrap_or(0);
+    match name[file_start..].rfind('.') {
+        Some(dot) if dot > 0 => name[..file_start + dot].to_string(),
+        _ => name.to_string(),
+    }
+}
+
+/// An error and everything it wraps, e.g. Tera's "Failed to parse" with the
+/// parser's position underneath
+fn error_chain(e: &dyn std::error::Error) -> String {
+    let mut msg = e.to_string();
+    let mut source = e.source();
+    while let Some(s) = source {
+        msg.push_str("\n");
+        msg.push_str(&s.to_string());
+        source = s.source();
+    }
+    msg
+}
+
+// ── Startup profiling ───────────────────────────────────
+
+/// How long one startup phase took
+#[derive(Debug, Clone, Serialize)]
+pub struct BootPhase {
+    pub name: String,
+    pub millis: u64,
+}
+
+fn phase_log() -> &'static Mutex<Vec<BootPhase>> {
+    static PHASES: OnceLock<Mutex<Vec<BootPhase>>> = OnceLock::new();
+    PHASES.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Run one startup phase, logging and recording how long it took
+pub fn timed<T>(name: &str, phase: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let out = phase();
+    let millis = start.elapsed().as_millis() as u64;
+    info!("[boot] {} took {} ms", name, millis);
+    if let Ok(mut phases) = phase_log().lock() {
+        phases.push(BootPhase {
+            name: name.to_string(),
+            millis,
+        });
+    }
+    out
+}
+
+/// Startup phases in the order they ran, for the Health page
+pub fn phases() -> Vec<BootPhase> {
+    phase_log().lock().map(|p| p.clone()).unwrap_or_default()
+}
@@ -1,8 +1,12 @@
 use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::params;
+use std::collections::HashMap;
 
-use crate::render::{INKWELL_DESIGN_CSS, INKWELL_SHELL_HTML, ONEGUY_DESIGN_CSS, ONEGUY_SHELL_HTML};
+use crate::render::{
+    INKWELL_DESIGN_CSS, INKWELL_SHELL_HTML, MASTHEAD_DESIGN_CSS, MASTHEAD_SHELL_HTML,
+    ONEGUY_DESIGN_CSS, ONEGUY_SHELL_HTML,
+};
 
 pub type DbPool = Pool<SqliteConnectionManager>;
 
@@ -167,12 +171,30 @@ pub fn run_migrations(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
             description TEXT NOT NULL DEFAULT '',
             layout_html TEXT NOT NULL DEFAULT '',
             style_css TEXT NOT NULL DEFAULT '',
+            custom_css TEXT NOT NULL DEFAULT '',
+            head_scripts TEXT NOT NULL DEFAULT '',
+            footer_scripts TEXT NOT NULL DEFAULT '',
+            parent_id INTEGER DEFAULT NULL,
             thumbnail_path TEXT,
             is_active INTEGER DEFAULT 0,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
             updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
         );
 
+        -- Widgets shown in a design's sidebar and footer areas
+        CREATE TABLE IF NOT EXISTS widgets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            design_id INTEGER NOT NULL,
+            area TEXT NOT NULL,
+            widget_type TEXT NOT NULL,
+            title TEXT NOT NULL DEFAULT '',
+            config TEXT NOT NULL DEFAULT '{}',
+            sort_order INTEGER NOT NULL DEFAULT 0,
+            created_at DATETIME NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (design_id) REFERENCES designs(id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_widgets_design ON widgets(design_id);
+
         -- Design templates (one per page type per design)
         CREATE TABLE IF NOT EXISTS design_templates (
             id INTEGER PRIMARY KEY,
@@ -185,6 +207,22 @@ pub fn run_migrations(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
             UNIQUE(design_id, template_type)
         );
 
+        -- Design template history (every saved state, newest kept)
+        CREATE TABLE IF NOT EXISTS design_template_versions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            design_id INTEGER NOT NULL,
+            template_type TEXT NOT NULL,
+            layout_html TEXT NOT NULL DEFAULT '',
+            style_css TEXT NOT NULL DEFAULT '',
+            grapesjs_data TEXT NOT NULL DEFAULT '',
+            user_id INTEGER,
+            user_name TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (design_id) REFERENCES designs(id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_design_template_versions
+            ON design_template_versions(design_id, template_type);
+
         -- Settings (key-value)
         CREATE TABLE IF NOT EXISTS settings (
             key TEXT PRIMARY KEY,
@@ -201,7 +239,9 @@ pub fn run_migrations(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
             comments_count INTEGER DEFAULT 0,
             skipped_count INTEGER DEFAULT 0,
             log TEXT,
-            imported_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            imported_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            created TEXT,
+            rolled_back_at DATETIME
         );
 
         -- Admin sessions
@@ -224,6 +264,11 @@ pub fn run_migrations(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
             user_agent TEXT,
             device_type TEXT,
             browser TEXT,
+            utm_source TEXT,
+            utm_medium TEXT,
+            utm_campaign TEXT,
+            utm_term TEXT,
+            utm_content TEXT,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP
         );
 
@@ -232,6 +277,59 @@ pub fn run_migrations(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
         CREATE INDEX IF NOT EXISTS idx_views_country ON page_views(country);
         CREATE INDEX IF NOT EXISTS idx_views_referrer ON page_views(referrer);
 
+        -- Conversion goal completions, at most one per goal, visitor and day
+        CREATE TABLE IF NOT EXISTS goal_completions (
+            id INTEGER PRIMARY KEY,
+            goal TEXT NOT NULL,
+            ip_hash TEXT NOT NULL,
+            path TEXT NOT NULL DEFAULT '',
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE INDEX IF NOT EXISTS idx_goal_completions ON goal_completions(goal, created_at);
+
+        -- Clicks on external links routed through /out
+        CREATE TABLE IF NOT EXISTS outbound_clicks (
+            id INTEGER PRIMARY KEY,
+            url TEXT NOT NULL,
+            ip_hash TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE INDEX IF NOT EXISTS idx_outbound_clicks_date ON outbound_clicks(created_at);
+
+        -- Public site searches, with the result clicked from each (if any)
+        CREATE TABLE IF NOT EXISTS search_log (
+            id INTEGER PRIMARY KEY,
+            query TEXT NOT NULL,
+            results INTEGER NOT NULL DEFAULT 0,
+            ip_hash TEXT NOT NULL,
+            clicked_path TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE INDEX IF NOT EXISTS idx_search_log_date ON search_log(created_at);
+
+        -- A/B test impressions and clicks, one of each per visitor and variant
+        CREATE TABLE IF NOT EXISTS experiment_events (
+            id INTEGER PRIMARY KEY,
+            experiment TEXT NOT NULL,
+            variant TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            ip_hash TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE(experiment, variant, kind, ip_hash)
+        );
+        CREATE INDEX IF NOT EXISTS idx_experiment_events_date ON experiment_events(created_at);
+
+        -- Scroll depth and time on page reported by the engagement beacon
+        CREATE TABLE IF NOT EXISTS engagement_pings (
+            id INTEGER PRIMARY KEY,
+            path TEXT NOT NULL,
+            ip_hash TEXT NOT NULL,
+            scroll INTEGER NOT NULL DEFAULT 0,
+            seconds INTEGER NOT NULL DEFAULT 0,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE INDEX IF NOT EXISTS idx_engagement_pings_path ON engagement_pings(path, created_at);
+
         -- Magic link tokens
         CREATE TABLE IF NOT EXISTS magic_links (
             id INTEGER PRIMARY KEY,
@@ -301,6 +399,57 @@ pub fn run_migrations(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
         CREATE INDEX IF NOT EXISTS idx_fw_events_type ON fw_events(event_type);
         CREATE INDEX IF NOT EXISTS idx_fw_events_created ON fw_events(created_at);
 
+        -- Firewall: per-IP offense counter for ban escalation
+        CREATE TABLE IF NOT EXISTS fw_offenses (
+            ip TEXT PRIMARY KEY,
+            count INTEGER NOT NULL DEFAULT 0,
+            last_offense DATETIME NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Firewall: custom rules
+        CREATE TABLE IF NOT EXISTS fw_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            match_type TEXT NOT NULL,
+            pattern TEXT NOT NULL,
+            action TEXT NOT NULL DEFAULT 'block',
+            rate_limit INTEGER NOT NULL DEFAULT 60,
+            priority INTEGER NOT NULL DEFAULT 100,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            hits INTEGER NOT NULL DEFAULT 0,
+            created_at DATETIME NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Redirects (exact paths, or prefixes ending in *)
+        CREATE TABLE IF NOT EXISTS redirects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source TEXT NOT NULL UNIQUE,
+            target TEXT NOT NULL,
+            status_code INTEGER NOT NULL DEFAULT 301,
+            hits INTEGER NOT NULL DEFAULT 0,
+            created_at DATETIME NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- 404 log: one row per missing public path
+        CREATE TABLE IF NOT EXISTS not_found_log (
+            path TEXT PRIMARY KEY,
+            referrer TEXT,
+            hits INTEGER NOT NULL DEFAULT 1,
+            first_seen DATETIME NOT NULL DEFAULT (datetime('now')),
+            last_seen DATETIME NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Search engine pings (IndexNow, sitemap) sent on publish
+        CREATE TABLE IF NOT EXISTS ping_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            engine TEXT NOT NULL,
+            urls TEXT NOT NULL,
+            success INTEGER NOT NULL DEFAULT 0,
+            status_code INTEGER NOT NULL DEFAULT 0,
+            message TEXT NOT NULL DEFAULT '',
+            created_at DATETIME NOT NULL DEFAULT (datetime('now'))
+        );
+
         -- Audit log
         CREATE TABLE IF NOT EXISTS audit_log (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -326,15 +475,169 @@ pub fn run_migrations(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
             from_addr TEXT NOT NULL,
             subject TEXT NOT NULL,
             body_text TEXT NOT NULL,
+            reply_to TEXT NOT NULL DEFAULT '',
             attempts INTEGER NOT NULL DEFAULT 0,
             max_attempts INTEGER NOT NULL DEFAULT 5,
             next_retry_at DATETIME NOT NULL DEFAULT (datetime('now')),
             status TEXT NOT NULL DEFAULT 'pending',
             error TEXT DEFAULT '',
-            created_at DATETIME NOT NULL DEFAULT (datetime('now'))
+            created_at DATETIME NOT NULL DEFAULT (datetime('now')),
+            sent_at DATETIME DEFAULT NULL
         );
         CREATE INDEX IF NOT EXISTS idx_email_queue_status ON email_queue(status);
         CREATE INDEX IF NOT EXISTS idx_email_queue_retry ON email_queue(next_retry_at);
+
+        -- Reply tokens for inbound comment replies (plus-addressed Reply-To)
+        CREATE TABLE IF NOT EXISTS mta_reply_tokens (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            token TEXT UNIQUE NOT NULL,
+            post_id INTEGER NOT NULL,
+            content_type TEXT NOT NULL DEFAULT 'post',
+            comment_id INTEGER NOT NULL,
+            email TEXT NOT NULL,
+            created_at DATETIME NOT NULL,
+            expires_at DATETIME NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_mta_reply_tokens_expires ON mta_reply_tokens(expires_at);
+
+        -- Per-provider email quota counters (hour buckets 'YYYY-MM-DDTHH', day buckets 'YYYY-MM-DD')
+        CREATE TABLE IF NOT EXISTS email_quota_usage (
+            provider TEXT NOT NULL,
+            bucket TEXT NOT NULL,
+            count INTEGER NOT NULL DEFAULT 0,
+            PRIMARY KEY (provider, bucket)
+        );
+
+        -- Single-use signed tokens (magic links, password resets, double opt-in)
+        CREATE TABLE IF NOT EXISTS signed_tokens (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            token_hash TEXT UNIQUE NOT NULL,
+            purpose TEXT NOT NULL,
+            email TEXT NOT NULL,
+            payload TEXT NOT NULL DEFAULT '',
+            created_at DATETIME NOT NULL,
+            expires_at DATETIME NOT NULL,
+            used_at DATETIME
+        );
+        CREATE INDEX IF NOT EXISTS idx_signed_tokens_expires ON signed_tokens(expires_at);
+
+        -- Newsletter subscribers (double opt-in)
+        CREATE TABLE IF NOT EXISTS newsletter_subscribers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            email TEXT UNIQUE NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            created_at DATETIME NOT NULL,
+            confirmed_at DATETIME
+        );
+
+        -- Comment reply subscriptions (double opt-in)
+        CREATE TABLE IF NOT EXISTS comment_subscriptions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            post_id INTEGER NOT NULL,
+            content_type TEXT NOT NULL DEFAULT 'post',
+            email TEXT NOT NULL,
+            confirmed INTEGER NOT NULL DEFAULT 0,
+            created_at DATETIME NOT NULL,
+            UNIQUE(post_id, content_type, email)
+        );
+
+        -- Custom roles and customised built-in roles
+        CREATE TABLE IF NOT EXISTS roles (
+            slug TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            capabilities TEXT NOT NULL DEFAULT '',
+            created_at DATETIME NOT NULL
+        );
+
+        -- Devices each user has signed in from (for new-device login alerts)
+        CREATE TABLE IF NOT EXISTS login_devices (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            ip_hash TEXT NOT NULL,
+            device TEXT NOT NULL,
+            location TEXT NOT NULL DEFAULT '',
+            first_seen DATETIME NOT NULL,
+            last_seen DATETIME NOT NULL,
+            UNIQUE(user_id, ip_hash, device)
+        );
+
+        -- Attempts counted by the persistent rate limiter (sliding window log)
+        CREATE TABLE IF NOT EXISTS rate_limit_hits (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            key TEXT NOT NULL,
+            hit_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_rate_limit_hits_key ON rate_limit_hits(key, hit_at);
+
+        -- AI rewrite suggestions from the SEO audit, pending review
+        CREATE TABLE IF NOT EXISTS seo_suggestions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            content_type TEXT NOT NULL,
+            content_id INTEGER NOT NULL,
+            field TEXT NOT NULL,
+            current TEXT NOT NULL DEFAULT '',
+            suggested TEXT NOT NULL,
+            level INTEGER NOT NULL DEFAULT 0,
+            reason TEXT NOT NULL DEFAULT '',
+            status TEXT NOT NULL DEFAULT 'pending',
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE INDEX IF NOT EXISTS idx_seo_suggestions_content ON seo_suggestions(content_type, content_id);
+
+        -- One row per AI provider call, for the usage and cost dashboard
+        CREATE TABLE IF NOT EXISTS ai_usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            provider TEXT NOT NULL,
+            model TEXT NOT NULL,
+            feature TEXT NOT NULL DEFAULT '',
+            prompt_tokens INTEGER NOT NULL DEFAULT 0,
+            completion_tokens INTEGER NOT NULL DEFAULT 0,
+            latency_ms INTEGER NOT NULL DEFAULT 0,
+            success INTEGER NOT NULL DEFAULT 1,
+            error TEXT NOT NULL DEFAULT '',
+            created_at DATETIME NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_ai_usage_created ON ai_usage(created_at);
+
+        -- AI embedding vectors for semantic search and related posts
+        CREATE TABLE IF NOT EXISTS embeddings (
+            item_type TEXT NOT NULL,
+            item_id INTEGER NOT NULL,
+            model TEXT NOT NULL,
+            content_hash TEXT NOT NULL,
+            vector BLOB NOT NULL,
+            updated_at DATETIME NOT NULL,
+            PRIMARY KEY (item_type, item_id)
+        );
+
+        -- Keys for the public API; only a hash of each key is kept
+        CREATE TABLE IF NOT EXISTS api_keys (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            prefix TEXT NOT NULL,
+            key_hash TEXT NOT NULL UNIQUE,
+            scope TEXT NOT NULL DEFAULT 'read',
+            rate_limit INTEGER NOT NULL DEFAULT 0,
+            last_used_at DATETIME,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+
+        -- Who uploaded each file, for users who only see their own uploads
+        CREATE TABLE IF NOT EXISTS media_owners (
+            path TEXT PRIMARY KEY,
+            user_id INTEGER NOT NULL,
+            created_at DATETIME NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_media_owners_user ON media_owners(user_id);
+
+        CREATE TABLE IF NOT EXISTS media_jobs (
+            path TEXT PRIMARY KEY,
+            source TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            attempts INTEGER NOT NULL DEFAULT 0,
+            error TEXT NOT NULL DEFAULT '',
+            created_at DATETIME NOT NULL
+        );
         ",
     )?;
 
@@ -377,6 +680,14 @@ pub fn run_migrations(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
         conn.execute_batch("ALTER TABLE sessions ADD COLUMN user_id INTEGER DEFAULT NULL;")?;
     }
 
+    // Add sudo_until (step-up authentication) to sessions if missing
+    let has_session_sudo: bool = conn
+        .prepare("SELECT sudo_until FROM sessions LIMIT 0")
+        .is_ok();
+    if !has_session_sudo {
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN sudo_until DATETIME DEFAULT NULL;")?;
+    }
+
     // Add user_id to posts if missing
     let has_post_user_id: bool = conn.prepare("SELECT user_id FROM posts LIMIT 0").is_ok();
     if !has_post_user_id {
@@ -411,6 +722,68 @@ pub fn run_migrations(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
         )?;
     }
 
+    // Add per-item robots, canonical, schema type and focus keyword fields to posts and portfolio if missing
+    for table in ["posts", "portfolio"] {
+        let has_robots = conn
+            .prepare(&format!("SELECT robots_noindex FROM {} LIMIT 0", table))
+            .is_ok();
+        if !has_robots {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {t} ADD COLUMN robots_noindex INTEGER NOT NULL DEFAULT 0;
+                 ALTER TABLE {t} ADD COLUMN robots_nofollow INTEGER NOT NULL DEFAULT 0;
+                 ALTER TABLE {t} ADD COLUMN canonical_url TEXT;",
+                t = table
+            ))?;
+        }
+        let has_schema_type = conn
+            .prepare(&format!("SELECT schema_type FROM {} LIMIT 0", table))
+            .is_ok();
+        if !has_schema_type {
+            conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN schema_type TEXT", table),
+                [],
+            )?;
+        }
+        let has_focus_keyword = conn
+            .prepare(&format!("SELECT focus_keyword FROM {} LIMIT 0", table))
+            .is_ok();
+        if !has_focus_keyword {
+            conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN focus_keyword TEXT", table),
+                [],
+            )?;
+        }
+        let has_template = conn
+            .prepare(&format!("SELECT template FROM {} LIMIT 0", table))
+            .is_ok();
+        if !has_template {
+            conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN template TEXT", table),
+                [],
+            )?;
+        }
+    }
+
+    // Translation metadata on posts, for hreflang alternates
+    let has_language = conn.prepare("SELECT language FROM posts LIMIT 0").is_ok();
+    if !has_language {
+        conn.execute_batch(
+            "ALTER TABLE posts ADD COLUMN language TEXT;
+             ALTER TABLE posts ADD COLUMN translation_group TEXT;
+             CREATE INDEX IF NOT EXISTS idx_posts_translation_group ON posts(translation_group);",
+        )?;
+    }
+
+    // Podcast episode audio on posts
+    let has_audio = conn.prepare("SELECT audio_file FROM posts LIMIT 0").is_ok();
+    if !has_audio {
+        conn.execute_batch(
+            "ALTER TABLE posts ADD COLUMN audio_file TEXT;
+             ALTER TABLE posts ADD COLUMN audio_duration INTEGER;
+             ALTER TABLE posts ADD COLUMN audio_bytes INTEGER;",
+        )?;
+    }
+
     // Add grapesjs_data to design_templates if missing (Phase 3: stores GrapesJS JSON for re-editing)
     let has_grapesjs_data: bool = conn
         .prepare("SELECT grapesjs_data FROM design_templates LIMIT 0")
@@ -498,6 +871,37 @@ pub fn run_migrations(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
         )?;
     }
 
+    // Add passwordless to users if missing
+    let has_passwordless: bool = conn
+        .prepare("SELECT passwordless FROM users LIMIT 0")
+        .is_ok();
+    if !has_passwordless {
+        conn.execute_batch(
+            "ALTER TABLE users ADD COLUMN passwordless INTEGER NOT NULL DEFAULT 0;",
+        )?;
+    }
+
+    // Add mfa_method to users if missing
+    let has_mfa_method: bool = conn.prepare("SELECT mfa_method FROM users LIMIT 0").is_ok();
+    if !has_mfa_method {
+        conn.execute_batch(
+            "ALTER TABLE users ADD COLUMN mfa_method TEXT NOT NULL DEFAULT 'totp';",
+        )?;
+    }
+
+    // Author page profile fields
+    let has_author_slug: bool = conn
+        .prepare("SELECT author_slug FROM users LIMIT 0")
+        .is_ok();
+    if !has_author_slug {
+        conn.execute_batch(
+            "ALTER TABLE users ADD COLUMN author_slug TEXT NOT NULL DEFAULT '';
+             ALTER TABLE users ADD COLUMN bio TEXT NOT NULL DEFAULT '';
+             ALTER TABLE users ADD COLUMN website TEXT NOT NULL DEFAULT '';
+             ALTER TABLE users ADD COLUMN social_links TEXT NOT NULL DEFAULT '';",
+        )?;
+    }
+
     // Add uuid column to orders if missing
     let has_order_uuid: bool = conn.prepare("SELECT uuid FROM orders LIMIT 0").is_ok();
     if !has_order_uuid {
@@ -507,6 +911,90 @@ pub fn run_migrations(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
         conn.execute_batch("CREATE UNIQUE INDEX IF NOT EXISTS idx_orders_uuid ON orders(uuid);")?;
     }
 
+    // Add error_log to email_queue if missing (per-attempt error history)
+    let has_queue_error_log: bool = conn
+        .prepare("SELECT error_log FROM email_queue LIMIT 0")
+        .is_ok();
+    if !has_queue_error_log {
+        conn.execute_batch(
+            "ALTER TABLE email_queue ADD COLUMN error_log TEXT NOT NULL DEFAULT '';",
+        )?;
+    }
+
+    // Add sent_at to email_queue if missing (when delivery succeeded)
+    let has_queue_sent_at: bool = conn
+        .prepare("SELECT sent_at FROM email_queue LIMIT 0")
+        .is_ok();
+    if !has_queue_sent_at {
+        conn.execute_batch("ALTER TABLE email_queue ADD COLUMN sent_at DATETIME DEFAULT NULL;")?;
+    }
+
+    // Add reply_to to email_queue if missing (per-message Reply-To)
+    let has_queue_reply_to: bool = conn
+        .prepare("SELECT reply_to FROM email_queue LIMIT 0")
+        .is_ok();
+    if !has_queue_reply_to {
+        conn.execute_batch(
+            "ALTER TABLE email_queue ADD COLUMN reply_to TEXT NOT NULL DEFAULT '';",
+        )?;
+    }
+
+    // Add UTM campaign columns to page_views if missing
+    let has_utm: bool = conn
+        .prepare("SELECT utm_source FROM page_views LIMIT 0")
+        .is_ok();
+    if !has_utm {
+        conn.execute_batch(
+            "ALTER TABLE page_views ADD COLUMN utm_source TEXT;
+             ALTER TABLE page_views ADD COLUMN utm_medium TEXT;
+             ALTER TABLE page_views ADD COLUMN utm_campaign TEXT;
+             ALTER TABLE page_views ADD COLUMN utm_term TEXT;
+             ALTER TABLE page_views ADD COLUMN utm_content TEXT;",
+        )?;
+    }
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_views_utm_campaign ON page_views(utm_campaign);",
+    )?;
+
+    // Add rollback tracking to imports if missing
+    let has_import_created: bool = conn.prepare("SELECT created FROM imports LIMIT 0").is_ok();
+    if !has_import_created {
+        conn.execute_batch(
+            "ALTER TABLE imports ADD COLUMN created TEXT;
+             ALTER TABLE imports ADD COLUMN rolled_back_at DATETIME;",
+        )?;
+    }
+
+    // Number each status change, so the automation triggers can page through
+    // posts published, orders completed and comments approved after a
+    // cursor however long after creation that happened. Existing rows take
+    // their id, which keeps cursors handed out before this working.
+    for table in ["posts", "orders", "comments"] {
+        let has_status_seq: bool = conn
+            .prepare(&format!("SELECT status_seq FROM {} LIMIT 0", table))
+            .is_ok();
+        if !has_status_seq {
+            conn.execute_batch(&format!(
+                "ALTER TABLE {table} ADD COLUMN status_seq INTEGER NOT NULL DEFAULT 0;
+                 UPDATE {table} SET status_seq = id;"
+            ))?;
+        }
+        conn.execute_batch(&format!(
+            "CREATE INDEX IF NOT EXISTS idx_{table}_status_seq ON {table}(status, status_seq);
+             CREATE TRIGGER IF NOT EXISTS {table}_status_seq_insert AFTER INSERT ON {table}
+             BEGIN
+                 UPDATE {table} SET status_seq = (SELECT MAX(status_seq) + 1 FROM {table})
+                 WHERE id = NEW.id;
+             END;
+             CREATE TRIGGER IF NOT EXISTS {table}_status_seq_update AFTER UPDATE OF status ON {table}
+             WHEN OLD.status IS NOT NEW.status
+             BEGIN
+                 UPDATE {table} SET status_seq = (SELECT MAX(status_seq) + 1 FROM {table})
+                 WHERE id = NEW.id;
+             END;"
+        ))?;
+    }
+
     // Drop the migration connection before FTS calls (avoids deadlock with max_size=1 pools)
     drop(conn);
 
@@ -528,9 +1016,20 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("site_logo", ""),
         ("site_favicon", ""),
         ("site_url", "http://localhost:8000"),
+        ("site_language", "en"),
         ("timezone", "UTC"),
         ("date_format", "%B %d, %Y"),
         ("rss_feed_count", "25"),
+        ("rss_content", "excerpt"),
+        ("rss_featured_media", "false"),
+        ("rss_item_footer", ""),
+        ("podcast_enabled", "false"),
+        ("podcast_title", ""),
+        ("podcast_author", ""),
+        ("podcast_email", ""),
+        ("podcast_category", ""),
+        ("podcast_artwork", ""),
+        ("podcast_explicit", "false"),
         ("admin_email", ""),
         ("admin_display_name", "Admin"),
         ("admin_theme", "dark"),
@@ -543,7 +1042,34 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("mfa_secret", ""),
         ("mfa_recovery_codes", "[]"),
         ("session_expiry_hours", "24"),
+        ("sudo_timeout_minutes", "10"),
+        ("secrets_scan_action", "block"),
         ("login_rate_limit", "5"),
+        ("rate_limit_backend", "memory"),
+        ("api_rate_limit_enabled", "true"),
+        ("api_rate_limit_anonymous", "60"),
+        ("api_rate_limit_read", "300"),
+        ("api_rate_limit_write", "60"),
+        ("cors_enabled", "false"),
+        ("cors_allowed_origins", ""),
+        ("cors_allowed_methods", "GET, POST, OPTIONS"),
+        ("cors_allowed_headers", "Content-Type, Authorization, X-API-Key"),
+        ("cors_allow_credentials", "false"),
+        ("cors_max_age", "600"),
+        ("login_alerts_enabled", "true"),
+        ("password_min_length", "8"),
+        ("password_require_uppercase", "false"),
+        ("password_require_lowercase", "false"),
+        ("password_require_number", "false"),
+        ("password_require_symbol", "false"),
+        ("password_deny_common", "true"),
+        ("password_breach_check", "off"),
+        ("password_breach_dir", ""),
+        ("password_hash_algorithm", "argon2id"),
+        ("password_argon2_memory_kib", "19456"),
+        ("password_argon2_iterations", "2"),
+        ("password_argon2_parallelism", "1"),
+        ("password_bcrypt_cost", "12"),
         ("login_captcha_enabled", "false"),
         ("login_captcha_provider", ""),
         // Anti-spam / Captcha services
@@ -566,6 +1092,10 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         // Visitors (Design)
         ("design_site_search", "true"),
         ("search_nav_position", "after"),
+        ("search_engine", "builtin"),
+        ("search_engine_url", ""),
+        ("search_engine_api_key", ""),
+        ("search_engine_index", "velocty"),
         ("design_powered_by", "false"),
         ("social_order", "instagram,twitter,facebook,youtube,tiktok,linkedin,pinterest,behance,dribbble,github,vimeo,500px"),
         ("share_order", "facebook,x,linkedin"),
@@ -598,6 +1128,7 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("blog_show_author", "true"),
         ("blog_show_date", "true"),
         ("blog_show_reading_time", "true"),
+        ("blog_related_posts", "false"),
         ("blog_featured_image_required", "false"),
         ("blog_written_by_label", "By"),
         // Portfolio
@@ -621,6 +1152,12 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("portfolio_lightbox_show_tags", "center"),
         ("portfolio_lightbox_nav", "true"),
         ("portfolio_lightbox_keyboard", "true"),
+        ("portfolio_lightbox_loop", "true"),
+        ("portfolio_lightbox_zoom", "true"),
+        ("portfolio_lightbox_autoplay", "false"),
+        ("portfolio_lightbox_autoplay_interval", "5"),
+        ("portfolio_lightbox_preload", "true"),
+        ("portfolio_lightbox_deep_link", "true"),
         // Comments
         ("comments_enabled", "true"),
         ("comments_on_blog", "true"),
@@ -630,6 +1167,8 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("comments_rate_limit", "5"),
         ("comments_require_name", "true"),
         ("comments_require_email", "false"),
+        ("comments_subscriptions_enabled", "false"),
+        ("membership_enabled", "false"),
         // Fonts
         ("font_primary", "Roboto"),
         ("font_heading", "Roboto"),
@@ -643,6 +1182,11 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("font_size_h6", "1rem"),
         ("font_google_enabled", "true"),
         ("font_google_custom", ""),
+        ("font_google_self_host", "false"),
+        ("font_google_subsets", "latin"),
+        ("font_google_local_css", ""),
+        ("font_google_local_key", ""),
+        ("font_google_local_error", ""),
         ("font_adobe_enabled", "false"),
         ("font_adobe_project_id", ""),
         ("font_custom_name", ""),
@@ -665,6 +1209,7 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("images_reencode", "false"),
         ("images_strip_metadata", "false"),
         ("images_allowed_types", "jpg,jpeg,png,gif,webp,svg,tiff"),
+        ("import_fetch_remote_images", "true"),
         // Video
         ("video_upload_enabled", "false"),
         ("video_max_upload_mb", "100"),
@@ -681,12 +1226,20 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("seo_twitter_cards", "true"),
         ("seo_canonical_base", ""),
         ("seo_robots_txt", "User-agent: *\nAllow: /"),
+        ("seo_robots_blocked_bots", ""),
+        ("seo_llms_txt_enabled", "false"),
+        ("seo_llms_txt", ""),
         // SEO — Webmaster verification
         ("seo_google_verification", ""),
         ("seo_bing_verification", ""),
         ("seo_yandex_verification", ""),
         ("seo_pinterest_verification", ""),
         ("seo_baidu_verification", ""),
+        // SEO — Ping search engines on publish
+        ("seo_ping_bing", "false"),
+        ("seo_ping_yandex", "false"),
+        ("seo_ping_google", "false"),
+        ("seo_indexnow_key", ""),
         // SEO — Google Analytics
         ("seo_ga_enabled", "false"),
         ("seo_ga_measurement_id", ""),
@@ -713,6 +1266,15 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("seo_umami_host", "https://analytics.umami.is"),
         // Frontend
         ("design_back_to_top", "true"),
+        // Dark palette: "off", "auto" (follows the visitor's system) or "always"
+        ("design_dark_mode", "off"),
+        ("design_dark_mode_toggle", "false"),
+        // "default" shows the blog or portfolio on "/"; "sections" builds it
+        // from homepage_sections (JSON, empty means one of each section)
+        ("homepage_mode", "default"),
+        ("homepage_sections", ""),
+        // Comma-separated slugs of the designs that show breadcrumbs
+        ("design_breadcrumbs", ""),
         ("cookie_consent_enabled", "false"),
         ("cookie_consent_style", "minimal"),
         ("cookie_consent_position", "bottom"),
@@ -811,12 +1373,15 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("commerce_currency", "USD"),
         ("downloads_max_per_purchase", "3"),
         ("downloads_expiry_hours", "48"),
+        // Years completed orders keep buyer details when a buyer is erased
+        ("commerce_order_retention_years", "7"),
         ("downloads_license_template", "DIGITAL DOWNLOAD LICENSE AGREEMENT\n\nThis license is granted by the website owner (\"Licensor\") to the purchaser (\"Licensee\").\n\n1. GRANT OF LICENSE\nThe Licensor grants the Licensee a non-exclusive, non-transferable, worldwide license to use the purchased digital file (\"Work\") subject to the terms below.\n\n2. PERMITTED USES\n- Personal use (prints, wallpapers, personal projects)\n- Commercial use in a single end product (website, marketing material, publication)\n- Social media use with credit to the Licensor\n\n3. RESTRICTIONS\n- The Work may NOT be resold, sublicensed, or redistributed as-is\n- The Work may NOT be used in on-demand print services (POD) without a separate license\n- The Work may NOT be included in any competing stock/download service\n- The Work may NOT be used to train AI or machine learning models\n\n4. ATTRIBUTION\nAttribution is appreciated but not required for personal or commercial use.\n\n5. WARRANTY\nThe Work is provided \"as is\" without warranty of any kind. The Licensor is not liable for any damages arising from the use of the Work.\n\n6. TERMINATION\nThis license is effective until terminated. It terminates automatically if the Licensee breaches any terms. Upon termination, the Licensee must destroy all copies of the Work.\n\nBy downloading the Work, the Licensee agrees to these terms."),
         // AI (Phase 4 — defaults ready)
-        ("ai_failover_chain", "ollama,openai,gemini,groq,cloudflare"),
+        ("ai_failover_chain", "ollama,openai,gemini,groq,mistral,openrouter,cloudflare"),
         ("ai_ollama_enabled", "false"),
         ("ai_ollama_url", "http://localhost:11434"),
         ("ai_ollama_model", ""),
+        ("ai_ollama_embedding_model", "nomic-embed-text"),
         ("ai_openai_enabled", "false"),
         ("ai_openai_api_key", ""),
         ("ai_openai_model", "gpt-4"),
@@ -831,6 +1396,19 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("ai_groq_enabled", "false"),
         ("ai_groq_api_key", ""),
         ("ai_groq_model", "llama-3.3-70b-versatile"),
+        ("ai_mistral_enabled", "false"),
+        ("ai_mistral_api_key", ""),
+        ("ai_mistral_model", "mistral-large-latest"),
+        ("ai_openrouter_enabled", "false"),
+        ("ai_openrouter_api_key", ""),
+        ("ai_openrouter_base_url", "https://openrouter.ai/api/v1"),
+        ("ai_openrouter_model", ""),
+        ("ai_openrouter_embedding_model", ""),
+        ("ai_embeddings_enabled", "false"),
+        ("ai_vision_captions_enabled", "false"),
+        ("ai_vision_captions_batch", "5"),
+        ("ai_vision_captions_delay", "5"),
+        ("ai_pricing", ""),
         ("ai_suggest_meta", "true"),
         ("ai_suggest_tags", "true"),
         ("ai_suggest_categories", "false"),
@@ -841,7 +1419,42 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("ai_temperature", "0.7"),
         // Email
         ("email_failover_enabled", "false"),
-        ("email_failover_chain", "builtin,gmail,resend,ses,postmark,brevo,sendpulse,mailgun,moosend,mandrill,sparkpost,smtp"),
+        ("email_failover_chain", "builtin,gmail,resend,ses,postmark,brevo,sendpulse,mailgun,moosend,mandrill,sparkpost,sendgrid,smtp"),
+        ("email_routing_rules", ""),
+        // Newsletter sign-up (double opt-in)
+        ("newsletter_enabled", "false"),
+        // Weekly admin digest
+        ("digest_enabled", "false"),
+        ("digest_day", "mon"),
+        ("digest_hour", "8"),
+        ("digest_last_sent", ""),
+        // Email provider quotas (0 = unlimited)
+        ("email_builtin_quota_hourly", "0"),
+        ("email_builtin_quota_daily", "0"),
+        ("email_gmail_quota_hourly", "0"),
+        ("email_gmail_quota_daily", "0"),
+        ("email_resend_quota_hourly", "0"),
+        ("email_resend_quota_daily", "0"),
+        ("email_ses_quota_hourly", "0"),
+        ("email_ses_quota_daily", "0"),
+        ("email_postmark_quota_hourly", "0"),
+        ("email_postmark_quota_daily", "0"),
+        ("email_brevo_quota_hourly", "0"),
+        ("email_brevo_quota_daily", "0"),
+        ("email_sendpulse_quota_hourly", "0"),
+        ("email_sendpulse_quota_daily", "0"),
+        ("email_mailgun_quota_hourly", "0"),
+        ("email_mailgun_quota_daily", "0"),
+        ("email_moosend_quota_hourly", "0"),
+        ("email_moosend_quota_daily", "0"),
+        ("email_mandrill_quota_hourly", "0"),
+        ("email_mandrill_quota_daily", "0"),
+        ("email_sparkpost_quota_hourly", "0"),
+        ("email_sparkpost_quota_daily", "0"),
+        ("email_sendgrid_quota_hourly", "0"),
+        ("email_sendgrid_quota_daily", "0"),
+        ("email_smtp_quota_hourly", "0"),
+        ("email_smtp_quota_daily", "0"),
         ("email_from_name", ""),
         ("email_from_address", ""),
         ("email_reply_to", ""),
@@ -872,12 +1485,18 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("email_sparkpost_enabled", "false"),
         ("email_sparkpost_api_key", ""),
         ("email_sparkpost_region", "us"),
+        ("email_sendgrid_enabled", "false"),
+        ("email_sendgrid_api_key", ""),
+        ("email_sendgrid_sandbox", "false"),
         ("email_smtp_enabled", "false"),
         ("email_smtp_host", ""),
         ("email_smtp_port", "587"),
         ("email_smtp_username", ""),
         ("email_smtp_password", ""),
         ("email_smtp_encryption", "tls"),
+        // SMTP connection pooling (relays and built-in MTA)
+        ("email_pool_max_connections", "4"),
+        ("email_pool_idle_timeout", "60"),
         // Built-in MTA
         ("email_builtin_enabled", "true"),
         ("mta_from_address", ""),
@@ -885,6 +1504,10 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("mta_dkim_private_key", ""),
         ("mta_dkim_generated_at", ""),
         ("mta_max_emails_per_hour", "30"),
+        ("mta_inbound_enabled", "false"),
+        ("mta_inbound_port", "2525"),
+        ("mta_inbound_bind", "0.0.0.0"),
+        ("mta_reply_token_days", "30"),
         // Firewall
         ("firewall_enabled", "false"),
         ("fw_monitor_bots", "true"),
@@ -901,10 +1524,21 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("fw_path_traversal_protection", "true"),
         ("fw_csrf_strict", "true"),
         ("fw_injection_ban_duration", "7d"),
+        ("fw_honeypot_enabled", "true"),
+        (
+            "fw_honeypot_paths",
+            crate::security::firewall::honeypot::DEFAULT_TRAP_PATHS,
+        ),
+        ("fw_honeypot_auto_ban", "true"),
+        ("fw_honeypot_ban_duration", "7d"),
+        ("fw_honeypot_exclude_ips", ""),
         ("fw_rate_limit_enabled", "true"),
         ("fw_rate_limit_requests", "100"),
         ("fw_rate_limit_window", "60"),
         ("fw_rate_limit_ban_duration", "1h"),
+        ("fw_ban_escalation", "true"),
+        ("fw_ban_escalation_steps", "1h,24h,7d,permanent"),
+        ("fw_ban_escalation_window_days", "30"),
         ("fw_payment_abuse_detection", "true"),
         ("fw_payment_ban_threshold", "3"),
         ("fw_payment_ban_duration", "30d"),
@@ -913,7 +1547,38 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("fw_geo_block_admin", "true"),
         ("fw_geo_blocked_countries", ""),
         ("fw_geo_allowed_countries", ""),
+        ("fw_asn_db_path", ""),
+        ("geoip_db_path", ""),
         ("fw_security_headers", "true"),
+        ("security_referrer_policy", "strict-origin-when-cross-origin"),
+        ("security_frame_ancestors", "self"),
+        ("security_hsts_max_age", "31536000"),
+        ("security_hsts_subdomains", "true"),
+        ("security_hsts_preload", "false"),
+        ("csp_mode", "off"),
+        ("csp_nonce_enabled", "true"),
+        ("csp_allow_google_fonts", "true"),
+        ("csp_allow_analytics", "true"),
+        ("analytics_goals", ""),
+        ("analytics_experiments", ""),
+        ("analytics_outbound_tracking", "false"),
+        ("analytics_engagement_tracking", "false"),
+        ("analytics_privacy_mode", "false"),
+        ("analytics_respect_dnt", "false"),
+        ("analytics_retention_days", "0"),
+        ("analytics_daily_salt", ""),
+        ("analytics_search_log", "true"),
+        ("analytics_report_enabled", "false"),
+        ("analytics_report_day", "1"),
+        ("analytics_report_email", "true"),
+        ("analytics_report_webhook_url", ""),
+        ("analytics_report_webhook_secret", ""),
+        ("analytics_report_last_sent", ""),
+        ("csp_allow_payments", "true"),
+        ("csp_allow_captcha", "true"),
+        ("csp_allow_embeds", "true"),
+        ("csp_custom_directives", ""),
+        ("csp_report_uri", ""),
         // Background Tasks
         ("task_session_cleanup_interval", "30"),
         ("task_session_max_age_days", "30"),
@@ -921,8 +1586,49 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         ("task_scheduled_publish_interval", "1"),
         ("task_audit_log_cleanup_interval", "1440"),
         ("task_audit_log_max_age_days", "90"),
+        ("task_fw_events_max_age_days", "90"),
+        // SIEM forwarding
+        ("siem_enabled", "false"),
+        ("siem_mode", "webhook"),
+        ("siem_webhook_url", ""),
+        ("siem_webhook_secret", ""),
+        ("siem_syslog_target", ""),
+        ("siem_syslog_protocol", "udp"),
+        ("siem_include_firewall", "true"),
+        ("siem_interval_seconds", "15"),
         ("task_analytics_cleanup_interval", "1440"),
         ("task_analytics_max_age_days", "365"),
+        ("task_embeddings_interval", "15"),
+        ("task_vision_captions_interval", "60"),
+        ("task_geoip_reload_interval", "60"),
+        ("task_ai_usage_max_age_days", "365"),
+        // Off-site backups
+        ("backup_enabled", "false"),
+        ("task_backup_interval", "1440"),
+        ("backup_passphrase", ""),
+        ("backup_s3_enabled", "false"),
+        ("backup_s3_endpoint", ""),
+        ("backup_s3_region", "us-east-1"),
+        ("backup_s3_bucket", ""),
+        ("backup_s3_prefix", "velocty"),
+        ("backup_s3_access_key", ""),
+        ("backup_s3_secret_key", ""),
+        ("backup_s3_keep", "7"),
+        ("backup_b2_enabled", "false"),
+        ("backup_b2_endpoint", ""),
+        ("backup_b2_region", ""),
+        ("backup_b2_bucket", ""),
+        ("backup_b2_prefix", "velocty"),
+        ("backup_b2_access_key", ""),
+        ("backup_b2_secret_key", ""),
+        ("backup_b2_keep", "7"),
+        ("backup_sftp_enabled", "false"),
+        ("backup_sftp_host", ""),
+        ("backup_sftp_port", "22"),
+        ("backup_sftp_user", ""),
+        ("backup_sftp_path", ""),
+        ("backup_sftp_key_path", ""),
+        ("backup_sftp_keep", "7"),
         // Image Proxy
         ("image_proxy_secret_old", ""),
         ("image_proxy_secret_old_expires", ""),
@@ -930,6 +1636,11 @@ pub fn default_settings() -> Vec<(&'static str, &'static str)> {
         // Deploy
         ("site_environment", "staging"),
         ("deploy_receive_key", ""),
+        // Page cache
+        ("page_cache_enabled", "true"),
+        ("page_cache_ttl_seconds", "300"),
+        ("page_cache_max_entries", "500"),
+        ("page_cache_disk", "false"),
     ]
 }
 
@@ -964,6 +1675,17 @@ pub fn seed_defaults(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    // Seed the signed-token secret. INSERT OR IGNORE keeps the first one written
+    // if another process seeds concurrently.
+    {
+        use rand::Rng;
+        let bytes: [u8; 32] = rand::thread_rng().gen();
+        conn.execute(
+            "INSERT OR IGNORE INTO settings (key, value) VALUES ('signed_token_secret', ?1)",
+            params![hex::encode(bytes)],
+        )?;
+    }
+
     // Seed default designs if none exist
     let design_count: i64 = conn.query_row("SELECT COUNT(*) FROM designs", [], |row| row.get(0))?;
 
@@ -976,6 +1698,10 @@ pub fn seed_defaults(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
             "INSERT INTO designs (name, slug, description, layout_html, style_css, is_active) VALUES (?1, ?2, ?3, ?4, ?5, 0)",
             params!["Oneguy", "oneguy", "A clean, sidebar-driven portfolio theme for photographers and illustrators. Fixed navigation, masonry and grid layouts, minimal journal — designed to let your work speak for itself.", "", ONEGUY_DESIGN_CSS],
         )?;
+        conn.execute(
+            "INSERT INTO designs (name, slug, description, layout_html, style_css, is_active) VALUES (?1, ?2, ?3, ?4, ?5, 0)",
+            params!["Masthead", "masthead", "A magazine-style journal theme with a front-page story, newspaper columns and bold headlines. Portfolio pages use the Oneguy layouts.", MASTHEAD_SHELL_HTML, MASTHEAD_DESIGN_CSS],
+        )?;
     }
 
     // Backfill legal page content if empty (fix for duplicate seed entries bug)
@@ -997,6 +1723,28 @@ pub fn seed_defaults(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
         conn.execute_batch("ALTER TABLE comments ADD COLUMN parent_id INTEGER DEFAULT NULL;")?;
     }
 
+    // Link comments to member accounts
+    let has_comment_user: bool = conn.prepare("SELECT user_id FROM comments LIMIT 0").is_ok();
+    if !has_comment_user {
+        conn.execute_batch("ALTER TABLE comments ADD COLUMN user_id INTEGER DEFAULT NULL;")?;
+    }
+
+    // Credit posts to the user who wrote them
+    let has_post_author: bool = conn.prepare("SELECT author_id FROM posts LIMIT 0").is_ok();
+    if !has_post_author {
+        conn.execute_batch(
+            "ALTER TABLE posts ADD COLUMN author_id INTEGER DEFAULT NULL;
+             CREATE INDEX IF NOT EXISTS idx_posts_author ON posts(author_id);",
+        )?;
+    }
+
+    // Sort keys of the keyset-paginated lists
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_posts_published ON posts(published_at);
+         CREATE INDEX IF NOT EXISTS idx_comments_created ON comments(created_at);
+         CREATE INDEX IF NOT EXISTS idx_orders_created ON orders(created_at);",
+    )?;
+
     // Seed admin password if not set
     let admin_exists: i64 = conn.query_row(
         "SELECT COUNT(*) FROM settings WHERE key = 'admin_password_hash'",
@@ -1006,8 +1754,15 @@ pub fn seed_defaults(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
 
     if admin_exists == 0 {
         // Default password: "admin" — user MUST change on first login
-        let hash =
-            bcrypt::hash("admin", bcrypt::DEFAULT_COST).expect("Failed to hash default password");
+        let hash_settings: HashMap<String, String> = conn
+            .prepare("SELECT key, value FROM settings WHERE key LIKE 'password_%'")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        let hash = crate::security::auth::hash_password_with(
+            "admin",
+            &crate::security::auth::HashConfig::from_settings(&hash_settings),
+        )
+        .expect("Failed to hash default password");
         conn.execute(
             "INSERT INTO settings (key, value) VALUES ('admin_password_hash', ?1)",
             params![hash],
@@ -1137,6 +1892,26 @@ pub fn seed_defaults(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
         conn.execute_batch("ALTER TABLE designs ADD COLUMN description TEXT NOT NULL DEFAULT '';")?;
     }
 
+    // Add per-design custom CSS and head/footer scripts if missing
+    let has_design_custom_code: bool = conn
+        .prepare("SELECT custom_css FROM designs LIMIT 0")
+        .is_ok();
+    if !has_design_custom_code {
+        conn.execute_batch(
+            "ALTER TABLE designs ADD COLUMN custom_css TEXT NOT NULL DEFAULT '';
+             ALTER TABLE designs ADD COLUMN head_scripts TEXT NOT NULL DEFAULT '';
+             ALTER TABLE designs ADD COLUMN footer_scripts TEXT NOT NULL DEFAULT '';",
+        )?;
+    }
+
+    // Add parent_id to designs for child designs
+    let has_design_parent: bool = conn
+        .prepare("SELECT parent_id FROM designs LIMIT 0")
+        .is_ok();
+    if !has_design_parent {
+        conn.execute_batch("ALTER TABLE designs ADD COLUMN parent_id INTEGER DEFAULT NULL;")?;
+    }
+
     // Backfill Oneguy description if empty
     conn.execute(
         "UPDATE designs SET description = ?1 WHERE slug = 'oneguy' AND description = ''",
@@ -1158,12 +1933,33 @@ pub fn seed_defaults(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    // Ensure Masthead design exists (migration for existing databases)
+    {
+        let masthead_exists: bool = conn.query_row(
+            "SELECT COUNT(*) FROM designs WHERE slug = 'masthead'",
+            [],
+            |row| row.get::<_, i64>(0),
+        )? > 0;
+        if !masthead_exists {
+            conn.execute(
+                "INSERT INTO designs (name, slug, description, layout_html, style_css, is_active) VALUES (?1, ?2, ?3, ?4, ?5, 0)",
+                params!["Masthead", "masthead", "A magazine-style journal theme with a front-page story, newspaper columns and bold headlines. Portfolio pages use the Oneguy layouts.", MASTHEAD_SHELL_HTML, MASTHEAD_DESIGN_CSS],
+            )?;
+        }
+    }
+
     // Keep Inkwell design in sync with the binary constants
     conn.execute(
         "UPDATE designs SET layout_html = ?1, style_css = ?2 WHERE slug = 'inkwell'",
         params![INKWELL_SHELL_HTML, INKWELL_DESIGN_CSS],
     )?;
 
+    // Keep Masthead design in sync with the binary constants
+    conn.execute(
+        "UPDATE designs SET layout_html = ?1, style_css = ?2 WHERE slug = 'masthead'",
+        params![MASTHEAD_SHELL_HTML, MASTHEAD_DESIGN_CSS],
+    )?;
+
     // Keep Oneguy design in sync with the binary constants
     conn.execute(
         "UPDATE designs SET layout_html = ?1, style_css = ?2 WHERE slug = 'oneguy'",
@@ -0,0 +1,102 @@
+//! Sanity checks for design shells. They run at boot and on the Health
+//! page, so a design that would render broken pages is reported before a
+//! visitor finds it.
+
+use crate::store::Store;
+
+/// Placeholders the renderer fills in a design's shell
+pub const SHELL_PLACEHOLDERS: &[&str] = &[
+    "seo_meta",
+    "webmaster_meta",
+    "favicon_link",
+    "font_links",
+    "css_vars",
+    "base_css",
+    "design_css",
+    "body_class",
+    "data_attrs",
+    "wrapper_classes",
+    "logo_html",
+    "site_name_html",
+    "tagline_html",
+    "categories_below_menu",
+    "nav_links",
+    "search_box",
+    "share_sidebar",
+    "custom_sidebar_html",
+    "social_sidebar",
+    "footer_legal_links",
+    "body_content",
+    "footer_inner",
+    "back_to_top",
+    "lightbox_js",
+    "image_protection_js",
+    "analytics_scripts",
+    "cookie_consent",
+];
+
+/// `{{placeholder}}` tags in a shell, in order, as the renderer sees them
+/// (lowercase letters and underscores)
+fn placeholders(html: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut rest = html;
+    while let Some(start) = rest.find("{{") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find("}}") else {
+            break;
+        };
+        let tag = &rest[..end];
+        if !tag.is_empty() && tag.bytes().all(|b| b.is_ascii_lowercase() || b == b'_') {
+            found.push(tag);
+            rest = &rest[end + 2..];
+        }
+    }
+    found
+}
+
+/// What's wrong with one shell: no `{{body_content}}`, so every page would
+/// render without its content, and placeholders the renderer doesn't know,
+/// which are silently dropped
+pub fn shell_problems(layout_html: &str) -> Vec<String> {
+    let tags = placeholders(layout_html);
+    let mut problems = Vec::new();
+    if !tags.contains(&"body_content") {
+        problems.push("the shell has no {{body_content}} placeholder".to_string());
+    }
+    let mut unknown: Vec<&str> = tags
+        .into_iter()
+        .filter(|t| !SHELL_PLACEHOLDERS.contains(t))
+        .collect();
+    unknown.sort_unstable();
+    unknown.dedup();
+    for tag in unknown {
+        problems.push(format!("unknown placeholder {{{{{}}}}} is ignored", tag));
+    }
+    problems
+}
+
+/// Problems across every design, each naming its design, including a
+/// missing active design and parents that no longer exist
+pub fn problems(store: &dyn Store) -> Vec<String> {
+    let designs = store.design_list();
+    let mut problems = Vec::new();
+    if !designs.iter().any(|d| d.is_active) {
+        problems.push("No design is active; public pages can't render".to_string());
+    }
+    for design in designs {
+        if let Some(pid) = design.parent_id {
+            if store.design_find_by_id(pid).is_none() {
+                problems.push(format!(
+                    "Design \"{}\": its parent design no longer exists",
+                    design.name
+                ));
+            }
+        }
+        let name = design.name.clone();
+        let resolved = super::inheritance::resolve(store, design);
+        for problem in shell_problems(&resolved.design.layout_html) {
+            problems.push(format!("Design \"{}\": {}", name, problem));
+        }
+    }
+    problems
+}
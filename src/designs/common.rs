@@ -114,7 +114,7 @@ pub(crate) fn build_classic_comments(context: &Value, settings: &Value, post_id:
         }
     }
 
-    html.push_str(&format!(
+    html.push_str(&crate::render::nonce_scripts(&format!(
         "<section class=\"bsc-comment-form\">\
 \n    <h3>Leave a Reply</h3>\
 \n    {captcha_script}\
@@ -135,7 +135,7 @@ pub(crate) fn build_classic_comments(context: &Value, settings: &Value, post_id:
 \n(function(){{\
 \nvar f=document.getElementById('comment-form');\
 \nif(!f)return;\
-\nvar getToken={captcha_get_token_js};\
+\nvar getToken={captcha_get_token_js};{member_js}\
 \ndocument.querySelectorAll('.reply-btn').forEach(function(btn){{\
 \n    btn.addEventListener('click',function(e){{\
 \n        e.preventDefault();\
@@ -193,7 +193,8 @@ pub(crate) fn build_classic_comments(context: &Value, settings: &Value, post_id:
         name_req = name_req,
         captcha_html = captcha_html,
         captcha_get_token_js = captcha_get_token_js,
-    ));
+        member_js = crate::render::member_comment_js(settings),
+    )));
 
     html
 }
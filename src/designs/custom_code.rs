@@ -0,0 +1,66 @@
+//! Per-design custom CSS and head/footer scripts, added by the site owner
+//! on top of a design's own styles.
+
+use crate::models::design::Design;
+
+/// Upper bound for each custom code field
+pub const MAX_LEN: usize = 64 * 1024;
+
+/// Refuse a custom code field over `MAX_LEN`. Custom CSS is then cleaned by
+/// `package::sanitize_css`, like a shared design's, so it can't close its
+/// `<style>` block or run script.
+pub fn check_len(label: &str, value: &str) -> Result<(), String> {
+    if value.len() > MAX_LEN {
+        return Err(format!("{} is too long", label));
+    }
+    Ok(())
+}
+
+/// Scripts are trusted markup, but they may not restructure the page:
+/// document-level tags (`<html>`, `<head>`, `<body>`, `<base>`, `<title>`)
+/// and `<meta http-equiv>` are stripped.
+pub fn sanitize_scripts(html: &str) -> Result<String, String> {
+    check_len("Custom scripts", html)?;
+    let patterns = [
+        r"(?i)<!doctype[^>]*>",
+        r"(?is)</?\s*(html|head|body|base|title)\b[^>]*>",
+        r"(?is)<meta\b[^>]*http-equiv[^>]*>",
+    ];
+    let mut out = html.replace('\0', "");
+    for p in patterns {
+        if let Ok(re) = regex::Regex::new(p) {
+            out = re.replace_all(&out, "").into_owned();
+        }
+    }
+    Ok(out.trim().to_string())
+}
+
+/// Add the design's custom CSS and head scripts at the end of `<head>`,
+/// after the design styles, and its footer scripts before `</body>`.
+/// The scripts get the request's CSP nonce.
+pub fn inject(mut html: String, design: &Design) -> String {
+    let mut head = String::new();
+    if !design.custom_css.is_empty() {
+        head.push_str(&format!(
+            "<style id=\"design-custom-css\">\n{}\n</style>\n",
+            design.custom_css
+        ));
+    }
+    if !design.head_scripts.is_empty() {
+        head.push_str(&crate::render::nonce_scripts(&design.head_scripts));
+        head.push('\n');
+    }
+    if !head.is_empty() {
+        if let Some(pos) = html.find("</head>") {
+            html.insert_str(pos, &head);
+        }
+    }
+    if !design.footer_scripts.is_empty() {
+        let footer = format!("{}\n", crate::render::nonce_scripts(&design.footer_scripts));
+        match html.rfind("</body>") {
+            Some(pos) => html.insert_str(pos, &footer),
+            None => html.push_str(&footer),
+        }
+    }
+    html
+}
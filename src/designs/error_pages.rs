@@ -0,0 +1,104 @@
+//! Error pages: each design can define its own 404 and 500 page as design
+//! templates of type "404" and "500". They render inside the design's shell
+//! like any other page; when a design has none, the built-in pages are used,
+//! and the catchers fall back to plain static HTML when rendering fails.
+
+use serde_json::Value;
+
+use crate::render::html_escape;
+use crate::store::Store;
+
+/// Template types for error pages, as (type, label)
+pub const TYPES: &[(&str, &str)] = &[("404", "Not Found (404)"), ("500", "Server Error (500)")];
+
+/// Upper bound for an error page's HTML and CSS
+pub const MAX_LEN: usize = 64 * 1024;
+
+/// Whether `template_type` is one of the error pages
+pub fn is_error_page(template_type: &str) -> bool {
+    TYPES.iter().any(|(t, _)| *t == template_type)
+}
+
+/// The design's own (or inherited) page for `template_type`, already
+/// rendered. None when the design doesn't define one.
+pub fn custom(
+    store: &dyn Store,
+    design_id: i64,
+    template_type: &str,
+    context: &Value,
+) -> Option<String> {
+    if !is_error_page(template_type) {
+        return None;
+    }
+    let template = store.design_template_get(design_id, template_type)?;
+    if template.layout_html.trim().is_empty() {
+        return None;
+    }
+    let site_name = context
+        .get("settings")
+        .and_then(|s| s.get("site_name"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("Velocty");
+    let body = template
+        .layout_html
+        .replace("{{site_name}}", &html_escape(site_name))
+        .replace("{{status}}", template_type);
+    if template.style_css.trim().is_empty() {
+        Some(body)
+    } else {
+        Some(format!(
+            "<style class=\"error-page-css\">\n{}\n</style>\n{}",
+            template.style_css, body
+        ))
+    }
+}
+
+/// Built-in 500 page body
+pub fn render_500(_context: &Value) -> String {
+    r#"<div class="error-page">
+    <h1>500</h1>
+    <p>Something went wrong on our end. Please try again in a moment.</p>
+    <a href="/">← Back to home</a>
+</div>"#
+        .to_string()
+}
+
+/// Standalone page for when the design can't be rendered at all
+pub fn static_fallback(status: u16) -> String {
+    let message = match status {
+        404 => "Page not found.",
+        _ => "Internal server error.",
+    };
+    format!(
+        "<html><body style='font-family:sans-serif;text-align:center;padding:80px'><h1>{}</h1><p>{}</p><a href='/'>← Home</a></body></html>",
+        status, message
+    )
+}
+
+/// Render the error page for `status` through the active design, or the
+/// static fallback when there's no active design or rendering panics.
+/// Used by the catchers, which must always answer.
+pub fn render(store: &dyn Store, status: u16, context: &Value) -> String {
+    if store.design_active().is_none() {
+        return static_fallback(status);
+    }
+    let template_type = status.to_string();
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        crate::render::render_page(store, &template_type, context)
+    }))
+    .unwrap_or_else(|_| {
+        log::error!("[render] The {} page failed to render", status);
+        static_fallback(status)
+    })
+}
+
+/// Clean up an error page submitted in the designer
+pub fn sanitize(html: &str, css: &str) -> Result<(String, String), String> {
+    if html.len() > MAX_LEN || css.len() > MAX_LEN {
+        return Err("Error page is too long".into());
+    }
+    Ok((
+        super::package::sanitize_html(html).trim().to_string(),
+        super::package::sanitize_css(css).trim().to_string(),
+    ))
+}
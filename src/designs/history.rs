@@ -0,0 +1,71 @@
+//! Template history: every save of a design template is kept as a version
+//! (see `Store::design_template_save`), so the designer can compare an older
+//! state with the current one and roll back to it.
+
+use serde::Serialize;
+
+/// Above this many lines on either side the diff shows the whole text as
+/// replaced instead of matching lines up
+const MAX_DIFF_LINES: usize = 4000;
+
+/// One line of a diff
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffLine {
+    /// "same", "added" or "removed"
+    pub kind: &'static str,
+    pub text: String,
+}
+
+/// Line diff turning `old` into `new`, from their longest common
+/// subsequence of lines
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let line = |kind, text: &str| DiffLine {
+        kind,
+        text: text.to_string(),
+    };
+    if a.len() > MAX_DIFF_LINES || b.len() > MAX_DIFF_LINES {
+        return a
+            .iter()
+            .map(|l| line("removed", l))
+            .chain(b.iter().map(|l| line("added", l)))
+            .collect();
+    }
+
+    // lcs[i][j] = common lines between a[i..] and b[j..]
+    let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::with_capacity(a.len().max(b.len()));
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            out.push(line("same", a[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(line("removed", a[i]));
+            i += 1;
+        } else {
+            out.push(line("added", b[j]));
+            j += 1;
+        }
+    }
+    out.extend(a[i..].iter().map(|l| line("removed", l)));
+    out.extend(b[j..].iter().map(|l| line("added", l)));
+    out
+}
+
+/// Whether the diff has any change
+pub fn has_changes(diff: &[DiffLine]) -> bool {
+    diff.iter().any(|l| l.kind != "same")
+}
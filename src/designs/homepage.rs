@@ -0,0 +1,383 @@
+//! Homepage section builder: the homepage composed from an ordered list of
+//! sections configured in the customizer (`homepage_sections`), used when
+//! `homepage_mode` is "sections".
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::render::{format_date, html_escape, slug_url};
+
+/// Section kinds as (key, label)
+pub const SECTION_TYPES: &[(&str, &str)] = &[
+    ("hero", "Hero"),
+    ("featured_portfolio", "Featured Portfolio"),
+    ("latest_posts", "Latest Posts"),
+    ("about", "About"),
+    ("newsletter", "Newsletter"),
+];
+
+const MAX_SECTIONS: usize = 20;
+/// Most items a portfolio or posts section will show
+pub const MAX_ITEMS: i64 = 24;
+
+/// One block of the homepage. Which fields matter depends on `kind`:
+/// `count` for portfolio and posts, `image` for the hero and about blurb,
+/// `button_text`/`button_url` for the hero and newsletter.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Section {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default = "enabled_default")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub text: String,
+    #[serde(default)]
+    pub image: String,
+    #[serde(default)]
+    pub button_text: String,
+    #[serde(default)]
+    pub button_url: String,
+    #[serde(default)]
+    pub count: i64,
+}
+
+fn enabled_default() -> bool {
+    true
+}
+
+impl Section {
+    fn new(kind: &str, count: i64) -> Self {
+        Section {
+            kind: kind.to_string(),
+            enabled: true,
+            title: String::new(),
+            text: String::new(),
+            image: String::new(),
+            button_text: String::new(),
+            button_url: String::new(),
+            count,
+        }
+    }
+
+    /// Items to show, within 1..=MAX_ITEMS
+    pub fn item_count(&self) -> i64 {
+        if self.count <= 0 {
+            6
+        } else {
+            self.count.min(MAX_ITEMS)
+        }
+    }
+}
+
+/// One of each section, in the default order
+pub fn default_sections() -> Vec<Section> {
+    SECTION_TYPES
+        .iter()
+        .map(|(k, _)| Section::new(k, 6))
+        .collect()
+}
+
+/// Stored sections, falling back to the defaults when the setting is
+/// missing or unreadable. Unknown section types are dropped.
+pub fn parse_sections(raw: &str) -> Vec<Section> {
+    match serde_json::from_str::<Vec<Section>>(raw) {
+        Ok(list) => list
+            .into_iter()
+            .filter(|s| SECTION_TYPES.iter().any(|(k, _)| *k == s.kind))
+            .take(MAX_SECTIONS)
+            .collect(),
+        Err(_) => default_sections(),
+    }
+}
+
+/// Check sections posted from the customizer and return them as the JSON
+/// to store.
+pub fn normalize_sections(raw: &str) -> Result<String, String> {
+    let mut list: Vec<Section> =
+        serde_json::from_str(raw).map_err(|e| format!("Homepage sections are not valid: {}", e))?;
+    if list.len() > MAX_SECTIONS {
+        return Err(format!(
+            "The homepage can have at most {} sections",
+            MAX_SECTIONS
+        ));
+    }
+    for s in &mut list {
+        if !SECTION_TYPES.iter().any(|(k, _)| *k == s.kind) {
+            return Err(format!("Unknown homepage section: {}", s.kind));
+        }
+        s.title = s.title.trim().chars().take(200).collect();
+        s.text = s.text.trim().chars().take(5000).collect();
+        s.button_text = s.button_text.trim().chars().take(100).collect();
+        s.button_url = s.button_url.trim().to_string();
+        let url = s.button_url.to_lowercase();
+        if !(url.is_empty()
+            || url.starts_with('/')
+            || url.starts_with('#')
+            || url.starts_with("https://")
+            || url.starts_with("http://"))
+        {
+            return Err(format!(
+                "{} button link must be a site path or an http(s) URL",
+                s.kind
+            ));
+        }
+        s.image = s
+            .image
+            .trim()
+            .trim_start_matches("/uploads/")
+            .trim_start_matches('/')
+            .to_string();
+        if s.image.contains("..") || s.image.contains(':') {
+            return Err(format!("{} image must be an uploaded file", s.kind));
+        }
+        s.count = s.count.clamp(0, MAX_ITEMS);
+    }
+    serde_json::to_string(&list).map_err(|e| e.to_string())
+}
+
+pub const CSS: &str = r#"
+.home-sections { display: flex; flex-direction: column; gap: 56px; }
+.home-section-title { font-size: 1.4em; margin: 0 0 20px; }
+.home-hero { padding: 64px 24px; text-align: center; background-size: cover; background-position: center; border-radius: 6px; }
+.home-hero.has-image { color: #fff; text-shadow: 0 1px 8px rgba(0,0,0,.45); }
+.home-hero h1 { font-size: 2.4em; margin: 0 0 12px; }
+.home-hero p { font-size: 1.15em; margin: 0 auto 20px; max-width: 640px; }
+.home-button { display: inline-block; padding: 10px 22px; border-radius: 4px; background: var(--color-accent, #333); color: #fff; text-decoration: none; text-shadow: none; }
+.home-portfolio-grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(220px, 1fr)); gap: 16px; }
+.home-portfolio-grid a { display: block; color: inherit; text-decoration: none; }
+.home-portfolio-grid img { width: 100%; aspect-ratio: 4/3; object-fit: cover; display: block; border-radius: 4px; }
+.home-portfolio-grid span { display: block; margin-top: 6px; font-size: 14px; }
+.home-posts { list-style: none; margin: 0; padding: 0; }
+.home-posts li { margin: 0 0 18px; }
+.home-posts time { display: block; font-size: 12px; opacity: .7; }
+.home-posts p { margin: 4px 0 0; }
+.home-about { display: flex; gap: 32px; align-items: flex-start; }
+.home-about img { width: 240px; max-width: 40%; border-radius: 6px; }
+.home-newsletter { text-align: center; }
+.home-newsletter form { justify-content: center; max-width: 460px; margin: 0 auto; }
+@media (max-width: 640px) { .home-about { flex-direction: column; } .home-about img { max-width: 100%; } }
+"#;
+
+/// Render the homepage body from `context`: `home_sections` (the stored
+/// sections), `featured_portfolio` and `latest_posts` (the items they
+/// draw from) and `settings`. Disabled and empty sections are skipped.
+pub fn render(context: &Value) -> String {
+    let settings = context.get("settings").cloned().unwrap_or_default();
+    let sections: Vec<Section> = context
+        .get("home_sections")
+        .cloned()
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    let empty = vec![];
+    let portfolio = context
+        .get("featured_portfolio")
+        .and_then(|v| v.as_array())
+        .unwrap_or(&empty);
+    let posts = context
+        .get("latest_posts")
+        .and_then(|v| v.as_array())
+        .unwrap_or(&empty);
+
+    let inner: String = sections
+        .iter()
+        .filter(|s| s.enabled)
+        .map(|s| match s.kind.as_str() {
+            "hero" => hero(s, &settings),
+            "featured_portfolio" => featured_portfolio(s, portfolio, &settings),
+            "latest_posts" => latest_posts(s, posts, &settings),
+            "about" => about(s),
+            "newsletter" => newsletter(s, &settings),
+            _ => String::new(),
+        })
+        .collect();
+    format!("<div class=\"home-sections\">{}</div>", inner)
+}
+
+fn setting<'a>(settings: &'a Value, key: &str, default: &'a str) -> &'a str {
+    settings
+        .get(key)
+        .and_then(|v| v.as_str())
+        .unwrap_or(default)
+}
+
+fn heading(s: &Section, default: &str) -> String {
+    let title = if s.title.is_empty() {
+        default
+    } else {
+        &s.title
+    };
+    if title.is_empty() {
+        return String::new();
+    }
+    format!(
+        "<h2 class=\"home-section-title\">{}</h2>",
+        html_escape(title)
+    )
+}
+
+fn button(s: &Section) -> String {
+    if s.button_text.is_empty() || s.button_url.is_empty() {
+        return String::new();
+    }
+    format!(
+        "<a class=\"home-button\" href=\"{}\">{}</a>",
+        html_escape(&s.button_url),
+        html_escape(&s.button_text)
+    )
+}
+
+/// Blank-line separated paragraphs
+fn paragraphs(text: &str) -> String {
+    text.split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| format!("<p>{}</p>", html_escape(p).replace('\n', "<br>")))
+        .collect()
+}
+
+/// Site name and tagline unless the section overrides them
+fn hero(s: &Section, settings: &Value) -> String {
+    let title = if s.title.is_empty() {
+        setting(settings, "site_name", "")
+    } else {
+        &s.title
+    };
+    let text = if s.text.is_empty() {
+        setting(settings, "site_caption", "")
+    } else {
+        &s.text
+    };
+    let (class, style) = if s.image.is_empty() {
+        ("home-hero", String::new())
+    } else {
+        (
+            "home-hero has-image",
+            format!(
+                " style=\"background-image:url('/uploads/{}')\"",
+                html_escape(&s.image)
+            ),
+        )
+    };
+    let text = if text.is_empty() {
+        String::new()
+    } else {
+        format!("<p>{}</p>", html_escape(text))
+    };
+    format!(
+        "<section class=\"{}\"{}><h1>{}</h1>{}{}</section>",
+        class,
+        style,
+        html_escape(title),
+        text,
+        button(s)
+    )
+}
+
+fn featured_portfolio(s: &Section, items: &[Value], settings: &Value) -> String {
+    if setting(settings, "portfolio_enabled", "false") != "true" || items.is_empty() {
+        return String::new();
+    }
+    let portfolio_slug = setting(settings, "portfolio_slug", "portfolio");
+    let cards: String = items
+        .iter()
+        .take(s.item_count() as usize)
+        .map(|item| {
+            let title = item.get("title").and_then(|v| v.as_str()).unwrap_or("");
+            let slug = item.get("slug").and_then(|v| v.as_str()).unwrap_or("");
+            let image = item
+                .get("thumbnail_path")
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .or_else(|| item.get("image_path").and_then(|v| v.as_str()))
+                .unwrap_or("");
+            format!(
+                "<a href=\"{}\"><img src=\"/uploads/{}\" alt=\"{}\" loading=\"lazy\"><span>{}</span></a>",
+                slug_url(portfolio_slug, slug),
+                html_escape(image),
+                html_escape(title),
+                html_escape(title)
+            )
+        })
+        .collect();
+    format!(
+        "<section class=\"home-portfolio\">{}<div class=\"home-portfolio-grid\">{}</div></section>",
+        heading(s, "Featured Work"),
+        cards
+    )
+}
+
+fn latest_posts(s: &Section, posts: &[Value], settings: &Value) -> String {
+    if setting(settings, "journal_enabled", "true") == "false" || posts.is_empty() {
+        return String::new();
+    }
+    let blog_slug = setting(settings, "blog_slug", "journal");
+    let items: String = posts
+        .iter()
+        .take(s.item_count() as usize)
+        .map(|p| {
+            let title = p.get("title").and_then(|v| v.as_str()).unwrap_or("");
+            let slug = p.get("slug").and_then(|v| v.as_str()).unwrap_or("");
+            let date = p
+                .get("published_at")
+                .and_then(|v| v.as_str())
+                .map(|d| format!("<time>{}</time>", format_date(d, settings)))
+                .unwrap_or_default();
+            let excerpt = p
+                .get("excerpt")
+                .and_then(|v| v.as_str())
+                .filter(|e| !e.is_empty())
+                .map(|e| format!("<p>{}</p>", html_escape(e)))
+                .unwrap_or_default();
+            format!(
+                "<li>{}<a href=\"{}\">{}</a>{}</li>",
+                date,
+                slug_url(blog_slug, slug),
+                html_escape(title),
+                excerpt
+            )
+        })
+        .collect();
+    format!(
+        "<section class=\"home-latest-posts\">{}<ul class=\"home-posts\">{}</ul></section>",
+        heading(s, "Latest Posts"),
+        items
+    )
+}
+
+fn about(s: &Section) -> String {
+    if s.text.is_empty() && s.image.is_empty() {
+        return String::new();
+    }
+    let image = if s.image.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<img src=\"/uploads/{}\" alt=\"{}\">",
+            html_escape(&s.image),
+            html_escape(&s.title)
+        )
+    };
+    format!(
+        "<section class=\"home-about\">{}<div>{}{}</div></section>",
+        image,
+        heading(s, ""),
+        paragraphs(&s.text)
+    )
+}
+
+/// Uses the newsletter widget's signup form, so it's hidden the same way
+/// while sign-up is turned off.
+fn newsletter(s: &Section, settings: &Value) -> String {
+    let config = json!({ "text": s.text, "button": s.button_text });
+    let form = crate::render::widgets::newsletter_form("home-newsletter", &config, settings);
+    if form.is_empty() {
+        return String::new();
+    }
+    format!(
+        "<section class=\"home-newsletter widget-newsletter\">{}{}</section>",
+        heading(s, "Newsletter"),
+        form
+    )
+}
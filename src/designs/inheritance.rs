@@ -0,0 +1,74 @@
+//! Child designs: a design with a parent uses the parent's shell, CSS and
+//! page templates for whatever it doesn't define itself.
+
+use crate::models::design::{Design, MAX_DESIGN_DEPTH};
+use crate::store::Store;
+
+/// A design ready to render, with what it inherits filled in
+pub struct Resolved {
+    pub design: Design,
+    /// Slug of the bundled design at the root of the chain, which picks
+    /// the journal and portfolio renderers
+    pub base_slug: String,
+}
+
+/// Fill in a design's inherited parts: an empty shell comes from the
+/// nearest ancestor that has one, and the CSS is every ancestor's CSS,
+/// root first, followed by the design's own so its rules win.
+pub fn resolve(store: &dyn Store, design: Design) -> Resolved {
+    if design.parent_id.is_none() {
+        let base_slug = design.slug.clone();
+        return Resolved { design, base_slug };
+    }
+    let lineage = store.design_lineage(design);
+    let mut design = lineage[0].clone();
+    if design.layout_html.trim().is_empty() {
+        if let Some(layout) = lineage
+            .iter()
+            .skip(1)
+            .map(|d| &d.layout_html)
+            .find(|l| !l.trim().is_empty())
+        {
+            design.layout_html = layout.clone();
+        }
+    }
+    design.style_css = lineage
+        .iter()
+        .rev()
+        .map(|d| d.style_css.trim())
+        .filter(|css| !css.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let base_slug = lineage.last().map(|d| d.slug.clone()).unwrap_or_default();
+    Resolved { design, base_slug }
+}
+
+/// Slug of the design at the root of `design`'s chain
+pub fn base_slug(store: &dyn Store, design: Design) -> String {
+    resolve(store, design).base_slug
+}
+
+/// Check that `parent_id` can become the parent of design `id`
+pub fn validate_parent(store: &dyn Store, id: i64, parent_id: Option<i64>) -> Result<(), String> {
+    let pid = match parent_id {
+        Some(pid) => pid,
+        None => return Ok(()),
+    };
+    if pid == id {
+        return Err("A design can't inherit from itself".into());
+    }
+    let parent = store
+        .design_find_by_id(pid)
+        .ok_or("Parent design not found")?;
+    let lineage = store.design_lineage(parent);
+    if lineage.iter().any(|d| d.id == id) {
+        return Err("That parent already inherits from this design".into());
+    }
+    if lineage.len() >= MAX_DESIGN_DEPTH {
+        return Err(format!(
+            "Designs can be nested at most {} deep",
+            MAX_DESIGN_DEPTH
+        ));
+    }
+    Ok(())
+}
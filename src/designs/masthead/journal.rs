@@ -0,0 +1,634 @@
+use serde_json::Value;
+
+use crate::designs::common::build_classic_comments;
+use crate::render::{
+    author_link, build_pagination, build_related_posts, build_share_buttons, count_words_html,
+    format_date, html_escape, strip_html_to_text, truncate_words,
+};
+
+/// First category of a post as the small label above its headline.
+fn kicker(post: &Value, blog_slug: &str) -> String {
+    post.get("categories")
+        .and_then(|v| v.as_array())
+        .and_then(|cats| cats.first())
+        .map(|cat| {
+            let name = cat.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            let slug = cat.get("slug").and_then(|v| v.as_str()).unwrap_or("");
+            format!(
+                "<a class=\"mh-kicker\" href=\"/{}/category/{}\">{}</a>",
+                blog_slug,
+                slug,
+                html_escape(name)
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Render the blog list page in the Masthead style.
+/// Layout: on the first page the newest post is the front-page story
+/// (large image left, headline and standfirst right), and the rest run in
+/// newspaper columns below it. Later pages are columns only.
+pub fn render_list(context: &Value) -> String {
+    let posts = match context.get("posts") {
+        Some(Value::Array(p)) if !p.is_empty() => p,
+        _ => return "<p>No posts yet.</p>".to_string(),
+    };
+
+    let settings = context.get("settings").cloned().unwrap_or_default();
+    let sg = |key: &str, def: &str| -> String {
+        settings
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or(def)
+            .to_string()
+    };
+    let blog_slug = sg("blog_slug", "journal");
+    let show_date = sg("blog_show_date", "true") == "true";
+    let show_author = sg("blog_show_author", "true") == "true";
+    let show_reading_time = sg("blog_show_reading_time", "true") == "true";
+    let excerpt_words: usize = sg("blog_excerpt_words", "40").parse().unwrap_or(40);
+    let current_page = context
+        .get("current_page")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(1);
+
+    // Meta line: date · author · reading time
+    let meta = |post: &Value| -> String {
+        let content_html = post
+            .get("content_html")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let raw_date = post
+            .get("published_at")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let date = format_date(raw_date, &settings);
+        let author = post
+            .get("author_name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let word_count = count_words_html(content_html);
+        let mut parts: Vec<String> = Vec::new();
+        if show_date && !date.is_empty() {
+            parts.push(html_escape(&date));
+        }
+        if show_author && !author.is_empty() {
+            parts.push(format!("By {}", author_link(post, author)));
+        }
+        if show_reading_time && word_count > 0 {
+            let minutes = ((word_count as f64) / 200.0).ceil().max(1.0) as i64;
+            parts.push(format!("{} min read", minutes));
+        }
+        if parts.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "<div class=\"mh-meta\">{}</div>",
+                parts.join(" &nbsp;&middot;&nbsp; ")
+            )
+        }
+    };
+    let excerpt = |post: &Value, words: usize| -> String {
+        let raw = post.get("excerpt").and_then(|v| v.as_str()).unwrap_or("");
+        let source = if raw.is_empty() {
+            strip_html_to_text(
+                post.get("content_html")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(""),
+            )
+        } else {
+            raw.to_string()
+        };
+        truncate_words(&source, words)
+    };
+
+    let mut html = String::new();
+    let mut rest = posts.iter();
+
+    // ── Front-page story ──
+    if current_page <= 1 {
+        if let Some(lead) = rest.next() {
+            let title = lead.get("title").and_then(|v| v.as_str()).unwrap_or("");
+            let slug = lead.get("slug").and_then(|v| v.as_str()).unwrap_or("");
+            let image = lead
+                .get("featured_image")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            let image_html = if image.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    "<a class=\"mh-lead-image\" href=\"/{}/{}\"><img src=\"/uploads/{}\" alt=\"{}\"></a>",
+                    blog_slug,
+                    slug,
+                    image,
+                    html_escape(title)
+                )
+            };
+            html.push_str(&format!(
+                "<section class=\"mh-front{no_image}\">\
+                 {image_html}\
+                 <div class=\"mh-lead-body\">\
+                 {kicker}\
+                 <h2 class=\"mh-lead-title\"><a href=\"/{blog_slug}/{slug}\">{title}</a></h2>\
+                 <p class=\"mh-standfirst\">{excerpt}</p>\
+                 {meta}\
+                 </div>\
+                 </section>",
+                no_image = if image.is_empty() { " mh-no-image" } else { "" },
+                image_html = image_html,
+                kicker = kicker(lead, &blog_slug),
+                blog_slug = blog_slug,
+                slug = slug,
+                title = html_escape(title),
+                excerpt = html_escape(&excerpt(lead, excerpt_words * 2)),
+                meta = meta(lead),
+            ));
+        }
+    }
+
+    // ── Columns ──
+    html.push_str("<div class=\"blog-list mh-columns\">");
+    for post in rest {
+        let title = post.get("title").and_then(|v| v.as_str()).unwrap_or("");
+        let slug = post.get("slug").and_then(|v| v.as_str()).unwrap_or("");
+        let image = post
+            .get("featured_image")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        let image_html = if image.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "<a class=\"mh-card-image\" href=\"/{}/{}\"><img src=\"/uploads/{}\" alt=\"{}\" loading=\"lazy\"></a>",
+                blog_slug,
+                slug,
+                image,
+                html_escape(title)
+            )
+        };
+        html.push_str(&format!(
+            "<article class=\"blog-item mh-card\">\
+             {image_html}\
+             {kicker}\
+             <h3 class=\"mh-card-title\"><a href=\"/{blog_slug}/{slug}\">{title}</a></h3>\
+             <p class=\"mh-card-excerpt\">{excerpt}</p>\
+             {meta}\
+             </article>",
+            image_html = image_html,
+            kicker = kicker(post, &blog_slug),
+            blog_slug = blog_slug,
+            slug = slug,
+            title = html_escape(title),
+            excerpt = html_escape(&excerpt(post, excerpt_words)),
+            meta = meta(post),
+        ));
+    }
+    html.push_str("</div>");
+
+    // Pagination
+    let total_pages = context
+        .get("total_pages")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(1);
+    let pagination_type = sg("blog_pagination_type", "classic");
+
+    if total_pages > 1 {
+        match pagination_type.as_str() {
+            "load_more" => {
+                html.push_str(&format!(
+                    "<div class=\"pagination\" style=\"justify-content:center\">\
+                     <button id=\"load-more-btn\" data-page=\"{}\" data-total=\"{}\" \
+                     style=\"padding:10px 28px;border:1px solid #ddd;border-radius:4px;background:transparent;cursor:pointer;font-size:14px\">\
+                     Load More</button></div>",
+                    current_page + 1, total_pages
+                ));
+            }
+            "infinite" => {
+                html.push_str(&format!(
+                    "<div id=\"infinite-sentinel\" data-page=\"{}\" data-total=\"{}\" \
+                     style=\"height:1px\"></div>",
+                    current_page + 1,
+                    total_pages
+                ));
+            }
+            _ => {
+                html.push_str(&build_pagination(current_page, total_pages));
+            }
+        }
+    }
+
+    format!("<div class=\"mh-journal\">{}</div>", html)
+}
+
+/// Render the blog single page in the Masthead style.
+/// Layout: centred kicker, headline, standfirst and byline rule, then the
+/// featured image across the column and the story with a drop cap.
+pub fn render_single(context: &Value) -> String {
+    let post = match context.get("post") {
+        Some(p) => p,
+        None => return String::new(),
+    };
+    let settings = context.get("settings").cloned().unwrap_or_default();
+    let sg = |key: &str, def: &str| -> String {
+        settings
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or(def)
+            .to_string()
+    };
+    let show_author = sg("blog_show_author", "true") == "true";
+    let show_date = sg("blog_show_date", "true") == "true";
+    let show_reading_time = sg("blog_show_reading_time", "true") == "true";
+
+    let title = post.get("title").and_then(|v| v.as_str()).unwrap_or("");
+    let content = post
+        .get("content_html")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let standfirst = post.get("excerpt").and_then(|v| v.as_str()).unwrap_or("");
+    let raw_date = post
+        .get("published_at")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let date = format_date(raw_date, &settings);
+    let featured = post
+        .get("featured_image")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let author = post
+        .get("author_name")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let word_count = count_words_html(content);
+    let reading_time = ((word_count as f64) / 200.0).ceil().max(1.0) as i64;
+
+    let blog_slug = sg("blog_slug", "journal");
+    let post_slug = post.get("slug").and_then(|v| v.as_str()).unwrap_or("");
+    let site_url = sg("site_url", "");
+    let page_url = if !site_url.is_empty() {
+        format!("{}/{}/{}", site_url, blog_slug, post_slug)
+    } else {
+        String::new()
+    };
+    let share_pos = sg("share_icons_position", "below_content");
+
+    let comments_on = context
+        .get("comments_enabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let mut html = String::from("<article class=\"mh-article\">");
+
+    // ── Headline block ──
+    html.push_str("<header class=\"mh-article-head\">");
+    if let Some(Value::Array(cats)) = context.get("categories") {
+        if let Some(cat) = cats.first() {
+            let name = cat.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            let cat_slug = cat.get("slug").and_then(|v| v.as_str()).unwrap_or("");
+            html.push_str(&format!(
+                "<a class=\"mh-kicker\" href=\"/{}/category/{}\">{}</a>",
+                blog_slug,
+                cat_slug,
+                html_escape(name)
+            ));
+        }
+    }
+    html.push_str(&format!(
+        "<h1 class=\"mh-article-title\">{}</h1>",
+        html_escape(title)
+    ));
+    if !standfirst.is_empty() {
+        html.push_str(&format!(
+            "<p class=\"mh-standfirst\">{}</p>",
+            html_escape(standfirst)
+        ));
+    }
+    let mut byline: Vec<String> = Vec::new();
+    if show_author && !author.is_empty() {
+        byline.push(format!("By <strong>{}</strong>", author_link(post, author)));
+    }
+    if show_date && !date.is_empty() {
+        byline.push(html_escape(&date));
+    }
+    if show_reading_time && word_count > 0 {
+        byline.push(format!("{} min read", reading_time));
+    }
+    if !byline.is_empty() {
+        html.push_str(&format!(
+            "<div class=\"mh-byline\">{}</div>",
+            byline.join(" &nbsp;&middot;&nbsp; ")
+        ));
+    }
+    html.push_str("</header>");
+
+    if !featured.is_empty() {
+        html.push_str(&format!(
+            "<figure class=\"mh-article-hero\"><img src=\"/uploads/{}\" alt=\"{}\"></figure>",
+            featured,
+            html_escape(title)
+        ));
+    }
+
+    if share_pos == "below_image" && !page_url.is_empty() {
+        html.push_str(&build_share_buttons(&settings, &page_url, title));
+    }
+
+    html.push_str(&format!("<div class=\"mh-content\">{}</div>", content));
+
+    // Tags
+    if let Some(Value::Array(tags)) = context.get("tags") {
+        if !tags.is_empty() {
+            html.push_str("<div class=\"mh-tags\">");
+            for tag in tags {
+                let name = tag.get("name").and_then(|v| v.as_str()).unwrap_or("");
+                let tag_slug = tag.get("slug").and_then(|v| v.as_str()).unwrap_or("");
+                html.push_str(&format!(
+                    "<a href=\"/{}/tag/{}\">#{}</a>",
+                    blog_slug,
+                    tag_slug,
+                    html_escape(name)
+                ));
+            }
+            html.push_str("</div>");
+        }
+    }
+
+    if share_pos == "below_content" && !page_url.is_empty() {
+        html.push_str(&build_share_buttons(&settings, &page_url, title));
+    }
+    html.push_str(&build_related_posts(context, &blog_slug));
+
+    // Prev / Next navigation
+    let mut nav_html = String::new();
+    if let Some(prev) = context.get("prev_post") {
+        let prev_title = prev.get("title").and_then(|v| v.as_str()).unwrap_or("");
+        let prev_slug = prev.get("slug").and_then(|v| v.as_str()).unwrap_or("");
+        nav_html.push_str(&format!(
+            "<a href=\"/{}/{}\">&larr; {}</a>",
+            blog_slug,
+            prev_slug,
+            html_escape(prev_title)
+        ));
+    } else {
+        nav_html.push_str("<span></span>");
+    }
+    if let Some(next) = context.get("next_post") {
+        let next_title = next.get("title").and_then(|v| v.as_str()).unwrap_or("");
+        let next_slug = next.get("slug").and_then(|v| v.as_str()).unwrap_or("");
+        nav_html.push_str(&format!(
+            "<a href=\"/{}/{}\">{} &rarr;</a>",
+            blog_slug,
+            next_slug,
+            html_escape(next_title)
+        ));
+    }
+    if !nav_html.is_empty() {
+        html.push_str(&format!("<nav class=\"post-nav\">{}</nav>", nav_html));
+    }
+
+    if comments_on {
+        let post_id = post.get("id").and_then(|v| v.as_i64()).unwrap_or(0);
+        html.push_str(&build_classic_comments(context, &settings, post_id));
+    }
+
+    html.push_str("</article>");
+
+    // JSON-LD structured data
+    if settings.get("seo_structured_data").and_then(|v| v.as_str()) == Some("true") {
+        html.push_str(&crate::seo::jsonld::post_jsonld(post, &settings));
+    }
+
+    html
+}
+
+/// CSS for the Masthead front page and columns.
+pub fn list_css() -> &'static str {
+    r#"
+/* ── Masthead List ── */
+.mh-journal {
+    max-width: 1200px;
+    margin: 0 auto;
+    padding: 32px 20px;
+}
+.mh-kicker {
+    display: inline-block;
+    font-family: var(--font-captions);
+    font-size: 11px;
+    font-weight: 700;
+    text-transform: uppercase;
+    letter-spacing: 0.12em;
+    color: var(--color-accent);
+    text-decoration: none;
+    margin-bottom: 8px;
+}
+.mh-meta {
+    font-family: var(--font-captions);
+    font-size: 11px;
+    letter-spacing: 0.04em;
+    color: var(--color-text-secondary);
+    margin-top: 10px;
+}
+.mh-front {
+    display: grid;
+    grid-template-columns: 3fr 2fr;
+    gap: 40px;
+    align-items: center;
+    padding-bottom: 40px;
+    margin-bottom: 40px;
+    border-bottom: 3px double var(--color-text);
+}
+.mh-front.mh-no-image {
+    grid-template-columns: 1fr;
+    text-align: center;
+}
+.mh-lead-image img {
+    width: 100%;
+    height: auto;
+    display: block;
+    object-fit: cover;
+}
+.mh-lead-title {
+    font-family: var(--font-heading);
+    font-size: 40px;
+    line-height: 1.1;
+    font-weight: 700;
+    margin: 0 0 16px;
+}
+.mh-lead-title a,
+.mh-card-title a {
+    color: var(--color-text);
+    text-decoration: none;
+}
+.mh-lead-title a:hover,
+.mh-card-title a:hover {
+    text-decoration: underline;
+}
+.mh-standfirst {
+    font-size: 18px;
+    line-height: 1.6;
+    color: var(--color-text-secondary);
+    margin: 0;
+}
+.blog-list.mh-columns {
+    display: grid;
+    grid-template-columns: repeat(3, 1fr);
+    gap: 0;
+}
+.mh-card {
+    padding: 0 24px 32px;
+    border-left: 1px solid var(--color-border);
+}
+.mh-card:nth-child(3n+1) {
+    padding-left: 0;
+    border-left: none;
+}
+.mh-card:nth-child(3n) {
+    padding-right: 0;
+}
+.mh-card-image img {
+    width: 100%;
+    aspect-ratio: 3 / 2;
+    object-fit: cover;
+    display: block;
+    margin-bottom: 14px;
+}
+.mh-card-title {
+    font-family: var(--font-heading);
+    font-size: 20px;
+    line-height: 1.25;
+    margin: 0 0 10px;
+}
+.mh-card-excerpt {
+    font-size: 14px;
+    line-height: 1.7;
+    color: var(--color-text-secondary);
+    margin: 0;
+}
+
+@media (max-width: 900px) {
+    .mh-front {
+        grid-template-columns: 1fr;
+        gap: 20px;
+    }
+    .blog-list.mh-columns {
+        grid-template-columns: repeat(2, 1fr);
+    }
+    .mh-card,
+    .mh-card:nth-child(3n),
+    .mh-card:nth-child(3n+1) {
+        padding: 0 16px 28px;
+        border-left: none;
+    }
+}
+@media (max-width: 600px) {
+    .mh-lead-title {
+        font-size: 28px;
+    }
+    .blog-list.mh-columns {
+        grid-template-columns: 1fr;
+    }
+    .mh-card,
+    .mh-card:nth-child(3n),
+    .mh-card:nth-child(3n+1) {
+        padding: 0 0 24px;
+    }
+}
+"#
+}
+
+/// CSS for the Masthead single page.
+pub fn single_css() -> &'static str {
+    r#"
+/* ── Masthead Single ── */
+.mh-article {
+    max-width: 760px;
+    margin: 0 auto;
+    padding: 40px 20px;
+}
+.mh-article-head {
+    text-align: center;
+    padding-bottom: 24px;
+    margin-bottom: 32px;
+    border-bottom: 1px solid var(--color-text);
+}
+.mh-article-title {
+    font-family: var(--font-heading);
+    font-size: 44px;
+    line-height: 1.1;
+    font-weight: 700;
+    margin: 0 0 16px;
+}
+.mh-article-head .mh-standfirst {
+    margin: 0 auto 20px;
+    max-width: 620px;
+}
+.mh-byline {
+    font-family: var(--font-captions);
+    font-size: 12px;
+    letter-spacing: 0.04em;
+    color: var(--color-text-secondary);
+}
+.mh-article-hero {
+    margin: 0 0 32px;
+}
+.mh-article-hero img {
+    width: 100%;
+    height: auto;
+    display: block;
+}
+.mh-content {
+    font-size: 18px;
+    line-height: 1.8;
+    color: var(--color-text);
+}
+.mh-content > p:first-of-type::first-letter {
+    float: left;
+    font-family: var(--font-heading);
+    font-size: 3.6em;
+    line-height: 0.9;
+    font-weight: 700;
+    padding: 6px 8px 0 0;
+}
+.mh-content p {
+    margin-bottom: 1.2em;
+}
+.mh-content blockquote {
+    border-top: 1px solid var(--color-text);
+    border-bottom: 1px solid var(--color-text);
+    margin: 1.5em 0;
+    padding: 0.8em 0;
+    font-family: var(--font-heading);
+    font-size: 1.3em;
+    text-align: center;
+}
+.mh-content img {
+    max-width: 100%;
+    height: auto;
+}
+.mh-tags {
+    margin-top: 28px;
+    display: flex;
+    flex-wrap: wrap;
+    gap: 12px;
+}
+.mh-tags a {
+    font-family: var(--font-captions);
+    font-size: 12px;
+    color: var(--color-text-secondary);
+    text-decoration: none;
+}
+
+@media (max-width: 600px) {
+    .mh-article {
+        padding: 20px 16px;
+    }
+    .mh-article-title {
+        font-size: 30px;
+    }
+    .mh-content {
+        font-size: 16px;
+    }
+}
+"#
+}
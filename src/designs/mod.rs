@@ -1,4 +1,14 @@
+pub mod check;
 pub mod common;
 pub mod contact;
+pub mod custom_code;
+pub mod error_pages;
+pub mod history;
+pub mod homepage;
+pub mod inheritance;
 pub mod inkwell;
+pub mod masthead;
 pub mod oneguy;
+pub mod package;
+pub mod preview;
+pub mod templates;
@@ -2,7 +2,8 @@ use serde_json::Value;
 
 use crate::designs::common::{author_initials, build_classic_comments, name_hue};
 use crate::render::{
-    build_share_buttons, count_words_html, format_date, format_date_iso8601, html_escape,
+    author_link, build_related_posts, build_share_buttons, count_words_html, format_date,
+    html_escape,
 };
 
 /// Render the blog single page in the Classic style.
@@ -135,9 +136,10 @@ pub fn render_single(context: &Value) -> String {
             hue,
             html_escape(&initials),
             html_escape(&written_by_label),
-            html_escape(author),
+            author_link(post, author),
         ));
     }
+    html.push_str(&build_related_posts(context, &blog_slug));
 
     // Prev / Next navigation
     let mut nav_html = String::new();
@@ -182,62 +184,7 @@ pub fn render_single(context: &Value) -> String {
 
     // JSON-LD structured data
     if settings.get("seo_structured_data").and_then(|v| v.as_str()) == Some("true") {
-        let site_name = sg("site_name", "Velocty");
-        let site_url_ld = sg("site_url", "http://localhost:8000");
-        let slug = post.get("slug").and_then(|v| v.as_str()).unwrap_or("");
-        let headline = post.get("title").and_then(|v| v.as_str()).unwrap_or("");
-        let desc = post
-            .get("meta_description")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let raw_pub = post
-            .get("published_at")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let raw_mod = post
-            .get("updated_at")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let published = format_date_iso8601(raw_pub, &settings);
-        let modified = format_date_iso8601(raw_mod, &settings);
-        let image = post
-            .get("featured_image")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let mut ld = format!(
-            r#"<script type="application/ld+json">
-{{
-    "@context": "https://schema.org",
-    "@type": "BlogPosting",
-    "headline": "{}",
-    "description": "{}",
-    "url": "{}/{}/{}",
-    "datePublished": "{}",
-    "dateModified": "{}",
-    "publisher": {{ "@type": "Organization", "name": "{}" }}"#,
-            html_escape(headline),
-            html_escape(desc),
-            site_url_ld,
-            blog_slug,
-            slug,
-            published,
-            modified,
-            html_escape(&site_name),
-        );
-        if !image.is_empty() {
-            ld.push_str(&format!(
-                ",\n    \"image\": \"{}/uploads/{}\"",
-                site_url_ld, image
-            ));
-        }
-        if !author.is_empty() {
-            ld.push_str(&format!(
-                ",\n    \"author\": {{ \"@type\": \"Person\", \"name\": \"{}\" }}",
-                html_escape(author)
-            ));
-        }
-        ld.push_str("\n}\n</script>");
-        html.push_str(&ld);
+        html.push_str(&crate::seo::jsonld::post_jsonld(post, &settings));
     }
 
     html
@@ -2,8 +2,8 @@ use serde_json::Value;
 
 use crate::designs::common::build_classic_comments;
 use crate::render::{
-    build_pagination, build_share_buttons, count_words_html, format_date, format_date_iso8601,
-    html_escape, strip_html_to_text, truncate_words,
+    author_link, build_pagination, build_related_posts, build_share_buttons, count_words_html,
+    format_date, html_escape, strip_html_to_text, truncate_words,
 };
 
 /// Render the blog list page in the Editorial style.
@@ -109,7 +109,7 @@ pub fn render_list(context: &Value) -> String {
         {
             let mut author_rt: Vec<String> = Vec::new();
             if !author_name.is_empty() {
-                author_rt.push(html_escape(author_name));
+                author_rt.push(author_link(post, author_name));
             }
             if show_reading_time && word_count > 0 {
                 author_rt.push(format!("{} min read", reading_time));
@@ -404,6 +404,7 @@ pub fn render_single(context: &Value) -> String {
     if share_pos == "below_content" && !page_url.is_empty() {
         html.push_str(&build_share_buttons(&settings, &page_url, title));
     }
+    html.push_str(&build_related_posts(context, &blog_slug));
 
     // Prev / Next navigation
     let mut nav_html = String::new();
@@ -460,7 +461,7 @@ pub fn render_single(context: &Value) -> String {
     if show_author && !author.is_empty() {
         html.push_str(&format!(
             "<div class=\"bes-sidebar-item\"><span class=\"bes-sidebar-label\">{}</span></div>",
-            html_escape(author)
+            author_link(post, author)
         ));
     }
 
@@ -529,62 +530,7 @@ pub fn render_single(context: &Value) -> String {
 
     // JSON-LD structured data
     if settings.get("seo_structured_data").and_then(|v| v.as_str()) == Some("true") {
-        let site_name = sg("site_name", "Velocty");
-        let site_url_ld = sg("site_url", "http://localhost:8000");
-        let slug = post.get("slug").and_then(|v| v.as_str()).unwrap_or("");
-        let headline = post.get("title").and_then(|v| v.as_str()).unwrap_or("");
-        let desc = post
-            .get("meta_description")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let raw_pub = post
-            .get("published_at")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let raw_mod = post
-            .get("updated_at")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let published = format_date_iso8601(raw_pub, &settings);
-        let modified = format_date_iso8601(raw_mod, &settings);
-        let image = post
-            .get("featured_image")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let mut ld = format!(
-            r#"<script type="application/ld+json">
-{{
-    "@context": "https://schema.org",
-    "@type": "BlogPosting",
-    "headline": "{}",
-    "description": "{}",
-    "url": "{}/{}/{}",
-    "datePublished": "{}",
-    "dateModified": "{}",
-    "publisher": {{ "@type": "Organization", "name": "{}" }}"#,
-            html_escape(headline),
-            html_escape(desc),
-            site_url_ld,
-            blog_slug,
-            slug,
-            published,
-            modified,
-            html_escape(&site_name),
-        );
-        if !image.is_empty() {
-            ld.push_str(&format!(
-                ",\n    \"image\": \"{}/uploads/{}\"",
-                site_url_ld, image
-            ));
-        }
-        if !author.is_empty() {
-            ld.push_str(&format!(
-                ",\n    \"author\": {{ \"@type\": \"Person\", \"name\": \"{}\" }}",
-                html_escape(author)
-            ));
-        }
-        ld.push_str("\n}\n</script>");
-        html.push_str(&ld);
+        html.push_str(&crate::seo::jsonld::post_jsonld(post, &settings));
     }
 
     html
@@ -9,6 +9,13 @@ use crate::render::{
     render_404, slug_url,
 };
 
+/// Fragment that opens an item in the lightbox when the grid loads, e.g.
+/// `item-sunset` for `/portfolio#item-sunset`. The grid item carries it as
+/// its id, so without scripts the link still scrolls to the item.
+pub fn item_anchor(slug: &str) -> String {
+    format!("item-{}", slug)
+}
+
 /// Render the portfolio grid/masonry listing page.
 pub fn render_grid(context: &Value) -> String {
     let items = match context.get("items") {
@@ -271,13 +278,15 @@ pub fn render_grid(context: &Value) -> String {
         };
 
         html.push_str(&format!(
-            r#"<div class="{item_class}" data-categories="{cats_data}" data-price="{price}" data-sell="{sell}"{media_type}>
+            r#"<div class="{item_class}" id="{anchor}" data-slug="{slug}" data-categories="{cats_data}" data-price="{price}" data-sell="{sell}"{media_type}>
     <a href="{item_url}" class="portfolio-link" data-id="{item_id}" data-title="{title}" data-likes="{likes}" data-tags="{tag_data}" style="position:relative;display:block">
         {media_tag}
         {price_badge}
         {heart_overlay}
     </a>"#,
             item_class = item_class_str,
+            anchor = html_escape(&item_anchor(slug)),
+            slug = html_escape(slug),
             cats_data = cats_data,
             item_url = item_url,
             item_id = item_id,
@@ -384,7 +393,7 @@ pub fn render_grid(context: &Value) -> String {
                      Load More</button></div>",
                     current_page + 1, total_pages
                 ));
-                html.push_str(LOAD_MORE_JS);
+                html.push_str(&crate::render::nonce_scripts(LOAD_MORE_JS));
             }
             "infinite" => {
                 html.push_str(&format!(
@@ -393,7 +402,7 @@ pub fn render_grid(context: &Value) -> String {
                     current_page + 1,
                     total_pages
                 ));
-                html.push_str(INFINITE_SCROLL_JS);
+                html.push_str(&crate::render::nonce_scripts(INFINITE_SCROLL_JS));
             }
             _ => {
                 // Classic pagination
@@ -791,43 +800,7 @@ pub fn render_single(context: &Value) -> String {
 
     // JSON-LD structured data
     if settings.get("seo_structured_data").and_then(|v| v.as_str()) == Some("true") {
-        let site_name = settings
-            .get("site_name")
-            .and_then(|v| v.as_str())
-            .unwrap_or("Velocty");
-        let site_url = settings
-            .get("site_url")
-            .and_then(|v| v.as_str())
-            .unwrap_or("http://localhost:8000");
-        let portfolio_slug = settings
-            .get("portfolio_slug")
-            .and_then(|v| v.as_str())
-            .unwrap_or("portfolio");
-        let slug = item.get("slug").and_then(|v| v.as_str()).unwrap_or("");
-        let meta_desc = item
-            .get("meta_description")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        html.push_str(&format!(
-            r#"<script type="application/ld+json">
-{{
-    "@context": "https://schema.org",
-    "@type": "ImageObject",
-    "name": "{}",
-    "description": "{}",
-    "contentUrl": "{}/uploads/{}",
-    "url": "{}{}",
-    "publisher": {{ "@type": "Organization", "name": "{}" }}
-}}
-</script>"#,
-            html_escape(title),
-            html_escape(meta_desc),
-            site_url,
-            image,
-            site_url,
-            slug_url(portfolio_slug, slug),
-            html_escape(site_name),
-        ));
+        html.push_str(&crate::seo::jsonld::portfolio_jsonld(item, &settings));
     }
 
     html
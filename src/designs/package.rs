@@ -0,0 +1,470 @@
+//! Design packages: a design's shell, CSS, page templates and look-related
+//! settings as a zip, for sharing designs between installs.
+//!
+//! Layout:
+//! - `manifest.json` — format, version, name, slug, description, parent
+//! - `layout.html`, `style.css` — the design shell
+//! - `templates/<type>/layout.html`, `style.css`, `grapesjs.json`
+//! - `settings.json` — look-related settings (see `is_design_setting`)
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Write};
+use std::sync::OnceLock;
+
+use serde_json::{json, Value};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use crate::store::Store;
+
+pub const FORMAT: &str = "velocty-design";
+pub const VERSION: i64 = 1;
+
+/// Largest file accepted inside a package
+const MAX_ENTRY_BYTES: u64 = 5 * 1024 * 1024;
+const MAX_ENTRIES: usize = 200;
+
+/// Settings that make up a design's look. Site content, credentials and
+/// behaviour settings never travel with a package.
+const SETTING_PREFIXES: &[&str] = &[
+    "color_",
+    "font_",
+    "layout_margin_",
+    "layout_content_boundary",
+    "layout_header_type",
+    "layout_sidebar_position",
+    "design_",
+    "footer_alignment",
+    "footer_behavior",
+    "copyright_alignment",
+    "nav_position",
+    "blog_display_type",
+    "blog_grid_columns",
+    "blog_list_style",
+    "portfolio_display_type",
+    "portfolio_grid_columns",
+    "portfolio_border_style",
+    "portfolio_fade_animation",
+    "portfolio_lightbox_",
+    "commerce_button_",
+    "site_accent_color",
+    "site_background_color",
+    "site_text_color",
+    "site_text_secondary_color",
+    "site_logo_position",
+    "site_logo_width",
+    "share_icons_position",
+    "social_icons_position",
+    "search_nav_position",
+    "contact_alignment",
+    "contact_layout",
+];
+
+pub fn is_design_setting(key: &str) -> bool {
+    SETTING_PREFIXES.iter().any(|p| key.starts_with(p))
+        && !crate::security::secrets::is_secret_key(key)
+}
+
+/// Page structure a design may use on top of ammonia's default allowlist.
+/// Script, embedding and form elements stay out.
+const LAYOUT_TAGS: &[&str] = &[
+    "main", "section", "picture", "source", "video", "audio", "button",
+];
+
+fn fragment_cleaner() -> &'static ammonia::Builder<'static> {
+    static CLEANER: OnceLock<ammonia::Builder<'static>> = OnceLock::new();
+    CLEANER.get_or_init(|| {
+        let mut b = ammonia::Builder::default();
+        b.add_tags(LAYOUT_TAGS)
+            .add_generic_attributes(["class", "id", "style", "role", "dir"])
+            .add_generic_attribute_prefixes(["data-", "aria-"])
+            .add_tag_attributes("a", ["target"])
+            .add_tag_attributes("img", ["loading", "srcset", "sizes", "decoding"])
+            .add_tag_attributes("source", ["src", "srcset", "type", "media", "sizes"])
+            .add_tag_attributes(
+                "video",
+                [
+                    "src",
+                    "poster",
+                    "controls",
+                    "autoplay",
+                    "muted",
+                    "loop",
+                    "playsinline",
+                ],
+            )
+            .add_tag_attributes("audio", ["src", "controls", "loop"])
+            .add_tag_attributes("button", ["type"]);
+        b
+    })
+}
+
+fn head_cleaner() -> &'static ammonia::Builder<'static> {
+    static CLEANER: OnceLock<ammonia::Builder<'static>> = OnceLock::new();
+    CLEANER.get_or_init(|| {
+        let mut b = ammonia::Builder::empty();
+        b.tags(["meta", "link", "title", "style"].into_iter().collect())
+            .clean_content_tags(["script"].into_iter().collect())
+            .add_tag_attributes("meta", ["charset", "name", "property", "content"])
+            .add_tag_attributes("link", ["href", "type", "sizes", "media", "crossorigin"])
+            .add_tag_attribute_values(
+                "link",
+                "rel",
+                [
+                    "stylesheet",
+                    "icon",
+                    "shortcut icon",
+                    "apple-touch-icon",
+                    "preconnect",
+                    "canonical",
+                    "alternate",
+                    "manifest",
+                ],
+            )
+            .url_schemes(["http", "https"].into_iter().collect())
+            .url_relative(ammonia::UrlRelative::PassThrough)
+            .link_rel(None);
+        b
+    })
+}
+
+/// Clean shared HTML against an allowlist: script and embedding elements,
+/// `on*` handlers and non-web URLs (however they're encoded) are dropped.
+/// The `{{placeholder}}` tags the renderer fills in are plain text and
+/// are left alone.
+pub fn sanitize_html(html: &str) -> String {
+    fragment_cleaner().clean(html).to_string()
+}
+
+/// A design shell is a whole document with placeholders inside its
+/// `<html>` and `<body>` tags, which a fragment cleaner would throw away.
+/// The head and body are cleaned separately and the document frame is
+/// rebuilt around them, keeping only `lang`, `class`, `id` and bare
+/// `{{placeholder}}` attributes. Input without a `<body>` is a fragment.
+pub fn sanitize_shell(html: &str) -> String {
+    let body_re = regex::Regex::new(r"(?is)<body\b([^>]*)>(.*?)(?:</body\s*>|$)").unwrap();
+    let Some(body) = body_re.captures(html) else {
+        return sanitize_html(html);
+    };
+    let head = regex::Regex::new(r"(?is)<head\b[^>]*>(.*?)</head\s*>")
+        .unwrap()
+        .captures(html)
+        .map(|c| head_cleaner().clean(&c[1]).to_string())
+        .unwrap_or_default();
+    let lang = regex::Regex::new(r#"(?is)<html\b[^>]*\blang\s*=\s*["']?([A-Za-z0-9-]{1,35})"#)
+        .unwrap()
+        .captures(html)
+        .map(|c| format!(" lang=\"{}\"", &c[1]))
+        .unwrap_or_default();
+    format!(
+        "<!DOCTYPE html>\n<html{}>\n<head>\n{}\n</head>\n<body{}>\n{}\n</body>\n</html>",
+        lang,
+        head.trim(),
+        shell_body_attrs(&body[1]),
+        sanitize_html(&body[2]).trim()
+    )
+}
+
+fn shell_body_attrs(raw: &str) -> String {
+    let quoted = regex::Regex::new(r#"(?is)\b(class|id)\s*=\s*"([^"]*)""#).unwrap();
+    let mut out = String::new();
+    for c in quoted.captures_iter(raw) {
+        let value = c[2]
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        out.push_str(&format!(" {}=\"{}\"", c[1].to_lowercase(), value));
+    }
+    let rest = regex::Regex::new(r#"(?s)"[^"]*"|'[^']*'"#)
+        .unwrap()
+        .replace_all(raw, "");
+    for p in regex::Regex::new(r"\{\{\s*[a-z_]+\s*\}\}")
+        .unwrap()
+        .find_iter(&rest)
+    {
+        out.push(' ');
+        out.push_str(p.as_str());
+    }
+    out
+}
+
+/// Strip CSS that can run script or break out of its `<style>` block.
+pub fn sanitize_css(css: &str) -> String {
+    let patterns = [
+        r"(?i)</\s*style",
+        r"(?i)<!--|-->",
+        r"(?i)expression\s*\(",
+        r"(?i)-moz-binding\s*:",
+        r"(?i)behavior\s*:",
+        r"(?i)javascript\s*:",
+        r"(?i)vbscript\s*:",
+    ];
+    let mut out = css.to_string();
+    for p in patterns {
+        if let Ok(re) = regex::Regex::new(p) {
+            out = re.replace_all(&out, "").into_owned();
+        }
+    }
+    out
+}
+
+/// GrapesJS project data must be JSON; any markup inside it is sanitized
+/// like a template.
+fn sanitize_grapesjs(raw: &str) -> Result<String, String> {
+    if raw.trim().is_empty() {
+        return Ok(String::new());
+    }
+    fn walk(v: &mut Value) {
+        match v {
+            Value::String(s) if s.contains('<') => *s = sanitize_html(s),
+            Value::String(s) if s.contains(':') => *s = sanitize_css(s),
+            Value::Array(items) => items.iter_mut().for_each(walk),
+            Value::Object(map) => map.values_mut().for_each(walk),
+            _ => {}
+        }
+    }
+    let mut data: Value =
+        serde_json::from_str(raw).map_err(|e| format!("Invalid GrapesJS data: {}", e))?;
+    walk(&mut data);
+    serde_json::to_string(&data).map_err(|e| e.to_string())
+}
+
+/// Package a design as a zip. Returns (file name, bytes).
+pub fn export_zip(store: &dyn Store, design_id: i64) -> Result<(String, Vec<u8>), String> {
+    let design = store
+        .design_find_by_id(design_id)
+        .ok_or("Design not found")?;
+    let parent = design
+        .parent_id
+        .and_then(|pid| store.design_find_by_id(pid))
+        .map(|p| p.slug);
+    let manifest = json!({
+        "format": FORMAT,
+        "version": VERSION,
+        "name": design.name,
+        "slug": design.slug,
+        "description": design.description,
+        "parent": parent,
+        "exported_at": chrono::Utc::now().to_rfc3339(),
+    });
+    let settings: HashMap<String, String> = store
+        .setting_all()
+        .into_iter()
+        .filter(|(k, _)| is_design_setting(k))
+        .collect();
+
+    let mut buf = Cursor::new(Vec::new());
+    {
+        let mut zip = ZipWriter::new(&mut buf);
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let mut add = |name: &str, data: &[u8]| -> Result<(), String> {
+            zip.start_file(name, options).map_err(|e| e.to_string())?;
+            zip.write_all(data).map_err(|e| e.to_string())
+        };
+        add(
+            "manifest.json",
+            serde_json::to_string_pretty(&manifest)
+                .unwrap_or_default()
+                .as_bytes(),
+        )?;
+        add("layout.html", design.layout_html.as_bytes())?;
+        add("style.css", design.style_css.as_bytes())?;
+        add(
+            "settings.json",
+            serde_json::to_string_pretty(&settings)
+                .unwrap_or_default()
+                .as_bytes(),
+        )?;
+        for t in store.design_template_for_design(design.id) {
+            let dir = format!("templates/{}", t.template_type);
+            add(&format!("{}/layout.html", dir), t.layout_html.as_bytes())?;
+            add(&format!("{}/style.css", dir), t.style_css.as_bytes())?;
+            if !t.grapesjs_data.is_empty() {
+                add(
+                    &format!("{}/grapesjs.json", dir),
+                    t.grapesjs_data.as_bytes(),
+                )?;
+            }
+        }
+        zip.finish().map_err(|e| e.to_string())?;
+    }
+    let slug = if design.slug.is_empty() {
+        crate::models::design::Design::slugify(&design.name)
+    } else {
+        design.slug.clone()
+    };
+    Ok((format!("velocty-design-{}.zip", slug), buf.into_inner()))
+}
+
+/// What an imported package became
+pub struct ImportedDesign {
+    pub id: i64,
+    pub name: String,
+    pub templates: usize,
+    pub settings_applied: usize,
+}
+
+/// Read every file in the package as text, refusing oversized or
+/// unexpected entries.
+fn read_entries(bytes: &[u8]) -> Result<HashMap<String, String>, String> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Invalid ZIP: {}", e))?;
+    if archive.len() > MAX_ENTRIES {
+        return Err("The package has too many files".into());
+    }
+    let mut files = HashMap::new();
+    for i in 0..archive.len() {
+        let file = archive.by_index(i).map_err(|e| e.to_string())?;
+        if file.is_dir() {
+            continue;
+        }
+        let name = file.name().trim_start_matches("./").to_string();
+        if name.contains("..") || name.starts_with('/') || name.contains('\\') {
+            return Err(format!("Unsafe path in package: {}", name));
+        }
+        if file.size() > MAX_ENTRY_BYTES {
+            return Err(format!("{} is too large", name));
+        }
+        let mut text = String::new();
+        file.take(MAX_ENTRY_BYTES)
+            .read_to_string(&mut text)
+            .map_err(|_| format!("{} isn't a text file", name))?;
+        files.insert(name, text);
+    }
+    Ok(files)
+}
+
+/// A slug no existing design uses, based on `wanted`
+fn free_slug(store: &dyn Store, wanted: &str) -> String {
+    let base = match crate::models::design::Design::slugify(wanted) {
+        s if s.is_empty() => "design".to_string(),
+        s => s,
+    };
+    let mut slug = base.clone();
+    let mut n = 2;
+    while store.design_find_by_slug(&slug).is_some() {
+        slug = format!("{}-{}", base, n);
+        n += 1;
+    }
+    slug
+}
+
+/// Create a new (inactive) design from a package. Markup and CSS are
+/// sanitized; its settings are applied only when `apply_settings` is set,
+/// since they change the live site's look.
+pub fn import_zip(
+    store: &dyn Store,
+    bytes: &[u8],
+    apply_settings: bool,
+) -> Result<ImportedDesign, String> {
+    let files = read_entries(bytes)?;
+    let manifest: Value = files
+        .get("manifest.json")
+        .ok_or("Not a design package: manifest.json is missing")
+        .and_then(|m| {
+            serde_json::from_str(m).map_err(|_| "Not a design package: invalid manifest.json")
+        })?;
+    if manifest.get("format").and_then(|v| v.as_str()) != Some(FORMAT) {
+        return Err("Not a design package".into());
+    }
+    let version = manifest
+        .get("version")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    if version > VERSION {
+        return Err(format!(
+            "This package needs a newer version of Velocty (format {})",
+            version
+        ));
+    }
+    let name = manifest
+        .get("name")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .ok_or("The package has no design name")?;
+    let name: String = name.chars().take(100).collect();
+    let wanted_slug = manifest
+        .get("slug")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .unwrap_or(&name);
+
+    // Validate the templates before creating anything
+    let mut templates: Vec<(String, String, String, String)> = Vec::new();
+    let mut types: Vec<&str> = files
+        .keys()
+        .filter_map(|path| path.strip_prefix("templates/"))
+        .filter_map(|rest| rest.split_once('/').map(|(t, _)| t))
+        .collect();
+    types.sort();
+    types.dedup();
+    for t in types {
+        if t.is_empty()
+            || !t
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(format!("Invalid template name: {}", t));
+        }
+        let get = |file: &str| {
+            files
+                .get(&format!("templates/{}/{}", t, file))
+                .cloned()
+                .unwrap_or_default()
+        };
+        templates.push((
+            t.to_string(),
+            sanitize_shell(&get("layout.html")),
+            sanitize_css(&get("style.css")),
+            sanitize_grapesjs(&get("grapesjs.json"))?,
+        ));
+    }
+    let settings: HashMap<String, String> = match files.get("settings.json") {
+        Some(s) => serde_json::from_str(s).map_err(|_| "Invalid settings.json")?,
+        None => HashMap::new(),
+    };
+
+    let layout_html = sanitize_shell(files.get("layout.html").map_or("", |s| s.as_str()));
+    let style_css = sanitize_css(files.get("style.css").map_or("", |s| s.as_str()));
+    let slug = free_slug(store, wanted_slug);
+    let id = store.design_create(&name)?;
+    store.design_update_full(id, &slug, &layout_html, &style_css)?;
+    for (t, layout, css, grapesjs) in &templates {
+        store.design_template_upsert_full(id, t, layout, css, grapesjs)?;
+    }
+    // A child design keeps its parent when this install has it
+    if let Some(parent) = manifest
+        .get("parent")
+        .and_then(|v| v.as_str())
+        .and_then(|slug| store.design_find_by_slug(slug))
+    {
+        store.design_set_parent(id, Some(parent.id))?;
+    }
+
+    let mut settings_applied = 0;
+    if apply_settings {
+        let design_settings: HashMap<String, String> = settings
+            .into_iter()
+            .filter(|(k, v)| is_design_setting(k) && v.len() <= 10_000)
+            .map(|(k, v)| {
+                let v = if k.starts_with("font_") || k.starts_with("color_") {
+                    sanitize_css(&v)
+                } else {
+                    v
+                };
+                (k, v)
+            })
+            .collect();
+        settings_applied = design_settings.len();
+        store.setting_set_many(&design_settings)?;
+    }
+
+    Ok(ImportedDesign {
+        id,
+        name,
+        templates: templates.len(),
+        settings_applied,
+    })
+}
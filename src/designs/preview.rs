@@ -0,0 +1,49 @@
+//! Design preview: an admin can browse the public site with a design that
+//! isn't active yet, to check it against real content before switching.
+//! The choice lives in a private cookie and only counts while the visitor
+//! is signed in with permission to manage designs.
+
+use rocket::http::{Cookie, CookieJar, SameSite};
+use rocket::request::{FromRequest, Outcome, Request};
+
+use crate::security::auth::DesignManager;
+
+pub const COOKIE: &str = "velocty_design_preview";
+
+/// Start previewing design `id` in this browser
+pub fn set_cookie(cookies: &CookieJar<'_>, id: i64) {
+    let mut cookie = Cookie::new(COOKIE, id.to_string());
+    cookie.set_http_only(true);
+    cookie.set_same_site(SameSite::Lax);
+    cookie.set_path("/");
+    cookie.set_max_age(rocket::time::Duration::hours(12));
+    cookies.add_private(cookie);
+}
+
+/// Stop previewing and go back to the active design
+pub fn clear_cookie(cookies: &CookieJar<'_>) {
+    cookies.remove_private(Cookie::from(COOKIE));
+}
+
+/// Request guard: the design the signed-in admin is previewing. None for
+/// everyone else, so the cookie alone never changes what a visitor sees.
+pub struct DesignPreview(pub Option<i64>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for DesignPreview {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let id = request
+            .cookies()
+            .get_private(COOKIE)
+            .and_then(|c| c.value().parse::<i64>().ok());
+        if id.is_none() {
+            return Outcome::Success(DesignPreview(None));
+        }
+        match request.guard::<DesignManager>().await {
+            Outcome::Success(_) => Outcome::Success(DesignPreview(id)),
+            _ => Outcome::Success(DesignPreview(None)),
+        }
+    }
+}
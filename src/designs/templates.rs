@@ -0,0 +1,145 @@
+//! Per-content templates: alternative layouts a post or portfolio item can
+//! pick. Every design offers the common ones and a bundled design can
+//! contribute more of its own for posts.
+
+use serde_json::Value;
+
+use crate::render::html_escape;
+use crate::store::Store;
+
+/// Templates every design offers, as (key, label)
+pub const COMMON_TEMPLATES: &[(&str, &str)] = &[
+    ("full_width", "Full Width"),
+    ("no_sidebar", "No Sidebar"),
+    ("landing", "Landing Page"),
+];
+
+/// Slugs of the designs that ship with Velocty
+const BUNDLED_DESIGNS: &[&str] = &["oneguy", "inkwell", "masthead"];
+
+/// Post templates a bundled design adds to the common ones
+fn design_templates(base_slug: &str) -> &'static [(&'static str, &'static str)] {
+    match base_slug {
+        "oneguy" => &[("wide", "Wide Article")],
+        "masthead" => &[("feature", "Feature Story")],
+        _ => &[],
+    }
+}
+
+/// Every template the design rooted at `base_slug` offers for
+/// `content_type` ("post" or "portfolio"), as (key, label)
+pub fn available(base_slug: &str, content_type: &str) -> Vec<(&'static str, &'static str)> {
+    let extra = if content_type == "post" {
+        design_templates(base_slug)
+    } else {
+        &[]
+    };
+    COMMON_TEMPLATES.iter().chain(extra).copied().collect()
+}
+
+/// The templates the active design offers for `content_type`, for the
+/// editor's selector
+pub fn for_active_design(
+    store: &dyn Store,
+    content_type: &str,
+) -> Vec<(&'static str, &'static str)> {
+    match store.design_active() {
+        Some(d) => available(&super::inheritance::base_slug(store, d), content_type),
+        None => COMMON_TEMPLATES.to_vec(),
+    }
+}
+
+/// Clean up a submitted template choice. Blank means the default layout.
+/// Keys any bundled design offers are kept, so content keeps its choice
+/// when the site switches to a design that doesn't offer it and gets it
+/// back when switching back.
+pub fn normalize(raw: Option<&str>, content_type: &str) -> Option<String> {
+    let key = raw.map(str::trim).filter(|k| !k.is_empty())?;
+    let known = BUNDLED_DESIGNS
+        .iter()
+        .any(|s| available(s, content_type).iter().any(|(k, _)| *k == key));
+    known.then(|| key.to_string())
+}
+
+/// The template the content on this page asked for, when the design
+/// rooted at `base_slug` offers it
+pub fn chosen(template_type: &str, context: &Value, base_slug: &str) -> Option<&'static str> {
+    let (record, content_type) = match template_type {
+        "blog_single" => (context.get("post")?, "post"),
+        "portfolio_single" => (context.get("item")?, "portfolio"),
+        _ => return None,
+    };
+    let key = record.get("template")?.as_str()?;
+    available(base_slug, content_type)
+        .into_iter()
+        .map(|(k, _)| k)
+        .find(|k| *k == key)
+}
+
+/// Body class for a template, e.g. `template-full-width`
+pub fn body_class(key: &str) -> String {
+    format!("template-{}", key.replace('_', "-"))
+}
+
+/// A landing page: just the title and the content, without the byline,
+/// comments or related posts
+pub fn render_landing(template_type: &str, context: &Value) -> String {
+    let (record, body) = match template_type {
+        "portfolio_single" => {
+            let item = match context.get("item") {
+                Some(i) => i,
+                None => return String::new(),
+            };
+            let image = item
+                .get("image_path")
+                .and_then(|v| v.as_str())
+                .filter(|p| !p.is_empty())
+                .map(|p| {
+                    format!(
+                        "<figure class=\"landing-hero\"><img src=\"/uploads/{}\" alt=\"\"></figure>",
+                        html_escape(p)
+                    )
+                })
+                .unwrap_or_default();
+            let desc = item
+                .get("description_html")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            (item, format!("{}{}", image, desc))
+        }
+        _ => {
+            let post = match context.get("post") {
+                Some(p) => p,
+                None => return String::new(),
+            };
+            let content = post
+                .get("content_html")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+            (post, content)
+        }
+    };
+    let title = record.get("title").and_then(|v| v.as_str()).unwrap_or("");
+    format!(
+        "<article class=\"landing\"><h1 class=\"landing-title\">{}</h1><div class=\"landing-content\">{}</div></article>",
+        html_escape(title),
+        body
+    )
+}
+
+pub const CSS: &str = r#"
+body.template-full-width .content-column, body.template-full-width .topbar-page, body.template-full-width .content { max-width: none; }
+body.template-no-sidebar .sidebar, body.template-no-sidebar .mobile-header { display: none; }
+body.template-no-sidebar .content-column { margin-left: 0; margin-right: 0; max-width: none; }
+body.template-landing .sidebar, body.template-landing .mobile-header, body.template-landing .topbar, body.template-landing .site-footer, body.template-landing .breadcrumbs { display: none; }
+body.template-landing .content-column { margin-left: 0; margin-right: 0; max-width: none; }
+.landing { max-width: 860px; margin: 0 auto; padding: 48px 24px; }
+.landing-title { font-family: var(--font-heading); font-size: 2.6em; line-height: 1.15; margin: 0 0 24px; text-align: center; }
+.landing-hero { margin: 0 0 32px; }
+.landing-hero img { width: 100%; height: auto; display: block; }
+body.template-feature .mh-article-title { font-size: 3.4em; text-align: center; }
+body.template-feature .mh-article-head { text-align: center; }
+body.template-feature .mh-article-hero { max-width: none; margin-left: calc(50% - 50vw); margin-right: calc(50% - 50vw); }
+body.template-feature .mh-article-hero img { width: 100%; max-height: 70vh; object-fit: cover; }
+"#;
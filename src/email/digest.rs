@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+
+use crate::models::analytics::CountEntry;
+use crate::store::Store;
+
+/// Weekly summary figures for the admin digest.
+#[derive(Debug, Default)]
+pub struct DigestStats {
+    pub from: String,
+    pub to: String,
+    pub views: i64,
+    pub unique_visitors: i64,
+    pub top_posts: Vec<CountEntry>,
+    pub top_portfolio: Vec<CountEntry>,
+    pub comments_pending: i64,
+    pub revenue: f64,
+    pub currency: String,
+    pub firewall_events: i64,
+}
+
+/// Whether the weekly digest should go out now: enabled, on the configured
+/// weekday (`digest_day`, "mon".."sun"), at or after `digest_hour` (UTC), and
+/// not already sent within the last six days.
+pub fn is_due(settings: &HashMap<String, String>, now: NaiveDateTime) -> bool {
+    if settings.get("digest_enabled").map(|v| v.as_str()) != Some("true") {
+        return false;
+    }
+    let day: String = settings
+        .get("digest_day")
+        .map(|d| d.to_lowercase())
+        .unwrap_or_else(|| "mon".to_string())
+        .chars()
+        .take(3)
+        .collect();
+    if now.weekday().to_string().to_lowercase() != day {
+        return false;
+    }
+    let hour = settings
+        .get("digest_hour")
+        .and_then(|h| h.trim().parse::<u32>().ok())
+        .unwrap_or(8)
+        .min(23);
+    if now.time() < chrono::NaiveTime::from_hms_opt(hour, 0, 0).unwrap_or_default() {
+        return false;
+    }
+    match settings
+        .get("digest_last_sent")
+        .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    {
+        Some(last) => now.date() - last >= Duration::days(6),
+        None => true,
+    }
+}
+
+/// Collect the last seven days of activity from the Store.
+pub fn collect(store: &dyn Store, now: NaiveDateTime) -> DigestStats {
+    let from = (now - Duration::days(7))
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    let to = now.format("%Y-%m-%d %H:%M:%S").to_string();
+    let overview = store.analytics_overview(&from, &to);
+
+    let blog_slug = store.setting_get_or("blog_slug", "journal");
+    let portfolio_prefix = format!("/{}/", store.setting_get_or("portfolio_slug", "portfolio"));
+    let posts_prefix = if blog_slug.is_empty() {
+        "/".to_string()
+    } else {
+        format!("/{}/", blog_slug)
+    };
+    // A blog mounted at the site root matches every path, so drop non-post pages
+    let top_posts = store
+        .analytics_top_paths(&posts_prefix, &from, &to, 10)
+        .into_iter()
+        .filter(|e| e.label != "/" && !e.label.starts_with(&portfolio_prefix))
+        .take(5)
+        .collect();
+
+    DigestStats {
+        views: overview.total_views,
+        unique_visitors: overview.unique_visitors,
+        top_posts,
+        top_portfolio: store.analytics_top_portfolio(&from, &to, 5),
+        comments_pending: store.comment_count(Some("pending")),
+        revenue: store.order_revenue_by_period(7),
+        currency: store.setting_get_or("commerce_currency", "USD"),
+        firewall_events: store.fw_event_count_since_hours(24 * 7),
+        from,
+        to,
+    }
+}
+
+/// Render the digest as a plain-text email body.
+pub fn render(site_name: &str, site_url: &str, stats: &DigestStats) -> String {
+    let mut body = format!(
+        "Weekly summary for {} ({} to {})\n\n\
+         Page views: {}\n\
+         Unique visitors: {}\n\
+         Comments awaiting moderation: {}\n\
+         Sales revenue: {} {:.2}\n\
+         Firewall events: {}\n",
+        site_name,
+        &stats.from[..10.min(stats.from.len())],
+        &stats.to[..10.min(stats.to.len())],
+        stats.views,
+        stats.unique_visitors,
+        stats.comments_pending,
+        stats.currency,
+        stats.revenue,
+        stats.firewall_events,
+    );
+
+    for (heading, entries) in [
+        ("Top posts", &stats.top_posts),
+        ("Top portfolio items", &stats.top_portfolio),
+    ] {
+        if entries.is_empty() {
+            continue;
+        }
+        body.push_str(&format!("\n{}:\n", heading));
+        for e in entries {
+            body.push_str(&format!("  {} — {} views\n", e.label, e.count));
+        }
+    }
+
+    if !site_url.is_empty() {
+        body.push_str(&format!("\n{}\n", site_url));
+    }
+    body.push_str("\nYou can turn off this digest under Settings › Email.\n");
+    body
+}
+
+/// Build and email the weekly digest to `admin_email`, then record the send date.
+pub fn send(store: &dyn Store, now: NaiveDateTime) -> Result<(), String> {
+    let settings = store.setting_all();
+    let admin_email = settings.get("admin_email").cloned().unwrap_or_default();
+    if admin_email.is_empty() {
+        return Err("Admin email not configured".into());
+    }
+    let from = super::get_from_or_admin(&settings);
+    if from.is_empty() {
+        return Err("No email provider configured".into());
+    }
+
+    let site_name = settings
+        .get("site_name")
+        .cloned()
+        .unwrap_or_else(|| "Velocty".to_string());
+    let site_url = settings.get("site_url").cloned().unwrap_or_default();
+    let stats = collect(store, now);
+    let subject = format!("[{}] Your weekly summary", site_name);
+    let body = render(&site_name, &site_url, &stats);
+
+    super::send_via_configured_provider(
+        store,
+        "notification",
+        &from,
+        &admin_email,
+        &subject,
+        &body,
+    )?;
+    store.setting_set("digest_last_sent", &now.format("%Y-%m-%d").to_string())
+}
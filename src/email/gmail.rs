@@ -27,6 +27,7 @@ pub fn send(
         587,
         &address,
         &app_password,
+        &super::smtp::PoolOptions::from_settings(settings),
         from,
         to,
         subject,
@@ -1,10 +1,15 @@
 pub mod brevo;
+pub mod digest;
 pub mod gmail;
 pub mod mailgun;
 pub mod mandrill;
 pub mod moosend;
+pub mod optin;
 pub mod postmark;
+pub mod report;
 pub mod resend;
+pub mod routing;
+pub mod sendgrid;
 pub mod sendpulse;
 pub mod ses;
 pub mod smtp;
@@ -13,8 +18,7 @@ pub mod sparkpost;
 use std::collections::HashMap;
 
 use lettre::message::header::ContentType;
-use lettre::transport::smtp::authentication::Credentials;
-use lettre::{Message, SmtpTransport, Transport};
+use lettre::{Message, Transport};
 
 use crate::store::Store;
 
@@ -69,67 +73,9 @@ pub fn send_purchase_email(
 
     let subject = format!("Your purchase: {} — {}", item_title, site_name);
 
-    if let Err(e) = send_via_provider(&settings, &from, buyer_email, &subject, &body) {
-        eprintln!(
-            "[email] Failed to send purchase email to {}: {}",
-            buyer_email, e
-        );
-    } else {
-        eprintln!("[email] Purchase email sent to {}", buyer_email);
-    }
-}
-
-/// Send a purchase confirmation email using pre-fetched settings (for use in background threads).
-pub fn send_purchase_email_with_settings(
-    settings: &HashMap<String, String>,
-    buyer_email: &str,
-    item_title: &str,
-    purchase_note: &str,
-    download_url: &str,
-    license_key: Option<&str>,
-    amount: f64,
-    currency: &str,
-) {
-    let site_name = settings
-        .get("site_name")
-        .cloned()
-        .unwrap_or_else(|| "Velocty".to_string());
-    let from_email = get_from_email(settings);
-
-    if from_email.is_none() {
-        eprintln!(
-            "[email] No email provider configured, skipping purchase email to {}",
-            buyer_email
-        );
-        return;
-    }
-    let from = from_email.unwrap();
-
-    let mut body = format!(
-        "Thank you for your purchase!\n\n\
-         Item: {}\n\
-         Amount: {} {:.2}\n",
-        item_title, currency, amount,
-    );
-
-    if !purchase_note.is_empty() {
-        body.push_str(&format!("Includes: {}\n", purchase_note));
-    }
-
-    body.push_str(&format!("\nDownload your file:\n{}\n", download_url));
-
-    if let Some(key) = license_key {
-        body.push_str(&format!("\nLicense Key: {}\n", key));
-    }
-
-    body.push_str(&format!(
-        "\nPlease save this email for your records.\n\n— {}\n",
-        site_name
-    ));
-
-    let subject = format!("Your purchase: {} — {}", item_title, site_name);
-
-    if let Err(e) = send_via_provider(settings, &from, buyer_email, &subject, &body) {
+    if let Err(e) =
+        send_via_configured_provider(store, "transactional", &from, buyer_email, &subject, &body)
+    {
         eprintln!(
             "[email] Failed to send purchase email to {}: {}",
             buyer_email, e
@@ -192,6 +138,7 @@ fn get_from_email(settings: &HashMap<String, String>) -> Option<String> {
         "email_moosend_enabled",
         "email_mandrill_enabled",
         "email_sparkpost_enabled",
+        "email_sendgrid_enabled",
     ];
     for provider in &api_providers {
         if settings.get(*provider).map(|v| v.as_str()) == Some("true") {
@@ -204,9 +151,26 @@ fn get_from_email(settings: &HashMap<String, String>) -> Option<String> {
     None
 }
 
-/// Send email via the first configured provider in the failover chain.
-pub fn send_via_provider(
+/// Send email for a message category (transactional, notification, contact, newsletter).
+/// Applies the routing rules, skips providers that are over their hourly/daily quota,
+/// and records usage for the provider that delivered the message.
+pub fn send_via_configured_provider(
+    store: &dyn Store,
+    category: &str,
+    from: &str,
+    to: &str,
+    subject: &str,
+    body: &str,
+) -> Result<(), String> {
+    let settings = store.setting_all();
+    let chain = routing::resolve_chain(&settings, category);
+    send_with_chain(&settings, &chain, Some(store), from, to, subject, body)
+}
+
+fn send_with_chain(
     settings: &HashMap<String, String>,
+    chain: &[String],
+    store: Option<&dyn Store>,
     from: &str,
     to: &str,
     subject: &str,
@@ -215,29 +179,21 @@ pub fn send_via_provider(
     let failover_enabled =
         settings.get("email_failover_enabled").map(|v| v.as_str()) == Some("true");
 
-    let chain_str = settings
-        .get("email_failover_chain")
-        .cloned()
-        .unwrap_or_else(|| {
-            "builtin,gmail,resend,ses,postmark,brevo,sendpulse,mailgun,moosend,mandrill,sparkpost,smtp"
-                .to_string()
-        });
-
-    let chain: Vec<&str> = chain_str
-        .split(',')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .collect();
-
     let mut last_error = String::new();
 
-    for provider_name in &chain {
-        let enabled_key = format!("email_{}_enabled", provider_name);
-        if settings.get(&enabled_key).map(|v| v.as_str()) != Some("true") {
-            continue;
+    for provider_name in chain {
+        if let Some(s) = store {
+            let limits = routing::quota_limits(settings, provider_name);
+            if limits != (0, 0)
+                && !routing::within_quota(limits, s.email_quota_usage(provider_name))
+            {
+                log::info!("Email provider {} is over quota, skipping", provider_name);
+                last_error = format!("Provider {} is over quota", provider_name);
+                continue;
+            }
         }
 
-        let result = match *provider_name {
+        let result = match provider_name.as_str() {
             "gmail" => gmail::send(settings, from, to, subject, body),
             "smtp" => smtp::send(settings, from, to, subject, body),
             "resend" => resend::send(settings, from, to, subject, body),
@@ -249,6 +205,7 @@ pub fn send_via_provider(
             "moosend" => moosend::send(settings, from, to, subject, body),
             "mandrill" => mandrill::send(settings, from, to, subject, body),
             "sparkpost" => sparkpost::send(settings, from, to, subject, body),
+            "sendgrid" => sendgrid::send(settings, from, to, subject, body),
             "builtin" => crate::mta::send(settings, from, to, subject, body),
             _ => {
                 log::warn!("Unknown email provider: {}", provider_name);
@@ -257,7 +214,12 @@ pub fn send_via_provider(
         };
 
         match result {
-            Ok(()) => return Ok(()),
+            Ok(()) => {
+                if let Some(s) = store {
+                    let _ = s.email_quota_record(provider_name);
+                }
+                return Ok(());
+            }
             Err(e) => {
                 log::warn!("Email provider {} failed: {}", provider_name, e);
                 last_error = e;
@@ -279,12 +241,14 @@ pub fn send_via_provider(
     }
 }
 
-/// Shared SMTP send function used by gmail.rs and smtp.rs
+/// Shared SMTP send function used by gmail.rs and smtp.rs.
+/// Reuses a pooled transport per relay (see `smtp::relay_transport`).
 pub fn send_smtp(
     host: &str,
     port: u16,
     username: &str,
     password: &str,
+    pool: &smtp::PoolOptions,
     from: &str,
     to: &str,
     subject: &str,
@@ -303,13 +267,7 @@ pub fn send_smtp(
         .body(body.to_string())
         .map_err(|e| format!("Failed to build email: {}", e))?;
 
-    let creds = Credentials::new(username.to_string(), password.to_string());
-
-    let mailer = SmtpTransport::starttls_relay(host)
-        .map_err(|e| format!("SMTP relay error: {}", e))?
-        .port(port)
-        .credentials(creds)
-        .build();
+    let mailer = smtp::relay_transport(host, port, username, password, pool)?;
 
     mailer
         .send(&email)
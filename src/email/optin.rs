@@ -0,0 +1,187 @@
+use crate::security::signed_token;
+use crate::store::Store;
+
+/// Confirmation links stay valid for two days.
+const CONFIRM_TTL_MINUTES: i64 = 48 * 60;
+
+fn valid_email(email: &str) -> bool {
+    email.contains('@') && email.contains('.') && !email.contains(char::is_whitespace)
+}
+
+fn site_info(store: &dyn Store) -> (String, String) {
+    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
+    let site_name = store.setting_get_or("site_name", "Velocty");
+    (site_url.trim_end_matches('/').to_string(), site_name)
+}
+
+fn send(
+    store: &dyn Store,
+    category: &str,
+    to: &str,
+    subject: &str,
+    body: &str,
+) -> Result<(), String> {
+    let from = super::get_from_or_admin(&store.setting_all());
+    if from.is_empty() {
+        return Err("No email provider configured".into());
+    }
+    super::send_via_configured_provider(store, category, &from, to, subject, body)
+}
+
+// ── Newsletter ──────────────────────────────────────────
+
+/// Register a pending newsletter subscriber and email a confirmation link.
+/// Already-confirmed addresses are left alone and get no email.
+pub fn request_newsletter(store: &dyn Store, email: &str) -> Result<(), String> {
+    let email = email.trim().to_lowercase();
+    if !valid_email(&email) {
+        return Err("Please enter a valid email address".into());
+    }
+    if store.newsletter_status(&email).as_deref() == Some("confirmed") {
+        return Ok(());
+    }
+    store.newsletter_subscribe(&email)?;
+    let token = signed_token::issue(
+        store,
+        signed_token::NEWSLETTER_CONFIRM,
+        &email,
+        "",
+        CONFIRM_TTL_MINUTES,
+    )?;
+
+    let (site_url, site_name) = site_info(store);
+    let link = format!("{}/api/newsletter/confirm?token={}", site_url, token);
+    let body = format!(
+        "Hello,\n\n\
+         Please confirm your subscription to {}:\n\n\
+         {}\n\n\
+         This link expires in 48 hours. If you didn't sign up, ignore this email \
+         and you won't be subscribed.\n\n\
+         — {}\n",
+        site_name, link, site_name
+    );
+    send(
+        store,
+        "newsletter",
+        &email,
+        &format!("Confirm your subscription — {}", site_name),
+        &body,
+    )
+}
+
+/// Redeem a newsletter confirmation link. Returns the confirmed address.
+pub fn confirm_newsletter(store: &dyn Store, token: &str) -> Result<String, String> {
+    let t = signed_token::verify(store, signed_token::NEWSLETTER_CONFIRM, token)?;
+    store.newsletter_confirm(&t.email)?;
+    Ok(t.email)
+}
+
+// ── Comment subscriptions ───────────────────────────────
+
+/// Register an unconfirmed subscription to new comments on a post and email a
+/// confirmation link. The token payload carries "content_type:post_id".
+pub fn request_comment_subscription(
+    store: &dyn Store,
+    post_id: i64,
+    content_type: &str,
+    email: &str,
+) -> Result<(), String> {
+    let email = email.trim().to_lowercase();
+    if !valid_email(&email) {
+        return Err("Please enter a valid email address".into());
+    }
+    let title = content_title(store, post_id, content_type).ok_or("Content not found")?;
+    store.comment_subscription_create(post_id, content_type, &email)?;
+    let token = signed_token::issue(
+        store,
+        signed_token::COMMENT_SUBSCRIBE,
+        &email,
+        &format!("{}:{}", content_type, post_id),
+        CONFIRM_TTL_MINUTES,
+    )?;
+
+    let (site_url, site_name) = site_info(store);
+    let link = format!("{}/api/comment/subscribe/confirm?token={}", site_url, token);
+    let body = format!(
+        "Hello,\n\n\
+         Confirm that you want an email when someone comments on \"{}\":\n\n\
+         {}\n\n\
+         This link expires in 48 hours. If you didn't ask for this, ignore this email.\n\n\
+         — {}\n",
+        title, link, site_name
+    );
+    send(
+        store,
+        "notification",
+        &email,
+        &format!("Confirm comment notifications — {}", site_name),
+        &body,
+    )
+}
+
+/// Redeem a comment subscription link. Returns (content_type, post_id).
+pub fn confirm_comment_subscription(
+    store: &dyn Store,
+    token: &str,
+) -> Result<(String, i64), String> {
+    let t = signed_token::verify(store, signed_token::COMMENT_SUBSCRIBE, token)?;
+    let (content_type, post_id) = t
+        .payload
+        .split_once(':')
+        .and_then(|(ct, id)| id.parse::<i64>().ok().map(|id| (ct.to_string(), id)))
+        .ok_or("Invalid or expired link")?;
+    store.comment_subscription_confirm(post_id, &content_type, &t.email)?;
+    Ok((content_type, post_id))
+}
+
+/// Email confirmed subscribers about a newly approved comment. The comment's
+/// author is skipped, as is the parent comment's author when inbound replies
+/// already notify them.
+pub fn notify_comment_subscribers(store: &dyn Store, comment_id: i64) {
+    let comment = match store.comment_find_by_id(comment_id) {
+        Some(c) if c.status == "approved" => c,
+        _ => return,
+    };
+    let mut skip = vec![comment
+        .author_email
+        .clone()
+        .unwrap_or_default()
+        .to_lowercase()];
+    if store.setting_get_bool("mta_inbound_enabled") {
+        if let Some(parent) = comment.parent_id.and_then(|p| store.comment_find_by_id(p)) {
+            skip.push(parent.author_email.unwrap_or_default().to_lowercase());
+        }
+    }
+    let recipients: Vec<String> = store
+        .comment_subscribers(comment.post_id, &comment.content_type)
+        .into_iter()
+        .filter(|e| !skip.contains(e))
+        .collect();
+    if recipients.is_empty() {
+        return;
+    }
+
+    let title = content_title(store, comment.post_id, &comment.content_type).unwrap_or_default();
+    let (_, site_name) = site_info(store);
+    let subject = format!("New comment on \"{}\" — {}", title, site_name);
+    let body = format!(
+        "{} wrote:\n\n{}\n\n—\nYou're receiving this because you subscribed to comments on \"{}\".\n",
+        comment.author_name, comment.body, title
+    );
+    for to in recipients {
+        if let Err(e) = send(store, "notification", &to, &subject, &body) {
+            log::warn!(
+                "[email] Comment subscriber notification to {} failed: {}",
+                to,
+                e
+            );
+        }
+    }
+}
+
+fn content_title(store: &dyn Store, post_id: i64, content_type: &str) -> Option<String> {
+    match content_type {
+        "portfolio" => store.portfolio_find_by_id(post_id).map(|p| p.title),
+        _ => store.post_find_by_id(post_id).map(|p| p.title),
+    }
+}
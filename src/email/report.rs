@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use serde_json::{json, Value};
+
+use crate::store::Store;
+
+/// First and last day of the calendar month before `now`.
+pub fn previous_month(now: NaiveDateTime) -> (NaiveDate, NaiveDate) {
+    let first_this = now.date().with_day(1).unwrap_or_else(|| now.date());
+    let last = first_this - Duration::days(1);
+    let first = last.with_day(1).unwrap_or(last);
+    (first, last)
+}
+
+/// Whether the monthly analytics report should go out now: enabled, on or
+/// after `analytics_report_day` of the month, and last month not yet reported.
+pub fn is_due(settings: &HashMap<String, String>, now: NaiveDateTime) -> bool {
+    if settings.get("analytics_report_enabled").map(|v| v.as_str()) != Some("true") {
+        return false;
+    }
+    let day = settings
+        .get("analytics_report_day")
+        .and_then(|d| d.trim().parse::<u32>().ok())
+        .unwrap_or(1)
+        .clamp(1, 28);
+    if now.day() < day {
+        return false;
+    }
+    let month = previous_month(now).0.format("%Y-%m").to_string();
+    settings.get("analytics_report_last_sent") != Some(&month)
+}
+
+/// Every export dataset for the month as one JSON document.
+pub fn collect(store: &dyn Store, first: NaiveDate, last: NaiveDate) -> Value {
+    let from = format!("{} 00:00:00", first.format("%Y-%m-%d"));
+    let to = format!("{} 23:59:59", last.format("%Y-%m-%d"));
+    let mut data = serde_json::Map::new();
+    for dataset in crate::analytics::EXPORT_DATASETS {
+        if let Some(v) = crate::analytics::export_json(store, dataset, &from, &to) {
+            data.insert(dataset.to_string(), v);
+        }
+    }
+    json!({
+        "site": store.setting_get_or("site_name", "Velocty"),
+        "site_url": store.setting_get_or("site_url", ""),
+        "month": first.format("%Y-%m").to_string(),
+        "from": first.format("%Y-%m-%d").to_string(),
+        "to": last.format("%Y-%m-%d").to_string(),
+        "data": Value::Object(data),
+    })
+}
+
+/// Render the report as a plain-text email body.
+pub fn render(report: &Value) -> String {
+    let data = &report["data"];
+    let num = |v: &Value| v.as_i64().unwrap_or(0);
+    let mut body = format!(
+        "Analytics report for {} — {}\n\n\
+         Page views: {}\n\
+         Unique visitors: {}\n",
+        report["site"].as_str().unwrap_or_default(),
+        report["month"].as_str().unwrap_or_default(),
+        num(&data["overview"]["total_views"]),
+        num(&data["overview"]["unique_visitors"]),
+    );
+
+    let days = data["calendar"]
+        .as_array()
+        .map(|a| a.as_slice())
+        .unwrap_or(&[]);
+    if let Some(busiest) = days.iter().max_by_key(|d| num(&d["views"])) {
+        body.push_str(&format!(
+            "Busiest day: {} ({} views)\n",
+            busiest["date"].as_str().unwrap_or_default(),
+            num(&busiest["views"])
+        ));
+    }
+
+    for (heading, key, label) in [
+        ("Top countries", "geo", "country"),
+        ("Top referrers", "referrers", "referrer"),
+    ] {
+        let rows = data[key].as_array().map(|a| a.as_slice()).unwrap_or(&[]);
+        if rows.is_empty() {
+            continue;
+        }
+        body.push_str(&format!("\n{}:\n", heading));
+        for r in rows.iter().take(10) {
+            body.push_str(&format!(
+                "  {} — {} views\n",
+                r[label].as_str().unwrap_or_default(),
+                num(&r["views"])
+            ));
+        }
+    }
+
+    if let Some(url) = report["site_url"].as_str().filter(|u| !u.is_empty()) {
+        body.push_str(&format!("\n{}\n", url));
+    }
+    body.push_str("\nYou can turn off this report under Settings › Visitors.\n");
+    body
+}
+
+fn push_webhook(url: &str, secret: &str, report: &Value) -> Result<(), String> {
+    let body = serde_json::to_vec(report).map_err(|e| e.to_string())?;
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+    let mut req = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("User-Agent", "Velocty");
+    if !secret.is_empty() {
+        req = req.header(
+            "X-Velocty-Signature",
+            format!("sha256={}", crate::security::siem::sign_body(secret, &body)),
+        );
+    }
+    let resp = req
+        .body(body)
+        .send()
+        .map_err(|e| format!("Webhook request failed: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Webhook returned {}", resp.status()));
+    }
+    Ok(())
+}
+
+/// Build last month's report, email it to `admin_email` and/or POST it to
+/// the report webhook, then record the month as sent.
+pub fn send(store: &dyn Store, now: NaiveDateTime) -> Result<(), String> {
+    let settings = store.setting_all();
+    let get = |k: &str| settings.get(k).cloned().unwrap_or_default();
+    let by_email = get("analytics_report_email") == "true";
+    let webhook_url = get("analytics_report_webhook_url");
+    if !by_email && webhook_url.is_empty() {
+        return Err("No report delivery configured".into());
+    }
+
+    let (first, last) = previous_month(now);
+    let report = collect(store, first, last);
+
+    if !webhook_url.is_empty() {
+        push_webhook(
+            &webhook_url,
+            &get("analytics_report_webhook_secret"),
+            &report,
+        )?;
+    }
+    if by_email {
+        let admin_email = get("admin_email");
+        if admin_email.is_empty() {
+            return Err("Admin email not configured".into());
+        }
+        let from = super::get_from_or_admin(&settings);
+        if from.is_empty() {
+            return Err("No email provider configured".into());
+        }
+        let subject = format!(
+            "[{}] Analytics report for {}",
+            report["site"].as_str().unwrap_or_default(),
+            report["month"].as_str().unwrap_or_default()
+        );
+        super::send_via_configured_provider(
+            store,
+            "notification",
+            &from,
+            &admin_email,
+            &subject,
+            &render(&report),
+        )?;
+    }
+    store.setting_set(
+        "analytics_report_last_sent",
+        &first.format("%Y-%m").to_string(),
+    )
+}
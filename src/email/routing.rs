@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+/// Default failover order when `email_failover_chain` is not set.
+pub const DEFAULT_CHAIN: &str =
+    "builtin,gmail,resend,ses,postmark,brevo,sendpulse,mailgun,moosend,mandrill,sparkpost,sendgrid,smtp";
+
+/// Message categories understood by the routing rules.
+pub const CATEGORIES: [&str; 4] = ["transactional", "notification", "contact", "newsletter"];
+
+/// Parse `email_routing_rules`: one rule per line, `category: provider, provider`.
+/// Blank lines and lines starting with `#` are ignored.
+pub fn parse_routing_rules(text: &str) -> HashMap<String, Vec<String>> {
+    let mut rules = HashMap::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (category, providers) = match line.split_once(':').or_else(|| line.split_once('=')) {
+            Some(pair) => pair,
+            None => continue,
+        };
+        let providers: Vec<String> = providers
+            .split(',')
+            .map(|p| p.trim().to_lowercase())
+            .filter(|p| !p.is_empty())
+            .collect();
+        if !providers.is_empty() {
+            rules.insert(category.trim().to_lowercase(), providers);
+        }
+    }
+    rules
+}
+
+/// Validate routing rules text, returning a human-readable error for the first bad line.
+pub fn validate_routing_rules(text: &str) -> Result<(), String> {
+    let known: Vec<&str> = DEFAULT_CHAIN.split(',').collect();
+    for (category, providers) in parse_routing_rules(text) {
+        if !CATEGORIES.contains(&category.as_str()) {
+            return Err(format!(
+                "Unknown email category '{}' in routing rules (use one of: {})",
+                category,
+                CATEGORIES.join(", ")
+            ));
+        }
+        if let Some(p) = providers.iter().find(|p| !known.contains(&p.as_str())) {
+            return Err(format!("Unknown email provider '{}' in routing rules", p));
+        }
+    }
+    Ok(())
+}
+
+/// Resolve the ordered list of enabled providers to try for a message category.
+/// Routed providers come first; with failover enabled, the rest of the failover
+/// chain follows as a fallback.
+pub fn resolve_chain(settings: &HashMap<String, String>, category: &str) -> Vec<String> {
+    let failover_enabled =
+        settings.get("email_failover_enabled").map(|v| v.as_str()) == Some("true");
+    let chain_str = settings
+        .get("email_failover_chain")
+        .filter(|s| !s.trim().is_empty())
+        .cloned()
+        .unwrap_or_else(|| DEFAULT_CHAIN.to_string());
+    let mut chain: Vec<String> = chain_str
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    // Providers added after the chain was saved still participate, at the end
+    for p in DEFAULT_CHAIN.split(',') {
+        if !chain.iter().any(|c| c == p) {
+            chain.push(p.to_string());
+        }
+    }
+
+    let rules = parse_routing_rules(
+        settings
+            .get("email_routing_rules")
+            .map(|s| s.as_str())
+            .unwrap_or(""),
+    );
+
+    let mut ordered: Vec<String> = match rules.get(category) {
+        Some(routed) => {
+            let mut list = routed.clone();
+            if failover_enabled {
+                list.extend(chain.into_iter().filter(|p| !routed.contains(p)));
+            }
+            list
+        }
+        None => chain,
+    };
+    ordered.retain(|p| is_enabled(settings, p));
+    ordered
+}
+
+fn is_enabled(settings: &HashMap<String, String>, provider: &str) -> bool {
+    settings
+        .get(&format!("email_{}_enabled", provider))
+        .map(|v| v.as_str())
+        == Some("true")
+}
+
+/// Configured (hourly, daily) quotas for a provider. 0 means unlimited.
+pub fn quota_limits(settings: &HashMap<String, String>, provider: &str) -> (u64, u64) {
+    let get = |key: String| {
+        settings
+            .get(&key)
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    (
+        get(format!("email_{}_quota_hourly", provider)),
+        get(format!("email_{}_quota_daily", provider)),
+    )
+}
+
+/// Whether another message fits within the (hourly, daily) limits given current usage.
+pub fn within_quota(limits: (u64, u64), usage: (u64, u64)) -> bool {
+    (limits.0 == 0 || usage.0 < limits.0) && (limits.1 == 0 || usage.1 < limits.1)
+}
+
+/// Quota bucket keys for the current UTC hour and day ("2024-05-01T12", "2024-05-01").
+pub fn quota_buckets(now: chrono::DateTime<chrono::Utc>) -> (String, String) {
+    (
+        now.format("%Y-%m-%dT%H").to_string(),
+        now.format("%Y-%m-%d").to_string(),
+    )
+}
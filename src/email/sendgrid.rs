@@ -0,0 +1,70 @@
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Build the SendGrid v3 mail/send payload. Sandbox mode validates the request
+/// without delivering it (useful for testing credentials).
+pub fn build_payload(
+    settings: &HashMap<String, String>,
+    from: &str,
+    to: &str,
+    subject: &str,
+    body: &str,
+) -> serde_json::Value {
+    let sandbox = settings
+        .get("email_sendgrid_sandbox")
+        .map(|v| v == "true")
+        .unwrap_or(false);
+
+    let mut from_obj = json!({ "email": from });
+    if let Some(name) = settings.get("email_from_name").filter(|n| !n.is_empty()) {
+        from_obj["name"] = json!(name);
+    }
+
+    json!({
+        "personalizations": [{ "to": [{ "email": to }] }],
+        "from": from_obj,
+        "subject": subject,
+        "content": [{ "type": "text/plain", "value": body }],
+        "mail_settings": { "sandbox_mode": { "enable": sandbox } }
+    })
+}
+
+/// Send email via SendGrid API (https://docs.sendgrid.com/api-reference/mail-send/mail-send)
+pub fn send(
+    settings: &HashMap<String, String>,
+    from: &str,
+    to: &str,
+    subject: &str,
+    body: &str,
+) -> Result<(), String> {
+    let api_key = settings
+        .get("email_sendgrid_api_key")
+        .cloned()
+        .unwrap_or_default();
+    if api_key.is_empty() {
+        return Err("SendGrid API key not configured".into());
+    }
+
+    let payload = build_payload(settings, from, to, subject, body);
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+
+    let resp = client
+        .post("https://api.sendgrid.com/v3/mail/send")
+        .header("Authorization", format!("Bearer {}", api_key))
+        .header("Content-Type", "application/json")
+        .json(&payload)
+        .send()
+        .map_err(|e| format!("SendGrid request failed: {}", e))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().unwrap_or_default();
+        return Err(format!("SendGrid returned {}: {}", status, text));
+    }
+
+    Ok(())
+}
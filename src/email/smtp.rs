@@ -1,7 +1,145 @@
 use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::PoolConfig;
+use lettre::SmtpTransport;
 
 use super::send_smtp;
 
+/// Connection pool settings shared by the SMTP relays and built-in MTA delivery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolOptions {
+    /// Maximum open connections per server (how many sends may run concurrently).
+    pub max_connections: u32,
+    /// How long an idle connection is kept alive for reuse.
+    pub idle_timeout: Duration,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            max_connections: 4,
+            idle_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl PoolOptions {
+    /// Read `email_pool_max_connections` (1–32) and `email_pool_idle_timeout` (seconds, 5–600).
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let defaults = Self::default();
+        let max_connections = settings
+            .get("email_pool_max_connections")
+            .and_then(|v| v.trim().parse::<u32>().ok())
+            .map(|n| n.clamp(1, 32))
+            .unwrap_or(defaults.max_connections);
+        let idle_timeout = settings
+            .get("email_pool_idle_timeout")
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(|s| Duration::from_secs(s.clamp(5, 600)))
+            .unwrap_or(defaults.idle_timeout);
+        Self {
+            max_connections,
+            idle_timeout,
+        }
+    }
+
+    pub fn config(&self) -> PoolConfig {
+        PoolConfig::new()
+            .max_size(self.max_connections)
+            .idle_timeout(self.idle_timeout)
+    }
+}
+
+/// Transports unused for this long are dropped, closing their pooled connections.
+const TRANSPORT_IDLE_TTL: Duration = Duration::from_secs(15 * 60);
+
+/// Most transports kept per cache. Direct delivery opens one per MX host, so
+/// without a cap the cache would grow with every recipient domain.
+pub const MAX_CACHED_TRANSPORTS: usize = 64;
+
+pub struct CachedTransport {
+    fingerprint: String,
+    transport: SmtpTransport,
+    last_used: Instant,
+}
+
+pub type TransportCache = Mutex<HashMap<String, CachedTransport>>;
+
+/// Return the cached transport for `key`, building a new one when none exists or
+/// its `fingerprint` (credentials, pool options) changed. Idle transports expire
+/// after `TRANSPORT_IDLE_TTL`, and the least recently used one is evicted once
+/// `MAX_CACHED_TRANSPORTS` are held. Dropping a transport closes its connections.
+pub fn cached_transport<F>(
+    cache: &TransportCache,
+    key: &str,
+    fingerprint: &str,
+    build: F,
+) -> Result<SmtpTransport, String>
+where
+    F: FnOnce() -> Result<SmtpTransport, String>,
+{
+    let mut map = cache
+        .lock()
+        .map_err(|_| "SMTP pool lock poisoned".to_string())?;
+    let now = Instant::now();
+    map.retain(|_, c| now.duration_since(c.last_used) < TRANSPORT_IDLE_TTL);
+    if let Some(cached) = map.get_mut(key) {
+        if cached.fingerprint == fingerprint {
+            cached.last_used = now;
+            return Ok(cached.transport.clone());
+        }
+    }
+    let transport = build()?;
+    if !map.contains_key(key) && map.len() >= MAX_CACHED_TRANSPORTS {
+        let oldest = map
+            .iter()
+            .min_by_key(|(_, c)| c.last_used)
+            .map(|(k, _)| k.clone());
+        if let Some(k) = oldest {
+            map.remove(&k);
+        }
+    }
+    map.insert(
+        key.to_string(),
+        CachedTransport {
+            fingerprint: fingerprint.to_string(),
+            transport: transport.clone(),
+            last_used: now,
+        },
+    );
+    Ok(transport)
+}
+
+fn relay_cache() -> &'static TransportCache {
+    static RELAYS: OnceLock<TransportCache> = OnceLock::new();
+    RELAYS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Pooled STARTTLS relay transport (Gmail, custom SMTP). Connections stay open
+/// between sends so batches don't renegotiate TLS and re-authenticate per message.
+pub fn relay_transport(
+    host: &str,
+    port: u16,
+    username: &str,
+    password: &str,
+    pool: &PoolOptions,
+) -> Result<SmtpTransport, String> {
+    let key = format!("{}:{}:{}", host, port, username);
+    let fingerprint = format!("{}:{:?}", password, pool);
+    cached_transport(relay_cache(), &key, &fingerprint, || {
+        let creds = Credentials::new(username.to_string(), password.to_string());
+        Ok(SmtpTransport::starttls_relay(host)
+            .map_err(|e| format!("SMTP relay error: {}", e))?
+            .port(port)
+            .credentials(creds)
+            .pool_config(pool.config())
+            .build())
+    })
+}
+
 pub fn send(
     settings: &HashMap<String, String>,
     from: &str,
@@ -27,5 +165,8 @@ pub fn send(
         return Err("SMTP host or username not configured".into());
     }
 
-    send_smtp(&host, port, &username, &password, from, to, subject, body)
+    let pool = PoolOptions::from_settings(settings);
+    send_smtp(
+        &host, port, &username, &password, &pool, from, to, subject, body,
+    )
 }
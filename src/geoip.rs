@@ -0,0 +1,151 @@
+use std::net::IpAddr;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::SystemTime;
+
+use crate::store::Store;
+
+type GeoReader = maxminddb::Reader<Vec<u8>>;
+
+/// The database file last opened. `reader` is None when it couldn't be
+/// read, so lookups don't retry the open on every request; the reload task
+/// tries again once the file changes.
+struct Loaded {
+    path: String,
+    modified: Option<SystemTime>,
+    reader: Option<Arc<GeoReader>>,
+}
+
+fn slot() -> &'static RwLock<Option<Loaded>> {
+    static SLOT: OnceLock<RwLock<Option<Loaded>>> = OnceLock::new();
+    SLOT.get_or_init(|| RwLock::new(None))
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn open(path: &str) -> (Loaded, Result<(), String>) {
+    let (reader, result) = match maxminddb::Reader::open_readfile(path) {
+        Ok(r) => (Some(Arc::new(r)), Ok(())),
+        Err(e) => (
+            None,
+            Err(format!("Cannot open GeoIP database {}: {}", path, e)),
+        ),
+    };
+    let loaded = Loaded {
+        path: path.to_string(),
+        modified: modified(path),
+        reader,
+    };
+    (loaded, result)
+}
+
+/// Location of a client IP from the local database.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GeoInfo {
+    /// ISO 3166-1 alpha-2 code, e.g. "DE"
+    pub country: Option<String>,
+    /// English city name; only GeoLite2-City databases have one
+    pub city: Option<String>,
+}
+
+/// The reader for `path`, opening it on first use or when the setting
+/// points somewhere new.
+fn reader(path: &str) -> Option<Arc<GeoReader>> {
+    if let Some(loaded) = slot().read().ok()?.as_ref() {
+        if loaded.path == path {
+            return loaded.reader.clone();
+        }
+    }
+    let mut guard = slot().write().ok()?;
+    if let Some(loaded) = guard.as_ref() {
+        if loaded.path == path {
+            return loaded.reader.clone();
+        }
+    }
+    let (loaded, result) = open(path);
+    if let Err(e) = result {
+        log::warn!("[geoip] {}", e);
+    }
+    let r = loaded.reader.clone();
+    *guard = Some(loaded);
+    r
+}
+
+/// Look `ip` up in the GeoLite2 Country or City database set in
+/// `geoip_db_path`. Empty when no database is configured, the IP is private
+/// or it isn't in the database. Never makes a network call.
+pub fn lookup(store: &dyn Store, ip: &str) -> GeoInfo {
+    let path = store.setting_get_or("geoip_db_path", "");
+    if path.is_empty() {
+        return GeoInfo::default();
+    }
+    let addr: IpAddr = match ip.parse() {
+        Ok(a) => a,
+        Err(_) => return GeoInfo::default(),
+    };
+    match reader(&path) {
+        Some(r) => lookup_in(&r, addr),
+        None => GeoInfo::default(),
+    }
+}
+
+fn lookup_in(reader: &GeoReader, addr: IpAddr) -> GeoInfo {
+    // Country databases decode as a City record without the city
+    let record = match reader.lookup::<maxminddb::geoip2::City>(addr) {
+        Ok(r) => r,
+        Err(_) => return GeoInfo::default(),
+    };
+    GeoInfo {
+        country: record
+            .country
+            .and_then(|c| c.iso_code)
+            .map(|c| c.to_uppercase()),
+        city: record
+            .city
+            .and_then(|c| c.names)
+            .and_then(|n| n.get("en").map(|s| s.to_string())),
+    }
+}
+
+/// Reopen the database when its file has changed on disk (e.g. after a
+/// weekly geoipupdate) or the path setting has. Returns whether it reloaded.
+pub fn reload(store: &dyn Store) -> Result<bool, String> {
+    let path = store.setting_get_or("geoip_db_path", "");
+    let mut guard = slot().write().map_err(|e| e.to_string())?;
+    if path.is_empty() {
+        *guard = None;
+        return Ok(false);
+    }
+    let unchanged = guard
+        .as_ref()
+        .is_some_and(|l| l.path == path && l.modified == modified(&path));
+    if unchanged {
+        return Ok(false);
+    }
+    let (loaded, result) = open(&path);
+    *guard = Some(loaded);
+    result.map(|_| true)
+}
+
+/// Parse a comma-separated country list ("cn, RU") into upper-case codes.
+pub fn parse_countries(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(|c| c.trim().to_uppercase())
+        .filter(|c| c.len() == 2)
+        .collect()
+}
+
+/// Whether country blocking turns away a visitor from `country`. With an
+/// allow list only those countries get in; otherwise the block list applies.
+/// Visitors whose country isn't known are never blocked.
+pub fn country_blocked(country: Option<&str>, blocked: &[String], allowed: &[String]) -> bool {
+    let country = match country {
+        Some(c) => c.to_uppercase(),
+        None => return false,
+    };
+    if !allowed.is_empty() {
+        return !allowed.contains(&country);
+    }
+    blocked.contains(&country)
+}
@@ -32,6 +32,18 @@ pub struct HealthReport {
     pub content: ContentStats,
     pub running_as_root: bool,
     pub process_user: String,
+    pub backups: Vec<crate::backup::DestinationStatus>,
+    pub page_cache: crate::render::page_cache::Stats,
+    pub startup: StartupInfo,
+}
+
+/// How the last startup went
+#[derive(Debug, Serialize)]
+pub struct StartupInfo {
+    pub phases: Vec<crate::boot::BootPhase>,
+    pub total_millis: u64,
+    /// Design shells that would render broken pages
+    pub design_problems: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -159,6 +171,18 @@ pub fn gather(pool: Option<&DbPool>, store: &dyn Store) -> HealthReport {
         content: gather_content_from_store(store),
         running_as_root,
         process_user,
+        backups: crate::backup::status(store),
+        page_cache: crate::render::page_cache::stats(),
+        startup: gather_startup(store),
+    }
+}
+
+fn gather_startup(store: &dyn Store) -> StartupInfo {
+    let phases = crate::boot::phases();
+    StartupInfo {
+        total_millis: phases.iter().map(|p| p.millis).sum(),
+        phases,
+        design_problems: crate::designs::check::problems(store),
     }
 }
 
@@ -736,6 +760,16 @@ pub fn run_session_cleanup(store: &dyn Store) -> ToolResult {
     }
 }
 
+pub fn run_page_cache_clear() -> ToolResult {
+    let entries = crate::render::page_cache::stats().entries;
+    crate::render::page_cache::invalidate();
+    ToolResult {
+        ok: true,
+        message: format!("Cleared {} cached page(s).", entries),
+        details: None,
+    }
+}
+
 pub fn run_orphan_scan(store: &dyn Store, uploads_dir: &str) -> ToolResult {
     let dir = Path::new(uploads_dir);
     if !dir.exists() {
@@ -898,6 +932,55 @@ pub fn export_database() -> ToolResult {
     }
 }
 
+/// Rebuild the external search engine's index from the published content.
+pub fn run_search_reindex(store: &dyn Store) -> ToolResult {
+    match crate::search_engine::reindex(store) {
+        Ok(count) => ToolResult {
+            ok: true,
+            message: format!("Sent {} documents to the search engine.", count),
+            details: None,
+        },
+        Err(e) => ToolResult {
+            ok: false,
+            message: format!("Reindex failed: {}", e),
+            details: None,
+        },
+    }
+}
+
+/// Run the off-site backup now, regardless of the schedule.
+pub fn run_backup(store: &dyn Store) -> ToolResult {
+    if !crate::backup::DESTINATIONS
+        .iter()
+        .any(|(key, _)| store.setting_get_bool(&format!("backup_{}_enabled", key)))
+    {
+        return ToolResult {
+            ok: false,
+            message: "No backup destinations are enabled.".into(),
+            details: None,
+        };
+    }
+    match crate::backup::run(store) {
+        Ok(count) => {
+            let failed: Vec<String> = crate::backup::status(store)
+                .into_iter()
+                .filter(|d| d.enabled && !d.last_error.is_empty())
+                .map(|d| format!("{}: {}", d.label, d.last_error))
+                .collect();
+            ToolResult {
+                ok: failed.is_empty(),
+                message: format!("Backup uploaded to {} destination(s).", count),
+                details: (!failed.is_empty()).then(|| failed.join("\n")),
+            }
+        }
+        Err(e) => ToolResult {
+            ok: false,
+            message: format!("Backup failed: {}", e),
+            details: None,
+        },
+    }
+}
+
 pub fn export_content(store: &dyn Store) -> ToolResult {
     match store.health_export_content() {
         Ok(json_data) => {
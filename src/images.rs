@@ -1,3 +1,5 @@
+pub mod queue;
+
 use image::imageops::FilterType;
 use image::GenericImageView;
 use std::fs;
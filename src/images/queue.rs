@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::store::Store;
+
+// ── Image optimization queue ────────────────────────────
+//
+// Resizing, re-encoding and WebP conversion run in a background task rather
+// than while the upload request waits. An upload gets its final filename
+// right away (with the .webp or .jpg extension it will end up with) and its
+// original is parked in `QUEUE_DIR`. Until the optimized rendition lands in
+// the uploads directory, requests for the final name are answered with the
+// original.
+
+/// Where originals wait for their optimized rendition
+pub const QUEUE_DIR: &str = "website/site/media-queue";
+
+const UPLOAD_DIR: &str = "website/site/uploads";
+
+/// Attempts before a job is marked failed
+pub const MAX_ATTEMPTS: i64 = 3;
+
+/// Jobs processed per run of the background task
+const BATCH_SIZE: i64 = 10;
+
+/// An upload waiting for optimization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaJob {
+    /// Final filename, relative to the uploads directory
+    pub path: String,
+    /// The original upload, under `QUEUE_DIR`
+    pub source: String,
+    pub status: String, // "pending", "failed"
+    pub attempts: i64,
+    pub error: String,
+    pub created_at: String,
+}
+
+fn is_heic(ext: &str) -> bool {
+    ext == "heic" || ext == "heif"
+}
+
+/// Whether the media settings leave anything to do for an image with this
+/// extension. SVGs and videos never reach the queue.
+pub fn needs_processing(store: &dyn Store, ext: &str) -> bool {
+    let ext = ext.to_lowercase();
+    if is_heic(&ext) {
+        return true;
+    }
+    if store.setting_get_bool("images_webp_convert") && ext != "webp" {
+        return true;
+    }
+    let optimize = store.setting_get_i64("images_max_dimension") > 0
+        || store.setting_get_bool("images_reencode")
+        || store.setting_get_bool("images_strip_metadata");
+    optimize && ext != "gif" && ext != "webp"
+}
+
+/// The filename `filename` will have once optimized: WebP when conversion
+/// is on, JPEG for HEIC, otherwise unchanged
+pub fn final_filename(store: &dyn Store, filename: &str, ext: &str) -> String {
+    let ext = ext.to_lowercase();
+    let target = if store.setting_get_bool("images_webp_convert") && ext != "webp" {
+        "webp"
+    } else if is_heic(&ext) {
+        "jpg"
+    } else {
+        return filename.to_string();
+    };
+    let stem = filename
+        .rsplit_once('.')
+        .map(|(stem, _)| stem)
+        .unwrap_or(filename);
+    format!("{}.{}", stem, target)
+}
+
+/// Park the upload written at `uploads/<filename>` and queue its
+/// optimization. Returns the final filename to hand back to the editor.
+pub fn enqueue(store: &dyn Store, filename: &str, ext: &str) -> Result<String, String> {
+    let path = final_filename(store, filename, ext);
+    std::fs::create_dir_all(QUEUE_DIR).map_err(|e| e.to_string())?;
+    let current = Path::new(UPLOAD_DIR).join(filename);
+    let source =
+        Path::new(QUEUE_DIR).join(format!("{}.{}", uuid::Uuid::new_v4(), ext.to_lowercase()));
+    std::fs::rename(&current, &source).map_err(|e| e.to_string())?;
+    let source = source.to_string_lossy().to_string();
+    if let Err(e) = store.media_job_push(&path, &source) {
+        let _ = std::fs::rename(&source, &current);
+        return Err(e);
+    }
+    Ok(path)
+}
+
+/// The original to serve for `path` (relative to the uploads directory)
+/// while its optimized rendition isn't ready
+pub fn pending_source(store: &dyn Store, path: &str) -> Option<PathBuf> {
+    let job = store.media_job_get(path)?;
+    let source = Path::new(&job.source).canonicalize().ok()?;
+    let queue_dir = Path::new(QUEUE_DIR).canonicalize().ok()?;
+    source.starts_with(&queue_dir).then_some(source)
+}
+
+/// Optimize the next batch of queued uploads. Returns how many finished.
+pub fn process_pending(store: &dyn Store) -> usize {
+    let mut done = 0;
+    for job in store.media_job_pending(BATCH_SIZE) {
+        match run(store, &job) {
+            Ok(()) => done += 1,
+            Err(e) => {
+                let give_up = job.attempts + 1 >= MAX_ATTEMPTS;
+                log::warn!(
+                    "[media] Optimizing {} failed (attempt {}): {}",
+                    job.path,
+                    job.attempts + 1,
+                    e
+                );
+                if give_up && keep_original(&job) {
+                    let _ = store.media_job_delete(&job.path);
+                } else {
+                    let _ = store.media_job_fail(&job.path, &e, give_up);
+                }
+            }
+        }
+    }
+    done
+}
+
+fn run(store: &dyn Store, job: &MediaJob) -> Result<(), String> {
+    let source = Path::new(&job.source);
+    if !source.is_file() {
+        // Deleted from the media library while queued
+        return store.media_job_delete(&job.path);
+    }
+    let dest = Path::new(UPLOAD_DIR).join(&job.path);
+    crate::routes::admin::optimize_into(store, source, &dest)?;
+    store.media_job_delete(&job.path)?;
+    let _ = std::fs::remove_file(source);
+    Ok(())
+}
+
+/// After the last attempt, an original that already has the final extension
+/// takes the final name as-is, so the job can be dropped
+fn keep_original(job: &MediaJob) -> bool {
+    let ext = |p: &str| p.rsplit('.').next().unwrap_or("").to_lowercase();
+    ext(&job.source) == ext(&job.path)
+        && std::fs::rename(&job.source, Path::new(UPLOAD_DIR).join(&job.path)).is_ok()
+}
+
+/// Remove a queued upload and its original
+pub fn discard(store: &dyn Store, path: &str) -> Result<bool, String> {
+    let Some(job) = store.media_job_get(path) else {
+        return Ok(false);
+    };
+    if let Some(source) = pending_source(store, path) {
+        let _ = std::fs::remove_file(source);
+    }
+    store.media_job_delete(&job.path)?;
+    Ok(true)
+}
@@ -0,0 +1,426 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::models::import::ImportCreated;
+use crate::models::post::PostForm;
+use crate::store::Store;
+
+use super::{download_media, localize_remote_images, record_import};
+
+/// Placeholder Ghost (4.0+) writes in place of the site URL in exports
+const GHOST_URL: &str = "__GHOST_URL__";
+
+/// Result of a Ghost import
+pub struct GhostImportResult {
+    pub posts_imported: i64,
+    pub authors_imported: i64,
+    pub skipped: i64,
+    pub media_downloaded: i64,
+    pub media_failed: i64,
+    pub log: Vec<String>,
+    pub created: ImportCreated,
+}
+
+/// Ids are strings in current exports and integers in old ones
+fn id_of(v: &Value) -> String {
+    match v.get("id") {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Number(n)) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn str_field<'a>(v: &'a Value, key: &str) -> &'a str {
+    v.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+fn opt_field(v: &Value, key: &str) -> Option<String> {
+    v.get(key)
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+}
+
+/// Link-table rows (`posts_tags`, `posts_authors`) as post id → ids in order
+fn relations(data: &Value, table: &str, key: &str) -> HashMap<String, Vec<String>> {
+    let mut rows: Vec<(String, String, i64)> = data
+        .get(table)
+        .and_then(|v| v.as_array())
+        .map(|rows| {
+            rows.iter()
+                .map(|r| {
+                    let id = |k: &str| match r.get(k) {
+                        Some(Value::String(s)) => s.clone(),
+                        Some(Value::Number(n)) => n.to_string(),
+                        _ => String::new(),
+                    };
+                    let order = r.get("sort_order").and_then(|v| v.as_i64()).unwrap_or(0);
+                    (id("post_id"), id(key), order)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    rows.sort_by_key(|(_, _, order)| *order);
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for (post_id, other_id, _) in rows {
+        map.entry(post_id).or_default().push(other_id);
+    }
+    map
+}
+
+/// Rows of a table keyed by id
+fn by_id<'a>(data: &'a Value, table: &str) -> HashMap<String, &'a Value> {
+    data.get(table)
+        .and_then(|v| v.as_array())
+        .map(|rows| rows.iter().map(|r| (id_of(r), r)).collect())
+        .unwrap_or_default()
+}
+
+/// Ghost dates ("2024-01-15T10:30:00.000Z", or "2024-01-15 10:30:00" in
+/// old exports) as UTC.
+fn parse_ghost_date(date: &str) -> Option<chrono::NaiveDateTime> {
+    if date.is_empty() {
+        return None;
+    }
+    chrono::DateTime::parse_from_rfc3339(date)
+        .map(|d| d.naive_utc())
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S"))
+        .ok()
+}
+
+/// Resolve a Ghost image reference to a full URL, if it can be fetched
+fn image_url(raw: &str, source_url: &str) -> Option<String> {
+    if let Some(path) = raw.strip_prefix(GHOST_URL) {
+        return (!source_url.is_empty()).then(|| format!("{}{}", source_url, path));
+    }
+    if raw.starts_with("/content/images/") {
+        return (!source_url.is_empty()).then(|| format!("{}{}", source_url, raw));
+    }
+    (raw.starts_with("http://") || raw.starts_with("https://")).then(|| raw.to_string())
+}
+
+/// Downloads images once per import, however often they're referenced
+struct Media<'a> {
+    store: &'a dyn Store,
+    source_url: &'a str,
+    done: HashMap<String, Option<String>>,
+}
+
+impl Media<'_> {
+    /// Upload path for an image, downloading it on first sight
+    fn fetch(
+        &mut self,
+        raw: &str,
+        post_date: Option<&chrono::NaiveDateTime>,
+        result: &mut GhostImportResult,
+    ) -> Option<String> {
+        if let Some(done) = self.done.get(raw) {
+            return done.clone();
+        }
+        let local = match image_url(raw, self.source_url) {
+            Some(url) => match download_media(&url, self.store, "post", post_date) {
+                Ok(local) => {
+                    result.media_downloaded += 1;
+                    result.created.media.push(local.clone());
+                    Some(local)
+                }
+                Err(e) => {
+                    result.media_failed += 1;
+                    result
+                        .log
+                        .push(format!("Media failed for '{}': {}", url, e));
+                    None
+                }
+            },
+            None => {
+                result.media_failed += 1;
+                result
+                    .log
+                    .push(format!("Media skipped (no site URL given): {}", raw));
+                None
+            }
+        };
+        self.done.insert(raw.to_string(), local.clone());
+        local
+    }
+
+    /// Swap every Ghost-hosted image in `html` for its local copy
+    fn rewrite(
+        &mut self,
+        html: &str,
+        post_date: Option<&chrono::NaiveDateTime>,
+        result: &mut GhostImportResult,
+    ) -> String {
+        let mut output = html.to_string();
+        let pattern = "(?:__GHOST_URL__|https?://[^\"'\\s()]+)?/content/images/[^\"'\\s()]+";
+        if let Ok(re) = regex::Regex::new(pattern) {
+            let urls: Vec<String> = re.find_iter(html).map(|m| m.as_str().to_string()).collect();
+            for url in urls {
+                if let Some(local) = self.fetch(&url, post_date, result) {
+                    output = output.replace(&url, &format!("/uploads/{}", local));
+                }
+            }
+        }
+        // Remaining links point at the old site
+        output.replace(GHOST_URL, self.source_url)
+    }
+}
+
+/// Ghost's own HTML, else its plaintext as paragraphs (exports from the
+/// Lexical editor can leave `html` empty).
+fn post_html(post: &Value) -> String {
+    let html = str_field(post, "html");
+    if !html.trim().is_empty() {
+        return html.to_string();
+    }
+    str_field(post, "plaintext")
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(|p| format!("<p>{}</p>", crate::render::html_escape(p)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Create an author account for each Ghost user, with a random password
+/// they must change on first login. Returns Ghost user id → display name.
+fn import_authors(
+    store: &dyn Store,
+    data: &Value,
+    result: &mut GhostImportResult,
+) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    for (id, user) in by_id(data, "users") {
+        let name = opt_field(user, "name").unwrap_or_else(|| "Ghost author".to_string());
+        names.insert(id, name.clone());
+        let Some(email) = opt_field(user, "email") else {
+            continue;
+        };
+        if store.user_get_by_email(&email).is_some() {
+            continue;
+        }
+        let temp_pass: String = (0..16)
+            .map(|_| {
+                let idx = rand::random::<u8>() % 62;
+                match idx {
+                    0..=9 => (b'0' + idx) as char,
+                    10..=35 => (b'a' + idx - 10) as char,
+                    _ => (b'A' + idx - 36) as char,
+                }
+            })
+            .collect();
+        let hash = crate::security::auth::hash_password_for(store, &temp_pass).unwrap_or_default();
+        match store.user_create(&email, &hash, &name, "author") {
+            Ok(new_id) => {
+                let _ = store.user_set_force_password_change(new_id, true);
+                result.created.users.push(new_id);
+                result.authors_imported += 1;
+                result.log.push(format!("Imported author: {}", name));
+            }
+            Err(e) => result.log.push(format!("Skipped author '{}': {}", name, e)),
+        }
+    }
+    names
+}
+
+/// Parse and import a Ghost JSON export. `source_url` is the old site's
+/// address, used to fetch images stored as `__GHOST_URL__/content/images/…`.
+pub fn import_ghost(
+    store: &dyn Store,
+    json: &str,
+    source_url: Option<&str>,
+) -> Result<GhostImportResult, String> {
+    let root: Value = serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+    // Exports wrap the tables in db[0].data; very old ones use data directly
+    let data = root
+        .get("db")
+        .and_then(|db| db.get(0))
+        .unwrap_or(&root)
+        .get("data")
+        .ok_or("Not a Ghost export: no data section")?;
+    let posts = data
+        .get("posts")
+        .and_then(|v| v.as_array())
+        .ok_or("Not a Ghost export: no posts")?;
+
+    let mut result = GhostImportResult {
+        posts_imported: 0,
+        authors_imported: 0,
+        skipped: 0,
+        media_downloaded: 0,
+        media_failed: 0,
+        log: Vec::new(),
+        created: ImportCreated::default(),
+    };
+
+    let source_url = source_url.unwrap_or("").trim().trim_end_matches('/');
+    let tags = by_id(data, "tags");
+    let post_tags = relations(data, "posts_tags", "tag_id");
+    let post_authors = relations(data, "posts_authors", "author_id");
+    // SEO fields moved to their own table in Ghost 4
+    let posts_meta: HashMap<String, &Value> = data
+        .get("posts_meta")
+        .and_then(|v| v.as_array())
+        .map(|rows| {
+            rows.iter()
+                .map(|r| (str_field(r, "post_id").to_string(), r))
+                .collect()
+        })
+        .unwrap_or_default();
+    let authors = import_authors(store, data, &mut result);
+    let mut media = Media {
+        store,
+        source_url,
+        done: HashMap::new(),
+    };
+
+    for post in posts {
+        let id = id_of(post);
+        let title = str_field(post, "title").trim();
+        let slug = str_field(post, "slug").trim();
+        let kind = post.get("type").and_then(|v| v.as_str()).unwrap_or("post");
+        let is_page = kind == "page" || post.get("page").and_then(|v| v.as_bool()) == Some(true);
+        if is_page {
+            result.skipped += 1;
+            result.log.push(format!("Skipped page: {}", title));
+            continue;
+        }
+        if title.is_empty() || slug.is_empty() {
+            result.skipped += 1;
+            result
+                .log
+                .push("Skipped post: missing title or slug".to_string());
+            continue;
+        }
+        if store.post_find_by_slug(slug).is_some() {
+            result.skipped += 1;
+            result
+                .log
+                .push(format!("Skipped post '{}': Duplicate slug", title));
+            continue;
+        }
+
+        let date = parse_ghost_date(str_field(post, "published_at"))
+            .or_else(|| parse_ghost_date(str_field(post, "created_at")));
+        let featured_image = opt_field(post, "feature_image")
+            .and_then(|raw| media.fetch(&raw, date.as_ref(), &mut result));
+        let content_html = media.rewrite(&post_html(post), date.as_ref(), &mut result);
+        let (content_html, downloaded, failed) = localize_remote_images(
+            store,
+            &content_html,
+            date.as_ref(),
+            title,
+            &mut result.log,
+            &mut result.created.media,
+        );
+        result.media_downloaded += downloaded;
+        result.media_failed += failed;
+        let meta = posts_meta.get(&id).copied();
+        let seo = |key: &str| opt_field(post, key).or_else(|| meta.and_then(|m| opt_field(m, key)));
+        let status = match str_field(post, "status") {
+            "published" | "sent" => "published",
+            "scheduled" => "scheduled",
+            _ => "draft",
+        };
+
+        let form = PostForm {
+            title: title.to_string(),
+            slug: slug.to_string(),
+            content_json: "{}".to_string(),
+            content_html,
+            excerpt: opt_field(post, "custom_excerpt"),
+            featured_image,
+            meta_title: seo("meta_title"),
+            meta_description: seo("meta_description"),
+            status: status.to_string(),
+            published_at: date.map(|d| d.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            category_ids: None,
+            tag_ids: None,
+            robots_noindex: None,
+            robots_nofollow: None,
+            canonical_url: opt_field(post, "canonical_url").or_else(|| seo("canonical_url")),
+            schema_type: None,
+            focus_keyword: None,
+            language: opt_field(post, "locale"),
+            translation_group: None,
+            audio_file: None,
+            audio_duration: None,
+            audio_bytes: None,
+            template: None,
+        };
+        let post_id = match store.post_create(&form) {
+            Ok(id) => id,
+            Err(e) => {
+                result.skipped += 1;
+                result.log.push(format!("Skipped post '{}': {}", title, e));
+                continue;
+            }
+        };
+        result.created.posts.push(post_id);
+
+        // Internal tags (#name) only organise things inside Ghost
+        let mut tag_ids = Vec::new();
+        for tag in post_tags
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|t| tags.get(t))
+        {
+            let name = str_field(tag, "name").trim();
+            if name.is_empty() || name.starts_with('#') {
+                continue;
+            }
+            let existing = store
+                .tag_find_by_slug(str_field(tag, "slug"))
+                .or_else(|| store.tag_find_by_slug(&slug::slugify(name)))
+                .map(|t| t.id);
+            let found = match existing {
+                Some(tag_id) => Ok(tag_id),
+                None => {
+                    let created = store.tag_find_or_create(name);
+                    if let Ok(tag_id) = created {
+                        result.created.tags.push(tag_id);
+                    }
+                    created
+                }
+            };
+            match found {
+                Ok(tag_id) if !tag_ids.contains(&tag_id) => tag_ids.push(tag_id),
+                Ok(_) => {}
+                Err(e) => result.log.push(format!("Tag '{}' failed: {}", name, e)),
+            }
+        }
+        if !tag_ids.is_empty() {
+            let _ = store.tag_set_for_content(post_id, "post", &tag_ids);
+        }
+
+        result.posts_imported += 1;
+        let by: Vec<&str> = post_authors
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|a| authors.get(a).map(|n| n.as_str()))
+            .collect();
+        if by.is_empty() {
+            result.log.push(format!("Imported post: {}", title));
+        } else {
+            result
+                .log
+                .push(format!("Imported post: {} (by {})", title, by.join(", ")));
+        }
+    }
+
+    // Record import in history
+    record_import(
+        store,
+        "ghost",
+        Some(source_url).filter(|s| !s.is_empty()),
+        (result.posts_imported, 0, 0, result.skipped),
+        &result.log,
+        &result.created,
+    );
+
+    Ok(result)
+}
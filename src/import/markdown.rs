@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+use crate::models::category::CategoryForm;
+use crate::models::import::ImportCreated;
+use crate::models::post::PostForm;
+use crate::store::Store;
+
+/// Result of a Markdown (Jekyll/Hugo) import
+pub struct MarkdownImportResult {
+    pub posts_imported: i64,
+    pub skipped: i64,
+    pub media_downloaded: i64,
+    pub media_failed: i64,
+    pub log: Vec<String>,
+    pub created: ImportCreated,
+}
+
+/// A front-matter value: a scalar or a list
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrontValue {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl FrontValue {
+    fn text(&self) -> Option<&str> {
+        match self {
+            FrontValue::Text(s) => Some(s.as_str()).filter(|s| !s.is_empty()),
+            FrontValue::List(_) => None,
+        }
+    }
+
+    /// Lists as they are; a string is split on commas, or on spaces as
+    /// Jekyll does when there are none.
+    fn items(&self) -> Vec<String> {
+        let items: Vec<&str> = match self {
+            FrontValue::List(l) => l.iter().map(|s| s.as_str()).collect(),
+            FrontValue::Text(s) if s.contains(',') => s.split(',').collect(),
+            FrontValue::Text(s) => s.split_whitespace().collect(),
+        };
+        items
+            .into_iter()
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    fn truthy(&self) -> bool {
+        matches!(self.text(), Some("true" | "yes" | "on"))
+    }
+}
+
+/// Strip matching quotes from a scalar
+fn unquote(raw: &str) -> String {
+    let raw = raw.trim();
+    for q in ['"', '\''] {
+        if raw.len() >= 2 && raw.starts_with(q) && raw.ends_with(q) {
+            return raw[1..raw.len() - 1].to_string();
+        }
+    }
+    raw.to_string()
+}
+
+/// A scalar, or an inline `[a, "b"]` list
+fn parse_value(raw: &str) -> FrontValue {
+    let raw = raw.trim();
+    match raw.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+        Some(inner) => FrontValue::List(
+            inner
+                .split(',')
+                .map(unquote)
+                .filter(|s| !s.is_empty())
+                .collect(),
+        ),
+        None => FrontValue::Text(unquote(raw)),
+    }
+}
+
+/// Split a file into its front matter (YAML between `---` lines, or
+/// Hugo's TOML between `+++` lines) and the markdown body. Only flat keys
+/// are read: scalars, inline lists and YAML `- item` lists.
+pub fn parse_front_matter(text: &str) -> (HashMap<String, FrontValue>, &str) {
+    let mut fields = HashMap::new();
+    let text = text.trim_start_matches('\u{feff}');
+    let Some(fence) = ["---", "+++"]
+        .into_iter()
+        .find(|f| text.lines().next().map(str::trim_end) == Some(*f))
+    else {
+        return (fields, text);
+    };
+    let separator = if fence == "---" { ':' } else { '=' };
+    let mut offset = text.find('\n').map(|i| i + 1).unwrap_or(text.len());
+    let mut last_key: Option<String> = None;
+    let mut body = None;
+    while offset < text.len() {
+        let end = text[offset..]
+            .find('\n')
+            .map(|i| offset + i + 1)
+            .unwrap_or(text.len());
+        let line = text[offset..end].trim_end();
+        offset = end;
+        if line == fence {
+            body = Some(&text[offset..]);
+            break;
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(item) = trimmed.strip_prefix("- ") {
+            if let Some(key) = &last_key {
+                match fields.get_mut(key) {
+                    Some(FrontValue::List(items)) => items.push(unquote(item)),
+                    _ => {
+                        fields.insert(key.clone(), FrontValue::List(vec![unquote(item)]));
+                    }
+                }
+            }
+            continue;
+        }
+        if line.starts_with(char::is_whitespace) {
+            // Nested maps aren't used for any mapped field
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(separator) {
+            let key = key.trim().to_lowercase();
+            if value.trim().is_empty() {
+                last_key = Some(key);
+            } else {
+                last_key = None;
+                fields.insert(key, parse_value(value));
+            }
+        }
+    }
+    match body {
+        Some(body) => (fields, body),
+        // No closing fence: it wasn't front matter after all
+        None => (HashMap::new(), text),
+    }
+}
+
+/// Front-matter dates: plain dates, "2024-01-15 10:30:00 +0100" (Jekyll)
+/// and RFC 3339 (Hugo).
+fn parse_date(raw: &str) -> Option<chrono::NaiveDateTime> {
+    let raw = raw.trim();
+    chrono::DateTime::parse_from_rfc3339(raw)
+        .map(|d| d.naive_utc())
+        .or_else(|_| {
+            chrono::DateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S %z").map(|d| d.naive_utc())
+        })
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S"))
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(raw, "%Y-%m-%dT%H:%M:%S"))
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M"))
+        .or_else(|_| {
+            chrono::NaiveDate::parse_from_str(raw.get(..10).unwrap_or(raw), "%Y-%m-%d")
+                .map(|d| d.and_hms_opt(0, 0, 0).unwrap_or_default())
+        })
+        .ok()
+}
+
+/// Slug and date implied by a path: Jekyll's `2024-01-15-my-post.md`, or
+/// the folder of a Hugo page bundle (`my-post/index.md`).
+fn slug_from_path(path: &str) -> (String, Option<chrono::NaiveDateTime>) {
+    let mut parts = path.rsplit('/');
+    let file = parts.next().unwrap_or("");
+    let stem = file.rsplit_once('.').map(|(s, _)| s).unwrap_or(file);
+    let stem = if stem == "index" {
+        parts.next().unwrap_or(stem)
+    } else {
+        stem
+    };
+    let dated = stem
+        .get(..10)
+        .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .filter(|_| stem.as_bytes().get(10) == Some(&b'-'));
+    match dated {
+        Some(date) => (slug::slugify(&stem[11..]), date.and_hms_opt(0, 0, 0)),
+        None => (slug::slugify(stem), None),
+    }
+}
+
+/// Whether a zip entry is a post to import: markdown outside hidden
+/// folders, and not a Hugo `_index.md` section page.
+fn is_post_file(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    let is_markdown = lower.ends_with(".md") || lower.ends_with(".markdown");
+    let file = lower.rsplit('/').next().unwrap_or("");
+    is_markdown
+        && !file.starts_with('_')
+        && !lower.split('/').any(|part| part.starts_with('.'))
+        && !matches!(file, "readme.md" | "license.md" | "changelog.md")
+}
+
+/// Import one markdown file as a post
+fn import_file(
+    store: &dyn Store,
+    path: &str,
+    text: &str,
+    result: &mut MarkdownImportResult,
+) -> Result<String, String> {
+    let (fields, body) = parse_front_matter(text);
+    let get = |key: &str| fields.get(key).and_then(|v| v.text());
+    let (path_slug, path_date) = slug_from_path(path);
+
+    let slug = get("slug")
+        .map(slug::slugify)
+        .filter(|s| !s.is_empty())
+        .unwrap_or(path_slug);
+    let title = get("title").map(str::to_string).unwrap_or_else(|| {
+        let words = slug.replace('-', " ");
+        let mut chars = words.chars();
+        chars
+            .next()
+            .map(|c| c.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    });
+    if title.is_empty() || slug.is_empty() {
+        return Err("Missing title or slug".to_string());
+    }
+    if store.post_find_by_slug(&slug).is_some() {
+        return Err("Duplicate slug".to_string());
+    }
+
+    let date = get("date").and_then(parse_date).or(path_date);
+    let is_draft = fields.get("draft").is_some_and(|v| v.truthy())
+        || get("published") == Some("false")
+        || path.split('/').any(|p| p == "_drafts");
+    let status = if is_draft {
+        "draft"
+    } else if date.is_some_and(|d| d > chrono::Utc::now().naive_utc()) {
+        "scheduled"
+    } else {
+        "published"
+    };
+
+    let (content_html, downloaded, failed) = super::localize_remote_images(
+        store,
+        &crate::render::markdown_to_html(body),
+        date.as_ref(),
+        &title,
+        &mut result.log,
+        &mut result.created.media,
+    );
+    result.media_downloaded += downloaded;
+    result.media_failed += failed;
+
+    let form = PostForm {
+        title: title.clone(),
+        slug: slug.clone(),
+        content_json: "{}".to_string(),
+        content_html,
+        excerpt: get("excerpt")
+            .or_else(|| get("summary"))
+            .or_else(|| get("description"))
+            .map(str::to_string),
+        featured_image: None,
+        meta_title: None,
+        meta_description: get("description").map(str::to_string),
+        status: status.to_string(),
+        published_at: date.map(|d| d.format("%Y-%m-%dT%H:%M:%S").to_string()),
+        category_ids: None,
+        tag_ids: None,
+        robots_noindex: None,
+        robots_nofollow: None,
+        canonical_url: get("canonical_url")
+            .or_else(|| get("canonicalurl"))
+            .map(str::to_string),
+        schema_type: None,
+        focus_keyword: None,
+        language: get("lang").map(str::to_string),
+        translation_group: None,
+        audio_file: None,
+        audio_duration: None,
+        audio_bytes: None,
+        template: None,
+    };
+    let post_id = store.post_create(&form)?;
+    result.created.posts.push(post_id);
+
+    let mut category_ids = Vec::new();
+    let categories = ["categories", "category"]
+        .iter()
+        .filter_map(|k| fields.get(*k))
+        .flat_map(|v| v.items());
+    for name in categories {
+        let cat_slug = slug::slugify(&name);
+        let cat_id = match store.category_find_by_slug(&cat_slug) {
+            Some(c) => c.id,
+            None => {
+                let id = store.category_create(&CategoryForm {
+                    name,
+                    slug: cat_slug,
+                    r#type: "post".to_string(),
+                })?;
+                result.created.categories.push(id);
+                id
+            }
+        };
+        if !category_ids.contains(&cat_id) {
+            category_ids.push(cat_id);
+        }
+    }
+    if !category_ids.is_empty() {
+        store.category_set_for_content(post_id, "post", &category_ids)?;
+    }
+
+    let mut tag_ids = Vec::new();
+    for name in fields.get("tags").map(|v| v.items()).unwrap_or_default() {
+        let is_new = store.tag_find_by_slug(&slug::slugify(&name)).is_none();
+        let tag_id = store.tag_find_or_create(&name)?;
+        if is_new {
+            result.created.tags.push(tag_id);
+        }
+        if !tag_ids.contains(&tag_id) {
+            tag_ids.push(tag_id);
+        }
+    }
+    if !tag_ids.is_empty() {
+        store.tag_set_for_content(post_id, "post", &tag_ids)?;
+    }
+
+    Ok(title)
+}
+
+/// Import every markdown file in a zip of a Jekyll or Hugo site (or any
+/// folder of `.md` files with front matter) as journal posts.
+pub fn import_markdown_zip(
+    store: &dyn Store,
+    bytes: &[u8],
+) -> Result<MarkdownImportResult, String> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("Invalid ZIP: {}", e))?;
+
+    let mut files: Vec<(String, String)> = Vec::new();
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
+        let name = file.name().to_string();
+        if file.is_dir() || !is_post_file(&name) {
+            continue;
+        }
+        let mut content = String::new();
+        if file.read_to_string(&mut content).is_ok() {
+            files.push((name, content));
+        }
+    }
+    if files.is_empty() {
+        return Err("No markdown files found in the ZIP".to_string());
+    }
+    files.sort();
+
+    let mut result = MarkdownImportResult {
+        posts_imported: 0,
+        skipped: 0,
+        media_downloaded: 0,
+        media_failed: 0,
+        log: Vec::new(),
+        created: ImportCreated::default(),
+    };
+    for (path, content) in &files {
+        match import_file(store, path, content, &mut result) {
+            Ok(title) => {
+                result.posts_imported += 1;
+                result.log.push(format!("Imported post: {}", title));
+            }
+            Err(e) => {
+                result.skipped += 1;
+                result.log.push(format!("Skipped {}: {}", path, e));
+            }
+        }
+    }
+
+    // Record import in history
+    super::record_import(
+        store,
+        "markdown",
+        None,
+        (result.posts_imported, 0, 0, result.skipped),
+        &result.log,
+        &result.created,
+    );
+
+    Ok(result)
+}
@@ -1,2 +1,395 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::models::import::ImportCreated;
+use crate::store::Store;
+
+pub mod ghost;
+pub mod markdown;
+pub mod squarespace;
 pub mod tumblr;
 pub mod wordpress;
+
+/// Redirects followed when downloading media, each checked like the first URL
+const MAX_REDIRECTS: usize = 5;
+
+/// Whether `ip` is on the public internet: not loopback, private,
+/// link-local, shared (CGNAT), multicast or otherwise reserved.
+pub(crate) fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+                || a >= 240)
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// Resolve an http(s) URL's host and refuse it unless every address is
+/// public, so imported HTML can't make the server fetch internal services.
+fn resolve_public(url: &reqwest::Url) -> Result<SocketAddr, String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!("Unsupported scheme: {}", url.scheme()));
+    }
+    let host = url.host_str().ok_or("URL has no host")?;
+    let port = url.port_or_known_default().unwrap_or(80);
+    let addrs: Vec<SocketAddr> = (host.trim_matches(['[', ']']), port)
+        .to_socket_addrs()
+        .map_err(|e| format!("Could not resolve {}: {}", host, e))?
+        .collect();
+    if addrs.is_empty() || addrs.iter().any(|a| !is_public_ip(a.ip())) {
+        return Err(format!("{} is not a public address", host));
+    }
+    Ok(addrs[0])
+}
+
+/// GET `url`, connecting only to the address `resolve_public` checked and
+/// re-checking every redirect hop.
+fn fetch_public(url: &str) -> Result<reqwest::blocking::Response, String> {
+    let mut url = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    for _ in 0..=MAX_REDIRECTS {
+        let addr = resolve_public(&url)?;
+        let host = url.host_str().unwrap_or_default().to_string();
+        let client = reqwest::blocking::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .resolve(&host, addr)
+            .connect_timeout(Duration::from_secs(10))
+            .timeout(Duration::from_secs(60))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let resp = client
+            .get(url.clone())
+            .send()
+            .map_err(|e| format!("Download failed: {}", e))?;
+        if !resp.status().is_redirection() {
+            return Ok(resp);
+        }
+        let location = resp
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|l| l.to_str().ok())
+            .ok_or("Redirect without a Location")?;
+        url = url
+            .join(location)
+            .map_err(|e| format!("Invalid redirect: {}", e))?;
+    }
+    Err("Too many redirects".to_string())
+}
+
+/// The upload extension for a downloaded file, from its leading bytes
+/// rather than the URL, which an exporter controls.
+pub(crate) fn sniff_extension(bytes: &[u8]) -> Option<&'static str> {
+    if let Ok(format) = image::guess_format(bytes) {
+        return match format {
+            image::ImageFormat::Jpeg => Some("jpg"),
+            image::ImageFormat::Png => Some("png"),
+            image::ImageFormat::Gif => Some("gif"),
+            image::ImageFormat::WebP => Some("webp"),
+            image::ImageFormat::Tiff => Some("tiff"),
+            image::ImageFormat::Avif => Some("avif"),
+            image::ImageFormat::Ico => Some("ico"),
+            image::ImageFormat::Bmp => Some("bmp"),
+            _ => None,
+        };
+    }
+    let head = &bytes[..bytes.len().min(512)];
+    if head.len() >= 12 && &head[4..8] == b"ftyp" {
+        return Some(match &head[8..12] {
+            b"qt  " => "mov",
+            b"heic" | b"heix" | b"hevc" | b"heim" | b"heis" | b"mif1" | b"msf1" => "heic",
+            _ => "mp4",
+        });
+    }
+    if head.starts_with(&[0x1a, 0x45, 0xdf, 0xa3]) {
+        let is_webm = head.windows(4).any(|w| w == b"webm");
+        return Some(if is_webm { "webm" } else { "mkv" });
+    }
+    if head.len() >= 12 && head.starts_with(b"RIFF") && &head[8..12] == b"AVI " {
+        return Some("avi");
+    }
+    let text = String::from_utf8_lossy(head).to_lowercase();
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    if text.starts_with("<svg") || (text.starts_with("<?xml") && text.contains("<svg")) {
+        return Some("svg");
+    }
+    None
+}
+
+/// Download a remote media file into uploads, respecting the
+/// media_organization setting, and run it through the same processing as
+/// an upload (SVG sanitizing, optimization, WebP). Only public hosts are
+/// fetched, the body is capped at the configured upload size, and the type
+/// must be one the media settings allow. Returns the path relative to
+/// uploads.
+pub(crate) fn download_media(
+    url: &str,
+    store: &dyn Store,
+    prefix: &str,
+    post_date: Option<&chrono::NaiveDateTime>,
+) -> Result<String, String> {
+    let mb = |key: &str, default: u64| {
+        store
+            .setting_get(key)
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|v| *v > 0)
+            .unwrap_or(default)
+            * 1024
+            * 1024
+    };
+    let image_limit = mb("images_max_upload_mb", 10);
+    let video_limit = mb("video_max_upload_mb", 100);
+
+    let resp = fetch_public(url)?;
+    if !resp.status().is_success() {
+        return Err(format!("HTTP {}", resp.status()));
+    }
+    let max = image_limit.max(video_limit);
+    if resp.content_length().is_some_and(|len| len > max) {
+        return Err("File is larger than the upload limit".to_string());
+    }
+    let mut bytes = Vec::new();
+    resp.take(max + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Read failed: {}", e))?;
+
+    let ext = sniff_extension(&bytes).ok_or("Unrecognised file type")?;
+    let is_video = matches!(ext, "mp4" | "mov" | "webm" | "mkv" | "avi");
+    let allowed = if is_video {
+        store.setting_get_or("video_upload_enabled", "false") == "true"
+            && crate::routes::admin::is_video_ext(ext, store)
+    } else {
+        store
+            .setting_get_or("images_allowed_types", "jpg,jpeg,png,gif,webp,svg,tiff")
+            .split(',')
+            .any(|a| a.trim().eq_ignore_ascii_case(ext))
+    };
+    if !allowed {
+        return Err(format!("File type .{} is not allowed", ext));
+    }
+    let limit = if is_video { video_limit } else { image_limit };
+    if bytes.len() as u64 > limit {
+        return Err("File is larger than the upload limit".to_string());
+    }
+
+    let subdir = if let Some(dt) = post_date {
+        crate::routes::admin::media_subdir_for_date(store, prefix, dt)
+    } else {
+        crate::routes::admin::media_subdir(store, prefix)
+    };
+
+    let uid = uuid::Uuid::new_v4();
+    let rel_path = format!("{}{}_{}.{}", subdir, prefix, uid, ext);
+    let upload_dir = Path::new("website/site/uploads");
+    let full_dir = upload_dir.join(&subdir);
+    let _ = std::fs::create_dir_all(&full_dir);
+    let dest_path = upload_dir.join(&rel_path);
+
+    let mut file = std::fs::File::create(&dest_path).map_err(|e| format!("Write failed: {}", e))?;
+    file.write_all(&bytes)
+        .map_err(|e| format!("Write failed: {}", e))?;
+    drop(file);
+
+    crate::routes::admin::process_upload(store, rel_path, prefix, &uid, ext)
+        .ok_or_else(|| "Rejected by upload processing".to_string())
+}
+
+/// With `import_fetch_remote_images` on, download every `<img>` in imported
+/// HTML that's hosted elsewhere and point it at the local copy (dropping
+/// its `srcset`, which still names the old host). Each failure is logged
+/// against `title` and that image left as it was. Returns the HTML and the
+/// number downloaded and failed; the saved files are added to `media`.
+pub(crate) fn localize_remote_images(
+    store: &dyn Store,
+    html: &str,
+    post_date: Option<&chrono::NaiveDateTime>,
+    title: &str,
+    log: &mut Vec<String>,
+    media: &mut Vec<String>,
+) -> (String, i64, i64) {
+    if !store.setting_get_bool("import_fetch_remote_images") {
+        return (html.to_string(), 0, 0);
+    }
+    let (Ok(img_re), Ok(src_re), Ok(srcset_re)) = (
+        regex::Regex::new(r"(?i)<img\b[^>]*>"),
+        regex::Regex::new(r#"(?i)\ssrc\s*=\s*["'](https?://[^"']+)["']"#),
+        regex::Regex::new(r#"(?i)\s(?:srcset|sizes)\s*=\s*("[^"]*"|'[^']*')"#),
+    ) else {
+        return (html.to_string(), 0, 0);
+    };
+    let site_url = store.setting_get_or("site_url", "");
+    let site_url = site_url.trim_end_matches('/');
+
+    let mut output = html.to_string();
+    let mut fetched: HashMap<String, Option<String>> = HashMap::new();
+    let (mut downloaded, mut failed) = (0, 0);
+    for tag in img_re.find_iter(html).map(|m| m.as_str()) {
+        let Some(src) = src_re.captures(tag).and_then(|c| c.get(1)) else {
+            continue;
+        };
+        let src = src.as_str();
+        if !site_url.is_empty() && src.starts_with(site_url) {
+            continue;
+        }
+        if !fetched.contains_key(src) {
+            let url = src.replace("&amp;", "&");
+            let local = match download_media(&url, store, "post", post_date) {
+                Ok(local) => {
+                    downloaded += 1;
+                    media.push(local.clone());
+                    Some(local)
+                }
+                Err(e) => {
+                    failed += 1;
+                    log.push(format!("Image failed in '{}': {} ({})", title, url, e));
+                    None
+                }
+            };
+            fetched.insert(src.to_string(), local);
+        }
+        if let Some(Some(local)) = fetched.get(src) {
+            let new_tag = replace_src(tag, &src_re, &format!("/uploads/{}", local));
+            let new_tag = srcset_re.replace_all(&new_tag, "");
+            output = output.replace(tag, &new_tag);
+        }
+    }
+    (output, downloaded, failed)
+}
+
+/// Rebuild the `src` attribute `src_re` matched in `tag`, leaving any other
+/// attribute that happens to contain the same URL alone.
+pub(crate) fn replace_src(tag: &str, src_re: &regex::Regex, new_src: &str) -> String {
+    match src_re.find(tag) {
+        Some(m) => format!(
+            "{} src=\"{}\"{}",
+            &tag[..m.start()],
+            new_src,
+            &tag[m.end()..]
+        ),
+        None => tag.to_string(),
+    }
+}
+
+/// Record what an import run created on its history entry, for rollback.
+pub(crate) fn record_import(
+    store: &dyn Store,
+    source: &str,
+    filename: Option<&str>,
+    counts: (i64, i64, i64, i64),
+    log: &[String],
+    created: &ImportCreated,
+) {
+    let log_json = serde_json::to_string(log).unwrap_or_default();
+    let (posts, portfolio, comments, skipped) = counts;
+    match store.import_create(
+        source,
+        filename,
+        posts,
+        portfolio,
+        comments,
+        skipped,
+        Some(&log_json),
+    ) {
+        Ok(id) => {
+            let _ = store.import_set_created(id, created);
+        }
+        Err(e) => log::error!("Failed to record {} import: {}", source, e),
+    }
+}
+
+/// How many of each thing a rollback removed
+#[derive(Debug, Default, serde::Serialize)]
+pub struct RollbackResult {
+    pub posts: i64,
+    pub portfolio: i64,
+    pub comments: i64,
+    pub categories: i64,
+    pub tags: i64,
+    pub users: i64,
+    pub media: i64,
+}
+
+/// Delete everything an import run created. Anything since deleted by
+/// hand is passed over, as are admin accounts. The run is marked rolled
+/// back so it can't be undone twice.
+pub fn rollback(store: &dyn Store, import_id: i64) -> Result<RollbackResult, String> {
+    let import = store
+        .import_find_by_id(import_id)
+        .ok_or("Import not found")?;
+    if import.rolled_back_at.is_some() {
+        return Err("This import has already been rolled back".into());
+    }
+    let created = &import.created;
+    if created.is_empty() {
+        return Err("This import didn't record what it created, so it can't be rolled back".into());
+    }
+
+    let mut r = RollbackResult::default();
+    for &id in &created.comments {
+        if store.comment_find_by_id(id).is_some() && store.comment_delete(id).is_ok() {
+            r.comments += 1;
+        }
+    }
+    for &id in &created.posts {
+        if store.post_find_by_id(id).is_some() && store.post_delete(id).is_ok() {
+            r.posts += 1;
+        }
+    }
+    for &id in &created.portfolio {
+        if store.portfolio_find_by_id(id).is_some() && store.portfolio_delete(id).is_ok() {
+            r.portfolio += 1;
+        }
+    }
+    for &id in &created.categories {
+        if store.category_find_by_id(id).is_some() && store.category_delete(id).is_ok() {
+            r.categories += 1;
+        }
+    }
+    for &id in &created.tags {
+        if store.tag_find_by_id(id).is_some() && store.tag_delete(id).is_ok() {
+            r.tags += 1;
+        }
+    }
+    for &id in &created.users {
+        let Some(user) = store.user_get_by_id(id) else {
+            continue;
+        };
+        if user.role != "admin" && store.user_delete(id).is_ok() {
+            r.users += 1;
+        }
+    }
+    let upload_dir = Path::new("website/site/uploads");
+    for rel in &created.media {
+        let safe = !rel.is_empty()
+            && Path::new(rel)
+                .components()
+                .all(|c| matches!(c, std::path::Component::Normal(_)));
+        if safe && std::fs::remove_file(upload_dir.join(rel)).is_ok() {
+            r.media += 1;
+        }
+    }
+
+    store.import_mark_rolled_back(import_id)?;
+    Ok(r)
+}
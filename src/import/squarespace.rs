@@ -0,0 +1,9 @@
+use crate::store::Store;
+
+use super::wordpress::{import_wxr_from, WpImportResult};
+
+/// Import a Squarespace export. Squarespace writes WordPress's WXR format,
+/// with images on its CDN; those are downloaded like WordPress uploads.
+pub fn import_squarespace(store: &dyn Store, xml_content: &str) -> Result<WpImportResult, String> {
+    import_wxr_from(store, xml_content, "squarespace")
+}
@@ -162,6 +162,11 @@ pub fn import_page(
         .get("video_upload_enabled")
         .map(|v| v == "true")
         .unwrap_or(false);
+    // Quotes, links, chats and asks: "journal" imports them as text posts
+    let other_as_journal = settings
+        .get("tumblr_other_types")
+        .map(|v| v == "journal")
+        .unwrap_or(false);
 
     let mut items: Vec<TumblrImportedItem> = Vec::new();
     let mut skipped = 0u64;
@@ -218,6 +223,14 @@ pub fn import_page(
                     skipped += 1;
                 }
             }
+            other if other_as_journal && OTHER_TYPES.contains(&other) => {
+                match other_type_as_text(other, post)
+                    .and_then(|text| import_text_post(store, &text, &tags, date))
+                {
+                    Some(item) => items.push(item),
+                    None => skipped += 1,
+                }
+            }
             _ => {
                 skipped += 1;
             }
@@ -316,6 +329,17 @@ fn import_text_post(
         published_at,
         category_ids: None,
         tag_ids: None,
+        robots_noindex: None,
+        robots_nofollow: None,
+        canonical_url: None,
+        schema_type: None,
+        focus_keyword: None,
+        language: None,
+        translation_group: None,
+        audio_file: None,
+        audio_duration: None,
+        audio_bytes: None,
+        template: None,
     };
 
     let post_id = store.post_create(&form).ok()?;
@@ -331,6 +355,92 @@ fn import_text_post(
     })
 }
 
+// ── Quote / Link / Chat / Ask → Journal ──────────────
+
+/// Tumblr post types with no Velocty equivalent, imported as journal posts
+/// when `tumblr_other_types` is "journal" and skipped otherwise
+pub const OTHER_TYPES: &[&str] = &["quote", "link", "chat", "answer"];
+
+/// A copy of a quote, link, chat or ask post with `title` and `body` set,
+/// so it can go through the text post import.
+pub fn other_type_as_text(post_type: &str, post: &Value) -> Option<Value> {
+    let field = |k: &str| post.get(k).and_then(|v| v.as_str()).unwrap_or("").trim();
+    let (title, body) = match post_type {
+        "quote" => {
+            let text = field("text");
+            if text.is_empty() {
+                return None;
+            }
+            let source = field("source");
+            let mut body = format!("<blockquote>{}</blockquote>", text);
+            if !source.is_empty() {
+                body.push_str(&format!("\n<p>— {}</p>", source));
+            }
+            (String::new(), body)
+        }
+        "link" => {
+            let url = field("url");
+            if url.is_empty() {
+                return None;
+            }
+            let label = Some(field("title"))
+                .filter(|t| !t.is_empty())
+                .unwrap_or(url);
+            let body = format!(
+                "<p><a href=\"{}\">{}</a></p>\n{}",
+                html_escape(url),
+                html_escape(label),
+                field("description")
+            );
+            (label.to_string(), body)
+        }
+        "chat" => {
+            let lines: Vec<String> = post
+                .get("dialogue")
+                .and_then(|v| v.as_array())
+                .map(|d| {
+                    d.iter()
+                        .map(|line| {
+                            let get = |k: &str| line.get(k).and_then(|v| v.as_str()).unwrap_or("");
+                            format!(
+                                "<p><strong>{}</strong> {}</p>",
+                                html_escape(get("label")),
+                                html_escape(get("phrase"))
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            if lines.is_empty() {
+                return None;
+            }
+            (field("title").to_string(), lines.join("\n"))
+        }
+        "answer" => {
+            let question = field("question");
+            let answer = field("answer");
+            if question.is_empty() && answer.is_empty() {
+                return None;
+            }
+            let asker = Some(field("asking_name"))
+                .filter(|a| !a.is_empty())
+                .unwrap_or("Anonymous");
+            let body = format!(
+                "<blockquote><p><strong>{}</strong> asked: {}</p></blockquote>\n{}",
+                html_escape(asker),
+                html_escape(question),
+                answer
+            );
+            (strip_html(question).chars().take(80).collect(), body)
+        }
+        _ => return None,
+    };
+    let mut text = post.clone();
+    text["title"] = Value::String(title);
+    text["body"] = Value::String(body);
+    Some(text)
+}
+
 // ── Photo Post → Portfolio ───────────────────────────
 
 fn import_photo_post(
@@ -444,6 +554,12 @@ fn import_photo_post(
         published_at,
         category_ids: None,
         tag_ids: None,
+        robots_noindex: None,
+        robots_nofollow: None,
+        canonical_url: None,
+        schema_type: None,
+        focus_keyword: None,
+        template: None,
     };
 
     let item_id = store.portfolio_create(&form).ok()?;
@@ -605,6 +721,17 @@ fn import_video_post(
             published_at,
             category_ids: None,
             tag_ids: None,
+            robots_noindex: None,
+            robots_nofollow: None,
+            canonical_url: None,
+            schema_type: None,
+            focus_keyword: None,
+            language: None,
+            translation_group: None,
+            audio_file: None,
+            audio_duration: None,
+            audio_bytes: None,
+            template: None,
         };
 
         let item_id = store.post_create(&form).ok()?;
@@ -649,6 +776,12 @@ fn import_video_post(
         published_at,
         category_ids: None,
         tag_ids: None,
+        robots_noindex: None,
+        robots_nofollow: None,
+        canonical_url: None,
+        schema_type: None,
+        focus_keyword: None,
+        template: None,
     };
 
     let item_id = store.portfolio_create(&form).ok()?;
@@ -739,6 +872,7 @@ pub fn suggest_for_item(
         max_tokens: Some(512),
         temperature: Some(0.7),
         image_base64,
+        feature: "tumblr_import".to_string(),
     };
 
     let resp = crate::ai::complete(store, &req).map_err(|e| e.0)?;
@@ -816,6 +950,17 @@ pub fn apply_updates(store: &dyn Store, updates: &[ApplyUpdate]) -> Result<u64,
                         published_at: post.published_at.map(|d| d.to_string()),
                         category_ids: None,
                         tag_ids: None,
+                        robots_noindex: None,
+                        robots_nofollow: None,
+                        canonical_url: None,
+                        schema_type: None,
+                        focus_keyword: None,
+                        language: None,
+                        translation_group: None,
+                        audio_file: None,
+                        audio_duration: None,
+                        audio_bytes: None,
+                        template: post.template,
                     };
                     let _ = store.post_update(update.id, &form);
                 }
@@ -876,6 +1021,12 @@ pub fn apply_updates(store: &dyn Store, updates: &[ApplyUpdate]) -> Result<u64,
                         published_at: item.published_at.map(|d| d.to_string()),
                         category_ids: None,
                         tag_ids: None,
+                        robots_noindex: None,
+                        robots_nofollow: None,
+                        canonical_url: None,
+                        schema_type: None,
+                        focus_keyword: None,
+                        template: item.template,
                     };
                     let _ = store.portfolio_update(update.id, &form);
                 }
@@ -1,15 +1,15 @@
-use std::collections::HashMap;
-use std::io::Write;
-use std::path::Path;
-
 use quick_xml::events::Event;
 use quick_xml::Reader;
+use std::collections::HashMap;
 
 use crate::models::category::CategoryForm;
 use crate::models::comment::CommentForm;
+use crate::models::import::ImportCreated;
 use crate::models::post::PostForm;
 use crate::store::Store;
 
+use super::{download_media, localize_remote_images, record_import};
+
 /// Result of a WordPress import
 pub struct WpImportResult {
     pub posts_imported: i64,
@@ -19,6 +19,7 @@ pub struct WpImportResult {
     pub media_downloaded: i64,
     pub media_failed: i64,
     pub log: Vec<String>,
+    pub created: ImportCreated,
 }
 
 /// Parsed WP comment with parent_id for threading
@@ -51,6 +52,16 @@ struct WpItem {
 
 /// Parse and import a WordPress WXR XML export file
 pub fn import_wxr(store: &dyn Store, xml_content: &str) -> Result<WpImportResult, String> {
+    import_wxr_from(store, xml_content, "wordpress")
+}
+
+/// Import a WXR file from any platform that writes one (WordPress,
+/// Squarespace), recording it in history under `source`.
+pub(crate) fn import_wxr_from(
+    store: &dyn Store,
+    xml_content: &str,
+    source: &str,
+) -> Result<WpImportResult, String> {
     let mut result = WpImportResult {
         posts_imported: 0,
         portfolio_imported: 0,
@@ -59,6 +70,7 @@ pub fn import_wxr(store: &dyn Store, xml_content: &str) -> Result<WpImportResult
         media_downloaded: 0,
         media_failed: 0,
         log: Vec::new(),
+        created: ImportCreated::default(),
     };
 
     // Phase 1: Parse all items from XML
@@ -84,6 +96,7 @@ pub fn import_wxr(store: &dyn Store, xml_content: &str) -> Result<WpImportResult
                     match download_media(url, store, "post", post_dt.as_ref()) {
                         Ok(local) => {
                             result.media_downloaded += 1;
+                            result.created.media.push(local.clone());
                             Some(local)
                         }
                         Err(e) => {
@@ -106,6 +119,17 @@ pub fn import_wxr(store: &dyn Store, xml_content: &str) -> Result<WpImportResult
                     post_dt.as_ref(),
                     &mut result,
                 );
+                // Then any other remote images
+                let (content, downloaded, failed) = localize_remote_images(
+                    store,
+                    &content,
+                    post_dt.as_ref(),
+                    &item.title,
+                    &mut result.log,
+                    &mut result.created.media,
+                );
+                result.media_downloaded += downloaded;
+                result.media_failed += failed;
 
                 match import_post(
                     store,
@@ -119,6 +143,7 @@ pub fn import_wxr(store: &dyn Store, xml_content: &str) -> Result<WpImportResult
                     &item.tags,
                     &item.comments,
                     featured_local.as_deref(),
+                    &mut result.created,
                 ) {
                     Ok(_) => {
                         result.posts_imported += 1;
@@ -141,15 +166,18 @@ pub fn import_wxr(store: &dyn Store, xml_content: &str) -> Result<WpImportResult
     }
 
     // Record import in history
-    let log_json = serde_json::to_string(&result.log).unwrap_or_default();
-    let _ = store.import_create(
-        "wordpress",
+    record_import(
+        store,
+        source,
         None,
-        result.posts_imported,
-        result.portfolio_imported,
-        result.comments_imported,
-        result.skipped,
-        Some(&log_json),
+        (
+            result.posts_imported,
+            result.portfolio_imported,
+            result.comments_imported,
+            result.skipped,
+        ),
+        &result.log,
+        &result.created,
     );
 
     Ok(result)
@@ -374,46 +402,6 @@ fn find_featured_image(item: &WpItem, attachment_map: &HashMap<i64, String>) ->
     None
 }
 
-/// Download a media file respecting the media_organization setting.
-fn download_media(
-    url: &str,
-    store: &dyn Store,
-    prefix: &str,
-    post_date: Option<&chrono::NaiveDateTime>,
-) -> Result<String, String> {
-    let orig_filename = url.rsplit('/').next().unwrap_or("image.jpg").to_string();
-    let ext = orig_filename
-        .rsplit('.')
-        .next()
-        .unwrap_or("jpg")
-        .to_lowercase();
-
-    let subdir = if let Some(dt) = post_date {
-        crate::routes::admin::media_subdir_for_date(store, prefix, dt)
-    } else {
-        crate::routes::admin::media_subdir(store, prefix)
-    };
-
-    let uid = uuid::Uuid::new_v4();
-    let rel_path = format!("{}{}_{}.{}", subdir, prefix, uid, ext);
-    let upload_dir = Path::new("website/site/uploads");
-    let full_dir = upload_dir.join(&subdir);
-    let _ = std::fs::create_dir_all(&full_dir);
-    let dest_path = upload_dir.join(&rel_path);
-
-    let resp = reqwest::blocking::get(url).map_err(|e| format!("Download failed: {}", e))?;
-    if !resp.status().is_success() {
-        return Err(format!("HTTP {}", resp.status()));
-    }
-    let bytes = resp.bytes().map_err(|e| format!("Read failed: {}", e))?;
-
-    let mut file = std::fs::File::create(&dest_path).map_err(|e| format!("Write failed: {}", e))?;
-    file.write_all(&bytes)
-        .map_err(|e| format!("Write failed: {}", e))?;
-
-    Ok(rel_path)
-}
-
 /// Parse WordPress date string into NaiveDateTime for media organization.
 fn parse_wp_date_to_naive(date: &str) -> Option<chrono::NaiveDateTime> {
     if date.is_empty() {
@@ -434,8 +422,8 @@ fn rewrite_inline_images(
 ) -> String {
     let mut output = content.to_string();
 
-    // Find all wp-content/uploads URLs in the HTML
-    let re_pattern = "https?://[^\"'\\s]+/wp-content/uploads/[^\"'\\s]+";
+    // Find all wp-content/uploads URLs in the HTML, and the Squarespace CDN
+    let re_pattern = "https?://(?:[^\"'\\s]+/wp-content/uploads/|images\\.squarespace-cdn\\.com/|static1\\.squarespace\\.com/static/)[^\"'\\s]+";
     if let Ok(re) = regex::Regex::new(re_pattern) {
         let urls: Vec<String> = re
             .find_iter(content)
@@ -447,6 +435,7 @@ fn rewrite_inline_images(
                     let html_path = format!("/uploads/{}", local_path);
                     output = output.replace(&url, &html_path);
                     result.media_downloaded += 1;
+                    result.created.media.push(local_path);
                 }
                 Err(_) => {
                     result.media_failed += 1;
@@ -471,6 +460,7 @@ fn import_post(
     tags: &[String],
     comments: &[WpComment],
     featured_image: Option<&str>,
+    created: &mut ImportCreated,
 ) -> Result<i64, String> {
     if title.is_empty() || slug.is_empty() {
         return Err("Missing title or slug".to_string());
@@ -514,27 +504,47 @@ fn import_post(
         },
         category_ids: None,
         tag_ids: None,
+        robots_noindex: None,
+        robots_nofollow: None,
+        canonical_url: None,
+        schema_type: None,
+        focus_keyword: None,
+        language: None,
+        translation_group: None,
+        audio_file: None,
+        audio_duration: None,
+        audio_bytes: None,
+        template: None,
     };
 
     let post_id = store.post_create(&form)?;
+    created.posts.push(post_id);
 
     // Import categories
     for cat_name in categories {
         let cat_slug = slug::slugify(cat_name);
         let cat_id = match store.category_find_by_slug(&cat_slug) {
             Some(c) => c.id,
-            None => store.category_create(&CategoryForm {
-                name: cat_name.clone(),
-                slug: cat_slug,
-                r#type: "post".to_string(),
-            })?,
+            None => {
+                let id = store.category_create(&CategoryForm {
+                    name: cat_name.clone(),
+                    slug: cat_slug,
+                    r#type: "post".to_string(),
+                })?;
+                created.categories.push(id);
+                id
+            }
         };
         store.category_set_for_content(post_id, "post", &[cat_id])?;
     }
 
     // Import tags
     for tag_name in tags {
+        let is_new = store.tag_find_by_slug(&slug::slugify(tag_name)).is_none();
         let tag_id = store.tag_find_or_create(tag_name)?;
+        if is_new {
+            created.tags.push(tag_id);
+        }
         store.tag_set_for_content(post_id, "post", &[tag_id])?;
     }
 
@@ -555,6 +565,7 @@ fn import_post(
             parent_id: None,
         };
         if let Ok(cid) = store.comment_create(&comment_form) {
+            created.comments.push(cid);
             let _ = store.comment_update_status(cid, &c.status);
             if c.wp_id > 0 {
                 wp_comment_map.insert(c.wp_id, cid);
@@ -11,17 +11,21 @@ use rocket_dyn_templates::Template;
 
 mod ai;
 mod analytics;
+mod backup;
 mod boot;
 mod db;
 mod designs;
 mod email;
+mod geoip;
 mod health;
 mod image_proxy;
 mod images;
 mod mta;
+mod outbound;
 mod rate_limit;
 mod render;
 mod rss;
+mod search_engine;
 mod security;
 mod seo;
 mod svg_sanitizer;
@@ -43,8 +47,9 @@ mod tests;
 use std::sync::{Arc, RwLock};
 
 use rocket::fairing::{Fairing, Info, Kind};
-use rocket::http::{uri::Origin, Header};
+use rocket::http::{uri::Origin, Header, Method};
 use rocket::response::content::RawHtml;
+use rocket::response::Redirect;
 
 use models::settings::SettingsCache;
 use store::Store;
@@ -155,7 +160,7 @@ impl Fairing for NoCacheAdmin {
 }
 
 #[catch(404)]
-fn not_found(req: &rocket::Request<'_>) -> RawHtml<String> {
+fn not_found(req: &rocket::Request<'_>) -> Result<RawHtml<String>, Redirect> {
     let slug = req
         .rocket()
         .state::<AdminSlug>()
@@ -166,10 +171,10 @@ fn not_found(req: &rocket::Request<'_>) -> RawHtml<String> {
     if let Some(setup) = req.rocket().state::<SetupMode>() {
         if setup.0 {
             let setup_url = format!("/{}/setup", slug);
-            return RawHtml(format!(
+            return Ok(RawHtml(format!(
                 "<html><head><meta http-equiv=\"refresh\" content=\"0;url={}\"></head><body></body></html>",
                 setup_url
-            ));
+            )));
         }
     }
 
@@ -181,32 +186,102 @@ fn not_found(req: &rocket::Request<'_>) -> RawHtml<String> {
         && !path.ends_with("/setup")
     {
         let login_url = format!("/{}/login", slug);
-        return RawHtml(format!(
+        return Ok(RawHtml(format!(
                 "<html><head><meta http-equiv=\"refresh\" content=\"0;url={}\"></head><body></body></html>",
                 login_url
-            ));
+            )));
     }
 
     if let Some(store) = req.rocket().state::<Arc<dyn Store>>() {
         let s: &dyn Store = &**store;
-        let settings = s.setting_all();
-        let nav_cats = s.category_list_nav_visible(Some("portfolio"));
-        let nav_journal_cats = s.category_list_nav_visible(Some("post"));
+
+        // Configured redirects (e.g. old WordPress slugs) before the 404 page
+        if matches!(req.method(), Method::Get | Method::Head) {
+            let rules = s.redirect_list();
+            if let Some((rule, mut target)) = models::redirect::find_match(&rules, path) {
+                if let Some(query) = req.uri().query().filter(|_| !target.contains('?')) {
+                    target = format!("{}?{}", target, query);
+                }
+                s.redirect_hit(rule.id);
+                return Err(if rule.status_code == 302 {
+                    Redirect::found(target)
+                } else {
+                    Redirect::moved(target)
+                });
+            }
+        }
+
+        // Count the miss for the SEO audit's top-404s panel; a background
+        // task writes the counts in batches
+        if req.method() == Method::Get && path.len() <= models::redirect::NOT_FOUND_MAX_LEN {
+            if let Some(buffer) = req
+                .rocket()
+                .state::<Arc<models::redirect::NotFoundBuffer>>()
+            {
+                let referrer = req
+                    .headers()
+                    .get_one("Referer")
+                    .filter(|r| !r.is_empty() && r.len() <= models::redirect::NOT_FOUND_MAX_LEN);
+                buffer.record(path, referrer);
+            }
+        }
+
+        // Bots probing for missing paths make 404s one of the commonest renders
+        let ctx = match req.rocket().state::<SettingsCache>() {
+            Some(cache) => cache.render_context(s),
+            None => Arc::new(models::settings::RenderContext::load(s)),
+        };
         let context = serde_json::json!({
-            "settings": settings,
-            "nav_categories": nav_cats,
-            "nav_journal_categories": nav_journal_cats,
+            "settings": ctx.settings,
+            "nav_categories": ctx.nav_categories,
+            "nav_journal_categories": ctx.nav_journal_categories,
             "page_type": "404",
             "seo": "<title>404 — Page Not Found</title>",
         });
-        return RawHtml(render::render_page(s, "404", &context));
+        let nonce = security::headers::CspNonce::for_request(req).0;
+        return Ok(RawHtml(render::with_csp_nonce(nonce.as_deref(), || {
+            designs::error_pages::render(s, 404, &context)
+        })));
     }
-    RawHtml("<html><body style='font-family:sans-serif;text-align:center;padding:80px'><h1>404</h1><p>Page not found.</p><a href='/'>← Home</a></body></html>".to_string())
+    Ok(RawHtml(designs::error_pages::static_fallback(404)))
+}
+
+/// Raised by the `SudoMode` guard; the admin UI asks the user to confirm
+/// their identity and retries the request.
+#[catch(428)]
+fn sudo_required() -> rocket::serde::json::Json<serde_json::Value> {
+    rocket::serde::json::Json(serde_json::json!({
+        "ok": false,
+        "success": false,
+        "sudo_required": true,
+        "error": "Confirm your identity to continue",
+    }))
 }
 
 #[catch(500)]
-fn server_error() -> RawHtml<String> {
-    RawHtml("<html><body style='font-family:sans-serif;text-align:center;padding:80px'><h1>500</h1><p>Internal server error.</p><a href='/'>← Home</a></body></html>".to_string())
+fn server_error(req: &rocket::Request<'_>) -> RawHtml<String> {
+    // Admin pages keep the plain page: the design may be what's broken
+    let path = req.uri().path().as_str();
+    if path.starts_with(ADMIN_INTERNAL_MOUNT) {
+        return RawHtml(designs::error_pages::static_fallback(500));
+    }
+    match req.rocket().state::<Arc<dyn Store>>() {
+        Some(store) => {
+            let s: &dyn Store = &**store;
+            let context = serde_json::json!({
+                "settings": s.setting_all(),
+                "nav_categories": s.category_list_nav_visible(Some("portfolio")),
+                "nav_journal_categories": s.category_list_nav_visible(Some("post")),
+                "page_type": "500",
+                "seo": "<title>500 — Server Error</title>",
+            });
+            let nonce = security::headers::CspNonce::for_request(req).0;
+            RawHtml(render::with_csp_nonce(nonce.as_deref(), || {
+                designs::error_pages::render(s, 500, &context)
+            }))
+        }
+        None => RawHtml(designs::error_pages::static_fallback(500)),
+    }
 }
 
 /// Read velocty.toml and return the backend string ("sqlite", "mongodb", or empty if missing).
@@ -261,29 +336,35 @@ fn create_store() -> Option<Arc<dyn Store>> {
 fn rocket() -> _ {
     env_logger::init();
 
-    // Boot check — verify/create directories, validate critical files
-    boot::run();
     health::init_uptime();
 
-    let maybe_store = create_store();
+    // Boot check — verify/create directories, validate critical files and
+    // compile the templates
+    boot::timed("Boot checks", boot::run);
+
+    let maybe_store = boot::timed("Database connection", create_store);
 
     if let Some(store) = maybe_store {
         // ── Full server mode: DB is configured ──
 
         // Run migrations and seed defaults
-        store
-            .run_migrations()
+        boot::timed("Migrations", || store.run_migrations())
             .expect("Failed to run database migrations");
-        store
-            .seed_defaults()
+        boot::timed("Default settings", || store.seed_defaults())
             .expect("Failed to seed default settings");
 
+        // Broken designs are reported now rather than on a visitor's request
+        for problem in boot::timed("Design check", || designs::check::problems(&*store)) {
+            log::warn!("[boot] {}", problem);
+        }
+
         let backend = read_config_backend();
 
         let admin_slug = store.setting_get_or("admin_slug", "admin");
         let admin_api_mount = format!("{}/api", ADMIN_INTERNAL_MOUNT);
 
-        let settings_cache = SettingsCache::load_from_store(&*store);
+        let settings_cache =
+            boot::timed("Settings cache", || SettingsCache::load_from_store(&*store));
 
         eprintln!("Database backend: {}", backend);
         eprintln!(
@@ -300,9 +381,15 @@ fn rocket() -> _ {
             .manage(settings_cache)
             .manage(rate_limit::RateLimiter::new())
             .manage(security::firewall::FwRateLimiter::new())
+            .manage(analytics::LiveVisitors::new())
+            .manage(Arc::new(models::redirect::NotFoundBuffer::new()))
             .attach(Template::fairing())
             .attach(AdminSlugRewriter)
             .attach(security::firewall::FirewallFairing)
+            .attach(rate_limit::ApiRateLimit)
+            .attach(security::cors::Cors)
+            .attach(security::headers::SecurityHeaders)
+            .attach(render::conditional::ConditionalGet)
             .attach(analytics::AnalyticsFairing)
             .attach(NoCacheAdmin)
             .attach(tasks::BackgroundTasks)
@@ -312,39 +399,55 @@ fn rocket() -> _ {
             .mount(&admin_api_mount, routes::admin::api_routes())
             .mount(&admin_api_mount, routes::ai::routes())
             .mount("/api", routes::api::routes())
+            .mount("/api/v1", routes::api_v1::routes())
+            .mount("/api", routes::openapi::routes())
+            .mount("/api/automation", routes::automation::routes())
             .mount("/api", routes::deploy::public_routes())
             .mount(&admin_api_mount, routes::deploy::admin_routes())
             .mount("/", routes::commerce::routes())
+            .mount("/account", routes::account::routes())
             .mount(ADMIN_INTERNAL_MOUNT, routes::security::routes())
-            .register("/", catchers![not_found, server_error]);
+            .register("/", catchers![not_found, sudo_required, server_error]);
 
         // SQLite backend: also manage the raw DbPool for SQLite-specific
         // health tools (VACUUM, WAL checkpoint, integrity check)
         if backend != "mongodb" {
-            let pool = db::init_pool().expect("Failed to initialize SQLite pool");
-            db::run_migrations(&pool).expect("Failed to run SQLite migrations");
-            db::seed_defaults(&pool).expect("Failed to seed SQLite defaults");
+            let pool = boot::timed("SQLite tools pool", || {
+                let pool = db::init_pool().expect("Failed to initialize SQLite pool");
+                db::run_migrations(&pool).expect("Failed to run SQLite migrations");
+                db::seed_defaults(&pool).expect("Failed to seed SQLite defaults");
+                pool
+            });
             rocket = rocket.manage(pool);
         }
 
         // Multi-site: initialize registry and mount super admin routes
         #[cfg(feature = "multi-site")]
         {
-            let registry = site::init_registry().expect("Failed to initialize site registry");
-            site::run_registry_migrations(&registry).expect("Failed to run registry migrations");
+            let registry = boot::timed("Site registry", || {
+                let registry = site::init_registry().expect("Failed to initialize site registry");
+                site::run_registry_migrations(&registry)
+                    .expect("Failed to run registry migrations");
+                registry
+            });
 
             // Auto-migrate single-site data into multi-site if website/site/ exists
             if let Err(e) = site::migrate_single_to_multi(&registry, "localhost", "My Site") {
                 eprintln!("Warning: single→multi migration failed: {}", e);
             }
 
+            // Certificates for mapped domains, when velocty.toml enables ACME
+            site::acme::spawn_renewal(&registry);
+            site::backup::spawn_scheduler(&registry);
+
             eprintln!("Multi-site mode enabled. Super admin at: /super/");
             rocket = rocket
                 .manage(registry)
                 .manage(site::SiteStoreManager::new())
                 .manage(site::SitePoolManager::new())
                 .attach(site::SiteResolver)
-                .mount("/super", routes::super_admin::routes());
+                .mount("/super", routes::super_admin::routes())
+                .mount("/", routes::super_admin::root_routes());
         }
 
         rocket
@@ -361,6 +464,6 @@ fn rocket() -> _ {
             .attach(NoCacheAdmin)
             .mount("/static", FileServer::from("website/static"))
             .mount(ADMIN_INTERNAL_MOUNT, routes::security::setup_only_routes())
-            .register("/", catchers![not_found, server_error])
+            .register("/", catchers![not_found, sudo_required, server_error])
     }
 }
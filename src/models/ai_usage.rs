@@ -0,0 +1,31 @@
+use serde::Serialize;
+
+/// One call to an AI provider.
+#[derive(Debug, Clone, Serialize)]
+pub struct AiUsage {
+    pub id: i64,
+    pub provider: String,
+    pub model: String,
+    /// What made the call: "suggest_meta", "generate_post", "embeddings", …
+    pub feature: String,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub latency_ms: i64,
+    pub success: bool,
+    pub error: String,
+    /// UTC, "YYYY-MM-DD HH:MM:SS"
+    pub created_at: String,
+}
+
+/// Calls and tokens summed per (provider, model, feature) over a period.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct AiUsageTotal {
+    pub provider: String,
+    pub model: String,
+    pub feature: String,
+    pub calls: i64,
+    pub failures: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub latency_ms: i64,
+}
@@ -15,9 +15,54 @@ pub struct PageView {
     pub user_agent: Option<String>,
     pub device_type: Option<String>,
     pub browser: Option<String>,
+    pub utm_source: Option<String>,
+    pub utm_medium: Option<String>,
+    pub utm_campaign: Option<String>,
+    pub utm_term: Option<String>,
+    pub utm_content: Option<String>,
     pub created_at: NaiveDateTime,
 }
 
+/// Campaign parameters from a landing page's query string.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Utm {
+    pub source: Option<String>,
+    pub medium: Option<String>,
+    pub campaign: Option<String>,
+    pub term: Option<String>,
+    pub content: Option<String>,
+}
+
+/// Longest UTM value kept; anything past it is noise or abuse.
+const UTM_MAX_LEN: usize = 100;
+
+impl Utm {
+    /// Read the utm_* parameters from a raw query string. Empty values are
+    /// ignored and long ones are cut short.
+    pub fn from_query(query: &str) -> Utm {
+        let mut utm = Utm::default();
+        for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+            let slot = match key.as_ref() {
+                "utm_source" => &mut utm.source,
+                "utm_medium" => &mut utm.medium,
+                "utm_campaign" => &mut utm.campaign,
+                "utm_term" => &mut utm.term,
+                "utm_content" => &mut utm.content,
+                _ => continue,
+            };
+            let value = value.trim();
+            if slot.is_none() && !value.is_empty() {
+                *slot = Some(value.chars().take(UTM_MAX_LEN).collect());
+            }
+        }
+        utm
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Utm::default()
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct OverviewStats {
     pub total_views: i64,
@@ -54,6 +99,67 @@ pub struct StreamEntry {
     pub count: i64,
 }
 
+#[derive(Debug, Serialize)]
+pub struct CampaignEntry {
+    pub campaign: String,
+    pub source: String,
+    pub medium: String,
+    pub views: i64,
+    pub visitors: i64,
+}
+
+/// How often one site search query was made.
+#[derive(Debug, Serialize)]
+pub struct SearchQueryStat {
+    pub query: String,
+    pub searches: i64,
+    /// Results the latest of these searches returned
+    pub results: i64,
+    /// Searches after which a result was clicked
+    pub clicks: i64,
+}
+
+/// Clicks on one external link.
+#[derive(Debug, Serialize)]
+pub struct OutboundEntry {
+    pub url: String,
+    pub clicks: i64,
+    pub visitors: i64,
+}
+
+/// Scroll depth and time on page, as sent by the engagement beacon when a
+/// visitor leaves a page.
+#[derive(Debug, Deserialize)]
+pub struct EngagementPing {
+    pub path: String,
+    /// Furthest point scrolled to, in percent of the page
+    pub scroll: i64,
+    /// Seconds the page was visible
+    pub seconds: i64,
+}
+
+/// How far and how long visitors read one page, from engagement pings.
+#[derive(Debug, Serialize, Default)]
+pub struct EngagementStats {
+    /// Page views that reported back
+    pub pings: i64,
+    pub avg_scroll: f64,
+    pub avg_seconds: f64,
+    /// Percentage of those views that scrolled to the end (90% or more)
+    pub read_through: f64,
+}
+
+/// Traffic to a single post or portfolio item.
+#[derive(Debug, Serialize, Default)]
+pub struct PathStats {
+    pub views: i64,
+    pub unique_visitors: i64,
+    pub daily: Vec<DailyCount>,
+}
+
+/// UTM dimensions the campaign breakdown can group by.
+pub const UTM_DIMENSIONS: &[&str] = &["source", "medium", "campaign", "term", "content"];
+
 #[derive(Debug, Serialize)]
 pub struct TagRelation {
     pub source: String,
@@ -72,12 +178,53 @@ impl PageView {
         user_agent: Option<&str>,
         device_type: Option<&str>,
         browser: Option<&str>,
+    ) -> Result<(), String> {
+        Self::record_with_utm(
+            pool,
+            path,
+            ip_hash,
+            country,
+            city,
+            referrer,
+            user_agent,
+            device_type,
+            browser,
+            &Utm::default(),
+        )
+    }
+
+    pub fn record_with_utm(
+        pool: &DbPool,
+        path: &str,
+        ip_hash: &str,
+        country: Option<&str>,
+        city: Option<&str>,
+        referrer: Option<&str>,
+        user_agent: Option<&str>,
+        device_type: Option<&str>,
+        browser: Option<&str>,
+        utm: &Utm,
     ) -> Result<(), String> {
         let conn = pool.get().map_err(|e| e.to_string())?;
         conn.execute(
-            "INSERT INTO page_views (path, ip_hash, country, city, referrer, user_agent, device_type, browser)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![path, ip_hash, country, city, referrer, user_agent, device_type, browser],
+            "INSERT INTO page_views (path, ip_hash, country, city, referrer, user_agent, device_type, browser,
+                utm_source, utm_medium, utm_campaign, utm_term, utm_content)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+            params![
+                path,
+                ip_hash,
+                country,
+                city,
+                referrer,
+                user_agent,
+                device_type,
+                browser,
+                utm.source,
+                utm.medium,
+                utm.campaign,
+                utm.term,
+                utm.content
+            ],
         )
         .map_err(|e| e.to_string())?;
         Ok(())
@@ -336,6 +483,128 @@ impl PageView {
         .unwrap_or_default()
     }
 
+    pub fn top_paths(
+        pool: &DbPool,
+        prefix: &str,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Vec<CountEntry> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+
+        let mut stmt = match conn.prepare(
+            "SELECT path as label, COUNT(*) as count
+             FROM page_views
+             WHERE substr(path, 1, length(?1)) = ?1 AND created_at BETWEEN ?2 AND ?3
+             GROUP BY path
+             ORDER BY count DESC
+             LIMIT ?4",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+
+        stmt.query_map(params![prefix, from, to, limit], |row| {
+            Ok(CountEntry {
+                label: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    /// Views, visitors and daily views of one page (with or without a
+    /// trailing slash).
+    pub fn path_stats(pool: &DbPool, path: &str, from: &str, to: &str) -> PathStats {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return PathStats::default(),
+        };
+
+        let (views, unique_visitors): (i64, i64) = conn
+            .query_row(
+                "SELECT COUNT(*), COUNT(DISTINCT ip_hash) FROM page_views
+                 WHERE (path = ?1 OR path = ?1 || '/') AND created_at BETWEEN ?2 AND ?3",
+                params![path, from, to],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap_or((0, 0));
+
+        let daily = match conn.prepare(
+            "SELECT DATE(created_at) as date, COUNT(*) as count
+             FROM page_views
+             WHERE (path = ?1 OR path = ?1 || '/') AND created_at BETWEEN ?2 AND ?3
+             GROUP BY date
+             ORDER BY date",
+        ) {
+            Ok(mut stmt) => stmt
+                .query_map(params![path, from, to], |row| {
+                    Ok(DailyCount {
+                        date: row.get(0)?,
+                        count: row.get(1)?,
+                    })
+                })
+                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+                .unwrap_or_default(),
+            Err(_) => vec![],
+        };
+
+        PathStats {
+            views,
+            unique_visitors,
+            daily,
+        }
+    }
+
+    /// Views of one page per referrer or per country (`column` is
+    /// "referrer" or "country").
+    pub fn path_breakdown(
+        pool: &DbPool,
+        path: &str,
+        column: &str,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Vec<CountEntry> {
+        let (column, fallback) = match column {
+            "referrer" => ("referrer", "Direct"),
+            "country" => ("country", "Unknown"),
+            _ => return vec![],
+        };
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+
+        let sql = format!(
+            "SELECT COALESCE({col}, '{fallback}') as label, COUNT(*) as count
+             FROM page_views
+             WHERE (path = ?1 OR path = ?1 || '/') AND created_at BETWEEN ?2 AND ?3
+             GROUP BY {col}
+             ORDER BY count DESC
+             LIMIT ?4",
+            col = column,
+            fallback = fallback
+        );
+        let mut stmt = match conn.prepare(&sql) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+
+        stmt.query_map(params![path, from, to, limit], |row| {
+            Ok(CountEntry {
+                label: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
     pub fn top_referrers(pool: &DbPool, from: &str, to: &str, limit: i64) -> Vec<CountEntry> {
         let conn = match pool.get() {
             Ok(c) => c,
@@ -364,6 +633,81 @@ impl PageView {
         .unwrap_or_default()
     }
 
+    /// Views and visitors per campaign/source/medium, for views that came
+    /// in with a utm_campaign or utm_source.
+    pub fn campaigns(pool: &DbPool, from: &str, to: &str, limit: i64) -> Vec<CampaignEntry> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+
+        let mut stmt = match conn.prepare(
+            "SELECT COALESCE(utm_campaign, '(none)'), COALESCE(utm_source, '(none)'),
+                    COALESCE(utm_medium, '(none)'), COUNT(*) as views, COUNT(DISTINCT ip_hash)
+             FROM page_views
+             WHERE (utm_campaign IS NOT NULL OR utm_source IS NOT NULL)
+             AND created_at BETWEEN ?1 AND ?2
+             GROUP BY utm_campaign, utm_source, utm_medium
+             ORDER BY views DESC
+             LIMIT ?3",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+
+        stmt.query_map(params![from, to, limit], |row| {
+            Ok(CampaignEntry {
+                campaign: row.get(0)?,
+                source: row.get(1)?,
+                medium: row.get(2)?,
+                views: row.get(3)?,
+                visitors: row.get(4)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    /// View counts per value of one UTM dimension ("source", "medium", …).
+    pub fn utm_breakdown(
+        pool: &DbPool,
+        dimension: &str,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Vec<CountEntry> {
+        if !UTM_DIMENSIONS.contains(&dimension) {
+            return vec![];
+        }
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+
+        // The column name comes from the allow-list above
+        let mut stmt = match conn.prepare(&format!(
+            "SELECT utm_{col} as label, COUNT(*) as count
+             FROM page_views
+             WHERE utm_{col} IS NOT NULL AND created_at BETWEEN ?1 AND ?2
+             GROUP BY utm_{col}
+             ORDER BY count DESC
+             LIMIT ?3",
+            col = dimension
+        )) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+
+        stmt.query_map(params![from, to, limit], |row| {
+            Ok(CountEntry {
+                label: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
     pub fn tag_relations(pool: &DbPool) -> Vec<TagRelation> {
         let conn = match pool.get() {
             Ok(c) => c,
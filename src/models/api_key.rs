@@ -0,0 +1,128 @@
+use rand::RngCore;
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::db::DbPool;
+
+/// Key scopes. A key's scope picks its default request budget; write keys
+/// are needed for API endpoints that change content.
+pub const SCOPES: &[&str] = &["read", "write"];
+
+/// Prefix of every generated key, so keys are recognisable in headers and
+/// in secret scanners.
+pub const KEY_PREFIX: &str = "vk_";
+
+/// A key for the public API. Only a SHA-256 hash of the key is stored; the
+/// key itself is shown once, when it is created.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiKey {
+    pub id: i64,
+    pub name: String,
+    /// First characters of the key, to tell keys apart in the admin
+    pub prefix: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    /// "read" or "write"
+    pub scope: String,
+    /// Requests per minute; 0 uses the scope's default budget
+    pub rate_limit: i64,
+    pub last_used_at: Option<String>,
+    pub created_at: String,
+}
+
+impl ApiKey {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(ApiKey {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            prefix: row.get("prefix")?,
+            key_hash: row.get("key_hash")?,
+            scope: row.get("scope")?,
+            rate_limit: row.get("rate_limit")?,
+            last_used_at: row.get("last_used_at")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+
+    pub fn list(pool: &DbPool) -> Vec<ApiKey> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt =
+            match conn.prepare("SELECT * FROM api_keys ORDER BY created_at DESC, id DESC") {
+                Ok(s) => s,
+                Err(_) => return vec![],
+            };
+        stmt.query_map([], Self::from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn find_by_hash(pool: &DbPool, key_hash: &str) -> Option<ApiKey> {
+        let conn = pool.get().ok()?;
+        conn.query_row(
+            "SELECT * FROM api_keys WHERE key_hash = ?1",
+            params![key_hash],
+            Self::from_row,
+        )
+        .ok()
+    }
+
+    /// Insert a key. Returns its id.
+    pub fn create(pool: &DbPool, key: &ApiKey) -> Result<i64, String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO api_keys (name, prefix, key_hash, scope, rate_limit) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![key.name, key.prefix, key.key_hash, key.scope, key.rate_limit],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn delete(pool: &DbPool, id: i64) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM api_keys WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Record that the key was just used
+    pub fn touch(pool: &DbPool, id: i64) {
+        if let Ok(conn) = pool.get() {
+            let _ = conn.execute(
+                "UPDATE api_keys SET last_used_at = datetime('now') WHERE id = ?1",
+                params![id],
+            );
+        }
+    }
+}
+
+/// A new random key, returned as (key, display prefix, hash to store)
+pub fn generate() -> (String, String, String) {
+    let mut bytes = [0u8; 24];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let key = format!("{}{}", KEY_PREFIX, hex::encode(bytes));
+    let prefix = key[..KEY_PREFIX.len() + 6].to_string();
+    let hash = hash_key(&key);
+    (key, prefix, hash)
+}
+
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.trim().as_bytes()))
+}
+
+/// Check a key's name, scope and budget before it's created
+pub fn validate(key: &ApiKey) -> Result<(), String> {
+    if key.name.trim().is_empty() {
+        return Err("Name is required".into());
+    }
+    if !SCOPES.contains(&key.scope.as_str()) {
+        return Err(format!("Unknown scope: {}", key.scope));
+    }
+    if key.rate_limit < 0 {
+        return Err("Rate limit can't be negative".into());
+    }
+    Ok(())
+}
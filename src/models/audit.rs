@@ -3,6 +3,7 @@ use rusqlite::params;
 use serde::Serialize;
 
 use crate::db::DbPool;
+use crate::store::cursor::{self, Cursor};
 
 #[derive(Debug, Serialize)]
 pub struct AuditEntry {
@@ -85,25 +86,71 @@ impl AuditEntry {
 
         conn.prepare(&sql)
             .and_then(|mut stmt| {
-                stmt.query_map(param_refs.as_slice(), |row| {
-                    Ok(AuditEntry {
-                        id: row.get("id")?,
-                        user_id: row.get("user_id")?,
-                        user_name: row.get("user_name")?,
-                        action: row.get("action")?,
-                        entity_type: row.get("entity_type")?,
-                        entity_id: row.get("entity_id")?,
-                        entity_title: row.get("entity_title")?,
-                        details: row.get("details")?,
-                        ip_address: row.get("ip_address")?,
-                        created_at: row.get("created_at")?,
-                    })
-                })
-                .map(|rows| rows.filter_map(|r| r.ok()).collect())
+                stmt.query_map(param_refs.as_slice(), Self::from_row)
+                    .map(|rows| rows.filter_map(|r| r.ok()).collect())
             })
             .unwrap_or_default()
     }
 
+    /// Entries with an id greater than `after_id`, oldest first
+    pub fn list_after(pool: &DbPool, after_id: i64, limit: i64) -> Vec<Self> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        conn.prepare("SELECT * FROM audit_log WHERE id > ?1 ORDER BY id LIMIT ?2")
+            .and_then(|mut stmt| {
+                stmt.query_map(params![after_id, limit], Self::from_row)
+                    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            })
+            .unwrap_or_default()
+    }
+
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(AuditEntry {
+            id: row.get("id")?,
+            user_id: row.get("user_id")?,
+            user_name: row.get("user_name")?,
+            action: row.get("action")?,
+            entity_type: row.get("entity_type")?,
+            entity_id: row.get("entity_id")?,
+            entity_title: row.get("entity_title")?,
+            details: row.get("details")?,
+            ip_address: row.get("ip_address")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+
+    /// Keyset page of `list`
+    pub fn list_keyset(
+        pool: &DbPool,
+        action_filter: Option<&str>,
+        entity_filter: Option<&str>,
+        user_filter: Option<i64>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Self>, Option<Cursor>) {
+        let mut filters: Vec<(&str, Box<dyn rusqlite::types::ToSql>)> = Vec::new();
+        if let Some(a) = action_filter {
+            filters.push(("action", Box::new(a.to_string())));
+        }
+        if let Some(e) = entity_filter {
+            filters.push(("entity_type", Box::new(e.to_string())));
+        }
+        if let Some(u) = user_filter {
+            filters.push(("user_id", Box::new(u)));
+        }
+        cursor::sqlite_page(
+            pool,
+            "audit_log",
+            "created_at",
+            filters,
+            after,
+            limit,
+            Self::from_row,
+        )
+    }
+
     pub fn count(
         pool: &DbPool,
         action_filter: Option<&str>,
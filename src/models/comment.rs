@@ -3,6 +3,7 @@ use rusqlite::{params, Row};
 use serde::{Deserialize, Serialize};
 
 use crate::db::DbPool;
+use crate::store::cursor::{self, Cursor};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Comment {
@@ -14,6 +15,8 @@ pub struct Comment {
     pub body: String,
     pub status: String,
     pub parent_id: Option<i64>,
+    /// Member account that wrote the comment, if signed in
+    pub user_id: Option<i64>,
     pub created_at: NaiveDateTime,
 }
 
@@ -39,6 +42,7 @@ impl Comment {
             body: row.get("body")?,
             status: row.get("status")?,
             parent_id: row.get("parent_id").ok(),
+            user_id: row.get("user_id").ok().flatten(),
             created_at: row.get("created_at")?,
         })
     }
@@ -53,6 +57,32 @@ impl Comment {
         .ok()
     }
 
+    /// Like `Post::list_since`, for comments; `None` matches every status
+    pub fn list_since(
+        pool: &DbPool,
+        status: Option<&str>,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, Self)> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT * FROM comments WHERE (?1 IS NULL OR status = ?1)
+             AND (?2 IS NULL OR status_seq > ?2)
+             ORDER BY CASE WHEN ?2 IS NULL THEN -status_seq ELSE status_seq END LIMIT ?3",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![status, since_seq, limit], |row| {
+            Ok((row.get("status_seq")?, Self::from_row(row)?))
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
     pub fn list(pool: &DbPool, status: Option<&str>, limit: i64, offset: i64) -> Vec<Self> {
         let conn = match pool.get() {
             Ok(c) => c,
@@ -100,6 +130,65 @@ impl Comment {
             .unwrap_or_default()
     }
 
+    /// Comments written by a member account or under its email address
+    pub fn list_by_author(pool: &DbPool, user_id: i64, email: &str) -> Vec<Self> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT * FROM comments WHERE user_id = ?1 OR lower(author_email) = ?2 \
+             ORDER BY created_at DESC",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![user_id, email.to_lowercase()], Self::from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Latest non-spam comments on posts credited to `author_id`
+    pub fn list_on_author_posts(pool: &DbPool, author_id: i64, limit: i64) -> Vec<Self> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT c.* FROM comments c JOIN posts p ON p.id = c.post_id \
+             WHERE c.content_type = 'post' AND p.author_id = ?1 AND c.status != 'spam' \
+             ORDER BY c.created_at DESC LIMIT ?2",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![author_id, limit], Self::from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Keyset page of `list`
+    pub fn list_keyset(
+        pool: &DbPool,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Self>, Option<Cursor>) {
+        let mut filters: Vec<(&str, Box<dyn rusqlite::types::ToSql>)> = Vec::new();
+        if let Some(s) = status {
+            filters.push(("status", Box::new(s.to_string())));
+        }
+        cursor::sqlite_page(
+            pool,
+            "comments",
+            "created_at",
+            filters,
+            after,
+            limit,
+            Self::from_row,
+        )
+    }
+
     pub fn count(pool: &DbPool, status: Option<&str>) -> i64 {
         let conn = match pool.get() {
             Ok(c) => c,
@@ -156,4 +245,21 @@ impl Comment {
             .map_err(|e| e.to_string())?;
         Ok(())
     }
+
+    /// Replace the author of every comment from `list_by_author` with `name`,
+    /// dropping the email and account link. Returns how many changed.
+    pub fn anonymize_author(
+        pool: &DbPool,
+        user_id: i64,
+        email: &str,
+        name: &str,
+    ) -> Result<usize, String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE comments SET author_name = ?3, author_email = NULL, user_id = NULL \
+             WHERE user_id = ?1 OR lower(author_email) = ?2",
+            params![user_id, email.to_lowercase(), name],
+        )
+        .map_err(|e| e.to_string())
+    }
 }
@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::db::DbPool;
 
+/// Longest parent chain a design can have, itself included
+pub const MAX_DESIGN_DEPTH: usize = 5;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Design {
     pub id: i64,
@@ -12,6 +15,13 @@ pub struct Design {
     pub description: String,
     pub layout_html: String,
     pub style_css: String,
+    /// Site owner's CSS, injected after the design's own styles
+    pub custom_css: String,
+    /// Markup added at the end of `<head>` and `<body>`
+    pub head_scripts: String,
+    pub footer_scripts: String,
+    /// Design this one inherits its shell, CSS and templates from
+    pub parent_id: Option<i64>,
     pub thumbnail_path: Option<String>,
     pub is_active: bool,
     pub created_at: NaiveDateTime,
@@ -41,6 +51,16 @@ impl Design {
                 .unwrap_or_default(),
             layout_html: row.get("layout_html")?,
             style_css: row.get("style_css")?,
+            custom_css: row
+                .get::<_, Option<String>>("custom_css")?
+                .unwrap_or_default(),
+            head_scripts: row
+                .get::<_, Option<String>>("head_scripts")?
+                .unwrap_or_default(),
+            footer_scripts: row
+                .get::<_, Option<String>>("footer_scripts")?
+                .unwrap_or_default(),
+            parent_id: row.get("parent_id")?,
             thumbnail_path: row.get("thumbnail_path")?,
             is_active: active_raw != 0,
             created_at: row.get("created_at")?,
@@ -126,14 +146,50 @@ impl Design {
         Ok(conn.last_insert_rowid())
     }
 
+    pub fn update_custom_code(
+        pool: &DbPool,
+        id: i64,
+        custom_css: &str,
+        head_scripts: &str,
+        footer_scripts: &str,
+    ) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE designs SET custom_css = ?1, head_scripts = ?2, footer_scripts = ?3, updated_at = datetime('now') WHERE id = ?4",
+            params![custom_css, head_scripts, footer_scripts, id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn set_parent(pool: &DbPool, id: i64, parent_id: Option<i64>) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE designs SET parent_id = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![parent_id, id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
     pub fn duplicate(pool: &DbPool, id: i64, new_name: &str) -> Result<i64, String> {
         let original = Self::find_by_id(pool, id).ok_or("Design not found")?;
         let slug = Self::slugify(new_name);
         let conn = pool.get().map_err(|e| e.to_string())?;
 
         conn.execute(
-            "INSERT INTO designs (name, slug, layout_html, style_css) VALUES (?1, ?2, ?3, ?4)",
-            params![new_name, slug, original.layout_html, original.style_css],
+            "INSERT INTO designs (name, slug, layout_html, style_css, parent_id, custom_css, head_scripts, footer_scripts)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                new_name,
+                slug,
+                original.layout_html,
+                original.style_css,
+                original.parent_id,
+                original.custom_css,
+                original.head_scripts,
+                original.footer_scripts,
+            ],
         )
         .map_err(|e| e.to_string())?;
 
@@ -160,6 +216,19 @@ impl Design {
             params![id],
         )
         .map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM design_template_versions WHERE design_id = ?1",
+            params![id],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM widgets WHERE design_id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        // Children stand on their own once their parent is gone
+        conn.execute(
+            "UPDATE designs SET parent_id = NULL WHERE parent_id = ?1",
+            params![id],
+        )
+        .map_err(|e| e.to_string())?;
         conn.execute("DELETE FROM designs WHERE id = ?1", params![id])
             .map_err(|e| e.to_string())?;
         Ok(())
@@ -246,3 +315,101 @@ impl DesignTemplate {
         Ok(())
     }
 }
+
+/// Most saved versions kept per (design, template type); older ones are
+/// pruned when a new one is recorded
+pub const MAX_TEMPLATE_VERSIONS: i64 = 50;
+
+/// A saved state of a design template, recorded on every edit
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DesignTemplateVersion {
+    pub id: i64,
+    pub design_id: i64,
+    pub template_type: String,
+    pub layout_html: String,
+    pub style_css: String,
+    pub grapesjs_data: String,
+    /// Who saved it; None for versions recorded without a signed-in user
+    pub user_id: Option<i64>,
+    pub user_name: String,
+    pub created_at: String,
+}
+
+impl DesignTemplateVersion {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(DesignTemplateVersion {
+            id: row.get("id")?,
+            design_id: row.get("design_id")?,
+            template_type: row.get("template_type")?,
+            layout_html: row.get("layout_html")?,
+            style_css: row.get("style_css")?,
+            grapesjs_data: row.get("grapesjs_data")?,
+            user_id: row.get("user_id")?,
+            user_name: row.get("user_name")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+
+    /// Newest first
+    pub fn list(pool: &DbPool, design_id: i64, template_type: &str) -> Vec<Self> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT * FROM design_template_versions
+             WHERE design_id = ?1 AND template_type = ?2
+             ORDER BY id DESC",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![design_id, template_type], Self::from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn find(pool: &DbPool, id: i64) -> Option<Self> {
+        let conn = pool.get().ok()?;
+        conn.query_row(
+            "SELECT * FROM design_template_versions WHERE id = ?1",
+            params![id],
+            Self::from_row,
+        )
+        .ok()
+    }
+
+    pub fn create(pool: &DbPool, version: &DesignTemplateVersion) -> Result<i64, String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO design_template_versions
+                (design_id, template_type, layout_html, style_css, grapesjs_data, user_id, user_name)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                version.design_id,
+                version.template_type,
+                version.layout_html,
+                version.style_css,
+                version.grapesjs_data,
+                version.user_id,
+                version.user_name
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        let id = conn.last_insert_rowid();
+        conn.execute(
+            "DELETE FROM design_template_versions
+             WHERE design_id = ?1 AND template_type = ?2 AND id NOT IN (
+                SELECT id FROM design_template_versions
+                WHERE design_id = ?1 AND template_type = ?2
+                ORDER BY id DESC LIMIT ?3)",
+            params![
+                version.design_id,
+                version.template_type,
+                MAX_TEMPLATE_VERSIONS
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(id)
+    }
+}
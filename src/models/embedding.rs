@@ -0,0 +1,25 @@
+use serde::Serialize;
+
+/// Vector for a post or portfolio item, computed by an AI provider.
+/// `model` names the provider and model ("openai:text-embedding-3-small");
+/// vectors from different models can't be compared with each other.
+#[derive(Debug, Clone, Serialize)]
+pub struct Embedding {
+    pub item_type: String, // "post" or "portfolio"
+    pub item_id: i64,
+    pub model: String,
+    /// Hash of the text the vector was computed from, to spot stale vectors.
+    pub content_hash: String,
+    pub vector: Vec<f32>,
+}
+
+/// Pack a vector as little-endian f32s for BLOB storage.
+pub fn vector_to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub fn vector_from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+        .collect()
+}
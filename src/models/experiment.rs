@@ -0,0 +1,141 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// An A/B test from the `analytics_experiments` setting: two versions of a
+/// post title, or of the homepage hero line (the site tagline).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Experiment {
+    pub name: String,
+    /// "home" for the homepage hero, or "post:<slug>" for a post title
+    pub target: String,
+    pub variants: [String; 2],
+}
+
+impl Experiment {
+    /// Slug of the post whose title this tests, if it tests one.
+    pub fn post_slug(&self) -> Option<&str> {
+        self.target.strip_prefix("post:")
+    }
+
+    pub fn is_home(&self) -> bool {
+        self.target == "home"
+    }
+
+    /// The variant (0 = A, 1 = B) a visitor sees. Hashing the experiment
+    /// name in as well keeps one visitor's groups independent across tests.
+    pub fn variant_for(&self, ip_hash: &str) -> usize {
+        let mut hasher = Sha256::new();
+        hasher.update(self.name.as_bytes());
+        hasher.update(b":");
+        hasher.update(ip_hash.as_bytes());
+        (hasher.finalize()[0] & 1) as usize
+    }
+}
+
+/// Variant labels as stored with each event.
+pub const VARIANT_LABELS: [&str; 2] = ["A", "B"];
+
+/// Parse the `analytics_experiments` setting: one
+/// `Name | target | Variant A | Variant B` per line.
+pub fn parse_experiments(raw: &str) -> Vec<Experiment> {
+    raw.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let parts: Vec<&str> = line.split('|').map(|p| p.trim()).collect();
+            let [name, target, a, b] = parts.as_slice() else {
+                return None;
+            };
+            let valid_target = *target == "home"
+                || target
+                    .strip_prefix("post:")
+                    .is_some_and(|slug| !slug.is_empty());
+            (!name.is_empty() && valid_target && !a.is_empty() && !b.is_empty()).then(|| {
+                Experiment {
+                    name: name.to_string(),
+                    target: target.to_string(),
+                    variants: [a.to_string(), b.to_string()],
+                }
+            })
+        })
+        .collect()
+}
+
+/// Distinct visitors who saw a variant, and how many of them clicked through.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct VariantStat {
+    pub experiment: String,
+    pub variant: String,
+    pub impressions: i64,
+    pub clicks: i64,
+}
+
+/// One variant's line in an experiment report.
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantReport {
+    pub variant: String,
+    pub text: String,
+    pub impressions: i64,
+    pub clicks: i64,
+    /// Percentage of visitors who clicked through
+    pub click_rate: f64,
+}
+
+/// Both variants of an experiment side by side, with a two-proportion
+/// z-test on their click-through rates.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExperimentReport {
+    pub name: String,
+    pub target: String,
+    pub variants: Vec<VariantReport>,
+    pub z_score: f64,
+    pub p_value: f64,
+    /// Confidence (in percent) that the rates really differ
+    pub confidence: f64,
+    /// The better variant once the difference is significant at 95%
+    pub winner: Option<String>,
+}
+
+/// Fewest visitors per variant before a result is reported as significant
+pub const MIN_SAMPLE: i64 = 30;
+
+/// Two-proportion z-test. Returns (z, two-sided p-value) for B against A,
+/// or None when either variant has no impressions or there's no variance.
+pub fn two_proportion_test(
+    a_impressions: i64,
+    a_clicks: i64,
+    b_impressions: i64,
+    b_clicks: i64,
+) -> Option<(f64, f64)> {
+    if a_impressions <= 0 || b_impressions <= 0 {
+        return None;
+    }
+    let (n1, n2) = (a_impressions as f64, b_impressions as f64);
+    let p1 = a_clicks as f64 / n1;
+    let p2 = b_clicks as f64 / n2;
+    let pooled = (a_clicks + b_clicks) as f64 / (n1 + n2);
+    let se = (pooled * (1.0 - pooled) * (1.0 / n1 + 1.0 / n2)).sqrt();
+    if se == 0.0 || !se.is_finite() {
+        return None;
+    }
+    let z = (p2 - p1) / se;
+    let p = 2.0 * (1.0 - normal_cdf(z.abs()));
+    Some((z, p.clamp(0.0, 1.0)))
+}
+
+/// Standard normal CDF via the Abramowitz–Stegun erf approximation
+/// (error below 1.5e-7, ample for reporting).
+fn normal_cdf(x: f64) -> f64 {
+    let t = x / std::f64::consts::SQRT_2;
+    let sign = if t < 0.0 { -1.0 } else { 1.0 };
+    let t = t.abs();
+    let k = 1.0 / (1.0 + 0.327_591_1 * t);
+    let poly = k
+        * (0.254_829_592
+            + k * (-0.284_496_736
+                + k * (1.421_413_741 + k * (-1.453_152_027 + k * 1.061_405_429))));
+    let erf = 1.0 - poly * (-t * t).exp();
+    0.5 * (1.0 + sign * erf)
+}
@@ -1,3 +1,6 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
 use rusqlite::{params, Row};
 use serde::{Deserialize, Serialize};
 
@@ -28,6 +31,26 @@ pub struct FwEvent {
     pub created_at: String,
 }
 
+/// A custom firewall rule. `match_type` picks what `pattern` is tested
+/// against: "path" or "user_agent" (regex), "header" ("Name" for presence,
+/// "Name: regex" for its value) or "asn" (comma-separated AS numbers).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FwRule {
+    pub id: i64,
+    pub name: String,
+    pub match_type: String,
+    pub pattern: String,
+    /// "block", "challenge" or "rate_limit"
+    pub action: String,
+    /// Requests per minute per IP, for "rate_limit" rules
+    pub rate_limit: i64,
+    /// Lower runs first
+    pub priority: i64,
+    pub enabled: bool,
+    pub hits: i64,
+    pub created_at: String,
+}
+
 impl FwBan {
     fn from_row(row: &Row) -> rusqlite::Result<Self> {
         Ok(FwBan {
@@ -126,6 +149,13 @@ impl FwBan {
             .map_err(|e| e.to_string())
     }
 
+    /// Unban everyone
+    pub fn unban_all(pool: &DbPool) -> Result<usize, String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute("UPDATE fw_bans SET active = 0 WHERE active = 1", [])
+            .map_err(|e| e.to_string())
+    }
+
     /// List active bans
     pub fn active_bans(pool: &DbPool, limit: i64, offset: i64) -> Vec<FwBan> {
         let conn = match pool.get() {
@@ -186,6 +216,42 @@ impl FwBan {
             );
         }
     }
+
+    /// Count a new offense for an IP and return its running total. The count
+    /// starts over when the previous offense is older than `window_days`.
+    pub fn record_offense(pool: &DbPool, ip: &str, window_days: i64) -> i64 {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return 1,
+        };
+        let _ = conn.execute(
+            "INSERT INTO fw_offenses (ip, count, last_offense) VALUES (?1, 1, datetime('now'))
+             ON CONFLICT(ip) DO UPDATE SET
+                count = CASE WHEN last_offense < datetime('now', ?2) THEN 1 ELSE count + 1 END,
+                last_offense = datetime('now')",
+            params![ip, format!("-{} days", window_days)],
+        );
+        conn.query_row(
+            "SELECT count FROM fw_offenses WHERE ip = ?1",
+            params![ip],
+            |row| row.get::<_, i64>(0),
+        )
+        .unwrap_or(1)
+    }
+
+    /// Offenses recorded for an IP in its current window
+    pub fn offense_count(pool: &DbPool, ip: &str) -> i64 {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return 0,
+        };
+        conn.query_row(
+            "SELECT count FROM fw_offenses WHERE ip = ?1",
+            params![ip],
+            |row| row.get(0),
+        )
+        .unwrap_or(0)
+    }
 }
 
 impl FwEvent {
@@ -264,6 +330,32 @@ impl FwEvent {
     }
 
     /// Total event count (with optional type filter)
+    /// Events with an id greater than `after_id`, oldest first
+    pub fn list_after(pool: &DbPool, after_id: i64, limit: i64) -> Vec<FwEvent> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt =
+            match conn.prepare("SELECT * FROM fw_events WHERE id > ?1 ORDER BY id LIMIT ?2") {
+                Ok(s) => s,
+                Err(_) => return vec![],
+            };
+        stmt.query_map(params![after_id, limit], Self::from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Delete events older than `max_age_days`
+    pub fn cleanup(pool: &DbPool, max_age_days: i64) -> Result<usize, String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM fw_events WHERE created_at < datetime('now', ?1)",
+            params![format!("-{} days", max_age_days)],
+        )
+        .map_err(|e| e.to_string())
+    }
+
     pub fn count_all(pool: &DbPool, event_type: Option<&str>) -> i64 {
         let conn = match pool.get() {
             Ok(c) => c,
@@ -361,6 +453,223 @@ impl FwEvent {
     }
 }
 
+impl FwRule {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(FwRule {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            match_type: row.get("match_type")?,
+            pattern: row.get("pattern")?,
+            action: row.get("action")?,
+            rate_limit: row.get("rate_limit")?,
+            priority: row.get("priority")?,
+            enabled: row.get::<_, i64>("enabled")? == 1,
+            hits: row.get("hits")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+
+    /// All rules in evaluation order
+    pub fn list(pool: &DbPool) -> Vec<FwRule> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare("SELECT * FROM fw_rules ORDER BY priority, id") {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map([], Self::from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Insert a rule (id 0) or update an existing one. Returns the rule id.
+    pub fn save(pool: &DbPool, rule: &FwRule) -> Result<i64, String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        if rule.id == 0 {
+            conn.execute(
+                "INSERT INTO fw_rules (name, match_type, pattern, action, rate_limit, priority, enabled)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    rule.name,
+                    rule.match_type,
+                    rule.pattern,
+                    rule.action,
+                    rule.rate_limit,
+                    rule.priority,
+                    rule.enabled as i64
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            return Ok(conn.last_insert_rowid());
+        }
+        conn.execute(
+            "UPDATE fw_rules SET name = ?1, match_type = ?2, pattern = ?3, action = ?4,
+             rate_limit = ?5, priority = ?6, enabled = ?7 WHERE id = ?8",
+            params![
+                rule.name,
+                rule.match_type,
+                rule.pattern,
+                rule.action,
+                rule.rate_limit,
+                rule.priority,
+                rule.enabled as i64,
+                rule.id
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(rule.id)
+    }
+
+    pub fn delete(pool: &DbPool, id: i64) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM fw_rules WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Bump a rule's match counter
+    pub fn record_hit(pool: &DbPool, id: i64) {
+        if let Ok(conn) = pool.get() {
+            let _ = conn.execute(
+                "UPDATE fw_rules SET hits = hits + 1 WHERE id = ?1",
+                params![id],
+            );
+        }
+    }
+}
+
+// ── Rule cache ──────────────────────────────────────────
+//
+// The firewall fairing matches every request against the custom rules, so
+// each Store keeps the list in memory. Saving or deleting a rule through any
+// Store bumps the generation, which drops every Store's copy.
+
+static RULES_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Drop every cached rule list, in every Store.
+pub fn invalidate_rules() {
+    RULES_GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// The rule list and the generation it was read at.
+#[derive(Default)]
+pub struct FwRuleCache {
+    inner: RwLock<Option<(u64, Vec<FwRule>)>>,
+}
+
+impl FwRuleCache {
+    /// The cached rules, or `load` them and remember the result.
+    pub fn get_or_load(&self, load: impl FnOnce() -> Vec<FwRule>) -> Vec<FwRule> {
+        let generation = RULES_GENERATION.load(Ordering::SeqCst);
+        if let Ok(r) = self.inner.read() {
+            if let Some((g, rules)) = r.as_ref() {
+                if *g == generation {
+                    return rules.clone();
+                }
+            }
+        }
+        let rules = load();
+        if let Ok(mut w) = self.inner.write() {
+            // Same as the role cache: skip storing if a change raced the load
+            if RULES_GENERATION.load(Ordering::SeqCst) == generation {
+                *w = Some((generation, rules.clone()));
+            }
+        }
+        rules
+    }
+
+    /// Count a match on the cached copy too, so the admin list stays current
+    /// without reloading the rules.
+    pub fn record_hit(&self, id: i64) {
+        if let Ok(mut w) = self.inner.write() {
+            if let Some(rule) = w
+                .as_mut()
+                .and_then(|(_, rules)| rules.iter_mut().find(|r| r.id == id))
+            {
+                rule.hits += 1;
+            }
+        }
+    }
+}
+
+/// Default ban escalation ladder for repeat offenders
+pub const DEFAULT_ESCALATION_STEPS: &str = "1h,24h,7d,permanent";
+
+/// Length of a duration string in hours, or None for "permanent".
+fn duration_hours(duration: &str) -> Option<i64> {
+    let d = duration.trim();
+    if d == "permanent" || d.is_empty() {
+        return None;
+    }
+    Some(if let Some(n) = d.strip_suffix('d') {
+        n.parse::<i64>().unwrap_or(1) * 24
+    } else if let Some(n) = d.strip_suffix('h') {
+        n.parse().unwrap_or(1)
+    } else {
+        d.parse().unwrap_or(24)
+    })
+}
+
+/// Pick the ban duration for an IP's `offenses`-th offense. The first offense
+/// gets `requested` (rounded up to the nearest step); each repeat moves one
+/// step further along the comma-separated `steps` ladder.
+pub fn escalation_step(requested: &str, offenses: i64, steps: &str) -> String {
+    let ladder: Vec<&str> = steps
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let requested_hours = duration_hours(requested);
+    // Permanent sorts after everything else
+    let longer_or_equal = |step: &str| match (duration_hours(step), requested_hours) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(s), Some(r)) => s >= r,
+    };
+    let start = match ladder.iter().position(|s| longer_or_equal(s)) {
+        Some(i) => i,
+        None => return requested.to_string(),
+    };
+    if offenses <= 1 {
+        return requested.to_string();
+    }
+    let index = (start + offenses as usize - 1).min(ladder.len() - 1);
+    ladder[index].to_string()
+}
+
+/// Record an offense for `ip` and return the escalated ban duration, or the
+/// requested one unchanged when escalation is off.
+pub fn escalated_duration(store: &dyn crate::store::Store, ip: &str, requested: &str) -> String {
+    if store.setting_get_or("fw_ban_escalation", "true") != "true" {
+        return requested.to_string();
+    }
+    let window: i64 = store
+        .setting_get_or("fw_ban_escalation_window_days", "30")
+        .parse()
+        .unwrap_or(30);
+    let offenses = store.fw_offense_record(ip, window.clamp(1, 3650));
+    let steps = store.setting_get_or("fw_ban_escalation_steps", DEFAULT_ESCALATION_STEPS);
+    escalation_step(requested, offenses, &steps)
+}
+
+/// Ban `ip` on a firewall rule's behalf: the rule's duration is escalated for
+/// repeat offenders. Bans set by an admin use `fw_ban_create_with_duration`
+/// directly and keep the duration they asked for.
+pub fn auto_ban(
+    store: &dyn crate::store::Store,
+    ip: &str,
+    reason: &str,
+    detail: Option<&str>,
+    duration: &str,
+    country: Option<&str>,
+    user_agent: Option<&str>,
+) -> Result<i64, String> {
+    let duration = escalated_duration(store, ip, duration);
+    store.fw_ban_create_with_duration(ip, reason, detail, &duration, country, user_agent)
+}
+
 /// Convert a duration string like "1h", "6h", "24h", "7d", "30d", "permanent" to an expiry datetime string
 fn duration_to_expiry(duration: &str) -> Option<String> {
     if duration == "permanent" || duration.is_empty() {
@@ -0,0 +1,69 @@
+use serde::Serialize;
+
+/// A conversion goal from the `analytics_goals` setting: a name and the
+/// page a visitor reaches when they complete it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Goal {
+    pub name: String,
+    /// "/contact/thanks" (exact), "/shop/*" (prefix), or one of the
+    /// built-in events "checkout" and "download".
+    pub pattern: String,
+}
+
+impl Goal {
+    pub fn matches(&self, path: &str) -> bool {
+        let path = path.trim_end_matches('/');
+        match self.pattern.as_str() {
+            // The download page a buyer lands on once a payment clears
+            "checkout" => {
+                let rest = path.strip_prefix("/download/").unwrap_or("");
+                !rest.is_empty() && !rest.contains('/')
+            }
+            "download" => path.starts_with("/download/") && path.ends_with("/file"),
+            p => match p.strip_suffix('*') {
+                Some(prefix) => path.starts_with(prefix) || path == prefix.trim_end_matches('/'),
+                None => path == p.trim_end_matches('/'),
+            },
+        }
+    }
+}
+
+/// Parse the `analytics_goals` setting: one `Name | pattern` per line.
+pub fn parse_goals(raw: &str) -> Vec<Goal> {
+    raw.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let (name, pattern) = line.split_once('|')?;
+            let (name, pattern) = (name.trim(), pattern.trim());
+            let valid = pattern == "checkout" || pattern == "download" || pattern.starts_with('/');
+            (!name.is_empty() && valid).then(|| Goal {
+                name: name.to_string(),
+                pattern: pattern.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Completions of one goal over a period.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct GoalStat {
+    pub goal: String,
+    pub completions: i64,
+    /// Distinct visitors who completed it
+    pub converters: i64,
+}
+
+/// A goal's completions against the period's visitors.
+#[derive(Debug, Clone, Serialize)]
+pub struct GoalReport {
+    pub goal: String,
+    pub pattern: String,
+    pub completions: i64,
+    pub converters: i64,
+    pub visitors: i64,
+    /// Percentage of visitors who converted
+    pub conversion_rate: f64,
+}
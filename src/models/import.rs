@@ -15,6 +15,41 @@ pub struct Import {
     pub skipped_count: i64,
     pub log: Option<String>,
     pub imported_at: NaiveDateTime,
+    /// What the run created, for rollback
+    pub created: ImportCreated,
+    pub rolled_back_at: Option<NaiveDateTime>,
+}
+
+/// IDs of everything an import run created, and the upload paths of media
+/// it saved. Stored as JSON on the history record.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ImportCreated {
+    pub posts: Vec<i64>,
+    pub portfolio: Vec<i64>,
+    pub comments: Vec<i64>,
+    pub categories: Vec<i64>,
+    pub tags: Vec<i64>,
+    pub users: Vec<i64>,
+    pub media: Vec<String>,
+}
+
+impl ImportCreated {
+    pub fn is_empty(&self) -> bool {
+        self.posts.is_empty()
+            && self.portfolio.is_empty()
+            && self.comments.is_empty()
+            && self.categories.is_empty()
+            && self.tags.is_empty()
+            && self.users.is_empty()
+            && self.media.is_empty()
+    }
+
+    /// Parse the stored JSON; empty for runs recorded before tracking
+    pub fn from_json(json: Option<&str>) -> Self {
+        json.and_then(|j| serde_json::from_str(j).ok())
+            .unwrap_or_default()
+    }
 }
 
 impl Import {
@@ -29,6 +64,8 @@ impl Import {
             skipped_count: row.get("skipped_count")?,
             log: row.get("log")?,
             imported_at: row.get("imported_at")?,
+            created: ImportCreated::from_json(row.get::<_, Option<String>>("created")?.as_deref()),
+            rolled_back_at: row.get("rolled_back_at")?,
         })
     }
 
@@ -47,6 +84,37 @@ impl Import {
             .unwrap_or_default()
     }
 
+    pub fn find_by_id(pool: &DbPool, id: i64) -> Option<Self> {
+        let conn = pool.get().ok()?;
+        conn.query_row(
+            "SELECT * FROM imports WHERE id = ?1",
+            params![id],
+            Self::from_row,
+        )
+        .ok()
+    }
+
+    pub fn set_created(pool: &DbPool, id: i64, created: &ImportCreated) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        let json = serde_json::to_string(created).map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE imports SET created = ?1 WHERE id = ?2",
+            params![json, id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn mark_rolled_back(pool: &DbPool, id: i64) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE imports SET rolled_back_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            params![id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
     pub fn create(
         pool: &DbPool,
         source: &str,
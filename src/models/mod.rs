@@ -1,15 +1,26 @@
+pub mod ai_usage;
 pub mod analytics;
+pub mod api_key;
 pub mod audit;
 pub mod category;
 pub mod comment;
 pub mod design;
+pub mod embedding;
+pub mod experiment;
 pub mod firewall;
+pub mod goal;
 pub mod import;
 pub mod order;
 pub mod passkey;
+pub mod ping_log;
 pub mod portfolio;
 pub mod post;
+pub mod redirect;
+pub mod role;
 pub mod search;
+pub mod seo_suggestion;
 pub mod settings;
+pub mod settings_transfer;
 pub mod tag;
 pub mod user;
+pub mod widget;
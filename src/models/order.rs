@@ -3,6 +3,7 @@ use rusqlite::{params, Row};
 use serde::{Deserialize, Serialize};
 
 use crate::db::DbPool;
+use crate::store::cursor::{self, Cursor};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Order {
@@ -105,6 +106,31 @@ impl Order {
             .unwrap_or_default()
     }
 
+    /// Like `Post::list_since`, for orders
+    pub fn list_since(
+        pool: &DbPool,
+        status: &str,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, Self)> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT * FROM orders WHERE status = ?1 AND (?2 IS NULL OR status_seq > ?2)
+             ORDER BY CASE WHEN ?2 IS NULL THEN -status_seq ELSE status_seq END LIMIT ?3",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![status, since_seq, limit], |row| {
+            Ok((row.get("status_seq")?, Self::from_row(row)?))
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
     pub fn list_by_status(pool: &DbPool, status: &str, limit: i64, offset: i64) -> Vec<Self> {
         let conn = match pool.get() {
             Ok(c) => c,
@@ -153,6 +179,28 @@ impl Order {
             .unwrap_or_default()
     }
 
+    /// Keyset page of `list`, or of `list_by_status` with a status
+    pub fn list_keyset(
+        pool: &DbPool,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Self>, Option<Cursor>) {
+        let mut filters: Vec<(&str, Box<dyn rusqlite::types::ToSql>)> = Vec::new();
+        if let Some(s) = status {
+            filters.push(("status", Box::new(s.to_string())));
+        }
+        cursor::sqlite_page(
+            pool,
+            "orders",
+            "created_at",
+            filters,
+            after,
+            limit,
+            Self::from_row,
+        )
+    }
+
     pub fn count(pool: &DbPool) -> i64 {
         let conn = match pool.get() {
             Ok(c) => c,
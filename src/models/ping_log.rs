@@ -0,0 +1,74 @@
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::db::DbPool;
+
+/// The result of one search engine ping (IndexNow or a sitemap ping).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PingLogEntry {
+    pub id: i64,
+    /// "bing", "yandex" or "google"
+    pub engine: String,
+    /// The URLs submitted (one per line), or the sitemap URL
+    pub urls: String,
+    pub success: bool,
+    /// HTTP status, or 0 when the request never got a response
+    pub status_code: i64,
+    pub message: String,
+    pub created_at: String,
+}
+
+impl PingLogEntry {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(PingLogEntry {
+            id: row.get("id")?,
+            engine: row.get("engine")?,
+            urls: row.get("urls")?,
+            success: row.get::<_, i64>("success")? != 0,
+            status_code: row.get("status_code")?,
+            message: row.get("message")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+
+    pub fn add(pool: &DbPool, entry: &PingLogEntry) -> Result<i64, String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO ping_log (engine, urls, success, status_code, message)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                entry.engine,
+                entry.urls,
+                entry.success as i64,
+                entry.status_code,
+                entry.message
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Newest pings first
+    pub fn recent(pool: &DbPool, limit: i64) -> Vec<PingLogEntry> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare("SELECT * FROM ping_log ORDER BY id DESC LIMIT ?1") {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![limit], Self::from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn cleanup(pool: &DbPool, max_age_days: i64) -> Result<usize, String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM ping_log WHERE created_at < datetime('now', ?1)",
+            params![format!("-{} days", max_age_days)],
+        )
+        .map_err(|e| e.to_string())
+    }
+}
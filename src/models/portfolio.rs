@@ -27,6 +27,17 @@ pub struct PortfolioItem {
     pub updated_at: NaiveDateTime,
     pub seo_score: i32,
     pub seo_issues: String,
+    /// Per-item robots overrides, emitted as `<meta name="robots">`
+    pub robots_noindex: bool,
+    pub robots_nofollow: bool,
+    /// Replaces the generated canonical URL when set
+    pub canonical_url: Option<String>,
+    /// Schema.org type for the JSON-LD; None picks one from the item
+    pub schema_type: Option<String>,
+    /// Phrase the content analysis checks keyword use against
+    pub focus_keyword: Option<String>,
+    /// Layout the design renders this item with; None is the default
+    pub template: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -48,6 +59,12 @@ pub struct PortfolioForm {
     pub published_at: Option<String>,
     pub category_ids: Option<Vec<i64>>,
     pub tag_ids: Option<Vec<i64>>,
+    pub robots_noindex: Option<bool>,
+    pub robots_nofollow: Option<bool>,
+    pub canonical_url: Option<String>,
+    pub schema_type: Option<String>,
+    pub focus_keyword: Option<String>,
+    pub template: Option<String>,
 }
 
 impl PortfolioItem {
@@ -81,6 +98,12 @@ impl PortfolioItem {
             updated_at: row.get("updated_at")?,
             seo_score: row.get("seo_score").unwrap_or(-1),
             seo_issues: row.get("seo_issues").unwrap_or_else(|_| "[]".to_string()),
+            robots_noindex: row.get::<_, i64>("robots_noindex").unwrap_or(0) != 0,
+            robots_nofollow: row.get::<_, i64>("robots_nofollow").unwrap_or(0) != 0,
+            canonical_url: row.get("canonical_url").unwrap_or(None),
+            schema_type: row.get("schema_type").unwrap_or(None),
+            focus_keyword: row.get("focus_keyword").unwrap_or(None),
+            template: row.get("template").unwrap_or(None),
         })
     }
 
@@ -195,8 +218,9 @@ impl PortfolioItem {
 
         conn.execute(
             "INSERT INTO portfolio (title, slug, description_json, description_html, image_path, thumbnail_path,
-             meta_title, meta_description, sell_enabled, price, purchase_note, payment_provider, download_file_path, status, published_at, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, COALESCE(?15, CURRENT_TIMESTAMP), COALESCE(?15, CURRENT_TIMESTAMP))",
+             meta_title, meta_description, sell_enabled, price, purchase_note, payment_provider, download_file_path, status, published_at, created_at, updated_at,
+             robots_noindex, robots_nofollow, canonical_url, schema_type, focus_keyword, template)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, COALESCE(?15, CURRENT_TIMESTAMP), COALESCE(?15, CURRENT_TIMESTAMP), ?16, ?17, ?18, ?19, ?20, ?21)",
             params![
                 form.title,
                 form.slug,
@@ -213,6 +237,12 @@ impl PortfolioItem {
                 form.download_file_path.as_deref().unwrap_or(""),
                 form.status,
                 published_at,
+                form.robots_noindex.unwrap_or(false) as i64,
+                form.robots_nofollow.unwrap_or(false) as i64,
+                form.canonical_url,
+                form.schema_type,
+                form.focus_keyword,
+                form.template,
             ],
         )
         .map_err(|e| e.to_string())?;
@@ -234,6 +264,7 @@ impl PortfolioItem {
             "UPDATE portfolio SET title=?1, slug=?2, description_json=?3, description_html=?4,
              image_path=?5, thumbnail_path=?6, meta_title=?7, meta_description=?8,
              sell_enabled=?9, price=?10, purchase_note=?11, payment_provider=?12, download_file_path=?13, status=?14, published_at=?15,
+             robots_noindex=?17, robots_nofollow=?18, canonical_url=?19, schema_type=?20, focus_keyword=?21, template=?22,
              updated_at=CURRENT_TIMESTAMP WHERE id=?16",
             params![
                 form.title,
@@ -252,6 +283,12 @@ impl PortfolioItem {
                 form.status,
                 published_at,
                 id,
+                form.robots_noindex.unwrap_or(false) as i64,
+                form.robots_nofollow.unwrap_or(false) as i64,
+                form.canonical_url,
+                form.schema_type,
+                form.focus_keyword,
+                form.template,
             ],
         )
         .map_err(|e| e.to_string())?;
@@ -3,6 +3,7 @@ use rusqlite::{params, Row};
 use serde::{Deserialize, Serialize};
 
 use crate::db::DbPool;
+use crate::store::cursor::{self, Cursor};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Post {
@@ -21,6 +22,29 @@ pub struct Post {
     pub updated_at: NaiveDateTime,
     pub seo_score: i32,
     pub seo_issues: String,
+    /// Per-post robots overrides, emitted as `<meta name="robots">`
+    pub robots_noindex: bool,
+    pub robots_nofollow: bool,
+    /// Replaces the generated canonical URL when set (e.g. syndicated posts)
+    pub canonical_url: Option<String>,
+    /// Schema.org type for the JSON-LD; None picks one from the content
+    pub schema_type: Option<String>,
+    /// Phrase the content analysis checks keyword use against
+    pub focus_keyword: Option<String>,
+    /// Language code such as "en" or "pt-BR"; None means the site language
+    pub language: Option<String>,
+    /// Posts sharing a group are translations of each other
+    pub translation_group: Option<String>,
+    /// Podcast episode audio: a path under uploads or a full URL
+    pub audio_file: Option<String>,
+    /// Episode length in seconds
+    pub audio_duration: Option<i64>,
+    /// Size of the audio file, which podcast enclosures must state
+    pub audio_bytes: Option<i64>,
+    /// Layout the design renders this post with; None is the default
+    pub template: Option<String>,
+    /// User credited in the byline; None falls back to the first admin
+    pub author_id: Option<i64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,6 +61,17 @@ pub struct PostForm {
     pub published_at: Option<String>,
     pub category_ids: Option<Vec<i64>>,
     pub tag_ids: Option<Vec<i64>>,
+    pub robots_noindex: Option<bool>,
+    pub robots_nofollow: Option<bool>,
+    pub canonical_url: Option<String>,
+    pub schema_type: Option<String>,
+    pub focus_keyword: Option<String>,
+    pub language: Option<String>,
+    pub translation_group: Option<String>,
+    pub audio_file: Option<String>,
+    pub audio_duration: Option<i64>,
+    pub audio_bytes: Option<i64>,
+    pub template: Option<String>,
 }
 
 impl Post {
@@ -57,6 +92,18 @@ impl Post {
             updated_at: row.get("updated_at")?,
             seo_score: row.get("seo_score").unwrap_or(-1),
             seo_issues: row.get("seo_issues").unwrap_or_else(|_| "[]".to_string()),
+            robots_noindex: row.get::<_, i64>("robots_noindex").unwrap_or(0) != 0,
+            robots_nofollow: row.get::<_, i64>("robots_nofollow").unwrap_or(0) != 0,
+            canonical_url: row.get("canonical_url").unwrap_or(None),
+            schema_type: row.get("schema_type").unwrap_or(None),
+            focus_keyword: row.get("focus_keyword").unwrap_or(None),
+            language: row.get("language").unwrap_or(None),
+            translation_group: row.get("translation_group").unwrap_or(None),
+            audio_file: row.get("audio_file").unwrap_or(None),
+            audio_duration: row.get("audio_duration").unwrap_or(None),
+            audio_bytes: row.get("audio_bytes").unwrap_or(None),
+            template: row.get("template").unwrap_or(None),
+            author_id: row.get("author_id").unwrap_or(None),
         })
     }
 
@@ -80,6 +127,33 @@ impl Post {
         .ok()
     }
 
+    /// Up to `limit` posts that moved to `status` after change `since_seq`,
+    /// in the order they did, or the latest to without one. Each comes with
+    /// its change number.
+    pub fn list_since(
+        pool: &DbPool,
+        status: &str,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, Self)> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT * FROM posts WHERE status = ?1 AND (?2 IS NULL OR status_seq > ?2)
+             ORDER BY CASE WHEN ?2 IS NULL THEN -status_seq ELSE status_seq END LIMIT ?3",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![status, since_seq, limit], |row| {
+            Ok((row.get("status_seq")?, Self::from_row(row)?))
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
     pub fn list(pool: &DbPool, status: Option<&str>, limit: i64, offset: i64) -> Vec<Self> {
         let conn = match pool.get() {
             Ok(c) => c,
@@ -111,6 +185,32 @@ impl Post {
             .unwrap_or_default()
     }
 
+    /// Keyset page of `list`, optionally limited to one author's posts
+    pub fn list_keyset(
+        pool: &DbPool,
+        owner: Option<i64>,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Self>, Option<Cursor>) {
+        let mut filters: Vec<(&str, Box<dyn rusqlite::types::ToSql>)> = Vec::new();
+        if let Some(u) = owner {
+            filters.push(("author_id", Box::new(u)));
+        }
+        if let Some(s) = status {
+            filters.push(("status", Box::new(s.to_string())));
+        }
+        cursor::sqlite_page(
+            pool,
+            "posts",
+            "published_at",
+            filters,
+            after,
+            limit,
+            Self::from_row,
+        )
+    }
+
     pub fn count(pool: &DbPool, status: Option<&str>) -> i64 {
         let conn = match pool.get() {
             Ok(c) => c,
@@ -146,8 +246,10 @@ impl Post {
         });
 
         conn.execute(
-            "INSERT INTO posts (title, slug, content_json, content_html, excerpt, featured_image, meta_title, meta_description, status, published_at, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, COALESCE(?10, CURRENT_TIMESTAMP), COALESCE(?10, CURRENT_TIMESTAMP))",
+            "INSERT INTO posts (title, slug, content_json, content_html, excerpt, featured_image, meta_title, meta_description, status, published_at, created_at, updated_at,
+             robots_noindex, robots_nofollow, canonical_url, schema_type, focus_keyword, language, translation_group,
+             audio_file, audio_duration, audio_bytes, template)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, COALESCE(?10, CURRENT_TIMESTAMP), COALESCE(?10, CURRENT_TIMESTAMP), ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)",
             params![
                 form.title,
                 form.slug,
@@ -159,6 +261,17 @@ impl Post {
                 form.meta_description,
                 form.status,
                 published_at,
+                form.robots_noindex.unwrap_or(false) as i64,
+                form.robots_nofollow.unwrap_or(false) as i64,
+                form.canonical_url,
+                form.schema_type,
+                form.focus_keyword,
+                form.language,
+                form.translation_group,
+                form.audio_file,
+                form.audio_duration,
+                form.audio_bytes,
+                form.template,
             ],
         )
         .map_err(|e| e.to_string())?;
@@ -180,6 +293,8 @@ impl Post {
         conn.execute(
             "UPDATE posts SET title=?1, slug=?2, content_json=?3, content_html=?4, excerpt=?5,
              featured_image=?6, meta_title=?7, meta_description=?8, status=?9, published_at=?10,
+             robots_noindex=?12, robots_nofollow=?13, canonical_url=?14, schema_type=?15, focus_keyword=?16,
+             language=?17, translation_group=?18, audio_file=?19, audio_duration=?20, audio_bytes=?21, template=?22,
              updated_at=CURRENT_TIMESTAMP WHERE id=?11",
             params![
                 form.title,
@@ -193,6 +308,17 @@ impl Post {
                 form.status,
                 published_at,
                 id,
+                form.robots_noindex.unwrap_or(false) as i64,
+                form.robots_nofollow.unwrap_or(false) as i64,
+                form.canonical_url,
+                form.schema_type,
+                form.focus_keyword,
+                form.language,
+                form.translation_group,
+                form.audio_file,
+                form.audio_duration,
+                form.audio_bytes,
+                form.template,
             ],
         )
         .map_err(|e| e.to_string())?;
@@ -242,6 +368,139 @@ impl Post {
         ).ok()
     }
 
+    /// Published posts in a translation group, oldest first
+    pub fn translations(pool: &DbPool, group: &str) -> Vec<Self> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT * FROM posts WHERE translation_group = ?1 AND status = 'published' ORDER BY id",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![group], Self::from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Published posts with episode audio, newest first
+    pub fn episodes(pool: &DbPool, limit: i64) -> Vec<Self> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT * FROM posts WHERE status = 'published' AND audio_file IS NOT NULL AND audio_file != ''
+             ORDER BY published_at DESC LIMIT ?1",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![limit], Self::from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Published posts credited to `user_id`, newest first. With
+    /// `include_unattributed`, posts without an author count as theirs too.
+    pub fn by_author(
+        pool: &DbPool,
+        user_id: i64,
+        include_unattributed: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<Self> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT * FROM posts WHERE status = 'published' AND (author_id = ?1 OR (?2 AND author_id IS NULL))
+             ORDER BY published_at DESC LIMIT ?3 OFFSET ?4",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(
+            params![user_id, include_unattributed, limit, offset],
+            Self::from_row,
+        )
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    pub fn count_by_author(pool: &DbPool, user_id: i64, include_unattributed: bool) -> i64 {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return 0,
+        };
+        conn.query_row(
+            "SELECT COUNT(*) FROM posts WHERE status = 'published' AND (author_id = ?1 OR (?2 AND author_id IS NULL))",
+            params![user_id, include_unattributed],
+            |row| row.get(0),
+        )
+        .unwrap_or(0)
+    }
+
+    pub fn set_author(pool: &DbPool, id: i64, user_id: Option<i64>) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE posts SET author_id = ?1 WHERE id = ?2",
+            params![user_id, id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Posts credited to `user_id` in any status, or only in `status`
+    pub fn list_by_owner(
+        pool: &DbPool,
+        user_id: i64,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<Self> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT * FROM posts WHERE author_id = ?1 AND (?2 IS NULL OR status = ?2)
+             ORDER BY published_at DESC LIMIT ?3 OFFSET ?4",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![user_id, status, limit, offset], Self::from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn count_by_owner(pool: &DbPool, user_id: i64, status: Option<&str>) -> i64 {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return 0,
+        };
+        conn.query_row(
+            "SELECT COUNT(*) FROM posts WHERE author_id = ?1 AND (?2 IS NULL OR status = ?2)",
+            params![user_id, status],
+            |row| row.get(0),
+        )
+        .unwrap_or(0)
+    }
+
+    /// Credit every post of `from` to `to`; returns how many moved
+    pub fn reassign_author(pool: &DbPool, from: i64, to: i64) -> Result<usize, String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE posts SET author_id = ?1 WHERE author_id = ?2",
+            params![to, from],
+        )
+        .map_err(|e| e.to_string())
+    }
+
     pub fn update_status(pool: &DbPool, id: i64, status: &str) -> Result<(), String> {
         let conn = pool.get().map_err(|e| e.to_string())?;
         conn.execute(
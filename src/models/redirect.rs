@@ -0,0 +1,494 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::db::DbPool;
+
+/// A path redirect, checked before a request falls through to the 404 page.
+/// `source` is an exact path, or a prefix ending in `*`; a `*` in `target`
+/// is replaced by whatever the wildcard matched.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RedirectRule {
+    pub id: i64,
+    pub source: String,
+    pub target: String,
+    /// 301 (permanent) or 302 (temporary)
+    pub status_code: i64,
+    pub hits: i64,
+    pub created_at: String,
+}
+
+impl RedirectRule {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(RedirectRule {
+            id: row.get("id")?,
+            source: row.get("source")?,
+            target: row.get("target")?,
+            status_code: row.get("status_code")?,
+            hits: row.get("hits")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+
+    pub fn list(pool: &DbPool) -> Vec<RedirectRule> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare("SELECT * FROM redirects ORDER BY source") {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map([], Self::from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Insert a redirect (id 0) or update an existing one. Returns its id.
+    pub fn save(pool: &DbPool, rule: &RedirectRule) -> Result<i64, String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        if rule.id == 0 {
+            conn.execute(
+                "INSERT INTO redirects (source, target, status_code) VALUES (?1, ?2, ?3)",
+                params![rule.source, rule.target, rule.status_code],
+            )
+            .map_err(|e| e.to_string())?;
+            return Ok(conn.last_insert_rowid());
+        }
+        conn.execute(
+            "UPDATE redirects SET source = ?1, target = ?2, status_code = ?3 WHERE id = ?4",
+            params![rule.source, rule.target, rule.status_code, rule.id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(rule.id)
+    }
+
+    pub fn delete(pool: &DbPool, id: i64) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM redirects WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Bump a redirect's hit counter
+    pub fn record_hit(pool: &DbPool, id: i64) {
+        if let Ok(conn) = pool.get() {
+            let _ = conn.execute(
+                "UPDATE redirects SET hits = hits + 1 WHERE id = ?1",
+                params![id],
+            );
+        }
+    }
+
+    /// Where this rule sends `path`, or None if it doesn't match. A prefix
+    /// only matches whole path segments: `/blog*` covers `/blog` and
+    /// `/blog/post`, not `/blogging`.
+    pub fn target_for(&self, path: &str) -> Option<String> {
+        match self.source.strip_suffix('*') {
+            Some(prefix) => {
+                let rest = path.strip_prefix(prefix)?;
+                if !prefix.ends_with('/') && !rest.is_empty() && !rest.starts_with('/') {
+                    return None;
+                }
+                Some(self.target.replacen('*', rest, 1))
+            }
+            None => (self.source == path).then(|| self.target.clone()),
+        }
+    }
+}
+
+// ── Redirect cache ──────────────────────────────────────
+//
+// Every GET 404 is checked against the redirects, so each Store keeps the
+// list in memory. Saving or deleting a redirect through any Store bumps the
+// generation, which drops every Store's copy.
+
+static REDIRECTS_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Drop every cached redirect list, in every Store.
+pub fn invalidate_redirects() {
+    REDIRECTS_GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// The redirect list and the generation it was read at.
+#[derive(Default)]
+pub struct RedirectCache {
+    inner: RwLock<Option<(u64, Vec<RedirectRule>)>>,
+}
+
+impl RedirectCache {
+    /// The cached redirects, or `load` them and remember the result.
+    pub fn get_or_load(&self, load: impl FnOnce() -> Vec<RedirectRule>) -> Vec<RedirectRule> {
+        let generation = REDIRECTS_GENERATION.load(Ordering::SeqCst);
+        if let Ok(r) = self.inner.read() {
+            if let Some((g, rules)) = r.as_ref() {
+                if *g == generation {
+                    return rules.clone();
+                }
+            }
+        }
+        let rules = load();
+        if let Ok(mut w) = self.inner.write() {
+            if REDIRECTS_GENERATION.load(Ordering::SeqCst) == generation {
+                *w = Some((generation, rules.clone()));
+            }
+        }
+        rules
+    }
+
+    /// Count a hit on the cached copy too, so the admin list stays current.
+    pub fn record_hit(&self, id: i64) {
+        if let Ok(mut w) = self.inner.write() {
+            if let Some(rule) = w
+                .as_mut()
+                .and_then(|(_, rules)| rules.iter_mut().find(|r| r.id == id))
+            {
+                rule.hits += 1;
+            }
+        }
+    }
+}
+
+/// A public path that 404'd, with how often and where the latest visitor
+/// came from.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NotFoundEntry {
+    pub path: String,
+    pub referrer: Option<String>,
+    pub hits: i64,
+    pub first_seen: String,
+    pub last_seen: String,
+}
+
+/// Longest path or referrer kept in the 404 log
+pub const NOT_FOUND_MAX_LEN: usize = 500;
+
+/// Rows kept in the 404 log; the least-hit paths are trimmed past this
+pub const NOT_FOUND_MAX_ROWS: i64 = 5_000;
+
+/// Distinct paths buffered between flushes; new paths past this are dropped
+/// until the next flush, so a scan can't grow the buffer without bound
+pub const NOT_FOUND_BUFFER_PATHS: usize = 1_000;
+
+/// 404s counted in memory for one path, waiting to be written
+#[derive(Debug, Clone)]
+pub struct NotFoundHit {
+    pub path: String,
+    pub referrer: Option<String>,
+    pub hits: i64,
+}
+
+/// 404s counted by the catcher and written in batches by a background task,
+/// so a miss never waits on the database.
+pub struct NotFoundBuffer {
+    pending: Mutex<HashMap<String, NotFoundHit>>,
+}
+
+impl NotFoundBuffer {
+    pub fn new() -> Self {
+        NotFoundBuffer {
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record(&self, path: &str, referrer: Option<&str>) {
+        let mut pending = self.pending.lock().unwrap();
+        if let Some(hit) = pending.get_mut(path) {
+            hit.hits += 1;
+            if referrer.is_some() {
+                hit.referrer = referrer.map(|r| r.to_string());
+            }
+            return;
+        }
+        if pending.len() < NOT_FOUND_BUFFER_PATHS {
+            pending.insert(
+                path.to_string(),
+                NotFoundHit {
+                    path: path.to_string(),
+                    referrer: referrer.map(|r| r.to_string()),
+                    hits: 1,
+                },
+            );
+        }
+    }
+
+    /// Everything counted since the last call
+    pub fn take(&self) -> Vec<NotFoundHit> {
+        let mut pending = self.pending.lock().unwrap();
+        pending.drain().map(|(_, hit)| hit).collect()
+    }
+}
+
+impl Default for NotFoundBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NotFoundEntry {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(NotFoundEntry {
+            path: row.get("path")?,
+            referrer: row.get("referrer")?,
+            hits: row.get("hits")?,
+            first_seen: row.get("first_seen")?,
+            last_seen: row.get("last_seen")?,
+        })
+    }
+
+    /// Count a 404, keeping the previous referrer when this one has none
+    pub fn record(pool: &DbPool, path: &str, referrer: Option<&str>) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO not_found_log (path, referrer) VALUES (?1, ?2)
+             ON CONFLICT(path) DO UPDATE SET hits = hits + 1, last_seen = datetime('now'),
+             referrer = COALESCE(excluded.referrer, referrer)",
+            params![path, referrer],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Write a batch of buffered 404s in one transaction, then trim the log
+    /// back to `NOT_FOUND_MAX_ROWS`.
+    pub fn record_batch(pool: &DbPool, hits: &[NotFoundHit]) -> Result<(), String> {
+        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        for hit in hits {
+            tx.execute(
+                "INSERT INTO not_found_log (path, referrer, hits) VALUES (?1, ?2, ?3)
+                 ON CONFLICT(path) DO UPDATE SET hits = hits + excluded.hits,
+                 last_seen = datetime('now'), referrer = COALESCE(excluded.referrer, referrer)",
+                params![hit.path, hit.referrer, hit.hits],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.execute(
+            "DELETE FROM not_found_log WHERE path IN (
+                 SELECT path FROM not_found_log ORDER BY hits DESC, last_seen DESC
+                 LIMIT -1 OFFSET ?1)",
+            params![NOT_FOUND_MAX_ROWS],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())
+    }
+
+    /// Most-hit paths first
+    pub fn top(pool: &DbPool, limit: i64) -> Vec<NotFoundEntry> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn
+            .prepare("SELECT * FROM not_found_log ORDER BY hits DESC, last_seen DESC LIMIT ?1")
+        {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![limit], Self::from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn dismiss(pool: &DbPool, path: &str) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM not_found_log WHERE path = ?1", params![path])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Drop paths that haven't 404'd in `max_age_days`
+    pub fn cleanup(pool: &DbPool, max_age_days: i64) -> Result<usize, String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM not_found_log WHERE last_seen < datetime('now', ?1)",
+            params![format!("-{} days", max_age_days)],
+        )
+        .map_err(|e| e.to_string())
+    }
+}
+
+/// Tidy a source path: keep only the path of a full URL, add the leading
+/// slash and drop a trailing one (the router ignores it too).
+pub fn normalize_source(source: &str) -> String {
+    let mut s = source.trim();
+    if let Some(pos) = s.find("://") {
+        s = s[pos + 3..]
+            .find('/')
+            .map(|i| &s[pos + 3 + i..])
+            .unwrap_or("/");
+    }
+    let s = s.split(['?', '#']).next().unwrap_or("");
+    let s = if s.len() > 1 {
+        s.trim_end_matches('/')
+    } else {
+        s
+    };
+    if s.starts_with('/') {
+        s.to_string()
+    } else {
+        format!("/{}", s)
+    }
+}
+
+/// Check a rule before saving.
+pub fn validate(rule: &RedirectRule) -> Result<(), String> {
+    if rule.source.is_empty() || rule.source == "/" || !rule.source.starts_with('/') {
+        return Err("Source must be a path such as /old-post".into());
+    }
+    if rule.source.trim_end_matches('*').contains('*') {
+        return Err("Source may only end in a single * wildcard".into());
+    }
+    let target = rule.target.trim();
+    if target.is_empty() {
+        return Err("Target is required".into());
+    }
+    if !(target.starts_with('/') || target.starts_with("https://") || target.starts_with("http://"))
+        || target.contains(char::is_whitespace)
+    {
+        return Err("Target must be a path or an http(s) URL".into());
+    }
+    if rule.status_code != 301 && rule.status_code != 302 {
+        return Err("Status must be 301 or 302".into());
+    }
+    if rule.source == rule.target {
+        return Err("Source and target are the same".into());
+    }
+    Ok(())
+}
+
+/// The rule for `path` and where it sends it: an exact match wins,
+/// otherwise the longest matching wildcard prefix.
+pub fn find_match<'a>(rules: &'a [RedirectRule], path: &str) -> Option<(&'a RedirectRule, String)> {
+    let path = if path.len() > 1 {
+        path.trim_end_matches('/')
+    } else {
+        path
+    };
+    let rule = rules.iter().find(|r| r.source == path).or_else(|| {
+        rules
+            .iter()
+            .filter(|r| r.source.ends_with('*') && r.target_for(path).is_some())
+            .max_by_key(|r| r.source.len())
+    })?;
+    Some((rule, rule.target_for(path)?))
+}
+
+/// Split one CSV line, honouring double-quoted fields.
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+/// Parse `source,target[,status]` lines (status defaults to 301). A header
+/// row and blank lines are skipped; bad lines come back as errors with
+/// their line number.
+pub fn parse_csv(text: &str) -> (Vec<RedirectRule>, Vec<String>) {
+    let mut rules = Vec::new();
+    let mut errors = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields = split_csv_line(line);
+        if i == 0 && fields[0].eq_ignore_ascii_case("source") {
+            continue;
+        }
+        let status_code = match fields.get(2).map(|s| s.as_str()) {
+            None | Some("") => 301,
+            Some(s) => s.parse().unwrap_or(0),
+        };
+        let rule = RedirectRule {
+            id: 0,
+            source: normalize_source(&fields[0]),
+            target: fields.get(1).cloned().unwrap_or_default(),
+            status_code,
+            hits: 0,
+            created_at: String::new(),
+        };
+        match validate(&rule) {
+            Ok(()) => rules.push(rule),
+            Err(e) => errors.push(format!("Line {}: {}", i + 1, e)),
+        }
+    }
+    (rules, errors)
+}
+
+/// Last meaningful segment of a path, without a file extension — the part
+/// an old permalink (`/2019/05/my-post.html`) shares with its new slug.
+fn slug_of(path: &str) -> String {
+    let last = path
+        .split(['?', '#'])
+        .next()
+        .unwrap_or("")
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or("");
+    let stem = match last.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() && ext.len() <= 5 => stem,
+        _ => last,
+    };
+    stem.to_lowercase().replace('_', "-")
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                prev
+            } else {
+                1 + prev.min(cur).min(row[j])
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+/// Pick the content URL whose slug is closest to the one in a 404'd path.
+/// `candidates` are site paths such as `/journal/my-post`; a match needs at
+/// least 60% similarity.
+pub fn suggest_target(path: &str, candidates: &[String]) -> Option<String> {
+    let wanted = slug_of(path);
+    if wanted.is_empty() {
+        return None;
+    }
+    candidates
+        .iter()
+        .filter_map(|url| {
+            let slug = slug_of(url);
+            let longest = wanted.chars().count().max(slug.chars().count());
+            if slug.is_empty() || longest == 0 {
+                return None;
+            }
+            let score = 1.0 - levenshtein(&wanted, &slug) as f64 / longest as f64;
+            (score >= 0.6).then_some((score, url))
+        })
+        .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(_, url)| url.clone())
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+/// A stored role definition. Built-in roles (editor, author, subscriber) only
+/// have a row once their capabilities have been customised; custom roles
+/// always have one.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Role {
+    pub slug: String,
+    pub name: String,
+    pub capabilities: Vec<String>,
+    pub created_at: String,
+}
+
+impl Role {
+    /// Capabilities are stored as a comma-separated list.
+    pub fn parse_capabilities(s: &str) -> Vec<String> {
+        s.split(',')
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .collect()
+    }
+}
+
+// ── Role cache ──────────────────────────────────────────
+//
+// Capability guards look up the signed-in user's role on every request, so
+// each Store keeps the roles it has read in memory. A role change through
+// any Store bumps the generation, which drops every Store's cached roles —
+// two Store instances over the same database never disagree.
+
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Drop every cached role, in every Store.
+pub fn invalidate() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Roles by slug (None for a slug with no stored row), and the generation
+/// they were read at.
+#[derive(Default)]
+pub struct RoleCache {
+    inner: RwLock<(u64, HashMap<String, Option<Role>>)>,
+}
+
+impl RoleCache {
+    /// The cached role for `slug`, or `load` it and remember the result.
+    pub fn get_or_load(&self, slug: &str, load: impl FnOnce() -> Option<Role>) -> Option<Role> {
+        let generation = GENERATION.load(Ordering::SeqCst);
+        if let Ok(r) = self.inner.read() {
+            if r.0 == generation {
+                if let Some(role) = r.1.get(slug) {
+                    return role.clone();
+                }
+            }
+        }
+        let role = load();
+        if let Ok(mut w) = self.inner.write() {
+            // A change made while loading leaves the generation moved on;
+            // don't store what may already be stale.
+            if GENERATION.load(Ordering::SeqCst) == generation {
+                if w.0 != generation {
+                    *w = (generation, HashMap::new());
+                }
+                w.1.insert(slug.to_string(), role.clone());
+            }
+        }
+        role
+    }
+}
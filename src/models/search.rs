@@ -12,11 +12,125 @@ pub struct SearchResult {
     pub snippet: String,
     pub image: Option<String>,
     pub date: Option<String>,
+    /// Relevance, lower is better (FTS5's BM25 convention). Whole-word
+    /// matches always rank ahead of substring matches.
     pub rank: f64,
 }
 
+/// BM25 weight of a title match relative to a body match
+pub const TITLE_WEIGHT: f64 = 10.0;
+
+/// Terms shorter than this aren't searched for inside words
+pub const MIN_SUBSTRING_LEN: usize = 3;
+
+/// Words of context in a result snippet
+pub const SNIPPET_WORDS: usize = 40;
+
+/// Results per page of the public search page
+pub const RESULTS_PER_PAGE: usize = 10;
+
+/// Most results the public search page pages through
+pub const MAX_RESULTS: i64 = 200;
+
+/// Words of a search query with punctuation and FTS5 operators removed
+pub(crate) fn query_terms(query: &str) -> Vec<String> {
+    query
+        .split_whitespace()
+        .map(|w| {
+            w.chars()
+                .filter(|c| c.is_alphanumeric() || *c == '_')
+                .collect::<String>()
+        })
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Rank for a substring match found after the whole-word pass: just behind
+/// `floor` (the last whole-word rank), ordered by its own BM25 score
+pub(crate) fn substring_rank(floor: f64, score: f64) -> f64 {
+    floor + 1.0 / (1.0 + score.abs())
+}
+
+/// Whether `word` matches one of the query terms the way the search does:
+/// as a prefix, or anywhere inside it for terms of `MIN_SUBSTRING_LEN` or
+/// more letters. Case is ignored.
+pub fn term_matches(word: &str, terms: &[String]) -> bool {
+    let word = word.to_lowercase();
+    terms.iter().any(|t| {
+        let t = t.to_lowercase();
+        word.starts_with(&t) || (t.chars().count() >= MIN_SUBSTRING_LEN && word.contains(&t))
+    })
+}
+
+/// Up to `SNIPPET_WORDS` words of `body`, starting shortly before the first
+/// word that matches `terms` so the match is in view. Without a match it's
+/// the opening words. Cut ends are marked with an ellipsis.
+pub(crate) fn snippet(body: &str, terms: &[String]) -> String {
+    let words: Vec<&str> = body.split_whitespace().collect();
+    let first = words
+        .iter()
+        .position(|w| term_matches(w.trim_matches(|c: char| !c.is_alphanumeric()), terms))
+        .unwrap_or(0);
+    let start = first
+        .saturating_sub(SNIPPET_WORDS / 4)
+        .min(words.len().saturating_sub(SNIPPET_WORDS));
+    let end = (start + SNIPPET_WORDS).min(words.len());
+    format!(
+        "{}{}{}",
+        if start > 0 { "…" } else { "" },
+        words[start..end].join(" "),
+        if end < words.len() { "…" } else { "" }
+    )
+}
+
+/// Where a page of search results sits among all of them, and how many
+/// there are of each type, for the filter links
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct SearchPaging {
+    /// Results of the selected type (all types when none is)
+    pub total: usize,
+    pub posts: usize,
+    pub portfolio: usize,
+    pub page: usize,
+    pub total_pages: usize,
+}
+
+/// Page `page` of `results`, keeping only `item_type` ("post" or
+/// "portfolio") when given. Results keep their ranked order; a page past
+/// the end shows the last one.
+pub fn paginate(
+    results: Vec<SearchResult>,
+    item_type: Option<&str>,
+    page: usize,
+) -> (Vec<SearchResult>, SearchPaging) {
+    let count = |t: &str| results.iter().filter(|r| r.item_type == t).count();
+    let (posts, portfolio) = (count("post"), count("portfolio"));
+    let matching: Vec<SearchResult> = results
+        .into_iter()
+        .filter(|r| item_type.is_none_or(|t| r.item_type == t))
+        .collect();
+    let total = matching.len();
+    let total_pages = total.div_ceil(RESULTS_PER_PAGE).max(1);
+    let page = page.clamp(1, total_pages);
+    let shown = matching
+        .into_iter()
+        .skip((page - 1) * RESULTS_PER_PAGE)
+        .take(RESULTS_PER_PAGE)
+        .collect();
+    (
+        shown,
+        SearchPaging {
+            total,
+            posts,
+            portfolio,
+            page,
+            total_pages,
+        },
+    )
+}
+
 /// Strip HTML tags from a string (simple regex-free approach).
-fn strip_html(html: &str) -> String {
+pub(crate) fn strip_html(html: &str) -> String {
     let mut out = String::with_capacity(html.len());
     let mut inside_tag = false;
     for ch in html.chars() {
@@ -32,7 +146,7 @@ fn strip_html(html: &str) -> String {
 }
 
 /// Truncate text to approximately `max_words` words.
-fn truncate_words(text: &str, max_words: usize) -> String {
+pub(crate) fn truncate_words(text: &str, max_words: usize) -> String {
     let words: Vec<&str> = text.split_whitespace().collect();
     if words.len() <= max_words {
         text.to_string()
@@ -41,7 +155,9 @@ fn truncate_words(text: &str, max_words: usize) -> String {
     }
 }
 
-/// Create the FTS5 virtual table if it doesn't exist.
+/// Create the FTS5 virtual tables if they don't exist: `search_index` for
+/// whole words and prefixes (stemmed), and `search_trigram`, a trigram copy
+/// of the titles and bodies (sharing rowids) for matching inside words.
 pub fn create_fts_table(pool: &DbPool) -> Result<(), String> {
     let conn = pool.get().map_err(|e| e.to_string())?;
     conn.execute_batch(
@@ -54,18 +170,56 @@ pub fn create_fts_table(pool: &DbPool) -> Result<(), String> {
             image UNINDEXED,
             date UNINDEXED,
             tokenize='porter unicode61'
+        );
+        CREATE VIRTUAL TABLE IF NOT EXISTS search_trigram USING fts5(
+            title,
+            body,
+            tokenize='trigram'
         );",
     )
     .map_err(|e| e.to_string())?;
     Ok(())
 }
 
+fn insert_item(
+    conn: &rusqlite::Connection,
+    item_type: &str,
+    item_id: i64,
+    title: &str,
+    body: &str,
+    slug: &str,
+    image: Option<&str>,
+    date: Option<&str>,
+) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO search_index (item_type, item_id, title, body, slug, image, date) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![item_type, item_id, title, body, slug, image, date],
+    )?;
+    conn.execute(
+        "INSERT INTO search_trigram (rowid, title, body) VALUES (?1, ?2, ?3)",
+        params![conn.last_insert_rowid(), title, body],
+    )?;
+    Ok(())
+}
+
+fn delete_item(conn: &rusqlite::Connection, item_type: &str, item_id: i64) {
+    let _ = conn.execute(
+        "DELETE FROM search_trigram WHERE rowid IN
+            (SELECT rowid FROM search_index WHERE item_type = ?1 AND item_id = ?2)",
+        params![item_type, item_id],
+    );
+    let _ = conn.execute(
+        "DELETE FROM search_index WHERE item_type = ?1 AND item_id = ?2",
+        params![item_type, item_id],
+    );
+}
+
 /// Rebuild the entire search index from published posts and portfolio items.
 pub fn rebuild_index(pool: &DbPool) -> Result<usize, String> {
     let conn = pool.get().map_err(|e| e.to_string())?;
 
     // Clear existing index
-    conn.execute("DELETE FROM search_index", [])
+    conn.execute_batch("DELETE FROM search_index; DELETE FROM search_trigram;")
         .map_err(|e| e.to_string())?;
 
     let mut count = 0usize;
@@ -94,9 +248,15 @@ pub fn rebuild_index(pool: &DbPool) -> Result<usize, String> {
         for row in rows {
             let (id, title, html, slug, image, date) = row.map_err(|e| e.to_string())?;
             let body = strip_html(&html);
-            conn.execute(
-                "INSERT INTO search_index (item_type, item_id, title, body, slug, image, date) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                params!["post", id, title, body, slug, image, date],
+            insert_item(
+                &conn,
+                "post",
+                id,
+                &title,
+                &body,
+                &slug,
+                image.as_deref(),
+                date.as_deref(),
             )
             .map_err(|e| e.to_string())?;
             count += 1;
@@ -127,9 +287,15 @@ pub fn rebuild_index(pool: &DbPool) -> Result<usize, String> {
         for row in rows {
             let (id, title, html, slug, image, date) = row.map_err(|e| e.to_string())?;
             let body = strip_html(&html.unwrap_or_default());
-            conn.execute(
-                "INSERT INTO search_index (item_type, item_id, title, body, slug, image, date) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-                params!["portfolio", id, title, body, slug, image, date],
+            insert_item(
+                &conn,
+                "portfolio",
+                id,
+                &title,
+                &body,
+                &slug,
+                image.as_deref(),
+                date.as_deref(),
             )
             .map_err(|e| e.to_string())?;
             count += 1;
@@ -158,18 +324,12 @@ pub fn upsert_item(
     };
 
     // Always remove old entry first
-    let _ = conn.execute(
-        "DELETE FROM search_index WHERE item_type = ?1 AND item_id = ?2",
-        params![item_type, item_id],
-    );
+    delete_item(&conn, item_type, item_id);
 
     // Only insert if published
     if is_published {
         let body = strip_html(html_body);
-        let _ = conn.execute(
-            "INSERT INTO search_index (item_type, item_id, title, body, slug, image, date) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
-            params![item_type, item_id, title, body, slug, image, date],
-        );
+        let _ = insert_item(&conn, item_type, item_id, title, &body, slug, image, date);
     }
 }
 
@@ -180,54 +340,103 @@ pub fn remove_item(pool: &DbPool, item_type: &str, item_id: i64) {
         Ok(c) => c,
         Err(_) => return,
     };
-    let _ = conn.execute(
-        "DELETE FROM search_index WHERE item_type = ?1 AND item_id = ?2",
-        params![item_type, item_id],
-    );
+    delete_item(&conn, item_type, item_id);
 }
 
-/// Search the FTS index. Returns results ranked by relevance.
+/// Search the FTS index. Returns results ranked by relevance: BM25 with
+/// titles weighted above bodies, each word also matching as a prefix
+/// ("photo" finds "photography"). When that leaves room under `limit`,
+/// words of three or more letters are looked for inside other words too
+/// ("graph" finds "photography"), ranked after every whole-word match.
+/// Shorter words are left out of that second pass.
 pub fn search(pool: &DbPool, query: &str, limit: i64) -> Vec<SearchResult> {
     let conn = match pool.get() {
         Ok(c) => c,
         Err(_) => return vec![],
     };
 
-    let query = query.trim();
-    if query.is_empty() {
+    let terms = query_terms(query);
+    if terms.is_empty() || limit <= 0 {
         return vec![];
     }
 
-    // Escape FTS5 special characters and append * for prefix matching
-    let fts_query = query
-        .split_whitespace()
-        .map(|w| {
-            // Remove FTS5 operators from user input
-            let clean: String = w
-                .chars()
-                .filter(|c| c.is_alphanumeric() || *c == '_')
-                .collect();
-            if clean.is_empty() {
-                String::new()
-            } else {
-                format!("\"{}\"*", clean)
-            }
-        })
-        .filter(|s| !s.is_empty())
-        .collect::<Vec<_>>()
-        .join(" ");
+    // Whole words and prefixes, in titles and bodies only
+    let fts_query = format!(
+        "{{title body}} : ({})",
+        terms
+            .iter()
+            .map(|t| format!("\"{}\"*", t))
+            .collect::<Vec<_>>()
+            .join(" ")
+    );
+    let mut results = run_search(
+        &conn,
+        &format!(
+            "SELECT item_type, item_id, title, body, slug, image, date,
+                    bm25(search_index, 0.0, 0.0, {}, 1.0, 0.0, 0.0, 0.0) AS score
+             FROM search_index
+             WHERE search_index MATCH ?1
+             ORDER BY score
+             LIMIT ?2",
+            TITLE_WEIGHT
+        ),
+        &fts_query,
+        limit,
+        &terms,
+    );
 
-    if fts_query.is_empty() {
-        return vec![];
+    let substrings: Vec<&String> = terms
+        .iter()
+        .filter(|t| t.chars().count() >= MIN_SUBSTRING_LEN)
+        .collect();
+    if (results.len() as i64) < limit && !substrings.is_empty() {
+        let trigram_query = substrings
+            .iter()
+            .map(|t| format!("\"{}\"", t))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let floor = results.last().map(|r| r.rank).unwrap_or(0.0);
+        let extra = run_search(
+            &conn,
+            &format!(
+                "SELECT s.item_type, s.item_id, s.title, s.body, s.slug, s.image, s.date,
+                        bm25(search_trigram, {}, 1.0) AS score
+                 FROM search_trigram t
+                 JOIN search_index s ON s.rowid = t.rowid
+                 WHERE search_trigram MATCH ?1
+                 ORDER BY score
+                 LIMIT ?2",
+                TITLE_WEIGHT
+            ),
+            &trigram_query,
+            limit,
+            &terms,
+        );
+        for mut r in extra {
+            if results.len() as i64 >= limit {
+                break;
+            }
+            if results
+                .iter()
+                .any(|e| e.item_type == r.item_type && e.item_id == r.item_id)
+            {
+                continue;
+            }
+            r.rank = substring_rank(floor, r.rank);
+            results.push(r);
+        }
     }
+    results
+}
 
-    let mut stmt = match conn.prepare(
-        "SELECT item_type, item_id, title, body, slug, image, date, rank
-         FROM search_index
-         WHERE search_index MATCH ?1
-         ORDER BY rank
-         LIMIT ?2",
-    ) {
+fn run_search(
+    conn: &rusqlite::Connection,
+    sql: &str,
+    fts_query: &str,
+    limit: i64,
+    terms: &[String],
+) -> Vec<SearchResult> {
+    let mut stmt = match conn.prepare(sql) {
         Ok(s) => s,
         Err(_) => return vec![],
     };
@@ -239,7 +448,7 @@ pub fn search(pool: &DbPool, query: &str, limit: i64) -> Vec<SearchResult> {
             item_id: row.get(1)?,
             title: row.get(2)?,
             slug: row.get(4)?,
-            snippet: truncate_words(&body, 40),
+            snippet: snippet(&body, terms),
             image: row.get(5)?,
             date: row.get(6)?,
             rank: row.get(7)?,
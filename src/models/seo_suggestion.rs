@@ -0,0 +1,23 @@
+use serde::Serialize;
+
+/// An AI rewrite proposed for a post or portfolio item, waiting for an admin
+/// to apply or dismiss it.
+#[derive(Debug, Clone, Serialize)]
+pub struct SeoSuggestion {
+    pub id: i64,
+    /// "post" or "portfolio"
+    pub content_type: String,
+    pub content_id: i64,
+    /// "meta_title", "meta_description" or "heading"
+    pub field: String,
+    /// Text being replaced; for headings, the heading to find
+    pub current: String,
+    pub suggested: String,
+    /// Heading level to use (2-4); 0 for other fields
+    pub level: i64,
+    /// Why the model proposed it
+    pub reason: String,
+    /// "pending", "applied" or "dismissed"
+    pub status: String,
+    pub created_at: String,
+}
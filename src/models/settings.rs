@@ -1,39 +1,105 @@
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 
 use crate::db::DbPool;
+use crate::models::category::Category;
+use crate::models::design::Design;
+use crate::render::page_cache;
+
+/// What every public page render reads besides its own content
+#[derive(Debug, Clone)]
+pub struct RenderContext {
+    pub settings: HashMap<String, String>,
+    /// Portfolio categories shown in the nav
+    pub nav_categories: Vec<Category>,
+    /// Journal categories shown in the nav
+    pub nav_journal_categories: Vec<Category>,
+    pub design: Option<Design>,
+}
+
+impl RenderContext {
+    pub fn load(store: &dyn crate::store::Store) -> Self {
+        RenderContext {
+            settings: store.setting_all(),
+            nav_categories: store.category_list_nav_visible(Some("portfolio")),
+            nav_journal_categories: store.category_list_nav_visible(Some("post")),
+            design: store.design_active(),
+        }
+    }
+}
 
 /// In-memory settings cache. Loaded once at startup, refreshed on settings save.
 /// All public route dispatching reads from this — zero DB overhead per request.
+/// It also holds the render context, reloaded after any Store write that
+/// drops the page cache (settings, categories, designs, content). Every
+/// reload also hands the search engine its connection settings.
 pub struct SettingsCache {
     inner: RwLock<HashMap<String, String>>,
+    /// The render context and the page cache generation it was loaded at
+    context: RwLock<Option<(u64, Arc<RenderContext>)>>,
 }
 
 impl SettingsCache {
     pub fn load(pool: &DbPool) -> Self {
-        Self {
-            inner: RwLock::new(Setting::all(pool)),
-        }
+        Self::with(Setting::all(pool))
     }
 
     pub fn refresh(&self, pool: &DbPool) {
-        if let Ok(mut w) = self.inner.write() {
-            *w = Setting::all(pool);
-        }
+        self.replace(Setting::all(pool));
+        self.drop_context();
     }
 
     pub fn load_from_store(store: &dyn crate::store::Store) -> Self {
+        Self::with(store.setting_all())
+    }
+
+    pub fn refresh_from_store(&self, store: &dyn crate::store::Store) {
+        self.replace(store.setting_all());
+        self.drop_context();
+    }
+
+    fn with(settings: HashMap<String, String>) -> Self {
+        crate::search_engine::configure(&settings);
         Self {
-            inner: RwLock::new(store.setting_all()),
+            inner: RwLock::new(settings),
+            context: RwLock::new(None),
         }
     }
 
-    pub fn refresh_from_store(&self, store: &dyn crate::store::Store) {
+    fn replace(&self, settings: HashMap<String, String>) {
+        crate::search_engine::configure(&settings);
         if let Ok(mut w) = self.inner.write() {
-            *w = store.setting_all();
+            *w = settings;
+        }
+    }
+
+    fn drop_context(&self) {
+        if let Ok(mut c) = self.context.write() {
+            *c = None;
+        }
+    }
+
+    /// Settings, nav categories and the active design for a page render,
+    /// from memory unless something changed since they were loaded.
+    /// Reloading also refreshes the settings `get` reads.
+    pub fn render_context(&self, store: &dyn crate::store::Store) -> Arc<RenderContext> {
+        // Read before loading: a write made mid-load leaves this stale
+        let generation = page_cache::generation();
+        if let Ok(c) = self.context.read() {
+            if let Some((at, ctx)) = c.as_ref() {
+                if *at == generation {
+                    return Arc::clone(ctx);
+                }
+            }
+        }
+        let ctx = Arc::new(RenderContext::load(store));
+        self.replace(ctx.settings.clone());
+        if let Ok(mut c) = self.context.write() {
+            *c = Some((generation, Arc::clone(&ctx)));
         }
+        ctx
     }
 
     pub fn get(&self, key: &str) -> Option<String> {
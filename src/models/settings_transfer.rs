@@ -0,0 +1,222 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::security::secrets;
+use crate::store::Store;
+
+// ── Settings export / import ───────────────────────────
+//
+// A JSON copy of the site's settings, to replicate a configuration on
+// another environment or site. Credentials are exported masked; a masked
+// value in an import keeps the target's own credential.
+
+/// `format` field of an export document
+pub const FORMAT: &str = "velocty-settings";
+/// Newest document version this build reads and the one it writes
+pub const VERSION: i64 = 1;
+
+/// Largest value accepted on import (logos are stored as data URLs)
+const MAX_VALUE_LEN: usize = 2 * 1024 * 1024;
+
+/// Settings that describe this instance rather than its configuration:
+/// where it lives, its setup state, admin credentials and bookkeeping that
+/// background tasks keep. Never exported or imported.
+pub const INSTANCE_KEYS: &[&str] = &[
+    "setup_completed",
+    "site_url",
+    "site_environment",
+    "admin_slug",
+    "admin_password_hash",
+    "db_backend",
+    "siem_fw_cursor",
+    "siem_audit_cursor",
+    "mta_dkim_generated_at",
+    "image_proxy_secret_old_expires",
+    "analytics_report_last_sent",
+    "digest_last_sent",
+    "seo_pagerank_cache",
+    "seo_moz_cache",
+    "font_google_local_key",
+    "font_google_local_css",
+    "font_google_local_error",
+    "search_engine_last_sync",
+    "search_engine_last_error",
+];
+
+pub fn is_instance_key(key: &str) -> bool {
+    INSTANCE_KEYS.contains(&key) || key.starts_with("mfa_")
+}
+
+/// The export document: every portable setting, credentials masked
+pub fn export(store: &dyn Store) -> Value {
+    let settings: BTreeMap<String, String> = store
+        .setting_all()
+        .into_iter()
+        .filter(|(k, _)| !is_instance_key(k))
+        .map(|(k, v)| {
+            let v = if secrets::is_secret_key(&k) {
+                secrets::mask(&v)
+            } else {
+                v
+            };
+            (k, v)
+        })
+        .collect();
+    json!({
+        "format": FORMAT,
+        "version": VERSION,
+        "site_name": store.setting_get_or("site_name", "Velocty"),
+        "exported_at": chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        "settings": settings,
+    })
+}
+
+/// A setting an import would change. Credentials are masked in `old` and
+/// `new`; the value to store is kept aside.
+#[derive(Debug, Serialize)]
+pub struct SettingChange {
+    pub key: String,
+    pub old: String,
+    pub new: String,
+    #[serde(skip_serializing)]
+    pub value: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkippedSetting {
+    pub key: String,
+    pub reason: String,
+}
+
+/// What importing a document would do, for the preview and for applying it
+#[derive(Debug, Default, Serialize)]
+pub struct ImportPlan {
+    pub changes: Vec<SettingChange>,
+    pub skipped: Vec<SkippedSetting>,
+    pub unchanged: usize,
+    pub warnings: Vec<String>,
+}
+
+impl ImportPlan {
+    /// Values to store when the plan is applied
+    pub fn updates(&self) -> HashMap<String, String> {
+        self.changes
+            .iter()
+            .map(|c| (c.key.clone(), c.value.clone()))
+            .collect()
+    }
+}
+
+fn skip(plan: &mut ImportPlan, key: &str, reason: &str) {
+    plan.skipped.push(SkippedSetting {
+        key: key.to_string(),
+        reason: reason.to_string(),
+    });
+}
+
+fn display(key: &str, value: &str) -> String {
+    if secrets::is_secret_key(key) {
+        secrets::mask(value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Check one imported value, normalising it where the settings page would
+fn validate(key: &str, value: String) -> Result<String, String> {
+    if value.len() > MAX_VALUE_LEN {
+        return Err("Value is too large".into());
+    }
+    match key {
+        "site_language" => crate::seo::hreflang::normalize_language(Some(value.as_str()))
+            .ok_or_else(|| format!("\"{}\" is not a language code", value)),
+        "seo_robots_txt" => crate::seo::robots::validate_robots(&value).map(|_| value),
+        "email_routing_rules" => {
+            crate::email::routing::validate_routing_rules(&value).map(|_| value)
+        }
+        "homepage_sections" if !value.trim().is_empty() => {
+            crate::designs::homepage::normalize_sections(&value)
+        }
+        "font_google_subsets" => Ok(crate::typography::self_host::normalize_subsets(&value)),
+        "site_logo" | "site_favicon" => {
+            Ok(crate::svg_sanitizer::sanitize_data_url(&value).unwrap_or(value))
+        }
+        _ => Ok(value),
+    }
+}
+
+/// Compare an export document against the `current` settings. Unknown,
+/// instance-specific, masked and invalid entries are skipped with a reason;
+/// a malformed document is an error.
+pub fn plan(current: &HashMap<String, String>, document: &Value) -> Result<ImportPlan, String> {
+    if document.get("format").and_then(Value::as_str) != Some(FORMAT) {
+        return Err("Not a Velocty settings export".into());
+    }
+    match document.get("version").and_then(Value::as_i64) {
+        Some(v) if (1..=VERSION).contains(&v) => {}
+        Some(v) => {
+            return Err(format!(
+                "Export version {} is newer than this Velocty supports",
+                v
+            ))
+        }
+        None => return Err("Export version is missing".into()),
+    }
+    let incoming = document
+        .get("settings")
+        .and_then(Value::as_object)
+        .ok_or("Export has no settings")?;
+
+    let mut plan = ImportPlan::default();
+    let mut keys: Vec<&String> = incoming.keys().collect();
+    keys.sort();
+    for key in keys {
+        let value = match &incoming[key] {
+            Value::String(s) => s.clone(),
+            Value::Bool(b) => b.to_string(),
+            Value::Number(n) => n.to_string(),
+            _ => {
+                skip(&mut plan, key, "Not a text value");
+                continue;
+            }
+        };
+        if is_instance_key(key) {
+            skip(&mut plan, key, "Specific to each site; not imported");
+            continue;
+        }
+        if !current.contains_key(key.as_str()) {
+            skip(&mut plan, key, "Unknown setting");
+            continue;
+        }
+        if secrets::is_secret_key(key) && (value.is_empty() || secrets::is_masked(&value)) {
+            skip(
+                &mut plan,
+                key,
+                "Credential not in the export; current value kept",
+            );
+            continue;
+        }
+        let value = match validate(key, value) {
+            Ok(v) => v,
+            Err(e) => {
+                skip(&mut plan, key, &e);
+                continue;
+            }
+        };
+        let old = &current[key.as_str()];
+        if *old == value {
+            plan.unchanged += 1;
+            continue;
+        }
+        plan.changes.push(SettingChange {
+            key: key.clone(),
+            old: display(key, old),
+            new: display(key, &value),
+            value,
+        });
+    }
+    plan.warnings = secrets::scan_public_fields(&plan.updates());
+    Ok(plan)
+}
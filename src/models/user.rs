@@ -10,7 +10,7 @@ pub struct User {
     pub password_hash: String,
     pub display_name: String,
     pub role: String,   // admin, editor, author, subscriber
-    pub status: String, // active, suspended, locked
+    pub status: String, // active, pending (unverified member), suspended, locked
     pub avatar: String,
     pub mfa_enabled: bool,
     pub mfa_secret: String,
@@ -21,6 +21,18 @@ pub struct User {
     pub auth_method: String,
     pub auth_method_fallback: String,
     pub force_password_change: bool,
+    /// Password sign-in disabled; the account uses passkeys, with sign-in
+    /// links and recovery codes as fallback.
+    pub passwordless: bool,
+    /// Second factor asked for at sign-in when MFA is on: "totp" or "email".
+    pub mfa_method: String,
+    /// Author page path segment (`/author/<slug>`); empty derives one from
+    /// the display name.
+    pub author_slug: String,
+    pub bio: String,
+    pub website: String,
+    /// Profile links for the author page, one URL per line.
+    pub social_links: String,
 }
 
 impl User {
@@ -49,11 +61,19 @@ impl User {
                 .get::<_, Option<String>>(14)?
                 .unwrap_or_else(|| "password".to_string()),
             force_password_change: row.get::<_, Option<i32>>(15)?.unwrap_or(0) != 0,
+            passwordless: row.get::<_, Option<i32>>(16)?.unwrap_or(0) != 0,
+            mfa_method: row
+                .get::<_, Option<String>>(17)?
+                .unwrap_or_else(|| "totp".to_string()),
+            author_slug: row.get::<_, Option<String>>(18)?.unwrap_or_default(),
+            bio: row.get::<_, Option<String>>(19)?.unwrap_or_default(),
+            website: row.get::<_, Option<String>>(20)?.unwrap_or_default(),
+            social_links: row.get::<_, Option<String>>(21)?.unwrap_or_default(),
         })
     }
 
     const SELECT_COLS: &'static str =
-        "id, email, password_hash, display_name, role, status, avatar, mfa_enabled, mfa_secret, mfa_recovery_codes, last_login_at, created_at, updated_at, auth_method, auth_method_fallback, force_password_change";
+        "id, email, password_hash, display_name, role, status, avatar, mfa_enabled, mfa_secret, mfa_recovery_codes, last_login_at, created_at, updated_at, auth_method, auth_method_fallback, force_password_change, passwordless, mfa_method, author_slug, bio, website, social_links";
 
     // ── Lookups ──
 
@@ -204,6 +224,23 @@ impl User {
         Ok(())
     }
 
+    pub fn update_author_profile(
+        pool: &DbPool,
+        id: i64,
+        author_slug: &str,
+        bio: &str,
+        website: &str,
+        social_links: &str,
+    ) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE users SET author_slug = ?1, bio = ?2, website = ?3, social_links = ?4, updated_at = CURRENT_TIMESTAMP WHERE id = ?5",
+            params![author_slug, bio, website, social_links, id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
     pub fn update_role(pool: &DbPool, id: i64, role: &str) -> Result<(), String> {
         let conn = pool.get().map_err(|e| e.to_string())?;
         conn.execute(
@@ -277,6 +314,16 @@ impl User {
         Ok(())
     }
 
+    pub fn set_status(pool: &DbPool, id: i64, status: &str) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE users SET status = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            params![status, id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
     pub fn unlock(pool: &DbPool, id: i64) -> Result<(), String> {
         let conn = pool.get().map_err(|e| e.to_string())?;
         conn.execute(
@@ -345,9 +392,42 @@ impl User {
             "auth_method": self.auth_method,
             "auth_method_fallback": self.auth_method_fallback,
             "force_password_change": self.force_password_change,
+            "passwordless": self.passwordless,
+            "mfa_method": self.mfa_method,
+            "author_slug": self.author_slug,
+            "bio": self.bio,
+            "website": self.website,
+            "social_links": self.social_links,
         })
     }
 
+    /// Path segment of the public author page
+    pub fn public_slug(&self) -> String {
+        if !self.author_slug.is_empty() {
+            return self.author_slug.clone();
+        }
+        match slug::slugify(&self.display_name) {
+            s if s.is_empty() => format!("author-{}", self.id),
+            s => s,
+        }
+    }
+
+    /// Profile links with a web URL, in the order entered
+    pub fn social_link_list(&self) -> Vec<String> {
+        self.social_links
+            .lines()
+            .map(str::trim)
+            .filter(|l| l.starts_with("https://") || l.starts_with("http://"))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Whether sign-in needs a second factor: an authenticator app code, or
+    /// an emailed code when that's the chosen method.
+    pub fn mfa_required(&self) -> bool {
+        self.mfa_enabled && (self.mfa_method == "email" || !self.mfa_secret.is_empty())
+    }
+
     pub fn update_auth_method(
         pool: &DbPool,
         id: i64,
@@ -362,4 +442,24 @@ impl User {
         .map_err(|e| e.to_string())?;
         Ok(())
     }
+
+    pub fn set_mfa_method(pool: &DbPool, id: i64, method: &str) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE users SET mfa_method = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            params![method, id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn set_passwordless(pool: &DbPool, id: i64, passwordless: bool) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE users SET passwordless = ?1, updated_at = CURRENT_TIMESTAMP WHERE id = ?2",
+            params![passwordless as i32, id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
 }
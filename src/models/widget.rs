@@ -0,0 +1,124 @@
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::db::DbPool;
+
+/// Widget kinds as (key, label)
+pub const WIDGET_TYPES: &[(&str, &str)] = &[
+    ("recent_posts", "Recent Posts"),
+    ("tag_cloud", "Tag Cloud"),
+    ("custom_html", "Custom HTML"),
+    ("newsletter", "Newsletter Signup"),
+    ("social_links", "Social Links"),
+];
+
+/// Regions of a design's shell that hold widgets, as (key, label)
+pub const WIDGET_AREAS: &[(&str, &str)] = &[("sidebar", "Sidebar"), ("footer", "Footer")];
+
+/// A block shown in one of a design's widget areas. `config` is a JSON
+/// object whose fields depend on `widget_type`: `count` for recent posts
+/// and the tag cloud, `html` for custom HTML, `text` and `button` for the
+/// newsletter signup.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Widget {
+    pub id: i64,
+    pub design_id: i64,
+    pub area: String,
+    pub widget_type: String,
+    pub title: String,
+    pub config: String,
+    pub sort_order: i64,
+    pub created_at: String,
+}
+
+impl Widget {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Widget {
+            id: row.get("id")?,
+            design_id: row.get("design_id")?,
+            area: row.get("area")?,
+            widget_type: row.get("widget_type")?,
+            title: row.get("title")?,
+            config: row.get("config")?,
+            sort_order: row.get("sort_order")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+
+    /// Parsed `config`, an empty object when it's missing or invalid
+    pub fn config_value(&self) -> Value {
+        serde_json::from_str::<Value>(&self.config)
+            .ok()
+            .filter(|v| v.is_object())
+            .unwrap_or_else(|| serde_json::json!({}))
+    }
+
+    /// All of a design's widgets, by area then position
+    pub fn list_for_design(pool: &DbPool, design_id: i64) -> Vec<Widget> {
+        let conn = match pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn
+            .prepare("SELECT * FROM widgets WHERE design_id = ?1 ORDER BY area, sort_order, id")
+        {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![design_id], Self::from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Insert a widget (id 0) or update an existing one. Returns its id.
+    pub fn save(pool: &DbPool, w: &Widget) -> Result<i64, String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        if w.id == 0 {
+            conn.execute(
+                "INSERT INTO widgets (design_id, area, widget_type, title, config, sort_order)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    w.design_id,
+                    w.area,
+                    w.widget_type,
+                    w.title,
+                    w.config,
+                    w.sort_order
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            return Ok(conn.last_insert_rowid());
+        }
+        conn.execute(
+            "UPDATE widgets SET area = ?1, title = ?2, config = ?3, sort_order = ?4 WHERE id = ?5",
+            params![w.area, w.title, w.config, w.sort_order, w.id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(w.id)
+    }
+
+    pub fn delete(pool: &DbPool, id: i64) -> Result<(), String> {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM widgets WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+/// Check a widget before it's saved
+pub fn validate(w: &Widget) -> Result<(), String> {
+    if !WIDGET_AREAS.iter().any(|(k, _)| *k == w.area) {
+        return Err(format!("Unknown widget area: {}", w.area));
+    }
+    if !WIDGET_TYPES.iter().any(|(k, _)| *k == w.widget_type) {
+        return Err(format!("Unknown widget type: {}", w.widget_type));
+    }
+    if w.title.chars().count() > 200 {
+        return Err("Title is too long".into());
+    }
+    if w.config.len() > 64 * 1024 {
+        return Err("Widget settings are too large".into());
+    }
+    Ok(())
+}
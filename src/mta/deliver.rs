@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
 use std::time::Duration;
 
 use lettre::message::header::ContentType;
@@ -8,6 +9,7 @@ use trust_dns_resolver::config::{ResolverConfig, ResolverOpts};
 use trust_dns_resolver::Resolver;
 
 use super::dkim;
+use crate::email::smtp::{cached_transport, PoolOptions, TransportCache};
 
 /// Look up MX records for a domain, returning hostnames sorted by priority (lowest first).
 pub fn mx_lookup(domain: &str) -> Result<Vec<String>, String> {
@@ -136,9 +138,10 @@ pub fn send_direct(
     }
 
     // Try each MX host in priority order
+    let pool = PoolOptions::from_settings(settings);
     let mut last_error = String::new();
     for mx_host in &mx_hosts {
-        match try_send_to_mx(mx_host, &email) {
+        match try_send_to_mx(mx_host, &email, &pool) {
             Ok(()) => {
                 log::info!("[mta] Email sent to {} via MX {}", to, mx_host);
                 return Ok(());
@@ -156,19 +159,30 @@ pub fn send_direct(
     ))
 }
 
+/// Pooled transport for an MX host. Queue batches to the same domain reuse the
+/// open (STARTTLS-upgraded) connection instead of reconnecting per message.
+fn mx_transport(mx_host: &str, pool: &PoolOptions) -> Result<SmtpTransport, String> {
+    static MX_TRANSPORTS: OnceLock<TransportCache> = OnceLock::new();
+    let cache = MX_TRANSPORTS.get_or_init(|| Mutex::new(HashMap::new()));
+    cached_transport(cache, mx_host, &format!("{:?}", pool), || {
+        // Opportunistic STARTTLS on port 25 (standard MX delivery)
+        let tls_params = TlsParameters::builder(mx_host.to_string())
+            .dangerous_accept_invalid_certs(false)
+            .build()
+            .map_err(|e| format!("TLS params error: {}", e))?;
+
+        Ok(SmtpTransport::builder_dangerous(mx_host)
+            .port(25)
+            .tls(Tls::Opportunistic(tls_params))
+            .timeout(Some(Duration::from_secs(30)))
+            .pool_config(pool.config())
+            .build())
+    })
+}
+
 /// Attempt to deliver an email to a specific MX host via SMTP.
-fn try_send_to_mx(mx_host: &str, email: &Message) -> Result<(), String> {
-    // Try STARTTLS on port 25 first (standard MX delivery)
-    let tls_params = TlsParameters::builder(mx_host.to_string())
-        .dangerous_accept_invalid_certs(false)
-        .build()
-        .map_err(|e| format!("TLS params error: {}", e))?;
-
-    let mailer = SmtpTransport::builder_dangerous(mx_host)
-        .port(25)
-        .tls(Tls::Opportunistic(tls_params))
-        .timeout(Some(Duration::from_secs(30)))
-        .build();
+fn try_send_to_mx(mx_host: &str, email: &Message, pool: &PoolOptions) -> Result<(), String> {
+    let mailer = mx_transport(mx_host, pool)?;
 
     mailer
         .send(email)
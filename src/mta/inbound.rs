@@ -0,0 +1,716 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use base64::Engine;
+use rocket::tokio;
+use rocket::tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use rocket::tokio::net::{TcpListener, TcpStream};
+
+use super::spf::{self, SpfResult};
+use crate::models::comment::CommentForm;
+use crate::security::permissions;
+use crate::store::Store;
+
+/// Local part used for plus-addressed reply tokens (`reply+<token>@domain`).
+pub const REPLY_LOCAL_PART: &str = "reply";
+
+/// Maximum accepted message size for inbound replies (256 KB).
+const MAX_MESSAGE_BYTES: usize = 256 * 1024;
+
+/// Maximum command or text line length, CRLF included (RFC 5321 §4.5.3.1).
+const MAX_LINE_BYTES: u64 = 1000;
+
+/// How long a client may stay silent before the session is dropped.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+const LINE_TOO_LONG: &str = "line too long";
+
+/// Maximum stored reply body length (characters).
+const MAX_REPLY_CHARS: usize = 5000;
+
+/// Maximum accepted RCPT TO recipients per message; each one posts a comment.
+const MAX_RECIPIENTS: usize = 10;
+
+/// A reply token maps an outgoing notification back to the comment thread it belongs to.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ReplyToken {
+    pub token: String,
+    pub post_id: i64,
+    pub content_type: String,
+    pub comment_id: i64,
+    pub email: String,
+    pub expires_at: String,
+}
+
+/// Minimal view of a parsed inbound message.
+#[derive(Debug, Clone, Default)]
+pub struct InboundMessage {
+    pub from_name: String,
+    pub from_email: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Generate a random reply token (32 hex chars).
+pub fn generate_token() -> String {
+    use rand::Rng;
+    let bytes: [u8; 16] = rand::thread_rng().gen();
+    hex::encode(bytes)
+}
+
+/// Build the plus-addressed reply address for a token.
+pub fn reply_address(token: &str, domain: &str) -> String {
+    format!("{}+{}@{}", REPLY_LOCAL_PART, token, domain)
+}
+
+/// Extract the reply token from a recipient like `reply+abc123@example.com`
+/// (angle brackets and case in the local part are tolerated).
+pub fn extract_token(addr: &str) -> Option<String> {
+    let addr = addr.trim().trim_start_matches('<').trim_end_matches('>');
+    let (local, _domain) = addr.rsplit_once('@')?;
+    let (prefix, token) = local.split_once('+')?;
+    if !prefix.eq_ignore_ascii_case(REPLY_LOCAL_PART) {
+        return None;
+    }
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(token.to_ascii_lowercase())
+}
+
+/// Split an address header value like `"Jane Doe" <jane@example.com>` into (name, email).
+pub fn parse_address(value: &str) -> (String, String) {
+    let value = value.trim();
+    if let (Some(start), Some(end)) = (value.rfind('<'), value.rfind('>')) {
+        if start < end {
+            let name = value[..start].trim().trim_matches('"').trim().to_string();
+            let email = value[start + 1..end].trim().to_lowercase();
+            return (name, email);
+        }
+    }
+    (String::new(), value.trim_matches('"').to_lowercase())
+}
+
+/// Strip quoted history, reply headers and signatures, keeping only the new text.
+pub fn strip_quoted(body: &str) -> String {
+    let mut kept: Vec<&str> = Vec::new();
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('>') {
+            continue;
+        }
+        if trimmed == "--"
+            || line == "-- "
+            || trimmed.starts_with("-----Original Message-----")
+            || trimmed.starts_with("________________________________")
+            || (trimmed.starts_with("On ") && trimmed.ends_with("wrote:"))
+        {
+            break;
+        }
+        kept.push(line.trim_end());
+    }
+    kept.join("\n").trim().to_string()
+}
+
+/// Parse a raw RFC 5322 message, returning the sender and the first text/plain body.
+pub fn parse_message(raw: &str) -> InboundMessage {
+    let (headers, body) = split_headers(raw);
+    let (from_name, from_email) = headers
+        .get("from")
+        .map(|v| parse_address(v))
+        .unwrap_or_default();
+    let subject = headers.get("subject").cloned().unwrap_or_default();
+    let content_type = headers.get("content-type").cloned().unwrap_or_default();
+    let encoding = headers
+        .get("content-transfer-encoding")
+        .cloned()
+        .unwrap_or_default();
+
+    let text = if content_type.to_lowercase().starts_with("multipart/") {
+        header_param(&content_type, "boundary")
+            .and_then(|b| first_text_part(body, &b))
+            .unwrap_or_default()
+    } else {
+        decode_body(body, &encoding)
+    };
+
+    InboundMessage {
+        from_name,
+        from_email,
+        subject,
+        body: text,
+    }
+}
+
+/// Split a message into lower-cased, unfolded headers and the body.
+fn split_headers(raw: &str) -> (HashMap<String, String>, &str) {
+    let raw = raw.trim_start_matches(['\r', '\n']);
+    let (head, body) = match raw.find("\r\n\r\n") {
+        Some(i) => (&raw[..i], &raw[i + 4..]),
+        None => match raw.find("\n\n") {
+            Some(i) => (&raw[..i], &raw[i + 2..]),
+            None => (raw, ""),
+        },
+    };
+
+    let mut headers: HashMap<String, String> = HashMap::new();
+    let mut current: Option<String> = None;
+    for line in head.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(ref key) = current {
+                if let Some(v) = headers.get_mut(key) {
+                    v.push(' ');
+                    v.push_str(line.trim());
+                }
+            }
+            continue;
+        }
+        if let Some((k, v)) = line.split_once(':') {
+            let key = k.trim().to_lowercase();
+            headers
+                .entry(key.clone())
+                .or_insert_with(|| v.trim().to_string());
+            current = Some(key);
+        }
+    }
+    (headers, body)
+}
+
+/// Read a `key=value` parameter from a structured header (e.g. boundary, charset).
+fn header_param(value: &str, name: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|p| {
+        let (k, v) = p.split_once('=')?;
+        if k.trim().eq_ignore_ascii_case(name) {
+            Some(v.trim().trim_matches('"').to_string())
+        } else {
+            None
+        }
+    })
+}
+
+/// Find the first text/plain part of a multipart body (recursing into nested multiparts).
+fn first_text_part(body: &str, boundary: &str) -> Option<String> {
+    let delimiter = format!("--{}", boundary);
+    for part in body.split(&delimiter).skip(1) {
+        if part.starts_with("--") {
+            break;
+        }
+        let (headers, part_body) = split_headers(part);
+        let ct = headers
+            .get("content-type")
+            .cloned()
+            .unwrap_or_else(|| "text/plain".to_string());
+        let ct_lower = ct.to_lowercase();
+        if ct_lower.starts_with("multipart/") {
+            if let Some(nested) =
+                header_param(&ct, "boundary").and_then(|b| first_text_part(part_body, &b))
+            {
+                return Some(nested);
+            }
+        } else if ct_lower.starts_with("text/plain") {
+            let enc = headers
+                .get("content-transfer-encoding")
+                .cloned()
+                .unwrap_or_default();
+            return Some(decode_body(part_body, &enc));
+        }
+    }
+    None
+}
+
+/// Decode a body according to its Content-Transfer-Encoding.
+fn decode_body(body: &str, encoding: &str) -> String {
+    match encoding.trim().to_lowercase().as_str() {
+        "base64" => {
+            let compact: String = body.chars().filter(|c| !c.is_whitespace()).collect();
+            base64::engine::general_purpose::STANDARD
+                .decode(compact)
+                .map(|b| String::from_utf8_lossy(&b).to_string())
+                .unwrap_or_default()
+        }
+        "quoted-printable" => decode_quoted_printable(body),
+        _ => body.replace("\r\n", "\n"),
+    }
+}
+
+fn decode_quoted_printable(body: &str) -> String {
+    let mut out: Vec<u8> = Vec::with_capacity(body.len());
+    let bytes = body.replace("\r\n", "\n").into_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'=' {
+            // Soft line break
+            if bytes.get(i + 1) == Some(&b'\n') {
+                i += 2;
+                continue;
+            }
+            if let (Some(h), Some(l)) = (bytes.get(i + 1), bytes.get(i + 2)) {
+                if let Ok(v) = u8::from_str_radix(&format!("{}{}", *h as char, *l as char), 16) {
+                    out.push(v);
+                    i += 3;
+                    continue;
+                }
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn domain_of(addr: &str) -> String {
+    addr.trim()
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .rsplit_once('@')
+        .map(|(_, d)| d.trim_end_matches('.').to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Relaxed DMARC-style alignment: the domains match or one is a subdomain of the other.
+pub fn aligned(a: &str, b: &str) -> bool {
+    !a.is_empty()
+        && !b.is_empty()
+        && (a == b || a.ends_with(&format!(".{}", b)) || b.ends_with(&format!(".{}", a)))
+}
+
+/// Whether the topmost Authentication-Results header (added by the relay that
+/// handed us the message) records an SPF, DKIM or DMARC pass for a domain
+/// aligned with `from_domain`.
+pub fn relay_authenticated(raw: &str, from_domain: &str) -> bool {
+    let (headers, _) = split_headers(raw);
+    let results = match headers.get("authentication-results") {
+        Some(v) => v.to_lowercase(),
+        None => return false,
+    };
+    results.split(';').skip(1).any(|clause| {
+        let mut parts = clause.split_whitespace();
+        let passed = matches!(
+            parts.next(),
+            Some("spf=pass") | Some("dkim=pass") | Some("dmarc=pass")
+        );
+        passed
+            && parts.any(|prop| {
+                let value = prop.split_once('=').map(|(_, v)| v).unwrap_or("");
+                let domain = value.rsplit('@').next().unwrap_or(value);
+                aligned(domain, from_domain)
+            })
+    })
+}
+
+/// Check that the From: address can be trusted as the comment author.
+///
+/// Mail delivered straight to the listener must come from an address that
+/// passes SPF for the envelope sender, and the envelope sender must align with
+/// the From: header. Mail handed over by a relay on this host is trusted on
+/// the strength of the Authentication-Results header that relay added.
+pub fn verify_sender(peer: IpAddr, mail_from: &str, raw: &str) -> Result<(), String> {
+    let from_domain = domain_of(&parse_message(raw).from_email);
+    if peer.is_loopback() {
+        return if relay_authenticated(raw, &from_domain) {
+            Ok(())
+        } else {
+            Err("Relayed message carries no passing Authentication-Results".to_string())
+        };
+    }
+    let envelope_domain = domain_of(mail_from);
+    if !aligned(&envelope_domain, &from_domain) {
+        return Err(format!(
+            "Envelope sender domain '{}' does not match From: domain '{}'",
+            envelope_domain, from_domain
+        ));
+    }
+    match spf::check(peer, &envelope_domain) {
+        SpfResult::Pass => Ok(()),
+        result => Err(format!(
+            "SPF {:?} for {} from {}",
+            result, envelope_domain, peer
+        )),
+    }
+}
+
+/// Turn an inbound message addressed to a reply token into a reply comment.
+/// Returns the new comment id.
+pub fn handle_reply(store: &dyn Store, token: &str, raw: &str) -> Result<i64, String> {
+    let reply = store
+        .mta_reply_token_find(token)
+        .ok_or("Unknown or expired reply token")?;
+    let msg = parse_message(raw);
+
+    // The token is bound to the address it was sent to; only that sender may use it.
+    if !msg.from_email.eq_ignore_ascii_case(&reply.email) {
+        return Err(format!(
+            "Sender {} does not match reply token recipient",
+            msg.from_email
+        ));
+    }
+
+    let text: String = strip_quoted(&msg.body)
+        .chars()
+        .take(MAX_REPLY_CHARS)
+        .collect();
+    if text.is_empty() {
+        return Err("Reply body is empty".to_string());
+    }
+
+    // Spend the token before posting so a replayed message cannot post twice
+    if !store.mta_reply_token_consume(token)? {
+        return Err("Reply token already used".to_string());
+    }
+
+    let user = store.user_get_by_email(&msg.from_email);
+    let author_name = match user {
+        Some(ref u) if !u.display_name.is_empty() => u.display_name.clone(),
+        _ if !msg.from_name.is_empty() => msg.from_name.clone(),
+        _ => msg
+            .from_email
+            .split('@')
+            .next()
+            .unwrap_or("Anonymous")
+            .to_string(),
+    };
+
+    let form = CommentForm {
+        post_id: reply.post_id,
+        content_type: Some(reply.content_type.clone()),
+        author_name,
+        author_email: Some(msg.from_email.clone()),
+        body: text,
+        honeypot: None,
+        parent_id: Some(reply.comment_id),
+    };
+    let id = store.comment_create(&form)?;
+
+    // Replies from staff, or on sites with auto-approve, go live immediately
+    let is_staff = user
+        .as_ref()
+        .map(|u| u.is_active() && permissions::user_can(store, u, permissions::DASHBOARD_VIEW))
+        .unwrap_or(false);
+    if is_staff || store.setting_get_or("comments_moderation", "manual") == "auto-approve" {
+        let _ = store.comment_update_status(id, "approved");
+    }
+
+    log::info!(
+        "[mta] Inbound reply from {} posted as comment {} on {} {}",
+        msg.from_email,
+        id,
+        reply.content_type,
+        reply.post_id
+    );
+    Ok(id)
+}
+
+/// Queue comment notifications with a plus-addressed Reply-To so recipients can reply by email.
+/// The admin is notified when `notify_admin` is set (new submissions); the parent comment's
+/// author is notified once the reply is approved.
+pub fn notify_comment(store: &dyn Store, comment_id: i64, notify_admin: bool) {
+    if !store.setting_get_bool("mta_inbound_enabled") {
+        return;
+    }
+    let comment = match store.comment_find_by_id(comment_id) {
+        Some(c) => c,
+        None => return,
+    };
+
+    let mut recipients: Vec<String> = Vec::new();
+    let admin_email = store.setting_get_or("admin_email", "");
+    if notify_admin && !admin_email.is_empty() {
+        recipients.push(admin_email.to_lowercase());
+    }
+    if comment.status == "approved" {
+        if let Some(parent) = comment.parent_id.and_then(|p| store.comment_find_by_id(p)) {
+            if let Some(email) = parent.author_email.filter(|e| !e.is_empty()) {
+                recipients.push(email.to_lowercase());
+            }
+        }
+    }
+    let own = comment
+        .author_email
+        .clone()
+        .unwrap_or_default()
+        .to_lowercase();
+    recipients.retain(|r| *r != own);
+    recipients.dedup();
+    if recipients.is_empty() {
+        return;
+    }
+
+    let settings = store.setting_all();
+    let from = settings
+        .get("mta_from_address")
+        .cloned()
+        .unwrap_or_default();
+    let domain = match from.rsplit_once('@') {
+        Some((_, d)) if !d.is_empty() => d.to_string(),
+        _ => return,
+    };
+    let site_name = settings
+        .get("site_name")
+        .cloned()
+        .unwrap_or_else(|| "Velocty".to_string());
+    let title = match comment.content_type.as_str() {
+        "portfolio" => store
+            .portfolio_find_by_id(comment.post_id)
+            .map(|p| p.title)
+            .unwrap_or_default(),
+        _ => store
+            .post_find_by_id(comment.post_id)
+            .map(|p| p.title)
+            .unwrap_or_default(),
+    };
+    let days = settings
+        .get("mta_reply_token_days")
+        .and_then(|v| v.parse::<i64>().ok())
+        .unwrap_or(30);
+
+    let subject = format!("New comment on \"{}\" — {}", title, site_name);
+    let body = format!(
+        "{} wrote:\n\n{}\n\n—\nReply to this email to respond. Your reply will be posted as a comment.\n",
+        comment.author_name, comment.body
+    );
+
+    for to in recipients {
+        let token = generate_token();
+        if let Err(e) = store.mta_reply_token_create(
+            &token,
+            comment.post_id,
+            &comment.content_type,
+            comment.id,
+            &to,
+            days,
+        ) {
+            log::error!("[mta] Failed to store reply token: {}", e);
+            continue;
+        }
+        let reply_to = reply_address(&token, &domain);
+        if let Err(e) = store.mta_queue_push(&to, &from, &subject, &body, &reply_to) {
+            log::warn!(
+                "[mta] Failed to queue comment notification to {}: {}",
+                to,
+                e
+            );
+        }
+    }
+}
+
+/// Start the inbound SMTP listener on `bind:port`. Accepts mail only for valid
+/// reply tokens, so it never acts as an open relay.
+pub async fn run_listener(store: Arc<dyn Store>, bind: String, port: u16) {
+    let listener = match TcpListener::bind((bind.as_str(), port)).await {
+        Ok(l) => l,
+        Err(e) => {
+            log::error!(
+                "[mta] Inbound SMTP listener failed to bind {}:{}: {}",
+                bind,
+                port,
+                e
+            );
+            return;
+        }
+    };
+    log::info!("[mta] Inbound SMTP listener on {}:{}", bind, port);
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                let s = Arc::clone(&store);
+                tokio::spawn(async move {
+                    if let Err(e) = handle_session(s, stream, peer).await {
+                        log::warn!("[mta] Inbound session from {} ended: {}", peer, e);
+                    }
+                });
+            }
+            Err(e) => log::warn!("[mta] Inbound accept failed: {}", e),
+        }
+    }
+}
+
+/// Drive a single SMTP session (HELO/EHLO, MAIL, RCPT, DATA, RSET, NOOP, QUIT).
+async fn handle_session(
+    store: Arc<dyn Store>,
+    stream: TcpStream,
+    peer: SocketAddr,
+) -> Result<(), String> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let hostname = blocking(&store, |s| s.setting_get_or("mta_from_address", ""))
+        .await?
+        .rsplit_once('@')
+        .map(|(_, d)| d.to_string())
+        .unwrap_or_else(|| "localhost".to_string());
+
+    let mut tokens: Vec<String> = Vec::new();
+    let mut mail_from = String::new();
+    let mut line = String::new();
+
+    reply(&mut writer, &format!("220 {} ESMTP Velocty", hostname)).await?;
+
+    loop {
+        let n = match read_line_capped(&mut reader, &mut line).await {
+            Err(e) if e == LINE_TOO_LONG => {
+                reply(&mut writer, "500 5.5.2 Line too long").await?;
+                return Err(e);
+            }
+            other => other?,
+        };
+        if n == 0 {
+            return Ok(());
+        }
+        let cmd = line.trim_end().to_string();
+        let upper = cmd.to_uppercase();
+
+        if upper.starts_with("HELO") || upper.starts_with("EHLO") {
+            reply(&mut writer, &format!("250 {}", hostname)).await?;
+        } else if upper.starts_with("MAIL FROM:") {
+            tokens.clear();
+            mail_from = cmd[10..]
+                .split_whitespace()
+                .next()
+                .unwrap_or("")
+                .to_string();
+            reply(&mut writer, "250 OK").await?;
+        } else if upper.starts_with("RCPT TO:") {
+            if tokens.len() >= MAX_RECIPIENTS {
+                reply(&mut writer, "452 4.5.3 Too many recipients").await?;
+                continue;
+            }
+            let token = match extract_token(cmd[8..].trim()) {
+                Some(t) => blocking(&store, move |s| s.mta_reply_token_find(&t))
+                    .await?
+                    .map(|r| r.token),
+                None => None,
+            };
+            match token {
+                Some(t) => {
+                    if !tokens.contains(&t) {
+                        tokens.push(t);
+                    }
+                    reply(&mut writer, "250 OK").await?;
+                }
+                None => reply(&mut writer, "550 No such recipient").await?,
+            }
+        } else if upper == "DATA" {
+            if tokens.is_empty() {
+                reply(&mut writer, "503 No valid recipients").await?;
+                continue;
+            }
+            reply(&mut writer, "354 End data with <CR><LF>.<CR><LF>").await?;
+            let raw = match read_data(&mut reader).await {
+                Ok(Some(raw)) => raw,
+                Ok(None) => {
+                    // Stop reading rather than drain an oversized message
+                    reply(&mut writer, "552 5.3.4 Message too large").await?;
+                    return Err("message too large".to_string());
+                }
+                Err(e) if e == LINE_TOO_LONG => {
+                    reply(&mut writer, "500 5.5.2 Line too long").await?;
+                    return Err(e);
+                }
+                Err(e) => return Err(e),
+            };
+            let (envelope, message) = (mail_from.clone(), raw.clone());
+            let verified =
+                tokio::task::spawn_blocking(move || verify_sender(peer.ip(), &envelope, &message))
+                    .await
+                    .map_err(|e| e.to_string())?;
+            if let Err(e) = verified {
+                log::warn!("[mta] Inbound reply from {} not verified: {}", peer, e);
+                reply(&mut writer, "550 5.7.1 Sender could not be verified").await?;
+                tokens.clear();
+                continue;
+            }
+            let batch: Vec<String> = tokens.drain(..).collect();
+            let accepted = blocking(&store, move |s| {
+                let mut accepted = false;
+                for t in batch {
+                    match handle_reply(s, &t, &raw) {
+                        Ok(_) => accepted = true,
+                        Err(e) => log::warn!("[mta] Inbound reply rejected: {}", e),
+                    }
+                }
+                accepted
+            })
+            .await?;
+            if accepted {
+                reply(&mut writer, "250 OK").await?;
+            } else {
+                reply(&mut writer, "550 Reply rejected").await?;
+            }
+        } else if upper == "RSET" {
+            tokens.clear();
+            reply(&mut writer, "250 OK").await?;
+        } else if upper == "NOOP" {
+            reply(&mut writer, "250 OK").await?;
+        } else if upper == "QUIT" {
+            reply(&mut writer, "221 Bye").await?;
+            return Ok(());
+        } else {
+            reply(&mut writer, "502 Command not implemented").await?;
+        }
+    }
+}
+
+/// Run a synchronous store call on the blocking pool so database work never
+/// stalls the runtime's worker threads.
+async fn blocking<T, F>(store: &Arc<dyn Store>, f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&dyn Store) -> T + Send + 'static,
+{
+    let s = Arc::clone(store);
+    tokio::task::spawn_blocking(move || f(&*s))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Read one line of at most `MAX_LINE_BYTES` into `line`, giving up after
+/// `IDLE_TIMEOUT` of silence. Returns 0 at end of stream.
+async fn read_line_capped<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    line: &mut String,
+) -> Result<usize, String> {
+    line.clear();
+    let n = tokio::time::timeout(
+        IDLE_TIMEOUT,
+        (&mut *reader).take(MAX_LINE_BYTES).read_line(line),
+    )
+    .await
+    .map_err(|_| "timeout".to_string())?
+    .map_err(|e| e.to_string())?;
+    if n as u64 == MAX_LINE_BYTES && !line.ends_with('\n') {
+        return Err(LINE_TOO_LONG.to_string());
+    }
+    Ok(n)
+}
+
+/// Read DATA lines until the terminating ".", undoing dot-stuffing.
+/// Returns `None` as soon as the message grows past `MAX_MESSAGE_BYTES`.
+async fn read_data<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<String>, String> {
+    let mut raw = String::new();
+    let mut line = String::new();
+    loop {
+        let n = read_line_capped(reader, &mut line).await?;
+        if n == 0 {
+            return Err("connection closed during DATA".to_string());
+        }
+        let trimmed = line.trim_end_matches(['\r', '\n']);
+        if trimmed == "." {
+            return Ok(Some(raw));
+        }
+        raw.push_str(trimmed.strip_prefix('.').unwrap_or(trimmed));
+        raw.push_str("\r\n");
+        if raw.len() > MAX_MESSAGE_BYTES {
+            return Ok(None);
+        }
+    }
+}
+
+async fn reply<W: AsyncWriteExt + Unpin>(writer: &mut W, msg: &str) -> Result<(), String> {
+    writer
+        .write_all(format!("{}\r\n", msg).as_bytes())
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -1,7 +1,9 @@
 pub mod deliver;
 pub mod dkim;
 pub mod dns;
+pub mod inbound;
 pub mod queue;
+pub mod spf;
 
 use std::collections::HashMap;
 
@@ -97,8 +99,14 @@ pub fn process_queue(store: &dyn Store) {
         // Mark as sending
         let _ = store.mta_queue_update_status(msg.id, "sending", None, None);
 
+        // A per-message Reply-To (e.g. comment reply addresses) overrides the site default
+        let mut msg_settings = settings.clone();
+        if !msg.reply_to.is_empty() {
+            msg_settings.insert("email_reply_to".to_string(), msg.reply_to.clone());
+        }
+
         match deliver::send_direct(
-            &settings,
+            &msg_settings,
             &msg.from_addr,
             &msg.to_addr,
             &msg.subject,
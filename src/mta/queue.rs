@@ -6,14 +6,27 @@ pub struct QueuedEmail {
     pub from_addr: String,
     pub subject: String,
     pub body_text: String,
+    /// Reply-To for this message; empty falls back to the site-wide setting.
+    pub reply_to: String,
     pub attempts: i64,
     pub max_attempts: i64,
     pub next_retry_at: String,
     pub status: String, // "pending", "sending", "sent", "failed"
     pub error: String,
+    pub error_log: String, // one "timestamp [attempt n] error" line per failed attempt
     pub created_at: String,
 }
 
+impl QueuedEmail {
+    /// Per-attempt error history, oldest first.
+    pub fn error_history(&self) -> Vec<&str> {
+        self.error_log
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .collect()
+    }
+}
+
 /// Retry schedule: delays in seconds after each failed attempt.
 /// Attempt 1: immediate, 2: 60s, 3: 300s, 4: 1800s, 5: 7200s
 const RETRY_DELAYS: [u64; 5] = [0, 60, 300, 1800, 7200];
@@ -39,3 +52,36 @@ pub fn next_retry_timestamp(attempts: i64) -> Option<String> {
 
 /// Maximum emails per hour (rate limit).
 pub const DEFAULT_MAX_EMAILS_PER_HOUR: u64 = 30;
+
+/// Format one error-history line for a failed attempt.
+pub fn error_log_line(attempt: i64, error: &str) -> String {
+    format!(
+        "{} [attempt {}] {}\n",
+        chrono::Utc::now().format("%Y-%m-%d %H:%M:%S"),
+        attempt,
+        error.replace('\n', " ")
+    )
+}
+
+/// Fill an hourly series of (hour, count) pairs for the last `hours` hours (oldest first),
+/// inserting zero buckets for hours with no sends. Hour keys are "YYYY-MM-DD HH:00".
+pub fn fill_hourly_buckets(
+    counts: &[(String, u64)],
+    hours: i64,
+    now: chrono::NaiveDateTime,
+) -> Vec<(String, u64)> {
+    (0..hours)
+        .rev()
+        .map(|i| {
+            let key = (now - chrono::Duration::hours(i))
+                .format("%Y-%m-%d %H:00")
+                .to_string();
+            let count = counts
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, c)| *c)
+                .unwrap_or(0);
+            (key, count)
+        })
+        .collect()
+}
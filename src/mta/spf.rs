@@ -0,0 +1,217 @@
+use std::net::IpAddr;
+
+use trust_dns_resolver::config::{ResolverConfig, ResolverOpts};
+use trust_dns_resolver::Resolver;
+
+/// RFC 7208 caps the DNS-querying mechanisms evaluated for one check.
+const MAX_LOOKUPS: usize = 10;
+
+/// Outcome of an SPF check, reduced to what the inbound listener acts on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpfResult {
+    Pass,
+    Fail,
+    SoftFail,
+    Neutral,
+    /// The domain publishes no SPF record.
+    None,
+    /// The record was malformed or needed too many lookups.
+    Error,
+}
+
+/// DNS queries needed to evaluate SPF, so tests can answer them without a resolver.
+pub trait SpfDns {
+    fn txt(&self, name: &str) -> Vec<String>;
+    fn ips(&self, name: &str) -> Vec<IpAddr>;
+    fn mx(&self, name: &str) -> Vec<String>;
+}
+
+impl SpfDns for Resolver {
+    fn txt(&self, name: &str) -> Vec<String> {
+        self.txt_lookup(name)
+            .map(|r| {
+                r.iter()
+                    .map(|txt| {
+                        txt.iter()
+                            .map(|d| String::from_utf8_lossy(d).to_string())
+                            .collect::<String>()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn ips(&self, name: &str) -> Vec<IpAddr> {
+        self.lookup_ip(name)
+            .map(|r| r.iter().collect())
+            .unwrap_or_default()
+    }
+
+    fn mx(&self, name: &str) -> Vec<String> {
+        self.mx_lookup(name)
+            .map(|r| {
+                r.iter()
+                    .map(|mx| mx.exchange().to_ascii().trim_end_matches('.').to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Check whether `ip` may send mail for `domain` using the system resolver.
+pub fn check(ip: IpAddr, domain: &str) -> SpfResult {
+    match Resolver::new(ResolverConfig::default(), ResolverOpts::default()) {
+        Ok(resolver) => check_host(&resolver, ip, domain),
+        Err(_) => SpfResult::Error,
+    }
+}
+
+/// Evaluate the SPF record of `domain` for `ip` (RFC 7208 `check_host`).
+/// Supports `all`, `ip4`, `ip6`, `a`, `mx`, `include` and `redirect`; macros
+/// and `exists`/`ptr` are treated as non-matching.
+pub fn check_host(dns: &dyn SpfDns, ip: IpAddr, domain: &str) -> SpfResult {
+    let mut lookups = 0;
+    evaluate(dns, ip, domain, &mut lookups)
+}
+
+fn evaluate(dns: &dyn SpfDns, ip: IpAddr, domain: &str, lookups: &mut usize) -> SpfResult {
+    let records: Vec<String> = dns
+        .txt(domain)
+        .into_iter()
+        .filter(|r| r.eq_ignore_ascii_case("v=spf1") || r.to_lowercase().starts_with("v=spf1 "))
+        .collect();
+    let record = match records.as_slice() {
+        [] => return SpfResult::None,
+        [r] => r.clone(),
+        _ => return SpfResult::Error,
+    };
+
+    let mut redirect: Option<String> = None;
+    for term in record.split_whitespace().skip(1) {
+        let term = term.to_lowercase();
+        if let Some(target) = term.strip_prefix("redirect=") {
+            redirect = Some(target.to_string());
+            continue;
+        }
+        if term.contains('=') {
+            continue; // unknown modifier (e.g. exp=)
+        }
+        let (qualifier, mechanism) = match term.chars().next() {
+            Some(c @ ('+' | '-' | '~' | '?')) => (c, &term[1..]),
+            _ => ('+', term.as_str()),
+        };
+        let (name, arg) = match mechanism.find([':', '/']) {
+            Some(i) => (&mechanism[..i], &mechanism[i..]),
+            None => (mechanism, ""),
+        };
+        let matched = match name {
+            "all" => true,
+            "ip4" | "ip6" => in_network(ip, arg.trim_start_matches(':')),
+            "a" | "mx" => {
+                if !count_lookup(lookups) {
+                    return SpfResult::Error;
+                }
+                let spec = match arg.strip_prefix(':') {
+                    Some(s) => s.to_string(),
+                    None => format!("{}{}", domain, arg),
+                };
+                let (host, prefix) = split_prefix(&spec, ip);
+                let hosts = if name == "mx" {
+                    dns.mx(host)
+                } else {
+                    vec![host.to_string()]
+                };
+                hosts
+                    .iter()
+                    .flat_map(|h| dns.ips(h))
+                    .any(|addr| same_network(ip, addr, prefix))
+            }
+            "include" => {
+                if !count_lookup(lookups) {
+                    return SpfResult::Error;
+                }
+                match evaluate(dns, ip, arg.trim_start_matches(':'), lookups) {
+                    SpfResult::Pass => true,
+                    SpfResult::Error | SpfResult::None => return SpfResult::Error,
+                    _ => false,
+                }
+            }
+            _ => false,
+        };
+        if matched {
+            return match qualifier {
+                '-' => SpfResult::Fail,
+                '~' => SpfResult::SoftFail,
+                '?' => SpfResult::Neutral,
+                _ => SpfResult::Pass,
+            };
+        }
+    }
+
+    match redirect {
+        Some(target) => {
+            if !count_lookup(lookups) {
+                return SpfResult::Error;
+            }
+            match evaluate(dns, ip, &target, lookups) {
+                SpfResult::None => SpfResult::Error,
+                other => other,
+            }
+        }
+        None => SpfResult::Neutral,
+    }
+}
+
+fn count_lookup(lookups: &mut usize) -> bool {
+    *lookups += 1;
+    *lookups <= MAX_LOOKUPS
+}
+
+/// Split `host[/v4-prefix][//v6-prefix]`, keeping the prefix for `ip`'s family.
+fn split_prefix(spec: &str, ip: IpAddr) -> (&str, Option<u8>) {
+    let (rest, v6) = match spec.split_once("//") {
+        Some((r, p)) => (r, p.parse().ok()),
+        None => (spec, None),
+    };
+    let (host, v4) = match rest.split_once('/') {
+        Some((h, p)) => (h, p.parse().ok()),
+        None => (rest, None),
+    };
+    (host, if ip.is_ipv4() { v4 } else { v6 })
+}
+
+/// Whether `ip` falls in the `addr[/prefix]` network given by an ip4:/ip6: term.
+fn in_network(ip: IpAddr, value: &str) -> bool {
+    let (addr, prefix) = match value.split_once('/') {
+        Some((a, p)) => (a, p.parse::<u8>().ok()),
+        None => (value, None),
+    };
+    match addr.parse::<IpAddr>() {
+        Ok(net) => same_network(ip, net, prefix),
+        Err(_) => false,
+    }
+}
+
+fn same_network(ip: IpAddr, net: IpAddr, prefix: Option<u8>) -> bool {
+    match (ip, net) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => {
+            let bits = prefix.unwrap_or(32).min(32) as u32;
+            let mask = if bits == 0 {
+                0
+            } else {
+                u32::MAX << (32 - bits)
+            };
+            u32::from(a) & mask == u32::from(b) & mask
+        }
+        (IpAddr::V6(a), IpAddr::V6(b)) => {
+            let bits = prefix.unwrap_or(128).min(128) as u32;
+            let mask = if bits == 0 {
+                0
+            } else {
+                u128::MAX << (128 - bits)
+            };
+            u128::from(a) & mask == u128::from(b) & mask
+        }
+        _ => false,
+    }
+}
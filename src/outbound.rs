@@ -0,0 +1,129 @@
+use crate::security::signed_token::{self, OUTBOUND_LINK};
+use crate::store::Store;
+
+/// The `/out` link for `url`, signed so the endpoint only redirects to
+/// links this site rendered.
+pub fn tracked_href(secret: &str, url: &str) -> String {
+    let encoded: String = url::form_urlencoded::byte_serialize(url.as_bytes()).collect();
+    format!(
+        "/out?u={}&s={}",
+        encoded,
+        signed_token::sign(secret, OUTBOUND_LINK, url)
+    )
+}
+
+/// Whether `sig` is this site's signature for `url`.
+pub fn verify(store: &dyn Store, url: &str, sig: &str) -> bool {
+    match signed_token::mac(store, OUTBOUND_LINK, url) {
+        Ok(expected) => crate::security::constant_time_eq(sig.as_bytes(), expected.as_bytes()),
+        Err(_) => false,
+    }
+}
+
+/// Whether `href` is an http(s) link to another site than `own_host`.
+fn is_external(href: &str, own_host: Option<&str>) -> bool {
+    let parsed = match url::Url::parse(href) {
+        Ok(u) => u,
+        Err(_) => return false,
+    };
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return false;
+    }
+    match (parsed.host_str(), own_host) {
+        (Some(host), Some(own)) => !host.eq_ignore_ascii_case(own),
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+/// Byte range of the `href` attribute's value in an `<a ...>` tag. The
+/// attributes are walked one by one, so `data-href=`, `xlink:href=` or an
+/// `href=` inside another attribute's value don't count.
+fn href_range(tag: &str) -> Option<(usize, usize)> {
+    let bytes = tag.as_bytes();
+    let skip_space = |mut i: usize| {
+        while bytes.get(i).is_some_and(|b| b.is_ascii_whitespace()) {
+            i += 1;
+        }
+        i
+    };
+    // Past the tag name
+    let mut i = tag.find(|c: char| c.is_ascii_whitespace())?;
+    loop {
+        i = skip_space(i);
+        let name_start = i;
+        while bytes
+            .get(i)
+            .is_some_and(|b| !b.is_ascii_whitespace() && !matches!(b, b'=' | b'>' | b'/'))
+        {
+            i += 1;
+        }
+        let name = &tag[name_start..i];
+        i = skip_space(i);
+        match bytes.get(i) {
+            Some(b'=') => {}
+            None | Some(b'>') if name.is_empty() => return None,
+            // A stray `/`, or an attribute without a value
+            Some(_) if name.is_empty() => {
+                i += 1;
+                continue;
+            }
+            _ => continue,
+        }
+        i = skip_space(i + 1);
+        let (start, end, next) = match bytes.get(i) {
+            Some(&q) if q == b'"' || q == b'\'' => {
+                let len = tag[i + 1..].find(q as char)?;
+                (i + 1, i + 1 + len, i + 2 + len)
+            }
+            Some(_) => {
+                let len = tag[i..]
+                    .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                    .unwrap_or(tag.len() - i);
+                (i, i + len, i + len)
+            }
+            None => return None,
+        };
+        if name.eq_ignore_ascii_case("href") {
+            return Some((start, end));
+        }
+        i = next;
+    }
+}
+
+fn rewrite_tag(tag: &str, secret: &str, own_host: Option<&str>) -> String {
+    let Some((start, end)) = href_range(tag) else {
+        return tag.to_string();
+    };
+    let href = tag[start..end].replace("&amp;", "&");
+    if !is_external(&href, own_host) {
+        return tag.to_string();
+    }
+    format!(
+        "{}{}{}",
+        &tag[..start],
+        tracked_href(secret, &href).replace('&', "&amp;"),
+        &tag[end..]
+    )
+}
+
+/// Point every `<a href>` to another site through `/out` so clicks are
+/// counted. Links to `site_url`'s host, relative links and other schemes
+/// (mailto:, tel:) are left alone.
+pub fn rewrite_links(html: &str, secret: &str, site_url: &str) -> String {
+    let own = url::Url::parse(site_url).ok();
+    let own_host = own.as_ref().and_then(|u| u.host_str());
+    let mut result = String::with_capacity(html.len());
+    let mut remaining = html;
+
+    while let Some(pos) = remaining.find("<a ") {
+        result.push_str(&remaining[..pos]);
+        let after = &remaining[pos..];
+        let end = after.find('>').map(|e| e + 1).unwrap_or(after.len());
+        result.push_str(&rewrite_tag(&after[..end], secret, own_host));
+        remaining = &after[end..];
+    }
+
+    result.push_str(remaining);
+    result
+}
@@ -1,7 +1,19 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::uri::Origin;
+use rocket::http::{Header, Method};
+use rocket::request::Outcome;
+use rocket::{Data, Request, Response};
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::sync::{Mutex, OnceLock};
 use std::time::{Duration, Instant};
 
+use crate::models::api_key;
+use crate::models::settings::SettingsCache;
+use crate::security::auth::{self, ClientIp};
+#[cfg(feature = "multi-site")]
+use crate::site::RegistryPool;
+use crate::store::Store;
+
 /// In-memory rate limiter keyed by (bucket, ip_hash).
 /// Each bucket (e.g. "login", "comment") has its own max attempts and window.
 pub struct RateLimiter {
@@ -36,6 +48,91 @@ impl RateLimiter {
         }
     }
 
+    /// Like `check_and_record`, but honours the `rate_limit_backend` setting.
+    /// With "store" the attempts are kept in the database so budgets survive
+    /// restarts and are shared between processes; if the store fails the
+    /// in-memory counter is used instead.
+    pub fn check(&self, store: &dyn Store, key: &str, max_attempts: u64, window: Duration) -> bool {
+        if store.setting_get_or("rate_limit_backend", "memory") == "store" {
+            match store.rate_limit_hit(key, max_attempts, window.as_secs() as i64) {
+                Ok(allowed) => return allowed,
+                Err(e) => log::warn!("[rate-limit] Store backend failed, using memory: {}", e),
+            }
+        }
+        self.check_and_record(key, max_attempts, window)
+    }
+
+    /// `check` for endpoints outside any site (the account directory). The
+    /// registry has no settings, so its own `rate_limit_hits` table is always
+    /// used, with the in-memory counter as the fallback.
+    #[cfg(feature = "multi-site")]
+    pub fn check_registry(
+        &self,
+        registry: &RegistryPool,
+        key: &str,
+        max_attempts: u64,
+        window: Duration,
+    ) -> bool {
+        match registry
+            .0
+            .rate_limit_hit(key, max_attempts, window.as_secs() as i64)
+        {
+            Ok(allowed) => allowed,
+            Err(e) => {
+                log::warn!("[rate-limit] Registry backend failed, using memory: {}", e);
+                self.check_and_record(key, max_attempts, window)
+            }
+        }
+    }
+
+    /// Record a request against a budget of `max_requests` per `window` and
+    /// report what's left of it, for RateLimit-* response headers. A request
+    /// over budget isn't recorded.
+    pub fn budget(&self, key: &str, max_requests: u64, window: Duration) -> Budget {
+        let mut map = self.entries.lock().unwrap();
+        let now = Instant::now();
+        let cutoff = now - window;
+
+        let attempts = map.entry(key.to_string()).or_default();
+        attempts.retain(|t| *t > cutoff);
+
+        let allowed = (attempts.len() as u64) < max_requests;
+        if allowed {
+            attempts.push(now);
+        }
+        let oldest_age = attempts
+            .first()
+            .map(|oldest| now.duration_since(*oldest))
+            .unwrap_or_default();
+        Budget::counted(
+            allowed,
+            max_requests,
+            attempts.len() as u64,
+            oldest_age,
+            window,
+        )
+    }
+
+    /// Like `budget`, but honours the `rate_limit_backend` setting the way
+    /// `check` does: with "store" the budget is counted in the database, so
+    /// it survives restarts and is shared between processes.
+    pub fn shared_budget(
+        &self,
+        store: &dyn Store,
+        backend: &str,
+        key: &str,
+        max_requests: u64,
+        window: Duration,
+    ) -> Budget {
+        if backend == "store" {
+            match store.rate_limit_budget(key, max_requests, window.as_secs() as i64) {
+                Ok(budget) => return budget,
+                Err(e) => log::warn!("[rate-limit] Store backend failed, using memory: {}", e),
+            }
+        }
+        self.budget(key, max_requests, window)
+    }
+
     /// Check remaining attempts without recording a new one.
     pub fn remaining(&self, key: &str, max_attempts: u64, window: Duration) -> u64 {
         let mut map = self.entries.lock().unwrap();
@@ -58,3 +155,216 @@ impl RateLimiter {
         });
     }
 }
+
+/// Where a request stands against its budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+    pub allowed: bool,
+    pub limit: u64,
+    pub remaining: u64,
+    /// Seconds until the budget has room again
+    pub reset_secs: u64,
+}
+
+impl Budget {
+    /// A budget with `used` requests in the window, the oldest of them
+    /// `oldest_age` ago. It frees up as that request ages out.
+    pub fn counted(
+        allowed: bool,
+        limit: u64,
+        used: u64,
+        oldest_age: Duration,
+        window: Duration,
+    ) -> Budget {
+        let reset = if used == 0 {
+            window
+        } else {
+            window.saturating_sub(oldest_age)
+        };
+        Budget {
+            allowed,
+            limit,
+            remaining: limit.saturating_sub(used),
+            reset_secs: reset.as_secs() + u64::from(reset.subsec_nanos() > 0),
+        }
+    }
+}
+
+// ── API budgets ────────────────────────────────────────
+
+/// Window the `api_rate_limit_*` budgets are counted over
+pub const API_WINDOW: Duration = Duration::from_secs(60);
+
+/// How often a key's `last_used_at` is written while it's in use
+const KEY_TOUCH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Whether key `id`'s last use is due to be recorded, at most once per
+/// `KEY_TOUCH_INTERVAL`, so busy keys don't write on every request
+pub fn key_touch_due(id: i64) -> bool {
+    static TOUCHED: OnceLock<Mutex<HashMap<i64, Instant>>> = OnceLock::new();
+    let mut map = match TOUCHED.get_or_init(|| Mutex::new(HashMap::new())).lock() {
+        Ok(m) => m,
+        Err(_) => return true,
+    };
+    let now = Instant::now();
+    if map
+        .get(&id)
+        .is_some_and(|at| now.duration_since(*at) < KEY_TOUCH_INTERVAL)
+    {
+        return false;
+    }
+    map.retain(|_, at| now.duration_since(*at) < KEY_TOUCH_INTERVAL);
+    map.insert(id, now);
+    true
+}
+
+/// The API key sent with a request, from `Authorization: Bearer <key>` or
+/// `X-API-Key`. Bearer tokens that aren't API keys (deploy tokens) are
+/// left alone.
+pub fn api_key_from_headers(headers: &rocket::http::HeaderMap<'_>) -> Option<String> {
+    headers
+        .get_one("X-API-Key")
+        .map(|k| k.trim().to_string())
+        .or_else(|| {
+            headers
+                .get_one("Authorization")
+                .and_then(|h| h.strip_prefix("Bearer "))
+                .map(|k| k.trim().to_string())
+                .filter(|k| k.starts_with(api_key::KEY_PREFIX))
+        })
+        .filter(|k| !k.is_empty())
+}
+
+/// Requests per minute allowed for a key, or for anonymous callers with
+/// `None`. A key's own limit wins over its scope's; 0 means unlimited.
+pub fn api_budget_for(settings: &SettingsCache, key: Option<&api_key::ApiKey>) -> u64 {
+    if let Some(limit) = key.map(|k| k.rate_limit).filter(|l| *l > 0) {
+        return limit as u64;
+    }
+    let (setting, default) = match key.map(|k| k.scope.as_str()) {
+        Some("write") => ("api_rate_limit_write", "60"),
+        Some(_) => ("api_rate_limit_read", "300"),
+        None => ("api_rate_limit_anonymous", "60"),
+    };
+    settings.get_or(setting, default).parse().unwrap_or(0)
+}
+
+/// What `ApiRateLimit` decided about a request
+#[derive(Clone)]
+enum ApiVerdict {
+    /// Not an API request, or no budget applies
+    Unmetered,
+    Metered(Budget),
+    /// The request named a key that doesn't exist
+    UnknownKey,
+}
+
+/// Request budgets for `/api/*`, per API key or, without one, per client IP.
+///
+/// Every metered response carries `RateLimit-Limit`, `RateLimit-Remaining`
+/// and `RateLimit-Reset`. A request over budget never reaches its route: it
+/// is answered with a 429 and `Retry-After`; one with an unknown key gets
+/// a 401. Limits come from the `SettingsCache`, and budgets are counted
+/// where `rate_limit_backend` says.
+pub struct ApiRateLimit;
+
+#[rocket::async_trait]
+impl Fairing for ApiRateLimit {
+    fn info(&self) -> Info {
+        Info {
+            name: "API Rate Limit",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        let path = req.uri().path().as_str().to_string();
+        // Deploy endpoints authenticate and pace themselves; CORS preflights
+        // are sent by the browser, not the caller
+        if !path.starts_with("/api/")
+            || path.starts_with("/api/deploy/")
+            || req.method() == Method::Options
+        {
+            return;
+        }
+        let store = match req.rocket().state::<std::sync::Arc<dyn Store>>() {
+            Some(s) => s.clone(),
+            None => return,
+        };
+        let limiter = match req.rocket().state::<RateLimiter>() {
+            Some(l) => l,
+            None => return,
+        };
+        let settings = match req.rocket().state::<SettingsCache>() {
+            Some(s) => s,
+            None => return,
+        };
+        if settings.get_or("api_rate_limit_enabled", "true") != "true" {
+            return;
+        }
+
+        let (bucket, max) = match api_key_from_headers(req.headers()) {
+            Some(key) => match store.api_key_find_by_hash(&api_key::hash_key(&key)) {
+                Some(k) => {
+                    if key_touch_due(k.id) {
+                        store.api_key_touch(k.id);
+                    }
+                    (
+                        format!("api:key:{}", k.id),
+                        api_budget_for(settings, Some(&k)),
+                    )
+                }
+                None => {
+                    req.local_cache(|| ApiVerdict::UnknownKey);
+                    reroute(req, "/api/unauthorized");
+                    return;
+                }
+            },
+            None => {
+                let ip = match req.guard::<ClientIp>().await {
+                    Outcome::Success(ip) => ip.0,
+                    _ => return,
+                };
+                (
+                    format!("api:ip:{}", auth::hash_ip(&ip)),
+                    api_budget_for(settings, None),
+                )
+            }
+        };
+        if max == 0 {
+            return;
+        }
+
+        let backend = settings.get_or("rate_limit_backend", "memory");
+        let budget = limiter.shared_budget(&*store, &backend, &bucket, max, API_WINDOW);
+        req.local_cache(|| ApiVerdict::Metered(budget));
+        if !budget.allowed {
+            reroute(req, "/api/rate-limited");
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        match req.local_cache(|| ApiVerdict::Unmetered) {
+            ApiVerdict::Metered(b) => {
+                res.set_header(Header::new("RateLimit-Limit", b.limit.to_string()));
+                res.set_header(Header::new("RateLimit-Remaining", b.remaining.to_string()));
+                res.set_header(Header::new("RateLimit-Reset", b.reset_secs.to_string()));
+                if !b.allowed {
+                    res.set_header(Header::new("Retry-After", b.reset_secs.to_string()));
+                }
+            }
+            ApiVerdict::UnknownKey => {
+                res.set_header(Header::new("WWW-Authenticate", "Bearer"));
+            }
+            ApiVerdict::Unmetered => {}
+        }
+    }
+}
+
+/// Answer a request with one of the API's refusal routes instead of its own
+fn reroute(req: &mut Request<'_>, path: &'static str) {
+    if let Ok(origin) = Origin::parse(path) {
+        req.set_method(Method::Get);
+        req.set_uri(origin);
+    }
+}
@@ -1,3 +1,9 @@
+pub mod conditional;
+pub mod page_cache;
+pub mod widgets;
+
+use std::cell::{Cell, RefCell};
+
 use serde_json::Value;
 
 use crate::models::design::Design;
@@ -5,16 +11,147 @@ use crate::seo;
 use crate::store::Store;
 use crate::typography;
 
+thread_local! {
+    /// Design being previewed by the request rendering on this thread
+    static PREVIEW_DESIGN: Cell<Option<i64>> = const { Cell::new(None) };
+    /// CSP nonce of the request rendering on this thread
+    static CSP_NONCE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Clears the previewed design when dropped, even if rendering panics
+struct PreviewReset;
+
+impl Drop for PreviewReset {
+    fn drop(&mut self) {
+        PREVIEW_DESIGN.with(|p| p.set(None));
+    }
+}
+
+/// Run `f` with design `preview` standing in for the active design in every
+/// `render_page` call it makes. Public handlers are synchronous, so the
+/// override can't reach another request.
+pub fn with_design_preview<T>(preview: Option<i64>, f: impl FnOnce() -> T) -> T {
+    PREVIEW_DESIGN.with(|p| p.set(preview));
+    let _reset = PreviewReset;
+    f()
+}
+
+/// Clears the CSP nonce when dropped, even if rendering panics
+struct NonceReset;
+
+impl Drop for NonceReset {
+    fn drop(&mut self) {
+        CSP_NONCE.with(|n| *n.borrow_mut() = None);
+    }
+}
+
+/// Run `f` with `nonce` (from the `CspNonce` guard) added to the scripts
+/// the renderer emits. Like `with_design_preview`, it can't reach another
+/// request.
+pub fn with_csp_nonce<T>(nonce: Option<&str>, f: impl FnOnce() -> T) -> T {
+    CSP_NONCE.with(|n| *n.borrow_mut() = nonce.map(String::from));
+    let _reset = NonceReset;
+    f()
+}
+
+/// Add the current request's CSP nonce to every `<script>` tag in `html`
+/// that doesn't already carry one; `html` is returned as is outside
+/// `with_csp_nonce`.
+///
+/// Only for markup the site authors itself: built-in snippets, the design
+/// shell and the owner's custom scripts. Post bodies, comments and widget
+/// HTML never go through it, so a script smuggled into content stays
+/// blocked by the policy.
+pub fn nonce_scripts(html: &str) -> String {
+    let nonce = match CSP_NONCE.with(|n| n.borrow().clone()) {
+        Some(n) => n,
+        None => return html.to_string(),
+    };
+    let mut out = String::with_capacity(html.len() + 64);
+    let mut rest = html;
+    while let Some(pos) = rest.find("<script") {
+        let after = &rest[pos + 7..];
+        out.push_str(&rest[..pos + 7]);
+        let is_tag = after.starts_with(|c: char| c == '>' || c.is_ascii_whitespace());
+        let tag_end = after.find('>').unwrap_or(after.len());
+        if is_tag && !after[..tag_end].contains("nonce=") {
+            out.push_str(&format!(" nonce=\"{}\"", nonce));
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
 /// Renders a full page using the active design's shell (layout_html) from the DB.
 /// The shell contains {{placeholder}} tags that are replaced with generated content.
 pub fn render_page(store: &dyn Store, template_type: &str, context: &Value) -> String {
-    let design = store.design_active().expect("No active design found");
-    render_with_shell(&design, template_type, context)
+    render_page_with(store, None, template_type, context)
+}
+
+/// `render_page` with the active design already at hand, e.g. from the
+/// `SettingsCache` render context. A design preview still takes precedence.
+pub fn render_page_with(
+    store: &dyn Store,
+    active: Option<&Design>,
+    template_type: &str,
+    context: &Value,
+) -> String {
+    let preview = PREVIEW_DESIGN
+        .with(|p| p.get())
+        .and_then(|id| store.design_find_by_id(id));
+    let previewing = preview.is_some();
+    let active = preview
+        .or_else(|| active.cloned())
+        .or_else(|| store.design_active())
+        .expect("No active design found");
+    let resolved = crate::designs::inheritance::resolve(store, active);
+    let settings = context.get("settings").cloned().unwrap_or_default();
+    let areas = widgets::render_areas(store, resolved.design.id, &settings);
+    let error_page =
+        crate::designs::error_pages::custom(store, resolved.design.id, template_type, context);
+    let mut html = render_with_shell(
+        &resolved.design,
+        &resolved.base_slug,
+        template_type,
+        context,
+        &areas,
+        error_page,
+    );
+    if previewing {
+        insert_before_body_end(
+            &mut html,
+            &build_preview_banner(&resolved.design.name, &settings),
+        );
+    }
+    html
+}
+
+/// Bar shown while an admin previews a design that isn't active
+fn build_preview_banner(design_name: &str, settings: &Value) -> String {
+    let admin_slug = settings
+        .get("admin_slug")
+        .and_then(|v| v.as_str())
+        .unwrap_or("admin");
+    format!(
+        r#"<div class="design-preview-bar" style="position:fixed;left:0;right:0;bottom:0;z-index:9999;display:flex;gap:16px;justify-content:center;align-items:center;padding:10px 16px;background:#111;color:#fff;font:14px/1.4 system-ui,sans-serif">Previewing <strong>{}</strong><a href="/{}/designer/preview/exit" style="color:#fff;text-decoration:underline">Exit preview</a></div>"#,
+        html_escape(design_name),
+        html_escape(admin_slug)
+    )
 }
 
 /// Unified renderer: uses the design's layout_html as the page shell,
 /// replaces {{placeholder}} tags with generated content from settings and context.
-fn render_with_shell(design: &Design, template_type: &str, context: &Value) -> String {
+/// `base_slug` is the bundled design whose renderers draw the journal.
+/// `error_page` is the design's own 404/500 body, when it has one.
+fn render_with_shell(
+    design: &Design,
+    base_slug: &str,
+    template_type: &str,
+    context: &Value,
+    areas: &widgets::Areas,
+    error_page: Option<String>,
+) -> String {
     let settings = context.get("settings").cloned().unwrap_or_default();
     let css_vars = typography::build_css_variables(&settings);
     let font_links = typography::build_font_links(&settings);
@@ -27,15 +164,34 @@ fn render_with_shell(design: &Design, template_type: &str, context: &Value) -> S
             .to_string()
     };
 
+    // ── Per-content template (landing, wide, ...) ──
+    let content_template = crate::designs::templates::chosen(template_type, context, base_slug);
+
     // ── Body content (page-type specific) ──
     let body_html = match template_type {
+        "404" | "500" if error_page.is_some() => error_page.unwrap_or_default(),
+        _ if content_template == Some("landing") => {
+            crate::designs::templates::render_landing(template_type, context)
+        }
+        "blog_single" if content_template == Some("wide") => {
+            crate::designs::inkwell::journal::render_single(context)
+        }
+        "homepage_sections" => crate::designs::homepage::render(context),
         "homepage" | "portfolio_grid" => crate::designs::oneguy::portfolio::render_grid(context),
         "portfolio_single" => crate::designs::oneguy::portfolio::render_single(context),
-        "blog_list" => render_blog_list(context, &design.slug),
-        "blog_single" => render_blog_single(context, &design.slug),
+        "blog_list" => match context.get("author_profile") {
+            Some(author) => format!(
+                "{}{}",
+                render_author_header(author),
+                render_blog_list(context, base_slug)
+            ),
+            None => render_blog_list(context, base_slug),
+        },
+        "blog_single" => render_blog_single(context, base_slug),
         "archives" => render_archives(context),
         "search" => render_search_page(context),
         "404" => render_404(context),
+        "500" => crate::designs::error_pages::render_500(context),
         _ => render_404(context),
     };
 
@@ -336,6 +492,12 @@ fn render_with_shell(design: &Design, template_type: &str, context: &Value) -> S
                 cls.push_str("footer-always-visible");
             }
         }
+        if let Some(key) = content_template {
+            if !cls.is_empty() {
+                cls.push(' ');
+            }
+            cls.push_str(&crate::designs::templates::body_class(key));
+        }
         cls
     };
     let wrapper_classes = {
@@ -385,25 +547,45 @@ fn render_with_shell(design: &Design, template_type: &str, context: &Value) -> S
         lb_buy = lb_buy, lb_buy_pos = lb_buy_pos, commerce_cur = commerce_cur,
         hearts_pos = hearts_pos,
     );
+    let data_attrs = format!("{} {}", data_attrs, lightbox_data_attrs(&settings));
 
     let image_protection_js = if sg("portfolio_image_protection", "false") == "true" {
-        IMAGE_PROTECTION_JS
+        nonce_scripts(IMAGE_PROTECTION_JS)
     } else {
-        ""
+        String::new()
     };
 
     // ── SEO ──
-    let seo_meta = context
+    let mut seo_meta = context
         .get("seo")
         .and_then(|s| s.as_str())
         .unwrap_or("")
         .to_string();
 
+    // ── Breadcrumbs (shown per design, JSON-LD whenever structured data is on) ──
+    let breadcrumbs = build_breadcrumbs(context);
+    let breadcrumbs_html = if breadcrumbs.is_empty()
+        || !sg("design_breadcrumbs", "")
+            .split(',')
+            .any(|s| s.trim() == design.slug)
+    {
+        String::new()
+    } else {
+        build_breadcrumbs_html(&breadcrumbs)
+    };
+    if !breadcrumbs.is_empty() && sg("seo_structured_data", "true") == "true" {
+        seo_meta.push('\n');
+        seo_meta.push_str(&seo::jsonld::build_breadcrumb_jsonld(
+            &sg("site_url", "http://localhost:8000"),
+            &breadcrumbs,
+        ));
+    }
+
     // ── Replace placeholders in the shell ──
     let mut html = if is_topbar {
-        ONEGUY_TOPBAR_SHELL_HTML.to_string()
+        nonce_scripts(ONEGUY_TOPBAR_SHELL_HTML)
     } else {
-        design.layout_html.clone()
+        nonce_scripts(&design.layout_html)
     };
     html = html.replace("{{seo_meta}}", &seo_meta);
     html = html.replace("{{webmaster_meta}}", &seo::build_webmaster_meta(&settings));
@@ -411,7 +593,7 @@ fn render_with_shell(design: &Design, template_type: &str, context: &Value) -> S
     html = html.replace("{{font_links}}", &font_links);
     html = html.replace("{{css_vars}}", &css_vars);
     let full_base_css = format!(
-        "{}{}{}{}{}{}{}{}{}{}",
+        "{}{}{}{}{}{}{}{}{}{}{}{}{}{}{}",
         BASE_CSS,
         crate::designs::oneguy::journal::list_classic::css(),
         crate::designs::oneguy::journal::list_classic::list_css(),
@@ -422,6 +604,11 @@ fn render_with_shell(design: &Design, template_type: &str, context: &Value) -> S
         crate::designs::oneguy::journal::grid::single_css(),
         crate::designs::inkwell::journal::list_css(),
         crate::designs::inkwell::journal::single_css(),
+        crate::designs::masthead::journal::list_css(),
+        crate::designs::masthead::journal::single_css(),
+        widgets::WIDGETS_CSS,
+        crate::designs::homepage::CSS,
+        crate::designs::templates::CSS,
     );
     html = html.replace("{{base_css}}", &full_base_css);
     html = html.replace("{{design_css}}", &design.style_css);
@@ -449,11 +636,23 @@ fn render_with_shell(design: &Design, template_type: &str, context: &Value) -> S
     html = html.replace("{{tagline_html}}", &tagline_html);
     html = html.replace("{{categories_below_menu}}", &categories_below_menu);
     html = html.replace("{{nav_links}}", &nav_links);
+    let search_box = if sg("design_site_search", "true") == "true" {
+        let query = context
+            .get("search_query")
+            .and_then(|v| v.as_str())
+            .unwrap_or("");
+        build_search_box(query, "", false)
+    } else {
+        String::new()
+    };
+    html = html.replace("{{search_box}}", &search_box);
     html = html.replace("{{share_sidebar}}", &share_sidebar);
+    // The top bar layout has no sidebar, so sidebar widgets only show
+    // with the sidebar layout
     let custom_sidebar = if is_topbar {
         String::new()
     } else {
-        build_custom_sidebar_html(&settings)
+        format!("{}{}", build_custom_sidebar_html(&settings), areas.sidebar)
     };
     html = html.replace("{{custom_sidebar_html}}", &custom_sidebar);
     html = html.replace("{{social_sidebar}}", &social_sidebar);
@@ -468,7 +667,7 @@ fn render_with_shell(design: &Design, template_type: &str, context: &Value) -> S
         .and_then(|v| v.as_str())
         .unwrap_or("");
     let body_with_cats = {
-        let mut b = String::new();
+        let mut b = breadcrumbs_html;
         if !categories_page_top.is_empty()
             && (page_type == "portfolio_grid" || page_type == "portfolio_single")
         {
@@ -483,10 +682,13 @@ fn render_with_shell(design: &Design, template_type: &str, context: &Value) -> S
         b
     };
     html = html.replace("{{body_content}}", &body_with_cats);
-    html = html.replace("{{footer_inner}}", &footer_inner);
+    html = html.replace(
+        "{{footer_inner}}",
+        &format!("{}{}", areas.footer, footer_inner),
+    );
     html = html.replace("{{back_to_top}}", &build_back_to_top(&settings));
     html = html.replace("{{lightbox_js}}", LIGHTBOX_JS);
-    html = html.replace("{{image_protection_js}}", image_protection_js);
+    html = html.replace("{{image_protection_js}}", &image_protection_js);
     html = html.replace(
         "{{analytics_scripts}}",
         &seo::build_analytics_scripts(&settings),
@@ -495,6 +697,8 @@ fn render_with_shell(design: &Design, template_type: &str, context: &Value) -> S
         "{{cookie_consent}}",
         &build_cookie_consent_banner(&settings),
     );
+    insert_before_head_end(&mut html, &build_color_scheme_toggle(&settings));
+    html = crate::designs::custom_code::inject(html, design);
 
     // Rewrite /uploads/ URLs to /img/<token> proxy URLs
     let proxy_secret = sg("image_proxy_secret", "");
@@ -502,6 +706,12 @@ fn render_with_shell(design: &Design, template_type: &str, context: &Value) -> S
         html = crate::image_proxy::rewrite_upload_urls(&html, &proxy_secret);
     }
 
+    // Route external links through /out when outbound clicks are tracked
+    let link_secret = sg("signed_token_secret", "");
+    if sg("analytics_outbound_tracking", "false") == "true" && !link_secret.is_empty() {
+        html = crate::outbound::rewrite_links(&html, &link_secret, &sg("site_url", ""));
+    }
+
     // Prepend site comment header
     let site_url = sg("site_url", "");
     let comment = format!(
@@ -544,7 +754,11 @@ pub fn render_legal_page(
         "categories": cats_json,
         "seo": seo_html,
     });
-    let design = store.design_active().expect("No active design found");
+    let design = crate::designs::inheritance::resolve(
+        store,
+        store.design_active().expect("No active design found"),
+    )
+    .design;
 
     let result = {
         let settings_v = context.get("settings").cloned().unwrap_or_default();
@@ -558,14 +772,14 @@ pub fn render_legal_page(
                 .to_string()
         };
 
-        let mut html = design.layout_html.clone();
+        let mut html = nonce_scripts(&design.layout_html);
         html = html.replace("{{seo_meta}}", &seo_html);
         html = html.replace("{{webmaster_meta}}", "");
         html = html.replace("{{favicon_link}}", &build_favicon_link(&settings_v));
         html = html.replace("{{font_links}}", &font_links);
         html = html.replace("{{css_vars}}", &css_vars);
         let full_base_css = format!(
-            "{}{}{}{}{}{}{}{}{}{}",
+            "{}{}{}{}{}{}{}{}{}{}{}{}",
             BASE_CSS,
             crate::designs::oneguy::journal::list_classic::css(),
             crate::designs::oneguy::journal::list_classic::list_css(),
@@ -576,6 +790,8 @@ pub fn render_legal_page(
             crate::designs::oneguy::journal::grid::single_css(),
             crate::designs::inkwell::journal::list_css(),
             crate::designs::inkwell::journal::single_css(),
+            crate::designs::masthead::journal::list_css(),
+            crate::designs::masthead::journal::single_css(),
         );
         html = html.replace("{{base_css}}", &full_base_css);
         html = html.replace("{{design_css}}", &design.style_css);
@@ -622,6 +838,7 @@ pub fn render_legal_page(
         html = html.replace("{{tagline_html}}", &tagline_html);
         html = html.replace("{{categories_below_menu}}", "");
         html = html.replace("{{nav_links}}", "");
+        html = html.replace("{{search_box}}", "");
         html = html.replace("{{share_sidebar}}", "");
         html = html.replace("{{custom_sidebar_html}}", "");
         html = html.replace(
@@ -645,11 +862,17 @@ pub fn render_legal_page(
             "{{cookie_consent}}",
             &build_cookie_consent_banner(&settings_v),
         );
+        insert_before_head_end(&mut html, &build_color_scheme_toggle(&settings_v));
+        html = crate::designs::custom_code::inject(html, &design);
         // Rewrite /uploads/ URLs to /img/<token> proxy URLs
         let proxy_secret = sg("image_proxy_secret", "");
         if !proxy_secret.is_empty() {
             html = crate::image_proxy::rewrite_upload_urls(&html, &proxy_secret);
         }
+        let link_secret = sg("signed_token_secret", "");
+        if sg("analytics_outbound_tracking", "false") == "true" && !link_secret.is_empty() {
+            html = crate::outbound::rewrite_links(&html, &link_secret, &sg("site_url", ""));
+        }
 
         // Prepend site comment header
         let site_url = sg("site_url", "");
@@ -704,7 +927,11 @@ pub fn render_contact_page(
         "page_type": "contact",
         "seo": seo_html,
     });
-    let design = store.design_active().expect("No active design found");
+    let design = crate::designs::inheritance::resolve(
+        store,
+        store.design_active().expect("No active design found"),
+    )
+    .design;
 
     let result = {
         let settings_v = context.get("settings").cloned().unwrap_or_default();
@@ -718,14 +945,14 @@ pub fn render_contact_page(
                 .to_string()
         };
 
-        let mut html = design.layout_html.clone();
+        let mut html = nonce_scripts(&design.layout_html);
         html = html.replace("{{seo_meta}}", &seo_html);
         html = html.replace("{{webmaster_meta}}", "");
         html = html.replace("{{favicon_link}}", &build_favicon_link(&settings_v));
         html = html.replace("{{font_links}}", &font_links);
         html = html.replace("{{css_vars}}", &css_vars);
         let full_base_css = format!(
-            "{}{}{}{}{}{}{}{}{}{}",
+            "{}{}{}{}{}{}{}{}{}{}{}{}",
             BASE_CSS,
             crate::designs::oneguy::journal::list_classic::css(),
             crate::designs::oneguy::journal::list_classic::list_css(),
@@ -736,6 +963,8 @@ pub fn render_contact_page(
             crate::designs::oneguy::journal::grid::single_css(),
             crate::designs::inkwell::journal::list_css(),
             crate::designs::inkwell::journal::single_css(),
+            crate::designs::masthead::journal::list_css(),
+            crate::designs::masthead::journal::single_css(),
         );
         html = html.replace("{{base_css}}", &full_base_css);
         html = html.replace("{{design_css}}", &design.style_css);
@@ -842,6 +1071,12 @@ pub fn render_contact_page(
         }
         html = html.replace("{{categories_below_menu}}", "");
         html = html.replace("{{nav_links}}", &nav_html);
+        let search_box = if sg("design_site_search", "true") == "true" {
+            build_search_box("", "", false)
+        } else {
+            String::new()
+        };
+        html = html.replace("{{search_box}}", &search_box);
         html = html.replace("{{share_sidebar}}", "");
         html = html.replace("{{custom_sidebar_html}}", "");
         html = html.replace(
@@ -865,10 +1100,16 @@ pub fn render_contact_page(
             "{{cookie_consent}}",
             &build_cookie_consent_banner(&settings_v),
         );
+        insert_before_head_end(&mut html, &build_color_scheme_toggle(&settings_v));
+        html = crate::designs::custom_code::inject(html, &design);
         let proxy_secret = sg("image_proxy_secret", "");
         if !proxy_secret.is_empty() {
             html = crate::image_proxy::rewrite_upload_urls(&html, &proxy_secret);
         }
+        let link_secret = sg("signed_token_secret", "");
+        if sg("analytics_outbound_tracking", "false") == "true" && !link_secret.is_empty() {
+            html = crate::outbound::rewrite_links(&html, &link_secret, &sg("site_url", ""));
+        }
 
         let site_url = sg("site_url", "");
         let comment = format!(
@@ -1004,7 +1245,9 @@ pub(crate) fn build_comments_section(
         }
     }
 
-    html.push_str(&format!(
+    let member_js = member_comment_js(settings);
+
+    html.push_str(&nonce_scripts(&format!(
         "<section class=\"comment-form\">\
 \n    <h3>Leave a Comment</h3>\
 \n    {captcha_script}\
@@ -1025,7 +1268,7 @@ pub(crate) fn build_comments_section(
 \n(function(){{\
 \nvar f=document.getElementById('comment-form');\
 \nif(!f)return;\
-\nvar getToken={captcha_get_token_js};\
+\nvar getToken={captcha_get_token_js};{member_js}\
 \ndocument.querySelectorAll('.reply-btn').forEach(function(btn){{\
 \n    btn.addEventListener('click',function(e){{\
 \n        e.preventDefault();\
@@ -1084,11 +1327,29 @@ pub(crate) fn build_comments_section(
         name_req = name_req,
         captcha_html = captcha_html,
         captcha_get_token_js = captcha_get_token_js,
-    ));
+        member_js = member_js,
+    )));
 
     html
 }
 
+/// Comment form script for signed-in members: fill in and lock the name and
+/// drop the email field, since the server takes both from the account.
+/// Expects the form in `f`; empty while membership is off.
+pub(crate) fn member_comment_js(settings: &Value) -> &'static str {
+    if settings.get("membership_enabled").and_then(|v| v.as_str()) == Some("true") {
+        "\nfetch('/account/me',{credentials:'same-origin'}).then(function(r){return r.ok?r.json():null}).then(function(me){\
+\n    if(!me||!me.signed_in)return;\
+\n    var n=f.querySelector('[name=author_name]');\
+\n    n.value=n.defaultValue=me.display_name;n.readOnly=true;\
+\n    var em=f.querySelector('[name=author_email]');\
+\n    if(em){em.required=false;em.style.display='none';}\
+\n}).catch(function(){});"
+    } else {
+        ""
+    }
+}
+
 /// Render a single comment and its nested replies recursively.
 pub(crate) fn render_comment(
     html: &mut String,
@@ -1804,6 +2065,31 @@ fn build_social_links_inline(settings: &Value) -> String {
     html
 }
 
+/// Build the "Related" list for a single post from `related_posts` in the
+/// context. Empty when the route didn't set any.
+pub(crate) fn build_related_posts(context: &Value, blog_slug: &str) -> String {
+    let items = match context.get("related_posts").and_then(|v| v.as_array()) {
+        Some(a) if !a.is_empty() => a,
+        _ => return String::new(),
+    };
+    let links: Vec<String> = items
+        .iter()
+        .map(|p| {
+            let title = p.get("title").and_then(|v| v.as_str()).unwrap_or("");
+            let slug = p.get("slug").and_then(|v| v.as_str()).unwrap_or("");
+            format!(
+                "<li><a href=\"{}\">{}</a></li>",
+                slug_url(blog_slug, slug),
+                html_escape(title)
+            )
+        })
+        .collect();
+    format!(
+        "<aside class=\"related-posts\"><h3>Related</h3><ul>{}</ul></aside>",
+        links.join("")
+    )
+}
+
 /// Build share icons for sharing pages/site.
 /// Renders icon-only links (no text). Respects social_brand_colors setting.
 pub(crate) fn build_share_buttons(settings: &Value, page_url: &str, page_title: &str) -> String {
@@ -1928,6 +2214,142 @@ pub(crate) fn slug_url(slug: &str, sub: &str) -> String {
     }
 }
 
+/// One step of a breadcrumb trail; `url` is a site path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breadcrumb {
+    pub label: String,
+    pub url: String,
+}
+
+/// The trail for the page in `context`: Home › Journal › Category › Post,
+/// and the same for portfolio items, category, tag and archive pages. Plain
+/// listings and the homepage have no trail (empty).
+pub(crate) fn build_breadcrumbs(context: &Value) -> Vec<Breadcrumb> {
+    let settings = context.get("settings").cloned().unwrap_or_default();
+    let sg = |key: &str, def: &str| -> String {
+        settings
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or(def)
+            .to_string()
+    };
+    let str_of = |v: Option<&Value>, key: &str| -> String {
+        v.and_then(|o| o.get(key))
+            .and_then(|s| s.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+    let crumb = |label: String, url: String| Breadcrumb { label, url };
+
+    let page_type = context
+        .get("page_type")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+    let (section_slug, section_label, current) = match page_type {
+        "blog_single" | "blog_list" => (
+            sg("blog_slug", "journal"),
+            sg("blog_label", "journal"),
+            context.get("post"),
+        ),
+        "portfolio_single" | "portfolio_grid" => (
+            sg("portfolio_slug", "portfolio"),
+            sg("portfolio_label", "experiences"),
+            context.get("item"),
+        ),
+        "archives" => {
+            return vec![
+                crumb("Home".into(), "/".into()),
+                crumb("Archives".into(), "/archives".into()),
+            ];
+        }
+        _ => return vec![],
+    };
+
+    let mut trail = vec![
+        crumb("Home".into(), "/".into()),
+        crumb(section_label, slug_url(&section_slug, "")),
+    ];
+    if page_type.ends_with("single") {
+        // A single post or item sits under its first category
+        let first_cat = context
+            .get("categories")
+            .and_then(|c| c.as_array())
+            .and_then(|c| c.first());
+        if first_cat.is_some() {
+            trail.push(crumb(
+                str_of(first_cat, "name"),
+                slug_url(
+                    &section_slug,
+                    &format!("category/{}", str_of(first_cat, "slug")),
+                ),
+            ));
+        }
+        trail.push(crumb(
+            str_of(current, "title"),
+            slug_url(&section_slug, &str_of(current, "slug")),
+        ));
+    } else if let Some(cat) = context.get("active_category") {
+        trail.push(crumb(
+            str_of(Some(cat), "name"),
+            slug_url(
+                &section_slug,
+                &format!("category/{}", str_of(Some(cat), "slug")),
+            ),
+        ));
+    } else if let Some(tag) = context.get("active_tag") {
+        trail.push(crumb(
+            str_of(Some(tag), "name"),
+            slug_url(&section_slug, &format!("tag/{}", str_of(Some(tag), "slug"))),
+        ));
+    } else if let Some(author) = context.get("author_profile") {
+        trail = vec![
+            crumb("Home".into(), "/".into()),
+            crumb(
+                str_of(Some(author), "display_name"),
+                str_of(Some(author), "url"),
+            ),
+        ];
+    } else if let (Some(year), Some(month)) = (
+        context.get("archive_year").and_then(|v| v.as_str()),
+        context.get("archive_month").and_then(|v| v.as_str()),
+    ) {
+        trail = vec![
+            crumb("Home".into(), "/".into()),
+            crumb("Archives".into(), "/archives".into()),
+            crumb(
+                format!("{}/{}", year, month),
+                format!("/archives/{}/{}", year, month),
+            ),
+        ];
+    } else {
+        return vec![];
+    }
+    trail
+}
+
+/// The visible trail; the last step is the current page and isn't a link.
+fn build_breadcrumbs_html(trail: &[Breadcrumb]) -> String {
+    let items: Vec<String> = trail
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            if i + 1 == trail.len() {
+                format!("<li aria-current=\"page\">{}</li>", html_escape(&c.label))
+            } else {
+                format!(
+                    "<li><a href=\"{}\">{}</a></li>",
+                    html_escape(&c.url),
+                    html_escape(&c.label)
+                )
+            }
+        })
+        .collect();
+    format!(
+        "<nav class=\"breadcrumbs\" aria-label=\"Breadcrumb\"><ol>{}</ol></nav>\n",
+        items.join("")
+    )
+}
+
 fn build_favicon_link(settings: &Value) -> String {
     let favicon = settings
         .get("site_favicon")
@@ -2122,7 +2544,8 @@ fn build_back_to_top(settings: &Value) -> String {
     if !enabled {
         return String::new();
     }
-    r#"<button id="back-to-top" aria-label="Back to top" style="display:none;position:fixed;bottom:24px;right:24px;z-index:999;width:40px;height:40px;border-radius:50%;border:1px solid #ddd;background:rgba(255,255,255,0.9);cursor:pointer;font-size:18px;line-height:1;box-shadow:0 2px 8px rgba(0,0,0,0.1);transition:opacity 0.3s">↑</button>
+    nonce_scripts(
+        r#"<button id="back-to-top" aria-label="Back to top" style="display:none;position:fixed;bottom:24px;right:24px;z-index:999;width:40px;height:40px;border-radius:50%;border:1px solid #ddd;background:rgba(255,255,255,0.9);cursor:pointer;font-size:18px;line-height:1;box-shadow:0 2px 8px rgba(0,0,0,0.1);transition:opacity 0.3s">↑</button>
 <script>
 (function(){
 var btn=document.getElementById('back-to-top');
@@ -2130,7 +2553,66 @@ if(!btn)return;
 window.addEventListener('scroll',function(){btn.style.display=window.scrollY>300?'block':'none';});
 btn.addEventListener('click',function(){window.scrollTo({top:0,behavior:'smooth'});});
 })();
-</script>"#.to_string()
+</script>"#,
+    )
+}
+
+/// Visitor light/dark switch, shown when the dark palette is on and
+/// `design_dark_mode_toggle` is set. The choice is kept in the
+/// `velocty_color_scheme` cookie and applied from `<head>` so the page
+/// doesn't flash the other palette.
+fn build_color_scheme_toggle(settings: &Value) -> String {
+    let get = |key: &str| settings.get(key).and_then(|v| v.as_str()).unwrap_or("");
+    let mode = get("design_dark_mode");
+    if (mode != "auto" && mode != "always") || get("design_dark_mode_toggle") != "true" {
+        return String::new();
+    }
+    let default_dark = if mode == "always" {
+        "true"
+    } else {
+        "window.matchMedia('(prefers-color-scheme: dark)').matches"
+    };
+    nonce_scripts(&format!(
+        r#"<script>
+(function(){{
+var root=document.documentElement;
+var m=document.cookie.match(/(?:^|; )velocty_color_scheme=(dark|light)/);
+if(m)root.setAttribute('data-color-scheme',m[1]);
+document.addEventListener('DOMContentLoaded',function(){{
+var btn=document.createElement('button');
+btn.id='color-scheme-toggle';btn.type='button';btn.setAttribute('aria-label','Switch light or dark mode');btn.textContent='\u25D0';
+btn.style.cssText='position:fixed;bottom:24px;left:24px;z-index:999;width:40px;height:40px;border-radius:50%;border:1px solid #ddd;background:rgba(255,255,255,0.9);cursor:pointer;font-size:18px;line-height:1;box-shadow:0 2px 8px rgba(0,0,0,0.1)';
+btn.addEventListener('click',function(){{
+var current=root.getAttribute('data-color-scheme')||({default_dark}?'dark':'light');
+var next=current==='dark'?'light':'dark';
+root.setAttribute('data-color-scheme',next);
+document.cookie='velocty_color_scheme='+next+';path=/;max-age=31536000;SameSite=Lax';
+}});
+document.body.appendChild(btn);
+}});
+}})();
+</script>
+"#,
+        default_dark = default_dark
+    ))
+}
+
+/// Put `snippet` at the end of the page's `<head>`
+fn insert_before_head_end(html: &mut String, snippet: &str) {
+    if snippet.is_empty() {
+        return;
+    }
+    if let Some(pos) = html.find("</head>") {
+        html.insert_str(pos, snippet);
+    }
+}
+
+fn insert_before_body_end(html: &mut String, snippet: &str) {
+    if let Some(pos) = html.rfind("</body>") {
+        html.insert_str(pos, snippet);
+    } else {
+        html.push_str(snippet);
+    }
 }
 
 fn build_cookie_consent_banner(settings: &Value) -> String {
@@ -2186,7 +2668,7 @@ fn build_cookie_consent_banner(settings: &Value) -> String {
 
     let btns_style = "display:flex;gap:8px;margin-top:14px;justify-content:flex-end;flex-wrap:wrap";
 
-    format!(
+    nonce_scripts(&format!(
         r##"<div id="cc-banner" style="{pos_css}">
 <div style="{inner_style}">
 <div style="font-size:13px;line-height:1.6">
@@ -2210,7 +2692,7 @@ function set(v){{document.cookie='velocty_consent='+v+';path=/;max-age=31536000;
 document.getElementById('cc-accept').onclick=function(){{set('all');}};
 document.getElementById('cc-necessary').onclick=function(){{set('necessary');}};
 var rj=document.getElementById('cc-reject');if(rj)rj.onclick=function(){{set('none');}};
-function loadAnalytics(){{document.querySelectorAll('script[data-consent="analytics"]').forEach(function(s){{var n=document.createElement('script');if(s.src)n.src=s.src;else n.textContent=s.textContent;n.async=true;Array.from(s.attributes).forEach(function(a){{if(a.name!=='type'&&a.name!=='data-consent')n.setAttribute(a.name,a.value);}});document.head.appendChild(n);}});}}
+function loadAnalytics(){{document.querySelectorAll('script[data-consent="analytics"]').forEach(function(s){{var n=document.createElement('script');if(s.src)n.src=s.src;else n.textContent=s.textContent;n.async=true;Array.from(s.attributes).forEach(function(a){{if(a.name!=='type'&&a.name!=='data-consent')n.setAttribute(a.name,a.value);}});if(s.nonce)n.nonce=s.nonce;document.head.appendChild(n);}});}}
 }})();
 </script>"##,
         pos_css = pos_css,
@@ -2223,7 +2705,7 @@ function loadAnalytics(){{document.querySelectorAll('script[data-consent="analyt
         btn_text = btn_text,
         reject_btn = reject_btn,
         btns_style = btns_style,
-    )
+    ))
 }
 
 pub(crate) fn format_date_iso8601(raw: &str, settings: &Value) -> String {
@@ -2366,12 +2848,64 @@ pub(crate) fn build_pagination(current: i64, total: i64) -> String {
     html
 }
 
+/// Bio card above an author's post list
+fn render_author_header(author: &Value) -> String {
+    let field = |key: &str| author.get(key).and_then(|v| v.as_str()).unwrap_or("");
+    let name = html_escape(field("display_name"));
+    let avatar = match field("avatar") {
+        "" => String::new(),
+        src => format!(
+            r#"<img class="author-avatar" src="{}" alt="{}" width="96" height="96">"#,
+            html_escape(src),
+            name
+        ),
+    };
+    let bio = match field("bio").trim() {
+        "" => String::new(),
+        text => format!(r#"<p class="author-bio">{}</p>"#, html_escape(text)),
+    };
+    let mut links: Vec<String> = Vec::new();
+    if !field("website").is_empty() {
+        links.push(format!(
+            r#"<a href="{}" rel="me noopener">Website</a>"#,
+            html_escape(field("website"))
+        ));
+    }
+    if let Some(Value::Array(social)) = author.get("social_links") {
+        for url in social.iter().filter_map(|v| v.as_str()) {
+            let host = url
+                .split("://")
+                .nth(1)
+                .and_then(|rest| rest.split('/').next())
+                .unwrap_or(url)
+                .trim_start_matches("www.");
+            links.push(format!(
+                r#"<a href="{}" rel="me noopener">{}</a>"#,
+                html_escape(url),
+                html_escape(host)
+            ));
+        }
+    }
+    let links = if links.is_empty() {
+        String::new()
+    } else {
+        format!(r#"<p class="author-links">{}</p>"#, links.join(" · "))
+    };
+    format!(
+        r#"<header class="author-header">{}<h1 class="author-name">{}</h1>{}{}</header>"#,
+        avatar, name, bio, links
+    )
+}
+
 fn render_blog_list(context: &Value, design_slug: &str) -> String {
     // Delegate to design-specific renderer based on active design
     match design_slug {
         "inkwell" => {
             return crate::designs::inkwell::journal::render_list(context);
         }
+        "masthead" => {
+            return crate::designs::masthead::journal::render_list(context);
+        }
         "oneguy" => {
             if let Some(html) = crate::designs::oneguy::journal::render_list(context) {
                 return html;
@@ -2468,7 +3002,7 @@ fn render_blog_list(context: &Value, design_slug: &str) -> String {
         if show_author && !author.is_empty() {
             meta_parts.push(format!(
                 "<span class=\"blog-author\">{}</span>",
-                html_escape(author)
+                author_link(post, author)
             ));
         }
         if show_date && !date.is_empty() {
@@ -2527,7 +3061,7 @@ fn render_blog_list(context: &Value, design_slug: &str) -> String {
                      Load More</button></div>",
                     current_page + 1, total_pages
                 ));
-                html.push_str(BLOG_LOAD_MORE_JS);
+                html.push_str(&nonce_scripts(BLOG_LOAD_MORE_JS));
             }
             "infinite" => {
                 html.push_str(&format!(
@@ -2536,7 +3070,7 @@ fn render_blog_list(context: &Value, design_slug: &str) -> String {
                     current_page + 1,
                     total_pages
                 ));
-                html.push_str(BLOG_INFINITE_SCROLL_JS);
+                html.push_str(&nonce_scripts(BLOG_INFINITE_SCROLL_JS));
             }
             _ => {
                 html.push_str(&build_pagination(current_page, total_pages));
@@ -2638,6 +3172,9 @@ fn render_blog_single(context: &Value, design_slug: &str) -> String {
         "inkwell" => {
             return crate::designs::inkwell::journal::render_single(context);
         }
+        "masthead" => {
+            return crate::designs::masthead::journal::render_single(context);
+        }
         "oneguy" => {
             if let Some(html) = crate::designs::oneguy::journal::render_single(context) {
                 return html;
@@ -2694,7 +3231,7 @@ fn render_blog_single(context: &Value, design_slug: &str) -> String {
     if show_author && !author.is_empty() {
         meta_parts.push(format!(
             "<span class=\"blog-author\">{}</span>",
-            html_escape(author)
+            author_link(post, author)
         ));
     }
     if show_date && !date.is_empty() {
@@ -2764,6 +3301,8 @@ fn render_blog_single(context: &Value, design_slug: &str) -> String {
         html.push_str(&build_share_buttons(&settings, &page_url, title));
     }
 
+    html.push_str(&build_related_posts(context, &blog_slug));
+
     // Prev / Next post navigation
     let mut nav_html = String::new();
     if let Some(prev) = context.get("prev_post") {
@@ -2806,67 +3345,7 @@ fn render_blog_single(context: &Value, design_slug: &str) -> String {
 
     // JSON-LD structured data
     if settings.get("seo_structured_data").and_then(|v| v.as_str()) == Some("true") {
-        let site_name = settings
-            .get("site_name")
-            .and_then(|v| v.as_str())
-            .unwrap_or("Velocty");
-        let site_url = settings
-            .get("site_url")
-            .and_then(|v| v.as_str())
-            .unwrap_or("http://localhost:8000");
-        let blog_slug = settings
-            .get("blog_slug")
-            .and_then(|v| v.as_str())
-            .unwrap_or("journal");
-        let slug = post.get("slug").and_then(|v| v.as_str()).unwrap_or("");
-        let headline = post.get("title").and_then(|v| v.as_str()).unwrap_or("");
-        let desc = post
-            .get("meta_description")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let raw_pub = post
-            .get("published_at")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let raw_mod = post
-            .get("updated_at")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let published = format_date_iso8601(raw_pub, &settings);
-        let modified = format_date_iso8601(raw_mod, &settings);
-        let image = post
-            .get("featured_image")
-            .and_then(|v| v.as_str())
-            .unwrap_or("");
-        let mut ld = format!(
-            r#"<script type="application/ld+json">
-{{
-    "@context": "https://schema.org",
-    "@type": "BlogPosting",
-    "headline": "{}",
-    "description": "{}",
-    "url": "{}/{}/{}",
-    "datePublished": "{}",
-    "dateModified": "{}",
-    "publisher": {{ "@type": "Organization", "name": "{}" }}"#,
-            html_escape(headline),
-            html_escape(desc),
-            site_url,
-            blog_slug,
-            slug,
-            published,
-            modified,
-            html_escape(site_name),
-        );
-        if !image.is_empty() {
-            ld.push_str(&format!(
-                r#", "image": "{}/uploads/{}""#,
-                site_url,
-                html_escape(image)
-            ));
-        }
-        ld.push_str("\n}\n</script>");
-        html.push_str(&ld);
+        html.push_str(&crate::seo::jsonld::post_jsonld(post, &settings));
     }
 
     html
@@ -2937,17 +3416,95 @@ fn render_archives(context: &Value) -> String {
     html
 }
 
+/// The search form, for the search page and for designs that place
+/// `{{search_box}}` in their shell. `query` and `kind` prefill it so a
+/// search can be refined without losing the type filter.
+pub(crate) fn build_search_box(query: &str, kind: &str, autofocus: bool) -> String {
+    let kind_input = if kind.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<input type=\"hidden\" name=\"kind\" value=\"{}\">",
+            html_escape(kind)
+        )
+    };
+    format!(
+        "<form class=\"search-form\" action=\"/search\" method=\"get\" role=\"search\">\
+         <input type=\"search\" name=\"q\" value=\"{}\" placeholder=\"Search…\" class=\"search-input\" aria-label=\"Search\"{}>{}\
+         <button type=\"submit\" class=\"search-btn\" aria-label=\"Search\">\
+         <svg width=\"18\" height=\"18\" viewBox=\"0 0 24 24\" fill=\"none\" stroke=\"currentColor\" stroke-width=\"2\"><circle cx=\"11\" cy=\"11\" r=\"8\"/><line x1=\"21\" y1=\"21\" x2=\"16.65\" y2=\"16.65\"/></svg>\
+         </button></form>",
+        html_escape(query),
+        if autofocus { " autofocus" } else { "" },
+        kind_input
+    )
+}
+
+/// Escape `text` and wrap the words that match the search `terms` in `<mark>`
+pub(crate) fn highlight_terms(text: &str, terms: &[String]) -> String {
+    let mut out = String::with_capacity(text.len() + 32);
+    let mut word = String::new();
+    let flush = |word: &mut String, out: &mut String| {
+        if word.is_empty() {
+            return;
+        }
+        if crate::models::search::term_matches(word, terms) {
+            out.push_str(&format!("<mark>{}</mark>", html_escape(word)));
+        } else {
+            out.push_str(&html_escape(word));
+        }
+        word.clear();
+    };
+    for ch in text.chars() {
+        if ch.is_alphanumeric() || ch == '_' {
+            word.push(ch);
+        } else {
+            flush(&mut word, &mut out);
+            out.push_str(&html_escape(&ch.to_string()));
+        }
+    }
+    flush(&mut word, &mut out);
+    out
+}
+
+/// `/search` link keeping the query, with an optional type filter and page
+fn search_href(query: &str, kind: &str, page: usize) -> String {
+    let mut href = format!("/search?q={}", urlencoding_simple(query));
+    if !kind.is_empty() {
+        href.push_str(&format!("&amp;kind={}", kind));
+    }
+    if page > 1 {
+        href.push_str(&format!("&amp;page={}", page));
+    }
+    href
+}
+
 pub(crate) fn render_search_page(context: &Value) -> String {
     let settings = context.get("settings").cloned().unwrap_or_default();
     let query = context
         .get("search_query")
         .and_then(|v| v.as_str())
         .unwrap_or("");
+    let kind = context
+        .get("search_kind")
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
     let results = context
         .get("search_results")
         .and_then(|v| v.as_array())
         .cloned()
         .unwrap_or_default();
+    let paging = context.get("search_paging").cloned().unwrap_or_default();
+    let count = |key: &str| paging.get(key).and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+    let (total, posts, portfolios) = (count("total"), count("posts"), count("portfolio"));
+    let (page, total_pages) = (count("page").max(1), count("total_pages").max(1));
+    // Logged searches tag result links so the click is attributed to them
+    let sid = context
+        .get("search_id")
+        .and_then(|v| v.as_i64())
+        .map(|id| format!("?sid={}", id))
+        .unwrap_or_default();
+    let terms = crate::models::search::query_terms(query);
 
     let blog_slug = settings
         .get("blog_slug")
@@ -2957,36 +3514,17 @@ pub(crate) fn render_search_page(context: &Value) -> String {
         .get("portfolio_slug")
         .and_then(|v| v.as_str())
         .unwrap_or("portfolio");
-    let journal_enabled = settings
-        .get("journal_enabled")
-        .and_then(|v| v.as_str())
-        .unwrap_or("true")
-        == "true";
-    let portfolio_enabled = settings
-        .get("portfolio_enabled")
-        .and_then(|v| v.as_str())
-        .unwrap_or("false")
-        == "true";
 
     let mut html = String::from("<div class=\"search-page\">");
-
-    // Search form
-    html.push_str(&format!(
-        "<h1 class=\"search-page-title\">Search</h1>\
-         <form class=\"search-form\" action=\"/search\" method=\"get\">\
-         <input type=\"text\" name=\"q\" value=\"{}\" placeholder=\"Search…\" class=\"search-input\" autofocus>\
-         <button type=\"submit\" class=\"search-btn\">\
-         <svg width=\"18\" height=\"18\" viewBox=\"0 0 24 24\" fill=\"none\" stroke=\"currentColor\" stroke-width=\"2\"><circle cx=\"11\" cy=\"11\" r=\"8\"/><line x1=\"21\" y1=\"21\" x2=\"16.65\" y2=\"16.65\"/></svg>\
-         </button></form>",
-        html_escape(query)
-    ));
+    html.push_str("<h1 class=\"search-page-title\">Search</h1>");
+    html.push_str(&build_search_box(query, kind, true));
 
     if query.is_empty() {
         html.push_str("</div>");
         return html;
     }
 
-    if results.is_empty() {
+    if posts + portfolios == 0 {
         html.push_str(&format!(
             "<p class=\"search-no-results\">No results found for <strong>{}</strong></p>",
             html_escape(query)
@@ -2995,82 +3533,137 @@ pub(crate) fn render_search_page(context: &Value) -> String {
         return html;
     }
 
-    // Split results by type
-    let mut posts: Vec<&Value> = vec![];
-    let mut portfolios: Vec<&Value> = vec![];
-    for r in &results {
-        match r.get("item_type").and_then(|v| v.as_str()) {
-            Some("post") => posts.push(r),
-            Some("portfolio") => portfolios.push(r),
-            _ => {}
-        }
-    }
-
-    let both_types =
-        journal_enabled && portfolio_enabled && !posts.is_empty() && !portfolios.is_empty();
-
     html.push_str(&format!(
         "<p class=\"search-summary\">{} result{} for <strong>{}</strong></p>",
-        results.len(),
-        if results.len() == 1 { "" } else { "s" },
+        total,
+        if total == 1 { "" } else { "s" },
         html_escape(query)
     ));
 
-    // Journal results
-    if !posts.is_empty() && journal_enabled {
-        if both_types {
-            html.push_str("<h2 class=\"search-section-title\">Journal</h2>");
-        }
-        html.push_str("<div class=\"search-results\">");
-        for r in &posts {
-            let title = r.get("title").and_then(|v| v.as_str()).unwrap_or("");
-            let slug = r.get("slug").and_then(|v| v.as_str()).unwrap_or("");
-            let snippet = r.get("snippet").and_then(|v| v.as_str()).unwrap_or("");
-            let date = r.get("date").and_then(|v| v.as_str()).unwrap_or("");
-            let href = slug_url(blog_slug, slug);
+    // Type filters, when the results are of both types or one is picked
+    let both_types = posts > 0 && portfolios > 0;
+    if both_types || !kind.is_empty() {
+        html.push_str("<nav class=\"search-filters\">");
+        for (value, label, n) in [
+            ("", "All", posts + portfolios),
+            ("post", "Journal", posts),
+            ("portfolio", "Portfolio", portfolios),
+        ] {
             html.push_str(&format!(
-                "<a href=\"{}\" class=\"search-result\">\
-                 <h3 class=\"search-result-title\">{}</h3>\
-                 <p class=\"search-result-snippet\">{}</p>\
-                 <span class=\"search-result-meta\">{}</span>\
-                 </a>",
-                href,
-                html_escape(title),
-                html_escape(snippet),
-                html_escape(date)
+                "<a href=\"{}\" class=\"search-filter{}\">{} <span class=\"search-filter-count\">{}</span></a>",
+                search_href(query, value, 1),
+                if value == kind { " active" } else { "" },
+                label,
+                n
             ));
         }
-        html.push_str("</div>");
+        html.push_str("</nav>");
     }
 
-    // Portfolio results
-    if !portfolios.is_empty() && portfolio_enabled {
-        if both_types {
-            html.push_str("<h2 class=\"search-section-title\">Portfolio</h2>");
+    // Results in ranked order
+    html.push_str("<div class=\"search-results\">");
+    for r in &results {
+        let item_type = r.get("item_type").and_then(|v| v.as_str()).unwrap_or("");
+        let title = r.get("title").and_then(|v| v.as_str()).unwrap_or("");
+        let slug = r.get("slug").and_then(|v| v.as_str()).unwrap_or("");
+        let snippet = r.get("snippet").and_then(|v| v.as_str()).unwrap_or("");
+        let (href, label, date) = if item_type == "portfolio" {
+            (
+                format!("/{}/{}{}", portfolio_slug, slug, sid),
+                "Portfolio",
+                "",
+            )
+        } else {
+            (
+                format!("{}{}", slug_url(blog_slug, slug), sid),
+                "Journal",
+                r.get("date").and_then(|v| v.as_str()).unwrap_or(""),
+            )
+        };
+        let mut meta = String::new();
+        if both_types && kind.is_empty() {
+            meta.push_str(&format!(
+                "<span class=\"search-result-type\">{}</span>",
+                label
+            ));
         }
-        html.push_str("<div class=\"search-results\">");
-        for r in &portfolios {
-            let title = r.get("title").and_then(|v| v.as_str()).unwrap_or("");
-            let slug = r.get("slug").and_then(|v| v.as_str()).unwrap_or("");
-            let snippet = r.get("snippet").and_then(|v| v.as_str()).unwrap_or("");
-            let href = format!("/{}/{}", portfolio_slug, slug);
+        if !date.is_empty() {
+            meta.push_str(&html_escape(date));
+        }
+        html.push_str(&format!(
+            "<a href=\"{}\" class=\"search-result search-result-{}\">\
+             <h3 class=\"search-result-title\">{}</h3>\
+             <p class=\"search-result-snippet\">{}</p>\
+             <span class=\"search-result-meta\">{}</span>\
+             </a>",
+            href,
+            html_escape(item_type),
+            highlight_terms(title, &terms),
+            highlight_terms(snippet, &terms),
+            meta
+        ));
+    }
+    html.push_str("</div>");
+
+    // Pagination, keeping the query and the type filter
+    if total_pages > 1 {
+        html.push_str("<nav class=\"pagination search-pagination\">");
+        if page > 1 {
             html.push_str(&format!(
-                "<a href=\"{}\" class=\"search-result\">\
-                 <h3 class=\"search-result-title\">{}</h3>\
-                 <p class=\"search-result-snippet\">{}</p>\
-                 </a>",
-                href,
-                html_escape(title),
-                html_escape(snippet),
+                "<a href=\"{}\">&laquo; Prev</a>",
+                search_href(query, kind, page - 1)
             ));
         }
-        html.push_str("</div>");
+        for p in 1..=total_pages {
+            if p == page {
+                html.push_str(&format!("<span class=\"current\">{}</span>", p));
+            } else {
+                html.push_str(&format!(
+                    "<a href=\"{}\">{}</a>",
+                    search_href(query, kind, p),
+                    p
+                ));
+            }
+        }
+        if page < total_pages {
+            html.push_str(&format!(
+                "<a href=\"{}\">Next &raquo;</a>",
+                search_href(query, kind, page + 1)
+            ));
+        }
+        html.push_str("</nav>");
     }
 
     html.push_str("</div>");
     html
 }
 
+/// Body data attributes for the lightbox's zoom, slideshow, preloading and
+/// deep-link behaviour. The slideshow interval is kept to 2–60 seconds.
+pub(crate) fn lightbox_data_attrs(settings: &Value) -> String {
+    let sg = |key: &str, def: &str| -> String {
+        settings
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or(def)
+            .to_string()
+    };
+    let interval = sg("portfolio_lightbox_autoplay_interval", "5")
+        .trim()
+        .parse::<u32>()
+        .unwrap_or(5)
+        .clamp(2, 60);
+    format!(
+        "data-lb-loop=\"{}\" data-lb-zoom=\"{}\" data-lb-autoplay=\"{}\" data-lb-interval=\"{}\" data-lb-preload=\"{}\" data-lb-deep-link=\"{}\"",
+        sg("portfolio_lightbox_loop", "true") != "false",
+        sg("portfolio_lightbox_zoom", "true") != "false",
+        sg("portfolio_lightbox_autoplay", "false") == "true",
+        interval,
+        sg("portfolio_lightbox_preload", "true") != "false",
+        sg("portfolio_lightbox_deep_link", "true") != "false",
+    )
+}
+
 pub(crate) fn render_404(_context: &Value) -> String {
     r#"<div class="error-page">
     <h1>404</h1>
@@ -3095,6 +3688,20 @@ fn urlencoding_simple(s: &str) -> String {
     result
 }
 
+/// Render CommonMark (with tables, footnotes, strikethrough and task lists)
+/// to HTML.
+pub(crate) fn markdown_to_html(markdown: &str) -> String {
+    use pulldown_cmark::{html, Options, Parser};
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TASKLISTS);
+    let mut out = String::new();
+    html::push_html(&mut out, Parser::new_ext(markdown, options));
+    out
+}
+
 pub(crate) fn html_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -3102,6 +3709,23 @@ pub(crate) fn html_escape(s: &str) -> String {
         .replace('"', "&quot;")
 }
 
+/// A post's author name for a byline, linked to their author page when the
+/// post carries an `author_url`.
+pub(crate) fn author_link(post: &Value, name: &str) -> String {
+    match post
+        .get("author_url")
+        .and_then(|v| v.as_str())
+        .filter(|u| !u.is_empty())
+    {
+        Some(url) => format!(
+            "<a href=\"{}\" rel=\"author\">{}</a>",
+            html_escape(url),
+            html_escape(name)
+        ),
+        None => html_escape(name),
+    }
+}
+
 /// Escape a string for safe embedding inside a JS single-quoted string literal.
 pub(crate) fn js_escape(s: &str) -> String {
     s.replace('\\', "\\\\")
@@ -3175,14 +3799,108 @@ const LIGHTBOX_JS: &str = r#"
     const commerceCur = b.commerceCurrency || 'USD';
     const heartsPos = b.heartsPosition || 'bottom_right';
     const navColor = b.lbNavColor || '#FFFFFF';
+    const loopItems = b.lbLoop !== 'false';
+    const useZoom = b.lbZoom !== 'false';
+    const slideshow = b.lbAutoplay === 'true';
+    const slideshowMs = (parseInt(b.lbInterval, 10) || 5) * 1000;
+    const preloadNeighbors = b.lbPreload !== 'false';
+    const deepLink = b.lbDeepLink !== 'false';
 
     // Lightbox setup — only when click mode is lightbox
     if (mode === 'lightbox') {
 
     const links = document.querySelectorAll('.portfolio-link');
-    let overlay, img, vid, titleEl, tagsEl, likesEl, shareEl, buyEl, closeBtn, prevBtn, nextBtn;
+    let overlay, img, vid, titleEl, tagsEl, likesEl, shareEl, buyEl, closeBtn, prevBtn, nextBtn, playBtn;
     let currentIndex = 0;
     const items = Array.from(links);
+    let slideTimer = null;
+    let zoom = { scale: 1, x: 0, y: 0 };
+
+    function itemSlug(link) {
+        var gridItem = link && link.closest('.grid-item');
+        return gridItem ? (gridItem.dataset.slug || '') : '';
+    }
+
+    // ── Zoom (wheel, pinch, double-click; drag to pan) ──
+    function applyZoom() {
+        if (!img) return;
+        img.style.transform = zoom.scale > 1
+            ? 'translate(' + zoom.x + 'px,' + zoom.y + 'px) scale(' + zoom.scale + ')'
+            : '';
+        img.classList.toggle('lb-zoomed', zoom.scale > 1);
+    }
+    function setZoom(scale) {
+        zoom.scale = Math.max(1, Math.min(4, scale));
+        if (zoom.scale === 1) { zoom.x = 0; zoom.y = 0; }
+        applyZoom();
+    }
+    function setupZoom() {
+        img.addEventListener('wheel', function(e) {
+            e.preventDefault();
+            setZoom(zoom.scale * (e.deltaY < 0 ? 1.15 : 1 / 1.15));
+        }, { passive: false });
+        img.addEventListener('dblclick', function(e) {
+            e.stopPropagation();
+            setZoom(zoom.scale > 1 ? 1 : 2);
+        });
+        var drag = null, pinch = null;
+        function dist(t) { return Math.hypot(t[0].clientX - t[1].clientX, t[0].clientY - t[1].clientY); }
+        img.addEventListener('touchstart', function(e) {
+            if (e.touches.length === 2) {
+                pinch = { d: dist(e.touches), scale: zoom.scale };
+            } else if (e.touches.length === 1 && zoom.scale > 1) {
+                drag = { x: e.touches[0].clientX - zoom.x, y: e.touches[0].clientY - zoom.y };
+            }
+        }, { passive: true });
+        img.addEventListener('touchmove', function(e) {
+            if (pinch && e.touches.length === 2) {
+                e.preventDefault();
+                setZoom(pinch.scale * dist(e.touches) / pinch.d);
+            } else if (drag && e.touches.length === 1) {
+                e.preventDefault();
+                zoom.x = e.touches[0].clientX - drag.x;
+                zoom.y = e.touches[0].clientY - drag.y;
+                applyZoom();
+            }
+        }, { passive: false });
+        img.addEventListener('touchend', function() { pinch = null; drag = null; });
+        img.addEventListener('mousedown', function(e) {
+            if (zoom.scale <= 1) return;
+            e.preventDefault();
+            drag = { x: e.clientX - zoom.x, y: e.clientY - zoom.y };
+        });
+        document.addEventListener('mousemove', function(e) {
+            if (!drag || e.buttons !== 1) return;
+            zoom.x = e.clientX - drag.x;
+            zoom.y = e.clientY - drag.y;
+            applyZoom();
+        });
+        document.addEventListener('mouseup', function() { drag = null; });
+    }
+
+    // ── Slideshow ──
+    function stopSlideshow() {
+        if (slideTimer) { clearInterval(slideTimer); slideTimer = null; }
+        if (playBtn) { playBtn.classList.remove('playing'); playBtn.title = 'Play slideshow'; }
+    }
+    function startSlideshow() {
+        stopSlideshow();
+        slideTimer = setInterval(function() {
+            if (!loopItems && currentIndex === items.length - 1) { stopSlideshow(); return; }
+            navigate(1);
+        }, slideshowMs);
+        if (playBtn) { playBtn.classList.add('playing'); playBtn.title = 'Pause slideshow'; }
+    }
+    function toggleSlideshow() {
+        if (slideTimer) stopSlideshow(); else startSlideshow();
+    }
+
+    function preload(index) {
+        var link = items[index];
+        if (!link || isVideoItem(link)) return;
+        var src = (link.querySelector('img') || {}).src;
+        if (src) { var pre = new Image(); pre.src = src; }
+    }
 
     function isVideoItem(link) {
         var gridItem = link.closest('.grid-item');
@@ -3216,8 +3934,10 @@ const LIGHTBOX_JS: &str = r#"
         }
         var titleAlign = titlePos === 'right' ? 'right' : titlePos === 'left' ? 'left' : 'center';
         var tagsAlign = tagsPos === 'right' ? 'right' : tagsPos === 'left' ? 'left' : 'center';
+        var playHtml = slideshow ? '<button class="lb-play" title="Pause slideshow"><span class="lb-play-icon">&#9654;</span><span class="lb-pause-icon">&#10074;&#10074;</span></button>' : '';
         overlay.innerHTML =
             '<button class="lb-close">&times;</button>' +
+            playHtml +
             '<div class="lb-content">' +
                 '<div class="lb-image-wrap">' +
                     '<img class="lb-image" src="" alt="">' +
@@ -3241,8 +3961,11 @@ const LIGHTBOX_JS: &str = r#"
         closeBtn = overlay.querySelector('.lb-close');
         prevBtn = overlay.querySelector('.lb-prev');
         nextBtn = overlay.querySelector('.lb-next');
+        playBtn = overlay.querySelector('.lb-play');
 
         closeBtn.addEventListener('click', close);
+        if (playBtn) playBtn.addEventListener('click', function(e) { e.stopPropagation(); toggleSlideshow(); });
+        if (useZoom) setupZoom();
         if (prevBtn) prevBtn.addEventListener('click', function(e) { e.stopPropagation(); navigate(-1); });
         if (nextBtn) nextBtn.addEventListener('click', function(e) { e.stopPropagation(); navigate(1); });
         overlay.addEventListener('click', function(e) { if (e.target === overlay) close(); });
@@ -3263,9 +3986,12 @@ const LIGHTBOX_JS: &str = r#"
     }
 
     function open(index) {
+        var wasOpen = overlay && overlay.classList.contains('active');
         if (!overlay) createOverlay();
         pauseVideo();
         currentIndex = index;
+        zoom = { scale: 1, x: 0, y: 0 };
+        applyZoom();
         var link = items[index];
         var isVid = isVideoItem(link);
         var mediaSrc = isVid ? (link.querySelector('video') || {}).src : (link.querySelector('img') || {}).src;
@@ -3327,19 +4053,43 @@ const LIGHTBOX_JS: &str = r#"
         }
         overlay.classList.add('active');
         document.body.style.overflow = 'hidden';
+        if (preloadNeighbors && items.length > 1) {
+            preload((index + 1) % items.length);
+            preload((index - 1 + items.length) % items.length);
+        }
+        if (deepLink) {
+            var slug = itemSlug(link);
+            if (slug && location.hash !== '#item-' + slug) {
+                history.replaceState(null, '', '#item-' + slug);
+            }
+        }
+        if (slideshow && !wasOpen) startSlideshow();
     }
 
     function close() {
         pauseVideo();
+        stopSlideshow();
         overlay.classList.remove('active');
         document.body.style.overflow = '';
+        if (deepLink && location.hash.indexOf('#item-') === 0) {
+            history.replaceState(null, '', location.pathname + location.search);
+        }
     }
 
     function navigate(dir) {
-        currentIndex = (currentIndex + dir + items.length) % items.length;
+        var next = currentIndex + dir;
+        if (!loopItems && (next < 0 || next >= items.length)) return;
+        currentIndex = (next + items.length) % items.length;
         open(currentIndex);
     }
 
+    function openFromHash() {
+        if (!deepLink || location.hash.indexOf('#item-') !== 0) return;
+        var slug = decodeURIComponent(location.hash.slice(6));
+        var i = items.findIndex(function(link) { return itemSlug(link) === slug; });
+        if (i >= 0) open(i);
+    }
+
     items.forEach(function(link, i) {
         link.addEventListener('click', function(e) {
             e.preventDefault();
@@ -3351,11 +4101,22 @@ const LIGHTBOX_JS: &str = r#"
         if (!overlay || !overlay.classList.contains('active')) return;
         if (e.key === 'Escape') close();
         if (useKeyboard) {
-            if (e.key === 'ArrowLeft') navigate(-1);
-            if (e.key === 'ArrowRight') navigate(1);
+            if (e.key === 'ArrowLeft') { stopSlideshow(); navigate(-1); }
+            if (e.key === 'ArrowRight') { stopSlideshow(); navigate(1); }
+            if (e.key === 'Home') { stopSlideshow(); open(0); }
+            if (e.key === 'End') { stopSlideshow(); open(items.length - 1); }
+            if (e.key === ' ' && slideshow) { e.preventDefault(); toggleSlideshow(); }
+            if (useZoom && (e.key === '+' || e.key === '=')) setZoom(zoom.scale * 1.25);
+            if (useZoom && e.key === '-') setZoom(zoom.scale / 1.25);
+            if (useZoom && e.key === '0') setZoom(1);
         }
     });
 
+    if (items.length) {
+        openFromHash();
+        window.addEventListener('hashchange', openFromHash);
+    }
+
     } // end lightbox-only block
 
     // ── Like button handler (portfolio single page + any .like-btn) ──
@@ -3580,6 +4341,24 @@ h6 { font-size: var(--font-size-h6); }
 
 .lightbox-overlay.active { display: flex; }
 
+.lb-image { transition: transform .15s ease-out; transform-origin: center; }
+.lb-image.lb-zoomed { cursor: grab; }
+.lb-play {
+    position: absolute;
+    top: 20px;
+    right: 64px;
+    background: none;
+    border: none;
+    color: #fff;
+    font-size: 18px;
+    cursor: pointer;
+    z-index: 1001;
+    opacity: .8;
+}
+.lb-play:hover { opacity: 1; }
+.lb-play .lb-pause-icon, .lb-play.playing .lb-play-icon { display: none; }
+.lb-play.playing .lb-pause-icon { display: inline; }
+
 .lb-content {
     text-align: center;
     max-width: 80vw;
@@ -3781,12 +4560,10 @@ h6 { font-size: var(--font-size-h6); }
 .search-btn:hover { border-color: var(--color-accent); color: var(--color-accent); }
 .search-summary { font-size: 14px; color: var(--color-text-secondary); margin-bottom: 24px; }
 .search-no-results { font-size: 15px; color: var(--color-text-secondary); margin-top: 16px; }
-.search-section-title {
-    font-size: 13px; font-weight: 700; text-transform: uppercase;
-    letter-spacing: 0.08em; color: var(--color-text-secondary);
-    margin: 32px 0 16px; padding-bottom: 8px;
-    border-bottom: 1px solid rgba(0,0,0,.08);
-}
+.search-filters { display: flex; gap: 16px; margin-bottom: 16px; font-size: 14px; }
+.search-filter { color: var(--color-text-secondary); text-decoration: none; padding-bottom: 4px; }
+.search-filter.active { color: var(--color-text); border-bottom: 2px solid var(--color-accent); }
+.search-filter-count { font-size: 12px; opacity: .7; }
 .search-results { display: flex; flex-direction: column; gap: 0; }
 .search-result {
     display: block; padding: 20px 0; text-decoration: none;
@@ -3806,6 +4583,9 @@ h6 { font-size: var(--font-size-h6); }
     font-size: 12px; color: var(--color-text-secondary);
     margin-top: 4px; display: inline-block;
 }
+.search-result-type { text-transform: uppercase; letter-spacing: 0.08em; margin-right: 8px; }
+.search-result mark { background: none; color: inherit; font-weight: 700; }
+.search-pagination { margin-top: 32px; }
 "#;
 
 /// Oneguy shell HTML — the full page wrapper with {{placeholder}} tags.
@@ -4657,6 +5437,13 @@ body.topbar-layout.footer-always-visible .site-footer {
 .post-nav a { color: var(--color-text); text-decoration: none; }
 .post-nav a:hover { color: var(--color-accent); }
 
+.related-posts { margin-top: 32px; font-size: 14px; }
+.related-posts h3 { font-size: 13px; text-transform: uppercase; letter-spacing: 0.05em; margin: 0 0 8px; }
+.related-posts ul { list-style: none; margin: 0; padding: 0; }
+.related-posts li { padding: 4px 0; }
+.related-posts a { color: var(--color-text); text-decoration: none; }
+.related-posts a:hover { color: var(--color-accent); }
+
 /* ── Portfolio Single ── */
 .portfolio-single {
     max-width: 1000px;
@@ -4820,6 +5607,30 @@ body.topbar-layout .site-wrapper { display: none; }
     border: none;
 }
 
+/* Breadcrumbs */
+.breadcrumbs ol {
+    display: flex;
+    flex-wrap: wrap;
+    list-style: none;
+    margin: 0;
+    padding: 12px 24px 0;
+    font-family: var(--font-nav);
+    font-size: 12px;
+    color: var(--color-text);
+    opacity: .75;
+}
+.breadcrumbs li + li::before {
+    content: "\203A";
+    padding: 0 6px;
+}
+.breadcrumbs a {
+    color: inherit;
+    text-decoration: none;
+}
+.breadcrumbs a:hover {
+    color: var(--color-accent);
+}
+
 /* Page Top categories (sidebar layout) */
 .categories-page-top {
     display: flex;
@@ -5017,6 +5828,14 @@ pub const INKWELL_SHELL_HTML: &str = ONEGUY_SHELL_HTML;
 /// Journal-specific CSS is provided by the inkwell::journal module.
 pub const INKWELL_DESIGN_CSS: &str = ONEGUY_DESIGN_CSS;
 
+/// Masthead shell HTML — the sidebar page structure shared with Oneguy.
+pub const MASTHEAD_SHELL_HTML: &str = ONEGUY_SHELL_HTML;
+
+/// Masthead design CSS — the Oneguy layout CSS. The magazine front page and
+/// article styles come from the masthead::journal module; portfolio pages
+/// use the Oneguy renderers.
+pub const MASTHEAD_DESIGN_CSS: &str = ONEGUY_DESIGN_CSS;
+
 pub(crate) fn build_commerce_html(
     price: f64,
     purchase_note: &str,
@@ -5356,5 +6175,5 @@ pub(crate) fn build_commerce_html(
     s.push_str("}).catch(function(){result.textContent='Error looking up purchase.';});\n}\n");
     s.push_str("</script>\n");
 
-    s
+    nonce_scripts(&s)
 }
@@ -0,0 +1,171 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{ContentType, Header, Method, Status};
+use rocket::{Request, Response};
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+
+use super::page_cache;
+use crate::ADMIN_INTERNAL_MOUNT;
+
+// ── Conditional GET ─────────────────────────────────────
+//
+// ETag (a hash of the body) and Last-Modified (the last content or settings
+// change) on public pages, feeds and sitemaps, so repeat visitors and feed
+// readers get a bodyless 304 when nothing changed. The change clock is the
+// page cache's: every Store write that drops cached pages also moves it.
+//
+// With a nonce-based CSP the nonce is left out of the hash, so the same page
+// keeps the same ETag. A 304 then drops the CSP headers: the browser keeps
+// the policy stored with its copy, whose nonce matches that copy's scripts.
+
+/// Paths that are never public content
+const PRIVATE_PREFIXES: &[&str] = &[
+    ADMIN_INTERNAL_MOUNT,
+    "/super",
+    "/account",
+    "/api",
+    "/download",
+    "/static",
+];
+
+/// Whether a response at `path` with this content type gets validators:
+/// HTML, plus the XML of feeds and sitemaps
+pub fn eligible(path: &str, content_type: Option<&ContentType>) -> bool {
+    if PRIVATE_PREFIXES.iter().any(|p| {
+        path.strip_prefix(p)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }) {
+        return false;
+    }
+    content_type.is_some_and(|ct| {
+        ct.is_html() || (ct.top().as_str() != "image" && ct.sub().as_str().ends_with("xml"))
+    })
+}
+
+/// Strong ETag for a response body
+pub fn etag(body: &[u8]) -> String {
+    format!("\"{}\"", &hex::encode(Sha256::digest(body))[..32])
+}
+
+/// The nonce a Content-Security-Policy value allows scripts with
+pub fn csp_nonce(policy: &str) -> Option<&str> {
+    let start = policy.find("'nonce-")? + "'nonce-".len();
+    let len = policy[start..].find('\'')?;
+    Some(&policy[start..start + len]).filter(|n| !n.is_empty())
+}
+
+/// ETag for a body rendered with a per-request `nonce`, hashed as if the
+/// nonce were absent so every render of the same page matches
+pub fn etag_without_nonce(body: &[u8], nonce: Option<&str>) -> String {
+    let nonce = match nonce {
+        Some(n) if !n.is_empty() => n.as_bytes(),
+        _ => return etag(body),
+    };
+    let mut stripped = Vec::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(at) = rest.windows(nonce.len()).position(|w| w == nonce) {
+        stripped.extend_from_slice(&rest[..at]);
+        rest = &rest[at + nonce.len()..];
+    }
+    stripped.extend_from_slice(rest);
+    etag(&stripped)
+}
+
+/// `secs` since the epoch as an HTTP date, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`
+pub fn http_date(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .unwrap_or_default()
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+/// Whether the client's copy is current. If-None-Match wins when both are
+/// sent, as RFC 9110 requires.
+pub fn not_modified(
+    if_none_match: Option<&str>,
+    if_modified_since: Option<&str>,
+    etag: &str,
+    last_modified: i64,
+) -> bool {
+    if let Some(inm) = if_none_match {
+        return inm
+            .split(',')
+            .map(str::trim)
+            .any(|t| t == "*" || t.strip_prefix("W/").unwrap_or(t) == etag);
+    }
+    if_modified_since
+        .and_then(|v| chrono::DateTime::parse_from_rfc2822(v.trim()).ok())
+        .is_some_and(|since| last_modified <= since.timestamp())
+}
+
+/// Adds validators to public responses and answers conditional requests
+/// with 304 Not Modified. Attached after `SecurityHeaders`, so it sees the
+/// final body and headers.
+pub struct ConditionalGet;
+
+#[rocket::async_trait]
+impl Fairing for ConditionalGet {
+    fn info(&self) -> Info {
+        Info {
+            name: "Conditional GET",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        if !matches!(req.method(), Method::Get | Method::Head) || res.status() != Status::Ok {
+            return;
+        }
+        if !eligible(req.uri().path().as_str(), res.content_type().as_ref()) {
+            return;
+        }
+        let headers = res.headers();
+        if headers.contains("ETag")
+            || headers
+                .get_one("Cache-Control")
+                .is_some_and(|v| v.contains("no-store") || v.contains("private"))
+        {
+            return;
+        }
+        let nonce = headers
+            .get("Content-Security-Policy")
+            .chain(headers.get("Content-Security-Policy-Report-Only"))
+            .find_map(csp_nonce)
+            .map(str::to_string);
+        // Streamed bodies are left alone
+        if res.body().preset_size().is_none() {
+            return;
+        }
+        let Ok(body) = res.body_mut().to_bytes().await else {
+            return;
+        };
+
+        let tag = etag_without_nonce(&body, nonce.as_deref());
+        let modified = page_cache::last_change();
+        res.set_header(Header::new("ETag", tag.clone()));
+        res.set_header(Header::new("Last-Modified", http_date(modified)));
+        // Revalidate every time rather than let browsers guess a lifetime
+        // from Last-Modified
+        if !res.headers().contains("Cache-Control") {
+            res.set_header(Header::new("Cache-Control", "no-cache"));
+        }
+
+        if not_modified(
+            req.headers().get_one("If-None-Match"),
+            req.headers().get_one("If-Modified-Since"),
+            &tag,
+            modified,
+        ) {
+            res.set_status(Status::NotModified);
+            res.set_sized_body(0, Cursor::new(Vec::new()));
+            // The browser's copy has the previous nonce; a 304 carrying the
+            // new one would replace its stored policy and block its scripts
+            if nonce.is_some() {
+                res.remove_header("Content-Security-Policy");
+                res.remove_header("Content-Security-Policy-Report-Only");
+            }
+        } else {
+            res.set_sized_body(body.len(), Cursor::new(body));
+        }
+    }
+}
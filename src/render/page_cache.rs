@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::models::settings::SettingsCache;
+
+// ── Full-page cache ─────────────────────────────────────
+//
+// Rendered public pages, kept in memory and optionally on disk so a restart
+// starts warm. Entries expire after `page_cache_ttl_seconds`; any write to
+// posts, portfolio items, comments, taxonomies, designs or settings goes
+// through the Store, which calls `invalidate` and drops every entry.
+// Pages rendered for a design preview or an A/B experiment are never
+// cached, since they differ between visitors; nor are pages carrying a
+// per-request CSP nonce.
+
+/// Where entries are written when `page_cache_disk` is on
+pub const DISK_DIR: &str = "website/site/cache/pages";
+
+struct Entry {
+    at: Instant,
+    html: String,
+}
+
+fn entries() -> &'static RwLock<HashMap<String, Entry>> {
+    static ENTRIES: OnceLock<RwLock<HashMap<String, Entry>>> = OnceLock::new();
+    ENTRIES.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Bumped by every invalidation; a page rendered across one isn't stored
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+static HITS: AtomicU64 = AtomicU64::new(0);
+static MISSES: AtomicU64 = AtomicU64::new(0);
+static INVALIDATIONS: AtomicU64 = AtomicU64::new(0);
+/// Unix time of the last invalidation; 0 until the first call to `last_change`
+static CHANGED_AT: AtomicI64 = AtomicI64::new(0);
+
+/// Cache settings, read from the in-memory settings so a hit never touches
+/// the database
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub enabled: bool,
+    pub ttl: Duration,
+    pub max_entries: usize,
+    pub disk: bool,
+}
+
+impl Config {
+    pub fn from_settings(settings: &SettingsCache) -> Self {
+        let num = |key: &str, default: u64| {
+            settings
+                .get_or(key, &default.to_string())
+                .trim()
+                .parse::<u64>()
+                .unwrap_or(default)
+        };
+        Config {
+            enabled: settings.get_or("page_cache_enabled", "true") == "true",
+            ttl: Duration::from_secs(num("page_cache_ttl_seconds", 300).max(1)),
+            max_entries: num("page_cache_max_entries", 500).max(1) as usize,
+            disk: settings.get_or("page_cache_disk", "false") == "true",
+        }
+    }
+}
+
+/// Cache key for a public page: the site, the path and page number, and the
+/// settings that decide which handler serves the path
+pub fn key(settings: &SettingsCache, path: &str, page: Option<i64>) -> String {
+    let routing: Vec<String> = [
+        "blog_slug",
+        "portfolio_slug",
+        "journal_enabled",
+        "portfolio_enabled",
+        "homepage_mode",
+    ]
+    .iter()
+    .map(|k| settings.get_or(k, ""))
+    .collect();
+    format!(
+        "{}|{}|{}|{}",
+        settings.get_or("site_url", ""),
+        path.trim_end_matches('/'),
+        page.unwrap_or(1).max(1),
+        routing.join(",")
+    )
+}
+
+/// Whether a request may be answered from the cache. `visitor` is empty for
+/// visitors that experiments skip.
+pub fn cacheable(settings: &SettingsCache, preview: Option<i64>, visitor: &str) -> bool {
+    if preview.is_some() {
+        return false;
+    }
+    visitor.is_empty()
+        || settings
+            .get_or("analytics_experiments", "")
+            .trim()
+            .is_empty()
+}
+
+/// The cached page for `key`, or `render`'s output, stored for next time.
+/// `render` returning None (a 404) is never cached.
+pub fn get_or_render(
+    config: &Config,
+    key: &str,
+    render: impl FnOnce() -> Option<String>,
+) -> Option<String> {
+    if !config.enabled {
+        return render();
+    }
+    if let Some(html) = lookup(config, key) {
+        HITS.fetch_add(1, Ordering::Relaxed);
+        return Some(html);
+    }
+    MISSES.fetch_add(1, Ordering::Relaxed);
+    let generation = GENERATION.load(Ordering::Acquire);
+    let html = render()?;
+    store(config, key, &html, generation);
+    Some(html)
+}
+
+fn lookup(config: &Config, key: &str) -> Option<String> {
+    if let Ok(map) = entries().read() {
+        if let Some(e) = map.get(key) {
+            return (e.at.elapsed() < config.ttl).then(|| e.html.clone());
+        }
+    }
+    if !config.disk {
+        return None;
+    }
+    // Warm the memory cache from an entry written before a restart
+    let path = disk_path(key);
+    let age = std::fs::metadata(&path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| SystemTime::now().duration_since(t).ok())?;
+    if age >= config.ttl {
+        return None;
+    }
+    let html = std::fs::read_to_string(&path).ok()?;
+    if let Ok(mut map) = entries().write() {
+        make_room(&mut map, config);
+        map.insert(
+            key.to_string(),
+            Entry {
+                at: Instant::now().checked_sub(age).unwrap_or_else(Instant::now),
+                html: html.clone(),
+            },
+        );
+    }
+    Some(html)
+}
+
+fn store(config: &Config, key: &str, html: &str, generation: u64) {
+    let Ok(mut map) = entries().write() else {
+        return;
+    };
+    // Content changed while this page was rendering; it may already be stale
+    if GENERATION.load(Ordering::Acquire) != generation {
+        return;
+    }
+    make_room(&mut map, config);
+    map.insert(
+        key.to_string(),
+        Entry {
+            at: Instant::now(),
+            html: html.to_string(),
+        },
+    );
+    if config.disk {
+        let _ = std::fs::create_dir_all(DISK_DIR);
+        if let Err(e) = std::fs::write(disk_path(key), html) {
+            log::warn!("[page_cache] Could not write {}: {}", DISK_DIR, e);
+        }
+    }
+}
+
+/// Drop expired entries, then the oldest, until there's space for one more
+fn make_room(map: &mut HashMap<String, Entry>, config: &Config) {
+    if map.len() < config.max_entries {
+        return;
+    }
+    map.retain(|_, e| e.at.elapsed() < config.ttl);
+    while map.len() >= config.max_entries {
+        let Some(oldest) = map.iter().min_by_key(|(_, e)| e.at).map(|(k, _)| k.clone()) else {
+            break;
+        };
+        map.remove(&oldest);
+    }
+}
+
+fn disk_path(key: &str) -> PathBuf {
+    Path::new(DISK_DIR).join(format!(
+        "{}.html",
+        hex::encode(Sha256::digest(key.as_bytes()))
+    ))
+}
+
+/// Drop every cached page; the Store calls this after content or settings
+/// change.
+pub fn invalidate() {
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+    INVALIDATIONS.fetch_add(1, Ordering::Relaxed);
+    CHANGED_AT.store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
+    if let Ok(mut map) = entries().write() {
+        map.clear();
+    }
+    if Path::new(DISK_DIR).exists() {
+        let _ = std::fs::remove_dir_all(DISK_DIR);
+    }
+}
+
+/// Counter bumped by every invalidation. Caches of other generated output
+/// (sitemaps, feeds) compare it to tell whether they're stale.
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Acquire)
+}
+
+/// When content or settings last changed, as Unix time. Nothing before this
+/// process started is known, so until the first change it's the first time
+/// anyone asked.
+pub fn last_change() -> i64 {
+    let now = chrono::Utc::now().timestamp();
+    match CHANGED_AT.compare_exchange(0, now, Ordering::Relaxed, Ordering::Relaxed) {
+        Ok(_) => now,
+        Err(at) => at,
+    }
+}
+
+/// Settings that background tasks and sign-in flows keep; they never change
+/// what a public page shows
+fn is_bookkeeping(key: &str) -> bool {
+    const KEYS: &[&str] = &[
+        "analytics_daily_salt",
+        "analytics_report_last_sent",
+        "digest_last_sent",
+        "mta_dkim_generated_at",
+        "image_proxy_secret_old_expires",
+        "seo_pagerank_cache",
+        "seo_moz_cache",
+        "font_google_local_error",
+        "search_engine_last_sync",
+        "search_engine_last_error",
+    ];
+    KEYS.contains(&key)
+        || key.ends_with("_cursor")
+        || key.starts_with("mfa_")
+        || key.starts_with("passkey_reg_")
+}
+
+/// Invalidate after settings are written, unless pages never show any of them
+pub fn settings_changed<K: AsRef<str>>(keys: impl IntoIterator<Item = K>) {
+    if keys.into_iter().any(|k| !is_bookkeeping(k.as_ref())) {
+        invalidate();
+    }
+}
+
+/// Counters for the health page
+#[derive(Debug, Clone, Serialize)]
+pub struct Stats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    /// Share of lookups answered from the cache, 0–100
+    pub hit_rate: f64,
+    pub invalidations: u64,
+}
+
+pub fn stats() -> Stats {
+    let hits = HITS.load(Ordering::Relaxed);
+    let misses = MISSES.load(Ordering::Relaxed);
+    let total = hits + misses;
+    Stats {
+        entries: entries().read().map(|m| m.len()).unwrap_or(0),
+        hits,
+        misses,
+        hit_rate: if total == 0 {
+            0.0
+        } else {
+            (hits as f64 * 1000.0 / total as f64).round() / 10.0
+        },
+        invalidations: INVALIDATIONS.load(Ordering::Relaxed),
+    }
+}
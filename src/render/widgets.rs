@@ -0,0 +1,182 @@
+//! Widget areas: the blocks configured per design for the sidebar and
+//! footer regions of the page shell.
+
+use serde_json::Value;
+
+use super::{html_escape, slug_url};
+use crate::models::widget::Widget;
+use crate::store::Store;
+
+/// Rendered HTML for each widget area of a page
+#[derive(Debug, Default)]
+pub struct Areas {
+    pub sidebar: String,
+    pub footer: String,
+}
+
+/// Most items a recent posts or tag cloud widget will show
+const MAX_ITEMS: i64 = 50;
+
+pub const WIDGETS_CSS: &str = r#"
+.widget-area-sidebar { margin-top: 24px; }
+.widget-area-footer { display: grid; grid-template-columns: repeat(auto-fit, minmax(200px, 1fr)); gap: 24px; margin-bottom: 24px; text-align: left; }
+.widget { margin-bottom: 24px; font-size: 14px; }
+.widget-title { font-size: 13px; font-weight: 600; text-transform: uppercase; letter-spacing: .5px; margin: 0 0 10px; }
+.widget ul { list-style: none; margin: 0; padding: 0; }
+.widget li { margin: 0 0 6px; }
+.widget-tag-cloud a { display: inline-block; margin: 0 6px 4px 0; line-height: 1.4; }
+.widget-newsletter form { display: flex; gap: 6px; flex-wrap: wrap; }
+.widget-newsletter input[type=email] { flex: 1; min-width: 0; padding: 6px 8px; font: inherit; }
+.widget-newsletter .widget-hp { position: absolute; left: -9999px; }
+.widget-newsletter-msg { font-size: 12px; margin-top: 6px; }
+"#;
+
+/// Render every widget area of `design_id`
+pub fn render_areas(store: &dyn Store, design_id: i64, settings: &Value) -> Areas {
+    let widgets = store.widget_list(design_id);
+    if widgets.is_empty() {
+        return Areas::default();
+    }
+    Areas {
+        sidebar: render_area(store, &widgets, "sidebar", settings),
+        footer: render_area(store, &widgets, "footer", settings),
+    }
+}
+
+/// The widgets placed in `area`, in order, wrapped in the area container.
+/// Empty when nothing there has content to show.
+pub fn render_area(store: &dyn Store, widgets: &[Widget], area: &str, settings: &Value) -> String {
+    let inner: String = widgets
+        .iter()
+        .filter(|w| w.area == area)
+        .map(|w| render_widget(store, w, settings))
+        .collect();
+    if inner.is_empty() {
+        return String::new();
+    }
+    format!(
+        "<div class=\"widget-area widget-area-{}\">{}</div>",
+        area, inner
+    )
+}
+
+fn render_widget(store: &dyn Store, w: &Widget, settings: &Value) -> String {
+    let config = w.config_value();
+    let count = config
+        .get("count")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(5)
+        .clamp(1, MAX_ITEMS);
+    let body = match w.widget_type.as_str() {
+        "recent_posts" => recent_posts(store, count, settings),
+        "tag_cloud" => tag_cloud(store, count, settings),
+        "custom_html" => config
+            .get("html")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        "newsletter" => newsletter_form(&format!("widget-newsletter-{}", w.id), &config, settings),
+        "social_links" => super::build_social_links(settings),
+        _ => String::new(),
+    };
+    if body.trim().is_empty() {
+        return String::new();
+    }
+    let title = if w.title.is_empty() {
+        String::new()
+    } else {
+        format!("<h3 class=\"widget-title\">{}</h3>", html_escape(&w.title))
+    };
+    format!(
+        "<div class=\"widget widget-{}\">{}{}</div>",
+        w.widget_type.replace('_', "-"),
+        title,
+        body
+    )
+}
+
+fn blog_slug(settings: &Value) -> String {
+    settings
+        .get("blog_slug")
+        .and_then(|v| v.as_str())
+        .unwrap_or("journal")
+        .to_string()
+}
+
+fn recent_posts(store: &dyn Store, count: i64, settings: &Value) -> String {
+    let posts = store.post_list(Some("published"), count, 0);
+    if posts.is_empty() {
+        return String::new();
+    }
+    let blog_slug = blog_slug(settings);
+    let items: String = posts
+        .iter()
+        .map(|p| {
+            format!(
+                "<li><a href=\"{}\">{}</a></li>",
+                slug_url(&blog_slug, &p.slug),
+                html_escape(&p.title)
+            )
+        })
+        .collect();
+    format!("<ul>{}</ul>", items)
+}
+
+/// The `count` most used tags, alphabetically, sized by use
+fn tag_cloud(store: &dyn Store, count: i64, settings: &Value) -> String {
+    let mut tags: Vec<(String, String, i64)> = store
+        .tag_list()
+        .into_iter()
+        .map(|t| {
+            let n = store.tag_count_items(t.id);
+            (t.name, t.slug, n)
+        })
+        .filter(|(_, _, n)| *n > 0)
+        .collect();
+    if tags.is_empty() {
+        return String::new();
+    }
+    tags.sort_by(|a, b| b.2.cmp(&a.2));
+    tags.truncate(count as usize);
+    tags.sort_by(|a, b| a.0.to_lowercase().cmp(&b.0.to_lowercase()));
+    let max = tags.iter().map(|t| t.2).max().unwrap_or(1).max(1) as f64;
+    let blog_slug = blog_slug(settings);
+    tags.iter()
+        .map(|(name, slug, n)| {
+            // 0.8em for the least used up to 1.6em for the most
+            let size = 0.8 + 0.8 * (*n as f64 / max);
+            format!(
+                "<a href=\"{}\" style=\"font-size:{:.2}em\">{}</a>",
+                slug_url(&blog_slug, &format!("tag/{}", slug)),
+                size,
+                html_escape(name)
+            )
+        })
+        .collect()
+}
+
+/// Signup form posting to `/api/newsletter/subscribe`, with `id` as its
+/// element id. Hidden while newsletter sign-up is turned off.
+pub(crate) fn newsletter_form(id: &str, config: &Value, settings: &Value) -> String {
+    if settings.get("newsletter_enabled").and_then(|v| v.as_str()) != Some("true") {
+        return String::new();
+    }
+    let text = config.get("text").and_then(|v| v.as_str()).unwrap_or("");
+    let button = config
+        .get("button")
+        .and_then(|v| v.as_str())
+        .filter(|b| !b.is_empty())
+        .unwrap_or("Subscribe");
+    let intro = if text.is_empty() {
+        String::new()
+    } else {
+        format!("<p>{}</p>", html_escape(text))
+    };
+    crate::render::nonce_scripts(&format!(
+        r#"{intro}<form id="{id}"><input type="email" name="email" placeholder="you@example.com" required><input type="text" name="website" class="widget-hp" tabindex="-1" autocomplete="off"><button type="submit">{button}</button></form><div class="widget-newsletter-msg" id="{id}-msg"></div>
+<script>(function(){{var f=document.getElementById('{id}'),m=document.getElementById('{id}-msg');if(!f)return;f.addEventListener('submit',function(e){{e.preventDefault();fetch('/api/newsletter/subscribe',{{method:'POST',headers:{{'Content-Type':'application/json'}},body:JSON.stringify({{email:f.email.value,honeypot:f.website.value}})}}).then(function(r){{return r.json();}}).then(function(d){{m.textContent=d.success?d.message:(d.error||'Something went wrong');if(d.success)f.reset();}}).catch(function(){{m.textContent='Something went wrong';}});}});}})();</script>"#,
+        intro = intro,
+        id = id,
+        button = html_escape(button),
+    ))
+}
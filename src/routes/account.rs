@@ -0,0 +1,601 @@
+use rocket::form::Form;
+use rocket::http::{ContentType, CookieJar};
+use rocket::response::content::RawHtml;
+use rocket::response::Redirect;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::models::user::User;
+use crate::rate_limit::RateLimiter;
+use crate::render::{self, html_escape};
+use crate::routes::admin::api::StatsExport;
+use crate::security::auth::{self, AuthenticatedUser, ClientIp};
+use crate::security::headers::CspNonce;
+use crate::security::{self, email_change, members, password_policy, privacy};
+use crate::store::Store;
+
+// ── Member accounts ─────────────────────────────────────
+//
+// Public sign-up, sign-in and profile pages for visitor accounts. Every
+// route 404s while `membership_enabled` is off.
+
+type Page = Result<RawHtml<String>, Redirect>;
+
+const RATE_WINDOW: Duration = Duration::from_secs(15 * 60);
+
+fn message_html(message: Option<(&str, &str)>) -> String {
+    match message {
+        Some((kind, text)) => format!(
+            r#"<p class="form-message form-message-{}">{}</p>"#,
+            kind,
+            html_escape(text)
+        ),
+        None => String::new(),
+    }
+}
+
+fn page(store: &dyn Store, title: &str, body: &str) -> RawHtml<String> {
+    let settings = store.setting_all();
+    RawHtml(render::render_legal_page(store, &settings, title, body))
+}
+
+/// The signed-in member, if the session belongs to one
+fn member(store: &dyn Store, user: Option<AuthenticatedUser>) -> Option<User> {
+    user.map(|u| u.user)
+        .filter(|u| members::is_member(store, u))
+}
+
+/// Widget and script for the configured captcha provider. The token lands in
+/// the form's hidden `captcha_token` field on submit.
+fn captcha_snippet(store: &dyn Store, form_id: &str) -> String {
+    let info = match security::active_captcha(store) {
+        Some(i) if !i.site_key.is_empty() => i,
+        _ => return String::new(),
+    };
+    let key = html_escape(&info.site_key);
+    let (script, widget, get_token) = match (info.provider.as_str(), info.version.as_str()) {
+        ("recaptcha", "v3") => (
+            format!("https://www.google.com/recaptcha/api.js?render={}", key),
+            String::new(),
+            format!(
+                "function(){{return grecaptcha.execute('{}',{{action:'account'}})}}",
+                key
+            ),
+        ),
+        ("recaptcha", _) => (
+            "https://www.google.com/recaptcha/api.js".to_string(),
+            format!(r#"<div class="g-recaptcha" data-sitekey="{}"></div>"#, key),
+            "function(){return Promise.resolve(grecaptcha.getResponse())}".to_string(),
+        ),
+        ("turnstile", _) => (
+            "https://challenges.cloudflare.com/turnstile/v0/api.js".to_string(),
+            format!(r#"<div class="cf-turnstile" data-sitekey="{}"></div>"#, key),
+            "function(){var r=document.querySelector('[name=cf-turnstile-response]');return Promise.resolve(r?r.value:'')}".to_string(),
+        ),
+        ("hcaptcha", _) => (
+            "https://js.hcaptcha.com/1/api.js".to_string(),
+            format!(r#"<div class="h-captcha" data-sitekey="{}"></div>"#, key),
+            "function(){return Promise.resolve(hcaptcha.getResponse())}".to_string(),
+        ),
+        _ => return String::new(),
+    };
+    render::nonce_scripts(&format!(
+        "{widget}<script src=\"{script}\" async defer></script>\
+\n<script>\
+\n(function(){{\
+\nvar f=document.getElementById('{form_id}');\
+\nif(!f)return;\
+\nvar getToken={get_token};\
+\nf.addEventListener('submit',function(e){{\
+\n    var t=f.querySelector('[name=captcha_token]');\
+\n    if(t.value)return;\
+\n    e.preventDefault();\
+\n    getToken().then(function(v){{t.value=v||'';f.submit();}}).catch(function(){{f.submit();}});\
+\n}});\
+\n}})();\
+\n</script>",
+        widget = widget,
+        script = script,
+        form_id = form_id,
+        get_token = get_token,
+    ))
+}
+
+fn field<'a>(data: &'a HashMap<String, String>, name: &str) -> &'a str {
+    data.get(name).map(|v| v.trim()).unwrap_or("")
+}
+
+// ── Profile ─────────────────────────────────────────────
+
+fn profile_page(store: &dyn Store, user: &User, message: Option<(&str, &str)>) -> RawHtml<String> {
+    let body = format!(
+        r#"{message}
+<p>Signed in as <strong>{email}</strong>.</p>
+<form method="post" action="/account" class="account-form">
+    <input type="hidden" name="action" value="profile">
+    <label>Display name<br><input type="text" name="display_name" value="{name}" maxlength="60" required></label>
+    <button type="submit">Save</button>
+</form>
+<h2>Change email</h2>
+<form method="post" action="/account" class="account-form">
+    <input type="hidden" name="action" value="email">
+    <label>New email<br><input type="email" name="email" autocomplete="email" required></label>
+    <button type="submit">Send confirmation</button>
+</form>
+<h2>Change password</h2>
+<form method="post" action="/account" class="account-form">
+    <input type="hidden" name="action" value="password">
+    <label>Current password<br><input type="password" name="current_password" autocomplete="current-password" required></label>
+    <label>New password<br><input type="password" name="new_password" autocomplete="new-password" minlength="{min_length}" required></label>
+    <button type="submit">Change password</button>
+</form>
+<form method="post" action="/account/logout" class="account-form">
+    <button type="submit">Sign out</button>
+</form>
+<h2>Your data</h2>
+<p><a href="/account/export">Download your data</a> as a JSON file: your profile, sign-ins, comments, orders and subscriptions.</p>
+<form method="post" action="/account/erase" class="account-form">
+    <p>Deleting your account removes your personal data. Your comments stay, shown as &ldquo;Deleted user&rdquo;. This can't be undone.</p>
+    <label>Password<br><input type="password" name="password" autocomplete="current-password" required></label>
+    <button type="submit">Delete my account</button>
+</form>"#,
+        message = message_html(message),
+        email = html_escape(&user.email),
+        name = html_escape(&user.display_name),
+        min_length = password_policy::PasswordPolicy::load(store).min_length,
+    );
+    page(store, "Your account", &body)
+}
+
+#[get("/")]
+pub fn profile(
+    store: &State<Arc<dyn Store>>,
+    user: Option<AuthenticatedUser>,
+    nonce: CspNonce,
+) -> Option<Page> {
+    render::with_csp_nonce(nonce.0.as_deref(), || {
+        let s: &dyn Store = &**store.inner();
+        if !members::enabled(s) {
+            return None;
+        }
+        Some(match member(s, user) {
+            Some(u) => Ok(profile_page(s, &u, None)),
+            None => Err(Redirect::to("/account/login")),
+        })
+    })
+}
+
+#[post("/", data = "<form>")]
+pub fn profile_submit(
+    store: &State<Arc<dyn Store>>,
+    user: Option<AuthenticatedUser>,
+    form: Form<HashMap<String, String>>,
+    nonce: CspNonce,
+) -> Option<Page> {
+    render::with_csp_nonce(nonce.0.as_deref(), || {
+        let s: &dyn Store = &**store.inner();
+        if !members::enabled(s) {
+            return None;
+        }
+        let user = match member(s, user) {
+            Some(u) => u,
+            None => return Some(Err(Redirect::to("/account/login"))),
+        };
+        let data = form.into_inner();
+
+        let result = match field(&data, "action") {
+            "email" => email_change::request(s, &user, field(&data, "email")).map(|_| {
+                "Check the new address for a link to confirm it. Until then your current address stays in use."
+            }),
+            "password" => {
+                let current = data
+                    .get("current_password")
+                    .map(String::as_str)
+                    .unwrap_or("");
+                let new = data.get("new_password").map(String::as_str).unwrap_or("");
+                if !auth::verify_password(current, &user.password_hash) {
+                    Err("Current password is incorrect".to_string())
+                } else {
+                    password_policy::check(s, new, &[&user.email, &user.display_name])
+                        .and_then(|_| auth::hash_password_for(s, new))
+                        .and_then(|hash| s.user_update_password(user.id, &hash))
+                        .map(|_| "Password changed")
+                }
+            }
+            _ => {
+                let name = field(&data, "display_name");
+                members::validate_display_name(name)
+                    .and_then(|_| s.user_update_profile(user.id, name, &user.email, &user.avatar))
+                    .map(|_| "Profile saved")
+            }
+        };
+
+        let user = s.user_get_by_id(user.id).unwrap_or(user);
+        Some(Ok(match result {
+            Ok(msg) => profile_page(s, &user, Some(("success", msg))),
+            Err(e) => profile_page(s, &user, Some(("error", e.as_str()))),
+        }))
+    })
+}
+
+// ── Personal data ───────────────────────────────────────
+
+#[get("/export")]
+pub fn export(
+    store: &State<Arc<dyn Store>>,
+    user: Option<AuthenticatedUser>,
+) -> Option<Result<StatsExport, Redirect>> {
+    let s: &dyn Store = &**store.inner();
+    if !members::enabled(s) {
+        return None;
+    }
+    let user = match member(s, user) {
+        Some(u) => u,
+        None => return Some(Err(Redirect::to("/account/login"))),
+    };
+    Some(Ok(StatsExport {
+        filename: "my-data.json".to_string(),
+        content_type: ContentType::JSON,
+        body: serde_json::to_string_pretty(&privacy::export(s, &user)).unwrap_or_default(),
+    }))
+}
+
+#[post("/erase", data = "<form>")]
+pub fn erase(
+    store: &State<Arc<dyn Store>>,
+    user: Option<AuthenticatedUser>,
+    cookies: &CookieJar<'_>,
+    form: Form<HashMap<String, String>>,
+    nonce: CspNonce,
+) -> Option<Page> {
+    render::with_csp_nonce(nonce.0.as_deref(), || {
+        let s: &dyn Store = &**store.inner();
+        if !members::enabled(s) {
+            return None;
+        }
+        let user = match member(s, user) {
+            Some(u) => u,
+            None => return Some(Err(Redirect::to("/account/login"))),
+        };
+        let password = form.get("password").map(String::as_str).unwrap_or("");
+        if !auth::verify_password(password, &user.password_hash) {
+            return Some(Ok(profile_page(
+                s,
+                &user,
+                Some(("error", "Password is incorrect")),
+            )));
+        }
+        if let Err(e) = privacy::erase(s, &user) {
+            log::error!("[account] Could not erase account {}: {}", user.id, e);
+            return Some(Ok(profile_page(
+                s,
+                &user,
+                Some((
+                    "error",
+                    "Could not delete your account. Please try again later.",
+                )),
+            )));
+        }
+        s.audit_log(
+            None,
+            None,
+            "erase",
+            Some("user"),
+            Some(user.id),
+            Some(&format!("User #{}", user.id)),
+            Some("Erased by the member from their account page"),
+            None,
+        );
+        auth::clear_session_cookie(cookies);
+        Some(Ok(page(
+            s,
+            "Account deleted",
+            "<p>Your account and personal data have been deleted.</p>",
+        )))
+    })
+}
+
+// ── Registration ────────────────────────────────────────
+
+fn register_page(
+    store: &dyn Store,
+    message: Option<(&str, &str)>,
+    email: &str,
+    name: &str,
+) -> RawHtml<String> {
+    let body = format!(
+        r#"{message}
+<form method="post" action="/account/register" id="account-register" class="account-form">
+    <label>Display name<br><input type="text" name="display_name" value="{name}" maxlength="60" required></label>
+    <label>Email<br><input type="email" name="email" value="{email}" autocomplete="email" required></label>
+    <label>Password<br><input type="password" name="password" autocomplete="new-password" minlength="{min_length}" required></label>
+    <div style="display:none"><input type="text" name="_honey" tabindex="-1" autocomplete="off"></div>
+    <input type="hidden" name="captcha_token" value="">
+    {captcha}
+    <button type="submit">Create account</button>
+</form>
+<p>Already have an account? <a href="/account/login">Sign in</a></p>"#,
+        message = message_html(message),
+        name = html_escape(name),
+        email = html_escape(email),
+        captcha = captcha_snippet(store, "account-register"),
+        min_length = password_policy::PasswordPolicy::load(store).min_length,
+    );
+    page(store, "Create an account", &body)
+}
+
+const REGISTERED: &str =
+    "Check your inbox for a link to confirm your email address and activate your account.";
+
+#[get("/register")]
+pub fn register(store: &State<Arc<dyn Store>>, nonce: CspNonce) -> Option<RawHtml<String>> {
+    render::with_csp_nonce(nonce.0.as_deref(), || {
+        let s: &dyn Store = &**store.inner();
+        if !members::enabled(s) {
+            return None;
+        }
+        Some(register_page(s, None, "", ""))
+    })
+}
+
+#[post("/register", data = "<form>")]
+pub fn register_submit(
+    store: &State<Arc<dyn Store>>,
+    limiter: &State<RateLimiter>,
+    client_ip: ClientIp,
+    form: Form<HashMap<String, String>>,
+    nonce: CspNonce,
+) -> Option<RawHtml<String>> {
+    render::with_csp_nonce(nonce.0.as_deref(), || {
+        let s: &dyn Store = &**store.inner();
+        if !members::enabled(s) {
+            return None;
+        }
+        let data = form.into_inner();
+        let email = field(&data, "email");
+        let name = field(&data, "display_name");
+        let password = data.get("password").map(String::as_str).unwrap_or("");
+        let error = |msg: &str| Some(register_page(s, Some(("error", msg)), email, name));
+
+        // Bots filling the hidden field get the normal success message
+        if !field(&data, "_honey").is_empty() {
+            return Some(register_page(s, Some(("success", REGISTERED)), "", ""));
+        }
+
+        let rate_key = format!("register:{}", auth::hash_ip(&client_ip.0));
+        if !limiter.check(s, &rate_key, 5, RATE_WINDOW) {
+            return error("Too many sign-up attempts. Please try again later.");
+        }
+
+        let token = field(&data, "captcha_token");
+        if !token.is_empty() {
+            match security::verify_captcha(s, token, Some(&client_ip.0)) {
+                Ok(false) => return error("Captcha verification failed. Please try again."),
+                Err(e) => log::warn!("[account] Captcha error (allowing): {}", e),
+                _ => {}
+            }
+        } else if security::has_captcha_provider(s) {
+            return error("Please complete the captcha.");
+        }
+
+        let site_url = s.setting_get_or("site_url", "http://localhost:8000");
+        match security::check_spam(
+            s,
+            &site_url,
+            &client_ip.0,
+            "",
+            name,
+            Some(name),
+            Some(email),
+        ) {
+            Ok(true) => return error("This sign-up was flagged as spam."),
+            Err(e) => log::warn!("[account] Spam check error (allowing): {}", e),
+            _ => {}
+        }
+
+        match members::register(s, email, name, password) {
+            Ok(Some(id)) => {
+                s.audit_log(
+                    Some(id),
+                    Some(name),
+                    "register",
+                    Some("user"),
+                    Some(id),
+                    Some(email),
+                    Some("Member account created; awaiting email confirmation"),
+                    None,
+                );
+                Some(register_page(s, Some(("success", REGISTERED)), "", ""))
+            }
+            Ok(None) => Some(register_page(s, Some(("success", REGISTERED)), "", "")),
+            Err(e) => error(&e),
+        }
+    })
+}
+
+#[get("/verify?<token>")]
+pub fn verify(
+    store: &State<Arc<dyn Store>>,
+    token: &str,
+    nonce: CspNonce,
+) -> Option<RawHtml<String>> {
+    render::with_csp_nonce(nonce.0.as_deref(), || {
+        let s: &dyn Store = &**store.inner();
+        if !members::enabled(s) {
+            return None;
+        }
+        let body = match members::verify(s, token) {
+            Ok(_) => {
+                message_html(Some((
+                    "success",
+                    "Your email address is confirmed and your account is active.",
+                ))) + r#"<p><a href="/account/login">Sign in</a></p>"#
+            }
+            Err(_) => message_html(Some((
+                "error",
+                "This confirmation link is invalid or has expired.",
+            ))),
+        };
+        Some(page(s, "Confirm your account", &body))
+    })
+}
+
+/// Confirmation link for a new email address. Staff use it too, so it works
+/// whether or not membership is enabled.
+#[get("/confirm-email?<token>")]
+pub fn confirm_email(
+    store: &State<Arc<dyn Store>>,
+    token: &str,
+    nonce: CspNonce,
+) -> RawHtml<String> {
+    render::with_csp_nonce(nonce.0.as_deref(), || {
+        let s: &dyn Store = &**store.inner();
+        let body = match email_change::confirm(s, token) {
+            Ok(user) => message_html(Some((
+                "success",
+                &format!("Your email address is now {}.", user.email),
+            ))),
+            Err(e) => message_html(Some(("error", e.as_str()))),
+        };
+        page(s, "Confirm your email address", &body)
+    })
+}
+
+// ── Sign in / out ───────────────────────────────────────
+
+fn login_page(store: &dyn Store, message: Option<(&str, &str)>, email: &str) -> RawHtml<String> {
+    let body = format!(
+        r#"{message}
+<form method="post" action="/account/login" id="account-login" class="account-form">
+    <label>Email<br><input type="email" name="email" value="{email}" autocomplete="email" required></label>
+    <label>Password<br><input type="password" name="password" autocomplete="current-password" required></label>
+    <input type="hidden" name="captcha_token" value="">
+    {captcha}
+    <button type="submit">Sign in</button>
+</form>
+<p>No account yet? <a href="/account/register">Create one</a></p>"#,
+        message = message_html(message),
+        email = html_escape(email),
+        captcha = captcha_snippet(store, "account-login"),
+    );
+    page(store, "Sign in", &body)
+}
+
+#[get("/login")]
+pub fn login(
+    store: &State<Arc<dyn Store>>,
+    user: Option<AuthenticatedUser>,
+    nonce: CspNonce,
+) -> Option<Page> {
+    render::with_csp_nonce(nonce.0.as_deref(), || {
+        let s: &dyn Store = &**store.inner();
+        if !members::enabled(s) {
+            return None;
+        }
+        Some(match member(s, user) {
+            Some(_) => Err(Redirect::to("/account")),
+            None => Ok(login_page(s, None, "")),
+        })
+    })
+}
+
+#[post("/login", data = "<form>")]
+pub fn login_submit(
+    store: &State<Arc<dyn Store>>,
+    limiter: &State<RateLimiter>,
+    cookies: &CookieJar<'_>,
+    client_ip: ClientIp,
+    form: Form<HashMap<String, String>>,
+    nonce: CspNonce,
+) -> Option<Page> {
+    render::with_csp_nonce(nonce.0.as_deref(), || {
+        let s: &dyn Store = &**store.inner();
+        if !members::enabled(s) {
+            return None;
+        }
+        let data = form.into_inner();
+        let email = field(&data, "email");
+        let error = |msg: &str| Some(Ok(login_page(s, Some(("error", msg)), email)));
+
+        let rate_key = format!("member-login:{}", auth::hash_ip(&client_ip.0));
+        let max_attempts = s.setting_get_i64("login_rate_limit").max(1) as u64;
+        if !limiter.check(s, &rate_key, max_attempts, RATE_WINDOW) {
+            return error("Too many sign-in attempts. Please try again in 15 minutes.");
+        }
+
+        let token = field(&data, "captcha_token");
+        if !token.is_empty() {
+            match security::verify_captcha(s, token, Some(&client_ip.0)) {
+                Ok(false) => return error("Captcha verification failed. Please try again."),
+                Err(e) => log::warn!("[account] Captcha error (allowing): {}", e),
+                _ => {}
+            }
+        } else if security::has_captcha_provider(s) {
+            return error("Please complete the captcha.");
+        }
+
+        let password = data.get("password").map(String::as_str).unwrap_or("");
+        let user = match members::authenticate(s, email, password) {
+            Ok(u) => u,
+            Err(e) => return error(&e),
+        };
+        let _ = s.user_touch_last_login(user.id);
+        match auth::create_session(s, user.id, None, None) {
+            Ok(sid) => {
+                auth::set_session_cookie_secure(cookies, &sid, s);
+                Some(Err(Redirect::to("/account")))
+            }
+            Err(e) => {
+                log::error!("[account] Could not create session: {}", e);
+                error("Could not sign you in. Please try again.")
+            }
+        }
+    })
+}
+
+#[post("/logout")]
+pub fn logout(store: &State<Arc<dyn Store>>, cookies: &CookieJar<'_>) -> Option<Redirect> {
+    let s: &dyn Store = &**store.inner();
+    if !members::enabled(s) {
+        return None;
+    }
+    if let Some(sid) = auth::session_id(cookies) {
+        let _ = auth::destroy_session(s, &sid);
+    }
+    auth::clear_session_cookie(cookies);
+    Some(Redirect::to("/"))
+}
+
+/// Who is signed in, for the comment form and theme widgets
+#[get("/me")]
+pub fn me(store: &State<Arc<dyn Store>>, user: Option<AuthenticatedUser>) -> Option<Json<Value>> {
+    let s: &dyn Store = &**store.inner();
+    if !members::enabled(s) {
+        return None;
+    }
+    Some(Json(match member(s, user) {
+        Some(u) => json!({ "signed_in": true, "display_name": u.display_name }),
+        None => json!({ "signed_in": false }),
+    }))
+}
+
+pub fn routes() -> Vec<rocket::Route> {
+    routes![
+        profile,
+        profile_submit,
+        register,
+        register_submit,
+        verify,
+        confirm_email,
+        export,
+        erase,
+        login,
+        login_submit,
+        logout,
+        me
+    ]
+}
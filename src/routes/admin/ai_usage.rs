@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use rocket::State;
+use rocket_dyn_templates::Template;
+use serde_json::json;
+
+use crate::ai::usage;
+use crate::security::auth::SettingsManager;
+use crate::store::Store;
+use crate::AdminSlug;
+
+// ── AI Usage & Cost ────────────────────────────────────
+
+#[get("/ai-usage?<month>")]
+pub fn ai_usage_page(
+    _admin: SettingsManager,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    month: Option<String>,
+) -> Template {
+    let this_month = chrono::Utc::now().format("%Y-%m").to_string();
+    let month = month
+        .filter(|m| usage::month_bounds(m).is_some())
+        .unwrap_or_else(|| this_month.clone());
+
+    let shift = |months: i32| {
+        chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+            .ok()
+            .and_then(|d| {
+                if months < 0 {
+                    d.checked_sub_months(chrono::Months::new(months.unsigned_abs()))
+                } else {
+                    d.checked_add_months(chrono::Months::new(months as u32))
+                }
+            })
+            .map(|d| d.format("%Y-%m").to_string())
+    };
+    let next_month = shift(1).filter(|m| *m <= this_month);
+
+    let report = usage::month_report(&**store.inner(), &month);
+    let sections = json!([
+        { "label": "Provider", "rows": report.by_provider },
+        { "label": "Feature", "rows": report.by_feature },
+        { "label": "Model", "rows": report.by_model },
+    ]);
+
+    let context = json!({
+        "page_title": "AI Usage",
+        "admin_slug": slug.get(),
+        "settings": store.setting_all(),
+        "report": report,
+        "sections": sections,
+        "recent": store.ai_usage_recent(50),
+        "month": month,
+        "prev_month": shift(-1),
+        "next_month": next_month,
+    });
+    Template::render("admin/ai_usage", &context)
+}
@@ -1,15 +1,26 @@
 use std::sync::Arc;
+use std::time::Duration;
 
+use rocket::http::{ContentType, Header};
+use rocket::request::Request;
+use rocket::response::stream::{Event, EventStream};
+use rocket::response::{self, Responder};
 use rocket::serde::json::Json;
-use rocket::State;
+use rocket::tokio::select;
+use rocket::tokio::time::interval;
+use rocket::{Shutdown, State};
 use serde_json::Value;
 
-use crate::security::auth::{AdminUser, EditorUser};
+use crate::analytics::LiveVisitors;
+use crate::security::auth::{
+    AnalyticsViewer, DashboardUser, DeployManager, MailManager, SeoManager, SettingsManager,
+};
+use crate::security::sudo::SudoMode;
 use crate::store::Store;
 
 #[get("/stats/overview?<from>&<to>")]
 pub fn stats_overview(
-    _admin: EditorUser,
+    _admin: AnalyticsViewer,
     store: &State<Arc<dyn Store>>,
     from: Option<String>,
     to: Option<String>,
@@ -22,7 +33,7 @@ pub fn stats_overview(
 
 #[get("/stats/flow?<from>&<to>")]
 pub fn stats_flow(
-    _admin: EditorUser,
+    _admin: AnalyticsViewer,
     store: &State<Arc<dyn Store>>,
     from: Option<String>,
     to: Option<String>,
@@ -35,7 +46,7 @@ pub fn stats_flow(
 
 #[get("/stats/geo?<from>&<to>")]
 pub fn stats_geo(
-    _admin: EditorUser,
+    _admin: AnalyticsViewer,
     store: &State<Arc<dyn Store>>,
     from: Option<String>,
     to: Option<String>,
@@ -48,7 +59,7 @@ pub fn stats_geo(
 
 #[get("/stats/stream?<from>&<to>")]
 pub fn stats_stream(
-    _admin: EditorUser,
+    _admin: AnalyticsViewer,
     store: &State<Arc<dyn Store>>,
     from: Option<String>,
     to: Option<String>,
@@ -61,7 +72,7 @@ pub fn stats_stream(
 
 #[get("/stats/calendar?<from>&<to>")]
 pub fn stats_calendar(
-    _admin: EditorUser,
+    _admin: AnalyticsViewer,
     store: &State<Arc<dyn Store>>,
     from: Option<String>,
     to: Option<String>,
@@ -74,7 +85,7 @@ pub fn stats_calendar(
 
 #[get("/stats/top-portfolio?<from>&<to>&<limit>")]
 pub fn stats_top_portfolio(
-    _admin: EditorUser,
+    _admin: AnalyticsViewer,
     store: &State<Arc<dyn Store>>,
     from: Option<String>,
     to: Option<String>,
@@ -89,7 +100,7 @@ pub fn stats_top_portfolio(
 
 #[get("/stats/top-referrers?<from>&<to>&<limit>")]
 pub fn stats_top_referrers(
-    _admin: EditorUser,
+    _admin: AnalyticsViewer,
     store: &State<Arc<dyn Store>>,
     from: Option<String>,
     to: Option<String>,
@@ -102,15 +113,215 @@ pub fn stats_top_referrers(
     Json(serde_json::to_value(data).unwrap_or_default())
 }
 
+#[get("/stats/campaigns?<from>&<to>&<limit>")]
+pub fn stats_campaigns(
+    _admin: AnalyticsViewer,
+    store: &State<Arc<dyn Store>>,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<i64>,
+) -> Json<Value> {
+    let from = from.unwrap_or_else(|| "2000-01-01".to_string());
+    let to = to.unwrap_or_else(|| "2099-12-31".to_string());
+    let limit = limit.unwrap_or(20);
+    let data = store.analytics_campaigns(&from, &to, limit);
+    Json(serde_json::to_value(data).unwrap_or_default())
+}
+
+/// Views per UTM source, medium, campaign, term or content.
+#[get("/stats/utm/<dimension>?<from>&<to>&<limit>")]
+pub fn stats_utm(
+    _admin: AnalyticsViewer,
+    store: &State<Arc<dyn Store>>,
+    dimension: &str,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<i64>,
+) -> Json<Value> {
+    if !crate::models::analytics::UTM_DIMENSIONS.contains(&dimension) {
+        return Json(serde_json::json!({"ok": false, "error": "Unknown UTM dimension"}));
+    }
+    let from = from.unwrap_or_else(|| "2000-01-01".to_string());
+    let to = to.unwrap_or_else(|| "2099-12-31".to_string());
+    let limit = limit.unwrap_or(10);
+    let data = store.analytics_utm_breakdown(dimension, &from, &to, limit);
+    Json(serde_json::to_value(data).unwrap_or_default())
+}
+
+#[get("/stats/goals?<from>&<to>")]
+pub fn stats_goals(
+    _admin: AnalyticsViewer,
+    store: &State<Arc<dyn Store>>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Json<Value> {
+    let from = from.unwrap_or_else(|| "2000-01-01".to_string());
+    let to = to.unwrap_or_else(|| "2099-12-31".to_string());
+    let data = crate::analytics::goal_report(&**store.inner(), &from, &to);
+    Json(serde_json::to_value(data).unwrap_or_default())
+}
+
+#[get("/stats/goals/daily?<goal>&<from>&<to>")]
+pub fn stats_goal_daily(
+    _admin: AnalyticsViewer,
+    store: &State<Arc<dyn Store>>,
+    goal: &str,
+    from: Option<String>,
+    to: Option<String>,
+) -> Json<Value> {
+    let from = from.unwrap_or_else(|| "2000-01-01".to_string());
+    let to = to.unwrap_or_else(|| "2099-12-31".to_string());
+    let data = store.goal_daily(goal, &from, &to);
+    Json(serde_json::to_value(data).unwrap_or_default())
+}
+
+/// A/B experiments with per-variant click-through and significance.
+#[get("/stats/experiments?<from>&<to>")]
+pub fn stats_experiments(
+    _admin: AnalyticsViewer,
+    store: &State<Arc<dyn Store>>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Json<Value> {
+    let from = from.unwrap_or_else(|| "2000-01-01".to_string());
+    let to = to.unwrap_or_else(|| "2099-12-31".to_string());
+    let data = crate::analytics::experiment_report(&**store.inner(), &from, &to);
+    Json(serde_json::to_value(data).unwrap_or_default())
+}
+
+#[get("/stats/outbound?<from>&<to>&<limit>")]
+pub fn stats_outbound(
+    _admin: AnalyticsViewer,
+    store: &State<Arc<dyn Store>>,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<i64>,
+) -> Json<Value> {
+    let from = from.unwrap_or_else(|| "2000-01-01".to_string());
+    let to = to.unwrap_or_else(|| "2099-12-31".to_string());
+    let limit = limit.unwrap_or(10);
+    let data = store.outbound_top_links(&from, &to, limit);
+    Json(serde_json::to_value(data).unwrap_or_default())
+}
+
+/// Most frequent site searches, and those that found nothing.
+#[get("/stats/searches?<from>&<to>&<limit>")]
+pub fn stats_searches(
+    _admin: AnalyticsViewer,
+    store: &State<Arc<dyn Store>>,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<i64>,
+) -> Json<Value> {
+    let from = from.unwrap_or_else(|| "2000-01-01".to_string());
+    let to = to.unwrap_or_else(|| "2099-12-31".to_string());
+    let limit = limit.unwrap_or(10);
+    Json(serde_json::json!({
+        "top": store.search_top_queries(&from, &to, limit),
+        "zero_results": store.search_zero_result_queries(&from, &to, limit),
+    }))
+}
+
+pub struct StatsExport {
+    pub filename: String,
+    pub content_type: ContentType,
+    pub body: String,
+}
+
+impl<'r> Responder<'r, 'static> for StatsExport {
+    fn respond_to(self, _req: &'r Request<'_>) -> response::Result<'static> {
+        rocket::Response::build()
+            .header(self.content_type)
+            .header(Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", self.filename),
+            ))
+            .sized_body(self.body.len(), std::io::Cursor::new(self.body))
+            .ok()
+    }
+}
+
+/// Download an analytics dataset (overview, geo, referrers or calendar) as
+/// CSV or JSON. `from`/`to` are YYYY-MM-DD and inclusive.
+#[get("/stats/export?<dataset>&<format>&<from>&<to>")]
+pub fn stats_export(
+    _admin: AnalyticsViewer,
+    store: &State<Arc<dyn Store>>,
+    dataset: &str,
+    format: Option<&str>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Option<StatsExport> {
+    let from = from
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| "2000-01-01".to_string());
+    let to = to
+        .filter(|d| !d.is_empty())
+        .unwrap_or_else(|| "2099-12-31".to_string());
+    // A bare end date covers the whole of that day
+    let to_bound = if to.len() == 10 {
+        format!("{} 23:59:59", to)
+    } else {
+        to.clone()
+    };
+    let s: &dyn Store = &**store.inner();
+    let day = |d: &str| -> String {
+        d.chars()
+            .filter(|c| c.is_ascii_digit() || *c == '-')
+            .collect()
+    };
+    let stem = format!("analytics-{}-{}-to-{}", dataset, day(&from), day(&to));
+    match format {
+        Some("json") => {
+            let data = crate::analytics::export_json(s, dataset, &from, &to_bound)?;
+            Some(StatsExport {
+                filename: format!("{}.json", stem),
+                content_type: ContentType::JSON,
+                body: serde_json::to_string_pretty(&data).unwrap_or_default(),
+            })
+        }
+        _ => Some(StatsExport {
+            filename: format!("{}.csv", stem),
+            content_type: ContentType::CSV,
+            body: crate::analytics::export_csv(s, dataset, &from, &to_bound)?,
+        }),
+    }
+}
+
+/// Visitors, pages and referrers of the last five minutes, from memory.
+#[get("/stats/now")]
+pub fn stats_now(_admin: AnalyticsViewer, live: &State<LiveVisitors>) -> Json<Value> {
+    Json(serde_json::to_value(live.snapshot()).unwrap_or_default())
+}
+
+/// `stats_now` pushed every few seconds as server-sent events.
+#[get("/stats/live")]
+pub fn stats_live<'a>(
+    _admin: AnalyticsViewer,
+    live: &'a State<LiveVisitors>,
+    mut shutdown: Shutdown,
+) -> EventStream![Event + 'a] {
+    EventStream! {
+        let mut tick = interval(Duration::from_secs(5));
+        loop {
+            select! {
+                _ = tick.tick() => {}
+                _ = &mut shutdown => break,
+            }
+            yield Event::json(&live.snapshot()).event("snapshot");
+        }
+    }
+}
+
 #[get("/stats/tags")]
-pub fn stats_tags(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn stats_tags(_admin: AnalyticsViewer, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let data = store.analytics_tag_relations();
     Json(serde_json::to_value(data).unwrap_or_default())
 }
 
 #[post("/theme", data = "<body>")]
 pub fn set_theme(
-    _admin: EditorUser,
+    _admin: DashboardUser,
     store: &State<Arc<dyn Store>>,
     body: Json<Value>,
 ) -> Json<Value> {
@@ -132,11 +343,12 @@ fn seo_check_item(
     excerpt: Option<&str>,
     content_html: Option<&str>,
     featured_image: Option<&str>,
+    focus_keyword: Option<&str>,
     content_id: i64,
 ) -> Value {
     let mut checks: Vec<Value> = Vec::new();
     let mut score: u32 = 0;
-    let total: u32 = 10;
+    let mut total: u32 = 8;
 
     // 1. Meta title
     let mt = meta_title.unwrap_or("");
@@ -222,22 +434,7 @@ fn seo_check_item(
         }
     }
 
-    // 8. Image alt text in content (check for <img without alt)
-    if html.contains("<img") {
-        let missing_alt =
-            html.contains("alt=\"\"") || (html.contains("<img") && !html.contains("alt="));
-        if missing_alt {
-            checks.push(serde_json::json!({"check": "Image Alt Text", "status": "warn", "message": "Some images may be missing alt text. Alt text improves accessibility and SEO."}));
-        } else {
-            checks.push(serde_json::json!({"check": "Image Alt Text", "status": "pass", "message": "Images have alt text."}));
-            score += 1;
-        }
-    } else {
-        checks.push(serde_json::json!({"check": "Image Alt Text", "status": "pass", "message": "No inline images to check."}));
-        score += 1;
-    }
-
-    // 9. Tags
+    // 8. Tags
     let tags = store.tag_for_content(content_id, content_type);
     if tags.is_empty() {
         checks.push(serde_json::json!({"check": "Tags", "status": "warn", "message": "No tags assigned. Tags help with internal linking and discovery."}));
@@ -246,7 +443,7 @@ fn seo_check_item(
         score += 1;
     }
 
-    // 10. Heading structure (H1 in content — should not have H1 since title is H1)
+    // 9. Heading structure (H1 in content — should not have H1 since title is H1)
     if html.contains("<h1") {
         checks.push(serde_json::json!({"check": "Heading Structure", "status": "warn", "message": "Content contains an H1 tag. The page title is already H1 — use H2+ in content."}));
     } else {
@@ -254,6 +451,25 @@ fn seo_check_item(
         score += 1;
     }
 
+    // Content analysis: keyword, heading order, readability, links, alt text
+    let analysis = crate::seo::audit::content_checklist(&crate::seo::audit::SeoInput {
+        title,
+        slug,
+        meta_title: mt,
+        meta_description: md,
+        body_html: html,
+        featured_image: fi,
+        content_type,
+        focus_keyword: focus_keyword.unwrap_or(""),
+    });
+    for check in analysis {
+        total += 1;
+        if check.status == "pass" {
+            score += 1;
+        }
+        checks.push(serde_json::json!(check));
+    }
+
     let grade = match score * 100 / total {
         90..=100 => "A",
         70..=89 => "B",
@@ -271,7 +487,7 @@ fn seo_check_item(
 }
 
 #[get("/seo-check/post/<id>")]
-pub fn seo_check_post(_admin: EditorUser, store: &State<Arc<dyn Store>>, id: i64) -> Json<Value> {
+pub fn seo_check_post(_admin: SeoManager, store: &State<Arc<dyn Store>>, id: i64) -> Json<Value> {
     let post = match store.post_find_by_id(id) {
         Some(p) => p,
         None => return Json(serde_json::json!({"error": "Post not found"})),
@@ -287,13 +503,14 @@ pub fn seo_check_post(_admin: EditorUser, store: &State<Arc<dyn Store>>, id: i64
         post.excerpt.as_deref(),
         Some(&post.content_html),
         post.featured_image.as_deref(),
+        post.focus_keyword.as_deref(),
         post.id,
     ))
 }
 
 #[get("/seo-check/portfolio/<id>")]
 pub fn seo_check_portfolio(
-    _admin: EditorUser,
+    _admin: SeoManager,
     store: &State<Arc<dyn Store>>,
     id: i64,
 ) -> Json<Value> {
@@ -312,6 +529,7 @@ pub fn seo_check_portfolio(
         None,
         item.description_html.as_deref(),
         Some(&item.image_path),
+        item.focus_keyword.as_deref(),
         item.id,
     ))
 }
@@ -319,7 +537,10 @@ pub fn seo_check_portfolio(
 /// Rotate the image proxy HMAC secret key.
 /// Copies current → old (with expiry), generates a new current key.
 #[post("/rotate-image-proxy-key")]
-pub fn rotate_image_proxy_key(admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn rotate_image_proxy_key(
+    admin: SettingsManager,
+    store: &State<Arc<dyn Store>>,
+) -> Json<Value> {
     use rand::Rng;
 
     let current_secret = store.setting_get_or("image_proxy_secret", "");
@@ -371,7 +592,7 @@ pub fn rotate_image_proxy_key(admin: AdminUser, store: &State<Arc<dyn Store>>) -
 
 /// Lightweight endpoint for sidebar widget — returns average SEO score
 #[get("/seo-score")]
-pub fn seo_score_summary(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn seo_score_summary(_admin: SeoManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let posts = store.post_list(None, 10000, 0);
     let items = store.portfolio_list(None, 10000, 0);
     let mut sum: i64 = 0;
@@ -397,7 +618,7 @@ pub fn seo_score_summary(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> J
 
 /// Rescan SEO scores for all posts and portfolio items
 #[post("/seo-rescan")]
-pub fn seo_rescan_all(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn seo_rescan_all(_admin: SeoManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let mut scanned = 0i32;
 
     // Score all posts
@@ -411,6 +632,7 @@ pub fn seo_rescan_all(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> Json
             body_html: &post.content_html,
             featured_image: post.featured_image.as_deref().unwrap_or(""),
             content_type: "post",
+            focus_keyword: post.focus_keyword.as_deref().unwrap_or(""),
         };
         let audit = crate::seo::audit::compute_score(&input);
         let _ = store.post_update_seo_score(
@@ -432,6 +654,7 @@ pub fn seo_rescan_all(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> Json
             body_html: item.description_html.as_deref().unwrap_or(""),
             featured_image: &item.image_path,
             content_type: "portfolio",
+            focus_keyword: item.focus_keyword.as_deref().unwrap_or(""),
         };
         let audit = crate::seo::audit::compute_score(&input);
         let _ = store.portfolio_update_seo_score(
@@ -456,10 +679,108 @@ pub fn seo_rescan_all(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> Json
     Json(serde_json::json!({ "ok": true, "scanned": scanned }))
 }
 
+/// Ask the AI for rewrite suggestions for one post or portfolio item
+#[post("/seo-ai/<content_type>/<id>")]
+pub fn seo_ai_suggest(
+    _admin: SeoManager,
+    store: &State<Arc<dyn Store>>,
+    content_type: &str,
+    id: i64,
+) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    if !crate::ai::is_enabled(s) {
+        return Json(serde_json::json!({"ok": false, "error": "No AI provider is enabled"}));
+    }
+    match crate::seo::rewrite::suggest(s, content_type, id) {
+        Ok(suggestions) => Json(serde_json::json!({"ok": true, "suggestions": suggestions})),
+        Err(e) => Json(serde_json::json!({"ok": false, "error": e})),
+    }
+}
+
+/// Suggestions for the lowest-scoring items that have none pending, a few at a time
+#[post("/seo-ai/low-scoring")]
+pub fn seo_ai_suggest_low_scoring(
+    _admin: SeoManager,
+    store: &State<Arc<dyn Store>>,
+) -> Json<Value> {
+    use crate::seo::rewrite::{BATCH_SIZE, LOW_SCORE};
+    let s: &dyn Store = &**store.inner();
+    if !crate::ai::is_enabled(s) {
+        return Json(serde_json::json!({"ok": false, "error": "No AI provider is enabled"}));
+    }
+    let pending: std::collections::HashSet<(String, i64)> = s
+        .seo_suggestion_pending(None)
+        .into_iter()
+        .map(|p| (p.content_type, p.content_id))
+        .collect();
+    let mut candidates: Vec<(i32, &str, i64)> = s
+        .post_list(None, 10000, 0)
+        .iter()
+        .map(|p| (p.seo_score, "post", p.id))
+        .chain(
+            s.portfolio_list(None, 10000, 0)
+                .iter()
+                .map(|p| (p.seo_score, "portfolio", p.id)),
+        )
+        .filter(|(score, ctype, id)| {
+            (0..LOW_SCORE).contains(score) && !pending.contains(&(ctype.to_string(), *id))
+        })
+        .collect();
+    candidates.sort();
+    let remaining = candidates.len().saturating_sub(BATCH_SIZE);
+
+    let mut done = 0;
+    let mut errors = Vec::new();
+    for (_, ctype, id) in candidates.into_iter().take(BATCH_SIZE) {
+        match crate::seo::rewrite::suggest(s, ctype, id) {
+            Ok(_) => done += 1,
+            Err(e) => errors.push(format!("{} {}: {}", ctype, id, e)),
+        }
+    }
+    Json(serde_json::json!({
+        "ok": done > 0 || errors.is_empty(),
+        "done": done,
+        "remaining": remaining,
+        "errors": errors,
+    }))
+}
+
+/// Write a pending suggestion into its post or portfolio item
+#[post("/seo-ai/suggestions/<id>/apply")]
+pub fn seo_ai_apply(_admin: SeoManager, store: &State<Arc<dyn Store>>, id: i64) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    let suggestion = match s.seo_suggestion_get(id) {
+        Some(sg) => sg,
+        None => return Json(serde_json::json!({"ok": false, "error": "Suggestion not found"})),
+    };
+    if let Err(e) = crate::seo::rewrite::apply(s, &suggestion) {
+        return Json(serde_json::json!({"ok": false, "error": e}));
+    }
+    s.audit_log(
+        Some(_admin.user.id),
+        Some(&_admin.user.display_name),
+        "seo_suggestion_apply",
+        Some(&suggestion.content_type),
+        Some(suggestion.content_id),
+        None,
+        Some(&format!("{}: {}", suggestion.field, suggestion.suggested)),
+        None,
+    );
+    Json(serde_json::json!({"ok": true}))
+}
+
+#[post("/seo-ai/suggestions/<id>/dismiss")]
+pub fn seo_ai_dismiss(_admin: SeoManager, store: &State<Arc<dyn Store>>, id: i64) -> Json<Value> {
+    match store.seo_suggestion_set_status(id, "dismissed") {
+        Ok(()) => Json(serde_json::json!({"ok": true})),
+        Err(e) => Json(serde_json::json!({"ok": false, "error": e})),
+    }
+}
+
 /// Fetch PageSpeed Insights for a URL (proxied to avoid CORS)
 #[get("/pagespeed?<url>")]
 pub fn pagespeed_fetch(
-    _admin: EditorUser,
+    _admin: SeoManager,
     store: &State<Arc<dyn Store>>,
     url: &str,
 ) -> Json<Value> {
@@ -556,7 +877,7 @@ pub fn pagespeed_fetch(
 
 /// Fetch Moz domain metrics (DA, PA, backlinks, spam score) — cached in settings
 #[get("/moz-domain")]
-pub fn moz_domain_fetch(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn moz_domain_fetch(_admin: SeoManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let access_id = store.setting_get_or("seo_moz_access_id", "");
     let secret_key = store.setting_get_or("seo_moz_secret_key", "");
 
@@ -630,7 +951,7 @@ pub fn moz_domain_fetch(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> Js
 
 /// Fetch cached Moz data without hitting the API
 #[get("/moz-domain/cached")]
-pub fn moz_domain_cached(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn moz_domain_cached(_admin: SeoManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let cached = store.setting_get_or("seo_moz_cache", "");
     if cached.is_empty() {
         return Json(serde_json::json!({"cached": false}));
@@ -643,7 +964,7 @@ pub fn moz_domain_cached(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> J
 
 /// Fetch Open PageRank score — cached in settings
 #[get("/pagerank")]
-pub fn pagerank_fetch(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn pagerank_fetch(_admin: SeoManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let api_key = store.setting_get_or("seo_openpagerank_api_key", "");
 
     if api_key.is_empty() {
@@ -717,7 +1038,7 @@ pub fn pagerank_fetch(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> Json
 
 /// Fetch cached PageRank data without hitting the API
 #[get("/pagerank/cached")]
-pub fn pagerank_cached(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn pagerank_cached(_admin: SeoManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let cached = store.setting_get_or("seo_pagerank_cache", "");
     if cached.is_empty() {
         return Json(serde_json::json!({"cached": false}));
@@ -732,7 +1053,7 @@ pub fn pagerank_cached(_admin: EditorUser, store: &State<Arc<dyn Store>>) -> Jso
 
 /// Run DNS health checks for the built-in MTA.
 #[post("/mta/dns-check")]
-pub fn mta_dns_check(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn mta_dns_check(_admin: MailManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let settings = store.setting_all();
     let results = crate::mta::dns::check_all(&settings);
     Json(serde_json::to_value(results).unwrap_or_default())
@@ -740,7 +1061,11 @@ pub fn mta_dns_check(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<V
 
 /// Regenerate DKIM keypair.
 #[post("/mta/regenerate-dkim")]
-pub fn mta_regenerate_dkim(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn mta_regenerate_dkim(
+    _admin: MailManager,
+    _sudo: SudoMode,
+    store: &State<Arc<dyn Store>>,
+) -> Json<Value> {
     let s: &dyn Store = &**store.inner();
     match crate::mta::regenerate_dkim(s) {
         Ok(public_key) => {
@@ -762,7 +1087,7 @@ pub fn mta_regenerate_dkim(_admin: AdminUser, store: &State<Arc<dyn Store>>) ->
 
 /// Send a test email via the built-in MTA.
 #[post("/mta/test-email")]
-pub fn mta_test_email(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn mta_test_email(_admin: MailManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let settings = store.setting_all();
     let from = settings
         .get("mta_from_address")
@@ -801,7 +1126,7 @@ pub fn mta_test_email(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<
 
 /// Get the required DNS records (static, no live check).
 #[get("/mta/required-records")]
-pub fn mta_required_records(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn mta_required_records(_admin: MailManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let settings = store.setting_all();
     let records = crate::mta::dns::required_records(&settings);
     Json(serde_json::to_value(records).unwrap_or_default())
@@ -809,7 +1134,7 @@ pub fn mta_required_records(_admin: AdminUser, store: &State<Arc<dyn Store>>) ->
 
 /// Get DKIM public key info for display.
 #[get("/mta/dkim-info")]
-pub fn mta_dkim_info(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn mta_dkim_info(_admin: MailManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let private_pem = store.setting_get_or("mta_dkim_private_key", "");
     let selector = store.setting_get_or("mta_dkim_selector", "velocty");
     let generated_at = store.setting_get_or("mta_dkim_generated_at", "");
@@ -843,7 +1168,7 @@ pub fn mta_dkim_info(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<V
 
 /// Regenerate the deploy receive key (production only).
 #[post("/deploy/regenerate-key")]
-pub fn deploy_regenerate_key(admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn deploy_regenerate_key(admin: DeployManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let env = store.setting_get_or("site_environment", "staging");
     if env != "production" {
         return Json(
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::models::api_key::{self, ApiKey};
+use crate::security::auth::SettingsManager;
+use crate::security::sudo::SudoMode;
+use crate::store::Store;
+
+// ── API Keys ───────────────────────────────────────────
+// Listed and managed on Settings › Security › API.
+
+#[derive(Deserialize)]
+pub struct ApiKeyForm {
+    pub name: String,
+    pub scope: String,
+    pub rate_limit: Option<i64>,
+}
+
+/// Create a key. The key itself is in the response and never shown again.
+#[post("/api/api-keys/create", format = "json", data = "<form>")]
+pub fn api_key_create(
+    admin: SettingsManager,
+    _sudo: SudoMode,
+    store: &State<Arc<dyn Store>>,
+    form: Json<ApiKeyForm>,
+) -> Json<Value> {
+    let (key, prefix, key_hash) = api_key::generate();
+    let mut record = ApiKey {
+        id: 0,
+        name: form.name.trim().to_string(),
+        prefix,
+        key_hash,
+        scope: form.scope.trim().to_string(),
+        rate_limit: form.rate_limit.unwrap_or(0),
+        last_used_at: None,
+        created_at: String::new(),
+    };
+    if let Err(e) = api_key::validate(&record) {
+        return Json(json!({"success": false, "error": e}));
+    }
+    match store.api_key_create(&record) {
+        Ok(id) => {
+            record.id = id;
+            store.audit_log(
+                Some(admin.user.id),
+                Some(&admin.user.display_name),
+                "api_key_create",
+                Some("api_key"),
+                Some(id),
+                Some(&record.name),
+                Some(&format!("Scope: {}", record.scope)),
+                None,
+            );
+            Json(json!({"success": true, "key": key, "api_key": record}))
+        }
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ApiKeyDeleteForm {
+    pub id: i64,
+}
+
+/// Revoke a key. Requests using it are refused from then on.
+#[post("/api/api-keys/delete", format = "json", data = "<form>")]
+pub fn api_key_delete(
+    admin: SettingsManager,
+    _sudo: SudoMode,
+    store: &State<Arc<dyn Store>>,
+    form: Json<ApiKeyDeleteForm>,
+) -> Json<Value> {
+    let name = store
+        .api_key_list()
+        .into_iter()
+        .find(|k| k.id == form.id)
+        .map(|k| k.name);
+    match store.api_key_delete(form.id) {
+        Ok(_) => {
+            store.audit_log(
+                Some(admin.user.id),
+                Some(&admin.user.display_name),
+                "api_key_revoke",
+                Some("api_key"),
+                Some(form.id),
+                name.as_deref(),
+                None,
+                None,
+            );
+            Json(json!({"success": true}))
+        }
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
@@ -9,7 +9,7 @@ use serde_json::{json, Value};
 
 use super::admin_base;
 use crate::models::category::CategoryForm;
-use crate::security::auth::EditorUser;
+use crate::security::auth::CategoryManager;
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -17,7 +17,7 @@ use crate::AdminSlug;
 
 #[get("/categories?<type_filter>&<page>")]
 pub fn categories_list(
-    _admin: EditorUser,
+    _admin: CategoryManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     type_filter: Option<String>,
@@ -63,7 +63,7 @@ pub fn categories_list(
 
 #[get("/tags?<page>")]
 pub fn tags_list(
-    _admin: EditorUser,
+    _admin: CategoryManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     page: Option<i64>,
@@ -112,7 +112,7 @@ pub struct CategoryFormData {
 
 #[post("/categories/new", data = "<form>")]
 pub fn category_create(
-    _admin: EditorUser,
+    _admin: CategoryManager,
     store: &State<Arc<dyn Store>>,
     admin_slug: &State<AdminSlug>,
     form: Form<CategoryFormData>,
@@ -132,7 +132,7 @@ pub fn category_create(
 
 #[post("/api/categories/create", format = "json", data = "<data>")]
 pub fn api_category_create(
-    _admin: EditorUser,
+    _admin: CategoryManager,
     store: &State<Arc<dyn Store>>,
     data: Json<Value>,
 ) -> Json<Value> {
@@ -163,7 +163,7 @@ pub fn api_category_create(
 
 #[post("/categories/<id>/edit", data = "<form>")]
 pub fn category_update(
-    _admin: EditorUser,
+    _admin: CategoryManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     id: i64,
@@ -187,7 +187,7 @@ pub fn category_update(
 
 #[post("/categories/<id>/delete")]
 pub fn category_delete(
-    _admin: EditorUser,
+    _admin: CategoryManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     id: i64,
@@ -214,7 +214,7 @@ pub fn category_delete(
 
 #[post("/api/categories/<id>/toggle-nav", format = "json", data = "<data>")]
 pub fn api_category_toggle_nav(
-    _admin: EditorUser,
+    _admin: CategoryManager,
     store: &State<Arc<dyn Store>>,
     id: i64,
     data: Json<Value>,
@@ -230,7 +230,7 @@ pub fn api_category_toggle_nav(
 
 #[post("/tags/<id>/delete")]
 pub fn tag_delete(
-    _admin: EditorUser,
+    _admin: CategoryManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     id: i64,
@@ -6,33 +6,37 @@ use rocket_dyn_templates::Template;
 use serde_json::json;
 
 use super::admin_base;
-use crate::security::auth::EditorUser;
+use super::{list_page, NUMBERED_PAGES};
+use crate::security::auth::CommentModerator;
 use crate::store::Store;
 use crate::AdminSlug;
 
 // ── Comments ───────────────────────────────────────────
 
-#[get("/comments?<status>&<page>")]
+#[get("/comments?<status>&<page>&<after>")]
 pub fn comments_list(
-    _admin: EditorUser,
+    _admin: CommentModerator,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     status: Option<String>,
     page: Option<i64>,
+    after: Option<String>,
 ) -> Template {
     let per_page = 20i64;
-    let current_page = page.unwrap_or(1).max(1);
-    let offset = (current_page - 1) * per_page;
 
-    let comments = store.comment_list(status.as_deref(), per_page, offset);
+    let list = list_page(after.as_deref(), page, per_page, |after, limit| {
+        store.comment_list_keyset(status.as_deref(), after, limit)
+    });
     let total = store.comment_count(status.as_deref());
     let total_pages = ((total as f64) / (per_page as f64)).ceil() as i64;
 
     let context = json!({
         "page_title": "Comments",
-        "comments": comments,
-        "current_page": current_page,
+        "comments": list.items,
+        "current_page": list.page.unwrap_or(0),
         "total_pages": total_pages,
+        "page_links": total_pages.min(NUMBERED_PAGES),
+        "next_cursor": list.next_cursor,
         "total": total,
         "status_filter": status,
         "count_all": store.comment_count(None),
@@ -48,18 +52,24 @@ pub fn comments_list(
 
 #[post("/comments/<id>/approve")]
 pub fn comment_approve(
-    _admin: EditorUser,
+    _admin: CommentModerator,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     id: i64,
 ) -> Redirect {
-    let _ = store.comment_update_status(id, "approved");
+    if store.comment_update_status(id, "approved").is_ok() {
+        let s = Arc::clone(store.inner());
+        std::thread::spawn(move || {
+            crate::mta::inbound::notify_comment(&*s, id, false);
+            crate::email::optin::notify_comment_subscribers(&*s, id);
+        });
+    }
     Redirect::to(format!("{}/comments", admin_base(slug)))
 }
 
 #[post("/comments/<id>/spam")]
 pub fn comment_spam(
-    _admin: EditorUser,
+    _admin: CommentModerator,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     id: i64,
@@ -70,7 +80,7 @@ pub fn comment_spam(
 
 #[post("/comments/<id>/delete")]
 pub fn comment_delete(
-    _admin: EditorUser,
+    _admin: CommentModerator,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     id: i64,
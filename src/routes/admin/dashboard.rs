@@ -4,7 +4,8 @@ use rocket::State;
 use rocket_dyn_templates::Template;
 use serde_json::json;
 
-use crate::security::auth::AuthorUser;
+use crate::security::auth::DashboardUser;
+use crate::security::permissions;
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -12,7 +13,7 @@ use crate::AdminSlug;
 
 #[get("/")]
 pub fn dashboard(
-    _admin: AuthorUser,
+    _admin: DashboardUser,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
 ) -> Template {
@@ -21,6 +22,12 @@ pub fn dashboard(
     let portfolio_count = store.portfolio_count(None);
     let comments_pending = store.comment_count(Some("pending"));
 
+    let s: &dyn Store = &**store.inner();
+    let can_view_analytics = permissions::user_can(s, &_admin.user, permissions::ANALYTICS_VIEW);
+    // Anyone who writes gets their own numbers, with or without site analytics
+    let contributions = permissions::user_can(s, &_admin.user, permissions::POSTS_EDIT)
+        .then(|| crate::analytics::author_contributions(s, _admin.user.id));
+
     let settings = store.setting_all();
     let mta_enabled = settings
         .get("email_builtin_enabled")
@@ -53,6 +60,8 @@ pub fn dashboard(
         "posts_draft": posts_draft,
         "portfolio_count": portfolio_count,
         "comments_pending": comments_pending,
+        "can_view_analytics": can_view_analytics,
+        "contributions": contributions,
         "mta_enabled": mta_enabled,
         "mta_sent": mta_sent,
         "mta_pending": mta_pending,
@@ -1,12 +1,23 @@
 use std::sync::Arc;
 
+use rocket::form::Form;
+use rocket::fs::TempFile;
+use rocket::http::CookieJar;
 use rocket::response::{Flash, Redirect};
+use rocket::serde::json::Json;
+use rocket::tokio::io::AsyncReadExt;
 use rocket::State;
 use rocket_dyn_templates::Template;
+use serde::Deserialize;
 use serde_json::json;
 
 use super::admin_base;
-use crate::security::auth::AdminUser;
+use super::health::ZipDownload;
+use crate::designs::{error_pages, history, homepage, inheritance, preview};
+use crate::models::widget::{self, Widget};
+use crate::security::auth::DesignManager;
+use crate::security::permissions;
+use crate::security::sudo::SudoMode;
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -14,7 +25,7 @@ use crate::AdminSlug;
 
 #[get("/designer")]
 pub fn designs_list(
-    _admin: AdminUser,
+    _admin: DesignManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     flash: Option<rocket::request::FlashMessage<'_>>,
@@ -40,7 +51,7 @@ pub fn designs_list(
 
 #[post("/designer/<id>/activate")]
 pub fn design_activate(
-    _admin: AdminUser,
+    _admin: DesignManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     id: i64,
@@ -66,16 +77,464 @@ pub fn design_activate(
     )
 }
 
+// ── Preview ──────────────────────────────────────────────
+
+/// Browse the site with design `id` in this browser only
+#[get("/designer/<id>/preview")]
+pub fn design_preview(
+    _admin: DesignManager,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    cookies: &CookieJar<'_>,
+    id: i64,
+) -> Result<Redirect, Flash<Redirect>> {
+    if store.design_find_by_id(id).is_none() {
+        return Err(Flash::error(
+            Redirect::to(format!("{}/designer", admin_base(slug))),
+            "Design not found",
+        ));
+    }
+    preview::set_cookie(cookies, id);
+    Ok(Redirect::to("/"))
+}
+
+#[get("/designer/preview/exit")]
+pub fn design_preview_exit(
+    _admin: DesignManager,
+    slug: &State<AdminSlug>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    preview::clear_cookie(cookies);
+    Redirect::to(format!("{}/designer", admin_base(slug)))
+}
+
+// ── Parent Design ────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct ParentData {
+    pub parent_id: Option<i64>,
+}
+
+#[post("/designer/<id>/parent", format = "json", data = "<body>")]
+pub fn design_parent(
+    _admin: DesignManager,
+    store: &State<Arc<dyn Store>>,
+    id: i64,
+    body: Json<ParentData>,
+) -> Json<serde_json::Value> {
+    let design = match store.design_find_by_id(id) {
+        Some(d) => d,
+        None => return Json(json!({ "ok": false, "error": "Design not found" })),
+    };
+    if let Err(e) = inheritance::validate_parent(&**store.inner(), id, body.parent_id) {
+        return Json(json!({ "ok": false, "error": e }));
+    }
+    if let Err(e) = store.design_set_parent(id, body.parent_id) {
+        return Json(json!({ "ok": false, "error": e }));
+    }
+    store.audit_log(
+        Some(_admin.user.id),
+        Some(&_admin.user.display_name),
+        "set_parent",
+        Some("design"),
+        Some(id),
+        Some(&design.name),
+        None,
+        None,
+    );
+    Json(json!({ "ok": true }))
+}
+
+// ── Custom CSS & Scripts ─────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct CustomCodeData {
+    #[serde(default)]
+    pub custom_css: String,
+    #[serde(default)]
+    pub head_scripts: String,
+    #[serde(default)]
+    pub footer_scripts: String,
+}
+
+/// Scripts run on the admin's own origin, so saving them takes the settings
+/// capability; `designs.manage` alone may change the CSS only.
+#[post("/designer/<id>/custom-code", format = "json", data = "<body>")]
+pub fn design_custom_code(
+    _admin: DesignManager,
+    _sudo: SudoMode,
+    store: &State<Arc<dyn Store>>,
+    id: i64,
+    body: Json<CustomCodeData>,
+) -> Json<serde_json::Value> {
+    use crate::designs::custom_code;
+    let design = match store.design_find_by_id(id) {
+        Some(d) => d,
+        None => return Json(json!({ "ok": false, "error": "Design not found" })),
+    };
+    let cleaned = custom_code::check_len("Custom CSS", &body.custom_css).and_then(|_| {
+        Ok((
+            crate::designs::package::sanitize_css(&body.custom_css)
+                .trim()
+                .to_string(),
+            custom_code::sanitize_scripts(&body.head_scripts)?,
+            custom_code::sanitize_scripts(&body.footer_scripts)?,
+        ))
+    });
+    let (css, head, footer) = match cleaned {
+        Ok(c) => c,
+        Err(e) => return Json(json!({ "ok": false, "error": e })),
+    };
+    if (head != design.head_scripts || footer != design.footer_scripts)
+        && !permissions::user_can(&**store.inner(), &_admin.user, permissions::SETTINGS_WRITE)
+    {
+        return Json(json!({
+            "ok": false,
+            "error": "Only users who can change site settings may edit scripts",
+        }));
+    }
+    if let Err(e) = store.design_update_custom_code(id, &css, &head, &footer) {
+        return Json(json!({ "ok": false, "error": e }));
+    }
+    store.audit_log(
+        Some(_admin.user.id),
+        Some(&_admin.user.display_name),
+        "custom_code",
+        Some("design"),
+        Some(id),
+        Some(&design.name),
+        None,
+        None,
+    );
+    Json(json!({
+        "ok": true,
+        "custom_css": css,
+        "head_scripts": head,
+        "footer_scripts": footer,
+    }))
+}
+
+// ── Error Pages ──────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct ErrorPagesData {
+    #[serde(default)]
+    pub html_404: String,
+    #[serde(default)]
+    pub css_404: String,
+    #[serde(default)]
+    pub html_500: String,
+    #[serde(default)]
+    pub css_500: String,
+}
+
+#[post("/designer/<id>/error-pages", format = "json", data = "<body>")]
+pub fn design_error_pages(
+    _admin: DesignManager,
+    store: &State<Arc<dyn Store>>,
+    id: i64,
+    body: Json<ErrorPagesData>,
+) -> Json<serde_json::Value> {
+    let design = match store.design_find_by_id(id) {
+        Some(d) => d,
+        None => return Json(json!({ "ok": false, "error": "Design not found" })),
+    };
+    let mut saved = json!({ "ok": true });
+    for (template_type, html, css) in [
+        ("404", &body.html_404, &body.css_404),
+        ("500", &body.html_500, &body.css_500),
+    ] {
+        let (html, css) = match error_pages::sanitize(html, css) {
+            Ok(c) => c,
+            Err(e) => return Json(json!({ "ok": false, "error": e })),
+        };
+        let exists = store.design_template_get_own(id, template_type).is_some();
+        if !exists && html.is_empty() && css.is_empty() {
+            continue;
+        }
+        let editor = Some((_admin.user.id, _admin.user.display_name.as_str()));
+        if let Err(e) = store.design_template_save(id, template_type, &html, &css, "", editor) {
+            return Json(json!({ "ok": false, "error": e }));
+        }
+        saved[format!("html_{}", template_type)] = json!(html);
+        saved[format!("css_{}", template_type)] = json!(css);
+    }
+    store.audit_log(
+        Some(_admin.user.id),
+        Some(&_admin.user.display_name),
+        "error_pages",
+        Some("design"),
+        Some(id),
+        Some(&design.name),
+        None,
+        None,
+    );
+    Json(saved)
+}
+
+// ── Template History ─────────────────────────────────────
+
+/// A template's saved versions, with version `v` (the newest by default)
+/// compared against the current template
+#[get("/designer/<id>/templates/<template_type>/history?<v>")]
+pub fn design_template_history(
+    _admin: DesignManager,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    flash: Option<rocket::request::FlashMessage<'_>>,
+    id: i64,
+    template_type: &str,
+    v: Option<i64>,
+) -> Option<Template> {
+    let design = store.design_find_by_id(id)?;
+    let versions = store.design_template_version_list(id, template_type);
+    let current = store.design_template_get_own(id, template_type);
+    let selected = match v {
+        Some(vid) => versions.iter().find(|x| x.id == vid),
+        None => versions.first(),
+    };
+
+    let mut context = json!({
+        "page_title": format!("History: {} / {}", design.name, template_type),
+        "design": design,
+        "template_type": template_type,
+        "versions": versions,
+        "admin_slug": slug.get(),
+        "settings": store.setting_all(),
+    });
+    if let Some(sel) = selected {
+        let (cur_html, cur_css) = current
+            .as_ref()
+            .map(|c| (c.layout_html.as_str(), c.style_css.as_str()))
+            .unwrap_or(("", ""));
+        let html_diff = history::line_diff(cur_html, &sel.layout_html);
+        let css_diff = history::line_diff(cur_css, &sel.style_css);
+        context["selected"] = json!(sel);
+        context["is_current"] =
+            json!(!history::has_changes(&html_diff) && !history::has_changes(&css_diff));
+        context["html_diff"] = json!(html_diff);
+        context["css_diff"] = json!(css_diff);
+        context["preview_doc"] = json!(format!(
+            "<!DOCTYPE html><html><head><style>{}</style></head><body>{}</body></html>",
+            sel.style_css, sel.layout_html
+        ));
+    }
+    if let Some(ref f) = flash {
+        context["flash_kind"] = json!(f.kind());
+        context["flash_msg"] = json!(f.message());
+    }
+
+    Some(Template::render("admin/designs/history", &context))
+}
+
+/// Make version `vid` the current template. The rollback is itself saved
+/// as a new version, so it can be undone the same way.
+#[post("/designer/<id>/templates/<template_type>/history/<vid>/restore")]
+pub fn design_template_restore(
+    _admin: DesignManager,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    id: i64,
+    template_type: &str,
+    vid: i64,
+) -> Flash<Redirect> {
+    let back = Redirect::to(format!(
+        "{}/designer/{}/templates/{}/history",
+        admin_base(slug),
+        id,
+        template_type
+    ));
+    let (design, version) = match (
+        store.design_find_by_id(id),
+        store.design_template_version_get(vid),
+    ) {
+        (Some(d), Some(v)) if v.design_id == id && v.template_type == template_type => (d, v),
+        _ => return Flash::error(back, "Version not found"),
+    };
+    let editor = Some((_admin.user.id, _admin.user.display_name.as_str()));
+    if let Err(e) = store.design_template_save(
+        id,
+        template_type,
+        &version.layout_html,
+        &version.style_css,
+        &version.grapesjs_data,
+        editor,
+    ) {
+        return Flash::error(back, e);
+    }
+    store.audit_log(
+        Some(_admin.user.id),
+        Some(&_admin.user.display_name),
+        "restore_template",
+        Some("design"),
+        Some(id),
+        Some(&design.name),
+        Some(&format!("{} version #{}", template_type, vid)),
+        None,
+    );
+    Flash::success(back, "Template restored")
+}
+
+// ── Widgets ──────────────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct WidgetData {
+    pub id: Option<i64>,
+    pub area: String,
+    pub widget_type: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub config: serde_json::Value,
+    #[serde(default)]
+    pub sort_order: i64,
+}
+
+#[post("/designer/<id>/widgets", format = "json", data = "<body>")]
+pub fn design_widget_save(
+    _admin: DesignManager,
+    store: &State<Arc<dyn Store>>,
+    id: i64,
+    body: Json<WidgetData>,
+) -> Json<serde_json::Value> {
+    if store.design_find_by_id(id).is_none() {
+        return Json(json!({ "ok": false, "error": "Design not found" }));
+    }
+    let existing = store.widget_list(id);
+    let widget_id = body.id.unwrap_or(0);
+    let widget_type = match existing.iter().find(|w| w.id == widget_id) {
+        // The type is fixed once a widget exists
+        Some(w) => w.widget_type.clone(),
+        None if widget_id == 0 => body.widget_type.clone(),
+        None => return Json(json!({ "ok": false, "error": "Widget not found" })),
+    };
+    let mut config = match &body.config {
+        serde_json::Value::Object(_) => body.config.clone(),
+        _ => json!({}),
+    };
+    if let Some(html) = config.get("html").and_then(|v| v.as_str()) {
+        config["html"] = json!(crate::designs::package::sanitize_html(html));
+    }
+    let widget = Widget {
+        id: widget_id,
+        design_id: id,
+        area: body.area.clone(),
+        widget_type,
+        title: body.title.trim().to_string(),
+        config: config.to_string(),
+        sort_order: body.sort_order,
+        created_at: String::new(),
+    };
+    if let Err(e) = widget::validate(&widget) {
+        return Json(json!({ "ok": false, "error": e }));
+    }
+    match store.widget_save(&widget) {
+        Ok(wid) => Json(json!({ "ok": true, "id": wid, "config": config })),
+        Err(e) => Json(json!({ "ok": false, "error": e })),
+    }
+}
+
+#[post("/designer/<id>/widgets/<widget_id>/delete")]
+pub fn design_widget_delete(
+    _admin: DesignManager,
+    store: &State<Arc<dyn Store>>,
+    id: i64,
+    widget_id: i64,
+) -> Json<serde_json::Value> {
+    if !store.widget_list(id).iter().any(|w| w.id == widget_id) {
+        return Json(json!({ "ok": false, "error": "Widget not found" }));
+    }
+    match store.widget_delete(widget_id) {
+        Ok(()) => Json(json!({ "ok": true })),
+        Err(e) => Json(json!({ "ok": false, "error": e })),
+    }
+}
+
+// ── Export / Import ──────────────────────────────────────
+
+#[get("/designer/<id>/export")]
+pub fn design_export(
+    _admin: DesignManager,
+    store: &State<Arc<dyn Store>>,
+    id: i64,
+) -> Result<ZipDownload, rocket::http::Status> {
+    let s: &dyn Store = &**store.inner();
+    let (filename, data) =
+        crate::designs::package::export_zip(s, id).map_err(|_| rocket::http::Status::NotFound)?;
+    Ok(ZipDownload { filename, data })
+}
+
+#[derive(FromForm)]
+pub struct DesignImportForm<'f> {
+    pub file: TempFile<'f>,
+    pub apply_settings: Option<bool>,
+}
+
+#[post("/designer/import", data = "<form>")]
+pub async fn design_import(
+    _admin: DesignManager,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    form: Form<DesignImportForm<'_>>,
+) -> Flash<Redirect> {
+    let redirect_url = format!("{}/designer", admin_base(slug));
+
+    let mut bytes = Vec::new();
+    let read = match form.file.open().await {
+        Ok(f) => f
+            .take(50 * 1024 * 1024)
+            .read_to_end(&mut bytes)
+            .await
+            .is_ok(),
+        Err(_) => false,
+    };
+    if !read || bytes.is_empty() {
+        return Flash::error(
+            Redirect::to(redirect_url),
+            "Failed to read the uploaded design package.",
+        );
+    }
+
+    let s: &dyn Store = &**store.inner();
+    let apply_settings = form.apply_settings.unwrap_or(false);
+    match crate::designs::package::import_zip(s, &bytes, apply_settings) {
+        Ok(d) => {
+            store.audit_log(
+                Some(_admin.user.id),
+                Some(&_admin.user.display_name),
+                "import",
+                Some("design"),
+                Some(d.id),
+                Some(&d.name),
+                None,
+                None,
+            );
+            let mut msg = format!("Imported {} with {} templates", d.name, d.templates);
+            if d.settings_applied > 0 {
+                msg.push_str(&format!(" and applied {} settings", d.settings_applied));
+            }
+            msg.push('.');
+            Flash::success(Redirect::to(redirect_url), msg)
+        }
+        Err(e) => Flash::error(
+            Redirect::to(redirect_url),
+            format!("Design import failed: {}", e),
+        ),
+    }
+}
+
 // ── Design Overview (live preview) ───────────────────────
 
 #[get("/designer/<design_slug>")]
 pub fn design_overview(
-    _admin: AdminUser,
+    _admin: DesignManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     design_slug: String,
 ) -> Option<Template> {
     let design = store.design_find_by_slug(&design_slug)?;
+    let can_edit_scripts =
+        permissions::user_can(&**store.inner(), &_admin.user, permissions::SETTINGS_WRITE);
     let portfolio_categories: Vec<serde_json::Value> = store
         .category_list(Some("portfolio"))
         .iter()
@@ -87,13 +546,60 @@ pub fn design_overview(
         .map(|c| json!({"id": c.id, "name": c.name, "slug": c.slug, "show_in_nav": c.show_in_nav}))
         .collect();
 
+    // Any other design can be the parent; the route refuses cycles
+    let parent_options: Vec<serde_json::Value> = store
+        .design_list()
+        .iter()
+        .filter(|d| d.id != design.id)
+        .map(|d| json!({"id": d.id, "name": d.name}))
+        .collect();
+
+    let error_page_templates: Vec<serde_json::Value> = error_pages::TYPES
+        .iter()
+        .map(|(t, label)| {
+            let own = store.design_template_get_own(design.id, t);
+            json!({
+                "type": t,
+                "label": label,
+                "layout_html": own.as_ref().map(|o| o.layout_html.as_str()).unwrap_or(""),
+                "style_css": own.as_ref().map(|o| o.style_css.as_str()).unwrap_or(""),
+            })
+        })
+        .collect();
+
+    let widgets: Vec<serde_json::Value> = store
+        .widget_list(design.id)
+        .iter()
+        .map(|w| {
+            let mut v = json!(w);
+            v["config"] = w.config_value();
+            v
+        })
+        .collect();
+
     let context = json!({
         "page_title": format!("Design: {}", design.name),
         "design": design,
+        "can_edit_scripts": can_edit_scripts,
         "admin_slug": slug.get(),
         "settings": store.setting_all(),
         "portfolio_categories": portfolio_categories,
         "journal_categories": journal_categories,
+        "widgets": widgets,
+        "widget_types": widget::WIDGET_TYPES,
+        "widget_areas": widget::WIDGET_AREAS,
+        "homepage_sections": serde_json::to_string(&homepage::parse_sections(
+            &store.setting_get_or("homepage_sections", ""),
+        ))
+        .unwrap_or_default(),
+        "homepage_section_types": homepage::SECTION_TYPES,
+        "parent_options": parent_options,
+        "error_pages": error_page_templates,
+        "template_types": store
+            .design_template_for_design(design.id)
+            .iter()
+            .map(|t| t.template_type.clone())
+            .collect::<Vec<_>>(),
     });
 
     Some(Template::render("admin/designs/overview", &context))
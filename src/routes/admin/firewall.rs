@@ -1,25 +1,35 @@
 use std::sync::Arc;
 
+use rocket::http::{ContentType, Header};
+use rocket::request::Request;
+use rocket::response::{self, Responder};
 use rocket::serde::json::Json;
 use rocket::State;
 use rocket_dyn_templates::Template;
 use serde::Deserialize;
 use serde_json::{json, Value};
 
-use crate::security::auth::AdminUser;
+use super::{list_page, NUMBERED_PAGES};
+use crate::models::firewall::FwRule;
+use crate::security::auth::FirewallManager;
+use crate::security::firewall::rules;
+use crate::security::sudo::SudoMode;
 use crate::store::Store;
 use crate::AdminSlug;
 
 // ── Firewall Dashboard ─────────────────────────────────
 
-#[get("/firewall?<ev_page>&<ban_page>&<audit_page>&<audit_action>&<audit_entity>&<audit_user>")]
+#[get(
+    "/firewall?<ev_page>&<ban_page>&<audit_page>&<audit_after>&<audit_action>&<audit_entity>&<audit_user>"
+)]
 pub fn firewall_dashboard(
-    _admin: AdminUser,
+    _admin: FirewallManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     ev_page: Option<i64>,
     ban_page: Option<i64>,
     audit_page: Option<i64>,
+    audit_after: Option<String>,
     audit_action: Option<String>,
     audit_entity: Option<String>,
     audit_user: Option<i64>,
@@ -40,14 +50,19 @@ pub fn firewall_dashboard(
 
     // Audit log pagination
     let audit_per_page: i64 = 50;
-    let audit_current = audit_page.unwrap_or(1).max(1);
-    let audit_offset = (audit_current - 1) * audit_per_page;
-    let audit_entries = store.audit_list(
-        audit_action.as_deref(),
-        audit_entity.as_deref(),
-        audit_user,
+    let audit = list_page(
+        audit_after.as_deref(),
+        audit_page,
         audit_per_page,
-        audit_offset,
+        |after, limit| {
+            store.audit_list_keyset(
+                audit_action.as_deref(),
+                audit_entity.as_deref(),
+                audit_user,
+                after,
+                limit,
+            )
+        },
     );
     let audit_total =
         store.audit_count(audit_action.as_deref(), audit_entity.as_deref(), audit_user);
@@ -62,6 +77,7 @@ pub fn firewall_dashboard(
     let event_counts = store.fw_event_counts_by_type();
     let events = store.fw_event_recent(None, per_page, ev_offset);
     let bans = store.fw_active_bans(per_page, ban_offset);
+    let fw_rules = store.fw_rule_list();
 
     let context = json!({
         "page_title": "Firewall",
@@ -74,16 +90,19 @@ pub fn firewall_dashboard(
         "event_counts": event_counts,
         "events": events,
         "bans": bans,
+        "rules": fw_rules,
         "ev_current_page": ev_current,
         "ev_total_pages": ev_total_pages,
         "ev_total": ev_total,
         "ban_current_page": ban_current,
         "ban_total_pages": ban_total_pages,
         "ban_total": ban_total,
-        "audit_entries": audit_entries,
+        "audit_entries": audit.items,
         "audit_total": audit_total,
-        "audit_current_page": audit_current,
+        "audit_current_page": audit.page.unwrap_or(0),
         "audit_total_pages": audit_total_pages,
+        "audit_page_links": audit_total_pages.min(NUMBERED_PAGES),
+        "audit_next_cursor": audit.next_cursor,
         "audit_action_filter": audit_action,
         "audit_entity_filter": audit_entity,
         "audit_user_filter": audit_user,
@@ -93,6 +112,87 @@ pub fn firewall_dashboard(
     Template::render("admin/firewall", &context)
 }
 
+// ── Audit Log Export ───────────────────────────────────
+
+/// Most entries a single export will contain
+const AUDIT_EXPORT_LIMIT: i64 = 100_000;
+
+pub struct AuditExport {
+    pub filename: String,
+    pub content_type: ContentType,
+    pub body: String,
+}
+
+impl<'r> Responder<'r, 'static> for AuditExport {
+    fn respond_to(self, _req: &'r Request<'_>) -> response::Result<'static> {
+        rocket::Response::build()
+            .header(self.content_type)
+            .header(Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", self.filename),
+            ))
+            .sized_body(self.body.len(), std::io::Cursor::new(self.body))
+            .ok()
+    }
+}
+
+/// Download the audit log as CSV or JSON Lines, with the same filters as the
+/// Audit Log tab plus an optional date range (`from`/`to`, YYYY-MM-DD, inclusive).
+#[get("/firewall/audit/export?<format>&<audit_action>&<audit_entity>&<audit_user>&<from>&<to>")]
+pub fn firewall_audit_export(
+    admin: FirewallManager,
+    store: &State<Arc<dyn Store>>,
+    format: Option<String>,
+    audit_action: Option<String>,
+    audit_entity: Option<String>,
+    audit_user: Option<i64>,
+    from: Option<String>,
+    to: Option<String>,
+) -> AuditExport {
+    let day = |v: &Option<String>| {
+        v.as_deref()
+            .and_then(|d| chrono::NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").ok())
+    };
+    let (from_day, to_day) = (day(&from), day(&to));
+    let entries: Vec<_> = store
+        .audit_list(
+            audit_action.as_deref().filter(|s| !s.is_empty()),
+            audit_entity.as_deref().filter(|s| !s.is_empty()),
+            audit_user,
+            AUDIT_EXPORT_LIMIT,
+            0,
+        )
+        .into_iter()
+        .filter(|e| from_day.is_none_or(|d| e.created_at.date() >= d))
+        .filter(|e| to_day.is_none_or(|d| e.created_at.date() <= d))
+        .collect();
+
+    store.audit_log(
+        Some(admin.user.id),
+        Some(&admin.user.display_name),
+        "audit_export",
+        Some("audit_log"),
+        None,
+        None,
+        Some(&format!("{} entries", entries.len())),
+        None,
+    );
+
+    let stamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+    match format.as_deref() {
+        Some("jsonl") => AuditExport {
+            filename: format!("audit-log-{}.jsonl", stamp),
+            content_type: ContentType::new("application", "x-ndjson"),
+            body: crate::security::siem::export_jsonl(&entries),
+        },
+        _ => AuditExport {
+            filename: format!("audit-log-{}.csv", stamp),
+            content_type: ContentType::CSV,
+            body: crate::security::siem::export_csv(&entries),
+        },
+    }
+}
+
 #[derive(Deserialize)]
 pub struct BanForm {
     pub ip: String,
@@ -102,7 +202,7 @@ pub struct BanForm {
 
 #[post("/api/firewall/ban", format = "json", data = "<form>")]
 pub fn firewall_ban(
-    _admin: AdminUser,
+    _admin: FirewallManager,
     store: &State<Arc<dyn Store>>,
     form: Json<BanForm>,
 ) -> Json<Value> {
@@ -133,7 +233,7 @@ pub struct UnbanForm {
 
 #[post("/api/firewall/unban", format = "json", data = "<form>")]
 pub fn firewall_unban(
-    _admin: AdminUser,
+    _admin: FirewallManager,
     store: &State<Arc<dyn Store>>,
     form: Json<UnbanForm>,
 ) -> Json<Value> {
@@ -142,3 +242,85 @@ pub fn firewall_unban(
         Err(e) => Json(json!({"success": false, "error": e})),
     }
 }
+
+#[post("/api/firewall/unban-all")]
+pub fn firewall_unban_all(
+    _admin: FirewallManager,
+    _sudo: SudoMode,
+    store: &State<Arc<dyn Store>>,
+) -> Json<Value> {
+    match store.fw_unban_all() {
+        Ok(count) => {
+            store.audit_log(
+                Some(_admin.user.id),
+                Some(&_admin.user.display_name),
+                "fw_unban_all",
+                Some("firewall"),
+                None,
+                None,
+                Some(&format!("{} bans lifted", count)),
+                None,
+            );
+            Json(json!({"success": true, "count": count}))
+        }
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
+
+// ── Custom Rules ───────────────────────────────────────
+
+#[derive(Deserialize)]
+pub struct RuleForm {
+    pub id: Option<i64>,
+    pub name: String,
+    pub match_type: String,
+    pub pattern: String,
+    pub action: String,
+    pub rate_limit: Option<i64>,
+    pub priority: Option<i64>,
+    pub enabled: Option<bool>,
+}
+
+#[post("/api/firewall/rules/save", format = "json", data = "<form>")]
+pub fn firewall_rule_save(
+    _admin: FirewallManager,
+    store: &State<Arc<dyn Store>>,
+    form: Json<RuleForm>,
+) -> Json<Value> {
+    let rule = FwRule {
+        id: form.id.unwrap_or(0),
+        name: form.name.trim().to_string(),
+        match_type: form.match_type.trim().to_string(),
+        pattern: form.pattern.trim().to_string(),
+        action: form.action.trim().to_string(),
+        rate_limit: form.rate_limit.unwrap_or(60),
+        priority: form.priority.unwrap_or(100),
+        enabled: form.enabled.unwrap_or(true),
+        hits: 0,
+        created_at: String::new(),
+    };
+    if let Err(e) = rules::validate(&rule) {
+        return Json(json!({"success": false, "error": e}));
+    }
+    match store.fw_rule_save(&rule) {
+        Ok(id) => Json(json!({"success": true, "id": id})),
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RuleDeleteForm {
+    pub id: i64,
+}
+
+#[post("/api/firewall/rules/delete", format = "json", data = "<form>")]
+pub fn firewall_rule_delete(
+    _admin: FirewallManager,
+    store: &State<Arc<dyn Store>>,
+    form: Json<RuleDeleteForm>,
+) -> Json<Value> {
+    match store.fw_rule_delete(form.id) {
+        Ok(_) => Json(json!({"success": true})),
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
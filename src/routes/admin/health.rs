@@ -1,4 +1,4 @@
-use std::io::{Cursor, Write};
+use std::io::Cursor;
 use std::sync::Arc;
 
 use rocket::http::{ContentType, Header};
@@ -9,10 +9,9 @@ use rocket::State;
 use rocket_dyn_templates::Template;
 use serde::Deserialize;
 use serde_json::{json, Value};
-use zip::write::SimpleFileOptions;
-use zip::ZipWriter;
 
-use crate::security::auth::AdminUser;
+use crate::security::auth::HealthManager;
+use crate::security::sudo::SudoMode;
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -34,11 +33,34 @@ impl<'r> Responder<'r, 'static> for ZipDownload {
     }
 }
 
+/// A ZIP streamed from disk rather than memory
+pub struct ZipFileDownload {
+    pub filename: String,
+    pub file: std::fs::File,
+    pub len: u64,
+}
+
+impl<'r> Responder<'r, 'static> for ZipFileDownload {
+    fn respond_to(self, _req: &'r Request<'_>) -> response::Result<'static> {
+        rocket::Response::build()
+            .header(ContentType::ZIP)
+            .header(Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", self.filename),
+            ))
+            .sized_body(
+                self.len as usize,
+                rocket::tokio::fs::File::from_std(self.file),
+            )
+            .ok()
+    }
+}
+
 // ── Health ─────────────────────────────────────────────────
 
 #[get("/health")]
 pub fn health_page(
-    _admin: AdminUser,
+    _admin: HealthManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
 ) -> Template {
@@ -55,7 +77,7 @@ pub fn health_page(
 }
 
 #[post("/health/vacuum")]
-pub fn health_vacuum(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn health_vacuum(_admin: HealthManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let s: &dyn Store = &**store.inner();
     if s.db_backend() != "sqlite" {
         return json_tool_result(crate::health::ToolResult {
@@ -79,7 +101,7 @@ pub fn health_vacuum(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<V
 }
 
 #[post("/health/wal-checkpoint")]
-pub fn health_wal_checkpoint(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn health_wal_checkpoint(_admin: HealthManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let s: &dyn Store = &**store.inner();
     if s.db_backend() != "sqlite" {
         return json_tool_result(crate::health::ToolResult {
@@ -103,7 +125,7 @@ pub fn health_wal_checkpoint(_admin: AdminUser, store: &State<Arc<dyn Store>>) -
 }
 
 #[post("/health/integrity-check")]
-pub fn health_integrity_check(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn health_integrity_check(_admin: HealthManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let s: &dyn Store = &**store.inner();
     if s.db_backend() != "sqlite" {
         return json_tool_result(crate::health::ToolResult {
@@ -133,28 +155,33 @@ pub fn health_integrity_check(_admin: AdminUser, store: &State<Arc<dyn Store>>)
 }
 
 #[post("/health/session-cleanup")]
-pub fn health_session_cleanup(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn health_session_cleanup(_admin: HealthManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let s: &dyn Store = &**store.inner();
     let r = crate::health::run_session_cleanup(s);
     json_tool_result(r)
 }
 
+#[post("/health/page-cache-clear")]
+pub fn health_page_cache_clear(_admin: HealthManager) -> Json<Value> {
+    json_tool_result(crate::health::run_page_cache_clear())
+}
+
 #[post("/health/orphan-scan")]
-pub fn health_orphan_scan(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn health_orphan_scan(_admin: HealthManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let s: &dyn Store = &**store.inner();
     let r = crate::health::run_orphan_scan(s, "website/site/uploads");
     json_tool_result(r)
 }
 
 #[post("/health/orphan-delete")]
-pub fn health_orphan_delete(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn health_orphan_delete(_admin: HealthManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let s: &dyn Store = &**store.inner();
     let r = crate::health::run_orphan_delete(s, "website/site/uploads");
     json_tool_result(r)
 }
 
 #[post("/health/unused-tags")]
-pub fn health_unused_tags(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn health_unused_tags(_admin: HealthManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let s: &dyn Store = &**store.inner();
     let r = crate::health::run_unused_tags_cleanup(s);
     json_tool_result(r)
@@ -167,7 +194,7 @@ pub struct AnalyticsPruneForm {
 
 #[post("/health/analytics-prune", format = "json", data = "<body>")]
 pub fn health_analytics_prune(
-    _admin: AdminUser,
+    _admin: HealthManager,
     store: &State<Arc<dyn Store>>,
     body: Json<AnalyticsPruneForm>,
 ) -> Json<Value> {
@@ -177,89 +204,74 @@ pub fn health_analytics_prune(
 }
 
 #[post("/health/export-db")]
-pub fn health_export_db(_admin: AdminUser) -> Json<Value> {
+pub fn health_export_db(_admin: HealthManager, _sudo: SudoMode) -> Json<Value> {
     let r = crate::health::export_database();
     json_tool_result(r)
 }
 
 #[post("/health/export-content")]
-pub fn health_export_content(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn health_export_content(_admin: HealthManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let s: &dyn Store = &**store.inner();
     let r = crate::health::export_content(s);
     json_tool_result(r)
 }
 
+#[post("/health/backup-run")]
+pub async fn health_backup_run(
+    _admin: HealthManager,
+    _sudo: SudoMode,
+    store: &State<Arc<dyn Store>>,
+) -> Json<Value> {
+    let s = Arc::clone(store.inner());
+    let r = rocket::tokio::task::spawn_blocking(move || crate::health::run_backup(&*s))
+        .await
+        .unwrap_or_else(|e| crate::health::ToolResult {
+            ok: false,
+            message: format!("Backup task failed: {}", e),
+            details: None,
+        });
+    json_tool_result(r)
+}
+
+#[post("/health/search-reindex")]
+pub async fn health_search_reindex(
+    _admin: HealthManager,
+    store: &State<Arc<dyn Store>>,
+) -> Json<Value> {
+    let s = Arc::clone(store.inner());
+    let r = rocket::tokio::task::spawn_blocking(move || crate::health::run_search_reindex(&*s))
+        .await
+        .unwrap_or_else(|e| crate::health::ToolResult {
+            ok: false,
+            message: format!("Reindex task failed: {}", e),
+            details: None,
+        });
+    json_tool_result(r)
+}
+
 #[get("/health/export-site")]
 pub fn health_export_site(
-    _admin: AdminUser,
+    _admin: HealthManager,
     store: &State<Arc<dyn Store>>,
-) -> Result<ZipDownload, rocket::http::Status> {
+) -> Result<ZipFileDownload, rocket::http::Status> {
     let s: &dyn Store = &**store.inner();
-
-    // Build full export JSON
-    let json_data = s
-        .health_export_full()
-        .map_err(|_| rocket::http::Status::InternalServerError)?;
-    let json_bytes = serde_json::to_string_pretty(&json_data)
-        .map_err(|_| rocket::http::Status::InternalServerError)?;
-
-    // Build ZIP in memory
-    let mut buf = Cursor::new(Vec::new());
-    {
-        let mut zip = ZipWriter::new(&mut buf);
-        let options =
-            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
-
-        // Add export.json
-        zip.start_file("export.json", options)
-            .map_err(|_| rocket::http::Status::InternalServerError)?;
-        zip.write_all(json_bytes.as_bytes())
-            .map_err(|_| rocket::http::Status::InternalServerError)?;
-
-        // Walk uploads/ directory and add all files
-        let uploads_dir = std::path::Path::new("website/site/uploads");
-        if uploads_dir.is_dir() {
-            add_dir_to_zip(&mut zip, uploads_dir, "uploads", options)
-                .map_err(|_| rocket::http::Status::InternalServerError)?;
-        }
-
-        zip.finish()
-            .map_err(|_| rocket::http::Status::InternalServerError)?;
-    }
-
-    let zip_bytes = buf.into_inner();
+    let error = |_: String| rocket::http::Status::InternalServerError;
+    let archive = crate::backup::build_archive(s).map_err(error)?;
+    let len = archive.size().map_err(error)?;
+    // The name is removed once the handle is taken; the open file streams on
+    let file = archive.into_file().map_err(error)?;
     let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
     let filename = format!("velocty_export_{}.zip", timestamp);
 
-    Ok(ZipDownload {
+    Ok(ZipFileDownload {
         filename,
-        data: zip_bytes,
+        file,
+        len,
     })
 }
 
-fn add_dir_to_zip<W: Write + std::io::Seek>(
-    zip: &mut ZipWriter<W>,
-    dir: &std::path::Path,
-    prefix: &str,
-    options: SimpleFileOptions,
-) -> Result<(), Box<dyn std::error::Error>> {
-    for entry in std::fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
-        if path.is_dir() {
-            add_dir_to_zip(zip, &path, &name, options)?;
-        } else if path.is_file() {
-            zip.start_file(&name, options)?;
-            let data = std::fs::read(&path)?;
-            zip.write_all(&data)?;
-        }
-    }
-    Ok(())
-}
-
 #[post("/health/mongo-ping")]
-pub fn health_mongo_ping(_admin: AdminUser) -> Json<Value> {
+pub fn health_mongo_ping(_admin: HealthManager) -> Json<Value> {
     let uri = crate::health::read_db_backend();
     if uri != "mongodb" {
         return Json(
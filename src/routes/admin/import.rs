@@ -3,15 +3,19 @@ use std::io::{Cursor, Read};
 use std::sync::Arc;
 
 use rocket::data::{Data, ToByteUnit};
+use rocket::form::Form;
+use rocket::fs::TempFile;
 use rocket::response::{Flash, Redirect};
 use rocket::serde::json::Json;
+use rocket::tokio::io::AsyncReadExt;
 use rocket::State;
 use rocket_dyn_templates::Template;
 use serde::Deserialize;
 use serde_json::json;
 
 use super::admin_base;
-use crate::security::auth::AdminUser;
+use crate::security::auth::Importer;
+use crate::security::sudo::SudoMode;
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -19,12 +23,31 @@ use crate::AdminSlug;
 
 #[get("/import")]
 pub fn import_page(
-    _admin: AdminUser,
+    _admin: Importer,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     flash: Option<rocket::request::FlashMessage<'_>>,
 ) -> Template {
-    let history = store.import_list();
+    // Each import's log lines, with the failures picked out for the table
+    let history: Vec<serde_json::Value> = store
+        .import_list()
+        .into_iter()
+        .map(|imp| {
+            let lines: Vec<String> = imp
+                .log
+                .as_deref()
+                .and_then(|l| serde_json::from_str(l).ok())
+                .unwrap_or_default();
+            let problems: Vec<&String> = lines
+                .iter()
+                .filter(|l| l.contains("failed") || l.to_lowercase().contains("skipped"))
+                .collect();
+            let mut v = json!(imp);
+            v["problems"] = json!(problems);
+            v["can_rollback"] = json!(imp.rolled_back_at.is_none() && !imp.created.is_empty());
+            v
+        })
+        .collect();
 
     let mut context = json!({
         "page_title": "Import",
@@ -41,11 +64,48 @@ pub fn import_page(
     Template::render("admin/import/index", &context)
 }
 
+// ── POST: Roll Back an Import ──────────────────────────
+
+#[post("/import/<id>/rollback")]
+pub fn import_rollback(
+    _admin: Importer,
+    _sudo: SudoMode,
+    store: &State<Arc<dyn Store>>,
+    id: i64,
+) -> Json<serde_json::Value> {
+    let s: &dyn Store = &**store.inner();
+    let source = s
+        .import_find_by_id(id)
+        .map(|i| i.source)
+        .unwrap_or_default();
+    match crate::import::rollback(s, id) {
+        Ok(r) => {
+            crate::seo::sitemap::invalidate();
+            let details = format!(
+                "{} posts, {} portfolio items, {} comments, {} categories, {} tags, {} users, {} media files",
+                r.posts, r.portfolio, r.comments, r.categories, r.tags, r.users, r.media
+            );
+            s.audit_log(
+                Some(_admin.user.id),
+                Some(&_admin.user.display_name),
+                "import_rollback",
+                Some("import"),
+                Some(id),
+                Some(&source),
+                Some(&details),
+                None,
+            );
+            Json(json!({ "ok": true, "removed": r, "message": format!("Removed {}.", details) }))
+        }
+        Err(e) => Json(json!({ "ok": false, "error": e })),
+    }
+}
+
 // ── POST: WordPress Import ─────────────────────────────
 
 #[post("/import/wordpress", data = "<data>")]
 pub async fn import_wordpress(
-    _admin: AdminUser,
+    _admin: Importer,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     data: Data<'_>,
@@ -65,8 +125,53 @@ pub async fn import_wordpress(
     let s: &dyn Store = &**store.inner();
     let xml_content = String::from_utf8_lossy(&bytes).to_string();
 
-    match crate::import::wordpress::import_wxr(s, &xml_content) {
+    wxr_flash(
+        crate::import::wordpress::import_wxr(s, &xml_content),
+        "WordPress",
+        redirect_url,
+    )
+}
+
+// ── POST: Squarespace Import (WXR) ─────────────────────
+
+#[post("/import/squarespace", data = "<data>")]
+pub async fn import_squarespace(
+    _admin: Importer,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    data: Data<'_>,
+) -> Flash<Redirect> {
+    let redirect_url = format!("{}/import", admin_base(slug));
+
+    let bytes = match data.open(100.mebibytes()).into_bytes().await {
+        Ok(b) if b.is_complete() => b.into_inner(),
+        _ => {
+            return Flash::error(
+                Redirect::to(redirect_url),
+                "Failed to read upload data (max 100 MB).",
+            )
+        }
+    };
+
+    let s: &dyn Store = &**store.inner();
+    let xml_content = String::from_utf8_lossy(&bytes).to_string();
+
+    wxr_flash(
+        crate::import::squarespace::import_squarespace(s, &xml_content),
+        "Squarespace",
+        redirect_url,
+    )
+}
+
+/// Flash message summarising a WXR (WordPress or Squarespace) import
+fn wxr_flash(
+    result: Result<crate::import::wordpress::WpImportResult, String>,
+    platform: &str,
+    redirect_url: String,
+) -> Flash<Redirect> {
+    match result {
         Ok(r) => {
+            crate::seo::sitemap::invalidate();
             let mut msg = format!(
                 "Imported {} posts, {} comments",
                 r.posts_imported, r.comments_imported
@@ -85,7 +190,125 @@ pub async fn import_wordpress(
         }
         Err(e) => Flash::error(
             Redirect::to(redirect_url),
-            format!("WordPress import failed: {}", e),
+            format!("{} import failed: {}", platform, e),
+        ),
+    }
+}
+
+// ── POST: Ghost Import ─────────────────────────────────
+
+#[derive(FromForm)]
+pub struct GhostImportForm<'f> {
+    pub file: TempFile<'f>,
+    pub source_url: Option<String>,
+}
+
+#[post("/import/ghost", data = "<form>")]
+pub async fn import_ghost(
+    _admin: Importer,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    form: Form<GhostImportForm<'_>>,
+) -> Flash<Redirect> {
+    let redirect_url = format!("{}/import", admin_base(slug));
+
+    let mut json_content = String::new();
+    let read = match form.file.open().await {
+        Ok(mut f) => f.read_to_string(&mut json_content).await.is_ok(),
+        Err(_) => false,
+    };
+    if !read {
+        return Flash::error(
+            Redirect::to(redirect_url),
+            "Failed to read the uploaded Ghost export.",
+        );
+    }
+
+    let s: &dyn Store = &**store.inner();
+    let source_url = form.source_url.as_deref().map(str::trim);
+    if let Some(url) = source_url.filter(|u| !u.is_empty()) {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Flash::error(
+                Redirect::to(redirect_url),
+                "The Ghost site URL must start with http:// or https://.",
+            );
+        }
+    }
+
+    match crate::import::ghost::import_ghost(s, &json_content, source_url) {
+        Ok(r) => {
+            crate::seo::sitemap::invalidate();
+            let mut msg = format!("Imported {} posts", r.posts_imported);
+            if r.authors_imported > 0 {
+                msg.push_str(&format!(", {} authors", r.authors_imported));
+            }
+            if r.media_downloaded > 0 {
+                msg.push_str(&format!(", {} media files downloaded", r.media_downloaded));
+            }
+            if r.media_failed > 0 {
+                msg.push_str(&format!(", {} media failed", r.media_failed));
+            }
+            if r.skipped > 0 {
+                msg.push_str(&format!(", {} skipped", r.skipped));
+            }
+            msg.push('.');
+            Flash::success(Redirect::to(redirect_url), msg)
+        }
+        Err(e) => Flash::error(
+            Redirect::to(redirect_url),
+            format!("Ghost import failed: {}", e),
+        ),
+    }
+}
+
+// ── POST: Markdown (Jekyll/Hugo) Import ────────────────
+
+#[derive(FromForm)]
+pub struct MarkdownImportForm<'f> {
+    pub file: TempFile<'f>,
+}
+
+#[post("/import/markdown", data = "<form>")]
+pub async fn import_markdown(
+    _admin: Importer,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    form: Form<MarkdownImportForm<'_>>,
+) -> Flash<Redirect> {
+    let redirect_url = format!("{}/import", admin_base(slug));
+
+    let mut bytes = Vec::new();
+    let read = match form.file.open().await {
+        Ok(mut f) => f.read_to_end(&mut bytes).await.is_ok(),
+        Err(_) => false,
+    };
+    if !read {
+        return Flash::error(
+            Redirect::to(redirect_url),
+            "Failed to read the uploaded ZIP.",
+        );
+    }
+
+    let s: &dyn Store = &**store.inner();
+    match crate::import::markdown::import_markdown_zip(s, &bytes) {
+        Ok(r) => {
+            crate::seo::sitemap::invalidate();
+            let mut msg = format!("Imported {} posts", r.posts_imported);
+            if r.media_downloaded > 0 {
+                msg.push_str(&format!(", {} media files downloaded", r.media_downloaded));
+            }
+            if r.media_failed > 0 {
+                msg.push_str(&format!(", {} media failed", r.media_failed));
+            }
+            if r.skipped > 0 {
+                msg.push_str(&format!(", {} skipped", r.skipped));
+            }
+            msg.push('.');
+            Flash::success(Redirect::to(redirect_url), msg)
+        }
+        Err(e) => Flash::error(
+            Redirect::to(redirect_url),
+            format!("Markdown import failed: {}", e),
         ),
     }
 }
@@ -94,7 +317,7 @@ pub async fn import_wordpress(
 
 #[post("/import/velocty", data = "<data>")]
 pub async fn import_velocty(
-    _admin: AdminUser,
+    _admin: Importer,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     data: Data<'_>,
@@ -131,6 +354,7 @@ pub async fn import_velocty(
 
     let s: &dyn Store = &**store.inner();
     let result = run_velocty_import(s, &export);
+    crate::seo::sitemap::invalidate();
 
     // Extract uploads from ZIP to disk
     let mut files_extracted = 0u64;
@@ -342,7 +566,7 @@ fn run_velocty_import(s: &dyn Store, export: &serde_json::Value) -> ImportCounts
                     }
                 })
                 .collect();
-            let hash = bcrypt::hash(&temp_pass, bcrypt::DEFAULT_COST).unwrap_or_default();
+            let hash = crate::security::auth::hash_password_for(s, &temp_pass).unwrap_or_default();
 
             if let Ok(new_id) = s.user_create(email, &hash, display_name, role) {
                 let _ = s.user_set_force_password_change(new_id, true);
@@ -408,6 +632,43 @@ fn run_velocty_import(s: &dyn Store, export: &serde_json::Value) -> ImportCounts
                 published_at: nonempty(published_at),
                 category_ids: None,
                 tag_ids: None,
+                robots_noindex: post
+                    .get("robots_noindex")
+                    .and_then(|v| v.as_i64())
+                    .map(|v| v != 0),
+                robots_nofollow: post
+                    .get("robots_nofollow")
+                    .and_then(|v| v.as_i64())
+                    .map(|v| v != 0),
+                canonical_url: nonempty(
+                    post.get("canonical_url")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(""),
+                ),
+                schema_type: nonempty(
+                    post.get("schema_type")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(""),
+                ),
+                focus_keyword: nonempty(
+                    post.get("focus_keyword")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(""),
+                ),
+                language: nonempty(post.get("language").and_then(|v| v.as_str()).unwrap_or("")),
+                translation_group: nonempty(
+                    post.get("translation_group")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(""),
+                ),
+                audio_file: nonempty(
+                    post.get("audio_file")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(""),
+                ),
+                audio_duration: post.get("audio_duration").and_then(|v| v.as_i64()),
+                audio_bytes: post.get("audio_bytes").and_then(|v| v.as_i64()),
+                template: nonempty(post.get("template").and_then(|v| v.as_str()).unwrap_or("")),
             };
             if let Ok(new_id) = s.post_create(&form) {
                 if old_id > 0 {
@@ -501,6 +762,30 @@ fn run_velocty_import(s: &dyn Store, export: &serde_json::Value) -> ImportCounts
                 published_at: nonempty(published_at),
                 category_ids: None,
                 tag_ids: None,
+                robots_noindex: item
+                    .get("robots_noindex")
+                    .and_then(|v| v.as_i64())
+                    .map(|v| v != 0),
+                robots_nofollow: item
+                    .get("robots_nofollow")
+                    .and_then(|v| v.as_i64())
+                    .map(|v| v != 0),
+                canonical_url: nonempty(
+                    item.get("canonical_url")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(""),
+                ),
+                schema_type: nonempty(
+                    item.get("schema_type")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(""),
+                ),
+                focus_keyword: nonempty(
+                    item.get("focus_keyword")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(""),
+                ),
+                template: nonempty(item.get("template").and_then(|v| v.as_str()).unwrap_or("")),
             };
             if let Ok(new_id) = s.portfolio_create(&form) {
                 if old_id > 0 {
@@ -725,11 +1010,12 @@ fn nonempty(s: &str) -> Option<String> {
 pub struct TumblrConfigInput {
     pub api_key: String,
     pub blog_url: String,
+    pub other_types: Option<String>,
 }
 
 #[post("/import/tumblr/config", data = "<body>")]
 pub fn tumblr_config(
-    _admin: AdminUser,
+    _admin: Importer,
     store: &State<Arc<dyn Store>>,
     body: Json<TumblrConfigInput>,
 ) -> Json<serde_json::Value> {
@@ -741,11 +1027,19 @@ pub fn tumblr_config(
     }
     let _ = s.setting_set("tumblr_api_key", key);
     let _ = s.setting_set("tumblr_blog_url", url);
+    if let Some(other) = body.other_types.as_deref() {
+        let other = if other == "journal" {
+            "journal"
+        } else {
+            "skip"
+        };
+        let _ = s.setting_set("tumblr_other_types", other);
+    }
     Json(json!({ "ok": true }))
 }
 
 #[post("/import/tumblr/start")]
-pub fn tumblr_start(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<serde_json::Value> {
+pub fn tumblr_start(_admin: Importer, store: &State<Arc<dyn Store>>) -> Json<serde_json::Value> {
     let s: &dyn Store = &**store.inner();
     let api_key = s.setting_get("tumblr_api_key").unwrap_or_default();
     let blog_url = s.setting_get("tumblr_blog_url").unwrap_or_default();
@@ -779,7 +1073,7 @@ pub struct TumblrPageInput {
 
 #[post("/import/tumblr/page", data = "<body>")]
 pub fn tumblr_page(
-    _admin: AdminUser,
+    _admin: Importer,
     store: &State<Arc<dyn Store>>,
     body: Json<TumblrPageInput>,
 ) -> Json<serde_json::Value> {
@@ -789,7 +1083,9 @@ pub fn tumblr_page(
     let settings = s.setting_all();
 
     let config = crate::import::tumblr::TumblrConfig { api_key, blog_url };
-    match crate::import::tumblr::import_page(s, &config, body.offset, &settings) {
+    let result = crate::import::tumblr::import_page(s, &config, body.offset, &settings);
+    crate::seo::sitemap::invalidate();
+    match result {
         Ok(result) => Json(json!({
             "ok": true,
             "items": result.items,
@@ -820,7 +1116,7 @@ pub struct TumblrSuggestItem {
 
 #[post("/import/tumblr/suggest", data = "<body>")]
 pub fn tumblr_suggest(
-    _admin: AdminUser,
+    _admin: Importer,
     store: &State<Arc<dyn Store>>,
     body: Json<TumblrSuggestInput>,
 ) -> Json<serde_json::Value> {
@@ -873,7 +1169,7 @@ pub struct TumblrApplyInput {
 
 #[post("/import/tumblr/apply", data = "<body>")]
 pub fn tumblr_apply(
-    _admin: AdminUser,
+    _admin: Importer,
     store: &State<Arc<dyn Store>>,
     body: Json<TumblrApplyInput>,
 ) -> Json<serde_json::Value> {
@@ -881,6 +1177,7 @@ pub fn tumblr_apply(
 
     match crate::import::tumblr::apply_updates(s, &body.updates) {
         Ok(count) => {
+            crate::seo::sitemap::invalidate();
             // Log the import
             let _ = s.import_create(
                 "tumblr",
@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use rocket::response::Redirect;
+use rocket::State;
+use rocket_dyn_templates::Template;
+use serde_json::json;
+
+use super::admin_base;
+use crate::mta::queue;
+use crate::security::auth::MailManager;
+use crate::store::Store;
+use crate::AdminSlug;
+
+// ── Mail Queue (built-in MTA) ──────────────────────────
+
+#[get("/mail-queue?<status>&<page>")]
+pub fn mail_queue_list(
+    _admin: MailManager,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    status: Option<String>,
+    page: Option<i64>,
+) -> Template {
+    let per_page: i64 = 25;
+    let current_page = page.unwrap_or(1).max(1);
+    let offset = (current_page - 1) * per_page;
+    let status = status.filter(|s| ["pending", "sending", "sent", "failed"].contains(&s.as_str()));
+
+    let messages = store.mta_queue_list(status.as_deref(), per_page, offset);
+    let total = store.mta_queue_count(status.as_deref());
+    let total_pages = ((total as f64) / (per_page as f64)).ceil() as i64;
+
+    let settings = store.setting_all();
+    let rate_limit = settings
+        .get("mta_max_emails_per_hour")
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(queue::DEFAULT_MAX_EMAILS_PER_HOUR);
+    let volume = queue::fill_hourly_buckets(
+        &store.mta_queue_sent_hourly(24),
+        24,
+        chrono::Utc::now().naive_utc(),
+    );
+    let volume_max = volume
+        .iter()
+        .map(|(_, c)| *c)
+        .max()
+        .unwrap_or(0)
+        .max(rate_limit)
+        .max(1);
+
+    let context = json!({
+        "page_title": "Mail Queue",
+        "admin_slug": slug.get(),
+        "settings": settings,
+        "messages": messages,
+        "status_filter": status,
+        "current_page": current_page,
+        "total_pages": total_pages,
+        "total": total,
+        "count_all": store.mta_queue_count(None),
+        "count_pending": store.mta_queue_count(Some("pending")),
+        "count_sending": store.mta_queue_count(Some("sending")),
+        "count_sent": store.mta_queue_count(Some("sent")),
+        "count_failed": store.mta_queue_count(Some("failed")),
+        "sent_last_hour": store.mta_queue_sent_last_hour().unwrap_or(0),
+        "rate_limit": rate_limit,
+        "volume": volume,
+        "volume_max": volume_max,
+    });
+    Template::render("admin/mail_queue/list", &context)
+}
+
+#[get("/mail-queue/<id>")]
+pub fn mail_queue_detail(
+    _admin: MailManager,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    id: i64,
+) -> Option<Template> {
+    let msg = store.mta_queue_get(id)?;
+    let context = json!({
+        "page_title": "Mail Queue",
+        "admin_slug": slug.get(),
+        "settings": store.setting_all(),
+        "message": msg,
+        "error_history": msg.error_history(),
+    });
+    Some(Template::render("admin/mail_queue/detail", &context))
+}
+
+#[post("/mail-queue/<id>/retry")]
+pub fn mail_queue_retry(
+    admin: MailManager,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    id: i64,
+) -> Redirect {
+    if store.mta_queue_retry(id).is_ok() {
+        store.audit_log(
+            Some(admin.user.id),
+            Some(&admin.user.display_name),
+            "retry",
+            Some("email_queue"),
+            Some(id),
+            None,
+            None,
+            None,
+        );
+    }
+    Redirect::to(format!("{}/mail-queue/{}", admin_base(slug), id))
+}
+
+#[post("/mail-queue/<id>/delete")]
+pub fn mail_queue_delete(
+    admin: MailManager,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    id: i64,
+) -> Redirect {
+    if store.mta_queue_delete(id).is_ok() {
+        store.audit_log(
+            Some(admin.user.id),
+            Some(&admin.user.display_name),
+            "delete",
+            Some("email_queue"),
+            Some(id),
+            None,
+            None,
+            None,
+        );
+    }
+    Redirect::to(format!("{}/mail-queue", admin_base(slug)))
+}
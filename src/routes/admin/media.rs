@@ -9,8 +9,9 @@ use rocket_dyn_templates::Template;
 use serde_json::{json, Value};
 
 use super::admin_base;
-use super::save_upload;
-use crate::security::auth::{AdminUser, AuthorUser, EditorUser};
+use super::save_owned_upload;
+use crate::security::auth::{DesignManager, MediaManager, MediaUploader};
+use crate::security::permissions;
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -27,6 +28,9 @@ pub struct MediaFile {
     pub is_video: bool,
     pub media_type: String,
     pub modified: String,
+    /// "processing" or "failed" while the upload waits in the optimization
+    /// queue, empty once it's ready
+    pub status: String,
 }
 
 /// Scan the uploads directory and return all media files sorted newest-first,
@@ -83,13 +87,7 @@ pub(crate) fn scan_media_files(store: &dyn Store) -> (Vec<MediaFile>, u64) {
                 })
                 .unwrap_or_default();
             let media_type = if is_image { "image" } else { "video" }.to_string();
-            let size_human = if size >= 1_048_576 {
-                format!("{:.1} MB", size as f64 / 1_048_576.0)
-            } else if size >= 1024 {
-                format!("{:.0} KB", size as f64 / 1024.0)
-            } else {
-                format!("{} B", size)
-            };
+            let size_human = human_size(size);
             files.push(MediaFile {
                 path: name.clone(),
                 name,
@@ -100,17 +98,59 @@ pub(crate) fn scan_media_files(store: &dyn Store) -> (Vec<MediaFile>, u64) {
                 is_video,
                 media_type,
                 modified,
+                status: String::new(),
             });
         }
     }
 
+    // Uploads still being optimized are listed under their final name
+    for job in store.media_job_list() {
+        if job.path.contains('/') || files.iter().any(|f| f.path == job.path) {
+            continue;
+        }
+        let size = std::fs::metadata(&job.source).map(|m| m.len()).unwrap_or(0);
+        total_disk_bytes += size;
+        files.push(MediaFile {
+            name: job.path.clone(),
+            path: job.path.clone(),
+            size,
+            size_human: human_size(size),
+            ext: job.path.rsplit('.').next().unwrap_or("").to_lowercase(),
+            is_image: true,
+            is_video: false,
+            media_type: "image".to_string(),
+            modified: job
+                .created_at
+                .chars()
+                .take(16)
+                .collect::<String>()
+                .replace('T', " "),
+            status: if job.status == "failed" {
+                "failed"
+            } else {
+                "processing"
+            }
+            .to_string(),
+        });
+    }
+
     files.sort_by(|a, b| b.modified.cmp(&a.modified));
     (files, total_disk_bytes)
 }
 
+fn human_size(size: u64) -> String {
+    if size >= 1_048_576 {
+        format!("{:.1} MB", size as f64 / 1_048_576.0)
+    } else if size >= 1024 {
+        format!("{:.0} KB", size as f64 / 1024.0)
+    } else {
+        format!("{} B", size)
+    }
+}
+
 #[get("/media?<page>&<filter>")]
 pub fn media_library(
-    _admin: EditorUser,
+    _admin: MediaManager,
     slug: &State<AdminSlug>,
     store: &State<Arc<dyn Store>>,
     page: Option<usize>,
@@ -172,7 +212,7 @@ pub fn media_library(
 
 #[get("/api/media?<page>&<filter>")]
 pub fn api_media_list(
-    _admin: AuthorUser,
+    _admin: MediaUploader,
     store: &State<Arc<dyn Store>>,
     page: Option<usize>,
     filter: Option<String>,
@@ -180,7 +220,13 @@ pub fn api_media_list(
     let per_page = 60usize;
     let current_page = page.unwrap_or(1).max(1);
 
-    let (files, _) = scan_media_files(&**store.inner());
+    let s: &dyn Store = &**store.inner();
+    let (mut files, _) = scan_media_files(s);
+    // Without the media library, the picker only offers the user's own uploads
+    if !permissions::sees_all_media(s, &_admin.user) {
+        let own = s.media_list_by_owner(_admin.user.id);
+        files.retain(|f| own.contains(&f.path));
+    }
 
     let filtered: Vec<&MediaFile> = match filter.as_deref() {
         Some(f) if !f.is_empty() => files.iter().filter(|file| file.media_type == f).collect(),
@@ -207,7 +253,7 @@ pub fn api_media_list(
 
 #[post("/media/<filename>/delete")]
 pub fn media_delete(
-    _admin: EditorUser,
+    _admin: MediaManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     filename: &str,
@@ -222,6 +268,21 @@ pub fn media_delete(
         return Redirect::to(format!("{}/media", admin_base(slug)));
     }
     let path = std::path::Path::new("website/site/uploads").join(filename);
+    // Not in the uploads directory yet: drop it from the optimization queue
+    if !path.exists() && crate::images::queue::discard(&**store.inner(), filename) == Ok(true) {
+        let _ = store.media_remove_owner(filename);
+        store.audit_log(
+            Some(_admin.user.id),
+            Some(&_admin.user.display_name),
+            "delete",
+            Some("media"),
+            None,
+            Some(filename),
+            None,
+            None,
+        );
+        return Redirect::to(format!("{}/media", admin_base(slug)));
+    }
     // Verify canonical path is still under uploads dir (defense-in-depth against symlinks/encoding tricks)
     let uploads_base = std::path::Path::new("website/site/uploads");
     let safe = match (uploads_base.canonicalize(), path.canonicalize()) {
@@ -234,6 +295,7 @@ pub fn media_delete(
     }
     if path.is_file() {
         let _ = std::fs::remove_file(&path);
+        let _ = store.media_remove_owner(filename);
         store.audit_log(
             Some(_admin.user.id),
             Some(&_admin.user.display_name),
@@ -257,14 +319,14 @@ pub struct ImageUploadForm<'f> {
 
 #[post("/upload/image", data = "<form>")]
 pub async fn upload_image(
-    _admin: AuthorUser,
+    _admin: MediaUploader,
     store: &State<Arc<dyn Store>>,
     mut form: Form<ImageUploadForm<'_>>,
 ) -> Json<Value> {
     if !super::is_allowed_media(&form.file, &**store.inner()) {
         return Json(json!({ "error": "File type not allowed" }));
     }
-    match save_upload(&mut form.file, "editor", &**store.inner()).await {
+    match save_owned_upload(&mut form.file, "editor", &**store.inner(), _admin.user.id).await {
         Some(filename) => Json(json!({ "location": format!("/uploads/{}", filename) })),
         None => Json(json!({ "error": "Upload failed" })),
     }
@@ -280,7 +342,7 @@ pub struct FontUploadForm<'f> {
 
 #[post("/upload/font", data = "<form>")]
 pub async fn upload_font(
-    _admin: AdminUser,
+    _admin: DesignManager,
     store: &State<Arc<dyn Store>>,
     mut form: Form<FontUploadForm<'_>>,
 ) -> Json<Value> {
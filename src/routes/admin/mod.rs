@@ -1,10 +1,13 @@
 use image::ImageEncoder;
 use rocket::fs::TempFile;
 
+use crate::store::cursor::Cursor;
 use crate::store::Store;
 use crate::AdminSlug;
 
+pub mod ai_usage;
 pub mod api;
+pub mod api_keys;
 pub mod categories;
 pub mod comments;
 pub mod dashboard;
@@ -12,9 +15,11 @@ pub mod designs;
 pub mod firewall;
 pub mod health;
 pub mod import;
+pub mod mail_queue;
 pub mod media;
 pub mod portfolio;
 pub mod posts;
+pub mod redirects;
 pub mod sales;
 pub mod seo_audit;
 pub mod settings;
@@ -60,6 +65,71 @@ pub(crate) fn resolve_status(status: &str, published_at: &Option<String>) -> Str
     status.to_string()
 }
 
+/// A per-item canonical URL from the editor: an absolute http(s) URL or a
+/// site path, otherwise none (use the page's own URL).
+pub(crate) fn canonical_override(raw: Option<&str>) -> Option<String> {
+    let url = raw?.trim();
+    let valid = url.starts_with("https://")
+        || url.starts_with("http://")
+        || (url.starts_with('/') && !url.starts_with("//"));
+    (valid && !url.contains(char::is_whitespace)).then(|| url.to_string())
+}
+
+/// Downgrade a published or scheduled status to draft when `user` lacks the
+/// publish capability for the content type.
+pub(crate) fn gate_publish(
+    store: &dyn Store,
+    user: &crate::models::user::User,
+    capability: &str,
+    status: &mut String,
+) {
+    if (status == "published" || status == "scheduled")
+        && !crate::security::permissions::user_can(store, user, capability)
+    {
+        *status = "draft".to_string();
+    }
+}
+
+/// Numbered pages a long admin list links to; past the last one it
+/// continues by cursor
+pub(crate) const NUMBERED_PAGES: i64 = 10;
+
+/// One page of a keyset-paginated admin list
+pub(crate) struct ListPage<T> {
+    pub items: Vec<T>,
+    /// The numbered page shown, or None when continuing from a cursor
+    pub page: Option<i64>,
+    /// Encoded cursor for the page after this one, if there is one
+    pub next_cursor: Option<String>,
+}
+
+/// The page after the `after` cursor, or numbered page `page` (at most
+/// `NUMBERED_PAGES`). `fetch` is one of the Store's `*_keyset` methods.
+pub(crate) fn list_page<T>(
+    after: Option<&str>,
+    page: Option<i64>,
+    per_page: i64,
+    fetch: impl FnOnce(Option<&Cursor>, i64) -> (Vec<T>, Option<Cursor>),
+) -> ListPage<T> {
+    if let Some(cursor) = after.and_then(Cursor::decode) {
+        let (items, next) = fetch(Some(&cursor), per_page);
+        return ListPage {
+            items,
+            page: None,
+            next_cursor: next.map(|c| c.encode()),
+        };
+    }
+    let page = page.unwrap_or(1).clamp(1, NUMBERED_PAGES);
+    let (mut items, next) = fetch(None, page * per_page);
+    let skip = ((page - 1) * per_page) as usize;
+    items.drain(..skip.min(items.len()));
+    ListPage {
+        items,
+        page: Some(page),
+        next_cursor: next.map(|c| c.encode()),
+    }
+}
+
 /// Compute the subdirectory for a media file based on the `media_organization` setting.
 /// `prefix` is the category/type (e.g. "post", "portfolio", "editor").
 /// Returns "" for flat, or a path like "2026/" or "2026/02/" or "portfolio/2026/" etc.
@@ -133,6 +203,47 @@ pub(crate) async fn save_upload(
         return None;
     }
 
+    // Resizing and WebP conversion happen in the background; the original
+    // is served under the final name until then
+    let ext_lower = ext.to_lowercase();
+    if ext_lower != "svg"
+        && !is_video_filename(&filename)
+        && crate::images::queue::needs_processing(store, &ext_lower)
+    {
+        match crate::images::queue::enqueue(store, &filename, &ext) {
+            Ok(final_name) => return Some(final_name),
+            Err(e) => log::warn!("[media] Could not queue {}: {}", filename, e),
+        }
+    }
+
+    process_upload(store, filename, prefix, &uid, &ext)
+}
+
+/// `save_upload`, recording `user_id` as the file's owner so it shows in
+/// their media picker
+pub(crate) async fn save_owned_upload(
+    file: &mut TempFile<'_>,
+    prefix: &str,
+    store: &dyn Store,
+    user_id: i64,
+) -> Option<String> {
+    let filename = save_upload(file, prefix, store).await?;
+    let _ = store.media_set_owner(&filename, user_id);
+    Some(filename)
+}
+
+/// Post-process a file already written to `website/site/uploads/<filename>`:
+/// sanitize SVGs, convert HEIC, then optimize and convert to WebP per the
+/// media settings. Returns the final filename, or None if it was rejected.
+pub(crate) fn process_upload(
+    store: &dyn Store,
+    filename: String,
+    prefix: &str,
+    uid: &uuid::Uuid,
+    ext: &str,
+) -> Option<String> {
+    let upload_dir = std::path::Path::new("website/site/uploads");
+    let dest = upload_dir.join(&filename);
     let ext_lower = ext.to_lowercase();
 
     // ── SVG files: sanitize to remove scripts, event handlers, etc. ──
@@ -186,7 +297,7 @@ pub(crate) async fn save_upload(
         // If WebP conversion is enabled, convert the JPG to WebP
         if store.setting_get_bool("images_webp_convert") {
             if let Some(webp_name) =
-                convert_to_webp_file(&jpg_dest, prefix, &uid, upload_dir, quality)
+                convert_to_webp_file(&jpg_dest, prefix, uid, upload_dir, quality)
             {
                 let _ = std::fs::remove_file(&jpg_dest);
                 return Some(webp_name);
@@ -201,7 +312,7 @@ pub(crate) async fn save_upload(
 
     // ── WebP conversion for other image types ──
     if store.setting_get_bool("images_webp_convert") && ext_lower != "webp" && ext_lower != "svg" {
-        if let Some(webp_name) = convert_to_webp_file(&dest, prefix, &uid, upload_dir, quality) {
+        if let Some(webp_name) = convert_to_webp_file(&dest, prefix, uid, upload_dir, quality) {
             let _ = std::fs::remove_file(&dest);
             return Some(webp_name);
         }
@@ -210,6 +321,53 @@ pub(crate) async fn save_upload(
     Some(filename)
 }
 
+/// Write the optimized rendition of the image at `src` to `dest`: HEIC is
+/// converted to JPEG, then resized / re-encoded per the media settings, and
+/// encoded as WebP when `dest` ends in `.webp`. `src` is left in place, and
+/// `dest` only appears once it's complete.
+pub(crate) fn optimize_into(
+    store: &dyn Store,
+    src: &std::path::Path,
+    dest: &std::path::Path,
+) -> Result<(), String> {
+    let quality = {
+        let q = store.setting_get_i64("images_quality") as u8;
+        if q == 0 {
+            85
+        } else {
+            q
+        }
+    };
+    let ext_of = |p: &std::path::Path| {
+        p.extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+    };
+    let src_ext = ext_of(src);
+    let heic = src_ext == "heic" || src_ext == "heif";
+    let ext = if heic { "jpg".to_string() } else { src_ext };
+    let work = src.with_extension(format!("work.{}", ext));
+    if heic {
+        if !convert_heic_to_jpg(src, &work, quality) {
+            return Err(
+                "HEIC conversion failed; is sips, magick or heif-convert installed?".into(),
+            );
+        }
+    } else {
+        std::fs::copy(src, &work).map_err(|e| e.to_string())?;
+    }
+
+    optimize_image(store, &work, &ext, quality);
+
+    let result = if ext_of(dest) == "webp" && ext != "webp" {
+        encode_webp(&work, dest, quality)
+    } else {
+        std::fs::rename(&work, dest).map_err(|e| e.to_string())
+    };
+    let _ = std::fs::remove_file(&work);
+    result
+}
+
 /// Optimize an image on disk: max dimension resize, re-encode JPEG/PNG, strip EXIF.
 /// This modifies the file in-place.
 fn optimize_image(store: &dyn Store, path: &std::path::Path, ext: &str, quality: u8) {
@@ -348,15 +506,25 @@ fn convert_to_webp_file(
     upload_dir: &std::path::Path,
     quality: u8,
 ) -> Option<String> {
-    let img = image::open(src).ok()?;
+    let webp_filename = format!("{}_{}.webp", prefix, uid);
+    encode_webp(src, &upload_dir.join(&webp_filename), quality).ok()?;
+    Some(webp_filename)
+}
+
+/// Encode the image at `src` as WebP into `dest`, via a temporary file so
+/// `dest` never holds a partial image
+fn encode_webp(src: &std::path::Path, dest: &std::path::Path, quality: u8) -> Result<(), String> {
+    let img = image::open(src).map_err(|e| e.to_string())?;
     let (w, h) = image::GenericImageView::dimensions(&img);
     let rgba = img.to_rgba8();
     let encoder = webp::Encoder::from_rgba(&rgba, w, h);
     let webp_data = encoder.encode(quality as f32);
-    let webp_filename = format!("{}_{}.webp", prefix, uid);
-    let webp_dest = upload_dir.join(&webp_filename);
-    std::fs::write(&webp_dest, &*webp_data).ok()?;
-    Some(webp_filename)
+    let tmp = dest.with_extension("webp.part");
+    std::fs::write(&tmp, &*webp_data).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, dest).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        e.to_string()
+    })
 }
 
 /// Extract the file extension from a TempFile (content-type → raw_name → field name)
@@ -461,9 +629,24 @@ pub fn routes() -> Vec<rocket::Route> {
         categories::tag_delete,
         designs::designs_list,
         designs::design_activate,
+        designs::design_preview,
+        designs::design_preview_exit,
+        designs::design_custom_code,
+        designs::design_error_pages,
+        designs::design_template_history,
+        designs::design_template_restore,
+        designs::design_parent,
+        designs::design_export,
+        designs::design_widget_save,
+        designs::design_widget_delete,
+        designs::design_import,
         designs::design_overview,
         import::import_page,
+        import::import_rollback,
         import::import_wordpress,
+        import::import_squarespace,
+        import::import_ghost,
+        import::import_markdown,
         import::import_velocty,
         import::tumblr_config,
         import::tumblr_start,
@@ -472,6 +655,9 @@ pub fn routes() -> Vec<rocket::Route> {
         import::tumblr_apply,
         settings::settings_page,
         settings::settings_save,
+        settings::settings_export,
+        settings::settings_import_preview,
+        settings::settings_import,
         media::media_library,
         media::api_media_list,
         media::media_delete,
@@ -482,6 +668,7 @@ pub fn routes() -> Vec<rocket::Route> {
         health::health_wal_checkpoint,
         health::health_integrity_check,
         health::health_session_cleanup,
+        health::health_page_cache_clear,
         health::health_orphan_scan,
         health::health_orphan_delete,
         health::health_unused_tags,
@@ -489,29 +676,58 @@ pub fn routes() -> Vec<rocket::Route> {
         health::health_export_db,
         health::health_export_content,
         health::health_export_site,
+        health::health_backup_run,
+        health::health_search_reindex,
         health::health_mongo_ping,
         users::mfa_setup,
         users::mfa_verify,
         users::mfa_disable,
+        users::mfa_method,
         users::mfa_recovery_codes,
         users::passkey_list,
         users::passkey_register_start,
         users::passkey_register_finish,
         users::passkey_delete,
+        users::passwordless_enable,
+        users::passwordless_disable,
+        users::sudo_confirm,
         sales::sales_dashboard,
         sales::sales_orders,
         firewall::firewall_dashboard,
         firewall::firewall_ban,
         firewall::firewall_unban,
+        firewall::firewall_unban_all,
+        firewall::firewall_rule_save,
+        firewall::firewall_rule_delete,
+        firewall::firewall_audit_export,
+        ai_usage::ai_usage_page,
+        mail_queue::mail_queue_list,
+        mail_queue::mail_queue_detail,
+        mail_queue::mail_queue_retry,
+        mail_queue::mail_queue_delete,
         users::users_list,
         users::user_create,
+        users::user_invite,
+        users::user_resend_invite,
         users::user_update,
         users::user_avatar_upload,
         users::user_lock,
         users::user_unlock,
         users::user_reset_password,
         users::user_delete,
+        users::user_export,
+        users::user_erase,
+        users::roles_page,
+        users::role_save,
+        users::role_delete,
         seo_audit::seo_audit_dashboard,
+        redirects::redirects_page,
+        redirects::redirect_save,
+        redirects::redirect_delete,
+        redirects::redirect_import,
+        redirects::not_found_dismiss,
+        api_keys::api_key_create,
+        api_keys::api_key_delete,
     ]
 }
 
@@ -525,12 +741,26 @@ pub fn api_routes() -> Vec<rocket::Route> {
         api::stats_top_portfolio,
         api::stats_top_referrers,
         api::stats_tags,
+        api::stats_campaigns,
+        api::stats_utm,
+        api::stats_goals,
+        api::stats_goal_daily,
+        api::stats_experiments,
+        api::stats_outbound,
+        api::stats_searches,
+        api::stats_export,
+        api::stats_now,
+        api::stats_live,
         api::set_theme,
         api::seo_check_post,
         api::seo_check_portfolio,
         api::rotate_image_proxy_key,
         api::seo_score_summary,
         api::seo_rescan_all,
+        api::seo_ai_suggest,
+        api::seo_ai_suggest_low_scoring,
+        api::seo_ai_apply,
+        api::seo_ai_dismiss,
         api::pagespeed_fetch,
         api::moz_domain_fetch,
         api::moz_domain_cached,
@@ -10,9 +10,12 @@ use serde::Deserialize;
 use serde_json::json;
 
 use super::admin_base;
-use super::save_upload;
+use super::save_owned_upload;
+use crate::designs::templates;
 use crate::models::portfolio::PortfolioForm;
-use crate::security::auth::AuthorUser;
+use crate::security::auth::{PortfolioDeleter, PortfolioEditor};
+use crate::security::permissions;
+use crate::seo::jsonld;
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -20,7 +23,7 @@ use crate::AdminSlug;
 
 #[get("/portfolio?<status>&<page>&<per_page>")]
 pub fn portfolio_list(
-    _admin: AuthorUser,
+    _admin: PortfolioEditor,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     status: Option<String>,
@@ -62,7 +65,7 @@ pub fn portfolio_list(
 
 #[get("/portfolio/new")]
 pub fn portfolio_new(
-    _admin: AuthorUser,
+    _admin: PortfolioEditor,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
 ) -> Template {
@@ -79,6 +82,8 @@ pub fn portfolio_new(
         "settings": store.setting_all(),
         "ai_enabled": ai_enabled,
         "ai_has_vision": ai_has_vision,
+        "schema_types": jsonld::PORTFOLIO_SCHEMA_TYPES,
+        "content_templates": templates::for_active_design(&**store.inner(), "portfolio"),
     });
 
     Template::render("admin/portfolio/edit", &context)
@@ -86,7 +91,7 @@ pub fn portfolio_new(
 
 #[get("/portfolio/<id>/edit")]
 pub fn portfolio_edit(
-    _admin: AuthorUser,
+    _admin: PortfolioEditor,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     id: i64,
@@ -96,6 +101,11 @@ pub fn portfolio_edit(
     let tags = store.tag_list();
     let item_categories = store.category_for_content(id, "portfolio");
     let item_tags = store.tag_for_content(id, "portfolio");
+    let path = crate::render::slug_url(
+        &store.setting_get_or("portfolio_slug", "portfolio"),
+        &item.slug,
+    );
+    let analytics = crate::analytics::content_panel(&**store.inner(), &path);
 
     let ai_enabled = crate::ai::is_enabled(&**store.inner());
     let ai_has_vision = crate::ai::has_vision_provider(&**store.inner());
@@ -106,10 +116,13 @@ pub fn portfolio_edit(
         "tags": tags,
         "item_categories": item_categories.iter().map(|c| c.id).collect::<Vec<_>>(),
         "item_tags": item_tags.iter().map(|t| t.id).collect::<Vec<_>>(),
+        "analytics": analytics,
         "admin_slug": slug.get(),
         "settings": store.setting_all(),
         "ai_enabled": ai_enabled,
         "ai_has_vision": ai_has_vision,
+        "schema_types": jsonld::PORTFOLIO_SCHEMA_TYPES,
+        "content_templates": templates::for_active_design(&**store.inner(), "portfolio"),
     });
 
     Some(Template::render("admin/portfolio/edit", &context))
@@ -117,7 +130,7 @@ pub fn portfolio_edit(
 
 #[post("/portfolio/<id>/delete")]
 pub fn portfolio_delete(
-    _admin: AuthorUser,
+    _admin: PortfolioDeleter,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     id: i64,
@@ -128,6 +141,8 @@ pub fn portfolio_delete(
         .unwrap_or_default();
     let _ = store.portfolio_delete(id);
     store.search_remove_item("portfolio", id);
+    store.embedding_remove("portfolio", id);
+    let _ = store.seo_suggestion_clear("portfolio", id);
     store.audit_log(
         Some(_admin.user.id),
         Some(&_admin.user.display_name),
@@ -148,7 +163,7 @@ pub struct BulkDeleteInput {
 
 #[post("/portfolio/bulk-delete", data = "<body>")]
 pub fn portfolio_bulk_delete(
-    _admin: AuthorUser,
+    _admin: PortfolioDeleter,
     store: &State<Arc<dyn Store>>,
     body: Json<BulkDeleteInput>,
 ) -> Json<serde_json::Value> {
@@ -157,6 +172,8 @@ pub fn portfolio_bulk_delete(
         if store.portfolio_find_by_id(*id).is_some() {
             let _ = store.portfolio_delete(*id);
             store.search_remove_item("portfolio", *id);
+            store.embedding_remove("portfolio", *id);
+            let _ = store.seo_suggestion_clear("portfolio", *id);
             deleted += 1;
         }
     }
@@ -191,17 +208,29 @@ pub struct PortfolioFormData<'f> {
     pub published_at: Option<String>,
     pub category_ids: Option<Vec<i64>>,
     pub tag_names: Option<String>,
+    pub robots_noindex: Option<String>,
+    pub robots_nofollow: Option<String>,
+    pub canonical_url: Option<String>,
+    pub schema_type: Option<String>,
+    pub focus_keyword: Option<String>,
+    pub template: Option<String>,
     pub image: Option<TempFile<'f>>,
     pub uploaded_image_path: Option<String>,
 }
 
 #[post("/portfolio/new", data = "<form>")]
 pub async fn portfolio_create(
-    _admin: AuthorUser,
+    _admin: PortfolioEditor,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     mut form: Form<PortfolioFormData<'_>>,
 ) -> Redirect {
+    super::gate_publish(
+        &**store.inner(),
+        &_admin.user,
+        permissions::PORTFOLIO_PUBLISH,
+        &mut form.status,
+    );
     let image_path = if form
         .uploaded_image_path
         .as_ref()
@@ -214,7 +243,7 @@ pub async fn portfolio_create(
                 if !super::is_allowed_media(f, &**store.inner()) {
                     return Redirect::to(format!("{}/portfolio/new", admin_base(slug)));
                 }
-                save_upload(f, "portfolio", &**store.inner())
+                save_owned_upload(f, "portfolio", &**store.inner(), _admin.user.id)
                     .await
                     .unwrap_or_else(|| "placeholder.jpg".to_string())
             }
@@ -253,6 +282,20 @@ pub async fn portfolio_create(
         },
         category_ids: form.category_ids.clone(),
         tag_ids: None,
+        robots_noindex: Some(form.robots_noindex.is_some()),
+        robots_nofollow: Some(form.robots_nofollow.is_some()),
+        canonical_url: super::canonical_override(form.canonical_url.as_deref()),
+        schema_type: jsonld::valid_schema_type(
+            form.schema_type.as_deref(),
+            jsonld::PORTFOLIO_SCHEMA_TYPES,
+        ),
+        focus_keyword: form
+            .focus_keyword
+            .as_deref()
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(String::from),
+        template: templates::normalize(form.template.as_deref(), "portfolio"),
     };
     let final_status = super::resolve_status(&form.status, &pf.published_at);
     let pf = PortfolioForm {
@@ -272,6 +315,7 @@ pub async fn portfolio_create(
                     body_html: form.description_html.as_deref().unwrap_or(""),
                     featured_image: &pf.image_path,
                     content_type: "portfolio",
+                    focus_keyword: pf.focus_keyword.as_deref().unwrap_or(""),
                 };
                 let audit = crate::seo::audit::compute_score(&seo_input);
                 let _ = store.portfolio_update_seo_score(
@@ -317,6 +361,10 @@ pub async fn portfolio_create(
                 Some(&final_status),
                 None,
             );
+            // Let search engines know (noindex content stays out of it)
+            if final_status == "published" && form.robots_noindex.is_none() {
+                crate::seo::ping::notify_content(store.inner(), "portfolio", &form.slug);
+            }
             if final_status == "draft" {
                 Redirect::to(format!(
                     "{}/portfolio/{}/edit?saved=draft",
@@ -335,12 +383,18 @@ pub async fn portfolio_create(
 
 #[post("/portfolio/<id>/edit", data = "<form>")]
 pub async fn portfolio_update(
-    _admin: AuthorUser,
+    _admin: PortfolioEditor,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     id: i64,
     mut form: Form<PortfolioFormData<'_>>,
 ) -> Redirect {
+    super::gate_publish(
+        &**store.inner(),
+        &_admin.user,
+        permissions::PORTFOLIO_PUBLISH,
+        &mut form.status,
+    );
     let image_path = if form
         .uploaded_image_path
         .as_ref()
@@ -353,7 +407,7 @@ pub async fn portfolio_update(
                 if !super::is_allowed_media(f, &**store.inner()) {
                     return Redirect::to(format!("{}/portfolio/{}/edit", admin_base(slug), id));
                 }
-                save_upload(f, "portfolio", &**store.inner())
+                save_owned_upload(f, "portfolio", &**store.inner(), _admin.user.id)
                     .await
                     .unwrap_or_else(|| "placeholder.jpg".to_string())
             }
@@ -401,6 +455,20 @@ pub async fn portfolio_update(
         },
         category_ids: form.category_ids.clone(),
         tag_ids: None,
+        robots_noindex: Some(form.robots_noindex.is_some()),
+        robots_nofollow: Some(form.robots_nofollow.is_some()),
+        canonical_url: super::canonical_override(form.canonical_url.as_deref()),
+        schema_type: jsonld::valid_schema_type(
+            form.schema_type.as_deref(),
+            jsonld::PORTFOLIO_SCHEMA_TYPES,
+        ),
+        focus_keyword: form
+            .focus_keyword
+            .as_deref()
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(String::from),
+        template: templates::normalize(form.template.as_deref(), "portfolio"),
     };
     let final_status = super::resolve_status(&form.status, &pf.published_at);
     let pf = PortfolioForm {
@@ -419,6 +487,7 @@ pub async fn portfolio_update(
             body_html: form.description_html.as_deref().unwrap_or(""),
             featured_image: &pf.image_path,
             content_type: "portfolio",
+            focus_keyword: pf.focus_keyword.as_deref().unwrap_or(""),
         };
         let audit = crate::seo::audit::compute_score(&seo_input);
         let _ = store.portfolio_update_seo_score(
@@ -465,6 +534,10 @@ pub async fn portfolio_update(
         Some(&final_status),
         None,
     );
+    // Let search engines know (noindex content stays out of it)
+    if final_status == "published" && form.robots_noindex.is_none() {
+        crate::seo::ping::notify_content(store.inner(), "portfolio", &form.slug);
+    }
     if final_status == "draft" {
         Redirect::to(format!(
             "{}/portfolio/{}/edit?saved=draft",
@@ -10,22 +10,28 @@ use serde::Deserialize;
 use serde_json::json;
 
 use super::admin_base;
-use super::save_upload;
+use super::save_owned_upload;
+use super::{list_page, NUMBERED_PAGES};
+use crate::designs::templates;
 use crate::models::post::PostForm;
-use crate::security::auth::{AuthorUser, EditorUser};
+use crate::models::user::User;
+use crate::security::auth::{PostDeleter, PostEditor};
+use crate::security::{members, permissions};
+use crate::seo::{hreflang, jsonld};
 use crate::store::Store;
 use crate::AdminSlug;
 
 // ── Posts ───────────────────────────────────────────────
 
-#[get("/posts?<status>&<page>&<per_page>")]
+#[get("/posts?<status>&<page>&<per_page>&<after>")]
 pub fn posts_list(
-    _admin: AuthorUser,
+    _admin: PostEditor,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     status: Option<String>,
     page: Option<i64>,
     per_page: Option<i64>,
+    after: Option<String>,
 ) -> Template {
     let per_page = match per_page.unwrap_or(50) {
         n if n >= 250 => 250,
@@ -34,26 +40,35 @@ pub fn posts_list(
         n if n >= 100 => 100,
         _ => 50,
     };
-    let current_page = page.unwrap_or(1).max(1);
-    let offset = (current_page - 1) * per_page;
 
-    let posts = store.post_list(status.as_deref(), per_page, offset);
-    let total = store.post_count(status.as_deref());
+    // Users who can't edit others' posts only see their own
+    let owner =
+        (!permissions::sees_all_posts(&**store.inner(), &_admin.user)).then_some(_admin.user.id);
+    let count = |status: Option<&str>| match owner {
+        Some(uid) => store.post_count_by_owner(uid, status),
+        None => store.post_count(status),
+    };
+    let list = list_page(after.as_deref(), page, per_page, |after, limit| {
+        store.post_list_keyset(owner, status.as_deref(), after, limit)
+    });
+    let total = count(status.as_deref());
     let total_pages = (total as f64 / per_page as f64).ceil() as i64;
 
     let context = json!({
         "page_title": "Journal",
-        "posts": posts,
-        "current_page": current_page,
+        "posts": list.items,
+        "current_page": list.page.unwrap_or(0),
         "total_pages": total_pages,
+        "page_links": total_pages.min(NUMBERED_PAGES),
+        "next_cursor": list.next_cursor,
         "total": total,
         "per_page": per_page,
         "status_filter": status,
-        "count_all": store.post_count(None),
-        "count_published": store.post_count(Some("published")),
-        "count_draft": store.post_count(Some("draft")),
-        "count_scheduled": store.post_count(Some("scheduled")),
-        "count_archived": store.post_count(Some("archived")),
+        "count_all": count(None),
+        "count_published": count(Some("published")),
+        "count_draft": count(Some("draft")),
+        "count_scheduled": count(Some("scheduled")),
+        "count_archived": count(Some("archived")),
         "admin_slug": slug.get(),
         "settings": store.setting_all(),
     });
@@ -63,7 +78,7 @@ pub fn posts_list(
 
 #[get("/posts/new")]
 pub fn posts_new(
-    _admin: AuthorUser,
+    _admin: PostEditor,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
 ) -> Template {
@@ -75,11 +90,15 @@ pub fn posts_new(
     let context = json!({
         "page_title": "New Post",
         "admin_slug": slug.get(),
+        "authors": author_choices(&**store.inner(), &_admin.user),
+        "post_author_id": _admin.user.id,
         "categories": categories,
         "tags": tags,
         "settings": store.setting_all(),
         "ai_enabled": ai_enabled,
         "ai_has_vision": ai_has_vision,
+        "schema_types": jsonld::POST_SCHEMA_TYPES,
+        "content_templates": templates::for_active_design(&**store.inner(), "post"),
     });
 
     Template::render("admin/posts/edit", &context)
@@ -87,30 +106,41 @@ pub fn posts_new(
 
 #[get("/posts/<id>/edit")]
 pub fn posts_edit(
-    _admin: AuthorUser,
+    _admin: PostEditor,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     id: i64,
 ) -> Option<Template> {
     let post = store.post_find_by_id(id)?;
+    if !permissions::can_edit_post(&**store.inner(), &_admin.user, &post) {
+        return None;
+    }
     let categories = store.category_list(Some("post"));
     let tags = store.tag_list();
     let post_categories = store.category_for_content(id, "post");
     let post_tags = store.tag_for_content(id, "post");
+    let path = crate::render::slug_url(&store.setting_get_or("blog_slug", "journal"), &post.slug);
+    let analytics = crate::analytics::content_panel(&**store.inner(), &path);
 
     let ai_enabled = crate::ai::is_enabled(&**store.inner());
     let ai_has_vision = crate::ai::has_vision_provider(&**store.inner());
     let context = json!({
         "page_title": "Edit Post",
+        "authors": author_choices(&**store.inner(), &_admin.user),
+        "post_author_id": post.author_id,
         "post": post,
         "categories": categories,
         "tags": tags,
         "post_categories": post_categories.iter().map(|c| c.id).collect::<Vec<_>>(),
         "post_tags": post_tags.iter().map(|t| t.id).collect::<Vec<_>>(),
+        "analytics": analytics,
         "admin_slug": slug.get(),
         "settings": store.setting_all(),
         "ai_enabled": ai_enabled,
         "ai_has_vision": ai_has_vision,
+        "schema_types": jsonld::POST_SCHEMA_TYPES,
+        "content_templates": templates::for_active_design(&**store.inner(), "post"),
+        "audio_duration": post.audio_duration.map(crate::rss::format_duration),
     });
 
     Some(Template::render("admin/posts/edit", &context))
@@ -118,17 +148,19 @@ pub fn posts_edit(
 
 #[post("/posts/<id>/delete")]
 pub fn posts_delete(
-    _admin: EditorUser,
+    _admin: PostDeleter,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     id: i64,
 ) -> Redirect {
-    let title = store
-        .post_find_by_id(id)
-        .map(|p| p.title)
-        .unwrap_or_default();
+    let title = match store.post_find_by_id(id) {
+        Some(p) if permissions::can_edit_post(&**store.inner(), &_admin.user, &p) => p.title,
+        _ => return Redirect::to(format!("{}/posts", admin_base(slug))),
+    };
     let _ = store.post_delete(id);
     store.search_remove_item("post", id);
+    store.embedding_remove("post", id);
+    let _ = store.seo_suggestion_clear("post", id);
     store.audit_log(
         Some(_admin.user.id),
         Some(&_admin.user.display_name),
@@ -149,15 +181,20 @@ pub struct BulkDeleteInput {
 
 #[post("/posts/bulk-delete", data = "<body>")]
 pub fn posts_bulk_delete(
-    _admin: EditorUser,
+    _admin: PostDeleter,
     store: &State<Arc<dyn Store>>,
     body: Json<BulkDeleteInput>,
 ) -> Json<serde_json::Value> {
     let mut deleted = 0u64;
     for id in &body.ids {
-        if store.post_find_by_id(*id).is_some() {
+        let allowed = store
+            .post_find_by_id(*id)
+            .is_some_and(|p| permissions::can_edit_post(&**store.inner(), &_admin.user, &p));
+        if allowed {
             let _ = store.post_delete(*id);
             store.search_remove_item("post", *id);
+            store.embedding_remove("post", *id);
+            let _ = store.seo_suggestion_clear("post", *id);
             deleted += 1;
         }
     }
@@ -188,17 +225,60 @@ pub struct PostFormData<'f> {
     pub published_at: Option<String>,
     pub category_ids: Option<Vec<i64>>,
     pub tag_names: Option<String>,
+    pub robots_noindex: Option<String>,
+    pub robots_nofollow: Option<String>,
+    pub canonical_url: Option<String>,
+    pub schema_type: Option<String>,
+    pub focus_keyword: Option<String>,
+    pub template: Option<String>,
+    pub language: Option<String>,
+    pub translation_group: Option<String>,
+    pub audio_file: Option<String>,
+    pub audio_duration: Option<String>,
+    pub audio_bytes: Option<String>,
     pub featured_image: Option<TempFile<'f>>,
     pub uploaded_featured_path: Option<String>,
+    /// Only honoured for users who may reassign posts
+    pub author_id: Option<i64>,
+}
+
+/// Accounts a post can be credited to, for users with `users.manage`; empty
+/// for everyone else, who can't reassign posts
+fn author_choices(store: &dyn Store, user: &User) -> Vec<serde_json::Value> {
+    if !permissions::user_can(store, user, permissions::USERS_MANAGE) {
+        return vec![];
+    }
+    store
+        .user_list_all()
+        .iter()
+        .filter(|u| !members::is_member(store, u))
+        .map(|u| json!({ "id": u.id, "display_name": u.display_name }))
+        .collect()
+}
+
+/// The author picked in the editor, if the user may reassign posts and the
+/// pick is a staff account
+fn chosen_author(store: &dyn Store, user: &User, author_id: Option<i64>) -> Option<i64> {
+    if !permissions::user_can(store, user, permissions::USERS_MANAGE) {
+        return None;
+    }
+    let author = store.user_get_by_id(author_id?)?;
+    (!members::is_member(store, &author)).then_some(author.id)
 }
 
 #[post("/posts/new", data = "<form>")]
 pub async fn posts_create(
-    _admin: AuthorUser,
+    _admin: PostEditor,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     mut form: Form<PostFormData<'_>>,
 ) -> Redirect {
+    super::gate_publish(
+        &**store.inner(),
+        &_admin.user,
+        permissions::POSTS_PUBLISH,
+        &mut form.status,
+    );
     let featured = if form
         .uploaded_featured_path
         .as_ref()
@@ -211,12 +291,18 @@ pub async fn posts_create(
                 if !super::is_allowed_image(f, &**store.inner()) {
                     return Redirect::to(format!("{}/posts/new", admin_base(slug)));
                 }
-                save_upload(f, "post", &**store.inner()).await
+                save_owned_upload(f, "post", &**store.inner(), _admin.user.id).await
             }
             _ => None,
         }
     };
 
+    let audio_file = form
+        .audio_file
+        .as_deref()
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .map(String::from);
     let post_form = PostForm {
         title: form.title.clone(),
         slug: form.slug.clone(),
@@ -237,6 +323,42 @@ pub async fn posts_create(
         },
         category_ids: form.category_ids.clone(),
         tag_ids: None,
+        robots_noindex: Some(form.robots_noindex.is_some()),
+        robots_nofollow: Some(form.robots_nofollow.is_some()),
+        canonical_url: super::canonical_override(form.canonical_url.as_deref()),
+        schema_type: jsonld::valid_schema_type(
+            form.schema_type.as_deref(),
+            jsonld::POST_SCHEMA_TYPES,
+        ),
+        focus_keyword: form
+            .focus_keyword
+            .as_deref()
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(String::from),
+        language: hreflang::normalize_language(form.language.as_deref()),
+        translation_group: form
+            .translation_group
+            .as_deref()
+            .map(str::trim)
+            .filter(|g| !g.is_empty())
+            .map(String::from),
+        audio_duration: form
+            .audio_duration
+            .as_deref()
+            .and_then(crate::rss::parse_duration),
+        audio_bytes: form
+            .audio_bytes
+            .as_deref()
+            .and_then(|b| b.trim().parse::<i64>().ok())
+            .filter(|b| *b > 0)
+            .or_else(|| {
+                audio_file
+                    .as_deref()
+                    .and_then(crate::rss::local_audio_bytes)
+            }),
+        audio_file,
+        template: templates::normalize(form.template.as_deref(), "post"),
     };
     let final_status = super::resolve_status(&form.status, &post_form.published_at);
     let post_form = PostForm {
@@ -246,6 +368,9 @@ pub async fn posts_create(
 
     match store.post_create(&post_form) {
         Ok(id) => {
+            let author = chosen_author(&**store.inner(), &_admin.user, form.author_id)
+                .unwrap_or(_admin.user.id);
+            let _ = store.post_set_author(id, Some(author));
             // Auto-compute SEO score
             {
                 let seo_input = crate::seo::audit::SeoInput {
@@ -256,6 +381,7 @@ pub async fn posts_create(
                     body_html: &form.content_html,
                     featured_image: post_form.featured_image.as_deref().unwrap_or(""),
                     content_type: "post",
+                    focus_keyword: post_form.focus_keyword.as_deref().unwrap_or(""),
                 };
                 let audit = crate::seo::audit::compute_score(&seo_input);
                 let _ = store.post_update_seo_score(
@@ -301,6 +427,10 @@ pub async fn posts_create(
                 Some(&final_status),
                 None,
             );
+            // Let search engines know (noindex content stays out of it)
+            if final_status == "published" && form.robots_noindex.is_none() {
+                crate::seo::ping::notify_content(store.inner(), "post", &form.slug);
+            }
             if final_status == "draft" {
                 Redirect::to(format!(
                     "{}/posts/{}/edit?saved=draft",
@@ -319,12 +449,22 @@ pub async fn posts_create(
 
 #[post("/posts/<id>/edit", data = "<form>")]
 pub async fn posts_update(
-    _admin: AuthorUser,
+    _admin: PostEditor,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     id: i64,
     mut form: Form<PostFormData<'_>>,
 ) -> Redirect {
+    match store.post_find_by_id(id) {
+        Some(p) if permissions::can_edit_post(&**store.inner(), &_admin.user, &p) => {}
+        _ => return Redirect::to(format!("{}/posts", admin_base(slug))),
+    }
+    super::gate_publish(
+        &**store.inner(),
+        &_admin.user,
+        permissions::POSTS_PUBLISH,
+        &mut form.status,
+    );
     let featured = if form
         .uploaded_featured_path
         .as_ref()
@@ -337,12 +477,18 @@ pub async fn posts_update(
                 if !super::is_allowed_image(f, &**store.inner()) {
                     return Redirect::to(format!("{}/posts/{}/edit", admin_base(slug), id));
                 }
-                save_upload(f, "post", &**store.inner()).await
+                save_owned_upload(f, "post", &**store.inner(), _admin.user.id).await
             }
             _ => store.post_find_by_id(id).and_then(|p| p.featured_image),
         }
     };
 
+    let audio_file = form
+        .audio_file
+        .as_deref()
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .map(String::from);
     let post_form = PostForm {
         title: form.title.clone(),
         slug: form.slug.clone(),
@@ -369,6 +515,42 @@ pub async fn posts_update(
         },
         category_ids: form.category_ids.clone(),
         tag_ids: None,
+        robots_noindex: Some(form.robots_noindex.is_some()),
+        robots_nofollow: Some(form.robots_nofollow.is_some()),
+        canonical_url: super::canonical_override(form.canonical_url.as_deref()),
+        schema_type: jsonld::valid_schema_type(
+            form.schema_type.as_deref(),
+            jsonld::POST_SCHEMA_TYPES,
+        ),
+        focus_keyword: form
+            .focus_keyword
+            .as_deref()
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(String::from),
+        language: hreflang::normalize_language(form.language.as_deref()),
+        translation_group: form
+            .translation_group
+            .as_deref()
+            .map(str::trim)
+            .filter(|g| !g.is_empty())
+            .map(String::from),
+        audio_duration: form
+            .audio_duration
+            .as_deref()
+            .and_then(crate::rss::parse_duration),
+        audio_bytes: form
+            .audio_bytes
+            .as_deref()
+            .and_then(|b| b.trim().parse::<i64>().ok())
+            .filter(|b| *b > 0)
+            .or_else(|| {
+                audio_file
+                    .as_deref()
+                    .and_then(crate::rss::local_audio_bytes)
+            }),
+        audio_file,
+        template: templates::normalize(form.template.as_deref(), "post"),
     };
     let final_status = super::resolve_status(&form.status, &post_form.published_at);
     let post_form = PostForm {
@@ -377,6 +559,9 @@ pub async fn posts_update(
     };
 
     let _ = store.post_update(id, &post_form);
+    if let Some(author) = chosen_author(&**store.inner(), &_admin.user, form.author_id) {
+        let _ = store.post_set_author(id, Some(author));
+    }
     // Auto-compute SEO score
     {
         let seo_input = crate::seo::audit::SeoInput {
@@ -387,6 +572,7 @@ pub async fn posts_update(
             body_html: &form.content_html,
             featured_image: post_form.featured_image.as_deref().unwrap_or(""),
             content_type: "post",
+            focus_keyword: post_form.focus_keyword.as_deref().unwrap_or(""),
         };
         let audit = crate::seo::audit::compute_score(&seo_input);
         let _ = store.post_update_seo_score(
@@ -433,6 +619,10 @@ pub async fn posts_update(
         Some(&final_status),
         None,
     );
+    // Let search engines know (noindex content stays out of it)
+    if final_status == "published" && form.robots_noindex.is_none() {
+        crate::seo::ping::notify_content(store.inner(), "post", &form.slug);
+    }
     if final_status == "draft" {
         Redirect::to(format!(
             "{}/posts/{}/edit?saved=draft",
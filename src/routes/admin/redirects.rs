@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use rocket::serde::json::Json;
+use rocket::State;
+use rocket_dyn_templates::Template;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::models::redirect::{self, RedirectRule};
+use crate::security::auth::SeoManager;
+use crate::store::Store;
+use crate::AdminSlug;
+
+// ── Redirect Manager ───────────────────────────────────
+
+/// `source` and `target` pre-fill the add form (the SEO audit's
+/// "Create redirect" link for a logged 404).
+#[get("/redirects?<source>&<target>")]
+pub fn redirects_page(
+    _admin: SeoManager,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    source: Option<String>,
+    target: Option<String>,
+) -> Template {
+    let prefill = source.map(|s| {
+        json!({
+            "id": 0,
+            "source": redirect::normalize_source(&s),
+            "target": target.unwrap_or_default(),
+            "status_code": 301,
+        })
+    });
+    let context = json!({
+        "page_title": "Redirects",
+        "admin_slug": slug.get(),
+        "settings": store.setting_all(),
+        "redirects": store.redirect_list(),
+        "prefill": prefill,
+    });
+    Template::render("admin/redirects", &context)
+}
+
+#[derive(Deserialize)]
+pub struct RedirectForm {
+    pub id: Option<i64>,
+    pub source: String,
+    pub target: String,
+    pub status_code: Option<i64>,
+}
+
+#[post("/api/redirects/save", format = "json", data = "<form>")]
+pub fn redirect_save(
+    _admin: SeoManager,
+    store: &State<Arc<dyn Store>>,
+    form: Json<RedirectForm>,
+) -> Json<Value> {
+    let rule = RedirectRule {
+        id: form.id.unwrap_or(0),
+        source: redirect::normalize_source(&form.source),
+        target: form.target.trim().to_string(),
+        status_code: form.status_code.unwrap_or(301),
+        hits: 0,
+        created_at: String::new(),
+    };
+    if let Err(e) = redirect::validate(&rule) {
+        return Json(json!({"success": false, "error": e}));
+    }
+    if store
+        .redirect_list()
+        .iter()
+        .any(|r| r.source == rule.source && r.id != rule.id)
+    {
+        return Json(json!({"success": false, "error": "A redirect for this path already exists"}));
+    }
+    match store.redirect_save(&rule) {
+        Ok(id) => {
+            // The path no longer 404s
+            let _ = store.not_found_dismiss(&rule.source);
+            Json(json!({"success": true, "id": id}))
+        }
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RedirectDeleteForm {
+    pub id: i64,
+}
+
+#[post("/api/redirects/delete", format = "json", data = "<form>")]
+pub fn redirect_delete(
+    _admin: SeoManager,
+    store: &State<Arc<dyn Store>>,
+    form: Json<RedirectDeleteForm>,
+) -> Json<Value> {
+    match store.redirect_delete(form.id) {
+        Ok(_) => Json(json!({"success": true})),
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RedirectImportForm {
+    pub csv: String,
+}
+
+/// Import `source,target[,status]` rows. A row whose source already has a
+/// redirect replaces it.
+#[post("/api/redirects/import", format = "json", data = "<form>")]
+pub fn redirect_import(
+    _admin: SeoManager,
+    store: &State<Arc<dyn Store>>,
+    form: Json<RedirectImportForm>,
+) -> Json<Value> {
+    let (rules, mut errors) = redirect::parse_csv(&form.csv);
+    let existing = store.redirect_list();
+    let mut imported = 0;
+    for mut rule in rules {
+        if let Some(old) = existing.iter().find(|r| r.source == rule.source) {
+            rule.id = old.id;
+        }
+        match store.redirect_save(&rule) {
+            Ok(_) => {
+                let _ = store.not_found_dismiss(&rule.source);
+                imported += 1;
+            }
+            Err(e) => errors.push(format!("{}: {}", rule.source, e)),
+        }
+    }
+    Json(json!({"success": true, "imported": imported, "errors": errors}))
+}
+
+#[derive(Deserialize)]
+pub struct NotFoundDismissForm {
+    pub path: String,
+}
+
+/// Remove a path from the 404 log (it'll reappear if it keeps 404ing).
+#[post("/api/not-found/dismiss", format = "json", data = "<form>")]
+pub fn not_found_dismiss(
+    _admin: SeoManager,
+    store: &State<Arc<dyn Store>>,
+    form: Json<NotFoundDismissForm>,
+) -> Json<Value> {
+    match store.not_found_dismiss(&form.path) {
+        Ok(_) => Json(json!({"success": true})),
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
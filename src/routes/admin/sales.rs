@@ -4,7 +4,8 @@ use rocket::State;
 use rocket_dyn_templates::Template;
 use serde_json::json;
 
-use crate::security::auth::AdminUser;
+use super::{list_page, NUMBERED_PAGES};
+use crate::security::auth::OrdersViewer;
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -12,7 +13,7 @@ use crate::AdminSlug;
 
 #[get("/sales")]
 pub fn sales_dashboard(
-    _admin: AdminUser,
+    _admin: OrdersViewer,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
 ) -> Template {
@@ -45,25 +46,25 @@ pub fn sales_dashboard(
     Template::render("admin/sales/dashboard", &context)
 }
 
-#[get("/sales/orders?<page>&<status>")]
+#[get("/sales/orders?<page>&<status>&<after>")]
 pub fn sales_orders(
-    _admin: AdminUser,
+    _admin: OrdersViewer,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     page: Option<i64>,
     status: Option<String>,
+    after: Option<String>,
 ) -> Template {
     let settings = store.setting_all();
     let per_page: i64 = 25;
-    let current_page = page.unwrap_or(1).max(1);
-    let offset = (current_page - 1) * per_page;
 
-    let (orders, total) = match status.as_deref() {
-        Some(s) if !s.is_empty() => (
-            store.order_list_by_status(s, per_page, offset),
-            store.order_count_by_status(s),
-        ),
-        _ => (store.order_list(per_page, offset), store.order_count()),
+    let status_filter = status.as_deref().filter(|s| !s.is_empty());
+    let list = list_page(after.as_deref(), page, per_page, |after, limit| {
+        store.order_list_keyset(status_filter, after, limit)
+    });
+    let total = match status_filter {
+        Some(s) => store.order_count_by_status(s),
+        None => store.order_count(),
     };
 
     let total_pages = (total as f64 / per_page as f64).ceil() as i64;
@@ -76,9 +77,11 @@ pub fn sales_orders(
         "page_title": "Orders",
         "admin_slug": &slug.get(),
         "settings": &settings,
-        "orders": orders,
-        "current_page": current_page,
+        "orders": list.items,
+        "current_page": list.page.unwrap_or(0),
         "total_pages": total_pages,
+        "page_links": total_pages.min(NUMBERED_PAGES),
+        "next_cursor": list.next_cursor,
         "total": total,
         "per_page": per_page,
         "filter_status": status.unwrap_or_default(),
@@ -4,12 +4,12 @@ use rocket::State;
 use rocket_dyn_templates::Template;
 use serde_json::json;
 
-use crate::security::auth::EditorUser;
+use crate::security::auth::SeoManager;
 use crate::store::Store;
 
 #[get("/seo-audit")]
 pub fn seo_audit_dashboard(
-    _admin: EditorUser,
+    _admin: SeoManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<super::AdminSlug>,
 ) -> Template {
@@ -162,6 +162,73 @@ pub fn seo_audit_dashboard(
         },
     ]);
 
+    // Pending AI rewrite suggestions, grouped by item
+    let ai_enabled = crate::ai::is_enabled(&**store.inner());
+    let titles: std::collections::HashMap<(String, i64), String> = post_rows
+        .iter()
+        .chain(portfolio_rows.iter())
+        .filter_map(|r| {
+            Some((
+                (r.get("type")?.as_str()?.to_string(), r.get("id")?.as_i64()?),
+                r.get("title")?.as_str()?.to_string(),
+            ))
+        })
+        .collect();
+    let mut suggestion_groups: Vec<serde_json::Value> = Vec::new();
+    for sg in store.seo_suggestion_pending(None) {
+        let key = (sg.content_type.clone(), sg.content_id);
+        let title = match titles.get(&key) {
+            Some(t) => t.clone(),
+            None => continue,
+        };
+        let group = suggestion_groups.iter_mut().find(|g| {
+            g.get("content_type").and_then(|v| v.as_str()) == Some(key.0.as_str())
+                && g.get("content_id").and_then(|v| v.as_i64()) == Some(key.1)
+        });
+        match group {
+            Some(group) => {
+                if let Some(list) = group.get_mut("suggestions").and_then(|v| v.as_array_mut()) {
+                    list.push(json!(sg));
+                }
+            }
+            None => suggestion_groups.push(json!({
+                "content_type": key.0,
+                "content_id": key.1,
+                "title": title,
+                "suggestions": [sg],
+            })),
+        }
+    }
+
+    // Top 404s, each with the closest published post or portfolio item
+    let blog_slug = settings.get("blog_slug").cloned().unwrap_or_default();
+    let portfolio_slug = settings.get("portfolio_slug").cloned().unwrap_or_default();
+    let candidates: Vec<String> = posts
+        .iter()
+        .filter(|p| p.status == "published")
+        .map(|p| crate::render::slug_url(&blog_slug, &p.slug))
+        .chain(
+            items
+                .iter()
+                .filter(|i| i.status == "published")
+                .map(|i| crate::render::slug_url(&portfolio_slug, &i.slug)),
+        )
+        .collect();
+    let not_found: Vec<serde_json::Value> = store
+        .not_found_top(50)
+        .into_iter()
+        .map(|e| {
+            let suggestion = crate::models::redirect::suggest_target(&e.path, &candidates);
+            json!({
+                "path": e.path,
+                "referrer": e.referrer,
+                "hits": e.hits,
+                "last_seen": e.last_seen,
+                "suggestion": suggestion,
+            })
+        })
+        .collect();
+
     let site_url = settings
         .get("seo_canonical_base")
         .cloned()
@@ -193,6 +260,11 @@ pub fn seo_audit_dashboard(
         "top_issues": top_issues,
         "checklist": checklist,
         "site_url": site_url,
+        "ai_enabled": ai_enabled,
+        "suggestion_groups": suggestion_groups,
+        "not_found": not_found,
+        "ping_log": store.ping_log_recent(20),
+        "low_score": crate::seo::rewrite::LOW_SCORE,
     });
 
     Template::render("admin/seo_audit", context)
@@ -2,14 +2,20 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use rocket::form::Form;
+use rocket::http::ContentType;
 use rocket::response::{Flash, Redirect};
+use rocket::serde::json::Json;
 use rocket::State;
 use rocket_dyn_templates::Template;
-use serde_json::json;
+use serde_json::{json, Value};
 
 use super::admin_base;
+use super::api::StatsExport;
 use crate::models::settings::SettingsCache;
-use crate::security::auth::AdminUser;
+use crate::models::settings_transfer;
+use crate::security::auth::SettingsManager;
+use crate::security::secrets;
+use crate::security::sudo::SudoMode;
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -17,7 +23,7 @@ use crate::AdminSlug;
 
 #[get("/settings/<section>")]
 pub fn settings_page(
-    _admin: AdminUser,
+    _admin: SettingsManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     section: &str,
@@ -79,12 +85,18 @@ pub fn settings_page(
         }
     };
 
-    let active_design_slug = store.design_active().map(|d| d.slug).unwrap_or_default();
+    let active_design_slug = store
+        .design_active()
+        .map(|d| crate::designs::inheritance::base_slug(&**store.inner(), d))
+        .unwrap_or_default();
+    // Credentials never go back to the browser in full
+    let mut settings = store.setting_all();
+    secrets::mask_settings(&mut settings);
     let mut context = json!({
         "page_title": format!("Settings — {}", section_label),
         "section": section,
         "admin_slug": slug.get(),
-        "settings": store.setting_all(),
+        "settings": settings,
         "current_user": _admin.user.safe_json(),
         "active_design_slug": active_design_slug,
     });
@@ -93,6 +105,45 @@ pub fn settings_page(
     if section == "security" {
         let pk_count = store.passkey_count_for_user(_admin.user.id);
         context["passkey_count"] = json!(pk_count);
+        context["mfa_totp_ready"] = json!(!_admin.user.mfa_secret.is_empty());
+        context["mfa_email_available"] =
+            json!(crate::security::mfa::email_available(&**store.inner()));
+        context["api_keys"] = json!(store.api_key_list());
+    }
+
+    if section == "visitors" {
+        let designs: Vec<_> = store
+            .design_list()
+            .into_iter()
+            .map(|d| json!({ "slug": d.slug, "name": d.name }))
+            .collect();
+        context["designs"] = json!(designs);
+        let engines: Vec<_> = crate::search_engine::ENGINES
+            .iter()
+            .map(|(key, label)| json!({ "key": key, "label": label }))
+            .collect();
+        context["search_engines"] = json!(engines);
+    }
+
+    if section == "general" {
+        context["podcast_categories"] = json!(crate::rss::PODCAST_CATEGORIES);
+        context["podcast_problems"] = json!(crate::rss::podcast_problems(&**store.inner()));
+    }
+
+    if section == "seo" {
+        let crawlers: Vec<_> = crate::seo::robots::AI_CRAWLERS
+            .iter()
+            .map(|(agent, owner)| json!({ "agent": agent, "owner": owner }))
+            .collect();
+        context["ai_crawlers"] = json!(crawlers);
+    }
+
+    if section == "typography" {
+        context["font_subsets"] = json!(crate::typography::self_host::SUBSETS);
+    }
+
+    if section == "ai" {
+        context["ai_chain"] = json!(crate::ai::failover_chain(&settings));
     }
 
     if let Some(ref f) = flash {
@@ -108,7 +159,7 @@ pub fn settings_page(
 
 #[post("/settings/<section>", data = "<form>")]
 pub fn settings_save(
-    _admin: AdminUser,
+    _admin: SettingsManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     cache: &State<SettingsCache>,
@@ -208,6 +259,11 @@ pub fn settings_save(
                 "SparkPost",
                 vec!["email_sparkpost_api_key"],
             ),
+            (
+                "email_sendgrid_enabled",
+                "SendGrid",
+                vec!["email_sendgrid_api_key"],
+            ),
             (
                 "email_smtp_enabled",
                 "Custom SMTP",
@@ -299,6 +355,12 @@ pub fn settings_save(
                 vec!["ai_cloudflare_account_id", "ai_cloudflare_api_token"],
             ),
             ("ai_groq_enabled", "Groq", vec!["ai_groq_api_key"]),
+            ("ai_mistral_enabled", "Mistral", vec!["ai_mistral_api_key"]),
+            (
+                "ai_openrouter_enabled",
+                "OpenRouter / OpenAI-compatible",
+                vec!["ai_openrouter_base_url", "ai_openrouter_model"],
+            ),
         ],
         _ => vec![],
     };
@@ -345,6 +407,7 @@ pub fn settings_save(
             "email_moosend_enabled",
             "email_mandrill_enabled",
             "email_sparkpost_enabled",
+            "email_sendgrid_enabled",
             "email_smtp_enabled",
         ];
         let any_email = email_keys
@@ -355,6 +418,81 @@ pub fn settings_save(
         }
     }
 
+    // Email routing rules must reference known categories and providers
+    if section == "email" {
+        if let Some(rules) = data.get("email_routing_rules") {
+            if let Err(e) = crate::email::routing::validate_routing_rules(rules) {
+                errors.push(e);
+            }
+        }
+        if let Some(bind) = data.get("mta_inbound_bind") {
+            if bind.trim().parse::<std::net::IpAddr>().is_err() {
+                errors.push(format!(
+                    "Inbound SMTP bind address \"{}\" is not an IP address",
+                    bind
+                ));
+            }
+        }
+    }
+
+    if let Some(language) = data.get("site_language") {
+        if crate::seo::hreflang::normalize_language(Some(language)).is_none() {
+            errors.push(format!(
+                "\"{}\" is not a language code (e.g. en, pt-BR)",
+                language
+            ));
+        }
+    }
+
+    // robots.txt rules must parse and can't hide the whole site
+    if section == "seo" {
+        if let Some(robots) = data.get("seo_robots_txt") {
+            if let Err(e) = crate::seo::robots::validate_robots(robots) {
+                errors.push(format!("robots.txt — {}", e));
+            }
+        }
+    }
+
+    // An external search engine needs a URL and an index name it accepts
+    if section == "visitors" {
+        let config = crate::search_engine::Config::from_lookup(|key| {
+            data.get(key).cloned().unwrap_or_default()
+        });
+        if let Some(problem) = crate::search_engine::validate(&config) {
+            errors.push(problem);
+        }
+    }
+
+    // Homepage sections are stored as checked by the section builder
+    if let Some(raw) = data.get("homepage_sections").cloned() {
+        if !raw.trim().is_empty() {
+            match crate::designs::homepage::normalize_sections(&raw) {
+                Ok(clean) => {
+                    data.insert("homepage_sections".to_string(), clean);
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+
+    if let Some(raw) = data.get("font_google_subsets").cloned() {
+        data.insert(
+            "font_google_subsets".to_string(),
+            crate::typography::self_host::normalize_subsets(&raw),
+        );
+    }
+
+    // Credentials pasted into fields that visitors can see
+    let leaks = secrets::scan_public_fields(&data);
+    let scan_action = store.setting_get_or("secrets_scan_action", "block");
+    if scan_action == "block" {
+        errors.extend(
+            leaks
+                .iter()
+                .map(|w| format!("{}. Remove it before saving", w)),
+        );
+    }
+
     // Reserved system routes that cannot be used as slugs
     const RESERVED_SLUGS: &[&str] = &[
         "static",
@@ -365,9 +503,12 @@ pub fn settings_save(
         "feed",
         "sitemap.xml",
         "robots.txt",
+        "llms.txt",
         "privacy",
         "terms",
         "archives",
+        "account",
+        "author",
         "login",
         "logout",
         "setup",
@@ -409,6 +550,35 @@ pub fn settings_save(
                 }
             }
         }
+        let report_uri = data.get("csp_report_uri").map(|v| v.trim()).unwrap_or("");
+        if !report_uri.is_empty() && !crate::security::headers::valid_report_uri(report_uri) {
+            errors.push("CSP Report URI must be an http(s) URL".to_string());
+        }
+        if let Some(custom) = data.get("csp_custom_directives") {
+            if let Err(e) = crate::security::headers::custom_directives(custom) {
+                errors.push(e);
+            }
+        }
+        if let Some(ancestors) = data.get("security_frame_ancestors") {
+            if let Err(e) = crate::security::headers::frame_ancestor_sources(ancestors) {
+                errors.push(e);
+            }
+        }
+        let cors_credentials =
+            data.get("cors_allow_credentials").map(|v| v.as_str()) == Some("true");
+        let cors_any = crate::security::cors::parse_origins(
+            data.get("cors_allowed_origins")
+                .map(|v| v.as_str())
+                .unwrap_or(""),
+        )
+        .iter()
+        .any(|o| o == "*");
+        if cors_credentials && cors_any {
+            errors.push(
+                "CORS credentials can't be allowed for every origin (*); list the frontend origins instead"
+                    .to_string(),
+            );
+        }
     }
 
     // Blog/Portfolio slug validation
@@ -489,12 +659,21 @@ pub fn settings_save(
     // Checkboxes don't submit a value when unchecked, so we must
     // explicitly reset all known boolean keys for this section first.
     let checkbox_keys: &[&str] = match section {
+        "general" => &[
+            "rss_featured_media",
+            "podcast_enabled",
+            "podcast_explicit",
+            "page_cache_enabled",
+            "page_cache_disk",
+        ],
         "ai" => &[
             "ai_ollama_enabled",
             "ai_openai_enabled",
             "ai_gemini_enabled",
             "ai_cloudflare_enabled",
             "ai_groq_enabled",
+            "ai_mistral_enabled",
+            "ai_openrouter_enabled",
             "ai_suggest_meta",
             "ai_suggest_tags",
             "ai_suggest_categories",
@@ -502,6 +681,8 @@ pub fn settings_save(
             "ai_suggest_slug",
             "ai_theme_generation",
             "ai_post_generation",
+            "ai_embeddings_enabled",
+            "ai_vision_captions_enabled",
         ],
         "email" => &[
             "email_failover_enabled",
@@ -515,8 +696,13 @@ pub fn settings_save(
             "email_moosend_enabled",
             "email_mandrill_enabled",
             "email_sparkpost_enabled",
+            "email_sendgrid_enabled",
+            "email_sendgrid_sandbox",
             "email_smtp_enabled",
             "email_builtin_enabled",
+            "mta_inbound_enabled",
+            "digest_enabled",
+            "newsletter_enabled",
         ],
         "blog" => &[
             "journal_enabled",
@@ -524,11 +710,18 @@ pub fn settings_save(
             "blog_show_date",
             "blog_show_reading_time",
             "blog_featured_image_required",
+            "blog_related_posts",
         ],
         "portfolio" => &[
             "portfolio_enabled",
             "portfolio_enable_likes",
             "portfolio_image_protection",
+            "portfolio_lightbox_keyboard",
+            "portfolio_lightbox_loop",
+            "portfolio_lightbox_zoom",
+            "portfolio_lightbox_autoplay",
+            "portfolio_lightbox_preload",
+            "portfolio_lightbox_deep_link",
         ],
         "contact" => &["contact_page_enabled", "contact_form_enabled"],
         "comments" => &[
@@ -538,16 +731,38 @@ pub fn settings_save(
             "comments_honeypot",
             "comments_require_name",
             "comments_require_email",
+            "comments_subscriptions_enabled",
+            "membership_enabled",
         ],
         "security" => &[
             "mfa_enabled",
             "login_captcha_enabled",
+            "login_alerts_enabled",
+            "password_require_uppercase",
+            "password_require_lowercase",
+            "password_require_number",
+            "password_require_symbol",
+            "password_deny_common",
             "security_akismet_enabled",
             "security_cleantalk_enabled",
             "security_oopspam_enabled",
             "security_recaptcha_enabled",
             "security_turnstile_enabled",
             "security_hcaptcha_enabled",
+            "fw_security_headers",
+            "security_hsts_subdomains",
+            "security_hsts_preload",
+            "csp_nonce_enabled",
+            "csp_allow_google_fonts",
+            "csp_allow_analytics",
+            "csp_allow_payments",
+            "csp_allow_captcha",
+            "csp_allow_embeds",
+            "siem_enabled",
+            "siem_include_firewall",
+            "api_rate_limit_enabled",
+            "cors_enabled",
+            "cors_allow_credentials",
         ],
         "commerce" => &[
             "commerce_paypal_enabled",
@@ -563,6 +778,10 @@ pub fn settings_save(
             "seo_structured_data",
             "seo_open_graph",
             "seo_twitter_cards",
+            "seo_ping_bing",
+            "seo_ping_yandex",
+            "seo_ping_google",
+            "seo_llms_txt_enabled",
             "seo_ga_enabled",
             "seo_plausible_enabled",
             "seo_fathom_enabled",
@@ -576,8 +795,14 @@ pub fn settings_save(
             "images_reencode",
             "images_strip_metadata",
             "video_upload_enabled",
+            "import_fetch_remote_images",
+        ],
+        "typography" => &[
+            "font_google_enabled",
+            "font_google_self_host",
+            "font_adobe_enabled",
+            "font_sitewide",
         ],
-        "typography" => &["font_google_enabled", "font_adobe_enabled", "font_sitewide"],
         "visitors" => &[
             "design_site_search",
             "design_back_to_top",
@@ -586,8 +811,21 @@ pub fn settings_save(
             "cookie_consent_show_reject",
             "privacy_policy_enabled",
             "terms_of_use_enabled",
+            "analytics_outbound_tracking",
+            "analytics_engagement_tracking",
+            "analytics_privacy_mode",
+            "analytics_respect_dnt",
+            "analytics_search_log",
+            "analytics_report_enabled",
+            "analytics_report_email",
         ],
         "social" => &["social_brand_colors", "share_enabled"],
+        "tasks" => &[
+            "backup_enabled",
+            "backup_s3_enabled",
+            "backup_b2_enabled",
+            "backup_sftp_enabled",
+        ],
         _ => &[],
     };
     for key in checkbox_keys {
@@ -598,13 +836,24 @@ pub fn settings_save(
     if section == "general" {
         for key in &["site_logo", "site_favicon"] {
             if let Some(val) = data.get(*key) {
-                if let Some(sanitized) = sanitize_svg_data_url(val) {
+                if let Some(sanitized) = crate::svg_sanitizer::sanitize_data_url(val) {
                     data.insert(key.to_string(), sanitized);
                 }
             }
         }
     }
 
+    // Slideshow interval in seconds
+    if section == "portfolio" {
+        if let Some(val) = data.get("portfolio_lightbox_autoplay_interval") {
+            let n: i64 = val.trim().parse().unwrap_or(5);
+            data.insert(
+                "portfolio_lightbox_autoplay_interval".to_string(),
+                n.clamp(2, 60).to_string(),
+            );
+        }
+    }
+
     // Server-side clamp for video settings
     if section == "images" {
         if let Some(val) = data.get("video_max_duration") {
@@ -634,6 +883,10 @@ pub fn settings_save(
         }
     }
 
+    // Masked credentials posted back unchanged keep their stored values
+    secrets::drop_masked(&mut data);
+
+    let search_engine_before = crate::search_engine::Config::load(&**store.inner());
     let _ = store.setting_set_many(&data);
 
     // If email settings changed and no providers remain enabled, revert magic link to password
@@ -649,6 +902,7 @@ pub fn settings_save(
             "email_moosend_enabled",
             "email_mandrill_enabled",
             "email_sparkpost_enabled",
+            "email_sendgrid_enabled",
             "email_smtp_enabled",
         ];
         let any_email = email_keys
@@ -669,6 +923,19 @@ pub fn settings_save(
     let s: &dyn Store = &**store.inner();
     cache.refresh_from_store(s);
 
+    // A new search engine connection starts from a full index
+    if section == "visitors" {
+        let config = crate::search_engine::Config::load(s);
+        if config.is_external() && config != search_engine_before {
+            crate::search_engine::reindex_in_background(Arc::clone(store.inner()));
+        }
+    }
+
+    // Fonts can be picked on the typography page or in the customizer
+    if section == "typography" || section == "customizer" {
+        crate::typography::self_host::refresh_in_background(Arc::clone(store.inner()));
+    }
+
     // If admin_slug changed, update the RwLock so the rewriter fairing uses the new slug
     if section == "security" {
         let new_slug = s.setting_get_or("admin_slug", "admin");
@@ -692,31 +959,98 @@ pub fn settings_save(
         _ => section,
     };
 
-    Ok(Flash::success(
-        Redirect::to(format!(
-            "{}/settings/{}{}",
-            admin_base(slug),
-            redirect_section,
-            tab_fragment
-        )),
-        "Settings saved successfully",
-    ))
+    let redirect = Redirect::to(format!(
+        "{}/settings/{}{}",
+        admin_base(slug),
+        redirect_section,
+        tab_fragment
+    ));
+    if scan_action == "warn" && !leaks.is_empty() {
+        return Ok(Flash::new(
+            redirect,
+            "warning",
+            format!("Settings saved, but {}", leaks.join(" | ")),
+        ));
+    }
+    Ok(Flash::success(redirect, "Settings saved successfully"))
 }
 
-/// If the value is an SVG data URL, decode → sanitize → re-encode.
-/// Returns Some(sanitized_data_url) if it was an SVG, None otherwise (leave as-is).
-fn sanitize_svg_data_url(value: &str) -> Option<String> {
-    use base64::Engine;
-    let engine = base64::engine::general_purpose::STANDARD;
-
-    // Check for base64-encoded SVG data URL
-    if let Some(b64) = value.strip_prefix("data:image/svg+xml;base64,") {
-        if let Ok(raw) = engine.decode(b64.trim()) {
-            if let Some(clean) = crate::svg_sanitizer::sanitize_svg(&raw) {
-                let encoded = engine.encode(&clean);
-                return Some(format!("data:image/svg+xml;base64,{}", encoded));
-            }
-        }
+// ── Export / Import ────────────────────────────────────
+
+/// Download every portable setting as JSON, credentials masked
+#[get("/api/settings/export")]
+pub fn settings_export(_admin: SettingsManager, store: &State<Arc<dyn Store>>) -> StatsExport {
+    let s: &dyn Store = &**store.inner();
+    let doc = settings_transfer::export(s);
+    StatsExport {
+        filename: format!(
+            "velocty-settings-{}.json",
+            chrono::Utc::now().format("%Y-%m-%d")
+        ),
+        content_type: ContentType::JSON,
+        body: serde_json::to_string_pretty(&doc).unwrap_or_default(),
     }
-    None
+}
+
+/// What importing an export would change, without changing anything
+#[post("/api/settings/import/preview", format = "json", data = "<document>")]
+pub fn settings_import_preview(
+    _admin: SettingsManager,
+    store: &State<Arc<dyn Store>>,
+    document: Json<Value>,
+) -> Json<Value> {
+    let current = store.setting_all();
+    Json(match settings_transfer::plan(&current, &document) {
+        Ok(plan) => json!({"success": true, "plan": plan}),
+        Err(e) => json!({"success": false, "error": e}),
+    })
+}
+
+/// Apply an export. The plan is worked out again from the stored settings,
+/// so the result matches the preview unless something changed since.
+#[post("/api/settings/import", format = "json", data = "<document>")]
+pub fn settings_import(
+    admin: SettingsManager,
+    _sudo: SudoMode,
+    store: &State<Arc<dyn Store>>,
+    cache: &State<SettingsCache>,
+    document: Json<Value>,
+) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    let plan = match settings_transfer::plan(&s.setting_all(), &document) {
+        Ok(p) => p,
+        Err(e) => return Json(json!({"success": false, "error": e})),
+    };
+    if !plan.warnings.is_empty() && s.setting_get_or("secrets_scan_action", "block") == "block" {
+        return Json(json!({
+            "success": false,
+            "error": format!("{}. Remove it before importing", plan.warnings.join(" | ")),
+        }));
+    }
+    if let Err(e) = s.setting_set_many(&plan.updates()) {
+        return Json(json!({"success": false, "error": e}));
+    }
+    cache.refresh_from_store(s);
+    if plan.changes.iter().any(|c| c.key.starts_with("font_")) {
+        crate::typography::self_host::refresh_in_background(Arc::clone(store.inner()));
+    }
+    s.audit_log(
+        Some(admin.user.id),
+        Some(&admin.user.display_name),
+        "settings_import",
+        Some("settings"),
+        None,
+        None,
+        Some(&format!(
+            "{} changed, {} skipped",
+            plan.changes.len(),
+            plan.skipped.len()
+        )),
+        None,
+    );
+    Json(json!({
+        "success": true,
+        "changed": plan.changes.len(),
+        "skipped": plan.skipped.len(),
+    }))
 }
@@ -2,14 +2,20 @@ use std::sync::Arc;
 
 use rocket::form::Form;
 use rocket::fs::TempFile;
+use rocket::http::{ContentType, CookieJar};
 use rocket::serde::json::Json;
 use rocket::State;
 use rocket_dyn_templates::Template;
 use serde::Deserialize;
 use serde_json::{json, Value};
 
+use super::api::StatsExport;
 use super::save_upload;
-use crate::security::auth::AdminUser;
+use crate::models::user::User;
+use crate::rate_limit::RateLimiter;
+use crate::security::auth::{ClientIp, DashboardUser, UserManager};
+use crate::security::sudo::SudoMode;
+use crate::security::{email_change, invite, password_policy, permissions, privacy};
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -17,7 +23,7 @@ use crate::AdminSlug;
 
 #[get("/users?<role>&<page>")]
 pub fn users_list(
-    _admin: AdminUser,
+    _admin: UserManager,
     store: &State<Arc<dyn Store>>,
     slug: &State<AdminSlug>,
     role: Option<String>,
@@ -33,6 +39,8 @@ pub fn users_list(
     let total_pages = ((total as f64) / (per_page as f64)).ceil() as i64;
     let users_json: Vec<serde_json::Value> = users.iter().map(|u| u.safe_json()).collect();
 
+    let policy = password_policy::PasswordPolicy::from_settings(&settings);
+    let password_hint = policy.summary();
     let context = json!({
         "page_title": "Users",
         "admin_slug": slug.get(),
@@ -48,10 +56,195 @@ pub fn users_list(
         "count_editor": store.user_count_by_role("editor"),
         "count_author": store.user_count_by_role("author"),
         "count_subscriber": store.user_count_by_role("subscriber"),
+        "custom_roles": custom_roles(&**store.inner()),
+        "password_hint": password_hint,
+        "password_min_length": policy.min_length,
     });
     Template::render("admin/users", &context)
 }
 
+/// Stored roles that aren't built in, for the role picker.
+fn custom_roles(store: &dyn Store) -> Vec<Value> {
+    store
+        .role_list()
+        .into_iter()
+        .filter(|r| !permissions::BUILT_IN_ROLES.contains(&r.slug.as_str()))
+        .map(|r| json!({ "slug": r.slug, "name": r.name }))
+        .collect()
+}
+
+// ── Roles & Capabilities ─────────────────────────────────────
+
+#[get("/users/roles")]
+pub fn roles_page(
+    _admin: UserManager,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+) -> Template {
+    let s: &dyn Store = &**store.inner();
+    let stored = s.role_list();
+    let mut roles: Vec<Value> = permissions::BUILT_IN_ROLES
+        .iter()
+        .map(|r| {
+            let name = stored
+                .iter()
+                .find(|x| x.slug == *r)
+                .map(|x| x.name.clone())
+                .unwrap_or_else(|| {
+                    let mut c = r.chars();
+                    c.next()
+                        .map(|f| f.to_uppercase().collect::<String>() + c.as_str())
+                        .unwrap_or_default()
+                });
+            json!({
+                "slug": r,
+                "name": name,
+                "built_in": true,
+                "customized": stored.iter().any(|x| x.slug == *r),
+                "locked": *r == "admin",
+                "capabilities": permissions::role_capabilities(s, r),
+                "users": s.user_count_by_role(r),
+            })
+        })
+        .collect();
+    roles.extend(
+        stored
+            .iter()
+            .filter(|r| !permissions::BUILT_IN_ROLES.contains(&r.slug.as_str()))
+            .map(|r| {
+                json!({
+                    "slug": r.slug,
+                    "name": r.name,
+                    "built_in": false,
+                    "locked": false,
+                    "capabilities": r.capabilities,
+                    "users": s.user_count_by_role(&r.slug),
+                })
+            }),
+    );
+    let capabilities: Vec<Value> = permissions::CAPABILITIES
+        .iter()
+        .map(|(c, label)| json!({ "key": c, "label": label }))
+        .collect();
+
+    let context = json!({
+        "page_title": "Roles",
+        "admin_slug": slug.get(),
+        "settings": s.setting_all(),
+        "roles": roles,
+        "capabilities": capabilities,
+    });
+    Template::render("admin/roles", &context)
+}
+
+#[derive(Deserialize)]
+pub struct RoleSaveForm {
+    pub slug: String,
+    pub name: String,
+    pub capabilities: Vec<String>,
+}
+
+#[post("/api/roles/save", format = "json", data = "<form>")]
+pub fn role_save(
+    _admin: UserManager,
+    _sudo: SudoMode,
+    store: &State<Arc<dyn Store>>,
+    form: Json<RoleSaveForm>,
+) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    let slug = form.slug.trim().to_lowercase();
+    let name = form.name.trim();
+
+    if slug == "admin" {
+        return Json(
+            json!({"success": false, "error": "The admin role always has every capability"}),
+        );
+    }
+    if let Err(e) = permissions::validate_slug(&slug) {
+        return Json(json!({"success": false, "error": e}));
+    }
+    if name.is_empty() {
+        return Json(json!({"success": false, "error": "Role name is required"}));
+    }
+    let capabilities = permissions::sanitize_capabilities(&form.capabilities);
+    if !permissions::user_holds_all(s, &_admin.user, &capabilities) {
+        return Json(
+            json!({"success": false, "error": "You can't grant capabilities you don't have"}),
+        );
+    }
+    // Editing an existing role changes access for everyone who has it
+    if permissions::role_exists(s, &slug) && !permissions::can_grant(s, &_admin.user, &slug) {
+        return Json(
+            json!({"success": false, "error": "You can't edit a role with more access than your own"}),
+        );
+    }
+
+    match s.role_save(&slug, name, &capabilities) {
+        Ok(()) => {
+            s.audit_log(
+                Some(_admin.user.id),
+                Some(&_admin.user.display_name),
+                "update",
+                Some("role"),
+                None,
+                Some(name),
+                Some(&capabilities.join(", ")),
+                None,
+            );
+            Json(json!({"success": true}))
+        }
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RoleDeleteForm {
+    pub slug: String,
+}
+
+/// Delete a custom role, or reset a built-in role to its default capabilities.
+#[post("/api/roles/delete", format = "json", data = "<form>")]
+pub fn role_delete(
+    _admin: UserManager,
+    _sudo: SudoMode,
+    store: &State<Arc<dyn Store>>,
+    form: Json<RoleDeleteForm>,
+) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    let slug = form.slug.trim();
+    if s.role_find(slug).is_none() {
+        return Json(json!({"success": false, "error": "Role not found"}));
+    }
+    if !permissions::can_grant(s, &_admin.user, slug) {
+        return Json(
+            json!({"success": false, "error": "You can't edit a role with more access than your own"}),
+        );
+    }
+    let built_in = permissions::BUILT_IN_ROLES.contains(&slug);
+    if !built_in && s.user_count_by_role(slug) > 0 {
+        return Json(
+            json!({"success": false, "error": "Reassign this role's users before deleting it"}),
+        );
+    }
+
+    match s.role_delete(slug) {
+        Ok(()) => {
+            s.audit_log(
+                Some(_admin.user.id),
+                Some(&_admin.user.display_name),
+                if built_in { "reset" } else { "delete" },
+                Some("role"),
+                None,
+                Some(slug),
+                None,
+                None,
+            );
+            Json(json!({"success": true}))
+        }
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
+
 #[derive(Deserialize)]
 pub struct UserCreateForm {
     pub email: String,
@@ -62,7 +255,7 @@ pub struct UserCreateForm {
 
 #[post("/api/users/create", format = "json", data = "<form>")]
 pub fn user_create(
-    _admin: AdminUser,
+    _admin: UserManager,
     store: &State<Arc<dyn Store>>,
     form: Json<UserCreateForm>,
 ) -> Json<Value> {
@@ -75,14 +268,20 @@ pub fn user_create(
     if email.is_empty() || display_name.is_empty() {
         return Json(json!({"success": false, "error": "Email and display name are required"}));
     }
-    if form.password.len() < 8 {
-        return Json(json!({"success": false, "error": "Password must be at least 8 characters"}));
+    if let Err(e) = password_policy::check(&**store.inner(), &form.password, &[email, display_name])
+    {
+        return Json(json!({"success": false, "error": e}));
     }
-    if !["admin", "editor", "author", "subscriber"].contains(&role) {
+    if !permissions::role_exists(&**store.inner(), role) {
         return Json(json!({"success": false, "error": "Invalid role"}));
     }
+    if !permissions::can_grant(&**store.inner(), &_admin.user, role) {
+        return Json(
+            json!({"success": false, "error": "You can't assign a role with more access than your own"}),
+        );
+    }
 
-    let hash = match auth::hash_password(&form.password) {
+    let hash = match auth::hash_password_for(&**store.inner(), &form.password) {
         Ok(h) => h,
         Err(e) => return Json(json!({"success": false, "error": e})),
     };
@@ -113,11 +312,47 @@ pub struct UserUpdateForm {
     pub role: Option<String>,
     pub password: Option<String>,
     pub avatar: Option<String>,
+    pub author_slug: Option<String>,
+    pub bio: Option<String>,
+    pub website: Option<String>,
+    /// One URL per line
+    pub social_links: Option<String>,
+}
+
+/// Check author page fields; returns the normalized slug
+fn validate_author_profile(
+    store: &dyn Store,
+    user_id: i64,
+    author_slug: &str,
+    website: &str,
+    social_links: &str,
+) -> Result<String, String> {
+    let author_slug = slug::slugify(author_slug.trim());
+    if !author_slug.is_empty()
+        && store
+            .user_list_all()
+            .iter()
+            .any(|u| u.id != user_id && u.public_slug() == author_slug)
+    {
+        return Err("Another user already has that author URL".into());
+    }
+    let is_url = |s: &str| s.starts_with("https://") || s.starts_with("http://");
+    if !website.is_empty() && !is_url(website) {
+        return Err("Website must start with https:// or http://".into());
+    }
+    if let Some(bad) = social_links
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !is_url(l))
+    {
+        return Err(format!("\"{}\" is not a web address", bad));
+    }
+    Ok(author_slug)
 }
 
 #[post("/api/users/update", format = "json", data = "<form>")]
 pub fn user_update(
-    _admin: AdminUser,
+    _admin: UserManager,
     store: &State<Arc<dyn Store>>,
     form: Json<UserUpdateForm>,
 ) -> Json<Value> {
@@ -127,13 +362,23 @@ pub fn user_update(
         Some(u) => u,
         None => return Json(json!({"success": false, "error": "User not found"})),
     };
+    if !permissions::can_grant(&**store.inner(), &_admin.user, &user.role) {
+        return Json(
+            json!({"success": false, "error": "You can't manage a user with more access than your own"}),
+        );
+    }
 
     // Update role if provided
     if let Some(ref role) = form.role {
         let role = role.trim();
-        if !["admin", "editor", "author", "subscriber"].contains(&role) {
+        if !permissions::role_exists(&**store.inner(), role) {
             return Json(json!({"success": false, "error": "Invalid role"}));
         }
+        if !permissions::can_grant(&**store.inner(), &_admin.user, role) {
+            return Json(
+                json!({"success": false, "error": "You can't assign a role with more access than your own"}),
+            );
+        }
         // Prevent demoting the last admin
         if user.role == "admin" && role != "admin" && store.user_count_by_role("admin") <= 1 {
             return Json(
@@ -154,13 +399,20 @@ pub fn user_update(
         let _ = store.user_update_avatar(form.id, &avatar);
     }
 
+    // A new email address only takes effect once it's confirmed from that
+    // address; until then the account keeps its current one
+    let email = user.email.clone();
+    let requested = form.email.as_deref().map(str::trim).unwrap_or(&email);
+    let email_pending = if requested.eq_ignore_ascii_case(&email) {
+        None
+    } else {
+        match email_change::request(&**store.inner(), &user, requested) {
+            Ok(new_email) => Some(new_email),
+            Err(e) => return Json(json!({"success": false, "error": e})),
+        }
+    };
+
     // Update profile fields if provided
-    let email = form
-        .email
-        .as_deref()
-        .unwrap_or(&user.email)
-        .trim()
-        .to_string();
     let display_name = form
         .display_name
         .as_deref()
@@ -171,6 +423,34 @@ pub fn user_update(
         return Json(json!({"success": false, "error": e}));
     }
 
+    // Author page fields, when the form sends them
+    if form.author_slug.is_some()
+        || form.bio.is_some()
+        || form.website.is_some()
+        || form.social_links.is_some()
+    {
+        let field =
+            |v: &Option<String>, current: &str| v.as_deref().unwrap_or(current).trim().to_string();
+        let bio = field(&form.bio, &user.bio);
+        let website = field(&form.website, &user.website);
+        let social_links = field(&form.social_links, &user.social_links);
+        let author_slug = match validate_author_profile(
+            &**store.inner(),
+            form.id,
+            &field(&form.author_slug, &user.author_slug),
+            &website,
+            &social_links,
+        ) {
+            Ok(s) => s,
+            Err(e) => return Json(json!({"success": false, "error": e})),
+        };
+        if let Err(e) =
+            store.user_update_author_profile(form.id, &author_slug, &bio, &website, &social_links)
+        {
+            return Json(json!({"success": false, "error": e}));
+        }
+    }
+
     // Sync to settings if this is the current logged-in user
     if form.id == _admin.user.id {
         let _ = store.setting_set("admin_email", &email);
@@ -183,12 +463,10 @@ pub fn user_update(
     // Update password if provided
     if let Some(ref pw) = form.password {
         if !pw.is_empty() {
-            if pw.len() < 8 {
-                return Json(
-                    json!({"success": false, "error": "Password must be at least 8 characters"}),
-                );
+            if let Err(e) = password_policy::check(&**store.inner(), pw, &[&email, &display_name]) {
+                return Json(json!({"success": false, "error": e}));
             }
-            let hash = match auth::hash_password(pw) {
+            let hash = match auth::hash_password_for(&**store.inner(), pw) {
                 Ok(h) => h,
                 Err(e) => return Json(json!({"success": false, "error": e})),
             };
@@ -198,7 +476,19 @@ pub fn user_update(
         }
     }
 
-    Json(json!({"success": true}))
+    if let Some(ref new_email) = email_pending {
+        store.audit_log(
+            Some(_admin.user.id),
+            Some(&_admin.user.display_name),
+            "email_change_requested",
+            Some("user"),
+            Some(form.id),
+            Some(&user.display_name),
+            Some(new_email),
+            None,
+        );
+    }
+    Json(json!({"success": true, "email_pending": email_pending}))
 }
 
 #[derive(FromForm)]
@@ -209,7 +499,7 @@ pub struct AvatarUploadForm<'f> {
 
 #[post("/api/users/avatar", data = "<form>")]
 pub async fn user_avatar_upload(
-    _admin: AdminUser,
+    _admin: UserManager,
     store: &State<Arc<dyn Store>>,
     mut form: Form<AvatarUploadForm<'_>>,
 ) -> Json<Value> {
@@ -241,6 +531,68 @@ pub async fn user_avatar_upload(
     }
 }
 
+#[derive(Deserialize)]
+pub struct UserInviteForm {
+    pub email: String,
+    pub display_name: String,
+    pub role: String,
+}
+
+/// Create the account without a password and email an invitation link;
+/// the invitee chooses their own password when accepting it
+#[post("/api/users/invite", format = "json", data = "<form>")]
+pub fn user_invite(
+    _admin: UserManager,
+    store: &State<Arc<dyn Store>>,
+    form: Json<UserInviteForm>,
+) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    match invite::invite(
+        s,
+        &_admin.user,
+        &form.email,
+        &form.display_name,
+        form.role.trim(),
+    ) {
+        Ok(id) => {
+            s.audit_log(
+                Some(_admin.user.id),
+                Some(&_admin.user.display_name),
+                "invite",
+                Some("user"),
+                Some(id),
+                Some(form.display_name.trim()),
+                Some(form.role.trim()),
+                None,
+            );
+            Json(json!({"success": true, "id": id}))
+        }
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
+
+#[post("/api/users/resend-invite", format = "json", data = "<form>")]
+pub fn user_resend_invite(
+    _admin: UserManager,
+    store: &State<Arc<dyn Store>>,
+    form: Json<UserActionForm>,
+) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    let user = match s.user_get_by_id(form.id) {
+        Some(u) => u,
+        None => return Json(json!({"success": false, "error": "User not found"})),
+    };
+    if !permissions::can_grant(s, &_admin.user, &user.role) {
+        return Json(
+            json!({"success": false, "error": "You can't manage a user with more access than your own"}),
+        );
+    }
+    match invite::resend(s, &_admin.user, &user) {
+        Ok(()) => Json(json!({"success": true})),
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
+
 #[derive(Deserialize)]
 pub struct UserActionForm {
     pub id: i64,
@@ -248,7 +600,7 @@ pub struct UserActionForm {
 
 #[post("/api/users/lock", format = "json", data = "<form>")]
 pub fn user_lock(
-    _admin: AdminUser,
+    _admin: UserManager,
     store: &State<Arc<dyn Store>>,
     form: Json<UserActionForm>,
 ) -> Json<Value> {
@@ -259,6 +611,11 @@ pub fn user_lock(
         if u.role == "admin" && store.user_count_by_role("admin") <= 1 {
             return Json(json!({"success": false, "error": "Cannot lock the last admin"}));
         }
+        if !permissions::can_grant(&**store.inner(), &_admin.user, &u.role) {
+            return Json(
+                json!({"success": false, "error": "You can't manage a user with more access than your own"}),
+            );
+        }
     }
     let target_name = store
         .user_get_by_id(form.id)
@@ -284,10 +641,19 @@ pub fn user_lock(
 
 #[post("/api/users/unlock", format = "json", data = "<form>")]
 pub fn user_unlock(
-    _admin: AdminUser,
+    _admin: UserManager,
     store: &State<Arc<dyn Store>>,
     form: Json<UserActionForm>,
 ) -> Json<Value> {
+    // Invited accounts become active by accepting their invitation
+    if store
+        .user_get_by_id(form.id)
+        .is_some_and(|u| u.status == invite::STATUS)
+    {
+        return Json(
+            json!({"success": false, "error": "This user hasn't accepted their invitation yet"}),
+        );
+    }
     let target_name = store
         .user_get_by_id(form.id)
         .map(|u| u.display_name)
@@ -312,7 +678,7 @@ pub fn user_unlock(
 
 #[post("/api/users/reset-password", format = "json", data = "<form>")]
 pub fn user_reset_password(
-    _admin: AdminUser,
+    _admin: UserManager,
     store: &State<Arc<dyn Store>>,
     form: Json<UserActionForm>,
 ) -> Json<Value> {
@@ -322,9 +688,14 @@ pub fn user_reset_password(
         Some(u) => u,
         None => return Json(json!({"success": false, "error": "User not found"})),
     };
+    if !permissions::can_grant(&**store.inner(), &_admin.user, &user.role) {
+        return Json(
+            json!({"success": false, "error": "You can't manage a user with more access than your own"}),
+        );
+    }
 
     let temp_pw = password_reset::generate_temp_password();
-    let hash = match auth::hash_password(&temp_pw) {
+    let hash = match auth::hash_password_for(&**store.inner(), &temp_pw) {
         Ok(h) => h,
         Err(e) => return Json(json!({"success": false, "error": e})),
     };
@@ -352,17 +723,13 @@ pub fn user_reset_password(
 
 #[post("/api/users/delete", format = "json", data = "<form>")]
 pub fn user_delete(
-    _admin: AdminUser,
+    _admin: UserManager,
+    _sudo: SudoMode,
     store: &State<Arc<dyn Store>>,
     form: Json<UserActionForm>,
 ) -> Json<Value> {
-    if form.id == _admin.user.id {
-        return Json(json!({"success": false, "error": "Cannot delete yourself"}));
-    }
-    if let Some(u) = store.user_get_by_id(form.id) {
-        if u.role == "admin" && store.user_count_by_role("admin") <= 1 {
-            return Json(json!({"success": false, "error": "Cannot delete the last admin"}));
-        }
+    if let Err(e) = check_removable(&**store.inner(), &_admin.user, form.id) {
+        return Json(json!({"success": false, "error": e}));
     }
     let target_name = store
         .user_get_by_id(form.id)
@@ -370,6 +737,9 @@ pub fn user_delete(
         .unwrap_or_default();
     match store.user_delete(form.id) {
         Ok(_) => {
+            // Their posts and uploads stay, now in the deleting admin's hands
+            let _ = store.post_reassign_author(form.id, _admin.user.id);
+            let _ = store.media_reassign_owner(form.id, _admin.user.id);
             store.audit_log(
                 Some(_admin.user.id),
                 Some(&_admin.user.display_name),
@@ -386,10 +756,95 @@ pub fn user_delete(
     }
 }
 
+/// Refuse to delete or erase yourself, the last admin, or someone with more
+/// access than `admin`
+fn check_removable(store: &dyn Store, admin: &User, id: i64) -> Result<(), String> {
+    if id == admin.id {
+        return Err("Cannot delete yourself".into());
+    }
+    if let Some(u) = store.user_get_by_id(id) {
+        if u.role == "admin" && store.user_count_by_role("admin") <= 1 {
+            return Err("Cannot delete the last admin".into());
+        }
+        if !permissions::can_grant(store, admin, &u.role) {
+            return Err("You can't manage a user with more access than your own".into());
+        }
+    }
+    Ok(())
+}
+
+// ── Personal data ───────────────────────────────────────
+
+/// Everything stored about a user, as a JSON download
+#[get("/api/users/<id>/export")]
+pub fn user_export(
+    _admin: UserManager,
+    store: &State<Arc<dyn Store>>,
+    id: i64,
+) -> Option<StatsExport> {
+    let s: &dyn Store = &**store.inner();
+    let user = s.user_get_by_id(id)?;
+    s.audit_log(
+        Some(_admin.user.id),
+        Some(&_admin.user.display_name),
+        "export_personal_data",
+        Some("user"),
+        Some(user.id),
+        Some(&user.display_name),
+        None,
+        None,
+    );
+    Some(StatsExport {
+        filename: format!("personal-data-{}.json", user.id),
+        content_type: ContentType::JSON,
+        body: serde_json::to_string_pretty(&privacy::export(s, &user)).unwrap_or_default(),
+    })
+}
+
+/// Erase a user's personal data and delete the account. Their posts and
+/// uploads go to the admin doing it, as with a plain delete.
+#[post("/api/users/erase", format = "json", data = "<form>")]
+pub fn user_erase(
+    _admin: UserManager,
+    _sudo: SudoMode,
+    store: &State<Arc<dyn Store>>,
+    form: Json<UserActionForm>,
+) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    if let Err(e) = check_removable(s, &_admin.user, form.id) {
+        return Json(json!({"success": false, "error": e}));
+    }
+    let user = match s.user_get_by_id(form.id) {
+        Some(u) => u,
+        None => return Json(json!({"success": false, "error": "User not found"})),
+    };
+    match privacy::erase(s, &user) {
+        Ok(result) => {
+            let _ = s.post_reassign_author(user.id, _admin.user.id);
+            let _ = s.media_reassign_owner(user.id, _admin.user.id);
+            s.audit_log(
+                Some(_admin.user.id),
+                Some(&_admin.user.display_name),
+                "erase",
+                Some("user"),
+                Some(user.id),
+                Some(&format!("User #{}", user.id)),
+                None,
+                None,
+            );
+            Json(json!({"success": true, "result": result}))
+        }
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
+
 // ── MFA Setup / Disable (per-user) ──────────────────────
+//
+// These and the passkey endpoints below only touch the signed-in user's own
+// account, so any staff member may use them (see the invitation welcome page).
 
 #[post("/mfa/setup", format = "json")]
-pub fn mfa_setup(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn mfa_setup(_admin: DashboardUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let site_name = store.setting_get_or("site_name", "Velocty");
 
     let secret = crate::security::mfa::generate_secret();
@@ -412,7 +867,7 @@ pub struct MfaVerifyForm {
 
 #[post("/mfa/verify", format = "json", data = "<body>")]
 pub fn mfa_verify(
-    _admin: AdminUser,
+    _admin: DashboardUser,
     store: &State<Arc<dyn Store>>,
     body: Json<MfaVerifyForm>,
 ) -> Json<Value> {
@@ -431,43 +886,97 @@ pub fn mfa_verify(
     let codes_json = serde_json::to_string(&recovery_codes).unwrap_or_else(|_| "[]".to_string());
 
     let _ = store.user_update_mfa(_admin.user.id, true, &pending, &codes_json);
+    let _ = store.user_set_mfa_method(_admin.user.id, "totp");
     let _ = store.setting_set(&pending_key, "");
 
-    // Keep settings in sync for backward compat
-    let _ = store.setting_set("mfa_secret", &pending);
-    let _ = store.setting_set("mfa_enabled", "true");
-    let _ = store.setting_set("mfa_recovery_codes", &codes_json);
+    // Keep settings in sync for backward compat; they describe the admin's MFA
+    if permissions::user_can(&**store.inner(), &_admin.user, permissions::USERS_MANAGE) {
+        let _ = store.setting_set("mfa_secret", &pending);
+        let _ = store.setting_set("mfa_enabled", "true");
+        let _ = store.setting_set("mfa_recovery_codes", &codes_json);
+    }
 
     Json(json!({ "ok": true, "recovery_codes": recovery_codes }))
 }
 
 #[post("/mfa/disable", format = "json", data = "<body>")]
 pub fn mfa_disable(
-    _admin: AdminUser,
+    _admin: DashboardUser,
     store: &State<Arc<dyn Store>>,
     body: Json<MfaVerifyForm>,
 ) -> Json<Value> {
-    if !_admin.user.mfa_enabled || _admin.user.mfa_secret.is_empty() {
+    if !_admin.user.mfa_required() {
         return Json(json!({ "ok": false, "error": "MFA is not enabled." }));
     }
 
-    // Verify current code before disabling
-    if !crate::security::mfa::verify_code(&_admin.user.mfa_secret, &body.code) {
+    // Verify current code before disabling; email-code users confirm with their password
+    let confirmed = if _admin.user.mfa_method == "email" {
+        crate::security::sudo::verify(&**store.inner(), &_admin.user, &body.code)
+    } else {
+        crate::security::mfa::verify_code(&_admin.user.mfa_secret, &body.code)
+    };
+    if !confirmed {
         return Json(json!({ "ok": false, "error": "Invalid code. MFA was not disabled." }));
     }
 
-    let _ = store.user_update_mfa(_admin.user.id, false, "", "[]");
-
-    // Keep settings in sync for backward compat
-    let _ = store.setting_set("mfa_enabled", "false");
-    let _ = store.setting_set("mfa_secret", "");
-    let _ = store.setting_set("mfa_recovery_codes", "[]");
+    // Passwordless accounts keep their recovery codes as a sign-in fallback
+    let codes = if _admin.user.passwordless {
+        _admin.user.mfa_recovery_codes.as_str()
+    } else {
+        "[]"
+    };
+    let _ = store.user_update_mfa(_admin.user.id, false, "", codes);
+    let _ = store.user_set_mfa_method(_admin.user.id, "totp");
+
+    // Keep settings in sync for backward compat; they describe the admin's MFA
+    if permissions::user_can(&**store.inner(), &_admin.user, permissions::USERS_MANAGE) {
+        let _ = store.setting_set("mfa_enabled", "false");
+        let _ = store.setting_set("mfa_secret", "");
+        let _ = store.setting_set("mfa_recovery_codes", "[]");
+    }
 
     Json(json!({ "ok": true }))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct MfaMethodForm {
+    pub method: String,
+}
+
+/// Switch between authenticator app and emailed codes. Choosing email
+/// codes turns MFA on when it was off.
+#[post("/mfa/method", format = "json", data = "<body>")]
+pub fn mfa_method(
+    _admin: DashboardUser,
+    store: &State<Arc<dyn Store>>,
+    body: Json<MfaMethodForm>,
+) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    match crate::security::mfa::set_method(s, &_admin.user, &body.method) {
+        Ok(codes) => {
+            if codes.is_some() && permissions::user_can(s, &_admin.user, permissions::USERS_MANAGE)
+            {
+                // Keep settings in sync for backward compat
+                let _ = s.setting_set("mfa_enabled", "true");
+            }
+            s.audit_log(
+                Some(_admin.user.id),
+                Some(&_admin.user.display_name),
+                "mfa_method",
+                Some("user"),
+                Some(_admin.user.id),
+                Some(&_admin.user.email),
+                Some(&body.method),
+                None,
+            );
+            Json(json!({ "ok": true, "recovery_codes": codes }))
+        }
+        Err(e) => Json(json!({ "ok": false, "error": e })),
+    }
+}
+
 #[get("/mfa/recovery-codes")]
-pub fn mfa_recovery_codes(_admin: AdminUser) -> Json<Value> {
+pub fn mfa_recovery_codes(_admin: DashboardUser) -> Json<Value> {
     let codes: Vec<String> =
         serde_json::from_str(&_admin.user.mfa_recovery_codes).unwrap_or_default();
     Json(json!({ "ok": true, "codes": codes }))
@@ -476,7 +985,7 @@ pub fn mfa_recovery_codes(_admin: AdminUser) -> Json<Value> {
 // ── Passkey (WebAuthn) Management ───────────────────────
 
 #[get("/passkeys")]
-pub fn passkey_list(_admin: AdminUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
+pub fn passkey_list(_admin: DashboardUser, store: &State<Arc<dyn Store>>) -> Json<Value> {
     let keys = store.passkey_list_for_user(_admin.user.id);
     let list: Vec<Value> = keys
         .iter()
@@ -498,7 +1007,7 @@ pub struct PasskeyNameForm {
 
 #[post("/passkeys/register/start", format = "json", data = "<body>")]
 pub fn passkey_register_start(
-    _admin: AdminUser,
+    _admin: DashboardUser,
     store: &State<Arc<dyn Store>>,
     body: Json<PasskeyNameForm>,
 ) -> Json<Value> {
@@ -536,7 +1045,7 @@ pub fn passkey_register_start(
 
 #[post("/passkeys/register/finish", format = "json", data = "<body>")]
 pub fn passkey_register_finish(
-    _admin: AdminUser,
+    _admin: DashboardUser,
     store: &State<Arc<dyn Store>>,
     body: Json<Value>,
 ) -> Json<Value> {
@@ -609,7 +1118,7 @@ pub struct PasskeyDeleteForm {
 
 #[post("/passkeys/delete", format = "json", data = "<body>")]
 pub fn passkey_delete(
-    _admin: AdminUser,
+    _admin: DashboardUser,
     store: &State<Arc<dyn Store>>,
     body: Json<PasskeyDeleteForm>,
 ) -> Json<Value> {
@@ -621,8 +1130,106 @@ pub fn passkey_delete(
                 let fallback = &_admin.user.auth_method_fallback;
                 let _ = store.user_update_auth_method(_admin.user.id, fallback, fallback);
             }
+            // Without a passkey the account needs its password back
+            if remaining == 0 && _admin.user.passwordless {
+                let _ = crate::security::passwordless::disable(&**store.inner(), &_admin.user);
+            }
             Json(json!({ "ok": true, "remaining": remaining }))
         }
         Err(e) => Json(json!({ "ok": false, "error": e })),
     }
 }
+
+// ── Passwordless Mode ───────────────────────────────────
+
+#[post("/passwordless/enable", format = "json")]
+pub fn passwordless_enable(_admin: UserManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    match crate::security::passwordless::enable(s, &_admin.user) {
+        Ok(codes) => {
+            s.audit_log(
+                Some(_admin.user.id),
+                Some(&_admin.user.display_name),
+                "passwordless_enable",
+                Some("user"),
+                Some(_admin.user.id),
+                Some(&_admin.user.email),
+                None,
+                None,
+            );
+            Json(json!({ "ok": true, "recovery_codes": codes }))
+        }
+        Err(e) => Json(json!({ "ok": false, "error": e })),
+    }
+}
+
+#[post("/passwordless/disable", format = "json")]
+pub fn passwordless_disable(_admin: UserManager, store: &State<Arc<dyn Store>>) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    match crate::security::passwordless::disable(s, &_admin.user) {
+        Ok(()) => {
+            s.audit_log(
+                Some(_admin.user.id),
+                Some(&_admin.user.display_name),
+                "passwordless_disable",
+                Some("user"),
+                Some(_admin.user.id),
+                Some(&_admin.user.email),
+                None,
+                None,
+            );
+            Json(json!({ "ok": true }))
+        }
+        Err(e) => Json(json!({ "ok": false, "error": e })),
+    }
+}
+
+// ── Step-up (sudo mode) ──────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct SudoForm {
+    pub secret: String,
+}
+
+/// Confirm the password (or an authenticator/recovery code) to unlock
+/// destructive actions for a few minutes
+#[post("/sudo", format = "json", data = "<body>")]
+pub fn sudo_confirm(
+    _admin: DashboardUser,
+    store: &State<Arc<dyn Store>>,
+    limiter: &State<RateLimiter>,
+    cookies: &CookieJar<'_>,
+    client_ip: ClientIp,
+    body: Json<SudoForm>,
+) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    if !limiter.check(
+        s,
+        &format!("sudo:{}", _admin.user.id),
+        5,
+        std::time::Duration::from_secs(15 * 60),
+    ) {
+        return Json(
+            json!({ "ok": false, "error": "Too many attempts. Please try again in 15 minutes." }),
+        );
+    }
+    let result = crate::security::sudo::confirm(s, cookies, &_admin.user, &body.secret);
+    s.audit_log(
+        Some(_admin.user.id),
+        Some(&_admin.user.display_name),
+        if result.is_ok() {
+            "sudo_granted"
+        } else {
+            "sudo_failed"
+        },
+        Some("user"),
+        Some(_admin.user.id),
+        Some(&_admin.user.email),
+        None,
+        Some(&client_ip.0),
+    );
+    match result {
+        Ok(()) => Json(json!({ "ok": true })),
+        Err(e) => Json(json!({ "ok": false, "error": e })),
+    }
+}
@@ -1,12 +1,12 @@
 use rocket::serde::json::Json;
 use rocket::State;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use std::sync::Arc;
 
 use crate::ai::{self, prompts, AiRequest};
-use crate::security::auth::EditorUser;
+use crate::security::auth::AiUser;
 use crate::store::Store;
 
 use super::parse_json_from_text;
@@ -34,7 +34,7 @@ pub struct DescribeImageRequest {
 
 #[post("/ai/generate-post", format = "json", data = "<body>")]
 pub fn generate_post(
-    _admin: EditorUser,
+    _admin: AiUser,
     store: &State<Arc<dyn Store>>,
     body: Json<GeneratePostRequest>,
 ) -> Json<Value> {
@@ -46,6 +46,7 @@ pub fn generate_post(
         max_tokens: Some(4096),
         temperature: Some(0.8),
         image_base64: body.image_base64.clone(),
+        feature: "generate_post".to_string(),
     };
 
     match ai::complete(&**store.inner(), &req) {
@@ -64,6 +65,172 @@ pub fn generate_post(
     }
 }
 
+// ── Outline, then Expand ─────────────────────────────
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub struct OutlineSection {
+    pub heading: String,
+    #[serde(default)]
+    pub points: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExpandSectionRequest {
+    pub title: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Headings of every section, so the model knows what the others cover
+    #[serde(default)]
+    pub outline: Vec<String>,
+    pub heading: String,
+    #[serde(default)]
+    pub points: Vec<String>,
+}
+
+fn string_list(v: Option<&Value>) -> Vec<String> {
+    v.and_then(|v| v.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|s| s.as_str())
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Sections from an outline response, skipping ones without a heading.
+/// Accepts bare strings as headings with no points.
+pub fn parse_outline_sections(parsed: &Value) -> Vec<OutlineSection> {
+    parsed
+        .get("sections")
+        .and_then(|v| v.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|s| {
+                    let heading = match s.as_str() {
+                        Some(h) => h,
+                        None => s.get("heading")?.as_str()?,
+                    };
+                    let heading = heading.trim();
+                    (!heading.is_empty()).then(|| OutlineSection {
+                        heading: heading.to_string(),
+                        points: string_list(s.get("points")),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Make sure an expanded section opens with its h2, whatever the model did.
+pub fn section_html(heading: &str, html: &str) -> String {
+    let html = html.trim();
+    if html.get(..3).is_some_and(|t| t.eq_ignore_ascii_case("<h2")) {
+        html.to_string()
+    } else {
+        format!("<h2>{}</h2>\n{}", html_escape(heading), html)
+    }
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Step one: an editable outline instead of a finished post
+#[post("/ai/generate-outline", format = "json", data = "<body>")]
+pub fn generate_outline(
+    _admin: AiUser,
+    store: &State<Arc<dyn Store>>,
+    body: Json<GeneratePostRequest>,
+) -> Json<Value> {
+    let req = AiRequest {
+        system: "You are a professional blog writer and content strategist. Plan clear, well-structured posts. \
+                 Always respond in valid JSON format as specified. Do not include markdown fences or explanations outside the JSON."
+            .to_string(),
+        prompt: prompts::generate_outline(&body.description),
+        max_tokens: Some(1024),
+        temperature: Some(0.7),
+        image_base64: body.image_base64.clone(),
+        feature: "generate_outline".to_string(),
+    };
+
+    match ai::complete(&**store.inner(), &req) {
+        Ok(resp) => match parse_json_from_text(&resp.text) {
+            Some(parsed) => {
+                let sections = parse_outline_sections(&parsed);
+                if sections.is_empty() {
+                    return Json(
+                        json!({"ok": false, "error": "The outline came back without sections"}),
+                    );
+                }
+                Json(json!({
+                    "ok": true,
+                    "provider": resp.provider,
+                    "title": parsed.get("title").and_then(|v| v.as_str()).unwrap_or(""),
+                    "keywords": string_list(parsed.get("keywords")),
+                    "sections": sections,
+                    "excerpt": parsed.get("excerpt").and_then(|v| v.as_str()).unwrap_or(""),
+                    "tags": string_list(parsed.get("tags")),
+                }))
+            }
+            None => Json(json!({"ok": false, "error": "Failed to parse AI response"})),
+        },
+        Err(e) => Json(json!({"ok": false, "error": e.to_string()})),
+    }
+}
+
+/// Step two: write one section of the approved outline. The editor calls
+/// this once per section, in order.
+#[post("/ai/expand-section", format = "json", data = "<body>")]
+pub fn expand_section(
+    _admin: AiUser,
+    store: &State<Arc<dyn Store>>,
+    body: Json<ExpandSectionRequest>,
+) -> Json<Value> {
+    if body.heading.trim().is_empty() {
+        return Json(json!({"ok": false, "error": "Section heading is empty"}));
+    }
+    let req = AiRequest {
+        system: "You are a professional blog writer. Write engaging, well-structured content. \
+                 Always respond in valid JSON format as specified. Do not include markdown fences or explanations outside the JSON."
+            .to_string(),
+        prompt: prompts::expand_section(
+            &body.title,
+            &body.keywords,
+            &body.outline,
+            body.heading.trim(),
+            &body.points,
+        ),
+        max_tokens: Some(1024),
+        temperature: Some(0.8),
+        image_base64: None,
+        feature: "expand_section".to_string(),
+    };
+
+    match ai::complete(&**store.inner(), &req) {
+        Ok(resp) => {
+            let html = match parse_json_from_text(&resp.text) {
+                Some(parsed) => parsed
+                    .get("html")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                // Fallback: treat the whole response as HTML
+                None => resp.text.clone(),
+            };
+            Json(json!({
+                "ok": true,
+                "provider": resp.provider,
+                "html": section_html(&body.heading, &html),
+            }))
+        }
+        Err(e) => Json(json!({"ok": false, "error": e.to_string()})),
+    }
+}
+
 // ── Suggest Content ──────────────────────────────────
 
 #[derive(Debug, Deserialize)]
@@ -75,7 +242,7 @@ pub struct SuggestContentRequest {
 
 #[post("/ai/suggest-content", format = "json", data = "<body>")]
 pub fn suggest_content(
-    _admin: EditorUser,
+    _admin: AiUser,
     store: &State<Arc<dyn Store>>,
     body: Json<SuggestContentRequest>,
 ) -> Json<Value> {
@@ -89,6 +256,7 @@ pub fn suggest_content(
         max_tokens: Some(2048),
         temperature: Some(0.8),
         image_base64: body.image_base64.clone(),
+        feature: "suggest_content".to_string(),
     };
 
     match ai::complete(&**store.inner(), &req) {
@@ -114,7 +282,7 @@ pub fn suggest_content(
 
 #[post("/ai/inline-assist", format = "json", data = "<body>")]
 pub fn inline_assist(
-    _admin: EditorUser,
+    _admin: AiUser,
     store: &State<Arc<dyn Store>>,
     body: Json<InlineAssistRequest>,
 ) -> Json<Value> {
@@ -126,6 +294,7 @@ pub fn inline_assist(
         max_tokens: Some(2048),
         temperature: Some(0.7),
         image_base64: None,
+        feature: "inline_assist".to_string(),
     };
 
     match ai::complete(&**store.inner(), &req) {
@@ -153,7 +322,7 @@ pub fn inline_assist(
 
 #[post("/ai/describe-image", format = "json", data = "<body>")]
 pub fn describe_image(
-    _admin: EditorUser,
+    _admin: AiUser,
     store: &State<Arc<dyn Store>>,
     body: Json<DescribeImageRequest>,
 ) -> Json<Value> {
@@ -163,6 +332,7 @@ pub fn describe_image(
         max_tokens: Some(512),
         temperature: Some(0.5),
         image_base64: Some(body.image_base64.clone()),
+        feature: "describe_image".to_string(),
     };
 
     match ai::complete(&**store.inner(), &req) {
@@ -175,6 +175,8 @@ pub fn routes() -> Vec<rocket::Route> {
         suggest::suggest_alt_text,
         suggest::suggest_title,
         generate::generate_post,
+        generate::generate_outline,
+        generate::expand_section,
         generate::suggest_content,
         generate::inline_assist,
         generate::describe_image,
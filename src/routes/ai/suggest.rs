@@ -6,7 +6,7 @@ use serde_json::{json, Value};
 use std::sync::Arc;
 
 use crate::ai::{self, prompts, AiRequest};
-use crate::security::auth::EditorUser;
+use crate::security::auth::AiUser;
 use crate::store::Store;
 
 use super::parse_json_from_text;
@@ -66,7 +66,7 @@ pub struct SuggestAllRequest {
 
 #[post("/ai/suggest-all", format = "json", data = "<body>")]
 pub fn suggest_all(
-    _admin: EditorUser,
+    _admin: AiUser,
     store: &State<Arc<dyn Store>>,
     body: Json<SuggestAllRequest>,
 ) -> Json<Value> {
@@ -88,6 +88,7 @@ pub fn suggest_all(
         max_tokens: Some(4096),
         temperature: Some(0.8),
         image_base64: body.image_base64.clone(),
+        feature: "suggest_all".to_string(),
     };
 
     match ai::complete(&**store.inner(), &req) {
@@ -133,7 +134,7 @@ pub fn suggest_all(
 
 #[post("/ai/suggest-meta", format = "json", data = "<body>")]
 pub fn suggest_meta(
-    _admin: EditorUser,
+    _admin: AiUser,
     store: &State<Arc<dyn Store>>,
     body: Json<SuggestMetaRequest>,
 ) -> Json<Value> {
@@ -146,6 +147,7 @@ pub fn suggest_meta(
         max_tokens: Some(256),
         temperature: Some(0.7),
         image_base64: body.image_base64.clone(),
+        feature: "suggest_meta".to_string(),
     };
 
     match ai::complete(&**store.inner(), &req) {
@@ -175,7 +177,7 @@ pub fn suggest_meta(
 
 #[post("/ai/suggest-tags", format = "json", data = "<body>")]
 pub fn suggest_tags(
-    _admin: EditorUser,
+    _admin: AiUser,
     store: &State<Arc<dyn Store>>,
     body: Json<SuggestTagsRequest>,
 ) -> Json<Value> {
@@ -190,6 +192,7 @@ pub fn suggest_tags(
         max_tokens: Some(256),
         temperature: Some(0.7),
         image_base64: body.image_base64.clone(),
+        feature: "suggest_tags".to_string(),
     };
 
     match ai::complete(&**store.inner(), &req) {
@@ -238,7 +241,7 @@ pub fn suggest_tags(
 
 #[post("/ai/suggest-categories", format = "json", data = "<body>")]
 pub fn suggest_categories(
-    _admin: EditorUser,
+    _admin: AiUser,
     store: &State<Arc<dyn Store>>,
     body: Json<SuggestCategoriesRequest>,
 ) -> Json<Value> {
@@ -257,6 +260,7 @@ pub fn suggest_categories(
         max_tokens: Some(256),
         temperature: Some(0.7),
         image_base64: body.image_base64.clone(),
+        feature: "suggest_categories".to_string(),
     };
 
     match ai::complete(&**store.inner(), &req) {
@@ -283,7 +287,7 @@ pub fn suggest_categories(
 
 #[post("/ai/suggest-slug", format = "json", data = "<body>")]
 pub fn suggest_slug(
-    _admin: EditorUser,
+    _admin: AiUser,
     store: &State<Arc<dyn Store>>,
     body: Json<SuggestSlugRequest>,
 ) -> Json<Value> {
@@ -293,6 +297,7 @@ pub fn suggest_slug(
         max_tokens: Some(128),
         temperature: Some(0.5),
         image_base64: body.image_base64.clone(),
+        feature: "suggest_slug".to_string(),
     };
 
     match ai::complete(&**store.inner(), &req) {
@@ -340,7 +345,7 @@ pub fn suggest_slug(
 
 #[post("/ai/suggest-alt-text", format = "json", data = "<body>")]
 pub fn suggest_alt_text(
-    _admin: EditorUser,
+    _admin: AiUser,
     store: &State<Arc<dyn Store>>,
     body: Json<SuggestAltTextRequest>,
 ) -> Json<Value> {
@@ -352,6 +357,7 @@ pub fn suggest_alt_text(
         max_tokens: Some(128),
         temperature: Some(0.5),
         image_base64: body.image_base64.clone(),
+        feature: "suggest_alt_text".to_string(),
     };
 
     match ai::complete(&**store.inner(), &req) {
@@ -374,7 +380,7 @@ pub fn suggest_alt_text(
 
 #[post("/ai/suggest-title", format = "json", data = "<body>")]
 pub fn suggest_title(
-    _admin: EditorUser,
+    _admin: AiUser,
     store: &State<Arc<dyn Store>>,
     body: Json<SuggestTitleRequest>,
 ) -> Json<Value> {
@@ -388,6 +394,7 @@ pub fn suggest_title(
             max_tokens: Some(512),
             temperature: Some(0.5),
             image_base64: body.image_base64.clone(),
+            feature: "suggest_title".to_string(),
         };
         match ai::complete(&**store.inner(), &img_req) {
             Ok(resp) => parse_json_from_text(&resp.text)
@@ -413,6 +420,7 @@ pub fn suggest_title(
         max_tokens: Some(128),
         temperature: Some(0.7),
         image_base64: None,
+        feature: "suggest_title".to_string(),
     };
 
     match ai::complete(&**store.inner(), &req) {
@@ -1,3 +1,6 @@
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::response::Redirect;
 use rocket::serde::json::Json;
 use rocket::State;
 use serde::{Deserialize, Serialize};
@@ -29,7 +32,12 @@ pub fn like_toggle(
 
     // Rate limit: 30 like toggles per 5 minutes per IP
     let rate_key = format!("like:{}", ip_hash);
-    if !limiter.check_and_record(&rate_key, 30, std::time::Duration::from_secs(5 * 60)) {
+    if !limiter.check(
+        &**store.inner(),
+        &rate_key,
+        30,
+        std::time::Duration::from_secs(5 * 60),
+    ) {
         let count = store.portfolio_find_by_id(id).map(|p| p.likes).unwrap_or(0);
         let liked = store.like_exists(id, &ip_hash);
         return Json(LikeResponse { liked, count });
@@ -85,9 +93,19 @@ pub struct CommentSubmit {
 pub fn comment_submit(
     store: &State<Arc<dyn Store>>,
     limiter: &State<RateLimiter>,
+    user: Option<auth::AuthenticatedUser>,
     form: Json<CommentSubmit>,
 ) -> Json<Value> {
     let s: &dyn Store = &**store.inner();
+    // Signed-in members comment under their account's name and email
+    let member = user
+        .map(|u| u.user)
+        .filter(|u| security::members::enabled(s) && security::members::is_member(s, u));
+    let mut form = form.into_inner();
+    if let Some(ref m) = member {
+        form.author_name = m.display_name.clone();
+        form.author_email = Some(m.email.clone());
+    }
     // Check per-content-type setting
     let ct = form.content_type.as_deref().unwrap_or("post");
     match ct {
@@ -121,7 +139,7 @@ pub fn comment_submit(
     let max_attempts = s.setting_get_i64("comments_rate_limit").max(1) as u64;
     let window = std::time::Duration::from_secs(15 * 60);
 
-    if !limiter.check_and_record(&rate_key, max_attempts, window) {
+    if !limiter.check(s, &rate_key, max_attempts, window) {
         return Json(json!({
             "success": false,
             "error": "Too many comments. Please wait before posting again."
@@ -172,10 +190,18 @@ pub fn comment_submit(
 
     match s.comment_create(&comment_form) {
         Ok(id) => {
+            if let Some(ref m) = member {
+                let _ = s.comment_set_user(id, m.id);
+            }
             let moderation = s.setting_get_or("comments_moderation", "manual");
             if moderation == "auto-approve" {
                 let _ = s.comment_update_status(id, "approved");
             }
+            let notify_store = Arc::clone(store.inner());
+            std::thread::spawn(move || {
+                crate::mta::inbound::notify_comment(&*notify_store, id, true);
+                crate::email::optin::notify_comment_subscribers(&*notify_store, id);
+            });
             Json(json!({
                 "success": true,
                 "id": id,
@@ -214,6 +240,136 @@ pub fn portfolio_filter(
     }))
 }
 
+// ── Double opt-in: newsletter & comment subscriptions ──
+
+#[derive(Debug, Deserialize)]
+pub struct NewsletterSubmit {
+    pub email: String,
+    pub honeypot: Option<String>,
+}
+
+#[post("/newsletter/subscribe", format = "json", data = "<form>")]
+pub fn newsletter_subscribe(
+    store: &State<Arc<dyn Store>>,
+    limiter: &State<RateLimiter>,
+    client_ip: ClientIp,
+    form: Json<NewsletterSubmit>,
+) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    if !s.setting_get_bool("newsletter_enabled") {
+        return Json(json!({"success": false, "error": "Newsletter sign-up is disabled"}));
+    }
+    let message = "Check your inbox to confirm your subscription";
+    // Bots filling the honeypot get the normal response and no email
+    if form.honeypot.as_deref().is_some_and(|h| !h.is_empty()) {
+        return Json(json!({"success": true, "message": message}));
+    }
+    let rate_key = format!("newsletter:{}", auth::hash_ip(&client_ip.0));
+    if !limiter.check(s, &rate_key, 5, std::time::Duration::from_secs(15 * 60)) {
+        return Json(json!({
+            "success": false,
+            "error": "Too many requests. Please try again later."
+        }));
+    }
+    match crate::email::optin::request_newsletter(s, &form.email) {
+        Ok(()) => Json(json!({"success": true, "message": message})),
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
+
+#[get("/newsletter/confirm?<token>")]
+pub fn newsletter_confirm(store: &State<Arc<dyn Store>>, token: &str) -> Redirect {
+    match crate::email::optin::confirm_newsletter(&**store.inner(), token) {
+        Ok(_) => Redirect::to("/?newsletter=confirmed"),
+        Err(_) => Redirect::to("/?newsletter=invalid"),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CommentSubscribeSubmit {
+    pub post_id: i64,
+    pub content_type: Option<String>,
+    pub email: String,
+}
+
+#[post("/comment/subscribe", format = "json", data = "<form>")]
+pub fn comment_subscribe(
+    store: &State<Arc<dyn Store>>,
+    limiter: &State<RateLimiter>,
+    client_ip: ClientIp,
+    form: Json<CommentSubscribeSubmit>,
+) -> Json<Value> {
+    let s: &dyn Store = &**store.inner();
+    if !s.setting_get_bool("comments_subscriptions_enabled") {
+        return Json(json!({"success": false, "error": "Comment subscriptions are disabled"}));
+    }
+    let ct = match form.content_type.as_deref().unwrap_or("post") {
+        "portfolio" => "portfolio",
+        _ => "post",
+    };
+    let rate_key = format!("comment-sub:{}", auth::hash_ip(&client_ip.0));
+    if !limiter.check(s, &rate_key, 5, std::time::Duration::from_secs(15 * 60)) {
+        return Json(json!({
+            "success": false,
+            "error": "Too many requests. Please try again later."
+        }));
+    }
+    match crate::email::optin::request_comment_subscription(s, form.post_id, ct, &form.email) {
+        Ok(()) => Json(json!({
+            "success": true,
+            "message": "Check your inbox to confirm comment notifications"
+        })),
+        Err(e) => Json(json!({"success": false, "error": e})),
+    }
+}
+
+#[get("/comment/subscribe/confirm?<token>")]
+pub fn comment_subscribe_confirm(store: &State<Arc<dyn Store>>, token: &str) -> Redirect {
+    match crate::email::optin::confirm_comment_subscription(&**store.inner(), token) {
+        Ok(_) => Redirect::to("/?comments=subscribed"),
+        Err(_) => Redirect::to("/?comments=invalid"),
+    }
+}
+
+// ── API budget refusals ────────────────────────────────
+// `rate_limit::ApiRateLimit` sends refused requests here instead of their route
+
+#[get("/rate-limited")]
+pub fn rate_limited() -> Custom<Json<Value>> {
+    Custom(
+        Status::TooManyRequests,
+        Json(
+            json!({"success": false, "error": "Rate limit exceeded. Retry after the time in the Retry-After header."}),
+        ),
+    )
+}
+
+#[get("/unauthorized")]
+pub fn unauthorized() -> Custom<Json<Value>> {
+    Custom(
+        Status::Unauthorized,
+        Json(json!({"success": false, "error": "Invalid API key"})),
+    )
+}
+
+/// CORS preflight for any API path; `security::cors::Cors` adds the headers
+#[options("/<_..>")]
+pub fn preflight() -> Status {
+    Status::NoContent
+}
+
 pub fn routes() -> Vec<rocket::Route> {
-    routes![like_toggle, like_status, comment_submit, portfolio_filter]
+    routes![
+        like_toggle,
+        like_status,
+        comment_submit,
+        portfolio_filter,
+        newsletter_subscribe,
+        newsletter_confirm,
+        comment_subscribe,
+        comment_subscribe_confirm,
+        rate_limited,
+        unauthorized,
+        preflight
+    ]
 }
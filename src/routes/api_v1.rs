@@ -0,0 +1,370 @@
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+use crate::models::portfolio::PortfolioItem;
+use crate::models::post::Post;
+use crate::store::cursor::Cursor;
+use crate::store::Store;
+
+// ── Public read-only content API (v1) ──────────────────
+//
+// Published content only, as JSON for mobile apps and headless frontends.
+// Lists take `page`, `per_page` (max 100) and `fields`, a comma-separated
+// list of the fields to return. The unfiltered post list also pages by
+// cursor: pass the `next_cursor` of one page as `cursor` to get the next,
+// which stays fast however deep the list goes.
+
+const DEFAULT_PER_PAGE: i64 = 10;
+const MAX_PER_PAGE: i64 = 100;
+
+type ApiResult = Result<Json<Value>, Custom<Json<Value>>>;
+
+fn error(status: Status, message: &str) -> Custom<Json<Value>> {
+    Custom(status, Json(json!({ "error": message })))
+}
+
+/// Page number and size from the query, clamped to sane bounds
+pub fn paging(page: Option<i64>, per_page: Option<i64>) -> (i64, i64) {
+    let page = page.unwrap_or(1).max(1);
+    let per_page = per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE);
+    (page, per_page)
+}
+
+/// Keep only the requested fields of an object. No `fields` keeps them all;
+/// unknown names are ignored.
+pub fn select_fields(item: Value, fields: Option<&str>) -> Value {
+    let wanted: Vec<&str> = match fields {
+        Some(f) => f
+            .split(',')
+            .map(str::trim)
+            .filter(|f| !f.is_empty())
+            .collect(),
+        None => return item,
+    };
+    if wanted.is_empty() {
+        return item;
+    }
+    match item {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(k, _)| wanted.contains(&k.as_str()))
+                .collect::<Map<String, Value>>(),
+        ),
+        other => other,
+    }
+}
+
+/// List response: items for one page, and `total` when it can be counted
+fn page_of(items: Vec<Value>, page: i64, per_page: i64, total: Option<i64>) -> Json<Value> {
+    // Lists fetch one extra row to tell whether another page follows
+    let has_more = items.len() as i64 > per_page;
+    let data: Vec<Value> = items.into_iter().take(per_page as usize).collect();
+    Json(json!({
+        "data": data,
+        "meta": {
+            "page": page,
+            "per_page": per_page,
+            "total": total,
+            "has_more": has_more,
+        }
+    }))
+}
+
+/// List response for a keyset page. `page` is only known when the list
+/// started from the top rather than from a cursor.
+fn cursor_page_of(
+    items: Vec<Value>,
+    page: Option<i64>,
+    per_page: i64,
+    total: Option<i64>,
+    next: Option<Cursor>,
+) -> Json<Value> {
+    Json(json!({
+        "data": items,
+        "meta": {
+            "page": page,
+            "per_page": per_page,
+            "total": total,
+            "has_more": next.is_some(),
+            "next_cursor": next.map(|c| c.encode()),
+        }
+    }))
+}
+
+fn terms(store: &dyn Store, id: i64, content_type: &str) -> (Value, Value) {
+    let categories: Vec<Value> = store
+        .category_for_content(id, content_type)
+        .into_iter()
+        .map(|c| json!({ "name": c.name, "slug": c.slug }))
+        .collect();
+    let tags: Vec<Value> = store
+        .tag_for_content(id, content_type)
+        .into_iter()
+        .map(|t| json!({ "name": t.name, "slug": t.slug }))
+        .collect();
+    (Value::Array(categories), Value::Array(tags))
+}
+
+/// The public view of a post: no drafts' internals, editor JSON or SEO audit
+pub fn post_json(store: &dyn Store, post: &Post) -> Value {
+    let blog_slug = store.setting_get_or("blog_slug", "journal");
+    let (categories, tags) = terms(store, post.id, "post");
+    json!({
+        "id": post.id,
+        "title": post.title,
+        "slug": post.slug,
+        "url": crate::render::slug_url(&blog_slug, &post.slug),
+        "excerpt": post.excerpt,
+        "content_html": post.content_html,
+        "featured_image": post.featured_image,
+        "meta_title": post.meta_title,
+        "meta_description": post.meta_description,
+        "language": post.language,
+        "translation_group": post.translation_group,
+        "audio_file": post.audio_file,
+        "audio_duration": post.audio_duration,
+        "published_at": post.published_at,
+        "updated_at": post.updated_at,
+        "categories": categories,
+        "tags": tags,
+    })
+}
+
+/// The public view of a portfolio item. Purchase files and notes stay private.
+pub fn portfolio_json(store: &dyn Store, item: &PortfolioItem) -> Value {
+    let portfolio_slug = store.setting_get_or("portfolio_slug", "portfolio");
+    let (categories, tags) = terms(store, item.id, "portfolio");
+    json!({
+        "id": item.id,
+        "title": item.title,
+        "slug": item.slug,
+        "url": crate::render::slug_url(&portfolio_slug, &item.slug),
+        "description_html": item.description_html,
+        "image": format!("/uploads/{}", item.image_path.trim_start_matches('/')),
+        "thumbnail": item
+            .thumbnail_path
+            .as_deref()
+            .filter(|t| !t.is_empty())
+            .map(|t| format!("/uploads/{}", t.trim_start_matches('/'))),
+        "meta_title": item.meta_title,
+        "meta_description": item.meta_description,
+        "likes": item.likes,
+        "for_sale": item.sell_enabled,
+        "price": if item.sell_enabled { item.price } else { None },
+        "published_at": item.published_at,
+        "updated_at": item.updated_at,
+        "categories": categories,
+        "tags": tags,
+    })
+}
+
+fn journal_enabled(store: &dyn Store) -> bool {
+    store.setting_get_or("journal_enabled", "true") != "false"
+}
+
+fn portfolio_enabled(store: &dyn Store) -> bool {
+    store.setting_get_or("portfolio_enabled", "false") == "true"
+}
+
+// ── Posts ──────────────────────────────────────────────
+
+#[get("/posts?<page>&<per_page>&<cursor>&<category>&<tag>&<fields>")]
+pub fn posts(
+    store: &State<Arc<dyn Store>>,
+    page: Option<i64>,
+    per_page: Option<i64>,
+    cursor: Option<&str>,
+    category: Option<&str>,
+    tag: Option<&str>,
+    fields: Option<&str>,
+) -> ApiResult {
+    let s: &dyn Store = &**store.inner();
+    if !journal_enabled(s) {
+        return Err(error(Status::NotFound, "The journal is disabled"));
+    }
+    let (page, per_page) = paging(page, per_page);
+    let filtered = category.is_some() || tag.is_some();
+    if cursor.is_some() && filtered {
+        return Err(error(
+            Status::BadRequest,
+            "Cursors page the unfiltered list only",
+        ));
+    }
+    // The first page and anything after a cursor come from the keyset list,
+    // so every response can hand out a cursor for the next page
+    if !filtered && (cursor.is_some() || page == 1) {
+        let after = match cursor {
+            Some(c) => {
+                Some(Cursor::decode(c).ok_or_else(|| error(Status::BadRequest, "Invalid cursor"))?)
+            }
+            None => None,
+        };
+        let (posts, next) = s.post_list_keyset(None, Some("published"), after.as_ref(), per_page);
+        let items = posts
+            .iter()
+            .map(|p| select_fields(post_json(s, p), fields))
+            .collect();
+        return Ok(cursor_page_of(
+            items,
+            after.is_none().then_some(1),
+            per_page,
+            Some(s.post_count(Some("published"))),
+            next,
+        ));
+    }
+    let offset = (page - 1) * per_page;
+    let (posts, total) = match (category, tag) {
+        (Some(_), Some(_)) => {
+            return Err(error(
+                Status::BadRequest,
+                "Filter by category or tag, not both",
+            ))
+        }
+        (Some(slug), None) => match s.category_find_by_slug(slug) {
+            Some(c) => (
+                s.post_by_category(c.id, per_page + 1, offset),
+                s.post_count_by_category(c.id),
+            ),
+            None => return Err(error(Status::NotFound, "Category not found")),
+        },
+        (None, Some(slug)) => match s.tag_find_by_slug(slug) {
+            Some(t) => (
+                s.post_by_tag(t.id, per_page + 1, offset),
+                s.post_count_by_tag(t.id),
+            ),
+            None => return Err(error(Status::NotFound, "Tag not found")),
+        },
+        (None, None) => (
+            s.post_list(Some("published"), per_page + 1, offset),
+            s.post_count(Some("published")),
+        ),
+    };
+    let items = posts
+        .iter()
+        .map(|p| select_fields(post_json(s, p), fields))
+        .collect();
+    Ok(page_of(items, page, per_page, Some(total)))
+}
+
+#[get("/posts/<slug>?<fields>")]
+pub fn post(store: &State<Arc<dyn Store>>, slug: &str, fields: Option<&str>) -> ApiResult {
+    let s: &dyn Store = &**store.inner();
+    if !journal_enabled(s) {
+        return Err(error(Status::NotFound, "The journal is disabled"));
+    }
+    match s.post_find_by_slug(slug) {
+        Some(p) if p.status == "published" => Ok(Json(select_fields(post_json(s, &p), fields))),
+        _ => Err(error(Status::NotFound, "Post not found")),
+    }
+}
+
+// ── Portfolio ──────────────────────────────────────────
+
+#[get("/portfolio?<page>&<per_page>&<category>&<tag>&<fields>")]
+pub fn portfolio(
+    store: &State<Arc<dyn Store>>,
+    page: Option<i64>,
+    per_page: Option<i64>,
+    category: Option<&str>,
+    tag: Option<&str>,
+    fields: Option<&str>,
+) -> ApiResult {
+    let s: &dyn Store = &**store.inner();
+    if !portfolio_enabled(s) {
+        return Err(error(Status::NotFound, "The portfolio is disabled"));
+    }
+    let (page, per_page) = paging(page, per_page);
+    let offset = (page - 1) * per_page;
+    let (items, total) = match (category, tag) {
+        (Some(_), Some(_)) => {
+            return Err(error(
+                Status::BadRequest,
+                "Filter by category or tag, not both",
+            ))
+        }
+        (Some(slug), None) => {
+            if s.category_find_by_slug(slug).is_none() {
+                return Err(error(Status::NotFound, "Category not found"));
+            }
+            (s.portfolio_by_category(slug, per_page + 1, offset), None)
+        }
+        (None, Some(slug)) => match s.tag_find_by_slug(slug) {
+            Some(t) => (
+                s.portfolio_by_tag(t.id, per_page + 1, offset),
+                Some(s.portfolio_count_by_tag(t.id)),
+            ),
+            None => return Err(error(Status::NotFound, "Tag not found")),
+        },
+        (None, None) => (
+            s.portfolio_list(Some("published"), per_page + 1, offset),
+            Some(s.portfolio_count(Some("published"))),
+        ),
+    };
+    let items = items
+        .iter()
+        .map(|i| select_fields(portfolio_json(s, i), fields))
+        .collect();
+    Ok(page_of(items, page, per_page, total))
+}
+
+#[get("/portfolio/<slug>?<fields>")]
+pub fn portfolio_item(
+    store: &State<Arc<dyn Store>>,
+    slug: &str,
+    fields: Option<&str>,
+) -> ApiResult {
+    let s: &dyn Store = &**store.inner();
+    if !portfolio_enabled(s) {
+        return Err(error(Status::NotFound, "The portfolio is disabled"));
+    }
+    match s.portfolio_find_by_slug(slug) {
+        Some(i) if i.status == "published" => {
+            Ok(Json(select_fields(portfolio_json(s, &i), fields)))
+        }
+        _ => Err(error(Status::NotFound, "Portfolio item not found")),
+    }
+}
+
+// ── Taxonomies ─────────────────────────────────────────
+
+#[derive(Debug, FromForm)]
+pub struct CategoryQuery {
+    /// "post" or "portfolio"
+    #[field(name = "type")]
+    pub kind: Option<String>,
+}
+
+#[get("/categories?<query..>")]
+pub fn categories(store: &State<Arc<dyn Store>>, query: CategoryQuery) -> ApiResult {
+    let kind = query.kind.as_deref();
+    if let Some(t) = kind.filter(|t| *t != "post" && *t != "portfolio") {
+        return Err(error(
+            Status::BadRequest,
+            &format!("Unknown category type: {}", t),
+        ));
+    }
+    let data: Vec<Value> = store
+        .category_list(kind)
+        .into_iter()
+        .map(|c| json!({ "id": c.id, "name": c.name, "slug": c.slug, "type": c.r#type }))
+        .collect();
+    Ok(Json(json!({ "data": data })))
+}
+
+#[get("/tags")]
+pub fn tags(store: &State<Arc<dyn Store>>) -> Json<Value> {
+    let data: Vec<Value> = store
+        .tag_list()
+        .into_iter()
+        .map(|t| json!({ "id": t.id, "name": t.name, "slug": t.slug }))
+        .collect();
+    Json(json!({ "data": data }))
+}
+
+pub fn routes() -> Vec<rocket::Route> {
+    routes![posts, post, portfolio, portfolio_item, categories, tags]
+}
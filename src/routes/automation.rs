@@ -0,0 +1,332 @@
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::models::comment::Comment;
+use crate::models::order::Order;
+use crate::models::post::PostForm;
+use crate::security::auth::ApiKeyUser;
+use crate::store::Store;
+
+// ── Automation (Zapier / Make) ─────────────────────────
+//
+// Polling triggers and simple actions for no-code automation platforms,
+// authenticated with an API key. Triggers return a bare JSON array, newest
+// first, each item with a unique `id` the platform deduplicates on and a
+// `seq` numbering the status change that put it in the feed (a draft being
+// published, an order completing, a comment being approved). Pass the
+// highest `seq` seen as `since` to page forward through later changes, so an
+// item that reaches the feed long after it was created isn't skipped. Buyer
+// and commenter emails are never included. Actions need a key with the
+// "write" scope.
+
+const DEFAULT_LIMIT: i64 = 25;
+const MAX_LIMIT: i64 = 100;
+
+type AutomationResult = Result<Json<Value>, Custom<Json<Value>>>;
+
+fn error(status: Status, message: &str) -> Custom<Json<Value>> {
+    Custom(status, Json(json!({ "error": message })))
+}
+
+/// The caller's key, or the error response for a missing or read-only one
+fn authorize(caller: Option<ApiKeyUser>, write: bool) -> Result<ApiKeyUser, Custom<Json<Value>>> {
+    match caller {
+        None => Err(error(Status::Unauthorized, "A valid API key is required")),
+        Some(c) if write && !c.can_write() => Err(error(
+            Status::Forbidden,
+            "This action needs an API key with the write scope",
+        )),
+        Some(c) => Ok(c),
+    }
+}
+
+fn page_limit(limit: Option<i64>) -> i64 {
+    limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+}
+
+/// At most `limit` items, newest first. Without a cursor these are the
+/// newest items; with `since` they're the oldest ones after it, so a burst
+/// bigger than `limit` arrives over several polls instead of being skipped.
+pub fn since_cursor(mut items: Vec<Value>, since: Option<i64>, limit: Option<i64>) -> Vec<Value> {
+    let id = |i: &Value| i["seq"].as_i64().unwrap_or(0);
+    let limit = page_limit(limit) as usize;
+    match since {
+        Some(since) => {
+            items.retain(|i| id(i) > since);
+            items.sort_by_key(id);
+        }
+        None => items.sort_by_key(|i| std::cmp::Reverse(id(i))),
+    }
+    items.truncate(limit);
+    items.sort_by_key(|i| std::cmp::Reverse(id(i)));
+    items
+}
+
+/// `item` with the number of the status change that put it in the feed
+fn with_seq(mut item: Value, seq: i64) -> Value {
+    item["seq"] = json!(seq);
+    item
+}
+
+fn order_json(store: &dyn Store, order: &Order) -> Value {
+    let item = store.portfolio_find_by_id(order.portfolio_id);
+    json!({
+        "id": order.id,
+        "uuid": order.uuid,
+        "portfolio_id": order.portfolio_id,
+        "portfolio_title": item.map(|i| i.title),
+        "amount": order.amount,
+        "currency": order.currency,
+        "provider": order.provider,
+        "status": order.status,
+        "created_at": order.created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    })
+}
+
+fn comment_json(store: &dyn Store, comment: &Comment) -> Value {
+    let title = match comment.content_type.as_str() {
+        "portfolio" => store.portfolio_find_by_id(comment.post_id).map(|i| i.title),
+        _ => store.post_find_by_id(comment.post_id).map(|p| p.title),
+    };
+    json!({
+        "id": comment.id,
+        "post_id": comment.post_id,
+        "content_type": comment.content_type,
+        "post_title": title,
+        "author_name": comment.author_name,
+        "body": comment.body,
+        "status": comment.status,
+        "parent_id": comment.parent_id,
+        "created_at": comment.created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    })
+}
+
+// ── Authentication test ────────────────────────────────
+
+/// Lets a platform check a key when the connection is set up
+#[get("/me")]
+pub fn me(store: &State<Arc<dyn Store>>, caller: Option<ApiKeyUser>) -> AutomationResult {
+    let caller = authorize(caller, false)?;
+    Ok(Json(json!({
+        "site_name": store.setting_get_or("site_name", "Velocty"),
+        "key_name": caller.key.name,
+        "scope": caller.key.scope,
+    })))
+}
+
+// ── Triggers ───────────────────────────────────────────
+
+#[get("/triggers/posts?<since>&<limit>")]
+pub fn new_posts(
+    store: &State<Arc<dyn Store>>,
+    caller: Option<ApiKeyUser>,
+    since: Option<i64>,
+    limit: Option<i64>,
+) -> AutomationResult {
+    authorize(caller, false)?;
+    let s: &dyn Store = &**store.inner();
+    let items = s
+        .post_list_since("published", since, page_limit(limit))
+        .iter()
+        .map(|(seq, p)| with_seq(crate::routes::api_v1::post_json(s, p), *seq))
+        .collect();
+    Ok(Json(Value::Array(since_cursor(items, since, limit))))
+}
+
+/// Completed orders only: pending ones may never be paid
+#[get("/triggers/orders?<since>&<limit>")]
+pub fn new_orders(
+    store: &State<Arc<dyn Store>>,
+    caller: Option<ApiKeyUser>,
+    since: Option<i64>,
+    limit: Option<i64>,
+) -> AutomationResult {
+    authorize(caller, false)?;
+    let s: &dyn Store = &**store.inner();
+    let items = s
+        .order_list_since("completed", since, page_limit(limit))
+        .iter()
+        .map(|(seq, o)| with_seq(order_json(s, o), *seq))
+        .collect();
+    Ok(Json(Value::Array(since_cursor(items, since, limit))))
+}
+
+/// All comments, or those with `status` ("pending", "approved", "spam").
+/// Unmoderated comments aren't public, so read keys only get approved ones.
+#[get("/triggers/comments?<since>&<limit>&<status>")]
+pub fn new_comments(
+    store: &State<Arc<dyn Store>>,
+    caller: Option<ApiKeyUser>,
+    since: Option<i64>,
+    limit: Option<i64>,
+    status: Option<&str>,
+) -> AutomationResult {
+    let caller = authorize(caller, false)?;
+    let status = match status.filter(|st| !st.is_empty()) {
+        st if caller.can_write() => st,
+        None | Some("approved") => Some("approved"),
+        Some(_) => {
+            return Err(error(
+                Status::Forbidden,
+                "Pending and spam comments need an API key with the write scope",
+            ))
+        }
+    };
+    let s: &dyn Store = &**store.inner();
+    let items = s
+        .comment_list_since(status, since, page_limit(limit))
+        .iter()
+        .map(|(seq, c)| with_seq(comment_json(s, c), *seq))
+        .collect();
+    Ok(Json(Value::Array(since_cursor(items, since, limit))))
+}
+
+// ── Actions ────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct DraftPostAction {
+    pub title: String,
+    pub content_html: Option<String>,
+    pub excerpt: Option<String>,
+    /// Tag names; missing tags are created
+    pub tags: Option<Vec<String>>,
+}
+
+/// Create a draft post for review in the admin. Never publishes.
+#[post("/actions/draft-post", format = "json", data = "<body>")]
+pub fn create_draft_post(
+    store: &State<Arc<dyn Store>>,
+    caller: Option<ApiKeyUser>,
+    body: Json<DraftPostAction>,
+) -> AutomationResult {
+    let caller = authorize(caller, true)?;
+    let s: &dyn Store = &**store.inner();
+    let title = body.title.trim();
+    if title.is_empty() {
+        return Err(error(Status::UnprocessableEntity, "Title is required"));
+    }
+    let slug = unique_post_slug(s, title);
+    let form = PostForm {
+        title: title.to_string(),
+        slug: slug.clone(),
+        content_json: "{}".to_string(),
+        content_html: crate::designs::package::sanitize_html(
+            body.content_html.as_deref().unwrap_or(""),
+        ),
+        excerpt: body
+            .excerpt
+            .as_deref()
+            .map(str::trim)
+            .filter(|e| !e.is_empty())
+            .map(str::to_string),
+        featured_image: None,
+        meta_title: None,
+        meta_description: None,
+        status: "draft".to_string(),
+        published_at: None,
+        category_ids: None,
+        tag_ids: None,
+        robots_noindex: None,
+        robots_nofollow: None,
+        canonical_url: None,
+        schema_type: None,
+        focus_keyword: None,
+        language: None,
+        translation_group: None,
+        audio_file: None,
+        audio_duration: None,
+        audio_bytes: None,
+        template: None,
+    };
+    let id = s
+        .post_create(&form)
+        .map_err(|e| error(Status::InternalServerError, &e))?;
+
+    let tag_ids: Vec<i64> = body
+        .tags
+        .iter()
+        .flatten()
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .filter_map(|t| s.tag_find_or_create(t).ok())
+        .collect();
+    if !tag_ids.is_empty() {
+        let _ = s.tag_set_for_content(id, "post", &tag_ids);
+    }
+
+    s.audit_log(
+        None,
+        Some(&format!("API key: {}", caller.key.name)),
+        "create",
+        Some("post"),
+        Some(id),
+        Some(title),
+        Some("Draft created by automation"),
+        None,
+    );
+    Ok(Json(json!({
+        "id": id,
+        "title": title,
+        "slug": slug,
+        "status": "draft",
+    })))
+}
+
+/// A post slug for `title` that isn't taken yet
+fn unique_post_slug(store: &dyn Store, title: &str) -> String {
+    let base = match slug::slugify(title) {
+        s if s.is_empty() => "post".to_string(),
+        s => s,
+    };
+    let mut candidate = base.clone();
+    let mut n = 2;
+    while store.post_find_by_slug(&candidate).is_some() {
+        candidate = format!("{}-{}", base, n);
+        n += 1;
+    }
+    candidate
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubscriberAction {
+    pub email: String,
+}
+
+/// Add a newsletter subscriber. They still get the confirmation email and
+/// aren't subscribed until they click it.
+#[post("/actions/subscriber", format = "json", data = "<body>")]
+pub fn add_subscriber(
+    store: &State<Arc<dyn Store>>,
+    caller: Option<ApiKeyUser>,
+    body: Json<SubscriberAction>,
+) -> AutomationResult {
+    authorize(caller, true)?;
+    let s: &dyn Store = &**store.inner();
+    if !s.setting_get_bool("newsletter_enabled") {
+        return Err(error(Status::Conflict, "Newsletter sign-up is disabled"));
+    }
+    crate::email::optin::request_newsletter(s, &body.email)
+        .map_err(|e| error(Status::UnprocessableEntity, &e))?;
+    let email = body.email.trim().to_lowercase();
+    Ok(Json(json!({
+        "id": email,
+        "email": email,
+        "status": s.newsletter_status(&email).unwrap_or_else(|| "pending".into()),
+    })))
+}
+
+pub fn routes() -> Vec<rocket::Route> {
+    routes![
+        me,
+        new_posts,
+        new_orders,
+        new_comments,
+        create_draft_post,
+        add_subscriber
+    ]
+}
@@ -14,6 +14,7 @@ use rocket::State;
 use serde::Deserialize;
 use serde_json::{json, Value};
 
+use crate::security::headers::CspNonce;
 use crate::store::Store;
 
 // Re-export constant_time_eq so payment provider submodules can use super::constant_time_eq
@@ -58,7 +59,7 @@ pub fn generate_license_key() -> String {
 /// After a payment provider confirms, this creates the order + download token + license + sends email.
 /// Returns JSON with download_token, license_key, etc.
 pub fn finalize_order(
-    store: &dyn Store,
+    store: &Arc<dyn Store>,
     order_uuid: &str,
     provider_order_id: &str,
     buyer_email: &str,
@@ -97,7 +98,6 @@ pub fn finalize_order(
     let base = site_url(&settings);
     let download_url = format!("{}/download/{}", base, token);
     let cur = currency(&settings);
-    // We need the settings for the email — pass them via the thread
     let email = buyer_email.to_string();
     let title = item.as_ref().map(|i| i.title.clone()).unwrap_or_default();
     let note = item
@@ -108,18 +108,9 @@ pub fn finalize_order(
     let amt = order.amount;
     let cur = cur.clone();
     let dl = download_url.clone();
-    let settings_clone = settings.clone();
+    let s = Arc::clone(store);
     std::thread::spawn(move || {
-        crate::email::send_purchase_email_with_settings(
-            &settings_clone,
-            &email,
-            &title,
-            &note,
-            &dl,
-            Some(&lk),
-            amt,
-            &cur,
-        );
+        crate::email::send_purchase_email(&*s, &email, &title, &note, &dl, Some(&lk), amt, &cur);
     });
 
     Ok(json!({
@@ -175,7 +166,11 @@ pub fn urlencoding(s: &str) -> String {
 // ── Download page: Validate token and show download UI ─
 
 #[get("/download/<token>")]
-pub fn download_page(store: &State<Arc<dyn Store>>, token: &str) -> rocket_dyn_templates::Template {
+pub fn download_page(
+    store: &State<Arc<dyn Store>>,
+    token: &str,
+    nonce: CspNonce,
+) -> rocket_dyn_templates::Template {
     let s: &dyn Store = &**store.inner();
     let settings: HashMap<String, String> = s.setting_all();
 
@@ -187,6 +182,7 @@ pub fn download_page(store: &State<Arc<dyn Store>>, token: &str) -> rocket_dyn_t
                 json!({
                     "error": "Invalid download link",
                     "settings": &settings,
+                "csp_nonce": &nonce.0,
                 }),
             );
         }
@@ -204,6 +200,7 @@ pub fn download_page(store: &State<Arc<dyn Store>>, token: &str) -> rocket_dyn_t
                 "error": reason,
                 "expired": true,
                 "settings": &settings,
+            "csp_nonce": &nonce.0,
             }),
         );
     }
@@ -216,6 +213,7 @@ pub fn download_page(store: &State<Arc<dyn Store>>, token: &str) -> rocket_dyn_t
                 json!({
                     "error": "Order not found or not completed",
                     "settings": &settings,
+                "csp_nonce": &nonce.0,
                 }),
             );
         }
@@ -229,6 +227,7 @@ pub fn download_page(store: &State<Arc<dyn Store>>, token: &str) -> rocket_dyn_t
                 json!({
                     "error": "Item not found",
                     "settings": &settings,
+                "csp_nonce": &nonce.0,
                 }),
             );
         }
@@ -240,6 +239,7 @@ pub fn download_page(store: &State<Arc<dyn Store>>, token: &str) -> rocket_dyn_t
         "download",
         json!({
             "settings": &settings,
+        "csp_nonce": &nonce.0,
             "item_title": item.title,
             "item_slug": item.slug,
             "image_path": item.image_path,
@@ -386,7 +386,7 @@ pub fn check_purchase(
     // Rate limit: 10 lookups per 15 minutes per IP to prevent email enumeration
     let ip_hash = crate::security::auth::hash_ip(&client_ip.0);
     let rate_key = format!("check_purchase:{}", ip_hash);
-    if !limiter.check_and_record(&rate_key, 10, std::time::Duration::from_secs(15 * 60)) {
+    if !limiter.check(s, &rate_key, 10, std::time::Duration::from_secs(15 * 60)) {
         return Json(json!({ "ok": false, "error": "Too many requests. Please try again later." }));
     }
 
@@ -125,7 +125,7 @@ pub fn mollie_webhook(store: &State<Arc<dyn Store>>, body: String) -> &'static s
                     .and_then(|d| d.get("consumerName"))
                     .and_then(|n| n.as_str())
                     .unwrap_or("");
-                let _ = finalize_order(s, order_id_str, payment_id, email, "");
+                let _ = finalize_order(store.inner(), order_id_str, payment_id, email, "");
             }
         }
     }
@@ -180,7 +180,7 @@ pub fn payoneer_webhook(store: &State<Arc<dyn Store>>, body: Json<Value>) -> &'s
                             .get("payee_email")
                             .and_then(|e| e.as_str())
                             .unwrap_or("");
-                        let _ = finalize_order(s, order_uuid, payout_id, email, "");
+                        let _ = finalize_order(store.inner(), order_uuid, payout_id, email, "");
                     }
                     Some(order) => {
                         log::warn!(
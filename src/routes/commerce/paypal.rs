@@ -142,7 +142,7 @@ pub fn paypal_capture_order(
     }
 
     match finalize_order(
-        s,
+        store.inner(),
         &body.order_id,
         &body.paypal_order_id,
         &body.buyer_email,
@@ -137,7 +137,7 @@ pub fn razorpay_verify(
     }
 
     match finalize_order(
-        s,
+        store.inner(),
         &body.order_id,
         &body.razorpay_payment_id,
         &body.buyer_email,
@@ -227,7 +227,7 @@ pub fn square_webhook(
         let sq_payment_id = payment.get("id").and_then(|i| i.as_str()).unwrap_or("");
 
         if !order_uuid.is_empty() {
-            let _ = finalize_order(s, order_uuid, sq_payment_id, buyer_email, "");
+            let _ = finalize_order(store.inner(), order_uuid, sq_payment_id, buyer_email, "");
         }
     }
 
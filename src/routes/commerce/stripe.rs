@@ -190,7 +190,7 @@ pub fn stripe_success(
             .and_then(|e| e.as_str())
             .unwrap_or("");
 
-        if let Ok(result) = finalize_order(s, order_id, session_id, buyer_email, "") {
+        if let Ok(result) = finalize_order(store.inner(), order_id, session_id, buyer_email, "") {
             if let Some(token) = result.get("download_token").and_then(|t| t.as_str()) {
                 return rocket::response::Redirect::to(format!("/download/{}", token));
             }
@@ -290,7 +290,7 @@ pub fn stripe_webhook(
             .unwrap_or("");
 
         if !order_id_str.is_empty() {
-            let _ = finalize_order(s, order_id_str, session_id, buyer_email, "");
+            let _ = finalize_order(store.inner(), order_id_str, session_id, buyer_email, "");
         }
     }
 
@@ -159,7 +159,7 @@ pub fn twocheckout_webhook(
         if !order_uuid.is_empty() {
             let email = body.customer_email.as_deref().unwrap_or("");
             let name = body.customer_name.as_deref().unwrap_or("");
-            let _ = finalize_order(s, order_uuid, sale_id, email, name);
+            let _ = finalize_order(store.inner(), order_uuid, sale_id, email, name);
         }
     }
 
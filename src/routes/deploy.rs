@@ -76,6 +76,17 @@ pub struct DeployPost {
     pub published_at: Option<String>,
     pub category_slugs: Vec<String>,
     pub tag_names: Vec<String>,
+    pub robots_noindex: Option<bool>,
+    pub robots_nofollow: Option<bool>,
+    pub canonical_url: Option<String>,
+    pub schema_type: Option<String>,
+    pub focus_keyword: Option<String>,
+    pub language: Option<String>,
+    pub translation_group: Option<String>,
+    pub audio_file: Option<String>,
+    pub audio_duration: Option<i64>,
+    pub audio_bytes: Option<i64>,
+    pub template: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -97,6 +108,12 @@ pub struct DeployPortfolioItem {
     pub purchase_note: Option<String>,
     pub payment_provider: Option<String>,
     pub download_file_path: Option<String>,
+    pub robots_noindex: Option<bool>,
+    pub robots_nofollow: Option<bool>,
+    pub canonical_url: Option<String>,
+    pub schema_type: Option<String>,
+    pub focus_keyword: Option<String>,
+    pub template: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -247,6 +264,17 @@ pub fn deploy_receive(
             } else {
                 Some(tag_ids.clone())
             },
+            robots_noindex: post.robots_noindex,
+            robots_nofollow: post.robots_nofollow,
+            canonical_url: post.canonical_url.clone(),
+            schema_type: post.schema_type.clone(),
+            focus_keyword: post.focus_keyword.clone(),
+            language: post.language.clone(),
+            translation_group: post.translation_group.clone(),
+            audio_file: post.audio_file.clone(),
+            audio_duration: post.audio_duration,
+            audio_bytes: post.audio_bytes,
+            template: post.template.clone(),
         };
 
         if let Some(existing) = s.post_find_by_slug(&post.slug) {
@@ -304,6 +332,12 @@ pub fn deploy_receive(
             } else {
                 Some(tag_ids.clone())
             },
+            robots_noindex: item.robots_noindex,
+            robots_nofollow: item.robots_nofollow,
+            canonical_url: item.canonical_url.clone(),
+            schema_type: item.schema_type.clone(),
+            focus_keyword: item.focus_keyword.clone(),
+            template: item.template.clone(),
         };
 
         if let Some(existing) = s.portfolio_find_by_slug(&item.slug) {
@@ -385,7 +419,7 @@ pub fn deploy_receive(
 /// Called by the deploy modal on the Dev/Staging side.
 #[get("/deploy/gather")]
 pub fn deploy_gather(
-    _admin: crate::security::auth::AdminUser,
+    _admin: crate::security::auth::DeployManager,
     store: &State<Arc<dyn Store>>,
 ) -> Json<Value> {
     let s: &dyn Store = &**store.inner();
@@ -411,6 +445,16 @@ pub fn deploy_gather(
                 "published_at": p.published_at.map(|d| d.to_string()),
                 "category_slugs": cats.iter().map(|c| c.slug.clone()).collect::<Vec<_>>(),
                 "tag_names": tags.iter().map(|t| t.name.clone()).collect::<Vec<_>>(),
+                "robots_noindex": p.robots_noindex,
+                "robots_nofollow": p.robots_nofollow,
+                "canonical_url": p.canonical_url,
+                "schema_type": p.schema_type,
+                "focus_keyword": p.focus_keyword,
+                "language": p.language,
+                "translation_group": p.translation_group,
+                "audio_file": p.audio_file,
+                "audio_duration": p.audio_duration,
+                "audio_bytes": p.audio_bytes,
             })
         })
         .collect();
@@ -441,6 +485,11 @@ pub fn deploy_gather(
                 "purchase_note": i.purchase_note,
                 "payment_provider": i.payment_provider,
                 "download_file_path": i.download_file_path,
+                "robots_noindex": i.robots_noindex,
+                "robots_nofollow": i.robots_nofollow,
+                "canonical_url": i.canonical_url,
+                "schema_type": i.schema_type,
+                "focus_keyword": i.focus_keyword,
             })
         })
         .collect();
@@ -494,7 +543,7 @@ pub fn deploy_gather(
 
 /// Read a single upload file as base64 for transfer.
 #[get("/deploy/upload-data?<path>")]
-pub fn deploy_upload_data(_admin: crate::security::auth::AdminUser, path: &str) -> Json<Value> {
+pub fn deploy_upload_data(_admin: crate::security::auth::DeployManager, path: &str) -> Json<Value> {
     let full_path = format!("website/site/{}", path.trim_start_matches('/'));
     if !is_safe_upload_path(&full_path) {
         return Json(json!({ "ok": false, "error": "Invalid path" }));
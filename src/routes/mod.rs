@@ -1,8 +1,12 @@
+pub mod account;
 pub mod admin;
 pub mod ai;
 pub mod api;
+pub mod api_v1;
+pub mod automation;
 pub mod commerce;
 pub mod deploy;
+pub mod openapi;
 pub mod public;
 pub mod security;
 #[cfg(feature = "multi-site")]
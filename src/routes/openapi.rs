@@ -0,0 +1,667 @@
+use rocket::serde::json::Json;
+use rocket::{Orbit, Rocket, Route, State};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+
+use crate::routes::api::{CommentSubmit, CommentSubscribeSubmit, LikeResponse, NewsletterSubmit};
+use crate::routes::automation::{DraftPostAction, SubscriberAction};
+use crate::routes::commerce::CheckPurchaseRequest;
+use crate::security::auth::AuthenticatedUser;
+use crate::store::Store;
+use crate::AdminSlug;
+
+// ── OpenAPI document ───────────────────────────────────
+//
+// `/api/openapi.json` describes every mounted API route. Paths, methods and
+// parameters come from the route table itself, so new routes show up
+// without touching this file; summaries and request/response schemas come
+// from the annotations below. Admin API routes are only listed for a
+// signed-in admin, since they reveal the admin slug.
+
+/// Routes the rate limiter reroutes to; not callable on their own
+const INTERNAL: &[&str] = &["/api/rate-limited", "/api/unauthorized"];
+
+/// A mounted route, as the spec sees it
+#[derive(Debug, Clone)]
+pub struct Endpoint {
+    pub method: String,
+    /// Mounted path in Rocket syntax, e.g. `/api/v1/posts/<slug>`
+    pub path: String,
+    /// Names of the query parameters
+    pub query: Vec<String>,
+    /// Media type of the request body, for routes that take one
+    pub format: Option<String>,
+    /// Handler name, used for the operation id and fallback summary
+    pub name: String,
+}
+
+impl Endpoint {
+    pub fn from_route(route: &Route) -> Self {
+        let query = route
+            .uri
+            .query()
+            .unwrap_or("")
+            .split('&')
+            .filter_map(|seg| seg.strip_prefix('<')?.strip_suffix('>'))
+            // `<params..>` collects the whole query into a form
+            .filter(|name| !name.ends_with(".."))
+            .map(str::to_string)
+            .collect();
+        Endpoint {
+            method: route.method.as_str().to_ascii_lowercase(),
+            path: route.uri.path().to_string(),
+            query,
+            format: route.format.as_ref().map(|f| f.to_string()),
+            name: route.name.as_deref().unwrap_or("").to_string(),
+        }
+    }
+}
+
+/// A request or response type with a schema in the document
+pub trait ApiSchema {
+    const NAME: &'static str;
+    fn schema() -> Value;
+}
+
+type Schema = (&'static str, fn() -> Value);
+
+fn schema_of<T: ApiSchema>() -> Schema {
+    (T::NAME, T::schema)
+}
+
+/// Summary and types for a route, matched by method and Rocket path
+struct Annotation {
+    method: &'static str,
+    path: &'static str,
+    summary: &'static str,
+    request: Option<Schema>,
+    response: Option<Schema>,
+}
+
+fn annotation(
+    method: &'static str,
+    path: &'static str,
+    summary: &'static str,
+    request: Option<Schema>,
+    response: Option<Schema>,
+) -> Annotation {
+    Annotation {
+        method,
+        path,
+        summary,
+        request,
+        response,
+    }
+}
+
+fn annotations() -> Vec<Annotation> {
+    vec![
+        annotation(
+            "get",
+            "/api/v1/posts",
+            "List published posts",
+            None,
+            Some(schema_of::<PostPage>()),
+        ),
+        annotation(
+            "get",
+            "/api/v1/posts/<slug>",
+            "Get a published post",
+            None,
+            Some(schema_of::<PostSchema>()),
+        ),
+        annotation(
+            "get",
+            "/api/v1/portfolio",
+            "List published portfolio items",
+            None,
+            Some(schema_of::<PortfolioPage>()),
+        ),
+        annotation(
+            "get",
+            "/api/v1/portfolio/<slug>",
+            "Get a published portfolio item",
+            None,
+            Some(schema_of::<PortfolioSchema>()),
+        ),
+        annotation(
+            "get",
+            "/api/v1/categories",
+            "List categories",
+            None,
+            Some(schema_of::<TermList>()),
+        ),
+        annotation(
+            "get",
+            "/api/v1/tags",
+            "List tags",
+            None,
+            Some(schema_of::<TermList>()),
+        ),
+        annotation(
+            "post",
+            "/api/like/<id>",
+            "Like or unlike a portfolio item",
+            None,
+            Some(schema_of::<LikeResponse>()),
+        ),
+        annotation(
+            "get",
+            "/api/like/<id>/status",
+            "Whether the caller liked a portfolio item",
+            None,
+            Some(schema_of::<LikeResponse>()),
+        ),
+        annotation(
+            "post",
+            "/api/comment",
+            "Submit a comment",
+            Some(schema_of::<CommentSubmit>()),
+            Some(schema_of::<ResultSchema>()),
+        ),
+        annotation(
+            "post",
+            "/api/newsletter/subscribe",
+            "Subscribe to the newsletter",
+            Some(schema_of::<NewsletterSubmit>()),
+            Some(schema_of::<ResultSchema>()),
+        ),
+        annotation(
+            "post",
+            "/api/comment/subscribe",
+            "Get emailed about new comments",
+            Some(schema_of::<CommentSubscribeSubmit>()),
+            Some(schema_of::<ResultSchema>()),
+        ),
+        annotation(
+            "post",
+            "/api/checkout/check",
+            "Check for an earlier purchase",
+            Some(schema_of::<CheckPurchaseRequest>()),
+            None,
+        ),
+        annotation(
+            "get",
+            "/api/automation/triggers/posts",
+            "Posts published since a cursor, for automation platforms",
+            None,
+            None,
+        ),
+        annotation(
+            "get",
+            "/api/automation/triggers/orders",
+            "Completed orders since a cursor, for automation platforms",
+            None,
+            None,
+        ),
+        annotation(
+            "get",
+            "/api/automation/triggers/comments",
+            "Comments since a cursor, for automation platforms",
+            None,
+            None,
+        ),
+        annotation(
+            "post",
+            "/api/automation/actions/draft-post",
+            "Create a draft post (write key)",
+            Some(schema_of::<DraftPostAction>()),
+            None,
+        ),
+        annotation(
+            "post",
+            "/api/automation/actions/subscriber",
+            "Add a newsletter subscriber (write key)",
+            Some(schema_of::<SubscriberAction>()),
+            None,
+        ),
+    ]
+}
+
+// ── Schemas ────────────────────────────────────────────
+
+fn string() -> Value {
+    json!({"type": "string"})
+}
+
+fn nullable_string() -> Value {
+    json!({"type": "string", "nullable": true})
+}
+
+fn integer() -> Value {
+    json!({"type": "integer", "format": "int64"})
+}
+
+fn object(properties: Value, required: &[&str]) -> Value {
+    json!({"type": "object", "properties": properties, "required": required})
+}
+
+fn reference(name: &str) -> Value {
+    json!({"$ref": format!("#/components/schemas/{}", name)})
+}
+
+fn page_of(item: &str) -> Value {
+    object(
+        json!({
+            "data": {"type": "array", "items": reference(item)},
+            "meta": {
+                "type": "object",
+                "properties": {
+                    "page": integer(),
+                    "per_page": integer(),
+                    "total": {"type": "integer", "nullable": true},
+                    "has_more": {"type": "boolean"},
+                },
+            },
+        }),
+        &["data", "meta"],
+    )
+}
+
+fn terms() -> Value {
+    json!({"type": "array", "items": object(json!({"name": string(), "slug": string()}), &[])})
+}
+
+pub struct PostSchema;
+impl ApiSchema for PostSchema {
+    const NAME: &'static str = "Post";
+    fn schema() -> Value {
+        object(
+            json!({
+                "id": integer(),
+                "title": string(),
+                "slug": string(),
+                "url": string(),
+                "excerpt": nullable_string(),
+                "content_html": string(),
+                "featured_image": nullable_string(),
+                "meta_title": nullable_string(),
+                "meta_description": nullable_string(),
+                "language": nullable_string(),
+                "translation_group": nullable_string(),
+                "audio_file": nullable_string(),
+                "audio_duration": {"type": "integer", "nullable": true, "description": "Seconds"},
+                "published_at": nullable_string(),
+                "updated_at": string(),
+                "categories": terms(),
+                "tags": terms(),
+            }),
+            &["id", "title", "slug"],
+        )
+    }
+}
+
+pub struct PortfolioSchema;
+impl ApiSchema for PortfolioSchema {
+    const NAME: &'static str = "PortfolioItem";
+    fn schema() -> Value {
+        object(
+            json!({
+                "id": integer(),
+                "title": string(),
+                "slug": string(),
+                "url": string(),
+                "description_html": nullable_string(),
+                "image": string(),
+                "thumbnail": nullable_string(),
+                "meta_title": nullable_string(),
+                "meta_description": nullable_string(),
+                "likes": integer(),
+                "for_sale": {"type": "boolean"},
+                "price": {"type": "number", "nullable": true},
+                "published_at": nullable_string(),
+                "updated_at": string(),
+                "categories": terms(),
+                "tags": terms(),
+            }),
+            &["id", "title", "slug"],
+        )
+    }
+}
+
+pub struct PostPage;
+impl ApiSchema for PostPage {
+    const NAME: &'static str = "PostPage";
+    fn schema() -> Value {
+        let mut page = page_of(PostSchema::NAME);
+        // Only the post list pages by cursor
+        page["properties"]["meta"]["properties"]["page"] =
+            json!({"type": "integer", "nullable": true});
+        page["properties"]["meta"]["properties"]["next_cursor"] = nullable_string();
+        page
+    }
+}
+
+pub struct PortfolioPage;
+impl ApiSchema for PortfolioPage {
+    const NAME: &'static str = "PortfolioPage";
+    fn schema() -> Value {
+        page_of(PortfolioSchema::NAME)
+    }
+}
+
+pub struct TermList;
+impl ApiSchema for TermList {
+    const NAME: &'static str = "TermList";
+    fn schema() -> Value {
+        object(
+            json!({"data": {"type": "array", "items": object(json!({
+                "id": integer(),
+                "name": string(),
+                "slug": string(),
+                "type": {"type": "string", "description": "Categories only: post or portfolio"},
+            }), &["id", "name", "slug"])}}),
+            &["data"],
+        )
+    }
+}
+
+/// The `{success, error}` shape most public endpoints answer with
+pub struct ResultSchema;
+impl ApiSchema for ResultSchema {
+    const NAME: &'static str = "Result";
+    fn schema() -> Value {
+        object(
+            json!({"success": {"type": "boolean"}, "message": string(), "error": string()}),
+            &["success"],
+        )
+    }
+}
+
+impl ApiSchema for LikeResponse {
+    const NAME: &'static str = "LikeResponse";
+    fn schema() -> Value {
+        object(
+            json!({"liked": {"type": "boolean"}, "count": integer()}),
+            &["liked", "count"],
+        )
+    }
+}
+
+impl ApiSchema for CommentSubmit {
+    const NAME: &'static str = "CommentSubmit";
+    fn schema() -> Value {
+        object(
+            json!({
+                "post_id": integer(),
+                "content_type": {"type": "string", "enum": ["post", "portfolio"]},
+                "author_name": string(),
+                "author_email": string(),
+                "body": string(),
+                "parent_id": integer(),
+                "captcha_token": string(),
+                "honeypot": {"type": "string", "description": "Leave empty"},
+            }),
+            &["post_id", "author_name", "body"],
+        )
+    }
+}
+
+impl ApiSchema for NewsletterSubmit {
+    const NAME: &'static str = "NewsletterSubmit";
+    fn schema() -> Value {
+        object(
+            json!({"email": string(), "honeypot": {"type": "string", "description": "Leave empty"}}),
+            &["email"],
+        )
+    }
+}
+
+impl ApiSchema for CommentSubscribeSubmit {
+    const NAME: &'static str = "CommentSubscribeSubmit";
+    fn schema() -> Value {
+        object(
+            json!({
+                "post_id": integer(),
+                "content_type": {"type": "string", "enum": ["post", "portfolio"]},
+                "email": string(),
+            }),
+            &["post_id", "email"],
+        )
+    }
+}
+
+impl ApiSchema for CheckPurchaseRequest {
+    const NAME: &'static str = "CheckPurchaseRequest";
+    fn schema() -> Value {
+        object(
+            json!({"portfolio_id": integer(), "email": string()}),
+            &["portfolio_id", "email"],
+        )
+    }
+}
+
+impl ApiSchema for DraftPostAction {
+    const NAME: &'static str = "DraftPostAction";
+    fn schema() -> Value {
+        object(
+            json!({
+                "title": string(),
+                "content_html": string(),
+                "excerpt": string(),
+                "tags": {"type": "array", "items": string()},
+            }),
+            &["title"],
+        )
+    }
+}
+
+impl ApiSchema for SubscriberAction {
+    const NAME: &'static str = "SubscriberAction";
+    fn schema() -> Value {
+        object(json!({"email": string()}), &["email"])
+    }
+}
+
+// ── Building the document ──────────────────────────────
+
+/// `/api/v1/posts/<slug>` → `/api/v1/posts/{slug}`, with the parameter names
+fn openapi_path(path: &str) -> (String, Vec<String>) {
+    let mut params = Vec::new();
+    let segments: Vec<String> = path
+        .split('/')
+        .map(
+            |seg| match seg.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+                Some(name) => {
+                    let name = name.trim_end_matches("..").to_string();
+                    params.push(name.clone());
+                    format!("{{{}}}", name)
+                }
+                None => seg.to_string(),
+            },
+        )
+        .collect();
+    (segments.join("/"), params)
+}
+
+fn param_schema(name: &str) -> Value {
+    if name == "id" || name.ends_with("_id") || name == "page" || name == "per_page" {
+        integer()
+    } else {
+        string()
+    }
+}
+
+fn tag_for(path: &str, admin: bool) -> &'static str {
+    if admin {
+        "Admin"
+    } else if path.starts_with("/api/v1/") {
+        "Content"
+    } else if path.starts_with("/api/deploy/") {
+        "Deploy"
+    } else if path.starts_with("/api/checkout/")
+        || path.starts_with("/api/mollie/")
+        || path.starts_with("/api/payoneer/")
+    {
+        "Commerce"
+    } else {
+        "Public"
+    }
+}
+
+/// "comment_subscribe_confirm" → "Comment subscribe confirm"
+fn humanize(name: &str) -> String {
+    let words = name.replace('_', " ");
+    let mut chars = words.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => words,
+    }
+}
+
+/// The OpenAPI 3 document for `endpoints`. Routes under the admin mount
+/// are listed under `/<admin_slug>` when `admin_slug` is given and left
+/// out otherwise.
+pub fn build(endpoints: &[Endpoint], server_url: &str, admin_slug: Option<&str>) -> Value {
+    let admin_api = format!("{}/api/", crate::ADMIN_INTERNAL_MOUNT);
+    let notes = annotations();
+    let mut schemas = Map::new();
+    let mut paths = Map::new();
+    // Handler names repeat across modules; operation ids must not
+    let mut seen_ids: std::collections::HashMap<String, usize> = Default::default();
+
+    for ep in endpoints {
+        // CORS preflights aren't operations callers make
+        if ep.method == "options" {
+            continue;
+        }
+        let is_admin = ep.path.starts_with(&admin_api);
+        let public_path = match (is_admin, admin_slug) {
+            (false, _) if ep.path.starts_with("/api/") && !INTERNAL.contains(&ep.path.as_str()) => {
+                ep.path.clone()
+            }
+            (true, Some(slug)) => {
+                ep.path
+                    .replacen(crate::ADMIN_INTERNAL_MOUNT, &format!("/{}", slug), 1)
+            }
+            _ => continue,
+        };
+        let note = notes
+            .iter()
+            .find(|n| n.method == ep.method && n.path == ep.path);
+        let (path, path_params) = openapi_path(&public_path);
+
+        let mut parameters: Vec<Value> = path_params
+            .iter()
+            .map(|p| json!({"name": p, "in": "path", "required": true, "schema": param_schema(p)}))
+            .collect();
+        parameters.extend(ep.query.iter().map(
+            |q| json!({"name": q, "in": "query", "required": false, "schema": param_schema(q)}),
+        ));
+
+        let response_schema = match note.and_then(|n| n.response) {
+            Some((name, schema)) => {
+                schemas.insert(name.to_string(), schema());
+                reference(name)
+            }
+            None => json!({"type": "object"}),
+        };
+        let mut responses = json!({
+            "200": {
+                "description": "OK",
+                "content": {"application/json": {"schema": response_schema}},
+            },
+        });
+        if is_admin {
+            responses["401"] = json!({"description": "Not signed in"});
+        } else {
+            responses["429"] = json!({"$ref": "#/components/responses/RateLimited"});
+        }
+
+        let uses = seen_ids.entry(ep.name.clone()).or_insert(0);
+        *uses += 1;
+        let operation_id = match *uses {
+            1 => ep.name.clone(),
+            n => format!("{}_{}", ep.name, n),
+        };
+
+        let mut op = json!({
+            "operationId": operation_id,
+            "summary": note.map(|n| n.summary.to_string()).unwrap_or_else(|| humanize(&ep.name)),
+            "tags": [tag_for(&ep.path, is_admin)],
+            "parameters": parameters,
+            "responses": responses,
+            "security": if is_admin {
+                json!([{"adminSession": []}])
+            } else {
+                json!([{}, {"apiKey": []}, {"bearerKey": []}])
+            },
+        });
+        if let Some(format) = &ep.format {
+            let body_schema = match note.and_then(|n| n.request) {
+                Some((name, schema)) => {
+                    schemas.insert(name.to_string(), schema());
+                    reference(name)
+                }
+                None => json!({"type": "object"}),
+            };
+            op["requestBody"] = json!({
+                "required": true,
+                "content": {format.as_str(): {"schema": body_schema}},
+            });
+        }
+
+        let item = paths
+            .entry(path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        item[ep.method.as_str()] = op;
+    }
+
+    // Page schemas point at their item schemas
+    if schemas.contains_key(PostPage::NAME) {
+        schemas.insert(PostSchema::NAME.into(), PostSchema::schema());
+    }
+    if schemas.contains_key(PortfolioPage::NAME) {
+        schemas.insert(PortfolioSchema::NAME.into(), PortfolioSchema::schema());
+    }
+    // The rate-limit response uses it even if no listed route does
+    schemas.insert(ResultSchema::NAME.into(), ResultSchema::schema());
+
+    let rate_header = |description: &str| json!({"description": description, "schema": integer()});
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Velocty API",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "servers": [{"url": server_url.trim_end_matches('/')}],
+        "paths": paths,
+        "components": {
+            "schemas": schemas,
+            "securitySchemes": {
+                "apiKey": {"type": "apiKey", "in": "header", "name": "X-API-Key"},
+                "bearerKey": {"type": "http", "scheme": "bearer"},
+                "adminSession": {"type": "apiKey", "in": "cookie", "name": "velocty_session"},
+            },
+            "responses": {
+                "RateLimited": {
+                    "description": "Over the caller's request budget",
+                    "headers": {
+                        "Retry-After": rate_header("Seconds until the budget has room"),
+                        "RateLimit-Limit": rate_header("Requests allowed per minute"),
+                        "RateLimit-Remaining": rate_header("Requests left in the window"),
+                        "RateLimit-Reset": rate_header("Seconds until the budget has room"),
+                    },
+                    "content": {"application/json": {"schema": reference(ResultSchema::NAME)}},
+                },
+            },
+        },
+    })
+}
+
+#[get("/openapi.json")]
+pub fn openapi_json(
+    rocket: &Rocket<Orbit>,
+    store: &State<Arc<dyn Store>>,
+    slug: &State<AdminSlug>,
+    admin: Option<AuthenticatedUser>,
+) -> Json<Value> {
+    let endpoints: Vec<Endpoint> = rocket.routes().map(Endpoint::from_route).collect();
+    let server = store.setting_get_or("site_url", "http://localhost:8000");
+    let admin_slug = admin.map(|_| slug.get());
+    Json(build(&endpoints, &server, admin_slug.as_deref()))
+}
+
+pub fn routes() -> Vec<rocket::Route> {
+    routes![openapi_json]
+}
@@ -1,7 +1,7 @@
 use rocket::form::Form;
 use rocket::http::{ContentType, CookieJar, Header, Status};
 use rocket::response::content::{RawHtml, RawXml};
-use rocket::response::{self, Responder, Response};
+use rocket::response::{self, Redirect, Responder, Response};
 use rocket::{Request, State};
 use serde_json::json;
 use std::collections::HashMap;
@@ -9,11 +9,18 @@ use std::io::Cursor;
 use std::path::Path;
 use std::sync::Arc;
 
+use crate::analytics::DoNotTrack;
+use crate::designs::preview::DesignPreview;
 use crate::image_proxy;
-use crate::models::settings::SettingsCache;
+use crate::models::post::Post;
+use crate::models::search;
+use crate::models::settings::{RenderContext, SettingsCache};
+use crate::models::user::User;
 use crate::render;
+use crate::render::page_cache;
 use crate::security::auth;
 use crate::security::auth::ClientIp;
+use crate::security::headers::CspNonce;
 use crate::seo;
 use crate::store::Store;
 
@@ -52,9 +59,18 @@ impl<'r> Responder<'r, 'static> for FileResponse {
 pub fn dynamic_route_index(
     store: &State<Arc<dyn Store>>,
     cache: &State<SettingsCache>,
+    client_ip: ClientIp,
+    dnt: DoNotTrack,
+    preview: DesignPreview,
     page: Option<i64>,
+    nonce: CspNonce,
 ) -> Option<RawHtml<String>> {
-    dispatch_root(&**store.inner(), cache, None, page)
+    let visitor = experiment_visitor(&**store.inner(), &client_ip, &dnt);
+    cached_page(cache, "/", page, preview.0, &visitor, &nonce, || {
+        render::with_design_preview(preview.0, || {
+            dispatch_root(&**store.inner(), cache, None, page, &visitor)
+        })
+    })
 }
 
 #[get("/<first>/<rest..>?<page>", rank = 90)]
@@ -63,15 +79,19 @@ pub fn dynamic_route_sub(
     cache: &State<SettingsCache>,
     first: &str,
     rest: std::path::PathBuf,
+    client_ip: ClientIp,
+    dnt: DoNotTrack,
+    preview: DesignPreview,
     page: Option<i64>,
+    nonce: CspNonce,
 ) -> Option<RawHtml<String>> {
-    let rest_str = rest.to_string_lossy();
-    dispatch_root(
-        &**store.inner(),
-        cache,
-        Some(&format!("{}/{}", first, rest_str)),
-        page,
-    )
+    let path = format!("{}/{}", first, rest.to_string_lossy());
+    let visitor = experiment_visitor(&**store.inner(), &client_ip, &dnt);
+    cached_page(cache, &path, page, preview.0, &visitor, &nonce, || {
+        render::with_design_preview(preview.0, || {
+            dispatch_root(&**store.inner(), cache, Some(&path), page, &visitor)
+        })
+    })
 }
 
 #[get("/<first>?<page>", rank = 91)]
@@ -79,18 +99,59 @@ pub fn dynamic_route_root(
     store: &State<Arc<dyn Store>>,
     cache: &State<SettingsCache>,
     first: &str,
+    client_ip: ClientIp,
+    dnt: DoNotTrack,
+    preview: DesignPreview,
+    page: Option<i64>,
+    nonce: CspNonce,
+) -> Option<RawHtml<String>> {
+    let visitor = experiment_visitor(&**store.inner(), &client_ip, &dnt);
+    cached_page(cache, first, page, preview.0, &visitor, &nonce, || {
+        render::with_design_preview(preview.0, || {
+            dispatch_root(&**store.inner(), cache, Some(first), page, &visitor)
+        })
+    })
+}
+
+/// Serve a public page from the page cache when it may be shared between
+/// visitors, rendering and storing it on a miss. A page carrying a CSP
+/// nonce is unique to its request, so it's always rendered.
+fn cached_page(
+    settings: &SettingsCache,
+    path: &str,
     page: Option<i64>,
+    preview: Option<i64>,
+    visitor: &str,
+    nonce: &CspNonce,
+    build: impl FnOnce() -> Option<RawHtml<String>>,
 ) -> Option<RawHtml<String>> {
-    dispatch_root(&**store.inner(), cache, Some(first), page)
+    let build = || render::with_csp_nonce(nonce.0.as_deref(), build);
+    if nonce.0.is_some() || !page_cache::cacheable(settings, preview, visitor) {
+        return build();
+    }
+    let config = page_cache::Config::from_settings(settings);
+    let key = page_cache::key(settings, path, page);
+    page_cache::get_or_render(&config, &key, || build().map(|h| h.0)).map(RawHtml)
+}
+
+/// The visitor hash that picks A/B experiment variants; empty for visitors
+/// who opted out of tracking, who always see the original.
+fn experiment_visitor(store: &dyn Store, client_ip: &ClientIp, dnt: &DoNotTrack) -> String {
+    if dnt.0 {
+        return String::new();
+    }
+    crate::analytics::visitor_hash(store, &client_ip.0)
 }
 
 /// Core dispatcher: resolves the full path against cached slugs and enabled flags.
 /// `path` is None for "/", or Some("journal"), Some("journal/my-post"), Some("category/foo"), etc.
+/// `visitor` is the hashed client IP, which picks A/B experiment variants.
 fn dispatch_root(
     store: &dyn Store,
     cache: &SettingsCache,
     path: Option<&str>,
     page: Option<i64>,
+    visitor: &str,
 ) -> Option<RawHtml<String>> {
     let blog_slug = cache.get_or("blog_slug", "journal");
     let portfolio_slug = cache.get_or("portfolio_slug", "portfolio");
@@ -114,7 +175,10 @@ fn dispatch_root(
         "feed",
         "download",
         "sitemap.xml",
+        "sitemaps",
         "robots.txt",
+        "llms.txt",
+        "indexnow-key.txt",
         "super",
         ".well-known",
         "favicon.ico",
@@ -140,23 +204,83 @@ fn dispatch_root(
         return None;
     }
 
+    let ctx = cache.render_context(store);
+
+    // The homepage carries the hero experiment, if one is configured
+    let home = path.is_empty() && page.unwrap_or(1) <= 1;
+
+    if home && cache.get_or("homepage_mode", "default") == "sections" {
+        return Some(do_homepage_sections(store, &ctx, visitor));
+    }
+
     // Try blog: strip blog_slug prefix
     if journal_enabled {
         if let Some(rest) = strip_slug_prefix(path, &blog_slug) {
-            return dispatch_blog(store, rest, page);
+            return dispatch_blog(store, &ctx, rest, page, visitor, home);
         }
     }
 
     // Try portfolio: strip portfolio_slug prefix
     if portfolio_enabled {
         if let Some(rest) = strip_slug_prefix(path, &portfolio_slug) {
-            return dispatch_portfolio(store, rest, page);
+            return dispatch_portfolio(store, &ctx, rest, page, visitor, home);
         }
     }
 
     None
 }
 
+/// The homepage built from the sections configured in the customizer
+fn do_homepage_sections(store: &dyn Store, ctx: &RenderContext, visitor: &str) -> RawHtml<String> {
+    let mut settings = ctx.settings.clone();
+    crate::analytics::apply_hero_experiment(store, &mut settings, visitor);
+    let sections = crate::designs::homepage::parse_sections(
+        settings
+            .get("homepage_sections")
+            .map(|s| s.as_str())
+            .unwrap_or(""),
+    );
+    // Fetch only what the enabled sections can show
+    let wanted = |kind: &str| {
+        sections
+            .iter()
+            .filter(|s| s.enabled && s.kind == kind)
+            .map(|s| s.item_count())
+            .max()
+            .unwrap_or(0)
+    };
+    let portfolio_count = wanted("featured_portfolio");
+    let posts_count = wanted("latest_posts");
+    let featured_portfolio = if portfolio_count > 0 {
+        store.portfolio_list(Some("published"), portfolio_count, 0)
+    } else {
+        vec![]
+    };
+    let latest_posts = if posts_count > 0 {
+        store.post_list(Some("published"), posts_count, 0)
+    } else {
+        vec![]
+    };
+
+    let context = json!({
+        "settings": settings,
+        "nav_categories": ctx.nav_categories,
+        "nav_journal_categories": ctx.nav_journal_categories,
+        "home_sections": sections,
+        "featured_portfolio": featured_portfolio,
+        "latest_posts": latest_posts,
+        "page_type": "homepage_sections",
+        "seo": seo::build_meta(store, None, None, "/"),
+    });
+
+    RawHtml(render::render_page_with(
+        store,
+        ctx.design.as_ref(),
+        "homepage_sections",
+        &context,
+    ))
+}
+
 /// If slug is empty, the feature claims "/" and all sub-paths → returns Some("") or Some(rest).
 /// If slug is non-empty, checks if path starts with that slug → returns Some(rest-after-slug) or None.
 fn strip_slug_prefix<'a>(path: &'a str, slug: &str) -> Option<&'a str> {
@@ -175,49 +299,63 @@ fn strip_slug_prefix<'a>(path: &'a str, slug: &str) -> Option<&'a str> {
     None
 }
 
-fn dispatch_blog(store: &dyn Store, rest: &str, page: Option<i64>) -> Option<RawHtml<String>> {
+fn dispatch_blog(
+    store: &dyn Store,
+    ctx: &RenderContext,
+    rest: &str,
+    page: Option<i64>,
+    visitor: &str,
+    home: bool,
+) -> Option<RawHtml<String>> {
     if rest.is_empty() {
-        return Some(do_blog_list(store, page));
+        return Some(do_blog_list(store, ctx, page, visitor, home));
     }
     let parts: Vec<&str> = rest.splitn(2, '/').collect();
     match parts.as_slice() {
-        ["category", slug] => do_blog_by_category(store, slug, page),
-        ["tag", slug] => do_blog_by_tag(store, slug, page),
-        [slug] => do_blog_single(store, slug),
+        ["category", slug] => do_blog_by_category(store, ctx, slug, page),
+        ["tag", slug] => do_blog_by_tag(store, ctx, slug, page),
+        [slug] => do_blog_single(store, ctx, slug, visitor),
         _ => None,
     }
 }
 
-fn dispatch_portfolio(store: &dyn Store, rest: &str, page: Option<i64>) -> Option<RawHtml<String>> {
+fn dispatch_portfolio(
+    store: &dyn Store,
+    ctx: &RenderContext,
+    rest: &str,
+    page: Option<i64>,
+    visitor: &str,
+    home: bool,
+) -> Option<RawHtml<String>> {
     if rest.is_empty() {
-        return Some(do_portfolio_grid(store, page));
+        return Some(do_portfolio_grid(store, ctx, page, visitor, home));
     }
     let parts: Vec<&str> = rest.splitn(2, '/').collect();
     match parts.as_slice() {
-        ["category", slug] => do_portfolio_by_category(store, slug, page),
-        ["tag", slug] => do_portfolio_by_tag(store, slug, page),
-        [slug] => do_portfolio_single(store, slug),
+        ["category", slug] => do_portfolio_by_category(store, ctx, slug, page),
+        ["tag", slug] => do_portfolio_by_tag(store, ctx, slug, page),
+        [slug] => do_portfolio_single(store, ctx, slug, visitor),
         _ => None,
     }
 }
 
-/// Portfolio nav categories for the sidebar — called by non-portfolio routes
-/// so the sidebar always shows the portfolio category tree.
-fn nav_categories(store: &dyn Store) -> Vec<crate::models::category::Category> {
-    store.category_list_nav_visible(Some("portfolio"))
-}
-
-/// Journal nav categories for the sidebar.
-fn nav_journal_categories(store: &dyn Store) -> Vec<crate::models::category::Category> {
-    store.category_list_nav_visible(Some("post"))
-}
-
 // ── Archives ──────────────────────────────────────────
 
 #[get("/archives")]
-pub fn archives(store: &State<Arc<dyn Store>>) -> RawHtml<String> {
-    let s: &dyn Store = &**store.inner();
-    let settings = s.setting_all();
+pub fn archives(
+    store: &State<Arc<dyn Store>>,
+    cache: &State<SettingsCache>,
+    preview: DesignPreview,
+    nonce: CspNonce,
+) -> Option<RawHtml<String>> {
+    cached_page(cache, "archives", None, preview.0, "", &nonce, || {
+        let s: &dyn Store = &**store.inner();
+        Some(render_archives(s, &cache.render_context(s), preview.0))
+    })
+}
+
+fn render_archives(s: &dyn Store, ctx: &RenderContext, preview: Option<i64>) -> RawHtml<String> {
+    let settings = &ctx.settings;
 
     let archive_entries: Vec<serde_json::Value> = s
         .post_archives()
@@ -227,28 +365,54 @@ pub fn archives(store: &State<Arc<dyn Store>>) -> RawHtml<String> {
 
     let context = json!({
         "settings": settings,
-        "nav_categories": nav_categories(s),
-        "nav_journal_categories": nav_journal_categories(s),
+        "nav_categories": ctx.nav_categories,
+        "nav_journal_categories": ctx.nav_journal_categories,
         "archives": archive_entries,
         "page_type": "archives",
         "seo": seo::build_meta(s, Some("Archives"), None, "/archives"),
     });
 
-    RawHtml(render::render_page(s, "archives", &context))
+    RawHtml(render::with_design_preview(preview, || {
+        render::render_page_with(s, ctx.design.as_ref(), "archives", &context)
+    }))
 }
 
 #[get("/archives/<year>/<month>?<page>")]
 pub fn archives_month(
     store: &State<Arc<dyn Store>>,
+    cache: &State<SettingsCache>,
+    year: &str,
+    month: &str,
+    page: Option<i64>,
+    preview: DesignPreview,
+    nonce: CspNonce,
+) -> Option<RawHtml<String>> {
+    let path = format!("archives/{}/{}", year, month);
+    cached_page(cache, &path, page, preview.0, "", &nonce, || {
+        let s: &dyn Store = &**store.inner();
+        Some(render_archives_month(
+            s,
+            &cache.render_context(s),
+            year,
+            month,
+            page,
+            preview.0,
+        ))
+    })
+}
+
+fn render_archives_month(
+    s: &dyn Store,
+    ctx: &RenderContext,
     year: &str,
     month: &str,
     page: Option<i64>,
+    preview: Option<i64>,
 ) -> RawHtml<String> {
-    let s: &dyn Store = &**store.inner();
     let per_page = s.setting_get_i64("blog_posts_per_page").max(1);
     let current_page = page.unwrap_or(1).max(1);
     let offset = (current_page - 1) * per_page;
-    let settings = s.setting_all();
+    let settings = &ctx.settings;
 
     let posts = s.post_by_year_month(year, month, per_page, offset);
     let total = s.post_count_by_year_month(year, month);
@@ -257,8 +421,8 @@ pub fn archives_month(
     let title = format!("Archives: {}/{}", year, month);
     let context = json!({
         "settings": settings,
-        "nav_categories": nav_categories(s),
-        "nav_journal_categories": nav_journal_categories(s),
+        "nav_categories": ctx.nav_categories,
+        "nav_journal_categories": ctx.nav_journal_categories,
         "posts": posts,
         "current_page": current_page,
         "total_pages": total_pages,
@@ -268,34 +432,197 @@ pub fn archives_month(
         "seo": seo::build_meta(s, Some(&title), None, &format!("/archives/{}/{}", year, month)),
     });
 
-    RawHtml(render::render_page(s, "blog_list", &context))
+    RawHtml(render::with_design_preview(preview, || {
+        render::render_page_with(s, ctx.design.as_ref(), "blog_list", &context)
+    }))
+}
+
+// ── Author pages ───────────────────────────────────────
+
+/// The user a post's byline credits: its author, or the first admin for
+/// posts without one
+fn post_author<'a>(users: &'a [User], post: &Post) -> Option<&'a User> {
+    post.author_id
+        .and_then(|id| users.iter().find(|u| u.id == id))
+        .or_else(|| users.iter().find(|u| u.role == "admin"))
+}
+
+fn insert_byline(obj: &mut serde_json::Map<String, serde_json::Value>, author: Option<&User>) {
+    let (name, url) = match author {
+        Some(u) => (
+            u.display_name.clone(),
+            format!("/author/{}", u.public_slug()),
+        ),
+        None => (String::new(), String::new()),
+    };
+    obj.insert("author_name".to_string(), json!(name));
+    obj.insert("author_url".to_string(), json!(url));
+}
+
+#[get("/author/<slug>?<page>")]
+pub fn author_page(
+    store: &State<Arc<dyn Store>>,
+    cache: &State<SettingsCache>,
+    slug: &str,
+    page: Option<i64>,
+    preview: DesignPreview,
+    nonce: CspNonce,
+) -> Option<RawHtml<String>> {
+    let path = format!("author/{}", slug);
+    cached_page(cache, &path, page, preview.0, "", &nonce, || {
+        let s: &dyn Store = &**store.inner();
+        render_author_page(s, &cache.render_context(s), slug, page, preview.0)
+    })
+}
+
+fn render_author_page(
+    s: &dyn Store,
+    ctx: &RenderContext,
+    slug: &str,
+    page: Option<i64>,
+    preview: Option<i64>,
+) -> Option<RawHtml<String>> {
+    let users = s.user_list_all();
+    let author = users.iter().find(|u| {
+        u.public_slug() == slug && u.is_active() && !crate::security::members::is_member(s, u)
+    })?;
+    // Posts without an author are credited to the first admin
+    let unattributed = users.iter().find(|u| u.role == "admin").map(|u| u.id) == Some(author.id);
+    let total = s.post_count_by_author(author.id, unattributed);
+    if total == 0 {
+        return None;
+    }
+
+    let per_page = s.setting_get_i64("blog_posts_per_page").max(1);
+    let current_page = page.unwrap_or(1).max(1);
+    let offset = (current_page - 1) * per_page;
+    let total_pages = (total as f64 / per_page as f64).ceil() as i64;
+    let posts_json: Vec<serde_json::Value> = s
+        .post_by_author(author.id, unattributed, per_page, offset)
+        .iter()
+        .map(|p| {
+            let mut pj = serde_json::to_value(p).unwrap_or_default();
+            if let Some(obj) = pj.as_object_mut() {
+                insert_byline(obj, Some(author));
+                obj.insert(
+                    "categories".to_string(),
+                    json!(s.category_for_content(p.id, "post")),
+                );
+                obj.insert(
+                    "comment_count".to_string(),
+                    json!(s.comment_for_post(p.id, "post").len()),
+                );
+            }
+            pj
+        })
+        .collect();
+
+    let path = format!("/author/{}", author.public_slug());
+    let description = (!author.bio.is_empty()).then_some(author.bio.as_str());
+    let context = json!({
+        "settings": ctx.settings,
+        "nav_categories": ctx.nav_categories,
+        "nav_journal_categories": ctx.nav_journal_categories,
+        "posts": posts_json,
+        "author_profile": {
+            "display_name": author.display_name,
+            "avatar": author.avatar,
+            "bio": author.bio,
+            "website": author.website,
+            "social_links": author.social_link_list(),
+            "url": path,
+        },
+        "current_page": current_page,
+        "total_pages": total_pages,
+        "page_type": "blog_list",
+        "seo": seo::build_meta(s, Some(&author.display_name), description, &path),
+    });
+
+    Some(RawHtml(render::with_design_preview(preview, || {
+        render::render_page_with(s, ctx.design.as_ref(), "blog_list", &context)
+    })))
 }
 
-// ── RSS Feed ───────────────────────────────────────────
+// ── RSS / Atom Feeds ───────────────────────────────────
 
 #[get("/feed")]
 pub fn rss_feed(store: &State<Arc<dyn Store>>) -> RawXml<String> {
-    RawXml(crate::rss::generate_feed(&**store.inner()))
+    RawXml(crate::rss::cached_feed(&**store.inner(), "rss").unwrap_or_default())
+}
+
+/// `/<blog_slug>/category/<slug>/feed` and `/<blog_slug>/tag/<slug>/feed`
+#[get("/<section>/<kind>/<slug>/feed", rank = 88)]
+pub fn archive_feed(
+    store: &State<Arc<dyn Store>>,
+    cache: &State<SettingsCache>,
+    section: &str,
+    kind: &str,
+    slug: &str,
+) -> Option<RawXml<String>> {
+    let blog_slug = cache.get_or("blog_slug", "journal");
+    if section != blog_slug || cache.get_or("journal_enabled", "true") == "false" {
+        return None;
+    }
+    if kind != "category" && kind != "tag" {
+        return None;
+    }
+    crate::rss::cached_feed(&**store.inner(), &format!("{}/{}", kind, slug)).map(RawXml)
+}
+
+#[get("/feed/podcast")]
+pub fn podcast_feed(store: &State<Arc<dyn Store>>) -> Option<RawXml<String>> {
+    crate::rss::cached_feed(&**store.inner(), "podcast").map(RawXml)
+}
+
+#[get("/feed/atom")]
+pub fn atom_feed(store: &State<Arc<dyn Store>>) -> (ContentType, String) {
+    (
+        ContentType::new("application", "atom+xml"),
+        crate::rss::cached_feed(&**store.inner(), "atom").unwrap_or_default(),
+    )
 }
 
 // ── Sitemap ────────────────────────────────────────────
 
 #[get("/sitemap.xml")]
 pub fn sitemap(store: &State<Arc<dyn Store>>) -> Option<RawXml<String>> {
-    seo::sitemap::generate_sitemap(&**store.inner()).map(RawXml)
+    seo::sitemap::cached_file(&**store.inner(), "index").map(RawXml)
 }
 
-// ── Robots.txt ─────────────────────────────────────────
+/// The files listed in the sitemap index, e.g. `/sitemaps/posts-1.xml`
+#[get("/sitemaps/<file>")]
+pub fn sitemap_file(store: &State<Arc<dyn Store>>, file: &str) -> Option<RawXml<String>> {
+    let name = file.strip_suffix(".xml")?;
+    if name == "index" {
+        return None;
+    }
+    seo::sitemap::cached_file(&**store.inner(), name).map(RawXml)
+}
+
+// ── Robots.txt / llms.txt ─────────────────────────────
 
 #[get("/robots.txt")]
 pub fn robots(store: &State<Arc<dyn Store>>) -> String {
-    seo::sitemap::generate_robots(&**store.inner())
+    seo::robots::generate_robots(&**store.inner())
+}
+
+#[get("/llms.txt")]
+pub fn llms_txt(store: &State<Arc<dyn Store>>) -> Option<String> {
+    seo::robots::generate_llms(&**store.inner())
+}
+
+// ── IndexNow key file ──────────────────────────────────
+
+#[get("/indexnow-key.txt")]
+pub fn indexnow_key_file(store: &State<Arc<dyn Store>>) -> Option<String> {
+    let s: &dyn Store = &**store.inner();
+    seo::ping::indexnow_enabled(s).then(|| seo::ping::indexnow_key(s))
 }
 
 // ── Privacy Policy ─────────────────────────────────────
 
 #[get("/privacy")]
-pub fn privacy_page(store: &State<Arc<dyn Store>>) -> Option<RawHtml<String>> {
+pub fn privacy_page(store: &State<Arc<dyn Store>>, nonce: CspNonce) -> Option<RawHtml<String>> {
     let s: &dyn Store = &**store.inner();
     let settings = s.setting_all();
     if settings.get("privacy_policy_enabled").map(|v| v.as_str()) != Some("true") {
@@ -305,14 +632,16 @@ pub fn privacy_page(store: &State<Arc<dyn Store>>) -> Option<RawHtml<String>> {
         .get("privacy_policy_content")
         .cloned()
         .unwrap_or_default();
-    let page_html = render::render_legal_page(s, &settings, "Privacy Policy", &html_body);
+    let page_html = render::with_csp_nonce(nonce.0.as_deref(), || {
+        render::render_legal_page(s, &settings, "Privacy Policy", &html_body)
+    });
     Some(RawHtml(page_html))
 }
 
 // ── Terms of Use ──────────────────────────────────────
 
 #[get("/terms")]
-pub fn terms_page(store: &State<Arc<dyn Store>>) -> Option<RawHtml<String>> {
+pub fn terms_page(store: &State<Arc<dyn Store>>, nonce: CspNonce) -> Option<RawHtml<String>> {
     let s: &dyn Store = &**store.inner();
     let settings = s.setting_all();
     if settings.get("terms_of_use_enabled").map(|v| v.as_str()) != Some("true") {
@@ -322,20 +651,24 @@ pub fn terms_page(store: &State<Arc<dyn Store>>) -> Option<RawHtml<String>> {
         .get("terms_of_use_content")
         .cloned()
         .unwrap_or_default();
-    let page_html = render::render_legal_page(s, &settings, "Terms of Use", &html_body);
+    let page_html = render::with_csp_nonce(nonce.0.as_deref(), || {
+        render::render_legal_page(s, &settings, "Terms of Use", &html_body)
+    });
     Some(RawHtml(page_html))
 }
 
 // ── Contact Page ──────────────────────────────────────
 
 #[get("/contact")]
-pub fn contact_page(store: &State<Arc<dyn Store>>) -> Option<RawHtml<String>> {
+pub fn contact_page(store: &State<Arc<dyn Store>>, nonce: CspNonce) -> Option<RawHtml<String>> {
     let s: &dyn Store = &**store.inner();
     let settings = s.setting_all();
     if settings.get("contact_page_enabled").map(|v| v.as_str()) != Some("true") {
         return None;
     }
-    let page_html = render::render_contact_page(s, &settings, None);
+    let page_html = render::with_csp_nonce(nonce.0.as_deref(), || {
+        render::render_contact_page(s, &settings, None)
+    });
     Some(RawHtml(page_html))
 }
 
@@ -344,121 +677,131 @@ pub fn contact_submit(
     store: &State<Arc<dyn Store>>,
     client_ip: ClientIp,
     form: Form<HashMap<String, String>>,
+    nonce: CspNonce,
 ) -> Option<RawHtml<String>> {
-    let s: &dyn Store = &**store.inner();
-    let settings = s.setting_all();
-    if settings.get("contact_page_enabled").map(|v| v.as_str()) != Some("true") {
-        return None;
-    }
-    if settings.get("contact_form_enabled").map(|v| v.as_str()) != Some("true") {
-        return None;
-    }
+    render::with_csp_nonce(nonce.0.as_deref(), || {
+        let s: &dyn Store = &**store.inner();
+        let settings = s.setting_all();
+        if settings.get("contact_page_enabled").map(|v| v.as_str()) != Some("true") {
+            return None;
+        }
+        if settings.get("contact_form_enabled").map(|v| v.as_str()) != Some("true") {
+            return None;
+        }
 
-    // Rate limit: max 5 submissions per IP per 15 minutes
-    {
-        use std::sync::Mutex;
-        use std::time::Instant;
-        static CONTACT_RATE: std::sync::LazyLock<Mutex<HashMap<String, (u32, Instant)>>> =
-            std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
-        let mut map = CONTACT_RATE.lock().unwrap_or_else(|e| e.into_inner());
-        let now = Instant::now();
-        let entry = map.entry(client_ip.0.clone()).or_insert((0, now));
-        if now.duration_since(entry.1).as_secs() >= 900 {
-            *entry = (1, now);
-        } else {
-            entry.0 += 1;
-            if entry.0 > 5 {
-                let html = render::render_contact_page(
-                    s,
-                    &settings,
-                    Some(("error", "Too many submissions. Please try again later.")),
-                );
-                return Some(RawHtml(html));
+        // Rate limit: max 5 submissions per IP per 15 minutes
+        {
+            use std::sync::Mutex;
+            use std::time::Instant;
+            static CONTACT_RATE: std::sync::LazyLock<Mutex<HashMap<String, (u32, Instant)>>> =
+                std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+            let mut map = CONTACT_RATE.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let entry = map.entry(client_ip.0.clone()).or_insert((0, now));
+            if now.duration_since(entry.1).as_secs() >= 900 {
+                *entry = (1, now);
+            } else {
+                entry.0 += 1;
+                if entry.0 > 5 {
+                    let html = render::render_contact_page(
+                        s,
+                        &settings,
+                        Some(("error", "Too many submissions. Please try again later.")),
+                    );
+                    return Some(RawHtml(html));
+                }
             }
         }
-    }
-
-    let data = form.into_inner();
-    let name = data.get("name").map(|s| s.trim()).unwrap_or("");
-    let email = data.get("email").map(|s| s.trim()).unwrap_or("");
-    let message = data.get("message").map(|s| s.trim()).unwrap_or("");
-    let honey = data.get("_honey").map(|s| s.trim()).unwrap_or("");
-
-    // Honeypot check
-    if !honey.is_empty() {
-        let html = render::render_contact_page(
-            s,
-            &settings,
-            Some(("success", "Message sent! Thank you.")),
-        );
-        return Some(RawHtml(html));
-    }
-
-    // Validation
-    if name.is_empty() || email.is_empty() || message.is_empty() {
-        let html = render::render_contact_page(
-            s,
-            &settings,
-            Some(("error", "Please fill in all required fields.")),
-        );
-        return Some(RawHtml(html));
-    }
 
-    // Basic email validation
-    if !email.contains('@') || !email.contains('.') {
-        let html = render::render_contact_page(
-            s,
-            &settings,
-            Some(("error", "Please enter a valid email address.")),
-        );
-        return Some(RawHtml(html));
-    }
+        let data = form.into_inner();
+        let name = data.get("name").map(|s| s.trim()).unwrap_or("");
+        let email = data.get("email").map(|s| s.trim()).unwrap_or("");
+        let message = data.get("message").map(|s| s.trim()).unwrap_or("");
+        let honey = data.get("_honey").map(|s| s.trim()).unwrap_or("");
 
-    // Send email to admin
-    let admin_email = settings.get("admin_email").cloned().unwrap_or_default();
-    if admin_email.is_empty() {
-        let html = render::render_contact_page(
-            s,
-            &settings,
-            Some((
-                "error",
-                "Contact form is not configured. Please try again later.",
-            )),
-        );
-        return Some(RawHtml(html));
-    }
+        // Honeypot check
+        if !honey.is_empty() {
+            let html = render::render_contact_page(
+                s,
+                &settings,
+                Some(("success", "Message sent! Thank you.")),
+            );
+            return Some(RawHtml(html));
+        }
 
-    let site_name = settings
-        .get("site_name")
-        .cloned()
-        .unwrap_or_else(|| "Velocty".to_string());
-    let subject = format!("[{}] Contact from {}", site_name, name);
-    let body = format!(
-        "New contact form submission:\n\nName: {}\nEmail: {}\n\nMessage:\n{}",
-        name, email, message
-    );
+        // Validation
+        if name.is_empty() || email.is_empty() || message.is_empty() {
+            let html = render::render_contact_page(
+                s,
+                &settings,
+                Some(("error", "Please fill in all required fields.")),
+            );
+            return Some(RawHtml(html));
+        }
 
-    let from = crate::email::get_from_or_admin(&settings);
-    match crate::email::send_via_provider(&settings, &from, &admin_email, &subject, &body) {
-        Ok(()) => {
-            log::info!("[contact] Form submitted by {} <{}>", name, email);
+        // Basic email validation
+        if !email.contains('@') || !email.contains('.') {
             let html = render::render_contact_page(
                 s,
                 &settings,
-                Some(("success", "Message sent! Thank you for getting in touch.")),
+                Some(("error", "Please enter a valid email address.")),
             );
-            Some(RawHtml(html))
+            return Some(RawHtml(html));
         }
-        Err(e) => {
-            log::error!("[contact] Failed to send email: {}", e);
+
+        // Send email to admin
+        let admin_email = settings.get("admin_email").cloned().unwrap_or_default();
+        if admin_email.is_empty() {
             let html = render::render_contact_page(
                 s,
                 &settings,
-                Some(("error", "Failed to send message. Please try again later.")),
+                Some((
+                    "error",
+                    "Contact form is not configured. Please try again later.",
+                )),
             );
-            Some(RawHtml(html))
+            return Some(RawHtml(html));
         }
-    }
+
+        let site_name = settings
+            .get("site_name")
+            .cloned()
+            .unwrap_or_else(|| "Velocty".to_string());
+        let subject = format!("[{}] Contact from {}", site_name, name);
+        let body = format!(
+            "New contact form submission:\n\nName: {}\nEmail: {}\n\nMessage:\n{}",
+            name, email, message
+        );
+
+        let from = crate::email::get_from_or_admin(&settings);
+        match crate::email::send_via_configured_provider(
+            s,
+            "contact",
+            &from,
+            &admin_email,
+            &subject,
+            &body,
+        ) {
+            Ok(()) => {
+                log::info!("[contact] Form submitted by {} <{}>", name, email);
+                let html = render::render_contact_page(
+                    s,
+                    &settings,
+                    Some(("success", "Message sent! Thank you for getting in touch.")),
+                );
+                Some(RawHtml(html))
+            }
+            Err(e) => {
+                log::error!("[contact] Failed to send email: {}", e);
+                let html = render::render_contact_page(
+                    s,
+                    &settings,
+                    Some(("error", "Failed to send message. Please try again later.")),
+                );
+                Some(RawHtml(html))
+            }
+        }
+    })
 }
 
 // ── Image proxy: /img/<token> ─────────────────────────
@@ -490,7 +833,7 @@ pub fn image_proxy_route(
     let path = image_proxy::decode_token_with_fallback(&secret, &old_secret, &old_expires, token)
         .ok_or(Status::NotFound)?;
 
-    serve_file_from_path(&path, "public, max-age=31536000, immutable")
+    serve_upload(s, &path, "public, max-age=31536000, immutable")
 }
 
 /// Serve /uploads/ files only for authenticated admin users.
@@ -516,7 +859,26 @@ pub fn serve_uploads(
     }
 
     let upload_path = format!("/uploads/{}", path.display());
-    serve_file_from_path(&upload_path, "private, max-age=3600")
+    serve_upload(s, &upload_path, "private, max-age=3600")
+}
+
+/// `serve_file_from_path`, falling back to the original of an upload still
+/// waiting in the optimization queue. That stand-in is never cached, so the
+/// optimized file replaces it as soon as it's ready.
+fn serve_upload(
+    store: &dyn Store,
+    path: &str,
+    cache_control: &str,
+) -> Result<FileResponse, Status> {
+    match serve_file_from_path(path, cache_control) {
+        Err(status) if status == Status::NotFound => {
+            let rel = path.strip_prefix("/uploads/").ok_or(Status::NotFound)?;
+            let source =
+                crate::images::queue::pending_source(store, rel).ok_or(Status::NotFound)?;
+            file_response(&source, "no-cache")
+        }
+        other => other,
+    }
 }
 
 /// Shared helper: resolve an /uploads/... path to a file and serve it.
@@ -539,7 +901,11 @@ fn serve_file_from_path(path: &str, cache_control: &str) -> Result<FileResponse,
     if !canonical.starts_with(&uploads_dir) {
         return Err(Status::Forbidden);
     }
+    file_response(&canonical, cache_control)
+}
 
+/// Read an already-validated file into a response
+fn file_response(canonical: &Path, cache_control: &str) -> Result<FileResponse, Status> {
     let bytes = std::fs::read(&canonical).map_err(|_| Status::InternalServerError)?;
     let mime = image_proxy::mime_from_extension(canonical.to_str().unwrap_or(""));
 
@@ -574,31 +940,106 @@ fn serve_file_from_path(path: &str, cache_control: &str) -> Result<FileResponse,
 
 // ── Search ────────────────────────────────────────────
 
-#[get("/search?<q>")]
-pub fn search_page(store: &State<Arc<dyn Store>>, q: Option<String>) -> RawHtml<String> {
+/// GET /search?q=&kind=&page= — ranked results, optionally of one type
+/// ("post" or "portfolio"), a page at a time
+#[get("/search?<q>&<kind>&<page>")]
+pub fn search_page(
+    store: &State<Arc<dyn Store>>,
+    cache: &State<SettingsCache>,
+    client_ip: ClientIp,
+    dnt: DoNotTrack,
+    preview: DesignPreview,
+    q: Option<String>,
+    kind: Option<String>,
+    page: Option<usize>,
+    nonce: CspNonce,
+) -> RawHtml<String> {
     let s: &dyn Store = &**store.inner();
-    let settings = s.setting_all();
+    let ctx = cache.render_context(s);
+    let settings = &ctx.settings;
     if settings.get("design_site_search").map(|v| v.as_str()) != Some("true") {
         return RawHtml(String::new());
     }
     let query = q.as_deref().unwrap_or("").trim().to_string();
-    let results = if query.is_empty() {
+    let kind = kind.filter(|k| k == "post" || k == "portfolio");
+    let page = page.unwrap_or(1);
+    // Only types the site shows
+    let shown = |item_type: &str| match item_type {
+        "post" => settings.get("journal_enabled").map(|v| v.as_str()) != Some("false"),
+        "portfolio" => settings.get("portfolio_enabled").map(|v| v.as_str()) == Some("true"),
+        _ => false,
+    };
+    let results: Vec<_> = if query.is_empty() {
         vec![]
     } else {
-        s.search_query(&query, 50)
+        crate::ai::embeddings::search_with_fallback(s, &query, search::MAX_RESULTS)
+            .into_iter()
+            .filter(|r| shown(&r.item_type))
+            .collect()
+    };
+    // Filtering or paging through a search isn't a new search
+    let search_id = if query.is_empty() || dnt.0 || kind.is_some() || page > 1 {
+        None
+    } else {
+        crate::analytics::record_search(s, &query, results.len(), &client_ip.0)
     };
+    let (results, paging) = search::paginate(results, kind.as_deref(), page);
 
-    let nav_cats = s.category_list_nav_visible(Some("portfolio"));
-    let nav_journal_cats = s.category_list_nav_visible(Some("post"));
     let context = json!({
         "settings": settings,
-        "nav_categories": nav_cats,
-        "nav_journal_categories": nav_journal_cats,
+        "nav_categories": ctx.nav_categories,
+        "nav_journal_categories": ctx.nav_journal_categories,
         "page_type": "search",
         "search_query": query,
+        "search_kind": kind,
         "search_results": results,
+        "search_paging": paging,
+        "search_id": search_id,
     });
-    RawHtml(render::render_page(s, "search", &context))
+    RawHtml(render::with_csp_nonce(nonce.0.as_deref(), || {
+        render::with_design_preview(preview.0, || {
+            render::render_page_with(s, ctx.design.as_ref(), "search", &context)
+        })
+    }))
+}
+
+/// Follow an external link rewritten by outbound click tracking, counting
+/// the click. Only links signed by this site redirect, so `/out` can't be
+/// used as an open redirect.
+#[get("/out?<u>&<s>")]
+pub fn outbound_redirect(
+    store: &State<Arc<dyn Store>>,
+    client_ip: ClientIp,
+    dnt: DoNotTrack,
+    u: &str,
+    s: &str,
+) -> Result<Redirect, Status> {
+    let st: &dyn Store = &**store.inner();
+    if !crate::outbound::verify(st, u, s) {
+        return Err(Status::NotFound);
+    }
+    if st.setting_get_bool("analytics_outbound_tracking") && !dnt.0 {
+        let _ = st.outbound_click_record(u, &crate::analytics::visitor_hash(st, &client_ip.0));
+    }
+    Ok(Redirect::to(u.to_string()))
+}
+
+/// Scroll depth and time on page from the engagement script. Sent with
+/// `navigator.sendBeacon` as a text/plain JSON body.
+#[post("/beacon", data = "<body>")]
+pub fn engagement_beacon(
+    store: &State<Arc<dyn Store>>,
+    client_ip: ClientIp,
+    dnt: DoNotTrack,
+    body: String,
+) -> Status {
+    if dnt.0 {
+        return Status::NoContent;
+    }
+    if let Ok(ping) = serde_json::from_str(&body) {
+        crate::analytics::record_engagement(&**store.inner(), &ping, &client_ip.0);
+    }
+    Status::NoContent
 }
 
 pub fn root_routes() -> Vec<rocket::Route> {
@@ -609,21 +1050,36 @@ pub fn root_routes() -> Vec<rocket::Route> {
         dynamic_route_root,
         archives,
         archives_month,
+        author_page,
         rss_feed,
+        atom_feed,
+        podcast_feed,
+        archive_feed,
         sitemap,
+        sitemap_file,
         robots,
+        llms_txt,
+        indexnow_key_file,
         privacy_page,
         terms_page,
         contact_page,
         contact_submit,
         image_proxy_route,
         serve_uploads,
+        outbound_redirect,
+        engagement_beacon,
     ]
 }
 
 // ── Internal dispatch functions (called by catch-all) ────
 
-fn do_blog_list(store: &dyn Store, page: Option<i64>) -> RawHtml<String> {
+fn do_blog_list(
+    store: &dyn Store,
+    ctx: &RenderContext,
+    page: Option<i64>,
+    visitor: &str,
+    home: bool,
+) -> RawHtml<String> {
     let per_page = store.setting_get_i64("blog_posts_per_page").max(1);
     let current_page = page.unwrap_or(1).max(1);
     let offset = (current_page - 1) * per_page;
@@ -631,21 +1087,18 @@ fn do_blog_list(store: &dyn Store, page: Option<i64>) -> RawHtml<String> {
     let posts = store.post_list(Some("published"), per_page, offset);
     let total = store.post_count(Some("published"));
     let total_pages = (total as f64 / per_page as f64).ceil() as i64;
-    let settings = store.setting_all();
+    let mut settings = ctx.settings.clone();
+    if home {
+        crate::analytics::apply_hero_experiment(store, &mut settings, visitor);
+    }
 
-    // Inject author_name into each post from the first admin user
-    let author_name = store
-        .user_list_all()
-        .into_iter()
-        .find(|u| u.role == "admin")
-        .map(|u| u.display_name)
-        .unwrap_or_default();
-    let posts_json: Vec<serde_json::Value> = posts
+    let users = store.user_list_all();
+    let mut posts_json: Vec<serde_json::Value> = posts
         .iter()
         .map(|p| {
             let mut pj = serde_json::to_value(p).unwrap_or_default();
             if let Some(obj) = pj.as_object_mut() {
-                obj.insert("author_name".to_string(), json!(author_name.clone()));
+                insert_byline(obj, post_author(&users, p));
                 let cats = store.category_for_content(p.id, "post");
                 obj.insert("categories".to_string(), json!(cats));
                 let cc = store.comment_for_post(p.id, "post").len();
@@ -654,11 +1107,12 @@ fn do_blog_list(store: &dyn Store, page: Option<i64>) -> RawHtml<String> {
             pj
         })
         .collect();
+    crate::analytics::apply_title_experiments(store, &mut posts_json, visitor);
 
     let context = json!({
         "settings": settings,
-        "nav_categories": nav_categories(store),
-        "nav_journal_categories": nav_journal_categories(store),
+        "nav_categories": ctx.nav_categories,
+        "nav_journal_categories": ctx.nav_journal_categories,
         "posts": posts_json,
         "current_page": current_page,
         "total_pages": total_pages,
@@ -666,10 +1120,20 @@ fn do_blog_list(store: &dyn Store, page: Option<i64>) -> RawHtml<String> {
         "seo": seo::build_meta(store, Some("Blog"), None, &render::slug_url(&store.setting_get_or("blog_slug", "journal"), "")),
     });
 
-    RawHtml(render::render_page(store, "blog_list", &context))
+    RawHtml(render::render_page_with(
+        store,
+        ctx.design.as_ref(),
+        "blog_list",
+        &context,
+    ))
 }
 
-fn do_blog_single(store: &dyn Store, slug: &str) -> Option<RawHtml<String>> {
+fn do_blog_single(
+    store: &dyn Store,
+    ctx: &RenderContext,
+    slug: &str,
+    visitor: &str,
+) -> Option<RawHtml<String>> {
     let post = store.post_find_by_slug(slug)?;
     if post.status != "published" {
         return None;
@@ -677,7 +1141,7 @@ fn do_blog_single(store: &dyn Store, slug: &str) -> Option<RawHtml<String>> {
 
     let categories = store.category_for_content(post.id, "post");
     let tags = store.tag_for_content(post.id, "post");
-    let settings = store.setting_all();
+    let settings = ctx.settings.clone();
     let comments_enabled = settings.get("comments_on_blog").map(|v| v.as_str()) == Some("true");
     let comments = if comments_enabled {
         store.comment_for_post(post.id, "post")
@@ -694,34 +1158,36 @@ fn do_blog_single(store: &dyn Store, slug: &str) -> Option<RawHtml<String>> {
         .as_ref()
         .and_then(|pa| store.post_next_published(pa));
 
-    // Get author name from the first admin user
-    let author_name = store
-        .user_list_all()
-        .into_iter()
-        .find(|u| u.role == "admin")
-        .map(|u| u.display_name)
-        .unwrap_or_default();
+    let users = store.user_list_all();
 
     let mut post_json = serde_json::to_value(&post).unwrap_or_default();
     if let Some(obj) = post_json.as_object_mut() {
-        obj.insert("author_name".to_string(), json!(author_name));
+        insert_byline(obj, post_author(&users, &post));
     }
+    crate::analytics::record_experiment_click(store, Some(&mut post_json), visitor);
+    let alternates = seo::hreflang::post_alternates(store, &post);
 
     let mut context = json!({
         "settings": settings,
         "post": post_json,
         "categories": categories,
-        "nav_categories": nav_categories(store),
-        "nav_journal_categories": nav_journal_categories(store),
+        "nav_categories": ctx.nav_categories,
+        "nav_journal_categories": ctx.nav_journal_categories,
         "tags": tags,
         "comments": comments,
         "comments_enabled": comments_enabled,
         "page_type": "blog_single",
-        "seo": seo::build_meta(
+        "seo": seo::build_meta_with(
             store,
             post.meta_title.as_deref().or(Some(&post.title)),
             post.meta_description.as_deref(),
             &render::slug_url(&store.setting_get_or("blog_slug", "journal"), &post.slug),
+            &seo::MetaOverrides {
+                noindex: post.robots_noindex,
+                nofollow: post.robots_nofollow,
+                canonical: post.canonical_url.as_deref(),
+                alternates: &alternates,
+            },
         ),
     });
 
@@ -731,12 +1197,73 @@ fn do_blog_single(store: &dyn Store, slug: &str) -> Option<RawHtml<String>> {
     if let Some(next) = next_post {
         context["next_post"] = json!({"title": next.title, "slug": next.slug});
     }
+    if settings.get("blog_related_posts").map(|v| v.as_str()) == Some("true") {
+        let related: Vec<serde_json::Value> = related_posts(store, post.id, &tags, 3)
+            .into_iter()
+            .map(|p| json!({"title": p.title, "slug": p.slug, "featured_image": p.featured_image}))
+            .collect();
+        context["related_posts"] = json!(related);
+    }
 
-    Some(RawHtml(render::render_page(store, "blog_single", &context)))
+    Some(RawHtml(render::render_page_with(
+        store,
+        ctx.design.as_ref(),
+        "blog_single",
+        &context,
+    )))
+}
+
+/// Up to `limit` posts related to `post_id`: those sharing the most tags,
+/// topped up with the nearest by meaning when AI embeddings are on.
+fn related_posts(
+    store: &dyn Store,
+    post_id: i64,
+    tags: &[crate::models::tag::Tag],
+    limit: usize,
+) -> Vec<crate::models::post::Post> {
+    let mut shared: Vec<(usize, crate::models::post::Post)> = Vec::new();
+    for tag in tags {
+        for p in store.post_by_tag(tag.id, 20, 0) {
+            if p.id == post_id {
+                continue;
+            }
+            match shared.iter_mut().find(|(_, s)| s.id == p.id) {
+                Some((count, _)) => *count += 1,
+                None => shared.push((1, p)),
+            }
+        }
+    }
+    // Most shared tags first, newest first among equals
+    shared.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then_with(|| b.1.published_at.cmp(&a.1.published_at))
+    });
+    let mut related: Vec<crate::models::post::Post> =
+        shared.into_iter().map(|(_, p)| p).take(limit).collect();
+
+    if related.len() < limit {
+        for (item_type, id, _) in crate::ai::embeddings::related(store, "post", post_id, limit * 2)
+        {
+            if related.len() >= limit {
+                break;
+            }
+            if item_type != "post" || related.iter().any(|p| p.id == id) {
+                continue;
+            }
+            if let Some(p) = store
+                .post_find_by_id(id)
+                .filter(|p| p.status == "published")
+            {
+                related.push(p);
+            }
+        }
+    }
+    related
 }
 
 fn do_blog_by_category(
     store: &dyn Store,
+    ctx: &RenderContext,
     slug: &str,
     page: Option<i64>,
 ) -> Option<RawHtml<String>> {
@@ -744,24 +1271,23 @@ fn do_blog_by_category(
     let per_page = store.setting_get_i64("blog_posts_per_page").max(1);
     let current_page = page.unwrap_or(1).max(1);
     let offset = (current_page - 1) * per_page;
-    let settings = store.setting_all();
+    let settings = ctx.settings.clone();
 
     let posts = store.post_by_category(category.id, per_page, offset);
     let total = store.post_count_by_category(category.id);
+    let path = render::slug_url(
+        &store.setting_get_or("blog_slug", "journal"),
+        &format!("category/{}", slug),
+    );
     let total_pages = (total as f64 / per_page as f64).ceil() as i64;
 
-    let author_name = store
-        .user_list_all()
-        .into_iter()
-        .find(|u| u.role == "admin")
-        .map(|u| u.display_name)
-        .unwrap_or_default();
+    let users = store.user_list_all();
     let posts_json: Vec<serde_json::Value> = posts
         .iter()
         .map(|p| {
             let mut pj = serde_json::to_value(p).unwrap_or_default();
             if let Some(obj) = pj.as_object_mut() {
-                obj.insert("author_name".to_string(), json!(author_name.clone()));
+                insert_byline(obj, post_author(&users, p));
                 let cats = store.category_for_content(p.id, "post");
                 obj.insert("categories".to_string(), json!(cats));
                 let cc = store.comment_for_post(p.id, "post").len();
@@ -773,42 +1299,51 @@ fn do_blog_by_category(
 
     let context = json!({
         "settings": settings,
-        "nav_categories": nav_categories(store),
-        "nav_journal_categories": nav_journal_categories(store),
+        "nav_categories": ctx.nav_categories,
+        "nav_journal_categories": ctx.nav_journal_categories,
         "posts": posts_json,
         "active_category": category,
         "current_page": current_page,
         "total_pages": total_pages,
         "page_type": "blog_list",
-        "seo": seo::build_meta(store, Some(&category.name), None, &render::slug_url(&store.setting_get_or("blog_slug", "journal"), &format!("category/{}", slug))),
+        "seo": format!("{}{}", seo::build_meta(store, Some(&category.name), None, &path), crate::rss::feed_link_tag(store, &category.name, &path)),
     });
 
-    Some(RawHtml(render::render_page(store, "blog_list", &context)))
+    Some(RawHtml(render::render_page_with(
+        store,
+        ctx.design.as_ref(),
+        "blog_list",
+        &context,
+    )))
 }
 
-fn do_blog_by_tag(store: &dyn Store, slug: &str, page: Option<i64>) -> Option<RawHtml<String>> {
+fn do_blog_by_tag(
+    store: &dyn Store,
+    ctx: &RenderContext,
+    slug: &str,
+    page: Option<i64>,
+) -> Option<RawHtml<String>> {
     let tag = store.tag_find_by_slug(slug)?;
     let per_page = store.setting_get_i64("blog_posts_per_page").max(1);
     let current_page = page.unwrap_or(1).max(1);
     let offset = (current_page - 1) * per_page;
-    let settings = store.setting_all();
+    let settings = ctx.settings.clone();
 
     let posts = store.post_by_tag(tag.id, per_page, offset);
     let total = store.post_count_by_tag(tag.id);
+    let path = render::slug_url(
+        &store.setting_get_or("blog_slug", "journal"),
+        &format!("tag/{}", slug),
+    );
     let total_pages = (total as f64 / per_page as f64).ceil() as i64;
 
-    let author_name = store
-        .user_list_all()
-        .into_iter()
-        .find(|u| u.role == "admin")
-        .map(|u| u.display_name)
-        .unwrap_or_default();
+    let users = store.user_list_all();
     let posts_json: Vec<serde_json::Value> = posts
         .iter()
         .map(|p| {
             let mut pj = serde_json::to_value(p).unwrap_or_default();
             if let Some(obj) = pj.as_object_mut() {
-                obj.insert("author_name".to_string(), json!(author_name.clone()));
+                insert_byline(obj, post_author(&users, p));
                 let cats = store.category_for_content(p.id, "post");
                 obj.insert("categories".to_string(), json!(cats));
                 let cc = store.comment_for_post(p.id, "post").len();
@@ -820,20 +1355,31 @@ fn do_blog_by_tag(store: &dyn Store, slug: &str, page: Option<i64>) -> Option<Ra
 
     let context = json!({
         "settings": settings,
-        "nav_categories": nav_categories(store),
-        "nav_journal_categories": nav_journal_categories(store),
+        "nav_categories": ctx.nav_categories,
+        "nav_journal_categories": ctx.nav_journal_categories,
         "posts": posts_json,
         "active_tag": tag,
         "current_page": current_page,
         "total_pages": total_pages,
         "page_type": "blog_list",
-        "seo": seo::build_meta(store, Some(&tag.name), None, &render::slug_url(&store.setting_get_or("blog_slug", "journal"), &format!("tag/{}", slug))),
+        "seo": format!("{}{}", seo::build_meta(store, Some(&tag.name), None, &path), crate::rss::feed_link_tag(store, &tag.name, &path)),
     });
 
-    Some(RawHtml(render::render_page(store, "blog_list", &context)))
+    Some(RawHtml(render::render_page_with(
+        store,
+        ctx.design.as_ref(),
+        "blog_list",
+        &context,
+    )))
 }
 
-fn do_portfolio_grid(store: &dyn Store, page: Option<i64>) -> RawHtml<String> {
+fn do_portfolio_grid(
+    store: &dyn Store,
+    ctx: &RenderContext,
+    page: Option<i64>,
+    visitor: &str,
+    home: bool,
+) -> RawHtml<String> {
     let per_page = store.setting_get_i64("portfolio_items_per_page").max(1);
     let current_page = page.unwrap_or(1).max(1);
     let offset = (current_page - 1) * per_page;
@@ -841,8 +1387,11 @@ fn do_portfolio_grid(store: &dyn Store, page: Option<i64>) -> RawHtml<String> {
     let items = store.portfolio_list(Some("published"), per_page, offset);
     let total = store.portfolio_count(Some("published"));
     let total_pages = (total as f64 / per_page as f64).ceil() as i64;
-    let categories = store.category_list_nav_visible(Some("portfolio"));
-    let settings = store.setting_all();
+    let categories = ctx.nav_categories.clone();
+    let mut settings = ctx.settings.clone();
+    if home {
+        crate::analytics::apply_hero_experiment(store, &mut settings, visitor);
+    }
 
     let items_with_meta: Vec<serde_json::Value> = items
         .iter()
@@ -861,25 +1410,36 @@ fn do_portfolio_grid(store: &dyn Store, page: Option<i64>) -> RawHtml<String> {
         "settings": settings,
         "items": items_with_meta,
         "categories": categories,
-        "nav_journal_categories": nav_journal_categories(store),
+        "nav_journal_categories": ctx.nav_journal_categories,
         "current_page": current_page,
         "total_pages": total_pages,
         "page_type": "portfolio_grid",
         "seo": seo::build_meta(store, Some("Portfolio"), None, &render::slug_url(&store.setting_get_or("portfolio_slug", "portfolio"), "")),
     });
 
-    RawHtml(render::render_page(store, "portfolio_grid", &context))
+    RawHtml(render::render_page_with(
+        store,
+        ctx.design.as_ref(),
+        "portfolio_grid",
+        &context,
+    ))
 }
 
-fn do_portfolio_single(store: &dyn Store, slug: &str) -> Option<RawHtml<String>> {
+fn do_portfolio_single(
+    store: &dyn Store,
+    ctx: &RenderContext,
+    slug: &str,
+    visitor: &str,
+) -> Option<RawHtml<String>> {
     let item = store.portfolio_find_by_slug(slug)?;
     if item.status != "published" {
         return None;
     }
+    crate::analytics::record_experiment_click(store, None, visitor);
 
     let categories = store.category_for_content(item.id, "portfolio");
     let tags = store.tag_for_content(item.id, "portfolio");
-    let settings = store.setting_all();
+    let settings = ctx.settings.clone();
     let comments_enabled =
         settings.get("comments_on_portfolio").map(|v| v.as_str()) == Some("true");
     let comments = if comments_enabled {
@@ -904,23 +1464,30 @@ fn do_portfolio_single(store: &dyn Store, slug: &str) -> Option<RawHtml<String>>
         "settings": settings,
         "item": item,
         "categories": categories,
-        "nav_categories": nav_categories(store),
-        "nav_journal_categories": nav_journal_categories(store),
+        "nav_categories": ctx.nav_categories,
+        "nav_journal_categories": ctx.nav_journal_categories,
         "tags": tags,
         "comments": comments,
         "comments_enabled": comments_enabled,
         "page_type": "portfolio_single",
         "commerce_enabled": any_commerce && item.sell_enabled && item.price.unwrap_or(0.0) > 0.0,
-        "seo": seo::build_meta(
+        "seo": seo::build_meta_with(
             store,
             item.meta_title.as_deref().or(Some(&item.title)),
             item.meta_description.as_deref(),
             &render::slug_url(&store.setting_get_or("portfolio_slug", "portfolio"), &item.slug),
+            &seo::MetaOverrides {
+                noindex: item.robots_noindex,
+                nofollow: item.robots_nofollow,
+                canonical: item.canonical_url.as_deref(),
+                alternates: &[],
+            },
         ),
     });
 
-    Some(RawHtml(render::render_page(
+    Some(RawHtml(render::render_page_with(
         store,
+        ctx.design.as_ref(),
         "portfolio_single",
         &context,
     )))
@@ -928,6 +1495,7 @@ fn do_portfolio_single(store: &dyn Store, slug: &str) -> Option<RawHtml<String>>
 
 fn do_portfolio_by_category(
     store: &dyn Store,
+    ctx: &RenderContext,
     slug: &str,
     page: Option<i64>,
 ) -> Option<RawHtml<String>> {
@@ -936,8 +1504,8 @@ fn do_portfolio_by_category(
     let current_page = page.unwrap_or(1).max(1);
     let offset = (current_page - 1) * per_page;
     let items = store.portfolio_by_category(slug, per_page, offset);
-    let categories = store.category_list_nav_visible(Some("portfolio"));
-    let settings = store.setting_all();
+    let categories = ctx.nav_categories.clone();
+    let settings = ctx.settings.clone();
 
     let items_with_meta: Vec<serde_json::Value> = items
         .iter()
@@ -956,7 +1524,7 @@ fn do_portfolio_by_category(
         "settings": settings,
         "items": items_with_meta,
         "categories": categories,
-        "nav_journal_categories": nav_journal_categories(store),
+        "nav_journal_categories": ctx.nav_journal_categories,
         "active_category": category,
         "current_page": current_page,
         "total_pages": ((store.portfolio_count(Some("published")) as f64 / per_page as f64).ceil() as i64),
@@ -964,8 +1532,9 @@ fn do_portfolio_by_category(
         "seo": seo::build_meta(store, Some(&category.name), None, &render::slug_url(&store.setting_get_or("portfolio_slug", "portfolio"), &format!("category/{}", slug))),
     });
 
-    Some(RawHtml(render::render_page(
+    Some(RawHtml(render::render_page_with(
         store,
+        ctx.design.as_ref(),
         "portfolio_grid",
         &context,
     )))
@@ -973,6 +1542,7 @@ fn do_portfolio_by_category(
 
 fn do_portfolio_by_tag(
     store: &dyn Store,
+    ctx: &RenderContext,
     slug: &str,
     page: Option<i64>,
 ) -> Option<RawHtml<String>> {
@@ -980,8 +1550,8 @@ fn do_portfolio_by_tag(
     let per_page = store.setting_get_i64("portfolio_items_per_page").max(1);
     let current_page = page.unwrap_or(1).max(1);
     let offset = (current_page - 1) * per_page;
-    let categories = store.category_list_nav_visible(Some("portfolio"));
-    let settings = store.setting_all();
+    let categories = ctx.nav_categories.clone();
+    let settings = ctx.settings.clone();
 
     let items = store.portfolio_by_tag(tag.id, per_page, offset);
 
@@ -1005,7 +1575,7 @@ fn do_portfolio_by_tag(
         "settings": settings,
         "items": items_with_meta,
         "categories": categories,
-        "nav_journal_categories": nav_journal_categories(store),
+        "nav_journal_categories": ctx.nav_journal_categories,
         "active_tag": tag,
         "current_page": current_page,
         "total_pages": total_pages,
@@ -1013,8 +1583,9 @@ fn do_portfolio_by_tag(
         "seo": seo::build_meta(store, Some(&tag.name), None, &render::slug_url(&store.setting_get_or("portfolio_slug", "portfolio"), &format!("tag/{}", slug))),
     });
 
-    Some(RawHtml(render::render_page(
+    Some(RawHtml(render::render_page_with(
         store,
+        ctx.design.as_ref(),
         "portfolio_grid",
         &context,
     )))
@@ -5,7 +5,8 @@ use rocket_dyn_templates::Template;
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::security::auth::{self, AuthorUser};
+use crate::security::auth::{self, DashboardUser};
+use crate::security::password_policy::{self, PasswordPolicy};
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -17,7 +18,7 @@ pub struct ChangePasswordForm {
 
 #[get("/change-password")]
 pub fn change_password_page(
-    user: AuthorUser,
+    user: DashboardUser,
     store: &State<Arc<dyn Store>>,
     admin_slug: &State<AdminSlug>,
 ) -> Result<Template, Redirect> {
@@ -38,12 +39,20 @@ pub fn change_password_page(
         s.setting_get_or("admin_theme", "dark"),
     );
     ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
+    ctx.insert(
+        "password_hint".to_string(),
+        PasswordPolicy::load(s).summary(),
+    );
+    ctx.insert(
+        "password_min_length".to_string(),
+        PasswordPolicy::load(s).min_length.to_string(),
+    );
     Ok(Template::render("admin/change_password", &ctx))
 }
 
 #[post("/change-password", data = "<form>")]
 pub fn change_password_submit(
-    user: AuthorUser,
+    user: DashboardUser,
     form: Form<ChangePasswordForm>,
     store: &State<Arc<dyn Store>>,
     admin_slug: &State<AdminSlug>,
@@ -56,17 +65,29 @@ pub fn change_password_submit(
         ctx.insert("error".to_string(), msg.to_string());
         ctx.insert("admin_theme".to_string(), theme.clone());
         ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
+        ctx.insert(
+            "password_hint".to_string(),
+            PasswordPolicy::load(s).summary(),
+        );
+        ctx.insert(
+            "password_min_length".to_string(),
+            PasswordPolicy::load(s).min_length.to_string(),
+        );
         Template::render("admin/change_password", &ctx)
     };
 
-    if form.new_password.len() < 8 {
-        return Err(make_err("Password must be at least 8 characters."));
+    if let Err(e) = password_policy::check(
+        s,
+        &form.new_password,
+        &[&user.user.email, &user.user.display_name],
+    ) {
+        return Err(make_err(&e));
     }
     if form.new_password != form.confirm_password {
         return Err(make_err("Passwords do not match."));
     }
 
-    let hash = match auth::hash_password(&form.new_password) {
+    let hash = match auth::hash_password_for(s, &form.new_password) {
         Ok(h) => h,
         Err(_) => return Err(make_err("Failed to hash password.")),
     };
@@ -0,0 +1,128 @@
+use rocket::form::Form;
+use rocket::http::CookieJar;
+use rocket::response::Redirect;
+use rocket::State;
+use rocket_dyn_templates::Template;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+
+use std::sync::Arc;
+
+use crate::security::auth::{self, DashboardUser};
+use crate::security::login_alert::{self, LoginClient};
+use crate::security::{invite, passkey, password_policy};
+use crate::store::Store;
+use crate::AdminSlug;
+
+#[derive(Debug, FromForm, Deserialize)]
+pub struct AcceptInviteForm {
+    pub token: String,
+    pub password: String,
+    pub confirm_password: String,
+}
+
+fn accept_context(s: &dyn Store, admin_slug: &str, token: &str) -> HashMap<String, String> {
+    let mut ctx: HashMap<String, String> = HashMap::new();
+    ctx.insert(
+        "admin_theme".to_string(),
+        s.setting_get_or("admin_theme", "dark"),
+    );
+    ctx.insert("admin_slug".to_string(), admin_slug.to_string());
+    ctx.insert(
+        "site_name".to_string(),
+        s.setting_get_or("site_name", "Velocty"),
+    );
+    ctx.insert("token".to_string(), token.to_string());
+    ctx.insert(
+        "password_hint".to_string(),
+        password_policy::PasswordPolicy::load(s).summary(),
+    );
+    ctx.insert(
+        "password_min_length".to_string(),
+        password_policy::PasswordPolicy::load(s)
+            .min_length
+            .to_string(),
+    );
+    ctx
+}
+
+/// GET /invite?token=xxx — choose a password for an invited account
+#[get("/invite?<token>")]
+pub fn accept_invite_page(
+    token: &str,
+    store: &State<Arc<dyn Store>>,
+    admin_slug: &State<AdminSlug>,
+) -> Template {
+    let s: &dyn Store = &**store.inner();
+    Template::render(
+        "admin/accept_invite",
+        &accept_context(s, admin_slug.get(), token),
+    )
+}
+
+/// POST /invite — set the password, activate the account and sign in
+#[post("/invite", data = "<form>")]
+pub fn accept_invite_submit(
+    form: Form<AcceptInviteForm>,
+    store: &State<Arc<dyn Store>>,
+    admin_slug: &State<AdminSlug>,
+    cookies: &CookieJar<'_>,
+    client: LoginClient,
+) -> Result<Redirect, Template> {
+    let s: &dyn Store = &**store.inner();
+    let make_err = |msg: &str| -> Template {
+        let mut ctx = accept_context(s, admin_slug.get(), &form.token);
+        ctx.insert("error".to_string(), msg.to_string());
+        Template::render("admin/accept_invite", &ctx)
+    };
+
+    if form.password != form.confirm_password {
+        return Err(make_err("Passwords do not match."));
+    }
+    let user = invite::accept(s, &form.token, &form.password).map_err(|e| make_err(&e))?;
+    s.audit_log(
+        Some(user.id),
+        Some(&user.display_name),
+        "invite_accept",
+        Some("user"),
+        Some(user.id),
+        Some(&user.email),
+        Some(&user.role),
+        Some(&client.ip),
+    );
+
+    // The password is set either way; without a session they sign in normally
+    let _ = s.user_touch_last_login(user.id);
+    match auth::create_session(s, user.id, None, None) {
+        Ok(session_id) => {
+            auth::set_session_cookie_secure(cookies, &session_id, s);
+            login_alert::record_login(store.inner(), &user, &client);
+            Ok(Redirect::to(format!("/{}/welcome", admin_slug.get())))
+        }
+        Err(_) => Ok(Redirect::to(format!(
+            "/{}/login?reset=success",
+            admin_slug.get()
+        ))),
+    }
+}
+
+/// GET /welcome — offer passkey and MFA setup to a newly onboarded user
+#[get("/welcome")]
+pub fn welcome_page(
+    user: DashboardUser,
+    store: &State<Arc<dyn Store>>,
+    admin_slug: &State<AdminSlug>,
+) -> Template {
+    let s: &dyn Store = &**store.inner();
+    let ctx = json!({
+        "admin_theme": s.setting_get_or("admin_theme", "dark"),
+        "admin_slug": admin_slug.get(),
+        "site_name": s.setting_get_or("site_name", "Velocty"),
+        "display_name": user.user.display_name,
+        "mfa_enabled": user.user.mfa_required(),
+        "passkey_available": passkey::build_webauthn(s).is_ok(),
+        "passkey_count": s.passkey_count_for_user(user.user.id),
+    });
+    Template::render("admin/welcome", &ctx)
+}
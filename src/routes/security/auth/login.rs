@@ -9,7 +9,8 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::rate_limit::RateLimiter;
-use crate::security::{self, auth, mfa};
+use crate::security::login_alert::{self, LoginClient};
+use crate::security::{self, auth, mfa, passwordless, permissions};
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -67,6 +68,7 @@ pub fn login_submit(
     limiter: &State<RateLimiter>,
     cookies: &CookieJar<'_>,
     client_ip: auth::ClientIp,
+    client: LoginClient,
 ) -> Result<Redirect, Template> {
     let s: &dyn Store = &**store.inner();
     let theme = s.setting_get_or("admin_theme", "dark");
@@ -85,7 +87,7 @@ pub fn login_submit(
     };
 
     // Check rate limit before processing
-    if !limiter.check_and_record(&rate_key, max_attempts, window) {
+    if !limiter.check(s, &rate_key, max_attempts, window) {
         return Err(make_err(
             "Too many login attempts. Please try again in 15 minutes.",
             &theme,
@@ -127,7 +129,8 @@ pub fn login_submit(
                 );
                 if s.setting_get_or("fw_ban_unknown_users", "false") == "true" {
                     let dur = s.setting_get_or("fw_unknown_user_ban_duration", "24h");
-                    let _ = s.fw_ban_create_with_duration(
+                    let _ = crate::models::firewall::auto_ban(
+                        s,
                         ip,
                         "unknown_user",
                         Some(&format!("Login attempt with unknown user: {}", form.email)),
@@ -156,8 +159,8 @@ pub fn login_submit(
         ));
     }
 
-    // Check role — subscribers cannot log into admin
-    if user.role == "subscriber" {
+    // Check role — only roles with dashboard access can log into admin
+    if !permissions::user_can(s, &user, permissions::DASHBOARD_VIEW) {
         return Err(make_err(
             "Your account does not have admin panel access.",
             &theme,
@@ -166,6 +169,26 @@ pub fn login_submit(
         ));
     }
 
+    // Passwordless accounts sign in with a passkey, link or recovery code only
+    if user.passwordless {
+        s.audit_log(
+            Some(user.id),
+            Some(&user.display_name),
+            "login_failed",
+            Some("user"),
+            Some(user.id),
+            Some(&user.email),
+            Some("Password sign-in is disabled"),
+            Some(ip),
+        );
+        return Err(make_err(
+            passwordless::PASSWORD_DISABLED_MSG,
+            &theme,
+            s,
+            &admin_slug.get(),
+        ));
+    }
+
     // Verify password
     if !auth::verify_password(&form.password, &user.password_hash) {
         // Firewall: failed password
@@ -187,7 +210,8 @@ pub fn login_submit(
             let count = s.fw_event_count_for_ip_since(ip, "failed_login", 15);
             if count >= threshold {
                 let dur = s.setting_get_or("fw_failed_login_ban_duration", "1h");
-                let _ = s.fw_ban_create_with_duration(
+                let _ = crate::models::firewall::auto_ban(
+                    s,
                     ip,
                     "failed_login",
                     Some("Too many failed login attempts"),
@@ -215,11 +239,13 @@ pub fn login_submit(
         ));
     }
 
+    // Password is correct — move outdated hashes to the configured algorithm
+    auth::upgrade_password_hash(s, &user, &form.password);
+
     // Check MFA (per-user)
-    if user.mfa_enabled && !user.mfa_secret.is_empty() {
-        let pending_token = uuid::Uuid::new_v4().to_string();
+    if user.mfa_required() {
         // Store user_id in a pending cookie so MFA page can complete login
-        mfa::set_pending_cookie(cookies, &format!("{}:{}", user.id, pending_token));
+        mfa::begin_challenge(s, limiter, cookies, &user);
         return Ok(Redirect::to(format!("/{}/mfa", admin_slug.get())));
     }
 
@@ -228,6 +254,7 @@ pub fn login_submit(
     match auth::create_session(s, user.id, None, None) {
         Ok(session_id) => {
             auth::set_session_cookie_secure(cookies, &session_id, s);
+            login_alert::record_login(store.inner(), &user, &client);
             s.audit_log(
                 Some(user.id),
                 Some(&user.display_name),
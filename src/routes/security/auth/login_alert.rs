@@ -0,0 +1,64 @@
+use rocket::form::Form;
+use rocket::response::Redirect;
+use rocket::State;
+use rocket_dyn_templates::Template;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use std::sync::Arc;
+
+use crate::security::{auth, login_alert};
+use crate::store::Store;
+use crate::AdminSlug;
+
+#[derive(Debug, FromForm, Deserialize)]
+pub struct NotMeForm {
+    pub token: String,
+}
+
+/// GET /login/not-me?token=xxx — confirm before locking, so mail scanners
+/// that prefetch links can't lock the account
+#[get("/login/not-me?<token>")]
+pub fn login_not_me_page(
+    token: &str,
+    store: &State<Arc<dyn Store>>,
+    admin_slug: &State<AdminSlug>,
+) -> Template {
+    let s: &dyn Store = &**store.inner();
+    let mut ctx: HashMap<String, String> = HashMap::new();
+    ctx.insert(
+        "admin_theme".to_string(),
+        s.setting_get_or("admin_theme", "dark"),
+    );
+    ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
+    ctx.insert("token".to_string(), token.to_string());
+    Template::render("admin/login_alert", &ctx)
+}
+
+/// POST /login/not-me — lock the account and continue to a password reset
+#[post("/login/not-me", data = "<form>")]
+pub fn login_not_me_submit(
+    form: Form<NotMeForm>,
+    store: &State<Arc<dyn Store>>,
+    admin_slug: &State<AdminSlug>,
+    client_ip: auth::ClientIp,
+) -> Result<Redirect, Template> {
+    let s: &dyn Store = &**store.inner();
+    match login_alert::deny(s, &form.token, &client_ip.0) {
+        Ok(reset_token) => Ok(Redirect::to(format!(
+            "/{}/reset-password?token={}",
+            admin_slug.get(),
+            reset_token
+        ))),
+        Err(e) => {
+            let mut ctx: HashMap<String, String> = HashMap::new();
+            ctx.insert(
+                "admin_theme".to_string(),
+                s.setting_get_or("admin_theme", "dark"),
+            );
+            ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
+            ctx.insert("error".to_string(), e);
+            Err(Template::render("admin/login_alert", &ctx))
+        }
+    }
+}
@@ -21,7 +21,7 @@ pub fn logout(
     Redirect::to(format!("/{}/login", admin_slug.get()))
 }
 
-/// Catch-all for any /<admin_slug>/* route that failed the AdminUser guard.
+/// Catch-all for any /<admin_slug>/* route that failed a capability guard.
 /// This fires when the guard returns Forward(Unauthorized).
 #[get("/<_path..>", rank = 99)]
 pub fn admin_redirect_to_login(
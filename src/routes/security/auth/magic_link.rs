@@ -9,6 +9,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::rate_limit::RateLimiter;
+use crate::security::login_alert::{self, LoginClient};
 use crate::security::{self, auth, magic_link, mfa};
 use crate::store::Store;
 use crate::AdminSlug;
@@ -29,12 +30,9 @@ pub struct MagicLinkForm {
 pub fn magic_link_page(
     store: &State<Arc<dyn Store>>,
     admin_slug: &State<AdminSlug>,
-) -> Result<NoCacheTemplate, Redirect> {
+) -> NoCacheTemplate {
     let s: &dyn Store = &**store.inner();
-    let login_method = s.setting_get_or("login_method", "password");
-    if login_method != "magic_link" {
-        return Err(Redirect::to(format!("/{}/login", admin_slug.get())));
-    }
+    // Passwordless accounts can use sign-in links whatever the site's login method
     let mut ctx: HashMap<String, String> = HashMap::new();
     ctx.insert(
         "admin_theme".to_string(),
@@ -42,7 +40,7 @@ pub fn magic_link_page(
     );
     ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
     inject_captcha_context(s, &mut ctx);
-    Ok(NoCacheTemplate(Template::render("admin/magic_link", &ctx)))
+    NoCacheTemplate(Template::render("admin/magic_link", &ctx))
 }
 
 #[post("/magic-link", data = "<form>")]
@@ -56,18 +54,6 @@ pub fn magic_link_submit(
     let s: &dyn Store = &**store.inner();
     let theme = s.setting_get_or("admin_theme", "dark");
 
-    let login_method = s.setting_get_or("login_method", "password");
-    if login_method != "magic_link" {
-        let mut ctx = HashMap::new();
-        ctx.insert(
-            "error".to_string(),
-            "Magic link login is not enabled".to_string(),
-        );
-        ctx.insert("admin_theme".to_string(), theme);
-        ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
-        return Err(Template::render("admin/magic_link", &ctx));
-    }
-
     // Verify login captcha
     let captcha_token = form.captcha_token.as_deref().unwrap_or("");
     match security::verify_login_captcha(s, captcha_token, None) {
@@ -88,7 +74,7 @@ pub fn magic_link_submit(
 
     // Rate limit magic link requests
     let rate_key = format!("magic_link:{}", client_ip.0);
-    if !limiter.check_and_record(&rate_key, 3, std::time::Duration::from_secs(15 * 60)) {
+    if !limiter.check(s, &rate_key, 3, std::time::Duration::from_secs(15 * 60)) {
         let mut ctx = HashMap::new();
         ctx.insert(
             "error".to_string(),
@@ -99,7 +85,9 @@ pub fn magic_link_submit(
         return Err(Template::render("admin/magic_link", &ctx));
     }
 
+    let login_method = s.setting_get_or("login_method", "password");
     let admin_email = s.setting_get_or("admin_email", "");
+    let email = form.email.trim();
 
     // Always show success message to prevent email enumeration
     let mut ctx = HashMap::new();
@@ -110,11 +98,16 @@ pub fn magic_link_submit(
         "If that email is registered, a magic link has been sent. Check your inbox.".to_string(),
     );
 
-    // Only actually send if the email matches a known user
-    if !admin_email.is_empty() && form.email.trim() == admin_email {
-        match magic_link::create_token(s, form.email.trim()) {
+    // Only actually send to the admin when the site uses magic links, or to
+    // an active passwordless account
+    let eligible =
+        (login_method == "magic_link" && !admin_email.is_empty() && email == admin_email)
+            || s.user_get_by_email(email)
+                .is_some_and(|u| u.passwordless && u.is_active());
+    if eligible {
+        match magic_link::create_token(s, email) {
             Ok(token) => {
-                if let Err(e) = magic_link::send_magic_link_email(s, &admin_email, &token) {
+                if let Err(e) = magic_link::send_magic_link_email(s, email, &token) {
                     log::error!("Failed to send magic link email: {}", e);
                 }
             }
@@ -134,7 +127,9 @@ pub fn magic_link_verify(
     token: &str,
     store: &State<Arc<dyn Store>>,
     admin_slug: &State<AdminSlug>,
+    limiter: &State<RateLimiter>,
     cookies: &CookieJar<'_>,
+    client: LoginClient,
 ) -> Result<Redirect, Template> {
     let s: &dyn Store = &**store.inner();
     let theme = s.setting_get_or("admin_theme", "dark");
@@ -153,6 +148,17 @@ pub fn magic_link_verify(
                 }
             };
 
+            if s.setting_get_or("login_method", "password") != "magic_link" && !user.passwordless {
+                let mut ctx = HashMap::new();
+                ctx.insert(
+                    "error".to_string(),
+                    "Magic link login is not enabled".to_string(),
+                );
+                ctx.insert("admin_theme".to_string(), theme);
+                ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
+                return Err(Template::render("admin/magic_link", &ctx));
+            }
+
             if !user.is_active() {
                 let mut ctx = HashMap::new();
                 ctx.insert(
@@ -165,9 +171,8 @@ pub fn magic_link_verify(
             }
 
             // Check if MFA is required (per-user)
-            if user.mfa_enabled && !user.mfa_secret.is_empty() {
-                let pending_token = uuid::Uuid::new_v4().to_string();
-                mfa::set_pending_cookie(cookies, &format!("{}:{}", user.id, pending_token));
+            if user.mfa_required() {
+                mfa::begin_challenge(s, limiter, cookies, &user);
                 return Ok(Redirect::to(format!("/{}/mfa", admin_slug.get())));
             }
 
@@ -176,6 +181,7 @@ pub fn magic_link_verify(
             match auth::create_session(s, user.id, None, None) {
                 Ok(session_id) => {
                     auth::set_session_cookie_secure(cookies, &session_id, s);
+                    login_alert::record_login(store.inner(), &user, &client);
                     Ok(Redirect::to(format!("/{}", admin_slug.get())))
                 }
                 Err(_) => {
@@ -8,6 +8,9 @@ use std::collections::HashMap;
 
 use std::sync::Arc;
 
+use crate::models::user::User;
+use crate::rate_limit::RateLimiter;
+use crate::security::login_alert::{self, LoginClient};
 use crate::security::{auth, mfa};
 use crate::store::Store;
 use crate::AdminSlug;
@@ -25,34 +28,72 @@ fn pending_user_id(cookies: &CookieJar<'_>) -> Option<i64> {
     val.split(':').next()?.parse().ok()
 }
 
+/// Context for the MFA page: the user's method decides the instructions.
+fn mfa_context(s: &dyn Store, admin_slug: &AdminSlug, user: &User) -> HashMap<String, String> {
+    let mut ctx: HashMap<String, String> = HashMap::new();
+    ctx.insert(
+        "admin_theme".to_string(),
+        s.setting_get_or("admin_theme", "dark"),
+    );
+    ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
+    ctx.insert("mfa_method".to_string(), user.mfa_method.clone());
+    ctx.insert("email_hint".to_string(), mfa::mask_email(&user.email));
+    ctx
+}
+
 #[get("/mfa")]
 pub fn mfa_page(
     store: &State<Arc<dyn Store>>,
     admin_slug: &State<AdminSlug>,
     cookies: &CookieJar<'_>,
 ) -> Result<NoCacheTemplate, Redirect> {
-    if pending_user_id(cookies).is_none() {
-        return Err(Redirect::to(format!("/{}/login", admin_slug.get())));
-    }
     let s: &dyn Store = &**store.inner();
-    let mut ctx: HashMap<String, String> = HashMap::new();
-    ctx.insert(
-        "admin_theme".to_string(),
-        s.setting_get_or("admin_theme", "dark"),
-    );
-    ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
+    let user = match pending_user_id(cookies).and_then(|id| s.user_get_by_id(id)) {
+        Some(u) => u,
+        None => return Err(Redirect::to(format!("/{}/login", admin_slug.get()))),
+    };
+    let ctx = mfa_context(s, admin_slug, &user);
     Ok(NoCacheTemplate(Template::render("admin/mfa", &ctx)))
 }
 
+/// Send another emailed code
+#[post("/mfa/resend")]
+pub fn mfa_resend(
+    store: &State<Arc<dyn Store>>,
+    admin_slug: &State<AdminSlug>,
+    limiter: &State<RateLimiter>,
+    cookies: &CookieJar<'_>,
+) -> Result<Template, Redirect> {
+    let s: &dyn Store = &**store.inner();
+    let user = match pending_user_id(cookies).and_then(|id| s.user_get_by_id(id)) {
+        Some(u) if u.mfa_method == "email" => u,
+        _ => return Err(Redirect::to(format!("/{}/login", admin_slug.get()))),
+    };
+    let mut ctx = mfa_context(s, admin_slug, &user);
+    match mfa::send_email_code_limited(s, limiter, &user) {
+        Ok(()) => {
+            ctx.insert(
+                "notice".to_string(),
+                "A new code is on its way.".to_string(),
+            );
+        }
+        Err(e) => {
+            ctx.insert("error".to_string(), e);
+        }
+    }
+    Ok(Template::render("admin/mfa", &ctx))
+}
+
 #[post("/mfa", data = "<form>")]
 pub fn mfa_submit(
     form: Form<MfaForm>,
     store: &State<Arc<dyn Store>>,
     admin_slug: &State<AdminSlug>,
+    limiter: &State<RateLimiter>,
     cookies: &CookieJar<'_>,
+    client: LoginClient,
 ) -> Result<Redirect, Template> {
     let s: &dyn Store = &**store.inner();
-    let theme = s.setting_get_or("admin_theme", "dark");
 
     // Extract user_id from pending cookie
     let user_id = match pending_user_id(cookies) {
@@ -65,12 +106,35 @@ pub fn mfa_submit(
         None => return Ok(Redirect::to(format!("/{}/login", admin_slug.get()))),
     };
 
+    let make_err = |msg: &str| -> Template {
+        let mut ctx = mfa_context(s, admin_slug, &user);
+        ctx.insert("error".to_string(), msg.to_string());
+        Template::render("admin/mfa", &ctx)
+    };
+
+    // Six-digit codes are only safe while guesses are capped
+    let max_attempts = s.setting_get_i64("login_rate_limit").max(1) as u64;
+    if !limiter.check(
+        s,
+        &format!("mfa:{}", user.id),
+        max_attempts,
+        std::time::Duration::from_secs(15 * 60),
+    ) {
+        return Err(make_err(
+            "Too many attempts. Please try again in 15 minutes.",
+        ));
+    }
+
     let code = form.code.trim();
 
-    // Try TOTP code first (against user's own secret)
-    let mut valid = mfa::verify_code(&user.mfa_secret, code);
+    // Try the user's chosen method first: emailed code or TOTP
+    let mut valid = if user.mfa_method == "email" {
+        mfa::verify_email_code(s, &user, code)
+    } else {
+        mfa::verify_code(&user.mfa_secret, code)
+    };
 
-    // If TOTP failed, try recovery code
+    // If that failed, try recovery code
     if !valid {
         let mut codes: Vec<String> =
             serde_json::from_str(&user.mfa_recovery_codes).unwrap_or_default();
@@ -84,14 +148,7 @@ pub fn mfa_submit(
     }
 
     if !valid {
-        let mut ctx = HashMap::new();
-        ctx.insert(
-            "error".to_string(),
-            "Invalid code. Please try again.".to_string(),
-        );
-        ctx.insert("admin_theme".to_string(), theme);
-        ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
-        return Err(Template::render("admin/mfa", &ctx));
+        return Err(make_err("Invalid code. Please try again."));
     }
 
     // Clear the pending cookie
@@ -102,14 +159,9 @@ pub fn mfa_submit(
     match auth::create_session(s, user.id, None, None) {
         Ok(session_id) => {
             auth::set_session_cookie_secure(cookies, &session_id, s);
+            login_alert::record_login(store.inner(), &user, &client);
             Ok(Redirect::to(format!("/{}", admin_slug.get())))
         }
-        Err(_) => {
-            let mut ctx = HashMap::new();
-            ctx.insert("error".to_string(), "Session creation failed".to_string());
-            ctx.insert("admin_theme".to_string(), theme);
-            ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
-            Err(Template::render("admin/mfa", &ctx))
-        }
+        Err(_) => Err(make_err("Session creation failed")),
     }
 }
@@ -1,25 +1,36 @@
 pub mod change_password;
+pub mod invite;
 pub mod login;
+pub mod login_alert;
 pub mod logout;
 pub mod magic_link;
 pub mod mfa;
 pub mod passkey;
 pub mod password_reset;
+pub mod recovery;
 pub mod setup;
 
 pub fn routes() -> Vec<rocket::Route> {
     routes![
         login::login_page,
         login::login_submit,
+        login_alert::login_not_me_page,
+        login_alert::login_not_me_submit,
         mfa::mfa_page,
         mfa::mfa_submit,
+        mfa::mfa_resend,
         magic_link::magic_link_page,
         magic_link::magic_link_submit,
         magic_link::magic_link_verify,
+        recovery::recovery_page,
+        recovery::recovery_submit,
         password_reset::forgot_password_page,
         password_reset::forgot_password_submit,
         password_reset::reset_password_page,
         password_reset::reset_password_submit,
+        invite::accept_invite_page,
+        invite::accept_invite_submit,
+        invite::welcome_page,
         logout::logout,
         logout::admin_redirect_to_login,
         setup::setup_page,
@@ -5,7 +5,8 @@ use serde_json::{json, Value};
 
 use std::sync::Arc;
 
-use crate::security::{auth, passkey};
+use crate::security::login_alert::{self, LoginClient};
+use crate::security::{auth, passkey, permissions};
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -15,14 +16,14 @@ pub fn passkey_check(store: &State<Arc<dyn Store>>, email: Option<&str>) -> Json
     let s: &dyn Store = &**store.inner();
     let email = match email {
         Some(e) if !e.is_empty() => e,
-        _ => return Json(json!({ "ok": true, "has_passkey": false })),
+        _ => return Json(json!({ "ok": true, "has_passkey": false, "passwordless": false })),
     };
     let user = match s.user_get_by_email(email) {
         Some(u) => u,
-        None => return Json(json!({ "ok": true, "has_passkey": false })),
+        None => return Json(json!({ "ok": true, "has_passkey": false, "passwordless": false })),
     };
     let has = user.auth_method == "passkey" && s.passkey_count_for_user(user.id) > 0;
-    Json(json!({ "ok": true, "has_passkey": has }))
+    Json(json!({ "ok": true, "has_passkey": has, "passwordless": has && user.passwordless }))
 }
 
 /// Start passkey authentication — returns challenge options for navigator.credentials.get()
@@ -75,6 +76,7 @@ pub fn passkey_auth_finish(
     admin_slug: &State<AdminSlug>,
     cookies: &CookieJar<'_>,
     client_ip: auth::ClientIp,
+    client: LoginClient,
     body: Json<Value>,
 ) -> Json<Value> {
     let s: &dyn Store = &**store.inner();
@@ -119,7 +121,7 @@ pub fn passkey_auth_finish(
     if !user.is_active() {
         return Json(json!({ "ok": false, "error": "Account is suspended or locked" }));
     }
-    if user.role == "subscriber" {
+    if !permissions::user_can(s, &user, permissions::DASHBOARD_VIEW) {
         return Json(json!({ "ok": false, "error": "No admin panel access" }));
     }
 
@@ -167,6 +169,7 @@ pub fn passkey_auth_finish(
             match auth::create_session(s, user.id, None, None) {
                 Ok(session_id) => {
                     auth::set_session_cookie_secure(cookies, &session_id, s);
+                    login_alert::record_login(store.inner(), &user, &client);
                     s.audit_log(
                         Some(user.id),
                         Some(&user.display_name),
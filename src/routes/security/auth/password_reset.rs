@@ -8,7 +8,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::rate_limit::RateLimiter;
-use crate::security::{auth, password_reset};
+use crate::security::{auth, password_policy, password_reset, permissions};
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -57,7 +57,7 @@ pub fn forgot_password_submit(
 
     // Rate limit: 3 requests per 15 minutes per IP
     let rate_key = format!("pw_reset:{}", client_ip.0);
-    if !limiter.check_and_record(&rate_key, 3, std::time::Duration::from_secs(15 * 60)) {
+    if !limiter.check(s, &rate_key, 3, std::time::Duration::from_secs(15 * 60)) {
         ctx.insert(
             "error".to_string(),
             "Too many requests. Please try again in 15 minutes.".to_string(),
@@ -74,7 +74,7 @@ pub fn forgot_password_submit(
 
     // Only actually send if the email matches a known user
     if let Some(user) = s.user_get_by_email(form.email.trim()) {
-        if user.is_active() && user.role != "subscriber" {
+        if user.is_active() && permissions::user_can(s, &user, permissions::DASHBOARD_VIEW) {
             match password_reset::create_token(s, &user.email) {
                 Ok(token) => {
                     if let Err(e) = password_reset::send_reset_email(s, &user.email, &token) {
@@ -106,6 +106,16 @@ pub fn reset_password_page(
     );
     ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
     ctx.insert("token".to_string(), token.to_string());
+    ctx.insert(
+        "password_hint".to_string(),
+        password_policy::PasswordPolicy::load(s).summary(),
+    );
+    ctx.insert(
+        "password_min_length".to_string(),
+        password_policy::PasswordPolicy::load(s)
+            .min_length
+            .to_string(),
+    );
     Template::render("admin/reset_password", &ctx)
 }
 
@@ -125,22 +135,29 @@ pub fn reset_password_submit(
         ctx.insert("admin_theme".to_string(), theme.clone());
         ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
         ctx.insert("token".to_string(), token.to_string());
+        ctx.insert(
+            "password_hint".to_string(),
+            password_policy::PasswordPolicy::load(s).summary(),
+        );
+        ctx.insert(
+            "password_min_length".to_string(),
+            password_policy::PasswordPolicy::load(s)
+                .min_length
+                .to_string(),
+        );
         Template::render("admin/reset_password", &ctx)
     };
 
-    if form.password.len() < 8 {
-        return Err(make_err(
-            "Password must be at least 8 characters.",
-            &form.token,
-        ));
+    if let Err(e) = password_policy::check(s, &form.password, &[]) {
+        return Err(make_err(&e, &form.token));
     }
     if form.password != form.confirm_password {
         return Err(make_err("Passwords do not match.", &form.token));
     }
 
     // Verify token
-    let email = match password_reset::verify_token(s, &form.token) {
-        Ok(e) => e,
+    let (email, unlock) = match password_reset::verify_token(s, &form.token) {
+        Ok(v) => v,
         Err(e) => return Err(make_err(&e, &form.token)),
     };
 
@@ -151,7 +168,7 @@ pub fn reset_password_submit(
     };
 
     // Hash and update password
-    let hash = match auth::hash_password(&form.password) {
+    let hash = match auth::hash_password_for(s, &form.password) {
         Ok(h) => h,
         Err(e) => return Err(make_err(&e, &form.token)),
     };
@@ -159,6 +176,23 @@ pub fn reset_password_submit(
     if let Err(e) = s.user_update_password(user.id, &hash) {
         return Err(make_err(&e, &form.token));
     }
+    if unlock && user.status == "locked" {
+        let _ = s.user_unlock(user.id);
+    }
+    // Choosing a password turns password sign-in back on
+    if user.passwordless {
+        let _ = s.user_set_passwordless(user.id, false);
+        s.audit_log(
+            Some(user.id),
+            Some(&user.display_name),
+            "passwordless_disable",
+            Some("user"),
+            Some(user.id),
+            Some(&user.email),
+            Some("Password reset"),
+            None,
+        );
+    }
 
     // Redirect to login with a flash-like param
     Ok(Redirect::to(format!(
@@ -0,0 +1,128 @@
+use rocket::form::Form;
+use rocket::http::CookieJar;
+use rocket::response::Redirect;
+use rocket::State;
+use rocket_dyn_templates::Template;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use std::sync::Arc;
+
+use crate::rate_limit::RateLimiter;
+use crate::security::login_alert::{self, LoginClient};
+use crate::security::{auth, passwordless, permissions};
+use crate::store::Store;
+use crate::AdminSlug;
+
+use super::super::NoCacheTemplate;
+
+#[derive(Debug, FromForm, Deserialize)]
+pub struct RecoveryForm {
+    pub email: String,
+    pub code: String,
+}
+
+/// Sign-in with a recovery code, for passwordless accounts that lost their passkey
+#[get("/login/recovery")]
+pub fn recovery_page(
+    store: &State<Arc<dyn Store>>,
+    admin_slug: &State<AdminSlug>,
+) -> NoCacheTemplate {
+    let s: &dyn Store = &**store.inner();
+    let mut ctx: HashMap<String, String> = HashMap::new();
+    ctx.insert(
+        "admin_theme".to_string(),
+        s.setting_get_or("admin_theme", "dark"),
+    );
+    ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
+    NoCacheTemplate(Template::render("admin/login_recovery", &ctx))
+}
+
+#[post("/login/recovery", data = "<form>")]
+pub fn recovery_submit(
+    form: Form<RecoveryForm>,
+    store: &State<Arc<dyn Store>>,
+    admin_slug: &State<AdminSlug>,
+    limiter: &State<RateLimiter>,
+    cookies: &CookieJar<'_>,
+    client_ip: auth::ClientIp,
+    client: LoginClient,
+) -> Result<Redirect, Template> {
+    let s: &dyn Store = &**store.inner();
+    let ip = &client_ip.0;
+
+    let make_err = |msg: &str| -> Template {
+        let mut ctx = HashMap::new();
+        ctx.insert("error".to_string(), msg.to_string());
+        ctx.insert(
+            "admin_theme".to_string(),
+            s.setting_get_or("admin_theme", "dark"),
+        );
+        ctx.insert("admin_slug".to_string(), admin_slug.get().clone());
+        Template::render("admin/login_recovery", &ctx)
+    };
+
+    // Shares the login attempt budget so codes can't be guessed faster than passwords
+    let max_attempts = s.setting_get_i64("login_rate_limit").max(1) as u64;
+    if !limiter.check(
+        s,
+        &format!("login:{}", ip),
+        max_attempts,
+        std::time::Duration::from_secs(15 * 60),
+    ) {
+        return Err(make_err(
+            "Too many login attempts. Please try again in 15 minutes.",
+        ));
+    }
+
+    let user = match s.user_get_by_email(form.email.trim()) {
+        Some(u) if u.passwordless => u,
+        _ => return Err(make_err("Invalid email or recovery code.")),
+    };
+    if !user.is_active() {
+        return Err(make_err(
+            "This account is suspended or locked. Contact an administrator.",
+        ));
+    }
+    if !permissions::user_can(s, &user, permissions::DASHBOARD_VIEW) {
+        return Err(make_err("Your account does not have admin panel access."));
+    }
+
+    if !passwordless::consume_recovery_code(s, &user, &form.code) {
+        s.audit_log(
+            Some(user.id),
+            Some(&user.display_name),
+            "login_failed",
+            Some("user"),
+            Some(user.id),
+            Some(&user.email),
+            Some("Wrong recovery code"),
+            Some(ip),
+        );
+        return Err(make_err("Invalid email or recovery code."));
+    }
+
+    let _ = s.user_touch_last_login(user.id);
+    match auth::create_session(s, user.id, None, None) {
+        Ok(session_id) => {
+            auth::set_session_cookie_secure(cookies, &session_id, s);
+            login_alert::record_login(store.inner(), &user, &client);
+            s.audit_log(
+                Some(user.id),
+                Some(&user.display_name),
+                "login",
+                Some("user"),
+                Some(user.id),
+                Some(&user.email),
+                Some("Recovery code"),
+                Some(ip),
+            );
+            // Straight to security settings so a new passkey can be registered
+            Ok(Redirect::to(format!(
+                "/{}/settings/security",
+                admin_slug.get()
+            )))
+        }
+        Err(_) => Err(make_err("Session creation failed")),
+    }
+}
@@ -6,9 +6,11 @@ use rocket::State;
 use rocket_dyn_templates::Template;
 use serde::{Deserialize, Serialize};
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::security::auth;
+use crate::security::password_policy::PasswordPolicy;
 use crate::store::Store;
 use crate::AdminSlug;
 
@@ -54,6 +56,8 @@ struct SetupContext {
     mongo_username: String,
     mongo_password: String,
     mongo_auth_db: String,
+    /// Shortest password the form accepts
+    password_min_length: usize,
 }
 
 #[derive(Debug, FromForm, Deserialize)]
@@ -82,6 +86,7 @@ pub fn setup_page(
     if !needs_setup(&**store.inner()) {
         return Err(Redirect::to(format!("/{}/login", admin_slug.get())));
     }
+    let min_length = PasswordPolicy::load(&**store.inner()).min_length;
     let ctx = SetupContext {
         error: None,
         admin_slug: admin_slug.get().clone(),
@@ -96,6 +101,7 @@ pub fn setup_page(
         mongo_username: String::new(),
         mongo_password: String::new(),
         mongo_auth_db: "admin".to_string(),
+        password_min_length: min_length,
     };
     Ok(NoCacheTemplate(Template::render("admin/setup", &ctx)))
 }
@@ -111,6 +117,7 @@ pub fn setup_submit(
     if !needs_setup(s) {
         return Ok(Redirect::to(format!("/{}/login", admin_slug.get())));
     }
+    let min_length = PasswordPolicy::load(s).min_length;
 
     let make_err = |msg: &str, form: &SetupForm| {
         let ctx = SetupContext {
@@ -136,6 +143,7 @@ pub fn setup_submit(
                 .mongo_auth_db
                 .clone()
                 .unwrap_or_else(|| "admin".to_string()),
+            password_min_length: min_length,
         };
         Template::render("admin/setup", &ctx)
     };
@@ -193,8 +201,11 @@ pub fn setup_submit(
     if form.admin_email.trim().is_empty() {
         return Err(make_err("Email is required.", &form));
     }
-    if form.password.len() < 8 {
-        return Err(make_err("Password must be at least 8 characters.", &form));
+    if form.password.chars().count() < min_length {
+        return Err(make_err(
+            &format!("Password must be at least {} characters.", min_length),
+            &form,
+        ));
     }
     if form.password != form.confirm_password {
         return Err(make_err("Passwords do not match.", &form));
@@ -245,7 +256,7 @@ pub fn setup_submit(
     }
 
     // Save
-    let hash = auth::hash_password(&form.password)
+    let hash = auth::hash_password_for(s, &form.password)
         .map_err(|_| make_err("Failed to hash password.", &form))?;
 
     // Create admin user in users table
@@ -286,6 +297,8 @@ pub fn setup_submit(
 
 #[get("/setup")]
 pub fn setup_page_no_db(admin_slug: &State<AdminSlug>) -> NoCacheTemplate {
+    // No settings exist yet, so the default policy applies
+    let min_length = PasswordPolicy::from_settings(&HashMap::new()).min_length;
     let ctx = SetupContext {
         error: None,
         admin_slug: admin_slug.get().clone(),
@@ -300,6 +313,7 @@ pub fn setup_page_no_db(admin_slug: &State<AdminSlug>) -> NoCacheTemplate {
         mongo_username: String::new(),
         mongo_password: String::new(),
         mongo_auth_db: "admin".to_string(),
+        password_min_length: min_length,
     };
     NoCacheTemplate(Template::render("admin/setup", &ctx))
 }
@@ -310,6 +324,7 @@ pub fn setup_submit_no_db(
     form: Form<SetupForm>,
     admin_slug: &State<AdminSlug>,
 ) -> Result<Template, Template> {
+    let min_length = PasswordPolicy::from_settings(&HashMap::new()).min_length;
     let make_err = |msg: &str, form: &SetupForm| {
         let ctx = SetupContext {
             error: Some(msg.to_string()),
@@ -334,6 +349,7 @@ pub fn setup_submit_no_db(
                 .mongo_auth_db
                 .clone()
                 .unwrap_or_else(|| "admin".to_string()),
+            password_min_length: min_length,
         };
         Template::render("admin/setup", &ctx)
     };
@@ -389,8 +405,11 @@ pub fn setup_submit_no_db(
     if form.admin_email.trim().is_empty() {
         return Err(make_err("Email is required.", &form));
     }
-    if form.password.len() < 8 {
-        return Err(make_err("Password must be at least 8 characters.", &form));
+    if form.password.chars().count() < min_length {
+        return Err(make_err(
+            &format!("Password must be at least {} characters.", min_length),
+            &form,
+        ));
     }
     if form.password != form.confirm_password {
         return Err(make_err("Passwords do not match.", &form));
@@ -483,7 +502,7 @@ pub fn setup_submit_no_db(
     }
 
     // ── Create admin user via Store trait ──
-    let hash = match auth::hash_password(&form.password) {
+    let hash = match auth::hash_password_for(&*store, &form.password) {
         Ok(h) => h,
         Err(_) => return Err(make_err("Failed to hash password.", &form)),
     };
@@ -0,0 +1,407 @@
+#![cfg(feature = "multi-site")]
+
+use rocket::form::Form;
+use rocket::http::{Cookie, CookieJar, SameSite};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::content::RawHtml;
+use rocket::response::Redirect;
+use rocket::State;
+use rocket_dyn_templates::Template;
+use std::collections::HashMap;
+
+use crate::rate_limit::RateLimiter;
+use crate::security::{auth, mfa};
+use crate::site::{self, directory, RegistryPool, SiteStoreManager};
+
+/// Directory session cookie, set on every host the session is handed to
+const DIRECTORY_COOKIE: &str = "velocty_directory";
+
+/// Ties the redeem confirmation form to the browser it was shown in
+const REDEEM_CSRF_COOKIE: &str = "velocty_redeem_csrf";
+
+/// Authenticator secret being set up, until a code from it is confirmed
+const MFA_SETUP_COOKIE: &str = "velocty_directory_mfa";
+
+/// The host a request was sent to, without the port
+pub struct RequestHost(pub String);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestHost {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let host = request
+            .headers()
+            .get_one("Host")
+            .unwrap_or("localhost")
+            .split(':')
+            .next()
+            .unwrap_or("localhost")
+            .to_ascii_lowercase();
+        Outcome::Success(RequestHost(host))
+    }
+}
+
+fn set_directory_cookie(cookies: &CookieJar<'_>, token: &str) {
+    let mut cookie = Cookie::new(DIRECTORY_COOKIE, token.to_string());
+    cookie.set_http_only(true);
+    cookie.set_same_site(SameSite::Lax);
+    cookie.set_path("/");
+    cookies.add_private(cookie);
+}
+
+fn current_session(
+    registry: &RegistryPool,
+    cookies: &CookieJar<'_>,
+) -> Option<(String, directory::DirectoryUser)> {
+    let token = cookies.get_private(DIRECTORY_COOKIE)?.value().to_string();
+    let user = directory::session_user(registry, &token)?;
+    Some((token, user))
+}
+
+fn account_page(registry: &RegistryPool, cookies: &CookieJar<'_>, error: Option<&str>) -> Template {
+    Template::render("super/account", account_context(registry, cookies, error))
+}
+
+fn account_context(
+    registry: &RegistryPool,
+    cookies: &CookieJar<'_>,
+    error: Option<&str>,
+) -> HashMap<String, serde_json::Value> {
+    let mut ctx = HashMap::new();
+    if let Some((_, user)) = current_session(registry, cookies) {
+        ctx.insert(
+            "mfa_enabled".to_string(),
+            serde_json::Value::Bool(!user.mfa_secret.is_empty()),
+        );
+        ctx.insert(
+            "grants".to_string(),
+            serde_json::to_value(directory::list_grants(registry, user.id)).unwrap_or_default(),
+        );
+        ctx.insert(
+            "user".to_string(),
+            serde_json::to_value(&user).unwrap_or_default(),
+        );
+    }
+    if let Some(e) = error {
+        ctx.insert(
+            "error".to_string(),
+            serde_json::Value::String(e.to_string()),
+        );
+    }
+    ctx
+}
+
+fn account_error(msg: &str) -> Redirect {
+    Redirect::to(format!(
+        "/super/account?error={}",
+        url::form_urlencoded::byte_serialize(msg.as_bytes()).collect::<String>()
+    ))
+}
+
+// ── Shared account sign-in ───────────────────────────────────
+
+#[get("/account?<error>")]
+pub fn account(
+    error: Option<String>,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Template {
+    account_page(
+        registry,
+        cookies,
+        error.as_deref().filter(|e| !e.is_empty()),
+    )
+}
+
+#[derive(Debug, FromForm)]
+pub struct AccountLoginForm {
+    pub email: String,
+    pub password: String,
+    pub code: Option<String>,
+}
+
+#[post("/account/login", data = "<form>")]
+pub fn account_login(
+    form: Form<AccountLoginForm>,
+    registry: &State<RegistryPool>,
+    stores: &State<SiteStoreManager>,
+    limiter: &State<RateLimiter>,
+    cookies: &CookieJar<'_>,
+    client_ip: auth::ClientIp,
+) -> Result<Redirect, Template> {
+    let rate_key = format!("directory_login:{}", auth::hash_ip(&client_ip.0));
+    if !limiter.check_registry(
+        registry,
+        &rate_key,
+        10,
+        std::time::Duration::from_secs(15 * 60),
+    ) {
+        return Err(account_page(
+            registry,
+            cookies,
+            Some("Too many sign-in attempts. Please try again in 15 minutes."),
+        ));
+    }
+    let user = match directory::authenticate(registry, &form.email, &form.password) {
+        Some(u) => u,
+        None => return Err(account_page(registry, cookies, Some("Invalid credentials"))),
+    };
+    // A code from the account's authenticator, or from one set up on any
+    // site account it signs in to
+    let secrets = directory::second_factor_secrets(registry, stores, &user);
+    let code = form.code.as_deref().unwrap_or("").trim();
+    if !secrets.is_empty() && !secrets.iter().any(|s| mfa::verify_code(s, code)) {
+        return Err(account_page(
+            registry,
+            cookies,
+            Some("Enter the code from your authenticator app"),
+        ));
+    }
+    match directory::create_session(registry, user.id) {
+        Ok(token) => {
+            set_directory_cookie(cookies, &token);
+            Ok(Redirect::to("/super/account"))
+        }
+        Err(_) => Err(account_page(
+            registry,
+            cookies,
+            Some("Session creation failed"),
+        )),
+    }
+}
+
+/// Signs out of the directory session and the site sessions it signed in
+/// on every site it was handed to
+#[get("/account/logout")]
+pub fn account_logout(
+    registry: &State<RegistryPool>,
+    stores: &State<SiteStoreManager>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if let Some(c) = cookies.get_private(DIRECTORY_COOKIE) {
+        let _ = directory::destroy_session(registry, stores, c.value());
+    }
+    cookies.remove_private(Cookie::from(DIRECTORY_COOKIE));
+    Redirect::to("/super/account")
+}
+
+// ── Authenticator app ────────────────────────────────────────
+
+/// Start setting up an authenticator app: the page shows the new secret,
+/// which is only saved once a code from it is confirmed
+#[post("/account/mfa/setup")]
+pub fn account_mfa_setup(
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Result<Template, Redirect> {
+    let (_, user) = current_session(registry, cookies).ok_or(Redirect::to("/super/account"))?;
+    let secret = mfa::generate_secret();
+    let mut cookie = Cookie::new(MFA_SETUP_COOKIE, secret.clone());
+    cookie.set_http_only(true);
+    cookie.set_same_site(SameSite::Strict);
+    cookie.set_path("/super/account");
+    cookie.set_max_age(rocket::time::Duration::minutes(10));
+    cookies.add_private(cookie);
+
+    let mut ctx = account_context(registry, cookies, None);
+    ctx.insert(
+        "mfa_setup".to_string(),
+        serde_json::json!({
+            "secret": secret,
+            "qr": mfa::qr_data_uri(&secret, "Velocty", &user.email).unwrap_or_default(),
+        }),
+    );
+    Ok(Template::render("super/account", &ctx))
+}
+
+#[derive(Debug, FromForm)]
+pub struct MfaCodeForm {
+    pub code: String,
+}
+
+#[post("/account/mfa/confirm", data = "<form>")]
+pub fn account_mfa_confirm(
+    form: Form<MfaCodeForm>,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    let (_, user) = match current_session(registry, cookies) {
+        Some(s) => s,
+        None => return Redirect::to("/super/account"),
+    };
+    let pending = cookies
+        .get_private(MFA_SETUP_COOKIE)
+        .map(|c| c.value().to_string());
+    let secret = match pending {
+        Some(s) if mfa::verify_code(&s, form.code.trim()) => s,
+        _ => return account_error("That code didn't match; start the setup again"),
+    };
+    cookies.remove_private(Cookie::build(MFA_SETUP_COOKIE).path("/super/account"));
+    match directory::set_mfa_secret(registry, user.id, &secret) {
+        Ok(()) => Redirect::to("/super/account"),
+        Err(e) => account_error(&e),
+    }
+}
+
+/// Turn the authenticator app off, with a current code from it
+#[post("/account/mfa/disable", data = "<form>")]
+pub fn account_mfa_disable(
+    form: Form<MfaCodeForm>,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    let (_, user) = match current_session(registry, cookies) {
+        Some(s) => s,
+        None => return Redirect::to("/super/account"),
+    };
+    if user.mfa_secret.is_empty() || !mfa::verify_code(&user.mfa_secret, form.code.trim()) {
+        return account_error("That code didn't match");
+    }
+    match directory::set_mfa_secret(registry, user.id, "") {
+        Ok(()) => Redirect::to("/super/account"),
+        Err(e) => account_error(&e),
+    }
+}
+
+/// Hand the session over to a site on its own host
+#[get("/account/open/<site_id>")]
+pub fn account_open(
+    site_id: i64,
+    registry: &State<RegistryPool>,
+    stores: &State<SiteStoreManager>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    let (token, _) = match current_session(registry, cookies) {
+        Some(s) => s,
+        None => return Redirect::to("/super/account"),
+    };
+    let target = match site::find_site_by_id(registry, site_id) {
+        Some(s) if s.status == "active" => s,
+        _ => return Redirect::to("/super/account?error=This+site+is+unavailable"),
+    };
+    let ticket = match directory::create_ticket(registry, &token, site_id) {
+        Ok(t) => t,
+        Err(_) => return Redirect::to("/super/account?error=No+access+to+this+site"),
+    };
+    let base = stores
+        .get_store(&target.slug)
+        .map(|s| s.setting_get_or("site_url", ""))
+        .ok()
+        .filter(|u| u.starts_with("http"))
+        .unwrap_or_else(|| format!("https://{}", target.hostname));
+    Redirect::to(format!(
+        "{}/super/account/redeem?ticket={}",
+        base.trim_end_matches('/'),
+        ticket
+    ))
+}
+
+/// Land on the site's host with a handoff ticket. Nothing is signed in
+/// yet: the page names the account and posts the ticket back with a token
+/// bound to this browser, so a link carrying someone else's ticket can't
+/// sign a visitor in to that account unnoticed.
+#[get("/account/redeem?<ticket>")]
+pub fn account_redeem_confirm(
+    ticket: &str,
+    host: RequestHost,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Result<RawHtml<String>, Redirect> {
+    let target = match site::find_site_for_host(registry, &host.0) {
+        Some(s) if s.status == "active" => s,
+        _ => return Err(account_error("This host isn't an active site")),
+    };
+    let user = directory::peek_ticket(registry, ticket, target.id)
+        .ok_or_else(|| account_error("This sign-in link has expired"))?;
+    let csrf = uuid::Uuid::new_v4().simple().to_string();
+    let mut cookie = Cookie::new(REDEEM_CSRF_COOKIE, csrf.clone());
+    cookie.set_http_only(true);
+    cookie.set_same_site(SameSite::Strict);
+    cookie.set_path("/super/account");
+    cookies.add_private(cookie);
+
+    let esc = crate::render::html_escape;
+    Ok(RawHtml(format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Sign in</title></head><body style=\"font-family:sans-serif;background:#1a1a2e;color:#e0e0e0;text-align:center;padding:80px\"><form method=\"post\" action=\"/super/account/redeem\"><p>Sign in to <strong>{}</strong> as <strong>{}</strong>?</p><input type=\"hidden\" name=\"ticket\" value=\"{}\"><input type=\"hidden\" name=\"csrf\" value=\"{}\"><button type=\"submit\" style=\"background:#E8913A;color:#fff;border:0;border-radius:6px;padding:10px 24px;font-size:15px;cursor:pointer\">Continue</button></form><p style=\"font-size:13px;opacity:.6\">Not you? Close this page.</p></body></html>",
+        esc(&target.display_name),
+        esc(&user.email),
+        esc(ticket),
+        esc(&csrf)
+    )))
+}
+
+#[derive(Debug, FromForm)]
+pub struct RedeemForm {
+    pub ticket: String,
+    pub csrf: String,
+}
+
+/// Redeem a handoff ticket on the site's host: the directory session
+/// becomes valid here and the account signs in to the site admin.
+#[post("/account/redeem", data = "<form>")]
+pub fn account_redeem(
+    form: Form<RedeemForm>,
+    host: RequestHost,
+    registry: &State<RegistryPool>,
+    stores: &State<SiteStoreManager>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    let fail = account_error;
+    let expected = cookies
+        .get_private(REDEEM_CSRF_COOKIE)
+        .map(|c| c.value().to_string());
+    cookies.remove_private(Cookie::build(REDEEM_CSRF_COOKIE).path("/super/account"));
+    let csrf_ok = expected.is_some_and(|e| {
+        !e.is_empty() && crate::security::constant_time_eq(e.as_bytes(), form.csrf.as_bytes())
+    });
+    if !csrf_ok {
+        return fail("This sign-in link wasn't opened in this browser");
+    }
+    let target = match site::find_site_for_host(registry, &host.0) {
+        Some(s) if s.status == "active" => s,
+        _ => return fail("This host isn't an active site"),
+    };
+    let (token, user, role) = match directory::redeem_ticket(registry, &form.ticket, target.id) {
+        Ok(r) => r,
+        Err(e) => return fail(&e),
+    };
+    let store = match stores.get_store(&target.slug) {
+        Ok(s) => s,
+        Err(e) => return fail(&e),
+    };
+    let at_limit = site::plan::site_plan(registry, target.id)
+        .is_some_and(|p| !p.has_room_for_user(store.user_count()));
+    if at_limit && store.user_get_by_email(&user.email).is_none() {
+        return fail("This site has reached its plan's user limit");
+    }
+    let local = match directory::sync_site_user(registry, target.id, &*store, &user, &role) {
+        Ok(u) => u,
+        Err(e) => return fail(&e),
+    };
+    let session_id = match auth::create_session(&*store, local.id, None, None) {
+        Ok(id) => id,
+        Err(e) => return fail(&e),
+    };
+    if let Err(e) = directory::record_site_session(registry, &token, target.id, &session_id) {
+        let _ = store.session_delete(&session_id);
+        return fail(&e);
+    }
+    let _ = store.user_touch_last_login(local.id);
+    store.audit_log(
+        Some(local.id),
+        Some(&local.display_name),
+        "login",
+        Some("user"),
+        Some(local.id),
+        Some(&local.email),
+        Some("Shared account"),
+        None,
+    );
+    set_directory_cookie(cookies, &token);
+    auth::set_session_cookie_secure(cookies, &session_id, &*store);
+
+    // The confirmation was posted from a page on this host, so the
+    // SameSite=Strict session cookie goes along with this redirect
+    Redirect::to(format!("/{}", store.setting_get_or("admin_slug", "admin")))
+}
@@ -0,0 +1,195 @@
+#![cfg(feature = "multi-site")]
+
+use rocket::form::Form;
+use rocket::http::CookieJar;
+use rocket::response::Redirect;
+use rocket::State;
+use rocket_dyn_templates::Template;
+use std::collections::HashMap;
+
+use super::auth::is_super_authenticated;
+use crate::routes::admin::health::ZipFileDownload;
+use crate::site::{self, backup, RegistryPool};
+
+// ── Site Backups ─────────────────────────────────────────────
+
+fn back(id: i64, result: Result<(), String>, done: &str) -> Redirect {
+    let (key, msg) = match result {
+        Ok(()) => ("notice", done.to_string()),
+        Err(e) => ("error", e),
+    };
+    Redirect::to(format!(
+        "/super/sites/{}/backups?{}={}",
+        id,
+        key,
+        url::form_urlencoded::byte_serialize(msg.as_bytes()).collect::<String>()
+    ))
+}
+
+fn human_size(bytes: i64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{} KB", (bytes + 1023) / 1024)
+    }
+}
+
+#[get("/sites/<id>/backups?<notice>&<error>")]
+pub fn backups_page(
+    id: i64,
+    notice: Option<String>,
+    error: Option<String>,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Result<Template, Redirect> {
+    if !is_super_authenticated(registry, cookies) {
+        return Err(Redirect::to("/super/login"));
+    }
+    let site = site::find_site_by_id(registry, id).ok_or_else(|| Redirect::to("/super/"))?;
+
+    let backups: Vec<serde_json::Value> = backup::list(registry, id)
+        .into_iter()
+        .map(|b| {
+            let mut v = serde_json::to_value(&b).unwrap_or_default();
+            v["size"] = serde_json::Value::String(human_size(b.size_bytes));
+            v
+        })
+        .collect();
+
+    let mut ctx = HashMap::new();
+    ctx.insert("backups".to_string(), serde_json::Value::Array(backups));
+    ctx.insert(
+        "schedule".to_string(),
+        serde_json::to_value(backup::schedule(registry, id)).unwrap_or_default(),
+    );
+    ctx.insert(
+        "site".to_string(),
+        serde_json::to_value(&site).unwrap_or_default(),
+    );
+    if let Some(status) = backup::restore_status(id) {
+        ctx.insert(
+            "restore".to_string(),
+            serde_json::to_value(status).unwrap_or_default(),
+        );
+    }
+    if let Some(n) = notice.filter(|n| !n.is_empty()) {
+        ctx.insert("notice".to_string(), serde_json::Value::String(n));
+    }
+    if let Some(e) = error.filter(|e| !e.is_empty()) {
+        ctx.insert("error".to_string(), serde_json::Value::String(e));
+    }
+    Ok(Template::render("super/site_backups", &ctx))
+}
+
+#[post("/sites/<id>/backups")]
+pub fn create_backup(id: i64, registry: &State<RegistryPool>, cookies: &CookieJar<'_>) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    let site = match site::find_site_by_id(registry, id) {
+        Some(s) => s,
+        None => return Redirect::to("/super/"),
+    };
+    back(
+        id,
+        backup::create(registry, &site, "manual").map(|_| ()),
+        "Backup created.",
+    )
+}
+
+#[get("/sites/<id>/backups/<backup_id>/download")]
+pub fn download_backup(
+    id: i64,
+    backup_id: i64,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Result<ZipFileDownload, Redirect> {
+    if !is_super_authenticated(registry, cookies) {
+        return Err(Redirect::to("/super/login"));
+    }
+    let site = site::find_site_by_id(registry, id).ok_or_else(|| Redirect::to("/super/"))?;
+    let b = backup::find(registry, backup_id)
+        .filter(|b| b.site_id == id)
+        .ok_or_else(|| Redirect::to(format!("/super/sites/{}/backups", id)))?;
+    let file = std::fs::File::open(backup::file_path(&site, &b))
+        .map_err(|_| back(id, Err("The backup file is missing".into()), ""))?;
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    Ok(ZipFileDownload {
+        filename: format!(
+            "{}_{}",
+            site.hostname
+                .replace(|c: char| !c.is_ascii_alphanumeric() && c != '.', "-"),
+            b.file_name
+        ),
+        file,
+        len,
+    })
+}
+
+#[post("/sites/<id>/backups/<backup_id>/restore")]
+pub fn restore_backup(
+    id: i64,
+    backup_id: i64,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    let site = match site::find_site_by_id(registry, id) {
+        Some(s) => s,
+        None => return Redirect::to("/super/"),
+    };
+    back(
+        id,
+        backup::restore_in_background(registry, &site, backup_id),
+        "Restore started. The current state is backed up first; refresh this page to follow it.",
+    )
+}
+
+#[post("/sites/<id>/backups/<backup_id>/delete")]
+pub fn delete_backup(
+    id: i64,
+    backup_id: i64,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    let site = match site::find_site_by_id(registry, id) {
+        Some(s) => s,
+        None => return Redirect::to("/super/"),
+    };
+    back(
+        id,
+        backup::delete(registry, &site, backup_id),
+        "Backup deleted.",
+    )
+}
+
+#[derive(Debug, FromForm)]
+pub struct ScheduleForm {
+    pub interval_hours: i64,
+    pub keep: i64,
+}
+
+#[post("/sites/<id>/backups/schedule", data = "<form>")]
+pub fn save_schedule(
+    id: i64,
+    form: Form<ScheduleForm>,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    if site::find_site_by_id(registry, id).is_none() {
+        return Redirect::to("/super/");
+    }
+    back(
+        id,
+        backup::set_schedule(registry, id, form.interval_hours, form.keep),
+        "Schedule saved.",
+    )
+}
@@ -7,13 +7,14 @@ use rocket_dyn_templates::Template;
 use std::collections::HashMap;
 
 use super::auth::is_super_authenticated;
-use crate::site::{self, RegistryPool};
+use crate::site::{self, usage, RegistryPool, SiteStoreManager};
 
 // ── Dashboard ────────────────────────────────────────────────
 
 #[get("/")]
 pub fn dashboard(
     registry: &State<RegistryPool>,
+    stores: &State<SiteStoreManager>,
     cookies: &CookieJar<'_>,
 ) -> Result<Template, Redirect> {
     if !is_super_authenticated(registry, cookies) {
@@ -21,10 +22,29 @@ pub fn dashboard(
     }
 
     let sites = site::list_sites(registry);
+    let mut per_site = Vec::new();
+    let rows: Vec<serde_json::Value> = sites
+        .iter()
+        .map(|s| {
+            let mut v = serde_json::to_value(s).unwrap_or_default();
+            if let Ok(store) = stores.get_store(&s.slug) {
+                let u = usage::gather(s, &*store);
+                v["usage"] = serde_json::to_value(&u).unwrap_or_default();
+                per_site.push(u);
+            }
+            v
+        })
+        .collect();
+
     let mut ctx = HashMap::new();
+    ctx.insert("sites".to_string(), serde_json::Value::Array(rows));
+    ctx.insert(
+        "totals".to_string(),
+        serde_json::to_value(usage::totals(&per_site)).unwrap_or_default(),
+    );
     ctx.insert(
-        "sites".to_string(),
-        serde_json::to_value(&sites).unwrap_or_default(),
+        "period_days".to_string(),
+        serde_json::Value::from(usage::PERIOD_DAYS),
     );
     Ok(Template::render("super/dashboard", &ctx))
 }
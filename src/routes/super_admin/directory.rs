@@ -0,0 +1,169 @@
+#![cfg(feature = "multi-site")]
+
+use rocket::form::Form;
+use rocket::http::CookieJar;
+use rocket::response::Redirect;
+use rocket::State;
+use rocket_dyn_templates::Template;
+use std::collections::HashMap;
+
+use super::auth::is_super_authenticated;
+use crate::site::{self, directory, RegistryPool, SiteStoreManager};
+
+// ── Shared User Directory ────────────────────────────────────
+
+#[get("/users?<error>")]
+pub fn users_page(
+    error: Option<String>,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Result<Template, Redirect> {
+    if !is_super_authenticated(registry, cookies) {
+        return Err(Redirect::to("/super/login"));
+    }
+    let users: Vec<serde_json::Value> = directory::list_users(registry)
+        .into_iter()
+        .map(|u| {
+            let grants = directory::list_grants(registry, u.id);
+            let mut v = serde_json::to_value(&u).unwrap_or_default();
+            v["grants"] = serde_json::to_value(grants).unwrap_or_default();
+            v
+        })
+        .collect();
+
+    let mut ctx = HashMap::new();
+    ctx.insert("users".to_string(), serde_json::Value::Array(users));
+    ctx.insert(
+        "sites".to_string(),
+        serde_json::to_value(site::list_sites(registry)).unwrap_or_default(),
+    );
+    ctx.insert(
+        "roles".to_string(),
+        serde_json::to_value(directory::GRANTABLE_ROLES).unwrap_or_default(),
+    );
+    if let Some(error) = error.filter(|e| !e.is_empty()) {
+        ctx.insert("error".to_string(), serde_json::Value::String(error));
+    }
+    Ok(Template::render("super/users", &ctx))
+}
+
+fn back(result: Result<(), String>) -> Redirect {
+    match result {
+        Ok(()) => Redirect::to("/super/users"),
+        Err(e) => Redirect::to(format!(
+            "/super/users?error={}",
+            url::form_urlencoded::byte_serialize(e.as_bytes()).collect::<String>()
+        )),
+    }
+}
+
+#[derive(Debug, FromForm)]
+pub struct DirectoryUserForm {
+    pub email: String,
+    pub display_name: String,
+    pub password: String,
+}
+
+#[post("/users", data = "<form>")]
+pub fn create_user(
+    form: Form<DirectoryUserForm>,
+    registry: &State<RegistryPool>,
+    stores: &State<SiteStoreManager>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    back(
+        directory::create_user(
+            registry,
+            stores,
+            &form.email,
+            &form.display_name,
+            &form.password,
+        )
+        .map(|_| ()),
+    )
+}
+
+#[derive(Debug, FromForm)]
+pub struct PasswordForm {
+    pub password: String,
+}
+
+#[post("/users/<id>/password", data = "<form>")]
+pub fn set_password(
+    id: i64,
+    form: Form<PasswordForm>,
+    registry: &State<RegistryPool>,
+    stores: &State<SiteStoreManager>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    back(directory::set_password(
+        registry,
+        stores,
+        id,
+        &form.password,
+    ))
+}
+
+#[post("/users/<id>/delete")]
+pub fn delete_user(
+    id: i64,
+    registry: &State<RegistryPool>,
+    stores: &State<SiteStoreManager>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    back(directory::delete_user(registry, stores, id))
+}
+
+#[derive(Debug, FromForm)]
+pub struct GrantForm {
+    pub site_id: i64,
+    pub role: String,
+}
+
+#[post("/users/<id>/grants", data = "<form>")]
+pub fn grant_site(
+    id: i64,
+    form: Form<GrantForm>,
+    registry: &State<RegistryPool>,
+    stores: &State<SiteStoreManager>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    if directory::find_user(registry, id).is_none()
+        || site::find_site_by_id(registry, form.site_id).is_none()
+    {
+        return Redirect::to("/super/users");
+    }
+    back(
+        directory::grant(registry, id, form.site_id, &form.role)
+            .and_then(|_| directory::apply_grant(registry, stores, id, form.site_id)),
+    )
+}
+
+#[post("/users/<id>/grants/<site_id>/revoke")]
+pub fn revoke_site(
+    id: i64,
+    site_id: i64,
+    registry: &State<RegistryPool>,
+    stores: &State<SiteStoreManager>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    if let Some(user) = directory::find_user(registry, id) {
+        directory::lock_site_user(registry, stores, &user, site_id);
+    }
+    back(directory::revoke(registry, id, site_id))
+}
@@ -195,3 +195,26 @@ pub fn tool_export_content(
         Err(e) => Json(json!({ "ok": false, "message": e })),
     }
 }
+
+#[post("/health/tool/<site_id>/backup")]
+pub fn tool_backup(
+    site_id: i64,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Json<Value> {
+    if !is_super_authenticated(registry, cookies) {
+        return Json(json!({ "ok": false, "message": "Unauthorized" }));
+    }
+    let site = match site::find_site_by_id(registry, site_id) {
+        Some(s) => s,
+        None => return Json(json!({ "ok": false, "message": "Site not found" })),
+    };
+    match crate::site::backup::create(registry, &site, "manual") {
+        Ok(b) => Json(json!({
+            "ok": true,
+            "message": format!("Backup created ({} KB)", b.size_bytes / 1024),
+            "details": format!("Download: /super/sites/{}/backups/{}/download", site.id, b.id),
+        })),
+        Err(e) => Json(json!({ "ok": false, "message": e })),
+    }
+}
@@ -1,8 +1,12 @@
 #![cfg(feature = "multi-site")]
 
+pub mod account;
 pub mod auth;
+pub mod backups;
 pub mod dashboard;
+pub mod directory;
 pub mod health;
+pub mod plans;
 pub mod sites;
 
 pub fn routes() -> Vec<rocket::Route> {
@@ -23,10 +27,48 @@ pub fn routes() -> Vec<rocket::Route> {
         health::tool_orphan_delete,
         health::tool_unused_tags,
         health::tool_export_content,
+        health::tool_backup,
         sites::new_site_page,
         sites::new_site_submit,
         sites::edit_site_page,
         sites::edit_site_submit,
         sites::delete_site,
+        sites::clone_site_page,
+        sites::clone_site_submit,
+        sites::add_domain,
+        sites::remove_domain,
+        sites::issue_certificate,
+        backups::backups_page,
+        backups::create_backup,
+        backups::download_backup,
+        backups::restore_backup,
+        backups::delete_backup,
+        backups::save_schedule,
+        plans::plans_page,
+        plans::create_plan,
+        plans::update_plan,
+        plans::delete_plan,
+        plans::assign_plan,
+        plans::plan_unavailable,
+        directory::users_page,
+        directory::create_user,
+        directory::set_password,
+        directory::delete_user,
+        directory::grant_site,
+        directory::revoke_site,
+        account::account,
+        account::account_login,
+        account::account_logout,
+        account::account_mfa_setup,
+        account::account_mfa_confirm,
+        account::account_mfa_disable,
+        account::account_open,
+        account::account_redeem_confirm,
+        account::account_redeem,
     ]
 }
+
+/// Routes served on every host rather than under `/super`
+pub fn root_routes() -> Vec<rocket::Route> {
+    routes![sites::acme_challenge]
+}
@@ -0,0 +1,173 @@
+#![cfg(feature = "multi-site")]
+
+use rocket::form::Form;
+use rocket::http::{CookieJar, Status};
+use rocket::response::status::Custom;
+use rocket::response::Redirect;
+use rocket::serde::json::Json;
+use rocket::State;
+use rocket_dyn_templates::Template;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use super::auth::is_super_authenticated;
+use crate::site::{self, plan, RegistryPool};
+
+// ── Plans ────────────────────────────────────────────────────
+
+#[get("/plans?<error>")]
+pub fn plans_page(
+    error: Option<String>,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Result<Template, Redirect> {
+    if !is_super_authenticated(registry, cookies) {
+        return Err(Redirect::to("/super/login"));
+    }
+    let counts = plan::site_counts(registry);
+    let plans: Vec<Value> = plan::list_plans(registry)
+        .into_iter()
+        .map(|p| {
+            let mut v = serde_json::to_value(&p).unwrap_or_default();
+            v["site_count"] = json!(counts.get(&p.id).copied().unwrap_or(0));
+            v
+        })
+        .collect();
+
+    let mut ctx = HashMap::new();
+    ctx.insert("plans".to_string(), Value::Array(plans));
+    ctx.insert(
+        "features".to_string(),
+        json!(plan::FEATURES
+            .iter()
+            .map(|(key, label)| json!({"key": key, "label": label}))
+            .collect::<Vec<_>>()),
+    );
+    if let Some(error) = error.filter(|e| !e.is_empty()) {
+        ctx.insert("error".to_string(), Value::String(error));
+    }
+    Ok(Template::render("super/plans", &ctx))
+}
+
+fn back(result: Result<(), String>) -> Redirect {
+    match result {
+        Ok(()) => Redirect::to("/super/plans"),
+        Err(e) => Redirect::to(format!(
+            "/super/plans?error={}",
+            url::form_urlencoded::byte_serialize(e.as_bytes()).collect::<String>()
+        )),
+    }
+}
+
+#[derive(Debug, FromForm)]
+pub struct PlanForm {
+    pub name: String,
+    pub commerce: Option<String>,
+    pub ai: Option<String>,
+    pub newsletter: Option<String>,
+    pub max_users: Option<i64>,
+}
+
+#[post("/plans", data = "<form>")]
+pub fn create_plan(
+    form: Form<PlanForm>,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    back(
+        plan::create_plan(
+            registry,
+            &form.name,
+            form.commerce.is_some(),
+            form.ai.is_some(),
+            form.newsletter.is_some(),
+            form.max_users.unwrap_or(0),
+        )
+        .map(|_| ()),
+    )
+}
+
+#[post("/plans/<id>", data = "<form>")]
+pub fn update_plan(
+    id: i64,
+    form: Form<PlanForm>,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    back(plan::update_plan(
+        registry,
+        id,
+        &form.name,
+        form.commerce.is_some(),
+        form.ai.is_some(),
+        form.newsletter.is_some(),
+        form.max_users.unwrap_or(0),
+    ))
+}
+
+#[post("/plans/<id>/delete")]
+pub fn delete_plan(id: i64, registry: &State<RegistryPool>, cookies: &CookieJar<'_>) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    back(plan::delete_plan(registry, id))
+}
+
+#[derive(Debug, FromForm)]
+pub struct AssignPlanForm {
+    /// Empty for no plan
+    pub plan_id: Option<i64>,
+}
+
+#[post("/sites/<id>/plan", data = "<form>")]
+pub fn assign_plan(
+    id: i64,
+    form: Form<AssignPlanForm>,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    if site::find_site_by_id(registry, id).is_none() {
+        return Redirect::to("/super/");
+    }
+    match plan::assign_plan(registry, id, form.plan_id) {
+        Ok(()) => Redirect::to(format!("/super/sites/{}", id)),
+        Err(e) => Redirect::to(format!(
+            "/super/sites/{}?error={}",
+            id,
+            url::form_urlencoded::byte_serialize(e.as_bytes()).collect::<String>()
+        )),
+    }
+}
+
+/// Where `SiteResolver` sends requests for features outside a site's plan
+#[get("/plan/unavailable/<feature>?<api>")]
+pub fn plan_unavailable(feature: &str, api: bool) -> Custom<Result<Json<Value>, Template>> {
+    let label = plan::feature_label(feature);
+    let message = if feature == plan::USERS_FEATURE {
+        "This site has reached its plan's user limit.".to_string()
+    } else {
+        format!("{} isn't included in this site's plan.", label)
+    };
+    if api {
+        return Custom(
+            Status::Forbidden,
+            Ok(Json(json!({"success": false, "error": message}))),
+        );
+    }
+    let mut ctx = HashMap::new();
+    ctx.insert("feature".to_string(), label.to_string());
+    ctx.insert("message".to_string(), message);
+    Custom(
+        Status::Forbidden,
+        Err(Template::render("super/plan_unavailable", &ctx)),
+    )
+}
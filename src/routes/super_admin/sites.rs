@@ -56,16 +56,14 @@ pub fn new_site_submit(
         return Err(Template::render("super/site_new", &ctx));
     }
 
-    // Use empty settings — email will fail gracefully if no provider is configured.
-    // The temp password is shown in the UI as a fallback.
-    let email_settings = HashMap::new();
-
+    // The super admin has no email providers of its own, so no welcome email is sent.
+    // The temp password is shown in the UI instead.
     match site::create_site(
         registry,
         form.hostname.trim(),
         form.display_name.trim(),
         form.admin_email.trim(),
-        &email_settings,
+        None,
     ) {
         Ok((_site, temp_password, email_sent)) => {
             if email_sent {
@@ -100,9 +98,10 @@ pub fn new_site_submit(
 
 // ── Edit Site ────────────────────────────────────────────────
 
-#[get("/sites/<id>")]
+#[get("/sites/<id>?<error>")]
 pub fn edit_site_page(
     id: i64,
+    error: Option<String>,
     registry: &State<RegistryPool>,
     cookies: &CookieJar<'_>,
 ) -> Result<Template, Redirect> {
@@ -116,10 +115,30 @@ pub fn edit_site_page(
     };
 
     let mut ctx = HashMap::new();
+    ctx.insert(
+        "domains".to_string(),
+        serde_json::to_value(site::list_domains(registry, site.id)).unwrap_or_default(),
+    );
     ctx.insert(
         "site".to_string(),
         serde_json::to_value(&site).unwrap_or_default(),
     );
+    ctx.insert(
+        "acme_enabled".to_string(),
+        serde_json::Value::Bool(site::acme::config().enabled),
+    );
+    ctx.insert(
+        "plans".to_string(),
+        serde_json::to_value(site::plan::list_plans(registry)).unwrap_or_default(),
+    );
+    ctx.insert(
+        "plan_id".to_string(),
+        serde_json::to_value(site::plan::site_plan(registry, site.id).map(|p| p.id))
+            .unwrap_or_default(),
+    );
+    if let Some(error) = error.filter(|e| !e.is_empty()) {
+        ctx.insert("error".to_string(), serde_json::Value::String(error));
+    }
     Ok(Template::render("super/site_edit", &ctx))
 }
 
@@ -154,3 +173,174 @@ pub fn delete_site(id: i64, registry: &State<RegistryPool>, cookies: &CookieJar<
     let _ = site::delete_site(registry, id);
     Redirect::to("/super/")
 }
+
+// ── Clone Site ───────────────────────────────────────────────
+
+fn clone_page(site: &site::Site, error: Option<&str>) -> Template {
+    let mut ctx = HashMap::new();
+    ctx.insert(
+        "site".to_string(),
+        serde_json::to_value(site).unwrap_or_default(),
+    );
+    if let Some(e) = error {
+        ctx.insert(
+            "error".to_string(),
+            serde_json::Value::String(e.to_string()),
+        );
+    }
+    Template::render("super/site_clone", &ctx)
+}
+
+#[get("/sites/<id>/clone")]
+pub fn clone_site_page(
+    id: i64,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Result<Template, Redirect> {
+    if !is_super_authenticated(registry, cookies) {
+        return Err(Redirect::to("/super/login"));
+    }
+    match site::find_site_by_id(registry, id) {
+        Some(s) => Ok(clone_page(&s, None)),
+        None => Err(Redirect::to("/super/")),
+    }
+}
+
+#[derive(Debug, FromForm)]
+pub struct CloneSiteForm {
+    pub hostname: String,
+    pub display_name: String,
+    pub admin_email: String,
+    pub include_content: Option<String>,
+}
+
+#[post("/sites/<id>/clone", data = "<form>")]
+pub fn clone_site_submit(
+    id: i64,
+    form: Form<CloneSiteForm>,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Result<Redirect, Template> {
+    if !is_super_authenticated(registry, cookies) {
+        return Ok(Redirect::to("/super/login"));
+    }
+    let source = match site::find_site_by_id(registry, id) {
+        Some(s) => s,
+        None => return Ok(Redirect::to("/super/")),
+    };
+
+    if form.hostname.trim().is_empty()
+        || form.display_name.trim().is_empty()
+        || form.admin_email.trim().is_empty()
+    {
+        return Err(clone_page(&source, Some("All fields are required.")));
+    }
+    if !form.admin_email.contains('@') {
+        return Err(clone_page(
+            &source,
+            Some("Please enter a valid email address."),
+        ));
+    }
+
+    match site::clone_site(
+        registry,
+        id,
+        form.hostname.trim(),
+        form.display_name.trim(),
+        form.admin_email.trim(),
+        form.include_content.is_some(),
+        None,
+    ) {
+        Ok((_site, Some(temp_password), false)) => {
+            // Email failed — show the temp password so the super admin can share it manually
+            let mut ctx = HashMap::new();
+            ctx.insert("success".to_string(), format!(
+                "Site cloned. Email delivery failed — please share these credentials manually: Email: {} / Temporary password: {}",
+                form.admin_email.trim(),
+                temp_password,
+            ));
+            ctx.insert(
+                "sites".to_string(),
+                serde_json::to_value(site::list_sites(registry))
+                    .unwrap_or_default()
+                    .to_string(),
+            );
+            Err(Template::render("super/dashboard", &ctx))
+        }
+        Ok(_) => Ok(Redirect::to("/super/")),
+        Err(e) => Err(clone_page(&source, Some(&e))),
+    }
+}
+
+// ── Custom Domains ───────────────────────────────────────────
+
+#[derive(Debug, FromForm)]
+pub struct DomainForm {
+    pub domain: String,
+}
+
+#[post("/sites/<id>/domains", data = "<form>")]
+pub fn add_domain(
+    id: i64,
+    form: Form<DomainForm>,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    if site::find_site_by_id(registry, id).is_none() {
+        return Redirect::to("/super/");
+    }
+    match site::add_domain(registry, id, &form.domain) {
+        Ok(domain) => {
+            site::acme::issue_in_background(registry, &domain.domain);
+            Redirect::to(format!("/super/sites/{}", id))
+        }
+        Err(e) => Redirect::to(format!("/super/sites/{}?error={}", id, urlencode(&e))),
+    }
+}
+
+#[post("/sites/<id>/domains/<domain_id>/delete")]
+pub fn remove_domain(
+    id: i64,
+    domain_id: i64,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    if site::find_domain(registry, domain_id).is_some_and(|d| d.site_id == id) {
+        let _ = site::remove_domain(registry, domain_id);
+    }
+    Redirect::to(format!("/super/sites/{}", id))
+}
+
+/// Request (or renew) the domain's certificate now
+#[post("/sites/<id>/domains/<domain_id>/certificate")]
+pub fn issue_certificate(
+    id: i64,
+    domain_id: i64,
+    registry: &State<RegistryPool>,
+    cookies: &CookieJar<'_>,
+) -> Redirect {
+    if !is_super_authenticated(registry, cookies) {
+        return Redirect::to("/super/login");
+    }
+    if let Some(domain) = site::find_domain(registry, domain_id).filter(|d| d.site_id == id) {
+        site::acme::issue_in_background(registry, &domain.domain);
+    }
+    Redirect::to(format!("/super/sites/{}", id))
+}
+
+fn urlencode(s: &str) -> String {
+    url::form_urlencoded::byte_serialize(s.as_bytes()).collect()
+}
+
+// ── ACME HTTP-01 challenges (mounted at the root, for every host) ──
+
+#[get("/.well-known/acme-challenge/<token>")]
+pub fn acme_challenge(token: &str, registry: &State<RegistryPool>) -> Option<String> {
+    site::acme::challenge_response(registry, token)
+}
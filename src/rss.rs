@@ -1,33 +1,191 @@
+use crate::models::post::Post;
+use crate::render::slug_url;
 use crate::store::Store;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// How many posts a feed lists (`rss_feed_count`, 1–100)
+fn feed_count(store: &dyn Store) -> i64 {
+    store
+        .setting_get_or("rss_feed_count", "25")
+        .parse::<i64>()
+        .unwrap_or(25)
+        .clamp(1, 100)
+}
+
+/// The latest published posts. Shared by the RSS and Atom feeds.
+fn feed_posts(store: &dyn Store) -> Vec<Post> {
+    store.post_list(Some("published"), feed_count(store), 0)
+}
+
+/// Absolute URL of a post
+fn post_link(site_url: &str, blog_slug: &str, slug: &str) -> String {
+    format!(
+        "{}{}",
+        site_url.trim_end_matches('/'),
+        slug_url(blog_slug, slug)
+    )
+}
+
+/// Format a stored UTC time in the configured timezone
+fn format_in_tz(ndt: NaiveDateTime, tz_name: &str, fmt: &str) -> String {
+    let utc: DateTime<Utc> = DateTime::from_naive_utc_and_offset(ndt, Utc);
+    match tz_name.parse::<chrono_tz::Tz>() {
+        Ok(tz) => utc.with_timezone(&tz).format(fmt).to_string(),
+        Err(_) => utc.format(fmt).to_string(),
+    }
+}
 
 /// Generate RSS 2.0 XML feed for published blog posts
 pub fn generate_feed(store: &dyn Store) -> String {
-    let site_name = store.setting_get_or("site_name", "Velocty");
     let site_url = store.setting_get_or("site_url", "http://localhost:8000");
-    let site_tagline = store.setting_get_or("site_caption", "");
-    let tz_name = store.setting_get_or("timezone", "UTC");
+    let channel = Channel {
+        title: store.setting_get_or("site_name", "Velocty"),
+        link: site_url.clone(),
+        description: store.setting_get_or("site_caption", ""),
+        self_url: format!("{}/feed", site_url),
+    };
+    render_rss(store, &channel, &feed_posts(store))
+}
+
+/// Generate the RSS feed for one journal category. None if there's no
+/// such category.
+pub fn generate_category_feed(store: &dyn Store, slug: &str) -> Option<String> {
+    let category = store
+        .category_find_by_slug(slug)
+        .filter(|c| c.r#type != "portfolio")?;
+    let channel = scoped_channel(store, &category.name, &format!("category/{}", slug));
+    let posts = store.post_by_category(category.id, feed_count(store), 0);
+    Some(render_rss(store, &channel, &posts))
+}
+
+/// Generate the RSS feed for one tag's posts. None if there's no such tag.
+pub fn generate_tag_feed(store: &dyn Store, slug: &str) -> Option<String> {
+    let tag = store.tag_find_by_slug(slug)?;
+    let channel = scoped_channel(store, &tag.name, &format!("tag/{}", slug));
+    let posts = store.post_by_tag(tag.id, feed_count(store), 0);
+    Some(render_rss(store, &channel, &posts))
+}
+
+/// `<link rel="alternate">` advertising the feed of a category or tag
+/// archive at `archive_path` (e.g. "/journal/tag/film").
+pub fn feed_link_tag(store: &dyn Store, name: &str, archive_path: &str) -> String {
+    let site_name = store.setting_get_or("site_name", "Velocty");
+    format!(
+        "\n<link rel=\"alternate\" type=\"application/rss+xml\" title=\"{}\" href=\"{}/feed\">",
+        xml_escape(&format!("{} — {}", site_name, name)),
+        xml_escape(archive_path)
+    )
+}
+
+/// How feed items are presented, from the feed settings
+struct ItemStyle {
+    /// Full `content_html` rather than just the excerpt
+    full_content: bool,
+    /// Featured image as `media:content`
+    featured_media: bool,
+    /// HTML appended to each item; {{title}}, {{url}} and {{site_name}}
+    /// are filled in
+    footer: String,
+    site_name: String,
+    site_url: String,
+}
+
+impl ItemStyle {
+    fn from_settings(store: &dyn Store) -> Self {
+        let site_url = store.setting_get_or("site_url", "http://localhost:8000");
+        ItemStyle {
+            full_content: store.setting_get_or("rss_content", "excerpt") == "full",
+            featured_media: store.setting_get_bool("rss_featured_media"),
+            footer: store
+                .setting_get_or("rss_item_footer", "")
+                .trim()
+                .to_string(),
+            site_name: store.setting_get_or("site_name", "Velocty"),
+            site_url: site_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// The footer block for one item, or "" when none is set
+    fn footer(&self, post: &Post, url: &str) -> String {
+        if self.footer.is_empty() {
+            return String::new();
+        }
+        let text = self
+            .footer
+            .replace("{{title}}", &xml_escape(&post.title))
+            .replace("{{url}}", &xml_escape(url))
+            .replace("{{site_name}}", &xml_escape(&self.site_name));
+        format!("\n<p>{}</p>", text)
+    }
+
+    /// `media:content` for the featured image, or "" when off or missing
+    fn media(&self, post: &Post, indent: &str) -> String {
+        let Some(image) = post
+            .featured_image
+            .as_deref()
+            .filter(|i| self.featured_media && !i.is_empty())
+        else {
+            return String::new();
+        };
+        let ext = image.rsplit('.').next().unwrap_or("").to_lowercase();
+        let mime = match ext.as_str() {
+            "png" => "image/png",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            "avif" => "image/avif",
+            "svg" => "image/svg+xml",
+            _ => "image/jpeg",
+        };
+        format!(
+            "{}<media:content url=\"{}\" type=\"{}\" medium=\"image\"/>\n",
+            indent,
+            xml_escape(&media_url(&self.site_url, image)),
+            mime
+        )
+    }
+}
+
+/// Point root-relative links and images in post HTML at the site, since
+/// feed readers have no page to resolve them against.
+fn absolutize(html: &str, site_url: &str) -> String {
+    html.replace("src=\"/", &format!("src=\"{}/", site_url))
+        .replace("href=\"/", &format!("href=\"{}/", site_url))
+        .replace(&format!("=\"{}//", site_url), "=\"//")
+}
+
+/// Title, links and description of one RSS channel
+struct Channel {
+    title: String,
+    link: String,
+    description: String,
+    self_url: String,
+}
 
+/// A channel for the archive at `<blog_slug>/<sub>`
+fn scoped_channel(store: &dyn Store, name: &str, sub: &str) -> Channel {
+    let site_name = store.setting_get_or("site_name", "Velocty");
+    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
     let blog_slug = store.setting_get_or("blog_slug", "blog");
-    let feed_count = store
-        .setting_get_or("rss_feed_count", "25")
-        .parse::<i64>()
-        .unwrap_or(25)
-        .clamp(1, 100);
+    let link = post_link(&site_url, &blog_slug, sub);
+    Channel {
+        title: format!("{} — {}", site_name, name),
+        description: format!("Posts filed under {} on {}", name, site_name),
+        self_url: format!("{}/feed", link),
+        link,
+    }
+}
 
-    let posts = store.post_list(Some("published"), feed_count, 0);
+fn render_rss(store: &dyn Store, channel: &Channel, posts: &[Post]) -> String {
+    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
+    let tz_name = store.setting_get_or("timezone", "UTC");
+    let blog_slug = store.setting_get_or("blog_slug", "blog");
 
     // Build date in the configured timezone (RFC 2822 format required by RSS spec)
-    let format_rfc2822 = |ndt: chrono::NaiveDateTime| -> String {
-        let utc: DateTime<Utc> = DateTime::from_naive_utc_and_offset(ndt, Utc);
-        if let Ok(tz) = tz_name.parse::<chrono_tz::Tz>() {
-            utc.with_timezone(&tz)
-                .format("%a, %d %b %Y %H:%M:%S %z")
-                .to_string()
-        } else {
-            utc.format("%a, %d %b %Y %H:%M:%S +0000").to_string()
-        }
-    };
+    let format_rfc2822 =
+        |ndt: NaiveDateTime| format_in_tz(ndt, &tz_name, "%a, %d %b %Y %H:%M:%S %z");
 
     let last_build = posts
         .first()
@@ -35,42 +193,65 @@ pub fn generate_feed(store: &dyn Store) -> String {
         .map(|d| format!("    <lastBuildDate>{}</lastBuildDate>\n", format_rfc2822(d)))
         .unwrap_or_default();
 
+    let style = ItemStyle::from_settings(store);
     let mut xml = format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
-<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom">
+<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:media="http://search.yahoo.com/mrss/">
 <channel>
     <title>{title}</title>
-    <link>{url}</link>
+    <link>{link}</link>
     <description>{desc}</description>
-    <atom:link href="{url}/feed" rel="self" type="application/rss+xml"/>
-    <language>en</language>
+    <atom:link href="{self_url}" rel="self" type="application/rss+xml"/>
+    <language>{language}</language>
 {last_build}"#,
-        title = xml_escape(&site_name),
-        url = xml_escape(&site_url),
-        desc = xml_escape(&site_tagline),
+        title = xml_escape(&channel.title),
+        link = xml_escape(&channel.link),
+        desc = xml_escape(&channel.description),
+        self_url = xml_escape(&channel.self_url),
+        language = crate::seo::hreflang::site_language(store),
         last_build = last_build,
     );
 
-    for post in &posts {
+    for post in posts {
         let pub_date = post.published_at.map(&format_rfc2822).unwrap_or_default();
 
         let excerpt = post.excerpt.as_deref().unwrap_or("");
+        let link = post_link(&site_url, &blog_slug, &post.slug);
+        let footer = style.footer(post, &link);
+
+        // The footer goes after the full content, or after the excerpt
+        // when that's all the item carries
+        let (desc, content) = if style.full_content {
+            let html = absolutize(&post.content_html, &style.site_url);
+            (
+                xml_escape(excerpt),
+                format!(
+                    "        <content:encoded>{}</content:encoded>\n",
+                    xml_escape(&format!("{}{}", html, footer))
+                ),
+            )
+        } else if footer.is_empty() {
+            (xml_escape(excerpt), String::new())
+        } else {
+            let html = format!("<p>{}</p>{}", xml_escape(excerpt), footer);
+            (xml_escape(&html), String::new())
+        };
 
         xml.push_str(&format!(
             r#"    <item>
         <title>{title}</title>
-        <link>{url}/{blog_slug}/{slug}</link>
-        <guid isPermaLink="true">{url}/{blog_slug}/{slug}</guid>
+        <link>{link}</link>
+        <guid isPermaLink="true">{link}</guid>
         <pubDate>{date}</pubDate>
         <description>{desc}</description>
-    </item>
+{content}{media}    </item>
 "#,
             title = xml_escape(&post.title),
-            url = xml_escape(&site_url),
-            blog_slug = &blog_slug,
-            slug = &post.slug,
+            link = xml_escape(&link),
             date = pub_date,
-            desc = xml_escape(excerpt),
+            desc = desc,
+            content = content,
+            media = style.media(post, "        "),
         ));
     }
 
@@ -78,6 +259,115 @@ pub fn generate_feed(store: &dyn Store) -> String {
     xml
 }
 
+/// Generate an Atom 1.0 feed of the same posts as the RSS feed, with the
+/// site owner as author.
+pub fn generate_atom(store: &dyn Store) -> String {
+    let site_name = store.setting_get_or("site_name", "Velocty");
+    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
+    let site_url = site_url.trim_end_matches('/');
+    let site_tagline = store.setting_get_or("site_caption", "");
+    let tz_name = store.setting_get_or("timezone", "UTC");
+    let blog_slug = store.setting_get_or("blog_slug", "blog");
+    let posts = feed_posts(store);
+
+    let format_rfc3339 = |ndt: NaiveDateTime| format_in_tz(ndt, &tz_name, "%Y-%m-%dT%H:%M:%S%:z");
+
+    // Posts have no author of their own; credit the first admin
+    let author = store
+        .user_list_all()
+        .into_iter()
+        .find(|u| u.role == "admin")
+        .map(|u| u.display_name)
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| site_name.clone());
+
+    // A feed must always say when it last changed
+    let updated = posts
+        .iter()
+        .map(|p| p.updated_at)
+        .max()
+        .unwrap_or_else(|| Utc::now().naive_utc());
+
+    let subtitle = if site_tagline.is_empty() {
+        String::new()
+    } else {
+        format!("  <subtitle>{}</subtitle>\n", xml_escape(&site_tagline))
+    };
+    let mut xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xmlns:media="http://search.yahoo.com/mrss/" xml:lang="{language}" xml:base="{url}/">
+  <title>{title}</title>
+{subtitle}  <id>{url}/</id>
+  <link rel="alternate" type="text/html" href="{url}/"/>
+  <link rel="self" type="application/atom+xml" href="{url}/feed/atom"/>
+  <updated>{updated}</updated>
+  <author><name>{author}</name></author>
+  <generator>Velocty</generator>
+"#,
+        language = crate::seo::hreflang::site_language(store),
+        url = xml_escape(site_url),
+        title = xml_escape(&site_name),
+        subtitle = subtitle,
+        updated = format_rfc3339(updated),
+        author = xml_escape(&author),
+    );
+
+    let style = ItemStyle::from_settings(store);
+    for post in &posts {
+        let url = post_link(site_url, &blog_slug, &post.slug);
+        let link = xml_escape(&url);
+        let footer = style.footer(post, &url);
+        let published = post
+            .published_at
+            .map(|d| format!("    <published>{}</published>\n", format_rfc3339(d)))
+            .unwrap_or_default();
+        let excerpt = post.excerpt.as_deref().unwrap_or("");
+        let summary = if !style.full_content && !footer.is_empty() {
+            let html = format!("<p>{}</p>{}", xml_escape(excerpt), footer);
+            format!(
+                "    <summary type=\"html\">{}</summary>\n",
+                xml_escape(&html)
+            )
+        } else if !excerpt.is_empty() {
+            format!(
+                "    <summary type=\"text\">{}</summary>\n",
+                xml_escape(excerpt)
+            )
+        } else {
+            String::new()
+        };
+        let content = if style.full_content {
+            format!(
+                "    <content type=\"html\">{}</content>\n",
+                xml_escape(&format!("{}{}", post.content_html, footer))
+            )
+        } else {
+            String::new()
+        };
+        xml.push_str(&format!(
+            r#"  <entry>
+    <title type="text">{title}</title>
+    <link rel="alternate" type="text/html" href="{link}"/>
+    <id>{link}</id>
+{published}    <updated>{updated}</updated>
+    <author><name>{author}</name></author>
+{summary}{content}{media}  </entry>
+"#,
+            title = xml_escape(&post.title),
+            link = link,
+            published = published,
+            updated = format_rfc3339(post.updated_at),
+            author = xml_escape(&author),
+            summary = summary,
+            content = content,
+            media = style.media(post, "    "),
+        ));
+    }
+
+    xml.push_str("</feed>");
+    xml
+}
+
 fn xml_escape(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -85,3 +375,384 @@ fn xml_escape(s: &str) -> String {
         .replace('"', "&quot;")
         .replace('\'', "&apos;")
 }
+
+// ── Podcast ─────────────────────────────────────────────
+
+/// Apple Podcasts top-level categories
+pub const PODCAST_CATEGORIES: &[&str] = &[
+    "Arts",
+    "Business",
+    "Comedy",
+    "Education",
+    "Fiction",
+    "Government",
+    "Health & Fitness",
+    "History",
+    "Kids & Family",
+    "Leisure",
+    "Music",
+    "News",
+    "Religion & Spirituality",
+    "Science",
+    "Society & Culture",
+    "Sports",
+    "Technology",
+    "True Crime",
+    "TV & Film",
+];
+
+/// Audio formats by extension: MIME type and whether Apple Podcasts
+/// accepts it
+const AUDIO_TYPES: &[(&str, &str, bool)] = &[
+    ("mp3", "audio/mpeg", true),
+    ("m4a", "audio/x-m4a", true),
+    ("aac", "audio/aac", false),
+    ("ogg", "audio/ogg", false),
+    ("opus", "audio/opus", false),
+    ("wav", "audio/wav", false),
+    ("flac", "audio/flac", false),
+];
+
+/// Most episodes listed in the podcast feed
+const MAX_EPISODES: i64 = 300;
+
+const UPLOAD_DIR: &str = "website/site/uploads";
+
+fn audio_type(file: &str) -> Option<(&'static str, bool)> {
+    let path = file.split(['?', '#']).next().unwrap_or("");
+    let ext = path.rsplit_once('.')?.1.to_lowercase();
+    AUDIO_TYPES
+        .iter()
+        .find(|(e, _, _)| *e == ext)
+        .map(|(_, mime, apple)| (*mime, *apple))
+}
+
+/// Parse an episode length given as "1:02:03", "42:10" or plain seconds
+pub fn parse_duration(raw: &str) -> Option<i64> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    let parts: Vec<&str> = raw.split(':').collect();
+    if parts.len() > 3 {
+        return None;
+    }
+    let mut secs: i64 = 0;
+    for (i, part) in parts.iter().enumerate() {
+        let n: i64 = part.trim().parse().ok().filter(|n| *n >= 0)?;
+        // Minutes and seconds after the first field stay under 60
+        if i > 0 && n >= 60 {
+            return None;
+        }
+        secs = secs * 60 + n;
+    }
+    Some(secs)
+}
+
+/// Seconds as "H:MM:SS", or "M:SS" under an hour
+pub fn format_duration(secs: i64) -> String {
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
+/// Size of an uploaded audio file, for filling in `audio_bytes`. None for
+/// remote URLs and missing files.
+pub fn local_audio_bytes(file: &str) -> Option<i64> {
+    if file.contains("://") || file.contains("..") {
+        return None;
+    }
+    let path = std::path::Path::new(UPLOAD_DIR).join(file.trim_start_matches('/'));
+    std::fs::metadata(path).ok().map(|m| m.len() as i64)
+}
+
+/// A full URL as is, otherwise a file under uploads
+fn media_url(site_url: &str, file: &str) -> String {
+    if file.starts_with("https://") || file.starts_with("http://") {
+        file.to_string()
+    } else {
+        format!("{}/uploads/{}", site_url, file.trim_start_matches('/'))
+    }
+}
+
+/// Channel-level podcast settings, each falling back to the site's own
+struct Podcast {
+    title: String,
+    description: String,
+    author: String,
+    email: String,
+    category: String,
+    artwork: String,
+    explicit: bool,
+}
+
+impl Podcast {
+    fn from_settings(store: &dyn Store) -> Self {
+        let or = |key: &str, fallback: &str| {
+            let v = store.setting_get_or(key, "");
+            if v.trim().is_empty() {
+                store.setting_get_or(fallback, "")
+            } else {
+                v
+            }
+        };
+        Podcast {
+            title: or("podcast_title", "site_name"),
+            description: or("site_caption", "seo_default_description"),
+            author: or("podcast_author", "site_name"),
+            email: or("podcast_email", "admin_email"),
+            category: store.setting_get_or("podcast_category", ""),
+            artwork: store.setting_get_or("podcast_artwork", ""),
+            explicit: store.setting_get_bool("podcast_explicit"),
+        }
+    }
+}
+
+/// Generate the podcast RSS feed: posts with episode audio, with the
+/// iTunes tags Apple Podcasts reads. None when the podcast is turned off.
+pub fn generate_podcast(store: &dyn Store) -> Option<String> {
+    if !store.setting_get_bool("podcast_enabled") {
+        return None;
+    }
+    let podcast = Podcast::from_settings(store);
+    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
+    let site_url = site_url.trim_end_matches('/');
+    let tz_name = store.setting_get_or("timezone", "UTC");
+    let blog_slug = store.setting_get_or("blog_slug", "blog");
+    let explicit = |on: bool| if on { "true" } else { "false" };
+
+    let artwork = if podcast.artwork.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "    <itunes:image href=\"{}\"/>\n",
+            xml_escape(&media_url(site_url, &podcast.artwork))
+        )
+    };
+    let category = if podcast.category.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "    <itunes:category text=\"{}\"/>\n",
+            xml_escape(&podcast.category)
+        )
+    };
+    let mut xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd" xmlns:content="http://purl.org/rss/1.0/modules/content/" xmlns:atom="http://www.w3.org/2005/Atom">
+<channel>
+    <title>{title}</title>
+    <link>{url}</link>
+    <description>{desc}</description>
+    <language>{language}</language>
+    <atom:link href="{url}/feed/podcast" rel="self" type="application/rss+xml"/>
+    <itunes:author>{author}</itunes:author>
+    <itunes:owner><itunes:name>{author}</itunes:name><itunes:email>{email}</itunes:email></itunes:owner>
+{artwork}{category}    <itunes:explicit>{explicit}</itunes:explicit>
+    <itunes:type>episodic</itunes:type>
+"#,
+        title = xml_escape(&podcast.title),
+        url = xml_escape(site_url),
+        desc = xml_escape(&podcast.description),
+        language = crate::seo::hreflang::site_language(store),
+        author = xml_escape(&podcast.author),
+        email = xml_escape(&podcast.email),
+        artwork = artwork,
+        category = category,
+        explicit = explicit(podcast.explicit),
+    );
+
+    for post in store.post_episodes(MAX_EPISODES) {
+        let Some(file) = post.audio_file.as_deref().filter(|f| !f.is_empty()) else {
+            continue;
+        };
+        let link = xml_escape(&post_link(site_url, &blog_slug, &post.slug));
+        let mime = audio_type(file).map(|(m, _)| m).unwrap_or("audio/mpeg");
+        let pub_date = post
+            .published_at
+            .map(|d| {
+                format!(
+                    "        <pubDate>{}</pubDate>\n",
+                    format_in_tz(d, &tz_name, "%a, %d %b %Y %H:%M:%S %z")
+                )
+            })
+            .unwrap_or_default();
+        let duration = post
+            .audio_duration
+            .map(|d| format!("        <itunes:duration>{}</itunes:duration>\n", d))
+            .unwrap_or_default();
+        let image = post
+            .featured_image
+            .as_deref()
+            .filter(|i| !i.is_empty())
+            .map(|i| {
+                format!(
+                    "        <itunes:image href=\"{}\"/>\n",
+                    xml_escape(&media_url(site_url, i))
+                )
+            })
+            .unwrap_or_default();
+        xml.push_str(&format!(
+            r#"    <item>
+        <title>{title}</title>
+        <link>{link}</link>
+        <guid isPermaLink="true">{link}</guid>
+{pub_date}        <description>{desc}</description>
+        <content:encoded>{content}</content:encoded>
+        <enclosure url="{audio}" length="{bytes}" type="{mime}"/>
+{duration}{image}        <itunes:explicit>{explicit}</itunes:explicit>
+        <itunes:episodeType>full</itunes:episodeType>
+    </item>
+"#,
+            title = xml_escape(&post.title),
+            link = link,
+            pub_date = pub_date,
+            desc = xml_escape(post.excerpt.as_deref().unwrap_or("")),
+            content = xml_escape(&post.content_html),
+            audio = xml_escape(&media_url(site_url, file)),
+            bytes = post.audio_bytes.unwrap_or(0),
+            mime = mime,
+            duration = duration,
+            image = image,
+            explicit = explicit(podcast.explicit),
+        ));
+    }
+
+    xml.push_str("</channel>\n</rss>");
+    Some(xml)
+}
+
+/// What would get the podcast feed rejected by Apple Podcasts. Empty when
+/// it meets the requirements.
+pub fn podcast_problems(store: &dyn Store) -> Vec<String> {
+    let podcast = Podcast::from_settings(store);
+    let mut problems = Vec::new();
+    if podcast.title.trim().is_empty() {
+        problems.push("The podcast needs a title".to_string());
+    }
+    if podcast.description.trim().is_empty() {
+        problems.push("The podcast needs a description (the site caption)".to_string());
+    }
+    if podcast.author.trim().is_empty() {
+        problems.push("The podcast needs an author".to_string());
+    }
+    if !podcast.email.contains('@') {
+        problems.push("The podcast needs an owner email address".to_string());
+    }
+    if !PODCAST_CATEGORIES.contains(&podcast.category.as_str()) {
+        problems.push("Choose an Apple Podcasts category".to_string());
+    }
+    problems.extend(artwork_problem(&podcast.artwork));
+
+    let episodes = store.post_episodes(MAX_EPISODES);
+    if episodes.is_empty() {
+        problems.push("Publish at least one post with episode audio".to_string());
+    }
+    for post in &episodes {
+        let file = post.audio_file.as_deref().unwrap_or("");
+        match audio_type(file) {
+            Some((_, true)) => {}
+            Some((mime, false)) => problems.push(format!(
+                "\"{}\": Apple Podcasts doesn't accept {} audio, use MP3 or M4A",
+                post.title, mime
+            )),
+            None => problems.push(format!(
+                "\"{}\": the audio file must be an MP3 or M4A",
+                post.title
+            )),
+        }
+        if post.audio_bytes.unwrap_or(0) <= 0 {
+            problems.push(format!(
+                "\"{}\": the audio file size is missing",
+                post.title
+            ));
+        }
+    }
+    problems
+}
+
+/// Artwork must be a JPEG or PNG square of 1400 to 3000 pixels
+fn artwork_problem(artwork: &str) -> Option<String> {
+    if artwork.is_empty() {
+        return Some("The podcast needs cover artwork".to_string());
+    }
+    let lower = artwork.to_lowercase();
+    if ![".jpg", ".jpeg", ".png"].iter().any(|e| lower.ends_with(e)) {
+        return Some("Cover artwork must be a JPEG or PNG".to_string());
+    }
+    if artwork.contains("://") || artwork.contains("..") {
+        return None;
+    }
+    let path = std::path::Path::new(UPLOAD_DIR).join(artwork.trim_start_matches('/'));
+    match image::image_dimensions(path) {
+        Ok((w, h)) if w != h || !(1400..=3000).contains(&w) => Some(format!(
+            "Cover artwork must be square, 1400 to 3000 pixels (it is {}×{})",
+            w, h
+        )),
+        _ => None,
+    }
+}
+
+// ── Feed cache ──────────────────────────────────────────
+//
+// Feed readers poll often, and every request used to query and render the
+// whole feed. Generated XML is kept until content or settings change (the
+// page cache's generation moves) or an hour passes, whichever comes first.
+
+/// Upper bound on how long a cached feed is served
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+struct Cached {
+    at: Instant,
+    generation: u64,
+    xml: String,
+}
+
+fn cache() -> &'static RwLock<HashMap<String, Cached>> {
+    static CACHE: OnceLock<RwLock<HashMap<String, Cached>>> = OnceLock::new();
+    CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// A feed by name — "rss", "atom", "podcast", "category/<slug>" or
+/// "tag/<slug>" — from the cache, generated on a miss. None for a feed
+/// that doesn't exist.
+pub fn cached_feed(store: &dyn Store, name: &str) -> Option<String> {
+    let key = format!("{}|{}", store.setting_get_or("site_url", ""), name);
+    let generation = crate::render::page_cache::generation();
+    if let Ok(map) = cache().read() {
+        if let Some(hit) = map.get(&key) {
+            if hit.generation == generation && hit.at.elapsed() < CACHE_TTL {
+                return Some(hit.xml.clone());
+            }
+        }
+    }
+
+    let xml = match name.split_once('/') {
+        Some(("category", slug)) => generate_category_feed(store, slug)?,
+        Some(("tag", slug)) => generate_tag_feed(store, slug)?,
+        Some(_) => return None,
+        None => match name {
+            "rss" => generate_feed(store),
+            "atom" => generate_atom(store),
+            "podcast" => generate_podcast(store)?,
+            _ => return None,
+        },
+    };
+    if let Ok(mut map) = cache().write() {
+        // Only feeds that exist are stored, so the map is bounded by the
+        // number of taxonomies; stale generations go on every write
+        map.retain(|_, e| e.generation == generation);
+        map.insert(
+            key,
+            Cached {
+                at: Instant::now(),
+                generation,
+                xml: xml.clone(),
+            },
+        );
+    }
+    Some(xml)
+}
@@ -0,0 +1,105 @@
+use serde_json::{json, Value};
+
+use super::{client, Config, Engine, SearchDoc};
+
+/// A Meilisearch index, addressed with a master or admin API key.
+/// Meilisearch applies writes as queued tasks, so a document shows up in
+/// results a moment after the request returns.
+pub struct Meilisearch {
+    url: String,
+    api_key: String,
+    index: String,
+}
+
+impl Meilisearch {
+    pub fn new(config: &Config) -> Self {
+        Meilisearch {
+            url: config.url.clone(),
+            api_key: config.api_key.clone(),
+            index: config.index.clone(),
+        }
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/indexes/{}{}", self.url, self.index, path)
+    }
+
+    fn send(&self, req: reqwest::blocking::RequestBuilder) -> Result<Value, String> {
+        let req = if self.api_key.is_empty() {
+            req
+        } else {
+            req.bearer_auth(&self.api_key)
+        };
+        let resp = req
+            .send()
+            .map_err(|e| format!("Meilisearch request failed: {}", e))?;
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().unwrap_or_default();
+            return Err(format!("Meilisearch returned {}: {}", status, text));
+        }
+        resp.json()
+            .map_err(|e| format!("Meilisearch JSON parse error: {}", e))
+    }
+}
+
+impl Engine for Meilisearch {
+    fn setup(&self) -> Result<(), String> {
+        // Updating the settings creates the index when it's missing.
+        // Searchable attributes rank in order, so titles weigh above bodies.
+        self.send(client()?.patch(self.endpoint("/settings")).json(&json!({
+            "searchableAttributes": ["title", "body"],
+            "filterableAttributes": ["item_type"],
+            "typoTolerance": { "enabled": true },
+        })))
+        .map(|_| ())
+    }
+
+    fn upsert(&self, docs: &[SearchDoc]) -> Result<(), String> {
+        self.send(
+            client()?
+                .post(self.endpoint("/documents?primaryKey=id"))
+                .json(docs),
+        )
+        .map(|_| ())
+    }
+
+    fn delete(&self, id: &str) -> Result<(), String> {
+        self.send(client()?.delete(self.endpoint(&format!("/documents/{}", id))))
+            .map(|_| ())
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        self.send(client()?.delete(self.endpoint("/documents")))
+            .map(|_| ())
+    }
+
+    fn search(&self, query: &str, limit: i64) -> Result<Vec<(SearchDoc, f64)>, String> {
+        let body = self.send(client()?.post(self.endpoint("/search")).json(&json!({
+            "q": query,
+            "limit": limit,
+            "facets": ["item_type"],
+            "showRankingScore": true,
+        })))?;
+        Ok(parse_hits(&body))
+    }
+}
+
+/// Documents from a search response, with their ranking scores (0–1)
+pub fn parse_hits(body: &Value) -> Vec<(SearchDoc, f64)> {
+    body.get("hits")
+        .and_then(|h| h.as_array())
+        .map(|hits| {
+            hits.iter()
+                .filter_map(|hit| {
+                    let score = hit
+                        .get("_rankingScore")
+                        .and_then(|s| s.as_f64())
+                        .unwrap_or(0.0);
+                    let doc: SearchDoc = serde_json::from_value(hit.clone()).ok()?;
+                    Some((doc, score))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
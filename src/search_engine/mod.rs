@@ -0,0 +1,472 @@
+pub mod meilisearch;
+pub mod typesense;
+
+use std::collections::HashMap;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::search::{query_terms, snippet, strip_html, SearchResult};
+use crate::store::Store;
+
+// ── External search engines ─────────────────────────────
+//
+// Large sites can hand search to Meilisearch or Typesense, which tolerate
+// typos and facet by content type. The built-in full-text index is always
+// kept too: it answers whenever no engine is configured or the engine
+// can't be reached. Published posts and portfolio items are pushed to the
+// engine as they're saved, through one background worker, and a full
+// reindex runs when the connection settings change or from the Health page.
+// The connection settings come from the `SettingsCache`. An engine that
+// keeps failing is skipped for a while rather than waited on.
+
+/// Engines as (key, label). "builtin" is the database's own index.
+pub const ENGINES: &[(&str, &str)] = &[
+    ("builtin", "Built-in"),
+    ("meilisearch", "Meilisearch"),
+    ("typesense", "Typesense"),
+];
+
+/// Documents sent per request during a reindex
+const BATCH_SIZE: usize = 500;
+
+/// How long to wait for the engine to accept a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Consecutive failures after which an engine is skipped
+const BREAKER_FAILURES: u32 = 3;
+
+/// How long a failing engine is skipped before it's tried again
+const BREAKER_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Index writes waiting for the sync worker. When it's full, further
+/// writes are dropped and left to the next reindex.
+const SYNC_QUEUE_SIZE: usize = 1024;
+
+/// One post or portfolio item as the engines index it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SearchDoc {
+    /// `<item_type>-<item_id>`, unique across both types
+    pub id: String,
+    pub item_type: String,
+    pub item_id: i64,
+    pub title: String,
+    /// Plain text, tags stripped
+    pub body: String,
+    pub slug: String,
+    pub image: Option<String>,
+    pub date: Option<String>,
+}
+
+impl SearchDoc {
+    pub fn new(
+        item_type: &str,
+        item_id: i64,
+        title: &str,
+        html_body: &str,
+        slug: &str,
+        image: Option<&str>,
+        date: Option<&str>,
+    ) -> Self {
+        SearchDoc {
+            id: doc_id(item_type, item_id),
+            item_type: item_type.to_string(),
+            item_id,
+            title: title.to_string(),
+            body: strip_html(html_body),
+            slug: slug.to_string(),
+            image: image.map(|s| s.to_string()),
+            date: date.map(|s| s.to_string()),
+        }
+    }
+
+    /// As a search result, its snippet cut around the query's first match.
+    /// `score` is the engine's, higher is better; ranks follow the FTS
+    /// convention of lower is better.
+    pub fn into_result(self, terms: &[String], score: f64) -> SearchResult {
+        SearchResult {
+            snippet: snippet(&self.body, terms),
+            item_type: self.item_type,
+            item_id: self.item_id,
+            title: self.title,
+            slug: self.slug,
+            image: self.image,
+            date: self.date,
+            rank: -score,
+        }
+    }
+}
+
+pub fn doc_id(item_type: &str, item_id: i64) -> String {
+    format!("{}-{}", item_type, item_id)
+}
+
+/// An external search index.
+pub trait Engine: Send {
+    /// Create the index if it's missing and apply its settings: title
+    /// weighted above body, typo tolerance, `item_type` as a facet
+    fn setup(&self) -> Result<(), String>;
+    /// Add or replace documents by id
+    fn upsert(&self, docs: &[SearchDoc]) -> Result<(), String>;
+    fn delete(&self, id: &str) -> Result<(), String>;
+    /// Remove every document, before a full reindex
+    fn clear(&self) -> Result<(), String>;
+    /// Best matches first, with the engine's score (higher is better)
+    fn search(&self, query: &str, limit: i64) -> Result<Vec<(SearchDoc, f64)>, String>;
+}
+
+/// Connection settings, from the `search_engine*` settings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub engine: String,
+    pub url: String,
+    pub api_key: String,
+    pub index: String,
+}
+
+impl Config {
+    pub fn load(store: &dyn Store) -> Self {
+        Self::from_lookup(|key| store.setting_get_or(key, ""))
+    }
+
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        Self::from_lookup(|key| settings.get(key).cloned().unwrap_or_default())
+    }
+
+    /// From any source of settings, e.g. a submitted settings form
+    pub fn from_lookup(lookup: impl Fn(&str) -> String) -> Self {
+        let get = |key: &str| lookup(key).trim().to_string();
+        let index = match get("search_engine_index") {
+            i if i.is_empty() => "velocty".to_string(),
+            i => i,
+        };
+        Config {
+            engine: get("search_engine"),
+            url: get("search_engine_url").trim_end_matches('/').to_string(),
+            api_key: get("search_engine_api_key"),
+            index,
+        }
+    }
+
+    /// Whether an external engine is chosen, as opposed to the built-in index
+    pub fn is_external(&self) -> bool {
+        !self.engine.is_empty() && self.engine != "builtin"
+    }
+}
+
+/// A problem with the engine settings, or None when they're usable. The
+/// built-in index always is.
+pub fn validate(config: &Config) -> Option<String> {
+    if !config.is_external() {
+        return None;
+    }
+    if !ENGINES.iter().any(|(key, _)| *key == config.engine) {
+        return Some(format!("Unknown search engine \"{}\"", config.engine));
+    }
+    if !(config.url.starts_with("http://") || config.url.starts_with("https://")) {
+        return Some("Search engine URL must start with http:// or https://".into());
+    }
+    if !config
+        .index
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Some("Search index name may only use letters, digits, - and _".into());
+    }
+    None
+}
+
+/// The engine settings last loaded into the `SettingsCache`
+fn current() -> &'static RwLock<Option<Config>> {
+    static CURRENT: OnceLock<RwLock<Option<Config>>> = OnceLock::new();
+    CURRENT.get_or_init(|| RwLock::new(None))
+}
+
+/// Take the engine settings from a freshly loaded settings map. The
+/// `SettingsCache` calls this whenever it reloads.
+pub fn configure(settings: &HashMap<String, String>) {
+    if let Ok(mut w) = current().write() {
+        *w = Some(Config::from_settings(settings));
+    }
+}
+
+/// The cached engine settings, read from the store only when no
+/// `SettingsCache` has been loaded (command-line tools)
+pub fn config_for(store: &dyn Store) -> Config {
+    current()
+        .read()
+        .ok()
+        .and_then(|c| c.clone())
+        .unwrap_or_else(|| Config::load(store))
+}
+
+// ── Circuit breaker ─────────────────────────────────────
+
+#[derive(Default)]
+struct Breaker {
+    failures: u32,
+    open_until: Option<Instant>,
+}
+
+fn breakers() -> &'static Mutex<HashMap<String, Breaker>> {
+    static BREAKERS: OnceLock<Mutex<HashMap<String, Breaker>>> = OnceLock::new();
+    BREAKERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Whether the engine at `url` is being skipped after repeated failures
+pub fn breaker_open(url: &str) -> bool {
+    breakers()
+        .lock()
+        .ok()
+        .and_then(|map| map.get(url).and_then(|b| b.open_until))
+        .is_some_and(|until| Instant::now() < until)
+}
+
+/// Count a call to the engine at `url`. A success closes the breaker;
+/// `BREAKER_FAILURES` failures in a row open it for `BREAKER_COOLDOWN`.
+pub fn record_result<T>(url: &str, result: &Result<T, String>) {
+    let Ok(mut map) = breakers().lock() else {
+        return;
+    };
+    if result.is_ok() {
+        map.remove(url);
+        return;
+    }
+    let breaker = map.entry(url.to_string()).or_default();
+    breaker.failures += 1;
+    if breaker.failures >= BREAKER_FAILURES {
+        breaker.open_until = Some(Instant::now() + BREAKER_COOLDOWN);
+    }
+}
+
+pub(crate) fn client() -> Result<reqwest::blocking::Client, String> {
+    reqwest::blocking::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(Duration::from_secs(10))
+        .user_agent("Velocty")
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))
+}
+
+/// The configured engine, or None to use the built-in index
+pub fn engine_for(config: &Config) -> Option<Box<dyn Engine>> {
+    if validate(config).is_some() {
+        return None;
+    }
+    match config.engine.as_str() {
+        "meilisearch" => Some(Box::new(meilisearch::Meilisearch::new(config))),
+        "typesense" => Some(Box::new(typesense::Typesense::new(config))),
+        _ => None,
+    }
+}
+
+/// Search through the configured engine, or the built-in index when there
+/// is none, it fails or it's being skipped after failing
+pub fn query(store: &dyn Store, query: &str, limit: i64) -> Vec<SearchResult> {
+    let config = config_for(store);
+    let engine = match engine_for(&config) {
+        Some(engine) if !breaker_open(&config.url) => engine,
+        _ => return store.search_query(query, limit),
+    };
+    let terms = query_terms(query);
+    if terms.is_empty() || limit <= 0 {
+        return vec![];
+    }
+    let result = engine.search(&terms.join(" "), limit);
+    record_result(&config.url, &result);
+    match result {
+        Ok(hits) => hits
+            .into_iter()
+            .map(|(doc, score)| doc.into_result(&terms, score))
+            .collect(),
+        Err(e) => {
+            log::warn!(
+                "[search] Engine unavailable, using the built-in index: {}",
+                e
+            );
+            store.search_query(query, limit)
+        }
+    }
+}
+
+// ── Sync worker ─────────────────────────────────────────
+
+enum SyncOp {
+    Upsert(SearchDoc),
+    Delete(String),
+}
+
+struct SyncJob {
+    config: Config,
+    op: SyncOp,
+}
+
+/// The queue feeding the single sync worker, started on first use
+fn sync_queue() -> &'static SyncSender<SyncJob> {
+    static QUEUE: OnceLock<SyncSender<SyncJob>> = OnceLock::new();
+    QUEUE.get_or_init(|| {
+        let (tx, rx) = mpsc::sync_channel::<SyncJob>(SYNC_QUEUE_SIZE);
+        std::thread::spawn(move || {
+            for job in rx {
+                run_sync(job);
+            }
+        });
+        tx
+    })
+}
+
+fn run_sync(job: SyncJob) {
+    let id = match &job.op {
+        SyncOp::Upsert(doc) => doc.id.clone(),
+        SyncOp::Delete(id) => id.clone(),
+    };
+    // Left to the next reindex while the engine is failing
+    if breaker_open(&job.config.url) {
+        return;
+    }
+    let Some(engine) = engine_for(&job.config) else {
+        return;
+    };
+    let result = match job.op {
+        SyncOp::Upsert(doc) => engine.upsert(&[doc]),
+        SyncOp::Delete(id) => engine.delete(&id),
+    };
+    record_result(&job.config.url, &result);
+    if let Err(e) = result {
+        log::warn!("[search] Syncing {} failed: {}", id, e);
+    }
+}
+
+/// Hand an index write to the sync worker without waiting for it
+fn enqueue(config: Config, op: SyncOp) {
+    match sync_queue().try_send(SyncJob { config, op }) {
+        Ok(()) => {}
+        Err(TrySendError::Full(_)) => {
+            log::warn!("[search] Sync queue is full; the next reindex will catch up")
+        }
+        Err(TrySendError::Disconnected(_)) => log::warn!("[search] Sync worker has stopped"),
+    }
+}
+
+/// Push a saved item to the engine, or take it out when it isn't
+/// published. Queued for the sync worker so saving never waits on the
+/// engine; the next reindex catches anything that fails or is dropped.
+pub fn sync_item(
+    store: &dyn Store,
+    item_type: &str,
+    item_id: i64,
+    title: &str,
+    html_body: &str,
+    slug: &str,
+    image: Option<&str>,
+    date: Option<&str>,
+    is_published: bool,
+) {
+    let config = config_for(store);
+    if engine_for(&config).is_none() {
+        return;
+    }
+    let op = if is_published {
+        SyncOp::Upsert(SearchDoc::new(
+            item_type, item_id, title, html_body, slug, image, date,
+        ))
+    } else {
+        SyncOp::Delete(doc_id(item_type, item_id))
+    };
+    enqueue(config, op);
+}
+
+/// Take a deleted item out of the engine, through the sync worker
+pub fn sync_removal(store: &dyn Store, item_type: &str, item_id: i64) {
+    let config = config_for(store);
+    if engine_for(&config).is_none() {
+        return;
+    }
+    enqueue(config, SyncOp::Delete(doc_id(item_type, item_id)));
+}
+
+/// Every published post and portfolio item, as engine documents
+pub fn published_docs(store: &dyn Store) -> Vec<SearchDoc> {
+    let mut docs: Vec<SearchDoc> = store
+        .post_list(Some("published"), i64::MAX, 0)
+        .into_iter()
+        .map(|p| {
+            SearchDoc::new(
+                "post",
+                p.id,
+                &p.title,
+                &p.content_html,
+                &p.slug,
+                p.featured_image.as_deref(),
+                p.published_at.map(|d| d.to_string()).as_deref(),
+            )
+        })
+        .collect();
+    docs.extend(
+        store
+            .portfolio_list(Some("published"), i64::MAX, 0)
+            .into_iter()
+            .map(|p| {
+                SearchDoc::new(
+                    "portfolio",
+                    p.id,
+                    &p.title,
+                    p.description_html.as_deref().unwrap_or(""),
+                    &p.slug,
+                    Some(&p.image_path),
+                    p.published_at.map(|d| d.to_string()).as_deref(),
+                )
+            }),
+    );
+    docs
+}
+
+/// Rebuild the engine's index from the published content. Returns how many
+/// documents were sent. The outcome is kept in `search_engine_last_sync`
+/// and `search_engine_last_error` for the settings page.
+pub fn reindex(store: &dyn Store) -> Result<usize, String> {
+    let config = Config::load(store);
+    if let Some(problem) = validate(&config) {
+        return Err(problem);
+    }
+    let Some(engine) = engine_for(&config) else {
+        return Err("No external search engine is configured".into());
+    };
+    let docs = published_docs(store);
+    let result = engine
+        .setup()
+        .and_then(|_| engine.clear())
+        .and_then(|_| {
+            docs.chunks(BATCH_SIZE)
+                .try_for_each(|batch| engine.upsert(batch))
+        })
+        .map(|_| docs.len());
+    record_result(&config.url, &result);
+    match &result {
+        Ok(count) => {
+            log::info!(
+                "[search] Reindexed {} documents in {}",
+                count,
+                config.engine
+            );
+            let stamp = chrono::Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
+            let _ = store.setting_set("search_engine_last_sync", &stamp);
+            let _ = store.setting_set("search_engine_last_error", "");
+        }
+        Err(e) => {
+            log::warn!("[search] Reindexing {} failed: {}", config.engine, e);
+            let _ = store.setting_set("search_engine_last_error", e);
+        }
+    }
+    result
+}
+
+/// Run `reindex` on a background thread, e.g. after the engine settings
+/// are saved
+pub fn reindex_in_background(store: Arc<dyn Store>) {
+    std::thread::spawn(move || {
+        let _ = reindex(&*store);
+    });
+}
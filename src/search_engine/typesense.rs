@@ -0,0 +1,151 @@
+use serde_json::{json, Value};
+
+use super::{client, Config, Engine, SearchDoc};
+use crate::models::search::TITLE_WEIGHT;
+
+/// Typesense caps a page of results at this many hits
+const MAX_PER_PAGE: i64 = 250;
+
+/// A Typesense collection, addressed with an admin API key.
+pub struct Typesense {
+    url: String,
+    api_key: String,
+    collection: String,
+}
+
+impl Typesense {
+    pub fn new(config: &Config) -> Self {
+        Typesense {
+            url: config.url.clone(),
+            api_key: config.api_key.clone(),
+            collection: config.index.clone(),
+        }
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/collections/{}{}", self.url, self.collection, path)
+    }
+
+    fn send(&self, req: reqwest::blocking::RequestBuilder) -> Result<String, String> {
+        let resp = req
+            .header("X-TYPESENSE-API-KEY", &self.api_key)
+            .send()
+            .map_err(|e| format!("Typesense request failed: {}", e))?;
+        let status = resp.status();
+        let text = resp.text().unwrap_or_default();
+        if !status.is_success() {
+            return Err(format!("Typesense returned {}: {}", status, text));
+        }
+        Ok(text)
+    }
+}
+
+/// Collection schema: `item_type` faceted, the display fields stored but
+/// not searched
+pub fn schema(collection: &str) -> Value {
+    json!({
+        "name": collection,
+        "fields": [
+            { "name": "item_type", "type": "string", "facet": true },
+            { "name": "item_id", "type": "int64" },
+            { "name": "title", "type": "string" },
+            { "name": "body", "type": "string" },
+            { "name": "slug", "type": "string", "index": false, "optional": true },
+            { "name": "image", "type": "string", "index": false, "optional": true },
+            { "name": "date", "type": "string", "index": false, "optional": true },
+        ],
+    })
+}
+
+impl Engine for Typesense {
+    fn setup(&self) -> Result<(), String> {
+        if self.send(client()?.get(self.endpoint(""))).is_ok() {
+            return Ok(());
+        }
+        self.send(
+            client()?
+                .post(format!("{}/collections", self.url))
+                .json(&schema(&self.collection)),
+        )
+        .map(|_| ())
+    }
+
+    fn upsert(&self, docs: &[SearchDoc]) -> Result<(), String> {
+        // The import endpoint takes one JSON document per line and reports
+        // each line's outcome rather than failing the request
+        let lines: Vec<String> = docs
+            .iter()
+            .filter_map(|d| serde_json::to_string(d).ok())
+            .collect();
+        let report = self.send(
+            client()?
+                .post(self.endpoint("/documents/import?action=upsert"))
+                .header("Content-Type", "text/plain")
+                .body(lines.join("\n")),
+        )?;
+        match report
+            .lines()
+            .filter_map(|l| serde_json::from_str::<Value>(l).ok())
+            .find(|r| r.get("success").and_then(|s| s.as_bool()) != Some(true))
+        {
+            Some(failed) => Err(format!(
+                "Typesense rejected a document: {}",
+                failed.get("error").and_then(|e| e.as_str()).unwrap_or("")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn delete(&self, id: &str) -> Result<(), String> {
+        match self.send(client()?.delete(self.endpoint(&format!("/documents/{}", id)))) {
+            // Never indexed, e.g. a draft
+            Err(e) if e.contains("404") => Ok(()),
+            other => other.map(|_| ()),
+        }
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        self.send(
+            client()?
+                .delete(self.endpoint("/documents"))
+                .query(&[("filter_by", "item_id:>=0")]),
+        )
+        .map(|_| ())
+    }
+
+    fn search(&self, query: &str, limit: i64) -> Result<Vec<(SearchDoc, f64)>, String> {
+        let weights = format!("{},1", TITLE_WEIGHT as i64);
+        let per_page = limit.clamp(1, MAX_PER_PAGE).to_string();
+        let text = self.send(client()?.get(self.endpoint("/documents/search")).query(&[
+            ("q", query),
+            ("query_by", "title,body"),
+            ("query_by_weights", weights.as_str()),
+            ("num_typos", "2"),
+            ("facet_by", "item_type"),
+            ("per_page", per_page.as_str()),
+        ]))?;
+        let body: Value = serde_json::from_str(&text)
+            .map_err(|e| format!("Typesense JSON parse error: {}", e))?;
+        Ok(parse_hits(&body))
+    }
+}
+
+/// Documents from a search response, with their text-match scores
+pub fn parse_hits(body: &Value) -> Vec<(SearchDoc, f64)> {
+    body.get("hits")
+        .and_then(|h| h.as_array())
+        .map(|hits| {
+            hits.iter()
+                .filter_map(|hit| {
+                    let score = hit
+                        .get("text_match")
+                        .and_then(|s| s.as_f64())
+                        .unwrap_or(0.0);
+                    let doc: SearchDoc =
+                        serde_json::from_value(hit.get("document")?.clone()).ok()?;
+                    Some((doc, score))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
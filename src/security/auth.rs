@@ -3,9 +3,11 @@ use rocket::http::{Cookie, CookieJar, Status};
 use rocket::request::{FromRequest, Outcome, Request};
 use rocket::State;
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::models::user::User;
+use crate::security::permissions;
 use crate::store::Store;
 
 const SESSION_COOKIE: &str = "velocty_session";
@@ -74,7 +76,7 @@ impl<'r> FromRequest<'r> for ClientIp {
 // ── Authenticated user guard (any active user with a valid session) ──
 
 /// Guard: any authenticated user with an active account.
-/// All role-specific guards deref to this.
+/// Capability guards build on the same session lookup.
 pub struct AuthenticatedUser {
     pub user: User,
 }
@@ -91,64 +93,189 @@ impl<'r> FromRequest<'r> for AuthenticatedUser {
     }
 }
 
-// ── Role-specific guards ──
+// ── API key guard ──
 
-/// Guard: requires role = admin
-pub struct AdminUser {
-    pub user: User,
+/// Guard: a request carrying a valid API key (`X-API-Key` or
+/// `Authorization: Bearer vk_…`). Forwards 401 without one.
+pub struct ApiKeyUser {
+    pub key: crate::models::api_key::ApiKey,
 }
 
-#[rocket::async_trait]
-impl<'r> FromRequest<'r> for AdminUser {
-    type Error = ();
-
-    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        match resolve_session_user(request).await {
-            Some(user) if user.is_admin() => Outcome::Success(AdminUser { user }),
-            Some(_) => Outcome::Forward(Status::Forbidden),
-            None => Outcome::Forward(Status::Unauthorized),
-        }
+impl ApiKeyUser {
+    /// Whether the key may call endpoints that change content
+    pub fn can_write(&self) -> bool {
+        self.key.scope == "write"
     }
 }
 
-/// Guard: requires role = admin or editor
-pub struct EditorUser {
-    pub user: User,
-}
-
 #[rocket::async_trait]
-impl<'r> FromRequest<'r> for EditorUser {
+impl<'r> FromRequest<'r> for ApiKeyUser {
     type Error = ();
 
     async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        match resolve_session_user(request).await {
-            Some(user) if user.is_editor_or_above() => Outcome::Success(EditorUser { user }),
-            Some(_) => Outcome::Forward(Status::Forbidden),
+        let store = match request.guard::<&State<Arc<dyn Store>>>().await {
+            Outcome::Success(s) => s,
+            _ => return Outcome::Forward(Status::Unauthorized),
+        };
+        let found = crate::rate_limit::api_key_from_headers(request.headers())
+            .and_then(|k| store.api_key_find_by_hash(&crate::models::api_key::hash_key(&k)));
+        match found {
+            Some(key) => Outcome::Success(ApiKeyUser { key }),
             None => Outcome::Forward(Status::Unauthorized),
         }
     }
 }
 
-/// Guard: requires role = admin, editor, or author
-pub struct AuthorUser {
-    pub user: User,
-}
+// ── Capability guards ──
 
-#[rocket::async_trait]
-impl<'r> FromRequest<'r> for AuthorUser {
-    type Error = ();
+/// Define a request guard that admits active users whose role grants
+/// `capability` (see `security::permissions`). Forwards 401 without a
+/// session and 403 when the capability is missing.
+macro_rules! capability_guard {
+    ($(#[$doc:meta])* $name:ident, $capability:expr) => {
+        $(#[$doc])*
+        pub struct $name {
+            pub user: User,
+        }
 
-    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        match resolve_session_user(request).await {
-            Some(user) if user.is_author_or_above() => Outcome::Success(AuthorUser { user }),
-            Some(_) => Outcome::Forward(Status::Forbidden),
-            None => Outcome::Forward(Status::Unauthorized),
+        #[rocket::async_trait]
+        impl<'r> FromRequest<'r> for $name {
+            type Error = ();
+
+            async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+                match resolve_capability_user(request, $capability).await {
+                    Ok(user) => Outcome::Success($name { user }),
+                    Err(status) => Outcome::Forward(status),
+                }
+            }
         }
-    }
+    };
 }
 
+capability_guard!(
+    /// Guard: may sign in to the admin dashboard
+    DashboardUser,
+    permissions::DASHBOARD_VIEW
+);
+capability_guard!(
+    /// Guard: may create and edit journal posts
+    PostEditor,
+    permissions::POSTS_EDIT
+);
+capability_guard!(
+    /// Guard: may delete journal posts
+    PostDeleter,
+    permissions::POSTS_DELETE
+);
+capability_guard!(
+    /// Guard: may create and edit portfolio items
+    PortfolioEditor,
+    permissions::PORTFOLIO_EDIT
+);
+capability_guard!(
+    /// Guard: may delete portfolio items
+    PortfolioDeleter,
+    permissions::PORTFOLIO_DELETE
+);
+capability_guard!(
+    /// Guard: may upload images and use the media picker
+    MediaUploader,
+    permissions::MEDIA_UPLOAD
+);
+capability_guard!(
+    /// Guard: may manage the media library
+    MediaManager,
+    permissions::MEDIA_MANAGE
+);
+capability_guard!(
+    /// Guard: may moderate comments
+    CommentModerator,
+    permissions::COMMENTS_MODERATE
+);
+capability_guard!(
+    /// Guard: may manage categories and tags
+    CategoryManager,
+    permissions::CATEGORIES_MANAGE
+);
+capability_guard!(
+    /// Guard: may view analytics
+    AnalyticsViewer,
+    permissions::ANALYTICS_VIEW
+);
+capability_guard!(
+    /// Guard: may run SEO checks and audits
+    SeoManager,
+    permissions::SEO_MANAGE
+);
+capability_guard!(
+    /// Guard: may use AI writing tools
+    AiUser,
+    permissions::AI_USE
+);
+capability_guard!(
+    /// Guard: may manage designs and fonts
+    DesignManager,
+    permissions::DESIGNS_MANAGE
+);
+capability_guard!(
+    /// Guard: may change site settings
+    SettingsManager,
+    permissions::SETTINGS_WRITE
+);
+capability_guard!(
+    /// Guard: may manage users and roles
+    UserManager,
+    permissions::USERS_MANAGE
+);
+capability_guard!(
+    /// Guard: may view sales and orders
+    OrdersViewer,
+    permissions::ORDERS_VIEW
+);
+capability_guard!(
+    /// Guard: may manage the firewall
+    FirewallManager,
+    permissions::FIREWALL_MANAGE
+);
+capability_guard!(
+    /// Guard: may run health and maintenance tools
+    HealthManager,
+    permissions::HEALTH_MANAGE
+);
+capability_guard!(
+    /// Guard: may import content
+    Importer,
+    permissions::IMPORT_RUN
+);
+capability_guard!(
+    /// Guard: may manage the mail server and queue
+    MailManager,
+    permissions::MAIL_MANAGE
+);
+capability_guard!(
+    /// Guard: may deploy the site
+    DeployManager,
+    permissions::DEPLOY_MANAGE
+);
+
 // ── Shared session resolution ──
 
+async fn resolve_capability_user(request: &Request<'_>, capability: &str) -> Result<User, Status> {
+    let user = resolve_session_user(request)
+        .await
+        .ok_or(Status::Unauthorized)?;
+    let store = request
+        .guard::<&State<Arc<dyn Store>>>()
+        .await
+        .succeeded()
+        .ok_or(Status::Unauthorized)?;
+    if permissions::user_can(&**store.inner(), &user, capability) {
+        Ok(user)
+    } else {
+        Err(Status::Forbidden)
+    }
+}
+
 async fn resolve_session_user(request: &Request<'_>) -> Option<User> {
     let store = request
         .guard::<&State<Arc<dyn Store>>>()
@@ -168,14 +295,163 @@ async fn resolve_session_user(request: &Request<'_>) -> Option<User> {
 
 // ── Password utilities ──
 
+/// Password hashing preferences from settings. New hashes use the preferred
+/// algorithm; hashes made with anything else still verify and are upgraded
+/// on the next successful login.
+#[derive(Debug, Clone)]
+pub struct HashConfig {
+    /// "argon2id" or "bcrypt"
+    pub algorithm: String,
+    pub argon2_memory_kib: u32,
+    pub argon2_iterations: u32,
+    pub argon2_parallelism: u32,
+    pub bcrypt_cost: u32,
+}
+
+impl Default for HashConfig {
+    /// OWASP's baseline Argon2id parameters (19 MiB, 2 passes, 1 lane).
+    fn default() -> Self {
+        HashConfig {
+            algorithm: "argon2id".to_string(),
+            argon2_memory_kib: 19_456,
+            argon2_iterations: 2,
+            argon2_parallelism: 1,
+            bcrypt_cost: bcrypt::DEFAULT_COST,
+        }
+    }
+}
+
+impl HashConfig {
+    pub fn from_store(store: &dyn Store) -> Self {
+        Self::from_lookup(|key| store.setting_get(key))
+    }
+
+    /// Same as `from_store`, for callers that only have the settings rows
+    /// (e.g. seeding inside a migration).
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        Self::from_lookup(|key| settings.get(key).cloned())
+    }
+
+    fn from_lookup(get: impl Fn(&str) -> Option<String>) -> Self {
+        let d = HashConfig::default();
+        let num = |key: &str, default: u32, min: u32, max: u32| {
+            get(key)
+                .and_then(|v| v.trim().parse::<u32>().ok())
+                .unwrap_or(default)
+                .clamp(min, max)
+        };
+        HashConfig {
+            algorithm: match get("password_hash_algorithm").as_deref() {
+                Some("bcrypt") => "bcrypt".to_string(),
+                _ => "argon2id".to_string(),
+            },
+            argon2_memory_kib: num(
+                "password_argon2_memory_kib",
+                d.argon2_memory_kib,
+                8_192,
+                1_048_576,
+            ),
+            argon2_iterations: num("password_argon2_iterations", d.argon2_iterations, 1, 10),
+            argon2_parallelism: num("password_argon2_parallelism", d.argon2_parallelism, 1, 8),
+            bcrypt_cost: num("password_bcrypt_cost", d.bcrypt_cost, 10, 15),
+        }
+    }
+
+    fn argon2(&self) -> Result<argon2::Argon2<'static>, String> {
+        let params = argon2::Params::new(
+            self.argon2_memory_kib,
+            self.argon2_iterations,
+            self.argon2_parallelism,
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(argon2::Argon2::new(
+            argon2::Algorithm::Argon2id,
+            argon2::Version::V0x13,
+            params,
+        ))
+    }
+}
+
+/// Hash with the default configuration (Argon2id). Only for accounts that
+/// live outside any site, such as the super admin and directory users, since
+/// the registry has no settings of its own.
 pub fn hash_password(password: &str) -> Result<String, String> {
-    bcrypt::hash(password, bcrypt::DEFAULT_COST).map_err(|e| e.to_string())
+    hash_password_with(password, &HashConfig::default())
+}
+
+/// Hash with the site's configured algorithm and parameters.
+pub fn hash_password_for(store: &dyn Store, password: &str) -> Result<String, String> {
+    hash_password_with(password, &HashConfig::from_store(store))
 }
 
+pub fn hash_password_with(password: &str, config: &HashConfig) -> Result<String, String> {
+    use argon2::password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+    if config.algorithm == "bcrypt" {
+        return bcrypt::hash(password, config.bcrypt_cost).map_err(|e| e.to_string());
+    }
+    let salt = SaltString::generate(&mut OsRng);
+    config
+        .argon2()?
+        .hash_password(password.as_bytes(), &salt)
+        .map(|h| h.to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// Verify against an Argon2 (PHC string) or bcrypt hash.
 pub fn verify_password(password: &str, hash: &str) -> bool {
+    use argon2::password_hash::{PasswordHash, PasswordVerifier};
+    if hash.starts_with("$argon2") {
+        return PasswordHash::new(hash)
+            .map(|parsed| {
+                argon2::Argon2::default()
+                    .verify_password(password.as_bytes(), &parsed)
+                    .is_ok()
+            })
+            .unwrap_or(false);
+    }
     bcrypt::verify(password, hash).unwrap_or(false)
 }
 
+/// Whether a stored hash was made with a different algorithm or parameters
+/// than `config` asks for.
+pub fn needs_rehash(hash: &str, config: &HashConfig) -> bool {
+    if config.algorithm == "bcrypt" {
+        // $2b$12$...
+        return !hash.starts_with("$2")
+            || hash.get(4..6).and_then(|c| c.parse::<u32>().ok()) != Some(config.bcrypt_cost);
+    }
+    let parsed = match argon2::password_hash::PasswordHash::new(hash) {
+        Ok(p) if p.algorithm.as_str() == "argon2id" => p,
+        _ => return true,
+    };
+    match argon2::Params::try_from(&parsed) {
+        Ok(p) => {
+            p.m_cost() != config.argon2_memory_kib
+                || p.t_cost() != config.argon2_iterations
+                || p.p_cost() != config.argon2_parallelism
+        }
+        Err(_) => true,
+    }
+}
+
+/// After a successful password login, re-hash with the configured algorithm
+/// if the stored hash is outdated (e.g. bcrypt from before Argon2id support).
+pub fn upgrade_password_hash(store: &dyn Store, user: &User, password: &str) {
+    let config = HashConfig::from_store(store);
+    if !needs_rehash(&user.password_hash, &config) {
+        return;
+    }
+    match hash_password_with(password, &config) {
+        Ok(hash) => {
+            if let Err(e) = store.user_update_password(user.id, &hash) {
+                log::warn!("[auth] Could not upgrade password hash: {}", e);
+            }
+        }
+        Err(e) => log::warn!("[auth] Could not upgrade password hash: {}", e),
+    }
+}
+
 // ── Session management ──
 
 pub fn create_session(
@@ -230,6 +506,13 @@ pub fn set_session_cookie_secure(cookies: &CookieJar<'_>, session_id: &str, stor
     cookies.add_private(cookie);
 }
 
+/// Id of the signed-in session, if any.
+pub fn session_id(cookies: &CookieJar<'_>) -> Option<String> {
+    cookies
+        .get_private(SESSION_COOKIE)
+        .map(|c| c.value().to_string())
+}
+
 pub fn clear_session_cookie(cookies: &CookieJar<'_>) {
     cookies.remove_private(Cookie::from(SESSION_COOKIE));
 }
@@ -0,0 +1,141 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Header, Method};
+use rocket::{Request, Response};
+
+use crate::models::settings::SettingsCache;
+
+/// Response headers a cross-origin caller may read
+const EXPOSED_HEADERS: &str = "RateLimit-Limit, RateLimit-Remaining, RateLimit-Reset, Retry-After";
+
+/// CORS settings, read from the `SettingsCache` on each API request
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    /// Origins such as `https://app.example.com`; `*` allows any
+    pub origins: Vec<String>,
+    pub methods: String,
+    pub headers: String,
+    pub credentials: bool,
+    pub max_age: u64,
+}
+
+impl CorsConfig {
+    /// The configuration, or `None` while CORS is turned off. Credentials
+    /// are never allowed alongside the `*` origin: that would let any site
+    /// read responses made with the visitor's cookies.
+    pub fn load(settings: &SettingsCache) -> Option<CorsConfig> {
+        if settings.get_or("cors_enabled", "false") != "true" {
+            return None;
+        }
+        let origins = parse_origins(&settings.get_or("cors_allowed_origins", ""));
+        let any = origins.iter().any(|o| o == "*");
+        Some(CorsConfig {
+            origins,
+            methods: settings.get_or("cors_allowed_methods", "GET, POST, OPTIONS"),
+            headers: settings.get_or(
+                "cors_allowed_headers",
+                "Content-Type, Authorization, X-API-Key",
+            ),
+            credentials: !any && settings.get_or("cors_allow_credentials", "false") == "true",
+            max_age: settings
+                .get_or("cors_max_age", "600")
+                .parse()
+                .unwrap_or(600),
+        })
+    }
+
+    /// The `Access-Control-Allow-Origin` value for a request from `origin`,
+    /// or `None` if the origin isn't allowed. Listed origins are echoed back;
+    /// `*` is only answered with `*`, and never with credentials.
+    pub fn allow_origin(&self, origin: &str) -> Option<String> {
+        let origin = origin.trim_end_matches('/');
+        if self.origins.iter().any(|o| o == "*") {
+            return (!self.credentials).then(|| "*".to_string());
+        }
+        self.origins
+            .iter()
+            .any(|o| o.eq_ignore_ascii_case(origin))
+            .then(|| origin.to_string())
+    }
+}
+
+/// Origins from a comma- or newline-separated list, without trailing slashes
+pub fn parse_origins(raw: &str) -> Vec<String> {
+    raw.split([',', '\n'])
+        .map(|o| o.trim().trim_end_matches('/').to_string())
+        .filter(|o| !o.is_empty())
+        .collect()
+}
+
+// ── CORS Fairing ────────────────────────────────────────
+
+/// Adds CORS headers to `/api/*` responses for allowed origins, so headless
+/// frontends on other domains can call the content and checkout APIs.
+/// Preflight `OPTIONS` requests are answered by `routes::api::preflight`.
+pub struct Cors;
+
+#[rocket::async_trait]
+impl Fairing for Cors {
+    fn info(&self) -> Info {
+        Info {
+            name: "CORS",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        if !req.uri().path().starts_with("/api/") {
+            return;
+        }
+        let origin = match req.headers().get_one("Origin") {
+            Some(o) => o,
+            None => return,
+        };
+        let settings = match req.rocket().state::<SettingsCache>() {
+            Some(s) => s,
+            None => return,
+        };
+        let config = match CorsConfig::load(settings) {
+            Some(c) => c,
+            None => return,
+        };
+        let allow = match config.allow_origin(origin) {
+            Some(a) => a,
+            None => return,
+        };
+
+        if allow != "*" {
+            vary_on_origin(res);
+        }
+        res.set_header(Header::new("Access-Control-Allow-Origin", allow));
+        if config.credentials {
+            res.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
+        }
+        if req.method() == Method::Options {
+            res.set_header(Header::new("Access-Control-Allow-Methods", config.methods));
+            res.set_header(Header::new("Access-Control-Allow-Headers", config.headers));
+            res.set_header(Header::new(
+                "Access-Control-Max-Age",
+                config.max_age.to_string(),
+            ));
+        } else {
+            res.set_header(Header::new(
+                "Access-Control-Expose-Headers",
+                EXPOSED_HEADERS,
+            ));
+        }
+    }
+}
+
+/// Add `Origin` to the response's `Vary`, keeping whatever else it already
+/// varies on (such as `Accept-Encoding` from compression)
+pub fn vary_on_origin(res: &mut Response<'_>) {
+    let covered = res
+        .headers()
+        .get("Vary")
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .any(|v| v == "*" || v.eq_ignore_ascii_case("Origin"));
+    if !covered {
+        res.adjoin_header(Header::new("Vary", "Origin"));
+    }
+}
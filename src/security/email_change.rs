@@ -0,0 +1,118 @@
+use crate::models::user::User;
+use crate::store::Store;
+
+use super::{members, signed_token};
+
+// ── Email address changes ───────────────────────────────
+//
+// A new address only replaces the old one once the link sent to it is
+// opened. Until then the old address keeps working for sign-in, resets and
+// alerts, and it's told when the change goes through.
+
+/// Confirmation links stay valid for a day
+const CONFIRM_TTL_MINUTES: i64 = 24 * 60;
+
+const INVALID: &str = "This confirmation link is invalid or has expired";
+
+fn send(store: &dyn Store, to: &str, subject: &str, body: &str) -> Result<(), String> {
+    let from = crate::email::get_from_or_admin(&store.setting_all());
+    if from.is_empty() {
+        return Err("No email provider configured".into());
+    }
+    crate::email::send_via_configured_provider(store, "transactional", &from, to, subject, body)
+}
+
+/// Ask to move `user` to `new_email`, emailing a confirmation link to the
+/// new address. Returns the address the link went to.
+pub fn request(store: &dyn Store, user: &User, new_email: &str) -> Result<String, String> {
+    let new_email = new_email.trim().to_lowercase();
+    if !members::valid_email(&new_email) {
+        return Err("Please enter a valid email address".into());
+    }
+    if new_email == user.email.to_lowercase() {
+        return Err("That is already the account's email address".into());
+    }
+    if store.user_get_by_email(&new_email).is_some() {
+        return Err("Another account already uses that email address".into());
+    }
+
+    let token = signed_token::issue(
+        store,
+        signed_token::EMAIL_CHANGE,
+        &new_email,
+        &format!("{}:{}", user.id, user.email),
+        CONFIRM_TTL_MINUTES,
+    )?;
+    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
+    let site_name = store.setting_get_or("site_name", "Velocty");
+    let link = format!(
+        "{}/account/confirm-email?token={}",
+        site_url.trim_end_matches('/'),
+        token
+    );
+    let body = format!(
+        "Hello,\n\n\
+         This address was entered as the new email for an account on {}.\n\
+         Open the link below to confirm it:\n\n\
+         {}\n\n\
+         This link expires in 24 hours. Until it's opened, the account keeps \
+         using its current address. If you didn't ask for this, ignore this email.\n\n\
+         — {}\n",
+        site_name, link, site_name
+    );
+    send(
+        store,
+        &new_email,
+        &format!("Confirm your new email address — {}", site_name),
+        &body,
+    )
+    .map_err(|e| format!("Could not send the confirmation email: {}", e))?;
+    Ok(new_email)
+}
+
+/// Redeem a confirmation link: switch the account to the new address and
+/// tell the old one.
+pub fn confirm(store: &dyn Store, token: &str) -> Result<User, String> {
+    let t = signed_token::verify(store, signed_token::EMAIL_CHANGE, token)?;
+    let (id, old_email) = t.payload.split_once(':').ok_or(INVALID)?;
+    let user = id
+        .parse()
+        .ok()
+        .and_then(|id| store.user_get_by_id(id))
+        .ok_or(INVALID)?;
+    // The address changed some other way after the link was sent
+    if user.email != old_email {
+        return Err(INVALID.into());
+    }
+    if store.user_get_by_email(&t.email).is_some() {
+        return Err("Another account already uses that email address".into());
+    }
+
+    store.user_update_profile(user.id, &user.display_name, &t.email, &user.avatar)?;
+    if store.setting_get_or("admin_email", "") == old_email {
+        let _ = store.setting_set("admin_email", &t.email);
+    }
+    if let Err(e) = notify_old_address(store, old_email, &t.email) {
+        log::warn!("[email_change] Could not notify the old address: {}", e);
+    }
+    store.user_get_by_id(user.id).ok_or_else(|| INVALID.into())
+}
+
+fn notify_old_address(store: &dyn Store, old_email: &str, new_email: &str) -> Result<(), String> {
+    let site_name = store.setting_get_or("site_name", "Velocty");
+    let body = format!(
+        "Hello,\n\n\
+         The email address of your account on {} was changed to {}.\n\
+         Emails about the account will go there from now on.\n\n\
+         If you didn't make this change, contact the site's administrator \
+         right away.\n\n\
+         — {}\n",
+        site_name, new_email, site_name
+    );
+    send(
+        store,
+        old_email,
+        &format!("Your email address was changed — {}", site_name),
+        &body,
+    )
+}
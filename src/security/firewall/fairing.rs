@@ -1,14 +1,15 @@
 use rocket::fairing::{Fairing, Info, Kind};
-use rocket::http::{Header, Status};
+use rocket::http::{ContentType, Status};
 use rocket::{Data, Request, Response};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
 use std::time::Instant;
 
+use crate::models::firewall::auto_ban;
 use crate::store::Store;
 
-use super::inspect;
+use super::{honeypot, inspect, rules};
 
 static FW_REQUEST_COUNTER: AtomicU64 = AtomicU64::new(0);
 
@@ -23,7 +24,7 @@ impl FwRateLimiter {
         }
     }
 
-    /// Returns true if the IP has exceeded the rate limit
+    /// Returns true if the key (usually an IP) has exceeded the rate limit
     fn check(&self, ip: &str, max_requests: u64, window_secs: u64) -> bool {
         let mut map = match self.buckets.lock() {
             Ok(m) => m,
@@ -131,10 +132,137 @@ impl Fairing for FirewallFairing {
 
         // ── 1. Ban check ──
         if store.fw_is_banned(&ip) {
-            request.local_cache(|| FwBlock(true));
+            request.local_cache(|| FwVerdict::Block);
             return;
         }
 
+        let country = crate::geoip::lookup(&**store, &ip).country;
+
+        // ── 1a. Country blocking ──
+        if store.setting_get_bool("fw_geo_blocking_enabled") {
+            // Admin paths have already been rewritten to the internal mount
+            let scope = if path.starts_with(crate::ADMIN_INTERNAL_MOUNT) {
+                "fw_geo_block_admin"
+            } else {
+                "fw_geo_block_visitors"
+            };
+            if store.setting_get_or(scope, "true") != "false"
+                && crate::geoip::country_blocked(
+                    country.as_deref(),
+                    &crate::geoip::parse_countries(
+                        &store.setting_get_or("fw_geo_blocked_countries", ""),
+                    ),
+                    &crate::geoip::parse_countries(
+                        &store.setting_get_or("fw_geo_allowed_countries", ""),
+                    ),
+                )
+            {
+                store.fw_event_log(
+                    &ip,
+                    "geo_block",
+                    Some("Country blocked"),
+                    country.as_deref(),
+                    Some(&ua),
+                    Some(&path),
+                );
+                request.local_cache(|| FwVerdict::Block);
+                return;
+            }
+        }
+
+        // ── 1b. Custom rules ──
+        let fw_rules = store.fw_rule_list();
+        if !fw_rules.is_empty() {
+            let asn = if rules::needs_asn(&fw_rules) {
+                rules::lookup_asn(&store.setting_get_or("fw_asn_db_path", ""), &ip)
+            } else {
+                None
+            };
+            let facts = rules::RequestFacts {
+                path: &path,
+                user_agent: &ua,
+                headers: request.headers(),
+                asn,
+            };
+            if let Some(rule) = rules::first_match(&fw_rules, &facts) {
+                let verdict = match rule.action.as_str() {
+                    "challenge" => {
+                        let token = rules::challenge_token(&**store, &ip).unwrap_or_default();
+                        let passed =
+                            request
+                                .cookies()
+                                .get(rules::CHALLENGE_COOKIE)
+                                .is_some_and(|c| {
+                                    !token.is_empty()
+                                        && crate::security::constant_time_eq(
+                                            c.value().as_bytes(),
+                                            token.as_bytes(),
+                                        )
+                                });
+                        (!passed).then_some(("rule_challenge", FwVerdict::Challenge(token)))
+                    }
+                    "rate_limit" => {
+                        let limiter = request.rocket().state::<FwRateLimiter>().unwrap();
+                        let key = format!("rule:{}:{}", rule.id, ip);
+                        limiter
+                            .check(&key, rule.rate_limit.max(1) as u64, 60)
+                            .then_some(("rule_rate_limit", FwVerdict::Throttle))
+                    }
+                    _ => Some(("rule_block", FwVerdict::Block)),
+                };
+                if let Some((event, verdict)) = verdict {
+                    store.fw_rule_hit(rule.id);
+                    store.fw_event_log(
+                        &ip,
+                        event,
+                        Some(&rule.name),
+                        country.as_deref(),
+                        Some(&ua),
+                        Some(&path),
+                    );
+                    request.local_cache(|| verdict);
+                    return;
+                }
+            }
+        }
+
+        // ── 1c. Honeypot trap paths ──
+        if store.setting_get_or("fw_honeypot_enabled", "true") == "true" {
+            let traps = honeypot::parse_list(&store.setting_get_or("fw_honeypot_paths", ""));
+            let admin_prefix = format!("/{}", store.setting_get_or("admin_slug", "admin"));
+            let is_admin = path == admin_prefix || path.starts_with(&format!("{}/", admin_prefix));
+            if !is_admin
+                && honeypot::is_trap(&path, &traps)
+                && !honeypot::is_excluded(
+                    &ip,
+                    &honeypot::parse_list(&store.setting_get_or("fw_honeypot_exclude_ips", "")),
+                )
+            {
+                store.fw_event_log(
+                    &ip,
+                    "honeypot",
+                    Some("Trap path requested"),
+                    country.as_deref(),
+                    Some(&ua),
+                    Some(&path),
+                );
+                if store.setting_get_or("fw_honeypot_auto_ban", "true") == "true" {
+                    let ban_dur = store.setting_get_or("fw_honeypot_ban_duration", "7d");
+                    let _ = auto_ban(
+                        &**store,
+                        &ip,
+                        "honeypot",
+                        Some(&format!("Requested trap path {}", path)),
+                        &ban_dur,
+                        country.as_deref(),
+                        Some(&ua),
+                    );
+                    request.local_cache(|| FwVerdict::Block);
+                    return;
+                }
+            }
+        }
+
         // ── 2. Rate limiting ──
         if store.setting_get_or("fw_rate_limit_enabled", "true") == "true" {
             let max_req: u64 = store
@@ -158,23 +286,24 @@ impl Fairing for FirewallFairing {
 
             if limiter.check(&ip, max_req, window) {
                 let ban_dur = store.setting_get_or("fw_rate_limit_ban_duration", "1h");
-                let _ = store.fw_ban_create_with_duration(
+                let _ = auto_ban(
+                    &**store,
                     &ip,
                     "rate_limit",
                     Some("Rate limit exceeded"),
                     &ban_dur,
-                    None,
+                    country.as_deref(),
                     Some(&ua),
                 );
                 store.fw_event_log(
                     &ip,
                     "rate_limit",
                     Some("Rate limit exceeded"),
-                    None,
+                    country.as_deref(),
                     Some(&ua),
                     Some(&path),
                 );
-                request.local_cache(|| FwBlock(true));
+                request.local_cache(|| FwVerdict::Block);
                 return;
             }
         }
@@ -187,16 +316,24 @@ impl Fairing for FirewallFairing {
             && inspect::contains_xss(&check_input)
         {
             let ban_dur = store.setting_get_or("fw_injection_ban_duration", "7d");
-            let _ = store.fw_ban_create_with_duration(
+            let _ = auto_ban(
+                &**store,
                 &ip,
                 "xss",
                 Some("XSS attempt detected"),
                 &ban_dur,
-                None,
+                country.as_deref(),
                 Some(&ua),
             );
-            store.fw_event_log(&ip, "xss", Some(&check_input), None, Some(&ua), Some(&path));
-            request.local_cache(|| FwBlock(true));
+            store.fw_event_log(
+                &ip,
+                "xss",
+                Some(&check_input),
+                country.as_deref(),
+                Some(&ua),
+                Some(&path),
+            );
+            request.local_cache(|| FwVerdict::Block);
             return;
         }
 
@@ -204,23 +341,24 @@ impl Fairing for FirewallFairing {
             && inspect::contains_sqli(&check_input)
         {
             let ban_dur = store.setting_get_or("fw_injection_ban_duration", "7d");
-            let _ = store.fw_ban_create_with_duration(
+            let _ = auto_ban(
+                &**store,
                 &ip,
                 "sqli",
                 Some("SQL injection attempt detected"),
                 &ban_dur,
-                None,
+                country.as_deref(),
                 Some(&ua),
             );
             store.fw_event_log(
                 &ip,
                 "sqli",
                 Some(&check_input),
-                None,
+                country.as_deref(),
                 Some(&ua),
                 Some(&path),
             );
-            request.local_cache(|| FwBlock(true));
+            request.local_cache(|| FwVerdict::Block);
             return;
         }
 
@@ -228,23 +366,24 @@ impl Fairing for FirewallFairing {
             && inspect::contains_path_traversal(&check_input)
         {
             let ban_dur = store.setting_get_or("fw_injection_ban_duration", "7d");
-            let _ = store.fw_ban_create_with_duration(
+            let _ = auto_ban(
+                &**store,
                 &ip,
                 "path_traversal",
                 Some("Path traversal attempt detected"),
                 &ban_dur,
-                None,
+                country.as_deref(),
                 Some(&ua),
             );
             store.fw_event_log(
                 &ip,
                 "path_traversal",
                 Some(&check_input),
-                None,
+                country.as_deref(),
                 Some(&ua),
                 Some(&path),
             );
-            request.local_cache(|| FwBlock(true));
+            request.local_cache(|| FwVerdict::Block);
             return;
         }
 
@@ -256,7 +395,7 @@ impl Fairing for FirewallFairing {
                 &ip,
                 "suspicious_bot",
                 Some(&ua),
-                None,
+                country.as_deref(),
                 Some(&ua),
                 Some(&path),
             );
@@ -269,15 +408,16 @@ impl Fairing for FirewallFairing {
                 let count = store.fw_event_count_for_ip_since(&ip, "suspicious_bot", 60);
                 if count >= threshold {
                     let ban_dur = store.setting_get_or("fw_bot_ban_duration", "24h");
-                    let _ = store.fw_ban_create_with_duration(
+                    let _ = auto_ban(
+                        &**store,
                         &ip,
                         "bot",
                         Some("Suspicious bot threshold exceeded"),
                         &ban_dur,
-                        None,
+                        country.as_deref(),
                         Some(&ua),
                     );
-                    request.local_cache(|| FwBlock(true));
+                    request.local_cache(|| FwVerdict::Block);
                     return;
                 }
             }
@@ -285,46 +425,37 @@ impl Fairing for FirewallFairing {
     }
 
     async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
-        // Check if this request was blocked
-        let blocked = req.local_cache(|| FwBlock(false));
-        if blocked.0 {
-            res.set_status(Status::Forbidden);
-            res.set_sized_body(None, std::io::Cursor::new("403 Forbidden"));
-            return;
-        }
-
-        // Security headers — always set regardless of firewall state
-        let store = match req.rocket().state::<std::sync::Arc<dyn Store>>() {
-            Some(s) => s,
-            None => return,
-        };
-
-        if store.setting_get_or("fw_security_headers", "true") == "true" {
-            res.set_header(Header::new("X-Frame-Options", "SAMEORIGIN"));
-            res.set_header(Header::new("X-Content-Type-Options", "nosniff"));
-            res.set_header(Header::new(
-                "Referrer-Policy",
-                "strict-origin-when-cross-origin",
-            ));
-            res.set_header(Header::new(
-                "Permissions-Policy",
-                "camera=(), microphone=(), geolocation=()",
-            ));
-            res.set_header(Header::new("X-XSS-Protection", "1; mode=block"));
-
-            // HSTS: only set when site_url starts with https to avoid breaking HTTP-only dev setups
-            let site_url = store.setting_get_or("site_url", "");
-            if site_url.starts_with("https://") {
-                // max-age=31536000 (1 year); includeSubDomains for full coverage
-                res.set_header(Header::new(
-                    "Strict-Transport-Security",
-                    "max-age=31536000; includeSubDomains",
-                ));
+        // Check if this request was blocked, challenged or throttled
+        match req.local_cache(|| FwVerdict::Allow) {
+            FwVerdict::Allow => {}
+            FwVerdict::Block => {
+                res.set_status(Status::Forbidden);
+                res.set_sized_body(None, std::io::Cursor::new("403 Forbidden"));
+            }
+            FwVerdict::Throttle => {
+                res.set_status(Status::TooManyRequests);
+                res.set_raw_header("Retry-After", "60");
+                res.set_sized_body(None, std::io::Cursor::new("429 Too Many Requests"));
+            }
+            FwVerdict::Challenge(token) => {
+                res.set_status(Status::Forbidden);
+                res.set_header(ContentType::HTML);
+                res.set_raw_header("Cache-Control", "no-store");
+                let nonce = crate::security::headers::CspNonce::for_request(req).0;
+                let page = rules::challenge_page(token, nonce.as_deref());
+                res.set_sized_body(None, std::io::Cursor::new(page));
             }
         }
     }
 }
 
-/// Local cache marker for blocked requests
-#[derive(Clone, Copy)]
-struct FwBlock(bool);
+/// Local cache marker for what the firewall decided about a request
+#[derive(Clone)]
+enum FwVerdict {
+    Allow,
+    Block,
+    /// Serve the JavaScript challenge page with this cookie value
+    Challenge(String),
+    /// Too many requests for a rate-limit rule
+    Throttle,
+}
@@ -0,0 +1,68 @@
+use std::net::IpAddr;
+
+/// Paths only vulnerability scanners ask for on a Velocty site, one per line.
+pub const DEFAULT_TRAP_PATHS: &str = "/wp-login.php
+/wp-admin*
+/xmlrpc.php
+/.env
+/.git*
+/phpmyadmin*
+/administrator/index.php
+/config.php
+/.aws/credentials
+/server-status
+/vendor/phpunit*
+/cgi-bin*";
+
+/// Split a one-per-line (or comma-separated) setting into trimmed entries,
+/// skipping blanks and `#` comments.
+pub fn parse_list(raw: &str) -> Vec<String> {
+    raw.split(['\n', ','])
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| l.to_string())
+        .collect()
+}
+
+/// Whether `path` is one of the traps. Entries match the whole path,
+/// case-insensitively; a trailing `*` matches any path with that prefix.
+pub fn is_trap(path: &str, traps: &[String]) -> bool {
+    let path = path.to_ascii_lowercase();
+    traps.iter().any(|t| {
+        let t = t.to_ascii_lowercase();
+        match t.strip_suffix('*') {
+            Some(prefix) => !prefix.is_empty() && path.starts_with(prefix),
+            None => path == t,
+        }
+    })
+}
+
+/// Whether `ip` is covered by an exclusion list of addresses and CIDR ranges
+/// ("203.0.113.7", "198.51.100.0/24", "2001:db8::/32").
+pub fn is_excluded(ip: &str, exclusions: &[String]) -> bool {
+    let addr: IpAddr = match ip.parse() {
+        Ok(a) => a,
+        Err(_) => return false,
+    };
+    exclusions.iter().any(|entry| match entry.split_once('/') {
+        Some((net, bits)) => match (net.trim().parse::<IpAddr>(), bits.trim().parse::<u32>()) {
+            (Ok(net), Ok(bits)) => in_range(addr, net, bits),
+            _ => false,
+        },
+        None => entry.parse::<IpAddr>().is_ok_and(|e| e == addr),
+    })
+}
+
+fn in_range(addr: IpAddr, net: IpAddr, bits: u32) -> bool {
+    match (addr, net) {
+        (IpAddr::V4(a), IpAddr::V4(n)) if bits <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - bits).unwrap_or(0);
+            u32::from(a) & mask == u32::from(n) & mask
+        }
+        (IpAddr::V6(a), IpAddr::V6(n)) if bits <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - bits).unwrap_or(0);
+            u128::from(a) & mask == u128::from(n) & mask
+        }
+        _ => false,
+    }
+}
@@ -1,4 +1,6 @@
 mod fairing;
+pub mod honeypot;
 mod inspect;
+pub mod rules;
 
 pub use fairing::{FirewallFairing, FwRateLimiter};
@@ -0,0 +1,214 @@
+use regex::Regex;
+use rocket::http::HeaderMap;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::models::firewall::FwRule;
+use crate::store::Store;
+
+pub const MATCH_TYPES: &[&str] = &["path", "user_agent", "header", "asn"];
+pub const ACTIONS: &[&str] = &["block", "challenge", "rate_limit"];
+
+/// Name of the cookie that proves a client passed a challenge.
+pub const CHALLENGE_COOKIE: &str = "velocty_fw_pass";
+
+/// What a rule is tested against.
+pub struct RequestFacts<'a> {
+    pub path: &'a str,
+    pub user_agent: &'a str,
+    pub headers: &'a HeaderMap<'a>,
+    /// Autonomous system number of the client IP, when an ASN database is
+    /// configured and has an entry for it.
+    pub asn: Option<u32>,
+}
+
+/// Compile a pattern case-insensitively, caching the result (including
+/// failures) so each rule is only compiled once.
+fn regex(pattern: &str) -> Option<Regex> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Option<Regex>>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    let mut map = cache.lock().ok()?;
+    if map.len() > 512 {
+        map.clear();
+    }
+    map.entry(pattern.to_string())
+        .or_insert_with(|| Regex::new(&format!("(?i){}", pattern)).ok())
+        .clone()
+}
+
+/// Split a header pattern into the header name and an optional value regex.
+/// "X-Debug" matches on presence, "X-Debug: ^1$" on the value.
+fn split_header(pattern: &str) -> (&str, Option<&str>) {
+    match pattern.split_once(':') {
+        Some((name, value)) => (name.trim(), Some(value.trim())),
+        None => (pattern.trim(), None),
+    }
+}
+
+/// Parse a comma-separated ASN list ("AS15169, 16509"). Returns None if any
+/// entry isn't a number.
+pub fn parse_asns(pattern: &str) -> Option<Vec<u32>> {
+    pattern
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            let s = s
+                .strip_prefix("AS")
+                .or_else(|| s.strip_prefix("as"))
+                .unwrap_or(s);
+            s.parse::<u32>().ok()
+        })
+        .collect()
+}
+
+/// Check a rule before saving it.
+pub fn validate(rule: &FwRule) -> Result<(), String> {
+    if rule.name.trim().is_empty() {
+        return Err("Rule name is required".into());
+    }
+    if !MATCH_TYPES.contains(&rule.match_type.as_str()) {
+        return Err(format!("Unknown match type '{}'", rule.match_type));
+    }
+    if !ACTIONS.contains(&rule.action.as_str()) {
+        return Err(format!("Unknown action '{}'", rule.action));
+    }
+    let pattern = rule.pattern.trim();
+    if pattern.is_empty() {
+        return Err("Pattern is required".into());
+    }
+    match rule.match_type.as_str() {
+        "path" | "user_agent" => {
+            Regex::new(pattern).map_err(|e| format!("Invalid pattern: {}", e))?;
+        }
+        "header" => {
+            let (name, value) = split_header(pattern);
+            if name.is_empty() || name.contains(char::is_whitespace) {
+                return Err("Header pattern must be 'Name' or 'Name: regex'".into());
+            }
+            if let Some(v) = value.filter(|v| !v.is_empty()) {
+                Regex::new(v).map_err(|e| format!("Invalid header value pattern: {}", e))?;
+            }
+        }
+        _ => match parse_asns(pattern) {
+            Some(list) if !list.is_empty() => {}
+            _ => return Err("ASN pattern must be a comma-separated list like AS15169".into()),
+        },
+    }
+    if rule.action == "rate_limit" && rule.rate_limit < 1 {
+        return Err("Rate limit must be at least 1 request per minute".into());
+    }
+    Ok(())
+}
+
+/// Whether a single rule matches the request. Disabled rules never match.
+pub fn matches(rule: &FwRule, facts: &RequestFacts<'_>) -> bool {
+    if !rule.enabled {
+        return false;
+    }
+    let pattern = rule.pattern.trim();
+    match rule.match_type.as_str() {
+        "path" => regex(pattern).is_some_and(|re| re.is_match(facts.path)),
+        "user_agent" => regex(pattern).is_some_and(|re| re.is_match(facts.user_agent)),
+        "header" => {
+            let (name, value) = split_header(pattern);
+            match value.filter(|v| !v.is_empty()) {
+                None => facts.headers.contains(name),
+                Some(v) => match regex(v) {
+                    Some(re) => facts.headers.get(name).any(|h| re.is_match(h)),
+                    None => false,
+                },
+            }
+        }
+        "asn" => match (facts.asn, parse_asns(pattern)) {
+            (Some(asn), Some(list)) => list.contains(&asn),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// First matching rule, in priority order.
+pub fn first_match<'r>(rules: &'r [FwRule], facts: &RequestFacts<'_>) -> Option<&'r FwRule> {
+    rules.iter().find(|r| matches(r, facts))
+}
+
+/// Whether any enabled rule needs an ASN lookup.
+pub fn needs_asn(rules: &[FwRule]) -> bool {
+    rules.iter().any(|r| r.enabled && r.match_type == "asn")
+}
+
+// ── ASN lookup (GeoLite2-ASN) ────────────────────────────
+
+type AsnReader = maxminddb::Reader<Vec<u8>>;
+
+/// Look up the autonomous system of `ip` in the MaxMind ASN database at
+/// `db_path`. The database is opened once and reused until the path changes.
+pub fn lookup_asn(db_path: &str, ip: &str) -> Option<u32> {
+    static READER: OnceLock<Mutex<Option<(String, Arc<AsnReader>)>>> = OnceLock::new();
+    if db_path.is_empty() {
+        return None;
+    }
+    let addr: IpAddr = ip.parse().ok()?;
+    let reader = {
+        let mut slot = READER.get_or_init(|| Mutex::new(None)).lock().ok()?;
+        match slot.as_ref() {
+            Some((path, r)) if path == db_path => Arc::clone(r),
+            _ => match maxminddb::Reader::open_readfile(db_path) {
+                Ok(r) => {
+                    let r = Arc::new(r);
+                    *slot = Some((db_path.to_string(), Arc::clone(&r)));
+                    r
+                }
+                Err(e) => {
+                    log::warn!("[firewall] Cannot open ASN database {}: {}", db_path, e);
+                    return None;
+                }
+            },
+        }
+    };
+    reader
+        .lookup::<maxminddb::geoip2::Asn>(addr)
+        .ok()
+        .and_then(|a| a.autonomous_system_number)
+}
+
+// ── Challenge ────────────────────────────────────────────
+
+/// Value of the challenge cookie for `ip`. It changes daily, so a pass lasts
+/// until midnight UTC at most.
+pub fn challenge_token(store: &dyn Store, ip: &str) -> Option<String> {
+    let day = chrono::Utc::now().format("%Y-%m-%d");
+    crate::security::signed_token::mac(
+        store,
+        crate::security::signed_token::FW_CHALLENGE,
+        &format!("{}|{}", ip, day),
+    )
+    .ok()
+}
+
+/// Interstitial page that sets the challenge cookie from script and reloads.
+/// Clients that don't run JavaScript never get past it. `nonce` is the
+/// request's CSP nonce, if the policy uses one.
+pub fn challenge_page(token: &str, nonce: Option<&str>) -> String {
+    let nonce_attr = nonce
+        .map(|n| format!(" nonce=\"{}\"", n))
+        .unwrap_or_default();
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head><meta charset="utf-8"><meta name="robots" content="noindex"><title>Checking your browser…</title></head>
+<body style="font-family:system-ui,sans-serif;text-align:center;padding:4rem 1rem;color:#333">
+<h1 style="font-size:1.4rem">Checking your browser…</h1>
+<p>This only takes a moment.</p>
+<noscript><p>Please enable JavaScript to continue.</p></noscript>
+<script{}>
+document.cookie = "{}={}; path=/; max-age=86400; SameSite=Lax";
+setTimeout(function () {{ location.reload(); }}, 800);
+</script>
+</body>
+</html>"#,
+        nonce_attr, CHALLENGE_COOKIE, token
+    )
+}
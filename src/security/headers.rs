@@ -0,0 +1,444 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::RngCore;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Request, Response};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::models::settings::SettingsCache;
+use crate::store::Store;
+use crate::ADMIN_INTERNAL_MOUNT;
+
+/// Inline event handlers emitted by the built-in designs and renderers. With a
+/// nonce-based policy these are allowed by hash via `'unsafe-hashes'`, so
+/// existing layouts keep working without `'unsafe-inline'`.
+const INLINE_HANDLERS: &[&str] = &[
+    "document.querySelector('.sidebar').classList.toggle('mobile-open')",
+    "document.querySelector('.topbar-nav').classList.toggle('mobile-open');this.classList.toggle('active')",
+    "this.classList.toggle('open');this.nextElementSibling.classList.toggle('open')",
+    "this.parentElement.nextElementSibling.classList.toggle('open')",
+    "lookupPurchase()",
+    "commerceRazorpay()",
+    "commerceRedirect('stripe')",
+    "commerceRedirect('mollie')",
+    "commerceRedirect('square')",
+    "commerceRedirect('2checkout')",
+    "commerceRedirect('payoneer')",
+];
+
+// ── Nonce ───────────────────────────────────────────────
+
+/// Per-request CSP nonce, None when public pages don't use a nonce-based
+/// policy. Public handlers take it as a request guard and render inside
+/// `render::with_csp_nonce`, so only the scripts the site itself emits carry
+/// it; the response body is never rewritten.
+#[derive(Clone)]
+pub struct CspNonce(pub Option<String>);
+
+impl CspNonce {
+    pub fn for_request(req: &Request<'_>) -> CspNonce {
+        req.local_cache(|| {
+            let enabled = match req.rocket().state::<SettingsCache>() {
+                Some(cache) => uses_nonce(&cache.all()),
+                None => false,
+            };
+            CspNonce(enabled.then(|| {
+                let mut bytes = [0u8; 16];
+                rand::thread_rng().fill_bytes(&mut bytes);
+                STANDARD.encode(bytes)
+            }))
+        })
+        .clone()
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CspNonce {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(CspNonce::for_request(request))
+    }
+}
+
+/// Whether public pages are served with a nonce-based policy
+pub fn uses_nonce(settings: &HashMap<String, String>) -> bool {
+    let mode = settings
+        .get("csp_mode")
+        .map(|v| v.as_str())
+        .unwrap_or("off");
+    (mode == "enforce" || mode == "report-only") && on(settings, "csp_nonce_enabled", true)
+}
+
+// ── Policy builder ──────────────────────────────────────
+
+fn on(settings: &HashMap<String, String>, key: &str, default: bool) -> bool {
+    match settings.get(key).map(|v| v.as_str()) {
+        Some("true") => true,
+        Some(_) => false,
+        None => default,
+    }
+}
+
+fn origin_of(url: &str) -> Option<String> {
+    let url = url.trim();
+    let scheme_end = url.find("://")? + 3;
+    let host_end = url[scheme_end..]
+        .find('/')
+        .map(|i| scheme_end + i)
+        .unwrap_or(url.len());
+    let origin = &url[..host_end];
+    if origin.len() > scheme_end && !origin.contains(char::is_whitespace) {
+        Some(origin.to_string())
+    } else {
+        None
+    }
+}
+
+/// Whether `uri` can go in `report-uri`: an absolute http(s) URL with
+/// nothing that would end the directive or the header.
+pub fn valid_report_uri(uri: &str) -> bool {
+    (uri.starts_with("https://") || uri.starts_with("http://"))
+        && origin_of(uri).is_some()
+        && !uri
+            .chars()
+            .any(|c| c == ';' || c == ',' || c.is_whitespace() || c.is_control())
+}
+
+/// The extra directives from `csp_custom_directives`, one per line or `;`.
+/// A directive is a name and its values; commas (which would start a
+/// second policy) and control characters are rejected.
+pub fn custom_directives(value: &str) -> Result<Vec<String>, String> {
+    let mut directives = Vec::new();
+    for d in value.split([';', '\n']).map(|d| d.trim()) {
+        if d.is_empty() {
+            continue;
+        }
+        let name = d.split_whitespace().next().unwrap_or("");
+        if !name.chars().all(|c| c.is_ascii_alphabetic() || c == '-')
+            || d.chars().any(|c| c == ',' || c.is_control())
+        {
+            return Err(format!("Invalid CSP directive: {}", d));
+        }
+        directives.push(d.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+    Ok(directives)
+}
+
+/// Whether `source` is a scheme-source (`https:`) or host-source
+/// (`https://*.example.com:8443/path`) as CSP defines them.
+fn valid_source(source: &str) -> bool {
+    let scheme_ok = |s: &str| {
+        s.starts_with(|c: char| c.is_ascii_alphabetic())
+            && s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    };
+    if let Some(scheme) = source.strip_suffix(':') {
+        return scheme_ok(scheme);
+    }
+    let rest = match source.split_once("://") {
+        Some((scheme, rest)) if scheme_ok(scheme) => rest,
+        Some(_) => return false,
+        None => source,
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, ""),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((h, p)) => (h, Some(p)),
+        None => (authority, None),
+    };
+    let host = match host {
+        "*" => "",
+        h => h.strip_prefix("*.").unwrap_or(h),
+    };
+    let host_ok = (host.is_empty() && authority.starts_with('*'))
+        || host.split('.').all(|label| {
+            !label.is_empty() && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    let port_ok = match port {
+        Some(p) => p == "*" || (!p.is_empty() && p.chars().all(|c| c.is_ascii_digit())),
+        None => true,
+    };
+    let path_ok = path
+        .chars()
+        .all(|c| c.is_ascii_graphic() && !matches!(c, ';' | ',' | '\'' | '"'));
+    host_ok && port_ok && path_ok
+}
+
+/// The `frame-ancestors` source list for a `security_frame_ancestors` value:
+/// "self", "none", or a space-separated list of origins, which are allowed
+/// alongside `'self'`. Anything that isn't a valid source is rejected so it
+/// can't add directives of its own.
+pub fn frame_ancestor_sources(value: &str) -> Result<String, String> {
+    match value.trim() {
+        "" | "self" | "'self'" => return Ok("'self'".to_string()),
+        "none" | "'none'" => return Ok("'none'".to_string()),
+        _ => {}
+    }
+    let mut sources = vec!["'self'".to_string()];
+    for source in value.split_whitespace() {
+        if source == "'self'" || source == "self" {
+            continue;
+        }
+        if !valid_source(source) {
+            return Err(format!("Invalid frame ancestor: {}", source));
+        }
+        sources.push(source.to_string());
+    }
+    Ok(sources.join(" "))
+}
+
+/// `frame-ancestors` value from settings. It is checked when saved; a value
+/// that slipped past falls back to `'self'`.
+pub fn frame_ancestors(settings: &HashMap<String, String>) -> String {
+    let value = settings
+        .get("security_frame_ancestors")
+        .map(|v| v.as_str())
+        .unwrap_or("self");
+    frame_ancestor_sources(value).unwrap_or_else(|_| "'self'".to_string())
+}
+
+/// Build the Content-Security-Policy for public pages from settings.
+/// `nonce` is included in `script-src` when nonce support is enabled.
+pub fn build_csp(settings: &HashMap<String, String>, nonce: Option<&str>) -> String {
+    let get = |key: &str| settings.get(key).map(|v| v.trim()).unwrap_or("");
+    let mut script = vec!["'self'".to_string()];
+    let mut style = vec!["'self'".to_string(), "'unsafe-inline'".to_string()];
+    let mut font = vec!["'self'".to_string(), "data:".to_string()];
+    let mut connect = vec!["'self'".to_string()];
+    let mut frame = vec!["'self'".to_string()];
+
+    match nonce {
+        Some(n) if on(settings, "csp_nonce_enabled", true) => {
+            script.push(format!("'nonce-{}'", n));
+            script.push("'unsafe-hashes'".to_string());
+            for h in INLINE_HANDLERS {
+                script.push(format!(
+                    "'sha256-{}'",
+                    STANDARD.encode(Sha256::digest(h.as_bytes()))
+                ));
+            }
+        }
+        _ => script.push("'unsafe-inline'".to_string()),
+    }
+
+    if on(settings, "csp_allow_google_fonts", true) {
+        style.push("https://fonts.googleapis.com".into());
+        font.push("https://fonts.gstatic.com".into());
+        style.push("https://use.typekit.net".into());
+        font.push("https://use.typekit.net".into());
+    }
+
+    if on(settings, "csp_allow_analytics", true) {
+        let enabled = |key: &str| get(key) == "true";
+        if enabled("seo_ga_enabled") {
+            script.push("https://www.googletagmanager.com".into());
+            connect.push("https://*.google-analytics.com".into());
+        }
+        if enabled("seo_plausible_enabled") {
+            let host = origin_of(get("seo_plausible_host"))
+                .unwrap_or_else(|| "https://plausible.io".into());
+            script.push(host.clone());
+            connect.push(host);
+        }
+        if enabled("seo_fathom_enabled") {
+            script.push("https://cdn.usefathom.com".into());
+            connect.push("https://cdn.usefathom.com".into());
+        }
+        if enabled("seo_matomo_enabled") {
+            if let Some(host) = origin_of(get("seo_matomo_url")) {
+                script.push(host.clone());
+                connect.push(host);
+            }
+        }
+        if enabled("seo_cloudflare_analytics_enabled") {
+            script.push("https://static.cloudflareinsights.com".into());
+            connect.push("https://cloudflareinsights.com".into());
+        }
+        if enabled("seo_clicky_enabled") {
+            script.push("https://static.getclicky.com".into());
+            connect.push("https://in.getclicky.com".into());
+        }
+        if enabled("seo_umami_enabled") {
+            let host = origin_of(get("seo_umami_host"))
+                .unwrap_or_else(|| "https://analytics.umami.is".into());
+            script.push(host.clone());
+            connect.push(host);
+        }
+    }
+
+    if on(settings, "csp_allow_payments", true) {
+        if get("commerce_paypal_enabled") == "true" {
+            for s in [&mut script, &mut connect, &mut frame] {
+                s.push("https://*.paypal.com".into());
+            }
+        }
+        if get("commerce_razorpay_enabled") == "true" {
+            script.push("https://checkout.razorpay.com".into());
+            connect.push("https://api.razorpay.com".into());
+            frame.push("https://api.razorpay.com".into());
+        }
+    }
+
+    if on(settings, "csp_allow_captcha", true) {
+        if get("security_recaptcha_enabled") == "true" {
+            script.push("https://www.google.com/recaptcha/".into());
+            script.push("https://www.gstatic.com/recaptcha/".into());
+            frame.push("https://www.google.com".into());
+        }
+        if get("security_hcaptcha_enabled") == "true" {
+            for s in [&mut script, &mut connect, &mut frame] {
+                s.push("https://hcaptcha.com".into());
+                s.push("https://*.hcaptcha.com".into());
+            }
+        }
+        if get("security_turnstile_enabled") == "true" {
+            script.push("https://challenges.cloudflare.com".into());
+            frame.push("https://challenges.cloudflare.com".into());
+        }
+    }
+
+    if on(settings, "csp_allow_embeds", true) {
+        frame.push("https://www.youtube.com".into());
+        frame.push("https://www.youtube-nocookie.com".into());
+        frame.push("https://player.vimeo.com".into());
+    }
+
+    let mut directives = vec![
+        "default-src 'self'".to_string(),
+        format!("script-src {}", script.join(" ")),
+        format!("style-src {}", style.join(" ")),
+        "img-src 'self' data: blob: https:".to_string(),
+        "media-src 'self' blob: https:".to_string(),
+        format!("font-src {}", font.join(" ")),
+        format!("connect-src {}", connect.join(" ")),
+        format!("frame-src {}", frame.join(" ")),
+        "object-src 'none'".to_string(),
+        "base-uri 'self'".to_string(),
+        "form-action 'self' https:".to_string(),
+        format!("frame-ancestors {}", frame_ancestors(settings)),
+    ];
+    // Both are checked when saved; a value that slipped past is dropped
+    // rather than allowed to break out of the header
+    directives.extend(custom_directives(get("csp_custom_directives")).unwrap_or_default());
+    let report_uri = get("csp_report_uri");
+    if valid_report_uri(report_uri) {
+        directives.push(format!("report-uri {}", report_uri));
+    }
+    directives.join("; ")
+}
+
+/// HSTS header value, or None when disabled.
+pub fn hsts_value(settings: &HashMap<String, String>) -> Option<String> {
+    let max_age: u64 = settings
+        .get("security_hsts_max_age")
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(31_536_000);
+    if max_age == 0 {
+        return None;
+    }
+    let mut value = format!("max-age={}", max_age);
+    if on(settings, "security_hsts_subdomains", true) {
+        value.push_str("; includeSubDomains");
+    }
+    if on(settings, "security_hsts_preload", false) {
+        value.push_str("; preload");
+    }
+    Some(value)
+}
+
+// ── Fairing ─────────────────────────────────────────────
+
+/// Adds security headers to every response, and the Content-Security-Policy
+/// to public pages. Admin pages rely on inline handlers throughout, so they
+/// only get `frame-ancestors`.
+pub struct SecurityHeaders;
+
+#[rocket::async_trait]
+impl Fairing for SecurityHeaders {
+    fn info(&self) -> Info {
+        Info {
+            name: "Security Headers",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let settings = match req.rocket().state::<SettingsCache>() {
+            Some(cache) => cache.all(),
+            None => match req.rocket().state::<Arc<dyn Store>>() {
+                Some(store) => store.setting_all(),
+                None => return,
+            },
+        };
+        if settings.get("fw_security_headers").map(|v| v.as_str()) == Some("false") {
+            return;
+        }
+
+        let ancestors = frame_ancestors(&settings);
+        let xfo = match ancestors.as_str() {
+            "'none'" => Some("DENY"),
+            "'self'" => Some("SAMEORIGIN"),
+            // Custom origins can't be expressed in X-Frame-Options
+            _ => None,
+        };
+        if let Some(xfo) = xfo {
+            res.set_header(Header::new("X-Frame-Options", xfo));
+        }
+        res.set_header(Header::new("X-Content-Type-Options", "nosniff"));
+        let referrer = settings
+            .get("security_referrer_policy")
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .unwrap_or("strict-origin-when-cross-origin");
+        res.set_header(Header::new("Referrer-Policy", referrer.to_string()));
+        res.set_header(Header::new(
+            "Permissions-Policy",
+            "camera=(), microphone=(), geolocation=()",
+        ));
+        res.set_header(Header::new("X-XSS-Protection", "1; mode=block"));
+
+        // HSTS: only over https to avoid breaking HTTP-only dev setups
+        let site_url = settings.get("site_url").map(|s| s.as_str()).unwrap_or("");
+        if site_url.starts_with("https://") {
+            if let Some(hsts) = hsts_value(&settings) {
+                res.set_header(Header::new("Strict-Transport-Security", hsts));
+            }
+        }
+
+        let path = req.uri().path();
+        let public = !path.starts_with(ADMIN_INTERNAL_MOUNT)
+            && !path.starts_with("/super")
+            && !path.starts_with("/static");
+        let mode = settings
+            .get("csp_mode")
+            .map(|v| v.as_str())
+            .unwrap_or("off");
+        let frame_only = format!("frame-ancestors {}", ancestors);
+        if !public || !(mode == "enforce" || mode == "report-only") {
+            res.set_header(Header::new("Content-Security-Policy", frame_only));
+            return;
+        }
+
+        // The nonce the page was rendered with, if its handler asked for one
+        let is_html = res.content_type().is_some_and(|ct| ct.is_html());
+        let nonce = if is_html {
+            CspNonce::for_request(req).0
+        } else {
+            None
+        };
+        let csp = build_csp(&settings, nonce.as_deref());
+        if mode == "enforce" {
+            res.set_header(Header::new("Content-Security-Policy", csp));
+        } else {
+            // frame-ancestors is ignored in report-only policies, so keep enforcing it
+            res.set_header(Header::new("Content-Security-Policy", frame_only));
+            res.set_header(Header::new("Content-Security-Policy-Report-Only", csp));
+        }
+    }
+}
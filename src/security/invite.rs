@@ -0,0 +1,117 @@
+use crate::models::user::User;
+use crate::store::Store;
+
+use super::{auth, members, password_policy, password_reset, permissions, signed_token};
+
+// ── Invitations ─────────────────────────────────────────
+//
+// Staff accounts created by invitation instead of with a password chosen by
+// an admin. The account starts "invited" with a random password nobody
+// knows, so it can't sign in until the emailed link is opened and the
+// invitee picks their own password. Accepting signs them in and offers
+// passkey and MFA setup before the dashboard.
+
+/// Status of an account whose invitation hasn't been accepted yet
+pub const STATUS: &str = "invited";
+
+/// Invitation links stay valid for a week
+const INVITE_TTL_MINUTES: i64 = 7 * 24 * 60;
+
+/// Create an invited account and email its invitation link
+pub fn invite(
+    store: &dyn Store,
+    inviter: &User,
+    email: &str,
+    display_name: &str,
+    role: &str,
+) -> Result<i64, String> {
+    let email = email.trim().to_lowercase();
+    let display_name = display_name.trim();
+    if !members::valid_email(&email) {
+        return Err("Please enter a valid email address".into());
+    }
+    if display_name.is_empty() {
+        return Err("Email and display name are required".into());
+    }
+    if !permissions::role_exists(store, role) {
+        return Err("Invalid role".into());
+    }
+    if !permissions::can_grant(store, inviter, role) {
+        return Err("You can't assign a role with more access than your own".into());
+    }
+    if store.user_get_by_email(&email).is_some() {
+        return Err("A user with this email already exists".into());
+    }
+
+    let hash = auth::hash_password_for(store, &password_reset::generate_temp_password())?;
+    let id = store.user_create(&email, &hash, display_name, role)?;
+    store.user_set_status(id, STATUS)?;
+    // An invitation nobody received can't be accepted; let the admin retry
+    if let Err(e) = send(store, inviter, &email) {
+        let _ = store.user_delete(id);
+        return Err(format!("Could not send the invitation email: {}", e));
+    }
+    Ok(id)
+}
+
+/// Email a new invitation link to an account that hasn't accepted yet.
+/// Earlier links keep working until they expire.
+pub fn resend(store: &dyn Store, inviter: &User, user: &User) -> Result<(), String> {
+    if user.status != STATUS {
+        return Err("This user has already accepted their invitation".into());
+    }
+    send(store, inviter, &user.email)
+}
+
+fn send(store: &dyn Store, inviter: &User, email: &str) -> Result<(), String> {
+    let token = signed_token::issue(store, signed_token::INVITE, email, "", INVITE_TTL_MINUTES)?;
+    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
+    let site_name = store.setting_get_or("site_name", "Velocty");
+    let admin_slug = store.setting_get_or("admin_slug", "admin");
+    let link = format!(
+        "{}/{}/invite?token={}",
+        site_url.trim_end_matches('/'),
+        admin_slug,
+        token
+    );
+    let body = format!(
+        "Hello,\n\n\
+         {} invited you to help run {}. Open this link to choose your password \
+         and set up your account:\n\n\
+         {}\n\n\
+         This link expires in 7 days. If you weren't expecting this invitation, \
+         you can ignore this email.\n\n\
+         — {}\n",
+        inviter.display_name, site_name, link, site_name
+    );
+    let from = crate::email::get_from_or_admin(&store.setting_all());
+    if from.is_empty() {
+        return Err("No email provider configured".into());
+    }
+    crate::email::send_via_configured_provider(
+        store,
+        "transactional",
+        &from,
+        email,
+        &format!("You're invited to {}", site_name),
+        &body,
+    )
+}
+
+/// Redeem an invitation: set the invitee's password and activate the account
+pub fn accept(store: &dyn Store, token: &str, password: &str) -> Result<User, String> {
+    // Check the password before the single-use token is spent
+    password_policy::check(store, password, &[])?;
+    let t = signed_token::verify(store, signed_token::INVITE, token)?;
+    let user = store
+        .user_get_by_email(&t.email)
+        .filter(|u| u.status == STATUS)
+        .ok_or("This invitation is no longer valid")?;
+
+    let hash = auth::hash_password_for(store, password)?;
+    store.user_update_password(user.id, &hash)?;
+    store.user_set_status(user.id, "active")?;
+    store
+        .user_get_by_id(user.id)
+        .ok_or_else(|| "This invitation is no longer valid".into())
+}
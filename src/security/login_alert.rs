@@ -0,0 +1,208 @@
+use std::sync::Arc;
+
+use rocket::http::HeaderMap;
+use rocket::request::{FromRequest, Outcome, Request};
+
+use crate::models::user::User;
+use crate::store::Store;
+
+use super::{auth, password_reset, signed_token};
+
+/// "This wasn't me" links stay valid for a week.
+const DENY_TTL_MINUTES: i64 = 7 * 24 * 60;
+
+/// Where a sign-in came from.
+#[derive(Debug, Clone)]
+pub struct LoginClient {
+    pub ip: String,
+    pub user_agent: String,
+    /// "City, CC", "CC", or empty when unknown.
+    pub location: String,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for LoginClient {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let ip = match request.guard::<auth::ClientIp>().await {
+            Outcome::Success(ip) => ip.0,
+            _ => "unknown".to_string(),
+        };
+        Outcome::Success(LoginClient {
+            ip,
+            user_agent: request
+                .headers()
+                .get_one("User-Agent")
+                .unwrap_or("")
+                .to_string(),
+            location: location_from_headers(request.headers()),
+        })
+    }
+}
+
+/// Country (and city, when available) from the geolocation headers added by
+/// Cloudflare or CloudFront.
+pub fn location_from_headers(headers: &HeaderMap<'_>) -> String {
+    let first = |names: &[&str]| {
+        names
+            .iter()
+            .filter_map(|n| headers.get_one(n))
+            .map(|v| v.trim())
+            .find(|v| !v.is_empty())
+            .map(|v| v.to_string())
+    };
+    // Cloudflare uses XX for unknown and T1 for Tor exit nodes
+    let country = first(&["CF-IPCountry", "CloudFront-Viewer-Country"])
+        .filter(|c| c != "XX")
+        .map(|c| if c == "T1" { "Tor".to_string() } else { c });
+    let city = first(&["CF-IPCity", "CloudFront-Viewer-City"]);
+    match (city, country) {
+        (Some(city), Some(country)) => format!("{}, {}", city, country),
+        (None, Some(country)) => country,
+        (Some(city), None) => city,
+        (None, None) => String::new(),
+    }
+}
+
+/// Human-readable "Browser on OS" label for a User-Agent string.
+pub fn device_label(ua: &str) -> String {
+    let (_, browser) = crate::analytics::parse_user_agent(ua);
+    let os = if ua.contains("iPhone") || ua.contains("iPad") {
+        "iOS"
+    } else if ua.contains("Android") {
+        "Android"
+    } else if ua.contains("CrOS") {
+        "ChromeOS"
+    } else if ua.contains("Windows") {
+        "Windows"
+    } else if ua.contains("Mac OS X") || ua.contains("Macintosh") {
+        "macOS"
+    } else if ua.contains("Linux") {
+        "Linux"
+    } else {
+        "unknown OS"
+    };
+    format!("{} on {}", browser, os)
+}
+
+/// Remember the device behind a successful sign-in and, when it hasn't been
+/// seen for this user before, email them an alert in the background. The
+/// very first sign-in only seeds the known-device list.
+pub fn record_login(store: &Arc<dyn Store>, user: &User, client: &LoginClient) {
+    let device = device_label(&client.user_agent);
+    let had_devices = store.login_device_count(user.id) > 0;
+    let is_new = match store.login_device_touch(
+        user.id,
+        &auth::hash_ip(&client.ip),
+        &device,
+        &client.location,
+    ) {
+        Ok(new) => new,
+        Err(e) => {
+            log::warn!("[login-alert] Could not record device: {}", e);
+            return;
+        }
+    };
+    if !is_new || !had_devices || !store.setting_get_bool("login_alerts_enabled") {
+        return;
+    }
+
+    let store = Arc::clone(store);
+    let user = user.clone();
+    let client = client.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = send_alert(&*store, &user, &client) {
+            log::warn!("[login-alert] Alert to {} failed: {}", user.email, e);
+        }
+    });
+}
+
+/// Email a new-device sign-in alert with a "this wasn't me" link.
+pub fn send_alert(store: &dyn Store, user: &User, client: &LoginClient) -> Result<(), String> {
+    let settings = store.setting_all();
+    let from = crate::email::get_from_or_admin(&settings);
+    if from.is_empty() {
+        return Err("No email provider configured".into());
+    }
+    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
+    let site_name = store.setting_get_or("site_name", "Velocty");
+    let admin_slug = store.setting_get_or("admin_slug", "admin");
+
+    let token = signed_token::issue(
+        store,
+        signed_token::LOGIN_ALERT,
+        &user.email,
+        "",
+        DENY_TTL_MINUTES,
+    )?;
+    let link = format!(
+        "{}/{}/login/not-me?token={}",
+        site_url.trim_end_matches('/'),
+        admin_slug,
+        token
+    );
+    let location = if client.location.is_empty() {
+        "Unknown"
+    } else {
+        &client.location
+    };
+
+    let subject = format!("New sign-in to your account — {}", site_name);
+    let body = format!(
+        "Hello {},\n\n\
+         Your {} account was just signed in to from a new device:\n\n\
+         Time: {} UTC\n\
+         Device: {}\n\
+         Location: {}\n\
+         IP address: {}\n\n\
+         If this was you, there's nothing to do.\n\n\
+         If it wasn't, secure your account now. This locks it, signs out every \
+         session and lets you choose a new password:\n\n\
+         {}\n\n\
+         This link is valid for 7 days.\n\n\
+         — {}\n",
+        user.display_name,
+        site_name,
+        chrono::Utc::now().format("%Y-%m-%d %H:%M"),
+        device_label(&client.user_agent),
+        location,
+        client.ip,
+        link,
+        site_name
+    );
+
+    crate::email::send_via_configured_provider(
+        store,
+        "transactional",
+        &from,
+        &user.email,
+        &subject,
+        &body,
+    )
+}
+
+/// Redeem a "this wasn't me" link: lock the account, end all sessions, forget
+/// known devices, and return a password reset token that unlocks the account
+/// once a new password is set.
+pub fn deny(store: &dyn Store, token: &str, ip: &str) -> Result<String, String> {
+    let t = signed_token::verify(store, signed_token::LOGIN_ALERT, token)?;
+    let user = store
+        .user_get_by_email(&t.email)
+        .ok_or("Invalid or expired link")?;
+
+    store.user_lock(user.id)?;
+    store.session_delete_for_user(user.id)?;
+    store.login_device_forget(user.id)?;
+    store.audit_log(
+        Some(user.id),
+        Some(&user.display_name),
+        "login_denied",
+        Some("user"),
+        Some(user.id),
+        Some(&user.email),
+        Some("Account locked from new-device alert"),
+        Some(ip),
+    );
+    password_reset::create_unlock_token(store, &user.email)
+}
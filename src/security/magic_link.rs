@@ -1,16 +1,16 @@
 use crate::store::Store;
 
+use super::signed_token;
+
 /// Create a magic link token for the given email. Stores in DB, returns the token.
 pub fn create_token(store: &dyn Store, email: &str) -> Result<String, String> {
-    let token = uuid::Uuid::new_v4().to_string();
-    store.magic_link_create(&token, email, 15)?;
-    Ok(token)
+    signed_token::issue(store, signed_token::MAGIC_LINK, email, "", 15)
 }
 
 /// Verify a magic link token. Returns the associated email if valid.
 /// Marks the token as used so it cannot be reused.
 pub fn verify_token(store: &dyn Store, token: &str) -> Result<String, String> {
-    store.magic_link_verify(token)
+    signed_token::verify(store, signed_token::MAGIC_LINK, token).map(|t| t.email)
 }
 
 /// Send a magic link email to the admin.
@@ -51,7 +51,14 @@ pub fn send_magic_link_email(store: &dyn Store, email: &str, token: &str) -> Res
     let from_email = get_from_email(&settings)
         .ok_or("No email provider configured. Magic link requires an email provider.")?;
 
-    crate::email::send_via_provider(&settings, &from_email, email, &subject, &body)
+    crate::email::send_via_configured_provider(
+        store,
+        "transactional",
+        &from_email,
+        email,
+        &subject,
+        &body,
+    )
 }
 
 /// Determine the "from" email address (mirrors email module logic)
@@ -85,6 +92,7 @@ fn get_from_email(settings: &std::collections::HashMap<String, String>) -> Optio
         "email_moosend_enabled",
         "email_mandrill_enabled",
         "email_sparkpost_enabled",
+        "email_sendgrid_enabled",
     ];
     for provider in &api_providers {
         if settings.get(*provider).map(|v| v.as_str()) == Some("true") {
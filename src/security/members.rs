@@ -0,0 +1,157 @@
+use crate::models::user::User;
+use crate::store::Store;
+
+use super::{auth, password_policy, permissions, signed_token};
+
+// ── Member accounts ─────────────────────────────────────
+//
+// Optional visitor accounts, turned on with `membership_enabled`. Members are
+// users with the built-in "subscriber" role: no dashboard access, signed in
+// on the public site under /account. New accounts stay "pending" until the
+// emailed verification link is opened.
+
+/// Role given to accounts created by registration
+pub const MEMBER_ROLE: &str = "subscriber";
+
+/// Verification links stay valid for two days
+const VERIFY_TTL_MINUTES: i64 = 48 * 60;
+
+pub fn enabled(store: &dyn Store) -> bool {
+    store.setting_get_bool("membership_enabled")
+}
+
+/// Whether `user` is a member rather than staff. Staff sign in through the
+/// admin, where MFA and the other sign-in policies apply.
+pub fn is_member(store: &dyn Store, user: &User) -> bool {
+    !permissions::user_can(store, user, permissions::DASHBOARD_VIEW)
+}
+
+pub fn valid_email(email: &str) -> bool {
+    let mut parts = email.splitn(2, '@');
+    let (local, domain) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    !local.is_empty() && domain.contains('.') && !email.contains(char::is_whitespace)
+}
+
+/// Display names members may use: not blank, not too long, no markup
+pub fn validate_display_name(name: &str) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Please enter a display name".into());
+    }
+    if name.chars().count() > 60 {
+        return Err("Display name is too long".into());
+    }
+    if name.contains(['<', '>']) {
+        return Err("Display name can't contain < or >".into());
+    }
+    Ok(())
+}
+
+/// Create a pending member account and email its verification link.
+///
+/// Returns `Ok(None)` when the address already has an account: the visitor
+/// sees the same "check your inbox" message, so registration can't be used
+/// to find out who has an account.
+pub fn register(
+    store: &dyn Store,
+    email: &str,
+    display_name: &str,
+    password: &str,
+) -> Result<Option<i64>, String> {
+    let email = email.trim().to_lowercase();
+    let display_name = display_name.trim();
+    if !valid_email(&email) {
+        return Err("Please enter a valid email address".into());
+    }
+    validate_display_name(display_name)?;
+    password_policy::check(store, password, &[&email, display_name])?;
+    if store.user_get_by_email(&email).is_some() {
+        return Ok(None);
+    }
+
+    let hash = auth::hash_password_for(store, password)?;
+    let id = store.user_create(&email, &hash, display_name, MEMBER_ROLE)?;
+    store.user_set_status(id, "pending")?;
+    // Without the email the account could never be activated, and the
+    // address couldn't register again; start over instead
+    if let Err(e) = send_verification(store, &email) {
+        log::error!("[members] Could not send verification email: {}", e);
+        let _ = store.user_delete(id);
+        return Err("Could not send the confirmation email. Please try again later.".into());
+    }
+    Ok(Some(id))
+}
+
+/// Email a verification link to a pending account
+pub fn send_verification(store: &dyn Store, email: &str) -> Result<(), String> {
+    let token = signed_token::issue(
+        store,
+        signed_token::MEMBER_VERIFY,
+        email,
+        "",
+        VERIFY_TTL_MINUTES,
+    )?;
+    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
+    let site_name = store.setting_get_or("site_name", "Velocty");
+    let link = format!(
+        "{}/account/verify?token={}",
+        site_url.trim_end_matches('/'),
+        token
+    );
+    let body = format!(
+        "Hello,\n\n\
+         Please confirm your email address to finish creating your account on {}:\n\n\
+         {}\n\n\
+         This link expires in 48 hours. If you didn't sign up, ignore this email \
+         and no account will be activated.\n\n\
+         — {}\n",
+        site_name, link, site_name
+    );
+    let from = crate::email::get_from_or_admin(&store.setting_all());
+    if from.is_empty() {
+        return Err("No email provider configured".into());
+    }
+    crate::email::send_via_configured_provider(
+        store,
+        "transactional",
+        &from,
+        email,
+        &format!("Confirm your account — {}", site_name),
+        &body,
+    )
+}
+
+/// Redeem a verification link and activate the account
+pub fn verify(store: &dyn Store, token: &str) -> Result<User, String> {
+    let t = signed_token::verify(store, signed_token::MEMBER_VERIFY, token)?;
+    let user = store
+        .user_get_by_email(&t.email)
+        .ok_or("Invalid or expired link")?;
+    if user.status == "pending" {
+        store.user_set_status(user.id, "active")?;
+    }
+    store
+        .user_get_by_id(user.id)
+        .ok_or_else(|| "Invalid or expired link".into())
+}
+
+/// Check a member's email and password. Staff accounts are refused here.
+pub fn authenticate(store: &dyn Store, email: &str, password: &str) -> Result<User, String> {
+    let invalid = || "Invalid email or password".to_string();
+    let user = store
+        .user_get_by_email(&email.trim().to_lowercase())
+        .ok_or_else(invalid)?;
+    if user.passwordless || !auth::verify_password(password, &user.password_hash) {
+        return Err(invalid());
+    }
+    if !is_member(store, &user) {
+        return Err("Staff accounts sign in through the dashboard".into());
+    }
+    match user.status.as_str() {
+        "active" => {}
+        "pending" => return Err("Please confirm your email address first".into()),
+        _ => return Err("This account is disabled".into()),
+    }
+    auth::upgrade_password_hash(store, &user, password);
+    Ok(user)
+}
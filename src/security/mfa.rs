@@ -1,4 +1,11 @@
 use rocket::http::{Cookie, CookieJar};
+use sha2::{Digest, Sha256};
+
+use crate::models::user::User;
+use crate::rate_limit::RateLimiter;
+use crate::store::Store;
+
+use super::signed_token;
 
 const MFA_PENDING_COOKIE: &str = "velocty_mfa_pending";
 
@@ -84,3 +91,158 @@ pub fn get_pending_cookie(cookies: &CookieJar<'_>) -> Option<String> {
         .get_private(MFA_PENDING_COOKIE)
         .map(|c| c.value().to_string())
 }
+
+/// Start the second sign-in step for `user`: remember them in the pending
+/// cookie and, when they use email codes, send one.
+pub fn begin_challenge(
+    store: &dyn Store,
+    limiter: &RateLimiter,
+    cookies: &CookieJar<'_>,
+    user: &User,
+) {
+    let pending_token = uuid::Uuid::new_v4().to_string();
+    set_pending_cookie(cookies, &format!("{}:{}", user.id, pending_token));
+    if user.mfa_method == "email" {
+        if let Err(e) = send_email_code_limited(store, limiter, user) {
+            log::warn!("MFA email code for user {} not sent: {}", user.id, e);
+        }
+    }
+}
+
+// ── Email codes ──────────────────────────────────────────
+
+/// Emailed codes expire together with the pending sign-in cookie.
+pub const EMAIL_CODE_TTL_MINUTES: i64 = 5;
+
+/// Whether an email provider is set up to deliver sign-in codes.
+pub fn email_available(store: &dyn Store) -> bool {
+    let settings = store.setting_all();
+    !crate::email::routing::resolve_chain(&settings, "transactional").is_empty()
+        && !crate::email::get_from_or_admin(&settings).is_empty()
+}
+
+/// Stored form of an emailed code. Tied to the user and signed with the
+/// site secret, so six digits can't be brute-forced from a copy of the
+/// database.
+fn email_code_hash(store: &dyn Store, user_id: i64, code: &str) -> Result<String, String> {
+    let mac = signed_token::mac(
+        store,
+        signed_token::MFA_EMAIL,
+        &format!("{}:{}", user_id, code),
+    )?;
+    Ok(hex::encode(Sha256::digest(mac.as_bytes())))
+}
+
+/// Generate a six-digit code for `user`, valid for `EMAIL_CODE_TTL_MINUTES`.
+pub fn issue_email_code(store: &dyn Store, user: &User) -> Result<String, String> {
+    use rand::Rng;
+    let code = format!("{:06}", rand::thread_rng().gen_range(0..1_000_000));
+    store.signed_token_create(
+        &email_code_hash(store, user.id, &code)?,
+        signed_token::MFA_EMAIL,
+        &user.email,
+        &user.id.to_string(),
+        EMAIL_CODE_TTL_MINUTES,
+    )?;
+    Ok(code)
+}
+
+/// Check an emailed code. A matching code is used up.
+pub fn verify_email_code(store: &dyn Store, user: &User, code: &str) -> bool {
+    let code = code.trim();
+    if code.len() != 6 || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    match email_code_hash(store, user.id, code) {
+        Ok(hash) => store
+            .signed_token_consume(&hash, signed_token::MFA_EMAIL)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Email a fresh sign-in code to `user`.
+pub fn send_email_code(store: &dyn Store, user: &User) -> Result<(), String> {
+    let settings = store.setting_all();
+    let from = crate::email::get_from_or_admin(&settings);
+    if from.is_empty() {
+        return Err("No email provider configured".into());
+    }
+    let code = issue_email_code(store, user)?;
+    let site_name = store.setting_get_or("site_name", "Velocty");
+    let subject = format!("{} is your {} sign-in code", code, site_name);
+    let body = format!(
+        "Hello {},\n\n\
+         Your sign-in code for {} is:\n\n\
+         {}\n\n\
+         It expires in {} minutes and can only be used once. If you didn't just \
+         sign in, someone has your password: change it right away.\n\n\
+         — {}\n",
+        user.display_name, site_name, code, EMAIL_CODE_TTL_MINUTES, site_name
+    );
+    crate::email::send_via_configured_provider(
+        store,
+        "transactional",
+        &from,
+        &user.email,
+        &subject,
+        &body,
+    )
+}
+
+/// `send_email_code`, limited to three codes per user every 15 minutes.
+pub fn send_email_code_limited(
+    store: &dyn Store,
+    limiter: &RateLimiter,
+    user: &User,
+) -> Result<(), String> {
+    if !limiter.check(
+        store,
+        &format!("mfa_email:{}", user.id),
+        3,
+        std::time::Duration::from_secs(15 * 60),
+    ) {
+        return Err("Too many codes requested. Please wait a few minutes.".into());
+    }
+    send_email_code(store, user)
+}
+
+/// Hide most of an address for display: "j•••@example.com".
+pub fn mask_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            let first: String = local.chars().take(1).collect();
+            format!("{}•••@{}", first, domain)
+        }
+        None => "your email address".to_string(),
+    }
+}
+
+/// Choose the user's second factor ("totp" or "email"). Choosing email
+/// turns MFA on when it was off; new recovery codes are then returned so
+/// they can be shown once. The authenticator app must be set up first.
+pub fn set_method(
+    store: &dyn Store,
+    user: &User,
+    method: &str,
+) -> Result<Option<Vec<String>>, String> {
+    match method {
+        "totp" if user.mfa_secret.is_empty() => {
+            return Err("Set up an authenticator app first.".into())
+        }
+        "totp" => {}
+        "email" if !email_available(store) => {
+            return Err("Configure an email provider before using email codes.".into())
+        }
+        "email" => {}
+        _ => return Err("Unknown MFA method.".into()),
+    }
+    store.user_set_mfa_method(user.id, method)?;
+    if user.mfa_enabled {
+        return Ok(None);
+    }
+    let codes = generate_recovery_codes();
+    let json = serde_json::to_string(&codes).map_err(|e| e.to_string())?;
+    store.user_update_mfa(user.id, true, &user.mfa_secret, &json)?;
+    Ok(Some(codes))
+}
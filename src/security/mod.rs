@@ -1,14 +1,28 @@
 pub mod akismet;
 pub mod auth;
 pub mod cleantalk;
+pub mod cors;
+pub mod email_change;
 pub mod firewall;
 pub mod hcaptcha;
+pub mod headers;
+pub mod invite;
+pub mod login_alert;
 pub mod magic_link;
+pub mod members;
 pub mod mfa;
 pub mod oopspam;
 pub mod passkey;
+pub mod password_policy;
 pub mod password_reset;
+pub mod passwordless;
+pub mod permissions;
+pub mod privacy;
 pub mod recaptcha;
+pub mod secrets;
+pub mod siem;
+pub mod signed_token;
+pub mod sudo;
 pub mod turnstile;
 
 use sha2::{Digest, Sha256};
@@ -0,0 +1,318 @@
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+
+use crate::store::Store;
+
+/// Frequently used passwords rejected when `password_deny_common` is on.
+/// Compared case-insensitively, after stripping trailing digits and symbols.
+const COMMON_PASSWORDS: &[&str] = &[
+    "password",
+    "passw0rd",
+    "p@ssword",
+    "p@ssw0rd",
+    "123456",
+    "12345678",
+    "123456789",
+    "1234567890",
+    "qwerty",
+    "qwertyuiop",
+    "azerty",
+    "abc123",
+    "letmein",
+    "welcome",
+    "admin",
+    "administrator",
+    "iloveyou",
+    "monkey",
+    "dragon",
+    "football",
+    "baseball",
+    "master",
+    "sunshine",
+    "princess",
+    "shadow",
+    "superman",
+    "batman",
+    "trustno1",
+    "starwars",
+    "whatever",
+    "freedom",
+    "hello",
+    "charlie",
+    "donald",
+    "michael",
+    "jennifer",
+    "jordan",
+    "hunter",
+    "ashley",
+    "bailey",
+    "access",
+    "secret",
+    "login",
+    "changeme",
+    "default",
+    "root",
+    "toor",
+    "test",
+    "guest",
+    "qazwsx",
+    "zaq1zaq1",
+    "1q2w3e4r",
+    "1qaz2wsx",
+    "asdfgh",
+    "asdfghjkl",
+    "zxcvbnm",
+    "111111",
+    "000000",
+    "654321",
+    "666666",
+    "121212",
+    "987654321",
+    "mustang",
+    "pokemon",
+    "computer",
+    "internet",
+    "samsung",
+    "cheese",
+    "summer",
+    "winter",
+    "spring",
+    "autumn",
+    "flower",
+    "killer",
+    "soccer",
+    "hockey",
+    "ranger",
+    "harley",
+    "thomas",
+    "robert",
+    "matthew",
+    "daniel",
+    "andrew",
+    "joshua",
+    "pepper",
+    "ginger",
+    "buster",
+    "tigger",
+    "cookie",
+    "lovely",
+    "velocty",
+];
+
+/// Password rules from the security settings.
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_uppercase: bool,
+    pub require_lowercase: bool,
+    pub require_number: bool,
+    pub require_symbol: bool,
+    pub deny_common: bool,
+    /// "off", "api" (Pwned Passwords range API) or "offline" (local range files).
+    pub breach_check: String,
+    /// Directory of `<PREFIX>.txt` range files, as written by the official
+    /// Pwned Passwords downloader.
+    pub breach_dir: String,
+}
+
+impl PasswordPolicy {
+    pub fn from_settings(settings: &HashMap<String, String>) -> Self {
+        let get = |key: &str| settings.get(key).map(|v| v.trim()).unwrap_or("");
+        let on = |key: &str| get(key) == "true";
+        PasswordPolicy {
+            min_length: get("password_min_length")
+                .parse::<usize>()
+                .unwrap_or(8)
+                .clamp(8, 128),
+            require_uppercase: on("password_require_uppercase"),
+            require_lowercase: on("password_require_lowercase"),
+            require_number: on("password_require_number"),
+            require_symbol: on("password_require_symbol"),
+            deny_common: get("password_deny_common") != "false",
+            breach_check: match get("password_breach_check") {
+                "" => "off".to_string(),
+                v => v.to_string(),
+            },
+            breach_dir: get("password_breach_dir").to_string(),
+        }
+    }
+
+    pub fn load(store: &dyn Store) -> Self {
+        Self::from_settings(&store.setting_all())
+    }
+
+    /// Short description of the rules, for form hints.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("At least {} characters", self.min_length)];
+        for (on, label) in [
+            (self.require_uppercase, "an uppercase letter"),
+            (self.require_lowercase, "a lowercase letter"),
+            (self.require_number, "a number"),
+            (self.require_symbol, "a symbol"),
+        ] {
+            if on {
+                parts.push(label.to_string());
+            }
+        }
+        match parts.len() {
+            1 => parts.remove(0),
+            _ => {
+                let first = parts.remove(0);
+                format!("{}, including {}", first, join_and(&parts))
+            }
+        }
+    }
+
+    /// Check length, complexity and the common password list. `personal`
+    /// holds values the password must not be built from (email, name).
+    /// Every broken rule is reported in one message.
+    pub fn validate(&self, password: &str, personal: &[&str]) -> Result<(), String> {
+        let mut missing = Vec::new();
+        if password.chars().count() < self.min_length {
+            missing.push(format!("be at least {} characters", self.min_length));
+        }
+        for (on, ok, label) in [
+            (
+                self.require_uppercase,
+                password.chars().any(|c| c.is_uppercase()),
+                "contain an uppercase letter",
+            ),
+            (
+                self.require_lowercase,
+                password.chars().any(|c| c.is_lowercase()),
+                "contain a lowercase letter",
+            ),
+            (
+                self.require_number,
+                password.chars().any(|c| c.is_ascii_digit()),
+                "contain a number",
+            ),
+            (
+                self.require_symbol,
+                password.chars().any(|c| !c.is_alphanumeric()),
+                "contain a symbol",
+            ),
+        ] {
+            if on && !ok {
+                missing.push(label.to_string());
+            }
+        }
+        if !missing.is_empty() {
+            return Err(format!("Password must {}.", join_and(&missing)));
+        }
+
+        if self.deny_common && is_common(password) {
+            return Err(
+                "This password is too common. Please choose something less predictable.".into(),
+            );
+        }
+        let lower = password.to_lowercase();
+        for value in personal {
+            let value = value.trim().to_lowercase();
+            let stem = value.split('@').next().unwrap_or("");
+            if stem.len() >= 4 && lower.contains(stem) {
+                return Err("Password must not contain your name or email address.".into());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn join_and(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [one] => one.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+/// Whether a password is on the common list, ignoring case and the digits or
+/// symbols people tend to tack on the end ("Password123!").
+pub fn is_common(password: &str) -> bool {
+    let lower = password.to_lowercase();
+    let stem = lower.trim_end_matches(|c: char| !c.is_alphabetic());
+    COMMON_PASSWORDS.contains(&lower.as_str())
+        || (!stem.is_empty() && COMMON_PASSWORDS.contains(&stem))
+}
+
+// ── Breach check (Pwned Passwords, k-anonymity) ──────────
+
+/// SHA-1 of the password as (5-char prefix, 35-char suffix), upper-case hex.
+/// Only the prefix ever leaves the server.
+pub fn sha1_range(password: &str) -> (String, String) {
+    let hash = hex::encode_upper(Sha1::digest(password.as_bytes()));
+    let (prefix, suffix) = hash.split_at(5);
+    (prefix.to_string(), suffix.to_string())
+}
+
+/// Find `suffix` in a range response ("SUFFIX:COUNT" per line) and return
+/// its breach count. Padding entries with a count of 0 are ignored.
+pub fn count_in_range(range: &str, suffix: &str) -> u64 {
+    range
+        .lines()
+        .filter_map(|l| l.trim().split_once(':'))
+        .find(|(s, _)| s.eq_ignore_ascii_case(suffix))
+        .and_then(|(_, n)| n.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// How many times the password appears in known breaches, using the
+/// configured source. Returns Ok(0) when the check is off.
+pub fn breach_count(policy: &PasswordPolicy, password: &str) -> Result<u64, String> {
+    let (prefix, suffix) = sha1_range(password);
+    let range = match policy.breach_check.as_str() {
+        "api" => {
+            let client = reqwest::blocking::Client::builder()
+                .timeout(std::time::Duration::from_secs(5))
+                .build()
+                .map_err(|e| format!("HTTP client error: {}", e))?;
+            let resp = client
+                .get(format!("https://api.pwnedpasswords.com/range/{}", prefix))
+                .header("Add-Padding", "true")
+                .header("User-Agent", "Velocty")
+                .send()
+                .map_err(|e| format!("Pwned Passwords request failed: {}", e))?;
+            if !resp.status().is_success() {
+                return Err(format!("Pwned Passwords returned {}", resp.status()));
+            }
+            resp.text().map_err(|e| e.to_string())?
+        }
+        "offline" => {
+            if policy.breach_dir.is_empty() {
+                return Err("Pwned Passwords directory not configured".into());
+            }
+            let path = std::path::Path::new(&policy.breach_dir).join(format!("{}.txt", prefix));
+            std::fs::read_to_string(&path)
+                .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?
+        }
+        _ => return Ok(0),
+    };
+    Ok(count_in_range(&range, &suffix))
+}
+
+/// Validate a new password against the site's policy and, when enabled, the
+/// breach corpus. An unreachable breach source is logged and doesn't block
+/// the change.
+pub fn check(store: &dyn Store, password: &str, personal: &[&str]) -> Result<(), String> {
+    check_with(&PasswordPolicy::load(store), password, personal)
+}
+
+/// [`check`] against a policy that doesn't come from one site's settings.
+pub fn check_with(
+    policy: &PasswordPolicy,
+    password: &str,
+    personal: &[&str],
+) -> Result<(), String> {
+    policy.validate(password, personal)?;
+    match breach_count(policy, password) {
+        Ok(0) => Ok(()),
+        Ok(n) => Err(format!(
+            "This password has appeared in {} known data breaches. Please choose a different one.",
+            n
+        )),
+        Err(e) => {
+            log::warn!("[password] Breach check skipped: {}", e);
+            Ok(())
+        }
+    }
+}
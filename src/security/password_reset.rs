@@ -1,16 +1,28 @@
 use crate::store::Store;
 
-/// Create a password reset token for the given email. Stores in magic_links table with purpose.
+use super::signed_token;
+
+/// Create a password reset token for the given email. Valid for 30 minutes;
+/// scoped to password resets so it can't be redeemed as a magic link.
 pub fn create_token(store: &dyn Store, email: &str) -> Result<String, String> {
-    let token = uuid::Uuid::new_v4().to_string();
-    store.magic_link_create(&token, email, 30)?;
-    Ok(token)
+    signed_token::issue(store, signed_token::PASSWORD_RESET, email, "", 30)
+}
+
+/// Payload marking a reset token that also unlocks the account.
+const UNLOCK: &str = "unlock";
+
+/// Create a reset token for an account that was locked by its owner from a
+/// new-device alert. Setting the new password unlocks the account.
+pub fn create_unlock_token(store: &dyn Store, email: &str) -> Result<String, String> {
+    signed_token::issue(store, signed_token::PASSWORD_RESET, email, UNLOCK, 30)
 }
 
-/// Verify a password reset token. Returns the associated email if valid.
+/// Verify a password reset token. Returns the associated email and whether
+/// the account should be unlocked once the password is changed.
 /// Marks the token as used so it cannot be reused.
-pub fn verify_token(store: &dyn Store, token: &str) -> Result<String, String> {
-    store.magic_link_verify(token)
+pub fn verify_token(store: &dyn Store, token: &str) -> Result<(String, bool), String> {
+    signed_token::verify(store, signed_token::PASSWORD_RESET, token)
+        .map(|t| (t.email, t.payload == UNLOCK))
 }
 
 /// Send a password reset email.
@@ -51,7 +63,14 @@ pub fn send_reset_email(store: &dyn Store, email: &str, token: &str) -> Result<(
     let from_email = get_from_email(&settings)
         .ok_or("No email provider configured. Password reset requires an email provider.")?;
 
-    crate::email::send_via_provider(&settings, &from_email, email, &subject, &body)
+    crate::email::send_via_configured_provider(
+        store,
+        "transactional",
+        &from_email,
+        email,
+        &subject,
+        &body,
+    )
 }
 
 /// Send an admin-initiated password reset email with a temporary password.
@@ -90,7 +109,14 @@ pub fn send_admin_reset_email(
 
     let from_email = get_from_email(&settings).ok_or("No email provider configured.")?;
 
-    crate::email::send_via_provider(&settings, &from_email, email, &subject, &body)
+    crate::email::send_via_configured_provider(
+        store,
+        "transactional",
+        &from_email,
+        email,
+        &subject,
+        &body,
+    )
 }
 
 /// Generate a random temporary password (12 chars, alphanumeric + symbols).
@@ -136,6 +162,7 @@ fn get_from_email(settings: &std::collections::HashMap<String, String>) -> Optio
         "email_moosend_enabled",
         "email_mandrill_enabled",
         "email_sparkpost_enabled",
+        "email_sendgrid_enabled",
     ];
     for provider in &api_providers {
         if settings.get(*provider).map(|v| v.as_str()) == Some("true") {
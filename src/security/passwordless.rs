@@ -0,0 +1,61 @@
+use crate::models::user::User;
+use crate::store::Store;
+
+use super::mfa;
+
+/// Shown when a passwordless account tries the password form.
+pub const PASSWORD_DISABLED_MSG: &str =
+    "Password sign-in is disabled for this account. Use your passkey, a sign-in link or a recovery code.";
+
+fn recovery_codes(user: &User) -> Vec<String> {
+    serde_json::from_str(&user.mfa_recovery_codes).unwrap_or_default()
+}
+
+/// Turn off password sign-in for `user`. Requires at least one registered
+/// passkey. Recovery codes are generated when the account has none left;
+/// those are returned so they can be shown once.
+pub fn enable(store: &dyn Store, user: &User) -> Result<Option<Vec<String>>, String> {
+    if store.passkey_count_for_user(user.id) == 0 {
+        return Err("Register a passkey before turning off password sign-in.".into());
+    }
+    if user.auth_method != "passkey" {
+        let fallback = if user.auth_method.is_empty() {
+            "password"
+        } else {
+            &user.auth_method
+        };
+        store.user_update_auth_method(user.id, "passkey", fallback)?;
+    }
+    let new_codes = if recovery_codes(user).is_empty() {
+        let codes = mfa::generate_recovery_codes();
+        let json = serde_json::to_string(&codes).map_err(|e| e.to_string())?;
+        store.user_update_mfa(user.id, user.mfa_enabled, &user.mfa_secret, &json)?;
+        Some(codes)
+    } else {
+        None
+    };
+    store.user_set_passwordless(user.id, true)?;
+    Ok(new_codes)
+}
+
+/// Allow password sign-in again.
+pub fn disable(store: &dyn Store, user: &User) -> Result<(), String> {
+    store.user_set_passwordless(user.id, false)
+}
+
+/// Check `code` against the user's recovery codes and, when it matches,
+/// remove it so it can't be used again. Case and surrounding whitespace are
+/// ignored.
+pub fn consume_recovery_code(store: &dyn Store, user: &User, code: &str) -> bool {
+    let mut codes = recovery_codes(user);
+    let code = code.trim().to_uppercase();
+    let pos = match codes.iter().position(|c| *c == code) {
+        Some(p) if !code.is_empty() => p,
+        _ => return false,
+    };
+    codes.remove(pos);
+    let updated = serde_json::to_string(&codes).unwrap_or_else(|_| "[]".to_string());
+    store
+        .user_update_mfa(user.id, user.mfa_enabled, &user.mfa_secret, &updated)
+        .is_ok()
+}
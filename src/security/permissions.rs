@@ -0,0 +1,183 @@
+use crate::models::post::Post;
+use crate::models::user::User;
+use crate::store::Store;
+
+// ── Capabilities ────────────────────────────────────────
+
+pub const DASHBOARD_VIEW: &str = "dashboard.view";
+pub const POSTS_EDIT: &str = "posts.edit";
+pub const POSTS_PUBLISH: &str = "posts.publish";
+pub const POSTS_DELETE: &str = "posts.delete";
+pub const POSTS_EDIT_OTHERS: &str = "posts.edit_others";
+pub const PORTFOLIO_EDIT: &str = "portfolio.edit";
+pub const PORTFOLIO_PUBLISH: &str = "portfolio.publish";
+pub const PORTFOLIO_DELETE: &str = "portfolio.delete";
+pub const MEDIA_UPLOAD: &str = "media.upload";
+pub const MEDIA_MANAGE: &str = "media.manage";
+pub const COMMENTS_MODERATE: &str = "comments.moderate";
+pub const CATEGORIES_MANAGE: &str = "categories.manage";
+pub const ANALYTICS_VIEW: &str = "analytics.view";
+pub const SEO_MANAGE: &str = "seo.manage";
+pub const AI_USE: &str = "ai.use";
+pub const DESIGNS_MANAGE: &str = "designs.manage";
+pub const SETTINGS_WRITE: &str = "settings.write";
+pub const USERS_MANAGE: &str = "users.manage";
+pub const ORDERS_VIEW: &str = "orders.view";
+pub const FIREWALL_MANAGE: &str = "firewall.manage";
+pub const HEALTH_MANAGE: &str = "health.manage";
+pub const IMPORT_RUN: &str = "import.run";
+pub const MAIL_MANAGE: &str = "mail.manage";
+pub const DEPLOY_MANAGE: &str = "deploy.manage";
+
+/// Every capability with a short label, in the order shown on the roles page.
+pub const CAPABILITIES: &[(&str, &str)] = &[
+    (DASHBOARD_VIEW, "Sign in to the admin dashboard"),
+    (POSTS_EDIT, "Create and edit journal posts"),
+    (POSTS_PUBLISH, "Publish and schedule journal posts"),
+    (POSTS_DELETE, "Delete journal posts"),
+    (POSTS_EDIT_OTHERS, "See and edit other users' journal posts"),
+    (PORTFOLIO_EDIT, "Create and edit portfolio items"),
+    (PORTFOLIO_PUBLISH, "Publish and schedule portfolio items"),
+    (PORTFOLIO_DELETE, "Delete portfolio items"),
+    (MEDIA_UPLOAD, "Upload images and use the media picker"),
+    (MEDIA_MANAGE, "Browse and delete files in the media library"),
+    (COMMENTS_MODERATE, "Moderate comments"),
+    (CATEGORIES_MANAGE, "Manage categories and tags"),
+    (ANALYTICS_VIEW, "View analytics"),
+    (SEO_MANAGE, "Run SEO checks and audits"),
+    (AI_USE, "Use AI writing tools"),
+    (DESIGNS_MANAGE, "Manage designs and fonts"),
+    (SETTINGS_WRITE, "Change site settings"),
+    (USERS_MANAGE, "Manage users and roles"),
+    (ORDERS_VIEW, "View sales and orders"),
+    (FIREWALL_MANAGE, "Manage the firewall"),
+    (HEALTH_MANAGE, "Run health and maintenance tools"),
+    (IMPORT_RUN, "Import content"),
+    (MAIL_MANAGE, "Manage the mail server and queue"),
+    (DEPLOY_MANAGE, "Deploy the site"),
+];
+
+// ── Roles ───────────────────────────────────────────────
+
+pub const BUILT_IN_ROLES: [&str; 4] = ["admin", "editor", "author", "subscriber"];
+
+const AUTHOR_CAPS: &[&str] = &[
+    DASHBOARD_VIEW,
+    POSTS_EDIT,
+    POSTS_PUBLISH,
+    PORTFOLIO_EDIT,
+    PORTFOLIO_PUBLISH,
+    PORTFOLIO_DELETE,
+    MEDIA_UPLOAD,
+];
+
+const EDITOR_EXTRA_CAPS: &[&str] = &[
+    POSTS_DELETE,
+    POSTS_EDIT_OTHERS,
+    MEDIA_MANAGE,
+    COMMENTS_MODERATE,
+    CATEGORIES_MANAGE,
+    ANALYTICS_VIEW,
+    SEO_MANAGE,
+    AI_USE,
+];
+
+/// Default capabilities of a built-in role, matching the old fixed ladder.
+/// Returns None for custom roles.
+pub fn default_capabilities(role: &str) -> Option<Vec<String>> {
+    let caps: Vec<&str> = match role {
+        "admin" => CAPABILITIES.iter().map(|(c, _)| *c).collect(),
+        "editor" => AUTHOR_CAPS
+            .iter()
+            .chain(EDITOR_EXTRA_CAPS.iter())
+            .copied()
+            .collect(),
+        "author" => AUTHOR_CAPS.to_vec(),
+        "subscriber" => vec![],
+        _ => return None,
+    };
+    Some(caps.into_iter().map(String::from).collect())
+}
+
+/// Effective capabilities of a role: the stored definition if there is one,
+/// otherwise the built-in default. Admin always has every capability so the
+/// site can't be locked out of its own settings.
+pub fn role_capabilities(store: &dyn Store, role: &str) -> Vec<String> {
+    if role == "admin" {
+        return default_capabilities("admin").unwrap_or_default();
+    }
+    match store.role_find(role) {
+        Some(r) => r.capabilities,
+        None => default_capabilities(role).unwrap_or_default(),
+    }
+}
+
+pub fn role_can(store: &dyn Store, role: &str, capability: &str) -> bool {
+    role_capabilities(store, role)
+        .iter()
+        .any(|c| c == capability)
+}
+
+pub fn user_can(store: &dyn Store, user: &User, capability: &str) -> bool {
+    role_can(store, &user.role, capability)
+}
+
+// ── Ownership ───────────────────────────────────────────
+//
+// Without `posts.edit_others` a user only lists and opens the posts credited
+// to them; without `media.manage` the media picker only shows their own
+// uploads. Posts with no author belong to no one but those who see all.
+
+pub fn sees_all_posts(store: &dyn Store, user: &User) -> bool {
+    user_can(store, user, POSTS_EDIT_OTHERS)
+}
+
+/// Whether `user` may open, edit or delete `post` in the admin.
+pub fn can_edit_post(store: &dyn Store, user: &User, post: &Post) -> bool {
+    post.author_id == Some(user.id) || sees_all_posts(store, user)
+}
+
+pub fn sees_all_media(store: &dyn Store, user: &User) -> bool {
+    user_can(store, user, MEDIA_MANAGE)
+}
+
+/// Whether `role` is built in or defined in the Store.
+pub fn role_exists(store: &dyn Store, role: &str) -> bool {
+    BUILT_IN_ROLES.contains(&role) || store.role_find(role).is_some()
+}
+
+/// Whether `user` holds every capability in `capabilities`.
+pub fn user_holds_all(store: &dyn Store, user: &User, capabilities: &[String]) -> bool {
+    let own = role_capabilities(store, &user.role);
+    capabilities.iter().all(|c| own.contains(c))
+}
+
+/// Whether `actor` may assign `role` to an account, or manage an account that
+/// has it. A role can only be handed out by someone holding all of its
+/// capabilities, so `users.manage` alone can't be used to take over an admin.
+pub fn can_grant(store: &dyn Store, actor: &User, role: &str) -> bool {
+    user_holds_all(store, actor, &role_capabilities(store, role))
+}
+
+/// Keep only known capabilities, in canonical order.
+pub fn sanitize_capabilities(caps: &[String]) -> Vec<String> {
+    CAPABILITIES
+        .iter()
+        .filter(|(c, _)| caps.iter().any(|x| x == c))
+        .map(|(c, _)| c.to_string())
+        .collect()
+}
+
+/// Validate a role slug: lowercase letters, digits, '-' and '_'.
+pub fn validate_slug(slug: &str) -> Result<(), String> {
+    if slug.is_empty() || slug.len() > 32 {
+        return Err("Role slug must be 1–32 characters".into());
+    }
+    if !slug
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err("Role slug may only contain lowercase letters, digits, '-' and '_'".into());
+    }
+    Ok(())
+}
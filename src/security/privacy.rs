@@ -0,0 +1,112 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::models::user::User;
+use crate::store::Store;
+
+// ── Personal data export and erasure ────────────────────
+//
+// Everything the site keeps about one account, as a JSON download, and the
+// matching "forget me": comments are anonymized, orders lose their buyer
+// details once accounting no longer needs them, and analytics recorded from
+// the account's known IPs, sessions and subscriptions are deleted along with
+// the account itself. Audit log entries stay as security records until the
+// audit retention prunes them.
+
+/// `format` field of an export document
+pub const FORMAT: &str = "velocty-personal-data";
+pub const VERSION: i64 = 1;
+
+/// Author shown on comments once their writer is erased
+pub const ERASED_AUTHOR: &str = "Deleted user";
+/// Buyer email left on anonymized orders (the .invalid TLD never resolves)
+pub const ERASED_EMAIL: &str = "erased@invalid";
+
+/// Upper bound on rows per list in an export
+const EXPORT_LIMIT: i64 = 10_000;
+
+/// The export document for `user`
+pub fn export(store: &dyn Store, user: &User) -> Value {
+    let audit: Vec<Value> = store
+        .audit_list(None, None, Some(user.id), EXPORT_LIMIT, 0)
+        .iter()
+        .map(|e| json!(e))
+        .collect();
+    let subscriptions: Vec<Value> = store
+        .comment_subscription_list_by_email(&user.email)
+        .into_iter()
+        .map(|(post_id, content_type)| json!({"post_id": post_id, "content_type": content_type}))
+        .collect();
+    json!({
+        "format": FORMAT,
+        "version": VERSION,
+        "site_name": store.setting_get_or("site_name", "Velocty"),
+        "exported_at": chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        "profile": user.safe_json(),
+        "sessions": store.session_list_for_user(user.id),
+        "login_devices": store.login_device_list(user.id),
+        "audit_entries": audit,
+        "comments": store.comment_list_by_author(user.id, &user.email),
+        "orders": store.order_list_by_email(&user.email, EXPORT_LIMIT, 0),
+        "newsletter": store.newsletter_status(&user.email),
+        "comment_subscriptions": subscriptions,
+    })
+}
+
+/// What an erasure changed
+#[derive(Debug, Default, Serialize)]
+pub struct Erasure {
+    pub comments_anonymized: usize,
+    pub orders_anonymized: usize,
+    /// Completed orders still inside the accounting retention period, kept
+    /// with their buyer details
+    pub orders_retained: usize,
+    pub page_views_deleted: usize,
+}
+
+/// Years completed orders keep their buyer details (`commerce_order_retention_years`)
+pub fn order_retention_years(store: &dyn Store) -> i64 {
+    store
+        .setting_get_or("commerce_order_retention_years", "7")
+        .trim()
+        .parse::<i64>()
+        .unwrap_or(7)
+        .max(0)
+}
+
+/// Remove `user`'s personal data and delete the account. Posts and uploads
+/// are left to the caller, which decides who inherits them.
+pub fn erase(store: &dyn Store, user: &User) -> Result<Erasure, String> {
+    let mut result = Erasure::default();
+    let email = user.email.to_lowercase();
+
+    // Analytics only know visitors by IP hash; the account's known devices
+    // tell us which hashes were theirs
+    let ip_hashes: Vec<String> = store
+        .login_device_list(user.id)
+        .iter()
+        .filter_map(|d| d["ip_hash"].as_str().map(str::to_string))
+        .filter(|h| !h.is_empty())
+        .collect();
+    result.page_views_deleted = store.analytics_forget_visitors(&ip_hashes)?;
+
+    result.comments_anonymized = store.comment_anonymize_author(user.id, &email, ERASED_AUTHOR)?;
+
+    let cutoff =
+        chrono::Utc::now().naive_utc() - chrono::Duration::days(365 * order_retention_years(store));
+    for order in store.order_list_by_email(&user.email, EXPORT_LIMIT, 0) {
+        if order.status == "completed" && order.created_at > cutoff {
+            result.orders_retained += 1;
+        } else {
+            store.order_update_buyer_info(order.id, ERASED_EMAIL, "")?;
+            result.orders_anonymized += 1;
+        }
+    }
+
+    store.subscriber_forget(&email)?;
+    store.session_delete_for_user(user.id)?;
+    store.login_device_forget(user.id)?;
+    store.passkey_delete_all_for_user(user.id)?;
+    store.user_delete(user.id)?;
+    Ok(result)
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+/// Settings that hold credentials. Their values are masked before a settings
+/// page is rendered and a masked value posted back leaves the stored one as is.
+pub const SECRET_KEYS: &[&str] = &[
+    "mfa_secret",
+    "mfa_recovery_codes",
+    "security_akismet_api_key",
+    "security_cleantalk_api_key",
+    "security_oopspam_api_key",
+    "security_recaptcha_secret_key",
+    "security_turnstile_secret_key",
+    "security_hcaptcha_secret_key",
+    "paypal_secret",
+    "payoneer_client_secret",
+    "stripe_secret_key",
+    "stripe_webhook_secret",
+    "twocheckout_secret_key",
+    "square_access_token",
+    "square_webhook_signature_key",
+    "razorpay_key_secret",
+    "mollie_api_key",
+    "ai_openai_api_key",
+    "ai_gemini_api_key",
+    "ai_cloudflare_api_token",
+    "ai_groq_api_key",
+    "ai_mistral_api_key",
+    "ai_openrouter_api_key",
+    "email_gmail_app_password",
+    "email_resend_api_key",
+    "email_ses_secret_key",
+    "email_postmark_server_token",
+    "email_brevo_api_key",
+    "email_sendpulse_client_secret",
+    "email_mailgun_api_key",
+    "email_moosend_api_key",
+    "email_mandrill_api_key",
+    "email_sparkpost_api_key",
+    "email_sendgrid_api_key",
+    "email_smtp_password",
+    "mta_dkim_private_key",
+    "siem_webhook_secret",
+    "analytics_report_webhook_secret",
+    "analytics_daily_salt",
+    "image_proxy_secret",
+    "image_proxy_secret_old",
+    "signed_token_secret",
+    "deploy_receive_key",
+    "backup_passphrase",
+    "backup_s3_secret_key",
+    "backup_b2_secret_key",
+    "search_engine_api_key",
+    "seo_cloudflare_analytics_token",
+    "seo_pagespeed_api_key",
+    "seo_moz_secret_key",
+    "seo_openpagerank_api_key",
+    "tumblr_api_key",
+];
+
+/// Free-text settings shown to visitors, where a pasted credential would be
+/// published.
+pub const PUBLIC_KEYS: &[(&str, &str)] = &[
+    ("site_name", "Site Name"),
+    ("site_caption", "Site Caption"),
+    ("seo_default_description", "Default Meta Description"),
+    ("copyright_text", "Footer Copyright Text"),
+    ("contact_text", "Contact Page Text"),
+    ("admin_bio", "Author Bio"),
+    ("layout_sidebar_custom_text", "Sidebar Text"),
+    ("privacy_policy_content", "Privacy Policy"),
+    ("terms_of_use_content", "Terms of Use"),
+];
+
+/// Start of every masked value. Real credentials never begin with it.
+pub const MASK: &str = "••••••••";
+
+pub fn is_secret_key(key: &str) -> bool {
+    SECRET_KEYS.contains(&key) || key.starts_with("mfa_pending_secret_")
+}
+
+/// Mask a credential for display, keeping the last four characters of long
+/// values so the admin can tell which key is configured.
+pub fn mask(value: &str) -> String {
+    if value.is_empty() {
+        return String::new();
+    }
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() >= 16 {
+        let tail: String = chars[chars.len() - 4..].iter().collect();
+        format!("{}{}", MASK, tail)
+    } else {
+        MASK.to_string()
+    }
+}
+
+pub fn is_masked(value: &str) -> bool {
+    value.starts_with(MASK)
+}
+
+/// Mask every credential in a settings map.
+pub fn mask_settings(settings: &mut HashMap<String, String>) {
+    for (key, value) in settings.iter_mut() {
+        if is_secret_key(key) {
+            *value = mask(value);
+        }
+    }
+}
+
+/// Drop credentials that came back masked from a settings form, so saving
+/// the page keeps the stored values.
+pub fn drop_masked(data: &mut HashMap<String, String>) {
+    data.retain(|key, value| !(is_secret_key(key) && is_masked(value)));
+}
+
+fn patterns() -> &'static [(&'static str, Regex)] {
+    static PATTERNS: OnceLock<Vec<(&'static str, Regex)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            ("private key", r"-----BEGIN [A-Z ]*PRIVATE KEY-----"),
+            ("AWS access key", r"\b(AKIA|ASIA)[0-9A-Z]{16}\b"),
+            ("Stripe secret key", r"\b[sr]k_(live|test)_[0-9A-Za-z]{16,}"),
+            ("Stripe webhook secret", r"\bwhsec_[0-9A-Za-z]{24,}"),
+            ("OpenAI API key", r"\bsk-(proj-)?[0-9A-Za-z_-]{32,}"),
+            ("Groq API key", r"\bgsk_[0-9A-Za-z]{32,}"),
+            ("Google API key", r"\bAIza[0-9A-Za-z_-]{35}\b"),
+            (
+                "GitHub token",
+                r"\b(gh[pousr]_[0-9A-Za-z]{36,}|github_pat_[0-9A-Za-z_]{40,})",
+            ),
+            ("Slack token", r"\bxox[abposr]-[0-9A-Za-z-]{10,}"),
+            (
+                "SendGrid API key",
+                r"\bSG\.[0-9A-Za-z_-]{16,}\.[0-9A-Za-z_-]{16,}",
+            ),
+            ("Mailgun API key", r"\bkey-[0-9a-f]{32}\b"),
+        ]
+        .into_iter()
+        .filter_map(|(name, re)| Regex::new(re).ok().map(|r| (name, r)))
+        .collect()
+    })
+}
+
+/// Name of the kind of credential `value` appears to contain, if any.
+pub fn detect(value: &str) -> Option<&'static str> {
+    patterns()
+        .iter()
+        .find(|(_, re)| re.is_match(value))
+        .map(|(name, _)| *name)
+}
+
+/// Warnings for public-facing fields in `data` that look like they contain
+/// a credential, e.g. "Footer Copyright Text looks like it contains a Stripe
+/// secret key".
+pub fn scan_public_fields(data: &HashMap<String, String>) -> Vec<String> {
+    PUBLIC_KEYS
+        .iter()
+        .filter_map(|(key, label)| {
+            let kind = detect(data.get(*key)?)?;
+            Some(format!("{} looks like it contains a {}", label, kind))
+        })
+        .collect()
+}
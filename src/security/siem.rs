@@ -0,0 +1,305 @@
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::io::Write;
+
+use crate::models::audit::AuditEntry;
+use crate::models::firewall::FwEvent;
+use crate::store::Store;
+
+/// Most entries of each kind sent per forwarding run.
+const BATCH_SIZE: i64 = 500;
+
+// ── Export ───────────────────────────────────────────────
+
+const CSV_HEADER: &str =
+    "id,created_at,user_id,user_name,action,entity_type,entity_id,entity_title,details,ip_address";
+
+pub(crate) fn csv_field(value: &str) -> String {
+    // Leading =, +, - or @ would be evaluated as a formula by spreadsheets
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// Audit entries as CSV, with a header row.
+pub fn export_csv(entries: &[AuditEntry]) -> String {
+    let mut out = String::from(CSV_HEADER);
+    out.push('\n');
+    for e in entries {
+        let opt = |v: &Option<String>| csv_field(v.as_deref().unwrap_or(""));
+        let num = |v: Option<i64>| v.map(|n| n.to_string()).unwrap_or_default();
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{}\n",
+            e.id,
+            e.created_at.format("%Y-%m-%dT%H:%M:%SZ"),
+            num(e.user_id),
+            opt(&e.user_name),
+            csv_field(&e.action),
+            opt(&e.entity_type),
+            num(e.entity_id),
+            opt(&e.entity_title),
+            opt(&e.details),
+            opt(&e.ip_address),
+        ));
+    }
+    out
+}
+
+/// Audit entries as JSON Lines, one event object per line.
+pub fn export_jsonl(entries: &[AuditEntry]) -> String {
+    entries
+        .iter()
+        .map(|e| audit_event(e).to_string() + "\n")
+        .collect()
+}
+
+// ── Event shape ──────────────────────────────────────────
+
+/// Normalise the stored timestamp formats (SQLite "YYYY-MM-DD HH:MM:SS",
+/// RFC 3339 from MongoDB) to RFC 3339 UTC.
+fn iso_timestamp(raw: &str) -> String {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(raw) {
+        return dt
+            .with_timezone(&chrono::Utc)
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string();
+    }
+    chrono::NaiveDateTime::parse_from_str(raw, "%Y-%m-%d %H:%M:%S")
+        .map(|dt| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+        .unwrap_or_else(|_| raw.to_string())
+}
+
+pub fn audit_event(e: &AuditEntry) -> Value {
+    json!({
+        "source": "audit",
+        "id": e.id,
+        "timestamp": e.created_at.format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+        "action": e.action,
+        "user_id": e.user_id,
+        "user_name": e.user_name,
+        "entity_type": e.entity_type,
+        "entity_id": e.entity_id,
+        "entity_title": e.entity_title,
+        "details": e.details,
+        "ip": e.ip_address,
+    })
+}
+
+pub fn firewall_event(e: &FwEvent) -> Value {
+    json!({
+        "source": "firewall",
+        "id": e.id,
+        "timestamp": iso_timestamp(&e.created_at),
+        "event_type": e.event_type,
+        "ip": e.ip,
+        "detail": e.detail,
+        "country": e.country,
+        "user_agent": e.user_agent,
+        "path": e.request_path,
+    })
+}
+
+/// RFC 5424 syslog line for an event, with the JSON event as the message.
+/// Facility is log audit (13); audit entries are notices, firewall events
+/// warnings.
+pub fn syslog_message(event: &Value, hostname: &str) -> String {
+    let (severity, msgid) = match event["source"].as_str() {
+        Some("firewall") => (4, "firewall"),
+        _ => (5, "audit"),
+    };
+    format!(
+        "<{}>1 {} {} velocty - {} - {}",
+        13 * 8 + severity,
+        event["timestamp"].as_str().unwrap_or("-"),
+        if hostname.is_empty() { "-" } else { hostname },
+        msgid,
+        event
+    )
+}
+
+/// Hex HMAC-SHA256 of a webhook body, sent as `X-Velocty-Signature: sha256=…`.
+pub fn sign_body(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// ── Transports ───────────────────────────────────────────
+
+fn send_webhook(url: &str, secret: &str, events: &[Value]) -> Result<(), String> {
+    let body = serde_json::to_vec(&json!({ "events": events })).map_err(|e| e.to_string())?;
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+    let mut req = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("User-Agent", "Velocty");
+    if !secret.is_empty() {
+        req = req.header(
+            "X-Velocty-Signature",
+            format!("sha256={}", sign_body(secret, &body)),
+        );
+    }
+    let resp = req
+        .body(body)
+        .send()
+        .map_err(|e| format!("Webhook request failed: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Webhook returned {}", resp.status()));
+    }
+    Ok(())
+}
+
+/// Send syslog lines to `target` ("host:port"). UDP sends one datagram per
+/// message; TCP uses octet-counting framing (RFC 6587).
+fn send_syslog(target: &str, protocol: &str, lines: &[String]) -> Result<(), String> {
+    let timeout = std::time::Duration::from_secs(5);
+    if protocol == "tcp" {
+        let addr = std::net::ToSocketAddrs::to_socket_addrs(target)
+            .map_err(|e| format!("Bad syslog target {}: {}", target, e))?
+            .next()
+            .ok_or_else(|| format!("Bad syslog target {}", target))?;
+        let mut stream = std::net::TcpStream::connect_timeout(&addr, timeout)
+            .map_err(|e| format!("Syslog connect failed: {}", e))?;
+        let _ = stream.set_write_timeout(Some(timeout));
+        for line in lines {
+            stream
+                .write_all(format!("{} {}", line.len(), line).as_bytes())
+                .map_err(|e| format!("Syslog write failed: {}", e))?;
+        }
+        return Ok(());
+    }
+    let socket =
+        std::net::UdpSocket::bind("0.0.0.0:0").map_err(|e| format!("Syslog socket: {}", e))?;
+    for line in lines {
+        socket
+            .send_to(line.as_bytes(), target)
+            .map_err(|e| format!("Syslog send failed: {}", e))?;
+    }
+    Ok(())
+}
+
+// ── Forwarding ───────────────────────────────────────────
+
+fn cursor(store: &dyn Store, key: &str) -> Option<i64> {
+    store.setting_get_or(key, "").parse().ok()
+}
+
+/// Send audit entries (and firewall events, when enabled) recorded since the
+/// last run to the configured SIEM. The position is kept in settings and
+/// only advanced after a successful send, so a failed run is retried. When
+/// forwarding starts, the position is set to the newest entry instead of
+/// replaying the whole history. Returns how many events were sent.
+pub fn forward_pending(store: &dyn Store) -> Result<usize, String> {
+    let include_fw = store.setting_get_or("siem_include_firewall", "true") == "true";
+    // Forget the position of anything not being forwarded, so turning it
+    // back on doesn't replay everything logged in between
+    for (key, active) in [
+        ("siem_audit_cursor", store.setting_get_bool("siem_enabled")),
+        (
+            "siem_fw_cursor",
+            store.setting_get_bool("siem_enabled") && include_fw,
+        ),
+    ] {
+        if !active && cursor(store, key).is_some() {
+            store.setting_set(key, "")?;
+        }
+    }
+    if !store.setting_get_bool("siem_enabled") {
+        return Ok(0);
+    }
+
+    let audit_cursor = match cursor(store, "siem_audit_cursor") {
+        Some(c) => c,
+        None => {
+            let newest = store
+                .audit_list(None, None, None, 50, 0)
+                .iter()
+                .map(|e| e.id)
+                .max()
+                .unwrap_or(0);
+            store.setting_set("siem_audit_cursor", &newest.to_string())?;
+            newest
+        }
+    };
+    let fw_cursor = match cursor(store, "siem_fw_cursor") {
+        Some(c) => c,
+        None => {
+            let newest = store
+                .fw_event_recent(None, 50, 0)
+                .iter()
+                .map(|e| e.id)
+                .max()
+                .unwrap_or(0);
+            store.setting_set("siem_fw_cursor", &newest.to_string())?;
+            newest
+        }
+    };
+
+    let audit = store.audit_list_after(audit_cursor, BATCH_SIZE);
+    let fw = if include_fw {
+        store.fw_event_list_after(fw_cursor, BATCH_SIZE)
+    } else {
+        vec![]
+    };
+    if audit.is_empty() && fw.is_empty() {
+        return Ok(0);
+    }
+    let events: Vec<Value> = audit
+        .iter()
+        .map(audit_event)
+        .chain(fw.iter().map(firewall_event))
+        .collect();
+
+    match store.setting_get_or("siem_mode", "webhook").as_str() {
+        "syslog" => {
+            let target = store.setting_get_or("siem_syslog_target", "");
+            if target.is_empty() {
+                return Err("Syslog target not configured".into());
+            }
+            let hostname = url::Url::parse(&store.setting_get_or("site_url", ""))
+                .ok()
+                .and_then(|u| u.host_str().map(|h| h.to_string()))
+                .unwrap_or_default();
+            let lines: Vec<String> = events
+                .iter()
+                .map(|e| syslog_message(e, &hostname))
+                .collect();
+            send_syslog(
+                &target,
+                &store.setting_get_or("siem_syslog_protocol", "udp"),
+                &lines,
+            )?;
+        }
+        _ => {
+            let url = store.setting_get_or("siem_webhook_url", "");
+            if url.is_empty() {
+                return Err("Webhook URL not configured".into());
+            }
+            send_webhook(
+                &url,
+                &store.setting_get_or("siem_webhook_secret", ""),
+                &events,
+            )?;
+        }
+    }
+
+    if let Some(last) = audit.last() {
+        store.setting_set("siem_audit_cursor", &last.id.to_string())?;
+    }
+    if let Some(last) = fw.last() {
+        store.setting_set("siem_fw_cursor", &last.id.to_string())?;
+    }
+    Ok(events.len())
+}
@@ -0,0 +1,93 @@
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::store::Store;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Token purposes. A token issued for one purpose never verifies for another.
+pub const MAGIC_LINK: &str = "magic_link";
+pub const PASSWORD_RESET: &str = "password_reset";
+pub const NEWSLETTER_CONFIRM: &str = "newsletter_confirm";
+pub const COMMENT_SUBSCRIBE: &str = "comment_subscribe";
+pub const EMAIL_CHANGE: &str = "email_change";
+pub const LOGIN_ALERT: &str = "login_alert";
+pub const FW_CHALLENGE: &str = "fw_challenge";
+pub const MFA_EMAIL: &str = "mfa_email";
+pub const OUTBOUND_LINK: &str = "outbound_link";
+pub const MEMBER_VERIFY: &str = "member_verify";
+pub const INVITE: &str = "invite";
+
+/// A verified, consumed token.
+#[derive(Debug, Clone, Serialize)]
+pub struct SignedToken {
+    pub purpose: String,
+    pub email: String,
+    /// Purpose-specific data (e.g. "post:42" for a comment subscription).
+    pub payload: String,
+    pub expires_at: String,
+}
+
+/// Issue a single-use token for `purpose`, valid for `ttl_minutes`.
+///
+/// The token is `<id>.<signature>`, where the signature is an HMAC over the
+/// purpose and id using the site's token secret. Only a SHA-256 hash of the
+/// token is stored, so a leaked database can't be used to redeem links.
+pub fn issue(
+    store: &dyn Store,
+    purpose: &str,
+    email: &str,
+    payload: &str,
+    ttl_minutes: i64,
+) -> Result<String, String> {
+    let mut id = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut id);
+    let id = hex::encode(id);
+    let token = format!("{}.{}", id, sign(&secret(store)?, purpose, &id));
+    store.signed_token_create(&hash_token(&token), purpose, email, payload, ttl_minutes)?;
+    Ok(token)
+}
+
+/// Verify and consume a token. Fails if the signature doesn't match `purpose`,
+/// the token expired, or it was already used.
+pub fn verify(store: &dyn Store, purpose: &str, token: &str) -> Result<SignedToken, String> {
+    let token = token.trim();
+    let (id, sig) = token.split_once('.').ok_or("Invalid or expired link")?;
+    let expected = sign(&secret(store)?, purpose, id);
+    if !super::constant_time_eq(sig.as_bytes(), expected.as_bytes()) {
+        return Err("Invalid or expired link".into());
+    }
+    store.signed_token_consume(&hash_token(token), purpose)
+}
+
+/// Stateless signature over `data` for `purpose`. Nothing is stored, so the
+/// result can be recomputed on every request (firewall challenge cookies).
+/// Use `issue` for anything that must be single-use.
+pub fn mac(store: &dyn Store, purpose: &str, data: &str) -> Result<String, String> {
+    Ok(sign(&secret(store)?, purpose, data))
+}
+
+/// Site-wide signing secret. It is seeded once by `seed_defaults` rather than
+/// on first use, so concurrent requests can't each generate a different one.
+fn secret(store: &dyn Store) -> Result<String, String> {
+    let secret = store.setting_get_or("signed_token_secret", "");
+    if secret.is_empty() {
+        return Err("Token signing secret is missing".into());
+    }
+    Ok(secret)
+}
+
+pub(crate) fn sign(secret: &str, purpose: &str, id: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(purpose.as_bytes());
+    mac.update(b":");
+    mac.update(id.as_bytes());
+    hex::encode(&mac.finalize().into_bytes()[..16])
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
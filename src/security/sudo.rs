@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use rocket::http::{CookieJar, Status};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::State;
+
+use crate::models::user::User;
+use crate::store::Store;
+
+use super::{auth, mfa, passwordless};
+
+/// How long a confirmation lasts when `sudo_timeout_minutes` isn't set.
+const DEFAULT_TIMEOUT_MINUTES: i64 = 10;
+
+/// Guard: the current session confirmed its credentials recently ("sudo
+/// mode"). Place it after the capability guard on destructive routes.
+/// Fails with 428 Precondition Required, which the admin UI answers by
+/// asking for the password or an authenticator code and retrying.
+pub struct SudoMode;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for SudoMode {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let store = match request.guard::<&State<Arc<dyn Store>>>().await {
+            Outcome::Success(s) => s,
+            _ => return Outcome::Error((Status::PreconditionRequired, ())),
+        };
+        match auth::session_id(request.cookies()) {
+            Some(sid) if store.session_sudo_active(&sid) => Outcome::Success(SudoMode),
+            _ => Outcome::Error((Status::PreconditionRequired, ())),
+        }
+    }
+}
+
+/// Check a step-up secret: the account password, a current TOTP code when
+/// MFA is on, or, for passwordless accounts, one of their recovery codes
+/// (which is used up).
+pub fn verify(store: &dyn Store, user: &User, secret: &str) -> bool {
+    let code = secret.trim();
+    if code.is_empty() {
+        return false;
+    }
+    if user.mfa_enabled && !user.mfa_secret.is_empty() && mfa::verify_code(&user.mfa_secret, code) {
+        return true;
+    }
+    if user.passwordless {
+        return passwordless::consume_recovery_code(store, user, code);
+    }
+    auth::verify_password(secret, &user.password_hash)
+}
+
+/// Confirm `secret` for the signed-in session and, when it checks out, put
+/// the session in sudo mode for `sudo_timeout_minutes`.
+pub fn confirm(
+    store: &dyn Store,
+    cookies: &CookieJar<'_>,
+    user: &User,
+    secret: &str,
+) -> Result<(), String> {
+    let sid = auth::session_id(cookies).ok_or("Not signed in")?;
+    if !verify(store, user, secret) {
+        return Err("That didn't match. Please try again.".into());
+    }
+    let minutes = match store.setting_get_i64("sudo_timeout_minutes") {
+        n if n > 0 => n,
+        _ => DEFAULT_TIMEOUT_MINUTES,
+    };
+    store.session_set_sudo(&sid, minutes)
+}
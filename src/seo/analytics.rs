@@ -2,7 +2,8 @@ use serde_json::Value;
 
 use super::html_escape;
 
-/// Build analytics script tags for all enabled third-party analytics providers.
+/// Build analytics script tags for all enabled third-party analytics providers
+/// and the built-in engagement beacon.
 /// When cookie consent is enabled, scripts are gated behind consent with
 /// `type="text/plain" data-consent="analytics"`.
 pub fn build_analytics_scripts(settings: &Value) -> String {
@@ -131,5 +132,20 @@ pub fn build_analytics_scripts(settings: &Value) -> String {
         }
     }
 
-    scripts
+    // Built-in engagement beacon (scroll depth and time on page)
+    if enabled("analytics_engagement_tracking") {
+        scripts.push_str(&format!("{}>{}</script>\n", stag, ENGAGEMENT_JS));
+    }
+
+    crate::render::nonce_scripts(&scripts)
 }
+
+/// Reports the furthest scroll depth and the seconds the page was visible
+/// to `/beacon`, once, when the visitor leaves or hides the page.
+const ENGAGEMENT_JS: &str = "(function(){var m=0,v=0,a=document.hidden?0:Date.now(),sent=false;\
+function d(){var e=document.documentElement,t=e.scrollHeight-e.clientHeight;return t>0?Math.min(100,Math.round((window.scrollY||e.scrollTop)*100/t)):100;}\
+m=d();window.addEventListener('scroll',function(){var x=d();if(x>m)m=x;},{passive:true});\
+function send(){if(a){v+=Date.now()-a;a=0;}if(sent||!navigator.sendBeacon)return;sent=true;\
+navigator.sendBeacon('/beacon',JSON.stringify({path:location.pathname,scroll:m,seconds:Math.round(v/1000)}));}\
+document.addEventListener('visibilitychange',function(){if(document.hidden)send();else if(!sent)a=Date.now();});\
+window.addEventListener('pagehide',send);})();";
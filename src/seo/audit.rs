@@ -26,8 +26,51 @@ pub struct SeoInput<'a> {
     pub body_html: &'a str,
     pub featured_image: &'a str,
     pub content_type: &'a str, // "post" or "portfolio"
+    pub focus_keyword: &'a str,
 }
 
+/// What the content analysis pass measured in a body
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ContentAnalysis {
+    pub word_count: usize,
+    pub sentence_count: usize,
+    pub avg_sentence_words: f64,
+    /// Share of sentences longer than `LONG_SENTENCE_WORDS`, 0-100
+    pub long_sentence_pct: f64,
+    /// Flesch reading ease (higher is easier); None for too few sentences
+    pub readability: Option<f64>,
+    /// Heading levels in document order, e.g. [2, 3, 3, 2]
+    pub headings: Vec<u8>,
+    /// Root-relative and relative links
+    pub internal_links: usize,
+    /// Absolute http(s) links
+    pub external_links: usize,
+    pub images: usize,
+    pub images_with_alt: usize,
+    /// Occurrences of the focus keyword in the body
+    pub keyword_count: usize,
+    /// Keyword words as a percentage of all words
+    pub keyword_density: f64,
+}
+
+/// One line of the editor's content checklist
+#[derive(Debug, Clone, Serialize)]
+pub struct ContentCheck {
+    pub check: String,
+    pub status: String, // "pass", "warn", "fail"
+    pub message: String,
+}
+
+/// Sentences with more words than this count as long
+pub const LONG_SENTENCE_WORDS: usize = 25;
+
+/// Readability is only scored from this many sentences up
+const MIN_SENTENCES_FOR_READABILITY: usize = 5;
+
+/// Keyword density outside this range (percent) is flagged
+const KEYWORD_DENSITY_MIN: f64 = 0.5;
+const KEYWORD_DENSITY_MAX: f64 = 3.0;
+
 /// Compute SEO score for a content item.
 /// Starts at 100 and deducts points for each issue found.
 pub fn compute_score(input: &SeoInput) -> SeoAudit {
@@ -230,6 +273,138 @@ pub fn compute_score(input: &SeoInput) -> SeoAudit {
         }
     }
 
+    // ── Content Analysis: structure, readability, keyword ──
+    let analysis = analyze_content(input.body_html, input.focus_keyword);
+
+    if count_pattern(&input.body_html.to_lowercase(), "<h1") > 0 {
+        issues.push(SeoIssue {
+            code: "content_h1".into(),
+            severity: "warning".into(),
+            message: "Content contains an H1 — the title is already the H1, use H2 and below"
+                .into(),
+            points_lost: 3,
+        });
+        deductions += 3;
+    }
+    if let Some((from, to)) = skipped_heading(&analysis.headings) {
+        issues.push(SeoIssue {
+            code: "heading_skipped".into(),
+            severity: "info".into(),
+            message: format!("Heading levels skip from H{} to H{}", from, to),
+            points_lost: 2,
+        });
+        deductions += 2;
+    }
+
+    if input.content_type == "post" {
+        if let Some(ease) = analysis.readability {
+            if ease < 30.0 {
+                issues.push(SeoIssue {
+                    code: "readability_hard".into(),
+                    severity: "warning".into(),
+                    message: format!(
+                        "Content is hard to read (reading ease {:.0}, aim for 60+)",
+                        ease
+                    ),
+                    points_lost: 5,
+                });
+                deductions += 5;
+            } else if ease < 50.0 {
+                issues.push(SeoIssue {
+                    code: "readability_fair".into(),
+                    severity: "info".into(),
+                    message: format!(
+                        "Content is fairly difficult to read (reading ease {:.0}, aim for 60+)",
+                        ease
+                    ),
+                    points_lost: 2,
+                });
+                deductions += 2;
+            }
+            if analysis.long_sentence_pct > 25.0 {
+                issues.push(SeoIssue {
+                    code: "sentences_long".into(),
+                    severity: "info".into(),
+                    message: format!(
+                        "{:.0}% of sentences are over {} words — try splitting them",
+                        analysis.long_sentence_pct, LONG_SENTENCE_WORDS
+                    ),
+                    points_lost: 2,
+                });
+                deductions += 2;
+            }
+        }
+        if word_count > 200 && analysis.internal_links == 0 && analysis.external_links > 0 {
+            issues.push(SeoIssue {
+                code: "no_internal_links".into(),
+                severity: "info".into(),
+                message: "No internal links — link to related content on this site".into(),
+                points_lost: 2,
+            });
+            deductions += 2;
+        }
+    }
+
+    let keyword = input.focus_keyword.trim().to_lowercase();
+    if !keyword.is_empty() {
+        if !effective_title.to_lowercase().contains(&keyword) {
+            issues.push(SeoIssue {
+                code: "keyword_not_in_title".into(),
+                severity: "warning".into(),
+                message: format!("Focus keyword \"{}\" is not in the title", keyword),
+                points_lost: 5,
+            });
+            deductions += 5;
+        }
+        if !input.meta_description.is_empty()
+            && !input.meta_description.to_lowercase().contains(&keyword)
+        {
+            issues.push(SeoIssue {
+                code: "keyword_not_in_description".into(),
+                severity: "info".into(),
+                message: format!(
+                    "Focus keyword \"{}\" is not in the meta description",
+                    keyword
+                ),
+                points_lost: 2,
+            });
+            deductions += 2;
+        }
+        if word_count > 0 {
+            if analysis.keyword_count == 0 {
+                issues.push(SeoIssue {
+                    code: "keyword_missing".into(),
+                    severity: "warning".into(),
+                    message: format!("Focus keyword \"{}\" never appears in the content", keyword),
+                    points_lost: 5,
+                });
+                deductions += 5;
+            } else if analysis.keyword_density < KEYWORD_DENSITY_MIN {
+                issues.push(SeoIssue {
+                    code: "keyword_density_low".into(),
+                    severity: "info".into(),
+                    message: format!(
+                        "Focus keyword density is low ({:.1}%, aim for {}-{}%)",
+                        analysis.keyword_density, KEYWORD_DENSITY_MIN, KEYWORD_DENSITY_MAX
+                    ),
+                    points_lost: 2,
+                });
+                deductions += 2;
+            } else if analysis.keyword_density > KEYWORD_DENSITY_MAX {
+                issues.push(SeoIssue {
+                    code: "keyword_density_high".into(),
+                    severity: "warning".into(),
+                    message: format!(
+                        "Focus keyword density is high ({:.1}%) — this can read as keyword stuffing",
+                        analysis.keyword_density
+                    ),
+                    points_lost: 3,
+                });
+                deductions += 3;
+            }
+        }
+    }
+
     let score = (100 - deductions).clamp(0, 100);
     let grade = if score >= 80 {
         "good"
@@ -247,6 +422,236 @@ pub fn compute_score(input: &SeoInput) -> SeoAudit {
     }
 }
 
+/// Measure a body's structure, readability and focus keyword use.
+/// Absolute http(s) links count as external, all other links as internal.
+pub fn analyze_content(body_html: &str, focus_keyword: &str) -> ContentAnalysis {
+    let text = strip_html_tags(body_html);
+    let word_count = count_words(&text);
+
+    // Sentences end at punctuation or at the end of a block element
+    let sentence_words: Vec<usize> = strip_html_blocks(body_html)
+        .split(['.', '!', '?', '\n'])
+        .map(count_words)
+        .filter(|&n| n > 0)
+        .collect();
+    let sentence_count = sentence_words.len();
+    let sentence_total: usize = sentence_words.iter().sum();
+    let avg_sentence_words = if sentence_count > 0 {
+        sentence_total as f64 / sentence_count as f64
+    } else {
+        0.0
+    };
+    let long_sentence_pct = if sentence_count > 0 {
+        sentence_words
+            .iter()
+            .filter(|&&n| n > LONG_SENTENCE_WORDS)
+            .count() as f64
+            * 100.0
+            / sentence_count as f64
+    } else {
+        0.0
+    };
+
+    let words = tokens(&text);
+    let readability =
+        (sentence_count >= MIN_SENTENCES_FOR_READABILITY && !words.is_empty()).then(|| {
+            let syllables: usize = words.iter().map(|w| count_syllables(w)).sum();
+            206.835
+                - 1.015 * (words.len() as f64 / sentence_count as f64)
+                - 84.6 * (syllables as f64 / words.len() as f64)
+        });
+
+    let (internal_links, external_links) = count_links(body_html);
+    let images = count_pattern(&body_html.to_lowercase(), "<img ");
+    let images_with_alt = images.saturating_sub(count_images_without_alt(body_html));
+
+    let keyword = tokens(focus_keyword);
+    let keyword_count = if keyword.is_empty() {
+        0
+    } else {
+        words
+            .windows(keyword.len())
+            .filter(|w| *w == keyword.as_slice())
+            .count()
+    };
+    let keyword_density = if words.is_empty() {
+        0.0
+    } else {
+        (keyword_count * keyword.len()) as f64 * 100.0 / words.len() as f64
+    };
+
+    ContentAnalysis {
+        word_count,
+        sentence_count,
+        avg_sentence_words,
+        long_sentence_pct,
+        readability,
+        headings: heading_levels(body_html),
+        internal_links,
+        external_links,
+        images,
+        images_with_alt,
+        keyword_count,
+        keyword_density,
+    }
+}
+
+/// The content analysis as checklist items for the editor's SEO panel
+pub fn content_checklist(input: &SeoInput) -> Vec<ContentCheck> {
+    let a = analyze_content(input.body_html, input.focus_keyword);
+    let mut checks = Vec::new();
+    let mut push = |check: &str, status: &str, message: String| {
+        checks.push(ContentCheck {
+            check: check.into(),
+            status: status.into(),
+            message,
+        })
+    };
+
+    let keyword = input.focus_keyword.trim();
+    if keyword.is_empty() {
+        push(
+            "Focus Keyword",
+            "warn",
+            "No focus keyword set. Add the phrase this page should rank for.".into(),
+        );
+    } else {
+        let title = if input.meta_title.is_empty() {
+            input.title
+        } else {
+            input.meta_title
+        };
+        let in_title = title.to_lowercase().contains(&keyword.to_lowercase());
+        if a.keyword_count == 0 {
+            push(
+                "Keyword Density",
+                "fail",
+                format!("\"{}\" doesn't appear in the content.", keyword),
+            );
+        } else if a.keyword_density < KEYWORD_DENSITY_MIN || a.keyword_density > KEYWORD_DENSITY_MAX
+        {
+            push(
+                "Keyword Density",
+                "warn",
+                format!(
+                    "{:.1}% ({} uses). Aim for {}–{}%.",
+                    a.keyword_density, a.keyword_count, KEYWORD_DENSITY_MIN, KEYWORD_DENSITY_MAX
+                ),
+            );
+        } else {
+            push(
+                "Keyword Density",
+                "pass",
+                format!("{:.1}% ({} uses).", a.keyword_density, a.keyword_count),
+            );
+        }
+        if in_title {
+            push(
+                "Keyword in Title",
+                "pass",
+                "The title uses the focus keyword.".into(),
+            );
+        } else {
+            push(
+                "Keyword in Title",
+                "warn",
+                "Put the focus keyword in the title, ideally near the start.".into(),
+            );
+        }
+    }
+
+    match skipped_heading(&a.headings) {
+        Some((from, to)) => push(
+            "Heading Order",
+            "warn",
+            format!(
+                "Jumps from H{} to H{}. Don't skip heading levels.",
+                from, to
+            ),
+        ),
+        None if a.headings.is_empty() && a.word_count > 300 => push(
+            "Heading Order",
+            "warn",
+            "No headings. Break long content up with H2 and H3 headings.".into(),
+        ),
+        None => push(
+            "Heading Order",
+            "pass",
+            format!("{} heading(s), in order.", a.headings.len()),
+        ),
+    }
+
+    if let Some(ease) = a.readability {
+        let (status, label) = if ease >= 60.0 {
+            ("pass", "easy to read")
+        } else if ease >= 30.0 {
+            ("warn", "fairly difficult")
+        } else {
+            ("fail", "hard to read")
+        };
+        push(
+            "Readability",
+            status,
+            format!("Reading ease {:.0} — {}. Aim for 60+.", ease, label),
+        );
+        if a.long_sentence_pct > 25.0 {
+            push(
+                "Sentence Length",
+                "warn",
+                format!(
+                    "{:.0}% of sentences are over {} words (average {:.0}). Split the long ones.",
+                    a.long_sentence_pct, LONG_SENTENCE_WORDS, a.avg_sentence_words
+                ),
+            );
+        } else {
+            push(
+                "Sentence Length",
+                "pass",
+                format!("Average {:.0} words per sentence.", a.avg_sentence_words),
+            );
+        }
+    }
+
+    let links = format!(
+        "{} internal, {} external.",
+        a.internal_links, a.external_links
+    );
+    if a.internal_links == 0 && input.content_type == "post" && a.word_count > 200 {
+        push(
+            "Links",
+            "warn",
+            format!("{} Link to related content on this site.", links),
+        );
+    } else {
+        push("Links", "pass", links);
+    }
+
+    if a.images == 0 {
+        push(
+            "Image Alt Text",
+            "pass",
+            "No inline images to check.".into(),
+        );
+    } else if a.images_with_alt < a.images {
+        push(
+            "Image Alt Text",
+            "warn",
+            format!(
+                "{} of {} images have alt text. Describe every image.",
+                a.images_with_alt, a.images
+            ),
+        );
+    } else {
+        push(
+            "Image Alt Text",
+            "pass",
+            format!("All {} images have alt text.", a.images),
+        );
+    }
+
+    checks
+}
+
 /// Serialize issues to a compact JSON string for DB storage
 pub fn issues_to_json(issues: &[SeoIssue]) -> String {
     serde_json::to_string(issues).unwrap_or_else(|_| "[]".to_string())
@@ -275,6 +680,138 @@ fn strip_html_tags(html: &str) -> String {
     result
 }
 
+/// Like `strip_html_tags`, but ends a line at each block element so
+/// headings and list items without punctuation still split sentences.
+fn strip_html_blocks(html: &str) -> String {
+    const BLOCKS: &[&str] = &[
+        "p",
+        "li",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "br",
+        "div",
+        "blockquote",
+        "tr",
+    ];
+    let mut result = String::with_capacity(html.len());
+    let mut tag = String::new();
+    let mut in_tag = false;
+    for ch in html.chars() {
+        if ch == '<' {
+            in_tag = true;
+            tag.clear();
+        } else if ch == '>' && in_tag {
+            in_tag = false;
+            let name = tag
+                .trim_start_matches('/')
+                .split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .unwrap_or("")
+                .to_lowercase();
+            result.push(if BLOCKS.contains(&name.as_str()) {
+                '\n'
+            } else {
+                ' '
+            });
+        } else if in_tag {
+            tag.push(ch);
+        } else {
+            result.push(ch);
+        }
+    }
+    result
+}
+
+/// Lowercase alphanumeric words, for keyword matching and syllable counts
+fn tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// Rough English syllable count: vowel groups, less a silent final "e"
+fn count_syllables(word: &str) -> usize {
+    let mut count = 0;
+    let mut prev_vowel = false;
+    for c in word.chars() {
+        let vowel = matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+        if vowel && !prev_vowel {
+            count += 1;
+        }
+        prev_vowel = vowel;
+    }
+    if count > 1 && word.ends_with('e') && !word.ends_with("le") {
+        count -= 1;
+    }
+    count.max(1)
+}
+
+/// Levels of the h1–h6 tags in document order
+fn heading_levels(html: &str) -> Vec<u8> {
+    let lower = html.to_lowercase();
+    let bytes = lower.as_bytes();
+    lower
+        .match_indices("<h")
+        .filter_map(|(i, _)| {
+            let level = *bytes.get(i + 2)?;
+            let after = bytes.get(i + 3).copied().unwrap_or(b'>');
+            ((b'1'..=b'6').contains(&level) && (after == b'>' || after.is_ascii_whitespace()))
+                .then(|| level - b'0')
+        })
+        .collect()
+}
+
+/// The first place a heading goes more than one level deeper than the
+/// one before it; the page title counts as the H1 everything sits under.
+fn skipped_heading(levels: &[u8]) -> Option<(u8, u8)> {
+    let mut prev = 1;
+    for &level in levels {
+        if level > prev + 1 {
+            return Some((prev, level));
+        }
+        prev = level;
+    }
+    None
+}
+
+/// (internal, external) link counts. In-page anchors, mailto: and tel:
+/// links count as neither.
+fn count_links(html: &str) -> (usize, usize) {
+    let lower = html.to_lowercase();
+    let (mut internal, mut external) = (0, 0);
+    for (i, _) in lower.match_indices("<a ") {
+        let tag = &lower[i..lower[i..].find('>').map(|e| i + e).unwrap_or(lower.len())];
+        let Some(pos) = tag.find("href=") else {
+            continue;
+        };
+        let rest = &tag[pos + 5..];
+        let href = match rest.chars().next() {
+            Some(q @ ('"' | '\'')) => rest[1..].split(q).next().unwrap_or(""),
+            _ => rest.split_whitespace().next().unwrap_or(""),
+        }
+        .trim();
+        if href.is_empty()
+            || href.starts_with('#')
+            || href.starts_with("mailto:")
+            || href.starts_with("tel:")
+            || href.starts_with("javascript:")
+        {
+            continue;
+        }
+        if href.starts_with("http://") || href.starts_with("https://") || href.starts_with("//") {
+            external += 1;
+        } else {
+            internal += 1;
+        }
+    }
+    (internal, external)
+}
+
 fn count_words(text: &str) -> usize {
     text.split_whitespace()
         .filter(|w| w.len() > 1 || w.chars().all(|c| c.is_alphanumeric()))
@@ -324,6 +861,7 @@ mod tests {
             body_html: body,
             featured_image: image,
             content_type,
+            focus_keyword: "",
         }
     }
 
@@ -462,4 +1000,74 @@ mod tests {
         let r = compute_score(&input);
         assert_eq!(r.grade, "good");
     }
+
+    #[test]
+    fn content_analysis_counts_structure() {
+        let body = "<h2>Film</h2><p>Shooting film photography is slow. \
+                    <a href=\"/journal/cameras\">Cameras</a> and \
+                    <a href=\"https://example.org\">a lab</a>.</p>\
+                    <h4>Skipped</h4><img src='a.jpg' alt='roll'><img src='b.jpg'>\
+                    <a href=\"#top\">top</a>";
+        let a = analyze_content(body, "Film Photography");
+        assert_eq!(a.headings, vec![2, 4]);
+        assert_eq!(skipped_heading(&a.headings), Some((2, 4)));
+        assert_eq!((a.internal_links, a.external_links), (1, 1));
+        assert_eq!((a.images, a.images_with_alt), (2, 1));
+        assert_eq!(a.keyword_count, 1);
+        assert!(a.keyword_density > 0.0);
+    }
+
+    #[test]
+    fn readability_needs_enough_sentences() {
+        let short = analyze_content("<p>One sentence only.</p>", "");
+        assert!(short.readability.is_none());
+
+        let easy = analyze_content(&"<p>The cat sat on the mat. </p>".repeat(6), "");
+        let hard = analyze_content(
+            &"<p>Institutional photographic documentation necessitates considerable \
+              organizational responsibility regarding archival preservation methodologies. </p>"
+                .repeat(6),
+            "",
+        );
+        assert!(easy.readability.unwrap() > 80.0);
+        assert!(hard.readability.unwrap() < 30.0);
+        assert_eq!(easy.sentence_count, 6);
+    }
+
+    #[test]
+    fn focus_keyword_checks() {
+        let body =
+            "<h2>Heading</h2><p>".to_string() + &"word ".repeat(350) + "</p><a href='/x'>l</a>";
+        let mut input = make_input(
+            "A Good Title That Is Long Enough For SEO",
+            "A Good Title That Is Long Enough For SEO",
+            "A meta description that is between 120 and 160 characters long for search engine optimization purposes here today.",
+            &body,
+            "photo.jpg",
+            "post",
+        );
+        input.focus_keyword = "sourdough";
+        let r = compute_score(&input);
+        for code in [
+            "keyword_not_in_title",
+            "keyword_not_in_description",
+            "keyword_missing",
+        ] {
+            assert!(r.issues.iter().any(|i| i.code == code), "{}", code);
+        }
+
+        // Every other word is the keyword: stuffing
+        let stuffed =
+            "<h2>Sourdough</h2><p>".to_string() + &"sourdough bread ".repeat(200) + "</p>";
+        input.title = "Sourdough Bread At Home, Step By Step Guide";
+        input.meta_title = "";
+        input.body_html = &stuffed;
+        let r = compute_score(&input);
+        assert!(r.issues.iter().any(|i| i.code == "keyword_density_high"));
+        assert!(!r.issues.iter().any(|i| i.code == "keyword_not_in_title"));
+        let checklist = content_checklist(&input);
+        assert!(checklist
+            .iter()
+            .any(|c| c.check == "Keyword Density" && c.status == "warn"));
+    }
 }
@@ -0,0 +1,106 @@
+use crate::models::post::Post;
+use crate::render::slug_url;
+use crate::store::Store;
+
+use super::html_escape;
+
+/// Tidy a language code into BCP 47 casing ("pt_br" → "pt-BR",
+/// "zh-hant" → "zh-Hant"). None when blank or malformed.
+pub fn normalize_language(raw: Option<&str>) -> Option<String> {
+    let raw = raw?.trim().replace('_', "-");
+    let mut parts = raw.split('-');
+    let primary = parts.next()?;
+    if !(2..=3).contains(&primary.len()) || !primary.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let mut code = primary.to_ascii_lowercase();
+    for part in parts {
+        if part.is_empty() || part.len() > 8 || !part.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        code.push('-');
+        match part.len() {
+            // Region, e.g. BR
+            2 => code.push_str(&part.to_ascii_uppercase()),
+            // Script, e.g. Hant
+            4 => {
+                code.push_str(&part[..1].to_ascii_uppercase());
+                code.push_str(&part[1..].to_ascii_lowercase());
+            }
+            _ => code.push_str(&part.to_ascii_lowercase()),
+        }
+    }
+    Some(code)
+}
+
+/// The site's default language (`site_language`, "en" if unset)
+pub fn site_language(store: &dyn Store) -> String {
+    normalize_language(Some(&store.setting_get_or("site_language", "en")))
+        .unwrap_or_else(|| "en".to_string())
+}
+
+/// Language versions in a translation group as (language, site path), one
+/// per language. Noindexed posts are left out.
+pub fn group_alternates(store: &dyn Store, group: &str) -> Vec<(String, String)> {
+    let blog_slug = store.setting_get_or("blog_slug", "journal");
+    let default_language = site_language(store);
+    let mut alternates: Vec<(String, String)> = Vec::new();
+    for post in store.post_translations(group) {
+        if post.robots_noindex {
+            continue;
+        }
+        let language = normalize_language(post.language.as_deref())
+            .unwrap_or_else(|| default_language.clone());
+        if alternates.iter().any(|(l, _)| *l == language) {
+            continue;
+        }
+        alternates.push((language, slug_url(&blog_slug, &post.slug)));
+    }
+    alternates
+}
+
+/// A post's language versions, itself included. Empty unless it's
+/// published alongside at least one translation.
+pub fn post_alternates(store: &dyn Store, post: &Post) -> Vec<(String, String)> {
+    let Some(group) = post.translation_group.as_deref().filter(|g| !g.is_empty()) else {
+        return vec![];
+    };
+    let alternates = group_alternates(store, group);
+    let own_path = slug_url(&store.setting_get_or("blog_slug", "journal"), &post.slug);
+    if alternates.len() < 2 || !alternates.iter().any(|(_, path)| *path == own_path) {
+        return vec![];
+    }
+    alternates
+}
+
+/// Which version `x-default` points at: the one in the site language,
+/// else the first.
+pub fn default_alternate<'a>(
+    alternates: &'a [(String, String)],
+    site_language: &str,
+) -> Option<&'a (String, String)> {
+    alternates
+        .iter()
+        .find(|(l, _)| l == site_language)
+        .or_else(|| alternates.first())
+}
+
+/// `<link rel="alternate" hreflang>` tags for every version plus `x-default`
+pub fn alternate_links(base: &str, alternates: &[(String, String)], site_language: &str) -> String {
+    let base = base.trim_end_matches('/');
+    let mut out = String::new();
+    for (language, path) in alternates {
+        out.push_str(&format!(
+            "\n<link rel=\"alternate\" hreflang=\"{}\" href=\"{}\">",
+            html_escape(language),
+            html_escape(&format!("{}{}", base, path))
+        ));
+    }
+    if let Some((_, path)) = default_alternate(alternates, site_language) {
+        out.push_str(&format!(
+            "\n<link rel=\"alternate\" hreflang=\"x-default\" href=\"{}\">",
+            html_escape(&format!("{}{}", base, path))
+        ));
+    }
+    out
+}
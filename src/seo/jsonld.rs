@@ -1,105 +1,316 @@
 use crate::models::portfolio::PortfolioItem;
 use crate::models::post::Post;
+use crate::render::{format_date_iso8601, slug_url, Breadcrumb};
+use crate::routes::admin::is_video_filename;
 use crate::store::Store;
-use chrono::{DateTime, Utc};
+use serde_json::{json, Map, Value};
 
-use super::json_escape;
+/// Schema.org types a post can be published as
+pub const POST_SCHEMA_TYPES: &[&str] = &[
+    "BlogPosting",
+    "Article",
+    "NewsArticle",
+    "TechArticle",
+    "CreativeWork",
+];
 
-/// Format a NaiveDateTime as ISO 8601 with timezone offset for schema.org
-fn format_iso8601(ndt: chrono::NaiveDateTime, tz_name: &str) -> String {
-    let utc: DateTime<Utc> = DateTime::from_naive_utc_and_offset(ndt, Utc);
-    if let Ok(tz) = tz_name.parse::<chrono_tz::Tz>() {
-        utc.with_timezone(&tz)
-            .format("%Y-%m-%dT%H:%M:%S%:z")
-            .to_string()
+/// Schema.org types a portfolio item can be published as
+pub const PORTFOLIO_SCHEMA_TYPES: &[&str] = &[
+    "ImageObject",
+    "Photograph",
+    "VisualArtwork",
+    "VideoObject",
+    "Product",
+    "CreativeWork",
+];
+
+/// Keep a submitted schema type only if it's one of `allowed`; anything
+/// else (including "" for automatic) is stored as None.
+pub fn valid_schema_type(raw: Option<&str>, allowed: &[&str]) -> Option<String> {
+    let t = raw?.trim();
+    allowed.contains(&t).then(|| t.to_string())
+}
+
+fn str_field<'a>(v: &'a Value, key: &str) -> &'a str {
+    v.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+/// The type a post is published as: its own choice, else BlogPosting.
+pub fn post_schema_type(post: &Value) -> &str {
+    let chosen = str_field(post, "schema_type");
+    if POST_SCHEMA_TYPES.contains(&chosen) {
+        chosen
     } else {
-        utc.format("%Y-%m-%dT%H:%M:%S+00:00").to_string()
+        POST_SCHEMA_TYPES[0]
     }
 }
 
-/// Build JSON-LD structured data for a blog post
-pub fn build_post_jsonld(store: &dyn Store, post: &Post) -> String {
-    let site_name = store.setting_get_or("site_name", "Velocty");
-    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
-    let blog_slug = store.setting_get_or("blog_slug", "journal");
-    let tz_name = store.setting_get_or("timezone", "UTC");
-
-    let published = post
-        .published_at
-        .map(|d| format_iso8601(d, &tz_name))
-        .unwrap_or_default();
-
-    let modified = format_iso8601(post.updated_at, &tz_name);
-
-    let mut ld = format!(
-        r#"<script type="application/ld+json">
-{{
-    "@context": "https://schema.org",
-    "@type": "BlogPosting",
-    "headline": "{}",
-    "description": "{}",
-    "url": "{}/{}/{}",
-    "datePublished": "{}",
-    "dateModified": "{}",
-    "publisher": {{
-        "@type": "Organization",
-        "name": "{}"
-    }}"#,
-        json_escape(&post.title),
-        json_escape(post.meta_description.as_deref().unwrap_or("")),
-        site_url,
-        blog_slug,
-        post.slug,
-        published,
-        modified,
-        json_escape(&site_name),
+/// The type a portfolio item is published as: its own choice, else
+/// VideoObject for a video, Product when it's for sale, else ImageObject.
+pub fn portfolio_schema_type(item: &Value) -> &str {
+    let chosen = str_field(item, "schema_type");
+    if PORTFOLIO_SCHEMA_TYPES.contains(&chosen) {
+        return chosen;
+    }
+    let for_sale = item
+        .get("sell_enabled")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false)
+        && item.get("price").and_then(|v| v.as_f64()).unwrap_or(0.0) > 0.0;
+    if is_video_filename(str_field(item, "image_path")) {
+        "VideoObject"
+    } else if for_sale {
+        "Product"
+    } else {
+        "ImageObject"
+    }
+}
+
+/// Wrap a JSON-LD object in its script tag
+fn script(ld: &Value) -> String {
+    format!(
+        "<script type=\"application/ld+json\">\n{}\n</script>",
+        serde_json::to_string_pretty(ld)
+            .unwrap_or_default()
+            .replace("</", "<\\/")
+    )
+}
+
+/// Site-level values every object needs
+struct Site<'a> {
+    settings: &'a Value,
+    url: String,
+    name: String,
+}
+
+impl<'a> Site<'a> {
+    fn new(settings: &'a Value) -> Self {
+        let get = |k: &str, d: &str| {
+            settings
+                .get(k)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .unwrap_or(d)
+                .to_string()
+        };
+        Site {
+            settings,
+            url: get("site_url", "http://localhost:8000")
+                .trim_end_matches('/')
+                .to_string(),
+            name: get("site_name", "Velocty"),
+        }
+    }
+
+    fn setting(&self, key: &str, default: &'a str) -> &'a str {
+        self.settings
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or(default)
+    }
+
+    fn upload(&self, path: &str) -> String {
+        format!("{}/uploads/{}", self.url, path.trim_start_matches('/'))
+    }
+
+    fn date(&self, raw: &str) -> Option<String> {
+        (!raw.is_empty()).then(|| format_date_iso8601(raw, self.settings))
+    }
+
+    fn publisher(&self) -> Value {
+        json!({ "@type": "Organization", "name": self.name })
+    }
+}
+
+/// Add `key` unless `value` is empty
+fn put(ld: &mut Map<String, Value>, key: &str, value: Option<String>) {
+    if let Some(v) = value.filter(|v| !v.is_empty()) {
+        ld.insert(key.to_string(), Value::String(v));
+    }
+}
+
+/// JSON-LD for a post given as its template context value (with
+/// `author_name` when known) and the settings map.
+pub fn post_jsonld(post: &Value, settings: &Value) -> String {
+    let site = Site::new(settings);
+    let schema_type = post_schema_type(post);
+    let title = str_field(post, "title");
+    let url = format!(
+        "{}{}",
+        site.url,
+        slug_url(
+            site.setting("blog_slug", "journal"),
+            str_field(post, "slug")
+        )
     );
+    let description = Some(str_field(post, "meta_description"))
+        .filter(|d| !d.is_empty())
+        .unwrap_or(str_field(post, "excerpt"));
+    let published = site.date(str_field(post, "published_at"));
 
-    // Add featured image if present
-    if let Some(ref img) = post.featured_image {
-        if !img.is_empty() {
-            ld.push_str(&format!(
-                r#",
-    "image": "{}/uploads/{}"
-"#,
-                site_url,
-                json_escape(img)
-            ));
+    let mut ld = Map::new();
+    ld.insert("@context".into(), json!("https://schema.org"));
+    ld.insert("@type".into(), json!(schema_type));
+    if schema_type == "CreativeWork" {
+        ld.insert("name".into(), json!(title));
+        put(
+            &mut ld,
+            "dateCreated",
+            site.date(str_field(post, "created_at")),
+        );
+    } else {
+        ld.insert("headline".into(), json!(title));
+        ld.insert(
+            "mainEntityOfPage".into(),
+            json!({ "@type": "WebPage", "@id": url }),
+        );
+    }
+    put(&mut ld, "description", Some(description.to_string()));
+    ld.insert("url".into(), json!(url));
+    put(&mut ld, "datePublished", published);
+    put(
+        &mut ld,
+        "dateModified",
+        site.date(str_field(post, "updated_at")),
+    );
+    let image = str_field(post, "featured_image");
+    if !image.is_empty() {
+        ld.insert("image".into(), json!(site.upload(image)));
+    }
+    let author = str_field(post, "author_name");
+    if !author.is_empty() {
+        let mut person = json!({ "@type": "Person", "name": author });
+        let author_url = str_field(post, "author_url");
+        if !author_url.is_empty() {
+            person["url"] = json!(format!("{}{}", site.url, author_url));
         }
+        ld.insert("author".into(), person);
     }
+    ld.insert("publisher".into(), site.publisher());
+    script(&Value::Object(ld))
+}
+
+/// JSON-LD for a portfolio item given as its template context value and
+/// the settings map.
+pub fn portfolio_jsonld(item: &Value, settings: &Value) -> String {
+    let site = Site::new(settings);
+    let schema_type = portfolio_schema_type(item);
+    let title = str_field(item, "title");
+    let url = format!(
+        "{}{}",
+        site.url,
+        slug_url(
+            site.setting("portfolio_slug", "portfolio"),
+            str_field(item, "slug")
+        )
+    );
+    let description = str_field(item, "meta_description");
+    let media = str_field(item, "image_path");
+    let thumbnail = Some(str_field(item, "thumbnail_path"))
+        .filter(|t| !t.is_empty() && !is_video_filename(t))
+        .or_else(|| Some(media).filter(|m| !m.is_empty() && !is_video_filename(m)))
+        .map(|t| site.upload(t));
+    let published = site.date(
+        Some(str_field(item, "published_at"))
+            .filter(|d| !d.is_empty())
+            .unwrap_or(str_field(item, "created_at")),
+    );
 
-    ld.push_str("\n}\n</script>");
-    ld
+    let mut ld = Map::new();
+    ld.insert("@context".into(), json!("https://schema.org"));
+    ld.insert("@type".into(), json!(schema_type));
+    ld.insert("name".into(), json!(title));
+    put(&mut ld, "description", Some(description.to_string()));
+    ld.insert("url".into(), json!(url));
+    match schema_type {
+        "VideoObject" => {
+            // Search engines require a description and thumbnail for videos
+            if description.is_empty() {
+                ld.insert("description".into(), json!(title));
+            }
+            if !media.is_empty() {
+                ld.insert("contentUrl".into(), json!(site.upload(media)));
+            }
+            put(&mut ld, "thumbnailUrl", thumbnail);
+            put(&mut ld, "uploadDate", published);
+        }
+        "Product" => {
+            put(&mut ld, "image", thumbnail);
+            let price = item.get("price").and_then(|v| v.as_f64()).unwrap_or(0.0);
+            if price > 0.0 {
+                ld.insert(
+                    "offers".into(),
+                    json!({
+                        "@type": "Offer",
+                        "price": format!("{:.2}", price),
+                        "priceCurrency": site.setting("commerce_currency", "USD"),
+                        "availability": "https://schema.org/InStock",
+                        "url": url,
+                    }),
+                );
+            }
+            ld.insert("brand".into(), site.publisher());
+        }
+        "ImageObject" | "Photograph" => {
+            if !media.is_empty() {
+                ld.insert("contentUrl".into(), json!(site.upload(media)));
+            }
+            if !str_field(item, "thumbnail_path").is_empty() {
+                put(&mut ld, "thumbnailUrl", thumbnail);
+            }
+            put(&mut ld, "datePublished", published);
+        }
+        _ => {
+            // VisualArtwork and CreativeWork
+            put(&mut ld, "image", thumbnail);
+            put(&mut ld, "dateCreated", published);
+        }
+    }
+    if schema_type != "Product" {
+        ld.insert("publisher".into(), site.publisher());
+    }
+    script(&Value::Object(ld))
+}
+
+/// Settings as the JSON map the Value-based builders expect
+fn settings_value(store: &dyn Store) -> Value {
+    serde_json::to_value(store.setting_all()).unwrap_or_default()
+}
+
+/// Build JSON-LD structured data for a blog post
+pub fn build_post_jsonld(store: &dyn Store, post: &Post) -> String {
+    post_jsonld(
+        &serde_json::to_value(post).unwrap_or_default(),
+        &settings_value(store),
+    )
 }
 
 /// Build JSON-LD structured data for a portfolio item
 pub fn build_portfolio_jsonld(store: &dyn Store, item: &PortfolioItem) -> String {
-    let site_name = store.setting_get_or("site_name", "Velocty");
-    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
-    let portfolio_slug = store.setting_get_or("portfolio_slug", "portfolio");
-
-    format!(
-        r#"<script type="application/ld+json">
-{{
-    "@context": "https://schema.org",
-    "@type": "ImageObject",
-    "name": "{}",
-    "description": "{}",
-    "contentUrl": "{}/uploads/{}",
-    "url": "{}/{}/{}",
-    "publisher": {{
-        "@type": "Organization",
-        "name": "{}"
-    }}
-}}
-</script>"#,
-        json_escape(&item.title),
-        json_escape(item.meta_description.as_deref().unwrap_or("")),
-        site_url,
-        item.image_path,
-        site_url,
-        portfolio_slug,
-        item.slug,
-        json_escape(&site_name),
+    portfolio_jsonld(
+        &serde_json::to_value(item).unwrap_or_default(),
+        &settings_value(store),
     )
 }
+
+/// Build BreadcrumbList JSON-LD matching a page's breadcrumb trail
+pub fn build_breadcrumb_jsonld(site_url: &str, trail: &[Breadcrumb]) -> String {
+    let site_url = site_url.trim_end_matches('/');
+    let items: Vec<Value> = trail
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            json!({
+                "@type": "ListItem",
+                "position": i + 1,
+                "name": c.label,
+                "item": format!("{}{}", site_url, c.url),
+            })
+        })
+        .collect();
+    script(&json!({
+        "@context": "https://schema.org",
+        "@type": "BreadcrumbList",
+        "itemListElement": items,
+    }))
+}
@@ -2,12 +2,35 @@ use crate::store::Store;
 
 use super::html_escape;
 
+/// Per-item robots and canonical overrides from the post/portfolio editor.
+#[derive(Debug, Default)]
+pub struct MetaOverrides<'a> {
+    pub noindex: bool,
+    pub nofollow: bool,
+    /// Absolute URL, or a site path resolved against the canonical base
+    pub canonical: Option<&'a str>,
+    /// Language versions as (language, site path), from
+    /// `hreflang::post_alternates`; emitted as hreflang links
+    pub alternates: &'a [(String, String)],
+}
+
 /// Build meta tags HTML string for a page
 pub fn build_meta(
     store: &dyn Store,
     title: Option<&str>,
     description: Option<&str>,
     path: &str,
+) -> String {
+    build_meta_with(store, title, description, path, &MetaOverrides::default())
+}
+
+/// `build_meta` for a single post or portfolio item, honouring its overrides.
+pub fn build_meta_with(
+    store: &dyn Store,
+    title: Option<&str>,
+    description: Option<&str>,
+    path: &str,
+    overrides: &MetaOverrides,
 ) -> String {
     let site_name = store.setting_get_or("site_name", "Velocty");
     let site_url = store.setting_get_or("site_url", "http://localhost:8000");
@@ -25,7 +48,11 @@ pub fn build_meta(
     };
 
     let page_desc = description.unwrap_or(&default_desc);
-    let canonical = format!("{}{}", canonical_base, path);
+    let canonical = match overrides.canonical.filter(|c| !c.is_empty()) {
+        Some(c) if c.starts_with('/') => format!("{}{}", canonical_base, c),
+        Some(c) => c.to_string(),
+        None => format!("{}{}", canonical_base, path),
+    };
 
     let mut meta = String::new();
 
@@ -39,6 +66,29 @@ pub fn build_meta(
         html_escape(&canonical),
     ));
 
+    if !overrides.alternates.is_empty() {
+        meta.push_str(&super::hreflang::alternate_links(
+            &canonical_base,
+            overrides.alternates,
+            &super::hreflang::site_language(store),
+        ));
+    }
+
+    let robots: Vec<&str> = [
+        (overrides.noindex, "noindex"),
+        (overrides.nofollow, "nofollow"),
+    ]
+    .iter()
+    .filter(|(on, _)| *on)
+    .map(|(_, directive)| *directive)
+    .collect();
+    if !robots.is_empty() {
+        meta.push_str(&format!(
+            "\n<meta name=\"robots\" content=\"{}\">",
+            robots.join(", ")
+        ));
+    }
+
     // Open Graph
     if og_enabled {
         meta.push_str(&format!(
@@ -1,7 +1,11 @@
 pub mod analytics;
 pub mod audit;
+pub mod hreflang;
 pub mod jsonld;
 pub mod meta;
+pub mod ping;
+pub mod rewrite;
+pub mod robots;
 pub mod sitemap;
 pub mod webmaster;
 
@@ -9,7 +13,7 @@ pub mod webmaster;
 pub use analytics::build_analytics_scripts;
 #[allow(unused_imports)]
 pub use jsonld::{build_portfolio_jsonld, build_post_jsonld};
-pub use meta::build_meta;
+pub use meta::{build_meta, build_meta_with, MetaOverrides};
 #[allow(unused_imports)]
 pub use sitemap::generate_sitemap;
 pub use webmaster::build_webmaster_meta;
@@ -20,9 +24,3 @@ fn html_escape(s: &str) -> String {
         .replace('>', "&gt;")
         .replace('"', "&quot;")
 }
-
-fn json_escape(s: &str) -> String {
-    s.replace('\\', "\\\\")
-        .replace('"', "\\\"")
-        .replace('\n', "\\n")
-}
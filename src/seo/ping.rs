@@ -0,0 +1,159 @@
+use rand::Rng;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::models::ping_log::PingLogEntry;
+use crate::store::Store;
+
+/// Where the IndexNow key file is served. IndexNow fetches it (via the
+/// `keyLocation` we send) to check that the pings really come from this site.
+pub const KEY_FILE_PATH: &str = "/indexnow-key.txt";
+
+/// IndexNow endpoints by engine, each behind its own `seo_ping_<engine>` toggle.
+const INDEXNOW_ENGINES: [(&str, &str); 2] = [
+    ("bing", "https://www.bing.com/indexnow"),
+    ("yandex", "https://yandex.com/indexnow"),
+];
+
+/// Whether any engine is switched on.
+pub fn enabled(store: &dyn Store) -> bool {
+    indexnow_enabled(store) || store.setting_get_bool("seo_ping_google")
+}
+
+pub fn indexnow_enabled(store: &dyn Store) -> bool {
+    INDEXNOW_ENGINES
+        .iter()
+        .any(|(engine, _)| store.setting_get_bool(&format!("seo_ping_{}", engine)))
+}
+
+/// The site's IndexNow key, generated and saved the first time it's needed.
+pub fn indexnow_key(store: &dyn Store) -> String {
+    let key = store.setting_get_or("seo_indexnow_key", "");
+    if !key.is_empty() {
+        return key;
+    }
+    let bytes: [u8; 16] = rand::thread_rng().gen();
+    let key = hex::encode(bytes);
+    let _ = store.setting_set("seo_indexnow_key", &key);
+    key
+}
+
+/// Absolute URL of a published post ("post") or portfolio item.
+pub fn content_url(store: &dyn Store, content_type: &str, slug: &str) -> String {
+    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
+    let section = if content_type == "portfolio" {
+        store.setting_get_or("portfolio_slug", "portfolio")
+    } else {
+        store.setting_get_or("blog_slug", "journal")
+    };
+    format!(
+        "{}{}",
+        site_url.trim_end_matches('/'),
+        crate::render::slug_url(&section, slug)
+    )
+}
+
+/// The IndexNow request body for `urls`.
+pub fn indexnow_payload(site_url: &str, key: &str, urls: &[String]) -> serde_json::Value {
+    let site_url = site_url.trim_end_matches('/');
+    let host = url::Url::parse(site_url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_string()))
+        .unwrap_or_default();
+    json!({
+        "host": host,
+        "key": key,
+        "keyLocation": format!("{}{}", site_url, KEY_FILE_PATH),
+        "urlList": urls,
+    })
+}
+
+/// Ping the enabled engines about `urls` on a background thread, so saving
+/// a post never waits on a search engine.
+pub fn notify(store: Arc<dyn Store>, urls: Vec<String>) {
+    if !enabled(&*store) {
+        return;
+    }
+    std::thread::spawn(move || {
+        let sent = run(&*store, &urls);
+        log::info!("[seo] Sent {} search engine pings", sent);
+    });
+}
+
+/// `notify` for a post ("post") or portfolio item that was just published
+/// or updated.
+pub fn notify_content(store: &Arc<dyn Store>, content_type: &str, slug: &str) {
+    let url = content_url(&**store, content_type, slug);
+    notify(Arc::clone(store), vec![url]);
+}
+
+/// Send the pings and log each result. With no `urls` (e.g. after a
+/// scheduled publish) only the sitemap ping goes out. Returns how many
+/// requests were made.
+pub fn run(store: &dyn Store, urls: &[String]) -> usize {
+    let client = match reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(15))
+        .user_agent("Velocty")
+        .build()
+    {
+        Ok(c) => c,
+        Err(e) => {
+            log::warn!("[seo] Ping client error: {}", e);
+            return 0;
+        }
+    };
+    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
+    let mut sent = 0;
+
+    if !urls.is_empty() && indexnow_enabled(store) {
+        let payload = indexnow_payload(&site_url, &indexnow_key(store), urls);
+        for (engine, endpoint) in INDEXNOW_ENGINES {
+            if !store.setting_get_bool(&format!("seo_ping_{}", engine)) {
+                continue;
+            }
+            let result = client.post(endpoint).json(&payload).send();
+            log_result(store, engine, &urls.join("\n"), result);
+            sent += 1;
+        }
+    }
+
+    if store.setting_get_bool("seo_ping_google") && store.setting_get_bool("seo_sitemap_enabled") {
+        let sitemap = format!("{}/sitemap.xml", site_url.trim_end_matches('/'));
+        let encoded: String = url::form_urlencoded::byte_serialize(sitemap.as_bytes()).collect();
+        let result = client
+            .get(format!("https://www.google.com/ping?sitemap={}", encoded))
+            .send();
+        log_result(store, "google", &sitemap, result);
+        sent += 1;
+    }
+    sent
+}
+
+fn log_result(
+    store: &dyn Store,
+    engine: &str,
+    urls: &str,
+    result: reqwest::Result<reqwest::blocking::Response>,
+) {
+    let (success, status_code, message) = match result {
+        Ok(resp) => {
+            let status = resp.status();
+            let reason = status.canonical_reason().unwrap_or("").to_string();
+            (status.is_success(), status.as_u16() as i64, reason)
+        }
+        Err(e) => (false, 0, e.to_string()),
+    };
+    if !success {
+        log::warn!("[seo] {} ping failed: {} {}", engine, status_code, message);
+    }
+    let _ = store.ping_log_add(&PingLogEntry {
+        id: 0,
+        engine: engine.to_string(),
+        urls: urls.to_string(),
+        success,
+        status_code,
+        message,
+        created_at: String::new(),
+    });
+}
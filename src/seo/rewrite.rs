@@ -0,0 +1,401 @@
+use regex::Regex;
+use serde_json::Value;
+use std::sync::OnceLock;
+
+use super::audit::{self, SeoInput};
+use crate::ai::{self, prompts, AiRequest};
+use crate::models::portfolio::{PortfolioForm, PortfolioItem};
+use crate::models::post::{Post, PostForm};
+use crate::models::search::{strip_html, truncate_words};
+use crate::models::seo_suggestion::SeoSuggestion;
+use crate::store::Store;
+
+/// Items scoring below this are offered for an AI pass in bulk.
+pub const LOW_SCORE: i32 = 80;
+
+/// Items handled per bulk request; each one is a full completion.
+pub const BATCH_SIZE: usize = 5;
+
+/// The fields of a post or portfolio item the audit looks at.
+struct Target {
+    title: String,
+    slug: String,
+    meta_title: String,
+    meta_description: String,
+    body_html: String,
+    featured_image: String,
+    focus_keyword: String,
+}
+
+fn load(store: &dyn Store, content_type: &str, id: i64) -> Option<Target> {
+    match content_type {
+        "post" => store.post_find_by_id(id).map(|p| Target {
+            title: p.title,
+            slug: p.slug,
+            meta_title: p.meta_title.unwrap_or_default(),
+            meta_description: p.meta_description.unwrap_or_default(),
+            body_html: p.content_html,
+            featured_image: p.featured_image.unwrap_or_default(),
+            focus_keyword: p.focus_keyword.unwrap_or_default(),
+        }),
+        "portfolio" => store.portfolio_find_by_id(id).map(|p| Target {
+            title: p.title,
+            slug: p.slug,
+            meta_title: p.meta_title.unwrap_or_default(),
+            meta_description: p.meta_description.unwrap_or_default(),
+            body_html: p.description_html.unwrap_or_default(),
+            featured_image: p.image_path,
+            focus_keyword: p.focus_keyword.unwrap_or_default(),
+        }),
+        _ => None,
+    }
+}
+
+fn audit_of(target: &Target, content_type: &str) -> audit::SeoAudit {
+    audit::compute_score(&SeoInput {
+        title: &target.title,
+        slug: &target.slug,
+        meta_title: &target.meta_title,
+        meta_description: &target.meta_description,
+        body_html: &target.body_html,
+        featured_image: &target.featured_image,
+        content_type,
+        focus_keyword: &target.focus_keyword,
+    })
+}
+
+fn heading_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?is)<h([1-6])([^>]*)>(.*?)</h[1-6]\s*>").unwrap())
+}
+
+fn heading_text(inner: &str) -> String {
+    strip_html(inner)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Headings in `html` as (level, text), in document order.
+pub fn extract_headings(html: &str) -> Vec<(u8, String)> {
+    heading_re()
+        .captures_iter(html)
+        .filter_map(|c| {
+            let level = c[1].parse().ok()?;
+            let text = heading_text(&c[3]);
+            (!text.is_empty()).then_some((level, text))
+        })
+        .collect()
+}
+
+/// Replace the first heading reading `from` with `to` at `level` (0 keeps
+/// the current level), keeping its attributes. None when no heading matches.
+pub fn replace_heading(html: &str, from: &str, to: &str, level: i64) -> Option<String> {
+    let from = heading_text(from);
+    let caps = heading_re()
+        .captures_iter(html)
+        .find(|c| heading_text(&c[3]).eq_ignore_ascii_case(&from))?;
+    let whole = caps.get(0)?;
+    let level = match level {
+        2..=6 => level.to_string(),
+        _ => caps[1].to_string(),
+    };
+    Some(format!(
+        "{}<h{level}{}>{}</h{level}>{}",
+        &html[..whole.start()],
+        &caps[2],
+        super::html_escape(to.trim()),
+        &html[whole.end()..],
+        level = level
+    ))
+}
+
+fn text_of(v: &Value) -> (String, String) {
+    let get = |k: &str| {
+        v.get(k)
+            .and_then(|s| s.as_str())
+            .unwrap_or("")
+            .trim()
+            .to_string()
+    };
+    match v.as_str() {
+        Some(s) => (s.trim().to_string(), String::new()),
+        None => (get("text"), get("reason")),
+    }
+}
+
+/// Turn the model's JSON answer into suggestions, dropping empty ones, ones
+/// that change nothing and heading changes for headings the item doesn't have.
+pub fn parse_suggestions(
+    content_type: &str,
+    content_id: i64,
+    meta_title: &str,
+    meta_description: &str,
+    headings: &[(u8, String)],
+    answer: &Value,
+) -> Vec<SeoSuggestion> {
+    let make =
+        |field: &str, current: &str, suggested: String, level: i64, reason: String| SeoSuggestion {
+            id: 0,
+            content_type: content_type.to_string(),
+            content_id,
+            field: field.to_string(),
+            current: current.to_string(),
+            suggested,
+            level,
+            reason,
+            status: "pending".to_string(),
+            created_at: String::new(),
+        };
+    let mut out = Vec::new();
+
+    for t in answer
+        .get("titles")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let (text, reason) = text_of(t);
+        if !text.is_empty()
+            && text != meta_title
+            && !out.iter().any(|s: &SeoSuggestion| s.suggested == text)
+        {
+            out.push(make("meta_title", meta_title, text, 0, reason));
+        }
+    }
+
+    if let Some(d) = answer.get("meta_description") {
+        let (text, reason) = text_of(d);
+        if !text.is_empty() && text != meta_description {
+            out.push(make("meta_description", meta_description, text, 0, reason));
+        }
+    }
+
+    for h in answer
+        .get("headings")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+    {
+        let from = heading_text(h.get("from").and_then(|v| v.as_str()).unwrap_or(""));
+        let to = h
+            .get("to")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        let level = h.get("level").and_then(|v| v.as_i64()).unwrap_or(0);
+        let current = match headings
+            .iter()
+            .find(|(_, text)| text.eq_ignore_ascii_case(&from))
+        {
+            Some(c) => c,
+            None => continue,
+        };
+        let level = if (2..=6).contains(&level) { level } else { 0 };
+        if to.is_empty() || (to == current.1 && (level == 0 || level == current.0 as i64)) {
+            continue;
+        }
+        let reason = h
+            .get("reason")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .trim()
+            .to_string();
+        out.push(make("heading", &current.1, to, level, reason));
+    }
+    out
+}
+
+/// Ask the AI failover chain for rewrites of one item and store them for
+/// review, replacing its earlier pending suggestions.
+pub fn suggest(
+    store: &dyn Store,
+    content_type: &str,
+    id: i64,
+) -> Result<Vec<SeoSuggestion>, String> {
+    let target = load(store, content_type, id).ok_or("Content not found")?;
+    let issues: Vec<String> = audit_of(&target, content_type)
+        .issues
+        .into_iter()
+        .map(|i| i.message)
+        .collect();
+    let headings = extract_headings(&target.body_html);
+
+    let req = AiRequest {
+        system: prompts::seo_system(),
+        prompt: prompts::seo_rewrite(
+            content_type,
+            &target.title,
+            &target.meta_title,
+            &target.meta_description,
+            &headings,
+            &issues,
+            &truncate_words(&strip_html(&target.body_html), 400),
+        ),
+        max_tokens: Some(1024),
+        temperature: Some(0.7),
+        image_base64: None,
+        feature: "seo_audit".to_string(),
+    };
+    let resp = ai::complete(store, &req).map_err(|e| e.to_string())?;
+    let answer = crate::routes::ai::parse_json_from_text(&resp.text)
+        .ok_or("The AI response couldn't be read")?;
+
+    let suggestions = parse_suggestions(
+        content_type,
+        id,
+        &target.meta_title,
+        &target.meta_description,
+        &headings,
+        &answer,
+    );
+    if suggestions.is_empty() {
+        return Err("The AI had no suggestions for this item".into());
+    }
+
+    store.seo_suggestion_clear(content_type, id)?;
+    let mut saved = Vec::with_capacity(suggestions.len());
+    for mut s in suggestions {
+        s.id = store.seo_suggestion_create(&s)?;
+        saved.push(s);
+    }
+    Ok(saved)
+}
+
+fn post_form(p: &Post) -> PostForm {
+    PostForm {
+        title: p.title.clone(),
+        slug: p.slug.clone(),
+        content_json: p.content_json.clone(),
+        content_html: p.content_html.clone(),
+        excerpt: p.excerpt.clone(),
+        featured_image: p.featured_image.clone(),
+        meta_title: p.meta_title.clone(),
+        meta_description: p.meta_description.clone(),
+        status: p.status.clone(),
+        published_at: p
+            .published_at
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string()),
+        category_ids: None,
+        tag_ids: None,
+        robots_noindex: Some(p.robots_noindex),
+        robots_nofollow: Some(p.robots_nofollow),
+        canonical_url: p.canonical_url.clone(),
+        schema_type: p.schema_type.clone(),
+        focus_keyword: p.focus_keyword.clone(),
+        language: p.language.clone(),
+        translation_group: p.translation_group.clone(),
+        audio_file: p.audio_file.clone(),
+        audio_duration: p.audio_duration,
+        audio_bytes: p.audio_bytes,
+        template: p.template.clone(),
+    }
+}
+
+fn portfolio_form(p: &PortfolioItem) -> PortfolioForm {
+    PortfolioForm {
+        title: p.title.clone(),
+        slug: p.slug.clone(),
+        description_json: p.description_json.clone(),
+        description_html: p.description_html.clone(),
+        image_path: p.image_path.clone(),
+        thumbnail_path: p.thumbnail_path.clone(),
+        meta_title: p.meta_title.clone(),
+        meta_description: p.meta_description.clone(),
+        sell_enabled: Some(p.sell_enabled),
+        price: p.price,
+        purchase_note: Some(p.purchase_note.clone()),
+        payment_provider: Some(p.payment_provider.clone()),
+        download_file_path: Some(p.download_file_path.clone()),
+        status: p.status.clone(),
+        published_at: p
+            .published_at
+            .map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string()),
+        category_ids: None,
+        tag_ids: None,
+        robots_noindex: Some(p.robots_noindex),
+        robots_nofollow: Some(p.robots_nofollow),
+        canonical_url: p.canonical_url.clone(),
+        schema_type: p.schema_type.clone(),
+        focus_keyword: p.focus_keyword.clone(),
+        template: p.template.clone(),
+    }
+}
+
+/// Write `s` into its item, rescore the item and mark `s` applied. Other
+/// pending suggestions for the same meta or description field are
+/// alternatives to it and are dismissed.
+pub fn apply(store: &dyn Store, s: &SeoSuggestion) -> Result<(), String> {
+    if s.status != "pending" {
+        return Err("This suggestion has already been handled".into());
+    }
+    let set = |meta_title: &mut Option<String>,
+               meta_description: &mut Option<String>,
+               body: &mut String|
+     -> Result<(), String> {
+        match s.field.as_str() {
+            "meta_title" => *meta_title = Some(s.suggested.clone()),
+            "meta_description" => *meta_description = Some(s.suggested.clone()),
+            "heading" => {
+                *body = replace_heading(body, &s.current, &s.suggested, s.level)
+                    .ok_or("That heading has changed since the suggestion was made")?;
+            }
+            // Image captions only fill in an empty portfolio description
+            "description" if s.content_type == "portfolio" => *body = s.suggested.clone(),
+            _ => return Err("Unknown suggestion type".into()),
+        }
+        Ok(())
+    };
+
+    match s.content_type.as_str() {
+        "post" => {
+            let post = store
+                .post_find_by_id(s.content_id)
+                .ok_or("Post not found")?;
+            let mut form = post_form(&post);
+            set(
+                &mut form.meta_title,
+                &mut form.meta_description,
+                &mut form.content_html,
+            )?;
+            store.post_update(post.id, &form)?;
+        }
+        "portfolio" => {
+            let item = store
+                .portfolio_find_by_id(s.content_id)
+                .ok_or("Portfolio item not found")?;
+            let mut form = portfolio_form(&item);
+            let mut body = form.description_html.clone().unwrap_or_default();
+            set(&mut form.meta_title, &mut form.meta_description, &mut body)?;
+            form.description_html = Some(body);
+            store.portfolio_update(item.id, &form)?;
+        }
+        _ => return Err("Unknown content type".into()),
+    }
+
+    rescore(store, &s.content_type, s.content_id);
+    store.seo_suggestion_set_status(s.id, "applied")?;
+    if s.field != "heading" {
+        for other in store.seo_suggestion_pending(Some((&s.content_type, s.content_id))) {
+            if other.field == s.field {
+                let _ = store.seo_suggestion_set_status(other.id, "dismissed");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Recompute and store an item's audit score.
+pub fn rescore(store: &dyn Store, content_type: &str, id: i64) {
+    let target = match load(store, content_type, id) {
+        Some(t) => t,
+        None => return,
+    };
+    let audit = audit_of(&target, content_type);
+    let issues = audit::issues_to_json(&audit.issues);
+    let _ = match content_type {
+        "post" => store.post_update_seo_score(id, audit.score, &issues),
+        _ => store.portfolio_update_seo_score(id, audit.score, &issues),
+    };
+}
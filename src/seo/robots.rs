@@ -0,0 +1,170 @@
+use crate::render::slug_url;
+use crate::store::Store;
+
+/// robots.txt used when the setting is empty
+pub const DEFAULT_ROBOTS: &str = "User-agent: *\nAllow: /";
+
+/// Crawlers that collect content for AI training or answers, as
+/// (user agent, who runs it). Each can be denied from SEO settings.
+pub const AI_CRAWLERS: &[(&str, &str)] = &[
+    ("GPTBot", "OpenAI"),
+    ("ChatGPT-User", "OpenAI"),
+    ("OAI-SearchBot", "OpenAI"),
+    ("ClaudeBot", "Anthropic"),
+    ("anthropic-ai", "Anthropic"),
+    ("Google-Extended", "Google"),
+    ("Applebot-Extended", "Apple"),
+    ("CCBot", "Common Crawl"),
+    ("PerplexityBot", "Perplexity"),
+    ("Bytespider", "ByteDance"),
+    ("meta-externalagent", "Meta"),
+    ("Amazonbot", "Amazon"),
+    ("cohere-ai", "Cohere"),
+];
+
+/// The AI crawlers ticked in `seo_robots_blocked_bots`
+pub fn blocked_crawlers(store: &dyn Store) -> Vec<&'static str> {
+    let chosen = store.setting_get_or("seo_robots_blocked_bots", "");
+    let chosen: Vec<&str> = chosen.split(',').map(str::trim).collect();
+    AI_CRAWLERS
+        .iter()
+        .map(|(agent, _)| *agent)
+        .filter(|agent| chosen.iter().any(|c| c.eq_ignore_ascii_case(agent)))
+        .collect()
+}
+
+/// robots.txt: the edited rules, a deny group per blocked AI crawler and
+/// the sitemap reference.
+pub fn generate_robots(store: &dyn Store) -> String {
+    let mut content = store.setting_get_or("seo_robots_txt", DEFAULT_ROBOTS);
+    if content.trim().is_empty() {
+        content = DEFAULT_ROBOTS.to_string();
+    }
+    let mut content = content.trim_end().replace("\r\n", "\n");
+    for agent in blocked_crawlers(store) {
+        content.push_str(&format!("\n\nUser-agent: {}\nDisallow: /", agent));
+    }
+    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
+    let has_sitemap_line = content
+        .lines()
+        .any(|l| l.trim().to_lowercase().starts_with("sitemap:"));
+    if store.setting_get_bool("seo_sitemap_enabled") && !has_sitemap_line {
+        content.push_str(&format!(
+            "\nSitemap: {}/sitemap.xml",
+            site_url.trim_end_matches('/')
+        ));
+    }
+    content
+}
+
+const DIRECTIVES: &[&str] = &[
+    "user-agent",
+    "allow",
+    "disallow",
+    "sitemap",
+    "crawl-delay",
+    "host",
+    "clean-param",
+];
+
+/// Check edited robots.txt rules before saving: every line must be a known
+/// `Field: value` directive or a comment, rules need a User-agent above
+/// them, and the whole site can't be disallowed for every crawler.
+pub fn validate_robots(text: &str) -> Result<(), String> {
+    // User agents of the group being read, and whether a rule ended the list
+    let mut agents: Vec<String> = Vec::new();
+    let mut in_rules = false;
+    for (i, raw) in text.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some((field, value)) = line.split_once(':') else {
+            return Err(format!(
+                "Line {}: expected \"Field: value\", got \"{}\"",
+                i + 1,
+                line
+            ));
+        };
+        let field = field.trim().to_lowercase();
+        let value = value.trim();
+        if !DIRECTIVES.contains(&field.as_str()) {
+            return Err(format!("Line {}: unknown directive \"{}\"", i + 1, field));
+        }
+        match field.as_str() {
+            "user-agent" => {
+                if in_rules {
+                    agents.clear();
+                    in_rules = false;
+                }
+                agents.push(value.to_string());
+            }
+            "allow" | "disallow" | "crawl-delay" => {
+                if agents.is_empty() {
+                    return Err(format!(
+                        "Line {}: \"{}\" needs a User-agent line above it",
+                        i + 1,
+                        field
+                    ));
+                }
+                in_rules = true;
+                if field == "disallow" && value == "/" && agents.iter().any(|a| a == "*") {
+                    return Err(format!(
+                        "Line {}: \"Disallow: /\" for every crawler would hide the whole site from search engines",
+                        i + 1
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// llms.txt, when enabled: the edited text, or a summary of the site
+/// with links to its sections if left empty. None when disabled.
+pub fn generate_llms(store: &dyn Store) -> Option<String> {
+    if !store.setting_get_bool("seo_llms_txt_enabled") {
+        return None;
+    }
+    let custom = store.setting_get_or("seo_llms_txt", "");
+    if !custom.trim().is_empty() {
+        return Some(custom);
+    }
+    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
+    let site_url = site_url.trim_end_matches('/');
+    let mut out = format!("# {}\n", store.setting_get_or("site_name", "Velocty"));
+    let description = store.setting_get_or("seo_default_description", "");
+    let description = if description.is_empty() {
+        store.setting_get_or("site_caption", "")
+    } else {
+        description
+    };
+    if !description.is_empty() {
+        out.push_str(&format!("\n> {}\n", description.trim()));
+    }
+    out.push_str("\n## Sections\n\n");
+    for (enabled, slug_key, default_slug, label) in [
+        ("journal_enabled", "blog_slug", "journal", "Journal"),
+        (
+            "portfolio_enabled",
+            "portfolio_slug",
+            "portfolio",
+            "Portfolio",
+        ),
+    ] {
+        if store.setting_get_bool(enabled) {
+            let slug = store.setting_get_or(slug_key, default_slug);
+            out.push_str(&format!(
+                "- [{}]({}{})\n",
+                label,
+                site_url,
+                slug_url(&slug, "")
+            ));
+        }
+    }
+    if store.setting_get_bool("seo_sitemap_enabled") {
+        out.push_str(&format!("- [Sitemap]({}/sitemap.xml)\n", site_url));
+    }
+    Some(out)
+}
@@ -1,70 +1,461 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use super::{hreflang, html_escape};
+use crate::render::{page_cache, slug_url};
+use crate::routes::admin::is_video_filename;
 use crate::store::Store;
 
-/// Generate sitemap.xml content.
+/// Most URLs a single sitemap file may list (the sitemaps.org limit)
+pub const MAX_URLS_PER_FILE: usize = 50_000;
+
+/// Built parts are rebuilt after this long even without a change, to pick
+/// up writes the Store doesn't report (imports, raw SQL).
+const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+const URLSET_OPEN: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9" xmlns:image="http://www.google.com/schemas/sitemap-image/1.1" xmlns:video="http://www.google.com/schemas/sitemap-video/1.1" xmlns:xhtml="http://www.w3.org/1999/xhtml">
+"#;
+
+/// Parts of the sitemap the Store marks as changed on its own. Section
+/// indexes and taxonomy archives are cheap and rebuilt after any change;
+/// the post and portfolio files, which scan whole tables, are only rebuilt
+/// when their own content changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Posts,
+    Portfolio,
+}
+
+static POSTS_GENERATION: AtomicU64 = AtomicU64::new(0);
+static PORTFOLIO_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Mark a section's files stale; the Store calls this after writing posts
+/// or portfolio items.
+pub fn changed(section: Section) {
+    match section {
+        Section::Posts => POSTS_GENERATION.fetch_add(1, Ordering::AcqRel),
+        Section::Portfolio => PORTFOLIO_GENERATION.fetch_add(1, Ordering::AcqRel),
+    };
+}
+
+/// One sitemap file: name (without `.xml`), XML and the newest lastmod in it
+type File = (String, String, Option<String>);
+
+/// The files of one part of the sitemap and what they were built from
+#[derive(Clone)]
+struct Part {
+    at: Instant,
+    generation: u64,
+    files: Vec<File>,
+}
+
+impl Part {
+    fn fresh(&self, generation: u64) -> bool {
+        self.generation == generation && self.at.elapsed() < CACHE_TTL
+    }
+}
+
+/// Parts in index order: pages, posts, portfolio, taxonomies
+const PARTS: usize = 4;
+
+/// The counter each part is built against
+fn generations() -> [u64; PARTS] {
+    let any = page_cache::generation();
+    [
+        any,
+        POSTS_GENERATION.load(Ordering::Acquire),
+        PORTFOLIO_GENERATION.load(Ordering::Acquire),
+        any,
+    ]
+}
+
+struct Built {
+    /// Settings the files were built with; a change rebuilds every part
+    config: String,
+    parts: Vec<Part>,
+    /// File name (without `.xml`) → XML; "index" is `/sitemap.xml`
+    files: HashMap<String, String>,
+}
+
+impl Built {
+    fn fresh(&self, config: &str, generations: &[u64; PARTS]) -> bool {
+        self.config == config && self.parts.iter().zip(generations).all(|(p, g)| p.fresh(*g))
+    }
+}
+
+/// Built sitemaps per site URL
+fn cache() -> &'static RwLock<HashMap<String, Built>> {
+    static CACHE: OnceLock<RwLock<HashMap<String, Built>>> = OnceLock::new();
+    CACHE.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Drop the built sitemaps, so every part is rebuilt on the next request.
+pub fn invalidate() {
+    if let Ok(mut c) = cache().write() {
+        c.clear();
+    }
+}
+
+/// A sitemap file by name ("index" for `/sitemap.xml`, or e.g. "posts-1"),
+/// built on first request and cached until its part changes, `invalidate`
+/// or the TTL. Only the stale parts are rebuilt. None when sitemaps are off
+/// or there's no such file.
+pub fn cached_file(store: &dyn Store, name: &str) -> Option<String> {
+    if !store.setting_get_bool("seo_sitemap_enabled") {
+        return None;
+    }
+    let ctx = Context::load(store);
+    let config = ctx.config_key();
+    // Read before building: a change made mid-build leaves the part stale
+    let generations = generations();
+    let previous: Vec<Option<Part>> = match cache().read() {
+        Ok(c) => match c.get(&ctx.site_url) {
+            Some(built) if built.fresh(&config, &generations) => {
+                return built.files.get(name).cloned();
+            }
+            Some(built) if built.config == config => built
+                .parts
+                .iter()
+                .zip(&generations)
+                .map(|(p, g)| p.fresh(*g).then(|| p.clone()))
+                .collect(),
+            _ => vec![None; PARTS],
+        },
+        Err(_) => vec![None; PARTS],
+    };
+
+    let parts: Vec<Part> = previous
+        .into_iter()
+        .enumerate()
+        .map(|(i, kept)| {
+            kept.unwrap_or_else(|| Part {
+                at: Instant::now(),
+                generation: generations[i],
+                files: build_part(store, &ctx, i),
+            })
+        })
+        .collect();
+    let files = assemble(&ctx, parts.iter().flat_map(|p| p.files.iter().cloned()));
+    let file = files.get(name).cloned();
+    if let Ok(mut c) = cache().write() {
+        c.insert(
+            ctx.site_url.clone(),
+            Built {
+                config,
+                parts,
+                files,
+            },
+        );
+    }
+    file
+}
+
+/// Generate the sitemap index served at `/sitemap.xml`.
 /// Returns None if seo_sitemap_enabled is false.
 pub fn generate_sitemap(store: &dyn Store) -> Option<String> {
     if !store.setting_get_bool("seo_sitemap_enabled") {
         return None;
     }
+    build_all(store).remove("index")
+}
 
-    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
-    let blog_slug = store.setting_get_or("blog_slug", "journal");
-    let portfolio_slug = store.setting_get_or("portfolio_slug", "portfolio");
+/// Generate one file listed in the index, e.g. "posts-1". Uncached.
+pub fn generate_sitemap_file(store: &dyn Store, name: &str) -> Option<String> {
+    if !store.setting_get_bool("seo_sitemap_enabled") || name == "index" {
+        return None;
+    }
+    build_all(store).remove(name)
+}
 
-    let mut xml = String::from(
-        r#"<?xml version="1.0" encoding="UTF-8"?>
-<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
-"#,
+/// One `<url>` entry; `extra` is any image:/video: markup.
+fn url_entry(loc: &str, lastmod: Option<&str>, priority: &str, extra: &str) -> String {
+    let lastmod = lastmod
+        .map(|d| format!("<lastmod>{}</lastmod>", d))
+        .unwrap_or_default();
+    format!(
+        "  <url><loc>{}</loc>{}<priority>{}</priority>{}</url>\n",
+        html_escape(loc),
+        lastmod,
+        priority,
+        extra
+    )
+}
+
+fn image_entry(loc: &str, title: &str) -> String {
+    format!(
+        "<image:image><image:loc>{}</image:loc><image:title>{}</image:title></image:image>",
+        html_escape(loc),
+        html_escape(title)
+    )
+}
+
+/// A video entry needs a still for `thumbnail_loc`, so a video without an
+/// image thumbnail gets none.
+fn video_entry(content_loc: &str, thumbnail_loc: &str, title: &str, description: &str) -> String {
+    format!(
+        "<video:video><video:thumbnail_loc>{}</video:thumbnail_loc><video:title>{}</video:title><video:description>{}</video:description><video:content_loc>{}</video:content_loc></video:video>",
+        html_escape(thumbnail_loc),
+        html_escape(title),
+        html_escape(description),
+        html_escape(content_loc)
+    )
+}
+
+/// `xhtml:link` alternates for each language version, plus x-default
+fn alternate_entries(
+    alternates: &[(String, String)],
+    site_language: &str,
+    site_url: &str,
+) -> String {
+    let link = |language: &str, path: &str| {
+        format!(
+            "<xhtml:link rel=\"alternate\" hreflang=\"{}\" href=\"{}\"/>",
+            html_escape(language),
+            html_escape(&format!("{}{}", site_url, path))
+        )
+    };
+    let mut out: String = alternates.iter().map(|(l, p)| link(l, p)).collect();
+    if let Some((_, path)) = hreflang::default_alternate(alternates, site_language) {
+        out.push_str(&link("x-default", path));
+    }
+    out
+}
+
+/// Split `entries` (with each one's lastmod) into files named
+/// `<prefix>-1`, `<prefix>-2`, … of at most `MAX_URLS_PER_FILE` URLs.
+fn paginate(files: &mut Vec<File>, prefix: &str, entries: Vec<(String, Option<String>)>) {
+    for (i, chunk) in entries.chunks(MAX_URLS_PER_FILE).enumerate() {
+        let mut xml = String::from(URLSET_OPEN);
+        for (entry, _) in chunk {
+            xml.push_str(entry);
+        }
+        xml.push_str("</urlset>");
+        let lastmod = chunk.iter().filter_map(|(_, m)| m.clone()).max();
+        files.push((format!("{}-{}", prefix, i + 1), xml, lastmod));
+    }
+}
+
+/// Settings every part is built with
+struct Context {
+    site_url: String,
+    blog_slug: String,
+    portfolio_slug: String,
+    site_language: String,
+}
+
+impl Context {
+    fn load(store: &dyn Store) -> Self {
+        Context {
+            site_url: store
+                .setting_get_or("site_url", "http://localhost:8000")
+                .trim_end_matches('/')
+                .to_string(),
+            blog_slug: store.setting_get_or("blog_slug", "journal"),
+            portfolio_slug: store.setting_get_or("portfolio_slug", "portfolio"),
+            site_language: hreflang::site_language(store),
+        }
+    }
+
+    fn config_key(&self) -> String {
+        format!(
+            "{}|{}|{}|{}",
+            self.site_url, self.blog_slug, self.portfolio_slug, self.site_language
+        )
+    }
+
+    fn abs(&self, path: String) -> String {
+        format!("{}{}", self.site_url, path)
+    }
+
+    fn upload(&self, path: &str) -> String {
+        format!("{}/uploads/{}", self.site_url, path)
+    }
+}
+
+/// Build the index and every file it lists.
+fn build_all(store: &dyn Store) -> HashMap<String, String> {
+    let ctx = Context::load(store);
+    let files: Vec<File> = (0..PARTS)
+        .flat_map(|i| build_part(store, &ctx, i))
+        .collect();
+    assemble(&ctx, files.into_iter())
+}
+
+/// The files of one part, by its position in the index
+fn build_part(store: &dyn Store, ctx: &Context, part: usize) -> Vec<File> {
+    let mut files = Vec::new();
+    match part {
+        0 => build_pages(ctx, &mut files),
+        1 => build_posts(store, ctx, &mut files),
+        2 => build_portfolio(store, ctx, &mut files),
+        _ => build_taxonomies(store, ctx, &mut files),
+    }
+    files
+}
+
+/// Homepage and section indexes
+fn build_pages(ctx: &Context, files: &mut Vec<File>) {
+    paginate(
+        files,
+        "pages",
+        vec![
+            (url_entry(&ctx.abs("/".into()), None, "1.0", ""), None),
+            (
+                url_entry(&ctx.abs(slug_url(&ctx.blog_slug, "")), None, "0.8", ""),
+                None,
+            ),
+            (
+                url_entry(&ctx.abs(slug_url(&ctx.portfolio_slug, "")), None, "0.8", ""),
+                None,
+            ),
+        ],
     );
+}
 
-    // Homepage
-    xml.push_str(&format!(
-        "  <url><loc>{}</loc><changefreq>daily</changefreq><priority>1.0</priority></url>\n",
-        site_url
-    ));
-
-    // Blog index
-    xml.push_str(&format!(
-        "  <url><loc>{}/{}</loc><changefreq>daily</changefreq><priority>0.8</priority></url>\n",
-        site_url, blog_slug
-    ));
-
-    // Portfolio index
-    xml.push_str(&format!(
-        "  <url><loc>{}/{}</loc><changefreq>daily</changefreq><priority>0.8</priority></url>\n",
-        site_url, portfolio_slug
-    ));
-
-    // Published posts
-    let posts = store.post_list(Some("published"), 1000, 0);
-    for post in &posts {
-        let lastmod = post.updated_at.format("%Y-%m-%d").to_string();
-        xml.push_str(&format!(
-            "  <url><loc>{}/{}/{}</loc><lastmod>{}</lastmod><priority>0.6</priority></url>\n",
-            site_url, blog_slug, post.slug, lastmod
-        ));
+/// Published posts, with their translations as alternates
+fn build_posts(store: &dyn Store, ctx: &Context, files: &mut Vec<File>) {
+    let mut groups: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    loop {
+        let batch = store.post_list(Some("published"), MAX_URLS_PER_FILE as i64, offset);
+        offset += batch.len() as i64;
+        for post in batch.iter().filter(|p| !p.robots_noindex) {
+            let lastmod = post.updated_at.format("%Y-%m-%d").to_string();
+            let image = post
+                .featured_image
+                .as_deref()
+                .filter(|i| !i.is_empty())
+                .map(|i| image_entry(&ctx.upload(i), &post.title))
+                .unwrap_or_default();
+            let path = slug_url(&ctx.blog_slug, &post.slug);
+            let alternates = post
+                .translation_group
+                .as_deref()
+                .filter(|g| !g.is_empty())
+                .map(|group| {
+                    groups
+                        .entry(group.to_string())
+                        .or_insert_with(|| hreflang::group_alternates(store, group))
+                        .clone()
+                })
+                .unwrap_or_default();
+            let image = if alternates.len() > 1 && alternates.iter().any(|(_, p)| *p == path) {
+                image + &alternate_entries(&alternates, &ctx.site_language, &ctx.site_url)
+            } else {
+                image
+            };
+            entries.push((
+                url_entry(&ctx.abs(path), Some(&lastmod), "0.6", &image),
+                Some(lastmod),
+            ));
+        }
+        if batch.len() < MAX_URLS_PER_FILE {
+            break;
+        }
     }
+    paginate(files, "posts", entries);
+}
 
-    // Published portfolio items
-    let items = store.portfolio_list(Some("published"), 1000, 0);
-    for item in &items {
-        let lastmod = item.updated_at.format("%Y-%m-%d").to_string();
-        xml.push_str(&format!(
-            "  <url><loc>{}/{}/{}</loc><lastmod>{}</lastmod><priority>0.6</priority></url>\n",
-            site_url, portfolio_slug, item.slug, lastmod
-        ));
+/// Published portfolio items, with their image or video
+fn build_portfolio(store: &dyn Store, ctx: &Context, files: &mut Vec<File>) {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    loop {
+        let batch = store.portfolio_list(Some("published"), MAX_URLS_PER_FILE as i64, offset);
+        offset += batch.len() as i64;
+        for item in batch.iter().filter(|i| !i.robots_noindex) {
+            let lastmod = item.updated_at.format("%Y-%m-%d").to_string();
+            let media = if is_video_filename(&item.image_path) {
+                item.thumbnail_path
+                    .as_deref()
+                    .filter(|t| !t.is_empty() && !is_video_filename(t))
+                    .map(|thumb| {
+                        let description = item
+                            .meta_description
+                            .as_deref()
+                            .filter(|d| !d.is_empty())
+                            .unwrap_or(&item.title);
+                        video_entry(
+                            &ctx.upload(&item.image_path),
+                            &ctx.upload(thumb),
+                            &item.title,
+                            description,
+                        )
+                    })
+                    .unwrap_or_default()
+            } else if !item.image_path.is_empty() {
+                image_entry(&ctx.upload(&item.image_path), &item.title)
+            } else {
+                String::new()
+            };
+            entries.push((
+                url_entry(
+                    &ctx.abs(slug_url(&ctx.portfolio_slug, &item.slug)),
+                    Some(&lastmod),
+                    "0.6",
+                    &media,
+                ),
+                Some(lastmod),
+            ));
+        }
+        if batch.len() < MAX_URLS_PER_FILE {
+            break;
+        }
     }
+    paginate(files, "portfolio", entries);
+}
 
-    xml.push_str("</urlset>");
-    Some(xml)
+/// Category and tag pages that have something on them
+fn build_taxonomies(store: &dyn Store, ctx: &Context, files: &mut Vec<File>) {
+    let mut entries = Vec::new();
+    for cat in store.category_list(None) {
+        if store.category_count_items(cat.id) == 0 {
+            continue;
+        }
+        let section = if cat.r#type == "portfolio" {
+            &ctx.portfolio_slug
+        } else {
+            &ctx.blog_slug
+        };
+        let path = slug_url(section, &format!("category/{}", cat.slug));
+        entries.push((url_entry(&ctx.abs(path), None, "0.4", ""), None));
+    }
+    for tag in store.tag_list() {
+        if store.post_count_by_tag(tag.id) > 0 {
+            let path = slug_url(&ctx.blog_slug, &format!("tag/{}", tag.slug));
+            entries.push((url_entry(&ctx.abs(path), None, "0.3", ""), None));
+        }
+        if store.portfolio_count_by_tag(tag.id) > 0 {
+            let path = slug_url(&ctx.portfolio_slug, &format!("tag/{}", tag.slug));
+            entries.push((url_entry(&ctx.abs(path), None, "0.3", ""), None));
+        }
+    }
+    paginate(files, "taxonomies", entries);
 }
 
-/// Generate robots.txt content with dynamic sitemap URL.
-pub fn generate_robots(store: &dyn Store) -> String {
-    let mut content = store.setting_get_or("seo_robots_txt", "User-agent: *\nAllow: /");
-    let site_url = store.setting_get_or("site_url", "http://localhost:8000");
-    if store.setting_get_bool("seo_sitemap_enabled") {
-        content.push_str(&format!("\nSitemap: {}/sitemap.xml", site_url));
+/// The index listing `files`, plus each file by name
+fn assemble(ctx: &Context, files: impl Iterator<Item = File>) -> HashMap<String, String> {
+    let mut index = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+"#,
+    );
+    let mut map = HashMap::new();
+    for (name, xml, lastmod) in files {
+        let lastmod = lastmod
+            .map(|d| format!("<lastmod>{}</lastmod>", d))
+            .unwrap_or_default();
+        index.push_str(&format!(
+            "  <sitemap><loc>{}/sitemaps/{}.xml</loc>{}</sitemap>\n",
+            ctx.site_url, name, lastmod
+        ));
+        map.insert(name, xml);
     }
-    content
+    index.push_str("</sitemapindex>");
+    map.insert("index".to_string(), index);
+    map
 }
@@ -6,12 +6,17 @@ use rocket::http::Status;
 use rocket::request::{FromRequest, Outcome, Request};
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::db::DbPool;
 use crate::store::Store;
 
+pub mod acme;
+pub mod backup;
+pub mod directory;
+pub mod plan;
+pub mod usage;
+
 // ── Registry pool newtype (distinct from DbPool for Rocket managed state) ──
 
 pub struct RegistryPool(pub DbPool);
@@ -44,6 +49,41 @@ pub struct Site {
     pub updated_at: String,
 }
 
+// ── Custom domain mapped to a site ───────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiteDomain {
+    pub id: i64,
+    pub site_id: i64,
+    pub domain: String,
+    /// "none", "pending", "issued" or "failed"
+    pub tls_status: String,
+    pub tls_error: String,
+    /// When the current certificate expires, UTC `YYYY-MM-DD HH:MM:SS`
+    pub tls_expires_at: Option<String>,
+    /// Failed issue attempts since the last success, for renewal backoff
+    pub tls_failures: i64,
+    /// When issuing last started, UTC `YYYY-MM-DD HH:MM:SS`
+    pub tls_attempted_at: Option<String>,
+    pub created_at: String,
+}
+
+impl SiteDomain {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(SiteDomain {
+            id: row.get("id")?,
+            site_id: row.get("site_id")?,
+            domain: row.get("domain")?,
+            tls_status: row.get("tls_status")?,
+            tls_error: row.get("tls_error")?,
+            tls_expires_at: row.get("tls_expires_at")?,
+            tls_failures: row.get("tls_failures")?,
+            tls_attempted_at: row.get("tls_attempted_at")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
 // ── SiteContext: injected per-request via request guard ───────
 
 pub struct SiteContext {
@@ -90,6 +130,11 @@ impl SiteStoreManager {
         self.stores.insert(slug.to_string(), store.clone());
         Ok(store)
     }
+
+    #[cfg(test)]
+    pub fn insert(&self, slug: &str, store: Arc<dyn Store>) {
+        self.stores.insert(slug.to_string(), store);
+    }
 }
 
 /// Create a Store instance for a specific site based on the global backend config.
@@ -221,14 +266,134 @@ pub fn run_registry_migrations(pool: &RegistryPool) -> Result<(), String> {
             password_hash TEXT NOT NULL,
             created_at DATETIME DEFAULT CURRENT_TIMESTAMP
         );
+        CREATE TABLE IF NOT EXISTS site_domains (
+            id INTEGER PRIMARY KEY,
+            site_id INTEGER NOT NULL,
+            domain TEXT UNIQUE NOT NULL,
+            tls_status TEXT NOT NULL DEFAULT 'none',
+            tls_error TEXT NOT NULL DEFAULT '',
+            tls_expires_at TEXT,
+            tls_failures INTEGER NOT NULL DEFAULT 0,
+            tls_attempted_at TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (site_id) REFERENCES sites(id)
+        );
+        CREATE TABLE IF NOT EXISTS acme_challenges (
+            token TEXT PRIMARY KEY,
+            key_authorization TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TABLE IF NOT EXISTS site_backups (
+            id INTEGER PRIMARY KEY,
+            site_id INTEGER NOT NULL,
+            file_name TEXT NOT NULL,
+            size_bytes INTEGER NOT NULL DEFAULT 0,
+            kind TEXT NOT NULL DEFAULT 'manual',
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (site_id) REFERENCES sites(id)
+        );
+        CREATE TABLE IF NOT EXISTS site_backup_schedules (
+            site_id INTEGER PRIMARY KEY,
+            interval_hours INTEGER NOT NULL DEFAULT 0,
+            keep INTEGER NOT NULL DEFAULT 7,
+            last_run_at TEXT,
+            last_error TEXT NOT NULL DEFAULT '',
+            FOREIGN KEY (site_id) REFERENCES sites(id)
+        );
+        CREATE TABLE IF NOT EXISTS plans (
+            id INTEGER PRIMARY KEY,
+            name TEXT UNIQUE NOT NULL,
+            commerce INTEGER NOT NULL DEFAULT 1,
+            ai INTEGER NOT NULL DEFAULT 1,
+            newsletter INTEGER NOT NULL DEFAULT 1,
+            max_users INTEGER NOT NULL DEFAULT 0,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TABLE IF NOT EXISTS site_plans (
+            site_id INTEGER PRIMARY KEY,
+            plan_id INTEGER NOT NULL,
+            FOREIGN KEY (site_id) REFERENCES sites(id),
+            FOREIGN KEY (plan_id) REFERENCES plans(id)
+        );
+        CREATE TABLE IF NOT EXISTS directory_users (
+            id INTEGER PRIMARY KEY,
+            email TEXT UNIQUE NOT NULL COLLATE NOCASE,
+            display_name TEXT NOT NULL DEFAULT '',
+            password_hash TEXT NOT NULL,
+            mfa_secret TEXT NOT NULL DEFAULT '',
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        );
+        CREATE TABLE IF NOT EXISTS directory_grants (
+            id INTEGER PRIMARY KEY,
+            user_id INTEGER NOT NULL,
+            site_id INTEGER NOT NULL,
+            role TEXT NOT NULL,
+            UNIQUE(user_id, site_id),
+            FOREIGN KEY (user_id) REFERENCES directory_users(id),
+            FOREIGN KEY (site_id) REFERENCES sites(id)
+        );
+        CREATE TABLE IF NOT EXISTS directory_sessions (
+            token TEXT PRIMARY KEY,
+            user_id INTEGER NOT NULL,
+            expires_at DATETIME NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES directory_users(id)
+        );
+        CREATE TABLE IF NOT EXISTS directory_tickets (
+            ticket TEXT PRIMARY KEY,
+            session_token TEXT NOT NULL,
+            user_id INTEGER NOT NULL,
+            site_id INTEGER NOT NULL,
+            expires_at DATETIME NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS directory_site_users (
+            user_id INTEGER NOT NULL,
+            site_id INTEGER NOT NULL,
+            site_user_id INTEGER NOT NULL,
+            PRIMARY KEY (user_id, site_id)
+        );
+        CREATE TABLE IF NOT EXISTS directory_site_sessions (
+            session_token TEXT NOT NULL,
+            site_id INTEGER NOT NULL,
+            site_session_id TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_directory_site_sessions_token ON directory_site_sessions(session_token);
         CREATE TABLE IF NOT EXISTS super_sessions (
             token TEXT PRIMARY KEY,
             admin_id INTEGER NOT NULL,
             expires_at DATETIME NOT NULL,
             FOREIGN KEY (admin_id) REFERENCES super_admins(id)
-        );",
+        );
+        CREATE TABLE IF NOT EXISTS rate_limit_hits (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            key TEXT NOT NULL,
+            hit_at INTEGER NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_rate_limit_hits_key ON rate_limit_hits(key, hit_at);",
     )
     .map_err(|e| e.to_string())?;
+
+    // Renewal backoff columns for registries created before them
+    if conn
+        .prepare("SELECT tls_failures FROM site_domains LIMIT 0")
+        .is_err()
+    {
+        conn.execute_batch(
+            "ALTER TABLE site_domains ADD COLUMN tls_failures INTEGER NOT NULL DEFAULT 0;
+             ALTER TABLE site_domains ADD COLUMN tls_attempted_at TEXT;",
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    // Directory second factor for registries created before it
+    if conn
+        .prepare("SELECT mfa_secret FROM directory_users LIMIT 0")
+        .is_err()
+    {
+        conn.execute_batch(
+            "ALTER TABLE directory_users ADD COLUMN mfa_secret TEXT NOT NULL DEFAULT '';",
+        )
+        .map_err(|e| e.to_string())?;
+    }
     Ok(())
 }
 
@@ -276,6 +441,23 @@ pub fn find_site_by_hostname(pool: &RegistryPool, hostname: &str) -> Option<Site
     .ok()
 }
 
+/// The site a request's host belongs to: its primary hostname first, then
+/// the custom domains mapped to it
+pub fn find_site_for_host(pool: &RegistryPool, host: &str) -> Option<Site> {
+    if let Some(site) = find_site_by_hostname(pool, host) {
+        return Some(site);
+    }
+    let conn = pool.get().ok()?;
+    let site_id: i64 = conn
+        .query_row(
+            "SELECT site_id FROM site_domains WHERE domain = ?1",
+            params![host.to_ascii_lowercase()],
+            |row| row.get(0),
+        )
+        .ok()?;
+    find_site_by_id(pool, site_id)
+}
+
 pub fn find_site_by_id(pool: &RegistryPool, id: i64) -> Option<Site> {
     let conn = pool.get().ok()?;
     conn.query_row("SELECT * FROM sites WHERE id = ?1", params![id], |row| {
@@ -298,7 +480,7 @@ pub fn create_site(
     hostname: &str,
     display_name: &str,
     admin_email: &str,
-    email_settings: &HashMap<String, String>,
+    email_store: Option<&dyn Store>,
 ) -> Result<(Site, String, bool), String> {
     // Use a random UUID as the folder name so the filesystem
     // doesn't reveal which database belongs to which site.
@@ -334,7 +516,7 @@ pub fn create_site(
 
     // Generate a random temporary password and create the site admin user
     let temp_password = generate_temp_password();
-    let hash = crate::security::auth::hash_password(&temp_password)
+    let hash = crate::security::auth::hash_password_for(&*store, &temp_password)
         .map_err(|e| format!("Failed to hash temp password: {}", e))?;
     let user_id = store
         .user_create(admin_email, &hash, display_name, "admin")
@@ -342,7 +524,7 @@ pub fn create_site(
     let _ = store.user_set_force_password_change(user_id, true);
 
     // Send welcome email with temporary password (uses super admin's email settings)
-    let email_sent = send_site_welcome_email(email_settings, hostname, admin_email, &temp_password);
+    let email_sent = send_site_welcome_email(email_store, hostname, admin_email, &temp_password);
 
     Ok((
         Site {
@@ -373,15 +555,20 @@ fn generate_temp_password() -> String {
 }
 
 /// Send a welcome email to the new site admin with their temporary password.
-/// Uses the super admin's email settings since the sub-site has no email configured yet.
+/// Goes through `email_store`'s providers, routing rules and quotas, since the
+/// sub-site has no email configured yet; without one nothing is sent.
 /// Returns true if the email was sent successfully.
 fn send_site_welcome_email(
-    settings: &HashMap<String, String>,
+    email_store: Option<&dyn Store>,
     hostname: &str,
     admin_email: &str,
     temp_password: &str,
 ) -> bool {
-    let from = crate::email::get_from_or_admin(settings);
+    let store = match email_store {
+        Some(s) => s,
+        None => return false,
+    };
+    let from = crate::email::get_from_or_admin(&store.setting_all());
     let subject = format!("Your new site on {} is ready", hostname);
     let body = format!(
         "Hello,\n\n\
@@ -395,7 +582,14 @@ fn send_site_welcome_email(
         email = admin_email,
         password = temp_password,
     );
-    match crate::email::send_via_provider(settings, &from, admin_email, &subject, &body) {
+    match crate::email::send_via_configured_provider(
+        store,
+        "transactional",
+        &from,
+        admin_email,
+        &subject,
+        &body,
+    ) {
         Ok(_) => true,
         Err(e) => {
             log::warn!(
@@ -409,6 +603,200 @@ fn send_site_welcome_email(
     }
 }
 
+// ── Cloning ──────────────────────────────────────────────────
+
+/// Activity, security and delivery state that belongs to the running
+/// site, never carried into a clone
+const CLONE_CLEARED_TABLES: &[&str] = &[
+    "sessions",
+    "page_views",
+    "goal_completions",
+    "outbound_clicks",
+    "search_log",
+    "experiment_events",
+    "engagement_pings",
+    "magic_links",
+    "fw_bans",
+    "fw_events",
+    "fw_offenses",
+    "not_found_log",
+    "ping_log",
+    "audit_log",
+    "email_queue",
+    "mta_reply_tokens",
+    "email_quota_usage",
+    "signed_tokens",
+    "rate_limit_hits",
+    "login_devices",
+    "ai_usage",
+    // Passkeys are bound to the source hostname
+    "user_passkeys",
+];
+
+/// Content, and the people and sales attached to it, copied only when
+/// the clone includes content. Referencing tables come first.
+const CLONE_CONTENT_TABLES: &[&str] = &[
+    "content_categories",
+    "content_tags",
+    "comment_subscriptions",
+    "comments",
+    "likes",
+    "download_tokens",
+    "licenses",
+    "orders",
+    "seo_suggestions",
+    "embeddings",
+    "posts",
+    "portfolio",
+    "categories",
+    "tags",
+    "imports",
+    "newsletter_subscribers",
+    "users",
+];
+
+/// Empty the tables a cloned database shouldn't keep. Settings, designs,
+/// widgets, roles, redirects and firewall rules always stay.
+pub fn strip_cloned_db(conn: &rusqlite::Connection, include_content: bool) -> Result<(), String> {
+    let mut tables: Vec<&str> = CLONE_CLEARED_TABLES.to_vec();
+    if !include_content {
+        tables.extend_from_slice(CLONE_CONTENT_TABLES);
+    }
+    for table in tables {
+        let exists: bool = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                params![table],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|n| n > 0)
+            .unwrap_or(false);
+        if exists {
+            conn.execute(&format!("DELETE FROM {}", table), [])
+                .map_err(|e| format!("Couldn't clear {}: {}", table, e))?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_dir(src: &std::path::Path, dst: &std::path::Path) -> Result<(), String> {
+    std::fs::create_dir_all(dst).map_err(|e| e.to_string())?;
+    let entries = match std::fs::read_dir(src) {
+        Ok(e) => e,
+        Err(_) => return Ok(()), // nothing to copy
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let target = dst.join(entry.file_name());
+        if path.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            std::fs::copy(&path, &target).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// Clone a site into a new one on `hostname`: its settings, designs and
+/// uploads, plus its content and user accounts when `include_content` is
+/// set. `admin_email` gets a new admin account with a temporary password
+/// unless it's already a user of the clone.
+/// Returns (Site, temp_password, email_sent). SQLite backend only.
+pub fn clone_site(
+    pool: &RegistryPool,
+    source_id: i64,
+    hostname: &str,
+    display_name: &str,
+    admin_email: &str,
+    include_content: bool,
+    email_store: Option<&dyn Store>,
+) -> Result<(Site, Option<String>, bool), String> {
+    if crate::health::read_db_backend() == "mongodb" {
+        return Err("Cloning is only available with the SQLite backend.".into());
+    }
+    let source = find_site_by_id(pool, source_id).ok_or("Site not found")?;
+    if find_site_for_host(pool, hostname).is_some() {
+        return Err(format!("{} is already in use", hostname));
+    }
+
+    let slug = uuid::Uuid::new_v4().to_string();
+    let src_base = format!("website/sites/{}", source.slug);
+    let base = format!("website/sites/{}", slug);
+    let copy = || -> Result<(), String> {
+        std::fs::create_dir_all(format!("{}/db", base)).map_err(|e| e.to_string())?;
+        // VACUUM INTO takes a consistent snapshot while the source is live
+        let src_conn = rusqlite::Connection::open(format!("{}/db/velocty.db", src_base))
+            .map_err(|e| e.to_string())?;
+        src_conn
+            .execute("VACUUM INTO ?1", params![format!("{}/db/velocty.db", base)])
+            .map_err(|e| format!("Couldn't copy the database: {}", e))?;
+        let conn = rusqlite::Connection::open(format!("{}/db/velocty.db", base))
+            .map_err(|e| e.to_string())?;
+        strip_cloned_db(&conn, include_content)?;
+        for dir in ["uploads", "designs"] {
+            copy_dir(
+                std::path::Path::new(&format!("{}/{}", src_base, dir)),
+                std::path::Path::new(&format!("{}/{}", base, dir)),
+            )?;
+        }
+        Ok(())
+    };
+    if let Err(e) = copy() {
+        let _ = std::fs::remove_dir_all(&base);
+        return Err(e);
+    }
+
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    if let Err(e) = conn.execute(
+        "INSERT INTO sites (slug, hostname, display_name) VALUES (?1, ?2, ?3)",
+        params![slug, hostname, display_name],
+    ) {
+        let _ = std::fs::remove_dir_all(&base);
+        return Err(e.to_string());
+    }
+    let id = conn.last_insert_rowid();
+
+    let store = create_site_store(&slug)?;
+    let _ = store.setting_set("site_url", &format!("https://{}", hostname));
+    let _ = store.setting_set("site_name", display_name);
+    let _ = store.setting_set("admin_email", admin_email);
+
+    let mut temp_password = None;
+    let mut email_sent = false;
+    if store.user_get_by_email(admin_email).is_none() {
+        let password = generate_temp_password();
+        let hash = crate::security::auth::hash_password_for(&*store, &password)
+            .map_err(|e| format!("Failed to hash temp password: {}", e))?;
+        let user_id = store
+            .user_create(admin_email, &hash, display_name, "admin")
+            .map_err(|e| format!("Failed to create site admin: {}", e))?;
+        let _ = store.user_set_force_password_change(user_id, true);
+        email_sent = send_site_welcome_email(email_store, hostname, admin_email, &password);
+        temp_password = Some(password);
+    }
+
+    log::info!(
+        "Cloned site '{}' to '{}' (slug: {}, content: {})",
+        source.hostname,
+        hostname,
+        slug,
+        include_content
+    );
+    Ok((
+        Site {
+            id,
+            slug,
+            hostname: hostname.to_string(),
+            display_name: display_name.to_string(),
+            status: "active".to_string(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        },
+        temp_password,
+        email_sent,
+    ))
+}
+
 pub fn update_site_status(pool: &RegistryPool, id: i64, status: &str) -> Result<(), String> {
     let conn = pool.get().map_err(|e| e.to_string())?;
     conn.execute(
@@ -422,6 +810,11 @@ pub fn update_site_status(pool: &RegistryPool, id: i64, status: &str) -> Result<
 pub fn delete_site(pool: &RegistryPool, id: i64) -> Result<(), String> {
     let site = find_site_by_id(pool, id).ok_or("Site not found")?;
     let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM site_domains WHERE site_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    directory::revoke_site(pool, id)?;
+    plan::assign_plan(pool, id, None)?;
+    backup::delete_all(pool, &site)?;
     conn.execute("DELETE FROM sites WHERE id = ?1", params![id])
         .map_err(|e| e.to_string())?;
 
@@ -431,6 +824,114 @@ pub fn delete_site(pool: &RegistryPool, id: i64) -> Result<(), String> {
     Ok(())
 }
 
+// ── Custom domains ───────────────────────────────────────────
+
+/// Lowercase a domain and check it's a plain hostname: letters, digits and
+/// hyphens in dot-separated labels, no scheme, port, path or wildcard
+pub fn normalize_domain(raw: &str) -> Result<String, String> {
+    let domain = raw.trim().trim_end_matches('.').to_ascii_lowercase();
+    let valid = !domain.is_empty()
+        && domain.len() <= 253
+        && domain.contains('.')
+        && domain.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    if valid {
+        Ok(domain)
+    } else {
+        Err(format!("\"{}\" is not a valid domain name", raw.trim()))
+    }
+}
+
+pub fn list_domains(pool: &RegistryPool, site_id: i64) -> Vec<SiteDomain> {
+    let conn = match pool.get() {
+        Ok(c) => c,
+        Err(_) => return vec![],
+    };
+    let mut stmt =
+        match conn.prepare("SELECT * FROM site_domains WHERE site_id = ?1 ORDER BY domain") {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+    stmt.query_map(params![site_id], SiteDomain::from_row)
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+}
+
+/// Every mapped domain, for certificate renewal
+pub fn list_all_domains(pool: &RegistryPool) -> Vec<SiteDomain> {
+    let conn = match pool.get() {
+        Ok(c) => c,
+        Err(_) => return vec![],
+    };
+    let mut stmt = match conn.prepare("SELECT * FROM site_domains ORDER BY domain") {
+        Ok(s) => s,
+        Err(_) => return vec![],
+    };
+    stmt.query_map([], SiteDomain::from_row)
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+}
+
+pub fn find_domain(pool: &RegistryPool, id: i64) -> Option<SiteDomain> {
+    let conn = pool.get().ok()?;
+    conn.query_row(
+        "SELECT * FROM site_domains WHERE id = ?1",
+        params![id],
+        SiteDomain::from_row,
+    )
+    .ok()
+}
+
+/// Map `raw` to a site. A domain can belong to one site only and can't be
+/// another site's primary hostname.
+pub fn add_domain(pool: &RegistryPool, site_id: i64, raw: &str) -> Result<SiteDomain, String> {
+    let domain = normalize_domain(raw)?;
+    if find_site_for_host(pool, &domain).is_some() {
+        return Err(format!("{} is already used by a site", domain));
+    }
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO site_domains (site_id, domain) VALUES (?1, ?2)",
+        params![site_id, domain],
+    )
+    .map_err(|e| e.to_string())?;
+    find_domain(pool, conn.last_insert_rowid()).ok_or_else(|| "Domain not saved".to_string())
+}
+
+pub fn remove_domain(pool: &RegistryPool, id: i64) -> Result<(), String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM site_domains WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn set_domain_tls(
+    pool: &RegistryPool,
+    domain: &str,
+    status: &str,
+    error: &str,
+    expires_at: Option<&str>,
+) -> Result<(), String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    conn.execute(
+        "UPDATE site_domains SET tls_status = ?1, tls_error = ?2,
+            tls_expires_at = COALESCE(?3, tls_expires_at),
+            tls_failures = CASE ?1 WHEN 'failed' THEN tls_failures + 1
+                WHEN 'issued' THEN 0 ELSE tls_failures END,
+            tls_attempted_at = CASE ?1 WHEN 'pending' THEN ?5 ELSE tls_attempted_at END
+         WHERE domain = ?4",
+        params![status, error, expires_at, domain, now],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
 // ── Super Admin auth helpers ─────────────────────────────────
 
 pub fn super_admin_exists(pool: &RegistryPool) -> bool {
@@ -551,7 +1052,7 @@ impl Fairing for SiteResolver {
             .unwrap_or("localhost")
             .to_string();
 
-        let site = match find_site_by_hostname(registry, &hostname) {
+        let site = match find_site_for_host(registry, &hostname) {
             Some(s) => s,
             None => return,
         };
@@ -570,6 +1071,24 @@ impl Fairing for SiteResolver {
             Err(_) => return,
         };
 
+        // Requests for features outside the site's plan are answered by
+        // the plan notice route instead of their own
+        if let Some(p) = plan::site_plan(registry, site.id) {
+            let path = req.uri().path().as_str().to_string();
+            if let Some(feature) = plan::blocked_feature(&p, req.method(), &path, &*store) {
+                let notice = format!(
+                    "/super/plan/unavailable/{}?api={}",
+                    feature,
+                    path.contains("/api/")
+                );
+                if let Ok(origin) = rocket::http::uri::Origin::parse_owned(notice) {
+                    req.set_method(rocket::http::Method::Get);
+                    req.set_uri(origin);
+                }
+                return;
+            }
+        }
+
         let uploads_dir = format!("website/sites/{}/uploads", site.slug);
 
         let ctx = SiteContext {
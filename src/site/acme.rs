@@ -0,0 +1,519 @@
+//! Automatic TLS for mapped domains: certificates are requested from an
+//! ACME CA (Let's Encrypt by default) with HTTP-01 challenges and renewed
+//! when they get close to expiry.
+//!
+//! Only runs when velocty.toml enables it:
+//!
+//! ```toml
+//! [tls]
+//! acme = true
+//! email = "ops@example.com"
+//! # directory = "https://acme-staging-v02.api.letsencrypt.org/directory"
+//! ```
+//!
+//! Certificates are written to `website/certs/<domain>/fullchain.pem` and
+//! `privkey.pem` (mode 0600). Velocty's own listener doesn't serve them;
+//! the TLS terminator in front of it (nginx, HAProxy, …) loads them from
+//! there and is reloaded after a renewal. A proxy that issues its own
+//! certificates doesn't need this at all.
+
+use std::path::Path;
+use std::time::Duration;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private};
+use openssl::rsa::Rsa;
+use openssl::sign::Signer;
+use rusqlite::params;
+use serde_json::{json, Value};
+
+use super::RegistryPool;
+
+pub const CERTS_DIR: &str = "website/certs";
+const LETS_ENCRYPT: &str = "https://acme-v02.api.letsencrypt.org/directory";
+
+/// Certificates are renewed this many days before they expire
+pub const RENEW_BEFORE_DAYS: i64 = 30;
+
+/// How often the renewal check runs
+const RENEW_CHECK_INTERVAL: Duration = Duration::from_secs(12 * 60 * 60);
+
+/// After a failed attempt the next automatic one waits 12 hours, doubling
+/// with each further failure up to this many hours
+const MAX_RETRY_HOURS: i64 = 7 * 24;
+
+/// Challenges and orders are polled this many times, a second apart
+const POLL_ATTEMPTS: usize = 30;
+
+pub struct AcmeConfig {
+    pub enabled: bool,
+    pub email: String,
+    pub directory: String,
+}
+
+/// The `[tls]` table of velocty.toml
+pub fn config() -> AcmeConfig {
+    let toml_val: toml::Value = std::fs::read_to_string("velocty.toml")
+        .unwrap_or_default()
+        .parse()
+        .unwrap_or(toml::Value::Table(Default::default()));
+    let tls = toml_val.get("tls");
+    let get = |key: &str| tls.and_then(|t| t.get(key));
+    AcmeConfig {
+        enabled: get("acme").and_then(|v| v.as_bool()).unwrap_or(false),
+        email: get("email")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        directory: get("directory")
+            .and_then(|v| v.as_str())
+            .unwrap_or(LETS_ENCRYPT)
+            .to_string(),
+    }
+}
+
+// ── HTTP-01 challenge responses ──────────────────────────────
+
+/// Key authorization to serve at `/.well-known/acme-challenge/<token>`
+pub fn challenge_response(pool: &RegistryPool, token: &str) -> Option<String> {
+    let conn = pool.get().ok()?;
+    conn.query_row(
+        "SELECT key_authorization FROM acme_challenges WHERE token = ?1",
+        params![token],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+fn challenge_set(pool: &RegistryPool, token: &str, key_authorization: &str) -> Result<(), String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO acme_challenges (token, key_authorization) VALUES (?1, ?2)",
+        params![token, key_authorization],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn challenge_clear(pool: &RegistryPool, token: &str) {
+    if let Ok(conn) = pool.get() {
+        let _ = conn.execute(
+            "DELETE FROM acme_challenges WHERE token = ?1",
+            params![token],
+        );
+    }
+}
+
+// ── ACME client (RFC 8555) ───────────────────────────────────
+
+fn b64(data: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(data)
+}
+
+struct Client {
+    http: reqwest::blocking::Client,
+    key: PKey<Private>,
+    directory: Value,
+    nonce: Option<String>,
+    /// Account URL, once registered
+    kid: Option<String>,
+}
+
+struct Response {
+    body: Value,
+    text: String,
+    location: Option<String>,
+}
+
+impl Client {
+    fn new(cfg: &AcmeConfig) -> Result<Self, String> {
+        let http = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .user_agent(concat!("Velocty/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| e.to_string())?;
+        let directory: Value = http
+            .get(&cfg.directory)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json())
+            .map_err(|e| format!("Couldn't reach the ACME directory: {}", e))?;
+        Ok(Client {
+            http,
+            key: account_key()?,
+            directory,
+            nonce: None,
+            kid: None,
+        })
+    }
+
+    fn endpoint(&self, name: &str) -> Result<String, String> {
+        self.directory
+            .get(name)
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| format!("ACME directory has no {}", name))
+    }
+
+    fn jwk(&self) -> Result<Value, String> {
+        let rsa = self.key.rsa().map_err(|e| e.to_string())?;
+        Ok(json!({
+            "e": b64(&rsa.e().to_vec()),
+            "kty": "RSA",
+            "n": b64(&rsa.n().to_vec()),
+        }))
+    }
+
+    /// RFC 7638 thumbprint of the account key
+    fn thumbprint(&self) -> Result<String, String> {
+        let jwk = self.jwk()?;
+        // Members in lexicographic order, no whitespace
+        let canonical = format!(
+            r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#,
+            jwk["e"].as_str().unwrap_or(""),
+            jwk["n"].as_str().unwrap_or("")
+        );
+        let digest = openssl::sha::sha256(canonical.as_bytes());
+        Ok(b64(&digest))
+    }
+
+    fn fresh_nonce(&mut self) -> Result<String, String> {
+        if let Some(n) = self.nonce.take() {
+            return Ok(n);
+        }
+        let resp = self
+            .http
+            .head(self.endpoint("newNonce")?)
+            .send()
+            .map_err(|e| e.to_string())?;
+        header(&resp, "Replay-Nonce").ok_or_else(|| "ACME server sent no nonce".to_string())
+    }
+
+    /// Signed POST; `payload` None is a POST-as-GET
+    fn post(&mut self, url: &str, payload: Option<&Value>) -> Result<Response, String> {
+        let mut retried = false;
+        loop {
+            let mut protected = json!({
+                "alg": "RS256",
+                "nonce": self.fresh_nonce()?,
+                "url": url,
+            });
+            match &self.kid {
+                Some(kid) => protected["kid"] = json!(kid),
+                None => protected["jwk"] = self.jwk()?,
+            }
+            let protected = b64(protected.to_string().as_bytes());
+            let payload = payload
+                .map(|p| b64(p.to_string().as_bytes()))
+                .unwrap_or_default();
+            let mut signer =
+                Signer::new(MessageDigest::sha256(), &self.key).map_err(|e| e.to_string())?;
+            signer
+                .update(format!("{}.{}", protected, payload).as_bytes())
+                .map_err(|e| e.to_string())?;
+            let signature = b64(&signer.sign_to_vec().map_err(|e| e.to_string())?);
+
+            let resp = self
+                .http
+                .post(url)
+                .header("Content-Type", "application/jose+json")
+                .body(
+                    json!({ "protected": protected, "payload": payload, "signature": signature })
+                        .to_string(),
+                )
+                .send()
+                .map_err(|e| e.to_string())?;
+            self.nonce = header(&resp, "Replay-Nonce");
+            let location = header(&resp, "Location");
+            let status = resp.status();
+            let text = resp.text().map_err(|e| e.to_string())?;
+            let body: Value = serde_json::from_str(&text).unwrap_or(Value::Null);
+            if status.is_success() {
+                return Ok(Response {
+                    body,
+                    text,
+                    location,
+                });
+            }
+            let kind = body.get("type").and_then(|v| v.as_str()).unwrap_or("");
+            if kind.ends_with(":badNonce") && !retried {
+                retried = true;
+                continue;
+            }
+            let detail = body.get("detail").and_then(|v| v.as_str()).unwrap_or(&text);
+            return Err(format!("ACME error ({}): {}", status.as_u16(), detail));
+        }
+    }
+
+    fn register(&mut self, email: &str) -> Result<(), String> {
+        let mut payload = json!({ "termsOfServiceAgreed": true });
+        if !email.is_empty() {
+            payload["contact"] = json!([format!("mailto:{}", email)]);
+        }
+        let url = self.endpoint("newAccount")?;
+        let resp = self.post(&url, Some(&payload))?;
+        self.kid = Some(
+            resp.location
+                .ok_or("ACME server didn't return an account URL")?,
+        );
+        Ok(())
+    }
+
+    /// POST-as-GET `url` until its status leaves "pending"/"processing"
+    fn poll(&mut self, url: &str) -> Result<Value, String> {
+        for _ in 0..POLL_ATTEMPTS {
+            let body = self.post(url, None)?.body;
+            match body.get("status").and_then(|v| v.as_str()) {
+                Some("pending") | Some("processing") => std::thread::sleep(Duration::from_secs(1)),
+                _ => return Ok(body),
+            }
+        }
+        Err("Timed out waiting for the ACME server".into())
+    }
+}
+
+fn header(resp: &reqwest::blocking::Response, name: &str) -> Option<String> {
+    resp.headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+/// The ACME account key, created on first use
+fn account_key() -> Result<PKey<Private>, String> {
+    let path = Path::new(CERTS_DIR).join("account.pem");
+    if let Ok(pem) = std::fs::read(&path) {
+        return PKey::private_key_from_pem(&pem).map_err(|e| e.to_string());
+    }
+    let key = PKey::from_rsa(Rsa::generate(2048).map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(CERTS_DIR).map_err(|e| e.to_string())?;
+    write_private(
+        &path,
+        &key.private_key_to_pem_pkcs8().map_err(|e| e.to_string())?,
+    )?;
+    Ok(key)
+}
+
+/// Write a private key readable by the owner only. The mode is set at
+/// creation, so the key is never briefly world-readable.
+fn write_private(path: &Path, data: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+    let mut opts = std::fs::OpenOptions::new();
+    opts.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        opts.mode(0o600);
+        // An existing file keeps its old mode on open
+        if path.exists() {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    opts.open(path)
+        .and_then(|mut f| f.write_all(data))
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+/// DER certificate signing request for `domain`
+fn csr(domain: &str, key: &PKey<Private>) -> Result<Vec<u8>, String> {
+    use openssl::stack::Stack;
+    use openssl::x509::extension::SubjectAlternativeName;
+    use openssl::x509::{X509NameBuilder, X509ReqBuilder};
+
+    let mut name = X509NameBuilder::new().map_err(|e| e.to_string())?;
+    name.append_entry_by_text("CN", domain)
+        .map_err(|e| e.to_string())?;
+    let mut req = X509ReqBuilder::new().map_err(|e| e.to_string())?;
+    req.set_subject_name(&name.build())
+        .map_err(|e| e.to_string())?;
+    req.set_pubkey(key).map_err(|e| e.to_string())?;
+    let san = SubjectAlternativeName::new()
+        .dns(domain)
+        .build(&req.x509v3_context(None))
+        .map_err(|e| e.to_string())?;
+    let mut extensions = Stack::new().map_err(|e| e.to_string())?;
+    extensions.push(san).map_err(|e| e.to_string())?;
+    req.add_extensions(&extensions).map_err(|e| e.to_string())?;
+    req.sign(key, MessageDigest::sha256())
+        .map_err(|e| e.to_string())?;
+    req.build().to_der().map_err(|e| e.to_string())
+}
+
+/// Expiry of the first certificate in a PEM chain, UTC
+/// `YYYY-MM-DD HH:MM:SS`
+pub fn not_after(pem: &[u8]) -> Option<String> {
+    let cert = openssl::x509::X509::from_pem(pem).ok()?;
+    // OpenSSL prints e.g. "Mar  1 12:00:00 2026 GMT"
+    let printed = cert.not_after().to_string();
+    let parsed = chrono::NaiveDateTime::parse_from_str(
+        &printed.split_whitespace().collect::<Vec<_>>().join(" "),
+        "%b %d %H:%M:%S %Y GMT",
+    )
+    .ok()?;
+    Some(parsed.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// Whether a certificate expiring at `expires_at` should be renewed now
+pub fn renewal_due(expires_at: Option<&str>, now: chrono::NaiveDateTime) -> bool {
+    match expires_at
+        .and_then(|s| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").ok())
+    {
+        Some(exp) => exp - now < chrono::Duration::days(RENEW_BEFORE_DAYS),
+        None => true,
+    }
+}
+
+/// Whether the renewal loop may try a domain again after `failures` failed
+/// attempts, the last one started at `attempted_at`
+pub fn retry_due(failures: i64, attempted_at: Option<&str>, now: chrono::NaiveDateTime) -> bool {
+    if failures <= 0 {
+        return true;
+    }
+    let hours = (12i64 << (failures - 1).min(8)).min(MAX_RETRY_HOURS);
+    match attempted_at
+        .and_then(|s| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").ok())
+    {
+        Some(at) => now - at >= chrono::Duration::hours(hours),
+        None => true,
+    }
+}
+
+/// Request a certificate for `domain` and write it to `CERTS_DIR`.
+/// Returns the new certificate's expiry.
+pub fn issue(pool: &RegistryPool, cfg: &AcmeConfig, domain: &str) -> Result<String, String> {
+    let mut client = Client::new(cfg)?;
+    client.register(&cfg.email)?;
+
+    let new_order = client.endpoint("newOrder")?;
+    let order = client.post(
+        &new_order,
+        Some(&json!({ "identifiers": [{ "type": "dns", "value": domain }] })),
+    )?;
+    let order_url = order
+        .location
+        .ok_or("ACME server didn't return an order URL")?;
+    let authorizations: Vec<String> = order.body["authorizations"]
+        .as_array()
+        .map(|a| {
+            a.iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    let finalize = order.body["finalize"]
+        .as_str()
+        .ok_or("ACME order has no finalize URL")?
+        .to_string();
+
+    let thumbprint = client.thumbprint()?;
+    for authz_url in authorizations {
+        let authz = client.post(&authz_url, None)?.body;
+        if authz["status"] == "valid" {
+            continue;
+        }
+        let challenge = authz["challenges"]
+            .as_array()
+            .and_then(|c| c.iter().find(|c| c["type"] == "http-01"))
+            .ok_or("The CA offered no HTTP-01 challenge")?;
+        let token = challenge["token"]
+            .as_str()
+            .ok_or("Challenge has no token")?;
+        let url = challenge["url"].as_str().ok_or("Challenge has no URL")?;
+        challenge_set(pool, token, &format!("{}.{}", token, thumbprint))?;
+        let result = client
+            .post(url, Some(&json!({})))
+            .and_then(|_| client.poll(&authz_url));
+        challenge_clear(pool, token);
+        let authz = result?;
+        if authz["status"] != "valid" {
+            let detail = authz["challenges"]
+                .as_array()
+                .and_then(|c| c.iter().find_map(|c| c["error"]["detail"].as_str()))
+                .unwrap_or("the CA couldn't reach this server on port 80");
+            return Err(format!("Validation failed: {}", detail));
+        }
+    }
+
+    let key = PKey::from_rsa(Rsa::generate(2048).map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?;
+    client.post(&finalize, Some(&json!({ "csr": b64(&csr(domain, &key)?) })))?;
+    let order = client.poll(&order_url)?;
+    if order["status"] != "valid" {
+        return Err(format!(
+            "Order ended as {}",
+            order["status"].as_str().unwrap_or("unknown")
+        ));
+    }
+    let cert_url = order["certificate"]
+        .as_str()
+        .ok_or("ACME order has no certificate")?;
+    let chain = client.post(cert_url, None)?.text;
+    let expires = not_after(chain.as_bytes()).ok_or("The CA returned an unreadable certificate")?;
+
+    let dir = Path::new(CERTS_DIR).join(domain);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    std::fs::write(dir.join("fullchain.pem"), chain).map_err(|e| e.to_string())?;
+    write_private(
+        &dir.join("privkey.pem"),
+        &key.private_key_to_pem_pkcs8().map_err(|e| e.to_string())?,
+    )?;
+    log::info!(
+        "[acme] Certificate issued for {} (expires {})",
+        domain,
+        expires
+    );
+    Ok(expires)
+}
+
+/// Issue a certificate for `domain` and record the outcome on the domain
+fn issue_and_record(pool: &RegistryPool, cfg: &AcmeConfig, domain: &str) {
+    let _ = super::set_domain_tls(pool, domain, "pending", "", None);
+    match issue(pool, cfg, domain) {
+        Ok(expires) => {
+            let _ = super::set_domain_tls(pool, domain, "issued", "", Some(&expires));
+        }
+        Err(e) => {
+            log::warn!("[acme] Certificate for {} failed: {}", domain, e);
+            let _ = super::set_domain_tls(pool, domain, "failed", &e, None);
+        }
+    }
+}
+
+/// Request a certificate for `domain` on a background thread. Does nothing
+/// unless ACME is enabled in velocty.toml.
+pub fn issue_in_background(pool: &RegistryPool, domain: &str) {
+    let cfg = config();
+    if !cfg.enabled {
+        return;
+    }
+    let pool = RegistryPool(pool.0.clone());
+    let domain = domain.to_string();
+    std::thread::spawn(move || issue_and_record(&pool, &cfg, &domain));
+}
+
+/// Renew every certificate that is missing or close to expiry, checking
+/// again every twelve hours. Domains whose last attempts failed back off
+/// (see `retry_due`); issuing from the super admin page always runs.
+pub fn spawn_renewal(pool: &RegistryPool) {
+    if !config().enabled {
+        return;
+    }
+    let pool = RegistryPool(pool.0.clone());
+    std::thread::spawn(move || loop {
+        let cfg = config();
+        if cfg.enabled {
+            let now = chrono::Utc::now().naive_utc();
+            for d in super::list_all_domains(&pool) {
+                if renewal_due(d.tls_expires_at.as_deref(), now)
+                    && retry_due(d.tls_failures, d.tls_attempted_at.as_deref(), now)
+                {
+                    issue_and_record(&pool, &cfg, &d.domain);
+                }
+            }
+        }
+        std::thread::sleep(RENEW_CHECK_INTERVAL);
+    });
+}
@@ -0,0 +1,593 @@
+//! Per-site backups: a ZIP of the site's database, uploads and designs,
+//! kept under `website/backups/<slug>/` and recorded in the registry. Each
+//! site can also have a schedule, run by a background thread. Archives are
+//! written and read straight from disk, and restores run in the background.
+//!
+//! SQLite backend only; with MongoDB use the database's own tooling.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use rusqlite::params;
+use serde::Serialize;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use super::{RegistryPool, Site};
+
+pub const BACKUPS_DIR: &str = "website/backups";
+
+/// How often the scheduler looks for due backups
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SiteBackup {
+    pub id: i64,
+    pub site_id: i64,
+    pub file_name: String,
+    pub size_bytes: i64,
+    /// "manual", "scheduled" or "pre-restore"
+    pub kind: String,
+    pub created_at: String,
+}
+
+impl SiteBackup {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(SiteBackup {
+            id: row.get("id")?,
+            site_id: row.get("site_id")?,
+            file_name: row.get("file_name")?,
+            size_bytes: row.get("size_bytes")?,
+            kind: row.get("kind")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Schedule {
+    pub site_id: i64,
+    /// 0 turns scheduled backups off
+    pub interval_hours: i64,
+    /// Scheduled backups to keep; manual ones are never pruned
+    pub keep: i64,
+    pub last_run_at: Option<String>,
+    pub last_error: String,
+}
+
+fn site_dir(site: &Site) -> PathBuf {
+    Path::new(BACKUPS_DIR).join(&site.slug)
+}
+
+fn site_db_path(site: &Site) -> String {
+    format!("website/sites/{}/db/velocty.db", site.slug)
+}
+
+fn sqlite_only() -> Result<(), String> {
+    if crate::health::read_db_backend() == "mongodb" {
+        return Err("Site backups are only available with the SQLite backend.".into());
+    }
+    Ok(())
+}
+
+// ── Records ──────────────────────────────────────────────────
+
+pub fn list(pool: &RegistryPool, site_id: i64) -> Vec<SiteBackup> {
+    let conn = match pool.get() {
+        Ok(c) => c,
+        Err(_) => return vec![],
+    };
+    let mut stmt = match conn
+        .prepare("SELECT * FROM site_backups WHERE site_id = ?1 ORDER BY created_at DESC, id DESC")
+    {
+        Ok(s) => s,
+        Err(_) => return vec![],
+    };
+    stmt.query_map(params![site_id], SiteBackup::from_row)
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+}
+
+pub fn find(pool: &RegistryPool, id: i64) -> Option<SiteBackup> {
+    let conn = pool.get().ok()?;
+    conn.query_row(
+        "SELECT * FROM site_backups WHERE id = ?1",
+        params![id],
+        SiteBackup::from_row,
+    )
+    .ok()
+}
+
+/// Where a backup's archive is stored
+pub fn file_path(site: &Site, backup: &SiteBackup) -> PathBuf {
+    site_dir(site).join(&backup.file_name)
+}
+
+pub fn delete(pool: &RegistryPool, site: &Site, id: i64) -> Result<(), String> {
+    let backup = find(pool, id)
+        .filter(|b| b.site_id == site.id)
+        .ok_or("Backup not found")?;
+    let _ = std::fs::remove_file(file_path(site, &backup));
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM site_backups WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Forget a deleted site's backups and remove their files
+pub fn delete_all(pool: &RegistryPool, site: &Site) -> Result<(), String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM site_backups WHERE site_id = ?1",
+        params![site.id],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM site_backup_schedules WHERE site_id = ?1",
+        params![site.id],
+    )
+    .map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_dir_all(site_dir(site));
+    Ok(())
+}
+
+// ── Backup ───────────────────────────────────────────────────
+
+/// Archive the site's database, uploads and designs and record it
+pub fn create(pool: &RegistryPool, site: &Site, kind: &str) -> Result<SiteBackup, String> {
+    sqlite_only()?;
+    let dir = site_dir(site);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let file_name = format!(
+        "velocty_site_{}_{}.zip",
+        chrono::Utc::now().format("%Y%m%d_%H%M%S"),
+        &uuid::Uuid::new_v4().to_string()[..8]
+    );
+    // VACUUM INTO gives a consistent copy while the site is live; the
+    // archive is written under a hidden name and renamed once complete
+    let snapshot = dir.join(format!(".snapshot-{}.db", uuid::Uuid::new_v4()));
+    let partial = dir.join(format!(".partial-{}.zip", uuid::Uuid::new_v4()));
+    let written = (|| -> Result<(), String> {
+        let conn = rusqlite::Connection::open(site_db_path(site)).map_err(|e| e.to_string())?;
+        conn.execute(
+            "VACUUM INTO ?1",
+            params![snapshot.to_string_lossy().to_string()],
+        )
+        .map_err(|e| format!("Couldn't copy the database: {}", e))?;
+        drop(conn);
+
+        let out = std::fs::File::create(&partial).map_err(|e| e.to_string())?;
+        let mut zip = ZipWriter::new(out);
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let manifest = serde_json::json!({
+            "hostname": site.hostname,
+            "display_name": site.display_name,
+            "created_at": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            "version": env!("CARGO_PKG_VERSION"),
+        });
+        zip.start_file("site.json", options)
+            .map_err(|e| e.to_string())?;
+        zip.write_all(manifest.to_string().as_bytes())
+            .map_err(|e| e.to_string())?;
+        zip.start_file("db/velocty.db", options)
+            .map_err(|e| e.to_string())?;
+        let mut db = std::fs::File::open(&snapshot).map_err(|e| e.to_string())?;
+        std::io::copy(&mut db, &mut zip).map_err(|e| e.to_string())?;
+        for name in ["uploads", "designs"] {
+            let path = PathBuf::from(format!("website/sites/{}/{}", site.slug, name));
+            if path.is_dir() {
+                crate::backup::add_dir_to_zip(&mut zip, &path, name, options)
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        zip.finish().map_err(|e| e.to_string())?;
+        std::fs::rename(&partial, dir.join(&file_name)).map_err(|e| e.to_string())
+    })();
+    let _ = std::fs::remove_file(&snapshot);
+    if written.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    written?;
+    let size = std::fs::metadata(dir.join(&file_name))
+        .map(|m| m.len() as i64)
+        .unwrap_or(0);
+
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO site_backups (site_id, file_name, size_bytes, kind) VALUES (?1, ?2, ?3, ?4)",
+        params![site.id, file_name, size, kind],
+    )
+    .map_err(|e| e.to_string())?;
+    let id = conn.last_insert_rowid();
+    log::info!("[backup] Backed up site {} ({} bytes)", site.hostname, size);
+    find(pool, id).ok_or_else(|| "Backup not recorded".into())
+}
+
+// ── Restore ──────────────────────────────────────────────────
+
+/// Replace every table in `conn`'s database with its contents in the
+/// database file at `snapshot`. Works on a live database: the copy runs in
+/// one transaction, so requests see either the old or the restored data.
+/// Columns are matched by name, so a backup from an older version restores
+/// into the current schema; tables the backup doesn't have are emptied.
+pub fn restore_tables(conn: &rusqlite::Connection, snapshot: &Path) -> Result<(), String> {
+    let tables = |schema: &str| -> Result<Vec<String>, String> {
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT name FROM {}.sqlite_master WHERE type = 'table'
+                 AND name NOT LIKE 'sqlite_%' AND sql NOT LIKE 'CREATE VIRTUAL%'",
+                schema
+            ))
+            .map_err(|e| e.to_string())?;
+        let names = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(names)
+    };
+    let columns = |schema: &str, table: &str| -> Vec<String> {
+        conn.prepare(&format!("PRAGMA {}.table_info(\"{}\")", schema, table))
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| row.get::<_, String>(1))
+                    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            })
+            .unwrap_or_default()
+    };
+
+    conn.execute(
+        "ATTACH DATABASE ?1 AS snapshot",
+        params![snapshot.to_string_lossy().to_string()],
+    )
+    .map_err(|e| format!("Couldn't open the backup's database: {}", e))?;
+    let result = (|| -> Result<(), String> {
+        let restored = tables("snapshot")?;
+        if !restored.iter().any(|t| t == "settings") {
+            return Err("The backup doesn't contain a Velocty database".into());
+        }
+        conn.execute_batch("PRAGMA foreign_keys = OFF; BEGIN IMMEDIATE;")
+            .map_err(|e| e.to_string())?;
+        let copy = (|| -> Result<(), String> {
+            for table in tables("main")? {
+                conn.execute(&format!("DELETE FROM main.\"{}\"", table), [])
+                    .map_err(|e| e.to_string())?;
+                if !restored.contains(&table) {
+                    continue;
+                }
+                let theirs = columns("snapshot", &table);
+                let shared: Vec<String> = columns("main", &table)
+                    .into_iter()
+                    .filter(|c| theirs.contains(c))
+                    .map(|c| format!("\"{}\"", c))
+                    .collect();
+                if shared.is_empty() {
+                    continue;
+                }
+                let list = shared.join(", ");
+                conn.execute(
+                    &format!(
+                        "INSERT INTO main.\"{0}\" ({1}) SELECT {1} FROM snapshot.\"{0}\"",
+                        table, list
+                    ),
+                    [],
+                )
+                .map_err(|e| format!("Couldn't restore {}: {}", table, e))?;
+            }
+            // Keep AUTOINCREMENT counters in step with the restored rows
+            let has_seq = |schema: &str| {
+                conn.query_row(
+                    &format!(
+                        "SELECT COUNT(*) FROM {}.sqlite_master WHERE name = 'sqlite_sequence'",
+                        schema
+                    ),
+                    [],
+                    |row| row.get::<_, i64>(0),
+                )
+                .map(|n| n > 0)
+                .unwrap_or(false)
+            };
+            if has_seq("main") && has_seq("snapshot") {
+                conn.execute_batch(
+                    "DELETE FROM main.sqlite_sequence;
+                     INSERT INTO main.sqlite_sequence (name, seq) SELECT name, seq FROM snapshot.sqlite_sequence;",
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            Ok(())
+        })();
+        match copy {
+            Ok(()) => conn.execute_batch("COMMIT;").map_err(|e| e.to_string())?,
+            Err(e) => {
+                let _ = conn.execute_batch("ROLLBACK;");
+                let _ = conn.execute_batch("PRAGMA foreign_keys = ON;");
+                return Err(e);
+            }
+        }
+        conn.execute_batch("PRAGMA foreign_keys = ON;")
+            .map_err(|e| e.to_string())
+    })();
+    let _ = conn.execute_batch("DETACH DATABASE snapshot;");
+    result
+}
+
+/// Swap in the `prefix/` folder of the archive as the site's `name` folder
+fn restore_dir(
+    archive: &mut zip::ZipArchive<std::fs::File>,
+    site: &Site,
+    name: &str,
+) -> Result<(), String> {
+    let live = PathBuf::from(format!("website/sites/{}/{}", site.slug, name));
+    let staged = PathBuf::from(format!("website/sites/{}/.{}-restore", site.slug, name));
+    let _ = std::fs::remove_dir_all(&staged);
+    std::fs::create_dir_all(&staged).map_err(|e| e.to_string())?;
+    let prefix = format!("{}/", name);
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
+        let rel = match file.enclosed_name() {
+            Some(p) => p,
+            None => continue, // absolute or `..` paths
+        };
+        let rel = match rel.to_string_lossy().strip_prefix(&prefix) {
+            Some(r) if !r.is_empty() && file.is_file() => PathBuf::from(r),
+            _ => continue,
+        };
+        let target = staged.join(rel);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut out = std::fs::File::create(&target).map_err(|e| e.to_string())?;
+        std::io::copy(&mut file, &mut out).map_err(|e| e.to_string())?;
+    }
+    let old = PathBuf::from(format!("website/sites/{}/.{}-old", site.slug, name));
+    let _ = std::fs::remove_dir_all(&old);
+    if live.exists() {
+        std::fs::rename(&live, &old).map_err(|e| e.to_string())?;
+    }
+    std::fs::rename(&staged, &live).map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_dir_all(&old);
+    Ok(())
+}
+
+/// Put the site back to the state in backup `id`. The current state is
+/// backed up first, so a restore can itself be undone. Cached pages,
+/// sitemaps, roles, firewall rules and redirects are dropped afterwards;
+/// the page cache generation also makes the settings cache reload.
+pub fn restore(pool: &RegistryPool, site: &Site, id: i64) -> Result<(), String> {
+    sqlite_only()?;
+    let backup = find(pool, id)
+        .filter(|b| b.site_id == site.id)
+        .ok_or("Backup not found")?;
+    let file = std::fs::File::open(file_path(site, &backup))
+        .map_err(|_| "The backup file is missing".to_string())?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+
+    let snapshot = site_dir(site).join(format!(".restore-{}.db", uuid::Uuid::new_v4()));
+    {
+        let mut entry = archive
+            .by_name("db/velocty.db")
+            .map_err(|_| "The backup has no database".to_string())?;
+        let mut out = std::fs::File::create(&snapshot).map_err(|e| e.to_string())?;
+        std::io::copy(&mut entry, &mut out).map_err(|e| e.to_string())?;
+    }
+
+    let result = (|| -> Result<(), String> {
+        create(pool, site, "pre-restore")?;
+        let conn = rusqlite::Connection::open(site_db_path(site)).map_err(|e| e.to_string())?;
+        conn.busy_timeout(Duration::from_secs(30))
+            .map_err(|e| e.to_string())?;
+        restore_tables(&conn, &snapshot)?;
+        restore_dir(&mut archive, site, "uploads")?;
+        restore_dir(&mut archive, site, "designs")?;
+        Ok(())
+    })();
+    let _ = std::fs::remove_file(&snapshot);
+    if result.is_ok() {
+        invalidate_caches();
+        log::info!(
+            "[backup] Restored site {} from {}",
+            site.hostname,
+            backup.file_name
+        );
+    }
+    result
+}
+
+/// Drop everything cached from the database a restore just replaced
+fn invalidate_caches() {
+    crate::render::page_cache::invalidate();
+    crate::seo::sitemap::invalidate();
+    crate::models::role::invalidate();
+    crate::models::firewall::invalidate_rules();
+    crate::models::redirect::invalidate_redirects();
+}
+
+/// The last restore started for a site
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreStatus {
+    /// File name of the backup being restored
+    pub backup: String,
+    pub running: bool,
+    pub error: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+fn restores() -> &'static Mutex<HashMap<i64, RestoreStatus>> {
+    static RESTORES: OnceLock<Mutex<HashMap<i64, RestoreStatus>>> = OnceLock::new();
+    RESTORES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn now_string() -> String {
+    chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// The last restore of `site_id` since this process started
+pub fn restore_status(site_id: i64) -> Option<RestoreStatus> {
+    restores().lock().ok()?.get(&site_id).cloned()
+}
+
+/// Record a restore of `site_id` as running; refused while one already is
+pub fn begin_restore(site_id: i64, backup: &str) -> Result<(), String> {
+    let mut map = restores().lock().map_err(|e| e.to_string())?;
+    if map.get(&site_id).is_some_and(|s| s.running) {
+        return Err("A restore of this site is already running.".into());
+    }
+    map.insert(
+        site_id,
+        RestoreStatus {
+            backup: backup.to_string(),
+            running: true,
+            error: String::new(),
+            started_at: now_string(),
+            finished_at: None,
+        },
+    );
+    Ok(())
+}
+
+/// Record how the running restore of `site_id` ended
+pub fn finish_restore(site_id: i64, result: &Result<(), String>) {
+    if let Ok(mut map) = restores().lock() {
+        if let Some(status) = map.get_mut(&site_id) {
+            status.running = false;
+            status.error = result.clone().err().unwrap_or_default();
+            status.finished_at = Some(now_string());
+        }
+    }
+}
+
+/// Start restoring backup `id` on a background thread; progress is read
+/// back through `restore_status`
+pub fn restore_in_background(pool: &RegistryPool, site: &Site, id: i64) -> Result<(), String> {
+    sqlite_only()?;
+    let backup = find(pool, id)
+        .filter(|b| b.site_id == site.id)
+        .ok_or("Backup not found")?;
+    begin_restore(site.id, &backup.file_name)?;
+    let pool = RegistryPool(pool.0.clone());
+    let site = site.clone();
+    std::thread::spawn(move || {
+        let result = restore(&pool, &site, id);
+        if let Err(e) = &result {
+            log::warn!("[backup] Restore of {} failed: {}", site.hostname, e);
+        }
+        finish_restore(site.id, &result);
+    });
+    Ok(())
+}
+
+// ── Schedules ────────────────────────────────────────────────
+
+pub fn schedule(pool: &RegistryPool, site_id: i64) -> Schedule {
+    let none = Schedule {
+        site_id,
+        interval_hours: 0,
+        keep: 7,
+        last_run_at: None,
+        last_error: String::new(),
+    };
+    let conn = match pool.get() {
+        Ok(c) => c,
+        Err(_) => return none,
+    };
+    conn.query_row(
+        "SELECT interval_hours, keep, last_run_at, last_error FROM site_backup_schedules WHERE site_id = ?1",
+        params![site_id],
+        |row| {
+            Ok(Schedule {
+                site_id,
+                interval_hours: row.get(0)?,
+                keep: row.get(1)?,
+                last_run_at: row.get(2)?,
+                last_error: row.get(3)?,
+            })
+        },
+    )
+    .unwrap_or(none)
+}
+
+pub fn set_schedule(
+    pool: &RegistryPool,
+    site_id: i64,
+    interval_hours: i64,
+    keep: i64,
+) -> Result<(), String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO site_backup_schedules (site_id, interval_hours, keep) VALUES (?1, ?2, ?3)
+         ON CONFLICT(site_id) DO UPDATE SET interval_hours = excluded.interval_hours, keep = excluded.keep",
+        params![site_id, interval_hours.clamp(0, 24 * 30), keep.clamp(1, 100)],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Whether a schedule is due at `now`
+pub fn is_due(schedule: &Schedule, now: chrono::NaiveDateTime) -> bool {
+    if schedule.interval_hours <= 0 {
+        return false;
+    }
+    match schedule
+        .last_run_at
+        .as_deref()
+        .and_then(|s| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").ok())
+    {
+        Some(last) => now - last >= chrono::Duration::hours(schedule.interval_hours),
+        None => true,
+    }
+}
+
+/// Remove scheduled backups beyond the newest `keep`
+fn prune(pool: &RegistryPool, site: &Site, keep: i64) {
+    let scheduled: Vec<SiteBackup> = list(pool, site.id)
+        .into_iter()
+        .filter(|b| b.kind == "scheduled")
+        .collect();
+    for b in scheduled.iter().skip(keep.max(1) as usize) {
+        let _ = delete(pool, site, b.id);
+    }
+}
+
+fn run_due(pool: &RegistryPool) {
+    let now = chrono::Utc::now().naive_utc();
+    for site in super::list_sites(pool) {
+        let sched = schedule(pool, site.id);
+        if !is_due(&sched, now) {
+            continue;
+        }
+        let error = match create(pool, &site, "scheduled") {
+            Ok(_) => {
+                prune(pool, &site, sched.keep);
+                String::new()
+            }
+            Err(e) => {
+                log::warn!(
+                    "[backup] Scheduled backup of {} failed: {}",
+                    site.hostname,
+                    e
+                );
+                e
+            }
+        };
+        if let Ok(conn) = pool.get() {
+            let _ = conn.execute(
+                "UPDATE site_backup_schedules SET last_run_at = ?1, last_error = ?2 WHERE site_id = ?3",
+                params![now.format("%Y-%m-%d %H:%M:%S").to_string(), error, site.id],
+            );
+        }
+    }
+}
+
+/// Run scheduled site backups in the background
+pub fn spawn_scheduler(pool: &RegistryPool) {
+    let pool = RegistryPool(pool.0.clone());
+    std::thread::spawn(move || loop {
+        run_due(&pool);
+        std::thread::sleep(SCHEDULE_CHECK_INTERVAL);
+    });
+}
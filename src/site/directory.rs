@@ -0,0 +1,667 @@
+//! Shared user directory: accounts kept in the registry and granted a role
+//! on any number of sites, so one person doesn't need a separate login per
+//! site.
+//!
+//! Signing in at `/super/account` on any host starts a directory session.
+//! Opening a site hands the session over with a one-time ticket. The ticket
+//! is redeemed on the site's own host (hostname or mapped domain), once the
+//! visitor confirms which account is signing in. The same session token
+//! becomes valid there. On first use the account is created in the site's
+//! users table with the granted role, so the site admin works as usual.
+//! That mirrored account has no password of its own: it only signs in
+//! through the directory, takes its role from the grant, and is locked
+//! when the grant or the directory account goes away.
+
+use rusqlite::params;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::{RegistryPool, SiteStoreManager};
+use crate::models::user::User;
+use crate::security::password_policy::{self, PasswordPolicy};
+use crate::store::Store;
+
+/// How long a directory session lasts
+const SESSION_HOURS: i64 = 12;
+
+/// How long a handoff ticket can be redeemed, confirmation page included
+const TICKET_SECONDS: i64 = 5 * 60;
+
+/// Roles a directory account can be granted on a site
+pub const GRANTABLE_ROLES: &[&str] = &["admin", "editor", "author"];
+
+/// Password hash of a mirrored site account. It never verifies, so the
+/// account can't sign in on the site with a password of its own.
+const NO_LOCAL_PASSWORD: &str = "!";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DirectoryUser {
+    pub id: i64,
+    pub email: String,
+    pub display_name: String,
+    #[serde(skip_serializing)]
+    pub password_hash: String,
+    /// Authenticator app secret, empty when the account has none
+    #[serde(skip_serializing)]
+    pub mfa_secret: String,
+    pub created_at: String,
+}
+
+impl DirectoryUser {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(DirectoryUser {
+            id: row.get("id")?,
+            email: row.get("email")?,
+            display_name: row.get("display_name")?,
+            password_hash: row.get("password_hash")?,
+            mfa_secret: row.get("mfa_secret")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+}
+
+/// A role on one site, with the site's names for display
+#[derive(Debug, Clone, Serialize)]
+pub struct Grant {
+    pub user_id: i64,
+    pub site_id: i64,
+    pub role: String,
+    pub hostname: String,
+    pub display_name: String,
+}
+
+// ── Accounts ─────────────────────────────────────────────────
+
+pub fn list_users(pool: &RegistryPool) -> Vec<DirectoryUser> {
+    let conn = match pool.get() {
+        Ok(c) => c,
+        Err(_) => return vec![],
+    };
+    let mut stmt = match conn.prepare("SELECT * FROM directory_users ORDER BY email") {
+        Ok(s) => s,
+        Err(_) => return vec![],
+    };
+    stmt.query_map([], DirectoryUser::from_row)
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+}
+
+pub fn find_user(pool: &RegistryPool, id: i64) -> Option<DirectoryUser> {
+    let conn = pool.get().ok()?;
+    conn.query_row(
+        "SELECT * FROM directory_users WHERE id = ?1",
+        params![id],
+        DirectoryUser::from_row,
+    )
+    .ok()
+}
+
+pub fn find_user_by_email(pool: &RegistryPool, email: &str) -> Option<DirectoryUser> {
+    let conn = pool.get().ok()?;
+    conn.query_row(
+        "SELECT * FROM directory_users WHERE email = ?1 COLLATE NOCASE",
+        params![email.trim()],
+        DirectoryUser::from_row,
+    )
+    .ok()
+}
+
+/// Password rules a directory password must meet: the default policy, and
+/// the policy of every site `user_id` can sign in to
+pub fn password_policies(
+    pool: &RegistryPool,
+    stores: &SiteStoreManager,
+    user_id: Option<i64>,
+) -> Vec<PasswordPolicy> {
+    let mut policies = vec![PasswordPolicy::from_settings(&HashMap::new())];
+    for g in user_id.map(|id| list_grants(pool, id)).unwrap_or_default() {
+        if let Some(store) = site_store(pool, stores, g.site_id) {
+            policies.push(PasswordPolicy::load(&*store));
+        }
+    }
+    policies
+}
+
+fn check_password(
+    policies: &[PasswordPolicy],
+    password: &str,
+    personal: &[&str],
+) -> Result<(), String> {
+    policies
+        .iter()
+        .try_for_each(|p| password_policy::check_with(p, password, personal))
+}
+
+pub fn create_user(
+    pool: &RegistryPool,
+    stores: &SiteStoreManager,
+    email: &str,
+    display_name: &str,
+    password: &str,
+) -> Result<i64, String> {
+    let email = email.trim();
+    if !email.contains('@') {
+        return Err("Please enter a valid email address.".into());
+    }
+    check_password(
+        &password_policies(pool, stores, None),
+        password,
+        &[email, display_name.trim()],
+    )?;
+    if find_user_by_email(pool, email).is_some() {
+        return Err(format!("{} already has an account.", email));
+    }
+    let hash = crate::security::auth::hash_password(password)?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO directory_users (email, display_name, password_hash) VALUES (?1, ?2, ?3)",
+        params![email, display_name.trim(), hash],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Change the password and sign the account out everywhere, on every site
+/// its sessions were handed to
+pub fn set_password(
+    pool: &RegistryPool,
+    stores: &SiteStoreManager,
+    id: i64,
+    password: &str,
+) -> Result<(), String> {
+    let user = find_user(pool, id).ok_or("User not found")?;
+    check_password(
+        &password_policies(pool, stores, Some(id)),
+        password,
+        &[&user.email, &user.display_name],
+    )?;
+    let hash = crate::security::auth::hash_password(password)?;
+    pool.get()
+        .map_err(|e| e.to_string())?
+        .execute(
+            "UPDATE directory_users SET password_hash = ?1 WHERE id = ?2",
+            params![hash, id],
+        )
+        .map_err(|e| e.to_string())?;
+    destroy_user_sessions(pool, stores, id)
+}
+
+/// Delete the account, locking its mirrored account on every site
+pub fn delete_user(pool: &RegistryPool, stores: &SiteStoreManager, id: i64) -> Result<(), String> {
+    if let Some(user) = find_user(pool, id) {
+        for (site_id, _) in mirrors(pool, id) {
+            lock_site_user(pool, stores, &user, site_id);
+        }
+    }
+    destroy_user_sessions(pool, stores, id)?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    for sql in [
+        "DELETE FROM directory_tickets WHERE user_id = ?1",
+        "DELETE FROM directory_site_users WHERE user_id = ?1",
+        "DELETE FROM directory_grants WHERE user_id = ?1",
+        "DELETE FROM directory_users WHERE id = ?1",
+    ] {
+        conn.execute(sql, params![id]).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// The account for `email` when `password` matches
+pub fn authenticate(pool: &RegistryPool, email: &str, password: &str) -> Option<DirectoryUser> {
+    find_user_by_email(pool, email)
+        .filter(|u| crate::security::auth::verify_password(password, &u.password_hash))
+}
+
+/// Turn the account's authenticator app on with `secret`, or off with an
+/// empty one
+pub fn set_mfa_secret(pool: &RegistryPool, id: i64, secret: &str) -> Result<(), String> {
+    pool.get()
+        .map_err(|e| e.to_string())?
+        .execute(
+            "UPDATE directory_users SET mfa_secret = ?1 WHERE id = ?2",
+            params![secret, id],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Authenticator secrets a sign-in can answer with a code: the account's
+/// own and those of its mirrored site accounts. Empty when no second
+/// factor is set up anywhere.
+pub fn second_factor_secrets(
+    pool: &RegistryPool,
+    stores: &SiteStoreManager,
+    user: &DirectoryUser,
+) -> Vec<String> {
+    let mut secrets = vec![];
+    if !user.mfa_secret.is_empty() {
+        secrets.push(user.mfa_secret.clone());
+    }
+    for (site_id, _) in mirrors(pool, user.id) {
+        let local = site_store(pool, stores, site_id)
+            .and_then(|store| mirrored_user(pool, &*store, user, site_id));
+        if let Some(local) = local.filter(|u| u.mfa_required() && u.mfa_method != "email") {
+            secrets.push(local.mfa_secret);
+        }
+    }
+    secrets
+}
+
+// ── Grants ───────────────────────────────────────────────────
+
+pub fn list_grants(pool: &RegistryPool, user_id: i64) -> Vec<Grant> {
+    let conn = match pool.get() {
+        Ok(c) => c,
+        Err(_) => return vec![],
+    };
+    let mut stmt = match conn.prepare(
+        "SELECT g.user_id, g.site_id, g.role, s.hostname, s.display_name
+         FROM directory_grants g JOIN sites s ON s.id = g.site_id
+         WHERE g.user_id = ?1 ORDER BY s.display_name",
+    ) {
+        Ok(s) => s,
+        Err(_) => return vec![],
+    };
+    stmt.query_map(params![user_id], |row| {
+        Ok(Grant {
+            user_id: row.get(0)?,
+            site_id: row.get(1)?,
+            role: row.get(2)?,
+            hostname: row.get(3)?,
+            display_name: row.get(4)?,
+        })
+    })
+    .map(|rows| rows.filter_map(|r| r.ok()).collect())
+    .unwrap_or_default()
+}
+
+/// The role `user_id` holds on `site_id`, if any
+pub fn role_on_site(pool: &RegistryPool, user_id: i64, site_id: i64) -> Option<String> {
+    let conn = pool.get().ok()?;
+    conn.query_row(
+        "SELECT role FROM directory_grants WHERE user_id = ?1 AND site_id = ?2",
+        params![user_id, site_id],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+/// Give `user_id` `role` on `site_id`, replacing any role it had there.
+/// The mirrored account on the site, if there is one yet, changes role
+/// with it.
+pub fn grant(pool: &RegistryPool, user_id: i64, site_id: i64, role: &str) -> Result<(), String> {
+    if !GRANTABLE_ROLES.contains(&role) {
+        return Err(format!("Unknown role: {}", role));
+    }
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO directory_grants (user_id, site_id, role) VALUES (?1, ?2, ?3)
+         ON CONFLICT(user_id, site_id) DO UPDATE SET role = excluded.role",
+        params![user_id, site_id, role],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Bring the mirrored account on `site_id` to the role granted there
+pub fn apply_grant(
+    pool: &RegistryPool,
+    stores: &SiteStoreManager,
+    user_id: i64,
+    site_id: i64,
+) -> Result<(), String> {
+    let (Some(user), Some(role)) = (
+        find_user(pool, user_id),
+        role_on_site(pool, user_id, site_id),
+    ) else {
+        return Ok(());
+    };
+    let Some(store) = site_store(pool, stores, site_id) else {
+        return Ok(());
+    };
+    match mirrored_user(pool, &*store, &user, site_id) {
+        Some(local) if local.role != role => store.user_update_role(local.id, &role),
+        _ => Ok(()),
+    }
+}
+
+pub fn revoke(pool: &RegistryPool, user_id: i64, site_id: i64) -> Result<(), String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM directory_grants WHERE user_id = ?1 AND site_id = ?2",
+        params![user_id, site_id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Forget every grant on a site that is being deleted
+pub fn revoke_site(pool: &RegistryPool, site_id: i64) -> Result<(), String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    for sql in [
+        "DELETE FROM directory_grants WHERE site_id = ?1",
+        "DELETE FROM directory_site_users WHERE site_id = ?1",
+        "DELETE FROM directory_site_sessions WHERE site_id = ?1",
+    ] {
+        conn.execute(sql, params![site_id])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// ── Sessions and handoff tickets ─────────────────────────────
+
+pub fn create_session(pool: &RegistryPool, user_id: i64) -> Result<String, String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    let token = uuid::Uuid::new_v4().to_string();
+    let expires = chrono::Utc::now().naive_utc() + chrono::Duration::hours(SESSION_HOURS);
+    conn.execute(
+        "INSERT INTO directory_sessions (token, user_id, expires_at) VALUES (?1, ?2, ?3)",
+        params![token, user_id, expires],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(token)
+}
+
+/// The account a directory session belongs to, while it's valid
+pub fn session_user(pool: &RegistryPool, token: &str) -> Option<DirectoryUser> {
+    let conn = pool.get().ok()?;
+    let now = chrono::Utc::now().naive_utc();
+    let user_id: i64 = conn
+        .query_row(
+            "SELECT user_id FROM directory_sessions WHERE token = ?1 AND expires_at > ?2",
+            params![token, now],
+            |row| row.get(0),
+        )
+        .ok()?;
+    find_user(pool, user_id)
+}
+
+/// Remember the site session a handoff of `session_token` signed in, so
+/// signing out ends it too
+pub fn record_site_session(
+    pool: &RegistryPool,
+    session_token: &str,
+    site_id: i64,
+    site_session_id: &str,
+) -> Result<(), String> {
+    pool.get()
+        .map_err(|e| e.to_string())?
+        .execute(
+            "INSERT INTO directory_site_sessions (session_token, site_id, site_session_id)
+             VALUES (?1, ?2, ?3)",
+            params![session_token, site_id, site_session_id],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// End a directory session, and the site sessions it signed in on every
+/// host it was handed to
+pub fn destroy_session(
+    pool: &RegistryPool,
+    stores: &SiteStoreManager,
+    token: &str,
+) -> Result<(), String> {
+    let site_sessions: Vec<(i64, String)> = {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT site_id, site_session_id FROM directory_site_sessions
+                 WHERE session_token = ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![token], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+    for (site_id, site_session_id) in site_sessions {
+        if let Some(store) = site_store(pool, stores, site_id) {
+            store.session_delete(&site_session_id)?;
+        }
+    }
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM directory_site_sessions WHERE session_token = ?1",
+        params![token],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM directory_tickets WHERE session_token = ?1",
+        params![token],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM directory_sessions WHERE token = ?1",
+        params![token],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Sign `user_id` out of every directory session it has
+fn destroy_user_sessions(
+    pool: &RegistryPool,
+    stores: &SiteStoreManager,
+    user_id: i64,
+) -> Result<(), String> {
+    let tokens: Vec<String> = {
+        let conn = pool.get().map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare("SELECT token FROM directory_sessions WHERE user_id = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![user_id], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+    for token in tokens {
+        destroy_session(pool, stores, &token)?;
+    }
+    Ok(())
+}
+
+/// One-time ticket that carries `session_token` over to `site_id`'s host
+pub fn create_ticket(
+    pool: &RegistryPool,
+    session_token: &str,
+    site_id: i64,
+) -> Result<String, String> {
+    let user = session_user(pool, session_token).ok_or("Session expired")?;
+    if role_on_site(pool, user.id, site_id).is_none() {
+        return Err("No access to this site".into());
+    }
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    let now = chrono::Utc::now().naive_utc();
+    conn.execute(
+        "DELETE FROM directory_tickets WHERE expires_at <= ?1",
+        params![now],
+    )
+    .map_err(|e| e.to_string())?;
+    let ticket = uuid::Uuid::new_v4().to_string();
+    let expires = now + chrono::Duration::seconds(TICKET_SECONDS);
+    conn.execute(
+        "INSERT INTO directory_tickets (ticket, session_token, user_id, site_id, expires_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![ticket, session_token, user.id, site_id, expires],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(ticket)
+}
+
+/// The account a ticket would sign in as on `site_id`, without using it up,
+/// for the confirmation page
+pub fn peek_ticket(pool: &RegistryPool, ticket: &str, site_id: i64) -> Option<DirectoryUser> {
+    let conn = pool.get().ok()?;
+    let now = chrono::Utc::now().naive_utc();
+    let session_token: String = conn
+        .query_row(
+            "SELECT session_token FROM directory_tickets
+             WHERE ticket = ?1 AND site_id = ?2 AND expires_at > ?3",
+            params![ticket, site_id, now],
+            |row| row.get(0),
+        )
+        .ok()?;
+    session_user(pool, &session_token)
+}
+
+/// Use up a ticket on `site_id`'s host. Returns the session token it
+/// carries, the account and its role on the site.
+pub fn redeem_ticket(
+    pool: &RegistryPool,
+    ticket: &str,
+    site_id: i64,
+) -> Result<(String, DirectoryUser, String), String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    let now = chrono::Utc::now().naive_utc();
+    let found: Option<(String, i64)> = conn
+        .query_row(
+            "SELECT session_token, site_id FROM directory_tickets
+             WHERE ticket = ?1 AND expires_at > ?2",
+            params![ticket, now],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok();
+    conn.execute(
+        "DELETE FROM directory_tickets WHERE ticket = ?1",
+        params![ticket],
+    )
+    .map_err(|e| e.to_string())?;
+    let (session_token, ticket_site) = found.ok_or("This sign-in link has expired")?;
+    if ticket_site != site_id {
+        return Err("This sign-in link is for another site".into());
+    }
+    let user = session_user(pool, &session_token).ok_or("Session expired")?;
+    let role = role_on_site(pool, user.id, site_id).ok_or("No access to this site")?;
+    Ok((session_token, user, role))
+}
+
+// ── Mirroring into a site ────────────────────────────────────
+
+fn site_store(
+    pool: &RegistryPool,
+    stores: &SiteStoreManager,
+    site_id: i64,
+) -> Option<Arc<dyn Store>> {
+    let site = super::find_site_by_id(pool, site_id)?;
+    stores.get_store(&site.slug).ok()
+}
+
+/// Sites `user_id` has a mirrored account on, with that account's id
+fn mirrors(pool: &RegistryPool, user_id: i64) -> Vec<(i64, i64)> {
+    let conn = match pool.get() {
+        Ok(c) => c,
+        Err(_) => return vec![],
+    };
+    let mut stmt = match conn
+        .prepare("SELECT site_id, site_user_id FROM directory_site_users WHERE user_id = ?1")
+    {
+        Ok(s) => s,
+        Err(_) => return vec![],
+    };
+    stmt.query_map(params![user_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+}
+
+/// The account the directory created for `user` on `site_id`, while it
+/// still exists under the same email
+pub fn mirrored_user(
+    pool: &RegistryPool,
+    store: &dyn Store,
+    user: &DirectoryUser,
+    site_id: i64,
+) -> Option<User> {
+    let site_user_id: i64 = pool
+        .get()
+        .ok()?
+        .query_row(
+            "SELECT site_user_id FROM directory_site_users WHERE user_id = ?1 AND site_id = ?2",
+            params![user.id, site_id],
+            |row| row.get(0),
+        )
+        .ok()?;
+    store
+        .user_get_by_id(site_user_id)
+        .filter(|u| u.email.eq_ignore_ascii_case(&user.email))
+}
+
+/// The site account a directory sign-in lands on.
+///
+/// The first sign-in creates it with the granted role and no usable
+/// password, and records it as mirrored. Later sign-ins bring its role in
+/// line with the grant; a locked one stays locked, and one that signs in
+/// with a passkey only or emailed codes is refused, since the directory
+/// can't check those. An authenticator app on it is checked by the
+/// directory sign-in itself.
+///
+/// An account that already existed under the email belongs to the site:
+/// its password, role and status are left as they are, and one protected
+/// by MFA or a passkey isn't signed in without that factor, so it must
+/// sign in on the site directly.
+pub fn sync_site_user(
+    pool: &RegistryPool,
+    site_id: i64,
+    store: &dyn Store,
+    user: &DirectoryUser,
+    role: &str,
+) -> Result<User, String> {
+    if let Some(local) = mirrored_user(pool, store, user, site_id) {
+        if !local.is_active() {
+            return Err("Your account on this site is locked; ask its administrator".into());
+        }
+        if local.passwordless || (local.mfa_required() && local.mfa_method == "email") {
+            return Err(
+                "Your account on this site signs in with a passkey or emailed codes; ask its administrator"
+                    .into(),
+            );
+        }
+        if local.role != role {
+            store.user_update_role(local.id, role)?;
+        }
+        return store
+            .user_get_by_id(local.id)
+            .ok_or_else(|| "User not found".into());
+    }
+    if let Some(local) = store.user_get_by_email(&user.email) {
+        if !local.is_active() {
+            return Err("Your account on this site is locked; ask its administrator".into());
+        }
+        if local.mfa_enabled || local.passwordless || store.passkey_count_for_user(local.id) > 0 {
+            return Err(
+                "Your account on this site uses two-step sign-in; sign in on the site directly"
+                    .into(),
+            );
+        }
+        return Ok(local);
+    }
+    let id = store.user_create(&user.email, NO_LOCAL_PASSWORD, &user.display_name, role)?;
+    pool.get()
+        .map_err(|e| e.to_string())?
+        .execute(
+            "INSERT OR REPLACE INTO directory_site_users (user_id, site_id, site_user_id)
+             VALUES (?1, ?2, ?3)",
+            params![user.id, site_id, id],
+        )
+        .map_err(|e| e.to_string())?;
+    store
+        .user_get_by_id(id)
+        .ok_or_else(|| "User not found".into())
+}
+
+/// Lock the account the directory created for `user` on `site_id` once it
+/// loses access there. Accounts the site had of its own are left alone.
+pub fn lock_site_user(
+    pool: &RegistryPool,
+    stores: &SiteStoreManager,
+    user: &DirectoryUser,
+    site_id: i64,
+) {
+    if let Some(store) = site_store(pool, stores, site_id) {
+        if let Some(local) = mirrored_user(pool, &*store, user, site_id) {
+            let _ = store.user_lock(local.id);
+        }
+    }
+}
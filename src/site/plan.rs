@@ -0,0 +1,285 @@
+//! Plans: named bundles of features kept in the registry and assigned to
+//! sites, so a hosted install can offer tiers from one build.
+//!
+//! A site without a plan has every feature. For a site on a plan, the
+//! `SiteResolver` sends requests for features the plan leaves out to a
+//! "not included in your plan" response instead of their route.
+
+use rusqlite::params;
+use serde::Serialize;
+
+use super::RegistryPool;
+use crate::store::Store;
+
+/// Features a plan can switch on or off, with their display names
+pub const FEATURES: &[(&str, &str)] = &[
+    ("commerce", "Commerce"),
+    ("ai", "AI"),
+    ("newsletter", "Newsletter"),
+];
+
+/// Gated feature reported when a site is at its plan's user limit
+pub const USERS_FEATURE: &str = "users";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Plan {
+    pub id: i64,
+    pub name: String,
+    pub commerce: bool,
+    pub ai: bool,
+    pub newsletter: bool,
+    /// 0 means no limit
+    pub max_users: i64,
+    pub created_at: String,
+}
+
+impl Plan {
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(Plan {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            commerce: row.get("commerce")?,
+            ai: row.get("ai")?,
+            newsletter: row.get("newsletter")?,
+            max_users: row.get("max_users")?,
+            created_at: row.get("created_at")?,
+        })
+    }
+
+    pub fn allows(&self, feature: &str) -> bool {
+        match feature {
+            "commerce" => self.commerce,
+            "ai" => self.ai,
+            "newsletter" => self.newsletter,
+            _ => true,
+        }
+    }
+
+    /// Whether a site with `user_count` users may add another
+    pub fn has_room_for_user(&self, user_count: i64) -> bool {
+        self.max_users <= 0 || user_count < self.max_users
+    }
+}
+
+// ── Plans ────────────────────────────────────────────────────
+
+pub fn list_plans(pool: &RegistryPool) -> Vec<Plan> {
+    let conn = match pool.get() {
+        Ok(c) => c,
+        Err(_) => return vec![],
+    };
+    let mut stmt = match conn.prepare("SELECT * FROM plans ORDER BY name") {
+        Ok(s) => s,
+        Err(_) => return vec![],
+    };
+    stmt.query_map([], Plan::from_row)
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+}
+
+pub fn find_plan(pool: &RegistryPool, id: i64) -> Option<Plan> {
+    let conn = pool.get().ok()?;
+    conn.query_row(
+        "SELECT * FROM plans WHERE id = ?1",
+        params![id],
+        Plan::from_row,
+    )
+    .ok()
+}
+
+fn check_plan_fields(name: &str, max_users: i64) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Plan name is required".into());
+    }
+    if max_users < 0 {
+        return Err("Max users can't be negative".into());
+    }
+    Ok(name.to_string())
+}
+
+pub fn create_plan(
+    pool: &RegistryPool,
+    name: &str,
+    commerce: bool,
+    ai: bool,
+    newsletter: bool,
+    max_users: i64,
+) -> Result<i64, String> {
+    let name = check_plan_fields(name, max_users)?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO plans (name, commerce, ai, newsletter, max_users) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![name, commerce, ai, newsletter, max_users],
+    )
+    .map_err(|e| {
+        if e.to_string().contains("UNIQUE") {
+            format!("A plan named {} already exists", name)
+        } else {
+            e.to_string()
+        }
+    })?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn update_plan(
+    pool: &RegistryPool,
+    id: i64,
+    name: &str,
+    commerce: bool,
+    ai: bool,
+    newsletter: bool,
+    max_users: i64,
+) -> Result<(), String> {
+    let name = check_plan_fields(name, max_users)?;
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    let changed = conn
+        .execute(
+            "UPDATE plans SET name = ?1, commerce = ?2, ai = ?3, newsletter = ?4, max_users = ?5
+             WHERE id = ?6",
+            params![name, commerce, ai, newsletter, max_users, id],
+        )
+        .map_err(|e| {
+            if e.to_string().contains("UNIQUE") {
+                format!("A plan named {} already exists", name)
+            } else {
+                e.to_string()
+            }
+        })?;
+    if changed == 0 {
+        return Err("Plan not found".into());
+    }
+    Ok(())
+}
+
+/// Delete a plan no site is on. Refusing otherwise keeps a deletion from
+/// quietly giving those sites every feature.
+pub fn delete_plan(pool: &RegistryPool, id: i64) -> Result<(), String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    let in_use: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM site_plans WHERE plan_id = ?1",
+            params![id],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if in_use > 0 {
+        return Err(format!(
+            "{} site{} still on this plan",
+            in_use,
+            if in_use == 1 { " is" } else { "s are" }
+        ));
+    }
+    conn.execute("DELETE FROM plans WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// How many sites are on each plan, by plan id
+pub fn site_counts(pool: &RegistryPool) -> std::collections::HashMap<i64, i64> {
+    let conn = match pool.get() {
+        Ok(c) => c,
+        Err(_) => return Default::default(),
+    };
+    let mut stmt = match conn.prepare("SELECT plan_id, COUNT(*) FROM site_plans GROUP BY plan_id") {
+        Ok(s) => s,
+        Err(_) => return Default::default(),
+    };
+    stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+}
+
+// ── Site assignment ──────────────────────────────────────────
+
+/// The plan a site is on; `None` means unrestricted
+pub fn site_plan(pool: &RegistryPool, site_id: i64) -> Option<Plan> {
+    let conn = pool.get().ok()?;
+    conn.query_row(
+        "SELECT p.* FROM plans p JOIN site_plans sp ON sp.plan_id = p.id WHERE sp.site_id = ?1",
+        params![site_id],
+        Plan::from_row,
+    )
+    .ok()
+}
+
+/// Put a site on a plan, or take it off any plan with `None`
+pub fn assign_plan(pool: &RegistryPool, site_id: i64, plan_id: Option<i64>) -> Result<(), String> {
+    let conn = pool.get().map_err(|e| e.to_string())?;
+    match plan_id {
+        Some(plan_id) => {
+            if find_plan(pool, plan_id).is_none() {
+                return Err("Plan not found".into());
+            }
+            conn.execute(
+                "INSERT INTO site_plans (site_id, plan_id) VALUES (?1, ?2)
+                 ON CONFLICT(site_id) DO UPDATE SET plan_id = excluded.plan_id",
+                params![site_id, plan_id],
+            )
+        }
+        None => conn.execute(
+            "DELETE FROM site_plans WHERE site_id = ?1",
+            params![site_id],
+        ),
+    }
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// ── Gating ───────────────────────────────────────────────────
+
+/// The feature a request path belongs to, for paths a plan can gate.
+/// Admin paths are matched after the admin slug is rewritten to
+/// `ADMIN_INTERNAL_MOUNT`. Payment callbacks and purchase downloads stay
+/// open so orders placed before a downgrade still complete.
+pub fn feature_for_path(path: &str) -> Option<&'static str> {
+    let adm = crate::ADMIN_INTERNAL_MOUNT;
+    let admin = |p: &str| {
+        path.strip_prefix(adm)
+            .is_some_and(|rest| rest == p || rest.starts_with(&format!("{}/", p)))
+    };
+    if path.starts_with("/api/checkout/")
+        || admin("/sales")
+        || admin("/settings/commerce")
+        || admin("/settings/paypal")
+    {
+        return Some("commerce");
+    }
+    if admin("/api/ai") || admin("/ai-usage") || admin("/settings/ai") {
+        return Some("ai");
+    }
+    if path.starts_with("/api/newsletter/") {
+        return Some("newsletter");
+    }
+    None
+}
+
+/// The feature `plan` doesn't include that a request needs, if any
+pub fn blocked_feature(
+    plan: &Plan,
+    method: rocket::http::Method,
+    path: &str,
+    store: &dyn Store,
+) -> Option<&'static str> {
+    if let Some(feature) = feature_for_path(path).filter(|f| !plan.allows(f)) {
+        return Some(feature);
+    }
+    let creates_user = method == rocket::http::Method::Post
+        && path == format!("{}/api/users/create", crate::ADMIN_INTERNAL_MOUNT);
+    if creates_user && !plan.has_room_for_user(store.user_count()) {
+        return Some(USERS_FEATURE);
+    }
+    None
+}
+
+/// Display name for a gated feature
+pub fn feature_label(feature: &str) -> &'static str {
+    if feature == USERS_FEATURE {
+        return "More users";
+    }
+    FEATURES
+        .iter()
+        .find(|(key, _)| *key == feature)
+        .map(|(_, label)| *label)
+        .unwrap_or("This feature")
+}
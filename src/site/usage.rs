@@ -0,0 +1,118 @@
+//! Usage across sites for the super admin dashboard: views, storage, email
+//! and sales, read from each site's own store.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::Site;
+use crate::store::Store;
+
+/// Days the dashboard's usage figures cover
+pub const PERIOD_DAYS: i64 = 30;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SiteUsage {
+    pub site_id: i64,
+    pub views: i64,
+    pub visitors: i64,
+    /// Database, uploads and designs on disk
+    pub storage_bytes: u64,
+    pub storage: String,
+    pub emails_sent: u64,
+    pub emails_failed: u64,
+    pub orders: i64,
+    pub revenue: f64,
+    pub currency: String,
+    /// Base URL of the site's admin, for drill-down links
+    pub admin_url: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageTotals {
+    pub views: i64,
+    pub visitors: i64,
+    pub storage_bytes: u64,
+    pub storage: String,
+    pub emails_sent: u64,
+    pub emails_failed: u64,
+    pub orders: i64,
+    /// Revenue per currency; sites selling in different currencies aren't summed
+    pub revenue: BTreeMap<String, f64>,
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let entries = match std::fs::read_dir(path) {
+        Ok(e) => e,
+        Err(_) => return 0,
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                dir_size(&path)
+            } else {
+                entry.metadata().map(|m| m.len()).unwrap_or(0)
+            }
+        })
+        .sum()
+}
+
+/// Usage of one site over the last `PERIOD_DAYS` days
+pub fn gather(site: &Site, store: &dyn Store) -> SiteUsage {
+    let today = chrono::Utc::now().date_naive();
+    let from = (today - chrono::Duration::days(PERIOD_DAYS - 1))
+        .format("%Y-%m-%d")
+        .to_string();
+    let to = today.format("%Y-%m-%d").to_string();
+    let overview = store.analytics_overview(&from, &to);
+
+    let storage_bytes = dir_size(Path::new(&format!("website/sites/{}", site.slug)));
+    let emails_sent = store
+        .mta_queue_sent_hourly(PERIOD_DAYS * 24)
+        .iter()
+        .map(|(_, n)| n)
+        .sum();
+
+    let base = store
+        .setting_get("site_url")
+        .filter(|u| u.starts_with("http"))
+        .unwrap_or_else(|| format!("https://{}", site.hostname));
+    let admin_url = format!(
+        "{}/{}",
+        base.trim_end_matches('/'),
+        store.setting_get_or("admin_slug", "admin")
+    );
+
+    SiteUsage {
+        site_id: site.id,
+        views: overview.total_views,
+        visitors: overview.unique_visitors,
+        storage_bytes,
+        storage: crate::health::human_bytes(storage_bytes),
+        emails_sent,
+        emails_failed: store.mta_queue_count(Some("failed")),
+        orders: store.order_count(),
+        revenue: store.order_revenue_by_period(PERIOD_DAYS),
+        currency: store.setting_get_or("commerce_currency", "USD"),
+        admin_url,
+    }
+}
+
+pub fn totals(usage: &[SiteUsage]) -> UsageTotals {
+    let mut t = UsageTotals::default();
+    for u in usage {
+        t.views += u.views;
+        t.visitors += u.visitors;
+        t.storage_bytes += u.storage_bytes;
+        t.emails_sent += u.emails_sent;
+        t.emails_failed += u.emails_failed;
+        t.orders += u.orders;
+        if u.revenue > 0.0 {
+            *t.revenue.entry(u.currency.clone()).or_insert(0.0) += u.revenue;
+        }
+    }
+    t.storage = crate::health::human_bytes(t.storage_bytes);
+    t
+}
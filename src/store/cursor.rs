@@ -0,0 +1,110 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rusqlite::types::ToSql;
+use rusqlite::Row;
+
+use crate::db::DbPool;
+
+// ── Keyset pagination ───────────────────────────────────
+//
+// Offset paging makes the database walk past every skipped row, which gets
+// slow on lists with tens of thousands of entries. The `*_keyset` Store
+// methods instead continue after the last row of the previous page: rows
+// sort by a key column (newest first) with the id as a tiebreak, and a page
+// asks for whatever sorts after the cursor. Rows with no key (drafts that
+// were never published) sort last, as they do in the offset lists.
+
+/// Where the next page starts: the sort key and id of the last row shown.
+/// An empty key stands for NULL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub key: String,
+    pub id: i64,
+}
+
+impl Cursor {
+    /// Opaque form for URLs and API responses
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}|{}", self.id, self.key))
+    }
+
+    pub fn decode(s: &str) -> Option<Cursor> {
+        let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(s.trim()).ok()?).ok()?;
+        let (id, key) = raw.split_once('|')?;
+        Some(Cursor {
+            key: key.to_string(),
+            id: id.parse().ok()?,
+        })
+    }
+}
+
+/// One keyset page from `table`, ordered by `col` then id, both descending.
+/// `filters` are column = value conditions. Returns the rows and, when
+/// there are more, the cursor for the next page.
+pub fn sqlite_page<T>(
+    pool: &DbPool,
+    table: &str,
+    col: &str,
+    filters: Vec<(&str, Box<dyn ToSql>)>,
+    after: Option<&Cursor>,
+    limit: i64,
+    from_row: impl Fn(&Row) -> rusqlite::Result<T>,
+) -> (Vec<T>, Option<Cursor>) {
+    let conn = match pool.get() {
+        Ok(c) => c,
+        Err(_) => return (vec![], None),
+    };
+    let limit = limit.max(1);
+
+    let mut sql = format!("SELECT *, {col} AS cursor_key FROM {table} WHERE 1=1");
+    let mut params_vec: Vec<Box<dyn ToSql>> = Vec::new();
+    for (column, value) in filters {
+        params_vec.push(value);
+        sql.push_str(&format!(" AND {} = ?{}", column, params_vec.len()));
+    }
+    if let Some(c) = after {
+        let id = params_vec.len() + 1;
+        params_vec.push(Box::new(c.id));
+        if c.key.is_empty() {
+            sql.push_str(&format!(" AND {col} IS NULL AND id < ?{id}"));
+        } else {
+            let key = params_vec.len() + 1;
+            params_vec.push(Box::new(c.key.clone()));
+            sql.push_str(&format!(
+                " AND ({col} < ?{key} OR ({col} = ?{key} AND id < ?{id}) OR {col} IS NULL)"
+            ));
+        }
+    }
+    // One extra row tells whether there's a next page
+    params_vec.push(Box::new(limit + 1));
+    sql.push_str(&format!(
+        " ORDER BY {col} DESC, id DESC LIMIT ?{}",
+        params_vec.len()
+    ));
+
+    let mut stmt = match conn.prepare(&sql) {
+        Ok(s) => s,
+        Err(_) => return (vec![], None),
+    };
+    let params_refs: Vec<&dyn ToSql> = params_vec.iter().map(|p| p.as_ref()).collect();
+    let mut rows: Vec<(T, Cursor)> = stmt
+        .query_map(params_refs.as_slice(), |row| {
+            let cursor = Cursor {
+                key: row
+                    .get::<_, Option<String>>("cursor_key")?
+                    .unwrap_or_default(),
+                id: row.get("id")?,
+            };
+            Ok((from_row(row)?, cursor))
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default();
+
+    let more = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+    let next = if more {
+        rows.last().map(|(_, c)| c.clone())
+    } else {
+        None
+    };
+    (rows.into_iter().map(|(item, _)| item).collect(), next)
+}
@@ -2,23 +2,39 @@ use std::collections::HashMap;
 
 use chrono::NaiveDateTime;
 
+use crate::models::ai_usage::{AiUsage, AiUsageTotal};
 use crate::models::analytics::{
-    CountEntry, DailyCount, FlowNode, OverviewStats, StreamEntry, TagRelation,
+    CampaignEntry, CountEntry, DailyCount, EngagementStats, FlowNode, OutboundEntry, OverviewStats,
+    PathStats, SearchQueryStat, StreamEntry, TagRelation, Utm,
 };
+use crate::models::api_key::ApiKey;
 use crate::models::audit::AuditEntry;
 use crate::models::category::{Category, CategoryForm};
 use crate::models::comment::{Comment, CommentForm};
-use crate::models::design::{Design, DesignTemplate};
-use crate::models::firewall::{FwBan, FwEvent};
-use crate::models::import::Import;
+use crate::models::design::{Design, DesignTemplate, DesignTemplateVersion, MAX_DESIGN_DEPTH};
+use crate::models::embedding::Embedding;
+use crate::models::experiment::VariantStat;
+use crate::models::firewall::{FwBan, FwEvent, FwRule};
+use crate::models::goal::GoalStat;
+use crate::models::import::{Import, ImportCreated};
 use crate::models::order::{DownloadToken, License, Order};
 use crate::models::passkey::UserPasskey;
+use crate::models::ping_log::PingLogEntry;
 use crate::models::portfolio::{PortfolioForm, PortfolioItem};
 use crate::models::post::{Post, PostForm};
+use crate::models::redirect::{NotFoundEntry, NotFoundHit, RedirectRule};
+use crate::models::role::Role;
 use crate::models::search::SearchResult;
+use crate::models::seo_suggestion::SeoSuggestion;
 use crate::models::tag::{Tag, TagForm};
 use crate::models::user::User;
+use crate::models::widget::Widget;
 
+use crate::render::page_cache;
+use crate::seo::sitemap;
+use cursor::Cursor;
+
+pub mod cursor;
 pub mod mongo;
 pub mod sqlite;
 
@@ -77,6 +93,15 @@ pub trait Store: Send + Sync {
         email: &str,
         avatar: &str,
     ) -> Result<(), String>;
+    /// Public author page fields
+    fn user_update_author_profile(
+        &self,
+        id: i64,
+        author_slug: &str,
+        bio: &str,
+        website: &str,
+        social_links: &str,
+    ) -> Result<(), String>;
     fn user_update_role(&self, id: i64, role: &str) -> Result<(), String>;
     fn user_update_password(&self, id: i64, password_hash: &str) -> Result<(), String>;
     fn user_update_avatar(&self, id: i64, avatar: &str) -> Result<(), String>;
@@ -90,14 +115,24 @@ pub trait Store: Send + Sync {
     ) -> Result<(), String>;
     fn user_lock(&self, id: i64) -> Result<(), String>;
     fn user_unlock(&self, id: i64) -> Result<(), String>;
+    /// Set an account's status ("active", "pending", "invited", "suspended", "locked").
+    fn user_set_status(&self, id: i64, status: &str) -> Result<(), String>;
     fn user_delete(&self, id: i64) -> Result<(), String>;
     fn user_update_auth_method(&self, id: i64, method: &str, fallback: &str) -> Result<(), String>;
     fn user_set_force_password_change(&self, id: i64, force: bool) -> Result<(), String>;
+    fn user_set_passwordless(&self, id: i64, passwordless: bool) -> Result<(), String>;
+    fn user_set_mfa_method(&self, id: i64, method: &str) -> Result<(), String>;
 
     // ── Posts ────────────────────────────────────────────────────────
     fn post_find_by_id(&self, id: i64) -> Option<Post>;
     fn post_find_by_slug(&self, slug: &str) -> Option<Post>;
     fn post_list(&self, status: Option<&str>, limit: i64, offset: i64) -> Vec<Post>;
+    /// Up to `limit` posts that moved to `status` after status change
+    /// `since_seq`, in the order they did, or the latest to without one.
+    /// Each comes with its change number, for polling consumers that page
+    /// forward through status changes.
+    fn post_list_since(&self, status: &str, since_seq: Option<i64>, limit: i64)
+        -> Vec<(i64, Post)>;
     fn post_count(&self, status: Option<&str>) -> i64;
     fn post_create(&self, form: &PostForm) -> Result<i64, String>;
     fn post_update(&self, id: i64, form: &PostForm) -> Result<(), String>;
@@ -113,6 +148,44 @@ pub trait Store: Send + Sync {
     fn post_count_by_category(&self, category_id: i64) -> i64;
     fn post_by_tag(&self, tag_id: i64, limit: i64, offset: i64) -> Vec<Post>;
     fn post_count_by_tag(&self, tag_id: i64) -> i64;
+    /// Published posts in a translation group
+    fn post_translations(&self, group: &str) -> Vec<Post>;
+    /// Published posts with episode audio, newest first
+    fn post_episodes(&self, limit: i64) -> Vec<Post>;
+    /// Published posts credited to a user, newest first. With
+    /// `include_unattributed`, posts without an author are included.
+    fn post_by_author(
+        &self,
+        user_id: i64,
+        include_unattributed: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<Post>;
+    fn post_count_by_author(&self, user_id: i64, include_unattributed: bool) -> i64;
+    fn post_set_author(&self, id: i64, user_id: Option<i64>) -> Result<(), String>;
+
+    /// Posts credited to `user_id` in any status, or only in `status`.
+    /// Backs the admin list for users who only see their own posts.
+    fn post_list_by_owner(
+        &self,
+        user_id: i64,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<Post>;
+    fn post_count_by_owner(&self, user_id: i64, status: Option<&str>) -> i64;
+    /// Keyset page of `post_list`, or of `post_list_by_owner` with an owner.
+    /// Returns the posts and the cursor for the next page, if any.
+    fn post_list_keyset(
+        &self,
+        owner: Option<i64>,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Post>, Option<Cursor>);
+
+    /// Credit every post of `from` to `to`; returns how many moved.
+    fn post_reassign_author(&self, from: i64, to: i64) -> Result<usize, String>;
 
     // ── Portfolio ───────────────────────────────────────────────────
     fn portfolio_find_by_id(&self, id: i64) -> Option<PortfolioItem>;
@@ -143,12 +216,40 @@ pub trait Store: Send + Sync {
     // ── Comments ────────────────────────────────────────────────────
     fn comment_find_by_id(&self, id: i64) -> Option<Comment>;
     fn comment_list(&self, status: Option<&str>, limit: i64, offset: i64) -> Vec<Comment>;
+    /// Like `post_list_since`; `None` matches every status.
+    fn comment_list_since(
+        &self,
+        status: Option<&str>,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, Comment)>;
     fn comment_for_post(&self, post_id: i64, content_type: &str) -> Vec<Comment>;
     fn comment_count(&self, status: Option<&str>) -> i64;
+    /// Keyset page of `comment_list`
+    fn comment_list_keyset(
+        &self,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Comment>, Option<Cursor>);
     fn comment_create(&self, form: &CommentForm) -> Result<i64, String>;
     fn comment_update_status(&self, id: i64, status: &str) -> Result<(), String>;
     fn comment_set_parent(&self, id: i64, parent_id: Option<i64>) -> Result<(), String>;
+    /// Link a comment to the member account that wrote it.
+    fn comment_set_user(&self, id: i64, user_id: i64) -> Result<(), String>;
     fn comment_delete(&self, id: i64) -> Result<(), String>;
+    /// Latest comments, spam excluded, on posts credited to `author_id`.
+    fn comment_list_on_author_posts(&self, author_id: i64, limit: i64) -> Vec<Comment>;
+    /// Comments by a member account or under its email address, newest first.
+    fn comment_list_by_author(&self, user_id: i64, email: &str) -> Vec<Comment>;
+    /// Rename the author of those comments to `name` and drop their email and
+    /// account link. Returns how many comments changed.
+    fn comment_anonymize_author(
+        &self,
+        user_id: i64,
+        email: &str,
+        name: &str,
+    ) -> Result<usize, String>;
 
     // ── Categories ──────────────────────────────────────────────────
     fn category_find_by_id(&self, id: i64) -> Option<Category>;
@@ -208,12 +309,55 @@ pub trait Store: Send + Sync {
         layout_html: &str,
         style_css: &str,
     ) -> Result<(), String>;
+    fn design_update_custom_code(
+        &self,
+        id: i64,
+        custom_css: &str,
+        head_scripts: &str,
+        footer_scripts: &str,
+    ) -> Result<(), String>;
+    /// Make `id` a child of `parent_id` (None detaches it). Callers check
+    /// for cycles first; see `designs::inheritance::validate_parent`.
+    fn design_set_parent(&self, id: i64, parent_id: Option<i64>) -> Result<(), String>;
     fn design_duplicate(&self, id: i64, new_name: &str) -> Result<i64, String>;
     fn design_delete(&self, id: i64) -> Result<(), String>;
+    /// The design followed by its parent, grandparent and so on. Stops at
+    /// a missing parent, a cycle or `MAX_DESIGN_DEPTH`.
+    fn design_lineage(&self, design: Design) -> Vec<Design> {
+        let mut chain = vec![design];
+        while chain.len() < MAX_DESIGN_DEPTH {
+            let parent = match chain.last().and_then(|d| d.parent_id) {
+                Some(pid) if !chain.iter().any(|d| d.id == pid) => self.design_find_by_id(pid),
+                _ => None,
+            };
+            match parent {
+                Some(p) => chain.push(p),
+                None => break,
+            }
+        }
+        chain
+    }
 
     // ── Design Templates ────────────────────────────────────────────
     fn design_template_for_design(&self, design_id: i64) -> Vec<DesignTemplate>;
-    fn design_template_get(&self, design_id: i64, template_type: &str) -> Option<DesignTemplate>;
+    /// The template a design itself defines for `template_type`
+    fn design_template_get_own(
+        &self,
+        design_id: i64,
+        template_type: &str,
+    ) -> Option<DesignTemplate>;
+    /// The template used for `template_type`: the design's own, or the
+    /// nearest ancestor's when the design doesn't override it.
+    fn design_template_get(&self, design_id: i64, template_type: &str) -> Option<DesignTemplate> {
+        if let Some(t) = self.design_template_get_own(design_id, template_type) {
+            return Some(t);
+        }
+        let design = self.design_find_by_id(design_id)?;
+        self.design_lineage(design)
+            .iter()
+            .skip(1)
+            .find_map(|d| self.design_template_get_own(d.id, template_type))
+    }
     fn design_template_upsert(
         &self,
         design_id: i64,
@@ -229,6 +373,87 @@ pub trait Store: Send + Sync {
         style_css: &str,
         grapesjs_data: &str,
     ) -> Result<(), String>;
+    /// Saved versions of a design's template, newest first
+    fn design_template_version_list(
+        &self,
+        design_id: i64,
+        template_type: &str,
+    ) -> Vec<DesignTemplateVersion>;
+    fn design_template_version_get(&self, id: i64) -> Option<DesignTemplateVersion>;
+    /// Record a version (its id is ignored) and prune the oldest ones.
+    /// Returns the new id.
+    fn design_template_version_create(
+        &self,
+        version: &DesignTemplateVersion,
+    ) -> Result<i64, String>;
+    /// Save a template from the editor and record the new state in its
+    /// history. The state from before the first recorded edit is kept too,
+    /// so it can be restored. `user` is the editor's (id, display name).
+    fn design_template_save(
+        &self,
+        design_id: i64,
+        template_type: &str,
+        layout_html: &str,
+        style_css: &str,
+        grapesjs_data: &str,
+        user: Option<(i64, &str)>,
+    ) -> Result<(), String> {
+        let history = self.design_template_version_list(design_id, template_type);
+        let version = |layout: &str, css: &str, grapesjs: &str, user: Option<(i64, &str)>| {
+            DesignTemplateVersion {
+                id: 0,
+                design_id,
+                template_type: template_type.to_string(),
+                layout_html: layout.to_string(),
+                style_css: css.to_string(),
+                grapesjs_data: grapesjs.to_string(),
+                user_id: user.map(|(id, _)| id),
+                user_name: user.map(|(_, name)| name.to_string()).unwrap_or_default(),
+                created_at: String::new(),
+            }
+        };
+        if history.is_empty() {
+            if let Some(current) = self.design_template_get_own(design_id, template_type) {
+                self.design_template_version_create(&version(
+                    &current.layout_html,
+                    &current.style_css,
+                    &current.grapesjs_data,
+                    None,
+                ))?;
+            }
+        }
+        self.design_template_upsert_full(
+            design_id,
+            template_type,
+            layout_html,
+            style_css,
+            grapesjs_data,
+        )?;
+        let unchanged = self
+            .design_template_version_list(design_id, template_type)
+            .first()
+            .is_some_and(|v| {
+                v.layout_html == layout_html
+                    && v.style_css == style_css
+                    && v.grapesjs_data == grapesjs_data
+            });
+        if !unchanged {
+            self.design_template_version_create(&version(
+                layout_html,
+                style_css,
+                grapesjs_data,
+                user,
+            ))?;
+        }
+        Ok(())
+    }
+
+    // ── Widgets ─────────────────────────────────────────────────────
+    /// A design's widgets, by area then position.
+    fn widget_list(&self, design_id: i64) -> Vec<Widget>;
+    /// Insert (id 0) or update a widget. Returns its id.
+    fn widget_save(&self, widget: &Widget) -> Result<i64, String>;
+    fn widget_delete(&self, id: i64) -> Result<(), String>;
 
     // ── Audit Log ───────────────────────────────────────────────────
     fn audit_log(
@@ -256,9 +481,20 @@ pub trait Store: Send + Sync {
         entity_filter: Option<&str>,
         user_filter: Option<i64>,
     ) -> i64;
+    /// Keyset page of `audit_list`
+    fn audit_list_keyset(
+        &self,
+        action_filter: Option<&str>,
+        entity_filter: Option<&str>,
+        user_filter: Option<i64>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<AuditEntry>, Option<Cursor>);
     fn audit_distinct_actions(&self) -> Vec<String>;
     fn audit_distinct_entity_types(&self) -> Vec<String>;
     fn audit_cleanup(&self, max_age_days: i64) -> Result<usize, String>;
+    /// Entries newer than `after_id`, oldest first (SIEM forwarding).
+    fn audit_list_after(&self, after_id: i64, limit: i64) -> Vec<AuditEntry>;
 
     // ── Firewall: Bans ──────────────────────────────────────────────
     fn fw_is_banned(&self, ip: &str) -> bool;
@@ -271,6 +507,8 @@ pub trait Store: Send + Sync {
         country: Option<&str>,
         user_agent: Option<&str>,
     ) -> Result<i64, String>;
+    /// Ban for exactly `duration` ("1h", "7d", "permanent", ...). Automatic
+    /// bans go through `models::firewall::auto_ban` to be escalated.
     fn fw_ban_create_with_duration(
         &self,
         ip: &str,
@@ -280,8 +518,14 @@ pub trait Store: Send + Sync {
         country: Option<&str>,
         user_agent: Option<&str>,
     ) -> Result<i64, String>;
+    /// Count an offense for `ip`, restarting after `window_days` without one.
+    /// Returns the offense number.
+    fn fw_offense_record(&self, ip: &str, window_days: i64) -> i64;
+    fn fw_offense_count(&self, ip: &str) -> i64;
     fn fw_unban(&self, ip: &str) -> Result<usize, String>;
     fn fw_unban_by_id(&self, id: i64) -> Result<usize, String>;
+    /// Lift every active ban. Returns how many were lifted.
+    fn fw_unban_all(&self) -> Result<usize, String>;
     fn fw_active_bans(&self, limit: i64, offset: i64) -> Vec<FwBan>;
     fn fw_active_count(&self) -> i64;
     fn fw_all_bans(&self, limit: i64, offset: i64) -> Vec<FwBan>;
@@ -303,6 +547,50 @@ pub trait Store: Send + Sync {
     fn fw_event_count_for_ip_since(&self, ip: &str, event_type: &str, minutes: i64) -> i64;
     fn fw_event_top_ips(&self, limit: i64) -> Vec<(String, i64)>;
     fn fw_event_counts_by_type(&self) -> Vec<(String, i64)>;
+    /// Events newer than `after_id`, oldest first (SIEM forwarding).
+    fn fw_event_list_after(&self, after_id: i64, limit: i64) -> Vec<FwEvent>;
+    fn fw_event_cleanup(&self, max_age_days: i64) -> Result<usize, String>;
+
+    // ── Firewall: Custom rules ──────────────────────────────────────
+    /// All rules, ordered by priority.
+    fn fw_rule_list(&self) -> Vec<FwRule>;
+    /// Insert (id 0) or update a rule. Returns its id.
+    fn fw_rule_save(&self, rule: &FwRule) -> Result<i64, String>;
+    fn fw_rule_delete(&self, id: i64) -> Result<(), String>;
+    fn fw_rule_hit(&self, id: i64);
+
+    // ── Redirects ───────────────────────────────────────────────────
+    /// All redirects, ordered by source path.
+    fn redirect_list(&self) -> Vec<RedirectRule>;
+    /// Insert (id 0) or update a redirect. Returns its id.
+    fn redirect_save(&self, rule: &RedirectRule) -> Result<i64, String>;
+    fn redirect_delete(&self, id: i64) -> Result<(), String>;
+    fn redirect_hit(&self, id: i64);
+    /// Count a public 404 for `path`.
+    fn not_found_record(&self, path: &str, referrer: Option<&str>) -> Result<(), String>;
+    /// Write buffered 404 counts, keeping at most `NOT_FOUND_MAX_ROWS` paths.
+    fn not_found_record_batch(&self, hits: &[NotFoundHit]) -> Result<(), String>;
+    /// Most-hit 404 paths first.
+    fn not_found_top(&self, limit: i64) -> Vec<NotFoundEntry>;
+    fn not_found_dismiss(&self, path: &str) -> Result<(), String>;
+    fn not_found_cleanup(&self, max_age_days: i64) -> Result<usize, String>;
+
+    // ── API keys ────────────────────────────────────────────────────
+    /// All keys, newest first.
+    fn api_key_list(&self) -> Vec<ApiKey>;
+    /// Look up a key by the SHA-256 hash of its value.
+    fn api_key_find_by_hash(&self, key_hash: &str) -> Option<ApiKey>;
+    /// Insert a key. Returns its id.
+    fn api_key_create(&self, key: &ApiKey) -> Result<i64, String>;
+    fn api_key_delete(&self, id: i64) -> Result<(), String>;
+    /// Record that a key was just used.
+    fn api_key_touch(&self, id: i64);
+
+    // ── Search engine pings ─────────────────────────────────────────
+    fn ping_log_add(&self, entry: &PingLogEntry) -> Result<i64, String>;
+    /// Newest pings first.
+    fn ping_log_recent(&self, limit: i64) -> Vec<PingLogEntry>;
+    fn ping_log_cleanup(&self, max_age_days: i64) -> Result<usize, String>;
 
     // ── Analytics ───────────────────────────────────────────────────
     fn analytics_record(
@@ -315,6 +603,7 @@ pub trait Store: Send + Sync {
         user_agent: Option<&str>,
         device_type: Option<&str>,
         browser: Option<&str>,
+        utm: &Utm,
     ) -> Result<(), String>;
     fn analytics_overview(&self, from: &str, to: &str) -> OverviewStats;
     fn analytics_flow_data(&self, from: &str, to: &str) -> Vec<FlowNode>;
@@ -323,7 +612,79 @@ pub trait Store: Send + Sync {
     fn analytics_calendar_data(&self, from: &str, to: &str) -> Vec<DailyCount>;
     fn analytics_top_portfolio(&self, from: &str, to: &str, limit: i64) -> Vec<CountEntry>;
     fn analytics_top_referrers(&self, from: &str, to: &str, limit: i64) -> Vec<CountEntry>;
+    /// Most viewed paths starting with `prefix` (e.g. "/journal/").
+    fn analytics_top_paths(
+        &self,
+        prefix: &str,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Vec<CountEntry>;
     fn analytics_tag_relations(&self) -> Vec<TagRelation>;
+    /// Views per UTM campaign, source and medium.
+    fn analytics_campaigns(&self, from: &str, to: &str, limit: i64) -> Vec<CampaignEntry>;
+    /// Views per value of one UTM dimension ("source", "medium", "campaign",
+    /// "term" or "content").
+    fn analytics_utm_breakdown(
+        &self,
+        dimension: &str,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Vec<CountEntry>;
+    /// Views, visitors and daily views of one page.
+    fn analytics_path_stats(&self, path: &str, from: &str, to: &str) -> PathStats;
+    /// Views of one page per "referrer" or per "country".
+    fn analytics_path_breakdown(
+        &self,
+        path: &str,
+        dimension: &str,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Vec<CountEntry>;
+    /// Record that a visitor completed a goal. Repeats by the same visitor on
+    /// the same day are ignored; returns whether this one was recorded.
+    fn goal_record(&self, goal: &str, ip_hash: &str, path: &str) -> Result<bool, String>;
+    /// Completions and distinct converting visitors per goal.
+    fn goal_stats(&self, from: &str, to: &str) -> Vec<GoalStat>;
+    /// Completions of one goal per day.
+    fn goal_daily(&self, goal: &str, from: &str, to: &str) -> Vec<DailyCount>;
+    /// Record a click on an external link.
+    fn outbound_click_record(&self, url: &str, ip_hash: &str) -> Result<(), String>;
+    /// Most clicked external links.
+    fn outbound_top_links(&self, from: &str, to: &str, limit: i64) -> Vec<OutboundEntry>;
+    /// Record a page's scroll depth (percent) and visible seconds from the
+    /// engagement beacon.
+    fn engagement_record(
+        &self,
+        path: &str,
+        ip_hash: &str,
+        scroll: i64,
+        seconds: i64,
+    ) -> Result<(), String>;
+    /// Average scroll depth, time on page and read-through of one path.
+    fn engagement_path_stats(&self, path: &str, from: &str, to: &str) -> EngagementStats;
+    /// Log a public site search; returns its id.
+    fn search_log_record(&self, query: &str, results: i64, ip_hash: &str) -> Result<i64, String>;
+    /// Record the result clicked from a logged search. Only the visitor who
+    /// searched can set it, and only once.
+    fn search_log_click(&self, id: i64, ip_hash: &str, path: &str) -> Result<(), String>;
+    /// Most frequent searches.
+    fn search_top_queries(&self, from: &str, to: &str, limit: i64) -> Vec<SearchQueryStat>;
+    /// Most frequent searches that found nothing.
+    fn search_zero_result_queries(&self, from: &str, to: &str, limit: i64) -> Vec<CountEntry>;
+    /// Record an experiment impression or click ("impression" | "click"),
+    /// at most once per visitor and variant.
+    fn experiment_event_record(
+        &self,
+        experiment: &str,
+        variant: &str,
+        kind: &str,
+        ip_hash: &str,
+    ) -> Result<(), String>;
+    /// Visitors shown each experiment variant, and how many of them clicked.
+    fn experiment_stats(&self, from: &str, to: &str) -> Vec<VariantStat>;
 
     // ── Orders ──────────────────────────────────────────────────────
     fn order_find_by_id(&self, id: i64) -> Option<Order>;
@@ -331,10 +692,24 @@ pub trait Store: Send + Sync {
     fn order_find_by_provider_order_id(&self, provider_order_id: &str) -> Option<Order>;
     fn order_list(&self, limit: i64, offset: i64) -> Vec<Order>;
     fn order_list_by_status(&self, status: &str, limit: i64, offset: i64) -> Vec<Order>;
+    /// Like `post_list_since`, for orders.
+    fn order_list_since(
+        &self,
+        status: &str,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, Order)>;
     fn order_list_by_email(&self, email: &str, limit: i64, offset: i64) -> Vec<Order>;
     fn order_list_by_portfolio(&self, portfolio_id: i64) -> Vec<Order>;
     fn order_count(&self) -> i64;
     fn order_count_by_status(&self, status: &str) -> i64;
+    /// Keyset page of `order_list`, or of `order_list_by_status`
+    fn order_list_keyset(
+        &self,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Order>, Option<Cursor>);
     fn order_total_revenue(&self) -> f64;
     fn order_revenue_by_period(&self, days: i64) -> f64;
     fn order_create(
@@ -419,6 +794,9 @@ pub trait Store: Send + Sync {
         skipped_count: i64,
         log: Option<&str>,
     ) -> Result<i64, String>;
+    fn import_find_by_id(&self, id: i64) -> Option<Import>;
+    fn import_set_created(&self, id: i64, created: &ImportCreated) -> Result<(), String>;
+    fn import_mark_rolled_back(&self, id: i64) -> Result<(), String>;
 
     // ── Search (FTS) ────────────────────────────────────────────────
     fn search_create_fts_table(&self) -> Result<(), String>;
@@ -457,8 +835,15 @@ pub trait Store: Send + Sync {
     }
     fn session_delete(&self, token: &str) -> Result<(), String>;
     fn session_delete_for_user(&self, user_id: i64) -> Result<(), String>;
+    /// A user's sessions (created_at, expires_at, ip_address, user_agent),
+    /// newest first. Tokens are never included.
+    fn session_list_for_user(&self, user_id: i64) -> Vec<serde_json::Value>;
     fn session_cleanup_expired(&self);
     fn session_count_recent_by_ip(&self, ip_hash: &str, minutes: i64) -> i64;
+    /// Put a session in sudo mode for `minutes` (0 or less ends it).
+    fn session_set_sudo(&self, token: &str, minutes: i64) -> Result<(), String>;
+    /// Whether the session is valid and still in sudo mode.
+    fn session_sudo_active(&self, token: &str) -> bool;
 
     // ── Magic links / password reset tokens ──────────────────────────
     fn magic_link_create(
@@ -475,8 +860,14 @@ pub trait Store: Send + Sync {
     fn like_remove(&self, portfolio_id: i64, ip_hash: &str) -> Result<(), String>;
 
     // ── Analytics pruning ───────────────────────────────────────────
+    /// Delete page views and the other analytics records (goals, outbound
+    /// clicks, searches, experiments, engagement) from before `before_date`
+    /// (YYYY-MM-DD). Returns the number of page views deleted.
     fn analytics_prune(&self, before_date: &str) -> Result<usize, String>;
     fn analytics_count(&self) -> i64;
+    /// Delete every analytics record made under one of these visitor hashes.
+    /// Returns the number of page views deleted.
+    fn analytics_forget_visitors(&self, ip_hashes: &[String]) -> Result<usize, String>;
 
     // ── Health / maintenance ──────────────────────────────────────────
     /// Return the database backend name: "sqlite" or "mongodb"
@@ -522,13 +913,14 @@ pub trait Store: Send + Sync {
     fn task_cleanup_analytics(&self, max_age_days: i64) -> Result<usize, String>;
 
     // ── Email queue (built-in MTA) ─────────────────────────────────
-    /// Push an email onto the retry queue.
+    /// Push an email onto the retry queue. An empty `reply_to` uses the site default.
     fn mta_queue_push(
         &self,
         to: &str,
         from: &str,
         subject: &str,
         body: &str,
+        reply_to: &str,
     ) -> Result<i64, String>;
 
     /// Fetch pending messages ready for delivery (status='pending', next_retry_at <= now).
@@ -552,11 +944,253 @@ pub trait Store: Send + Sync {
     /// Delete old queue entries older than `days`.
     fn mta_queue_cleanup(&self, days: u64) -> Result<u64, String>;
 
+    /// List queued messages (newest first), optionally filtered by status.
+    fn mta_queue_list(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<crate::mta::queue::QueuedEmail>;
+
+    /// Count queued messages, optionally filtered by status.
+    fn mta_queue_count(&self, status: Option<&str>) -> u64;
+
+    /// Fetch a single queued message by id.
+    fn mta_queue_get(&self, id: i64) -> Option<crate::mta::queue::QueuedEmail>;
+
+    /// Reset a message to pending with a fresh attempt budget (manual retry).
+    fn mta_queue_retry(&self, id: i64) -> Result<(), String>;
+
+    /// Delete a single queued message.
+    fn mta_queue_delete(&self, id: i64) -> Result<(), String>;
+
+    /// Sent-message counts per hour ("YYYY-MM-DD HH:00") over the last `hours` hours.
+    fn mta_queue_sent_hourly(&self, hours: i64) -> Vec<(String, u64)>;
+
+    /// Store a reply token for an outgoing comment notification (plus-addressed Reply-To).
+    fn mta_reply_token_create(
+        &self,
+        token: &str,
+        post_id: i64,
+        content_type: &str,
+        comment_id: i64,
+        email: &str,
+        expires_days: i64,
+    ) -> Result<(), String>;
+
+    /// Look up an unexpired reply token.
+    fn mta_reply_token_find(&self, token: &str) -> Option<crate::mta::inbound::ReplyToken>;
+
+    /// Delete an unexpired reply token, returning whether it was still there.
+    /// Each token carries a single reply.
+    fn mta_reply_token_consume(&self, token: &str) -> Result<bool, String>;
+
+    /// Delete expired reply tokens.
+    fn mta_reply_token_cleanup(&self) -> Result<usize, String>;
+
+    // ── Email provider quotas ───────────────────────────────────────
+    /// Count one delivered message against a provider's current hour and day buckets.
+    fn email_quota_record(&self, provider: &str) -> Result<(), String>;
+
+    /// Messages delivered by a provider in the current (hour, day).
+    fn email_quota_usage(&self, provider: &str) -> (u64, u64);
+
+    /// Delete quota buckets older than `days`.
+    fn email_quota_cleanup(&self, days: i64) -> Result<usize, String>;
+
+    // ── Signed tokens (magic links, resets, double opt-in) ──────────
+    /// Store a hashed single-use token for `purpose`.
+    fn signed_token_create(
+        &self,
+        token_hash: &str,
+        purpose: &str,
+        email: &str,
+        payload: &str,
+        expires_minutes: i64,
+    ) -> Result<(), String>;
+
+    /// Mark an unexpired, unused token as used and return it.
+    fn signed_token_consume(
+        &self,
+        token_hash: &str,
+        purpose: &str,
+    ) -> Result<crate::security::signed_token::SignedToken, String>;
+
+    /// Delete expired and used tokens.
+    fn signed_token_cleanup(&self) -> Result<usize, String>;
+
+    // ── Newsletter subscribers ──────────────────────────────────────
+    /// Add a pending subscriber (no-op if the address is already known).
+    fn newsletter_subscribe(&self, email: &str) -> Result<(), String>;
+
+    /// Mark a subscriber as confirmed.
+    fn newsletter_confirm(&self, email: &str) -> Result<(), String>;
+
+    /// Subscriber status ("pending", "confirmed"), if subscribed.
+    fn newsletter_status(&self, email: &str) -> Option<String>;
+
+    fn newsletter_count(&self, status: Option<&str>) -> i64;
+
+    // ── Comment subscriptions ───────────────────────────────────────
+    /// Add an unconfirmed subscription to a post's comments (no-op if it exists).
+    fn comment_subscription_create(
+        &self,
+        post_id: i64,
+        content_type: &str,
+        email: &str,
+    ) -> Result<(), String>;
+
+    fn comment_subscription_confirm(
+        &self,
+        post_id: i64,
+        content_type: &str,
+        email: &str,
+    ) -> Result<(), String>;
+
+    /// Confirmed subscriber emails for a post.
+    fn comment_subscribers(&self, post_id: i64, content_type: &str) -> Vec<String>;
+
+    /// Posts an address follows the comments of, as (post_id, content_type).
+    fn comment_subscription_list_by_email(&self, email: &str) -> Vec<(i64, String)>;
+
+    /// Remove an address from the newsletter and every comment subscription.
+    fn subscriber_forget(&self, email: &str) -> Result<(), String>;
+
+    // ── Roles ───────────────────────────────────────────────────────
+    /// Stored role definitions (custom roles and customised built-ins).
+    fn role_list(&self) -> Vec<Role>;
+
+    fn role_find(&self, slug: &str) -> Option<Role>;
+
+    /// Create or replace a role definition.
+    fn role_save(&self, slug: &str, name: &str, capabilities: &[String]) -> Result<(), String>;
+
+    fn role_delete(&self, slug: &str) -> Result<(), String>;
+
+    // ── Known login devices ─────────────────────────────────────────
+    /// Record a login from (ip_hash, device). Returns true when this user had
+    /// never signed in from that pair before.
+    fn login_device_touch(
+        &self,
+        user_id: i64,
+        ip_hash: &str,
+        device: &str,
+        location: &str,
+    ) -> Result<bool, String>;
+
+    fn login_device_count(&self, user_id: i64) -> i64;
+
+    /// Forget every known device for a user.
+    fn login_device_forget(&self, user_id: i64) -> Result<(), String>;
+
+    /// A user's known devices (ip_hash, device, location, first_seen,
+    /// last_seen), most recent first.
+    fn login_device_list(&self, user_id: i64) -> Vec<serde_json::Value>;
+
+    // ── Persistent rate limiting ────────────────────────────────────
+    /// Sliding-window check for `key`: drop attempts older than
+    /// `window_secs`, then record this one if fewer than `max_attempts`
+    /// remain. Returns whether the attempt is allowed.
+    fn rate_limit_hit(
+        &self,
+        key: &str,
+        max_attempts: u64,
+        window_secs: i64,
+    ) -> Result<bool, String>;
+
+    /// `rate_limit_hit` for request budgets: the same sliding window, also
+    /// reporting how much of it is left and when it frees up.
+    fn rate_limit_budget(
+        &self,
+        key: &str,
+        max_requests: u64,
+        window_secs: i64,
+    ) -> Result<crate::rate_limit::Budget, String>;
+
+    /// Delete recorded attempts older than `max_age_secs`.
+    fn rate_limit_cleanup(&self, max_age_secs: i64) -> Result<usize, String>;
+
+    // ── Embeddings ──────────────────────────────────────────────────
+    /// Store the vector for an item, replacing any previous one.
+    fn embedding_upsert(&self, embedding: &Embedding) -> Result<(), String>;
+
+    fn embedding_remove(&self, item_type: &str, item_id: i64);
+
+    /// Every stored vector computed with `model`.
+    fn embedding_list(&self, model: &str) -> Vec<Embedding>;
+
+    /// Delete vectors from any model other than `keep_model`.
+    fn embedding_prune(&self, keep_model: &str) -> Result<usize, String>;
+
+    // ── SEO suggestions ─────────────────────────────────────────────
+    fn seo_suggestion_create(&self, suggestion: &SeoSuggestion) -> Result<i64, String>;
+    fn seo_suggestion_get(&self, id: i64) -> Option<SeoSuggestion>;
+
+    /// Pending suggestions, oldest first; for one item when `content` is given.
+    fn seo_suggestion_pending(&self, content: Option<(&str, i64)>) -> Vec<SeoSuggestion>;
+    fn seo_suggestion_set_status(&self, id: i64, status: &str) -> Result<(), String>;
+
+    /// Whether `field` of an item has ever had a suggestion, in any status.
+    fn seo_suggestion_exists(&self, content_type: &str, content_id: i64, field: &str) -> bool;
+
+    /// Drop an item's pending suggestions (before asking for new ones or when
+    /// the item is deleted).
+    fn seo_suggestion_clear(&self, content_type: &str, content_id: i64) -> Result<(), String>;
+
+    // ── AI usage ────────────────────────────────────────────────────
+    fn ai_usage_record(&self, usage: &AiUsage) -> Result<(), String>;
+
+    /// Usage summed per (provider, model, feature) for calls made in
+    /// [since, until), both "YYYY-MM-DD HH:MM:SS" UTC.
+    fn ai_usage_totals(&self, since: &str, until: &str) -> Vec<AiUsageTotal>;
+
+    /// Most recent calls, newest first.
+    fn ai_usage_recent(&self, limit: i64) -> Vec<AiUsage>;
+
+    /// Delete records older than `max_age_days`.
+    fn ai_usage_cleanup(&self, max_age_days: i64) -> Result<usize, String>;
+
+    // ── Upload ownership ────────────────────────────────────────────
+    /// Record who uploaded `path` (relative to the uploads directory).
+    fn media_set_owner(&self, path: &str, user_id: i64) -> Result<(), String>;
+
+    /// Paths uploaded by `user_id`.
+    fn media_list_by_owner(&self, user_id: i64) -> Vec<String>;
+
+    fn media_remove_owner(&self, path: &str) -> Result<(), String>;
+
+    /// Move every upload of `from` to `to`; returns how many moved.
+    fn media_reassign_owner(&self, from: i64, to: i64) -> Result<usize, String>;
+
+    // ── Image optimization queue ────────────────────────────────────
+    /// Queue the original at `source` for optimization into `path`.
+    fn media_job_push(&self, path: &str, source: &str) -> Result<(), String>;
+
+    /// Pending jobs, oldest first.
+    fn media_job_pending(&self, limit: i64) -> Vec<crate::images::queue::MediaJob>;
+
+    fn media_job_get(&self, path: &str) -> Option<crate::images::queue::MediaJob>;
+
+    /// Every queued or failed job.
+    fn media_job_list(&self) -> Vec<crate::images::queue::MediaJob>;
+
+    /// Record a failed attempt; `give_up` marks the job "failed" for good.
+    fn media_job_fail(&self, path: &str, error: &str, give_up: bool) -> Result<(), String>;
+
+    fn media_job_delete(&self, path: &str) -> Result<(), String>;
+
     // ── Raw execute (escape hatch for migrations/health tools) ──────
     fn raw_execute(&self, sql: &str) -> Result<usize, String>;
     fn raw_query_i64(&self, sql: &str) -> Result<i64, String>;
 }
 
+/// After a write to posts or portfolio items: drop cached pages and mark
+/// that part of the sitemap for rebuilding. Called by both backends.
+pub(crate) fn content_changed(section: sitemap::Section) {
+    page_cache::invalidate();
+    sitemap::changed(section);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -733,6 +1367,17 @@ mod tests {
             published_at: None,
             category_ids: None,
             tag_ids: None,
+            robots_noindex: None,
+            robots_nofollow: None,
+            canonical_url: None,
+            schema_type: None,
+            focus_keyword: None,
+            language: None,
+            translation_group: None,
+            audio_file: None,
+            audio_duration: None,
+            audio_bytes: None,
+            template: None,
         };
         let id = s.post_create(&form).unwrap();
         assert!(id > 0);
@@ -779,6 +1424,12 @@ mod tests {
             published_at: None,
             category_ids: None,
             tag_ids: None,
+            robots_noindex: None,
+            robots_nofollow: None,
+            canonical_url: None,
+            schema_type: None,
+            focus_keyword: None,
+            template: None,
         };
         let id = s.portfolio_create(&form).unwrap();
         assert!(id > 0);
@@ -874,6 +1525,17 @@ mod tests {
                 published_at: None,
                 category_ids: None,
                 tag_ids: None,
+                robots_noindex: None,
+                robots_nofollow: None,
+                canonical_url: None,
+                schema_type: None,
+                focus_keyword: None,
+                language: None,
+                translation_group: None,
+                audio_file: None,
+                audio_duration: None,
+                audio_bytes: None,
+                template: None,
             })
             .unwrap();
 
@@ -961,6 +1623,12 @@ mod tests {
                 published_at: None,
                 category_ids: None,
                 tag_ids: None,
+                robots_noindex: None,
+                robots_nofollow: None,
+                canonical_url: None,
+                schema_type: None,
+                focus_keyword: None,
+                template: None,
             })
             .unwrap();
 
@@ -1012,6 +1680,12 @@ mod tests {
             published_at: None,
             category_ids: None,
             tag_ids: None,
+            robots_noindex: None,
+            robots_nofollow: None,
+            canonical_url: None,
+            schema_type: None,
+            focus_keyword: None,
+            template: None,
         };
         s.portfolio_create(&form).unwrap()
     }
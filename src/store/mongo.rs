@@ -6,28 +6,49 @@ use mongodb::options::ClientOptions;
 use mongodb::sync::Client;
 use mongodb::sync::Database;
 
+use crate::models::ai_usage::{AiUsage, AiUsageTotal};
 use crate::models::analytics::{
-    CountEntry, DailyCount, FlowNode, OverviewStats, StreamEntry, TagRelation,
+    CampaignEntry, CountEntry, DailyCount, EngagementStats, FlowNode, OutboundEntry, OverviewStats,
+    PathStats, SearchQueryStat, StreamEntry, TagRelation, Utm,
 };
+use crate::models::api_key::ApiKey;
 use crate::models::audit::AuditEntry;
 use crate::models::category::{Category, CategoryForm};
 use crate::models::comment::{Comment, CommentForm};
-use crate::models::design::{Design, DesignTemplate};
-use crate::models::firewall::{FwBan, FwEvent};
-use crate::models::import::Import;
+use crate::models::design::{Design, DesignTemplate, DesignTemplateVersion, MAX_TEMPLATE_VERSIONS};
+use crate::models::embedding::Embedding;
+use crate::models::experiment::VariantStat;
+use crate::models::firewall::{self, FwBan, FwEvent, FwRule, FwRuleCache};
+use crate::models::goal::GoalStat;
+use crate::models::import::{Import, ImportCreated};
 use crate::models::order::{DownloadToken, License, Order};
 use crate::models::passkey::UserPasskey;
+use crate::models::ping_log::PingLogEntry;
 use crate::models::portfolio::{PortfolioForm, PortfolioItem};
 use crate::models::post::{Post, PostForm};
-use crate::models::search::SearchResult;
+use crate::models::redirect::{
+    self, NotFoundEntry, NotFoundHit, RedirectCache, RedirectRule, NOT_FOUND_MAX_ROWS,
+};
+use crate::models::role::{self, Role, RoleCache};
+use crate::models::search::{
+    query_terms, snippet, strip_html, substring_rank, SearchResult, MIN_SUBSTRING_LEN, TITLE_WEIGHT,
+};
+use crate::models::seo_suggestion::SeoSuggestion;
 use crate::models::tag::{Tag, TagForm};
 use crate::models::user::User;
+use crate::models::widget::Widget;
+use crate::render::page_cache;
+use crate::seo::sitemap::Section;
 
+use super::cursor::Cursor;
 use super::Store;
 
 /// MongoDB-backed implementation of the Store trait.
 pub struct MongoStore {
     db: Database,
+    roles: RoleCache,
+    fw_rules: FwRuleCache,
+    redirects: RedirectCache,
 }
 
 impl MongoStore {
@@ -36,7 +57,12 @@ impl MongoStore {
         let client_options = ClientOptions::parse(uri).map_err(|e| e.to_string())?;
         let client = Client::with_options(client_options).map_err(|e| e.to_string())?;
         let db = client.database(db_name);
-        Ok(Self { db })
+        Ok(Self {
+            db,
+            roles: RoleCache::default(),
+            fw_rules: FwRuleCache::default(),
+            redirects: RedirectCache::default(),
+        })
     }
 
     /// Test connectivity by pinging the server.
@@ -67,6 +93,55 @@ impl MongoStore {
         }
     }
 
+    /// Number the status change about to be made to document `id` in
+    /// `collection`, unless it already has `status`. Mirrors the SQLite
+    /// `status_seq` triggers.
+    fn bump_status_seq(&self, collection: &str, id: i64, status: &str) -> Result<(), String> {
+        let coll = self.db.collection::<Document>(collection);
+        let changes = coll
+            .count_documents(doc! { "id": id, "status": { "$ne": status } }, None)
+            .map_err(|e| e.to_string())?;
+        if changes > 0 {
+            let seq = self.next_id(&format!("{}_status_seq", collection))?;
+            coll.update_one(
+                doc! { "id": id },
+                doc! { "$set": { "status_seq": seq } },
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Documents in `collection` matching `filter` whose status changed
+    /// after `since_seq`, in the order they did, or the latest without it
+    fn list_status_since(
+        &self,
+        collection: &str,
+        filter: Document,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, Document)> {
+        let coll = self.db.collection::<Document>(collection);
+        let mut filter = filter;
+        if let Some(since) = since_seq {
+            filter.insert("status_seq", doc! { "$gt": since });
+        }
+        let order = if since_seq.is_some() { 1 } else { -1 };
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "status_seq": order })
+            .limit(limit)
+            .build();
+        let cursor = match coll.find(filter, opts) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| Some((d.get_i64("status_seq").ok()?, d)))
+            .collect()
+    }
+
     // ── Helper: get a setting value ──
     fn get_setting_doc(&self, key: &str) -> Option<String> {
         let coll = self.db.collection::<Document>("settings");
@@ -132,6 +207,14 @@ impl Store for MongoStore {
                 None,
             )
             .map_err(|e| e.to_string())?;
+        posts
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "published_at": -1, "id": -1 })
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
 
         let portfolio = self.db.collection::<Document>("portfolio");
         portfolio
@@ -190,6 +273,116 @@ impl Store for MongoStore {
             )
             .map_err(|e| e.to_string())?;
 
+        let fw_offenses = self.db.collection::<Document>("fw_offenses");
+        fw_offenses
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "ip": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let fw_rules = self.db.collection::<Document>("fw_rules");
+        fw_rules
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "priority": 1, "id": 1 })
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let media_owners = self.db.collection::<Document>("media_owners");
+        media_owners
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "path": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let media_jobs = self.db.collection::<Document>("media_jobs");
+        media_jobs
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "path": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let api_keys = self.db.collection::<Document>("api_keys");
+        api_keys
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "key_hash": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let redirects = self.db.collection::<Document>("redirects");
+        redirects
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "source": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let widgets = self.db.collection::<Document>("widgets");
+        widgets
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "design_id": 1, "area": 1, "sort_order": 1 })
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let not_found = self.db.collection::<Document>("not_found_log");
+        not_found
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "path": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
         let audit = self.db.collection::<Document>("audit_log");
         audit
             .create_index(
@@ -206,6 +399,145 @@ impl Store for MongoStore {
             )
             .map_err(|e| e.to_string())?;
 
+        let signed_tokens = self.db.collection::<Document>("signed_tokens");
+        signed_tokens
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "token_hash": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let subscribers = self.db.collection::<Document>("newsletter_subscribers");
+        subscribers
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "email": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let roles = self.db.collection::<Document>("roles");
+        roles
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "slug": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let login_devices = self.db.collection::<Document>("login_devices");
+        login_devices
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "user_id": 1, "ip_hash": 1, "device": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let rate_limit_hits = self.db.collection::<Document>("rate_limit_hits");
+        rate_limit_hits
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "key": 1, "hit_at": 1 })
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
+        let reply_tokens = self.db.collection::<Document>("mta_reply_tokens");
+        reply_tokens
+            .create_index(
+                IndexModel::builder()
+                    .keys(doc! { "token": 1 })
+                    .options(
+                        mongodb::options::IndexOptions::builder()
+                            .unique(true)
+                            .build(),
+                    )
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+
+        // Status change numbers for documents written before them take the
+        // document's id, and each counter starts above every id
+        for name in ["posts", "orders", "comments"] {
+            let coll = self.db.collection::<Document>(name);
+            let missing: Vec<i64> = coll
+                .find(doc! { "status_seq": { "$exists": false } }, None)
+                .map(|c| {
+                    c.filter_map(|r| r.ok())
+                        .filter_map(|d| d.get_i64("id").ok())
+                        .collect()
+                })
+                .unwrap_or_default();
+            for id in missing {
+                coll.update_one(
+                    doc! { "id": id },
+                    doc! { "$set": { "status_seq": id } },
+                    None,
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            let top = coll
+                .find_one(
+                    doc! {},
+                    mongodb::options::FindOneOptions::builder()
+                        .sort(doc! { "status_seq": -1 })
+                        .build(),
+                )
+                .ok()
+                .flatten()
+                .and_then(|d| d.get_i64("status_seq").ok())
+                .unwrap_or(0);
+            self.db
+                .collection::<Document>("_counters")
+                .update_one(
+                    doc! { "_id": format!("{}_status_seq", name) },
+                    doc! { "$max": { "seq": top } },
+                    mongodb::options::UpdateOptions::builder()
+                        .upsert(true)
+                        .build(),
+                )
+                .map_err(|e| e.to_string())?;
+            coll.create_index(
+                IndexModel::builder()
+                    .keys(doc! { "status": 1, "status_seq": 1 })
+                    .build(),
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        // Text index for search, rebuilt on startup like the SQLite FTS table
+        self.search_create_fts_table()?;
+        let _ = self.search_rebuild_index();
+
         Ok(())
     }
 
@@ -232,6 +564,19 @@ impl Store for MongoStore {
             self.setting_set("image_proxy_secret", &secret)?;
         }
 
+        // Seed the signed-token secret; $setOnInsert keeps the first one written
+        {
+            use rand::Rng;
+            let bytes: [u8; 32] = rand::thread_rng().gen();
+            let filter = doc! { "key": "signed_token_secret" };
+            let update = doc! { "$setOnInsert": { "key": "signed_token_secret", "value": hex::encode(bytes) } };
+            let opts = mongodb::options::UpdateOptions::builder()
+                .upsert(true)
+                .build();
+            coll.update_one(filter, update, opts)
+                .map_err(|e| e.to_string())?;
+        }
+
         // Backfill legal page content if empty
         for (key, value) in &defaults {
             if (*key == "privacy_policy_content" || *key == "terms_of_use_content")
@@ -305,6 +650,29 @@ impl Store for MongoStore {
                 .map_err(|e| e.to_string())?;
         }
 
+        // Ensure Masthead design exists (new databases get it here too)
+        let masthead_exists = designs
+            .count_documents(doc! { "slug": "masthead" }, None)
+            .unwrap_or(0)
+            > 0;
+        if !masthead_exists {
+            let id = self.next_id("designs")?;
+            designs
+                .insert_one(doc! {
+                    "id": id,
+                    "name": "Masthead",
+                    "slug": "masthead",
+                    "description": "A magazine-style journal theme with a front-page story, newspaper columns and bold headlines. Portfolio pages use the Oneguy layouts.",
+                    "layout_html": crate::render::MASTHEAD_SHELL_HTML,
+                    "style_css": crate::render::MASTHEAD_DESIGN_CSS,
+                    "thumbnail_path": Bson::Null,
+                    "is_active": false,
+                    "created_at": chrono::Utc::now().to_rfc3339(),
+                    "updated_at": chrono::Utc::now().to_rfc3339(),
+                }, None)
+                .map_err(|e| e.to_string())?;
+        }
+
         // Keep design CSS/HTML in sync with binary constants
         let _ = designs.update_one(
             doc! { "slug": "inkwell" },
@@ -322,6 +690,14 @@ impl Store for MongoStore {
             }},
             None,
         );
+        let _ = designs.update_one(
+            doc! { "slug": "masthead" },
+            doc! { "$set": {
+                "layout_html": crate::render::MASTHEAD_SHELL_HTML,
+                "style_css": crate::render::MASTHEAD_DESIGN_CSS,
+            }},
+            None,
+        );
 
         // Backfill Oneguy description if empty
         let _ = designs.update_one(
@@ -350,6 +726,7 @@ impl Store for MongoStore {
             opts,
         )
         .map_err(|e| e.to_string())?;
+        page_cache::settings_changed([key]);
         Ok(())
     }
 
@@ -392,6 +769,7 @@ impl Store for MongoStore {
         let coll = self.db.collection::<Document>("settings");
         coll.delete_one(doc! { "key": key }, None)
             .map_err(|e| e.to_string())?;
+        page_cache::settings_changed([key]);
         Ok(())
     }
 
@@ -521,6 +899,30 @@ impl Store for MongoStore {
         Ok(())
     }
 
+    fn user_update_author_profile(
+        &self,
+        id: i64,
+        author_slug: &str,
+        bio: &str,
+        website: &str,
+        social_links: &str,
+    ) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("users");
+        coll.update_one(
+            doc! { "id": id },
+            doc! { "$set": {
+                "author_slug": author_slug,
+                "bio": bio,
+                "website": website,
+                "social_links": social_links,
+                "updated_at": chrono::Utc::now().to_rfc3339(),
+            }},
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
     fn user_update_role(&self, id: i64, role: &str) -> Result<(), String> {
         let coll = self.db.collection::<Document>("users");
         coll.update_one(
@@ -611,14 +1013,25 @@ impl Store for MongoStore {
         Ok(())
     }
 
-    fn user_delete(&self, id: i64) -> Result<(), String> {
-        self.session_delete_for_user(id)?;
-        let users = self.db.collection::<Document>("users");
-        users
-            .delete_one(doc! { "id": id }, None)
-            .map_err(|e| e.to_string())?;
-        Ok(())
-    }
+    fn user_set_status(&self, id: i64, status: &str) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("users");
+        coll.update_one(
+            doc! { "id": id },
+            doc! { "$set": { "status": status, "updated_at": chrono::Utc::now().to_rfc3339() } },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn user_delete(&self, id: i64) -> Result<(), String> {
+        self.session_delete_for_user(id)?;
+        let users = self.db.collection::<Document>("users");
+        users
+            .delete_one(doc! { "id": id }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
 
     fn user_update_auth_method(&self, id: i64, method: &str, fallback: &str) -> Result<(), String> {
         let coll = self.db.collection::<Document>("users");
@@ -649,6 +1062,34 @@ impl Store for MongoStore {
         Ok(())
     }
 
+    fn user_set_passwordless(&self, id: i64, passwordless: bool) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("users");
+        coll.update_one(
+            doc! { "id": id },
+            doc! { "$set": {
+                "passwordless": passwordless,
+                "updated_at": chrono::Utc::now().to_rfc3339(),
+            }},
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn user_set_mfa_method(&self, id: i64, method: &str) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("users");
+        coll.update_one(
+            doc! { "id": id },
+            doc! { "$set": {
+                "mfa_method": method,
+                "updated_at": chrono::Utc::now().to_rfc3339(),
+            }},
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
     // ── Posts ────────────────────────────────────────────────────────
     // TODO: Full MongoDB implementations for posts, portfolio, comments,
     // categories, tags, designs, audit, firewall, analytics, orders,
@@ -686,6 +1127,17 @@ impl Store for MongoStore {
             .filter_map(|d| doc_to_post(&d))
             .collect()
     }
+    fn post_list_since(
+        &self,
+        status: &str,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, Post)> {
+        self.list_status_since("posts", doc! { "status": status }, since_seq, limit)
+            .into_iter()
+            .filter_map(|(seq, d)| Some((seq, doc_to_post(&d)?)))
+            .collect()
+    }
     fn post_count(&self, status: Option<&str>) -> i64 {
         let coll = self.db.collection::<Document>("posts");
         let filter = match status {
@@ -716,13 +1168,27 @@ impl Store for MongoStore {
                 "updated_at": ts,
                 "seo_score": -1_i32,
                 "seo_issues": "[]",
+                "robots_noindex": form.robots_noindex.unwrap_or(false),
+                "robots_nofollow": form.robots_nofollow.unwrap_or(false),
+                "canonical_url": form.canonical_url.as_deref(),
+                "schema_type": form.schema_type.as_deref(),
+                "focus_keyword": form.focus_keyword.as_deref(),
+                "language": form.language.as_deref(),
+                "translation_group": form.translation_group.as_deref(),
+                "audio_file": form.audio_file.as_deref(),
+                "audio_duration": form.audio_duration,
+                "audio_bytes": form.audio_bytes,
+                "template": form.template.as_deref(),
+                "status_seq": self.next_id("posts_status_seq")?,
             },
             None,
         )
         .map_err(|e| e.to_string())?;
+        super::content_changed(Section::Posts);
         Ok(id)
     }
     fn post_update(&self, id: i64, form: &PostForm) -> Result<(), String> {
+        self.bump_status_seq("posts", id, &form.status)?;
         let coll = self.db.collection::<Document>("posts");
         coll.update_one(
             doc! { "id": id },
@@ -737,17 +1203,30 @@ impl Store for MongoStore {
                 "meta_description": form.meta_description.as_deref(),
                 "status": &form.status,
                 "published_at": form.published_at.as_deref(),
+                "robots_noindex": form.robots_noindex.unwrap_or(false),
+                "robots_nofollow": form.robots_nofollow.unwrap_or(false),
+                "canonical_url": form.canonical_url.as_deref(),
+                "schema_type": form.schema_type.as_deref(),
+                "focus_keyword": form.focus_keyword.as_deref(),
+                "language": form.language.as_deref(),
+                "translation_group": form.translation_group.as_deref(),
+                "audio_file": form.audio_file.as_deref(),
+                "audio_duration": form.audio_duration,
+                "audio_bytes": form.audio_bytes,
+                "template": form.template.as_deref(),
                 "updated_at": chrono::Utc::now().to_rfc3339(),
             }},
             None,
         )
         .map_err(|e| e.to_string())?;
+        super::content_changed(Section::Posts);
         Ok(())
     }
     fn post_delete(&self, id: i64) -> Result<(), String> {
         let coll = self.db.collection::<Document>("posts");
         coll.delete_one(doc! { "id": id }, None)
             .map_err(|e| e.to_string())?;
+        super::content_changed(Section::Posts);
         Ok(())
     }
     fn post_prev_published(&self, published_at: &NaiveDateTime) -> Option<Post> {
@@ -779,6 +1258,7 @@ impl Store for MongoStore {
         doc_to_post(&d)
     }
     fn post_update_status(&self, id: i64, status: &str) -> Result<(), String> {
+        self.bump_status_seq("posts", id, status)?;
         let coll = self.db.collection::<Document>("posts");
         coll.update_one(
             doc! { "id": id },
@@ -786,6 +1266,7 @@ impl Store for MongoStore {
             None,
         )
         .map_err(|e| e.to_string())?;
+        super::content_changed(Section::Posts);
         Ok(())
     }
     fn post_update_seo_score(&self, id: i64, score: i32, issues_json: &str) -> Result<(), String> {
@@ -971,6 +1452,151 @@ impl Store for MongoStore {
             .unwrap_or(0) as i64
     }
 
+    fn post_translations(&self, group: &str) -> Vec<Post> {
+        let coll = self.db.collection::<Document>("posts");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "id": 1 })
+            .build();
+        let cursor = match coll.find(
+            doc! { "translation_group": group, "status": "published" },
+            opts,
+        ) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_post(&d))
+            .collect()
+    }
+
+    fn post_episodes(&self, limit: i64) -> Vec<Post> {
+        let coll = self.db.collection::<Document>("posts");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "published_at": -1 })
+            .limit(limit)
+            .build();
+        let cursor = match coll.find(
+            doc! { "status": "published", "audio_file": { "$nin": [null, ""] } },
+            opts,
+        ) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_post(&d))
+            .collect()
+    }
+
+    fn post_by_author(
+        &self,
+        user_id: i64,
+        include_unattributed: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<Post> {
+        let coll = self.db.collection::<Document>("posts");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "published_at": -1 })
+            .skip(offset as u64)
+            .limit(limit)
+            .build();
+        let cursor = match coll.find(author_filter(user_id, include_unattributed), opts) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_post(&d))
+            .collect()
+    }
+
+    fn post_count_by_author(&self, user_id: i64, include_unattributed: bool) -> i64 {
+        let coll = self.db.collection::<Document>("posts");
+        coll.count_documents(author_filter(user_id, include_unattributed), None)
+            .unwrap_or(0) as i64
+    }
+
+    fn post_set_author(&self, id: i64, user_id: Option<i64>) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("posts");
+        coll.update_one(
+            doc! { "id": id },
+            doc! { "$set": { "author_id": user_id } },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
+        Ok(())
+    }
+
+    fn post_list_by_owner(
+        &self,
+        user_id: i64,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<Post> {
+        let coll = self.db.collection::<Document>("posts");
+        let mut filter = doc! { "author_id": user_id };
+        if let Some(s) = status {
+            filter.insert("status", s);
+        }
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "published_at": -1 })
+            .skip(offset as u64)
+            .limit(limit)
+            .build();
+        let cursor = match coll.find(filter, opts) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_post(&d))
+            .collect()
+    }
+
+    fn post_list_keyset(
+        &self,
+        owner: Option<i64>,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Post>, Option<Cursor>) {
+        let coll = self.db.collection::<Document>("posts");
+        let mut filter = doc! {};
+        if let Some(u) = owner {
+            filter.insert("author_id", u);
+        }
+        if let Some(s) = status {
+            filter.insert("status", s);
+        }
+        keyset_page(&coll, "published_at", filter, after, limit, doc_to_post)
+    }
+
+    fn post_count_by_owner(&self, user_id: i64, status: Option<&str>) -> i64 {
+        let coll = self.db.collection::<Document>("posts");
+        let mut filter = doc! { "author_id": user_id };
+        if let Some(s) = status {
+            filter.insert("status", s);
+        }
+        coll.count_documents(filter, None).unwrap_or(0) as i64
+    }
+
+    fn post_reassign_author(&self, from: i64, to: i64) -> Result<usize, String> {
+        let coll = self.db.collection::<Document>("posts");
+        let res = coll
+            .update_many(
+                doc! { "author_id": from },
+                doc! { "$set": { "author_id": to } },
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
+        Ok(res.modified_count as usize)
+    }
+
     fn portfolio_find_by_id(&self, id: i64) -> Option<PortfolioItem> {
         let coll = self.db.collection::<Document>("portfolio");
         let d = coll.find_one(doc! { "id": id }, None).ok()??;
@@ -1081,10 +1707,17 @@ impl Store for MongoStore {
                 "updated_at": ts,
                 "seo_score": -1_i32,
                 "seo_issues": "[]",
+                "robots_noindex": form.robots_noindex.unwrap_or(false),
+                "robots_nofollow": form.robots_nofollow.unwrap_or(false),
+                "canonical_url": form.canonical_url.as_deref(),
+                "schema_type": form.schema_type.as_deref(),
+                "focus_keyword": form.focus_keyword.as_deref(),
+                "template": form.template.as_deref(),
             },
             None,
         )
         .map_err(|e| e.to_string())?;
+        super::content_changed(Section::Portfolio);
         Ok(id)
     }
     fn portfolio_update(&self, id: i64, form: &PortfolioForm) -> Result<(), String> {
@@ -1107,11 +1740,18 @@ impl Store for MongoStore {
                 "download_file_path": form.download_file_path.as_deref().unwrap_or(""),
                 "status": &form.status,
                 "published_at": form.published_at.as_deref(),
+                "robots_noindex": form.robots_noindex.unwrap_or(false),
+                "robots_nofollow": form.robots_nofollow.unwrap_or(false),
+                "canonical_url": form.canonical_url.as_deref(),
+                "schema_type": form.schema_type.as_deref(),
+                "focus_keyword": form.focus_keyword.as_deref(),
+                "template": form.template.as_deref(),
                 "updated_at": chrono::Utc::now().to_rfc3339(),
             }},
             None,
         )
         .map_err(|e| e.to_string())?;
+        super::content_changed(Section::Portfolio);
         Ok(())
     }
     fn portfolio_update_status(&self, id: i64, status: &str) -> Result<(), String> {
@@ -1122,12 +1762,14 @@ impl Store for MongoStore {
             None,
         )
         .map_err(|e| e.to_string())?;
+        super::content_changed(Section::Portfolio);
         Ok(())
     }
     fn portfolio_delete(&self, id: i64) -> Result<(), String> {
         let coll = self.db.collection::<Document>("portfolio");
         coll.delete_one(doc! { "id": id }, None)
             .map_err(|e| e.to_string())?;
+        super::content_changed(Section::Portfolio);
         Ok(())
     }
     fn portfolio_increment_likes(&self, id: i64) -> Result<i64, String> {
@@ -1246,6 +1888,21 @@ impl Store for MongoStore {
             .filter_map(|d| doc_to_comment(&d))
             .collect()
     }
+    fn comment_list_since(
+        &self,
+        status: Option<&str>,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, Comment)> {
+        let filter = match status {
+            Some(s) => doc! { "status": s },
+            None => doc! {},
+        };
+        self.list_status_since("comments", filter, since_seq, limit)
+            .into_iter()
+            .filter_map(|(seq, d)| Some((seq, doc_to_comment(&d)?)))
+            .collect()
+    }
     fn comment_for_post(&self, post_id: i64, content_type: &str) -> Vec<Comment> {
         let coll = self.db.collection::<Document>("comments");
         let opts = mongodb::options::FindOptions::builder()
@@ -1263,6 +1920,19 @@ impl Store for MongoStore {
             .filter_map(|d| doc_to_comment(&d))
             .collect()
     }
+    fn comment_list_keyset(
+        &self,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Comment>, Option<Cursor>) {
+        let coll = self.db.collection::<Document>("comments");
+        let filter = match status {
+            Some(s) => doc! { "status": s },
+            None => doc! {},
+        };
+        keyset_page(&coll, "id", filter, after, limit, doc_to_comment)
+    }
     fn comment_count(&self, status: Option<&str>) -> i64 {
         let coll = self.db.collection::<Document>("comments");
         let filter = match status {
@@ -1286,13 +1956,16 @@ impl Store for MongoStore {
                 "status": "pending",
                 "parent_id": form.parent_id,
                 "created_at": &now,
+                "status_seq": self.next_id("comments_status_seq")?,
             },
             None,
         )
         .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
         Ok(id)
     }
     fn comment_update_status(&self, id: i64, status: &str) -> Result<(), String> {
+        self.bump_status_seq("comments", id, status)?;
         let coll = self.db.collection::<Document>("comments");
         coll.update_one(
             doc! { "id": id },
@@ -1300,6 +1973,7 @@ impl Store for MongoStore {
             None,
         )
         .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
         Ok(())
     }
     fn comment_set_parent(&self, id: i64, parent_id: Option<i64>) -> Result<(), String> {
@@ -1311,12 +1985,88 @@ impl Store for MongoStore {
             None,
         )
         .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
+        Ok(())
+    }
+    fn comment_set_user(&self, id: i64, user_id: i64) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("comments");
+        coll.update_one(
+            doc! { "id": id },
+            doc! { "$set": { "user_id": user_id } },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
         Ok(())
     }
+    fn comment_list_on_author_posts(&self, author_id: i64, limit: i64) -> Vec<Comment> {
+        let post_ids: Vec<i64> = match self
+            .db
+            .collection::<Document>("posts")
+            .find(doc! { "author_id": author_id }, None)
+        {
+            Ok(cursor) => cursor
+                .filter_map(|r| r.ok())
+                .filter_map(|d| d.get_i64("id").ok())
+                .collect(),
+            Err(_) => return vec![],
+        };
+        let coll = self.db.collection::<Document>("comments");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "id": -1 })
+            .limit(limit)
+            .build();
+        let cursor = match coll.find(
+            doc! {
+                "content_type": "post",
+                "post_id": { "$in": post_ids },
+                "status": { "$ne": "spam" },
+            },
+            opts,
+        ) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_comment(&d))
+            .collect()
+    }
+    fn comment_list_by_author(&self, user_id: i64, email: &str) -> Vec<Comment> {
+        let coll = self.db.collection::<Document>("comments");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "id": -1 })
+            .build();
+        let cursor = match coll.find(comment_author_filter(user_id, email), opts) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_comment(&d))
+            .collect()
+    }
+    fn comment_anonymize_author(
+        &self,
+        user_id: i64,
+        email: &str,
+        name: &str,
+    ) -> Result<usize, String> {
+        let coll = self.db.collection::<Document>("comments");
+        let res = coll
+            .update_many(
+                comment_author_filter(user_id, email),
+                doc! { "$set": { "author_name": name, "author_email": Bson::Null, "user_id": Bson::Null } },
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
+        Ok(res.modified_count as usize)
+    }
     fn comment_delete(&self, id: i64) -> Result<(), String> {
         let coll = self.db.collection::<Document>("comments");
         coll.delete_one(doc! { "id": id }, None)
             .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
         Ok(())
     }
 
@@ -1427,6 +2177,7 @@ impl Store for MongoStore {
             None,
         )
         .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
         Ok(id)
     }
     fn category_update(&self, id: i64, form: &CategoryForm) -> Result<(), String> {
@@ -1437,6 +2188,7 @@ impl Store for MongoStore {
             None,
         )
         .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
         Ok(())
     }
     fn category_delete(&self, id: i64) -> Result<(), String> {
@@ -1446,6 +2198,7 @@ impl Store for MongoStore {
         // Also remove content_categories links
         let cc = self.db.collection::<Document>("content_categories");
         let _ = cc.delete_many(doc! { "category_id": id }, None);
+        page_cache::invalidate();
         Ok(())
     }
     fn category_set_show_in_nav(&self, id: i64, show: bool) -> Result<(), String> {
@@ -1456,6 +2209,7 @@ impl Store for MongoStore {
             None,
         )
         .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
         Ok(())
     }
     fn category_list_nav_visible(&self, type_filter: Option<&str>) -> Vec<Category> {
@@ -1494,6 +2248,7 @@ impl Store for MongoStore {
                 None,
             ).map_err(|e| e.to_string())?;
         }
+        page_cache::invalidate();
         Ok(())
     }
 
@@ -1581,6 +2336,7 @@ impl Store for MongoStore {
             None,
         )
         .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
         Ok(id)
     }
     fn tag_update(&self, id: i64, form: &TagForm) -> Result<(), String> {
@@ -1591,6 +2347,7 @@ impl Store for MongoStore {
             None,
         )
         .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
         Ok(())
     }
     fn tag_delete(&self, id: i64) -> Result<(), String> {
@@ -1599,6 +2356,7 @@ impl Store for MongoStore {
             .map_err(|e| e.to_string())?;
         let ct = self.db.collection::<Document>("content_tags");
         let _ = ct.delete_many(doc! { "tag_id": id }, None);
+        page_cache::invalidate();
         Ok(())
     }
     fn tag_set_for_content(
@@ -1620,6 +2378,7 @@ impl Store for MongoStore {
             )
             .map_err(|e| e.to_string())?;
         }
+        page_cache::invalidate();
         Ok(())
     }
     fn tag_find_or_create(&self, name: &str) -> Result<i64, String> {
@@ -1675,6 +2434,7 @@ impl Store for MongoStore {
             None,
         )
         .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
         Ok(())
     }
     fn design_create(&self, name: &str) -> Result<i64, String> {
@@ -1720,6 +2480,46 @@ impl Store for MongoStore {
             None,
         )
         .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
+        Ok(())
+    }
+    fn design_update_custom_code(
+        &self,
+        id: i64,
+        custom_css: &str,
+        head_scripts: &str,
+        footer_scripts: &str,
+    ) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("designs");
+        let now = chrono::Utc::now().to_rfc3339();
+        coll.update_one(
+            doc! { "id": id },
+            doc! { "$set": {
+                "custom_css": custom_css,
+                "head_scripts": head_scripts,
+                "footer_scripts": footer_scripts,
+                "updated_at": &now,
+            }},
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
+        Ok(())
+    }
+    fn design_set_parent(&self, id: i64, parent_id: Option<i64>) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("designs");
+        let now = chrono::Utc::now().to_rfc3339();
+        let parent = match parent_id {
+            Some(pid) => Bson::Int64(pid),
+            None => Bson::Null,
+        };
+        coll.update_one(
+            doc! { "id": id },
+            doc! { "$set": { "parent_id": parent, "updated_at": &now } },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
         Ok(())
     }
     fn design_duplicate(&self, id: i64, new_name: &str) -> Result<i64, String> {
@@ -1736,6 +2536,10 @@ impl Store for MongoStore {
                 "description": &src.description,
                 "layout_html": &src.layout_html,
                 "style_css": &src.style_css,
+                "parent_id": src.parent_id.map(Bson::Int64).unwrap_or(Bson::Null),
+                "custom_css": &src.custom_css,
+                "head_scripts": &src.head_scripts,
+                "footer_scripts": &src.footer_scripts,
                 "thumbnail_path": Bson::Null,
                 "is_active": false,
                 "created_at": &now,
@@ -1763,6 +2567,16 @@ impl Store for MongoStore {
             .map_err(|e| e.to_string())?;
         let tmpl = self.db.collection::<Document>("design_templates");
         let _ = tmpl.delete_many(doc! { "design_id": id }, None);
+        let versions = self.db.collection::<Document>("design_template_versions");
+        let _ = versions.delete_many(doc! { "design_id": id }, None);
+        let widgets = self.db.collection::<Document>("widgets");
+        let _ = widgets.delete_many(doc! { "design_id": id }, None);
+        let _ = coll.update_many(
+            doc! { "parent_id": id },
+            doc! { "$set": { "parent_id": Bson::Null } },
+            None,
+        );
+        page_cache::invalidate();
         Ok(())
     }
 
@@ -1777,7 +2591,11 @@ impl Store for MongoStore {
             .filter_map(|d| doc_to_design_template(&d))
             .collect()
     }
-    fn design_template_get(&self, design_id: i64, template_type: &str) -> Option<DesignTemplate> {
+    fn design_template_get_own(
+        &self,
+        design_id: i64,
+        template_type: &str,
+    ) -> Option<DesignTemplate> {
         let coll = self.db.collection::<Document>("design_templates");
         let d = coll
             .find_one(
@@ -1836,13 +2654,125 @@ impl Store for MongoStore {
             )
             .map_err(|e| e.to_string())?;
         }
+        page_cache::invalidate();
         Ok(())
     }
-
-    fn audit_log(
+    fn design_template_version_list(
         &self,
-        _user_id: Option<i64>,
-        _user_name: Option<&str>,
+        design_id: i64,
+        template_type: &str,
+    ) -> Vec<DesignTemplateVersion> {
+        let coll = self.db.collection::<Document>("design_template_versions");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "id": -1 })
+            .build();
+        let cursor = match coll.find(
+            doc! { "design_id": design_id, "template_type": template_type },
+            opts,
+        ) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_design_template_version(&d))
+            .collect()
+    }
+    fn design_template_version_get(&self, id: i64) -> Option<DesignTemplateVersion> {
+        let coll = self.db.collection::<Document>("design_template_versions");
+        let d = coll.find_one(doc! { "id": id }, None).ok()??;
+        doc_to_design_template_version(&d)
+    }
+    fn design_template_version_create(
+        &self,
+        version: &DesignTemplateVersion,
+    ) -> Result<i64, String> {
+        let coll = self.db.collection::<Document>("design_template_versions");
+        let id = self.next_id("design_template_versions")?;
+        coll.insert_one(
+            doc! {
+                "id": id,
+                "design_id": version.design_id,
+                "template_type": &version.template_type,
+                "layout_html": &version.layout_html,
+                "style_css": &version.style_css,
+                "grapesjs_data": &version.grapesjs_data,
+                "user_id": version.user_id.map(Bson::Int64).unwrap_or(Bson::Null),
+                "user_name": &version.user_name,
+                "created_at": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        // Keep only the newest MAX_TEMPLATE_VERSIONS
+        let filter =
+            doc! { "design_id": version.design_id, "template_type": &version.template_type };
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "id": -1 })
+            .skip(MAX_TEMPLATE_VERSIONS as u64)
+            .limit(1)
+            .build();
+        if let Some(oldest_kept) = coll
+            .find(filter.clone(), opts)
+            .ok()
+            .and_then(|mut c| c.next())
+            .and_then(|r| r.ok())
+            .and_then(|d| d.get_i64("id").ok())
+        {
+            let mut prune = filter;
+            prune.insert("id", doc! { "$lte": oldest_kept });
+            let _ = coll.delete_many(prune, None);
+        }
+        Ok(id)
+    }
+
+    fn widget_list(&self, design_id: i64) -> Vec<Widget> {
+        let coll = self.db.collection::<Document>("widgets");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "area": 1, "sort_order": 1, "id": 1 })
+            .build();
+        let cursor = match coll.find(doc! { "design_id": design_id }, opts) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_widget(&d))
+            .collect()
+    }
+    fn widget_save(&self, widget: &Widget) -> Result<i64, String> {
+        let coll = self.db.collection::<Document>("widgets");
+        let fields = doc! {
+            "area": &widget.area,
+            "title": &widget.title,
+            "config": &widget.config,
+            "sort_order": widget.sort_order,
+        };
+        if widget.id == 0 {
+            let id = self.next_id("widgets")?;
+            let mut d = fields;
+            d.insert("id", id);
+            d.insert("design_id", widget.design_id);
+            d.insert("widget_type", &widget.widget_type);
+            d.insert("created_at", chrono::Utc::now().to_rfc3339());
+            coll.insert_one(d, None).map_err(|e| e.to_string())?;
+            return Ok(id);
+        }
+        coll.update_one(doc! { "id": widget.id }, doc! { "$set": fields }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(widget.id)
+    }
+    fn widget_delete(&self, id: i64) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("widgets");
+        coll.delete_one(doc! { "id": id }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn audit_log(
+        &self,
+        _user_id: Option<i64>,
+        _user_name: Option<&str>,
         _action: &str,
         _entity_type: Option<&str>,
         _entity_id: Option<i64>,
@@ -1854,6 +2784,7 @@ impl Store for MongoStore {
         let coll = self.db.collection::<Document>("audit_log");
         let _ = coll.insert_one(
             doc! {
+                "id": self.next_id("audit_log").unwrap_or(0),
                 "user_id": _user_id,
                 "user_name": _user_name,
                 "action": _action,
@@ -1901,6 +2832,27 @@ impl Store for MongoStore {
             .filter_map(|d| doc_to_audit(&d))
             .collect()
     }
+    fn audit_list_keyset(
+        &self,
+        action_filter: Option<&str>,
+        entity_filter: Option<&str>,
+        user_filter: Option<i64>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<AuditEntry>, Option<Cursor>) {
+        let coll = self.db.collection::<Document>("audit_log");
+        let mut filter = doc! {};
+        if let Some(a) = action_filter {
+            filter.insert("action", a);
+        }
+        if let Some(e) = entity_filter {
+            filter.insert("entity_type", e);
+        }
+        if let Some(u) = user_filter {
+            filter.insert("user_id", u);
+        }
+        keyset_page(&coll, "created_at", filter, after, limit, doc_to_audit)
+    }
     fn audit_count(
         &self,
         action_filter: Option<&str>,
@@ -1949,6 +2901,22 @@ impl Store for MongoStore {
         Ok(result.deleted_count as usize)
     }
 
+    fn audit_list_after(&self, after_id: i64, limit: i64) -> Vec<AuditEntry> {
+        let coll = self.db.collection::<Document>("audit_log");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "id": 1 })
+            .limit(limit)
+            .build();
+        let cursor = match coll.find(doc! { "id": { "$gt": after_id } }, opts) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_audit(&d))
+            .collect()
+    }
+
     fn fw_is_banned(&self, ip: &str) -> bool {
         let coll = self.db.collection::<Document>("fw_bans");
         let now = chrono::Utc::now().to_rfc3339();
@@ -2000,8 +2968,38 @@ impl Store for MongoStore {
         country: Option<&str>,
         user_agent: Option<&str>,
     ) -> Result<i64, String> {
-        let expires = parse_duration_to_expiry(duration);
-        self.fw_ban_create(ip, reason, detail, Some(&expires), country, user_agent)
+        let expires = match duration {
+            "permanent" | "" => None,
+            d => Some(parse_duration_to_expiry(d)),
+        };
+        self.fw_ban_create(ip, reason, detail, expires.as_deref(), country, user_agent)
+    }
+    fn fw_offense_record(&self, ip: &str, window_days: i64) -> i64 {
+        let coll = self.db.collection::<Document>("fw_offenses");
+        let now = chrono::Utc::now();
+        let cutoff = (now - chrono::Duration::days(window_days)).to_rfc3339();
+        let count = match coll.find_one(doc! { "ip": ip }, None) {
+            Ok(Some(d)) if d.get_str("last_offense").unwrap_or("") >= cutoff.as_str() => {
+                d.get_i64("count").unwrap_or(0) + 1
+            }
+            _ => 1,
+        };
+        let _ = coll.update_one(
+            doc! { "ip": ip },
+            doc! { "$set": { "count": count, "last_offense": now.to_rfc3339() } },
+            mongodb::options::UpdateOptions::builder()
+                .upsert(true)
+                .build(),
+        );
+        count
+    }
+    fn fw_offense_count(&self, ip: &str) -> i64 {
+        let coll = self.db.collection::<Document>("fw_offenses");
+        coll.find_one(doc! { "ip": ip }, None)
+            .ok()
+            .flatten()
+            .and_then(|d| d.get_i64("count").ok())
+            .unwrap_or(0)
     }
     fn fw_unban(&self, ip: &str) -> Result<usize, String> {
         let coll = self.db.collection::<Document>("fw_bans");
@@ -2014,6 +3012,17 @@ impl Store for MongoStore {
             .map_err(|e| e.to_string())?;
         Ok(result.modified_count as usize)
     }
+    fn fw_unban_all(&self) -> Result<usize, String> {
+        let coll = self.db.collection::<Document>("fw_bans");
+        let result = coll
+            .update_many(
+                doc! { "active": true },
+                doc! { "$set": { "active": false } },
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(result.modified_count as usize)
+    }
     fn fw_unban_by_id(&self, id: i64) -> Result<usize, String> {
         let coll = self.db.collection::<Document>("fw_bans");
         let result = coll
@@ -2092,6 +3101,7 @@ impl Store for MongoStore {
         let coll = self.db.collection::<Document>("fw_events");
         let _ = coll.insert_one(
             doc! {
+                "id": self.next_id("fw_events").unwrap_or(0),
                 "ip": _ip,
                 "event_type": _event_type,
                 "detail": _detail,
@@ -2195,228 +3205,979 @@ impl Store for MongoStore {
             .collect()
     }
 
-    fn analytics_record(
-        &self,
-        _path: &str,
-        _ip_hash: &str,
-        _country: Option<&str>,
-        _city: Option<&str>,
-        _referrer: Option<&str>,
-        _user_agent: Option<&str>,
-        _device_type: Option<&str>,
-        _browser: Option<&str>,
-    ) -> Result<(), String> {
-        let coll = self.db.collection::<Document>("page_views");
-        coll.insert_one(
-            doc! {
-                "path": _path,
-                "ip_hash": _ip_hash,
-                "country": _country,
-                "city": _city,
-                "referrer": _referrer,
-                "user_agent": _user_agent,
-                "device_type": _device_type,
-                "browser": _browser,
-                "created_at": chrono::Utc::now().to_rfc3339(),
-            },
-            None,
-        )
-        .map_err(|e| e.to_string())?;
-        Ok(())
+    fn fw_event_list_after(&self, after_id: i64, limit: i64) -> Vec<FwEvent> {
+        let coll = self.db.collection::<Document>("fw_events");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "id": 1 })
+            .limit(limit)
+            .build();
+        let cursor = match coll.find(doc! { "id": { "$gt": after_id } }, opts) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_fw_event(&d))
+            .collect()
+    }
+    fn fw_event_cleanup(&self, max_age_days: i64) -> Result<usize, String> {
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(max_age_days)).to_rfc3339();
+        let coll = self.db.collection::<Document>("fw_events");
+        let result = coll
+            .delete_many(doc! { "created_at": { "$lt": &cutoff } }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(result.deleted_count as usize)
     }
 
-    fn analytics_overview(&self, _from: &str, _to: &str) -> OverviewStats {
-        OverviewStats {
-            total_views: 0,
-            unique_visitors: 0,
-            posts_count: 0,
-            portfolio_count: 0,
-            comments_pending: 0,
-            total_likes: 0,
+    fn fw_rule_list(&self) -> Vec<FwRule> {
+        self.fw_rules.get_or_load(|| {
+            let coll = self.db.collection::<Document>("fw_rules");
+            let opts = mongodb::options::FindOptions::builder()
+                .sort(doc! { "priority": 1, "id": 1 })
+                .build();
+            let cursor = match coll.find(doc! {}, opts) {
+                Ok(c) => c,
+                Err(_) => return vec![],
+            };
+            cursor
+                .filter_map(|r| r.ok())
+                .filter_map(|d| doc_to_fw_rule(&d))
+                .collect()
+        })
+    }
+    fn fw_rule_save(&self, rule: &FwRule) -> Result<i64, String> {
+        let coll = self.db.collection::<Document>("fw_rules");
+        let fields = doc! {
+            "name": &rule.name,
+            "match_type": &rule.match_type,
+            "pattern": &rule.pattern,
+            "action": &rule.action,
+            "rate_limit": rule.rate_limit,
+            "priority": rule.priority,
+            "enabled": rule.enabled,
+        };
+        if rule.id == 0 {
+            let id = self.next_id("fw_rules")?;
+            let mut d = fields;
+            d.insert("id", id);
+            d.insert("hits", 0i64);
+            d.insert("created_at", chrono::Utc::now().to_rfc3339());
+            coll.insert_one(d, None).map_err(|e| e.to_string())?;
+            firewall::invalidate_rules();
+            return Ok(id);
         }
+        coll.update_one(doc! { "id": rule.id }, doc! { "$set": fields }, None)
+            .map_err(|e| e.to_string())?;
+        firewall::invalidate_rules();
+        Ok(rule.id)
     }
-    fn analytics_flow_data(&self, _from: &str, _to: &str) -> Vec<FlowNode> {
-        vec![]
-    }
-    fn analytics_geo_data(&self, _from: &str, _to: &str) -> Vec<CountEntry> {
-        vec![]
-    }
-    fn analytics_stream_data(&self, _from: &str, _to: &str) -> Vec<StreamEntry> {
-        vec![]
-    }
-    fn analytics_calendar_data(&self, _from: &str, _to: &str) -> Vec<DailyCount> {
-        vec![]
+    fn fw_rule_delete(&self, id: i64) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("fw_rules");
+        coll.delete_one(doc! { "id": id }, None)
+            .map_err(|e| e.to_string())?;
+        firewall::invalidate_rules();
+        Ok(())
     }
-    fn analytics_top_portfolio(&self, _from: &str, _to: &str, _limit: i64) -> Vec<CountEntry> {
-        vec![]
+    fn fw_rule_hit(&self, id: i64) {
+        let coll = self.db.collection::<Document>("fw_rules");
+        let _ = coll.update_one(doc! { "id": id }, doc! { "$inc": { "hits": 1i64 } }, None);
+        self.fw_rules.record_hit(id);
     }
-    fn analytics_top_referrers(&self, _from: &str, _to: &str, _limit: i64) -> Vec<CountEntry> {
-        vec![]
+
+    fn redirect_list(&self) -> Vec<RedirectRule> {
+        self.redirects.get_or_load(|| {
+            let coll = self.db.collection::<Document>("redirects");
+            let opts = mongodb::options::FindOptions::builder()
+                .sort(doc! { "source": 1 })
+                .build();
+            let cursor = match coll.find(doc! {}, opts) {
+                Ok(c) => c,
+                Err(_) => return vec![],
+            };
+            cursor
+                .filter_map(|r| r.ok())
+                .filter_map(|d| doc_to_redirect(&d))
+                .collect()
+        })
+    }
+    fn redirect_save(&self, rule: &RedirectRule) -> Result<i64, String> {
+        let coll = self.db.collection::<Document>("redirects");
+        let fields = doc! {
+            "source": &rule.source,
+            "target": &rule.target,
+            "status_code": rule.status_code,
+        };
+        if rule.id == 0 {
+            let id = self.next_id("redirects")?;
+            let mut d = fields;
+            d.insert("id", id);
+            d.insert("hits", 0i64);
+            d.insert("created_at", chrono::Utc::now().to_rfc3339());
+            coll.insert_one(d, None).map_err(|e| e.to_string())?;
+            redirect::invalidate_redirects();
+            return Ok(id);
+        }
+        coll.update_one(doc! { "id": rule.id }, doc! { "$set": fields }, None)
+            .map_err(|e| e.to_string())?;
+        redirect::invalidate_redirects();
+        Ok(rule.id)
     }
-    fn analytics_tag_relations(&self) -> Vec<TagRelation> {
-        vec![]
+    fn redirect_delete(&self, id: i64) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("redirects");
+        coll.delete_one(doc! { "id": id }, None)
+            .map_err(|e| e.to_string())?;
+        redirect::invalidate_redirects();
+        Ok(())
     }
-
-    fn order_find_by_id(&self, id: i64) -> Option<Order> {
-        let coll = self.db.collection::<Document>("orders");
-        let d = coll.find_one(doc! { "id": id }, None).ok()??;
-        doc_to_order(&d)
+    fn redirect_hit(&self, id: i64) {
+        let coll = self.db.collection::<Document>("redirects");
+        let _ = coll.update_one(doc! { "id": id }, doc! { "$inc": { "hits": 1i64 } }, None);
+        self.redirects.record_hit(id);
     }
-    fn order_find_by_uuid(&self, uuid: &str) -> Option<Order> {
-        let coll = self.db.collection::<Document>("orders");
-        let d = coll.find_one(doc! { "uuid": uuid }, None).ok()??;
-        doc_to_order(&d)
+    fn not_found_record(&self, path: &str, referrer: Option<&str>) -> Result<(), String> {
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let mut set = doc! { "last_seen": &now };
+        if let Some(r) = referrer {
+            set.insert("referrer", r);
+        }
+        let opts = mongodb::options::UpdateOptions::builder()
+            .upsert(true)
+            .build();
+        self.db
+            .collection::<Document>("not_found_log")
+            .update_one(
+                doc! { "path": path },
+                doc! {
+                    "$inc": { "hits": 1i64 },
+                    "$set": set,
+                    "$setOnInsert": { "first_seen": &now },
+                },
+                opts,
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
     }
-    fn order_find_by_provider_order_id(&self, provider_order_id: &str) -> Option<Order> {
-        let coll = self.db.collection::<Document>("orders");
-        let d = coll
-            .find_one(doc! { "provider_order_id": provider_order_id }, None)
-            .ok()??;
-        doc_to_order(&d)
+    fn not_found_record_batch(&self, hits: &[NotFoundHit]) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("not_found_log");
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        for hit in hits {
+            let mut set = doc! { "last_seen": &now };
+            if let Some(r) = &hit.referrer {
+                set.insert("referrer", r);
+            }
+            let opts = mongodb::options::UpdateOptions::builder()
+                .upsert(true)
+                .build();
+            coll.update_one(
+                doc! { "path": &hit.path },
+                doc! {
+                    "$inc": { "hits": hit.hits },
+                    "$set": set,
+                    "$setOnInsert": { "first_seen": &now },
+                },
+                opts,
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        let excess = coll
+            .count_documents(doc! {}, None)
+            .map_err(|e| e.to_string())? as i64
+            - NOT_FOUND_MAX_ROWS;
+        if excess > 0 {
+            let opts = mongodb::options::FindOptions::builder()
+                .sort(doc! { "hits": 1, "last_seen": 1 })
+                .limit(excess)
+                .projection(doc! { "path": 1 })
+                .build();
+            let paths: Vec<String> = coll
+                .find(doc! {}, opts)
+                .map_err(|e| e.to_string())?
+                .filter_map(|r| r.ok())
+                .filter_map(|d| d.get_str("path").ok().map(|p| p.to_string()))
+                .collect();
+            coll.delete_many(doc! { "path": { "$in": paths } }, None)
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
     }
-    fn order_list(&self, limit: i64, offset: i64) -> Vec<Order> {
-        let coll = self.db.collection::<Document>("orders");
+    fn not_found_top(&self, limit: i64) -> Vec<NotFoundEntry> {
         let opts = mongodb::options::FindOptions::builder()
-            .sort(doc! { "id": -1 })
-            .skip(offset as u64)
+            .sort(doc! { "hits": -1, "last_seen": -1 })
             .limit(limit)
             .build();
-        let cursor = match coll.find(doc! {}, opts) {
+        let cursor = match self
+            .db
+            .collection::<Document>("not_found_log")
+            .find(doc! {}, opts)
+        {
             Ok(c) => c,
             Err(_) => return vec![],
         };
         cursor
             .filter_map(|r| r.ok())
-            .filter_map(|d| doc_to_order(&d))
+            .filter_map(|d| {
+                Some(NotFoundEntry {
+                    path: d.get_str("path").ok()?.to_string(),
+                    referrer: d.get_str("referrer").ok().map(|s| s.to_string()),
+                    hits: d.get_i64("hits").unwrap_or(0),
+                    first_seen: d.get_str("first_seen").ok().unwrap_or("").to_string(),
+                    last_seen: d.get_str("last_seen").ok().unwrap_or("").to_string(),
+                })
+            })
             .collect()
     }
-    fn order_list_by_status(&self, status: &str, limit: i64, offset: i64) -> Vec<Order> {
-        let coll = self.db.collection::<Document>("orders");
-        let opts = mongodb::options::FindOptions::builder()
-            .sort(doc! { "id": -1 })
-            .skip(offset as u64)
-            .limit(limit)
-            .build();
-        let cursor = match coll.find(doc! { "status": status }, opts) {
-            Ok(c) => c,
+    fn not_found_dismiss(&self, path: &str) -> Result<(), String> {
+        self.db
+            .collection::<Document>("not_found_log")
+            .delete_one(doc! { "path": path }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+    fn not_found_cleanup(&self, max_age_days: i64) -> Result<usize, String> {
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(max_age_days))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        let result = self
+            .db
+            .collection::<Document>("not_found_log")
+            .delete_many(doc! { "last_seen": { "$lt": &cutoff } }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(result.deleted_count as usize)
+    }
+
+    fn api_key_list(&self) -> Vec<ApiKey> {
+        let coll = self.db.collection::<Document>("api_keys");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "created_at": -1, "id": -1 })
+            .build();
+        let cursor = match coll.find(doc! {}, opts) {
+            Ok(c) => c,
             Err(_) => return vec![],
         };
         cursor
             .filter_map(|r| r.ok())
-            .filter_map(|d| doc_to_order(&d))
+            .filter_map(|d| doc_to_api_key(&d))
             .collect()
     }
-    fn order_list_by_email(&self, email: &str, limit: i64, offset: i64) -> Vec<Order> {
-        let coll = self.db.collection::<Document>("orders");
+    fn api_key_find_by_hash(&self, key_hash: &str) -> Option<ApiKey> {
+        let coll = self.db.collection::<Document>("api_keys");
+        coll.find_one(doc! { "key_hash": key_hash }, None)
+            .ok()
+            .flatten()
+            .and_then(|d| doc_to_api_key(&d))
+    }
+    fn api_key_create(&self, key: &ApiKey) -> Result<i64, String> {
+        let coll = self.db.collection::<Document>("api_keys");
+        let id = self.next_id("api_keys")?;
+        coll.insert_one(
+            doc! {
+                "id": id,
+                "name": &key.name,
+                "prefix": &key.prefix,
+                "key_hash": &key.key_hash,
+                "scope": &key.scope,
+                "rate_limit": key.rate_limit,
+                "created_at": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(id)
+    }
+    fn api_key_delete(&self, id: i64) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("api_keys");
+        coll.delete_one(doc! { "id": id }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+    fn api_key_touch(&self, id: i64) {
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let coll = self.db.collection::<Document>("api_keys");
+        let _ = coll.update_one(
+            doc! { "id": id },
+            doc! { "$set": { "last_used_at": now } },
+            None,
+        );
+    }
+
+    fn ping_log_add(&self, entry: &PingLogEntry) -> Result<i64, String> {
+        let id = self.next_id("ping_log")?;
+        self.db
+            .collection::<Document>("ping_log")
+            .insert_one(
+                doc! {
+                    "id": id,
+                    "engine": &entry.engine,
+                    "urls": &entry.urls,
+                    "success": entry.success,
+                    "status_code": entry.status_code,
+                    "message": &entry.message,
+                    "created_at": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                },
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(id)
+    }
+    fn ping_log_recent(&self, limit: i64) -> Vec<PingLogEntry> {
         let opts = mongodb::options::FindOptions::builder()
             .sort(doc! { "id": -1 })
-            .skip(offset as u64)
             .limit(limit)
             .build();
-        let cursor = match coll.find(doc! { "buyer_email": email }, opts) {
+        let cursor = match self
+            .db
+            .collection::<Document>("ping_log")
+            .find(doc! {}, opts)
+        {
             Ok(c) => c,
             Err(_) => return vec![],
         };
         cursor
             .filter_map(|r| r.ok())
-            .filter_map(|d| doc_to_order(&d))
+            .filter_map(|d| {
+                Some(PingLogEntry {
+                    id: d.get_i64("id").ok()?,
+                    engine: d.get_str("engine").ok()?.to_string(),
+                    urls: d.get_str("urls").ok().unwrap_or("").to_string(),
+                    success: d.get_bool("success").unwrap_or(false),
+                    status_code: d.get_i64("status_code").unwrap_or(0),
+                    message: d.get_str("message").ok().unwrap_or("").to_string(),
+                    created_at: d.get_str("created_at").ok().unwrap_or("").to_string(),
+                })
+            })
             .collect()
     }
-    fn order_list_by_portfolio(&self, portfolio_id: i64) -> Vec<Order> {
-        let coll = self.db.collection::<Document>("orders");
-        let opts = mongodb::options::FindOptions::builder()
-            .sort(doc! { "id": -1 })
-            .build();
-        let cursor = match coll.find(doc! { "portfolio_id": portfolio_id }, opts) {
+    fn ping_log_cleanup(&self, max_age_days: i64) -> Result<usize, String> {
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(max_age_days))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        let result = self
+            .db
+            .collection::<Document>("ping_log")
+            .delete_many(doc! { "created_at": { "$lt": &cutoff } }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(result.deleted_count as usize)
+    }
+
+    fn analytics_record(
+        &self,
+        _path: &str,
+        _ip_hash: &str,
+        _country: Option<&str>,
+        _city: Option<&str>,
+        _referrer: Option<&str>,
+        _user_agent: Option<&str>,
+        _device_type: Option<&str>,
+        _browser: Option<&str>,
+        utm: &Utm,
+    ) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("page_views");
+        coll.insert_one(
+            doc! {
+                "path": _path,
+                "ip_hash": _ip_hash,
+                "country": _country,
+                "city": _city,
+                "referrer": _referrer,
+                "user_agent": _user_agent,
+                "device_type": _device_type,
+                "browser": _browser,
+                "utm_source": utm.source.as_deref(),
+                "utm_medium": utm.medium.as_deref(),
+                "utm_campaign": utm.campaign.as_deref(),
+                "utm_term": utm.term.as_deref(),
+                "utm_content": utm.content.as_deref(),
+                "created_at": chrono::Utc::now().to_rfc3339(),
+            },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn analytics_overview(&self, _from: &str, _to: &str) -> OverviewStats {
+        OverviewStats {
+            total_views: 0,
+            unique_visitors: 0,
+            posts_count: 0,
+            portfolio_count: 0,
+            comments_pending: 0,
+            total_likes: 0,
+        }
+    }
+    fn analytics_flow_data(&self, _from: &str, _to: &str) -> Vec<FlowNode> {
+        vec![]
+    }
+    fn analytics_geo_data(&self, _from: &str, _to: &str) -> Vec<CountEntry> {
+        vec![]
+    }
+    fn analytics_stream_data(&self, _from: &str, _to: &str) -> Vec<StreamEntry> {
+        vec![]
+    }
+    fn analytics_calendar_data(&self, _from: &str, _to: &str) -> Vec<DailyCount> {
+        vec![]
+    }
+    fn analytics_top_portfolio(&self, _from: &str, _to: &str, _limit: i64) -> Vec<CountEntry> {
+        vec![]
+    }
+    fn analytics_top_referrers(&self, _from: &str, _to: &str, _limit: i64) -> Vec<CountEntry> {
+        vec![]
+    }
+    fn analytics_top_paths(
+        &self,
+        _prefix: &str,
+        _from: &str,
+        _to: &str,
+        _limit: i64,
+    ) -> Vec<CountEntry> {
+        vec![]
+    }
+    fn analytics_tag_relations(&self) -> Vec<TagRelation> {
+        vec![]
+    }
+    fn analytics_campaigns(&self, _from: &str, _to: &str, _limit: i64) -> Vec<CampaignEntry> {
+        vec![]
+    }
+    fn analytics_utm_breakdown(
+        &self,
+        _dimension: &str,
+        _from: &str,
+        _to: &str,
+        _limit: i64,
+    ) -> Vec<CountEntry> {
+        vec![]
+    }
+    fn analytics_path_stats(&self, _path: &str, _from: &str, _to: &str) -> PathStats {
+        PathStats::default()
+    }
+    fn analytics_path_breakdown(
+        &self,
+        _path: &str,
+        _dimension: &str,
+        _from: &str,
+        _to: &str,
+        _limit: i64,
+    ) -> Vec<CountEntry> {
+        vec![]
+    }
+    fn goal_record(&self, goal: &str, ip_hash: &str, path: &str) -> Result<bool, String> {
+        let coll = self.db.collection::<Document>("goal_completions");
+        let now = chrono::Utc::now();
+        let today = now.format("%Y-%m-%d").to_string();
+        let exists = coll
+            .count_documents(
+                doc! { "goal": goal, "ip_hash": ip_hash, "created_at": { "$gte": &today } },
+                None,
+            )
+            .unwrap_or(0)
+            > 0;
+        if exists {
+            return Ok(false);
+        }
+        let id = self.next_id("goal_completions")?;
+        coll.insert_one(
+            doc! {
+                "id": id,
+                "goal": goal,
+                "ip_hash": ip_hash,
+                "path": path,
+                "created_at": now.format("%Y-%m-%d %H:%M:%S").to_string(),
+            },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(true)
+    }
+    fn goal_stats(&self, from: &str, to: &str) -> Vec<GoalStat> {
+        let coll = self.db.collection::<Document>("goal_completions");
+        let pipeline = vec![
+            doc! { "$match": { "created_at": { "$gte": from, "$lte": to } } },
+            doc! { "$group": {
+                "_id": "$goal",
+                "completions": { "$sum": 1 },
+                "visitors": { "$addToSet": "$ip_hash" },
+            } },
+        ];
+        let cursor = match coll.aggregate(pipeline, None) {
             Ok(c) => c,
             Err(_) => return vec![],
         };
         cursor
             .filter_map(|r| r.ok())
-            .filter_map(|d| doc_to_order(&d))
+            .map(|d| GoalStat {
+                goal: d.get_str("_id").unwrap_or("").to_string(),
+                completions: match d.get("completions") {
+                    Some(Bson::Int32(n)) => *n as i64,
+                    Some(Bson::Int64(n)) => *n,
+                    _ => 0,
+                },
+                converters: d.get_array("visitors").map(|a| a.len() as i64).unwrap_or(0),
+            })
             .collect()
     }
-    fn order_count(&self) -> i64 {
-        let coll = self.db.collection::<Document>("orders");
-        coll.count_documents(doc! {}, None).unwrap_or(0) as i64
-    }
-    fn order_count_by_status(&self, status: &str) -> i64 {
-        let coll = self.db.collection::<Document>("orders");
-        coll.count_documents(doc! { "status": status }, None)
-            .unwrap_or(0) as i64
-    }
-    fn order_total_revenue(&self) -> f64 {
-        let coll = self.db.collection::<Document>("orders");
+    fn goal_daily(&self, goal: &str, from: &str, to: &str) -> Vec<DailyCount> {
+        let coll = self.db.collection::<Document>("goal_completions");
         let pipeline = vec![
-            doc! { "$match": { "status": "completed" } },
-            doc! { "$group": { "_id": Bson::Null, "total": { "$sum": "$amount" } } },
+            doc! { "$match": { "goal": goal, "created_at": { "$gte": from, "$lte": to } } },
+            doc! { "$group": {
+                "_id": { "$substrBytes": ["$created_at", 0, 10] },
+                "count": { "$sum": 1 },
+            } },
+            doc! { "$sort": { "_id": 1 } },
         ];
         let cursor = match coll.aggregate(pipeline, None) {
             Ok(c) => c,
-            Err(_) => return 0.0,
+            Err(_) => return vec![],
         };
         cursor
             .filter_map(|r| r.ok())
-            .next()
-            .and_then(|d| d.get_f64("total").ok())
-            .unwrap_or(0.0)
+            .map(|d| DailyCount {
+                date: d.get_str("_id").unwrap_or("").to_string(),
+                count: match d.get("count") {
+                    Some(Bson::Int32(n)) => *n as i64,
+                    Some(Bson::Int64(n)) => *n,
+                    _ => 0,
+                },
+            })
+            .collect()
     }
-    fn order_revenue_by_period(&self, days: i64) -> f64 {
-        let coll = self.db.collection::<Document>("orders");
-        let cutoff = (chrono::Utc::now() - chrono::Duration::days(days)).to_rfc3339();
+    fn outbound_click_record(&self, url: &str, ip_hash: &str) -> Result<(), String> {
+        let id = self.next_id("outbound_clicks")?;
+        self.db
+            .collection::<Document>("outbound_clicks")
+            .insert_one(
+                doc! {
+                    "id": id,
+                    "url": url,
+                    "ip_hash": ip_hash,
+                    "created_at": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                },
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+    fn outbound_top_links(&self, from: &str, to: &str, limit: i64) -> Vec<OutboundEntry> {
+        let coll = self.db.collection::<Document>("outbound_clicks");
         let pipeline = vec![
-            doc! { "$match": { "status": "completed", "created_at": { "$gte": &cutoff } } },
-            doc! { "$group": { "_id": Bson::Null, "total": { "$sum": "$amount" } } },
+            doc! { "$match": { "created_at": { "$gte": from, "$lte": to } } },
+            doc! { "$group": {
+                "_id": "$url",
+                "clicks": { "$sum": 1 },
+                "visitors": { "$addToSet": "$ip_hash" },
+            } },
+            doc! { "$sort": { "clicks": -1, "_id": 1 } },
+            doc! { "$limit": limit },
         ];
         let cursor = match coll.aggregate(pipeline, None) {
             Ok(c) => c,
-            Err(_) => return 0.0,
+            Err(_) => return vec![],
         };
         cursor
             .filter_map(|r| r.ok())
-            .next()
-            .and_then(|d| d.get_f64("total").ok())
-            .unwrap_or(0.0)
+            .map(|d| OutboundEntry {
+                url: d.get_str("_id").unwrap_or("").to_string(),
+                clicks: match d.get("clicks") {
+                    Some(Bson::Int32(n)) => *n as i64,
+                    Some(Bson::Int64(n)) => *n,
+                    _ => 0,
+                },
+                visitors: d.get_array("visitors").map(|a| a.len() as i64).unwrap_or(0),
+            })
+            .collect()
     }
-    fn order_create(
+    fn engagement_record(
         &self,
-        portfolio_id: i64,
-        buyer_email: &str,
-        buyer_name: &str,
-        amount: f64,
-        currency: &str,
-        provider: &str,
-        provider_order_id: &str,
-        status: &str,
-    ) -> Result<(i64, String), String> {
-        let id = self.next_id("orders")?;
-        let order_uuid = uuid::Uuid::new_v4().to_string();
-        let now = chrono::Utc::now().to_rfc3339();
-        let coll = self.db.collection::<Document>("orders");
-        coll.insert_one(
-            doc! {
-                "id": id,
-                "uuid": &order_uuid,
-                "portfolio_id": portfolio_id,
-                "buyer_email": buyer_email,
-                "buyer_name": buyer_name,
-                "amount": amount,
-                "currency": currency,
-                "provider": provider,
-                "provider_order_id": provider_order_id,
-                "status": status,
-                "created_at": &now,
-            },
-            None,
-        )
-        .map_err(|e| e.to_string())?;
-        Ok((id, order_uuid))
+        path: &str,
+        ip_hash: &str,
+        scroll: i64,
+        seconds: i64,
+    ) -> Result<(), String> {
+        let id = self.next_id("engagement_pings")?;
+        self.db
+            .collection::<Document>("engagement_pings")
+            .insert_one(
+                doc! {
+                    "id": id,
+                    "path": path,
+                    "ip_hash": ip_hash,
+                    "scroll": scroll,
+                    "seconds": seconds,
+                    "created_at": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                },
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
     }
-    fn order_update_status(&self, id: i64, status: &str) -> Result<(), String> {
-        let coll = self.db.collection::<Document>("orders");
-        coll.update_one(
-            doc! { "id": id },
-            doc! { "$set": { "status": status } },
+    fn engagement_path_stats(&self, path: &str, from: &str, to: &str) -> EngagementStats {
+        let coll = self.db.collection::<Document>("engagement_pings");
+        let with_slash = format!("{}/", path);
+        let pipeline = vec![
+            doc! { "$match": {
+                "path": { "$in": [path, with_slash.as_str()] },
+                "created_at": { "$gte": from, "$lte": to },
+            } },
+            doc! { "$group": {
+                "_id": null,
+                "pings": { "$sum": 1 },
+                "avg_scroll": { "$avg": "$scroll" },
+                "avg_seconds": { "$avg": "$seconds" },
+                "read_through": { "$avg": { "$cond": [{ "$gte": ["$scroll", 90_i64] }, 100.0, 0.0] } },
+            } },
+        ];
+        let d = match coll
+            .aggregate(pipeline, None)
+            .ok()
+            .and_then(|mut c| c.next())
+        {
+            Some(Ok(d)) => d,
+            _ => return EngagementStats::default(),
+        };
+        EngagementStats {
+            pings: match d.get("pings") {
+                Some(Bson::Int32(n)) => *n as i64,
+                Some(Bson::Int64(n)) => *n,
+                _ => 0,
+            },
+            avg_scroll: d.get_f64("avg_scroll").unwrap_or(0.0),
+            avg_seconds: d.get_f64("avg_seconds").unwrap_or(0.0),
+            read_through: d.get_f64("read_through").unwrap_or(0.0),
+        }
+    }
+    fn search_log_record(&self, query: &str, results: i64, ip_hash: &str) -> Result<i64, String> {
+        let id = self.next_id("search_log")?;
+        self.db
+            .collection::<Document>("search_log")
+            .insert_one(
+                doc! {
+                    "id": id,
+                    "query": query,
+                    "results": results,
+                    "ip_hash": ip_hash,
+                    "clicked_path": Bson::Null,
+                    "created_at": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                },
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(id)
+    }
+    fn search_log_click(&self, id: i64, ip_hash: &str, path: &str) -> Result<(), String> {
+        self.db
+            .collection::<Document>("search_log")
+            .update_one(
+                doc! { "id": id, "ip_hash": ip_hash, "clicked_path": Bson::Null },
+                doc! { "$set": { "clicked_path": path } },
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+    fn search_top_queries(&self, from: &str, to: &str, limit: i64) -> Vec<SearchQueryStat> {
+        let coll = self.db.collection::<Document>("search_log");
+        let pipeline = vec![
+            doc! { "$match": { "created_at": { "$gte": from, "$lte": to } } },
+            doc! { "$sort": { "id": 1 } },
+            doc! { "$group": {
+                "_id": "$query",
+                "searches": { "$sum": 1 },
+                "results": { "$last": "$results" },
+                "clicks": { "$sum": { "$cond": [{ "$ifNull": ["$clicked_path", false] }, 1, 0] } },
+            } },
+            doc! { "$sort": { "searches": -1, "_id": 1 } },
+            doc! { "$limit": limit },
+        ];
+        let cursor = match coll.aggregate(pipeline, None) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let num = |d: &Document, k: &str| match d.get(k) {
+            Some(Bson::Int32(n)) => *n as i64,
+            Some(Bson::Int64(n)) => *n,
+            _ => 0,
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .map(|d| SearchQueryStat {
+                query: d.get_str("_id").unwrap_or("").to_string(),
+                searches: num(&d, "searches"),
+                results: num(&d, "results"),
+                clicks: num(&d, "clicks"),
+            })
+            .collect()
+    }
+    fn search_zero_result_queries(&self, from: &str, to: &str, limit: i64) -> Vec<CountEntry> {
+        let coll = self.db.collection::<Document>("search_log");
+        let pipeline = vec![
+            doc! { "$match": { "results": 0_i64, "created_at": { "$gte": from, "$lte": to } } },
+            doc! { "$group": { "_id": "$query", "count": { "$sum": 1 } } },
+            doc! { "$sort": { "count": -1, "_id": 1 } },
+            doc! { "$limit": limit },
+        ];
+        let cursor = match coll.aggregate(pipeline, None) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .map(|d| CountEntry {
+                label: d.get_str("_id").unwrap_or("").to_string(),
+                count: match d.get("count") {
+                    Some(Bson::Int32(n)) => *n as i64,
+                    Some(Bson::Int64(n)) => *n,
+                    _ => 0,
+                },
+            })
+            .collect()
+    }
+    fn experiment_event_record(
+        &self,
+        experiment: &str,
+        variant: &str,
+        kind: &str,
+        ip_hash: &str,
+    ) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("experiment_events");
+        let key =
+            doc! { "experiment": experiment, "variant": variant, "kind": kind, "ip_hash": ip_hash };
+        if coll.count_documents(key.clone(), None).unwrap_or(0) > 0 {
+            return Ok(());
+        }
+        let id = self.next_id("experiment_events")?;
+        let mut d = key;
+        d.insert("id", id);
+        d.insert(
+            "created_at",
+            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        );
+        coll.insert_one(d, None).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+    fn experiment_stats(&self, from: &str, to: &str) -> Vec<VariantStat> {
+        let coll = self.db.collection::<Document>("experiment_events");
+        let pipeline = vec![
+            doc! { "$match": { "created_at": { "$gte": from, "$lte": to } } },
+            doc! { "$group": {
+                "_id": { "experiment": "$experiment", "variant": "$variant", "ip_hash": "$ip_hash" },
+                "kinds": { "$addToSet": "$kind" },
+            } },
+            doc! { "$match": { "kinds": "impression" } },
+            doc! { "$group": {
+                "_id": { "experiment": "$_id.experiment", "variant": "$_id.variant" },
+                "impressions": { "$sum": 1 },
+                "clicks": { "$sum": { "$cond": [{ "$in": ["click", "$kinds"] }, 1, 0] } },
+            } },
+        ];
+        let cursor = match coll.aggregate(pipeline, None) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let num = |d: &Document, k: &str| match d.get(k) {
+            Some(Bson::Int32(n)) => *n as i64,
+            Some(Bson::Int64(n)) => *n,
+            _ => 0,
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| {
+                let id = d.get_document("_id").ok()?;
+                Some(VariantStat {
+                    experiment: id.get_str("experiment").unwrap_or("").to_string(),
+                    variant: id.get_str("variant").unwrap_or("").to_string(),
+                    impressions: num(&d, "impressions"),
+                    clicks: num(&d, "clicks"),
+                })
+            })
+            .collect()
+    }
+
+    fn order_find_by_id(&self, id: i64) -> Option<Order> {
+        let coll = self.db.collection::<Document>("orders");
+        let d = coll.find_one(doc! { "id": id }, None).ok()??;
+        doc_to_order(&d)
+    }
+    fn order_find_by_uuid(&self, uuid: &str) -> Option<Order> {
+        let coll = self.db.collection::<Document>("orders");
+        let d = coll.find_one(doc! { "uuid": uuid }, None).ok()??;
+        doc_to_order(&d)
+    }
+    fn order_find_by_provider_order_id(&self, provider_order_id: &str) -> Option<Order> {
+        let coll = self.db.collection::<Document>("orders");
+        let d = coll
+            .find_one(doc! { "provider_order_id": provider_order_id }, None)
+            .ok()??;
+        doc_to_order(&d)
+    }
+    fn order_list(&self, limit: i64, offset: i64) -> Vec<Order> {
+        let coll = self.db.collection::<Document>("orders");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "id": -1 })
+            .skip(offset as u64)
+            .limit(limit)
+            .build();
+        let cursor = match coll.find(doc! {}, opts) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_order(&d))
+            .collect()
+    }
+    fn order_list_by_status(&self, status: &str, limit: i64, offset: i64) -> Vec<Order> {
+        let coll = self.db.collection::<Document>("orders");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "id": -1 })
+            .skip(offset as u64)
+            .limit(limit)
+            .build();
+        let cursor = match coll.find(doc! { "status": status }, opts) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_order(&d))
+            .collect()
+    }
+    fn order_list_since(
+        &self,
+        status: &str,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, Order)> {
+        self.list_status_since("orders", doc! { "status": status }, since_seq, limit)
+            .into_iter()
+            .filter_map(|(seq, d)| Some((seq, doc_to_order(&d)?)))
+            .collect()
+    }
+    fn order_list_by_email(&self, email: &str, limit: i64, offset: i64) -> Vec<Order> {
+        let coll = self.db.collection::<Document>("orders");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "id": -1 })
+            .skip(offset as u64)
+            .limit(limit)
+            .build();
+        let cursor = match coll.find(doc! { "buyer_email": email }, opts) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_order(&d))
+            .collect()
+    }
+    fn order_list_by_portfolio(&self, portfolio_id: i64) -> Vec<Order> {
+        let coll = self.db.collection::<Document>("orders");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "id": -1 })
+            .build();
+        let cursor = match coll.find(doc! { "portfolio_id": portfolio_id }, opts) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| doc_to_order(&d))
+            .collect()
+    }
+    fn order_list_keyset(
+        &self,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Order>, Option<Cursor>) {
+        let coll = self.db.collection::<Document>("orders");
+        let filter = match status {
+            Some(s) => doc! { "status": s },
+            None => doc! {},
+        };
+        keyset_page(&coll, "id", filter, after, limit, doc_to_order)
+    }
+    fn order_count(&self) -> i64 {
+        let coll = self.db.collection::<Document>("orders");
+        coll.count_documents(doc! {}, None).unwrap_or(0) as i64
+    }
+    fn order_count_by_status(&self, status: &str) -> i64 {
+        let coll = self.db.collection::<Document>("orders");
+        coll.count_documents(doc! { "status": status }, None)
+            .unwrap_or(0) as i64
+    }
+    fn order_total_revenue(&self) -> f64 {
+        let coll = self.db.collection::<Document>("orders");
+        let pipeline = vec![
+            doc! { "$match": { "status": "completed" } },
+            doc! { "$group": { "_id": Bson::Null, "total": { "$sum": "$amount" } } },
+        ];
+        let cursor = match coll.aggregate(pipeline, None) {
+            Ok(c) => c,
+            Err(_) => return 0.0,
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .next()
+            .and_then(|d| d.get_f64("total").ok())
+            .unwrap_or(0.0)
+    }
+    fn order_revenue_by_period(&self, days: i64) -> f64 {
+        let coll = self.db.collection::<Document>("orders");
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(days)).to_rfc3339();
+        let pipeline = vec![
+            doc! { "$match": { "status": "completed", "created_at": { "$gte": &cutoff } } },
+            doc! { "$group": { "_id": Bson::Null, "total": { "$sum": "$amount" } } },
+        ];
+        let cursor = match coll.aggregate(pipeline, None) {
+            Ok(c) => c,
+            Err(_) => return 0.0,
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .next()
+            .and_then(|d| d.get_f64("total").ok())
+            .unwrap_or(0.0)
+    }
+    fn order_create(
+        &self,
+        portfolio_id: i64,
+        buyer_email: &str,
+        buyer_name: &str,
+        amount: f64,
+        currency: &str,
+        provider: &str,
+        provider_order_id: &str,
+        status: &str,
+    ) -> Result<(i64, String), String> {
+        let id = self.next_id("orders")?;
+        let order_uuid = uuid::Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+        let coll = self.db.collection::<Document>("orders");
+        coll.insert_one(
+            doc! {
+                "id": id,
+                "uuid": &order_uuid,
+                "portfolio_id": portfolio_id,
+                "buyer_email": buyer_email,
+                "buyer_name": buyer_name,
+                "amount": amount,
+                "currency": currency,
+                "provider": provider,
+                "provider_order_id": provider_order_id,
+                "status": status,
+                "created_at": &now,
+                "status_seq": self.next_id("orders_status_seq")?,
+            },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok((id, order_uuid))
+    }
+    fn order_update_status(&self, id: i64, status: &str) -> Result<(), String> {
+        self.bump_status_seq("orders", id, status)?;
+        let coll = self.db.collection::<Document>("orders");
+        coll.update_one(
+            doc! { "id": id },
+            doc! { "$set": { "status": status } },
             None,
         )
         .map_err(|e| e.to_string())?;
@@ -2679,28 +4440,217 @@ impl Store for MongoStore {
         .map_err(|e| e.to_string())?;
         Ok(id)
     }
-
-    fn search_create_fts_table(&self) -> Result<(), String> {
-        Ok(())
+    fn import_find_by_id(&self, id: i64) -> Option<Import> {
+        let coll = self.db.collection::<Document>("imports");
+        coll.find_one(doc! { "id": id }, None)
+            .ok()
+            .flatten()
+            .and_then(|d| doc_to_import(&d))
+    }
+    fn import_set_created(&self, id: i64, created: &ImportCreated) -> Result<(), String> {
+        let json = serde_json::to_string(created).map_err(|e| e.to_string())?;
+        let coll = self.db.collection::<Document>("imports");
+        coll.update_one(
+            doc! { "id": id },
+            doc! { "$set": { "created": json } },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+    fn import_mark_rolled_back(&self, id: i64) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("imports");
+        coll.update_one(
+            doc! { "id": id },
+            doc! { "$set": { "rolled_back_at": chrono::Utc::now().to_rfc3339() } },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn search_create_fts_table(&self) -> Result<(), String> {
+        use mongodb::options::IndexOptions;
+        use mongodb::IndexModel;
+
+        let coll = self.db.collection::<Document>("search_index");
+        coll.create_index(
+            IndexModel::builder()
+                .keys(doc! { "title": "text", "body": "text" })
+                .options(
+                    IndexOptions::builder()
+                        .name("search_text".to_string())
+                        .weights(doc! { "title": TITLE_WEIGHT as i32, "body": 1 })
+                        .default_language("english".to_string())
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        coll.create_index(
+            IndexModel::builder()
+                .keys(doc! { "item_type": 1, "item_id": 1 })
+                .build(),
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
     }
     fn search_rebuild_index(&self) -> Result<usize, String> {
-        Ok(0)
+        let coll = self.db.collection::<Document>("search_index");
+        coll.delete_many(doc! {}, None).map_err(|e| e.to_string())?;
+        let mut docs = Vec::new();
+        for p in self.post_list(Some("published"), i64::MAX, 0) {
+            docs.push(search_doc(
+                "post",
+                p.id,
+                &p.title,
+                &p.content_html,
+                &p.slug,
+                p.featured_image.as_deref(),
+                p.published_at.map(|d| d.to_string()).as_deref(),
+            ));
+        }
+        for p in self.portfolio_list(Some("published"), i64::MAX, 0) {
+            docs.push(search_doc(
+                "portfolio",
+                p.id,
+                &p.title,
+                p.description_html.as_deref().unwrap_or(""),
+                &p.slug,
+                Some(&p.image_path),
+                p.published_at.map(|d| d.to_string()).as_deref(),
+            ));
+        }
+        let count = docs.len();
+        if !docs.is_empty() {
+            coll.insert_many(docs, None).map_err(|e| e.to_string())?;
+        }
+        Ok(count)
     }
     fn search_upsert_item(
         &self,
-        _item_type: &str,
-        _item_id: i64,
-        _title: &str,
-        _html_body: &str,
-        _slug: &str,
-        _image: Option<&str>,
-        _date: Option<&str>,
-        _is_published: bool,
+        item_type: &str,
+        item_id: i64,
+        title: &str,
+        html_body: &str,
+        slug: &str,
+        image: Option<&str>,
+        date: Option<&str>,
+        is_published: bool,
     ) {
+        let coll = self.db.collection::<Document>("search_index");
+        let _ = coll.delete_many(doc! { "item_type": item_type, "item_id": item_id }, None);
+        if is_published {
+            let _ = coll.insert_one(
+                search_doc(item_type, item_id, title, html_body, slug, image, date),
+                None,
+            );
+        }
+        crate::search_engine::sync_item(
+            self,
+            item_type,
+            item_id,
+            title,
+            html_body,
+            slug,
+            image,
+            date,
+            is_published,
+        );
     }
-    fn search_remove_item(&self, _item_type: &str, _item_id: i64) {}
-    fn search_query(&self, _query: &str, _limit: i64) -> Vec<SearchResult> {
-        vec![]
+    fn search_remove_item(&self, item_type: &str, item_id: i64) {
+        let coll = self.db.collection::<Document>("search_index");
+        let _ = coll.delete_many(doc! { "item_type": item_type, "item_id": item_id }, None);
+        crate::search_engine::sync_removal(self, item_type, item_id);
+    }
+    fn search_query(&self, query: &str, limit: i64) -> Vec<SearchResult> {
+        let terms = query_terms(query);
+        if terms.is_empty() || limit <= 0 {
+            return vec![];
+        }
+        let coll = self.db.collection::<Document>("search_index");
+
+        // Whole words (stemmed by the text index, titles weighted), scored
+        // by MongoDB's text score; ranks are negated so lower is better
+        let opts = mongodb::options::FindOptions::builder()
+            .projection(doc! { "score": { "$meta": "textScore" } })
+            .sort(doc! { "score": { "$meta": "textScore" } })
+            .limit(limit)
+            .build();
+        let mut results: Vec<SearchResult> =
+            match coll.find(doc! { "$text": { "$search": terms.join(" ") } }, opts) {
+                Ok(cursor) => cursor
+                    .filter_map(|r| r.ok())
+                    .filter_map(|d| {
+                        let score = d.get_f64("score").unwrap_or(0.0);
+                        doc_to_search_result(&d, -score, &terms)
+                    })
+                    .collect(),
+                Err(_) => vec![],
+            };
+
+        // Prefixes and partial words, as the SQLite trigram pass does
+        let substrings: Vec<&String> = terms
+            .iter()
+            .filter(|t| t.chars().count() >= MIN_SUBSTRING_LEN)
+            .collect();
+        if results.len() as i64 >= limit || substrings.is_empty() {
+            return results;
+        }
+        let filter = doc! {
+            "$and": substrings
+                .iter()
+                .map(|t| {
+                    let pattern = regex::escape(t);
+                    doc! { "$or": [
+                        { "title": { "$regex": &pattern, "$options": "i" } },
+                        { "body": { "$regex": &pattern, "$options": "i" } },
+                    ] }
+                })
+                .collect::<Vec<_>>()
+        };
+        let opts = mongodb::options::FindOptions::builder()
+            .limit(limit + results.len() as i64)
+            .build();
+        let floor = results.last().map(|r| r.rank).unwrap_or(0.0);
+        let mut extra: Vec<SearchResult> = match coll.find(filter, opts) {
+            Ok(cursor) => cursor
+                .filter_map(|r| r.ok())
+                .filter_map(|d| {
+                    let title = d.get_str("title").unwrap_or("").to_lowercase();
+                    let body = d.get_str("body").unwrap_or("").to_lowercase();
+                    let score: f64 = substrings
+                        .iter()
+                        .map(|t| {
+                            let t = t.to_lowercase();
+                            let in_title = if title.contains(&t) {
+                                TITLE_WEIGHT
+                            } else {
+                                0.0
+                            };
+                            in_title + if body.contains(&t) { 1.0 } else { 0.0 }
+                        })
+                        .sum();
+                    doc_to_search_result(&d, substring_rank(floor, score), &terms)
+                })
+                .collect(),
+            Err(_) => vec![],
+        };
+        extra.sort_by(|a, b| a.rank.total_cmp(&b.rank));
+        for r in extra {
+            if results.len() as i64 >= limit {
+                break;
+            }
+            if !results
+                .iter()
+                .any(|e| e.item_type == r.item_type && e.item_id == r.item_id)
+            {
+                results.push(r);
+            }
+        }
+        results
     }
 
     fn session_create(&self, user_id: i64, token: &str, expires_at: &str) -> Result<(), String> {
@@ -2741,6 +4691,27 @@ impl Store for MongoStore {
         Ok(())
     }
 
+    fn session_list_for_user(&self, user_id: i64) -> Vec<serde_json::Value> {
+        let coll = self.db.collection::<Document>("sessions");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "created_at": -1 })
+            .build();
+        match coll.find(doc! { "user_id": user_id }, opts) {
+            Ok(cursor) => cursor
+                .filter_map(|r| r.ok())
+                .map(|d| {
+                    serde_json::json!({
+                        "created_at": d.get_str("created_at").unwrap_or(""),
+                        "expires_at": d.get_str("expires_at").unwrap_or(""),
+                        "ip_address": d.get_str("ip_address").ok(),
+                        "user_agent": d.get_str("user_agent").ok(),
+                    })
+                })
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
     fn session_create_full(
         &self,
         user_id: i64,
@@ -2781,6 +4752,33 @@ impl Store for MongoStore {
         .unwrap_or(0) as i64
     }
 
+    fn session_set_sudo(&self, token: &str, minutes: i64) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("sessions");
+        let until = (chrono::Utc::now() + chrono::Duration::minutes(minutes.max(0))).to_rfc3339();
+        coll.update_one(
+            doc! { "token": token },
+            doc! { "$set": { "sudo_until": until } },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn session_sudo_active(&self, token: &str) -> bool {
+        let coll = self.db.collection::<Document>("sessions");
+        let now = chrono::Utc::now().to_rfc3339();
+        coll.count_documents(
+            doc! {
+                "token": token,
+                "expires_at": { "$gt": &now },
+                "sudo_until": { "$gt": &now },
+            },
+            None,
+        )
+        .unwrap_or(0)
+            > 0
+    }
+
     fn magic_link_create(
         &self,
         token: &str,
@@ -2860,6 +4858,18 @@ impl Store for MongoStore {
     }
 
     fn analytics_prune(&self, before_date: &str) -> Result<usize, String> {
+        for name in [
+            "goal_completions",
+            "outbound_clicks",
+            "search_log",
+            "experiment_events",
+            "engagement_pings",
+        ] {
+            let _ = self
+                .db
+                .collection::<Document>(name)
+                .delete_many(doc! { "created_at": { "$lt": before_date } }, None);
+        }
         let coll = self.db.collection::<Document>("page_views");
         let result = coll
             .delete_many(doc! { "created_at": { "$lt": before_date } }, None)
@@ -2867,6 +4877,25 @@ impl Store for MongoStore {
         Ok(result.deleted_count as usize)
     }
 
+    fn analytics_forget_visitors(&self, ip_hashes: &[String]) -> Result<usize, String> {
+        let filter = doc! { "ip_hash": { "$in": ip_hashes } };
+        for name in [
+            "goal_completions",
+            "outbound_clicks",
+            "search_log",
+            "experiment_events",
+            "engagement_pings",
+        ] {
+            let _ = self
+                .db
+                .collection::<Document>(name)
+                .delete_many(filter.clone(), None);
+        }
+        let coll = self.db.collection::<Document>("page_views");
+        let result = coll.delete_many(filter, None).map_err(|e| e.to_string())?;
+        Ok(result.deleted_count as usize)
+    }
+
     fn analytics_count(&self) -> i64 {
         let coll = self.db.collection::<Document>("page_views");
         coll.count_documents(doc! {}, None).unwrap_or(0) as i64
@@ -3204,170 +5233,1179 @@ impl Store for MongoStore {
         let update = doc! {
             "$set": { "status": "published", "updated_at": &now }
         };
+        let due: Vec<i64> = self
+            .db
+            .collection::<Document>("posts")
+            .find(filter.clone(), None)
+            .map(|c| {
+                c.filter_map(|r| r.ok())
+                    .filter_map(|d| d.get_i64("id").ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        for id in due {
+            self.bump_status_seq("posts", id, "published")?;
+        }
         let mut total = 0usize;
-        for coll_name in &["posts", "portfolio"] {
+        for (coll_name, section) in [("posts", Section::Posts), ("portfolio", Section::Portfolio)] {
             let coll = self.db.collection::<Document>(coll_name);
             if let Ok(result) = coll.update_many(filter.clone(), update.clone(), None) {
                 total += result.modified_count as usize;
+                if result.modified_count > 0 {
+                    super::content_changed(section);
+                }
             }
         }
         Ok(total)
     }
 
-    fn task_cleanup_sessions(&self, max_age_days: i64) -> Result<usize, String> {
-        let coll = self.db.collection::<Document>("sessions");
-        let now = chrono::Utc::now();
-        let now_str = now.format("%Y-%m-%dT%H:%M:%S").to_string();
-        let cutoff = (now - chrono::Duration::days(max_age_days))
-            .format("%Y-%m-%dT%H:%M:%S")
+    fn task_cleanup_sessions(&self, max_age_days: i64) -> Result<usize, String> {
+        let coll = self.db.collection::<Document>("sessions");
+        let now = chrono::Utc::now();
+        let now_str = now.format("%Y-%m-%dT%H:%M:%S").to_string();
+        let cutoff = (now - chrono::Duration::days(max_age_days))
+            .format("%Y-%m-%dT%H:%M:%S")
+            .to_string();
+        let filter = doc! {
+            "$or": [
+                { "expires_at": { "$lt": &now_str } },
+                { "created_at": { "$lt": &cutoff } }
+            ]
+        };
+        let count = coll.count_documents(filter.clone(), None).unwrap_or(0) as usize;
+        coll.delete_many(filter, None).map_err(|e| e.to_string())?;
+        Ok(count)
+    }
+
+    fn task_cleanup_analytics(&self, max_age_days: i64) -> Result<usize, String> {
+        let since = chrono::Utc::now() - chrono::Duration::days(max_age_days);
+        for name in [
+            "goal_completions",
+            "outbound_clicks",
+            "search_log",
+            "experiment_events",
+            "engagement_pings",
+        ] {
+            let _ = self.db.collection::<Document>(name).delete_many(
+                doc! { "created_at": { "$lt": since.format("%Y-%m-%d %H:%M:%S").to_string() } },
+                None,
+            );
+        }
+        let coll = self.db.collection::<Document>("page_views");
+        let cutoff = since.format("%Y-%m-%dT%H:%M:%S").to_string();
+        let filter = doc! { "created_at": { "$lt": &cutoff } };
+        let count = coll.count_documents(filter.clone(), None).unwrap_or(0) as usize;
+        coll.delete_many(filter, None).map_err(|e| e.to_string())?;
+        Ok(count)
+    }
+
+    // ── Email queue (built-in MTA) ──────────────────────────────────
+
+    fn mta_queue_push(
+        &self,
+        to: &str,
+        from: &str,
+        subject: &str,
+        body: &str,
+        reply_to: &str,
+    ) -> Result<i64, String> {
+        let id = self.next_id("email_queue")?;
+        let now = chrono::Utc::now().to_rfc3339();
+        let coll = self.db.collection::<Document>("email_queue");
+        coll.insert_one(
+            doc! {
+                "id": id,
+                "to_addr": to,
+                "from_addr": from,
+                "subject": subject,
+                "body_text": body,
+                "reply_to": reply_to,
+                "attempts": 0_i64,
+                "max_attempts": 5_i64,
+                "next_retry_at": &now,
+                "status": "pending",
+                "error": "",
+                "error_log": "",
+                "created_at": &now,
+            },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(id)
+    }
+
+    fn mta_queue_pending(&self, limit: i64) -> Vec<crate::mta::queue::QueuedEmail> {
+        let coll = self.db.collection::<Document>("email_queue");
+        let now = chrono::Utc::now().to_rfc3339();
+        let filter = doc! {
+            "status": "pending",
+            "next_retry_at": { "$lte": &now },
+        };
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "next_retry_at": 1 })
+            .limit(Some(limit))
+            .build();
+        let cursor = match coll.find(filter, Some(opts)) {
+            Ok(c) => c,
+            Err(_) => return Vec::new(),
+        };
+        cursor
+            .flatten()
+            .filter_map(|d| doc_to_queued_email(&d))
+            .collect()
+    }
+
+    fn mta_queue_update_status(
+        &self,
+        id: i64,
+        status: &str,
+        error: Option<&str>,
+        next_retry: Option<&str>,
+    ) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("email_queue");
+        let mut update = doc! { "$set": { "status": status } };
+        if let Some(e) = error {
+            let existing = coll.find_one(doc! { "id": id }, None).ok().flatten();
+            let attempts = existing
+                .as_ref()
+                .and_then(|d| d.get_i64("attempts").ok())
+                .unwrap_or(0);
+            let mut log = existing
+                .as_ref()
+                .and_then(|d| d.get_str("error_log").ok())
+                .unwrap_or("")
+                .to_string();
+            log.push_str(&crate::mta::queue::error_log_line(attempts, e));
+            let set = update.get_document_mut("$set").unwrap();
+            set.insert("error", e);
+            set.insert("error_log", log);
+        }
+        if let Some(nr) = next_retry {
+            update
+                .get_document_mut("$set")
+                .unwrap()
+                .insert("next_retry_at", nr);
+        }
+        if status == "sending" {
+            update.insert("$inc", doc! { "attempts": 1_i64 });
+        }
+        if status == "sent" {
+            update
+                .get_document_mut("$set")
+                .unwrap()
+                .insert("sent_at", chrono::Utc::now().to_rfc3339());
+        }
+        coll.update_one(doc! { "id": id }, update, None)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn mta_queue_sent_last_hour(&self) -> Result<u64, String> {
+        let coll = self.db.collection::<Document>("email_queue");
+        let one_hour_ago = (chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339();
+        let filter = sent_since_filter(&one_hour_ago);
+        coll.count_documents(filter, None)
+            .map_err(|e| e.to_string())
+    }
+
+    fn mta_queue_stats(&self) -> (u64, u64, u64, u64) {
+        let coll = self.db.collection::<Document>("email_queue");
+        let sent = coll
+            .count_documents(doc! { "status": "sent" }, None)
+            .unwrap_or(0);
+        let pending = coll
+            .count_documents(doc! { "status": { "$in": ["pending", "sending"] } }, None)
+            .unwrap_or(0);
+        let failed = coll
+            .count_documents(doc! { "status": "failed" }, None)
+            .unwrap_or(0);
+        let total = coll.count_documents(doc! {}, None).unwrap_or(0);
+        (sent, pending, failed, total)
+    }
+
+    fn mta_queue_cleanup(&self, days: u64) -> Result<u64, String> {
+        let coll = self.db.collection::<Document>("email_queue");
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339();
+        let filter = doc! { "created_at": { "$lt": &cutoff } };
+        let result = coll.delete_many(filter, None).map_err(|e| e.to_string())?;
+        Ok(result.deleted_count)
+    }
+
+    fn mta_queue_list(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<crate::mta::queue::QueuedEmail> {
+        let coll = self.db.collection::<Document>("email_queue");
+        let filter = match status {
+            Some(st) => doc! { "status": st },
+            None => doc! {},
+        };
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "created_at": -1, "id": -1 })
+            .skip(Some(offset as u64))
+            .limit(Some(limit))
+            .build();
+        match coll.find(filter, Some(opts)) {
+            Ok(cursor) => cursor
+                .flatten()
+                .filter_map(|d| doc_to_queued_email(&d))
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn mta_queue_count(&self, status: Option<&str>) -> u64 {
+        let coll = self.db.collection::<Document>("email_queue");
+        let filter = match status {
+            Some(st) => doc! { "status": st },
+            None => doc! {},
+        };
+        coll.count_documents(filter, None).unwrap_or(0)
+    }
+
+    fn mta_queue_get(&self, id: i64) -> Option<crate::mta::queue::QueuedEmail> {
+        let coll = self.db.collection::<Document>("email_queue");
+        coll.find_one(doc! { "id": id }, None)
+            .ok()
+            .flatten()
+            .and_then(|d| doc_to_queued_email(&d))
+    }
+
+    fn mta_queue_retry(&self, id: i64) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("email_queue");
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = coll
+            .update_one(
+                doc! { "id": id, "status": { "$ne": "sending" } },
+                doc! { "$set": { "status": "pending", "attempts": 0_i64, "next_retry_at": &now } },
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        if result.matched_count == 0 {
+            return Err("Message not found or currently sending".to_string());
+        }
+        Ok(())
+    }
+
+    fn mta_queue_delete(&self, id: i64) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("email_queue");
+        coll.delete_one(doc! { "id": id }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn mta_queue_sent_hourly(&self, hours: i64) -> Vec<(String, u64)> {
+        let coll = self.db.collection::<Document>("email_queue");
+        let since = (chrono::Utc::now() - chrono::Duration::hours(hours)).to_rfc3339();
+        let cursor = match coll.find(sent_since_filter(&since), None) {
+            Ok(c) => c,
+            Err(_) => return Vec::new(),
+        };
+        let mut buckets: std::collections::BTreeMap<String, u64> =
+            std::collections::BTreeMap::new();
+        for d in cursor.flatten() {
+            if let Some(ts) = d
+                .get_str("sent_at")
+                .or_else(|_| d.get_str("created_at"))
+                .ok()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            {
+                let key = ts
+                    .with_timezone(&chrono::Utc)
+                    .format("%Y-%m-%d %H:00")
+                    .to_string();
+                *buckets.entry(key).or_insert(0) += 1;
+            }
+        }
+        buckets.into_iter().collect()
+    }
+
+    fn mta_reply_token_create(
+        &self,
+        token: &str,
+        post_id: i64,
+        content_type: &str,
+        comment_id: i64,
+        email: &str,
+        expires_days: i64,
+    ) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("mta_reply_tokens");
+        let now = chrono::Utc::now().to_rfc3339();
+        let expires = (chrono::Utc::now() + chrono::Duration::days(expires_days)).to_rfc3339();
+        coll.insert_one(
+            doc! {
+                "token": token,
+                "post_id": post_id,
+                "content_type": content_type,
+                "comment_id": comment_id,
+                "email": email,
+                "created_at": &now,
+                "expires_at": &expires,
+            },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn mta_reply_token_find(&self, token: &str) -> Option<crate::mta::inbound::ReplyToken> {
+        let coll = self.db.collection::<Document>("mta_reply_tokens");
+        let now = chrono::Utc::now().to_rfc3339();
+        let d = coll
+            .find_one(doc! { "token": token, "expires_at": { "$gt": &now } }, None)
+            .ok()??;
+        Some(crate::mta::inbound::ReplyToken {
+            token: d.get_str("token").ok()?.to_string(),
+            post_id: d.get_i64("post_id").ok()?,
+            content_type: d.get_str("content_type").unwrap_or("post").to_string(),
+            comment_id: d.get_i64("comment_id").ok()?,
+            email: d.get_str("email").ok()?.to_string(),
+            expires_at: d.get_str("expires_at").unwrap_or("").to_string(),
+        })
+    }
+
+    fn mta_reply_token_consume(&self, token: &str) -> Result<bool, String> {
+        let coll = self.db.collection::<Document>("mta_reply_tokens");
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = coll
+            .delete_one(doc! { "token": token, "expires_at": { "$gt": &now } }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(result.deleted_count > 0)
+    }
+
+    fn mta_reply_token_cleanup(&self) -> Result<usize, String> {
+        let coll = self.db.collection::<Document>("mta_reply_tokens");
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = coll
+            .delete_many(doc! { "expires_at": { "$lt": &now } }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(result.deleted_count as usize)
+    }
+
+    fn email_quota_record(&self, provider: &str) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("email_quota_usage");
+        let (hour, day) = crate::email::routing::quota_buckets(chrono::Utc::now());
+        let opts = mongodb::options::UpdateOptions::builder()
+            .upsert(true)
+            .build();
+        for bucket in [hour, day] {
+            coll.update_one(
+                doc! { "provider": provider, "bucket": &bucket },
+                doc! { "$inc": { "count": 1_i64 } },
+                Some(opts.clone()),
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn email_quota_usage(&self, provider: &str) -> (u64, u64) {
+        let coll = self.db.collection::<Document>("email_quota_usage");
+        let (hour, day) = crate::email::routing::quota_buckets(chrono::Utc::now());
+        let get = |bucket: &str| -> u64 {
+            coll.find_one(doc! { "provider": provider, "bucket": bucket }, None)
+                .ok()
+                .flatten()
+                .and_then(|d| d.get_i64("count").ok())
+                .unwrap_or(0) as u64
+        };
+        (get(&hour), get(&day))
+    }
+
+    fn email_quota_cleanup(&self, days: i64) -> Result<usize, String> {
+        let coll = self.db.collection::<Document>("email_quota_usage");
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(days))
+            .format("%Y-%m-%d")
+            .to_string();
+        let result = coll
+            .delete_many(doc! { "bucket": { "$lt": &cutoff } }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(result.deleted_count as usize)
+    }
+
+    fn signed_token_create(
+        &self,
+        token_hash: &str,
+        purpose: &str,
+        email: &str,
+        payload: &str,
+        expires_minutes: i64,
+    ) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("signed_tokens");
+        let now = chrono::Utc::now().to_rfc3339();
+        let expires =
+            (chrono::Utc::now() + chrono::Duration::minutes(expires_minutes)).to_rfc3339();
+        coll.insert_one(
+            doc! {
+                "token_hash": token_hash,
+                "purpose": purpose,
+                "email": email,
+                "payload": payload,
+                "created_at": &now,
+                "expires_at": &expires,
+                "used": false,
+            },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn signed_token_consume(
+        &self,
+        token_hash: &str,
+        purpose: &str,
+    ) -> Result<crate::security::signed_token::SignedToken, String> {
+        let coll = self.db.collection::<Document>("signed_tokens");
+        let now = chrono::Utc::now().to_rfc3339();
+        // Claim atomically so concurrent requests can't both redeem the token
+        let claimed = coll
+            .find_one_and_update(
+                doc! {
+                    "token_hash": token_hash,
+                    "purpose": purpose,
+                    "used": false,
+                    "expires_at": { "$gt": &now },
+                },
+                doc! { "$set": { "used": true, "used_at": &now } },
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        match claimed {
+            Some(d) => Ok(crate::security::signed_token::SignedToken {
+                purpose: d.get_str("purpose").unwrap_or(purpose).to_string(),
+                email: d.get_str("email").unwrap_or("").to_string(),
+                payload: d.get_str("payload").unwrap_or("").to_string(),
+                expires_at: d.get_str("expires_at").unwrap_or("").to_string(),
+            }),
+            None => {
+                let used = coll
+                    .find_one(
+                        doc! {
+                            "token_hash": token_hash,
+                            "purpose": purpose,
+                            "used": true,
+                            "expires_at": { "$gt": &now },
+                        },
+                        None,
+                    )
+                    .map_err(|e| e.to_string())?;
+                if used.is_some() {
+                    Err("This link has already been used".into())
+                } else {
+                    Err("Invalid or expired link".into())
+                }
+            }
+        }
+    }
+
+    fn signed_token_cleanup(&self) -> Result<usize, String> {
+        let coll = self.db.collection::<Document>("signed_tokens");
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = coll
+            .delete_many(
+                doc! { "$or": [ { "expires_at": { "$lt": &now } }, { "used": true } ] },
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(result.deleted_count as usize)
+    }
+
+    fn newsletter_subscribe(&self, email: &str) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("newsletter_subscribers");
+        let opts = mongodb::options::UpdateOptions::builder()
+            .upsert(true)
+            .build();
+        coll.update_one(
+            doc! { "email": email.to_lowercase() },
+            doc! { "$setOnInsert": {
+                "status": "pending",
+                "created_at": chrono::Utc::now().to_rfc3339(),
+            } },
+            opts,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn newsletter_confirm(&self, email: &str) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("newsletter_subscribers");
+        coll.update_one(
+            doc! { "email": email.to_lowercase() },
+            doc! { "$set": {
+                "status": "confirmed",
+                "confirmed_at": chrono::Utc::now().to_rfc3339(),
+            } },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn newsletter_status(&self, email: &str) -> Option<String> {
+        let coll = self.db.collection::<Document>("newsletter_subscribers");
+        let d = coll
+            .find_one(doc! { "email": email.to_lowercase() }, None)
+            .ok()??;
+        d.get_str("status").ok().map(|s| s.to_string())
+    }
+
+    fn newsletter_count(&self, status: Option<&str>) -> i64 {
+        let coll = self.db.collection::<Document>("newsletter_subscribers");
+        let filter = match status {
+            Some(s) => doc! { "status": s },
+            None => doc! {},
+        };
+        coll.count_documents(filter, None).unwrap_or(0) as i64
+    }
+
+    fn comment_subscription_create(
+        &self,
+        post_id: i64,
+        content_type: &str,
+        email: &str,
+    ) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("comment_subscriptions");
+        let opts = mongodb::options::UpdateOptions::builder()
+            .upsert(true)
+            .build();
+        coll.update_one(
+            doc! {
+                "post_id": post_id,
+                "content_type": content_type,
+                "email": email.to_lowercase(),
+            },
+            doc! { "$setOnInsert": {
+                "confirmed": false,
+                "created_at": chrono::Utc::now().to_rfc3339(),
+            } },
+            opts,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn comment_subscription_confirm(
+        &self,
+        post_id: i64,
+        content_type: &str,
+        email: &str,
+    ) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("comment_subscriptions");
+        coll.update_one(
+            doc! {
+                "post_id": post_id,
+                "content_type": content_type,
+                "email": email.to_lowercase(),
+            },
+            doc! { "$set": { "confirmed": true } },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn comment_subscribers(&self, post_id: i64, content_type: &str) -> Vec<String> {
+        let coll = self.db.collection::<Document>("comment_subscriptions");
+        let cursor = match coll.find(
+            doc! { "post_id": post_id, "content_type": content_type, "confirmed": true },
+            None,
+        ) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| d.get_str("email").ok().map(|s| s.to_string()))
+            .collect()
+    }
+
+    fn comment_subscription_list_by_email(&self, email: &str) -> Vec<(i64, String)> {
+        let coll = self.db.collection::<Document>("comment_subscriptions");
+        match coll.find(doc! { "email": email.to_lowercase() }, None) {
+            Ok(cursor) => cursor
+                .filter_map(|r| r.ok())
+                .filter_map(|d| {
+                    Some((
+                        d.get_i64("post_id").ok()?,
+                        d.get_str("content_type").unwrap_or("post").to_string(),
+                    ))
+                })
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    fn subscriber_forget(&self, email: &str) -> Result<(), String> {
+        let email = email.to_lowercase();
+        self.db
+            .collection::<Document>("newsletter_subscribers")
+            .delete_many(doc! { "email": &email }, None)
+            .map_err(|e| e.to_string())?;
+        self.db
+            .collection::<Document>("comment_subscriptions")
+            .delete_many(doc! { "email": &email }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn role_list(&self) -> Vec<Role> {
+        let coll = self.db.collection::<Document>("roles");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "name": 1 })
+            .build();
+        match coll.find(doc! {}, opts) {
+            Ok(cursor) => cursor
+                .filter_map(|r| r.ok())
+                .map(|d| doc_to_role(&d))
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    fn role_find(&self, slug: &str) -> Option<Role> {
+        self.roles.get_or_load(slug, || {
+            let coll = self.db.collection::<Document>("roles");
+            coll.find_one(doc! { "slug": slug }, None)
+                .ok()
+                .flatten()
+                .map(|d| doc_to_role(&d))
+        })
+    }
+
+    fn role_save(&self, slug: &str, name: &str, capabilities: &[String]) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("roles");
+        let opts = mongodb::options::UpdateOptions::builder()
+            .upsert(true)
+            .build();
+        coll.update_one(
+            doc! { "slug": slug },
+            doc! {
+                "$set": { "name": name, "capabilities": capabilities.join(",") },
+                "$setOnInsert": { "created_at": chrono::Utc::now().to_rfc3339() },
+            },
+            opts,
+        )
+        .map_err(|e| e.to_string())?;
+        role::invalidate();
+        Ok(())
+    }
+
+    fn role_delete(&self, slug: &str) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("roles");
+        coll.delete_one(doc! { "slug": slug }, None)
+            .map_err(|e| e.to_string())?;
+        role::invalidate();
+        Ok(())
+    }
+
+    fn login_device_touch(
+        &self,
+        user_id: i64,
+        ip_hash: &str,
+        device: &str,
+        location: &str,
+    ) -> Result<bool, String> {
+        let coll = self.db.collection::<Document>("login_devices");
+        let now = chrono::Utc::now().to_rfc3339();
+        let opts = mongodb::options::UpdateOptions::builder()
+            .upsert(true)
+            .build();
+        let res = coll
+            .update_one(
+                doc! { "user_id": user_id, "ip_hash": ip_hash, "device": device },
+                doc! {
+                    "$set": { "location": location, "last_seen": &now },
+                    "$setOnInsert": { "first_seen": &now },
+                },
+                opts,
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(res.upserted_id.is_some())
+    }
+
+    fn login_device_count(&self, user_id: i64) -> i64 {
+        let coll = self.db.collection::<Document>("login_devices");
+        coll.count_documents(doc! { "user_id": user_id }, None)
+            .unwrap_or(0) as i64
+    }
+
+    fn login_device_forget(&self, user_id: i64) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("login_devices");
+        coll.delete_many(doc! { "user_id": user_id }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn login_device_list(&self, user_id: i64) -> Vec<serde_json::Value> {
+        let coll = self.db.collection::<Document>("login_devices");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "last_seen": -1 })
+            .build();
+        match coll.find(doc! { "user_id": user_id }, opts) {
+            Ok(cursor) => cursor
+                .filter_map(|r| r.ok())
+                .map(|d| {
+                    serde_json::json!({
+                        "ip_hash": d.get_str("ip_hash").unwrap_or(""),
+                        "device": d.get_str("device").unwrap_or(""),
+                        "location": d.get_str("location").unwrap_or(""),
+                        "first_seen": d.get_str("first_seen").unwrap_or(""),
+                        "last_seen": d.get_str("last_seen").unwrap_or(""),
+                    })
+                })
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    fn rate_limit_hit(
+        &self,
+        key: &str,
+        max_attempts: u64,
+        window_secs: i64,
+    ) -> Result<bool, String> {
+        let coll = self.db.collection::<Document>("rate_limit_hits");
+        let now = chrono::Utc::now().timestamp_millis();
+        coll.delete_many(
+            doc! { "key": key, "hit_at": { "$lte": now - window_secs * 1000 } },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        let count = coll
+            .count_documents(doc! { "key": key }, None)
+            .map_err(|e| e.to_string())?;
+        if count >= max_attempts {
+            return Ok(false);
+        }
+        coll.insert_one(doc! { "key": key, "hit_at": now }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(true)
+    }
+
+    fn rate_limit_budget(
+        &self,
+        key: &str,
+        max_requests: u64,
+        window_secs: i64,
+    ) -> Result<crate::rate_limit::Budget, String> {
+        let coll = self.db.collection::<Document>("rate_limit_hits");
+        let now = chrono::Utc::now().timestamp_millis();
+        coll.delete_many(
+            doc! { "key": key, "hit_at": { "$lte": now - window_secs * 1000 } },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        let count = coll
+            .count_documents(doc! { "key": key }, None)
+            .map_err(|e| e.to_string())?;
+        let allowed = count < max_requests;
+        if allowed {
+            coll.insert_one(doc! { "key": key, "hit_at": now }, None)
+                .map_err(|e| e.to_string())?;
+        }
+        let opts = mongodb::options::FindOneOptions::builder()
+            .sort(doc! { "hit_at": 1 })
+            .build();
+        let oldest = coll
+            .find_one(doc! { "key": key }, opts)
+            .ok()
+            .flatten()
+            .and_then(|d| d.get_i64("hit_at").ok())
+            .unwrap_or(now);
+        Ok(crate::rate_limit::Budget::counted(
+            allowed,
+            max_requests,
+            count + u64::from(allowed),
+            std::time::Duration::from_millis((now - oldest).max(0) as u64),
+            std::time::Duration::from_secs(window_secs.max(0) as u64),
+        ))
+    }
+
+    fn rate_limit_cleanup(&self, max_age_secs: i64) -> Result<usize, String> {
+        let coll = self.db.collection::<Document>("rate_limit_hits");
+        let cutoff = chrono::Utc::now().timestamp_millis() - max_age_secs * 1000;
+        let res = coll
+            .delete_many(doc! { "hit_at": { "$lte": cutoff } }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(res.deleted_count as usize)
+    }
+
+    fn embedding_upsert(&self, embedding: &Embedding) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("embeddings");
+        let vector: Vec<Bson> = embedding
+            .vector
+            .iter()
+            .map(|v| Bson::Double(*v as f64))
+            .collect();
+        let opts = mongodb::options::UpdateOptions::builder()
+            .upsert(true)
+            .build();
+        coll.update_one(
+            doc! { "item_type": &embedding.item_type, "item_id": embedding.item_id },
+            doc! {
+                "$set": {
+                    "model": &embedding.model,
+                    "content_hash": &embedding.content_hash,
+                    "vector": vector,
+                    "updated_at": chrono::Utc::now().to_rfc3339(),
+                },
+            },
+            opts,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn embedding_remove(&self, item_type: &str, item_id: i64) {
+        let coll = self.db.collection::<Document>("embeddings");
+        let _ = coll.delete_many(doc! { "item_type": item_type, "item_id": item_id }, None);
+    }
+
+    fn embedding_list(&self, model: &str) -> Vec<Embedding> {
+        let coll = self.db.collection::<Document>("embeddings");
+        let cursor = match coll.find(doc! { "model": model }, None) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .map(|d| Embedding {
+                item_type: d.get_str("item_type").unwrap_or("").to_string(),
+                item_id: d.get_i64("item_id").unwrap_or(0),
+                model: d.get_str("model").unwrap_or("").to_string(),
+                content_hash: d.get_str("content_hash").unwrap_or("").to_string(),
+                vector: d
+                    .get_array("vector")
+                    .map(|a| {
+                        a.iter()
+                            .filter_map(|v| v.as_f64())
+                            .map(|v| v as f32)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    fn embedding_prune(&self, keep_model: &str) -> Result<usize, String> {
+        let coll = self.db.collection::<Document>("embeddings");
+        let res = coll
+            .delete_many(doc! { "model": { "$ne": keep_model } }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(res.deleted_count as usize)
+    }
+
+    fn seo_suggestion_create(&self, suggestion: &SeoSuggestion) -> Result<i64, String> {
+        let coll = self.db.collection::<Document>("seo_suggestions");
+        let id = self.next_id("seo_suggestions")?;
+        coll.insert_one(
+            doc! {
+                "id": id,
+                "content_type": &suggestion.content_type,
+                "content_id": suggestion.content_id,
+                "field": &suggestion.field,
+                "current": &suggestion.current,
+                "suggested": &suggestion.suggested,
+                "level": suggestion.level,
+                "reason": &suggestion.reason,
+                "status": "pending",
+                "created_at": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(id)
+    }
+
+    fn seo_suggestion_get(&self, id: i64) -> Option<SeoSuggestion> {
+        let coll = self.db.collection::<Document>("seo_suggestions");
+        coll.find_one(doc! { "id": id }, None)
+            .ok()
+            .flatten()
+            .and_then(|d| doc_to_seo_suggestion(&d))
+    }
+
+    fn seo_suggestion_pending(&self, content: Option<(&str, i64)>) -> Vec<SeoSuggestion> {
+        let coll = self.db.collection::<Document>("seo_suggestions");
+        let mut filter = doc! { "status": "pending" };
+        if let Some((content_type, content_id)) = content {
+            filter.insert("content_type", content_type);
+            filter.insert("content_id", content_id);
+        }
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "id": 1 })
+            .build();
+        match coll.find(filter, opts) {
+            Ok(cursor) => cursor
+                .filter_map(|r| r.ok())
+                .filter_map(|d| doc_to_seo_suggestion(&d))
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    fn seo_suggestion_set_status(&self, id: i64, status: &str) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("seo_suggestions");
+        coll.update_one(
+            doc! { "id": id },
+            doc! { "$set": { "status": status } },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn seo_suggestion_exists(&self, content_type: &str, content_id: i64, field: &str) -> bool {
+        let coll = self.db.collection::<Document>("seo_suggestions");
+        coll.count_documents(
+            doc! { "content_type": content_type, "content_id": content_id, "field": field },
+            None,
+        )
+        .map(|n| n > 0)
+        .unwrap_or(false)
+    }
+
+    fn seo_suggestion_clear(&self, content_type: &str, content_id: i64) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("seo_suggestions");
+        coll.delete_many(
+            doc! { "content_type": content_type, "content_id": content_id, "status": "pending" },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn ai_usage_record(&self, usage: &AiUsage) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("ai_usage");
+        let id = self.next_id("ai_usage")?;
+        coll.insert_one(
+            doc! {
+                "id": id,
+                "provider": &usage.provider,
+                "model": &usage.model,
+                "feature": &usage.feature,
+                "prompt_tokens": usage.prompt_tokens,
+                "completion_tokens": usage.completion_tokens,
+                "latency_ms": usage.latency_ms,
+                "success": usage.success,
+                "error": &usage.error,
+                "created_at": &usage.created_at,
+            },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn ai_usage_totals(&self, since: &str, until: &str) -> Vec<AiUsageTotal> {
+        let coll = self.db.collection::<Document>("ai_usage");
+        let pipeline = vec![
+            doc! { "$match": { "created_at": { "$gte": since, "$lt": until } } },
+            doc! { "$group": {
+                "_id": { "provider": "$provider", "model": "$model", "feature": "$feature" },
+                "calls": { "$sum": 1 },
+                "failures": { "$sum": { "$cond": ["$success", 0, 1] } },
+                "prompt_tokens": { "$sum": "$prompt_tokens" },
+                "completion_tokens": { "$sum": "$completion_tokens" },
+                "latency_ms": { "$sum": "$latency_ms" },
+            } },
+        ];
+        let cursor = match coll.aggregate(pipeline, None) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let num = |d: &Document, k: &str| match d.get(k) {
+            Some(Bson::Int32(n)) => *n as i64,
+            Some(Bson::Int64(n)) => *n,
+            Some(Bson::Double(n)) => *n as i64,
+            _ => 0,
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .map(|d| {
+                let key = d.get_document("_id").cloned().unwrap_or_default();
+                AiUsageTotal {
+                    provider: key.get_str("provider").unwrap_or("").to_string(),
+                    model: key.get_str("model").unwrap_or("").to_string(),
+                    feature: key.get_str("feature").unwrap_or("").to_string(),
+                    calls: num(&d, "calls"),
+                    failures: num(&d, "failures"),
+                    prompt_tokens: num(&d, "prompt_tokens"),
+                    completion_tokens: num(&d, "completion_tokens"),
+                    latency_ms: num(&d, "latency_ms"),
+                }
+            })
+            .collect()
+    }
+
+    fn ai_usage_recent(&self, limit: i64) -> Vec<AiUsage> {
+        let coll = self.db.collection::<Document>("ai_usage");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "id": -1 })
+            .limit(limit)
+            .build();
+        let cursor = match coll.find(doc! {}, opts) {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        cursor
+            .filter_map(|r| r.ok())
+            .map(|d| AiUsage {
+                id: d.get_i64("id").unwrap_or(0),
+                provider: d.get_str("provider").unwrap_or("").to_string(),
+                model: d.get_str("model").unwrap_or("").to_string(),
+                feature: d.get_str("feature").unwrap_or("").to_string(),
+                prompt_tokens: d.get_i64("prompt_tokens").unwrap_or(0),
+                completion_tokens: d.get_i64("completion_tokens").unwrap_or(0),
+                latency_ms: d.get_i64("latency_ms").unwrap_or(0),
+                success: d.get_bool("success").unwrap_or(true),
+                error: d.get_str("error").unwrap_or("").to_string(),
+                created_at: d.get_str("created_at").unwrap_or("").to_string(),
+            })
+            .collect()
+    }
+
+    fn ai_usage_cleanup(&self, max_age_days: i64) -> Result<usize, String> {
+        let coll = self.db.collection::<Document>("ai_usage");
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(max_age_days))
+            .format("%Y-%m-%d %H:%M:%S")
             .to_string();
-        let filter = doc! {
-            "$or": [
-                { "expires_at": { "$lt": &now_str } },
-                { "created_at": { "$lt": &cutoff } }
-            ]
+        let res = coll
+            .delete_many(doc! { "created_at": { "$lt": cutoff } }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(res.deleted_count as usize)
+    }
+
+    fn media_set_owner(&self, path: &str, user_id: i64) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("media_owners");
+        let opts = mongodb::options::UpdateOptions::builder()
+            .upsert(true)
+            .build();
+        coll.update_one(
+            doc! { "path": path },
+            doc! {
+                "$set": { "user_id": user_id },
+                "$setOnInsert": { "created_at": chrono::Utc::now().to_rfc3339() },
+            },
+            opts,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn media_list_by_owner(&self, user_id: i64) -> Vec<String> {
+        let coll = self.db.collection::<Document>("media_owners");
+        let cursor = match coll.find(doc! { "user_id": user_id }, None) {
+            Ok(c) => c,
+            Err(_) => return vec![],
         };
-        let count = coll.count_documents(filter.clone(), None).unwrap_or(0) as usize;
-        coll.delete_many(filter, None).map_err(|e| e.to_string())?;
-        Ok(count)
+        cursor
+            .filter_map(|r| r.ok())
+            .filter_map(|d| d.get_str("path").ok().map(|s| s.to_string()))
+            .collect()
     }
 
-    fn task_cleanup_analytics(&self, max_age_days: i64) -> Result<usize, String> {
-        let coll = self.db.collection::<Document>("page_views");
-        let cutoff = (chrono::Utc::now() - chrono::Duration::days(max_age_days))
-            .format("%Y-%m-%dT%H:%M:%S")
-            .to_string();
-        let filter = doc! { "created_at": { "$lt": &cutoff } };
-        let count = coll.count_documents(filter.clone(), None).unwrap_or(0) as usize;
-        coll.delete_many(filter, None).map_err(|e| e.to_string())?;
-        Ok(count)
+    fn media_remove_owner(&self, path: &str) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("media_owners");
+        coll.delete_one(doc! { "path": path }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(())
     }
 
-    // ── Email queue (built-in MTA) ──────────────────────────────────
+    fn media_reassign_owner(&self, from: i64, to: i64) -> Result<usize, String> {
+        let coll = self.db.collection::<Document>("media_owners");
+        let res = coll
+            .update_many(
+                doc! { "user_id": from },
+                doc! { "$set": { "user_id": to } },
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(res.modified_count as usize)
+    }
 
-    fn mta_queue_push(
-        &self,
-        to: &str,
-        from: &str,
-        subject: &str,
-        body: &str,
-    ) -> Result<i64, String> {
-        let id = self.next_id("email_queue")?;
-        let now = chrono::Utc::now().to_rfc3339();
-        let coll = self.db.collection::<Document>("email_queue");
-        coll.insert_one(
-            doc! {
-                "id": id,
-                "to_addr": to,
-                "from_addr": from,
-                "subject": subject,
-                "body_text": body,
-                "attempts": 0_i64,
-                "max_attempts": 5_i64,
-                "next_retry_at": &now,
+    fn media_job_push(&self, path: &str, source: &str) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("media_jobs");
+        let opts = mongodb::options::UpdateOptions::builder()
+            .upsert(true)
+            .build();
+        coll.update_one(
+            doc! { "path": path },
+            doc! { "$set": {
+                "source": source,
                 "status": "pending",
+                "attempts": 0i64,
                 "error": "",
-                "created_at": &now,
-            },
-            None,
+                "created_at": chrono::Utc::now().to_rfc3339(),
+            } },
+            opts,
         )
         .map_err(|e| e.to_string())?;
-        Ok(id)
+        Ok(())
     }
 
-    fn mta_queue_pending(&self, limit: i64) -> Vec<crate::mta::queue::QueuedEmail> {
-        let coll = self.db.collection::<Document>("email_queue");
-        let now = chrono::Utc::now().to_rfc3339();
-        let filter = doc! {
-            "status": "pending",
-            "next_retry_at": { "$lte": &now },
-        };
+    fn media_job_pending(&self, limit: i64) -> Vec<crate::images::queue::MediaJob> {
+        let coll = self.db.collection::<Document>("media_jobs");
         let opts = mongodb::options::FindOptions::builder()
-            .sort(doc! { "next_retry_at": 1 })
+            .sort(doc! { "created_at": 1 })
             .limit(Some(limit))
             .build();
-        let cursor = match coll.find(filter, Some(opts)) {
+        let cursor = match coll.find(doc! { "status": "pending" }, Some(opts)) {
             Ok(c) => c,
             Err(_) => return Vec::new(),
         };
         cursor
             .flatten()
-            .filter_map(|d| {
-                Some(crate::mta::queue::QueuedEmail {
-                    id: d.get_i64("id").ok()?,
-                    to_addr: d.get_str("to_addr").ok()?.to_string(),
-                    from_addr: d.get_str("from_addr").ok()?.to_string(),
-                    subject: d.get_str("subject").ok()?.to_string(),
-                    body_text: d.get_str("body_text").ok()?.to_string(),
-                    attempts: d.get_i64("attempts").ok().unwrap_or(0),
-                    max_attempts: d.get_i64("max_attempts").ok().unwrap_or(5),
-                    next_retry_at: d.get_str("next_retry_at").ok().unwrap_or("").to_string(),
-                    status: d.get_str("status").ok().unwrap_or("pending").to_string(),
-                    error: d.get_str("error").ok().unwrap_or("").to_string(),
-                    created_at: d.get_str("created_at").ok().unwrap_or("").to_string(),
-                })
-            })
+            .filter_map(|d| doc_to_media_job(&d))
             .collect()
     }
 
-    fn mta_queue_update_status(
-        &self,
-        id: i64,
-        status: &str,
-        error: Option<&str>,
-        next_retry: Option<&str>,
-    ) -> Result<(), String> {
-        let coll = self.db.collection::<Document>("email_queue");
-        let mut update = doc! { "$set": { "status": status } };
-        if let Some(e) = error {
-            update.get_document_mut("$set").unwrap().insert("error", e);
-        }
-        if let Some(nr) = next_retry {
-            update
-                .get_document_mut("$set")
-                .unwrap()
-                .insert("next_retry_at", nr);
-        }
-        if status == "sending" {
-            update.insert("$inc", doc! { "attempts": 1_i64 });
-        }
-        coll.update_one(doc! { "id": id }, update, None)
-            .map_err(|e| e.to_string())?;
-        Ok(())
+    fn media_job_get(&self, path: &str) -> Option<crate::images::queue::MediaJob> {
+        let coll = self.db.collection::<Document>("media_jobs");
+        coll.find_one(doc! { "path": path }, None)
+            .ok()
+            .flatten()
+            .and_then(|d| doc_to_media_job(&d))
     }
 
-    fn mta_queue_sent_last_hour(&self) -> Result<u64, String> {
-        let coll = self.db.collection::<Document>("email_queue");
-        let one_hour_ago = (chrono::Utc::now() - chrono::Duration::hours(1)).to_rfc3339();
-        let filter = doc! {
-            "status": "sent",
-            "created_at": { "$gte": &one_hour_ago },
+    fn media_job_list(&self) -> Vec<crate::images::queue::MediaJob> {
+        let coll = self.db.collection::<Document>("media_jobs");
+        let opts = mongodb::options::FindOptions::builder()
+            .sort(doc! { "created_at": -1 })
+            .build();
+        let cursor = match coll.find(doc! {}, Some(opts)) {
+            Ok(c) => c,
+            Err(_) => return Vec::new(),
         };
-        coll.count_documents(filter, None)
-            .map_err(|e| e.to_string())
+        cursor
+            .flatten()
+            .filter_map(|d| doc_to_media_job(&d))
+            .collect()
     }
 
-    fn mta_queue_stats(&self) -> (u64, u64, u64, u64) {
-        let coll = self.db.collection::<Document>("email_queue");
-        let sent = coll
-            .count_documents(doc! { "status": "sent" }, None)
-            .unwrap_or(0);
-        let pending = coll
-            .count_documents(doc! { "status": { "$in": ["pending", "sending"] } }, None)
-            .unwrap_or(0);
-        let failed = coll
-            .count_documents(doc! { "status": "failed" }, None)
-            .unwrap_or(0);
-        let total = coll.count_documents(doc! {}, None).unwrap_or(0);
-        (sent, pending, failed, total)
+    fn media_job_fail(&self, path: &str, error: &str, give_up: bool) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("media_jobs");
+        let mut set = doc! { "error": error };
+        if give_up {
+            set.insert("status", "failed");
+        }
+        coll.update_one(
+            doc! { "path": path },
+            doc! { "$set": set, "$inc": { "attempts": 1i64 } },
+            None,
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
     }
 
-    fn mta_queue_cleanup(&self, days: u64) -> Result<u64, String> {
-        let coll = self.db.collection::<Document>("email_queue");
-        let cutoff = (chrono::Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339();
-        let filter = doc! { "created_at": { "$lt": &cutoff } };
-        let result = coll.delete_many(filter, None).map_err(|e| e.to_string())?;
-        Ok(result.deleted_count)
+    fn media_job_delete(&self, path: &str) -> Result<(), String> {
+        let coll = self.db.collection::<Document>("media_jobs");
+        coll.delete_one(doc! { "path": path }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(())
     }
 
     fn raw_execute(&self, _sql: &str) -> Result<usize, String> {
@@ -3379,6 +6417,17 @@ impl Store for MongoStore {
     }
 }
 
+// ── Helper: Convert BSON Document to Role ───────────────────────────
+
+fn doc_to_role(doc: &Document) -> Role {
+    Role {
+        slug: doc.get_str("slug").unwrap_or_default().to_string(),
+        name: doc.get_str("name").unwrap_or_default().to_string(),
+        capabilities: Role::parse_capabilities(doc.get_str("capabilities").unwrap_or_default()),
+        created_at: doc.get_str("created_at").unwrap_or_default().to_string(),
+    }
+}
+
 // ── Helper: Convert BSON Document to User ───────────────────────────
 
 fn doc_to_user(doc: &Document) -> Option<User> {
@@ -3411,11 +6460,126 @@ fn doc_to_user(doc: &Document) -> Option<User> {
             .unwrap_or("password")
             .to_string(),
         force_password_change: doc.get_bool("force_password_change").unwrap_or(false),
+        passwordless: doc.get_bool("passwordless").unwrap_or(false),
+        mfa_method: doc.get_str("mfa_method").ok().unwrap_or("totp").to_string(),
+        author_slug: doc.get_str("author_slug").ok().unwrap_or("").to_string(),
+        bio: doc.get_str("bio").ok().unwrap_or("").to_string(),
+        website: doc.get_str("website").ok().unwrap_or("").to_string(),
+        social_links: doc.get_str("social_links").ok().unwrap_or("").to_string(),
+    })
+}
+
+// ── Helper: Keyset pages ─────────────────────────────────────────────
+
+/// One keyset page from `coll`, newest first by `col` with the id as a
+/// tiebreak. Lists that already sort by id pass "id" as `col`.
+fn keyset_page<T>(
+    coll: &mongodb::sync::Collection<Document>,
+    col: &str,
+    mut filter: Document,
+    after: Option<&Cursor>,
+    limit: i64,
+    convert: impl Fn(&Document) -> Option<T>,
+) -> (Vec<T>, Option<Cursor>) {
+    let limit = limit.max(1);
+    if let Some(c) = after {
+        if col == "id" {
+            filter.insert("id", doc! { "$lt": c.id });
+        } else if c.key.is_empty() {
+            filter.insert(col, Bson::Null);
+            filter.insert("id", doc! { "$lt": c.id });
+        } else {
+            filter.insert(
+                "$or",
+                vec![
+                    doc! { col: { "$lt": &c.key } },
+                    doc! { col: &c.key, "id": { "$lt": c.id } },
+                    doc! { col: Bson::Null },
+                ],
+            );
+        }
+    }
+    let sort = if col == "id" {
+        doc! { "id": -1 }
+    } else {
+        doc! { col: -1, "id": -1 }
+    };
+    let opts = mongodb::options::FindOptions::builder()
+        .sort(sort)
+        .limit(limit + 1)
+        .build();
+    let docs: Vec<Document> = match coll.find(filter, opts) {
+        Ok(cursor) => cursor.filter_map(|r| r.ok()).collect(),
+        Err(_) => return (vec![], None),
+    };
+
+    let next = if docs.len() as i64 > limit {
+        docs.get(limit as usize - 1).and_then(|d| {
+            Some(Cursor {
+                key: if col == "id" {
+                    String::new()
+                } else {
+                    d.get_str(col).unwrap_or("").to_string()
+                },
+                id: d.get_i64("id").ok()?,
+            })
+        })
+    } else {
+        None
+    };
+    let items = docs
+        .iter()
+        .take(limit as usize)
+        .filter_map(convert)
+        .collect();
+    (items, next)
+}
+
+/// A `search_index` document; the body is stored as plain text
+fn search_doc(
+    item_type: &str,
+    item_id: i64,
+    title: &str,
+    html_body: &str,
+    slug: &str,
+    image: Option<&str>,
+    date: Option<&str>,
+) -> Document {
+    doc! {
+        "item_type": item_type,
+        "item_id": item_id,
+        "title": title,
+        "body": strip_html(html_body),
+        "slug": slug,
+        "image": image,
+        "date": date,
+    }
+}
+
+fn doc_to_search_result(d: &Document, rank: f64, terms: &[String]) -> Option<SearchResult> {
+    Some(SearchResult {
+        item_type: d.get_str("item_type").ok()?.to_string(),
+        item_id: d.get_i64("item_id").ok()?,
+        title: d.get_str("title").ok()?.to_string(),
+        slug: d.get_str("slug").ok()?.to_string(),
+        snippet: snippet(d.get_str("body").unwrap_or(""), terms),
+        image: d.get_str("image").ok().map(|s| s.to_string()),
+        date: d.get_str("date").ok().map(|s| s.to_string()),
+        rank,
     })
 }
 
 // ── Helper: Convert BSON Document to Post ────────────────────────────
 
+/// Published posts credited to `user_id`, optionally with unattributed ones
+fn author_filter(user_id: i64, include_unattributed: bool) -> Document {
+    let mut authors = vec![doc! { "author_id": user_id }];
+    if include_unattributed {
+        authors.push(doc! { "author_id": null });
+    }
+    doc! { "status": "published", "$or": authors }
+}
+
 fn parse_naive_dt(s: &str) -> Option<NaiveDateTime> {
     NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
         .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S"))
@@ -3456,6 +6620,18 @@ fn doc_to_post(doc: &Document) -> Option<Post> {
             .and_then(parse_naive_dt_rfc3339)?,
         seo_score: doc.get_i32("seo_score").unwrap_or(-1),
         seo_issues: doc.get_str("seo_issues").ok().unwrap_or("[]").to_string(),
+        robots_noindex: doc.get_bool("robots_noindex").unwrap_or(false),
+        robots_nofollow: doc.get_bool("robots_nofollow").unwrap_or(false),
+        canonical_url: doc.get_str("canonical_url").ok().map(|s| s.to_string()),
+        schema_type: doc.get_str("schema_type").ok().map(|s| s.to_string()),
+        focus_keyword: doc.get_str("focus_keyword").ok().map(|s| s.to_string()),
+        language: doc.get_str("language").ok().map(|s| s.to_string()),
+        translation_group: doc.get_str("translation_group").ok().map(|s| s.to_string()),
+        audio_file: doc.get_str("audio_file").ok().map(|s| s.to_string()),
+        audio_duration: doc.get_i64("audio_duration").ok(),
+        audio_bytes: doc.get_i64("audio_bytes").ok(),
+        template: doc.get_str("template").ok().map(|s| s.to_string()),
+        author_id: doc.get_i64("author_id").ok(),
     })
 }
 
@@ -3501,11 +6677,25 @@ fn doc_to_portfolio(doc: &Document) -> Option<PortfolioItem> {
             .and_then(parse_naive_dt_rfc3339)?,
         seo_score: doc.get_i32("seo_score").unwrap_or(-1),
         seo_issues: doc.get_str("seo_issues").ok().unwrap_or("[]").to_string(),
+        robots_noindex: doc.get_bool("robots_noindex").unwrap_or(false),
+        robots_nofollow: doc.get_bool("robots_nofollow").unwrap_or(false),
+        canonical_url: doc.get_str("canonical_url").ok().map(|s| s.to_string()),
+        schema_type: doc.get_str("schema_type").ok().map(|s| s.to_string()),
+        focus_keyword: doc.get_str("focus_keyword").ok().map(|s| s.to_string()),
+        template: doc.get_str("template").ok().map(|s| s.to_string()),
     })
 }
 
 // ── Helper: Convert BSON Document to Comment ─────────────────────────
 
+/// Comments by a member account or under its email address (any case)
+fn comment_author_filter(user_id: i64, email: &str) -> Document {
+    doc! { "$or": [
+        { "user_id": user_id },
+        { "author_email": { "$regex": format!("^{}$", regex::escape(email)), "$options": "i" } },
+    ] }
+}
+
 fn doc_to_comment(doc: &Document) -> Option<Comment> {
     Some(Comment {
         id: doc.get_i64("id").ok()?,
@@ -3520,6 +6710,7 @@ fn doc_to_comment(doc: &Document) -> Option<Comment> {
         body: doc.get_str("body").ok().unwrap_or("").to_string(),
         status: doc.get_str("status").ok().unwrap_or("pending").to_string(),
         parent_id: doc.get_i64("parent_id").ok(),
+        user_id: doc.get_i64("user_id").ok(),
         created_at: doc
             .get_str("created_at")
             .ok()
@@ -3559,6 +6750,10 @@ fn doc_to_design(doc: &Document) -> Option<Design> {
         description: doc.get_str("description").ok().unwrap_or("").to_string(),
         layout_html: doc.get_str("layout_html").ok().unwrap_or("").to_string(),
         style_css: doc.get_str("style_css").ok().unwrap_or("").to_string(),
+        custom_css: doc.get_str("custom_css").ok().unwrap_or("").to_string(),
+        head_scripts: doc.get_str("head_scripts").ok().unwrap_or("").to_string(),
+        footer_scripts: doc.get_str("footer_scripts").ok().unwrap_or("").to_string(),
+        parent_id: doc.get_i64("parent_id").ok(),
         thumbnail_path: doc.get_str("thumbnail_path").ok().map(|s| s.to_string()),
         is_active: doc.get_bool("is_active").unwrap_or(false),
         created_at: doc
@@ -3589,6 +6784,22 @@ fn doc_to_design_template(doc: &Document) -> Option<DesignTemplate> {
     })
 }
 
+// ── Helper: Convert BSON Document to DesignTemplateVersion ───────────
+
+fn doc_to_design_template_version(doc: &Document) -> Option<DesignTemplateVersion> {
+    Some(DesignTemplateVersion {
+        id: doc.get_i64("id").ok()?,
+        design_id: doc.get_i64("design_id").ok()?,
+        template_type: doc.get_str("template_type").ok()?.to_string(),
+        layout_html: doc.get_str("layout_html").ok().unwrap_or("").to_string(),
+        style_css: doc.get_str("style_css").ok().unwrap_or("").to_string(),
+        grapesjs_data: doc.get_str("grapesjs_data").ok().unwrap_or("").to_string(),
+        user_id: doc.get_i64("user_id").ok(),
+        user_name: doc.get_str("user_name").ok().unwrap_or("").to_string(),
+        created_at: doc.get_str("created_at").ok().unwrap_or("").to_string(),
+    })
+}
+
 // ── Helper: Convert BSON Document to AuditEntry ──────────────────────
 
 fn doc_to_audit(doc: &Document) -> Option<AuditEntry> {
@@ -3640,6 +6851,66 @@ fn doc_to_fw_event(doc: &Document) -> Option<FwEvent> {
     })
 }
 
+// ── Helper: Convert BSON Document to FwRule ──────────────────────────
+
+fn doc_to_fw_rule(doc: &Document) -> Option<FwRule> {
+    Some(FwRule {
+        id: doc.get_i64("id").ok()?,
+        name: doc.get_str("name").ok().unwrap_or("").to_string(),
+        match_type: doc.get_str("match_type").ok()?.to_string(),
+        pattern: doc.get_str("pattern").ok()?.to_string(),
+        action: doc.get_str("action").ok().unwrap_or("block").to_string(),
+        rate_limit: doc.get_i64("rate_limit").unwrap_or(60),
+        priority: doc.get_i64("priority").unwrap_or(100),
+        enabled: doc.get_bool("enabled").unwrap_or(true),
+        hits: doc.get_i64("hits").unwrap_or(0),
+        created_at: doc.get_str("created_at").ok().unwrap_or("").to_string(),
+    })
+}
+
+// ── Helper: Convert BSON Document to ApiKey ──────────────────────────
+
+fn doc_to_api_key(doc: &Document) -> Option<ApiKey> {
+    Some(ApiKey {
+        id: doc.get_i64("id").ok()?,
+        name: doc.get_str("name").ok()?.to_string(),
+        prefix: doc.get_str("prefix").ok().unwrap_or("").to_string(),
+        key_hash: doc.get_str("key_hash").ok()?.to_string(),
+        scope: doc.get_str("scope").ok().unwrap_or("read").to_string(),
+        rate_limit: doc.get_i64("rate_limit").unwrap_or(0),
+        last_used_at: doc.get_str("last_used_at").ok().map(|s| s.to_string()),
+        created_at: doc.get_str("created_at").ok().unwrap_or("").to_string(),
+    })
+}
+
+// ── Helper: Convert BSON Document to RedirectRule ────────────────────
+
+fn doc_to_redirect(doc: &Document) -> Option<RedirectRule> {
+    Some(RedirectRule {
+        id: doc.get_i64("id").ok()?,
+        source: doc.get_str("source").ok()?.to_string(),
+        target: doc.get_str("target").ok()?.to_string(),
+        status_code: doc.get_i64("status_code").unwrap_or(301),
+        hits: doc.get_i64("hits").unwrap_or(0),
+        created_at: doc.get_str("created_at").ok().unwrap_or("").to_string(),
+    })
+}
+
+// ── Helper: Convert BSON Document to Widget ──────────────────────────
+
+fn doc_to_widget(doc: &Document) -> Option<Widget> {
+    Some(Widget {
+        id: doc.get_i64("id").ok()?,
+        design_id: doc.get_i64("design_id").ok()?,
+        area: doc.get_str("area").ok()?.to_string(),
+        widget_type: doc.get_str("widget_type").ok()?.to_string(),
+        title: doc.get_str("title").ok().unwrap_or("").to_string(),
+        config: doc.get_str("config").ok().unwrap_or("{}").to_string(),
+        sort_order: doc.get_i64("sort_order").unwrap_or(0),
+        created_at: doc.get_str("created_at").ok().unwrap_or("").to_string(),
+    })
+}
+
 // ── Helper: Convert BSON Document to Order ───────────────────────────
 
 fn doc_to_order(doc: &Document) -> Option<Order> {
@@ -3730,6 +7001,11 @@ fn doc_to_import(doc: &Document) -> Option<Import> {
             .get_str("imported_at")
             .ok()
             .and_then(parse_naive_dt_rfc3339)?,
+        created: ImportCreated::from_json(doc.get_str("created").ok()),
+        rolled_back_at: doc
+            .get_str("rolled_back_at")
+            .ok()
+            .and_then(parse_naive_dt_rfc3339),
     })
 }
 
@@ -3747,3 +7023,63 @@ fn parse_duration_to_expiry(duration: &str) -> String {
     };
     (chrono::Utc::now() + delta).to_rfc3339()
 }
+
+/// Sent queue entries delivered at or after `since`. Entries sent before
+/// `sent_at` was recorded fall back to their creation time.
+fn sent_since_filter(since: &str) -> Document {
+    doc! {
+        "status": "sent",
+        "$or": [
+            { "sent_at": { "$gte": since } },
+            { "sent_at": { "$exists": false }, "created_at": { "$gte": since } },
+        ],
+    }
+}
+
+// ── Helper: Convert BSON Document to QueuedEmail ─────────────────────
+
+fn doc_to_queued_email(d: &Document) -> Option<crate::mta::queue::QueuedEmail> {
+    Some(crate::mta::queue::QueuedEmail {
+        id: d.get_i64("id").ok()?,
+        to_addr: d.get_str("to_addr").ok()?.to_string(),
+        from_addr: d.get_str("from_addr").ok()?.to_string(),
+        subject: d.get_str("subject").ok()?.to_string(),
+        body_text: d.get_str("body_text").ok()?.to_string(),
+        reply_to: d.get_str("reply_to").ok().unwrap_or("").to_string(),
+        attempts: d.get_i64("attempts").ok().unwrap_or(0),
+        max_attempts: d.get_i64("max_attempts").ok().unwrap_or(5),
+        next_retry_at: d.get_str("next_retry_at").ok().unwrap_or("").to_string(),
+        status: d.get_str("status").ok().unwrap_or("pending").to_string(),
+        error: d.get_str("error").ok().unwrap_or("").to_string(),
+        error_log: d.get_str("error_log").ok().unwrap_or("").to_string(),
+        created_at: d.get_str("created_at").ok().unwrap_or("").to_string(),
+    })
+}
+
+fn doc_to_media_job(d: &Document) -> Option<crate::images::queue::MediaJob> {
+    Some(crate::images::queue::MediaJob {
+        path: d.get_str("path").ok()?.to_string(),
+        source: d.get_str("source").ok()?.to_string(),
+        status: d.get_str("status").ok().unwrap_or("pending").to_string(),
+        attempts: d.get_i64("attempts").ok().unwrap_or(0),
+        error: d.get_str("error").ok().unwrap_or("").to_string(),
+        created_at: d.get_str("created_at").ok().unwrap_or("").to_string(),
+    })
+}
+
+// ── Helper: Convert BSON Document to SeoSuggestion ───────────────────
+
+fn doc_to_seo_suggestion(d: &Document) -> Option<SeoSuggestion> {
+    Some(SeoSuggestion {
+        id: d.get_i64("id").ok()?,
+        content_type: d.get_str("content_type").ok()?.to_string(),
+        content_id: d.get_i64("content_id").ok()?,
+        field: d.get_str("field").ok()?.to_string(),
+        current: d.get_str("current").ok().unwrap_or("").to_string(),
+        suggested: d.get_str("suggested").ok()?.to_string(),
+        level: d.get_i64("level").ok().unwrap_or(0),
+        reason: d.get_str("reason").ok().unwrap_or("").to_string(),
+        status: d.get_str("status").ok().unwrap_or("pending").to_string(),
+        created_at: d.get_str("created_at").ok().unwrap_or("").to_string(),
+    })
+}
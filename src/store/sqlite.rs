@@ -5,23 +5,37 @@ use r2d2::Pool;
 use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::params;
 
+use crate::models::ai_usage::{AiUsage, AiUsageTotal};
 use crate::models::analytics::{
-    CountEntry, DailyCount, FlowNode, OverviewStats, StreamEntry, TagRelation,
+    CampaignEntry, CountEntry, DailyCount, EngagementStats, FlowNode, OutboundEntry, OverviewStats,
+    PathStats, SearchQueryStat, StreamEntry, TagRelation, Utm,
 };
+use crate::models::api_key::ApiKey;
 use crate::models::audit::AuditEntry;
 use crate::models::category::{Category, CategoryForm};
 use crate::models::comment::{Comment, CommentForm};
-use crate::models::design::{Design, DesignTemplate};
-use crate::models::firewall::{FwBan, FwEvent};
-use crate::models::import::Import;
+use crate::models::design::{Design, DesignTemplate, DesignTemplateVersion};
+use crate::models::embedding::{vector_from_blob, vector_to_blob, Embedding};
+use crate::models::experiment::VariantStat;
+use crate::models::firewall::{self, FwBan, FwEvent, FwRule, FwRuleCache};
+use crate::models::goal::GoalStat;
+use crate::models::import::{Import, ImportCreated};
 use crate::models::order::{DownloadToken, License, Order};
 use crate::models::passkey::UserPasskey;
+use crate::models::ping_log::PingLogEntry;
 use crate::models::portfolio::{PortfolioForm, PortfolioItem};
 use crate::models::post::{Post, PostForm};
+use crate::models::redirect::{self, NotFoundEntry, NotFoundHit, RedirectCache, RedirectRule};
+use crate::models::role::{self, Role, RoleCache};
 use crate::models::search::SearchResult;
+use crate::models::seo_suggestion::SeoSuggestion;
 use crate::models::tag::{Tag, TagForm};
 use crate::models::user::User;
+use crate::models::widget::Widget;
+use crate::render::page_cache;
+use crate::seo::sitemap::Section;
 
+use super::cursor::Cursor;
 use super::Store;
 
 pub type DbPool = Pool<SqliteConnectionManager>;
@@ -30,16 +44,24 @@ pub type DbPool = Pool<SqliteConnectionManager>;
 /// Wraps the existing r2d2 connection pool and delegates to model methods.
 pub struct SqliteStore {
     pub pool: DbPool,
+    roles: RoleCache,
+    fw_rules: FwRuleCache,
+    redirects: RedirectCache,
 }
 
 impl SqliteStore {
     pub fn new(pool: DbPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            roles: RoleCache::default(),
+            fw_rules: FwRuleCache::default(),
+            redirects: RedirectCache::default(),
+        }
     }
 
     pub fn new_at(path: &str) -> Result<Self, String> {
         let pool = crate::db::init_pool_at(path)?;
-        Ok(Self { pool })
+        Ok(Self::new(pool))
     }
 }
 
@@ -62,10 +84,12 @@ impl Store for SqliteStore {
 
     fn setting_set(&self, key: &str, value: &str) -> Result<(), String> {
         crate::models::settings::Setting::set(&self.pool, key, value)
+            .inspect(|_| page_cache::settings_changed([key]))
     }
 
     fn setting_set_many(&self, settings: &HashMap<String, String>) -> Result<(), String> {
         crate::models::settings::Setting::set_many(&self.pool, settings)
+            .inspect(|_| page_cache::settings_changed(settings.keys()))
     }
 
     fn setting_get_group(&self, prefix: &str) -> HashMap<String, String> {
@@ -80,6 +104,7 @@ impl Store for SqliteStore {
         let conn = self.pool.get().map_err(|e| e.to_string())?;
         conn.execute("DELETE FROM settings WHERE key = ?1", params![key])
             .map_err(|e| e.to_string())?;
+        page_cache::settings_changed([key]);
         Ok(())
     }
 
@@ -133,6 +158,17 @@ impl Store for SqliteStore {
         User::update_profile(&self.pool, id, display_name, email, avatar)
     }
 
+    fn user_update_author_profile(
+        &self,
+        id: i64,
+        author_slug: &str,
+        bio: &str,
+        website: &str,
+        social_links: &str,
+    ) -> Result<(), String> {
+        User::update_author_profile(&self.pool, id, author_slug, bio, website, social_links)
+    }
+
     fn user_update_role(&self, id: i64, role: &str) -> Result<(), String> {
         User::update_role(&self.pool, id, role)
     }
@@ -167,6 +203,10 @@ impl Store for SqliteStore {
         User::unlock(&self.pool, id)
     }
 
+    fn user_set_status(&self, id: i64, status: &str) -> Result<(), String> {
+        User::set_status(&self.pool, id, status)
+    }
+
     fn user_delete(&self, id: i64) -> Result<(), String> {
         User::delete(&self.pool, id)
     }
@@ -183,6 +223,13 @@ impl Store for SqliteStore {
         ).map_err(|e| e.to_string())?;
         Ok(())
     }
+    fn user_set_passwordless(&self, id: i64, passwordless: bool) -> Result<(), String> {
+        User::set_passwordless(&self.pool, id, passwordless)
+    }
+
+    fn user_set_mfa_method(&self, id: i64, method: &str) -> Result<(), String> {
+        User::set_mfa_method(&self.pool, id, method)
+    }
 
     // ── Posts ────────────────────────────────────────────────────────
 
@@ -198,20 +245,29 @@ impl Store for SqliteStore {
         Post::list(&self.pool, status, limit, offset)
     }
 
+    fn post_list_since(
+        &self,
+        status: &str,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, Post)> {
+        Post::list_since(&self.pool, status, since_seq, limit)
+    }
+
     fn post_count(&self, status: Option<&str>) -> i64 {
         Post::count(&self.pool, status)
     }
 
     fn post_create(&self, form: &PostForm) -> Result<i64, String> {
-        Post::create(&self.pool, form)
+        Post::create(&self.pool, form).inspect(|_| super::content_changed(Section::Posts))
     }
 
     fn post_update(&self, id: i64, form: &PostForm) -> Result<(), String> {
-        Post::update(&self.pool, id, form)
+        Post::update(&self.pool, id, form).inspect(|_| super::content_changed(Section::Posts))
     }
 
     fn post_delete(&self, id: i64) -> Result<(), String> {
-        Post::delete(&self.pool, id)
+        Post::delete(&self.pool, id).inspect(|_| super::content_changed(Section::Posts))
     }
 
     fn post_prev_published(&self, published_at: &NaiveDateTime) -> Option<Post> {
@@ -224,6 +280,7 @@ impl Store for SqliteStore {
 
     fn post_update_status(&self, id: i64, status: &str) -> Result<(), String> {
         Post::update_status(&self.pool, id, status)
+            .inspect(|_| super::content_changed(Section::Posts))
     }
 
     fn post_update_seo_score(&self, id: i64, score: i32, issues_json: &str) -> Result<(), String> {
@@ -287,6 +344,18 @@ impl Store for SqliteStore {
                 updated_at: row.get("updated_at")?,
                 seo_score: row.get("seo_score").unwrap_or(-1),
                 seo_issues: row.get("seo_issues").unwrap_or_else(|_| "[]".to_string()),
+                robots_noindex: row.get::<_, i64>("robots_noindex").unwrap_or(0) != 0,
+                robots_nofollow: row.get::<_, i64>("robots_nofollow").unwrap_or(0) != 0,
+                canonical_url: row.get("canonical_url").unwrap_or(None),
+                schema_type: row.get("schema_type").unwrap_or(None),
+                focus_keyword: row.get("focus_keyword").unwrap_or(None),
+                language: row.get("language").unwrap_or(None),
+                translation_group: row.get("translation_group").unwrap_or(None),
+                audio_file: row.get("audio_file").unwrap_or(None),
+                audio_duration: row.get("audio_duration").unwrap_or(None),
+                audio_bytes: row.get("audio_bytes").unwrap_or(None),
+                template: row.get("template").unwrap_or(None),
+                author_id: row.get("author_id").unwrap_or(None),
             })
         })
         .map(|rows| rows.filter_map(|r| r.ok()).collect())
@@ -340,6 +409,18 @@ impl Store for SqliteStore {
                 updated_at: row.get("updated_at")?,
                 seo_score: row.get("seo_score").unwrap_or(-1),
                 seo_issues: row.get("seo_issues").unwrap_or_else(|_| "[]".to_string()),
+                robots_noindex: row.get::<_, i64>("robots_noindex").unwrap_or(0) != 0,
+                robots_nofollow: row.get::<_, i64>("robots_nofollow").unwrap_or(0) != 0,
+                canonical_url: row.get("canonical_url").unwrap_or(None),
+                schema_type: row.get("schema_type").unwrap_or(None),
+                focus_keyword: row.get("focus_keyword").unwrap_or(None),
+                language: row.get("language").unwrap_or(None),
+                translation_group: row.get("translation_group").unwrap_or(None),
+                audio_file: row.get("audio_file").unwrap_or(None),
+                audio_duration: row.get("audio_duration").unwrap_or(None),
+                audio_bytes: row.get("audio_bytes").unwrap_or(None),
+                template: row.get("template").unwrap_or(None),
+                author_id: row.get("author_id").unwrap_or(None),
             })
         })
         .map(|rows| rows.filter_map(|r| r.ok()).collect())
@@ -392,6 +473,18 @@ impl Store for SqliteStore {
                 updated_at: row.get("updated_at")?,
                 seo_score: row.get("seo_score").unwrap_or(-1),
                 seo_issues: row.get("seo_issues").unwrap_or_else(|_| "[]".to_string()),
+                robots_noindex: row.get::<_, i64>("robots_noindex").unwrap_or(0) != 0,
+                robots_nofollow: row.get::<_, i64>("robots_nofollow").unwrap_or(0) != 0,
+                canonical_url: row.get("canonical_url").unwrap_or(None),
+                schema_type: row.get("schema_type").unwrap_or(None),
+                focus_keyword: row.get("focus_keyword").unwrap_or(None),
+                language: row.get("language").unwrap_or(None),
+                translation_group: row.get("translation_group").unwrap_or(None),
+                audio_file: row.get("audio_file").unwrap_or(None),
+                audio_duration: row.get("audio_duration").unwrap_or(None),
+                audio_bytes: row.get("audio_bytes").unwrap_or(None),
+                template: row.get("template").unwrap_or(None),
+                author_id: row.get("author_id").unwrap_or(None),
             })
         })
         .map(|rows| rows.filter_map(|r| r.ok()).collect())
@@ -413,6 +506,60 @@ impl Store for SqliteStore {
         .unwrap_or(0)
     }
 
+    fn post_translations(&self, group: &str) -> Vec<Post> {
+        Post::translations(&self.pool, group)
+    }
+
+    fn post_episodes(&self, limit: i64) -> Vec<Post> {
+        Post::episodes(&self.pool, limit)
+    }
+
+    fn post_by_author(
+        &self,
+        user_id: i64,
+        include_unattributed: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<Post> {
+        Post::by_author(&self.pool, user_id, include_unattributed, limit, offset)
+    }
+
+    fn post_count_by_author(&self, user_id: i64, include_unattributed: bool) -> i64 {
+        Post::count_by_author(&self.pool, user_id, include_unattributed)
+    }
+
+    fn post_set_author(&self, id: i64, user_id: Option<i64>) -> Result<(), String> {
+        Post::set_author(&self.pool, id, user_id).inspect(|_| page_cache::invalidate())
+    }
+
+    fn post_list_by_owner(
+        &self,
+        user_id: i64,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<Post> {
+        Post::list_by_owner(&self.pool, user_id, status, limit, offset)
+    }
+
+    fn post_count_by_owner(&self, user_id: i64, status: Option<&str>) -> i64 {
+        Post::count_by_owner(&self.pool, user_id, status)
+    }
+
+    fn post_list_keyset(
+        &self,
+        owner: Option<i64>,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Post>, Option<Cursor>) {
+        Post::list_keyset(&self.pool, owner, status, after, limit)
+    }
+
+    fn post_reassign_author(&self, from: i64, to: i64) -> Result<usize, String> {
+        Post::reassign_author(&self.pool, from, to).inspect(|_| page_cache::invalidate())
+    }
+
     // ── Portfolio ───────────────────────────────────────────────────
 
     fn portfolio_find_by_id(&self, id: i64) -> Option<PortfolioItem> {
@@ -442,18 +589,22 @@ impl Store for SqliteStore {
 
     fn portfolio_create(&self, form: &PortfolioForm) -> Result<i64, String> {
         PortfolioItem::create(&self.pool, form)
+            .inspect(|_| super::content_changed(Section::Portfolio))
     }
 
     fn portfolio_update(&self, id: i64, form: &PortfolioForm) -> Result<(), String> {
         PortfolioItem::update(&self.pool, id, form)
+            .inspect(|_| super::content_changed(Section::Portfolio))
     }
 
     fn portfolio_update_status(&self, id: i64, status: &str) -> Result<(), String> {
         PortfolioItem::update_status(&self.pool, id, status)
+            .inspect(|_| super::content_changed(Section::Portfolio))
     }
 
     fn portfolio_delete(&self, id: i64) -> Result<(), String> {
         PortfolioItem::delete(&self.pool, id)
+            .inspect(|_| super::content_changed(Section::Portfolio))
     }
 
     fn portfolio_increment_likes(&self, id: i64) -> Result<i64, String> {
@@ -517,6 +668,12 @@ impl Store for SqliteStore {
                 updated_at: row.get("updated_at")?,
                 seo_score: row.get("seo_score").unwrap_or(-1),
                 seo_issues: row.get("seo_issues").unwrap_or_else(|_| "[]".to_string()),
+                robots_noindex: row.get::<_, i64>("robots_noindex").unwrap_or(0) != 0,
+                robots_nofollow: row.get::<_, i64>("robots_nofollow").unwrap_or(0) != 0,
+                canonical_url: row.get("canonical_url").unwrap_or(None),
+                schema_type: row.get("schema_type").unwrap_or(None),
+                focus_keyword: row.get("focus_keyword").unwrap_or(None),
+                template: row.get("template").unwrap_or(None),
             })
         })
         .map(|rows| rows.filter_map(|r| r.ok()).collect())
@@ -548,6 +705,15 @@ impl Store for SqliteStore {
         Comment::list(&self.pool, status, limit, offset)
     }
 
+    fn comment_list_since(
+        &self,
+        status: Option<&str>,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, Comment)> {
+        Comment::list_since(&self.pool, status, since_seq, limit)
+    }
+
     fn comment_for_post(&self, post_id: i64, content_type: &str) -> Vec<Comment> {
         Comment::for_post(&self.pool, post_id, content_type)
     }
@@ -556,12 +722,21 @@ impl Store for SqliteStore {
         Comment::count(&self.pool, status)
     }
 
+    fn comment_list_keyset(
+        &self,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Comment>, Option<Cursor>) {
+        Comment::list_keyset(&self.pool, status, after, limit)
+    }
+
     fn comment_create(&self, form: &CommentForm) -> Result<i64, String> {
-        Comment::create(&self.pool, form)
+        Comment::create(&self.pool, form).inspect(|_| page_cache::invalidate())
     }
 
     fn comment_update_status(&self, id: i64, status: &str) -> Result<(), String> {
-        Comment::update_status(&self.pool, id, status)
+        Comment::update_status(&self.pool, id, status).inspect(|_| page_cache::invalidate())
     }
 
     fn comment_set_parent(&self, id: i64, parent_id: Option<i64>) -> Result<(), String> {
@@ -571,11 +746,40 @@ impl Store for SqliteStore {
             rusqlite::params![parent_id, id],
         )
         .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
         Ok(())
     }
 
+    fn comment_set_user(&self, id: i64, user_id: i64) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE comments SET user_id = ?1 WHERE id = ?2",
+            rusqlite::params![user_id, id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn comment_list_on_author_posts(&self, author_id: i64, limit: i64) -> Vec<Comment> {
+        Comment::list_on_author_posts(&self.pool, author_id, limit)
+    }
+
+    fn comment_list_by_author(&self, user_id: i64, email: &str) -> Vec<Comment> {
+        Comment::list_by_author(&self.pool, user_id, email)
+    }
+
+    fn comment_anonymize_author(
+        &self,
+        user_id: i64,
+        email: &str,
+        name: &str,
+    ) -> Result<usize, String> {
+        Comment::anonymize_author(&self.pool, user_id, email, name)
+            .inspect(|_| page_cache::invalidate())
+    }
+
     fn comment_delete(&self, id: i64) -> Result<(), String> {
-        Comment::delete(&self.pool, id)
+        Comment::delete(&self.pool, id).inspect(|_| page_cache::invalidate())
     }
 
     // ── Categories ──────────────────────────────────────────────────
@@ -614,19 +818,19 @@ impl Store for SqliteStore {
     }
 
     fn category_create(&self, form: &CategoryForm) -> Result<i64, String> {
-        Category::create(&self.pool, form)
+        Category::create(&self.pool, form).inspect(|_| page_cache::invalidate())
     }
 
     fn category_update(&self, id: i64, form: &CategoryForm) -> Result<(), String> {
-        Category::update(&self.pool, id, form)
+        Category::update(&self.pool, id, form).inspect(|_| page_cache::invalidate())
     }
 
     fn category_delete(&self, id: i64) -> Result<(), String> {
-        Category::delete(&self.pool, id)
+        Category::delete(&self.pool, id).inspect(|_| page_cache::invalidate())
     }
 
     fn category_set_show_in_nav(&self, id: i64, show: bool) -> Result<(), String> {
-        Category::set_show_in_nav(&self.pool, id, show)
+        Category::set_show_in_nav(&self.pool, id, show).inspect(|_| page_cache::invalidate())
     }
 
     fn category_list_nav_visible(&self, type_filter: Option<&str>) -> Vec<Category> {
@@ -640,6 +844,7 @@ impl Store for SqliteStore {
         category_ids: &[i64],
     ) -> Result<(), String> {
         Category::set_for_content(&self.pool, content_id, content_type, category_ids)
+            .inspect(|_| page_cache::invalidate())
     }
 
     // ── Tags ────────────────────────────────────────────────────────
@@ -673,15 +878,15 @@ impl Store for SqliteStore {
     }
 
     fn tag_create(&self, form: &TagForm) -> Result<i64, String> {
-        Tag::create(&self.pool, form)
+        Tag::create(&self.pool, form).inspect(|_| page_cache::invalidate())
     }
 
     fn tag_update(&self, id: i64, form: &TagForm) -> Result<(), String> {
-        Tag::update(&self.pool, id, form)
+        Tag::update(&self.pool, id, form).inspect(|_| page_cache::invalidate())
     }
 
     fn tag_delete(&self, id: i64) -> Result<(), String> {
-        Tag::delete(&self.pool, id)
+        Tag::delete(&self.pool, id).inspect(|_| page_cache::invalidate())
     }
 
     fn tag_set_for_content(
@@ -691,6 +896,7 @@ impl Store for SqliteStore {
         tag_ids: &[i64],
     ) -> Result<(), String> {
         Tag::set_for_content(&self.pool, content_id, content_type, tag_ids)
+            .inspect(|_| page_cache::invalidate())
     }
 
     fn tag_find_or_create(&self, name: &str) -> Result<i64, String> {
@@ -716,7 +922,7 @@ impl Store for SqliteStore {
     }
 
     fn design_activate(&self, id: i64) -> Result<(), String> {
-        Design::activate(&self.pool, id)
+        Design::activate(&self.pool, id).inspect(|_| page_cache::invalidate())
     }
 
     fn design_create(&self, name: &str) -> Result<i64, String> {
@@ -736,15 +942,31 @@ impl Store for SqliteStore {
             rusqlite::params![slug, layout_html, style_css, id],
         )
         .map_err(|e| e.to_string())?;
+        page_cache::invalidate();
         Ok(())
     }
 
+    fn design_update_custom_code(
+        &self,
+        id: i64,
+        custom_css: &str,
+        head_scripts: &str,
+        footer_scripts: &str,
+    ) -> Result<(), String> {
+        Design::update_custom_code(&self.pool, id, custom_css, head_scripts, footer_scripts)
+            .inspect(|_| page_cache::invalidate())
+    }
+
+    fn design_set_parent(&self, id: i64, parent_id: Option<i64>) -> Result<(), String> {
+        Design::set_parent(&self.pool, id, parent_id).inspect(|_| page_cache::invalidate())
+    }
+
     fn design_duplicate(&self, id: i64, new_name: &str) -> Result<i64, String> {
         Design::duplicate(&self.pool, id, new_name)
     }
 
     fn design_delete(&self, id: i64) -> Result<(), String> {
-        Design::delete(&self.pool, id)
+        Design::delete(&self.pool, id).inspect(|_| page_cache::invalidate())
     }
 
     // ── Design Templates ────────────────────────────────────────────
@@ -753,7 +975,11 @@ impl Store for SqliteStore {
         DesignTemplate::for_design(&self.pool, design_id)
     }
 
-    fn design_template_get(&self, design_id: i64, template_type: &str) -> Option<DesignTemplate> {
+    fn design_template_get_own(
+        &self,
+        design_id: i64,
+        template_type: &str,
+    ) -> Option<DesignTemplate> {
         DesignTemplate::get(&self.pool, design_id, template_type)
     }
 
@@ -765,6 +991,7 @@ impl Store for SqliteStore {
         style_css: &str,
     ) -> Result<(), String> {
         DesignTemplate::upsert(&self.pool, design_id, template_type, layout_html, style_css)
+            .inspect(|_| page_cache::invalidate())
     }
 
     fn design_template_upsert_full(
@@ -783,6 +1010,40 @@ impl Store for SqliteStore {
             style_css,
             grapesjs_data,
         )
+        .inspect(|_| page_cache::invalidate())
+    }
+
+    fn design_template_version_list(
+        &self,
+        design_id: i64,
+        template_type: &str,
+    ) -> Vec<DesignTemplateVersion> {
+        DesignTemplateVersion::list(&self.pool, design_id, template_type)
+    }
+
+    fn design_template_version_get(&self, id: i64) -> Option<DesignTemplateVersion> {
+        DesignTemplateVersion::find(&self.pool, id)
+    }
+
+    fn design_template_version_create(
+        &self,
+        version: &DesignTemplateVersion,
+    ) -> Result<i64, String> {
+        DesignTemplateVersion::create(&self.pool, version)
+    }
+
+    // ── Widgets ─────────────────────────────────────────────────────
+
+    fn widget_list(&self, design_id: i64) -> Vec<Widget> {
+        Widget::list_for_design(&self.pool, design_id)
+    }
+
+    fn widget_save(&self, widget: &Widget) -> Result<i64, String> {
+        Widget::save(&self.pool, widget)
+    }
+
+    fn widget_delete(&self, id: i64) -> Result<(), String> {
+        Widget::delete(&self.pool, id)
     }
 
     // ── Audit Log ───────────────────────────────────────────────────
@@ -838,6 +1099,24 @@ impl Store for SqliteStore {
         AuditEntry::count(&self.pool, action_filter, entity_filter, user_filter)
     }
 
+    fn audit_list_keyset(
+        &self,
+        action_filter: Option<&str>,
+        entity_filter: Option<&str>,
+        user_filter: Option<i64>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<AuditEntry>, Option<Cursor>) {
+        AuditEntry::list_keyset(
+            &self.pool,
+            action_filter,
+            entity_filter,
+            user_filter,
+            after,
+            limit,
+        )
+    }
+
     fn audit_distinct_actions(&self) -> Vec<String> {
         AuditEntry::distinct_actions(&self.pool)
     }
@@ -850,6 +1129,10 @@ impl Store for SqliteStore {
         AuditEntry::cleanup(&self.pool, max_age_days)
     }
 
+    fn audit_list_after(&self, after_id: i64, limit: i64) -> Vec<AuditEntry> {
+        AuditEntry::list_after(&self.pool, after_id, limit)
+    }
+
     // ── Firewall: Bans ──────────────────────────────────────────────
 
     fn fw_is_banned(&self, ip: &str) -> bool {
@@ -884,6 +1167,14 @@ impl Store for SqliteStore {
         )
     }
 
+    fn fw_offense_record(&self, ip: &str, window_days: i64) -> i64 {
+        FwBan::record_offense(&self.pool, ip, window_days)
+    }
+
+    fn fw_offense_count(&self, ip: &str) -> i64 {
+        FwBan::offense_count(&self.pool, ip)
+    }
+
     fn fw_unban(&self, ip: &str) -> Result<usize, String> {
         FwBan::unban(&self.pool, ip)
     }
@@ -892,6 +1183,10 @@ impl Store for SqliteStore {
         FwBan::unban_by_id(&self.pool, id)
     }
 
+    fn fw_unban_all(&self) -> Result<usize, String> {
+        FwBan::unban_all(&self.pool)
+    }
+
     fn fw_active_bans(&self, limit: i64, offset: i64) -> Vec<FwBan> {
         FwBan::active_bans(&self.pool, limit, offset)
     }
@@ -954,6 +1249,115 @@ impl Store for SqliteStore {
         FwEvent::counts_by_type(&self.pool)
     }
 
+    fn fw_event_list_after(&self, after_id: i64, limit: i64) -> Vec<FwEvent> {
+        FwEvent::list_after(&self.pool, after_id, limit)
+    }
+
+    fn fw_event_cleanup(&self, max_age_days: i64) -> Result<usize, String> {
+        FwEvent::cleanup(&self.pool, max_age_days)
+    }
+
+    fn fw_rule_list(&self) -> Vec<FwRule> {
+        self.fw_rules.get_or_load(|| FwRule::list(&self.pool))
+    }
+
+    fn fw_rule_save(&self, rule: &FwRule) -> Result<i64, String> {
+        let id = FwRule::save(&self.pool, rule)?;
+        firewall::invalidate_rules();
+        Ok(id)
+    }
+
+    fn fw_rule_delete(&self, id: i64) -> Result<(), String> {
+        FwRule::delete(&self.pool, id)?;
+        firewall::invalidate_rules();
+        Ok(())
+    }
+
+    fn fw_rule_hit(&self, id: i64) {
+        FwRule::record_hit(&self.pool, id);
+        self.fw_rules.record_hit(id);
+    }
+
+    // ── Redirects ───────────────────────────────────────────────────
+
+    fn redirect_list(&self) -> Vec<RedirectRule> {
+        self.redirects
+            .get_or_load(|| RedirectRule::list(&self.pool))
+    }
+
+    fn redirect_save(&self, rule: &RedirectRule) -> Result<i64, String> {
+        let id = RedirectRule::save(&self.pool, rule)?;
+        redirect::invalidate_redirects();
+        Ok(id)
+    }
+
+    fn redirect_delete(&self, id: i64) -> Result<(), String> {
+        RedirectRule::delete(&self.pool, id)?;
+        redirect::invalidate_redirects();
+        Ok(())
+    }
+
+    fn redirect_hit(&self, id: i64) {
+        RedirectRule::record_hit(&self.pool, id);
+        self.redirects.record_hit(id);
+    }
+
+    fn not_found_record(&self, path: &str, referrer: Option<&str>) -> Result<(), String> {
+        NotFoundEntry::record(&self.pool, path, referrer)
+    }
+
+    fn not_found_record_batch(&self, hits: &[NotFoundHit]) -> Result<(), String> {
+        NotFoundEntry::record_batch(&self.pool, hits)
+    }
+
+    fn not_found_top(&self, limit: i64) -> Vec<NotFoundEntry> {
+        NotFoundEntry::top(&self.pool, limit)
+    }
+
+    fn not_found_dismiss(&self, path: &str) -> Result<(), String> {
+        NotFoundEntry::dismiss(&self.pool, path)
+    }
+
+    fn not_found_cleanup(&self, max_age_days: i64) -> Result<usize, String> {
+        NotFoundEntry::cleanup(&self.pool, max_age_days)
+    }
+
+    // ── API keys ────────────────────────────────────────────────────
+
+    fn api_key_list(&self) -> Vec<ApiKey> {
+        ApiKey::list(&self.pool)
+    }
+
+    fn api_key_find_by_hash(&self, key_hash: &str) -> Option<ApiKey> {
+        ApiKey::find_by_hash(&self.pool, key_hash)
+    }
+
+    fn api_key_create(&self, key: &ApiKey) -> Result<i64, String> {
+        ApiKey::create(&self.pool, key)
+    }
+
+    fn api_key_delete(&self, id: i64) -> Result<(), String> {
+        ApiKey::delete(&self.pool, id)
+    }
+
+    fn api_key_touch(&self, id: i64) {
+        ApiKey::touch(&self.pool, id)
+    }
+
+    // ── Search engine pings ─────────────────────────────────────────
+
+    fn ping_log_add(&self, entry: &PingLogEntry) -> Result<i64, String> {
+        PingLogEntry::add(&self.pool, entry)
+    }
+
+    fn ping_log_recent(&self, limit: i64) -> Vec<PingLogEntry> {
+        PingLogEntry::recent(&self.pool, limit)
+    }
+
+    fn ping_log_cleanup(&self, max_age_days: i64) -> Result<usize, String> {
+        PingLogEntry::cleanup(&self.pool, max_age_days)
+    }
+
     // ── Analytics ───────────────────────────────────────────────────
 
     fn analytics_record(
@@ -966,8 +1370,9 @@ impl Store for SqliteStore {
         user_agent: Option<&str>,
         device_type: Option<&str>,
         browser: Option<&str>,
+        utm: &Utm,
     ) -> Result<(), String> {
-        crate::models::analytics::PageView::record(
+        crate::models::analytics::PageView::record_with_utm(
             &self.pool,
             path,
             ip_hash,
@@ -977,6 +1382,7 @@ impl Store for SqliteStore {
             user_agent,
             device_type,
             browser,
+            utm,
         )
     }
 
@@ -1008,48 +1414,375 @@ impl Store for SqliteStore {
         crate::models::analytics::PageView::top_referrers(&self.pool, from, to, limit)
     }
 
+    fn analytics_top_paths(
+        &self,
+        prefix: &str,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Vec<CountEntry> {
+        crate::models::analytics::PageView::top_paths(&self.pool, prefix, from, to, limit)
+    }
+
     fn analytics_tag_relations(&self) -> Vec<TagRelation> {
         crate::models::analytics::PageView::tag_relations(&self.pool)
     }
 
-    // ── Orders ──────────────────────────────────────────────────────
+    fn analytics_campaigns(&self, from: &str, to: &str, limit: i64) -> Vec<CampaignEntry> {
+        crate::models::analytics::PageView::campaigns(&self.pool, from, to, limit)
+    }
 
-    fn order_find_by_id(&self, id: i64) -> Option<Order> {
-        Order::find_by_id(&self.pool, id)
+    fn analytics_utm_breakdown(
+        &self,
+        dimension: &str,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Vec<CountEntry> {
+        crate::models::analytics::PageView::utm_breakdown(&self.pool, dimension, from, to, limit)
     }
 
-    fn order_find_by_uuid(&self, uuid: &str) -> Option<Order> {
-        Order::find_by_uuid(&self.pool, uuid)
+    fn analytics_path_stats(&self, path: &str, from: &str, to: &str) -> PathStats {
+        crate::models::analytics::PageView::path_stats(&self.pool, path, from, to)
     }
 
-    fn order_find_by_provider_order_id(&self, provider_order_id: &str) -> Option<Order> {
-        Order::find_by_provider_order_id(&self.pool, provider_order_id)
+    fn analytics_path_breakdown(
+        &self,
+        path: &str,
+        dimension: &str,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Vec<CountEntry> {
+        crate::models::analytics::PageView::path_breakdown(
+            &self.pool, path, dimension, from, to, limit,
+        )
     }
 
-    fn order_list(&self, limit: i64, offset: i64) -> Vec<Order> {
-        Order::list(&self.pool, limit, offset)
+    fn goal_record(&self, goal: &str, ip_hash: &str, path: &str) -> Result<bool, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let n = conn
+            .execute(
+                "INSERT INTO goal_completions (goal, ip_hash, path)
+                 SELECT ?1, ?2, ?3
+                 WHERE NOT EXISTS (
+                    SELECT 1 FROM goal_completions
+                    WHERE goal = ?1 AND ip_hash = ?2 AND DATE(created_at) = DATE('now')
+                 )",
+                params![goal, ip_hash, path],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(n > 0)
     }
 
-    fn order_list_by_status(&self, status: &str, limit: i64, offset: i64) -> Vec<Order> {
-        Order::list_by_status(&self.pool, status, limit, offset)
+    fn goal_stats(&self, from: &str, to: &str) -> Vec<GoalStat> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT goal, COUNT(*), COUNT(DISTINCT ip_hash)
+             FROM goal_completions
+             WHERE created_at BETWEEN ?1 AND ?2
+             GROUP BY goal",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![from, to], |row| {
+            Ok(GoalStat {
+                goal: row.get(0)?,
+                completions: row.get(1)?,
+                converters: row.get(2)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
     }
 
-    fn order_list_by_email(&self, email: &str, limit: i64, offset: i64) -> Vec<Order> {
-        Order::list_by_email(&self.pool, email, limit, offset)
+    fn goal_daily(&self, goal: &str, from: &str, to: &str) -> Vec<DailyCount> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT DATE(created_at) as date, COUNT(*)
+             FROM goal_completions
+             WHERE goal = ?1 AND created_at BETWEEN ?2 AND ?3
+             GROUP BY date
+             ORDER BY date",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![goal, from, to], |row| {
+            Ok(DailyCount {
+                date: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
     }
 
-    fn order_list_by_portfolio(&self, portfolio_id: i64) -> Vec<Order> {
-        Order::list_by_portfolio(&self.pool, portfolio_id)
+    fn outbound_click_record(&self, url: &str, ip_hash: &str) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO outbound_clicks (url, ip_hash) VALUES (?1, ?2)",
+            params![url, ip_hash],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
     }
 
-    fn order_count(&self) -> i64 {
-        Order::count(&self.pool)
+    fn outbound_top_links(&self, from: &str, to: &str, limit: i64) -> Vec<OutboundEntry> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT url, COUNT(*) as clicks, COUNT(DISTINCT ip_hash)
+             FROM outbound_clicks
+             WHERE created_at BETWEEN ?1 AND ?2
+             GROUP BY url
+             ORDER BY clicks DESC, url
+             LIMIT ?3",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![from, to, limit], |row| {
+            Ok(OutboundEntry {
+                url: row.get(0)?,
+                clicks: row.get(1)?,
+                visitors: row.get(2)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
     }
 
-    fn order_count_by_status(&self, status: &str) -> i64 {
+    fn engagement_record(
+        &self,
+        path: &str,
+        ip_hash: &str,
+        scroll: i64,
+        seconds: i64,
+    ) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO engagement_pings (path, ip_hash, scroll, seconds) VALUES (?1, ?2, ?3, ?4)",
+            params![path, ip_hash, scroll, seconds],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn engagement_path_stats(&self, path: &str, from: &str, to: &str) -> EngagementStats {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return EngagementStats::default(),
+        };
+        conn.query_row(
+            "SELECT COUNT(*), COALESCE(AVG(scroll), 0), COALESCE(AVG(seconds), 0),
+                    COALESCE(AVG(CASE WHEN scroll >= 90 THEN 100.0 ELSE 0.0 END), 0)
+             FROM engagement_pings
+             WHERE (path = ?1 OR path = ?1 || '/') AND created_at BETWEEN ?2 AND ?3",
+            params![path, from, to],
+            |row| {
+                Ok(EngagementStats {
+                    pings: row.get(0)?,
+                    avg_scroll: row.get(1)?,
+                    avg_seconds: row.get(2)?,
+                    read_through: row.get(3)?,
+                })
+            },
+        )
+        .unwrap_or_default()
+    }
+
+    fn search_log_record(&self, query: &str, results: i64, ip_hash: &str) -> Result<i64, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO search_log (query, results, ip_hash) VALUES (?1, ?2, ?3)",
+            params![query, results, ip_hash],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    fn search_log_click(&self, id: i64, ip_hash: &str, path: &str) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE search_log SET clicked_path = ?3
+             WHERE id = ?1 AND ip_hash = ?2 AND clicked_path IS NULL",
+            params![id, ip_hash, path],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn search_top_queries(&self, from: &str, to: &str, limit: i64) -> Vec<SearchQueryStat> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT query, COUNT(*) as searches,
+                    (SELECT s2.results FROM search_log s2 WHERE s2.query = s.query
+                     ORDER BY s2.id DESC LIMIT 1),
+                    COUNT(clicked_path)
+             FROM search_log s
+             WHERE created_at BETWEEN ?1 AND ?2
+             GROUP BY query
+             ORDER BY searches DESC, query
+             LIMIT ?3",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![from, to, limit], |row| {
+            Ok(SearchQueryStat {
+                query: row.get(0)?,
+                searches: row.get(1)?,
+                results: row.get(2)?,
+                clicks: row.get(3)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    fn search_zero_result_queries(&self, from: &str, to: &str, limit: i64) -> Vec<CountEntry> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT query, COUNT(*) as searches
+             FROM search_log
+             WHERE results = 0 AND created_at BETWEEN ?1 AND ?2
+             GROUP BY query
+             ORDER BY searches DESC, query
+             LIMIT ?3",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![from, to, limit], |row| {
+            Ok(CountEntry {
+                label: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    fn experiment_event_record(
+        &self,
+        experiment: &str,
+        variant: &str,
+        kind: &str,
+        ip_hash: &str,
+    ) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR IGNORE INTO experiment_events (experiment, variant, kind, ip_hash)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![experiment, variant, kind, ip_hash],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn experiment_stats(&self, from: &str, to: &str) -> Vec<VariantStat> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        // Clicks only count from visitors who were shown the variant
+        let mut stmt = match conn.prepare(
+            "SELECT i.experiment, i.variant, COUNT(*),
+                    COUNT(c.id)
+             FROM experiment_events i
+             LEFT JOIN experiment_events c
+               ON c.experiment = i.experiment AND c.variant = i.variant
+              AND c.ip_hash = i.ip_hash AND c.kind = 'click'
+             WHERE i.kind = 'impression' AND i.created_at BETWEEN ?1 AND ?2
+             GROUP BY i.experiment, i.variant",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![from, to], |row| {
+            Ok(VariantStat {
+                experiment: row.get(0)?,
+                variant: row.get(1)?,
+                impressions: row.get(2)?,
+                clicks: row.get(3)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    // ── Orders ──────────────────────────────────────────────────────
+
+    fn order_find_by_id(&self, id: i64) -> Option<Order> {
+        Order::find_by_id(&self.pool, id)
+    }
+
+    fn order_find_by_uuid(&self, uuid: &str) -> Option<Order> {
+        Order::find_by_uuid(&self.pool, uuid)
+    }
+
+    fn order_find_by_provider_order_id(&self, provider_order_id: &str) -> Option<Order> {
+        Order::find_by_provider_order_id(&self.pool, provider_order_id)
+    }
+
+    fn order_list(&self, limit: i64, offset: i64) -> Vec<Order> {
+        Order::list(&self.pool, limit, offset)
+    }
+
+    fn order_list_by_status(&self, status: &str, limit: i64, offset: i64) -> Vec<Order> {
+        Order::list_by_status(&self.pool, status, limit, offset)
+    }
+
+    fn order_list_since(
+        &self,
+        status: &str,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, Order)> {
+        Order::list_since(&self.pool, status, since_seq, limit)
+    }
+
+    fn order_list_by_email(&self, email: &str, limit: i64, offset: i64) -> Vec<Order> {
+        Order::list_by_email(&self.pool, email, limit, offset)
+    }
+
+    fn order_list_by_portfolio(&self, portfolio_id: i64) -> Vec<Order> {
+        Order::list_by_portfolio(&self.pool, portfolio_id)
+    }
+
+    fn order_count(&self) -> i64 {
+        Order::count(&self.pool)
+    }
+
+    fn order_count_by_status(&self, status: &str) -> i64 {
         Order::count_by_status(&self.pool, status)
     }
 
+    fn order_list_keyset(
+        &self,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Order>, Option<Cursor>) {
+        Order::list_keyset(&self.pool, status, after, limit)
+    }
+
     fn order_total_revenue(&self) -> f64 {
         Order::total_revenue(&self.pool)
     }
@@ -1254,6 +1987,18 @@ impl Store for SqliteStore {
         )
     }
 
+    fn import_find_by_id(&self, id: i64) -> Option<Import> {
+        Import::find_by_id(&self.pool, id)
+    }
+
+    fn import_set_created(&self, id: i64, created: &ImportCreated) -> Result<(), String> {
+        Import::set_created(&self.pool, id, created)
+    }
+
+    fn import_mark_rolled_back(&self, id: i64) -> Result<(), String> {
+        Import::mark_rolled_back(&self.pool, id)
+    }
+
     // ── Search (FTS) ────────────────────────────────────────────────
 
     fn search_create_fts_table(&self) -> Result<(), String> {
@@ -1286,10 +2031,22 @@ impl Store for SqliteStore {
             date,
             is_published,
         );
+        crate::search_engine::sync_item(
+            self,
+            item_type,
+            item_id,
+            title,
+            html_body,
+            slug,
+            image,
+            date,
+            is_published,
+        );
     }
 
     fn search_remove_item(&self, item_type: &str, item_id: i64) {
         crate::models::search::remove_item(&self.pool, item_type, item_id);
+        crate::search_engine::sync_removal(self, item_type, item_id);
     }
 
     fn search_query(&self, query: &str, limit: i64) -> Vec<SearchResult> {
@@ -1332,6 +2089,30 @@ impl Store for SqliteStore {
         Ok(())
     }
 
+    fn session_list_for_user(&self, user_id: i64) -> Vec<serde_json::Value> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT created_at, expires_at, ip_address, user_agent FROM sessions \
+             WHERE user_id = ?1 ORDER BY created_at DESC",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![user_id], |r| {
+            Ok(serde_json::json!({
+                "created_at": r.get::<_, String>(0)?,
+                "expires_at": r.get::<_, String>(1)?,
+                "ip_address": r.get::<_, Option<String>>(2)?,
+                "user_agent": r.get::<_, Option<String>>(3)?,
+            }))
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
     fn session_create_full(
         &self,
         user_id: i64,
@@ -1373,6 +2154,34 @@ impl Store for SqliteStore {
         .unwrap_or(0)
     }
 
+    fn session_set_sudo(&self, token: &str, minutes: i64) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let until = (chrono::Utc::now() + chrono::Duration::minutes(minutes.max(0)))
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        conn.execute(
+            "UPDATE sessions SET sudo_until = ?1 WHERE id = ?2",
+            params![until, token],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn session_sudo_active(&self, token: &str) -> bool {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return false,
+        };
+        conn.query_row(
+            "SELECT COUNT(*) FROM sessions WHERE id = ?1 \
+             AND expires_at > datetime('now') AND sudo_until > datetime('now')",
+            params![token],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|n| n > 0)
+        .unwrap_or(false)
+    }
+
     // ── Magic links / password reset tokens ─────────────────────────
 
     fn magic_link_create(
@@ -1456,6 +2265,19 @@ impl Store for SqliteStore {
 
     fn analytics_prune(&self, before_date: &str) -> Result<usize, String> {
         let conn = self.pool.get().map_err(|e| e.to_string())?;
+        for table in [
+            "goal_completions",
+            "outbound_clicks",
+            "search_log",
+            "experiment_events",
+            "engagement_pings",
+        ] {
+            conn.execute(
+                &format!("DELETE FROM {} WHERE created_at < ?1", table),
+                params![before_date],
+            )
+            .map_err(|e| e.to_string())?;
+        }
         conn.execute(
             "DELETE FROM page_views WHERE created_at < ?1",
             params![before_date],
@@ -1463,6 +2285,30 @@ impl Store for SqliteStore {
         .map_err(|e| e.to_string())
     }
 
+    fn analytics_forget_visitors(&self, ip_hashes: &[String]) -> Result<usize, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let mut deleted = 0;
+        for hash in ip_hashes {
+            for table in [
+                "goal_completions",
+                "outbound_clicks",
+                "search_log",
+                "experiment_events",
+                "engagement_pings",
+            ] {
+                conn.execute(
+                    &format!("DELETE FROM {} WHERE ip_hash = ?1", table),
+                    params![hash],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            deleted += conn
+                .execute("DELETE FROM page_views WHERE ip_hash = ?1", params![hash])
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(deleted)
+    }
+
     fn analytics_count(&self) -> i64 {
         let conn = match self.pool.get() {
             Ok(c) => c,
@@ -2053,6 +2899,12 @@ impl Store for SqliteStore {
                 [],
             )
             .map_err(|e| e.to_string())?;
+        if posts > 0 {
+            super::content_changed(Section::Posts);
+        }
+        if portfolio > 0 {
+            super::content_changed(Section::Portfolio);
+        }
         Ok(posts + portfolio)
     }
 
@@ -2067,9 +2919,35 @@ impl Store for SqliteStore {
 
     fn task_cleanup_analytics(&self, max_age_days: i64) -> Result<usize, String> {
         let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let cutoff = format!("-{} days", max_age_days);
+        conn.execute(
+            "DELETE FROM goal_completions WHERE created_at < datetime('now', ?1)",
+            rusqlite::params![cutoff],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM outbound_clicks WHERE created_at < datetime('now', ?1)",
+            rusqlite::params![cutoff],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM search_log WHERE created_at < datetime('now', ?1)",
+            rusqlite::params![cutoff],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM experiment_events WHERE created_at < datetime('now', ?1)",
+            rusqlite::params![cutoff],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM engagement_pings WHERE created_at < datetime('now', ?1)",
+            rusqlite::params![cutoff],
+        )
+        .map_err(|e| e.to_string())?;
         conn.execute(
             "DELETE FROM page_views WHERE created_at < datetime('now', ?1)",
-            rusqlite::params![format!("-{} days", max_age_days)],
+            rusqlite::params![cutoff],
         )
         .map_err(|e| e.to_string())
     }
@@ -2082,11 +2960,13 @@ impl Store for SqliteStore {
         from: &str,
         subject: &str,
         body: &str,
+        reply_to: &str,
     ) -> Result<i64, String> {
         let conn = self.pool.get().map_err(|e| e.to_string())?;
         conn.execute(
-            "INSERT INTO email_queue (to_addr, from_addr, subject, body_text) VALUES (?1, ?2, ?3, ?4)",
-            rusqlite::params![to, from, subject, body],
+            "INSERT INTO email_queue (to_addr, from_addr, subject, body_text, reply_to) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![to, from, subject, body, reply_to],
         )
         .map_err(|e| e.to_string())?;
         Ok(conn.last_insert_rowid())
@@ -2097,31 +2977,18 @@ impl Store for SqliteStore {
             Ok(c) => c,
             Err(_) => return Vec::new(),
         };
-        let mut stmt = match conn.prepare(
-            "SELECT id, to_addr, from_addr, subject, body_text, attempts, max_attempts, next_retry_at, status, error, created_at \
-             FROM email_queue WHERE status = 'pending' AND next_retry_at <= datetime('now') \
+        let sql = format!(
+            "SELECT {} FROM email_queue WHERE status = 'pending' AND next_retry_at <= datetime('now') \
              ORDER BY next_retry_at ASC LIMIT ?1",
-        ) {
+            QUEUE_COLS
+        );
+        let mut stmt = match conn.prepare(&sql) {
             Ok(s) => s,
             Err(_) => return Vec::new(),
         };
-        stmt.query_map(rusqlite::params![limit], |r| {
-            Ok(crate::mta::queue::QueuedEmail {
-                id: r.get(0)?,
-                to_addr: r.get(1)?,
-                from_addr: r.get(2)?,
-                subject: r.get(3)?,
-                body_text: r.get(4)?,
-                attempts: r.get(5)?,
-                max_attempts: r.get(6)?,
-                next_retry_at: r.get(7)?,
-                status: r.get(8)?,
-                error: r.get::<_, String>(9).unwrap_or_default(),
-                created_at: r.get(10)?,
-            })
-        })
-        .map(|rows| rows.filter_map(|r| r.ok()).collect())
-        .unwrap_or_default()
+        stmt.query_map(rusqlite::params![limit], row_to_queued_email)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
     }
 
     fn mta_queue_update_status(
@@ -2134,8 +3001,11 @@ impl Store for SqliteStore {
         let conn = self.pool.get().map_err(|e| e.to_string())?;
         conn.execute(
             "UPDATE email_queue SET status = ?1, error = COALESCE(?2, error), \
+             error_log = CASE WHEN ?2 IS NULL THEN error_log \
+                 ELSE error_log || datetime('now') || ' [attempt ' || attempts || '] ' || ?2 || char(10) END, \
              next_retry_at = COALESCE(?3, next_retry_at), \
-             attempts = CASE WHEN ?1 = 'sending' THEN attempts + 1 ELSE attempts END \
+             attempts = CASE WHEN ?1 = 'sending' THEN attempts + 1 ELSE attempts END, \
+             sent_at = CASE WHEN ?1 = 'sent' THEN datetime('now') ELSE sent_at END \
              WHERE id = ?4",
             rusqlite::params![status, error, next_retry, id],
         )
@@ -2146,7 +3016,8 @@ impl Store for SqliteStore {
     fn mta_queue_sent_last_hour(&self) -> Result<u64, String> {
         let conn = self.pool.get().map_err(|e| e.to_string())?;
         conn.query_row(
-            "SELECT COUNT(*) FROM email_queue WHERE status = 'sent' AND created_at >= datetime('now', '-1 hour')",
+            "SELECT COUNT(*) FROM email_queue WHERE status = 'sent' \
+             AND COALESCE(sent_at, created_at) >= datetime('now', '-1 hour')",
             [],
             |r| r.get(0),
         )
@@ -2189,18 +3060,1079 @@ impl Store for SqliteStore {
         Ok(count as u64)
     }
 
-    // ── Raw execute ─────────────────────────────────────────────────
+    fn mta_queue_list(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<crate::mta::queue::QueuedEmail> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return Vec::new(),
+        };
+        let sql = format!(
+            "SELECT {} FROM email_queue WHERE (?1 IS NULL OR status = ?1) \
+             ORDER BY created_at DESC, id DESC LIMIT ?2 OFFSET ?3",
+            QUEUE_COLS
+        );
+        let mut stmt = match conn.prepare(&sql) {
+            Ok(s) => s,
+            Err(_) => return Vec::new(),
+        };
+        stmt.query_map(params![status, limit, offset], row_to_queued_email)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
 
-    fn raw_execute(&self, sql: &str) -> Result<usize, String> {
-        let conn = self.pool.get().map_err(|e| e.to_string())?;
-        conn.execute(sql, []).map_err(|e| e.to_string())
+    fn mta_queue_count(&self, status: Option<&str>) -> u64 {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return 0,
+        };
+        conn.query_row(
+            "SELECT COUNT(*) FROM email_queue WHERE (?1 IS NULL OR status = ?1)",
+            params![status],
+            |r| r.get(0),
+        )
+        .unwrap_or(0)
     }
 
-    fn raw_query_i64(&self, sql: &str) -> Result<i64, String> {
+    fn mta_queue_get(&self, id: i64) -> Option<crate::mta::queue::QueuedEmail> {
+        let conn = self.pool.get().ok()?;
+        let sql = format!("SELECT {} FROM email_queue WHERE id = ?1", QUEUE_COLS);
+        conn.query_row(&sql, params![id], row_to_queued_email).ok()
+    }
+
+    fn mta_queue_retry(&self, id: i64) -> Result<(), String> {
         let conn = self.pool.get().map_err(|e| e.to_string())?;
-        conn.query_row(sql, [], |row| row.get(0))
-            .map_err(|e| e.to_string())
+        let updated = conn
+            .execute(
+                "UPDATE email_queue SET status = 'pending', attempts = 0, \
+                 next_retry_at = datetime('now') WHERE id = ?1 AND status != 'sending'",
+                params![id],
+            )
+            .map_err(|e| e.to_string())?;
+        if updated == 0 {
+            return Err("Message not found or currently sending".to_string());
+        }
+        Ok(())
     }
+
+    fn mta_queue_delete(&self, id: i64) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM email_queue WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn mta_queue_sent_hourly(&self, hours: i64) -> Vec<(String, u64)> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return Vec::new(),
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT strftime('%Y-%m-%d %H:00', COALESCE(sent_at, created_at)) AS hour, COUNT(*) \
+             FROM email_queue \
+             WHERE status = 'sent' AND COALESCE(sent_at, created_at) >= datetime('now', ?1) \
+             GROUP BY hour ORDER BY hour",
+        ) {
+            Ok(s) => s,
+            Err(_) => return Vec::new(),
+        };
+        stmt.query_map(params![format!("-{} hours", hours)], |r| {
+            Ok((r.get(0)?, r.get(1)?))
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    fn mta_reply_token_create(
+        &self,
+        token: &str,
+        post_id: i64,
+        content_type: &str,
+        comment_id: i64,
+        email: &str,
+        expires_days: i64,
+    ) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let now = chrono::Utc::now().naive_utc();
+        let expires = now + chrono::Duration::days(expires_days);
+        conn.execute(
+            "INSERT INTO mta_reply_tokens (token, post_id, content_type, comment_id, email, created_at, expires_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![token, post_id, content_type, comment_id, email, now, expires],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn mta_reply_token_find(&self, token: &str) -> Option<crate::mta::inbound::ReplyToken> {
+        let conn = self.pool.get().ok()?;
+        let now = chrono::Utc::now().naive_utc();
+        conn.query_row(
+            "SELECT token, post_id, content_type, comment_id, email, expires_at \
+             FROM mta_reply_tokens WHERE token = ?1 AND expires_at > ?2",
+            params![token, now],
+            |r| {
+                Ok(crate::mta::inbound::ReplyToken {
+                    token: r.get(0)?,
+                    post_id: r.get(1)?,
+                    content_type: r.get(2)?,
+                    comment_id: r.get(3)?,
+                    email: r.get(4)?,
+                    expires_at: r.get::<_, NaiveDateTime>(5)?.to_string(),
+                })
+            },
+        )
+        .ok()
+    }
+
+    fn mta_reply_token_consume(&self, token: &str) -> Result<bool, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let now = chrono::Utc::now().naive_utc();
+        conn.execute(
+            "DELETE FROM mta_reply_tokens WHERE token = ?1 AND expires_at > ?2",
+            params![token, now],
+        )
+        .map(|n| n > 0)
+        .map_err(|e| e.to_string())
+    }
+
+    fn mta_reply_token_cleanup(&self) -> Result<usize, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let now = chrono::Utc::now().naive_utc();
+        conn.execute(
+            "DELETE FROM mta_reply_tokens WHERE expires_at < ?1",
+            params![now],
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    // ── Email provider quotas ───────────────────────────────────────
+
+    fn email_quota_record(&self, provider: &str) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let (hour, day) = crate::email::routing::quota_buckets(chrono::Utc::now());
+        for bucket in [hour, day] {
+            conn.execute(
+                "INSERT INTO email_quota_usage (provider, bucket, count) VALUES (?1, ?2, 1) \
+                 ON CONFLICT(provider, bucket) DO UPDATE SET count = count + 1",
+                params![provider, bucket],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn email_quota_usage(&self, provider: &str) -> (u64, u64) {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return (0, 0),
+        };
+        let (hour, day) = crate::email::routing::quota_buckets(chrono::Utc::now());
+        let get = |bucket: &str| -> u64 {
+            conn.query_row(
+                "SELECT count FROM email_quota_usage WHERE provider = ?1 AND bucket = ?2",
+                params![provider, bucket],
+                |r| r.get(0),
+            )
+            .unwrap_or(0)
+        };
+        (get(&hour), get(&day))
+    }
+
+    fn email_quota_cleanup(&self, days: i64) -> Result<usize, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(days))
+            .format("%Y-%m-%d")
+            .to_string();
+        conn.execute(
+            "DELETE FROM email_quota_usage WHERE bucket < ?1",
+            params![cutoff],
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    // ── Signed tokens ───────────────────────────────────────────────
+
+    fn signed_token_create(
+        &self,
+        token_hash: &str,
+        purpose: &str,
+        email: &str,
+        payload: &str,
+        expires_minutes: i64,
+    ) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let now = chrono::Utc::now().naive_utc();
+        let expires = now + chrono::Duration::minutes(expires_minutes);
+        conn.execute(
+            "INSERT INTO signed_tokens (token_hash, purpose, email, payload, created_at, expires_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![token_hash, purpose, email, payload, now, expires],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn signed_token_consume(
+        &self,
+        token_hash: &str,
+        purpose: &str,
+    ) -> Result<crate::security::signed_token::SignedToken, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let now = chrono::Utc::now().naive_utc();
+        // Claim the token atomically so concurrent requests can't both redeem it
+        let claimed = conn
+            .execute(
+                "UPDATE signed_tokens SET used_at = ?3 \
+                 WHERE token_hash = ?1 AND purpose = ?2 AND used_at IS NULL AND expires_at > ?3",
+                params![token_hash, purpose, now],
+            )
+            .map_err(|e| e.to_string())?;
+        let row = conn.query_row(
+            "SELECT purpose, email, payload, expires_at FROM signed_tokens \
+             WHERE token_hash = ?1 AND purpose = ?2",
+            params![token_hash, purpose],
+            |r| {
+                let expires_at: NaiveDateTime = r.get(3)?;
+                Ok((
+                    crate::security::signed_token::SignedToken {
+                        purpose: r.get(0)?,
+                        email: r.get(1)?,
+                        payload: r.get(2)?,
+                        expires_at: expires_at.to_string(),
+                    },
+                    expires_at,
+                ))
+            },
+        );
+        match (claimed, row) {
+            (1, Ok((token, _))) => Ok(token),
+            (_, Ok((_, expires_at))) if expires_at > now => {
+                Err("This link has already been used".into())
+            }
+            _ => Err("Invalid or expired link".into()),
+        }
+    }
+
+    fn signed_token_cleanup(&self) -> Result<usize, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let now = chrono::Utc::now().naive_utc();
+        conn.execute(
+            "DELETE FROM signed_tokens WHERE expires_at < ?1 OR used_at IS NOT NULL",
+            params![now],
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    // ── Newsletter subscribers ──────────────────────────────────────
+
+    fn newsletter_subscribe(&self, email: &str) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR IGNORE INTO newsletter_subscribers (email, status, created_at) \
+             VALUES (?1, 'pending', ?2)",
+            params![email.to_lowercase(), chrono::Utc::now().naive_utc()],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn newsletter_confirm(&self, email: &str) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE newsletter_subscribers SET status = 'confirmed', confirmed_at = ?2 \
+             WHERE email = ?1",
+            params![email.to_lowercase(), chrono::Utc::now().naive_utc()],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn newsletter_status(&self, email: &str) -> Option<String> {
+        let conn = self.pool.get().ok()?;
+        conn.query_row(
+            "SELECT status FROM newsletter_subscribers WHERE email = ?1",
+            params![email.to_lowercase()],
+            |r| r.get(0),
+        )
+        .ok()
+    }
+
+    fn newsletter_count(&self, status: Option<&str>) -> i64 {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return 0,
+        };
+        match status {
+            Some(s) => conn.query_row(
+                "SELECT COUNT(*) FROM newsletter_subscribers WHERE status = ?1",
+                params![s],
+                |r| r.get(0),
+            ),
+            None => conn.query_row("SELECT COUNT(*) FROM newsletter_subscribers", [], |r| {
+                r.get(0)
+            }),
+        }
+        .unwrap_or(0)
+    }
+
+    // ── Comment subscriptions ───────────────────────────────────────
+
+    fn comment_subscription_create(
+        &self,
+        post_id: i64,
+        content_type: &str,
+        email: &str,
+    ) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR IGNORE INTO comment_subscriptions (post_id, content_type, email, confirmed, created_at) \
+             VALUES (?1, ?2, ?3, 0, ?4)",
+            params![
+                post_id,
+                content_type,
+                email.to_lowercase(),
+                chrono::Utc::now().naive_utc()
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn comment_subscription_confirm(
+        &self,
+        post_id: i64,
+        content_type: &str,
+        email: &str,
+    ) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE comment_subscriptions SET confirmed = 1 \
+             WHERE post_id = ?1 AND content_type = ?2 AND email = ?3",
+            params![post_id, content_type, email.to_lowercase()],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn comment_subscribers(&self, post_id: i64, content_type: &str) -> Vec<String> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT email FROM comment_subscriptions \
+             WHERE post_id = ?1 AND content_type = ?2 AND confirmed = 1 ORDER BY id",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![post_id, content_type], |r| r.get(0))
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    fn comment_subscription_list_by_email(&self, email: &str) -> Vec<(i64, String)> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT post_id, content_type FROM comment_subscriptions WHERE email = ?1 ORDER BY id",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![email.to_lowercase()], |r| {
+            Ok((r.get(0)?, r.get(1)?))
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    fn subscriber_forget(&self, email: &str) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let email = email.to_lowercase();
+        conn.execute(
+            "DELETE FROM newsletter_subscribers WHERE email = ?1",
+            params![email],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM comment_subscriptions WHERE email = ?1",
+            params![email],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // ── Roles ───────────────────────────────────────────────────────
+
+    fn role_list(&self) -> Vec<Role> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn
+            .prepare("SELECT slug, name, capabilities, created_at FROM roles ORDER BY name")
+        {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map([], role_from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    fn role_find(&self, slug: &str) -> Option<Role> {
+        self.roles.get_or_load(slug, || {
+            let conn = self.pool.get().ok()?;
+            conn.query_row(
+                "SELECT slug, name, capabilities, created_at FROM roles WHERE slug = ?1",
+                params![slug],
+                role_from_row,
+            )
+            .ok()
+        })
+    }
+
+    fn role_save(&self, slug: &str, name: &str, capabilities: &[String]) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO roles (slug, name, capabilities, created_at) VALUES (?1, ?2, ?3, ?4) \
+             ON CONFLICT(slug) DO UPDATE SET name = excluded.name, capabilities = excluded.capabilities",
+            params![
+                slug,
+                name,
+                capabilities.join(","),
+                chrono::Utc::now().naive_utc()
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        role::invalidate();
+        Ok(())
+    }
+
+    fn role_delete(&self, slug: &str) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM roles WHERE slug = ?1", params![slug])
+            .map_err(|e| e.to_string())?;
+        role::invalidate();
+        Ok(())
+    }
+
+    // ── Known login devices ─────────────────────────────────────────
+
+    fn login_device_touch(
+        &self,
+        user_id: i64,
+        ip_hash: &str,
+        device: &str,
+        location: &str,
+    ) -> Result<bool, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let now = chrono::Utc::now().naive_utc();
+        let inserted = conn
+            .execute(
+                "INSERT OR IGNORE INTO login_devices (user_id, ip_hash, device, location, first_seen, last_seen) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                params![user_id, ip_hash, device, location, now],
+            )
+            .map_err(|e| e.to_string())?;
+        if inserted == 0 {
+            conn.execute(
+                "UPDATE login_devices SET last_seen = ?1, location = ?2 \
+                 WHERE user_id = ?3 AND ip_hash = ?4 AND device = ?5",
+                params![now, location, user_id, ip_hash, device],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        Ok(inserted > 0)
+    }
+
+    fn login_device_count(&self, user_id: i64) -> i64 {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return 0,
+        };
+        conn.query_row(
+            "SELECT COUNT(*) FROM login_devices WHERE user_id = ?1",
+            params![user_id],
+            |r| r.get(0),
+        )
+        .unwrap_or(0)
+    }
+
+    fn login_device_forget(&self, user_id: i64) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM login_devices WHERE user_id = ?1",
+            params![user_id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn login_device_list(&self, user_id: i64) -> Vec<serde_json::Value> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT ip_hash, device, location, first_seen, last_seen FROM login_devices \
+             WHERE user_id = ?1 ORDER BY last_seen DESC",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![user_id], |r| {
+            Ok(serde_json::json!({
+                "ip_hash": r.get::<_, String>(0)?,
+                "device": r.get::<_, String>(1)?,
+                "location": r.get::<_, String>(2)?,
+                "first_seen": r.get::<_, String>(3)?,
+                "last_seen": r.get::<_, String>(4)?,
+            }))
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    // ── Persistent rate limiting ────────────────────────────────────
+
+    fn rate_limit_hit(
+        &self,
+        key: &str,
+        max_attempts: u64,
+        window_secs: i64,
+    ) -> Result<bool, String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+        let now = chrono::Utc::now().timestamp_millis();
+        let cutoff = now - window_secs * 1000;
+        // IMMEDIATE takes the write lock up front so concurrent requests
+        // can't both see a free slot
+        let tx = conn
+            .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)
+            .map_err(|e| e.to_string())?;
+        tx.execute(
+            "DELETE FROM rate_limit_hits WHERE key = ?1 AND hit_at <= ?2",
+            params![key, cutoff],
+        )
+        .map_err(|e| e.to_string())?;
+        let count: i64 = tx
+            .query_row(
+                "SELECT COUNT(*) FROM rate_limit_hits WHERE key = ?1",
+                params![key],
+                |r| r.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        let allowed = (count as u64) < max_attempts;
+        if allowed {
+            tx.execute(
+                "INSERT INTO rate_limit_hits (key, hit_at) VALUES (?1, ?2)",
+                params![key, now],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+        Ok(allowed)
+    }
+
+    fn rate_limit_budget(
+        &self,
+        key: &str,
+        max_requests: u64,
+        window_secs: i64,
+    ) -> Result<crate::rate_limit::Budget, String> {
+        let mut conn = self.pool.get().map_err(|e| e.to_string())?;
+        let now = chrono::Utc::now().timestamp_millis();
+        let tx = conn
+            .transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)
+            .map_err(|e| e.to_string())?;
+        tx.execute(
+            "DELETE FROM rate_limit_hits WHERE key = ?1 AND hit_at <= ?2",
+            params![key, now - window_secs * 1000],
+        )
+        .map_err(|e| e.to_string())?;
+        let (count, oldest): (i64, Option<i64>) = tx
+            .query_row(
+                "SELECT COUNT(*), MIN(hit_at) FROM rate_limit_hits WHERE key = ?1",
+                params![key],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .map_err(|e| e.to_string())?;
+        let allowed = (count as u64) < max_requests;
+        if allowed {
+            tx.execute(
+                "INSERT INTO rate_limit_hits (key, hit_at) VALUES (?1, ?2)",
+                params![key, now],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+        let age = std::time::Duration::from_millis((now - oldest.unwrap_or(now)).max(0) as u64);
+        Ok(crate::rate_limit::Budget::counted(
+            allowed,
+            max_requests,
+            count as u64 + u64::from(allowed),
+            age,
+            std::time::Duration::from_secs(window_secs.max(0) as u64),
+        ))
+    }
+
+    fn rate_limit_cleanup(&self, max_age_secs: i64) -> Result<usize, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        let cutoff = chrono::Utc::now().timestamp_millis() - max_age_secs * 1000;
+        conn.execute(
+            "DELETE FROM rate_limit_hits WHERE hit_at <= ?1",
+            params![cutoff],
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    // ── Embeddings ──────────────────────────────────────────────────
+
+    fn embedding_upsert(&self, embedding: &Embedding) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO embeddings (item_type, item_id, model, content_hash, vector, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'))
+             ON CONFLICT(item_type, item_id) DO UPDATE SET
+                model = excluded.model,
+                content_hash = excluded.content_hash,
+                vector = excluded.vector,
+                updated_at = excluded.updated_at",
+            params![
+                embedding.item_type,
+                embedding.item_id,
+                embedding.model,
+                embedding.content_hash,
+                vector_to_blob(&embedding.vector)
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn embedding_remove(&self, item_type: &str, item_id: i64) {
+        if let Ok(conn) = self.pool.get() {
+            let _ = conn.execute(
+                "DELETE FROM embeddings WHERE item_type = ?1 AND item_id = ?2",
+                params![item_type, item_id],
+            );
+        }
+    }
+
+    fn embedding_list(&self, model: &str) -> Vec<Embedding> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT item_type, item_id, model, content_hash, vector FROM embeddings WHERE model = ?1",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![model], |row| {
+            let blob: Vec<u8> = row.get(4)?;
+            Ok(Embedding {
+                item_type: row.get(0)?,
+                item_id: row.get(1)?,
+                model: row.get(2)?,
+                content_hash: row.get(3)?,
+                vector: vector_from_blob(&blob),
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    fn embedding_prune(&self, keep_model: &str) -> Result<usize, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM embeddings WHERE model != ?1",
+            params![keep_model],
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    // ── SEO suggestions ─────────────────────────────────────────────
+
+    fn seo_suggestion_create(&self, suggestion: &SeoSuggestion) -> Result<i64, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO seo_suggestions (content_type, content_id, field, current, suggested, level, reason, status)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'pending')",
+            params![
+                suggestion.content_type,
+                suggestion.content_id,
+                suggestion.field,
+                suggestion.current,
+                suggestion.suggested,
+                suggestion.level,
+                suggestion.reason
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    fn seo_suggestion_get(&self, id: i64) -> Option<SeoSuggestion> {
+        let conn = self.pool.get().ok()?;
+        conn.query_row(
+            "SELECT id, content_type, content_id, field, current, suggested, level, reason, status, created_at
+             FROM seo_suggestions WHERE id = ?1",
+            params![id],
+            seo_suggestion_from_row,
+        )
+        .ok()
+    }
+
+    fn seo_suggestion_pending(&self, content: Option<(&str, i64)>) -> Vec<SeoSuggestion> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let (content_type, content_id) = content.unwrap_or(("", 0));
+        let mut stmt = match conn.prepare(
+            "SELECT id, content_type, content_id, field, current, suggested, level, reason, status, created_at
+             FROM seo_suggestions
+             WHERE status = 'pending' AND (?1 = '' OR (content_type = ?1 AND content_id = ?2))
+             ORDER BY id",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![content_type, content_id], seo_suggestion_from_row)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    fn seo_suggestion_set_status(&self, id: i64, status: &str) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE seo_suggestions SET status = ?1 WHERE id = ?2",
+            params![status, id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn seo_suggestion_exists(&self, content_type: &str, content_id: i64, field: &str) -> bool {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return false,
+        };
+        conn.query_row(
+            "SELECT COUNT(*) FROM seo_suggestions WHERE content_type = ?1 AND content_id = ?2 AND field = ?3",
+            params![content_type, content_id, field],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|n| n > 0)
+        .unwrap_or(false)
+    }
+
+    fn seo_suggestion_clear(&self, content_type: &str, content_id: i64) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM seo_suggestions WHERE content_type = ?1 AND content_id = ?2 AND status = 'pending'",
+            params![content_type, content_id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // ── AI usage ────────────────────────────────────────────────────
+
+    fn ai_usage_record(&self, usage: &AiUsage) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO ai_usage (provider, model, feature, prompt_tokens, completion_tokens, latency_ms, success, error, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                usage.provider,
+                usage.model,
+                usage.feature,
+                usage.prompt_tokens,
+                usage.completion_tokens,
+                usage.latency_ms,
+                usage.success,
+                usage.error,
+                usage.created_at
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn ai_usage_totals(&self, since: &str, until: &str) -> Vec<AiUsageTotal> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT provider, model, feature, COUNT(*),
+                    SUM(CASE WHEN success THEN 0 ELSE 1 END),
+                    SUM(prompt_tokens), SUM(completion_tokens), SUM(latency_ms)
+             FROM ai_usage
+             WHERE created_at >= ?1 AND created_at < ?2
+             GROUP BY provider, model, feature",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![since, until], |row| {
+            Ok(AiUsageTotal {
+                provider: row.get(0)?,
+                model: row.get(1)?,
+                feature: row.get(2)?,
+                calls: row.get(3)?,
+                failures: row.get(4)?,
+                prompt_tokens: row.get(5)?,
+                completion_tokens: row.get(6)?,
+                latency_ms: row.get(7)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    fn ai_usage_recent(&self, limit: i64) -> Vec<AiUsage> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare(
+            "SELECT id, provider, model, feature, prompt_tokens, completion_tokens, latency_ms, success, error, created_at
+             FROM ai_usage ORDER BY id DESC LIMIT ?1",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![limit], |row| {
+            Ok(AiUsage {
+                id: row.get(0)?,
+                provider: row.get(1)?,
+                model: row.get(2)?,
+                feature: row.get(3)?,
+                prompt_tokens: row.get(4)?,
+                completion_tokens: row.get(5)?,
+                latency_ms: row.get(6)?,
+                success: row.get(7)?,
+                error: row.get(8)?,
+                created_at: row.get(9)?,
+            })
+        })
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    fn ai_usage_cleanup(&self, max_age_days: i64) -> Result<usize, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM ai_usage WHERE created_at < datetime('now', ?1)",
+            params![format!("-{} days", max_age_days)],
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    // ── Upload ownership ────────────────────────────────────────────
+
+    fn media_set_owner(&self, path: &str, user_id: i64) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO media_owners (path, user_id, created_at) VALUES (?1, ?2, datetime('now'))
+             ON CONFLICT(path) DO UPDATE SET user_id = excluded.user_id",
+            params![path, user_id],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn media_list_by_owner(&self, user_id: i64) -> Vec<String> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return vec![],
+        };
+        let mut stmt = match conn.prepare("SELECT path FROM media_owners WHERE user_id = ?1") {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        stmt.query_map(params![user_id], |r| r.get(0))
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    fn media_remove_owner(&self, path: &str) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM media_owners WHERE path = ?1", params![path])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn media_reassign_owner(&self, from: i64, to: i64) -> Result<usize, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE media_owners SET user_id = ?1 WHERE user_id = ?2",
+            params![to, from],
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    // ── Image optimization queue ────────────────────────────────────
+
+    fn media_job_push(&self, path: &str, source: &str) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO media_jobs (path, source, status, attempts, error, created_at)
+             VALUES (?1, ?2, 'pending', 0, '', datetime('now'))
+             ON CONFLICT(path) DO UPDATE SET source = excluded.source, status = 'pending',
+                attempts = 0, error = '', created_at = excluded.created_at",
+            params![path, source],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn media_job_pending(&self, limit: i64) -> Vec<crate::images::queue::MediaJob> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return Vec::new(),
+        };
+        let sql = format!(
+            "SELECT {} FROM media_jobs WHERE status = 'pending' ORDER BY created_at ASC LIMIT ?1",
+            MEDIA_JOB_COLS
+        );
+        let mut stmt = match conn.prepare(&sql) {
+            Ok(s) => s,
+            Err(_) => return Vec::new(),
+        };
+        stmt.query_map(params![limit], row_to_media_job)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    fn media_job_get(&self, path: &str) -> Option<crate::images::queue::MediaJob> {
+        let conn = self.pool.get().ok()?;
+        conn.query_row(
+            &format!("SELECT {} FROM media_jobs WHERE path = ?1", MEDIA_JOB_COLS),
+            params![path],
+            row_to_media_job,
+        )
+        .ok()
+    }
+
+    fn media_job_list(&self) -> Vec<crate::images::queue::MediaJob> {
+        let conn = match self.pool.get() {
+            Ok(c) => c,
+            Err(_) => return Vec::new(),
+        };
+        let sql = format!(
+            "SELECT {} FROM media_jobs ORDER BY created_at DESC",
+            MEDIA_JOB_COLS
+        );
+        let mut stmt = match conn.prepare(&sql) {
+            Ok(s) => s,
+            Err(_) => return Vec::new(),
+        };
+        stmt.query_map([], row_to_media_job)
+            .map(|rows| rows.filter_map(|r| r.ok()).collect())
+            .unwrap_or_default()
+    }
+
+    fn media_job_fail(&self, path: &str, error: &str, give_up: bool) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE media_jobs SET attempts = attempts + 1, error = ?1,
+                status = CASE WHEN ?2 THEN 'failed' ELSE status END
+             WHERE path = ?3",
+            params![error, give_up, path],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn media_job_delete(&self, path: &str) -> Result<(), String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM media_jobs WHERE path = ?1", params![path])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // ── Raw execute ─────────────────────────────────────────────────
+
+    fn raw_execute(&self, sql: &str) -> Result<usize, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.execute(sql, []).map_err(|e| e.to_string())
+    }
+
+    fn raw_query_i64(&self, sql: &str) -> Result<i64, String> {
+        let conn = self.pool.get().map_err(|e| e.to_string())?;
+        conn.query_row(sql, [], |row| row.get(0))
+            .map_err(|e| e.to_string())
+    }
+}
+
+const QUEUE_COLS: &str = "id, to_addr, from_addr, subject, body_text, attempts, max_attempts, \
+     next_retry_at, status, error, error_log, created_at, reply_to";
+
+fn row_to_queued_email(r: &rusqlite::Row) -> rusqlite::Result<crate::mta::queue::QueuedEmail> {
+    Ok(crate::mta::queue::QueuedEmail {
+        id: r.get(0)?,
+        to_addr: r.get(1)?,
+        from_addr: r.get(2)?,
+        subject: r.get(3)?,
+        body_text: r.get(4)?,
+        attempts: r.get(5)?,
+        max_attempts: r.get(6)?,
+        next_retry_at: r.get(7)?,
+        status: r.get(8)?,
+        error: r.get::<_, String>(9).unwrap_or_default(),
+        error_log: r.get::<_, String>(10).unwrap_or_default(),
+        created_at: r.get(11)?,
+        reply_to: r.get::<_, String>(12).unwrap_or_default(),
+    })
+}
+
+const MEDIA_JOB_COLS: &str = "path, source, status, attempts, error, created_at";
+
+fn row_to_media_job(r: &rusqlite::Row) -> rusqlite::Result<crate::images::queue::MediaJob> {
+    Ok(crate::images::queue::MediaJob {
+        path: r.get(0)?,
+        source: r.get(1)?,
+        status: r.get(2)?,
+        attempts: r.get(3)?,
+        error: r.get::<_, String>(4).unwrap_or_default(),
+        created_at: r.get(5)?,
+    })
+}
+
+fn role_from_row(r: &rusqlite::Row) -> rusqlite::Result<Role> {
+    Ok(Role {
+        slug: r.get(0)?,
+        name: r.get(1)?,
+        capabilities: Role::parse_capabilities(&r.get::<_, String>(2)?),
+        created_at: r.get(3)?,
+    })
+}
+
+fn seo_suggestion_from_row(r: &rusqlite::Row) -> rusqlite::Result<SeoSuggestion> {
+    Ok(SeoSuggestion {
+        id: r.get(0)?,
+        content_type: r.get(1)?,
+        content_id: r.get(2)?,
+        field: r.get(3)?,
+        current: r.get(4)?,
+        suggested: r.get(5)?,
+        level: r.get(6)?,
+        reason: r.get(7)?,
+        status: r.get(8)?,
+        created_at: r.get(9)?,
+    })
 }
 
 // ── Bridge: implement Store for DbPool directly ─────────────────────
@@ -2279,6 +4211,22 @@ impl Store for DbPool {
     ) -> Result<(), String> {
         SqliteStore::new(self.clone()).user_update_profile(id, display_name, email, avatar)
     }
+    fn user_update_author_profile(
+        &self,
+        id: i64,
+        author_slug: &str,
+        bio: &str,
+        website: &str,
+        social_links: &str,
+    ) -> Result<(), String> {
+        SqliteStore::new(self.clone()).user_update_author_profile(
+            id,
+            author_slug,
+            bio,
+            website,
+            social_links,
+        )
+    }
     fn user_update_role(&self, id: i64, role: &str) -> Result<(), String> {
         SqliteStore::new(self.clone()).user_update_role(id, role)
     }
@@ -2306,6 +4254,9 @@ impl Store for DbPool {
     fn user_unlock(&self, id: i64) -> Result<(), String> {
         SqliteStore::new(self.clone()).user_unlock(id)
     }
+    fn user_set_status(&self, id: i64, status: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).user_set_status(id, status)
+    }
     fn user_delete(&self, id: i64) -> Result<(), String> {
         SqliteStore::new(self.clone()).user_delete(id)
     }
@@ -2315,6 +4266,12 @@ impl Store for DbPool {
     fn user_set_force_password_change(&self, id: i64, force: bool) -> Result<(), String> {
         SqliteStore::new(self.clone()).user_set_force_password_change(id, force)
     }
+    fn user_set_passwordless(&self, id: i64, passwordless: bool) -> Result<(), String> {
+        SqliteStore::new(self.clone()).user_set_passwordless(id, passwordless)
+    }
+    fn user_set_mfa_method(&self, id: i64, method: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).user_set_mfa_method(id, method)
+    }
     fn post_find_by_id(&self, id: i64) -> Option<crate::models::post::Post> {
         SqliteStore::new(self.clone()).post_find_by_id(id)
     }
@@ -2329,6 +4286,14 @@ impl Store for DbPool {
     ) -> Vec<crate::models::post::Post> {
         SqliteStore::new(self.clone()).post_list(status, limit, offset)
     }
+    fn post_list_since(
+        &self,
+        status: &str,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, crate::models::post::Post)> {
+        SqliteStore::new(self.clone()).post_list_since(status, since_seq, limit)
+    }
     fn post_count(&self, status: Option<&str>) -> i64 {
         SqliteStore::new(self.clone()).post_count(status)
     }
@@ -2380,6 +4345,51 @@ impl Store for DbPool {
     fn post_count_by_tag(&self, tag_id: i64) -> i64 {
         SqliteStore::new(self.clone()).post_count_by_tag(tag_id)
     }
+    fn post_translations(&self, group: &str) -> Vec<Post> {
+        SqliteStore::new(self.clone()).post_translations(group)
+    }
+    fn post_episodes(&self, limit: i64) -> Vec<Post> {
+        SqliteStore::new(self.clone()).post_episodes(limit)
+    }
+    fn post_by_author(
+        &self,
+        user_id: i64,
+        include_unattributed: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<Post> {
+        SqliteStore::new(self.clone()).post_by_author(user_id, include_unattributed, limit, offset)
+    }
+    fn post_count_by_author(&self, user_id: i64, include_unattributed: bool) -> i64 {
+        SqliteStore::new(self.clone()).post_count_by_author(user_id, include_unattributed)
+    }
+    fn post_set_author(&self, id: i64, user_id: Option<i64>) -> Result<(), String> {
+        SqliteStore::new(self.clone()).post_set_author(id, user_id)
+    }
+    fn post_list_by_owner(
+        &self,
+        user_id: i64,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<Post> {
+        SqliteStore::new(self.clone()).post_list_by_owner(user_id, status, limit, offset)
+    }
+    fn post_count_by_owner(&self, user_id: i64, status: Option<&str>) -> i64 {
+        SqliteStore::new(self.clone()).post_count_by_owner(user_id, status)
+    }
+    fn post_list_keyset(
+        &self,
+        owner: Option<i64>,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Post>, Option<Cursor>) {
+        SqliteStore::new(self.clone()).post_list_keyset(owner, status, after, limit)
+    }
+    fn post_reassign_author(&self, from: i64, to: i64) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).post_reassign_author(from, to)
+    }
     fn portfolio_find_by_id(&self, id: i64) -> Option<crate::models::portfolio::PortfolioItem> {
         SqliteStore::new(self.clone()).portfolio_find_by_id(id)
     }
@@ -2458,6 +4468,14 @@ impl Store for DbPool {
     ) -> Vec<crate::models::comment::Comment> {
         SqliteStore::new(self.clone()).comment_list(status, limit, offset)
     }
+    fn comment_list_since(
+        &self,
+        status: Option<&str>,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, crate::models::comment::Comment)> {
+        SqliteStore::new(self.clone()).comment_list_since(status, since_seq, limit)
+    }
     fn comment_for_post(
         &self,
         post_id: i64,
@@ -2468,6 +4486,14 @@ impl Store for DbPool {
     fn comment_count(&self, status: Option<&str>) -> i64 {
         SqliteStore::new(self.clone()).comment_count(status)
     }
+    fn comment_list_keyset(
+        &self,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Comment>, Option<Cursor>) {
+        SqliteStore::new(self.clone()).comment_list_keyset(status, after, limit)
+    }
     fn comment_create(&self, form: &crate::models::comment::CommentForm) -> Result<i64, String> {
         SqliteStore::new(self.clone()).comment_create(form)
     }
@@ -2477,6 +4503,23 @@ impl Store for DbPool {
     fn comment_set_parent(&self, id: i64, parent_id: Option<i64>) -> Result<(), String> {
         SqliteStore::new(self.clone()).comment_set_parent(id, parent_id)
     }
+    fn comment_set_user(&self, id: i64, user_id: i64) -> Result<(), String> {
+        SqliteStore::new(self.clone()).comment_set_user(id, user_id)
+    }
+    fn comment_list_on_author_posts(&self, author_id: i64, limit: i64) -> Vec<Comment> {
+        SqliteStore::new(self.clone()).comment_list_on_author_posts(author_id, limit)
+    }
+    fn comment_list_by_author(&self, user_id: i64, email: &str) -> Vec<Comment> {
+        SqliteStore::new(self.clone()).comment_list_by_author(user_id, email)
+    }
+    fn comment_anonymize_author(
+        &self,
+        user_id: i64,
+        email: &str,
+        name: &str,
+    ) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).comment_anonymize_author(user_id, email, name)
+    }
     fn comment_delete(&self, id: i64) -> Result<(), String> {
         SqliteStore::new(self.clone()).comment_delete(id)
     }
@@ -2605,6 +4648,23 @@ impl Store for DbPool {
     ) -> Result<(), String> {
         SqliteStore::new(self.clone()).design_update_full(id, slug, layout_html, style_css)
     }
+    fn design_update_custom_code(
+        &self,
+        id: i64,
+        custom_css: &str,
+        head_scripts: &str,
+        footer_scripts: &str,
+    ) -> Result<(), String> {
+        SqliteStore::new(self.clone()).design_update_custom_code(
+            id,
+            custom_css,
+            head_scripts,
+            footer_scripts,
+        )
+    }
+    fn design_set_parent(&self, id: i64, parent_id: Option<i64>) -> Result<(), String> {
+        SqliteStore::new(self.clone()).design_set_parent(id, parent_id)
+    }
     fn design_duplicate(&self, id: i64, new_name: &str) -> Result<i64, String> {
         SqliteStore::new(self.clone()).design_duplicate(id, new_name)
     }
@@ -2614,8 +4674,12 @@ impl Store for DbPool {
     fn design_template_for_design(&self, design_id: i64) -> Vec<DesignTemplate> {
         SqliteStore::new(self.clone()).design_template_for_design(design_id)
     }
-    fn design_template_get(&self, design_id: i64, template_type: &str) -> Option<DesignTemplate> {
-        SqliteStore::new(self.clone()).design_template_get(design_id, template_type)
+    fn design_template_get_own(
+        &self,
+        design_id: i64,
+        template_type: &str,
+    ) -> Option<DesignTemplate> {
+        SqliteStore::new(self.clone()).design_template_get_own(design_id, template_type)
     }
     fn design_template_upsert(
         &self,
@@ -2647,6 +4711,31 @@ impl Store for DbPool {
             grapesjs_data,
         )
     }
+    fn design_template_version_list(
+        &self,
+        design_id: i64,
+        template_type: &str,
+    ) -> Vec<DesignTemplateVersion> {
+        SqliteStore::new(self.clone()).design_template_version_list(design_id, template_type)
+    }
+    fn design_template_version_get(&self, id: i64) -> Option<DesignTemplateVersion> {
+        SqliteStore::new(self.clone()).design_template_version_get(id)
+    }
+    fn design_template_version_create(
+        &self,
+        version: &DesignTemplateVersion,
+    ) -> Result<i64, String> {
+        SqliteStore::new(self.clone()).design_template_version_create(version)
+    }
+    fn widget_list(&self, design_id: i64) -> Vec<Widget> {
+        SqliteStore::new(self.clone()).widget_list(design_id)
+    }
+    fn widget_save(&self, widget: &Widget) -> Result<i64, String> {
+        SqliteStore::new(self.clone()).widget_save(widget)
+    }
+    fn widget_delete(&self, id: i64) -> Result<(), String> {
+        SqliteStore::new(self.clone()).widget_delete(id)
+    }
     fn audit_log(
         &self,
         user_id: Option<i64>,
@@ -2693,6 +4782,22 @@ impl Store for DbPool {
     ) -> i64 {
         SqliteStore::new(self.clone()).audit_count(action_filter, entity_filter, user_filter)
     }
+    fn audit_list_keyset(
+        &self,
+        action_filter: Option<&str>,
+        entity_filter: Option<&str>,
+        user_filter: Option<i64>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<AuditEntry>, Option<Cursor>) {
+        SqliteStore::new(self.clone()).audit_list_keyset(
+            action_filter,
+            entity_filter,
+            user_filter,
+            after,
+            limit,
+        )
+    }
     fn audit_distinct_actions(&self) -> Vec<String> {
         SqliteStore::new(self.clone()).audit_distinct_actions()
     }
@@ -2702,6 +4807,9 @@ impl Store for DbPool {
     fn audit_cleanup(&self, max_age_days: i64) -> Result<usize, String> {
         SqliteStore::new(self.clone()).audit_cleanup(max_age_days)
     }
+    fn audit_list_after(&self, after_id: i64, limit: i64) -> Vec<AuditEntry> {
+        SqliteStore::new(self.clone()).audit_list_after(after_id, limit)
+    }
     fn fw_is_banned(&self, ip: &str) -> bool {
         SqliteStore::new(self.clone()).fw_is_banned(ip)
     }
@@ -2729,12 +4837,21 @@ impl Store for DbPool {
         SqliteStore::new(self.clone())
             .fw_ban_create_with_duration(ip, reason, detail, duration, country, user_agent)
     }
+    fn fw_offense_record(&self, ip: &str, window_days: i64) -> i64 {
+        SqliteStore::new(self.clone()).fw_offense_record(ip, window_days)
+    }
+    fn fw_offense_count(&self, ip: &str) -> i64 {
+        SqliteStore::new(self.clone()).fw_offense_count(ip)
+    }
     fn fw_unban(&self, ip: &str) -> Result<usize, String> {
         SqliteStore::new(self.clone()).fw_unban(ip)
     }
     fn fw_unban_by_id(&self, id: i64) -> Result<usize, String> {
         SqliteStore::new(self.clone()).fw_unban_by_id(id)
     }
+    fn fw_unban_all(&self) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).fw_unban_all()
+    }
     fn fw_active_bans(&self, limit: i64, offset: i64) -> Vec<FwBan> {
         SqliteStore::new(self.clone()).fw_active_bans(limit, offset)
     }
@@ -2783,6 +4900,75 @@ impl Store for DbPool {
     fn fw_event_counts_by_type(&self) -> Vec<(String, i64)> {
         SqliteStore::new(self.clone()).fw_event_counts_by_type()
     }
+    fn fw_event_list_after(&self, after_id: i64, limit: i64) -> Vec<FwEvent> {
+        SqliteStore::new(self.clone()).fw_event_list_after(after_id, limit)
+    }
+    fn fw_event_cleanup(&self, max_age_days: i64) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).fw_event_cleanup(max_age_days)
+    }
+    fn fw_rule_list(&self) -> Vec<FwRule> {
+        SqliteStore::new(self.clone()).fw_rule_list()
+    }
+    fn fw_rule_save(&self, rule: &FwRule) -> Result<i64, String> {
+        SqliteStore::new(self.clone()).fw_rule_save(rule)
+    }
+    fn fw_rule_delete(&self, id: i64) -> Result<(), String> {
+        SqliteStore::new(self.clone()).fw_rule_delete(id)
+    }
+    fn fw_rule_hit(&self, id: i64) {
+        SqliteStore::new(self.clone()).fw_rule_hit(id)
+    }
+    fn redirect_list(&self) -> Vec<RedirectRule> {
+        SqliteStore::new(self.clone()).redirect_list()
+    }
+    fn redirect_save(&self, rule: &RedirectRule) -> Result<i64, String> {
+        SqliteStore::new(self.clone()).redirect_save(rule)
+    }
+    fn redirect_delete(&self, id: i64) -> Result<(), String> {
+        SqliteStore::new(self.clone()).redirect_delete(id)
+    }
+    fn redirect_hit(&self, id: i64) {
+        SqliteStore::new(self.clone()).redirect_hit(id)
+    }
+    fn not_found_record(&self, path: &str, referrer: Option<&str>) -> Result<(), String> {
+        SqliteStore::new(self.clone()).not_found_record(path, referrer)
+    }
+    fn not_found_record_batch(&self, hits: &[NotFoundHit]) -> Result<(), String> {
+        SqliteStore::new(self.clone()).not_found_record_batch(hits)
+    }
+    fn not_found_top(&self, limit: i64) -> Vec<NotFoundEntry> {
+        SqliteStore::new(self.clone()).not_found_top(limit)
+    }
+    fn not_found_dismiss(&self, path: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).not_found_dismiss(path)
+    }
+    fn not_found_cleanup(&self, max_age_days: i64) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).not_found_cleanup(max_age_days)
+    }
+    fn api_key_list(&self) -> Vec<ApiKey> {
+        SqliteStore::new(self.clone()).api_key_list()
+    }
+    fn api_key_find_by_hash(&self, key_hash: &str) -> Option<ApiKey> {
+        SqliteStore::new(self.clone()).api_key_find_by_hash(key_hash)
+    }
+    fn api_key_create(&self, key: &ApiKey) -> Result<i64, String> {
+        SqliteStore::new(self.clone()).api_key_create(key)
+    }
+    fn api_key_delete(&self, id: i64) -> Result<(), String> {
+        SqliteStore::new(self.clone()).api_key_delete(id)
+    }
+    fn api_key_touch(&self, id: i64) {
+        SqliteStore::new(self.clone()).api_key_touch(id)
+    }
+    fn ping_log_add(&self, entry: &PingLogEntry) -> Result<i64, String> {
+        SqliteStore::new(self.clone()).ping_log_add(entry)
+    }
+    fn ping_log_recent(&self, limit: i64) -> Vec<PingLogEntry> {
+        SqliteStore::new(self.clone()).ping_log_recent(limit)
+    }
+    fn ping_log_cleanup(&self, max_age_days: i64) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).ping_log_cleanup(max_age_days)
+    }
     fn analytics_record(
         &self,
         path: &str,
@@ -2793,6 +4979,7 @@ impl Store for DbPool {
         user_agent: Option<&str>,
         device_type: Option<&str>,
         browser: Option<&str>,
+        utm: &Utm,
     ) -> Result<(), String> {
         SqliteStore::new(self.clone()).analytics_record(
             path,
@@ -2803,6 +4990,7 @@ impl Store for DbPool {
             user_agent,
             device_type,
             browser,
+            utm,
         )
     }
     fn analytics_overview(&self, from: &str, to: &str) -> crate::models::analytics::OverviewStats {
@@ -2848,9 +5036,94 @@ impl Store for DbPool {
     ) -> Vec<crate::models::analytics::CountEntry> {
         SqliteStore::new(self.clone()).analytics_top_referrers(from, to, limit)
     }
+    fn analytics_top_paths(
+        &self,
+        prefix: &str,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Vec<crate::models::analytics::CountEntry> {
+        SqliteStore::new(self.clone()).analytics_top_paths(prefix, from, to, limit)
+    }
     fn analytics_tag_relations(&self) -> Vec<crate::models::analytics::TagRelation> {
         SqliteStore::new(self.clone()).analytics_tag_relations()
     }
+    fn analytics_campaigns(&self, from: &str, to: &str, limit: i64) -> Vec<CampaignEntry> {
+        SqliteStore::new(self.clone()).analytics_campaigns(from, to, limit)
+    }
+    fn analytics_utm_breakdown(
+        &self,
+        dimension: &str,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Vec<CountEntry> {
+        SqliteStore::new(self.clone()).analytics_utm_breakdown(dimension, from, to, limit)
+    }
+    fn analytics_path_stats(&self, path: &str, from: &str, to: &str) -> PathStats {
+        SqliteStore::new(self.clone()).analytics_path_stats(path, from, to)
+    }
+    fn analytics_path_breakdown(
+        &self,
+        path: &str,
+        dimension: &str,
+        from: &str,
+        to: &str,
+        limit: i64,
+    ) -> Vec<CountEntry> {
+        SqliteStore::new(self.clone()).analytics_path_breakdown(path, dimension, from, to, limit)
+    }
+    fn goal_record(&self, goal: &str, ip_hash: &str, path: &str) -> Result<bool, String> {
+        SqliteStore::new(self.clone()).goal_record(goal, ip_hash, path)
+    }
+    fn goal_stats(&self, from: &str, to: &str) -> Vec<GoalStat> {
+        SqliteStore::new(self.clone()).goal_stats(from, to)
+    }
+    fn goal_daily(&self, goal: &str, from: &str, to: &str) -> Vec<DailyCount> {
+        SqliteStore::new(self.clone()).goal_daily(goal, from, to)
+    }
+    fn outbound_click_record(&self, url: &str, ip_hash: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).outbound_click_record(url, ip_hash)
+    }
+    fn outbound_top_links(&self, from: &str, to: &str, limit: i64) -> Vec<OutboundEntry> {
+        SqliteStore::new(self.clone()).outbound_top_links(from, to, limit)
+    }
+    fn engagement_record(
+        &self,
+        path: &str,
+        ip_hash: &str,
+        scroll: i64,
+        seconds: i64,
+    ) -> Result<(), String> {
+        SqliteStore::new(self.clone()).engagement_record(path, ip_hash, scroll, seconds)
+    }
+    fn engagement_path_stats(&self, path: &str, from: &str, to: &str) -> EngagementStats {
+        SqliteStore::new(self.clone()).engagement_path_stats(path, from, to)
+    }
+    fn search_log_record(&self, query: &str, results: i64, ip_hash: &str) -> Result<i64, String> {
+        SqliteStore::new(self.clone()).search_log_record(query, results, ip_hash)
+    }
+    fn search_log_click(&self, id: i64, ip_hash: &str, path: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).search_log_click(id, ip_hash, path)
+    }
+    fn search_top_queries(&self, from: &str, to: &str, limit: i64) -> Vec<SearchQueryStat> {
+        SqliteStore::new(self.clone()).search_top_queries(from, to, limit)
+    }
+    fn search_zero_result_queries(&self, from: &str, to: &str, limit: i64) -> Vec<CountEntry> {
+        SqliteStore::new(self.clone()).search_zero_result_queries(from, to, limit)
+    }
+    fn experiment_event_record(
+        &self,
+        experiment: &str,
+        variant: &str,
+        kind: &str,
+        ip_hash: &str,
+    ) -> Result<(), String> {
+        SqliteStore::new(self.clone()).experiment_event_record(experiment, variant, kind, ip_hash)
+    }
+    fn experiment_stats(&self, from: &str, to: &str) -> Vec<VariantStat> {
+        SqliteStore::new(self.clone()).experiment_stats(from, to)
+    }
     fn order_find_by_id(&self, id: i64) -> Option<Order> {
         SqliteStore::new(self.clone()).order_find_by_id(id)
     }
@@ -2866,6 +5139,14 @@ impl Store for DbPool {
     fn order_list_by_status(&self, status: &str, limit: i64, offset: i64) -> Vec<Order> {
         SqliteStore::new(self.clone()).order_list_by_status(status, limit, offset)
     }
+    fn order_list_since(
+        &self,
+        status: &str,
+        since_seq: Option<i64>,
+        limit: i64,
+    ) -> Vec<(i64, Order)> {
+        SqliteStore::new(self.clone()).order_list_since(status, since_seq, limit)
+    }
     fn order_list_by_email(&self, email: &str, limit: i64, offset: i64) -> Vec<Order> {
         SqliteStore::new(self.clone()).order_list_by_email(email, limit, offset)
     }
@@ -2878,6 +5159,14 @@ impl Store for DbPool {
     fn order_count_by_status(&self, status: &str) -> i64 {
         SqliteStore::new(self.clone()).order_count_by_status(status)
     }
+    fn order_list_keyset(
+        &self,
+        status: Option<&str>,
+        after: Option<&Cursor>,
+        limit: i64,
+    ) -> (Vec<Order>, Option<Cursor>) {
+        SqliteStore::new(self.clone()).order_list_keyset(status, after, limit)
+    }
     fn order_total_revenue(&self) -> f64 {
         SqliteStore::new(self.clone()).order_total_revenue()
     }
@@ -3042,6 +5331,15 @@ impl Store for DbPool {
             log,
         )
     }
+    fn import_find_by_id(&self, id: i64) -> Option<Import> {
+        SqliteStore::new(self.clone()).import_find_by_id(id)
+    }
+    fn import_set_created(&self, id: i64, created: &ImportCreated) -> Result<(), String> {
+        SqliteStore::new(self.clone()).import_set_created(id, created)
+    }
+    fn import_mark_rolled_back(&self, id: i64) -> Result<(), String> {
+        SqliteStore::new(self.clone()).import_mark_rolled_back(id)
+    }
     fn search_create_fts_table(&self) -> Result<(), String> {
         SqliteStore::new(self.clone()).search_create_fts_table()
     }
@@ -3088,6 +5386,9 @@ impl Store for DbPool {
     fn session_delete_for_user(&self, user_id: i64) -> Result<(), String> {
         SqliteStore::new(self.clone()).session_delete_for_user(user_id)
     }
+    fn session_list_for_user(&self, user_id: i64) -> Vec<serde_json::Value> {
+        SqliteStore::new(self.clone()).session_list_for_user(user_id)
+    }
     fn session_create_full(
         &self,
         user_id: i64,
@@ -3105,6 +5406,12 @@ impl Store for DbPool {
     fn session_count_recent_by_ip(&self, ip_hash: &str, minutes: i64) -> i64 {
         SqliteStore::new(self.clone()).session_count_recent_by_ip(ip_hash, minutes)
     }
+    fn session_set_sudo(&self, token: &str, minutes: i64) -> Result<(), String> {
+        SqliteStore::new(self.clone()).session_set_sudo(token, minutes)
+    }
+    fn session_sudo_active(&self, token: &str) -> bool {
+        SqliteStore::new(self.clone()).session_sudo_active(token)
+    }
     fn magic_link_create(
         &self,
         token: &str,
@@ -3128,6 +5435,9 @@ impl Store for DbPool {
     fn analytics_prune(&self, before_date: &str) -> Result<usize, String> {
         SqliteStore::new(self.clone()).analytics_prune(before_date)
     }
+    fn analytics_forget_visitors(&self, ip_hashes: &[String]) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).analytics_forget_visitors(ip_hashes)
+    }
     fn analytics_count(&self) -> i64 {
         SqliteStore::new(self.clone()).analytics_count()
     }
@@ -3173,8 +5483,9 @@ impl Store for DbPool {
         from: &str,
         subject: &str,
         body: &str,
+        reply_to: &str,
     ) -> Result<i64, String> {
-        SqliteStore::new(self.clone()).mta_queue_push(to, from, subject, body)
+        SqliteStore::new(self.clone()).mta_queue_push(to, from, subject, body, reply_to)
     }
     fn mta_queue_pending(&self, limit: i64) -> Vec<crate::mta::queue::QueuedEmail> {
         SqliteStore::new(self.clone()).mta_queue_pending(limit)
@@ -3197,6 +5508,249 @@ impl Store for DbPool {
     fn mta_queue_cleanup(&self, days: u64) -> Result<u64, String> {
         SqliteStore::new(self.clone()).mta_queue_cleanup(days)
     }
+    fn mta_queue_list(
+        &self,
+        status: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Vec<crate::mta::queue::QueuedEmail> {
+        SqliteStore::new(self.clone()).mta_queue_list(status, limit, offset)
+    }
+    fn mta_queue_count(&self, status: Option<&str>) -> u64 {
+        SqliteStore::new(self.clone()).mta_queue_count(status)
+    }
+    fn mta_queue_get(&self, id: i64) -> Option<crate::mta::queue::QueuedEmail> {
+        SqliteStore::new(self.clone()).mta_queue_get(id)
+    }
+    fn mta_queue_retry(&self, id: i64) -> Result<(), String> {
+        SqliteStore::new(self.clone()).mta_queue_retry(id)
+    }
+    fn mta_queue_delete(&self, id: i64) -> Result<(), String> {
+        SqliteStore::new(self.clone()).mta_queue_delete(id)
+    }
+    fn mta_queue_sent_hourly(&self, hours: i64) -> Vec<(String, u64)> {
+        SqliteStore::new(self.clone()).mta_queue_sent_hourly(hours)
+    }
+    fn mta_reply_token_create(
+        &self,
+        token: &str,
+        post_id: i64,
+        content_type: &str,
+        comment_id: i64,
+        email: &str,
+        expires_days: i64,
+    ) -> Result<(), String> {
+        SqliteStore::new(self.clone()).mta_reply_token_create(
+            token,
+            post_id,
+            content_type,
+            comment_id,
+            email,
+            expires_days,
+        )
+    }
+    fn mta_reply_token_find(&self, token: &str) -> Option<crate::mta::inbound::ReplyToken> {
+        SqliteStore::new(self.clone()).mta_reply_token_find(token)
+    }
+    fn mta_reply_token_consume(&self, token: &str) -> Result<bool, String> {
+        SqliteStore::new(self.clone()).mta_reply_token_consume(token)
+    }
+    fn mta_reply_token_cleanup(&self) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).mta_reply_token_cleanup()
+    }
+    fn email_quota_record(&self, provider: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).email_quota_record(provider)
+    }
+    fn email_quota_usage(&self, provider: &str) -> (u64, u64) {
+        SqliteStore::new(self.clone()).email_quota_usage(provider)
+    }
+    fn email_quota_cleanup(&self, days: i64) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).email_quota_cleanup(days)
+    }
+    fn signed_token_create(
+        &self,
+        token_hash: &str,
+        purpose: &str,
+        email: &str,
+        payload: &str,
+        expires_minutes: i64,
+    ) -> Result<(), String> {
+        SqliteStore::new(self.clone()).signed_token_create(
+            token_hash,
+            purpose,
+            email,
+            payload,
+            expires_minutes,
+        )
+    }
+    fn signed_token_consume(
+        &self,
+        token_hash: &str,
+        purpose: &str,
+    ) -> Result<crate::security::signed_token::SignedToken, String> {
+        SqliteStore::new(self.clone()).signed_token_consume(token_hash, purpose)
+    }
+    fn signed_token_cleanup(&self) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).signed_token_cleanup()
+    }
+    fn newsletter_subscribe(&self, email: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).newsletter_subscribe(email)
+    }
+    fn newsletter_confirm(&self, email: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).newsletter_confirm(email)
+    }
+    fn newsletter_status(&self, email: &str) -> Option<String> {
+        SqliteStore::new(self.clone()).newsletter_status(email)
+    }
+    fn newsletter_count(&self, status: Option<&str>) -> i64 {
+        SqliteStore::new(self.clone()).newsletter_count(status)
+    }
+    fn comment_subscription_create(
+        &self,
+        post_id: i64,
+        content_type: &str,
+        email: &str,
+    ) -> Result<(), String> {
+        SqliteStore::new(self.clone()).comment_subscription_create(post_id, content_type, email)
+    }
+    fn comment_subscription_confirm(
+        &self,
+        post_id: i64,
+        content_type: &str,
+        email: &str,
+    ) -> Result<(), String> {
+        SqliteStore::new(self.clone()).comment_subscription_confirm(post_id, content_type, email)
+    }
+    fn comment_subscribers(&self, post_id: i64, content_type: &str) -> Vec<String> {
+        SqliteStore::new(self.clone()).comment_subscribers(post_id, content_type)
+    }
+    fn comment_subscription_list_by_email(&self, email: &str) -> Vec<(i64, String)> {
+        SqliteStore::new(self.clone()).comment_subscription_list_by_email(email)
+    }
+    fn subscriber_forget(&self, email: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).subscriber_forget(email)
+    }
+    fn role_list(&self) -> Vec<Role> {
+        SqliteStore::new(self.clone()).role_list()
+    }
+    fn role_find(&self, slug: &str) -> Option<Role> {
+        SqliteStore::new(self.clone()).role_find(slug)
+    }
+    fn role_save(&self, slug: &str, name: &str, capabilities: &[String]) -> Result<(), String> {
+        SqliteStore::new(self.clone()).role_save(slug, name, capabilities)
+    }
+    fn role_delete(&self, slug: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).role_delete(slug)
+    }
+    fn login_device_touch(
+        &self,
+        user_id: i64,
+        ip_hash: &str,
+        device: &str,
+        location: &str,
+    ) -> Result<bool, String> {
+        SqliteStore::new(self.clone()).login_device_touch(user_id, ip_hash, device, location)
+    }
+    fn login_device_count(&self, user_id: i64) -> i64 {
+        SqliteStore::new(self.clone()).login_device_count(user_id)
+    }
+    fn login_device_forget(&self, user_id: i64) -> Result<(), String> {
+        SqliteStore::new(self.clone()).login_device_forget(user_id)
+    }
+    fn login_device_list(&self, user_id: i64) -> Vec<serde_json::Value> {
+        SqliteStore::new(self.clone()).login_device_list(user_id)
+    }
+    fn rate_limit_hit(
+        &self,
+        key: &str,
+        max_attempts: u64,
+        window_secs: i64,
+    ) -> Result<bool, String> {
+        SqliteStore::new(self.clone()).rate_limit_hit(key, max_attempts, window_secs)
+    }
+    fn rate_limit_budget(
+        &self,
+        key: &str,
+        max_requests: u64,
+        window_secs: i64,
+    ) -> Result<crate::rate_limit::Budget, String> {
+        SqliteStore::new(self.clone()).rate_limit_budget(key, max_requests, window_secs)
+    }
+    fn rate_limit_cleanup(&self, max_age_secs: i64) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).rate_limit_cleanup(max_age_secs)
+    }
+    fn embedding_upsert(&self, embedding: &Embedding) -> Result<(), String> {
+        SqliteStore::new(self.clone()).embedding_upsert(embedding)
+    }
+    fn embedding_remove(&self, item_type: &str, item_id: i64) {
+        SqliteStore::new(self.clone()).embedding_remove(item_type, item_id)
+    }
+    fn embedding_list(&self, model: &str) -> Vec<Embedding> {
+        SqliteStore::new(self.clone()).embedding_list(model)
+    }
+    fn embedding_prune(&self, keep_model: &str) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).embedding_prune(keep_model)
+    }
+    fn seo_suggestion_create(&self, suggestion: &SeoSuggestion) -> Result<i64, String> {
+        SqliteStore::new(self.clone()).seo_suggestion_create(suggestion)
+    }
+    fn seo_suggestion_get(&self, id: i64) -> Option<SeoSuggestion> {
+        SqliteStore::new(self.clone()).seo_suggestion_get(id)
+    }
+    fn seo_suggestion_pending(&self, content: Option<(&str, i64)>) -> Vec<SeoSuggestion> {
+        SqliteStore::new(self.clone()).seo_suggestion_pending(content)
+    }
+    fn seo_suggestion_set_status(&self, id: i64, status: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).seo_suggestion_set_status(id, status)
+    }
+    fn seo_suggestion_exists(&self, content_type: &str, content_id: i64, field: &str) -> bool {
+        SqliteStore::new(self.clone()).seo_suggestion_exists(content_type, content_id, field)
+    }
+    fn seo_suggestion_clear(&self, content_type: &str, content_id: i64) -> Result<(), String> {
+        SqliteStore::new(self.clone()).seo_suggestion_clear(content_type, content_id)
+    }
+    fn ai_usage_record(&self, usage: &AiUsage) -> Result<(), String> {
+        SqliteStore::new(self.clone()).ai_usage_record(usage)
+    }
+    fn ai_usage_totals(&self, since: &str, until: &str) -> Vec<AiUsageTotal> {
+        SqliteStore::new(self.clone()).ai_usage_totals(since, until)
+    }
+    fn ai_usage_recent(&self, limit: i64) -> Vec<AiUsage> {
+        SqliteStore::new(self.clone()).ai_usage_recent(limit)
+    }
+    fn ai_usage_cleanup(&self, max_age_days: i64) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).ai_usage_cleanup(max_age_days)
+    }
+    fn media_set_owner(&self, path: &str, user_id: i64) -> Result<(), String> {
+        SqliteStore::new(self.clone()).media_set_owner(path, user_id)
+    }
+    fn media_list_by_owner(&self, user_id: i64) -> Vec<String> {
+        SqliteStore::new(self.clone()).media_list_by_owner(user_id)
+    }
+    fn media_remove_owner(&self, path: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).media_remove_owner(path)
+    }
+    fn media_reassign_owner(&self, from: i64, to: i64) -> Result<usize, String> {
+        SqliteStore::new(self.clone()).media_reassign_owner(from, to)
+    }
+    fn media_job_push(&self, path: &str, source: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).media_job_push(path, source)
+    }
+    fn media_job_pending(&self, limit: i64) -> Vec<crate::images::queue::MediaJob> {
+        SqliteStore::new(self.clone()).media_job_pending(limit)
+    }
+    fn media_job_get(&self, path: &str) -> Option<crate::images::queue::MediaJob> {
+        SqliteStore::new(self.clone()).media_job_get(path)
+    }
+    fn media_job_list(&self) -> Vec<crate::images::queue::MediaJob> {
+        SqliteStore::new(self.clone()).media_job_list()
+    }
+    fn media_job_fail(&self, path: &str, error: &str, give_up: bool) -> Result<(), String> {
+        SqliteStore::new(self.clone()).media_job_fail(path, error, give_up)
+    }
+    fn media_job_delete(&self, path: &str) -> Result<(), String> {
+        SqliteStore::new(self.clone()).media_job_delete(path)
+    }
     fn raw_execute(&self, sql: &str) -> Result<usize, String> {
         SqliteStore::new(self.clone()).raw_execute(sql)
     }
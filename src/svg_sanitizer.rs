@@ -126,6 +126,24 @@ pub fn sanitize_svg(input: &[u8]) -> Option<Vec<u8>> {
     Some(writer.into_inner().into_inner())
 }
 
+/// If the value is an SVG data URL, decode → sanitize → re-encode.
+/// Returns Some(sanitized_data_url) if it was an SVG, None otherwise (leave as-is).
+pub fn sanitize_data_url(value: &str) -> Option<String> {
+    use base64::Engine;
+    let engine = base64::engine::general_purpose::STANDARD;
+
+    // Check for base64-encoded SVG data URL
+    if let Some(b64) = value.strip_prefix("data:image/svg+xml;base64,") {
+        if let Ok(raw) = engine.decode(b64.trim()) {
+            if let Some(clean) = sanitize_svg(&raw) {
+                let encoded = engine.encode(&clean);
+                return Some(format!("data:image/svg+xml;base64,{}", encoded));
+            }
+        }
+    }
+    None
+}
+
 fn is_dangerous_element(tag: &str) -> bool {
     DANGEROUS_ELEMENTS.contains(&tag)
 }
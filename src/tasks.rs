@@ -55,6 +55,23 @@ impl Fairing for BackgroundTasks {
                     }
                     Err(e) => log::error!("[task] Magic link cleanup failed: {}", e),
                 }
+                match s.signed_token_cleanup() {
+                    Ok(count) => {
+                        if count > 0 {
+                            log::info!("[task] Cleaned up {} expired signed tokens", count);
+                        }
+                    }
+                    Err(e) => log::error!("[task] Signed token cleanup failed: {}", e),
+                }
+                // Rate limit windows are at most minutes long; a day is ample
+                match s.rate_limit_cleanup(24 * 3600) {
+                    Ok(count) => {
+                        if count > 0 {
+                            log::info!("[task] Cleaned up {} old rate limit entries", count);
+                        }
+                    }
+                    Err(e) => log::error!("[task] Rate limit cleanup failed: {}", e),
+                }
             }
         });
 
@@ -68,6 +85,14 @@ impl Fairing for BackgroundTasks {
                     Ok(count) => {
                         if count > 0 {
                             log::info!("[task] Published {} scheduled items", count);
+                            // No per-item URLs here, so just the sitemap ping
+                            if crate::seo::ping::enabled(&*s) {
+                                let st = Arc::clone(&s);
+                                let _ = tokio::task::spawn_blocking(move || {
+                                    crate::seo::ping::run(&*st, &[])
+                                })
+                                .await;
+                            }
                         }
                     }
                     Err(e) => log::error!("[task] Scheduled publish failed: {}", e),
@@ -75,6 +100,29 @@ impl Fairing for BackgroundTasks {
             }
         });
 
+        // 404 log flush: the catcher only counts misses in memory
+        if let Some(buffer) = rocket.state::<Arc<crate::models::redirect::NotFoundBuffer>>() {
+            let s = Arc::clone(&store);
+            let buffer = Arc::clone(buffer);
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    let hits = buffer.take();
+                    if hits.is_empty() {
+                        continue;
+                    }
+                    let st = Arc::clone(&s);
+                    match tokio::task::spawn_blocking(move || st.not_found_record_batch(&hits))
+                        .await
+                    {
+                        Ok(Err(e)) => log::error!("[task] 404 log flush failed: {}", e),
+                        Err(e) => log::error!("[task] 404 log flush panicked: {}", e),
+                        Ok(Ok(())) => {}
+                    }
+                }
+            });
+        }
+
         // Analytics cleanup task
         let s = Arc::clone(&store);
         tokio::spawn(async move {
@@ -90,6 +138,49 @@ impl Fairing for BackgroundTasks {
                     }
                     Err(e) => log::error!("[task] Analytics cleanup failed: {}", e),
                 }
+                match s.not_found_cleanup(max_age) {
+                    Ok(count) => {
+                        if count > 0 {
+                            log::info!("[task] Cleaned up {} stale 404 log entries", count);
+                        }
+                    }
+                    Err(e) => log::error!("[task] 404 log cleanup failed: {}", e),
+                }
+                match s.ping_log_cleanup(max_age) {
+                    Ok(count) => {
+                        if count > 0 {
+                            log::info!("[task] Cleaned up {} old search engine pings", count);
+                        }
+                    }
+                    Err(e) => log::error!("[task] Ping log cleanup failed: {}", e),
+                }
+                // The analytics retention period from Settings › Visitors, if set
+                let retention = get_setting_i64(&*s, "analytics_retention_days", 0);
+                if retention > 0 {
+                    let before = (chrono::Utc::now() - chrono::Duration::days(retention))
+                        .format("%Y-%m-%d")
+                        .to_string();
+                    match s.analytics_prune(&before) {
+                        Ok(count) => {
+                            if count > 0 {
+                                log::info!(
+                                    "[task] Pruned {} page views past the retention period",
+                                    count
+                                );
+                            }
+                        }
+                        Err(e) => log::error!("[task] Analytics retention prune failed: {}", e),
+                    }
+                }
+                let max_age = get_setting_i64(&*s, "task_ai_usage_max_age_days", 365);
+                match s.ai_usage_cleanup(max_age) {
+                    Ok(count) => {
+                        if count > 0 {
+                            log::info!("[task] Cleaned up {} old AI usage records", count);
+                        }
+                    }
+                    Err(e) => log::error!("[task] AI usage cleanup failed: {}", e),
+                }
             }
         });
 
@@ -108,6 +199,124 @@ impl Fairing for BackgroundTasks {
                     }
                     Err(e) => log::error!("[task] Audit log cleanup failed: {}", e),
                 }
+                let max_age = get_setting_i64(&*s, "task_fw_events_max_age_days", 90);
+                match s.fw_event_cleanup(max_age) {
+                    Ok(count) => {
+                        if count > 0 {
+                            log::info!("[task] Cleaned up {} old firewall events", count);
+                        }
+                    }
+                    Err(e) => log::error!("[task] Firewall event cleanup failed: {}", e),
+                }
+            }
+        });
+
+        // SIEM forwarding (audit log + firewall events)
+        let s = Arc::clone(&store);
+        tokio::spawn(async move {
+            loop {
+                let interval = get_interval(&*s, "siem_interval_seconds", 15);
+                tokio::time::sleep(Duration::from_secs(interval)).await;
+                let st = Arc::clone(&s);
+                match tokio::task::spawn_blocking(move || {
+                    crate::security::siem::forward_pending(&*st)
+                })
+                .await
+                {
+                    Ok(Ok(count)) => {
+                        if count > 0 {
+                            log::debug!("[task] Forwarded {} events to SIEM", count);
+                        }
+                    }
+                    Ok(Err(e)) => log::warn!("[task] SIEM forwarding failed: {}", e),
+                    Err(e) => log::error!("[task] SIEM forwarding task panicked: {}", e),
+                }
+            }
+        });
+
+        // AI embeddings for semantic search and related content
+        let s = Arc::clone(&store);
+        tokio::spawn(async move {
+            loop {
+                let interval = get_interval(&*s, "task_embeddings_interval", 15);
+                tokio::time::sleep(Duration::from_secs(interval * 60)).await;
+                if !s.setting_get_bool("ai_embeddings_enabled") {
+                    continue;
+                }
+                let st = Arc::clone(&s);
+                match tokio::task::spawn_blocking(move || crate::ai::embeddings::sync(&*st)).await {
+                    Ok(Ok(count)) => {
+                        if count > 0 {
+                            log::info!("[task] Embedded {} posts and portfolio items", count);
+                        }
+                    }
+                    Ok(Err(e)) => log::warn!("[task] Embedding sync failed: {}", e),
+                    Err(e) => log::error!("[task] Embedding sync task panicked: {}", e),
+                }
+            }
+        });
+
+        // Reload the GeoIP database when the file changes on disk
+        let s = Arc::clone(&store);
+        tokio::spawn(async move {
+            loop {
+                let interval = get_interval(&*s, "task_geoip_reload_interval", 60);
+                tokio::time::sleep(Duration::from_secs(interval * 60)).await;
+                if s.setting_get_or("geoip_db_path", "").is_empty() {
+                    continue;
+                }
+                let st = Arc::clone(&s);
+                match tokio::task::spawn_blocking(move || crate::geoip::reload(&*st)).await {
+                    Ok(Ok(true)) => log::info!("[task] Reloaded the GeoIP database"),
+                    Ok(Ok(false)) => {}
+                    Ok(Err(e)) => log::warn!("[task] GeoIP reload failed: {}", e),
+                    Err(e) => log::error!("[task] GeoIP reload task panicked: {}", e),
+                }
+            }
+        });
+
+        // AI captions for portfolio images, queued for review in the SEO audit
+        let s = Arc::clone(&store);
+        tokio::spawn(async move {
+            loop {
+                let interval = get_interval(&*s, "task_vision_captions_interval", 60);
+                tokio::time::sleep(Duration::from_secs(interval * 60)).await;
+                if !s.setting_get_bool("ai_vision_captions_enabled") {
+                    continue;
+                }
+                let st = Arc::clone(&s);
+                match tokio::task::spawn_blocking(move || crate::ai::captions::run_batch(&*st))
+                    .await
+                {
+                    Ok(Ok(count)) => {
+                        if count > 0 {
+                            log::info!("[task] Queued captions for {} portfolio items", count);
+                        }
+                    }
+                    Ok(Err(e)) => log::warn!("[task] Vision captioning failed: {}", e),
+                    Err(e) => log::error!("[task] Vision captioning task panicked: {}", e),
+                }
+            }
+        });
+
+        // Image optimization queue (resize / re-encode / WebP for new uploads)
+        let s = Arc::clone(&store);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                let st = Arc::clone(&s);
+                match tokio::task::spawn_blocking(move || {
+                    crate::images::queue::process_pending(&*st)
+                })
+                .await
+                {
+                    Ok(count) => {
+                        if count > 0 {
+                            log::info!("[task] Optimized {} uploaded images", count);
+                        }
+                    }
+                    Err(e) => log::error!("[task] Image optimization task panicked: {}", e),
+                }
             }
         });
 
@@ -124,9 +333,85 @@ impl Fairing for BackgroundTasks {
                 crate::mta::process_queue(&*s);
                 // Cleanup old queue entries (keep 30 days)
                 let _ = s.mta_queue_cleanup(30);
+                let _ = s.mta_reply_token_cleanup();
+                // Drop provider quota counters older than a week
+                let _ = s.email_quota_cleanup(7);
             }
         });
 
+        // Encrypted off-site backups
+        let s = Arc::clone(&store);
+        tokio::spawn(async move {
+            loop {
+                let interval = get_interval(&*s, "task_backup_interval", 1440);
+                tokio::time::sleep(Duration::from_secs(interval * 60)).await;
+                if !s.setting_get_bool("backup_enabled") {
+                    continue;
+                }
+                let st = Arc::clone(&s);
+                match tokio::task::spawn_blocking(move || crate::backup::run(&*st)).await {
+                    Ok(Ok(count)) => {
+                        if count > 0 {
+                            log::info!("[task] Backup uploaded to {} destinations", count);
+                        }
+                    }
+                    Ok(Err(e)) => log::error!("[task] Backup failed: {}", e),
+                    Err(e) => log::error!("[task] Backup task panicked: {}", e),
+                }
+            }
+        });
+
+        // Weekly admin digest (checked hourly)
+        let s = Arc::clone(&store);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs(60 * 60)).await;
+                let now = chrono::Utc::now().naive_utc();
+                if !crate::email::digest::is_due(&s.setting_all(), now) {
+                    continue;
+                }
+                let st = Arc::clone(&s);
+                match tokio::task::spawn_blocking(move || crate::email::digest::send(&*st, now))
+                    .await
+                {
+                    Ok(Ok(())) => log::info!("[task] Weekly digest sent"),
+                    Ok(Err(e)) => log::error!("[task] Weekly digest failed: {}", e),
+                    Err(e) => log::error!("[task] Weekly digest task panicked: {}", e),
+                }
+            }
+        });
+
+        // Monthly analytics report (checked hourly)
+        let s = Arc::clone(&store);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs(60 * 60)).await;
+                let now = chrono::Utc::now().naive_utc();
+                if !crate::email::report::is_due(&s.setting_all(), now) {
+                    continue;
+                }
+                let st = Arc::clone(&s);
+                match tokio::task::spawn_blocking(move || crate::email::report::send(&*st, now))
+                    .await
+                {
+                    Ok(Ok(())) => log::info!("[task] Monthly analytics report sent"),
+                    Ok(Err(e)) => log::error!("[task] Monthly analytics report failed: {}", e),
+                    Err(e) => log::error!("[task] Monthly analytics report task panicked: {}", e),
+                }
+            }
+        });
+
+        // Inbound SMTP listener for comment replies (built-in MTA)
+        if store.setting_get_bool("mta_inbound_enabled") {
+            let port = get_setting_i64(&*store, "mta_inbound_port", 2525) as u16;
+            let bind = store.setting_get_or("mta_inbound_bind", "0.0.0.0");
+            tokio::spawn(crate::mta::inbound::run_listener(
+                Arc::clone(&store),
+                bind,
+                port,
+            ));
+        }
+
         log::info!("[task] Background tasks started");
     }
 }
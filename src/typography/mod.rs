@@ -1,3 +1,5 @@
+pub mod self_host;
+
 use serde_json::Value;
 
 fn html_escape(s: &str) -> String {
@@ -72,7 +74,7 @@ pub fn build_css_variables(settings: &Value) -> String {
     let text_direction = get("font_text_direction", "ltr");
     let text_alignment = get("font_text_alignment", "left");
 
-    format!(
+    let root = format!(
         r#":root {{
     --font-primary: '{font_primary}', sans-serif;
     --font-heading: '{font_headings}', sans-serif;
@@ -235,78 +237,170 @@ pub fn build_css_variables(settings: &Value) -> String {
         lb_title_color = get("portfolio_lightbox_title_color", "#FFFFFF"),
         lb_tag_color = get("portfolio_lightbox_tag_color", "#AAAAAA"),
         lb_nav_color = get("portfolio_lightbox_nav_color", "#FFFFFF"),
-    )
+    );
+    format!("{}{}", root, build_dark_mode_css(settings))
 }
 
-/// Build the font loading HTML tags (Google Fonts, Adobe Fonts, custom @font-face).
-pub fn build_font_links(settings: &Value) -> String {
-    let get = |key: &str| -> &str { settings.get(key).and_then(|v| v.as_str()).unwrap_or("") };
+/// Surfaces the bundled designs paint light, repainted for the dark palette
+/// as (selectors, declarations).
+const DARK_SURFACE_RULES: &[(&str, &str)] = &[
+    (
+        ".grid-item.border-polaroid, .bed-row, .bed-content, .bes-card, .bgrid-card, .bgs-single::before",
+        "background: var(--color-surface);",
+    ),
+    (
+        ".bcl-thumb-placeholder, .bgrid-thumb-placeholder, .bed-image-placeholder",
+        "background: rgba(255,255,255,.06); color: var(--color-text-secondary);",
+    ),
+    (".bgrid-excerpt", "color: var(--color-text-secondary);"),
+    (
+        "#back-to-top, #color-scheme-toggle",
+        "background: var(--color-surface) !important; border-color: var(--color-border) !important; color: var(--color-text);",
+    ),
+];
 
-    let google_enabled = get("font_google_enabled") == "true";
-    let adobe_enabled = get("font_adobe_enabled") == "true";
-    let font_primary = get("font_primary");
-    let font_heading = get("font_heading");
-    let sitewide = get("font_sitewide") != "false";
-
-    let mut html = String::new();
+/// The dark palette, for `design_dark_mode` "auto" (follows the visitor's
+/// `prefers-color-scheme`) or "always". Either way a `data-color-scheme`
+/// attribute on `<html>`, set by the visitor toggle, wins.
+pub fn build_dark_mode_css(settings: &Value) -> String {
+    let get = |key: &str, default: &str| -> String {
+        settings
+            .get(key)
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty())
+            .unwrap_or(default)
+            .to_string()
+    };
+    let mode = get("design_dark_mode", "off");
+    if mode != "auto" && mode != "always" {
+        return String::new();
+    }
 
-    // Collect all Google Font families that need loading
-    if google_enabled {
-        let system_fonts = ["system-ui", "Georgia, serif", "ui-monospace, monospace", ""];
-        let mut families: Vec<String> = Vec::new();
+    let text = get("color_dark_text", "#e5e7eb");
+    let secondary = get("color_dark_text_secondary", "#9ca3af");
+    let heading = get("color_dark_heading", "#f9fafb");
+    let vars = format!(
+        "color-scheme: dark; --color-text: {text}; --color-text-secondary: {secondary}; --color-bg: {bg}; --color-surface: {surface}; --color-accent: {accent}; --color-link: {link}; --color-link-hover: {link_hover}; --color-border: {border}; --color-logo-text: {heading}; --color-tagline: {secondary}; --color-heading: {heading}; --color-subheading: {heading}; --color-caption: {secondary}; --color-footer: {secondary}; --color-categories: {secondary}; --color-tags: {secondary};",
+        text = text,
+        secondary = secondary,
+        heading = heading,
+        bg = get("color_dark_background", "#111827"),
+        surface = get("color_dark_surface", "#1f2937"),
+        accent = get("color_dark_accent", "#60a5fa"),
+        link = get("color_dark_link", "#60a5fa"),
+        link_hover = get("color_dark_link_hover", "#93c5fd"),
+        border = get("color_dark_border", "#374151"),
+    );
+    let scoped = |scope: &str| -> String {
+        let mut css = format!("{} {{ {} }}\n", scope, vars);
+        for (selectors, decls) in DARK_SURFACE_RULES {
+            let list: Vec<String> = selectors
+                .split(',')
+                .map(|s| format!("{} {}", scope, s.trim()))
+                .collect();
+            css.push_str(&format!("{} {{ {} }}\n", list.join(", "), decls));
+        }
+        css
+    };
 
-        let mut maybe_add = |name: &str| {
-            if !name.is_empty() && !system_fonts.contains(&name) && !name.starts_with("adobe-") {
-                let family = name.replace(' ', "+");
-                if !families.contains(&family) {
-                    families.push(family);
-                }
-            }
-        };
+    if mode == "always" {
+        format!("\n{}", scoped(":root:not([data-color-scheme=\"light\"])"))
+    } else {
+        format!(
+            "\n@media (prefers-color-scheme: dark) {{\n{}}}\n{}",
+            scoped(":root:not([data-color-scheme=\"light\"])"),
+            scoped(":root[data-color-scheme=\"dark\"]")
+        )
+    }
+}
 
-        maybe_add(font_primary);
-        maybe_add(font_heading);
+/// The Google Font families the settings use, in first-use order
+pub fn google_families(settings: &Value) -> Vec<String> {
+    let get = |key: &str| -> &str { settings.get(key).and_then(|v| v.as_str()).unwrap_or("") };
+    let sitewide = get("font_sitewide") != "false";
+    let system_fonts = ["system-ui", "Georgia, serif", "ui-monospace, monospace", ""];
+    let mut families: Vec<String> = Vec::new();
 
-        if !sitewide {
-            for key in &[
-                "font_body",
-                "font_headings",
-                "font_navigation",
-                "font_buttons",
-                "font_captions",
-            ] {
-                maybe_add(get(key));
+    let mut maybe_add = |name: &str| {
+        if !name.is_empty() && !system_fonts.contains(&name) && !name.starts_with("adobe-") {
+            let family = name.to_string();
+            if !families.contains(&family) {
+                families.push(family);
             }
         }
+    };
+
+    maybe_add(get("font_primary"));
+    maybe_add(get("font_heading"));
 
-        // Always load per-element fonts if set (these are independent of sitewide toggle)
+    if !sitewide {
         for key in &[
-            "font_logo",
-            "font_subheading",
-            "font_blockquote",
-            "font_list",
-            "font_footer",
-            "font_lightbox_title",
-            "font_categories",
-            "font_tags",
+            "font_body",
+            "font_headings",
+            "font_navigation",
+            "font_buttons",
+            "font_captions",
         ] {
             maybe_add(get(key));
         }
+    }
 
+    // Always load per-element fonts if set (these are independent of sitewide toggle)
+    for key in &[
+        "font_logo",
+        "font_subheading",
+        "font_blockquote",
+        "font_list",
+        "font_footer",
+        "font_lightbox_title",
+        "font_categories",
+        "font_tags",
+    ] {
+        maybe_add(get(key));
+    }
+    families
+}
+
+/// The Google Fonts stylesheet URL for `families`
+pub fn google_css_url(families: &[String]) -> String {
+    let params: Vec<String> = families
+        .iter()
+        .map(|f| format!("family={}:wght@300;400;500;600;700", f.replace(' ', "+")))
+        .collect();
+    format!(
+        "https://fonts.googleapis.com/css2?{}&display=swap",
+        params.join("&")
+    )
+}
+
+/// Build the font loading HTML tags (Google Fonts, Adobe Fonts, custom @font-face).
+pub fn build_font_links(settings: &Value) -> String {
+    let get = |key: &str| -> &str { settings.get(key).and_then(|v| v.as_str()).unwrap_or("") };
+
+    let google_enabled = get("font_google_enabled") == "true";
+    let adobe_enabled = get("font_adobe_enabled") == "true";
+
+    let mut html = String::new();
+
+    // Self-hosted Google Fonts never touch Google's servers; until the
+    // download finishes the site falls back to its system fonts
+    if google_enabled && get("font_google_self_host") == "true" {
+        let css = get("font_google_local_css");
+        if !css.is_empty() {
+            html.push_str(&format!("    <style>{}</style>\n", css));
+        }
+    } else if google_enabled {
+        let families = google_families(settings);
         if !families.is_empty() {
             html.push_str(
                 r#"    <link rel="preconnect" href="https://fonts.googleapis.com">
     <link rel="preconnect" href="https://fonts.gstatic.com" crossorigin>
 "#,
             );
-            let params: Vec<String> = families
-                .iter()
-                .map(|f| format!("family={}:wght@300;400;500;600;700", f))
-                .collect();
             html.push_str(&format!(
-                r#"    <link href="https://fonts.googleapis.com/css2?{}&display=swap" rel="stylesheet">
+                r#"    <link href="{}" rel="stylesheet">
 "#,
-                params.join("&")
+                google_css_url(&families)
             ));
         }
     }
@@ -0,0 +1,264 @@
+//! Self-hosted Google Fonts: the families in use are downloaded when the
+//! typography settings are saved and served from `/uploads/fonts/google/`,
+//! so visitors' browsers never contact Google.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{google_css_url, google_families};
+use crate::store::Store;
+
+/// Where the downloaded font files are kept
+const FONTS_DIR: &str = "website/site/uploads/fonts/google";
+
+/// Public URL of `FONTS_DIR`
+const FONTS_URL: &str = "/uploads/fonts/google";
+
+/// Google serves WOFF2, split by script, only to browsers it recognises
+const BROWSER_UA: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0 Safari/537.36";
+
+/// Scripts Google splits its fonts into, as (key, label)
+pub const SUBSETS: &[(&str, &str)] = &[
+    ("latin", "Latin"),
+    ("latin-ext", "Latin Extended"),
+    ("cyrillic", "Cyrillic"),
+    ("cyrillic-ext", "Cyrillic Extended"),
+    ("greek", "Greek"),
+    ("greek-ext", "Greek Extended"),
+    ("vietnamese", "Vietnamese"),
+];
+
+/// One @font-face rule from a Google Fonts stylesheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Face {
+    /// Script the rule covers, from the comment Google puts before it;
+    /// empty when there's none
+    pub subset: String,
+    pub family: String,
+    pub style: String,
+    pub weight: String,
+    pub url: String,
+    pub unicode_range: String,
+}
+
+/// The known subsets in a comma-separated list, "latin" when none are
+pub fn normalize_subsets(raw: &str) -> String {
+    let mut keep: Vec<&str> = Vec::new();
+    for s in raw.split(',').map(|s| s.trim().to_lowercase()) {
+        if let Some((key, _)) = SUBSETS.iter().find(|(k, _)| *k == s) {
+            if !keep.contains(key) {
+                keep.push(key);
+            }
+        }
+    }
+    if keep.is_empty() {
+        "latin".to_string()
+    } else {
+        keep.join(",")
+    }
+}
+
+/// The @font-face rules in a Google Fonts stylesheet
+pub fn parse_stylesheet(css: &str) -> Vec<Face> {
+    let mut faces = Vec::new();
+    let mut rest = css;
+    while let Some(start) = rest.find("@font-face") {
+        let before = &rest[..start];
+        let subset = before
+            .rfind("/*")
+            .and_then(|i| {
+                let comment = &before[i + 2..];
+                comment.find("*/").map(|j| comment[..j].trim().to_string())
+            })
+            .unwrap_or_default();
+        let body_start = match rest[start..].find('{') {
+            Some(i) => start + i + 1,
+            None => break,
+        };
+        let body_end = match rest[body_start..].find('}') {
+            Some(i) => body_start + i,
+            None => break,
+        };
+        let body = &rest[body_start..body_end];
+        rest = &rest[body_end + 1..];
+
+        let url = declaration(body, "src")
+            .and_then(|src| {
+                let i = src.find("url(")? + 4;
+                let j = src[i..].find(')')? + i;
+                Some(
+                    src[i..j]
+                        .trim_matches(|c| c == '\'' || c == '"')
+                        .to_string(),
+                )
+            })
+            .unwrap_or_default();
+        if url.is_empty() {
+            continue;
+        }
+        faces.push(Face {
+            subset,
+            family: declaration(body, "font-family")
+                .unwrap_or_default()
+                .trim_matches(|c| c == '\'' || c == '"')
+                .to_string(),
+            style: declaration(body, "font-style").unwrap_or_else(|| "normal".into()),
+            weight: declaration(body, "font-weight").unwrap_or_else(|| "400".into()),
+            url,
+            unicode_range: declaration(body, "unicode-range").unwrap_or_default(),
+        });
+    }
+    faces
+}
+
+/// Value of the `name` declaration in a rule body
+fn declaration(body: &str, name: &str) -> Option<String> {
+    body.split(';').find_map(|decl| {
+        let (k, v) = decl.split_once(':')?;
+        (k.trim() == name).then(|| v.trim().to_string())
+    })
+}
+
+/// Keep only `[A-Za-z0-9]` and the extra characters `allowed`
+fn clean(value: &str, allowed: &[char]) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || allowed.contains(c))
+        .collect()
+}
+
+/// File name a face is stored under, e.g. `open-sans-normal-400-latin.woff2`
+pub fn file_name(face: &Face) -> String {
+    let parts = [
+        face.family.as_str(),
+        face.style.as_str(),
+        face.weight.as_str(),
+        face.subset.as_str(),
+    ];
+    let stem = parts
+        .iter()
+        .filter(|p| !p.is_empty())
+        .map(|p| clean(&p.to_lowercase().replace(' ', "-"), &['-']))
+        .collect::<Vec<_>>()
+        .join("-");
+    format!("{}.woff2", stem)
+}
+
+/// @font-face CSS pointing at the local copies of `faces`
+pub fn local_css(faces: &[Face]) -> String {
+    faces
+        .iter()
+        .map(|f| {
+            let range = clean(&f.unicode_range, &['+', '-', ',', ' ', '?']);
+            format!(
+                "@font-face {{ font-family: '{}'; font-style: {}; font-weight: {}; font-display: swap; src: url('{}/{}') format('woff2');{} }}",
+                clean(&f.family, &[' ', '-']),
+                clean(&f.style, &[]),
+                clean(&f.weight, &[' ']),
+                FONTS_URL,
+                file_name(f),
+                if range.trim().is_empty() {
+                    String::new()
+                } else {
+                    format!(" unicode-range: {};", range.trim())
+                }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Download the families the settings use and store their @font-face CSS
+/// in `font_google_local_css`. Does nothing while self-hosting is off or
+/// when the same families and subsets are already downloaded.
+pub fn refresh(store: &dyn Store) -> Result<(), String> {
+    let settings = serde_json::to_value(store.setting_all()).unwrap_or_default();
+    let get = |key: &str| -> String {
+        settings
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+    if get("font_google_enabled") != "true" || get("font_google_self_host") != "true" {
+        return Ok(());
+    }
+    let families = google_families(&settings);
+    let subsets = normalize_subsets(&get("font_google_subsets"));
+    let key = format!("{}#{}", families.join("|"), subsets);
+    if key == get("font_google_local_key") {
+        return Ok(());
+    }
+    if families.is_empty() {
+        store.setting_set("font_google_local_css", "")?;
+        return store.setting_set("font_google_local_key", &key);
+    }
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .user_agent(BROWSER_UA)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let css = client
+        .get(google_css_url(&families))
+        .send()
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.text())
+        .map_err(|e| format!("Couldn't fetch the Google Fonts stylesheet: {}", e))?;
+    let wanted: Vec<&str> = subsets.split(',').collect();
+    let faces: Vec<Face> = parse_stylesheet(&css)
+        .into_iter()
+        .filter(|f| f.subset.is_empty() || wanted.contains(&f.subset.as_str()))
+        .collect();
+    if faces.is_empty() {
+        return Err("Google Fonts returned no font files".into());
+    }
+
+    // Fetch everything before touching the current files, so a failed
+    // download leaves the working fonts in place
+    let mut files = Vec::with_capacity(faces.len());
+    for face in &faces {
+        if !face.url.starts_with("https://fonts.gstatic.com/") {
+            return Err(format!("Unexpected font location: {}", face.url));
+        }
+        let bytes = client
+            .get(&face.url)
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.bytes())
+            .map_err(|e| format!("Couldn't download {}: {}", face.family, e))?;
+        files.push((file_name(face), bytes));
+    }
+    let dir = Path::new(FONTS_DIR);
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    for (name, bytes) in &files {
+        std::fs::write(dir.join(name), bytes).map_err(|e| e.to_string())?;
+    }
+
+    store.setting_set("font_google_local_css", &local_css(&faces))?;
+    store.setting_set("font_google_local_key", &key)?;
+    log::info!(
+        "[fonts] Downloaded {} font files for {}",
+        files.len(),
+        families.join(", ")
+    );
+    Ok(())
+}
+
+/// Run `refresh` on a background thread so saving settings never waits on
+/// Google. A failure is kept in `font_google_local_error` for the
+/// typography page to show.
+pub fn refresh_in_background(store: Arc<dyn Store>) {
+    std::thread::spawn(move || {
+        let error = match refresh(&*store) {
+            Ok(()) => String::new(),
+            Err(e) => {
+                log::warn!("[fonts] Self-hosting Google Fonts failed: {}", e);
+                e
+            }
+        };
+        let _ = store.setting_set("font_google_local_error", &error);
+    });
+}
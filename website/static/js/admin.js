@@ -31,6 +31,27 @@
         loadDashboardCharts();
     }
 
+    if (document.getElementById('live-card') && typeof EventSource !== 'undefined') {
+        var live = new EventSource('/admin/api/stats/live');
+        live.addEventListener('snapshot', function(e) { renderLive(JSON.parse(e.data)); });
+        fetch('/admin/api/stats/now').then(function(r) { return r.json(); }).then(renderLive).catch(function() {});
+    }
+
+    function renderLive(data) {
+        var esc = function(s) { var d = document.createElement('div'); d.textContent = s; return d.innerHTML; };
+        var list = function(id, items) {
+            var el = document.getElementById(id);
+            if (!el) return;
+            el.innerHTML = items.length
+                ? items.slice(0, 5).map(function(d) { return '<li><span>' + esc(d.label) + '</span><span>' + d.count + '</span></li>'; }).join('')
+                : '<li><span class="text-muted">—</span></li>';
+        };
+        document.getElementById('live-visitors').textContent = data.active_visitors;
+        document.getElementById('live-views').textContent = data.views;
+        list('live-pages', data.top_pages || []);
+        list('live-referrers', data.top_referrers || []);
+    }
+
     var chartColors = {
         accent: '#E8913A',
         accentHover: '#D07A2F',
@@ -49,7 +70,7 @@
 
     async function loadDashboardCharts() {
         try {
-            const [overview, flow, geo, stream, calendar, referrers, topPortfolio, tags] = await Promise.all([
+            const [overview, flow, geo, stream, calendar, referrers, topPortfolio, tags, campaigns, utmSources, goals, experiments, outbound, searches] = await Promise.all([
                 fetch('/admin/api/stats/overview').then(r => r.json()),
                 fetch('/admin/api/stats/flow').then(r => r.json()),
                 fetch('/admin/api/stats/geo').then(r => r.json()),
@@ -58,6 +79,12 @@
                 fetch('/admin/api/stats/top-referrers').then(r => r.json()),
                 fetch('/admin/api/stats/top-portfolio').then(r => r.json()),
                 fetch('/admin/api/stats/tags').then(r => r.json()),
+                fetch('/admin/api/stats/campaigns').then(r => r.json()),
+                fetch('/admin/api/stats/utm/source').then(r => r.json()),
+                fetch('/admin/api/stats/goals').then(r => r.json()),
+                fetch('/admin/api/stats/experiments').then(r => r.json()),
+                fetch('/admin/api/stats/outbound').then(r => r.json()),
+                fetch('/admin/api/stats/searches').then(r => r.json()),
             ]);
 
             if (flow && flow.length > 0) renderFlowChart('#chart-sankey', flow);
@@ -67,6 +94,13 @@
             if (referrers && referrers.length > 0) renderHorizontalBars('#chart-referrers', referrers, chartColors.accent);
             if (topPortfolio && topPortfolio.length > 0) renderHorizontalBars('#chart-top-portfolio', topPortfolio, chartColors.blue);
             if (overview) renderSunburst('#chart-sunburst', overview, geo);
+            if (campaigns && campaigns.length > 0) renderCampaignTable('#chart-campaigns', campaigns);
+            if (utmSources && utmSources.length > 0) renderHorizontalBars('#chart-utm-sources', utmSources, chartColors.purple);
+            if (goals && goals.length > 0) renderGoalTable('#chart-goals', goals);
+            if (experiments && experiments.length > 0) renderExperimentTable('#chart-experiments', experiments);
+            if (outbound && outbound.length > 0) renderOutboundTable('#chart-outbound', outbound);
+            if (searches && searches.top.length > 0) renderSearchTable('#chart-searches', searches.top);
+            if (searches && searches.zero_results.length > 0) renderHorizontalBars('#chart-searches-empty', searches.zero_results, chartColors.accent);
 
         } catch (e) {
             console.log('Dashboard charts: waiting for data', e);
@@ -387,6 +421,84 @@
             });
     }
 
+    function renderCampaignTable(selector, data) {
+        var container = document.querySelector(selector);
+        if (!container || !data.length) return;
+        var esc = function(s) { var d = document.createElement('div'); d.textContent = s; return d.innerHTML; };
+        var rows = data.slice(0, 10).map(function(d) {
+            return '<tr><td>' + esc(d.campaign) + '</td><td class="text-muted">' + esc(d.source) + ' / ' + esc(d.medium) +
+                '</td><td style="text-align:right">' + d.views + '</td><td style="text-align:right">' + d.visitors + '</td></tr>';
+        }).join('');
+        container.innerHTML = '<table class="data-table" style="font-size:13px"><thead><tr><th>Campaign</th><th>Source / Medium</th>' +
+            '<th style="text-align:right">Views</th><th style="text-align:right">Visitors</th></tr></thead><tbody>' + rows + '</tbody></table>';
+    }
+
+    function renderGoalTable(selector, data) {
+        var container = document.querySelector(selector);
+        if (!container || !data.length) return;
+        var esc = function(s) { var d = document.createElement('div'); d.textContent = s; return d.innerHTML; };
+        var rows = data.map(function(d) {
+            return '<tr><td>' + esc(d.goal) + '</td><td class="text-muted"><code>' + esc(d.pattern) + '</code></td>' +
+                '<td style="text-align:right">' + d.completions + '</td><td style="text-align:right">' + d.converters + '</td>' +
+                '<td style="text-align:right;font-weight:600">' + d.conversion_rate.toFixed(1) + '%</td></tr>';
+        }).join('');
+        container.innerHTML = '<table class="data-table" style="font-size:13px"><thead><tr><th>Goal</th><th>Page</th>' +
+            '<th style="text-align:right">Completions</th><th style="text-align:right">Visitors</th>' +
+            '<th style="text-align:right">Conversion</th></tr></thead><tbody>' + rows + '</tbody></table>';
+    }
+
+    function renderExperimentTable(selector, data) {
+        var container = document.querySelector(selector);
+        if (!container || !data.length) return;
+        var esc = function(s) { var d = document.createElement('div'); d.textContent = s; return d.innerHTML; };
+        var rows = data.map(function(e) {
+            var result = e.winner
+                ? '<span style="color:' + chartColors.green + ';font-weight:600">' + esc(e.winner) + ' wins</span>'
+                : '<span class="text-muted">Not significant yet</span>';
+            return e.variants.map(function(v, i) {
+                var head = i === 0
+                    ? '<td rowspan="2">' + esc(e.name) + '<div class="text-muted"><code>' + esc(e.target) + '</code></div></td>'
+                    : '';
+                var tail = i === 0
+                    ? '<td rowspan="2" style="text-align:right">' + e.confidence.toFixed(1) + '%<div>' + result + '</div></td>'
+                    : '';
+                return '<tr>' + head + '<td><strong>' + esc(v.variant) + '</strong> ' + esc(v.text) + '</td>' +
+                    '<td style="text-align:right">' + v.impressions + '</td><td style="text-align:right">' + v.clicks + '</td>' +
+                    '<td style="text-align:right;font-weight:600">' + v.click_rate.toFixed(1) + '%</td>' + tail + '</tr>';
+            }).join('');
+        }).join('');
+        container.innerHTML = '<table class="data-table" style="font-size:13px"><thead><tr><th>Experiment</th><th>Variant</th>' +
+            '<th style="text-align:right">Visitors</th><th style="text-align:right">Clicks</th>' +
+            '<th style="text-align:right">Click Rate</th><th style="text-align:right">Confidence</th></tr></thead><tbody>' + rows + '</tbody></table>';
+    }
+
+    function renderOutboundTable(selector, data) {
+        var container = document.querySelector(selector);
+        if (!container || !data.length) return;
+        var esc = function(s) { var d = document.createElement('div'); d.textContent = s; return d.innerHTML; };
+        var rows = data.map(function(d) {
+            var label = d.url.replace('https://', '').replace('http://', '');
+            if (label.length > 60) label = label.substring(0, 60) + '…';
+            return '<tr><td title="' + esc(d.url).replace(/"/g, '&quot;') + '">' + esc(label) + '</td>' +
+                '<td style="text-align:right">' + d.clicks + '</td><td style="text-align:right">' + d.visitors + '</td></tr>';
+        }).join('');
+        container.innerHTML = '<table class="data-table" style="font-size:13px"><thead><tr><th>Link</th>' +
+            '<th style="text-align:right">Clicks</th><th style="text-align:right">Visitors</th></tr></thead><tbody>' + rows + '</tbody></table>';
+    }
+
+    function renderSearchTable(selector, data) {
+        var container = document.querySelector(selector);
+        if (!container || !data.length) return;
+        var esc = function(s) { var d = document.createElement('div'); d.textContent = s; return d.innerHTML; };
+        var rows = data.map(function(d) {
+            return '<tr><td>' + esc(d.query) + '</td><td style="text-align:right">' + d.searches + '</td>' +
+                '<td style="text-align:right">' + d.results + '</td><td style="text-align:right">' + d.clicks + '</td></tr>';
+        }).join('');
+        container.innerHTML = '<table class="data-table" style="font-size:13px"><thead><tr><th>Query</th>' +
+            '<th style="text-align:right">Searches</th><th style="text-align:right">Results</th>' +
+            '<th style="text-align:right">Clicks</th></tr></thead><tbody>' + rows + '</tbody></table>';
+    }
+
     function renderCalendarHeatmap(selector, data) {
         var container = document.querySelector(selector);
         if (!container || !data.length) return;